@@ -27,6 +27,7 @@ private const val KEY_FILTER_LINUX_PROCESS = "filter_linux_process"
 private const val KEY_TOP_MOST_LAYER = "top_most_layer2"
 private const val KEY_MEMORY_BUFFER_SIZE = "memory_buffer_size"
 private const val KEY_SEARCH_PAGE_SIZE = "search_page_size"
+private const val KEY_SEARCH_MAX_RESULTS = "search_max_results"
 private const val KEY_RUDE_SEARCH = "rude_search"
 private const val KEY_FAILED_PAGE_THRESHOLD = "failed_page_threshold"
 private const val KEY_CHUNK_SIZE = "chunk_size"
@@ -40,6 +41,7 @@ private const val KEY_MEMORY_PREVIEW_INFINITE_SCROLL = "memory_preview_infinite_
 private const val DEFAULT_OPACITY = 0.55f
 private const val DEFAULT_MEMORY_BUFFER_SIZE = 512
 private const val DEFAULT_SEARCH_PAGE_SIZE = 100
+private const val DEFAULT_SEARCH_MAX_RESULTS = 0L // 0=不限制
 private const val DEFAULT_CHUNK_SIZE = 512
 private const val DEFAULT_SKIP_MEMORY = 0 // 0=否, 1=空, 2=空orZygote
 private const val DEFAULT_AUTO_PAUSE = false
@@ -95,6 +97,15 @@ var MMKV.searchPageSize: Int
         encode(KEY_SEARCH_PAGE_SIZE, value)
     }
 
+/**
+ * 首次搜索的结果数上限，达到后提前结束搜索，0 表示不限制
+ */
+var MMKV.searchMaxResults: Long
+    get() = decodeLong(KEY_SEARCH_MAX_RESULTS, DEFAULT_SEARCH_MAX_RESULTS)
+    set(value) {
+        encode(KEY_SEARCH_MAX_RESULTS, value)
+    }
+
 /**
  * 跳过内存选项
  * 0 = 否
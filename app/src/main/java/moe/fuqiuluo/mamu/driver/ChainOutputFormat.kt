@@ -0,0 +1,18 @@
+package moe.fuqiuluo.mamu.driver
+
+/**
+ * 指针扫描输出文件中链的格式，与 native 侧 ChainOutputFormat 的取值一致
+ */
+enum class ChainOutputFormat(val nativeId: Int) {
+    /** `libgame.so[0]+0x1A0->+0x10->-0x8` */
+    NATIVE(0),
+
+    /** Cheat Engine 风格：`"libgame.so"+0x1A0 -> +0x10 -> -0x8` */
+    CHEAT_ENGINE(1),
+
+    /** GameGuardian 风格：`libgame.so[0], 0x1A0, 0x10, -0x8` */
+    GAME_GUARDIAN(2),
+
+    /** 每行一个 JSON 对象 */
+    JSON_LINES(3),
+}
@@ -0,0 +1,51 @@
+@file:Suppress("KotlinJniMissingFunction")
+
+package moe.fuqiuluo.mamu.driver
+
+import android.content.Context
+import java.io.File
+
+/**
+ * 本地控制服务
+ *
+ * 通过应用私有目录下的 UNIX 域套接字（权限 0600）向设备上的脚本开放搜索、结果读取、
+ * 写入和指针扫描命令。协议为 4 字节小端长度 + JSON，默认关闭。
+ */
+object ControlServer {
+    private const val SOCKET_NAME = "control.sock"
+
+    init {
+        System.loadLibrary("mamu_core")
+    }
+
+    /**
+     * 套接字文件路径
+     */
+    fun socketFile(context: Context): File {
+        return File(context.filesDir, SOCKET_NAME)
+    }
+
+    /**
+     * 启动控制服务
+     *
+     * @return 是否启动成功，已经在运行时返回 false
+     */
+    fun start(context: Context): Boolean {
+        return nativeStartControlServer(socketFile(context).absolutePath)
+    }
+
+    /**
+     * 停止控制服务，会等待所有连接退出
+     */
+    fun stop() {
+        nativeStopControlServer()
+    }
+
+    fun isRunning(): Boolean {
+        return nativeIsControlServerRunning()
+    }
+
+    private external fun nativeStartControlServer(socketPath: String): Boolean
+    private external fun nativeStopControlServer()
+    private external fun nativeIsControlServerRunning(): Boolean
+}
@@ -62,6 +62,18 @@ object FreezeManager {
         }
     }
     
+    /**
+     * 以指定间隔冻结地址，条目有自己的写入间隔，不受 [setInterval] 影响
+     *
+     * @param address 要冻结的内存地址
+     * @param value 要写入的值（字节数组）
+     * @param intervalMs 写入间隔（毫秒），不大于 0 时使用默认的 50ms
+     * @return 是否添加成功
+     */
+    fun addFreeze(address: Long, value: ByteArray, intervalMs: Int = 0): Boolean {
+        return nativeAddFreeze(address, value, intervalMs)
+    }
+
     /**
      * 移除冻结地址
      * 
@@ -69,18 +81,35 @@ object FreezeManager {
      * @return 是否移除成功（地址不存在时返回 false）
      */
     fun removeFrozen(address: Long): Boolean {
-        return nativeRemoveFrozen(address)
+        return nativeRemoveFreeze(address)
     }
     
     /**
      * 清空所有冻结
      */
     fun clearAll() {
-        nativeClearAll()
+        nativeClearFreezes()
+    }
+
+    /**
+     * 列出所有冻结条目（含暂停和孤立的条目）
+     *
+     * @return JSON 数组 [{address, value, value_type, pid, state, interval_ms, failed_writes, consecutive_failures}]，
+     * interval_ms 为 0 表示使用全局间隔
+     */
+    fun listFreezes(): String {
+        return nativeListFreezes()
+    }
+
+    /**
+     * 获取累计写入失败次数
+     */
+    fun getFailedWriteCount(): Long {
+        return nativeGetFailedWriteCount()
     }
     
     /**
-     * 设置冻结间隔
+     * 设置全局冻结间隔，用于没有指定间隔的条目
      * 
      * @param microseconds 间隔时间（微秒）
      */
@@ -104,14 +133,84 @@ object FreezeManager {
     fun isFrozen(address: Long): Boolean {
         return nativeIsFrozen(address)
     }
+
+    /**
+     * 获取孤立冻结条目数量
+     * 绑定的进程变化后，旧进程的冻结条目会暂停写入并转为孤立状态
+     */
+    fun getOrphanCount(): Int {
+        return nativeGetOrphanCount()
+    }
+
+    /**
+     * 列出孤立冻结条目
+     *
+     * @return JSON 数组 [{address, value_type, pid}]
+     */
+    fun listOrphaned(): String {
+        return nativeListOrphaned()
+    }
+
+    /**
+     * 把孤立条目重新映射到当前绑定进程的新地址并恢复冻结
+     *
+     * @param oldAddrs 孤立条目的旧地址
+     * @param newAddrs 对应的新地址
+     * @return 重新激活的条目数量
+     */
+    fun rebindEntries(oldAddrs: LongArray, newAddrs: LongArray): Int {
+        return nativeRebindEntries(oldAddrs, newAddrs)
+    }
+
+    /**
+     * 丢弃所有孤立条目
+     */
+    fun clearOrphaned() {
+        nativeClearOrphaned()
+    }
+
+    /**
+     * 恢复暂停的冻结条目（例如从 GameGuardian 导入的冻结项）
+     *
+     * @param address 条目地址
+     * @return 是否恢复（地址不是暂停条目时返回 false）
+     */
+    fun resumeFrozen(address: Long): Boolean {
+        return nativeResumeFrozen(address)
+    }
+
+    /**
+     * 检查地址是否是暂停的冻结条目
+     */
+    fun isPaused(address: Long): Boolean {
+        return nativeIsPaused(address)
+    }
+
+    /**
+     * 冻结写入是否因绑定的进程退出而暂停
+     * 进程恢复存活或重新绑定后自动恢复；进程退出后它的条目转为孤立，可用 [rebindEntries] 映射到重启后的进程
+     */
+    fun isProcessPaused(): Boolean {
+        return nativeIsProcessPaused()
+    }
     
     // Native methods
     private external fun nativeStart()
     private external fun nativeStop()
     private external fun nativeAddFrozen(address: Long, value: ByteArray, valueType: Int): Boolean
-    private external fun nativeRemoveFrozen(address: Long): Boolean
-    private external fun nativeClearAll()
+    private external fun nativeAddFreeze(address: Long, value: ByteArray, intervalMs: Int): Boolean
+    private external fun nativeRemoveFreeze(address: Long): Boolean
+    private external fun nativeClearFreezes()
+    private external fun nativeListFreezes(): String
+    private external fun nativeGetFailedWriteCount(): Long
     private external fun nativeSetInterval(microseconds: Long)
     private external fun nativeGetFrozenCount(): Int
     private external fun nativeIsFrozen(address: Long): Boolean
+    private external fun nativeGetOrphanCount(): Int
+    private external fun nativeListOrphaned(): String
+    private external fun nativeRebindEntries(oldAddrs: LongArray, newAddrs: LongArray): Int
+    private external fun nativeClearOrphaned()
+    private external fun nativeResumeFrozen(address: Long): Boolean
+    private external fun nativeIsPaused(address: Long): Boolean
+    private external fun nativeIsProcessPaused(): Boolean
 }
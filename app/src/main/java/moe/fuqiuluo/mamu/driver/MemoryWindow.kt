@@ -0,0 +1,22 @@
+package moe.fuqiuluo.mamu.driver
+
+/**
+ * A window of memory read for the hex viewer
+ *
+ * @property address Start address of the window (may be unaligned)
+ * @property data Window bytes; bytes on invalid pages are 0
+ * @property pageValid Whether each page covered by the window was read, index 0 is the page containing [address]
+ * @property pageSize Page size used for [pageValid]
+ */
+class MemoryWindow(
+    val address: Long, val data: ByteArray, val pageValid: BooleanArray, val pageSize: Int
+) {
+    /**
+     * Whether the byte at [offset] into the window was read, invalid bytes should be rendered as `??`
+     */
+    fun isValid(offset: Int): Boolean {
+        if (offset < 0 || offset >= data.size) return false
+        val head = (address and (pageSize - 1).toLong()).toInt()
+        return pageValid[(head + offset) / pageSize]
+    }
+}
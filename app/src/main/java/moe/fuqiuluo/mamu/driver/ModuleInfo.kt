@@ -0,0 +1,22 @@
+package moe.fuqiuluo.mamu.driver
+
+/**
+ * A file loaded by the process, with all mappings of the same path merged
+ *
+ * @property name Full path of the file
+ * @property base Lowest start address of its mappings
+ * @property end Highest end address of its mappings
+ * @property isExecutableSegment Whether one of its mappings is executable
+ */
+data class ModuleInfo(
+    val name: String, val base: Long, val end: Long, val isExecutableSegment: Boolean
+) {
+    val size: Long
+        get() = end - base
+
+    override fun toString(): String {
+        return "ModuleInfo(0x%016X-0x%016X %s%s)".format(
+            base, end, name, if (isExecutableSegment) " [x]" else ""
+        )
+    }
+}
@@ -0,0 +1,15 @@
+package moe.fuqiuluo.mamu.driver
+
+/**
+ * 地址所在区域的访问属性，与 native 侧 PageAccess 的取值一致
+ */
+enum class PageAccess(val nativeId: Int) {
+    WRITABLE(0),
+    READ_ONLY(1),
+    EXECUTABLE(2),
+    UNMAPPED(3);
+
+    companion object {
+        fun fromNativeId(id: Int): PageAccess = entries.firstOrNull { it.nativeId == id } ?: UNMAPPED
+    }
+}
@@ -0,0 +1,6 @@
+package moe.fuqiuluo.mamu.driver
+
+/**
+ * 写入目标落在只读或可执行的页上，需要改用 pte_mapping/mprotect 等方式写入
+ */
+class PageNotWritableException(message: String) : RuntimeException(message)
@@ -2,9 +2,12 @@
 
 package moe.fuqiuluo.mamu.driver
 
+import android.util.Log
 import java.nio.ByteBuffer
 import java.nio.ByteOrder
 
+private const val TAG = "PointerScanner"
+
 /**
  * Kotlin interface for the native pointer scanner.
  *
@@ -42,18 +45,34 @@ object PointerScanner {
     /**
      * Shared buffer size in bytes.
      * Memory layout:
-     * [0-3]   phase          (Rust writes)  ScanPhase enum
-     * [4-7]   progress       (Rust writes)  0-100
-     * [8-11]  regions_done   (Rust writes)  completed region count
-     * [12-19] pointers_found (Rust writes)  total pointers found (i64)
-     * [20-27] chains_found   (Rust writes)  total chains found (i64)
-     * [28-31] current_depth  (Rust writes)  current search depth
-     * [32-35] heartbeat      (Rust writes)  periodic value for liveness
-     * [36-39] cancel_flag    (Kotlin writes) 1 = cancel requested
-     * [40-43] error_code     (Rust writes)  error code when phase is Error
-     * [44-47] reserved
-     */
-    const val SHARED_BUFFER_SIZE = 48
+     * [0-15]  header         (Rust writes)  magic, layout version, capabilities, size, see [SharedBufferHeader]
+     * [16-19] phase          (Rust writes)  ScanPhase enum
+     * [20-23] progress       (Rust writes)  0-100
+     * [24-27] regions_done   (Rust writes)  completed region count
+     * [28-35] pointers_found (Rust writes)  total pointers found (i64)
+     * [36-43] chains_found   (Rust writes)  total chains found (i64)
+     * [44-47] current_depth  (Rust writes)  current search depth
+     * [48-51] heartbeat      (Rust writes)  periodic value for liveness
+     * [52-55] cancel_flag    (Kotlin writes) 1 = cancel requested
+     * [56-59] error_code     (Rust writes)  error code when phase is Error
+     */
+    const val SHARED_BUFFER_SIZE = 60
+
+    /** Layout version of the shared buffer, bumped whenever fields are added or moved. */
+    const val SHARED_BUFFER_VERSION = 1
+
+    /** Header magic of the pointer scan shared buffer, "MPTR" in little endian. */
+    const val SHARED_BUFFER_MAGIC = 0x5254504D
+
+    /** Capability bits in the shared buffer header, see [getSharedBufferCapabilities]. */
+    object Capability {
+        /** [getCurrentDepth] is reported while building chains. */
+        const val DEPTH_PROGRESS = 1
+        /** [Phase.WRITING_FILE] reports its own progress and the chains written so far. */
+        const val WRITE_PROGRESS = 2
+        /** [Phase.VALIDATING] reports its own progress and the surviving chains in [getChainsFound]. */
+        const val VALIDATION_PROGRESS = 4
+    }
 
     /** Scan phase constants. */
     object Phase {
@@ -64,6 +83,7 @@ object PointerScanner {
         const val CANCELLED = 4
         const val ERROR = 5
         const val WRITING_FILE = 6
+        const val VALIDATING = 7
     }
 
     /** Error code constants. */
@@ -76,22 +96,56 @@ object PointerScanner {
         const val ALREADY_SCANNING = 5
         const val NO_PROCESS_BOUND = 6
         const val STORAGE_ERROR = 7
+        /** Parameters out of range, see [getErrorMessage]. */
+        const val INVALID_CONFIG = 8
+        /** Estimated search space too large after Phase 1, see [getErrorMessage]. */
+        const val SEARCH_SPACE_TOO_LARGE = 9
+        /** The bound process exited during the scan. */
+        const val PROCESS_DIED = 10
+    }
+
+    /** Status codes returned by [validateChains]. */
+    object ChainStatus {
+        /** Still resolves to the scan target. */
+        const val VALID = 0
+        /** Resolves fully, but to a different address. */
+        const val MOVED = 1
+        /** A dereference failed, see [failedDepth]. */
+        const val BROKEN = 2
+        /** Chain id or its module not found. */
+        const val UNRESOLVED = 3
+        /** Not checked because validation was cancelled. */
+        const val SKIPPED = 4
+
+        fun status(code: Int): Int = code and 0xFF
+
+        /** Index of the failed dereference for [BROKEN] chains. */
+        fun failedDepth(code: Int): Int = code ushr 8
     }
 
     /** Shared buffer offsets. */
     private object Offset {
-        const val PHASE = 0
-        const val PROGRESS = 4
-        const val REGIONS_DONE = 8
-        const val POINTERS_FOUND = 12
-        const val CHAINS_FOUND = 20
-        const val CURRENT_DEPTH = 28
-        const val HEARTBEAT = 32
-        const val CANCEL_FLAG = 36
-        const val ERROR_CODE = 40
+        const val PHASE = 16
+        const val PROGRESS = 20
+        const val REGIONS_DONE = 24
+        const val POINTERS_FOUND = 28
+        const val CHAINS_FOUND = 36
+        const val CURRENT_DEPTH = 44
+        const val HEARTBEAT = 48
+        const val CANCEL_FLAG = 52
+        const val ERROR_CODE = 56
     }
 
+    /** Kept even when the layout is incompatible, the native side still writes into it. */
     private var sharedBuffer: ByteBuffer? = null
+
+    /** Whether the native side wrote the header of [SHARED_BUFFER_VERSION] into [sharedBuffer]. */
+    @Volatile
+    private var layoutCompatible = false
+
+    /** The shared buffer if its fields can be interpreted with [Offset]. */
+    private val fields: ByteBuffer?
+        get() = sharedBuffer?.takeIf { layoutCompatible }
     private var isInitialized = false
 
     /**
@@ -104,9 +158,16 @@ object PointerScanner {
 
         if (nativeInit(cacheDir)) {
             // Allocate shared buffer for progress communication
-            sharedBuffer = ByteBuffer.allocateDirect(SHARED_BUFFER_SIZE)
+            val buffer = ByteBuffer.allocateDirect(SHARED_BUFFER_SIZE)
                 .order(ByteOrder.LITTLE_ENDIAN)
-            nativeSetSharedBuffer(sharedBuffer!!)
+            sharedBuffer = buffer
+            if (nativeSetSharedBuffer(buffer)) {
+                val version = SharedBufferHeader.layoutVersion(buffer, SHARED_BUFFER_MAGIC)
+                layoutCompatible = version == SHARED_BUFFER_VERSION
+                if (!layoutCompatible) {
+                    Log.e(TAG, "Shared buffer layout version $version does not match $SHARED_BUFFER_VERSION, progress is unavailable")
+                }
+            }
             isInitialized = true
             return true
         }
@@ -118,69 +179,83 @@ object PointerScanner {
      */
     fun getSharedBuffer(): ByteBuffer? = sharedBuffer
 
+    /**
+     * Reads the layout version from the shared buffer header.
+     * @return 0 if no buffer is set or the native side did not write a pointer scan header.
+     */
+    fun getSharedBufferVersion(): Int =
+        sharedBuffer?.let { SharedBufferHeader.layoutVersion(it, SHARED_BUFFER_MAGIC) } ?: 0
+
+    /**
+     * Reads the capability bits from the shared buffer header.
+     * @return Bit set of Capability constants.
+     */
+    fun getSharedBufferCapabilities(): Int =
+        sharedBuffer?.let { SharedBufferHeader.capabilities(it, SHARED_BUFFER_MAGIC) } ?: 0
+
     /**
      * Reads current scan phase from shared buffer.
      * @return One of Phase constants.
      */
-    fun getPhase(): Int = sharedBuffer?.getInt(Offset.PHASE) ?: Phase.IDLE
+    fun getPhase(): Int = fields?.getInt(Offset.PHASE) ?: Phase.IDLE
 
     /**
      * Reads current progress from shared buffer.
      * @return Progress value 0-100.
      */
-    fun getProgress(): Int = sharedBuffer?.getInt(Offset.PROGRESS) ?: 0
+    fun getProgress(): Int = fields?.getInt(Offset.PROGRESS) ?: 0
 
     /**
      * Reads completed region count from shared buffer.
      */
-    fun getRegionsDone(): Int = sharedBuffer?.getInt(Offset.REGIONS_DONE) ?: 0
+    fun getRegionsDone(): Int = fields?.getInt(Offset.REGIONS_DONE) ?: 0
 
     /**
      * Reads total pointers found from shared buffer.
      */
-    fun getPointersFound(): Long = sharedBuffer?.getLong(Offset.POINTERS_FOUND) ?: 0
+    fun getPointersFound(): Long = fields?.getLong(Offset.POINTERS_FOUND) ?: 0
 
     /**
      * Reads total chains found from shared buffer.
      */
-    fun getChainsFound(): Long = sharedBuffer?.getLong(Offset.CHAINS_FOUND) ?: 0
+    fun getChainsFound(): Long = fields?.getLong(Offset.CHAINS_FOUND) ?: 0
 
     /**
      * Reads current search depth from shared buffer.
      */
-    fun getCurrentDepth(): Int = sharedBuffer?.getInt(Offset.CURRENT_DEPTH) ?: 0
+    fun getCurrentDepth(): Int = fields?.getInt(Offset.CURRENT_DEPTH) ?: 0
 
     /**
      * Reads heartbeat value from shared buffer.
      */
-    fun getHeartbeat(): Int = sharedBuffer?.getInt(Offset.HEARTBEAT) ?: 0
+    fun getHeartbeat(): Int = fields?.getInt(Offset.HEARTBEAT) ?: 0
 
     /**
      * Reads error code from shared buffer.
      * @return One of ErrorCode constants.
      */
-    fun getErrorCode(): Int = sharedBuffer?.getInt(Offset.ERROR_CODE) ?: ErrorCode.NONE
+    fun getErrorCode(): Int = fields?.getInt(Offset.ERROR_CODE) ?: ErrorCode.NONE
 
     /**
      * Requests cancellation by writing to shared buffer.
      */
     fun requestCancelViaBuffer() {
-        sharedBuffer?.putInt(Offset.CANCEL_FLAG, 1)
+        fields?.putInt(Offset.CANCEL_FLAG, 1)
     }
 
     /**
      * Clears the cancel flag in shared buffer.
      */
     private fun clearCancelFlag() {
-        sharedBuffer?.putInt(Offset.CANCEL_FLAG, 0)
+        fields?.putInt(Offset.CANCEL_FLAG, 0)
     }
 
     /**
-     * Resets the shared buffer to initial state.
+     * Resets the shared buffer fields to initial state, keeping the header.
      */
     private fun resetSharedBuffer() {
-        sharedBuffer?.let { buffer ->
-            for (i in 0 until SHARED_BUFFER_SIZE step 4) {
+        fields?.let { buffer ->
+            for (i in SharedBufferHeader.SIZE until SHARED_BUFFER_SIZE step 4) {
                 buffer.putInt(i, 0)
             }
         }
@@ -206,7 +281,18 @@ object PointerScanner {
      * @param maxOffset Maximum offset per level in bytes (default: 0x1000).
      * @param align Pointer alignment in bytes (default: 4).
      * @param regions Memory regions to scan as list of (start, end, name, isStatic).
-     * @return Whether the scan started successfully.
+     * @param force Scan even if the estimated search space after Phase 1 is too large.
+     * @param outputFormat Line format of the output file.
+     * @param moduleFilter Only emit chains rooted in these modules (path or file name); null for all.
+     *        Filtered chains do not count towards [maxResults].
+     * @param liveVmaCheck Accept pointer targets by the live VMA list queried natively at scan start
+     *        instead of [regions], for when the module regions passed in are incomplete.
+     * @param requirePresentPages Also drop pointers whose target page is not present in memory,
+     *        checked by address translation on a sample of target pages.
+     * @param nativeStaticModules Ignore [MemoryRegionInfo.isStatic] and derive the static modules
+     *        natively from the module list of the bound process (see [WuwaDriver.listModules]).
+     * @return Whether the scan started successfully. Rejected parameters return false,
+     *         see [getErrorMessage].
      */
     fun startScan(
         targetAddress: Long,
@@ -215,25 +301,19 @@ object PointerScanner {
         align: Int = 4,
         regions: List<MemoryRegionInfo>,
         isLayerBFS: Boolean,
-        maxResults: Int = 0
+        maxResults: Int = 0,
+        force: Boolean = false,
+        outputFormat: ChainOutputFormat = ChainOutputFormat.NATIVE,
+        moduleFilter: List<String>? = null,
+        liveVmaCheck: Boolean = false,
+        requirePresentPages: Boolean = false,
+        nativeStaticModules: Boolean = false
     ): Boolean {
         if (!isInitialized) {
             return false
         }
 
-        // Prepare region data for JNI
-        val regionAddresses = LongArray(regions.size * 2)
-        val regionNames = Array(regions.size) { "" }
-        val staticFlags = BooleanArray(regions.size)
-        val permFlagsArray = IntArray(regions.size)
-
-        regions.forEachIndexed { index, region ->
-            regionAddresses[index * 2] = region.start
-            regionAddresses[index * 2 + 1] = region.end
-            regionNames[index] = region.name
-            staticFlags[index] = region.isStatic
-            permFlagsArray[index] = region.permFlags
-        }
+        val arrays = RegionArrays(regions)
 
         resetSharedBuffer()
         clearCancelFlag()
@@ -243,20 +323,101 @@ object PointerScanner {
             maxDepth,
             maxOffset,
             align,
-            regionAddresses,
-            regionNames,
-            staticFlags,
-            permFlagsArray,
+            arrays.addresses,
+            arrays.names,
+            if (nativeStaticModules) null else arrays.staticFlags,
+            arrays.permFlags,
             isLayerBFS,
-            maxResults
+            maxResults,
+            force,
+            outputFormat.nativeId,
+            moduleFilter?.toTypedArray(),
+            liveVmaCheck,
+            requirePresentPages
+        )
+    }
+
+    /**
+     * Rescan a previous result file after the target process restarted.
+     *
+     * Only chains that still resolve to [newTarget] are written to a new output file;
+     * progress is reported in the BuildingChains phase, then WritingFile, and can be cancelled like a scan.
+     *
+     * @param previousFile Output file of an earlier scan or rescan.
+     * @param newTarget The address the chains should resolve to now.
+     * @param regions Memory regions of the current process, used for module base addresses.
+     * @param adjustLastOffset Also keep chains whose last pointer is within the previous scan's
+     *                         max offset of [newTarget], rewriting their last offset.
+     * @return Whether the rescan started.
+     */
+    fun startRescan(
+        previousFile: String,
+        newTarget: Long,
+        regions: List<MemoryRegionInfo>,
+        adjustLastOffset: Boolean = false
+    ): Boolean {
+        if (!isInitialized) {
+            return false
+        }
+
+        val arrays = RegionArrays(regions)
+
+        resetSharedBuffer()
+        clearCancelFlag()
+
+        return nativeStartRescan(
+            previousFile,
+            newTarget,
+            adjustLastOffset,
+            arrays.addresses,
+            arrays.names,
+            arrays.staticFlags,
+            arrays.permFlags
         )
     }
 
     /**
-     * Get the number of chains found.
+     * Region data prepared for JNI.
+     */
+    private class RegionArrays(regions: List<MemoryRegionInfo>) {
+        val addresses = LongArray(regions.size * 2)
+        val names = Array(regions.size) { "" }
+        val staticFlags = BooleanArray(regions.size)
+        val permFlags = IntArray(regions.size)
+
+        init {
+            regions.forEachIndexed { index, region ->
+                addresses[index * 2] = region.start
+                addresses[index * 2 + 1] = region.end
+                names[index] = region.name
+                staticFlags[index] = region.isStatic
+                permFlags[index] = region.permFlags
+            }
+        }
+    }
+
+    /**
+     * Get the number of chains in the output file (what [getChains] pages through).
+     * May be lower than [getChainsFound] when the result limit truncated the file.
      */
     fun getChainCount(): Long = nativeGetChainCount()
 
+    /**
+     * Open a previously saved output file as the current result.
+     * The chain index next to the file is rebuilt if it is missing or stale.
+     * @return Number of chains in the file.
+     */
+    fun openResults(path: String): Long = nativeOpenResults(path)
+
+    /**
+     * Delete the current result's output file and its chain index, then reset state.
+     * Throws while a scan or validation is running.
+     */
+    fun deleteResults() {
+        nativeDeleteResults()
+        resetSharedBuffer()
+    }
+
     /**
      * Get the output file path where scan results were written.
      * Returns empty string if no scan result available.
@@ -264,7 +425,7 @@ object PointerScanner {
     fun getOutputFilePath(): String = nativeGetOutputFilePath()
 
     /**
-     * Get a range of chain results.
+     * Get a range of chain results, read from the output file through its index.
      * @param start Starting index.
      * @param count Number of results to retrieve.
      * @return Array of pointer chain results.
@@ -273,6 +434,68 @@ object PointerScanner {
         return nativeGetChains(start, count)
     }
 
+    /**
+     * Get example chains found so far as JSON. Can be called while the scan is running.
+     * Contains `seen`, up to 16 reservoir-sampled `samples` and the `shallowest` chain.
+     */
+    fun getSamples(): String = nativeGetPointerScanSamples()
+
+    /**
+     * Re-resolve chains of the last scan against live memory, in parallel.
+     * Blocks until done; [requestCancelViaBuffer] skips the remaining chains.
+     * @param chainIds Line numbers in the output file, not counting comments and blank lines.
+     * @return One [ChainStatus] code per id.
+     */
+    fun validateChains(chainIds: LongArray): IntArray = nativeValidateChains(chainIds)
+
+    /**
+     * Validate chains of the current result against live memory in the background.
+     *
+     * Chains whose dereference fails, or whose final value differs from [expectedValue] when given,
+     * are marked invalid and skipped by [getValidChains]. Progress is reported in the Validating phase
+     * with the shrinking surviving count in [getChainsFound]; cancel like a scan. Marks accumulate
+     * over validations until the next scan or [openResults].
+     *
+     * @param start First chain id to check.
+     * @param count Number of chains to check, clamped to the result.
+     * @param expectedValue Bytes the final address should hold, or null to only check dereferences.
+     * @return Whether the validation started.
+     */
+    fun startValidation(start: Long = 0, count: Long = Long.MAX_VALUE, expectedValue: ByteArray? = null): Boolean {
+        if (!isInitialized) {
+            return false
+        }
+
+        resetSharedBuffer()
+        clearCancelFlag()
+
+        return nativeStartValidation(start, count, expectedValue)
+    }
+
+    /**
+     * Get the number of chains not marked invalid, all chains when no validation ran.
+     */
+    fun getValidChainCount(): Long = nativeGetValidChainCount()
+
+    /**
+     * Get a range of chains that survived validation, same as [getChains] when no validation ran.
+     * @param start Starting index among the surviving chains.
+     * @param count Number of results to retrieve.
+     */
+    fun getValidChains(start: Int, count: Int): Array<PointerChainResult> = nativeGetValidChains(start, count)
+
+    /**
+     * Set how many worker threads pointer scans use, 0 for the shared default pool.
+     * Takes effect from the next scan.
+     */
+    fun setThreadCount(threads: Int) = nativeSetThreadCount(threads)
+
+    /**
+     * Run pointer scan worker threads at background priority (SCHED_BATCH, nice 10).
+     * Takes effect from the next scan.
+     */
+    fun setBackgroundPriority(background: Boolean) = nativeSetBackgroundPriority(background)
+
     /**
      * Clear all scan results and reset state.
      */
@@ -292,9 +515,16 @@ object PointerScanner {
         Phase.CANCELLED -> "Cancelled"
         Phase.ERROR -> "Error"
         Phase.WRITING_FILE -> "Writing File"
+        Phase.VALIDATING -> "Validating"
         else -> "Unknown"
     }
 
+    /**
+     * Get the message of the last error, with a suggested fix when parameters were rejected.
+     * Returns empty string if there is no error.
+     */
+    fun getErrorMessage(): String = nativeGetErrorMessage()
+
     /**
      * Get error code as human-readable string.
      */
@@ -307,6 +537,8 @@ object PointerScanner {
         ErrorCode.ALREADY_SCANNING -> "Already Scanning"
         ErrorCode.NO_PROCESS_BOUND -> "No Process Bound"
         ErrorCode.STORAGE_ERROR -> "Storage Error"
+        ErrorCode.PROCESS_DIED -> "Process Exited"
+        ErrorCode.INVALID_CONFIG, ErrorCode.SEARCH_SPACE_TOO_LARGE -> getErrorMessage()
         else -> "Unknown Error"
     }
 
@@ -320,18 +552,42 @@ object PointerScanner {
         align: Int,
         regions: LongArray,
         regionNames: Array<String>,
-        staticFlags: BooleanArray,
+        staticFlags: BooleanArray?,
         permFlags: IntArray,
         isLayerBFS: Boolean,
-        maxResults: Int
+        maxResults: Int,
+        force: Boolean,
+        outputFormat: Int,
+        moduleFilter: Array<String>?,
+        liveVmaCheck: Boolean,
+        requirePresentPages: Boolean
+    ): Boolean
+    private external fun nativeStartRescan(
+        previousFile: String,
+        newTarget: Long,
+        adjustLastOffset: Boolean,
+        regions: LongArray,
+        regionNames: Array<String>,
+        staticFlags: BooleanArray,
+        permFlags: IntArray
     ): Boolean
     private external fun nativeIsScanning(): Boolean
     private external fun nativeRequestCancel()
     private external fun nativeGetChainCount(): Long
+    private external fun nativeOpenResults(path: String): Long
+    private external fun nativeDeleteResults()
     private external fun nativeGetOutputFilePath(): String
     private external fun nativeGetChains(start: Int, count: Int): Array<PointerChainResult>
     private external fun nativeClear()
+    private external fun nativeGetPointerScanSamples(): String
     private external fun nativeGetPhase(): Int
+    private external fun nativeGetErrorMessage(): String
+    private external fun nativeValidateChains(chainIds: LongArray): IntArray
+    private external fun nativeStartValidation(start: Long, count: Long, expectedValue: ByteArray?): Boolean
+    private external fun nativeGetValidChainCount(): Long
+    private external fun nativeGetValidChains(start: Int, count: Int): Array<PointerChainResult>
+    private external fun nativeSetThreadCount(threads: Int)
+    private external fun nativeSetBackgroundPriority(background: Boolean)
 }
 
 /**
@@ -0,0 +1,45 @@
+package moe.fuqiuluo.mamu.driver
+
+/**
+ * Memory region change between a region snapshot and the current memory map
+ *
+ * @property start Start address of the region
+ * @property end Current end address (end address at snapshot time for removed regions)
+ * @property oldEnd End address at snapshot time (same as end for new regions)
+ * @property type Permission flags (combination of MemRegionEntry.MEM_* constants)
+ * @property name Region name (path or identifier)
+ * @property change One of the CHANGE_* constants
+ */
+data class RegionDiffEntry(
+    val start: Long, val end: Long, val oldEnd: Long, val type: Int, val name: String, val change: Int
+) {
+    companion object {
+        const val CHANGE_NEW = 0
+        const val CHANGE_REMOVED = 1
+        const val CHANGE_GROWN = 2
+        const val CHANGE_SHRUNK = 3
+    }
+
+    val isNew: Boolean
+        get() = change == CHANGE_NEW
+
+    val isRemoved: Boolean
+        get() = change == CHANGE_REMOVED
+
+    val isResized: Boolean
+        get() = change == CHANGE_GROWN || change == CHANGE_SHRUNK
+
+    /** Size change in bytes, negative for shrunk regions */
+    val sizeDelta: Long
+        get() = when (change) {
+            CHANGE_NEW -> end - start
+            CHANGE_REMOVED -> start - end
+            else -> end - oldEnd
+        }
+
+    override fun toString(): String {
+        return "RegionDiffEntry(0x%016X-0x%016X (was 0x%016X) %s, change=%d)".format(
+            start, end, oldEnd, name, change
+        )
+    }
+}
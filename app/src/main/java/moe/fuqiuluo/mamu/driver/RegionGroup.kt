@@ -0,0 +1,20 @@
+package moe.fuqiuluo.mamu.driver
+
+/**
+ * Search results that fall into one memory region, used for collapsible sections.
+ *
+ * @property start Start address of the region (gap start when [mapped] is false)
+ * @property end End address of the region (gap end when [mapped] is false)
+ * @property name Display name, e.g. `libunity.so`, `anonymous:7fa2000000`, `[heap]` or `unmapped`
+ * @property count Number of results in the region
+ * @property firstIndex Result store index of the first result in the region
+ * @property mapped Whether the region is still present in the process map
+ */
+data class RegionGroup(
+    val start: Long,
+    val end: Long,
+    val name: String,
+    val count: Long,
+    val firstIndex: Long,
+    val mapped: Boolean,
+)
@@ -0,0 +1,12 @@
+package moe.fuqiuluo.mamu.driver
+
+/**
+ * 结果集文本导出的格式，与 native 侧 ResultExportFormat 的取值一致
+ */
+enum class ResultExportFormat(val nativeId: Int) {
+    /** `index,address,type,value`，精确结果没有 value 列 */
+    CSV(0),
+
+    /** 每行一个 JSON 对象：`{"index","address","type","value"}` */
+    JSON_LINES(1),
+}
@@ -0,0 +1,85 @@
+@file:Suppress("KotlinJniMissingFunction")
+
+package moe.fuqiuluo.mamu.driver
+
+/**
+ * 保存的地址列表
+ *
+ * 列表保存在 native 层，按添加顺序排列，重新绑定进程后保留（不可读的值显示 N/A）。
+ * 值的刷新在后台批量进行：定时调用 [refreshValues]，[getGeneration] 变化后再取一次值即可。
+ */
+object SavedAddressManager {
+
+    init {
+        System.loadLibrary("mamu_core")
+    }
+
+    /**
+     * 保存地址，地址已经保存时更新类型和标签
+     *
+     * @param address 地址
+     * @param valueType 值类型 ID（不支持 Pattern）
+     * @param label 标签
+     * @return 是否保存成功
+     */
+    fun addSaved(address: Long, valueType: Int, label: String = ""): Boolean {
+        return nativeAddSaved(address, valueType, label)
+    }
+
+    /**
+     * 删除保存的地址，冻结中的地址同时取消冻结
+     */
+    fun removeSaved(address: Long): Boolean {
+        return nativeRemoveSaved(address)
+    }
+
+    /**
+     * 清空保存的地址，冻结中的地址同时取消冻结
+     */
+    fun clearSaved() {
+        nativeClearSaved()
+    }
+
+    /**
+     * 列出保存的地址
+     *
+     * @return JSON 数组 [{address, value_type, label, frozen}]，按添加顺序
+     */
+    fun listSaved(): String {
+        return nativeListSaved()
+    }
+
+    /**
+     * 冻结或取消冻结保存的地址，冻结的值为最近一次刷新读到的值
+     *
+     * @return 地址没有保存或值还不可读时返回 false
+     */
+    fun setFrozen(address: Long, frozen: Boolean): Boolean {
+        return nativeSetSavedFrozen(address, frozen)
+    }
+
+    /**
+     * 在后台开始一次批量刷新，立即返回最近一次刷新的值
+     *
+     * @return 格式化后的值，顺序与 [listSaved] 相同，不可读的值为 "N/A"
+     */
+    fun refreshValues(): Array<String> {
+        return nativeRefreshSavedValues()
+    }
+
+    /**
+     * 当前代数，列表变化或一次刷新完成时递增
+     */
+    fun getGeneration(): Long {
+        return nativeGetSavedGeneration()
+    }
+
+    // Native methods
+    private external fun nativeAddSaved(address: Long, valueType: Int, label: String): Boolean
+    private external fun nativeRemoveSaved(address: Long): Boolean
+    private external fun nativeClearSaved()
+    private external fun nativeListSaved(): String
+    private external fun nativeSetSavedFrozen(address: Long, frozen: Boolean): Boolean
+    private external fun nativeRefreshSavedValues(): Array<String>
+    private external fun nativeGetSavedGeneration(): Long
+}
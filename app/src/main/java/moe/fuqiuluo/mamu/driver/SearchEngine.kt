@@ -15,15 +15,46 @@ object SearchEngine {
     /**
      * Shared buffer size in bytes.
      * Memory layout:
-     * [0-3]   status         (Rust writes)  SearchStatus enum
-     * [4-7]   progress       (Rust writes)  0-100
-     * [8-11]  regions_done   (Rust writes)  completed region count
-     * [12-19] found_count    (Rust writes)  total results found (i64)
-     * [20-23] heartbeat      (Rust writes)  periodic random value
-     * [24-27] cancel_flag    (Kotlin writes) 1 = cancel requested
-     * [28-31] error_code     (Rust writes)  error code when status is Error
+     * [0-15]  header         (Rust writes)  magic, layout version, capabilities, size, see [SharedBufferHeader]
+     * [16-19] status         (Rust writes)  SearchStatus enum
+     * [20-23] progress       (Rust writes)  0-100
+     * [24-27] regions_done   (Rust writes)  completed region count
+     * [28-35] found_count    (Rust writes)  total results found (i64)
+     * [36-39] heartbeat      (Rust writes)  periodic random value
+     * [40-43] cancel_flag    (Kotlin writes) 1 = cancel requested
+     * [44-47] error_code     (Rust writes)  error code when status is Error
+     * [48-51] flags          (Rust writes)  event bits, see [Flag]
+     * [52-59] readable_bytes (Rust writes)  bytes read successfully by the last scan (i64)
+     * [60-67] failed_bytes   (Rust writes)  bytes the last scan failed to read (i64)
+     * [68-71] phase          (Rust writes)  what the search is doing, see [Phase]
+     * [72-75] phase_progress (Rust writes)  0-100 within the current phase after [Phase.COLLECTING]
+     * [76-83] bytes_scanned  (Rust writes)  bytes the scan has covered so far, readable + failed (i64)
+     * [84-91] failed_pages   (Rust writes)  pages the scan failed to read so far (i64)
+     * [92-95] throughput     (Rust writes)  average MB/s of the scan so far (f32)
      */
-    const val SHARED_BUFFER_SIZE = 32
+    const val SHARED_BUFFER_SIZE = 96
+
+    /** Layout version of the shared buffer, bumped whenever fields are added or moved. */
+    const val SHARED_BUFFER_VERSION = 3
+
+    /** Header magic of the search shared buffer, "MSRC" in little endian. */
+    const val SHARED_BUFFER_MAGIC = 0x4352534D
+
+    /** Capability bits in the shared buffer header, see [getSharedBufferCapabilities]. */
+    object Capability {
+        /** [getPhase] and [getPhaseProgress] are reported. */
+        const val PHASES = 1
+        /** Readable/failed bytes, bytes scanned, failed pages and throughput are reported. */
+        const val SCAN_STATS = 2
+        /** [getFlags] carries event bits. */
+        const val EVENT_FLAGS = 4
+        /** [getStatus] can be [Status.PARTIALLY_COMPLETED]. */
+        const val PARTIAL_STATUS = 8
+        /** [getStatus] can be [Status.COMPLETED_TRUNCATED]. */
+        const val TRUNCATED_STATUS = 16
+        /** [getStatus] can be [Status.SNAPSHOT_ONLY]. */
+        const val SNAPSHOT_STATUS = 32
+    }
 
     /** Search status constants. */
     object Status {
@@ -32,6 +63,27 @@ object SearchEngine {
         const val COMPLETED = 2
         const val CANCELLED = 3
         const val ERROR = 4
+        /** A fuzzy initial scan was cancelled; the finished regions' results are kept, see [resumeFuzzySearchAsync]. */
+        const val PARTIALLY_COMPLETED = 5
+        /** An initial scan reached its result cap and stopped early; the results found so far are kept. */
+        const val COMPLETED_TRUNCATED = 6
+        /**
+         * A condition refine on exact results had no old values to compare against; it only recorded the current
+         * values and did not apply the condition. The next condition refine compares against them.
+         */
+        const val SNAPSHOT_ONLY = 7
+    }
+
+    /**
+     * Phases of a running search. [getProgress] covers [COLLECTING] only,
+     * the later phases report through [getPhaseProgress].
+     */
+    object Phase {
+        const val COLLECTING = 0
+        const val SORTING = 1
+        /** Reading current values of the results for compatibility mode. */
+        const val CAPTURING = 2
+        const val STORING = 3
     }
 
     /** Error code constants. */
@@ -42,21 +94,74 @@ object SearchEngine {
         const val MEMORY_READ_FAILED = 3
         const val INTERNAL_ERROR = 4
         const val ALREADY_SEARCHING = 5
+        /** No heartbeat within the stall timeout; the watchdog aborted the search. */
+        const val STALLED = 6
+        /** The bound process exited; the search was cancelled and the process unbound. */
+        const val PROCESS_DIED = 7
+    }
+
+    /** Shared buffer flag bits. */
+    object Flag {
+        /** Deferred compatibility mode capture ran after a refine, fuzzy switching is available. */
+        const val COMPAT_CAPTURED = 1
+        /** The first scan reused cached results for at least one unchanged region. */
+        const val SCAN_CACHE_REUSED = 2
+        /** The scan found nothing and read almost no bytes, so the empty result says nothing about the value. */
+        const val NOTHING_READABLE = 4
+        /** Memory pressure crossed the soft limit, results were written to the store during the scan. */
+        const val MEMORY_PRESSURE_STREAMED = 8
+        /** Memory pressure crossed the hard limit and some regions were not scanned; the results are partial. */
+        const val PARTIAL_RESULTS = 16
+        /** A condition refine on exact results only captured the current values; the next one compares against them. */
+        const val SNAPSHOT_CAPTURED = 32
+        /** The initial scan stopped early because it reached the result cap; the results are partial. */
+        const val RESULTS_TRUNCATED = 64
+    }
+
+    /** Single-value refine strategies, see [setRefineStrategy]. */
+    /** Display orders of [getResults], see [setResultOrder]. */
+    object ResultOrder {
+        const val STORAGE = 0
+        /** Results from the earliest pass first, storage order within a pass. */
+        const val OLDEST_FIRST = 1
+    }
+
+    object RefineStrategy {
+        const val AUTO = -1
+        const val PER_ITEM = 0
+        const val RESCAN_AND_INTERSECT = 1
     }
 
     /** Shared buffer offsets. */
     private object Offset {
-        const val STATUS = 0
-        const val PROGRESS = 4
-        const val REGIONS_DONE = 8
-        const val FOUND_COUNT = 12
-        const val HEARTBEAT = 20
-        const val CANCEL_FLAG = 24
-        const val ERROR_CODE = 28
+        const val STATUS = 16
+        const val PROGRESS = 20
+        const val REGIONS_DONE = 24
+        const val FOUND_COUNT = 28
+        const val HEARTBEAT = 36
+        const val CANCEL_FLAG = 40
+        const val ERROR_CODE = 44
+        const val FLAGS = 48
+        const val READABLE_BYTES = 52
+        const val FAILED_BYTES = 60
+        const val PHASE = 68
+        const val PHASE_PROGRESS = 72
+        const val BYTES_SCANNED = 76
+        const val FAILED_PAGES = 84
+        const val THROUGHPUT = 92
     }
 
+    /** Kept even when the layout is incompatible, the native side still writes into it. */
     private var sharedBuffer: ByteBuffer? = null
 
+    /** Whether the native side wrote the header of [SHARED_BUFFER_VERSION] into [sharedBuffer]. */
+    @Volatile
+    private var layoutCompatible = false
+
+    /** The shared buffer if its fields can be interpreted with [Offset]. */
+    private val fields: ByteBuffer?
+        get() = sharedBuffer?.takeIf { layoutCompatible }
+
     /**
      * Initializes the search engine.
      * @param bufferSize Search buffer size in bytes (for caching search results).
@@ -71,9 +176,7 @@ object SearchEngine {
     ): Boolean {
         if (nativeInitSearchEngine(bufferSize, cacheFileDir, chunkSize)) {
             // Allocate shared buffer for progress communication.
-            sharedBuffer =
-                ByteBuffer.allocateDirect(SHARED_BUFFER_SIZE).order(ByteOrder.LITTLE_ENDIAN)
-            nativeSetSharedBuffer(sharedBuffer!!)
+            setSharedBuffer(ByteBuffer.allocateDirect(SHARED_BUFFER_SIZE).order(ByteOrder.LITTLE_ENDIAN))
             return true
         }
         return false
@@ -89,47 +192,104 @@ object SearchEngine {
      * Reads current search status from shared buffer.
      * @return One of Status constants.
      */
-    fun getStatus(): Int = sharedBuffer?.getInt(Offset.STATUS) ?: Status.IDLE
+    fun getStatus(): Int = fields?.getInt(Offset.STATUS) ?: Status.IDLE
 
     /**
      * Reads current progress from shared buffer.
      * @return Progress value 0-100.
      */
-    fun getProgress(): Int = sharedBuffer?.getInt(Offset.PROGRESS) ?: 0
+    fun getProgress(): Int = fields?.getInt(Offset.PROGRESS) ?: 0
 
     /**
      * Reads completed region count from shared buffer.
      */
-    fun getRegionsDone(): Int = sharedBuffer?.getInt(Offset.REGIONS_DONE) ?: 0
+    fun getRegionsDone(): Int = fields?.getInt(Offset.REGIONS_DONE) ?: 0
 
     /**
      * Reads total found count from shared buffer.
      */
-    fun getFoundCount(): Long = sharedBuffer?.getLong(Offset.FOUND_COUNT) ?: 0
+    fun getFoundCount(): Long = fields?.getLong(Offset.FOUND_COUNT) ?: 0
 
     /**
      * Reads heartbeat value from shared buffer.
      */
-    fun getHeartbeat(): Int = sharedBuffer?.getInt(Offset.HEARTBEAT) ?: 0
+    fun getHeartbeat(): Int = fields?.getInt(Offset.HEARTBEAT) ?: 0
 
     /**
      * Reads error code from shared buffer.
      * @return One of ErrorCode constants.
      */
-    fun getErrorCode(): Int = sharedBuffer?.getInt(Offset.ERROR_CODE) ?: ErrorCode.NONE
+    fun getErrorCode(): Int = fields?.getInt(Offset.ERROR_CODE) ?: ErrorCode.NONE
+
+    /**
+     * Reads event flags of the last operation from shared buffer.
+     * @return Bit set of Flag constants.
+     */
+    fun getFlags(): Int = fields?.getInt(Offset.FLAGS) ?: 0
+
+    /**
+     * Reads the number of bytes the last scan read successfully.
+     */
+    fun getReadableBytes(): Long = fields?.getLong(Offset.READABLE_BYTES) ?: 0
+
+    /**
+     * Reads the number of bytes the last scan failed to read.
+     */
+    fun getFailedBytes(): Long = fields?.getLong(Offset.FAILED_BYTES) ?: 0
+
+    /**
+     * Reads the current phase of the search from shared buffer.
+     * @return One of Phase constants.
+     */
+    fun getPhase(): Int = fields?.getInt(Offset.PHASE) ?: Phase.COLLECTING
+
+    /**
+     * Reads the progress within the current phase from shared buffer.
+     * @return Progress value 0-100.
+     */
+    fun getPhaseProgress(): Int = fields?.getInt(Offset.PHASE_PROGRESS) ?: 0
+
+    /**
+     * Reads the layout version from the shared buffer header.
+     * @return 0 if no buffer is set or the native side did not write a search header.
+     */
+    fun getSharedBufferVersion(): Int =
+        sharedBuffer?.let { SharedBufferHeader.layoutVersion(it, SHARED_BUFFER_MAGIC) } ?: 0
+
+    /**
+     * Reads the capability bits from the shared buffer header.
+     * @return Bit set of Capability constants.
+     */
+    fun getSharedBufferCapabilities(): Int =
+        sharedBuffer?.let { SharedBufferHeader.capabilities(it, SHARED_BUFFER_MAGIC) } ?: 0
+
+    /**
+     * Reads the number of bytes the scan has covered so far (readable + failed).
+     */
+    fun getBytesScanned(): Long = fields?.getLong(Offset.BYTES_SCANNED) ?: 0
+
+    /**
+     * Reads the number of pages the scan has failed to read so far.
+     */
+    fun getFailedPages(): Long = fields?.getLong(Offset.FAILED_PAGES) ?: 0
+
+    /**
+     * Reads the average throughput of the scan so far in MB/s.
+     */
+    fun getThroughputMbPerSec(): Float = fields?.getFloat(Offset.THROUGHPUT) ?: 0f
 
     /**
      * Requests cancellation by writing to shared buffer. No JNI call needed.
      */
     fun requestCancelViaBuffer() {
-        sharedBuffer?.putInt(Offset.CANCEL_FLAG, 1)
+        fields?.putInt(Offset.CANCEL_FLAG, 1)
     }
 
     /**
      * Clears the cancel flag in shared buffer.
      */
     private fun clearCancelFlag() {
-        sharedBuffer?.putInt(Offset.CANCEL_FLAG, 0)
+        fields?.putInt(Offset.CANCEL_FLAG, 0)
     }
 
     /**
@@ -147,10 +307,16 @@ object SearchEngine {
     /**
      * Starts an async exact/group search. Returns immediately.
      * Progress is communicated via the shared buffer.
-     * @param query Search content.
+     * @param query Search content. A `:aN` suffix after the range (e.g. `100D:a1`) scans candidates at
+     *              N-byte alignment instead of the value type size; single values also accept `123F:1`.
      * @param type Data type.
      * @param ranges Memory range set.
      * @param useDeepSearch Whether to use deep search.
+     * @param pauseTarget Whether to stop the scanned process (SIGSTOP) until the scan finishes.
+     * @param targetPid Process to scan, 0 for the bound process. Other processes are read through
+     *                  [WuwaDriver.bindSecondaryProcess] handles; only the initial scan uses it.
+     * @param maxResults Stop the scan once this many results are found, 0 for no cap. A capped scan
+     *                   completes with [Status.COMPLETED_TRUNCATED] and may keep slightly more results.
      * @return Whether the search started successfully.
      */
     fun startSearchAsync(
@@ -159,16 +325,15 @@ object SearchEngine {
         ranges: Set<MemoryRange>,
         useDeepSearch: Boolean,
         keepResult: Boolean = false,
+        pauseTarget: Boolean = false,
+        targetPid: Int = 0,
+        maxResults: Long = 0,
     ): Boolean {
-        val nativeRegions = mutableListOf<Long>()
-
-        WuwaDriver.queryMemRegionsWithRetry()
-            .divideToSimpleMemoryRange()
-            .filter { ranges.contains(it.range) }
-            .forEach {
-                nativeRegions.add(it.start)
-                nativeRegions.add(it.end)
-            }
+        val nativeRegions = if (targetPid > 0) {
+            WuwaDriver.getFilteredRegions(ranges, targetPid)
+        } else {
+            WuwaDriver.getFilteredRegions(ranges)
+        }
 
         clearSharedBuffer()
         newSharedBuffer()
@@ -176,9 +341,12 @@ object SearchEngine {
         return nativeStartSearchAsync(
             query,
             type.nativeId,
-            nativeRegions.toLongArray(),
+            nativeRegions,
             useDeepSearch,
-            keepResult
+            keepResult,
+            pauseTarget,
+            targetPid,
+            maxResults
         )
     }
 
@@ -189,6 +357,9 @@ object SearchEngine {
      * @param regions Memory region array, format [start1, end1, start2, end2, ...].
      * @param useDeepSearch Whether to use deep search.
      * @param keepResult Whether to keep existing results when switching modes.
+     * @param pauseTarget Whether to stop the scanned process (SIGSTOP) until the scan finishes.
+     * @param targetPid Process to scan, 0 for the bound process (see [startSearchAsync]).
+     * @param maxResults Result cap of the scan, 0 for no cap (see [startSearchAsync]).
      * @return Whether the search started successfully.
      */
     fun startSearchAsyncWithCustomRange(
@@ -197,17 +368,23 @@ object SearchEngine {
         regions: LongArray,
         useDeepSearch: Boolean,
         keepResult: Boolean = false,
+        pauseTarget: Boolean = false,
+        targetPid: Int = 0,
+        maxResults: Long = 0,
     ): Boolean {
         clearSharedBuffer()
         if (!newSharedBuffer()) {
             throw RuntimeException("failed to init SharedBuffer")
         }
-        return nativeStartSearchAsync(query, type.nativeId, regions, useDeepSearch, keepResult)
+        return nativeStartSearchAsync(query, type.nativeId, regions, useDeepSearch, keepResult, pauseTarget, targetPid, maxResults)
     }
 
     /**
      * Starts an async refine search. Returns immediately.
-     * @param query Search content.
+     * @param query Search content. With the pattern type this is a pattern like "1A ?B ??" matched at exactly
+     * each result address; an empty string re-matches the pattern of the current pattern results.
+     * Plain values refine pattern results by the value at the start of each match.
+     * To refine by a condition such as increased or decreased, use [startFuzzyRefineAsync].
      * @param type Data type.
      * @return Whether the search started successfully.
      */
@@ -267,10 +444,10 @@ object SearchEngine {
     }
 
     /**
-     * Gets search results.
-     * @param start Starting index.
+     * Gets search results in display order.
+     * @param start Starting index; while a filter is active this is a position in the filtered results.
      * @param count Number of results to get.
-     * @return Search result array.
+     * @return Search result array; [SearchResultItem.nativePosition] is the result handle, use it to remove results.
      */
     fun getResults(start: Int, count: Int): Array<SearchResultItem> {
         return nativeGetResults(start, count).also {
@@ -279,7 +456,61 @@ object SearchEngine {
     }
 
     /**
-     * Gets total result count.
+     * Re-reads live memory for a page of exact results (pull to refresh).
+     * [getResults] shows the values cached when the search matched; call this for the visible page, then fetch it again.
+     * @param start Starting index in display order, same as [getResults].
+     * @param count Number of results to refresh.
+     * @return Number of results whose cached value was refreshed.
+     */
+    fun refreshResultValues(start: Int, count: Int): Int = nativeRefreshResultValues(start, count)
+
+    /**
+     * Groups the current results by memory region. Cached until the results change.
+     * @return Groups in result order; the array index is the group index for [getResultsForGroup].
+     */
+    fun getRegionGroups(): Array<RegionGroup> {
+        return nativeGetRegionGroups()
+    }
+
+    /**
+     * Gets the results whose address is in [start, end), ordered by address.
+     * Ignores the filter and display order, so it answers whether an address is in the result set.
+     * @param start Start address (inclusive).
+     * @param end End address (exclusive).
+     * @param maxCount Maximum number of results to return.
+     * @return Results in the range; [ResultRange.truncated] is set when the range holds more than [maxCount].
+     */
+    fun getResultsInRange(start: Long, end: Long, maxCount: Int): ResultRange {
+        return nativeGetResultsInRange(start, end, maxCount)
+    }
+
+    /**
+     * Gets the results whose address is in [startAddr, endAddr) and where [startAddr] falls in the result store.
+     * Both are found by binary search, so jumping to an address in a large result set does not page from index 0.
+     * @param startAddr Start address (inclusive).
+     * @param endAddr End address (exclusive).
+     * @param limit Maximum number of results to return.
+     * @return Results in the window; [ResultWindow.startIndex] is the store index of the first result at or
+     * after [startAddr], which is its insertion point when the address is not in the result set.
+     */
+    fun getResultsByAddressRange(startAddr: Long, endAddr: Long, limit: Int): ResultWindow {
+        return nativeGetResultsByAddressRange(startAddr, endAddr, limit)
+    }
+
+    /**
+     * Gets a page of results within one region group.
+     * @param groupIndex Index into [getRegionGroups].
+     * @param start Starting index within the group.
+     * @param count Number of results to get.
+     * @return Search result array; [SearchResultItem.nativePosition] is the result handle.
+     */
+    fun getResultsForGroup(groupIndex: Int, start: Int, count: Int): Array<SearchResultItem> {
+        return nativeGetResultsForGroup(groupIndex, start, count)
+    }
+
+    /**
+     * Gets total result count. While a filter is active this is the number of results that pass it,
+     * so it matches the positions accepted by [getResults].
      */
     fun getTotalResultCount(): Long {
         return nativeGetTotalResultCount()
@@ -293,7 +524,7 @@ object SearchEngine {
     }
 
     /**
-     * Removes a single search result.
+     * Removes a single search result by position. Positions shift when results change, prefer [removeByHandles].
      * @param index Search result index.
      * @return Whether removal was successful.
      */
@@ -302,7 +533,7 @@ object SearchEngine {
     }
 
     /**
-     * Removes multiple search results.
+     * Removes multiple search results by position. Positions shift when results change, prefer [removeByHandles].
      * @param indices Search result index array.
      * @return Whether removal was successful.
      */
@@ -311,7 +542,7 @@ object SearchEngine {
     }
 
     /**
-     * Keeps only the specified search results, removes all others.
+     * Keeps only the specified search results by position, removes all others. Prefer [keepOnlyHandles].
      * @param indices Search result index array to keep.
      * @return Whether operation was successful.
      */
@@ -319,9 +550,69 @@ object SearchEngine {
         return nativeKeepOnlyResults(indices)
     }
 
+    /**
+     * Removes search results by handle ([SearchResultItem.nativePosition]).
+     * Handles keep pointing at the same result after other removals and in-place refines;
+     * results that no longer exist are skipped.
+     * @param handles Result handles.
+     * @return Number of results removed.
+     */
+    fun removeByHandles(handles: LongArray): Int {
+        return nativeRemoveByHandles(handles)
+    }
+
+    /**
+     * Keeps only the results with the given handles ([SearchResultItem.nativePosition]), removes all others.
+     * Throws if none of the handles exist anymore, instead of clearing the results.
+     * @param handles Result handles to keep.
+     * @return Number of results kept.
+     */
+    fun keepOnlyHandles(handles: LongArray): Int {
+        return nativeKeepOnlyHandles(handles)
+    }
+
+    /**
+     * Whether the last refine can be undone with [undoRefine]. Only one level is kept;
+     * a new search, import or rebase drops it.
+     */
+    fun canUndoRefine(): Boolean {
+        return nativeCanUndoRefine()
+    }
+
+    /**
+     * Restores the result set from before the last refine (exact or fuzzy), including labels.
+     * Removals done after that refine are undone too. Throws if there is nothing to undo.
+     * @return Number of restored results.
+     */
+    fun undoRefine(): Long {
+        return nativeUndoRefine()
+    }
+
+    /**
+     * Sets the label of the result with the given handle ([SearchResultItem.nativePosition]).
+     * Labels follow the address through refines and are included in exported result files.
+     * Throws if the result no longer exists.
+     * @param handle Result handle.
+     * @param label Label text, empty to remove the label.
+     */
+    fun setResultLabel(handle: Long, label: String) {
+        nativeSetResultLabel(handle, label)
+    }
+
+    /**
+     * Gets the labels of the results with the given handles ([SearchResultItem.nativePosition]).
+     * @param handles Result handles.
+     * @return Labels in the same order, null for results without a label or that no longer exist.
+     */
+    fun getResultLabels(handles: LongArray): Array<String?> {
+        return nativeGetResultLabels(handles)
+    }
+
     /**
      * Sets filter conditions (address range, value range, data type, permissions).
      * Only affects search result filtering, does not affect actual search process.
+     * @param minPass Only results that entered the set in this pass or later, negative for no bound.
+     * @param maxPass Only results that entered the set in this pass or earlier, negative for no bound.
      */
     fun setFilter(
         enableAddressFilter: Boolean,
@@ -329,13 +620,24 @@ object SearchEngine {
         addressEnd: Long,
         enableTypeFilter: Boolean,
         typeIds: IntArray,
+        minPass: Int = -1,
+        maxPass: Int = -1,
     ) {
         nativeSetFilter(
             enableAddressFilter, addressStart, addressEnd,
             enableTypeFilter, typeIds,
+            minPass, maxPass,
         )
     }
 
+    /**
+     * Sets the display order of [getResults].
+     * @param order [ResultOrder] constant.
+     */
+    fun setResultOrder(order: Int) {
+        nativeSetResultOrder(order)
+    }
+
     /**
      * Clears all filter conditions.
      */
@@ -387,17 +689,176 @@ object SearchEngine {
         return nativeGetCompatibilityMode()
     }
 
+    /**
+     * Gets the effective compatibility mode state.
+     * @return JSON object of {requested, active, deferred, auto_threshold}.
+     */
+    fun getCompatibilityState(): String {
+        return nativeGetCompatibilityState()
+    }
+
+    /**
+     * Gets the read statistics of the current or last scan.
+     * @return JSON object of {bytes_scanned, readable_bytes, failed_bytes, failed_pages, mb_per_sec}.
+     */
+    fun getSearchStats(): String {
+        return nativeGetSearchStats()
+    }
+
+    /**
+     * Sets the result count above which compatibility mode stores exact results only.
+     * The value capture then runs once a refine brings the count under the threshold.
+     * @param threshold Result count threshold, 0 disables the automatic policy.
+     */
+    fun setCompatAutoThreshold(threshold: Long) {
+        nativeSetCompatAutoThreshold(threshold)
+    }
+
+    /**
+     * Estimates a scan without starting it.
+     * The first estimate on a device runs a short probe read to seed the throughput statistic.
+     * Throws while a search is running, since the probe would compete with the scan for the driver.
+     * @param type Data type to search for; determines the fuzzy scan stride.
+     * @param ranges Memory range set.
+     * @param fuzzy Whether to project result count and storage for a fuzzy initial scan.
+     * @return JSON object of {total_bytes, region_count, throughput_bytes_per_sec, estimated_millis,
+     *         projected_results, projected_storage_bytes, free_disk_bytes, probed, warnings};
+     *         estimated_millis is null when the probe could not read anything.
+     */
+    fun estimateScan(
+        type: DisplayValueType,
+        ranges: Set<MemoryRange>,
+        fuzzy: Boolean,
+    ): String {
+        val nativeRegions = mutableListOf<Long>()
+
+        WuwaDriver.queryMemRegionsWithRetry()
+            .divideToSimpleMemoryRange()
+            .filter { ranges.contains(it.range) }
+            .forEach {
+                nativeRegions.add(it.start)
+                nativeRegions.add(it.end)
+            }
+
+        return nativeEstimateScan(type.nativeId, nativeRegions.toLongArray(), fuzzy)
+    }
+
+    /**
+     * Sets the limits that scan estimates warn about.
+     * @param diskBudget Bytes a fuzzy scan may store on disk.
+     * @param maxResults Maximum result count.
+     */
+    fun setScanLimits(diskBudget: Long, maxResults: Long) {
+        nativeSetScanLimits(diskBudget, maxResults)
+    }
+
+    /**
+     * Enables the per-region first scan cache (best effort, off by default).
+     * Repeating an identical first scan reuses results for regions whose sampled pages
+     * are unchanged; a value changing between sample pages can be missed.
+     * Disabling the cache deletes the cached files.
+     */
+    fun setScanCacheEnabled(enabled: Boolean) {
+        nativeSetScanCacheEnabled(enabled)
+    }
+
+    /**
+     * Sorts fuzzy initial scan results by address and drops duplicate addresses
+     * once the scan completes (on by default). Range lookups and refines then read
+     * the store in one ascending pass; turn it off to skip the extra pass over huge result sets.
+     */
+    fun setSortFuzzyResults(enabled: Boolean) {
+        nativeSetSortFuzzyResults(enabled)
+    }
+
+    /**
+     * Sets how many worker threads searches and refines use.
+     * Takes effect from the next search; a running search keeps its threads.
+     * @param threads Thread count, 0 uses the shared default pool.
+     */
+    fun setSearchThreadCount(threads: Int) {
+        nativeSetSearchThreadCount(threads)
+    }
+
+    /**
+     * Runs search worker threads at background priority (SCHED_BATCH, nice 10)
+     * so the target process keeps its frame rate. Takes effect from the next search.
+     */
+    fun setSearchBackgroundPriority(background: Boolean) {
+        nativeSetSearchBackgroundPriority(background)
+    }
+
+    /**
+     * Sets how long a search may go without a heartbeat before the watchdog aborts it
+     * and reports [ErrorCode.STALLED]. Takes effect from the next search.
+     * @param timeoutMillis Timeout in milliseconds (default 30000), 0 disables the watchdog.
+     */
+    fun setStallTimeout(timeoutMillis: Long) {
+        nativeSetStallTimeout(timeoutMillis)
+    }
+
+    /**
+     * Escape hatch for a search that never finishes: aborts it, clears the partial results
+     * and lets a new search start. A running search ends with [ErrorCode.STALLED].
+     */
+    fun forceReset() {
+        nativeForceResetSearchEngine()
+    }
+
+    /**
+     * Overrides how single-value refines read the current results.
+     * @param strategy One of [RefineStrategy]. AUTO picks from the result count and the
+     * bytes of the pages the results occupy.
+     */
+    fun setRefineStrategy(strategy: Int) {
+        nativeSetRefineStrategy(strategy)
+    }
+
+    /**
+     * Sets how many bytes a per-address refine reads at once. Nearby results are read together
+     * and matched from that buffer, which cuts driver calls on dense result sets.
+     * @param bytes 0 reads every address on its own; other values are clamped to 4KB..64KB.
+     */
+    fun setRefineReadWindow(bytes: Int) {
+        nativeSetRefineReadWindow(bytes)
+    }
+
+    /**
+     * Sets how integer values are shown in result items. Floats, doubles, patterns and strings are not affected.
+     * @param signed Show decimal values as signed; ignored for hex.
+     * @param radix 10 for decimal or 16 for hex, zero-padded to the type width (Dword shows 8 digits).
+     */
+    fun setValueDisplay(signed: Boolean = true, radix: Int = 10) {
+        nativeSetValueDisplay(if (signed) 1 else 0, radix)
+    }
+
+    /**
+     * Sets how many pages refines cache when they read results one by one. Consecutive results
+     * on the same page are then read once per refine, and unreadable pages are not retried.
+     * @param pages 0 disables the cache; values above 4096 are clamped. Defaults to 256.
+     */
+    fun setRefineCachePages(pages: Int) {
+        nativeSetRefineCachePages(pages)
+    }
+
     /**
      * Starts an async fuzzy initial search. Records all values in memory regions.
+     *
+     * With [DisplayValueType.AUTO] each 4-byte aligned address is recorded once and refines keep it while
+     * its Dword, Float or (8-byte aligned only) Qword reading matches, narrowing the result type as they do.
      * @param type Data type to search for.
      * @param ranges Memory range set.
      * @param keepResult If true and currently in exact mode, convert exact results to fuzzy results.
+     * @param pauseTarget Whether to stop the bound process (SIGSTOP) until the initial scan finishes.
+     * @param alignment Alignment of the recorded addresses, a power of two up to 8x the type size; 0 for the type size.
      * @return Whether the search started successfully.
      */
     fun startFuzzySearchAsync(
         type: DisplayValueType,
         ranges: Set<MemoryRange>,
         keepResult: Boolean = false,
+        pauseTarget: Boolean = false,
+        alignment: Int = 0,
     ): Boolean {
         val nativeRegions = mutableListOf<Long>()
 
@@ -412,7 +873,7 @@ object SearchEngine {
         clearSharedBuffer()
         newSharedBuffer()
 
-        return nativeStartFuzzySearchAsync(type.nativeId, nativeRegions.toLongArray(), keepResult)
+        return nativeStartFuzzySearchAsync(type.nativeId, nativeRegions.toLongArray(), keepResult, pauseTarget, alignment)
     }
 
     /**
@@ -420,22 +881,46 @@ object SearchEngine {
      * @param type Data type to search for.
      * @param regions Memory region array, format [start1, end1, start2, end2, ...].
      * @param keepResult If true and currently in exact mode, convert exact results to fuzzy results.
+     * @param pauseTarget Whether to stop the bound process (SIGSTOP) until the initial scan finishes.
+     * @param alignment Alignment of the recorded addresses, 0 for the type size.
      * @return Whether the search started successfully.
      */
     fun startFuzzySearchAsyncWithCustomRange(
         type: DisplayValueType,
         regions: LongArray,
         keepResult: Boolean = false,
+        pauseTarget: Boolean = false,
+        alignment: Int = 0,
     ): Boolean {
         clearSharedBuffer()
         if (!newSharedBuffer()) {
             throw RuntimeException("failed to init SharedBuffer")
         }
-        return nativeStartFuzzySearchAsync(type.nativeId, regions, keepResult)
+        return nativeStartFuzzySearchAsync(type.nativeId, regions, keepResult, pauseTarget, alignment)
+    }
+
+    /**
+     * Resumes a fuzzy initial scan that was cancelled with [Status.PARTIALLY_COMPLETED].
+     * Only the regions it did not finish are scanned, their results are appended to the kept ones.
+     * Fails when the results changed since the scan was cancelled (refine, removal or a new search).
+     * @param pauseTarget Whether to stop the bound process (SIGSTOP) until the scan finishes.
+     * @return Whether the scan resumed successfully.
+     */
+    fun resumeFuzzySearchAsync(pauseTarget: Boolean = false): Boolean {
+        clearSharedBuffer()
+        newSharedBuffer()
+        return nativeResumeFuzzySearchAsync(pauseTarget)
     }
 
     /**
      * Starts an async fuzzy refine search with a condition.
+     *
+     * Also works on exact results without converting them to fuzzy. The old values are the ones recorded by the
+     * previous condition refine, or else the values read when the results were found; addresses without a recorded
+     * value are kept if still readable. When no values were recorded (too many results, or the results were
+     * refined since), the call ignores [condition], only records the current values (dropping unreadable
+     * addresses) and ends with [Status.SNAPSHOT_ONLY] and [Flag.SNAPSHOT_CAPTURED]; the next call compares
+     * against them.
      * @param condition Fuzzy condition to apply.
      * @param param1 First parameter for conditions that need it.
      * @param param2 Second parameter for range conditions.
@@ -451,6 +936,154 @@ object SearchEngine {
         return nativeStartFuzzyRefineAsync(condition.nativeId, param1, param2)
     }
 
+    /**
+     * Starts an async fuzzy refine that compares current values against an earlier result generation.
+     * @param generationId Generation id returned by [listResultGenerations].
+     * @param condition Fuzzy condition to apply.
+     * @param param1 First parameter for conditions that need it.
+     * @param param2 Second parameter for range conditions.
+     * @param keepMissing Keep results that do not exist in that generation.
+     * @return Whether the search started successfully.
+     */
+    fun startFuzzyRefineAgainstGeneration(
+        generationId: Int,
+        condition: FuzzyCondition,
+        param1: Long = 0,
+        param2: Long = 0,
+        keepMissing: Boolean = false,
+    ): Boolean {
+        clearSharedBuffer()
+        newSharedBuffer()
+        return nativeStartFuzzyRefineAgainstGeneration(generationId, condition.nativeId, param1, param2, keepMissing)
+    }
+
+    /**
+     * Starts an async refine that keeps only fuzzy results whose value does not change for [durationMs].
+     * Progress reports elapsed milliseconds as regions done and the remaining count as found count;
+     * cancelling keeps the results filtered so far.
+     * @param durationMs Observation window in milliseconds.
+     * @param sampleMs Sampling interval in milliseconds; changes shorter than this may be missed.
+     * @return Whether the refine started successfully.
+     */
+    fun refineStableFor(durationMs: Long, sampleMs: Long = 500): Boolean {
+        clearSharedBuffer()
+        newSharedBuffer()
+        return nativeRefineStableFor(durationMs, sampleMs)
+    }
+
+    /**
+     * Gets our own memory usage and the pressure limits consulted by scans.
+     * @return JSON with `rss`, `peak_rss`, `soft_limit`, `hard_limit` (bytes) and `level` (`Normal`, `Soft`, `Hard`).
+     */
+    fun getProcessMemoryStats(): String {
+        return nativeGetProcessMemoryStats()
+    }
+
+    /**
+     * Sets the memory pressure limits. Above [softLimit] scans write results to the store early and
+     * use smaller buffers ([Flag.MEMORY_PRESSURE_STREAMED]); above [hardLimit] they stop starting new
+     * regions and complete with [Flag.PARTIAL_RESULTS].
+     * @param softLimit Soft limit in bytes.
+     * @param hardLimit Hard limit in bytes, not below [softLimit].
+     * @return Whether the limits were accepted.
+     */
+    fun setMemoryLimits(softLimit: Long, hardLimit: Long): Boolean {
+        return nativeSetMemoryLimits(softLimit, hardLimit)
+    }
+
+    /**
+     * Verifies the on-disk result store of the current mode.
+     * @return JSON with `mode`, the `store` integrity report and the `recovery` report
+     * produced when results left by an unclean shutdown were adopted at init (or null).
+     */
+    fun verifyResultStore(): String {
+        return nativeVerifyResultStore()
+    }
+
+    /**
+     * Lists recorded fuzzy result generations.
+     * @return JSON array of {id, timestamp, count, condition}.
+     */
+    fun listResultGenerations(): String {
+        return nativeListResultGenerations()
+    }
+
+    /**
+     * Imports a GameGuardian saved list (.txt export) into the result list.
+     * Current results are replaced; frozen entries are added to FreezeManager paused,
+     * resume them with FreezeManager.resumeFrozen.
+     * @param path Path of the saved list file.
+     * @return JSON {imported, skipped, frozen_created, warnings, labels: [{address, name}]}.
+     */
+    fun importGGSavedList(path: String): String {
+        return nativeImportGGSavedList(path)
+    }
+
+    /**
+     * Exports the current results (exact or fuzzy, with the pattern length) to a binary file,
+     * so they can be restored after the game or this process restarts.
+     * @param path Destination file path, overwritten if it exists.
+     * @return Number of exported results.
+     */
+    fun exportResults(path: String): Long {
+        return nativeExportResults(path)
+    }
+
+    /**
+     * Exports the current results as text for a spreadsheet or for sharing. Each row has the index,
+     * hex address and value type id; fuzzy rows also carry the stored value, formatted like the result list.
+     * Throws on IO errors.
+     * @param path Destination file path, overwritten if it exists.
+     * @param format CSV or JSON Lines.
+     * @return Number of rows written.
+     */
+    fun exportResults(path: String, format: ResultExportFormat): Long {
+        return nativeExportResults(path, format.nativeId)
+    }
+
+    /**
+     * Restores results saved by [exportResults], replacing the current results.
+     * Switches the result mode when the file was exported in the other mode.
+     * Throws if the file is corrupt or truncated.
+     * @param path File written by [exportResults].
+     * @return Number of imported results.
+     */
+    fun importResults(path: String): Long {
+        return nativeImportResults(path)
+    }
+
+    /**
+     * Saves the current search session so a long fuzzy session survives a crash: the results,
+     * the result mode, the pattern length and the compatibility mode state.
+     * Throws while a search is running.
+     * @param path Session directory; a relative path is placed under `sessions` in the cache dir.
+     * An existing session in the directory is replaced.
+     * @return Number of saved results.
+     */
+    fun saveSession(path: String): Long {
+        return nativeSaveSession(path)
+    }
+
+    /**
+     * Restores a session saved by [saveSession], replacing the current results.
+     * Throws while a search is running, or if the session is missing, from another version or corrupt.
+     * @param path The directory passed to [saveSession].
+     * @return Number of loaded results.
+     */
+    fun loadSession(path: String): Long {
+        return nativeLoadSession(path)
+    }
+
+    /**
+     * Moves results recorded against [module] loaded at [oldBase] to [newBase], e.g. after
+     * [importResults] of a file saved before the game restarted. Results outside the old module are kept as is.
+     * The module size is taken from its current mapping in the bound process.
+     * @return Number of rebased results.
+     */
+    fun rebaseResults(module: String, oldBase: Long, newBase: Long): Long {
+        return nativeRebaseResults(module, oldBase, newBase)
+    }
+
     /**
      * Starts an async pattern/signature search.
      * @param pattern Pattern string like "1A 2B ?C D? ?? FF"
@@ -494,6 +1127,47 @@ object SearchEngine {
         return nativeStartPatternSearchAsync(pattern, regions)
     }
 
+    /**
+     * Starts an async pattern replace: searches the pattern and patches every match.
+     * Each match is patched completely or not at all; patched sites become the result list
+     * and their count is reported as the found count.
+     * @param pattern Pattern string like "1A 2B ?C D? ?? FF"
+     * @param replacement Replacement string like "90 ?? 1F", "??" keeps the original byte.
+     * Must not be longer than the pattern.
+     * @param ranges Memory range set.
+     * @return Whether the replace started successfully.
+     */
+    fun startPatternReplaceAsync(
+        pattern: String,
+        replacement: String,
+        ranges: Set<MemoryRange>,
+    ): Boolean {
+        val nativeRegions = mutableListOf<Long>()
+
+        WuwaDriver.queryMemRegionsWithRetry()
+            .divideToSimpleMemoryRange()
+            .filter { ranges.contains(it.range) }
+            .forEach {
+                nativeRegions.add(it.start)
+                nativeRegions.add(it.end)
+            }
+
+        clearSharedBuffer()
+        newSharedBuffer()
+
+        return nativeStartPatternReplaceAsync(pattern, replacement, nativeRegions.toLongArray())
+    }
+
+    /**
+     * Re-reads a Qword result and dereferences it for click-through navigation.
+     * @param handle Result handle ([SearchResultItem.nativePosition]).
+     * @return Status and target address, see [PointerTarget].
+     */
+    fun getPointerTarget(handle: Long): PointerTarget {
+        val result = nativeGetPointerTarget(handle)
+        return PointerTarget(status = result[0].toInt(), target = result[1])
+    }
+
     /**
      * Gets the current pattern length (for UI display).
      * @return Pattern length in bytes, or -1 if no pattern search has been performed.
@@ -539,7 +1213,16 @@ object SearchEngine {
             throw IllegalArgumentException("Buffer must be at least $SHARED_BUFFER_SIZE bytes")
         }
         sharedBuffer = buffer
-        return nativeSetSharedBuffer(buffer)
+        if (!nativeSetSharedBuffer(buffer)) {
+            layoutCompatible = false
+            return false
+        }
+        val version = SharedBufferHeader.layoutVersion(buffer, SHARED_BUFFER_MAGIC)
+        layoutCompatible = version == SHARED_BUFFER_VERSION
+        if (!layoutCompatible) {
+            Log.e(TAG, "Shared buffer layout version $version does not match $SHARED_BUFFER_VERSION, progress is unavailable")
+        }
+        return layoutCompatible
     }
 
     /**
@@ -547,6 +1230,7 @@ object SearchEngine {
      */
     private fun clearSharedBuffer() {
         nativeClearSharedBuffer()
+        layoutCompatible = false
         sharedBuffer = null
     }
 
@@ -573,7 +1257,10 @@ object SearchEngine {
         defaultType: Int,
         regions: LongArray,
         useDeepSearch: Boolean,
-        keepResult: Boolean
+        keepResult: Boolean,
+        pauseTarget: Boolean,
+        targetPid: Int,
+        maxResults: Long
     ): Boolean
 
     private external fun nativeStartRefineAsync(query: String, defaultType: Int): Boolean
@@ -590,23 +1277,55 @@ object SearchEngine {
     ): Long
 
     private external fun nativeGetResults(start: Int, count: Int): Array<SearchResultItem>
+    private external fun nativeRefreshResultValues(start: Int, count: Int): Int
+    private external fun nativeGetRegionGroups(): Array<RegionGroup>
+
+    private external fun nativeGetResultsForGroup(groupIndex: Int, start: Int, size: Int): Array<SearchResultItem>
+
+    private external fun nativeGetResultsInRange(start: Long, end: Long, maxCount: Int): ResultRange
+    private external fun nativeGetResultsByAddressRange(startAddr: Long, endAddr: Long, limit: Int): ResultWindow
+
     private external fun nativeGetTotalResultCount(): Long
     private external fun nativeClearSearchResults()
     private external fun nativeRemoveResult(index: Int): Boolean
     private external fun nativeRemoveResults(indices: IntArray): Boolean
     private external fun nativeKeepOnlyResults(indices: IntArray): Boolean
+    private external fun nativeRemoveByHandles(handles: LongArray): Int
+    private external fun nativeKeepOnlyHandles(handles: LongArray): Int
+    private external fun nativeCanUndoRefine(): Boolean
+    private external fun nativeUndoRefine(): Long
+    private external fun nativeSetResultLabel(handle: Long, label: String)
+    private external fun nativeGetResultLabels(handles: LongArray): Array<String?>
     private external fun nativeSetFilter(
         enableAddressFilter: Boolean,
         addressStart: Long,
         addressEnd: Long,
         enableTypeFilter: Boolean,
         typeIds: IntArray,
+        minPass: Int,
+        maxPass: Int,
     )
 
+    private external fun nativeSetResultOrder(order: Int)
     private external fun nativeClearFilter()
     private external fun nativeGetCurrentSearchMode(): Int
     private external fun nativeSetCompatibilityMode(enabled: Boolean)
     private external fun nativeGetCompatibilityMode(): Boolean
+    private external fun nativeGetCompatibilityState(): String
+    private external fun nativeGetSearchStats(): String
+    private external fun nativeSetCompatAutoThreshold(threshold: Long)
+    private external fun nativeEstimateScan(type: Int, regions: LongArray, fuzzy: Boolean): String
+    private external fun nativeSetScanLimits(diskBudget: Long, maxResults: Long)
+    private external fun nativeSetScanCacheEnabled(enabled: Boolean)
+    private external fun nativeSetSortFuzzyResults(enabled: Boolean)
+    private external fun nativeSetSearchThreadCount(threads: Int)
+    private external fun nativeSetSearchBackgroundPriority(background: Boolean)
+    private external fun nativeSetStallTimeout(timeoutMillis: Long)
+    private external fun nativeForceResetSearchEngine()
+    private external fun nativeSetRefineStrategy(strategy: Int)
+    private external fun nativeSetRefineReadWindow(bytes: Int)
+    private external fun nativeSetRefineCachePages(pages: Int)
+    private external fun nativeSetValueDisplay(signed: Int, radix: Int)
     @Deprecated("同步搜索版本已废弃")
     private external fun nativeRefineSearch(
         query: String,
@@ -622,22 +1341,65 @@ object SearchEngine {
     private external fun nativeStartFuzzySearchAsync(
         valueType: Int,
         regions: LongArray,
-        keepResult: Boolean
+        keepResult: Boolean,
+        pauseTarget: Boolean,
+        alignment: Int
     ): Boolean
 
+    private external fun nativeResumeFuzzySearchAsync(pauseTarget: Boolean): Boolean
+
     private external fun nativeStartFuzzyRefineAsync(
         conditionId: Int,
         param1: Long,
         param2: Long
     ): Boolean
 
+    private external fun nativeStartFuzzyRefineAgainstGeneration(
+        generationId: Int,
+        conditionId: Int,
+        param1: Long,
+        param2: Long,
+        keepMissing: Boolean
+    ): Boolean
+
+    private external fun nativeRefineStableFor(durationMs: Long, sampleMs: Long): Boolean
+
+    private external fun nativeListResultGenerations(): String
+
+    private external fun nativeVerifyResultStore(): String
+
+    private external fun nativeGetProcessMemoryStats(): String
+
+    private external fun nativeSetMemoryLimits(softLimit: Long, hardLimit: Long): Boolean
+
+    private external fun nativeImportGGSavedList(path: String): String
+
+    private external fun nativeExportResults(path: String): Long
+
+    private external fun nativeExportResults(path: String, format: Int): Long
+
+    private external fun nativeImportResults(path: String): Long
+
+    private external fun nativeSaveSession(path: String): Long
+
+    private external fun nativeLoadSession(path: String): Long
+    private external fun nativeRebaseResults(module: String, oldBase: Long, newBase: Long): Long
+
     private external fun nativeStartPatternSearchAsync(
         pattern: String,
         regions: LongArray
     ): Boolean
 
+    private external fun nativeStartPatternReplaceAsync(
+        pattern: String,
+        replacement: String,
+        regions: LongArray
+    ): Boolean
+
     private external fun nativeGetCurrentPatternLen(): Int
 
+    private external fun nativeGetPointerTarget(handle: Long): LongArray
+
     // Legacy native methods kept for backward compatibility.
     @Deprecated("Low performance")
     private external fun nativeSetProgressBuffer(buffer: ByteBuffer): Boolean
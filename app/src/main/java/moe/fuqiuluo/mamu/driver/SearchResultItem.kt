@@ -3,7 +3,7 @@ package moe.fuqiuluo.mamu.driver
 import moe.fuqiuluo.mamu.floating.data.model.DisplayValueType
 
 interface SearchResultItem {
-    val nativePosition: Long // 原始索引，目的是方便
+    val nativePosition: Long // 搜索结果为稳定句柄，结果集变化后仍指向同一个结果，用于删除 / 保留
 
     val displayValueType: DisplayValueType?
 }
@@ -13,6 +13,10 @@ data class ExactSearchResultItem(
     val address: Long,
     val valueType: Int,
     val value: String,
+    val isPointer: Boolean = false, // Qword 值指向已映射的可读内存
+    val pointerModule: String? = null, // 指针目标的 "模块+偏移"，仅文件映射区域
+    val pass: Int = 0, // 进入结果集的轮次，0 为首次扫描
+    val label: String? = null, // 用户备注，见 SearchEngine.setResultLabel
 ): SearchResultItem {
     override val displayValueType: DisplayValueType?
         get() = DisplayValueType.fromNativeId(valueType)
@@ -22,12 +26,59 @@ data class FuzzySearchResultItem(
     override val nativePosition: Long,
     val address: Long,
     val value: String,
-    val valueType: Int
+    val valueType: Int,
+    val isPointer: Boolean = false,
+    val pointerModule: String? = null,
+    val pass: Int = 0,
+    val label: String? = null,
 ): SearchResultItem {
     override val displayValueType: DisplayValueType?
         get() = DisplayValueType.fromNativeId(valueType)
 }
 
+/**
+ * 一段地址内的搜索结果，见 SearchEngine.getResultsInRange
+ *
+ * @property items 按地址升序的结果，最多 maxCount 条
+ * @property truncated 范围内的结果多于 maxCount，只返回了地址最小的部分
+ */
+class ResultRange(
+    val items: Array<SearchResultItem>,
+    val truncated: Boolean
+)
+
+/**
+ * 一段地址窗口内的搜索结果和窗口起点在结果存储中的位置，见 SearchEngine.getResultsByAddressRange
+ *
+ * @property items 按地址升序的结果，最多 limit 条
+ * @property truncated 窗口内的结果多于 limit，只返回了地址最小的部分
+ * @property startIndex 第一个地址不小于窗口起点的结果在结果存储中的索引，没有时为结果总数
+ */
+class ResultWindow(
+    val items: Array<SearchResultItem>,
+    val truncated: Boolean,
+    val startIndex: Long
+)
+
+/**
+ * Qword 结果解引用的结果，见 SearchEngine.getPointerTarget
+ */
+data class PointerTarget(
+    val status: Int,
+    val target: Long
+) {
+    val isValid: Boolean
+        get() = status == STATUS_VALID
+
+    companion object {
+        const val STATUS_VALID = 0
+        const val STATUS_NOT_QWORD = 1
+        const val STATUS_READ_FAILED = 2
+        const val STATUS_UNMAPPED = 3
+        const val STATUS_INVALID_INDEX = 4
+    }
+}
+
 data class PointerChainResultItem(
     override val nativePosition: Long,
     val address: Long,  // 指针地址
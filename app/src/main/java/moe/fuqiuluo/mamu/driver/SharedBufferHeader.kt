@@ -0,0 +1,43 @@
+package moe.fuqiuluo.mamu.driver
+
+import java.nio.ByteBuffer
+
+/**
+ * Header at the start of the search and pointer scan shared buffers.
+ * Memory layout (written by Rust when the buffer is set):
+ * [0-3]   magic          protocol tag, see [SearchEngine.SHARED_BUFFER_MAGIC] / [PointerScanner.SHARED_BUFFER_MAGIC]
+ * [4-7]   layout_version bumped whenever fields are added or moved
+ * [8-11]  capabilities   feature bits defined by each protocol
+ * [12-15] size           bytes used by the layout
+ *
+ * The header never moves, so it must be checked before any other field is interpreted.
+ */
+object SharedBufferHeader {
+    const val SIZE = 16
+
+    private object Offset {
+        const val MAGIC = 0
+        const val VERSION = 4
+        const val CAPABILITIES = 8
+        const val LAYOUT_SIZE = 12
+    }
+
+    /**
+     * Reads the layout version from the header.
+     * @return 0 if the buffer was not set by the native side or belongs to another protocol.
+     */
+    fun layoutVersion(buffer: ByteBuffer, magic: Int): Int =
+        if (buffer.getInt(Offset.MAGIC) == magic) buffer.getInt(Offset.VERSION) else 0
+
+    /**
+     * Reads the capability bits from the header, 0 on a magic mismatch.
+     */
+    fun capabilities(buffer: ByteBuffer, magic: Int): Int =
+        if (buffer.getInt(Offset.MAGIC) == magic) buffer.getInt(Offset.CAPABILITIES) else 0
+
+    /**
+     * Reads the number of bytes the native layout uses, 0 on a magic mismatch.
+     */
+    fun layoutSize(buffer: ByteBuffer, magic: Int): Int =
+        if (buffer.getInt(Offset.MAGIC) == magic) buffer.getInt(Offset.LAYOUT_SIZE) else 0
+}
@@ -0,0 +1,27 @@
+package moe.fuqiuluo.mamu.driver
+
+/**
+ * A decoded struct field returned by [WuwaDriver.decodeStruct]
+ *
+ * @property name Field name from the layout
+ * @property address Address of the field
+ * @property value Formatted value; "NULL" for null pointer fields
+ * @property raw Raw bytes of the field
+ * @property children Fields of the pointed-to struct for pointer fields, empty otherwise
+ */
+class StructField(
+    val name: String,
+    val address: Long,
+    val value: String,
+    val raw: ByteArray,
+    val children: Array<StructField>,
+) {
+    companion object {
+        /** Layout type id of a pointer field that is dereferenced into a sub-layout */
+        const val TYPE_POINTER = 100
+    }
+
+    override fun toString(): String {
+        return "StructField(%s @ 0x%016X = %s, children=%d)".format(name, address, value, children.size)
+    }
+}
@@ -0,0 +1,67 @@
+@file:Suppress("KotlinJniMissingFunction")
+
+package moe.fuqiuluo.mamu.driver
+
+/**
+ * 观察地址的值历史
+ *
+ * 按固定间隔采样一组地址，每个地址保留最近的若干采样，用于画出数值走势。
+ * 搜索进行中时采样自动暂停；连续读取失败 10 次的地址会被移除。
+ */
+object WatchManager {
+
+    init {
+        System.loadLibrary("mamu_core")
+    }
+
+    /**
+     * 观察一组地址并启动采样，替换之前观察的地址（类型没变的地址保留历史）
+     *
+     * @param addrs 要观察的地址
+     * @param valueTypes 对应的值类型 ID（不支持 Pattern）
+     * @param intervalMs 采样间隔（毫秒），不大于 0 时使用默认的 100ms
+     * @param historySize 每个地址保留的采样数，不大于 0 时使用默认的 300
+     * @return 是否启动成功
+     */
+    fun startWatch(addrs: LongArray, valueTypes: IntArray, intervalMs: Int = 0, historySize: Int = 0): Boolean {
+        return nativeStartWatch(addrs, valueTypes, intervalMs, historySize)
+    }
+
+    /**
+     * 停止采样并清空观察的地址
+     */
+    fun stopWatch() {
+        nativeStopWatch()
+    }
+
+    /**
+     * 获取地址的采样历史
+     *
+     * @param address 观察的地址
+     * @return JSON 数组 [{timestamp_ms, value}]（从旧到新，value 为格式化后的值），地址没有在观察或已被移除时返回 null
+     */
+    fun getWatchHistory(address: Long): String? {
+        return nativeGetWatchHistory(address)
+    }
+
+    /**
+     * 获取正在观察的地址
+     */
+    fun getWatchedAddresses(): LongArray {
+        return nativeGetWatchedAddresses()
+    }
+
+    /**
+     * 是否正在采样
+     */
+    fun isWatching(): Boolean {
+        return nativeIsWatching()
+    }
+
+    // Native methods
+    private external fun nativeStartWatch(addrs: LongArray, valueTypes: IntArray, intervalMs: Int, historySize: Int): Boolean
+    private external fun nativeStopWatch()
+    private external fun nativeGetWatchHistory(address: Long): String?
+    private external fun nativeGetWatchedAddresses(): LongArray
+    private external fun nativeIsWatching(): Boolean
+}
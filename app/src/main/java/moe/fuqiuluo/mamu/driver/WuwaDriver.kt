@@ -4,6 +4,9 @@ package moe.fuqiuluo.mamu.driver
 
 import moe.fuqiuluo.mamu.data.model.DriverInfo
 import moe.fuqiuluo.mamu.data.model.DriverInstallResult
+import moe.fuqiuluo.mamu.floating.data.model.DisplayValueType
+import moe.fuqiuluo.mamu.floating.data.model.MemoryRange
+import java.nio.ByteBuffer
 
 object WuwaDriver {
     init {
@@ -19,23 +22,113 @@ object WuwaDriver {
     val isProcessBound: Boolean
         get() = nativeIsProcessBound()
 
+    /** Binding handle states, see [bindStatus]. */
+    object BindStatus {
+        const val UNBOUND = 0
+        const val BOUND = 1
+        /** The handle went stale (target exec'd or driver reloaded), re-bind pending. */
+        const val STALE = 2
+        /** The target exec'd and was re-bound automatically under the same pid. */
+        const val TARGET_REBOUND = 3
+        /** Automatic re-bind failed repeatedly, bind again manually. */
+        const val LOST = 4
+        /** The bound process exited and was unbound, see [rebindByName]. */
+        const val PROCESS_DIED = 5
+    }
+
+    /** Process list orderings, ties are always broken by pid ascending. */
+    object ProcessSortMode {
+        const val PRIORITY = 0
+        const val RSS_DESC = 1
+        const val NAME = 2
+        /** Approximated by pid descending. */
+        const val RECENTLY_STARTED = 3
+    }
+
+    /** Memory dump result status, see [dumpMemoryToFile]. */
+    object DumpStatus {
+        const val COMPLETED = 0
+        /** Cancelled by [cancelDump]; the file only holds what was written before. */
+        const val CANCELLED = 1
+    }
+
+    /**
+     * 绑定句柄状态
+     * @return BindStatus 常量
+     */
+    val bindStatus: Int
+        get() = nativeGetBindStatus()
+
     fun setMemoryAccessMode(mode: Int) = nativeSetMemoryAccessMode(mode)
 
     fun isProcessAlive(pid: Int) = nativeIsProcessAlive(pid)
 
+    /**
+     * 绑定的进程是否仍然存活
+     * 进程退出后看门狗会取消搜索和指针扫描并解绑，[bindStatus] 变为 [BindStatus.PROCESS_DIED]
+     * @return 未绑定进程时返回 false
+     */
+    val isBoundProcessAlive: Boolean
+        get() = nativeIsBoundProcessAlive()
+
     fun listProcesses() = nativeGetProcessList()
 
     fun getProcessInfo(pid: Int) = nativeGetProcessInfo(pid)
 
-    fun listProcessesWithInfo(): Array<CProcInfo> {
-        return nativeGetProcessListWithInfo()
+    /**
+     * 列出进程（带详细信息）
+     * @param sortMode ProcessSortMode 常量
+     * @param hideKernelThreads 过滤内核线程
+     */
+    fun listProcessesWithInfo(
+        sortMode: Int = ProcessSortMode.PRIORITY,
+        hideKernelThreads: Boolean = false
+    ): Array<CProcInfo> {
+        return nativeGetProcessListWithInfo(sortMode, hideKernelThreads)
     }
 
     fun bindProcess(pid: Int) = nativeBindProcess(pid)
 
     fun unbindProcess() = nativeUnbindProcess()
 
-    fun queryMemRegions(pid: Int = currentBindPid) = nativeQueryMemRegions(pid)
+    /**
+     * 按进程名查找重启后的进程并绑定，保存的地址列表不受影响
+     * @param processName 进程名（包名）
+     * @return 新的 PID，找不到进程或绑定失败时返回 0
+     */
+    fun rebindByName(processName: String): Int = nativeRebindByName(processName)
+
+    /**
+     * 绑定一个次要进程（多进程游戏中的另一个进程），不改变当前绑定的进程
+     * 之后可以用 [readMemoryOf] 读取它，或把它作为搜索的目标进程
+     * @param pid 次要进程的 PID，不能是当前绑定的进程
+     */
+    fun bindSecondaryProcess(pid: Int): Boolean = nativeBindSecondaryProcess(pid)
+
+    /**
+     * 释放次要进程的句柄
+     * @return 没有绑定这个进程时返回 false
+     */
+    fun releaseSecondaryProcess(pid: Int): Boolean = nativeReleaseSecondaryProcess(pid)
+
+    /** 已绑定的次要进程 PID */
+    val secondaryPids: IntArray
+        get() = nativeGetSecondaryPids()
+
+    /**
+     * 查询进程的内存区域，过滤在 native 侧完成，不满足的区域不会构造成对象；区域名已还原内核的转义
+     * @param permMask 必须具有的 MemRegionEntry.MEM_* 权限位，0 表示不限
+     * @param nameFilter 区域名必须包含的子串，null 或空串表示不限
+     * @param startAddr 与 [startAddr, endAddr) 相交的区域才保留
+     * @param endAddr 0 表示没有上界
+     */
+    fun queryMemRegions(
+        pid: Int = currentBindPid,
+        permMask: Int = 0,
+        nameFilter: String? = null,
+        startAddr: Long = 0,
+        endAddr: Long = 0,
+    ): Array<MemRegionEntry> = nativeQueryMemRegions(pid, permMask, nameFilter, startAddr, endAddr)
 
     fun queryMemRegionsWithRetry(
         pid: Int = currentBindPid,
@@ -53,6 +146,36 @@ object WuwaDriver {
         throw RuntimeException("failed to queryMemRegions")
     }
 
+    /**
+     * 保存进程当前的内存区域列表，之后用 diffRegions 比较
+     * native 侧最多保留少量快照，超出时丢弃最早的，用完请调用 releaseSnapshot
+     * @return 快照 id
+     */
+    fun snapshotRegions(pid: Int = currentBindPid): Int = nativeSnapshotRegions(pid)
+
+    /**
+     * 重新查询快照所属进程的内存区域，返回快照之后新增、释放和大小变化的区域
+     * 起始地址和名称相同、结束地址变化的区域（例如堆扩展）算作大小变化
+     */
+    fun diffRegions(snapshotId: Int): Array<RegionDiffEntry> = nativeDiffRegions(snapshotId)
+
+    fun releaseSnapshot(snapshotId: Int): Boolean = nativeReleaseSnapshot(snapshotId)
+
+    /**
+     * 进程加载的文件列表，同一路径的映射合并为一项（最低起始地址到最高结束地址），按基址排序
+     */
+    fun listModules(pid: Int = currentBindPid): Array<ModuleInfo> = nativeListModules(pid)
+
+    /**
+     * 在 native 侧按内存范围过滤区域，分类规则与 divideToSimpleMemoryRange 一致
+     * @param ranges 要保留的内存范围
+     * @return 区域数组，格式 [start1, end1, start2, end2, ...]，可直接用于搜索
+     */
+    fun getFilteredRegions(ranges: Set<MemoryRange>, pid: Int = currentBindPid): LongArray {
+        val presetMask = ranges.fold(0) { mask, range -> mask or (1 shl range.ordinal) }
+        return nativeGetFilteredRegions(pid, presetMask)
+    }
+
     fun setDriverFd(fd: Int): Boolean = nativeSetDriverFd(fd)
 
     /**
@@ -63,6 +186,42 @@ object WuwaDriver {
      */
     fun readMemory(addr: Long, size: Int): ByteArray? = nativeReadMemory(addr, size)
 
+    /**
+     * 读取指定进程的内存
+     * 当前绑定的进程等同于 [readMemory]；次要进程走 [bindSecondaryProcess] 绑定的句柄，未绑定时直接用 PID 通过驱动读取
+     * @param pid 目标进程 PID
+     * @param addr 要读取的虚拟地址
+     * @param size 读取大小
+     * @return 读取的字节数组，失败返回null
+     */
+    fun readMemoryOf(pid: Int, addr: Long, size: Int): ByteArray? = nativeReadMemoryOf(pid, addr, size)
+
+    /**
+     * 读取一段内存窗口（用于十六进制查看器）
+     * 部分页不可读时不失败，无效页的字节为 0，并在 [MemoryWindow.pageValid] 中标出
+     * @param addr 窗口起始地址，可以不按页对齐
+     * @param size 窗口大小
+     * @return 窗口内容，失败（未绑定进程等）返回null
+     */
+    fun readMemoryWindow(addr: Long, size: Int): MemoryWindow? = nativeReadMemoryWindow(addr, size)
+
+    /**
+     * 把一段内存读入调用方复用的 direct ByteBuffer（用于高频刷新的内存查看器，不分配新数组）
+     * 部分页不可读时不失败，无效页的字节为 0，各页有效性用 [getReadValidity] 取得
+     * @param addr 起始地址，可以不按页对齐
+     * @param buffer direct ByteBuffer，容量不小于 size，否则抛出异常
+     * @param size 读取大小
+     * @return 可读页内的字节数
+     */
+    fun readMemoryInto(addr: Long, buffer: ByteBuffer, size: Int): Int = nativeReadMemoryInto(addr, buffer, size)
+
+    /**
+     * 取得本线程最近一次 [readMemoryInto] 各页是否有效，每页一个字节（1 为有效），第 0 项是 addr 所在的页
+     * @param out direct ByteBuffer，容量不足时抛出异常
+     * @return 页数
+     */
+    fun getReadValidity(out: ByteBuffer): Int = nativeGetReadValidity(out)
+
     /**
      * 批量读取内存
      * @param addrs 要读取的地址数组
@@ -72,11 +231,37 @@ object WuwaDriver {
     fun batchReadMemory(addrs: LongArray, sizes: IntArray): Array<ByteArray?> =
         nativeBatchReadMemory(addrs, sizes)
 
+    /**
+     * 读取 UE FString，非法 UTF-16 替换为 U+FFFD
+     * @param addr FString 结构地址
+     * @param maxLen 最大字符数（含结尾 NUL），超过时抛出异常
+     */
+    fun readFString(addr: Long, maxLen: Int = 4096): String = nativeReadFString(addr, maxLen)
+
+    /**
+     * 读取 NUL 结尾的 ANSI/UTF-8 字符串，跨入不可读页时返回可读部分
+     * @param addr 字符串地址
+     * @param maxLen 最多读取的字节数
+     */
+    fun readCString(addr: Long, maxLen: Int = 1024): String = nativeReadCString(addr, maxLen)
+
+    /**
+     * 按布局解析一个结构体
+     * 布局为 JSON 数组，每个字段为 {"name", "offset", "type", "size"?, "fields"?}：
+     * type 为 ValueType 的 id 或 [StructField.TYPE_POINTER]，size 仅变长类型需要，
+     * fields 为指针指向的子布局，最多解引用 3 层；空指针或子结构不可读时该字段没有子字段
+     * @param addr 结构体地址
+     * @param layoutJson 字段布局
+     * @return 各字段的值，顺序与布局相同
+     */
+    fun decodeStruct(addr: Long, layoutJson: String): Array<StructField> = nativeDecodeStruct(addr, layoutJson)
+
     /**
      * 统一的内存写入方法，使用当前配置的 access_mode
      * @param addr 要写入的虚拟地址
      * @param data 要写入的数据
      * @return 写入是否成功
+     * @throws PageNotWritableException 目标页只读或可执行
      */
     fun writeMemory(addr: Long, data: ByteArray): Boolean = nativeWriteMemory(addr, data)
 
@@ -89,6 +274,122 @@ object WuwaDriver {
     fun batchWriteMemory(addrs: LongArray, dataArray: Array<ByteArray>): BooleanArray =
         nativeBatchWriteMemory(addrs, dataArray)
 
+    /**
+     * 按类型解析字符串后写入，解析规则与搜索输入一致（十进制、0x 前缀或 h 后缀的十六进制、浮点、负数）
+     * 超出类型范围的值（如 Word 写入 70000）会抛出异常，而不是截断
+     * @param addr 要写入的虚拟地址
+     * @param value 用户输入的值
+     * @param type 目标类型
+     * @return 写入是否成功
+     * @throws PageNotWritableException 目标页只读或可执行
+     */
+    fun writeTypedValue(addr: Long, value: String, type: DisplayValueType): Boolean =
+        nativeWriteTypedValue(addr, value, type.nativeId)
+
+    /**
+     * 批量按类型写入，解析失败或写入失败的项返回 false
+     * @return 每一项写入是否成功的结果数组
+     */
+    fun writeTypedValues(addrs: LongArray, values: Array<String>, types: Array<DisplayValueType>): BooleanArray =
+        nativeWriteTypedValues(addrs, values, types.map { it.nativeId }.toIntArray())
+
+    /**
+     * 原子组写入：多个地址在一个时间窗口内连续写入，任一写入失败或窗口超限时用原值回滚
+     * @param addrs 要写入的地址数组，目标范围不能重叠
+     * @param dataArray 每个地址对应的数据
+     * @param maxWindowUs 第一个到最后一个写入完成允许的最大微秒数，0 表示不限制
+     * @return JSON {committed, window_us, failed_index, rolled_back, error}
+     */
+    fun writeAtomicGroup(addrs: LongArray, dataArray: Array<ByteArray>, maxWindowUs: Long): String =
+        nativeWriteAtomicGroup(addrs, dataArray, maxWindowUs)
+
+    /**
+     * 获取内存访问统计（交互式/批量读取的等待与完成计数）
+     * @return JSON 字符串
+     */
+    fun getMemoryStats(): String = nativeGetMemoryStats()
+
+    /**
+     * 开关内存访问 QoS，开启后扫描等批量读取会为前台读取让路
+     * @param enabled 是否开启
+     */
+    fun setMemoryQosEnabled(enabled: Boolean) = nativeSetMemoryQosEnabled(enabled)
+
+    /**
+     * 获取读取路径统计（BindProc / 驱动回退的读取与失败次数，是否已自动切换）
+     * @return JSON 字符串
+     */
+    fun getReadPathStats(): String = nativeGetReadPathStats()
+
+    /**
+     * BindProc 连续读取失败达到阈值后自动改走驱动读取，重新绑定或切换访问模式后恢复
+     * @param threshold 连续失败次数，0 关闭自动切换
+     */
+    fun setReadFallbackAutoSwitch(threshold: Int) = nativeSetReadFallbackAutoSwitch(threshold)
+
+    /**
+     * 把 [start, end) 的内存写入文件，不可读的页写 0，在调用线程上同步执行
+     * @return JSON {bytes_written, pages_failed, status}，status 见 [DumpStatus]
+     */
+    fun dumpMemoryToFile(start: Long, end: Long, path: String): String =
+        nativeDumpMemoryToFile(start, end, path)
+
+    /**
+     * 转储绑定进程中的模块（第一个映射到最后一个映射）
+     * @param moduleName 模块文件名或完整路径，如 libil2cpp.so
+     * @return JSON {bytes_written, pages_failed, status}，status 见 [DumpStatus]
+     */
+    fun dumpModule(moduleName: String, path: String): String = nativeDumpModule(moduleName, path)
+
+    /**
+     * 把按模块旧基址记录的地址平移到模块当前的基址（游戏重启后 ASLR 改变了加载地址）
+     * @param addrs 保存的绝对地址
+     * @param moduleName 模块名或完整路径
+     * @param oldBase 记录地址时模块的基址
+     * @param outside 与 addrs 等长，不在旧模块范围内（原样返回）的位置写入 true
+     * @return 平移后的地址
+     */
+    fun rebaseAddresses(
+        addrs: LongArray,
+        moduleName: String,
+        oldBase: Long,
+        outside: BooleanArray = BooleanArray(addrs.size),
+    ): LongArray = nativeRebaseAddresses(addrs, moduleName, oldBase, outside)
+
+    /**
+     * 查询每个地址所在区域是否可写，区域列表按绑定进程缓存
+     * @param addrs 要查询的地址
+     * @return 与 addrs 等长的访问属性
+     */
+    fun classifyAddresses(addrs: LongArray): List<PageAccess> =
+        nativeClassifyAddresses(addrs).map { PageAccess.fromNativeId(it) }
+
+    /**
+     * 把每个地址格式化为 "区域名+0x偏移"（如 libunity.so+0x12345），偏移相对于所在区域的起始
+     * @param addrs 要解析的地址
+     * @return 与 addrs 等长，不在任何区域内的地址为 null
+     */
+    fun resolveAddresses(addrs: LongArray): Array<String?> = nativeResolveAddresses(addrs)
+
+    /** 丢弃缓存的区域列表，目标进程映射变化（如 mprotect 之后）时调用 */
+    fun invalidateRegionCache() = nativeInvalidateRegionCache()
+
+    /** 取消正在进行的转储，当前块写完后停止 */
+    fun cancelDump() = nativeCancelDump()
+
+    /**
+     * 当前转储的进度
+     * @return JSON {running, bytes_written, total, pages_failed}
+     */
+    fun getDumpProgress(): String = nativeGetDumpProgress()
+
+    /**
+     * 运行本进程自检（精确/细化/模糊/特征码/指针扫描/写入校验）
+     * @param cacheDir 临时缓存目录，自检结束后会清理
+     * @return JSON 格式的自检报告
+     */
+    fun runSelfTest(cacheDir: String): String = nativeRunSelfTest(cacheDir)
+
     /**
      * 获取可用的驱动列表
      * @return 可用驱动信息数组
@@ -120,22 +421,61 @@ object WuwaDriver {
     private external fun nativeSetDriverFd(fd: Int): Boolean
     private external fun nativeSetMemoryAccessMode(mode: Int)
     private external fun nativeIsProcessAlive(pid: Int): Boolean
+    private external fun nativeIsBoundProcessAlive(): Boolean
+    private external fun nativeRebindByName(processName: String): Int
     private external fun nativeGetProcessList(): IntArray
     private external fun nativeGetProcessInfo(pid: Int): CProcInfo
-    private external fun nativeGetProcessListWithInfo(): Array<CProcInfo>
+    private external fun nativeGetProcessListWithInfo(sortMode: Int, hideKernelThreads: Boolean): Array<CProcInfo>
     private external fun nativeBindProcess(pid: Int): Boolean
     private external fun nativeIsProcessBound(): Boolean
     private external fun nativeUnbindProcess(): Boolean
+    private external fun nativeBindSecondaryProcess(pid: Int): Boolean
+    private external fun nativeReleaseSecondaryProcess(pid: Int): Boolean
+    private external fun nativeGetSecondaryPids(): IntArray
     private external fun nativeGetCurrentBindPid(): Int
-    private external fun nativeQueryMemRegions(pid: Int): Array<MemRegionEntry>
+    private external fun nativeGetBindStatus(): Int
+    private external fun nativeQueryMemRegions(pid: Int, permMask: Int, nameFilter: String?, startAddr: Long, endAddr: Long): Array<MemRegionEntry>
+    private external fun nativeGetFilteredRegions(pid: Int, presetMask: Int): LongArray
+    private external fun nativeSnapshotRegions(pid: Int): Int
+    private external fun nativeDiffRegions(snapshotId: Int): Array<RegionDiffEntry>
+    private external fun nativeReleaseSnapshot(snapshotId: Int): Boolean
+    private external fun nativeListModules(pid: Int): Array<ModuleInfo>
     private external fun nativeReadMemory(addr: Long, size: Int): ByteArray?
+    private external fun nativeReadMemoryOf(pid: Int, addr: Long, size: Int): ByteArray?
+    private external fun nativeReadMemoryWindow(addr: Long, size: Int): MemoryWindow?
+    private external fun nativeReadMemoryInto(addr: Long, buffer: ByteBuffer, size: Int): Int
+    private external fun nativeGetReadValidity(out: ByteBuffer): Int
     private external fun nativeBatchReadMemory(addrs: LongArray, sizes: IntArray): Array<ByteArray?>
+    private external fun nativeReadFString(addr: Long, maxLen: Int): String
+    private external fun nativeReadCString(addr: Long, maxLen: Int): String
+    private external fun nativeDecodeStruct(addr: Long, layoutJson: String): Array<StructField>
     private external fun nativeWriteMemory(addr: Long, data: ByteArray): Boolean
+    private external fun nativeWriteTypedValue(addr: Long, value: String, typeId: Int): Boolean
+    private external fun nativeWriteTypedValues(addrs: LongArray, values: Array<String>, typeIds: IntArray): BooleanArray
     private external fun nativeBatchWriteMemory(
         addrs: LongArray,
         dataArray: Array<ByteArray>
     ): BooleanArray
 
+    private external fun nativeWriteAtomicGroup(
+        addrs: LongArray,
+        dataArray: Array<ByteArray>,
+        maxWindowUs: Long
+    ): String
+
+    private external fun nativeGetMemoryStats(): String
+    private external fun nativeSetMemoryQosEnabled(enabled: Boolean)
+    private external fun nativeGetReadPathStats(): String
+    private external fun nativeSetReadFallbackAutoSwitch(threshold: Int)
+    private external fun nativeDumpMemoryToFile(start: Long, end: Long, path: String): String
+    private external fun nativeDumpModule(moduleName: String, path: String): String
+    private external fun nativeRebaseAddresses(addrs: LongArray, moduleName: String, oldBase: Long, outside: BooleanArray): LongArray
+    private external fun nativeClassifyAddresses(addrs: LongArray): IntArray
+    private external fun nativeResolveAddresses(addrs: LongArray): Array<String?>
+    private external fun nativeInvalidateRegionCache()
+    private external fun nativeCancelDump()
+    private external fun nativeGetDumpProgress(): String
+    private external fun nativeRunSelfTest(cacheDir: String): String
     private external fun nativeGetAvailableDrivers(): Array<DriverInfo>
     private external fun nativeDownloadAndInstallDriver(driverName: String): DriverInstallResult
     private external fun nativeIsDriverInstalled(): Boolean
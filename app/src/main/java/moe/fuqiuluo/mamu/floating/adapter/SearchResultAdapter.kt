@@ -400,6 +400,18 @@ class SearchResultAdapter(
         private val binding: ItemSearchResultBinding
     ) : RecyclerView.ViewHolder(binding.root) {
 
+        private fun bindPointerTarget(isPointer: Boolean, pointerModule: String?, value: String) {
+            binding.pointerChainText.apply {
+                if (isPointer) {
+                    val target = pointerModule ?: value.toLongOrNull()?.let { "0x${it.toString(16).uppercase()}" } ?: value
+                    text = "→ $target"
+                    visibility = View.VISIBLE
+                } else {
+                    visibility = View.GONE
+                }
+            }
+        }
+
         fun bind(item: SearchResultItem, position: Int) {
             when (item) {
                 is ExactSearchResultItem -> {
@@ -421,8 +433,8 @@ class SearchResultAdapter(
                             backupValueText.visibility = View.GONE
                         }
 
-                        // 指针链信息（Exact搜索不显示）
-                        pointerChainText.visibility = View.GONE
+                        // Qword 指针值显示目标模块+偏移
+                        bindPointerTarget(item.isPointer, item.pointerModule, item.value)
 
                         // 类型简称和颜色
                         typeText.apply {
@@ -458,8 +470,8 @@ class SearchResultAdapter(
                             backupValueText.visibility = View.GONE
                         }
 
-                        // 指针链信息（Fuzzy搜索不显示）
-                        pointerChainText.visibility = View.GONE
+                        // Qword 指针值显示目标模块+偏移
+                        bindPointerTarget(item.isPointer, item.pointerModule, item.value)
 
                         // 类型简称和颜色
                         typeText.apply {
@@ -25,6 +25,7 @@ import moe.fuqiuluo.mamu.data.settings.memoryRegionCacheInterval
 import moe.fuqiuluo.mamu.databinding.FloatingMemoryPreviewLayoutBinding
 import moe.fuqiuluo.mamu.driver.LocalMemoryOps
 import moe.fuqiuluo.mamu.driver.FreezeManager
+import moe.fuqiuluo.mamu.driver.PointerTarget
 import moe.fuqiuluo.mamu.driver.SearchEngine
 import moe.fuqiuluo.mamu.driver.WuwaDriver
 import moe.fuqiuluo.mamu.floating.adapter.InfiniteMemoryAdapter
@@ -205,7 +206,32 @@ class MemoryPreviewController(
     private fun subscribeToNavigateEvents() {
         coroutineScope.launch {
             FloatingEventBus.navigateToMemoryAddressEvents.collect { event ->
-                jumpToAddress(event.address)
+                val resultHandle = event.followResultHandle
+                if (resultHandle != null) {
+                    followResultPointer(resultHandle)
+                } else {
+                    jumpToAddress(event.address)
+                }
+            }
+        }
+    }
+
+    /**
+     * 解引用搜索结果中的指针并跳转到目标地址
+     */
+    private fun followResultPointer(resultHandle: Long) {
+        coroutineScope.launch(Dispatchers.IO) {
+            val pointer = SearchEngine.getPointerTarget(resultHandle)
+            withContext(Dispatchers.Main) {
+                when (pointer.status) {
+                    PointerTarget.STATUS_VALID -> jumpToAddress(pointer.target)
+                    PointerTarget.STATUS_NOT_QWORD -> notification.showError("该结果不是 Qword 指针")
+                    PointerTarget.STATUS_READ_FAILED -> notification.showError("读取指针失败")
+                    PointerTarget.STATUS_UNMAPPED -> notification.showError(
+                        "指针目标未映射: 0x${pointer.target.toString(16).uppercase()}"
+                    )
+                    else -> notification.showError("无效的搜索结果")
+                }
             }
         }
     }
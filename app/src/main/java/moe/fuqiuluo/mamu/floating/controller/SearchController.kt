@@ -318,7 +318,7 @@ class SearchController(
                 // 异步调用 native 删除
                 coroutineScope.launch {
                     val success = withContext(Dispatchers.IO) {
-                        SearchEngine.removeResult(item.nativePosition.toInt())
+                        SearchEngine.removeByHandles(longArrayOf(item.nativePosition)) > 0
                     }
 
                     if (success) {
@@ -865,15 +865,14 @@ class SearchController(
         }
 
         coroutineScope.launch {
-            val nativePositions = searchResultAdapter.getNativePositions()
-            val indices = nativePositions.map { it.toInt() }.toIntArray()
+            val handles = searchResultAdapter.getNativePositions()
 
-            val success = withContext(Dispatchers.IO) {
-                SearchEngine.removeResults(indices)
+            val removed = withContext(Dispatchers.IO) {
+                runCatching { SearchEngine.removeByHandles(handles) }.getOrDefault(-1)
             }
 
-            if (success) {
-                notification.showSuccess("已移除 ${selectedItems.size} 个结果")
+            if (removed >= 0) {
+                notification.showSuccess("已移除 $removed 个结果")
 
                 // 重新加载搜索结果
                 val totalCount = SearchEngine.getTotalResultCount().toInt()
@@ -905,17 +904,16 @@ class SearchController(
         }
 
         coroutineScope.launch {
-            // 获取选中项的 native positions
-            val selectedPositions = searchResultAdapter.getNativePositions()
+            // 选中项的句柄，期间结果集发生变化也不会保留错误的行
+            val handles = searchResultAdapter.getNativePositions()
 
-            // 直接调用 keepOnlyResults，保留选中的项，删除其他所有项
-            val indices = selectedPositions.map { it.toInt() }.toIntArray()
-            val success = withContext(Dispatchers.IO) {
-                SearchEngine.keepOnlyResults(indices)
+            // 保留选中的项，删除其他所有项
+            val kept = withContext(Dispatchers.IO) {
+                runCatching { SearchEngine.keepOnlyHandles(handles) }.getOrDefault(-1)
             }
 
-            if (success) {
-                notification.showSuccess("已将 ${selectedItems.size} 个选中项设为搜索结果")
+            if (kept >= 0) {
+                notification.showSuccess("已将 $kept 个选中项设为搜索结果")
 
                 // 重新加载搜索结果
                 val newTotalCount = SearchEngine.getTotalResultCount().toInt()
@@ -1055,6 +1053,21 @@ class SearchController(
                         UIActionEvent.JumpToMemoryPreview(address)
                     )
                 }
+
+                override fun onJumpToPointer(fromAddress: Long, toAddress: Long) {
+                    // 指针结果由 native 重新读取并校验目标后再跳转
+                    val isPointer = when (result) {
+                        is ExactSearchResultItem -> result.isPointer
+                        is FuzzySearchResultItem -> result.isPointer
+                        else -> false
+                    }
+                    FloatingEventBus.tryEmitUIAction(
+                        UIActionEvent.JumpToMemoryPreview(
+                            address = toAddress,
+                            followResultHandle = if (isPointer) result.nativePosition else null
+                        )
+                    )
+                }
             }
         )
 
@@ -93,22 +93,22 @@ enum class DisplayValueType(
     UTF_8(
         code = "UTF-8",
         displayName = "文本 UTF-8",
-        rangeDescription = "输入UTF-8编码的文本",
+        rangeDescription = "输入UTF-8编码的文本，用双引号包裹，后缀 i 或前缀 ~ 忽略大小写，前缀 u 按UTF-16搜索",
         iconRes = R.drawable.type_text_24px,
         textColor = Color.WHITE,
-        nativeId = 100,
-        memorySize = Long.MIN_VALUE,
-        isDisabled = true
+        nativeId = 9,
+        memorySize = 0,  // 可变长度
+        isDisabled = false
     ),
     UTF_16LE(
         code = "UTF-16LE",
         displayName = "文本 UTF-16LE",
-        rangeDescription = "输入UTF-16LE编码的文本",
+        rangeDescription = "输入UTF-16LE编码的文本，用双引号包裹，后缀 i 或前缀 ~ 忽略大小写",
         iconRes = R.drawable.type_text_24px,
         textColor = Color.WHITE,
-        nativeId = 101,
-        memorySize = Long.MIN_VALUE,
-        isDisabled = true
+        nativeId = 10,
+        memorySize = 0,  // 可变长度
+        isDisabled = false
     ),
     HEX(
         code = "HEX",
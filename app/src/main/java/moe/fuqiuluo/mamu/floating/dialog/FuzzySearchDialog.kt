@@ -547,7 +547,9 @@ class FuzzySearchDialog(
                     currentProgress = SearchEngine.getProgress(),
                     regionsOrAddrsSearched = SearchEngine.getRegionsDone(),
                     totalFound = SearchEngine.getFoundCount(),
-                    heartbeat = SearchEngine.getHeartbeat()
+                    heartbeat = SearchEngine.getHeartbeat(),
+                    phase = SearchEngine.getPhase(),
+                    phaseProgress = SearchEngine.getPhaseProgress()
                 )
 
                 progressDialog?.updateProgress(data)
@@ -564,6 +566,18 @@ class FuzzySearchDialog(
                         break
                     }
 
+                    SearchEngine.Status.PARTIALLY_COMPLETED -> {
+                        onSearchPartiallyCompleted(data.totalFound)
+                        break
+                    }
+
+                    SearchEngine.Status.SNAPSHOT_ONLY -> {
+                        val elapsed = System.currentTimeMillis() - searchStartTime
+                        onSearchFinished(isRefineSearch, data.totalFound, elapsed)
+                        notification.showWarning("没有可比较的旧值，本次只记录了当前值，请再细化一次")
+                        break
+                    }
+
                     SearchEngine.Status.ERROR -> {
                         onSearchError(SearchEngine.getErrorCode())
                         break
@@ -626,6 +640,24 @@ class FuzzySearchDialog(
         notification.showWarning(context.getString(R.string.search_cancelled))
     }
 
+    /**
+     * 初始扫描被取消，已扫描区域的结果保留，可以直接细化
+     */
+    private fun onSearchPartiallyCompleted(totalFound: Long) {
+        isSearching = false
+        progressDialog?.dismiss()
+        progressDialog = null
+        notification.showWarning("搜索已取消，保留已扫描区域的 $totalFound 个结果")
+
+        isInitialMode = false
+        updateModeUI()
+        updateCurrentResults()
+        if (totalFound > 0) {
+            shouldShowAfterFullscreen = true
+        }
+        onSearchCompleted?.invoke(searchRanges, totalFound)
+    }
+
     /**
      * 搜索错误
      */
@@ -257,7 +257,7 @@ class PointerScanDialog(
         progressDialog?.dismiss()
         progressDialog = null
 
-        val chainCount = PointerScanner.getChainCount()
+        val chainCount = PointerScanner.getChainsFound()
         val outputFile = PointerScanner.getOutputFilePath()
 
         // 使用通知显示结果
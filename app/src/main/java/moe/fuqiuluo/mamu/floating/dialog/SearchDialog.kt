@@ -23,6 +23,7 @@ import moe.fuqiuluo.mamu.driver.WuwaDriver
 import moe.fuqiuluo.mamu.data.settings.getDialogOpacity
 import moe.fuqiuluo.mamu.data.settings.keyboardType
 import moe.fuqiuluo.mamu.data.settings.selectedMemoryRanges
+import moe.fuqiuluo.mamu.data.settings.searchMaxResults
 import moe.fuqiuluo.mamu.floating.data.local.SearchHistoryRepository
 import moe.fuqiuluo.mamu.floating.event.FloatingEventBus
 import moe.fuqiuluo.mamu.floating.event.UIActionEvent
@@ -70,7 +71,9 @@ class SearchDialog(
             currentProgress = SearchEngine.getProgress(),
             regionsOrAddrsSearched = SearchEngine.getRegionsDone(),
             totalFound = SearchEngine.getFoundCount(),
-            heartbeat = SearchEngine.getHeartbeat()
+            heartbeat = SearchEngine.getHeartbeat(),
+            phase = SearchEngine.getPhase(),
+            phaseProgress = SearchEngine.getPhaseProgress()
         )
     }
 
@@ -93,6 +96,12 @@ class SearchDialog(
                         onSearchFinished(isRefineSearch, data.totalFound, elapsed)
                         break
                     }
+                    SearchEngine.Status.COMPLETED_TRUNCATED -> {
+                        val elapsed = System.currentTimeMillis() - searchStartTime
+                        onSearchFinished(isRefineSearch, data.totalFound, elapsed)
+                        notification.showWarning(context.getString(R.string.search_results_truncated, data.totalFound))
+                        break
+                    }
                     SearchEngine.Status.CANCELLED -> {
                         onSearchCancelled()
                         break
@@ -149,6 +158,8 @@ class SearchDialog(
             SearchEngine.ErrorCode.INVALID_QUERY -> "无效的搜索表达式"
             SearchEngine.ErrorCode.MEMORY_READ_FAILED -> "内存读取失败"
             SearchEngine.ErrorCode.ALREADY_SEARCHING -> "搜索正在进行中"
+            SearchEngine.ErrorCode.STALLED -> "搜索长时间无响应，已中止"
+            SearchEngine.ErrorCode.PROCESS_DIED -> "目标进程已退出，搜索已取消"
             else -> "搜索出错 (code: $errorCode)"
         }
         notification.showError(errorMessage)
@@ -460,7 +471,8 @@ class SearchDialog(
                             expression,
                             valueType,
                             nativeRegions.toLongArray(),
-                            useDeepSearch = binding.cbIsDeeplySearch.isChecked
+                            useDeepSearch = binding.cbIsDeeplySearch.isChecked,
+                            maxResults = mmkv.searchMaxResults
                         )
                     }
                     if (started) {
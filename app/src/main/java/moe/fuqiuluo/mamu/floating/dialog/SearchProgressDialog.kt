@@ -7,19 +7,22 @@ import com.tencent.mmkv.MMKV
 import moe.fuqiuluo.mamu.R
 import moe.fuqiuluo.mamu.data.settings.dialogTransparencyEnabled
 import moe.fuqiuluo.mamu.databinding.DialogSearchProgressBinding
+import moe.fuqiuluo.mamu.driver.SearchEngine
 import moe.fuqiuluo.mamu.data.settings.getDialogOpacity
 import kotlin.math.max
 import kotlin.random.Random
 
 /**
  * 搜索进度数据
- * 对应native层的共享内存结构（20字节）
+ * 对应native层的共享内存结构
  */
 data class SearchProgressData(
     val currentProgress: Int,      // 0-100
     val regionsOrAddrsSearched: Int,       // 已搜索的区域数/地址数
     val totalFound: Long,           // 当前找到的结果数
-    val heartbeat: Int              // 心跳随机数（用于检测是否卡死）
+    val heartbeat: Int,             // 心跳随机数（用于检测是否卡死）
+    val phase: Int = SearchEngine.Phase.COLLECTING, // 扫描之后的排序/读取/写入阶段
+    val phaseProgress: Int = 0      // 当前阶段内的进度 0-100
 )
 
 /**
@@ -77,11 +80,25 @@ class SearchProgressDialog(
     fun updateProgress(data: SearchProgressData) {
         if (!::binding.isInitialized) return
 
-        binding.progressBar.progress = data.currentProgress
-        binding.tvProgress.text = "${data.currentProgress}%"
         binding.tvRegions.text = "${data.regionsOrAddrsSearched}"
         binding.tvResults.text = String.format("%,d", data.totalFound)
-        binding.progressTitle.text = MOE_TITLES.random(Random(data.heartbeat))
+
+        // 区域扫描完成后还有排序、读取当前值和写入结果，显示阶段和阶段内进度
+        val phaseTitle = when (data.phase) {
+            SearchEngine.Phase.SORTING -> R.string.search_phase_sorting
+            SearchEngine.Phase.CAPTURING -> R.string.search_phase_capturing
+            SearchEngine.Phase.STORING -> R.string.search_phase_storing
+            else -> null
+        }
+        if (phaseTitle != null) {
+            binding.progressBar.progress = data.phaseProgress
+            binding.tvProgress.text = "${data.phaseProgress}%"
+            binding.progressTitle.setText(phaseTitle)
+        } else {
+            binding.progressBar.progress = data.currentProgress
+            binding.tvProgress.text = "${data.currentProgress}%"
+            binding.progressTitle.text = MOE_TITLES.random(Random(data.heartbeat))
+        }
     }
 }
 
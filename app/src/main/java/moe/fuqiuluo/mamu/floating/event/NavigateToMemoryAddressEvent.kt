@@ -3,7 +3,10 @@ package moe.fuqiuluo.mamu.floating.event
 /**
  * 导航到内存地址事件
  * 用于从搜索界面跳转到内存预览界面指定地址
+ *
+ * followResultHandle 不为空时忽略 address，先解引用该搜索结果（Qword 指针）再跳转到目标地址
  */
 data class NavigateToMemoryAddressEvent(
-    val address: Long
+    val address: Long,
+    val followResultHandle: Long? = null
 )
@@ -51,8 +51,8 @@ sealed class UIActionEvent {
     /** 请求切换到断点 Tab */
     data object SwitchToBreakpointsTab : UIActionEvent()
 
-    /** 请求跳转到内存预览并定位到指定地址 */
-    data class JumpToMemoryPreview(val address: Long) : UIActionEvent()
+    /** 请求跳转到内存预览并定位到指定地址，followResultHandle 指定时跳转到该搜索结果指向的地址 */
+    data class JumpToMemoryPreview(val address: Long, val followResultHandle: Long? = null) : UIActionEvent()
 
     /** 更新搜索Tab的Badge数量 */
     data class UpdateSearchBadge(val count: Int, val total: Int?) : UIActionEvent()
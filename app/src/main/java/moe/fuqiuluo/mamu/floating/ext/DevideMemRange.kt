@@ -102,7 +102,7 @@ private fun classifyRegion(entry: MemRegionEntry, procName: String): DisplayMemR
             }
 
             if (entry.name.contains("/data/")) {
-                MemoryRange.Xa
+                return@run MemoryRange.Xa
             }
 
             return@run MemoryRange.Xs
@@ -207,7 +207,7 @@ private fun classifyRegion(entry: MemRegionEntry, procName: String): DisplayMemR
                     if (name.contains("gralloc")) return false
                     if (name.startsWith("[vdso]")) return false
                     if (name.startsWith("[vectors]")) return false
-                    if (name.startsWith("/dev/") && name.startsWith("/dev/ashmem")) return false
+                    if (name.startsWith("/dev/") && !name.startsWith("/dev/ashmem")) return false
                     return true
                 }(entry.name)
             ) {
@@ -294,7 +294,10 @@ class FloatingWindowService : Service(), ProcessDeathMonitor.Callback {
                         switchToTab(TAB_MEMORY_PREVIEW)
 
                         FloatingEventBus.tryEmitNavigateToMemoryAddress(
-                            NavigateToMemoryAddressEvent(address = event.address)
+                            NavigateToMemoryAddressEvent(
+                                address = event.address,
+                                followResultHandle = event.followResultHandle
+                            )
                         )
                     }
 
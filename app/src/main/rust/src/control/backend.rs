@@ -0,0 +1,224 @@
+//! Control command dispatch
+//!
+//! 命令先在这里完成解析和校验，再交给 `ControlBackend` 执行。
+//! `EngineBackend` 对接全局的搜索、指针扫描和驱动管理器，状态检查与 JNI 入口一致。
+
+use super::protocol::{ControlCommand, ControlError, ControlErrorCode, ControlResult, ControlStatus, MAX_RESULTS_PER_REQUEST, PointerScanRequest, ResultEntry};
+use crate::core::DRIVER_MANAGER;
+use crate::pointer_scan::manager::POINTER_SCAN_MANAGER;
+use crate::pointer_scan::scanner::ScanRegion;
+use crate::pointer_scan::types::{ChainOutputFormat, VmStaticData, assign_module_indices};
+use crate::search::engine::shared_buffer::SearchStatus;
+use crate::search::{SEARCH_ENGINE_MANAGER, SearchEngineManager, SearchQuery, SearchResultItem, ValueType, parse_search_query, parse_typed_value};
+use serde::Serialize;
+
+/// 控制命令的执行端
+pub trait ControlBackend: Send + Sync {
+    fn start_search(&self, query: SearchQuery, regions: Vec<(u64, u64)>, deep: bool) -> ControlResult<()>;
+    fn start_refine(&self, query: SearchQuery) -> ControlResult<()>;
+    fn status(&self) -> ControlResult<ControlStatus>;
+    fn results(&self, start: usize, count: usize) -> ControlResult<Vec<ResultEntry>>;
+    fn write_bytes(&self, address: u64, bytes: &[u8]) -> ControlResult<()>;
+    fn start_pointer_scan(&self, request: PointerScanRequest) -> ControlResult<()>;
+}
+
+fn invalid(message: impl Into<String>) -> ControlError {
+    ControlError::new(ControlErrorCode::InvalidRequest, message)
+}
+
+fn to_json<T: Serialize>(value: T) -> ControlResult<serde_json::Value> {
+    serde_json::to_value(value).map_err(|e| ControlError::new(ControlErrorCode::Failed, e.to_string()))
+}
+
+fn value_type_of(id: i32) -> ControlResult<ValueType> {
+    ValueType::from_id(id).ok_or_else(|| invalid(format!("Invalid value type: {}", id)))
+}
+
+fn parse_query(query: &str, value_type: i32) -> ControlResult<SearchQuery> {
+    parse_search_query(query, value_type_of(value_type)?).map_err(|e| invalid(format!("Parse error: {}", e)))
+}
+
+/// 把写入值解析成目标类型的字节，只接受单个精确值
+pub fn encode_write_value(value: &str, value_type: ValueType) -> ControlResult<Vec<u8>> {
+    parse_typed_value(value, value_type).map_err(|e| invalid(format!("Parse error: {}", e)))
+}
+
+/// 执行一条命令，返回响应的 data 字段
+pub fn dispatch(backend: &dyn ControlBackend, command: ControlCommand) -> ControlResult<serde_json::Value> {
+    match command {
+        ControlCommand::StartSearch {
+            query,
+            value_type,
+            regions,
+            deep,
+            max_results,
+        } => {
+            if regions.is_empty() || regions.iter().any(|&(start, end)| start >= end) {
+                return Err(invalid("Regions must be non-empty [start, end) pairs"));
+            }
+            backend.start_search(parse_query(&query, value_type)?.with_max_results(max_results), regions, deep)?;
+            Ok(serde_json::Value::Null)
+        },
+        ControlCommand::StartRefine { query, value_type } => {
+            backend.start_refine(parse_query(&query, value_type)?)?;
+            Ok(serde_json::Value::Null)
+        },
+        ControlCommand::Status => to_json(backend.status()?),
+        ControlCommand::Results { start, count } => to_json(backend.results(start, count.min(MAX_RESULTS_PER_REQUEST))?),
+        ControlCommand::WriteValue { address, value, value_type } => {
+            let bytes = encode_write_value(&value, value_type_of(value_type)?)?;
+            backend.write_bytes(address, &bytes)?;
+            Ok(serde_json::Value::Null)
+        },
+        ControlCommand::StartPointerScan(request) => {
+            if request.regions.is_empty() {
+                return Err(invalid("No memory regions provided"));
+            }
+            backend.start_pointer_scan(request)?;
+            Ok(serde_json::Value::Null)
+        },
+    }
+}
+
+fn status_name(status: SearchStatus) -> &'static str {
+    match status {
+        SearchStatus::Idle => "idle",
+        SearchStatus::Searching => "searching",
+        SearchStatus::Completed => "completed",
+        SearchStatus::Cancelled => "cancelled",
+        SearchStatus::Error => "error",
+        SearchStatus::PartiallyCompleted => "partially_completed",
+        SearchStatus::CompletedTruncated => "completed_truncated",
+        SearchStatus::SnapshotOnly => "snapshot_only",
+    }
+}
+
+fn lock_error(name: &str) -> ControlError {
+    ControlError::new(ControlErrorCode::Failed, format!("Failed to acquire {} lock", name))
+}
+
+/// 对接全局管理器的执行端
+pub struct EngineBackend;
+
+impl EngineBackend {
+    fn check_bound() -> ControlResult<()> {
+        let driver_manager = DRIVER_MANAGER.read().map_err(|_| lock_error("DriverManager"))?;
+        if !driver_manager.is_process_bound() {
+            return Err(ControlError::new(ControlErrorCode::NotBound, "No process bound"));
+        }
+        Ok(())
+    }
+
+    /// 与 JNI 入口相同的检查：已初始化且没有正在运行的搜索
+    fn with_idle_search_manager<T>(f: impl FnOnce(&mut SearchEngineManager) -> anyhow::Result<T>) -> ControlResult<T> {
+        let mut manager = SEARCH_ENGINE_MANAGER.write().map_err(|_| lock_error("SearchEngineManager"))?;
+        if !manager.is_initialized() {
+            return Err(ControlError::new(ControlErrorCode::NotInitialized, "SearchEngineManager not initialized"));
+        }
+        if manager.is_searching() {
+            return Err(ControlError::new(ControlErrorCode::AlreadyRunning, "Search already in progress"));
+        }
+        Ok(f(&mut manager)?)
+    }
+}
+
+impl ControlBackend for EngineBackend {
+    fn start_search(&self, query: SearchQuery, regions: Vec<(u64, u64)>, deep: bool) -> ControlResult<()> {
+        Self::check_bound()?;
+        Self::with_idle_search_manager(|manager| manager.start_search_async(query, regions, deep, false, false))
+    }
+
+    fn start_refine(&self, query: SearchQuery) -> ControlResult<()> {
+        Self::check_bound()?;
+        Self::with_idle_search_manager(|manager| manager.start_refine_async(query, None))
+    }
+
+    fn status(&self) -> ControlResult<ControlStatus> {
+        let manager = SEARCH_ENGINE_MANAGER.read().map_err(|_| lock_error("SearchEngineManager"))?;
+        let (status, progress) = manager.search_status();
+        let result_count = if manager.is_initialized() { manager.get_total_count()? } else { 0 };
+        let pointer_scanning = POINTER_SCAN_MANAGER.read().map_err(|_| lock_error("PointerScanManager"))?.is_scanning();
+
+        Ok(ControlStatus {
+            searching: manager.is_searching(),
+            status: status_name(status).to_string(),
+            progress,
+            result_count,
+            pointer_scanning,
+        })
+    }
+
+    fn results(&self, start: usize, count: usize) -> ControlResult<Vec<ResultEntry>> {
+        let manager = SEARCH_ENGINE_MANAGER.read().map_err(|_| lock_error("SearchEngineManager"))?;
+        if !manager.is_initialized() {
+            return Err(ControlError::new(ControlErrorCode::NotInitialized, "SearchEngineManager not initialized"));
+        }
+        Ok(manager
+            .get_results(start, count)?
+            .into_iter()
+            .map(|item| match item {
+                SearchResultItem::Exact(exact) => ResultEntry {
+                    address: exact.address,
+                    value_type: exact.typ.to_id(),
+                },
+                SearchResultItem::Fuzzy(fuzzy) => {
+                    // packed 结构，字段先拷贝出来
+                    let value_type = fuzzy.value_type;
+                    ResultEntry {
+                        address: fuzzy.address,
+                        value_type: value_type.to_id(),
+                    }
+                },
+            })
+            .collect())
+    }
+
+    fn write_bytes(&self, address: u64, bytes: &[u8]) -> ControlResult<()> {
+        Self::check_bound()?;
+        let driver_manager = DRIVER_MANAGER.read().map_err(|_| lock_error("DriverManager"))?;
+        Ok(driver_manager.write_memory_unified(address, bytes)?)
+    }
+
+    fn start_pointer_scan(&self, request: PointerScanRequest) -> ControlResult<()> {
+        Self::check_bound()?;
+        let mut manager = POINTER_SCAN_MANAGER.write().map_err(|_| lock_error("PointerScanManager"))?;
+        if manager.is_scanning() {
+            return Err(ControlError::new(ControlErrorCode::AlreadyRunning, "Scan already in progress"));
+        }
+
+        let output_format = ChainOutputFormat::from_id(request.output_format)
+            .ok_or_else(|| invalid(format!("Invalid chain output format: {}", request.output_format)))?;
+        let mut static_modules: Vec<VmStaticData> = request
+            .regions
+            .iter()
+            .filter(|region| region.is_static)
+            .map(|region| VmStaticData::new(region.name.clone(), region.start, region.end, true))
+            .collect();
+        assign_module_indices(&mut static_modules);
+        let scan_regions = request
+            .regions
+            .into_iter()
+            .map(|region| ScanRegion {
+                start: region.start,
+                end: region.end,
+                name: region.name,
+            })
+            .collect();
+
+        Ok(manager.start_scan_async(
+            request.target,
+            request.max_depth,
+            request.max_offset,
+            request.align,
+            scan_regions,
+            static_modules,
+            true,
+            request.max_results,
+            request.force,
+            output_format,
+            request.module_filter,
+            request.live_vma_check,
+            request.require_present_pages,
+        )?)
+    }
+}
@@ -0,0 +1,11 @@
+//! Local control server
+//!
+//! 给设备上的脚本（例如 Termux）使用的可选控制接口：UNIX 域套接字 + 长度前缀 JSON 协议，
+//! 命令对应搜索引擎、指针扫描和内存写入的现有操作。默认关闭，需要通过 JNI 显式启动。
+
+pub mod backend;
+pub mod protocol;
+pub mod server;
+
+pub use backend::{ControlBackend, EngineBackend};
+pub use server::{CONTROL_SERVER, ControlServer};
@@ -0,0 +1,227 @@
+//! Control protocol
+//!
+//! 每一帧是 4 字节小端长度 + UTF-8 JSON。请求用 `cmd` 字段区分命令，可以带一个 `id`，
+//! 响应原样带回：
+//!
+//! ```text
+//! -> {"id":1,"cmd":"start_search","query":"100","value_type":2,"regions":[[4096,8192]]}
+//! <- {"id":1,"ok":true,"data":null}
+//! -> {"id":2,"cmd":"results","start":0,"count":10}
+//! <- {"id":2,"ok":false,"error":{"code":"not_initialized","message":"..."}}
+//! ```
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// 单帧最大长度，超出后无法再同步帧边界，直接断开连接
+pub const MAX_FRAME_LEN: usize = 1024 * 1024;
+
+/// `results` 一次最多返回的条数，更大的 count 会被截断。
+/// 每条结果序列化后不超过 60 字节，一整页远小于 MAX_FRAME_LEN
+pub const MAX_RESULTS_PER_REQUEST: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ControlErrorCode {
+    /// 帧内容不是合法的请求
+    InvalidRequest,
+    /// 帧长度超过 MAX_FRAME_LEN
+    FrameTooLarge,
+    NotInitialized,
+    /// 已有搜索或指针扫描在运行
+    AlreadyRunning,
+    /// 没有绑定进程
+    NotBound,
+    /// 命令执行失败
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ControlError {
+    pub code: ControlErrorCode,
+    pub message: String,
+}
+
+impl ControlError {
+    pub fn new(code: ControlErrorCode, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+}
+
+impl From<anyhow::Error> for ControlError {
+    fn from(e: anyhow::Error) -> Self {
+        Self::new(ControlErrorCode::Failed, e.to_string())
+    }
+}
+
+pub type ControlResult<T> = Result<T, ControlError>;
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ControlRequest {
+    #[serde(default)]
+    pub id: Option<u64>,
+    #[serde(flatten)]
+    pub command: ControlCommand,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum ControlCommand {
+    /// 首次搜索，query 与 UI 搜索框的语法相同
+    StartSearch {
+        query: String,
+        value_type: i32,
+        regions: Vec<(u64, u64)>,
+        #[serde(default)]
+        deep: bool,
+        /// 结果数上限，达到后提前结束扫描，0 表示不限制
+        #[serde(default)]
+        max_results: usize,
+    },
+    /// 在当前结果上改善搜索
+    StartRefine {
+        query: String,
+        value_type: i32,
+    },
+    Status,
+    /// 获取一页结果，count 超过 MAX_RESULTS_PER_REQUEST 时按上限返回
+    Results {
+        start: usize,
+        count: usize,
+    },
+    /// 写入一个值，value 按 value_type 解析
+    WriteValue {
+        address: u64,
+        value: String,
+        value_type: i32,
+    },
+    StartPointerScan(PointerScanRequest),
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PointerScanRequest {
+    pub target: u64,
+    pub max_depth: u32,
+    pub max_offset: u32,
+    #[serde(default = "default_pointer_align")]
+    pub align: u32,
+    /// 0 表示不限制
+    #[serde(default)]
+    pub max_results: u32,
+    /// 跳过 Phase 1 之后的规模估算
+    #[serde(default)]
+    pub force: bool,
+    /// 输出文件的链格式（ChainOutputFormat 的 id），0 为默认格式
+    #[serde(default)]
+    pub output_format: i32,
+    /// 只输出以这些模块为根的链
+    #[serde(default)]
+    pub module_filter: Option<Vec<String>>,
+    /// 按扫描开始时的实时 VMA 列表验证指针，而不是按传入的区域
+    #[serde(default)]
+    pub live_vma_check: bool,
+    /// 抽样检查目标页，丢弃指向不在内存中的页的指针
+    #[serde(default)]
+    pub require_present_pages: bool,
+    pub regions: Vec<PointerScanRegion>,
+}
+
+fn default_pointer_align() -> u32 {
+    8
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PointerScanRegion {
+    pub start: u64,
+    pub end: u64,
+    pub name: String,
+    #[serde(default)]
+    pub is_static: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ControlStatus {
+    pub searching: bool,
+    /// idle / searching / completed / cancelled / error
+    pub status: String,
+    pub progress: i32,
+    pub result_count: usize,
+    pub pointer_scanning: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResultEntry {
+    pub address: u64,
+    pub value_type: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ControlResponse {
+    pub id: Option<u64>,
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ControlError>,
+}
+
+impl ControlResponse {
+    pub fn from_result(id: Option<u64>, result: ControlResult<serde_json::Value>) -> Self {
+        match result {
+            Ok(data) => Self {
+                id,
+                ok: true,
+                data: Some(data),
+                error: None,
+            },
+            Err(error) => Self::error(id, error),
+        }
+    }
+
+    pub fn error(id: Option<u64>, error: ControlError) -> Self {
+        Self {
+            id,
+            ok: false,
+            data: None,
+            error: Some(error),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum FrameError {
+    /// 声明的帧长度
+    TooLarge(usize),
+    Io(std::io::Error),
+}
+
+/// 读取一帧，对端在帧边界关闭连接时返回 `Ok(None)`
+pub async fn read_frame<R>(reader: &mut R) -> Result<Option<Vec<u8>>, FrameError>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut len_buf = [0u8; 4];
+    match reader.read_exact(&mut len_buf).await {
+        Ok(_) => {},
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(FrameError::Io(e)),
+    }
+
+    let len = u32::from_le_bytes(len_buf) as usize;
+    if len > MAX_FRAME_LEN {
+        return Err(FrameError::TooLarge(len));
+    }
+
+    let mut payload = vec![0u8; len];
+    reader.read_exact(&mut payload).await.map_err(FrameError::Io)?;
+    Ok(Some(payload))
+}
+
+pub async fn write_frame<W>(writer: &mut W, payload: &[u8]) -> std::io::Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    writer.write_all(&(payload.len() as u32).to_le_bytes()).await?;
+    writer.write_all(payload).await?;
+    writer.flush().await
+}
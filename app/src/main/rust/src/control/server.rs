@@ -0,0 +1,383 @@
+//! Local control server
+//!
+//! 在 TOKIO_RUNTIME 上监听一个 UNIX 域套接字，把请求帧交给 `dispatch` 执行。
+//! 多个客户端可以同时连接，但命令逐条执行；停止时会唤醒所有正在等待请求的连接并等它们退出。
+
+use super::backend::{ControlBackend, dispatch};
+use super::protocol::{ControlError, ControlErrorCode, ControlRequest, ControlResponse, FrameError, MAX_FRAME_LEN, read_frame, write_frame};
+use crate::core::globals::TOKIO_RUNTIME;
+use anyhow::Result;
+use lazy_static::lazy_static;
+use log::{debug, info, warn};
+use nix::libc;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::{UnixListener, UnixStream};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+
+lazy_static! {
+    /// 当前运行的控制服务（默认不启动）
+    pub static ref CONTROL_SERVER: Mutex<Option<ControlServer>> = Mutex::new(None);
+}
+
+/// accept 出错后的退避时间，避免 fd 耗尽时空转
+const ACCEPT_ERROR_BACKOFF: Duration = Duration::from_millis(100);
+
+pub struct ControlServer {
+    path: PathBuf,
+    shutdown: CancellationToken,
+    tracker: TaskTracker,
+    accept_handle: JoinHandle<()>,
+}
+
+impl ControlServer {
+    /// 绑定套接字并开始接受连接，套接字文件创建时权限即为 0600
+    pub fn start(path: PathBuf, backend: Arc<dyn ControlBackend>) -> Result<Self> {
+        // 上次异常退出留下的套接字文件
+        if path.exists() {
+            std::fs::remove_file(&path)?;
+        }
+
+        // bind 之后再 chmod 会留下一段其他用户可连接的窗口，改为创建时就用 0600。
+        // umask 是进程级的，bind 期间其他线程新建的文件也会受影响，只持续这一次调用
+        let listener = {
+            let _guard = TOKIO_RUNTIME.enter();
+            let old_umask = unsafe { libc::umask(0o177) };
+            let bound = UnixListener::bind(&path);
+            unsafe { libc::umask(old_umask) };
+            bound?
+        };
+
+        let shutdown = CancellationToken::new();
+        let tracker = TaskTracker::new();
+        let accept_handle = TOKIO_RUNTIME.spawn(accept_loop(listener, backend, shutdown.clone(), tracker.clone()));
+
+        info!("Control server listening on {:?}", path);
+        Ok(Self {
+            path,
+            shutdown,
+            tracker,
+            accept_handle,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 停止服务并等待所有连接退出，然后删除套接字文件
+    ///
+    /// 会阻塞当前线程，不能在 tokio 运行时的线程里调用。
+    pub fn stop(self) {
+        self.shutdown.cancel();
+        TOKIO_RUNTIME.block_on(async {
+            let _ = self.accept_handle.await;
+            self.tracker.close();
+            self.tracker.wait().await;
+        });
+        let _ = std::fs::remove_file(&self.path);
+        info!("Control server stopped");
+    }
+}
+
+async fn accept_loop(listener: UnixListener, backend: Arc<dyn ControlBackend>, shutdown: CancellationToken, tracker: TaskTracker) {
+    // 多个客户端的命令逐条执行
+    let command_lock = Arc::new(tokio::sync::Mutex::new(()));
+
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    tracker.spawn(handle_connection(stream, Arc::clone(&backend), Arc::clone(&command_lock), shutdown.clone()));
+                },
+                Err(e) => {
+                    warn!("Control server accept failed: {:?}", e);
+                    tokio::time::sleep(ACCEPT_ERROR_BACKOFF).await;
+                },
+            },
+        }
+    }
+}
+
+async fn handle_connection(mut stream: UnixStream, backend: Arc<dyn ControlBackend>, command_lock: Arc<tokio::sync::Mutex<()>>, shutdown: CancellationToken) {
+    loop {
+        let frame = tokio::select! {
+            _ = shutdown.cancelled() => break,
+            frame = read_frame(&mut stream) => frame,
+        };
+
+        let (response, close) = match frame {
+            Ok(Some(payload)) => (handle_frame(&payload, &backend, &command_lock).await, false),
+            Ok(None) => break,
+            // 长度前缀不可信，之后的字节无法再对齐到帧边界
+            Err(FrameError::TooLarge(len)) => {
+                let error = ControlError::new(
+                    ControlErrorCode::FrameTooLarge,
+                    format!("Frame of {} bytes exceeds limit of {} bytes", len, MAX_FRAME_LEN),
+                );
+                (ControlResponse::error(None, error), true)
+            },
+            Err(FrameError::Io(e)) => {
+                debug!("Control connection read failed: {:?}", e);
+                break;
+            },
+        };
+
+        let payload = match serde_json::to_vec(&response) {
+            Ok(payload) => payload,
+            Err(e) => {
+                warn!("Failed to serialize control response: {:?}", e);
+                break;
+            },
+        };
+        if write_frame(&mut stream, &payload).await.is_err() || close {
+            break;
+        }
+    }
+}
+
+async fn handle_frame(payload: &[u8], backend: &Arc<dyn ControlBackend>, command_lock: &tokio::sync::Mutex<()>) -> ControlResponse {
+    let value: serde_json::Value = match serde_json::from_slice(payload) {
+        Ok(value) => value,
+        Err(e) => return ControlResponse::error(None, ControlError::new(ControlErrorCode::InvalidRequest, e.to_string())),
+    };
+    // 命令不合法时也尽量带回请求 id
+    let id = value.get("id").and_then(|id| id.as_u64());
+    let request: ControlRequest = match serde_json::from_value(value) {
+        Ok(request) => request,
+        Err(e) => return ControlResponse::error(id, ControlError::new(ControlErrorCode::InvalidRequest, e.to_string())),
+    };
+
+    let _guard = command_lock.lock().await;
+    let backend = Arc::clone(backend);
+    let result = tokio::task::spawn_blocking(move || dispatch(backend.as_ref(), request.command))
+        .await
+        .unwrap_or_else(|e| Err(ControlError::new(ControlErrorCode::Failed, format!("Command task failed: {}", e))));
+    ControlResponse::from_result(request.id, result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::backend::EngineBackend;
+    use crate::control::protocol::{ControlStatus, MAX_RESULTS_PER_REQUEST, ResultEntry};
+    use crate::core::DRIVER_MANAGER;
+    use crate::search::SEARCH_ENGINE_MANAGER;
+    use crate::search::engine::SHARED_BUFFER_SIZE;
+    use crate::search::tests::engine_fixture::{lock_engine, reset_engine};
+    use crate::search::tests::mock_memory::MockMemory;
+    use crate::search::tests::temp_dir::TempDir;
+    use serde_json::json;
+    use std::io::{Read, Write};
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::UnixStream as StdUnixStream;
+    use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+    const BASE: u64 = 0x7800000000;
+    const REGION_SIZE: usize = 0x4000;
+
+    fn mock_target() -> Arc<Mutex<MockMemory>> {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, REGION_SIZE).unwrap();
+        for offset in [0x10u64, 0x1200, 0x2FF0, 0x3004] {
+            mem.mem_write_u32(BASE + offset, 1000).unwrap();
+        }
+        Arc::new(Mutex::new(mem))
+    }
+
+    fn socket_path(name: &str) -> PathBuf {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        std::env::temp_dir().join(format!("mamu_ctl_{}_{}.sock", name, nanos))
+    }
+
+    fn connect(path: &Path) -> StdUnixStream {
+        let stream = StdUnixStream::connect(path).unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        stream
+    }
+
+    fn send_raw(stream: &mut StdUnixStream, payload: &[u8]) {
+        stream.write_all(&(payload.len() as u32).to_le_bytes()).unwrap();
+        stream.write_all(payload).unwrap();
+    }
+
+    fn recv(stream: &mut StdUnixStream) -> ControlResponse {
+        let mut len = [0u8; 4];
+        stream.read_exact(&mut len).unwrap();
+        let mut payload = vec![0u8; u32::from_le_bytes(len) as usize];
+        stream.read_exact(&mut payload).unwrap();
+        serde_json::from_slice(&payload).unwrap()
+    }
+
+    fn call(stream: &mut StdUnixStream, request: serde_json::Value) -> ControlResponse {
+        send_raw(stream, request.to_string().as_bytes());
+        recv(stream)
+    }
+
+    fn addresses(response: &ControlResponse) -> Vec<u64> {
+        let entries: Vec<ResultEntry> = serde_json::from_value(response.data.clone().unwrap()).unwrap();
+        entries.iter().map(|entry| entry.address).collect()
+    }
+
+    fn error_code(response: ControlResponse) -> ControlErrorCode {
+        assert!(!response.ok);
+        response.error.unwrap().code
+    }
+
+    /// 轮询 status 直到异步搜索结束
+    fn wait_idle(stream: &mut StdUnixStream) -> ControlStatus {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        loop {
+            let status: ControlStatus = serde_json::from_value(call(stream, json!({"cmd": "status"})).data.unwrap()).unwrap();
+            if !status.searching {
+                return status;
+            }
+            assert!(Instant::now() < deadline, "search did not finish");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    /// 全局搜索引擎和驱动管理器的完整流程，驱动读写换成 MockMemory
+    #[test]
+    fn test_search_refine_read_cycle_over_socket() {
+        let _guard = lock_engine();
+        let path = socket_path("cycle");
+        let server = ControlServer::start(path.clone(), Arc::new(EngineBackend)).unwrap();
+
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        let mut client = connect(&path);
+        let search = json!({"id": 1, "cmd": "start_search", "query": "1000", "value_type": 2, "regions": [[BASE, BASE + REGION_SIZE as u64]]});
+
+        // 状态检查与 JNI 入口一致：先要绑定进程，再要初始化引擎
+        assert_eq!(error_code(call(&mut client, search.clone())), ControlErrorCode::NotBound);
+        let target = mock_target();
+        DRIVER_MANAGER.write().unwrap().set_test_memory(Some(Arc::clone(&target)));
+        assert_eq!(error_code(call(&mut client, search.clone())), ControlErrorCode::NotInitialized);
+        assert_eq!(
+            error_code(call(&mut client, json!({"cmd": "results", "start": 0, "count": 10}))),
+            ControlErrorCode::NotInitialized
+        );
+
+        let dir = TempDir::new("control_cycle");
+        let mut shared = vec![0u8; SHARED_BUFFER_SIZE];
+        {
+            let mut manager = SEARCH_ENGINE_MANAGER.write().unwrap();
+            manager.init(0, dir.to_string_lossy().into_owned(), 0).unwrap();
+            assert!(manager.set_shared_buffer(shared.as_mut_ptr(), shared.len()));
+        }
+
+        let response = call(&mut client, search);
+        assert!(response.ok, "{:?}", response.error);
+        assert_eq!(response.id, Some(1));
+        let status = wait_idle(&mut client);
+        assert_eq!(status.status, "completed");
+        assert_eq!(status.result_count, 4);
+
+        let page = call(&mut client, json!({"id": 3, "cmd": "results", "start": 1, "count": 2}));
+        assert_eq!(addresses(&page), vec![BASE + 0x1200, BASE + 0x2FF0]);
+        let entries: Vec<ResultEntry> = serde_json::from_value(page.data.unwrap()).unwrap();
+        assert!(entries.iter().all(|entry| entry.value_type == 2));
+
+        // 写入改变一个值，再改善搜索
+        let response = call(
+            &mut client,
+            json!({"cmd": "write_value", "address": BASE + 0x1200, "value": "7", "value_type": 2}),
+        );
+        assert!(response.ok, "{:?}", response.error);
+        assert_eq!(target.lock().unwrap().mem_read(BASE + 0x1200, 4).unwrap(), 7u32.to_le_bytes());
+
+        let response = call(&mut client, json!({"cmd": "start_refine", "query": "1000", "value_type": 2}));
+        assert!(response.ok, "{:?}", response.error);
+        assert_eq!(wait_idle(&mut client).result_count, 3);
+
+        // 第二个客户端共享同一个会话，超大的 count 按上限截断
+        let mut other = connect(&path);
+        let page = call(&mut other, json!({"cmd": "results", "start": 0, "count": usize::MAX}));
+        assert_eq!(addresses(&page), vec![BASE + 0x10, BASE + 0x2FF0, BASE + 0x3004]);
+
+        // 参数错误返回结构化错误
+        let response = call(&mut other, json!({"id": 8, "cmd": "start_pointer_scan", "target": BASE, "max_depth": 3, "max_offset": 256, "regions": []}));
+        assert_eq!(response.id, Some(8));
+        assert_eq!(error_code(response), ControlErrorCode::InvalidRequest);
+        let response = call(
+            &mut client,
+            json!({"id": 9, "cmd": "write_value", "address": BASE, "value": "1~5", "value_type": 2}),
+        );
+        assert_eq!(response.id, Some(9));
+        assert_eq!(error_code(response), ControlErrorCode::InvalidRequest);
+
+        server.stop();
+        assert!(!path.exists());
+
+        reset_engine();
+    }
+
+    /// 一页最多的结果，每个字段取最长的序列化形式，响应仍在帧长度限制内
+    #[test]
+    fn test_results_page_limit_fits_in_frame() {
+        let entries = vec![
+            ResultEntry {
+                address: u64::MAX,
+                value_type: i32::MIN,
+            };
+            MAX_RESULTS_PER_REQUEST
+        ];
+        let response = ControlResponse::from_result(Some(u64::MAX), Ok(serde_json::to_value(entries).unwrap()));
+        assert!(serde_json::to_vec(&response).unwrap().len() < MAX_FRAME_LEN);
+    }
+
+    #[test]
+    fn test_framing_rejects_garbage_and_oversized_frames() {
+        let path = socket_path("framing");
+        let server = ControlServer::start(path.clone(), Arc::new(EngineBackend)).unwrap();
+        let mut client = connect(&path);
+
+        // 非法 JSON 和未知命令：返回错误，连接保持可用
+        send_raw(&mut client, b"\xFF\x00not json");
+        let response = recv(&mut client);
+        assert!(!response.ok);
+        assert_eq!(response.error.unwrap().code, ControlErrorCode::InvalidRequest);
+
+        let response = call(&mut client, json!({"id": 4, "cmd": "format_disk"}));
+        assert_eq!(response.id, Some(4));
+        assert_eq!(response.error.unwrap().code, ControlErrorCode::InvalidRequest);
+
+        assert!(call(&mut client, json!({"cmd": "status"})).ok);
+
+        // 超长帧：返回错误后断开
+        client.write_all(&((MAX_FRAME_LEN as u32) + 1).to_le_bytes()).unwrap();
+        let response = recv(&mut client);
+        assert_eq!(response.error.unwrap().code, ControlErrorCode::FrameTooLarge);
+        let mut rest = Vec::new();
+        assert_eq!(client.read_to_end(&mut rest).unwrap(), 0);
+
+        server.stop();
+    }
+
+    #[test]
+    fn test_stop_with_client_mid_request() {
+        let path = socket_path("shutdown");
+        let server = ControlServer::start(path.clone(), Arc::new(EngineBackend)).unwrap();
+
+        // 只发出一半的请求帧
+        let mut client = connect(&path);
+        client.write_all(&100u32.to_le_bytes()).unwrap();
+        client.write_all(b"{\"cmd\":").unwrap();
+        std::thread::sleep(Duration::from_millis(50));
+
+        let started = Instant::now();
+        server.stop();
+        assert!(started.elapsed() < Duration::from_secs(2), "stop took {:?}", started.elapsed());
+        assert!(!path.exists());
+
+        let mut rest = Vec::new();
+        assert_eq!(client.read_to_end(&mut rest).unwrap(), 0);
+        assert!(StdUnixStream::connect(&path).is_err());
+    }
+}
@@ -0,0 +1,76 @@
+//! Address rebase
+//!
+//! 游戏重启后 ASLR 会把模块加载到新的基址，保存的绝对地址全部失效。已知地址记录时模块的基址，
+//! 就可以把落在旧模块范围内的地址按偏移平移到当前基址。
+//!
+//! 旧模块的大小取当前映射的大小：同一个 so 重启前后的映射布局相同。
+
+use crate::core::DriverManager;
+use crate::core::region_map::RegionMap;
+use anyhow::{Result, anyhow};
+
+/// 把 [old_base, old_base + size) 内的地址平移到 new_base
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AddressRebase {
+    pub old_base: u64,
+    pub new_base: u64,
+    pub size: u64,
+}
+
+impl AddressRebase {
+    pub fn new(old_base: u64, new_base: u64, size: u64) -> Self {
+        Self { old_base, new_base, size }
+    }
+
+    /// 按绑定进程中模块当前的映射创建，`module` 的匹配规则与 `RegionMap::module_range` 相同
+    pub fn for_module(driver_manager: &DriverManager, module: &str, old_base: u64) -> Result<Self> {
+        if !driver_manager.is_process_bound() {
+            return Err(anyhow!("No process is bound. Please bind a process first."));
+        }
+        let pid = driver_manager.get_bound_pid();
+        let (start, end) = RegionMap::query(driver_manager, pid)
+            .map_err(|e| anyhow!("Unable to get memory regions for pid {}: {}", pid, e))?
+            .module_range(module)
+            .ok_or_else(|| anyhow!("Module {} is not loaded", module))?;
+        Ok(Self::new(old_base, start, end - start))
+    }
+
+    /// 地址在旧模块范围内时返回平移后的地址
+    pub fn apply(&self, addr: u64) -> Option<u64> {
+        let offset = addr.checked_sub(self.old_base)?;
+        (offset < self.size).then(|| self.new_base + offset)
+    }
+
+    /// 平移一组地址，范围外的地址保持不变并在返回的数组中标记为 true
+    pub fn apply_all(&self, addrs: &[u64]) -> (Vec<u64>, Vec<bool>) {
+        addrs
+            .iter()
+            .map(|&addr| match self.apply(addr) {
+                Some(rebased) => (rebased, false),
+                None => (addr, true),
+            })
+            .unzip()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rebase_inside_module_only() {
+        let rebase = AddressRebase::new(0x7A00_0000, 0x7B40_0000, 0x2000);
+        let (addrs, outside) = rebase.apply_all(&[0x7A00_0000, 0x7A00_1FF8, 0x7A00_2000, 0x79FF_FFF8, 0x10]);
+
+        assert_eq!(addrs, vec![0x7B40_0000, 0x7B40_1FF8, 0x7A00_2000, 0x79FF_FFF8, 0x10]);
+        assert_eq!(outside, vec![false, false, true, true, true]);
+    }
+
+    #[test]
+    fn test_rebase_to_lower_base() {
+        let rebase = AddressRebase::new(0x7B40_0000, 0x7A00_0000, 0x1000);
+        assert_eq!(rebase.apply(0x7B40_0123), Some(0x7A00_0123));
+        assert_eq!(rebase.apply(0x7B40_1000), None);
+        assert_eq!(AddressRebase::new(0x1000, 0x2000, 0).apply(0x1000), None);
+    }
+}
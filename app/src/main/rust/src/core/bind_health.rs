@@ -0,0 +1,584 @@
+//! Bound process health tracking
+//!
+//! 目标进程 exec（部分游戏会自我重启）或驱动模块重新加载后 pid 不变，
+//! 但 BindProc 仍指向已经失效的 mm，读取全部失败或返回垃圾数据。
+//! 这里跟踪读取失败率和进程身份（进程名），发现句柄失效后由看门狗自动重新绑定同一 pid。
+//!
+//! 目标进程退出（游戏崩溃）后之后的读取全部失败，界面只能看到各种读取错误。看门狗每次检查时
+//! 也询问驱动进程是否存活，进程退出后取消正在进行的搜索和指针扫描（错误码 ProcessDied），
+//! 解绑进程并把状态记为 ProcessDied；进程重启后由界面按进程名重新绑定（nativeRebindByName）。
+
+use crate::core::globals::{DRIVER_MANAGER, FREEZE_MANAGER, TOKIO_RUNTIME};
+use crate::pointer_scan::manager::POINTER_SCAN_MANAGER;
+use crate::search::engine::SEARCH_ENGINE_MANAGER;
+use anyhow::Result;
+use log::{error, info, warn};
+use std::fmt;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU32, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// 失败率统计窗口（读取次数），窗口满且失败率正常时清零
+pub const FAILURE_WINDOW_READS: u32 = 64;
+
+/// 窗口内失败比例达到这个百分比时认为失败率升高
+pub const FAILURE_RATE_PERCENT: u32 = 90;
+
+/// 两次因失败率触发的验证之间的最小间隔
+pub const VALIDATE_TTL: Duration = Duration::from_secs(2);
+
+/// 连续重新绑定失败次数上限，达到后放弃，等待用户手动绑定
+pub const MAX_REBIND_ATTEMPTS: u32 = 3;
+
+/// 第一次重试的退避时间，之后每次翻倍
+pub const REBIND_BACKOFF: Duration = Duration::from_secs(1);
+
+/// 看门狗检查间隔
+pub const WATCHDOG_INTERVAL: Duration = Duration::from_secs(1);
+
+/// nativeGetBindStatus 返回的状态码
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
+pub enum BindStatus {
+    Unbound = 0,
+    /// 句柄正常
+    Bound = 1,
+    /// 句柄已失效，等待重新绑定
+    Stale = 2,
+    /// 目标进程 exec 后已自动重新绑定同一 pid
+    TargetRebound = 3,
+    /// 重新绑定多次失败，已放弃
+    Lost = 4,
+    /// 绑定的进程已退出，已自动解绑
+    ProcessDied = 5,
+}
+
+impl BindStatus {
+    fn from_i32(value: i32) -> Self {
+        match value {
+            1 => Self::Bound,
+            2 => Self::Stale,
+            3 => Self::TargetRebound,
+            4 => Self::Lost,
+            5 => Self::ProcessDied,
+            _ => Self::Unbound,
+        }
+    }
+}
+
+/// 句柄失效期间的读取错误，与普通的读取失败区分
+#[derive(Debug)]
+pub struct StaleBindingError {
+    pub pid: i32,
+    pub cause: String,
+}
+
+impl fmt::Display for StaleBindingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Stale binding for pid {}: {}", self.pid, self.cause)
+    }
+}
+
+impl std::error::Error for StaleBindingError {}
+
+pub fn is_stale_binding(error: &anyhow::Error) -> bool {
+    error.is::<StaleBindingError>()
+}
+
+/// 健康检查需要的绑定操作，DriverManager 使用真实驱动实现，测试使用模拟实现
+pub trait BindTarget {
+    /// 进程身份（进程名），exec 后通常会变化；进程不存在时返回 None
+    fn process_identity(&self, pid: i32) -> Option<String>;
+    /// 对已知可读地址做一次 1 字节读取
+    fn validate(&self) -> bool;
+    /// 进程是否仍然存在，无法判断时按存活处理
+    fn is_alive(&self, pid: i32) -> bool;
+    /// 重新绑定同一 pid 并刷新模块映射
+    fn rebind(&mut self, pid: i32) -> Result<()>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecoverOutcome {
+    /// 句柄正常，无需处理
+    NotStale,
+    Rebound,
+    /// 本次重新绑定失败，退避后重试
+    Failed,
+    /// 仍在退避时间内
+    Waiting,
+    /// 达到重试上限，不再重试
+    GaveUp,
+}
+
+#[derive(Debug, Default)]
+struct HealthState {
+    identity: Option<String>,
+    last_validate: Option<Instant>,
+    attempts: u32,
+    next_attempt: Option<Instant>,
+}
+
+#[derive(Debug)]
+pub struct BindHealth {
+    status: AtomicI32,
+    reads: AtomicU32,
+    failures: AtomicU32,
+    rebind_count: AtomicU64,
+    state: Mutex<HealthState>,
+}
+
+impl Default for BindHealth {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BindHealth {
+    pub fn new() -> Self {
+        Self {
+            status: AtomicI32::new(BindStatus::Unbound as i32),
+            reads: AtomicU32::new(0),
+            failures: AtomicU32::new(0),
+            rebind_count: AtomicU64::new(0),
+            state: Mutex::new(HealthState::default()),
+        }
+    }
+
+    /// 显式绑定进程后重置，`identity` 为绑定时的进程名
+    pub fn reset(&self, identity: Option<String>) {
+        if let Ok(mut state) = self.state.lock() {
+            *state = HealthState {
+                identity,
+                ..Default::default()
+            };
+        }
+        self.reset_counters();
+        self.rebind_count.store(0, Ordering::Relaxed);
+        self.status.store(BindStatus::Bound as i32, Ordering::Release);
+    }
+
+    /// 解绑进程
+    pub fn clear(&self) {
+        self.reset(None);
+        self.status.store(BindStatus::Unbound as i32, Ordering::Release);
+    }
+
+    pub fn status(&self) -> BindStatus {
+        BindStatus::from_i32(self.status.load(Ordering::Acquire))
+    }
+
+    /// 绑定的进程退出后解绑，状态记为 ProcessDied，直到下一次绑定或解绑
+    pub fn mark_died(&self) {
+        self.clear();
+        self.status.store(BindStatus::ProcessDied as i32, Ordering::Release);
+    }
+
+    /// 句柄已失效（包括已放弃重新绑定）
+    pub fn is_stale(&self) -> bool {
+        matches!(self.status(), BindStatus::Stale | BindStatus::Lost)
+    }
+
+    /// 自动重新绑定成功的次数
+    pub fn rebind_count(&self) -> u64 {
+        self.rebind_count.load(Ordering::Relaxed)
+    }
+
+    /// 记录一次读取调用的结果，页级别的部分失败不算失败
+    pub fn record_read(&self, ok: bool) {
+        let reads = self.reads.fetch_add(1, Ordering::Relaxed) + 1;
+        let failures = if ok {
+            self.failures.load(Ordering::Relaxed)
+        } else {
+            self.failures.fetch_add(1, Ordering::Relaxed) + 1
+        };
+        if reads >= FAILURE_WINDOW_READS && !Self::is_elevated(reads, failures) {
+            self.reset_counters();
+        }
+    }
+
+    /// 失效期间把读取错误归类为 StaleBindingError
+    pub fn classify_read_error(&self, error: anyhow::Error, pid: i32) -> anyhow::Error {
+        if self.is_stale() {
+            anyhow::Error::new(StaleBindingError { pid, cause: error.to_string() })
+        } else {
+            error
+        }
+    }
+
+    fn is_elevated(reads: u32, failures: u32) -> bool {
+        reads >= FAILURE_WINDOW_READS && failures * 100 >= reads * FAILURE_RATE_PERCENT
+    }
+
+    fn failure_rate_elevated(&self) -> bool {
+        Self::is_elevated(self.reads.load(Ordering::Relaxed), self.failures.load(Ordering::Relaxed))
+    }
+
+    fn reset_counters(&self) {
+        self.reads.store(0, Ordering::Relaxed);
+        self.failures.store(0, Ordering::Relaxed);
+    }
+
+    /// 检测绑定的进程是否已经退出，只需要读锁；未绑定时不检测
+    pub fn detect_death<T: BindTarget + ?Sized>(&self, target: &T, pid: i32) -> bool {
+        if matches!(self.status(), BindStatus::Unbound | BindStatus::ProcessDied) {
+            return false;
+        }
+        !target.is_alive(pid)
+    }
+
+    /// 检测句柄是否失效，只需要读锁
+    ///
+    /// 进程名变化（exec）直接判定失效；失败率升高时按 TTL 验证句柄，验证失败判定失效。
+    /// 返回句柄当前是否处于失效状态。
+    pub fn detect<T: BindTarget + ?Sized>(&self, target: &T, pid: i32, now: Instant) -> bool {
+        match self.status() {
+            BindStatus::Unbound | BindStatus::ProcessDied => return false,
+            BindStatus::Stale | BindStatus::Lost => return true,
+            BindStatus::Bound | BindStatus::TargetRebound => {},
+        }
+
+        let Ok(mut state) = self.state.lock() else {
+            return false;
+        };
+
+        let current = target.process_identity(pid);
+        let identity_changed = matches!((&state.identity, &current), (Some(old), Some(new)) if old != new);
+
+        let should_validate = self.failure_rate_elevated() && state.last_validate.is_none_or(|last| now.duration_since(last) >= VALIDATE_TTL);
+        if !identity_changed && !should_validate {
+            return false;
+        }
+
+        if identity_changed {
+            warn!("Bound process {} changed identity {:?} -> {:?}, binding is stale", pid, state.identity, current);
+        } else {
+            state.last_validate = Some(now);
+            if target.validate() {
+                self.reset_counters();
+                return false;
+            }
+            warn!("Bound process {} failed validation after elevated read failures, binding is stale", pid);
+        }
+
+        self.status.store(BindStatus::Stale as i32, Ordering::Release);
+        true
+    }
+
+    /// 重新绑定失效的句柄，需要写锁；按指数退避重试，达到上限后放弃
+    pub fn recover<T: BindTarget + ?Sized>(&self, target: &mut T, pid: i32, now: Instant) -> RecoverOutcome {
+        match self.status() {
+            BindStatus::Stale => {},
+            BindStatus::Lost => return RecoverOutcome::GaveUp,
+            _ => return RecoverOutcome::NotStale,
+        }
+
+        let Ok(mut state) = self.state.lock() else {
+            return RecoverOutcome::Failed;
+        };
+        if state.next_attempt.is_some_and(|next| now < next) {
+            return RecoverOutcome::Waiting;
+        }
+
+        state.attempts += 1;
+        match target.rebind(pid) {
+            Ok(()) => {
+                *state = HealthState {
+                    identity: target.process_identity(pid),
+                    ..Default::default()
+                };
+                self.reset_counters();
+                self.rebind_count.fetch_add(1, Ordering::Relaxed);
+                self.status.store(BindStatus::TargetRebound as i32, Ordering::Release);
+                info!("Re-bound process {} after stale binding", pid);
+                RecoverOutcome::Rebound
+            },
+            Err(e) => {
+                warn!(
+                    "Failed to re-bind process {} (attempt {}/{}): {:?}",
+                    pid, state.attempts, MAX_REBIND_ATTEMPTS, e
+                );
+                if state.attempts >= MAX_REBIND_ATTEMPTS {
+                    self.status.store(BindStatus::Lost as i32, Ordering::Release);
+                    error!("Giving up re-binding process {}", pid);
+                    return RecoverOutcome::GaveUp;
+                }
+                state.next_attempt = Some(now + REBIND_BACKOFF * (1 << (state.attempts - 1)));
+                RecoverOutcome::Failed
+            },
+        }
+    }
+}
+
+static WATCHDOG_STARTED: AtomicBool = AtomicBool::new(false);
+
+/// 启动绑定看门狗（只启动一次），绑定进程时调用
+pub fn ensure_watchdog() {
+    if WATCHDOG_STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    TOKIO_RUNTIME.spawn(async {
+        loop {
+            tokio::time::sleep(WATCHDOG_INTERVAL).await;
+            if let Err(e) = tokio::task::spawn_blocking(watchdog_tick).await {
+                error!("Bind watchdog tick failed: {:?}", e);
+            }
+        }
+    });
+}
+
+/// 看门狗的一轮检查：先在读锁下检测，确认进程退出后解绑，确认句柄失效后才获取写锁重新绑定
+fn watchdog_tick() {
+    let now = Instant::now();
+    {
+        let Ok(manager) = DRIVER_MANAGER.read() else {
+            return;
+        };
+        if !manager.is_process_bound() {
+            return;
+        }
+        let pid = manager.get_bound_pid();
+        if manager.bind_health().detect_death(&*manager, pid) {
+            drop(manager);
+            release_dead_process(pid);
+            return;
+        }
+        if !manager.bind_health().detect(&*manager, pid, now) {
+            return;
+        }
+    }
+
+    let Ok(mut manager) = DRIVER_MANAGER.write() else {
+        return;
+    };
+    let pid = manager.get_bound_pid();
+    if pid == 0 {
+        return;
+    }
+    let health = manager.bind_health();
+    health.recover(&mut *manager, pid, now);
+}
+
+/// 绑定的进程退出：取消搜索和指针扫描，再解绑并把它的冻结条目转为孤立，进程重启后可以重新映射
+///
+/// 不持有 DRIVER_MANAGER 的锁时获取搜索管理器的锁，保持先搜索后驱动的加锁顺序。
+fn release_dead_process(pid: i32) {
+    warn!("Bound process {} has exited, cancelling scans and unbinding", pid);
+    if let Ok(mut manager) = SEARCH_ENGINE_MANAGER.write() {
+        manager.abort_for_process_death();
+    }
+    if let Ok(mut manager) = POINTER_SCAN_MANAGER.write() {
+        manager.abort_for_process_death();
+    }
+
+    let Ok(mut manager) = DRIVER_MANAGER.write() else {
+        return;
+    };
+    // 检测之后用户可能已经绑定了其他进程
+    if manager.get_bound_pid() != pid {
+        return;
+    }
+    manager.unbind_process();
+    manager.bind_health().mark_died();
+    drop(manager);
+
+    if let Ok(freeze_manager) = FREEZE_MANAGER.read() {
+        freeze_manager.suspend_for_pid(pid);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use std::cell::Cell;
+
+    /// 模拟驱动：exec 后进程名变化、读取全部失败，重新绑定后恢复
+    struct MockTarget {
+        name: String,
+        exec_name: Option<String>,
+        alive: bool,
+        handle_valid: bool,
+        rebind_ok: bool,
+        validate_calls: Cell<u32>,
+        rebind_calls: u32,
+    }
+
+    impl MockTarget {
+        fn new(name: &str) -> Self {
+            Self {
+                name: name.to_string(),
+                exec_name: None,
+                alive: true,
+                handle_valid: true,
+                rebind_ok: true,
+                validate_calls: Cell::new(0),
+                rebind_calls: 0,
+            }
+        }
+
+        fn exec(&mut self, new_name: Option<&str>) {
+            self.exec_name = new_name.map(str::to_string);
+            self.handle_valid = false;
+        }
+
+        fn read(&self, health: &BindHealth) -> Result<()> {
+            let result = if self.handle_valid { Ok(()) } else { Err(anyhow!("BindProc read failed")) };
+            health.record_read(result.is_ok());
+            result.map_err(|e| health.classify_read_error(e, 100))
+        }
+    }
+
+    impl BindTarget for MockTarget {
+        fn process_identity(&self, _pid: i32) -> Option<String> {
+            Some(self.exec_name.clone().unwrap_or_else(|| self.name.clone()))
+        }
+
+        fn validate(&self) -> bool {
+            self.validate_calls.set(self.validate_calls.get() + 1);
+            self.handle_valid
+        }
+
+        fn is_alive(&self, _pid: i32) -> bool {
+            self.alive
+        }
+
+        fn rebind(&mut self, _pid: i32) -> Result<()> {
+            self.rebind_calls += 1;
+            if !self.rebind_ok {
+                return Err(anyhow!("Process bind failed"));
+            }
+            self.handle_valid = true;
+            Ok(())
+        }
+    }
+
+    fn bound(target: &MockTarget) -> BindHealth {
+        let health = BindHealth::new();
+        health.reset(target.process_identity(100));
+        health
+    }
+
+    #[test]
+    fn test_exec_detected_by_identity_and_rebound() {
+        let mut target = MockTarget::new("com.example.launcher");
+        let health = bound(&target);
+        let now = Instant::now();
+        assert!(!health.detect(&target, 100, now));
+
+        target.exec(Some("com.example.game"));
+        let err = target.read(&health).unwrap_err();
+        assert!(!is_stale_binding(&err), "errors before detection stay generic");
+
+        assert!(health.detect(&target, 100, now));
+        assert_eq!(health.status(), BindStatus::Stale);
+        assert_eq!(target.validate_calls.get(), 0, "an identity change needs no probe");
+        assert!(is_stale_binding(&target.read(&health).unwrap_err()));
+
+        assert_eq!(health.recover(&mut target, 100, now), RecoverOutcome::Rebound);
+        assert_eq!(health.status(), BindStatus::TargetRebound);
+        assert_eq!(health.rebind_count(), 1);
+        assert!(target.read(&health).is_ok());
+        // 新的进程名已记录，不会再次触发
+        assert!(!health.detect(&target, 100, now));
+        assert_eq!(health.recover(&mut target, 100, now), RecoverOutcome::NotStale);
+        assert_eq!(target.rebind_calls, 1);
+    }
+
+    #[test]
+    fn test_failure_rate_triggers_validation_on_ttl() {
+        let mut target = MockTarget::new("com.example.game");
+        let health = bound(&target);
+        let now = Instant::now();
+
+        // 零散的失败不会触发验证
+        for i in 0..FAILURE_WINDOW_READS * 2 {
+            health.record_read(i % 8 != 0);
+        }
+        assert!(!health.detect(&target, 100, now));
+        assert_eq!(target.validate_calls.get(), 0);
+
+        // 失败率升高但句柄验证通过：不判定失效，TTL 内不再验证
+        for _ in 0..FAILURE_WINDOW_READS {
+            health.record_read(false);
+        }
+        assert!(!health.detect(&target, 100, now));
+        assert_eq!(target.validate_calls.get(), 1);
+        for _ in 0..FAILURE_WINDOW_READS {
+            health.record_read(false);
+        }
+        assert!(!health.detect(&target, 100, now + VALIDATE_TTL / 2));
+        assert_eq!(target.validate_calls.get(), 1);
+
+        // 驱动模块重新加载：进程名不变，句柄失效
+        target.exec(None);
+        for _ in 0..FAILURE_WINDOW_READS {
+            let _ = target.read(&health);
+        }
+        assert!(health.detect(&target, 100, now + VALIDATE_TTL));
+        assert_eq!(target.validate_calls.get(), 2);
+        assert_eq!(health.recover(&mut target, 100, now + VALIDATE_TTL), RecoverOutcome::Rebound);
+    }
+
+    #[test]
+    fn test_rebind_failures_back_off_and_give_up() {
+        let mut target = MockTarget::new("com.example.game");
+        target.rebind_ok = false;
+        let health = bound(&target);
+        let mut now = Instant::now();
+
+        target.exec(Some("com.example.game:relaunch"));
+        assert!(health.detect(&target, 100, now));
+
+        assert_eq!(health.recover(&mut target, 100, now), RecoverOutcome::Failed);
+        assert_eq!(health.recover(&mut target, 100, now), RecoverOutcome::Waiting);
+        assert_eq!(target.rebind_calls, 1);
+
+        let mut outcomes = Vec::new();
+        for _ in 0..10 {
+            now += REBIND_BACKOFF * 4;
+            outcomes.push(health.recover(&mut target, 100, now));
+        }
+        assert_eq!(target.rebind_calls, MAX_REBIND_ATTEMPTS);
+        assert_eq!(outcomes[0], RecoverOutcome::Failed);
+        assert!(outcomes[1..].iter().all(|o| *o == RecoverOutcome::GaveUp));
+        assert_eq!(health.status(), BindStatus::Lost);
+        assert!(health.detect(&target, 100, now));
+        assert!(is_stale_binding(&target.read(&health).unwrap_err()));
+
+        // 用户手动重新绑定后恢复
+        health.reset(Some("com.example.game".to_string()));
+        assert_eq!(health.status(), BindStatus::Bound);
+        assert_eq!(health.recover(&mut target, 100, now), RecoverOutcome::NotStale);
+    }
+
+    #[test]
+    fn test_death_detected_until_unbound() {
+        let mut target = MockTarget::new("com.example.game");
+        let health = BindHealth::new();
+        target.alive = false;
+        // 未绑定时不检测
+        assert!(!health.detect_death(&target, 100));
+
+        health.reset(target.process_identity(100));
+        target.alive = true;
+        assert!(!health.detect_death(&target, 100));
+        target.alive = false;
+        assert!(health.detect_death(&target, 100));
+
+        health.mark_died();
+        assert_eq!(health.status(), BindStatus::ProcessDied);
+        assert!(!health.detect_death(&target, 100));
+        // 退出的进程不再尝试重新绑定同一 pid
+        target.exec(Some("com.example.game:relaunch"));
+        assert!(!health.detect(&target, 100, Instant::now()));
+        assert_eq!(health.recover(&mut target, 100, Instant::now()), RecoverOutcome::NotStale);
+        assert_eq!(target.rebind_calls, 0);
+
+        // 按进程名重新绑定重启后的进程
+        target.alive = true;
+        health.reset(Some("com.example.game".to_string()));
+        assert_eq!(health.status(), BindStatus::Bound);
+        assert!(!health.detect_death(&target, 200));
+    }
+}
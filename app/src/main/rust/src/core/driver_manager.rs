@@ -1,14 +1,94 @@
 //! Driver manager implementation
 
+use crate::core::atomic_write::{AtomicWriteReport, raise_current_thread_priority, write_atomic_group_with};
+use crate::core::bind_health::{BindHealth, BindTarget};
+use crate::core::globals::{MEMORY_QOS, PAGE_SIZE, SELF_REGIONS};
 use crate::core::memory_mode::MemoryAccessMode;
-use crate::wuwa::{BindProc, PageStatusBitmap, WuWaDriver, WuwaMemoryType};
-use log::error;
+use crate::core::process_pause::{PauseGuard, pause_target, resume_paused_target};
+use crate::core::qos::AccessQos;
+use crate::core::read_fallback::{ReadFallback, ReadPaths};
+use crate::core::region_map::{ModuleInfo, PageAccess, RegionMap, ResolvedAddress, current_region_map, invalidate_region_map, resolve_addresses};
+use crate::core::secondary_procs::{PidReadPaths, SecondaryProcesses};
+use crate::wuwa::{BindProc, PageStatusBitmap, WuWaDriver, WuwaMemoryType, read_cstring_with, read_fstring_with};
+use log::warn;
+use std::sync::Arc;
+
+/// 一段内存窗口的内容和按页的有效性
+pub struct MemoryWindow {
+    /// 窗口内的字节，无效页的部分为 0
+    pub data: Vec<u8>,
+    /// 窗口覆盖的每一页是否读取成功，第 0 项是 `addr` 所在的页（`addr` 可以不按页对齐）
+    pub page_valid: Vec<bool>,
+}
+
+/// 把读取时的页状态位图转换为窗口覆盖的各页是否有效，并把无效页的字节清零
+fn window_from_status(addr: u64, mut data: Vec<u8>, status: &PageStatusBitmap, page_size: usize) -> MemoryWindow {
+    let mut page_valid = Vec::new();
+    apply_page_status(addr, &mut data, status, page_size, &mut page_valid);
+    MemoryWindow { data, page_valid }
+}
+
+/// 按页状态把无效页的字节清零，各页是否有效写入 `page_valid`，返回有效页内的字节数
+fn apply_page_status(addr: u64, data: &mut [u8], status: &PageStatusBitmap, page_size: usize, page_valid: &mut Vec<bool>) -> usize {
+    let head = addr as usize & (page_size - 1);
+    let page_count = (head + data.len()).div_ceil(page_size);
+    page_valid.clear();
+    page_valid.extend((0..page_count).map(|page| status.is_page_success(page)));
+
+    let mut valid_bytes = data.len();
+    for (page, _) in page_valid.iter().enumerate().filter(|(_, valid)| !**valid) {
+        let start = (page * page_size).saturating_sub(head);
+        let end = ((page + 1) * page_size - head).min(data.len());
+        data[start..end].fill(0);
+        valid_bytes -= end - start;
+    }
+    valid_bytes
+}
+
+/// 把一段内存窗口读入调用方的缓冲区 `out`，语义同 `read_memory_window`，返回有效页内的字节数。
+/// `out` 不按页对齐时先读入调用方复用的 `staging` 再复制
+pub(crate) fn read_window_into<R>(
+    addr: u64,
+    out: &mut [u8],
+    staging: &mut Vec<u8>,
+    page_valid: &mut Vec<bool>,
+    page_size: usize,
+    read: R,
+) -> anyhow::Result<usize>
+where
+    R: FnOnce(u64, &mut [u8], &mut PageStatusBitmap) -> anyhow::Result<()>,
+{
+    page_valid.clear();
+    let mut status = PageStatusBitmap::new(out.len(), addr as usize);
+    if (out.as_ptr() as usize).is_multiple_of(page_size) {
+        read(addr, out, &mut status)?;
+    } else {
+        staging.clear();
+        staging.resize(out.len(), 0);
+        read(addr, staging, &mut status)?;
+        out.copy_from_slice(staging);
+    }
+    Ok(apply_page_status(addr, out, &status, page_size, page_valid))
+}
 
 pub struct DriverManager {
     driver: Option<WuWaDriver>,
     bound_process: Option<BindProc>,
     bound_pid: i32,
     access_mode: MemoryAccessMode,
+    bind_health: Arc<BindHealth>,
+    read_fallback: ReadFallback,
+    /// 绑定进程之外需要读取的进程（见 secondary_procs）
+    secondary: SecondaryProcesses<BindProc>,
+    /// 测试中代替驱动的模拟内存，设置后视为已绑定进程，统一读写入口都走它
+    #[cfg(test)]
+    test_memory: Option<Arc<std::sync::Mutex<crate::search::tests::mock_memory::MockMemory>>>,
+    /// 测试中代替其他进程的模拟内存，`read_memory_of` 读取这些 pid 时走它们
+    #[cfg(test)]
+    test_processes: std::collections::HashMap<i32, Arc<std::sync::Mutex<crate::search::tests::mock_memory::MockMemory>>>,
+    /// 测试中挡住部分地址的读取，见 `ReadGate`
+    #[cfg(test)]
+    test_read_gate: Option<Arc<crate::search::tests::mock_memory::ReadGate>>,
 }
 
 impl DriverManager {
@@ -18,9 +98,35 @@ impl DriverManager {
             bound_process: None,
             bound_pid: 0,
             access_mode: MemoryAccessMode::None,
+            bind_health: Arc::new(BindHealth::new()),
+            read_fallback: ReadFallback::new(),
+            secondary: SecondaryProcesses::new(),
+            #[cfg(test)]
+            test_memory: None,
+            #[cfg(test)]
+            test_processes: std::collections::HashMap::new(),
+            #[cfg(test)]
+            test_read_gate: None,
         }
     }
 
+    #[cfg(test)]
+    pub(crate) fn set_test_memory(&mut self, memory: Option<Arc<std::sync::Mutex<crate::search::tests::mock_memory::MockMemory>>>) {
+        self.test_memory = memory;
+        self.test_processes.clear();
+        self.test_read_gate = None;
+    }
+
+    #[cfg(test)]
+    pub(crate) fn set_test_read_gate(&mut self, gate: Option<Arc<crate::search::tests::mock_memory::ReadGate>>) {
+        self.test_read_gate = gate;
+    }
+
+    #[cfg(test)]
+    pub(crate) fn set_test_process_memory(&mut self, pid: i32, memory: Arc<std::sync::Mutex<crate::search::tests::mock_memory::MockMemory>>) {
+        self.test_processes.insert(pid, memory);
+    }
+
     pub fn set_driver(&mut self, driver: WuWaDriver) {
         self.driver = Some(driver);
     }
@@ -36,33 +142,21 @@ impl DriverManager {
     /// 设置内存访问模式
     pub fn set_access_mode(&mut self, mode: MemoryAccessMode) -> anyhow::Result<()> {
         self.access_mode = mode;
+        self.read_fallback.reset_switch();
         if self.is_process_bound() {
             if let Some(bind_proc) = &self.bound_process {
-                match self.get_access_mode() {
-                    MemoryAccessMode::None => {}, // do nothing
-                    MemoryAccessMode::NonCacheable => {
-                        bind_proc.set_memory_type(WuwaMemoryType::DeviceNGnRnE)?;
-                    },
-                    MemoryAccessMode::WriteThrough => {
-                        bind_proc.set_memory_type(WuwaMemoryType::NormalWt)?;
-                    },
-                    MemoryAccessMode::Normal => {
-                        bind_proc.set_memory_type(WuwaMemoryType::Normal)?;
-                    },
-                    MemoryAccessMode::PageFault => {}, // do nothing
-                };
+                self.apply_memory_type(bind_proc)?;
             }
         }
+        for bind_proc in self.secondary.handles() {
+            self.apply_memory_type(&bind_proc)?;
+        }
 
         Ok(())
     }
 
-    pub fn get_access_mode(&self) -> MemoryAccessMode {
-        self.access_mode
-    }
-
-    /// 绑定进程以进行内存访问
-    pub fn bind_process(&mut self, bind_proc: BindProc, pid: i32) -> anyhow::Result<()> {
+    /// 按访问模式设置句柄的内存类型，缺页模式和物理模式不需要设置，这个时候不走bindproc去读写内存
+    fn apply_memory_type(&self, bind_proc: &BindProc) -> anyhow::Result<()> {
         match self.get_access_mode() {
             MemoryAccessMode::None => {}, // do nothing
             MemoryAccessMode::NonCacheable => {
@@ -76,19 +170,60 @@ impl DriverManager {
             },
             MemoryAccessMode::PageFault => {}, // do nothing
         };
-        // 缺页模式和物理模式不需要设置内存类型，这个时候不走bindproc去读写内存
+        Ok(())
+    }
+
+    pub fn get_access_mode(&self) -> MemoryAccessMode {
+        self.access_mode
+    }
+
+    /// 物理内存读取模式下从搜索区域中去掉本进程的映射（见 self_regions），其他模式原样返回
+    pub fn exclude_self_regions(&self, regions: Vec<(u64, u64)>) -> Vec<(u64, u64)> {
+        if self.access_mode != MemoryAccessMode::None {
+            return regions;
+        }
+        SELF_REGIONS.exclude(regions)
+    }
+
+    /// 绑定进程以进行内存访问
+    pub fn bind_process(&mut self, bind_proc: BindProc, pid: i32) -> anyhow::Result<()> {
+        self.attach(bind_proc, pid)?;
+        self.bind_health.reset(self.process_identity_of(pid));
+        self.read_fallback.reset();
+        Ok(())
+    }
+
+    fn attach(&mut self, bind_proc: BindProc, pid: i32) -> anyhow::Result<()> {
+        self.apply_memory_type(&bind_proc)?;
+        if self.bound_pid != pid {
+            resume_paused_target();
+        }
         self.bound_process = Some(bind_proc);
         self.bound_pid = pid;
+        // 绑定进程不再同时作为次要进程
+        self.secondary.remove(pid);
+        self.read_fallback.reset_switch();
+        // 重新绑定（包括同一 pid 重新附加）后区域映射可能已变化
+        invalidate_region_map();
         Ok(())
     }
 
     /// 解绑当前绑定的进程
     pub fn unbind_process(&mut self) {
+        // 扫描中解绑时不能把目标进程留在停止状态
+        resume_paused_target();
         self.bound_process = None;
         self.bound_pid = 0;
+        self.bind_health.clear();
+        self.read_fallback.reset();
+        invalidate_region_map();
     }
 
     pub fn is_process_bound(&self) -> bool {
+        #[cfg(test)]
+        if self.test_memory.is_some() {
+            return true;
+        }
         self.bound_process.is_some() && self.bound_pid != 0
     }
 
@@ -96,10 +231,59 @@ impl DriverManager {
         self.bound_pid
     }
 
+    /// 暂停绑定的进程直到返回的 guard 被释放（见 process_pause）
+    pub fn pause_target(&self) -> anyhow::Result<PauseGuard<'static>> {
+        if !self.is_process_bound() {
+            return Err(anyhow::anyhow!("No process bound"));
+        }
+        pause_target(self.bound_pid)
+    }
+
     pub fn get_bound_process(&self) -> Option<&BindProc> {
         self.bound_process.as_ref()
     }
 
+    /// 绑定一个次要进程，之后可以用 `read_memory_of` 读取它，不影响当前绑定的进程。
+    /// 只需要 DRIVER_MANAGER 的读锁
+    pub fn bind_secondary_process(&self, pid: i32) -> anyhow::Result<()> {
+        if pid <= 0 {
+            return Err(anyhow::anyhow!("Invalid pid: {}", pid));
+        }
+        if self.is_process_bound() && pid == self.bound_pid {
+            return Err(anyhow::anyhow!("Process {} is already the bound process", pid));
+        }
+        let driver = self.get_driver().ok_or_else(|| anyhow::anyhow!("Driver not initialized"))?;
+        let bind_proc = driver.bind_process(pid)?;
+        self.apply_memory_type(&bind_proc)?;
+        self.secondary.insert(pid, bind_proc)
+    }
+
+    /// 释放次要进程的句柄，没有绑定时返回 false
+    pub fn release_secondary(&self, pid: i32) -> bool {
+        self.secondary.remove(pid)
+    }
+
+    /// 已绑定的次要进程
+    pub fn secondary_pids(&self) -> Vec<i32> {
+        self.secondary.pids()
+    }
+
+    /// 绑定句柄的健康状态，看门狗据此检测进程 exec 并重新绑定
+    pub fn bind_health(&self) -> Arc<BindHealth> {
+        Arc::clone(&self.bind_health)
+    }
+
+    /// BindProc 读取失败时的驱动路径回退和各路径的读取统计
+    pub fn read_fallback(&self) -> &ReadFallback {
+        &self.read_fallback
+    }
+
+    fn process_identity_of(&self, pid: i32) -> Option<String> {
+        let info = self.get_driver()?.get_process_info(pid).ok()?;
+        let end = info.name.iter().position(|&c| c == 0).unwrap_or(info.name.len());
+        Some(String::from_utf8_lossy(&info.name[..end]).into_owned())
+    }
+
     /// 统一的内存读取方法，使用当前配置的 access_mode
     ///
     /// # Arguments
@@ -110,11 +294,40 @@ impl DriverManager {
     /// # Returns
     /// * `Ok(())` 如果读取成功（对于部分读取检查 page_status）
     /// * `Err` 如果操作失败
+    ///
+    /// 句柄失效期间的读取错误是 `StaleBindingError`。
+    /// BindProc 模式下读取失败会通过驱动路径重试一次，见 `ReadFallback`
     pub fn read_memory_unified(
         &self,
         addr: u64,
         buf: &mut [u8],
         page_status: Option<&mut PageStatusBitmap>,
+    ) -> anyhow::Result<()> {
+        #[cfg(test)]
+        if let Some(memory) = &self.test_memory {
+            if let Some(gate) = &self.test_read_gate {
+                gate.wait(addr);
+            }
+            let memory = memory.lock().unwrap();
+            return match page_status {
+                Some(status) => memory.mem_read_with_status(addr, buf, status),
+                None => memory.mem_read_into(addr, buf),
+            };
+        }
+        let result = if self.access_mode.uses_bind_proc() {
+            self.read_fallback.read(self, addr, buf, page_status)
+        } else {
+            self.read_memory_raw(addr, buf, page_status)
+        };
+        self.bind_health.record_read(result.is_ok());
+        result.map_err(|e| self.bind_health.classify_read_error(e, self.bound_pid))
+    }
+
+    fn read_memory_raw(
+        &self,
+        addr: u64,
+        buf: &mut [u8],
+        page_status: Option<&mut PageStatusBitmap>,
     ) -> anyhow::Result<()> {
         // Strip ARM MTE tags (bits 56-63) — they don't participate in page table mapping
         let addr = addr & 0x0000_FFFF_FFFF_FFFF;
@@ -149,11 +362,7 @@ impl DriverManager {
             },
             MemoryAccessMode::PageFault => {
                 // 缺页模式：通过 driver 正常读取（不跟踪页状态）
-                let driver = self
-                    .get_driver()
-                    .ok_or_else(|| anyhow::anyhow!("Driver not initialized"))?;
-                let pid = self.get_bound_pid();
-                driver.read_memory(pid, addr as usize, buf.as_mut_ptr() as usize, buf.len())?;
+                self.read_driver(addr, buf)?;
 
                 // 标记所有页为成功，因为这个方法不跟踪每页状态
                 if let Some(status) = page_status {
@@ -163,16 +372,127 @@ impl DriverManager {
             },
             MemoryAccessMode::NonCacheable | MemoryAccessMode::WriteThrough | MemoryAccessMode::Normal => {
                 // 使用 bind_proc 和配置的 access_mode
-                let bind_proc = self
-                    .get_bound_process()
-                    .ok_or_else(|| anyhow::anyhow!("Process not bound"))?;
-                bind_proc.read_memory(addr as usize, buf, page_status)
+                self.read_bound(addr, buf, page_status)
             },
         }
     }
 
+    /// 带 QoS 等级的内存读取，扫描等批量读取使用 `AccessQos::Bulk`，
+    /// 前台交互式读取使用 `AccessQos::Interactive`
+    ///
+    /// 节流只发生在进入驱动之前（见 `MemoryQos`），驱动里排队的读取仍按到达顺序完成
+    pub fn read_memory_with_qos(
+        &self,
+        addr: u64,
+        buf: &mut [u8],
+        page_status: Option<&mut PageStatusBitmap>,
+        qos: AccessQos,
+    ) -> anyhow::Result<()> {
+        MEMORY_QOS.run(qos, || self.read_memory_unified(addr, buf, page_status))
+    }
+
+    /// 读取指定进程的内存。绑定进程（或 pid 为 0）等同于 `read_memory_unified`；
+    /// 其他进程有次要句柄时走句柄，失败或没有句柄时用显式 pid 通过驱动读取（物理模式下读物理内存）
+    pub fn read_memory_of(
+        &self,
+        pid: i32,
+        addr: u64,
+        buf: &mut [u8],
+        page_status: Option<&mut PageStatusBitmap>,
+    ) -> anyhow::Result<()> {
+        if pid == 0 || pid == self.bound_pid {
+            return self.read_memory_unified(addr, buf, page_status);
+        }
+        #[cfg(test)]
+        if let Some(memory) = self.test_processes.get(&pid) {
+            let memory = memory.lock().unwrap();
+            return match page_status {
+                Some(status) => memory.mem_read_with_status(addr, buf, status),
+                None => memory.mem_read_into(addr, buf),
+            };
+        }
+        let addr = addr & 0x0000_FFFF_FFFF_FFFF;
+        if self.access_mode.uses_bind_proc() {
+            self.secondary.read(self, pid, addr, buf, page_status)
+        } else {
+            self.read_pid(pid, addr, buf, page_status)
+        }
+    }
+
+    /// 带 QoS 等级读取 `target` 指定的进程，None 表示绑定进程
+    pub fn read_target_with_qos(
+        &self,
+        target: Option<i32>,
+        addr: u64,
+        buf: &mut [u8],
+        page_status: Option<&mut PageStatusBitmap>,
+        qos: AccessQos,
+    ) -> anyhow::Result<()> {
+        match target {
+            Some(pid) => MEMORY_QOS.run(qos, || self.read_memory_of(pid, addr, buf, page_status)),
+            None => self.read_memory_with_qos(addr, buf, page_status, qos),
+        }
+    }
+
+    /// 读取一段内存窗口，部分页不可读时不失败，而是在 `page_valid` 中标出
+    pub fn read_memory_window(&self, addr: u64, size: usize) -> anyhow::Result<MemoryWindow> {
+        let mut data = vec![0u8; size];
+        let mut page_status = PageStatusBitmap::new(size, addr as usize);
+        self.read_memory_with_qos(addr, &mut data, Some(&mut page_status), AccessQos::Interactive)?;
+        Ok(window_from_status(addr, data, &page_status, *PAGE_SIZE))
+    }
+
+    /// 把一段内存窗口读入调用方的缓冲区，见 `read_window_into`
+    pub fn read_memory_window_into(&self, addr: u64, out: &mut [u8], staging: &mut Vec<u8>, page_valid: &mut Vec<bool>) -> anyhow::Result<usize> {
+        read_window_into(addr, out, staging, page_valid, *PAGE_SIZE, |addr, buf, status| {
+            self.read_memory_with_qos(addr, buf, Some(status), AccessQos::Interactive)
+        })
+    }
+
+    /// 读取 UE FString，字符数（含结尾 NUL）超过 max_len 时返回错误
+    pub fn read_fstring(&self, addr: u64, max_len: usize) -> anyhow::Result<String> {
+        read_fstring_with(addr as usize, max_len, true, |va, buf, status| {
+            self.read_memory_with_qos(va as u64, buf, Some(status), AccessQos::Interactive)
+        })
+    }
+
+    /// 读取 NUL 结尾的 ANSI/UTF-8 字符串，最多 max_len 字节
+    pub fn read_cstring(&self, addr: u64, max_len: usize) -> anyhow::Result<String> {
+        read_cstring_with(addr as usize, max_len, |va, buf, status| {
+            self.read_memory_with_qos(va as u64, buf, Some(status), AccessQos::Interactive)
+        })
+    }
+
+    /// 丢弃绑定进程的区域缓存，下一次使用时重新查询
+    pub fn invalidate_region_cache(&self) {
+        invalidate_region_map();
+    }
+
+    /// 按缓存的区域列表判断每个地址所在区域的访问权限
+    pub fn classify_addresses(&self, addrs: &[u64]) -> anyhow::Result<Vec<PageAccess>> {
+        let map = current_region_map(self)?;
+        Ok(addrs.iter().map(|&addr| map.classify(addr)).collect())
+    }
+
+    /// 进程加载的文件列表：按路径合并 `query_mem_regions` 的文件映射，按基址排序
+    pub fn list_modules(&self, pid: i32) -> anyhow::Result<Vec<ModuleInfo>> {
+        Ok(RegionMap::query(self, pid)?.modules())
+    }
+
+    /// 地址所在的区域（名称、区域内偏移、权限），不在任何区域内或没有绑定进程时返回 None
+    pub fn resolve_address(&self, addr: u64) -> Option<ResolvedAddress> {
+        resolve_addresses(self, &[addr]).ok()?.pop().flatten()
+    }
+
+    /// 批量解析地址所在的区域，找不到区域且缓存过旧时重新查询一次区域列表
+    pub fn resolve_addresses(&self, addrs: &[u64]) -> anyhow::Result<Vec<Option<ResolvedAddress>>> {
+        resolve_addresses(self, addrs)
+    }
+
     /// 统一的内存写入方法，使用当前配置的 access_mode
     ///
+    /// 物理内存模式之外，目标落在缓存中不可写的区域时不写入，返回 `PageNotWritableError`。
+    ///
     /// # Arguments
     /// * `addr` - 要写入的虚拟地址
     /// * `buf` - 写入数据缓冲区
@@ -185,8 +505,18 @@ impl DriverManager {
         addr: u64,
         buf: &[u8],
     ) -> anyhow::Result<()> {
+        #[cfg(test)]
+        if let Some(memory) = &self.test_memory {
+            return memory.lock().unwrap().mem_write(addr, buf);
+        }
         // Strip ARM MTE tags (bits 56-63) — they don't participate in page table mapping
         let addr = addr & 0x0000_FFFF_FFFF_FFFF;
+        // 物理内存写入不经过页表权限；拿不到区域列表时不做判断
+        if self.access_mode != MemoryAccessMode::None
+            && let Ok(map) = current_region_map(self)
+        {
+            map.check_writable(addr, buf.len())?;
+        }
         match self.access_mode {
             MemoryAccessMode::None => {
                 // 物理内存写入（绕过 access_mode）
@@ -225,4 +555,193 @@ impl DriverManager {
             },
         }
     }
+
+    /// 原子组写入：所有目标在一个窗口内连续写入，失败或窗口超过 max_window_us 时回滚
+    ///
+    /// 写入前读取全部原值做预校验（物理内存模式下还会确认每一页都能翻译），
+    /// 写入在一个提升到最高优先级的专用线程上进行以减少被抢占。
+    pub fn write_atomic_group(&self, writes: &[(u64, Vec<u8>)], max_window_us: u64) -> anyhow::Result<AtomicWriteReport> {
+        if !self.is_process_bound() {
+            return Err(anyhow::anyhow!("Process not bound"));
+        }
+
+        let read = |addr: u64, buf: &mut [u8]| -> anyhow::Result<()> {
+            if self.access_mode == MemoryAccessMode::None {
+                let driver = self.get_driver().ok_or_else(|| anyhow::anyhow!("Driver not initialized"))?;
+                let page_size = *PAGE_SIZE as u64;
+                let start = addr & 0x0000_FFFF_FFFF_FFFF;
+                let mut page = start & !(page_size - 1);
+                while page < start + buf.len() as u64 {
+                    driver.addr_translate(self.bound_pid, page as usize)?;
+                    page += page_size;
+                }
+            }
+            self.read_memory_with_qos(addr, buf, None, AccessQos::Interactive)
+        };
+        let write = |addr: u64, buf: &[u8]| self.write_memory_unified(addr, buf);
+
+        std::thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    raise_current_thread_priority();
+                    write_atomic_group_with(writes, max_window_us, read, write)
+                })
+                .join()
+                .map_err(|_| anyhow::anyhow!("Atomic write thread panicked"))?
+        })
+    }
+}
+
+impl ReadPaths for DriverManager {
+    fn read_bound(&self, addr: u64, buf: &mut [u8], page_status: Option<&mut PageStatusBitmap>) -> anyhow::Result<()> {
+        let bind_proc = self
+            .get_bound_process()
+            .ok_or_else(|| anyhow::anyhow!("Process not bound"))?;
+        bind_proc.read_memory((addr & 0x0000_FFFF_FFFF_FFFF) as usize, buf, page_status)
+    }
+
+    fn read_driver(&self, addr: u64, buf: &mut [u8]) -> anyhow::Result<()> {
+        let driver = self
+            .get_driver()
+            .ok_or_else(|| anyhow::anyhow!("Driver not initialized"))?;
+        let addr = addr & 0x0000_FFFF_FFFF_FFFF;
+        driver.read_memory(self.get_bound_pid(), addr as usize, buf.as_mut_ptr() as usize, buf.len())?;
+        Ok(())
+    }
+}
+
+impl PidReadPaths for DriverManager {
+    type Handle = BindProc;
+
+    fn read_handle(&self, handle: &BindProc, addr: u64, buf: &mut [u8], page_status: Option<&mut PageStatusBitmap>) -> anyhow::Result<()> {
+        handle.read_memory(addr as usize, buf, page_status)
+    }
+
+    fn read_pid(&self, pid: i32, addr: u64, buf: &mut [u8], page_status: Option<&mut PageStatusBitmap>) -> anyhow::Result<()> {
+        let driver = self
+            .get_driver()
+            .ok_or_else(|| anyhow::anyhow!("Driver not initialized"))?;
+        if self.access_mode == MemoryAccessMode::None {
+            let mut temp_status;
+            let status = match page_status {
+                Some(status) => status,
+                None => {
+                    temp_status = PageStatusBitmap::new(buf.len(), addr as usize);
+                    &mut temp_status
+                },
+            };
+            driver.read_physical_memory_with_status(pid, addr as usize, buf.as_mut_ptr() as usize, buf.len(), status)?;
+        } else {
+            driver.read_memory(pid, addr as usize, buf.as_mut_ptr() as usize, buf.len())?;
+            // 驱动路径不跟踪每页状态
+            if let Some(status) = page_status {
+                status.mark_all_success();
+            }
+        }
+        Ok(())
+    }
+}
+
+impl BindTarget for DriverManager {
+    fn process_identity(&self, pid: i32) -> Option<String> {
+        self.process_identity_of(pid)
+    }
+
+    /// 读取模块映射中第一个可读区域的 1 个字节；拿不到映射时无法判断，按有效处理
+    fn validate(&self) -> bool {
+        let Some(probe) = current_region_map(self).ok().and_then(|map| map.probe_address()) else {
+            return true;
+        };
+        let mut byte = [0u8; 1];
+        self.read_memory_raw(probe, &mut byte, None).is_ok()
+    }
+
+    fn is_alive(&self, pid: i32) -> bool {
+        match self.get_driver() {
+            Some(driver) => driver.is_process_alive(pid).unwrap_or(true),
+            None => true,
+        }
+    }
+
+    fn rebind(&mut self, pid: i32) -> anyhow::Result<()> {
+        let driver = self.get_driver().ok_or_else(|| anyhow::anyhow!("Driver not initialized"))?;
+        let bind_proc = driver.bind_process(pid)?;
+        self.attach(bind_proc, pid)?;
+        if let Err(e) = current_region_map(self) {
+            warn!("Failed to refresh region map after re-bind: {:?}", e);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_from_unaligned_status() {
+        let page_size = *PAGE_SIZE;
+        // 窗口从第 0 页末尾 16 字节开始，跨过整个第 1 页，结束在第 2 页开头 16 字节
+        let addr = 0x7000_0000 + (page_size - 16) as u64;
+        let size = page_size + 32;
+        let mut status = PageStatusBitmap::new(size, addr as usize);
+        status.mark_success(0);
+        status.mark_success(2);
+
+        let window = window_from_status(addr, vec![0xAB; size], &status, page_size);
+        assert_eq!(window.page_valid, vec![true, false, true]);
+        assert!(window.data[..16].iter().all(|&b| b == 0xAB));
+        assert!(window.data[16..16 + page_size].iter().all(|&b| b == 0));
+        assert!(window.data[16 + page_size..].iter().all(|&b| b == 0xAB));
+
+        // 对齐且只覆盖一页的窗口
+        let mut status = PageStatusBitmap::new(64, 0x7000_0000);
+        status.mark_success(0);
+        let window = window_from_status(0x7000_0000, vec![1; 64], &status, page_size);
+        assert_eq!(window.page_valid, vec![true]);
+        assert_eq!(window.data, vec![1; 64]);
+    }
+
+    #[test]
+    fn test_read_window_into_zero_fills_failed_pages() {
+        let page_size = *PAGE_SIZE;
+        let size = 3 * page_size;
+        // 中间一页读取失败，失败页的内容是驱动留下的垃圾
+        let read = |_addr: u64, buf: &mut [u8], status: &mut PageStatusBitmap| {
+            buf.fill(0xCD);
+            status.mark_success(0);
+            status.mark_success(2);
+            Ok(())
+        };
+
+        // 不按页对齐的目标缓冲区经过中转缓冲区
+        let mut backing = vec![0u8; size + 1];
+        let mut staging = Vec::new();
+        let mut page_valid = Vec::new();
+        let out = &mut backing[1..];
+        let valid = read_window_into(0x7000_0000, out, &mut staging, &mut page_valid, page_size, read).unwrap();
+        assert_eq!(valid, 2 * page_size);
+        assert_eq!(page_valid, vec![true, false, true]);
+        assert!(out[page_size..2 * page_size].iter().all(|&b| b == 0));
+        assert!(out[..page_size].iter().chain(&out[2 * page_size..]).all(|&b| b == 0xCD));
+        assert_eq!(staging.len(), size);
+
+        // 按页对齐的目标缓冲区直接读取，不使用中转缓冲区
+        let layout = std::alloc::Layout::from_size_align(size, page_size).unwrap();
+        let ptr = unsafe { std::alloc::alloc_zeroed(layout) };
+        let aligned = unsafe { std::slice::from_raw_parts_mut(ptr, size) };
+        let mut staging = Vec::new();
+        let valid = read_window_into(0x7000_0000, aligned, &mut staging, &mut page_valid, page_size, read).unwrap();
+        assert_eq!(valid, 2 * page_size);
+        assert!(staging.is_empty());
+        assert!(aligned[page_size..2 * page_size].iter().all(|&b| b == 0));
+        unsafe { std::alloc::dealloc(ptr, layout) };
+
+        // 整体读取失败时返回错误，不留下上一次的有效性
+        let failed = read_window_into(0x7000_0000, &mut backing[1..], &mut Vec::new(), &mut page_valid, page_size, |_, _, _| {
+            Err(anyhow::anyhow!("read failed"))
+        });
+        assert!(failed.is_err());
+        assert!(page_valid.is_empty());
+    }
 }
@@ -1,16 +1,71 @@
 //! Freeze Manager - 内存值冻结管理器
 //!
 //! 使用 tokio 实现高精度定时写入，将冻结的地址值持续写入目标进程内存。
+//!
+//! 每个条目记录创建时绑定的 pid。绑定的进程变化后（重新附加、切换游戏），
+//! pid 不匹配的条目转为孤立状态：暂停写入但不删除，等待 `rebind_entries` 映射到新地址。
+//! 用户主动解绑（`nativeUnbindProcess`）时该进程的条目由 `clear_for_pid` 直接清除；驱动不可用时冻结循环自行退出。
+//!
+//! 目标进程退出时，一轮写入会全部失败。此时询问驱动进程是否存活，确认退出后暂停写入，
+//! 不再累计失败次数，直到进程恢复存活或绑定了新的进程。看门狗解绑已退出的进程（`bind_health::release_dead_process`）
+//! 时调用 `suspend_for_pid`，条目转为孤立而不是清除，进程重启并按进程名重新绑定后可以用 `rebind_entries` 恢复。
+//!
+//! 条目可以有自己的写入间隔，没有时使用管理器的全局间隔。循环每次醒来只写入到期的条目，
+//! 然后睡到最早的下一个到期时间。写入失败按条目累计，UI 据此标出失效的冻结。
 
 use crate::core::globals::DRIVER_MANAGER;
+use anyhow::Result;
 use dashmap::DashMap;
-use log::{debug, error, warn};
+use log::{debug, error, info, warn};
+use serde::Serialize;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::sync::Notify;
 use tokio::task::JoinHandle;
 
+/// 同一条目两次写入失败日志之间的最小间隔
+const FAILURE_LOG_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 指定间隔添加冻结时的默认间隔
+pub const DEFAULT_FREEZE_INTERVAL_MS: u64 = 50;
+
+/// 冻结循环两次醒来之间的最短间隔，避免极小的条目间隔占满一个线程
+const MIN_WAKE_INTERVAL: Duration = Duration::from_millis(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FreezeState {
+    Active,
+    /// 创建条目的进程已不是当前绑定的进程，暂停写入
+    Orphaned,
+    /// 用户暂停（例如从其他工具导入的冻结项），`resume_frozen` 后开始写入
+    Paused,
+}
+
+/// 按条目限制写入失败日志的频率
+#[derive(Clone, Default)]
+struct FailureLog {
+    last_logged: Option<Instant>,
+    suppressed: u32,
+}
+
+impl FailureLog {
+    /// 是否应该输出这次失败，返回自上次输出后被抑制的次数
+    fn should_log(&mut self, now: Instant) -> Option<u32> {
+        match self.last_logged {
+            Some(last) if now.duration_since(last) < FAILURE_LOG_INTERVAL => {
+                self.suppressed += 1;
+                None
+            },
+            _ => {
+                self.last_logged = Some(now);
+                Some(std::mem::take(&mut self.suppressed))
+            },
+        }
+    }
+}
+
 /// 冻结条目
 #[derive(Clone)]
 pub struct FrozenEntry {
@@ -18,18 +73,87 @@ pub struct FrozenEntry {
     pub value: Vec<u8>,
     /// 值类型 ID（用于调试/日志）
     pub value_type: i32,
+    /// 创建条目时绑定的进程
+    pub pid: i32,
+    pub state: FreezeState,
+    /// 写入间隔，None 时使用管理器的全局间隔
+    pub interval: Option<Duration>,
+    /// 累计写入失败次数
+    pub failed_writes: u64,
+    /// 连续写入失败次数，成功写入后清零
+    pub consecutive_failures: u32,
+    /// 下一次应写入的时间，None 表示立即写入
+    next_due: Option<Instant>,
+    failure_log: FailureLog,
+}
+
+impl FrozenEntry {
+    fn new(value: Vec<u8>, value_type: i32, pid: i32) -> Self {
+        Self::with_state(value, value_type, pid, FreezeState::Active)
+    }
+
+    fn with_state(value: Vec<u8>, value_type: i32, pid: i32, state: FreezeState) -> Self {
+        Self {
+            value,
+            value_type,
+            pid,
+            state,
+            interval: None,
+            failed_writes: 0,
+            consecutive_failures: 0,
+            next_due: None,
+            failure_log: FailureLog::default(),
+        }
+    }
+}
+
+/// 冻结条目信息（供 UI 列出）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FreezeInfo {
+    pub address: u64,
+    pub value: Vec<u8>,
+    pub value_type: i32,
+    pub pid: i32,
+    pub state: FreezeState,
+    /// 条目自己的写入间隔（毫秒），0 表示使用全局间隔
+    pub interval_ms: u64,
+    pub failed_writes: u64,
+    pub consecutive_failures: u32,
+}
+
+/// 孤立条目信息（供 UI 提示重新映射）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OrphanedEntry {
+    pub address: u64,
+    pub value_type: i32,
+    pub pid: i32,
+}
+
+/// 一次冻结写入的统计
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TickStats {
+    pub written: usize,
+    pub failed: usize,
+    /// 实际输出的失败日志条数
+    pub logged: usize,
+    /// 活动条目中最早的下一次写入时间
+    pub next_due: Option<Instant>,
 }
 
 /// 冻结管理器
 pub struct FreezeManager {
     /// 冻结地址映射表：地址 -> 冻结条目
     frozen_entries: Arc<DashMap<u64, FrozenEntry>>,
-    /// 冻结间隔（微秒）
+    /// 全局冻结间隔（微秒），没有指定间隔的条目使用
     interval_us: Arc<AtomicU64>,
+    /// 所有条目累计的写入失败次数
+    failed_writes: Arc<AtomicU64>,
     /// 是否正在运行
     running: Arc<AtomicBool>,
     /// 用于通知任务停止
     stop_notify: Arc<Notify>,
+    /// 绑定的进程已退出，暂停写入
+    process_paused: Arc<AtomicBool>,
     /// 后台任务句柄
     task_handle: Option<JoinHandle<()>>,
 }
@@ -39,8 +163,10 @@ impl FreezeManager {
         Self {
             frozen_entries: Arc::new(DashMap::new()),
             interval_us: Arc::new(AtomicU64::new(33000)), // 默认 33ms
+            failed_writes: Arc::new(AtomicU64::new(0)),
             running: Arc::new(AtomicBool::new(false)),
             stop_notify: Arc::new(Notify::new()),
+            process_paused: Arc::new(AtomicBool::new(false)),
             task_handle: None,
         }
     }
@@ -55,8 +181,10 @@ impl FreezeManager {
 
         let entries = Arc::clone(&self.frozen_entries);
         let interval_us = Arc::clone(&self.interval_us);
+        let failed_writes = Arc::clone(&self.failed_writes);
         let running = Arc::clone(&self.running);
         let stop_notify = Arc::clone(&self.stop_notify);
+        let process_paused = Arc::clone(&self.process_paused);
 
         let handle = tokio::spawn(async move {
             debug!("FreezeManager: 冻结循环已启动");
@@ -70,14 +198,30 @@ impl FreezeManager {
                 // 获取当前间隔
                 let interval = Duration::from_micros(interval_us.load(Ordering::Relaxed));
 
-                // 执行冻结写入
+                // 执行冻结写入，睡到最早到期的条目
+                let mut wait = interval;
                 if !entries.is_empty() {
-                    Self::write_frozen_values(&entries);
+                    let now = Instant::now();
+                    match Self::write_frozen_values(&entries, &process_paused, now, interval) {
+                        Some(stats) => {
+                            failed_writes.fetch_add(stats.failed as u64, Ordering::Relaxed);
+                            if let Some(next_due) = stats.next_due {
+                                wait = next_due
+                                    .saturating_duration_since(now)
+                                    .clamp(MIN_WAKE_INTERVAL, interval.max(MIN_WAKE_INTERVAL));
+                            }
+                        },
+                        None => {
+                            warn!("FreezeManager: 驱动不可用，冻结循环退出");
+                            running.store(false, Ordering::SeqCst);
+                            break;
+                        },
+                    }
                 }
 
                 // 等待间隔或停止信号
                 tokio::select! {
-                    _ = tokio::time::sleep(interval) => {},
+                    _ = tokio::time::sleep(wait) => {},
                     _ = stop_notify.notified() => {
                         if !running.load(Ordering::SeqCst) {
                             break;
@@ -110,34 +254,237 @@ impl FreezeManager {
         }
     }
 
-    /// 写入所有冻结值
-    fn write_frozen_values(entries: &DashMap<u64, FrozenEntry>) {
+    /// 写入到期的冻结值，驱动不可用时返回 None
+    fn write_frozen_values(entries: &DashMap<u64, FrozenEntry>, process_paused: &AtomicBool, now: Instant, default_interval: Duration) -> Option<TickStats> {
         let manager = match DRIVER_MANAGER.read() {
             Ok(m) => m,
             Err(e) => {
                 error!("FreezeManager: 无法获取 DRIVER_MANAGER 读锁: {}", e);
-                return;
+                return Some(TickStats::default());
             },
         };
 
+        if !manager.is_driver_loaded() {
+            return None;
+        }
         if !manager.is_process_bound() {
-            return;
+            return Some(TickStats::default());
         }
 
-        for entry in entries.iter() {
+        let pid = manager.get_bound_pid();
+        // 驱动查询失败时按存活处理，避免误暂停
+        let is_alive = || manager.get_driver().is_none_or(|driver| driver.is_process_alive(pid).unwrap_or(true));
+        Some(Self::tick_guarded(
+            entries,
+            process_paused,
+            pid,
+            now,
+            default_interval,
+            is_alive,
+            |addr, value| manager.write_memory_unified(addr, value),
+        ))
+    }
+
+    /// 带进程存活检查的一轮写入
+    ///
+    /// 暂停期间每轮先询问进程是否存活，仍已退出时不写入；一轮写入全部失败时确认一次，
+    /// 进程已退出则暂停。暂停和恢复只各输出一次日志。
+    fn tick_guarded<A, W>(
+        entries: &DashMap<u64, FrozenEntry>,
+        process_paused: &AtomicBool,
+        bound_pid: i32,
+        now: Instant,
+        default_interval: Duration,
+        mut is_alive: A,
+        write: W,
+    ) -> TickStats
+    where
+        A: FnMut() -> bool,
+        W: FnMut(u64, &[u8]) -> Result<()>,
+    {
+        if process_paused.load(Ordering::Acquire) {
+            if !is_alive() {
+                return TickStats::default();
+            }
+            info!("FreezeManager: 进程 {} 恢复存活，继续冻结写入", bound_pid);
+            process_paused.store(false, Ordering::Release);
+        }
+
+        let stats = Self::tick_with(entries, bound_pid, now, default_interval, write);
+        if stats.written == 0 && stats.failed > 0 && !is_alive() {
+            warn!("FreezeManager: 进程 {} 已退出，暂停冻结写入", bound_pid);
+            process_paused.store(true, Ordering::Release);
+            // 进程退出导致的失败不计入累计失败次数
+            return TickStats { failed: 0, ..stats };
+        }
+        stats
+    }
+
+    /// 按当前绑定的 pid 写入一轮到期的条目：pid 不匹配的条目转为孤立并跳过，失败日志按条目限频
+    fn tick_with<W>(entries: &DashMap<u64, FrozenEntry>, bound_pid: i32, now: Instant, default_interval: Duration, mut write: W) -> TickStats
+    where
+        W: FnMut(u64, &[u8]) -> Result<()>,
+    {
+        let mut stats = TickStats::default();
+        for mut entry in entries.iter_mut() {
             let addr = *entry.key();
-            let frozen = entry.value();
+            let frozen = entry.value_mut();
+
+            if frozen.state != FreezeState::Active {
+                continue;
+            }
+            if frozen.pid != bound_pid {
+                info!("FreezeManager: 0x{:X} 的进程 {} 已不再绑定，转为孤立", addr, frozen.pid);
+                frozen.state = FreezeState::Orphaned;
+                continue;
+            }
+
+            if let Some(due) = frozen.next_due
+                && due > now
+            {
+                stats.next_due = Some(stats.next_due.map_or(due, |next| next.min(due)));
+                continue;
+            }
+            let due = now + frozen.interval.unwrap_or(default_interval);
+            frozen.next_due = Some(due);
+            stats.next_due = Some(stats.next_due.map_or(due, |next| next.min(due)));
+
+            match write(addr, &frozen.value) {
+                Ok(()) => {
+                    stats.written += 1;
+                    frozen.consecutive_failures = 0;
+                },
+                Err(e) => {
+                    stats.failed += 1;
+                    frozen.failed_writes += 1;
+                    frozen.consecutive_failures += 1;
+                    if let Some(suppressed) = frozen.failure_log.should_log(now) {
+                        stats.logged += 1;
+                        warn!("FreezeManager: 写入地址 0x{:X} 失败: {}（期间抑制 {} 条）", addr, e, suppressed);
+                    }
+                },
+            }
+        }
+        stats
+    }
+
+    /// 添加冻结地址，条目归属于 pid
+    pub fn add_frozen(&self, address: u64, value: Vec<u8>, value_type: i32, pid: i32) {
+        debug!("FreezeManager: 添加冻结 addr=0x{:X}, type={}, len={}, pid={}", address, value_type, value.len(), pid);
+        self.frozen_entries.insert(address, FrozenEntry::new(value, value_type, pid));
+    }
+
+    /// 添加使用自己写入间隔的冻结地址
+    pub fn add_frozen_with_interval(&self, address: u64, value: Vec<u8>, value_type: i32, pid: i32, interval: Duration) {
+        debug!("FreezeManager: 添加冻结 addr=0x{:X}, len={}, pid={}, interval={:?}", address, value.len(), pid, interval);
+        let mut entry = FrozenEntry::new(value, value_type, pid);
+        entry.interval = Some(interval);
+        self.frozen_entries.insert(address, entry);
+    }
+
+    /// 添加暂停状态的冻结地址，不写入，直到 `resume_frozen`
+    pub fn add_frozen_paused(&self, address: u64, value: Vec<u8>, value_type: i32, pid: i32) {
+        debug!("FreezeManager: 添加暂停的冻结 addr=0x{:X}, type={}, len={}, pid={}", address, value_type, value.len(), pid);
+        self.frozen_entries.insert(address, FrozenEntry::with_state(value, value_type, pid, FreezeState::Paused));
+    }
+
+    /// 恢复暂停的冻结条目，条目不存在或不是暂停状态时返回 false
+    pub fn resume_frozen(&self, address: u64) -> bool {
+        match self.frozen_entries.get_mut(&address) {
+            Some(mut entry) if entry.state == FreezeState::Paused => {
+                entry.state = FreezeState::Active;
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// 地址是否是暂停的冻结条目
+    pub fn is_paused(&self, address: u64) -> bool {
+        self.frozen_entries.get(&address).is_some_and(|e| e.state == FreezeState::Paused)
+    }
+
+    /// 获取条目的副本
+    pub fn get_entry(&self, address: u64) -> Option<FrozenEntry> {
+        self.frozen_entries.get(&address).map(|e| e.clone())
+    }
 
-            if let Err(e) = manager.write_memory_unified(addr, &frozen.value) {
-                warn!("FreezeManager: 写入地址 0x{:X} 失败: {}", addr, e);
+    /// 绑定的进程变化后立即把不属于 bound_pid 的条目转为孤立，返回孤立条目数
+    ///
+    /// 重新绑定后解除因进程退出造成的暂停。
+    pub fn sync_with_process(&self, bound_pid: i32) -> usize {
+        self.process_paused.store(false, Ordering::Release);
+        for mut entry in self.frozen_entries.iter_mut() {
+            if entry.pid != bound_pid {
+                entry.state = FreezeState::Orphaned;
             }
         }
+        self.get_orphan_count()
     }
 
-    /// 添加冻结地址
-    pub fn add_frozen(&self, address: u64, value: Vec<u8>, value_type: i32) {
-        debug!("FreezeManager: 添加冻结 addr=0x{:X}, type={}, len={}", address, value_type, value.len());
-        self.frozen_entries.insert(address, FrozenEntry { value, value_type });
+    /// 冻结写入是否因绑定的进程退出而暂停
+    pub fn is_process_paused(&self) -> bool {
+        self.process_paused.load(Ordering::Acquire)
+    }
+
+    /// 绑定的进程退出后保留它的条目并转为孤立，进程重启后可以重新映射，返回孤立的数量
+    pub fn suspend_for_pid(&self, pid: i32) -> usize {
+        let mut suspended = 0;
+        for mut entry in self.frozen_entries.iter_mut() {
+            if entry.pid == pid && entry.state == FreezeState::Active {
+                entry.state = FreezeState::Orphaned;
+                suspended += 1;
+            }
+        }
+        debug!("FreezeManager: 进程 {} 已退出，{} 个冻结转为孤立", pid, suspended);
+        suspended
+    }
+
+    /// 获取孤立条目数量
+    pub fn get_orphan_count(&self) -> usize {
+        self.frozen_entries.iter().filter(|e| e.state == FreezeState::Orphaned).count()
+    }
+
+    /// 列出孤立条目
+    pub fn list_orphaned(&self) -> Vec<OrphanedEntry> {
+        let mut orphans: Vec<_> = self
+            .frozen_entries
+            .iter()
+            .filter(|e| e.state == FreezeState::Orphaned)
+            .map(|e| OrphanedEntry {
+                address: *e.key(),
+                value_type: e.value_type,
+                pid: e.pid,
+            })
+            .collect();
+        orphans.sort_by_key(|o| o.address);
+        orphans
+    }
+
+    /// 把孤立条目按 (旧地址, 新地址) 映射到 pid 重新激活，返回重新激活的数量
+    ///
+    /// 映射里没有出现的孤立条目保持孤立。
+    pub fn rebind_entries(&self, mapping: &[(u64, u64)], pid: i32) -> usize {
+        let mut rebound = 0;
+        for &(old_addr, new_addr) in mapping {
+            let Some((_, entry)) = self
+                .frozen_entries
+                .remove_if(&old_addr, |_, e| e.state == FreezeState::Orphaned)
+            else {
+                continue;
+            };
+            debug!("FreezeManager: 重新映射 0x{:X} -> 0x{:X} (pid {} -> {})", old_addr, new_addr, entry.pid, pid);
+            let mut rebound_entry = FrozenEntry::new(entry.value, entry.value_type, pid);
+            rebound_entry.interval = entry.interval;
+            self.frozen_entries.insert(new_addr, rebound_entry);
+            rebound += 1;
+        }
+        rebound
+    }
+
+    /// 丢弃所有孤立条目
+    pub fn clear_orphaned(&self) {
+        self.frozen_entries.retain(|_, e| e.state != FreezeState::Orphaned);
     }
 
     /// 移除冻结地址
@@ -152,25 +499,63 @@ impl FreezeManager {
         self.frozen_entries.clear();
     }
 
+    /// 解绑进程时清除属于该进程的条目，其他进程的孤立条目保留，返回清除的数量
+    pub fn clear_for_pid(&self, pid: i32) -> usize {
+        let before = self.frozen_entries.len();
+        self.frozen_entries.retain(|_, e| e.pid != pid);
+        let cleared = before - self.frozen_entries.len();
+        debug!("FreezeManager: 解绑进程 {}，清除 {} 个冻结", pid, cleared);
+        cleared
+    }
+
+    /// 列出所有条目（含暂停和孤立），按地址排序
+    pub fn list_entries(&self) -> Vec<FreezeInfo> {
+        let mut entries: Vec<_> = self
+            .frozen_entries
+            .iter()
+            .map(|e| FreezeInfo {
+                address: *e.key(),
+                value: e.value.clone(),
+                value_type: e.value_type,
+                pid: e.pid,
+                state: e.state,
+                interval_ms: e.interval.map_or(0, |interval| interval.as_millis() as u64),
+                failed_writes: e.failed_writes,
+                consecutive_failures: e.consecutive_failures,
+            })
+            .collect();
+        entries.sort_by_key(|e| e.address);
+        entries
+    }
+
+    /// 所有条目累计的写入失败次数（包括已经移除的条目）
+    pub fn get_failed_write_count(&self) -> u64 {
+        self.failed_writes.load(Ordering::Relaxed)
+    }
+
     /// 设置冻结间隔（微秒）
     pub fn set_interval(&self, microseconds: u64) {
         debug!("FreezeManager: 设置间隔 {} μs", microseconds);
         self.interval_us.store(microseconds, Ordering::Relaxed);
     }
 
-    /// 获取冻结数量
+    /// 获取冻结数量（不含孤立条目）
     pub fn get_frozen_count(&self) -> usize {
-        self.frozen_entries.len()
+        self.frozen_entries.iter().filter(|e| e.state == FreezeState::Active).count()
     }
 
-    /// 检查地址是否被冻结
+    /// 检查地址是否被冻结（孤立条目不算）
     pub fn is_frozen(&self, address: u64) -> bool {
-        self.frozen_entries.contains_key(&address)
+        self.frozen_entries.get(&address).is_some_and(|e| e.state == FreezeState::Active)
     }
 
-    /// 获取所有冻结的地址
+    /// 获取所有冻结的地址（不含孤立条目）
     pub fn get_frozen_addresses(&self) -> Vec<u64> {
-        self.frozen_entries.iter().map(|e| *e.key()).collect()
+        self.frozen_entries
+            .iter()
+            .filter(|e| e.state == FreezeState::Active)
+            .map(|e| *e.key())
+            .collect()
     }
 }
 
@@ -179,3 +564,231 @@ impl Drop for FreezeManager {
         self.stop();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use std::collections::HashMap;
+
+    const OLD_PID: i32 = 1000;
+    const NEW_PID: i32 = 2000;
+    const TICK: Duration = Duration::from_millis(33);
+
+    fn recording_writer(log: &mut HashMap<u64, usize>) -> impl FnMut(u64, &[u8]) -> Result<()> + '_ {
+        |addr, _| {
+            *log.entry(addr).or_default() += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_paused_entries_are_not_written_until_resumed() {
+        let manager = FreezeManager::new();
+        manager.add_frozen_paused(0x1000, vec![9, 9, 9, 9], 4, OLD_PID);
+        manager.add_frozen(0x2000, vec![1, 2, 3, 4], 4, OLD_PID);
+        assert!(manager.is_paused(0x1000));
+        assert!(!manager.is_frozen(0x1000));
+        assert_eq!(manager.get_frozen_count(), 1);
+        assert_eq!(manager.get_entry(0x1000).map(|e| e.value), Some(vec![9, 9, 9, 9]));
+
+        let mut writes = HashMap::new();
+        let now = Instant::now();
+        let stats = FreezeManager::tick_with(&manager.frozen_entries, OLD_PID, now, TICK, recording_writer(&mut writes));
+        assert_eq!(stats.written, 1);
+        assert!(!writes.contains_key(&0x1000));
+
+        // 只有暂停的条目能恢复
+        assert!(!manager.resume_frozen(0x2000));
+        assert!(!manager.resume_frozen(0x3000));
+        assert!(manager.resume_frozen(0x1000));
+        assert!(manager.is_frozen(0x1000) && !manager.is_paused(0x1000));
+
+        let stats = FreezeManager::tick_with(&manager.frozen_entries, OLD_PID, now + TICK, TICK, recording_writer(&mut writes));
+        assert_eq!(stats.written, 2);
+        assert_eq!(writes.get(&0x1000), Some(&1));
+    }
+
+    #[test]
+    fn test_reattach_orphans_and_rebind_resumes() {
+        let manager = FreezeManager::new();
+        for addr in [0x1000u64, 0x2000, 0x3000] {
+            manager.add_frozen(addr, vec![1, 2, 3, 4], 2, OLD_PID);
+        }
+
+        let mut writes = HashMap::new();
+        let now = Instant::now();
+        let stats = FreezeManager::tick_with(&manager.frozen_entries, OLD_PID, now, TICK, recording_writer(&mut writes));
+        assert_eq!(stats.written, 3);
+
+        // 重新附加到新进程：所有条目孤立，不再写入
+        writes.clear();
+        let stats = FreezeManager::tick_with(&manager.frozen_entries, NEW_PID, now, TICK, recording_writer(&mut writes));
+        assert_eq!(stats, TickStats::default());
+        assert!(writes.is_empty());
+        assert_eq!(manager.get_orphan_count(), 3);
+        assert_eq!(manager.get_frozen_count(), 0);
+        assert!(!manager.is_frozen(0x1000));
+        let orphans = manager.list_orphaned();
+        assert_eq!(orphans.iter().map(|o| o.address).collect::<Vec<_>>(), vec![0x1000, 0x2000, 0x3000]);
+        assert!(orphans.iter().all(|o| o.pid == OLD_PID && o.value_type == 2));
+
+        // 只映射其中两个
+        let rebound = manager.rebind_entries(&[(0x1000, 0x11000), (0x2000, 0x12000), (0x9000, 0x19000)], NEW_PID);
+        assert_eq!(rebound, 2);
+        assert_eq!(manager.get_orphan_count(), 1);
+        assert!(manager.is_frozen(0x11000) && manager.is_frozen(0x12000));
+
+        let stats = FreezeManager::tick_with(&manager.frozen_entries, NEW_PID, now, TICK, recording_writer(&mut writes));
+        assert_eq!(stats.written, 2);
+        assert_eq!(writes.get(&0x11000), Some(&1));
+        assert_eq!(writes.get(&0x12000), Some(&1));
+        assert!(!writes.contains_key(&0x3000));
+
+        manager.clear_orphaned();
+        assert_eq!(manager.get_orphan_count(), 0);
+        assert_eq!(manager.get_frozen_count(), 2);
+    }
+
+    #[test]
+    fn test_sync_with_process_orphans_immediately() {
+        let manager = FreezeManager::new();
+        manager.add_frozen(0x1000, vec![0; 4], 2, OLD_PID);
+        manager.add_frozen(0x2000, vec![0; 4], 2, NEW_PID);
+        assert_eq!(manager.sync_with_process(NEW_PID), 1);
+        assert_eq!(manager.get_frozen_addresses(), vec![0x2000]);
+        // 已激活的条目不会被重新映射
+        assert_eq!(manager.rebind_entries(&[(0x2000, 0x5000)], NEW_PID), 0);
+    }
+
+    #[test]
+    fn test_failure_log_rate_limited_per_entry() {
+        let manager = FreezeManager::new();
+        manager.add_frozen(0x1000, vec![0; 4], 2, OLD_PID);
+        manager.add_frozen(0x2000, vec![0; 4], 2, OLD_PID);
+        let failing = |_: u64, _: &[u8]| -> Result<()> { Err(anyhow!("page not present")) };
+
+        let start = Instant::now();
+        let mut logged = 0;
+        for tick in 0..10u64 {
+            let now = start + Duration::from_millis(tick * 33);
+            let stats = FreezeManager::tick_with(&manager.frozen_entries, OLD_PID, now, TICK, failing);
+            assert_eq!(stats.failed, 2);
+            logged += stats.logged;
+        }
+        // 每个条目只输出第一次失败
+        assert_eq!(logged, 2);
+
+        let later = start + FAILURE_LOG_INTERVAL + Duration::from_millis(1);
+        let stats = FreezeManager::tick_with(&manager.frozen_entries, OLD_PID, later, TICK, failing);
+        assert_eq!(stats.logged, 2);
+        let suppressed = manager.frozen_entries.get(&0x1000).unwrap().failure_log.suppressed;
+        assert_eq!(suppressed, 0);
+    }
+
+    #[test]
+    fn test_per_entry_interval_schedules_writes() {
+        let manager = FreezeManager::new();
+        manager.add_frozen(0x1000, vec![0; 4], 2, OLD_PID);
+        manager.add_frozen_with_interval(0x2000, vec![0; 4], 2, OLD_PID, Duration::from_millis(100));
+        manager.add_frozen_with_interval(0x3000, vec![0; 4], 2, OLD_PID, Duration::from_millis(10));
+
+        let start = Instant::now();
+        let mut writes = HashMap::new();
+        let mut next_due = None;
+        for ms in (0..=200u64).step_by(10) {
+            let now = start + Duration::from_millis(ms);
+            let stats = FreezeManager::tick_with(&manager.frozen_entries, OLD_PID, now, TICK, recording_writer(&mut writes));
+            next_due = stats.next_due;
+        }
+        // 0..=200ms：10ms 的条目每次都写，33ms 的条目在 0/40/80/120/160/200，100ms 的条目在 0/100/200
+        assert_eq!(writes.get(&0x3000), Some(&21));
+        assert_eq!(writes.get(&0x1000), Some(&6));
+        assert_eq!(writes.get(&0x2000), Some(&3));
+        assert_eq!(next_due, Some(start + Duration::from_millis(210)));
+
+        let listed = manager.list_entries();
+        assert_eq!(
+            listed.iter().map(|e| (e.address, e.interval_ms)).collect::<Vec<_>>(),
+            vec![(0x1000, 0), (0x2000, 100), (0x3000, 10)]
+        );
+    }
+
+    #[test]
+    fn test_failure_counters_and_clear_for_pid() {
+        let manager = FreezeManager::new();
+        manager.add_frozen(0x1000, vec![0; 4], 2, OLD_PID);
+        manager.add_frozen(0x2000, vec![0; 4], 2, OLD_PID);
+        manager.add_frozen(0x3000, vec![0; 4], 2, NEW_PID);
+        // 0x1000 所在的页不可写
+        let flaky = |fail: bool| {
+            move |addr: u64, _: &[u8]| -> Result<()> {
+                if fail && addr == 0x1000 {
+                    return Err(anyhow!("page not present"));
+                }
+                Ok(())
+            }
+        };
+
+        let start = Instant::now();
+        for tick in 0..3u64 {
+            FreezeManager::tick_with(&manager.frozen_entries, OLD_PID, start + TICK * tick as u32, TICK, flaky(true));
+        }
+        let entry = manager.get_entry(0x1000).unwrap();
+        assert_eq!((entry.failed_writes, entry.consecutive_failures), (3, 3));
+        assert_eq!(manager.get_entry(0x2000).unwrap().failed_writes, 0);
+
+        // 成功写入后连续失败清零，累计失败保留
+        FreezeManager::tick_with(&manager.frozen_entries, OLD_PID, start + TICK * 3, TICK, flaky(false));
+        let entry = manager.get_entry(0x1000).unwrap();
+        assert_eq!((entry.failed_writes, entry.consecutive_failures), (3, 0));
+
+        // 解绑只清除该进程的条目
+        assert_eq!(manager.clear_for_pid(OLD_PID), 2);
+        assert_eq!(manager.list_entries().iter().map(|e| e.address).collect::<Vec<_>>(), vec![0x3000]);
+    }
+
+    fn guarded(manager: &FreezeManager, now: Instant, alive: bool, write: impl FnMut(u64, &[u8]) -> Result<()>) -> TickStats {
+        FreezeManager::tick_guarded(&manager.frozen_entries, &manager.process_paused, OLD_PID, now, TICK, || alive, write)
+    }
+
+    #[test]
+    fn test_process_death_pauses_and_rebind_resumes() {
+        let manager = FreezeManager::new();
+        manager.add_frozen(0x1000, vec![0; 4], 2, OLD_PID);
+        manager.add_frozen(0x2000, vec![0; 4], 2, OLD_PID);
+        let failing = |_: u64, _: &[u8]| -> Result<()> { Err(anyhow!("no such process")) };
+
+        // 存活进程上的写入失败不会暂停
+        let start = Instant::now();
+        let stats = guarded(&manager, start, true, failing);
+        assert_eq!(stats.failed, 2);
+        assert!(!manager.is_process_paused());
+
+        // 进程退出：暂停，这一轮的失败不计数，之后不再写入
+        let stats = guarded(&manager, start + TICK, false, failing);
+        assert_eq!(stats.failed, 0);
+        assert!(manager.is_process_paused());
+        let mut writes = HashMap::new();
+        for tick in 2..5u32 {
+            let stats = guarded(&manager, start + TICK * tick, false, recording_writer(&mut writes));
+            assert_eq!(stats, TickStats::default());
+        }
+        assert!(writes.is_empty());
+        assert_eq!(manager.get_entry(0x1000).unwrap().failed_writes, 2);
+
+        // 进程恢复存活后继续写入
+        let stats = guarded(&manager, start + TICK * 5, true, recording_writer(&mut writes));
+        assert_eq!(stats.written, 2);
+        assert!(!manager.is_process_paused());
+
+        // 看门狗解绑后条目转为孤立而不是清除，绑定重启的进程后重新映射
+        manager.process_paused.store(true, Ordering::Release);
+        assert_eq!(manager.suspend_for_pid(OLD_PID), 2);
+        assert_eq!(manager.get_frozen_count(), 0);
+        assert_eq!(manager.sync_with_process(NEW_PID), 2);
+        assert!(!manager.is_process_paused());
+        assert_eq!(manager.rebind_entries(&[(0x1000, 0x11000), (0x2000, 0x12000)], NEW_PID), 2);
+        assert_eq!(manager.get_frozen_addresses().len(), 2);
+    }
+}
@@ -2,8 +2,17 @@
 
 use crate::core::driver_manager::DriverManager;
 use crate::core::freeze_manager::FreezeManager;
+use crate::core::memory_dump::DumpState;
+use crate::core::memory_pressure::MemoryPressureGuard;
+use crate::core::process_list::ProcessCache;
+use crate::core::qos::{DEFAULT_BULK_CONCURRENCY, MemoryQos};
+use crate::core::region_map::RegionMap;
+use crate::core::region_snapshot::RegionSnapshots;
+use crate::core::saved_addresses::SavedAddressManager;
+use crate::core::self_regions::SelfRegions;
+use crate::core::watch_manager::WatchManager;
 use lazy_static::lazy_static;
-use std::sync::RwLock;
+use std::sync::{Arc, Mutex, RwLock};
 use tokio::runtime::Runtime;
 
 lazy_static! {
@@ -12,6 +21,33 @@ lazy_static! {
     /// Global freeze manager for value freezing
     pub static ref FREEZE_MANAGER: RwLock<FreezeManager> = RwLock::new(FreezeManager::new());
 
+    /// Global watch manager for value history sampling
+    pub static ref WATCH_MANAGER: RwLock<WatchManager> = RwLock::new(WatchManager::new());
+
+    /// Saved address list with batched value refresh
+    pub static ref SAVED_ADDRESSES: SavedAddressManager = SavedAddressManager::new();
+
+    /// Global QoS gate for driver memory access
+    pub static ref MEMORY_QOS: MemoryQos = MemoryQos::new(DEFAULT_BULK_CONCURRENCY);
+
+    /// Own RSS tracking, consulted by scans to degrade before the LMK kills the process
+    pub static ref MEMORY_GUARD: MemoryPressureGuard = MemoryPressureGuard::new();
+
+    /// Short-lived process list cache for the process picker
+    pub static ref PROCESS_CACHE: ProcessCache = ProcessCache::default();
+
+    /// Cached region map of the bound process, used for pointer display
+    pub static ref REGION_MAP: RwLock<Option<Arc<RegionMap>>> = RwLock::new(None);
+
+    /// Region list snapshots for diffing memory maps over time
+    pub static ref REGION_SNAPSHOTS: Mutex<RegionSnapshots> = Mutex::new(RegionSnapshots::new());
+
+    /// Our own mappings that physical-read scans must skip
+    pub static ref SELF_REGIONS: SelfRegions = SelfRegions::new();
+
+    /// Progress and cancellation of the current memory dump
+    pub static ref MEMORY_DUMP: DumpState = DumpState::new();
+
     /// Global tokio runtime for async tasks
     /// 使用多线程运行时，worker threads 数量为 CPU 核心数
     pub static ref TOKIO_RUNTIME: Runtime = Runtime::new().expect("Failed to create tokio runtime");
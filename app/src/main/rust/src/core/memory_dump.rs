@@ -0,0 +1,264 @@
+//! Memory dump
+//!
+//! 把一段内存（通常是一个模块）按块读出写入文件。不可读的页写 0，文件偏移和内存地址一一对应，
+//! 可以直接交给反汇编工具按基址加载。
+//!
+//! 转储在调用线程上同步进行，进度和取消通过全局的 `DumpState` 共享；取消后文件保留已写入的部分，
+//! 报告中的 status 为 `DUMP_STATUS_CANCELLED`。
+
+use crate::core::globals::PAGE_SIZE;
+use crate::wuwa::PageStatusBitmap;
+use anyhow::{Result, anyhow};
+use log::{info, warn};
+use serde::Serialize;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+/// 每次读取的块大小
+pub const DUMP_CHUNK_SIZE: usize = 512 * 1024;
+
+/// 整个范围已写入文件
+pub const DUMP_STATUS_COMPLETED: i32 = 0;
+/// 中途取消，文件只包含取消前写入的部分
+pub const DUMP_STATUS_CANCELLED: i32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DumpReport {
+    pub bytes_written: u64,
+    /// 不可读、以 0 填充的页数
+    pub pages_failed: u64,
+    pub status: i32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DumpProgress {
+    pub running: bool,
+    pub bytes_written: u64,
+    pub total: u64,
+    pub pages_failed: u64,
+}
+
+/// 当前转储的进度和取消标志，同一时间只允许一个转储
+#[derive(Debug, Default)]
+pub struct DumpState {
+    running: AtomicBool,
+    cancel: AtomicBool,
+    bytes_written: AtomicU64,
+    total: AtomicU64,
+    pages_failed: AtomicU64,
+}
+
+/// 转储结束（包括出错返回）时清除 running
+struct RunningGuard<'a>(&'a DumpState);
+
+impl Drop for RunningGuard<'_> {
+    fn drop(&mut self) {
+        self.0.running.store(false, Ordering::Release);
+    }
+}
+
+impl DumpState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 请求取消正在进行的转储，当前块写完后停止
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Release);
+    }
+
+    pub fn progress(&self) -> DumpProgress {
+        DumpProgress {
+            running: self.running.load(Ordering::Acquire),
+            bytes_written: self.bytes_written.load(Ordering::Relaxed),
+            total: self.total.load(Ordering::Relaxed),
+            pages_failed: self.pages_failed.load(Ordering::Relaxed),
+        }
+    }
+
+    fn begin(&self, total: u64) -> Result<RunningGuard<'_>> {
+        if self.running.swap(true, Ordering::AcqRel) {
+            return Err(anyhow!("Another memory dump is already running"));
+        }
+        self.cancel.store(false, Ordering::Release);
+        self.bytes_written.store(0, Ordering::Relaxed);
+        self.total.store(total, Ordering::Relaxed);
+        self.pages_failed.store(0, Ordering::Relaxed);
+        Ok(RunningGuard(self))
+    }
+}
+
+/// 把 [start, end) 写入 path
+///
+/// `read` 读取一块内存并在页状态中标出成功的页；整块读取失败时该块全部按不可读处理。
+pub fn dump_to_file<R>(state: &DumpState, start: u64, end: u64, path: &Path, mut read: R) -> Result<DumpReport>
+where
+    R: FnMut(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+{
+    if end <= start {
+        return Err(anyhow!("Invalid dump range: 0x{:X}-0x{:X}", start, end));
+    }
+    let _running = state.begin(end - start)?;
+    let page_size = *PAGE_SIZE as u64;
+    let mut file = File::create(path).map_err(|e| anyhow!("Failed to create {:?}: {}", path, e))?;
+
+    let mut report = DumpReport { bytes_written: 0, pages_failed: 0, status: DUMP_STATUS_COMPLETED };
+    let mut buffer = vec![0u8; DUMP_CHUNK_SIZE];
+    let mut addr = start;
+    while addr < end {
+        if state.cancel.load(Ordering::Acquire) {
+            report.status = DUMP_STATUS_CANCELLED;
+            break;
+        }
+
+        // 第一块截到页边界，之后每块都从页边界开始，跨块的页不会重复计数
+        let head = addr & (page_size - 1);
+        let size = (DUMP_CHUNK_SIZE as u64 - head).min(end - addr) as usize;
+        let chunk = &mut buffer[..size];
+        let mut status = PageStatusBitmap::new(size, addr as usize);
+        let page_count = (head as usize + size).div_ceil(page_size as usize);
+
+        let failed = match read(addr, chunk, &mut status) {
+            Ok(()) => {
+                let mut failed = 0;
+                for page in (0..page_count).filter(|&page| !status.is_page_success(page)) {
+                    let page_start = (page as u64 * page_size).saturating_sub(head) as usize;
+                    let page_end = (((page + 1) as u64 * page_size - head) as usize).min(size);
+                    chunk[page_start..page_end].fill(0);
+                    failed += 1;
+                }
+                failed
+            },
+            Err(_) => {
+                chunk.fill(0);
+                page_count as u64
+            },
+        };
+
+        file.write_all(chunk).map_err(|e| anyhow!("Failed to write {:?}: {}", path, e))?;
+        report.bytes_written += size as u64;
+        report.pages_failed += failed;
+        state.bytes_written.store(report.bytes_written, Ordering::Relaxed);
+        state.pages_failed.store(report.pages_failed, Ordering::Relaxed);
+        addr += size as u64;
+    }
+
+    file.sync_all().map_err(|e| anyhow!("Failed to sync {:?}: {}", path, e))?;
+    if report.pages_failed > 0 {
+        warn!("Memory dump {:?}: {} unreadable pages written as zeros", path, report.pages_failed);
+    }
+    info!(
+        "Memory dump 0x{:X}-0x{:X} to {:?}: {} bytes, {}",
+        start,
+        end,
+        path,
+        report.bytes_written,
+        if report.status == DUMP_STATUS_CANCELLED { "cancelled" } else { "completed" }
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn temp_file(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("mamu_dump_{}_{}.bin", name, std::process::id()))
+    }
+
+    /// 按地址生成内容，便于检查文件偏移和地址是否对应
+    fn fill(addr: u64, buf: &mut [u8]) {
+        for (i, byte) in buf.iter_mut().enumerate() {
+            *byte = ((addr + i as u64) % 251) as u8 + 1;
+        }
+    }
+
+    #[test]
+    fn test_unreadable_pages_written_as_zeros() {
+        let page = *PAGE_SIZE as u64;
+        let start = 0x7000_0000 + 0x10;
+        let end = start + DUMP_CHUNK_SIZE as u64 + 3 * page;
+        let bad_page = (0x7000_0000 + 2 * page) / page;
+        let path = temp_file("zeros");
+        let state = DumpState::new();
+
+        let mut calls = 0;
+        let report = dump_to_file(&state, start, end, &path, |addr, buf, status| {
+            calls += 1;
+            if calls == 2 {
+                return Err(anyhow!("read failed"));
+            }
+            fill(addr, buf);
+            let first = addr / page;
+            for index in 0..(addr % page + buf.len() as u64).div_ceil(page) {
+                if first + index != bad_page {
+                    status.mark_success(index as usize);
+                }
+            }
+            Ok(())
+        })
+        .unwrap();
+
+        let data = std::fs::read(&path).unwrap();
+        assert_eq!(data.len() as u64, end - start);
+        assert_eq!(report.bytes_written, end - start);
+        assert_eq!(report.status, DUMP_STATUS_COMPLETED);
+
+        // 第一块截到页边界，第二块整块失败
+        let first_chunk = DUMP_CHUNK_SIZE - 0x10;
+        let second_pages = (end - start - first_chunk as u64).div_ceil(page);
+        assert_eq!(report.pages_failed, 1 + second_pages);
+        assert!(data[first_chunk..].iter().all(|&b| b == 0));
+
+        let bad = (bad_page * page - start) as usize;
+        assert!(data[bad..bad + page as usize].iter().all(|&b| b == 0));
+        let mut expected = vec![0u8; bad];
+        fill(start, &mut expected);
+        assert_eq!(&data[..bad], &expected[..]);
+        assert_ne!(data[bad + page as usize], 0);
+
+        assert!(!state.progress().running);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_cancel_leaves_truncated_file() {
+        let start = 0x7100_0000;
+        let end = start + 4 * DUMP_CHUNK_SIZE as u64;
+        let path = temp_file("cancel");
+        let state = DumpState::new();
+
+        let report = dump_to_file(&state, start, end, &path, |addr, buf, status| {
+            // 同一时间只允许一个转储
+            assert!(state.progress().running);
+            assert!(dump_to_file(&state, start, end, &temp_file("nested"), |_, _, _| Ok(())).is_err());
+
+            if addr == start + DUMP_CHUNK_SIZE as u64 {
+                state.cancel();
+            }
+            fill(addr, buf);
+            status.mark_all_success();
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(report.status, DUMP_STATUS_CANCELLED);
+        assert_eq!(report.bytes_written, 2 * DUMP_CHUNK_SIZE as u64);
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), report.bytes_written);
+
+        let progress = state.progress();
+        assert!(!progress.running);
+        assert_eq!((progress.bytes_written, progress.total), (report.bytes_written, end - start));
+
+        // 取消标志在下一次转储开始时清除
+        let report = dump_to_file(&state, start, start + 100, &path, |_, _, _| Ok(())).unwrap();
+        assert_eq!(report.status, DUMP_STATUS_COMPLETED);
+        assert_eq!(report.pages_failed, 1);
+        assert!(dump_to_file(&state, start, start, &path, |_, _, _| Ok(())).is_err());
+
+        let _ = std::fs::remove_file(path);
+    }
+}
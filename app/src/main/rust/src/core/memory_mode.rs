@@ -23,4 +23,10 @@ impl MemoryAccessMode {
             _ => None,
         }
     }
+
+    /// 读写通过 BindProc 句柄进行的模式
+    #[inline]
+    pub fn uses_bind_proc(self) -> bool {
+        matches!(self, MemoryAccessMode::NonCacheable | MemoryAccessMode::WriteThrough | MemoryAccessMode::Normal)
+    }
 }
\ No newline at end of file
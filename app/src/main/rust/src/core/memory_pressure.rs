@@ -0,0 +1,232 @@
+//! Process memory pressure guard
+//!
+//! 4GB 设备上的大范围扫描，结果 Vec 加上 rayon 的块缓冲可能超出可用内存，整个进程被 LMK 杀掉，
+//! 所有结果一起丢失。这里跟踪自身的 RSS（后台任务周期性读取 `/proc/self/statm`），提供两级阈值：
+//! - 软限制：扫描把累积的结果提前写入结果管理器，并缩小块缓冲
+//! - 硬限制：扫描不再开始新的区域，以部分结果完成
+//!
+//! 扫描只在区域边界查询缓存的压力等级，不会自己读取 statm。
+
+use crate::core::globals::{PAGE_SIZE, TOKIO_RUNTIME};
+use anyhow::{Result, anyhow};
+use log::{debug, warn};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+
+/// 后台采样 RSS 的间隔
+pub const RSS_SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// 默认软/硬限制占物理内存的百分比
+const DEFAULT_SOFT_LIMIT_PERCENT: u64 = 30;
+const DEFAULT_HARD_LIMIT_PERCENT: u64 = 45;
+
+/// 无法获取物理内存大小时的默认限制
+const FALLBACK_SOFT_LIMIT: u64 = 1024 * 1024 * 1024;
+const FALLBACK_HARD_LIMIT: u64 = 1536 * 1024 * 1024;
+
+/// 内存压力等级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum PressureLevel {
+    Normal,
+    /// 超过软限制
+    Soft,
+    /// 超过硬限制
+    Hard,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MemoryStats {
+    pub rss: u64,
+    pub peak_rss: u64,
+    pub soft_limit: u64,
+    pub hard_limit: u64,
+    pub level: PressureLevel,
+}
+
+/// 读取当前 RSS（字节），失败返回 None
+pub type RssReader = Box<dyn Fn() -> Option<u64> + Send + Sync>;
+
+pub struct MemoryPressureGuard {
+    reader: RssReader,
+    rss: AtomicU64,
+    peak_rss: AtomicU64,
+    soft_limit: AtomicU64,
+    hard_limit: AtomicU64,
+    sampler_started: AtomicBool,
+}
+
+impl MemoryPressureGuard {
+    /// 读取自身 statm，限制按物理内存计算
+    pub fn new() -> Self {
+        let (soft, hard) = default_limits();
+        Self::with_reader(Box::new(read_self_rss), soft, hard)
+    }
+
+    pub fn with_reader(reader: RssReader, soft_limit: u64, hard_limit: u64) -> Self {
+        Self {
+            reader,
+            rss: AtomicU64::new(0),
+            peak_rss: AtomicU64::new(0),
+            soft_limit: AtomicU64::new(soft_limit),
+            hard_limit: AtomicU64::new(hard_limit.max(soft_limit)),
+            sampler_started: AtomicBool::new(false),
+        }
+    }
+
+    /// 读取一次 RSS 并更新缓存值，读取失败时保留上一次的值
+    pub fn sample(&self) -> u64 {
+        match (self.reader)() {
+            Some(rss) => {
+                self.rss.store(rss, Ordering::Relaxed);
+                self.peak_rss.fetch_max(rss, Ordering::Relaxed);
+                rss
+            },
+            None => self.rss.load(Ordering::Relaxed),
+        }
+    }
+
+    /// 最近一次采样的 RSS
+    pub fn rss(&self) -> u64 {
+        self.rss.load(Ordering::Relaxed)
+    }
+
+    /// 按最近一次采样计算的压力等级
+    pub fn level(&self) -> PressureLevel {
+        let rss = self.rss();
+        if rss >= self.hard_limit.load(Ordering::Relaxed) {
+            PressureLevel::Hard
+        } else if rss >= self.soft_limit.load(Ordering::Relaxed) {
+            PressureLevel::Soft
+        } else {
+            PressureLevel::Normal
+        }
+    }
+
+    /// 设置软/硬限制（字节），要求 0 < soft <= hard
+    pub fn set_limits(&self, soft_limit: u64, hard_limit: u64) -> Result<()> {
+        if soft_limit == 0 || soft_limit > hard_limit {
+            return Err(anyhow!("Invalid memory limits: soft={} hard={}", soft_limit, hard_limit));
+        }
+        self.soft_limit.store(soft_limit, Ordering::Relaxed);
+        self.hard_limit.store(hard_limit, Ordering::Relaxed);
+        debug!("Memory pressure limits set: soft={} hard={}", soft_limit, hard_limit);
+        Ok(())
+    }
+
+    pub fn stats(&self) -> MemoryStats {
+        MemoryStats {
+            rss: self.rss(),
+            peak_rss: self.peak_rss.load(Ordering::Relaxed),
+            soft_limit: self.soft_limit.load(Ordering::Relaxed),
+            hard_limit: self.hard_limit.load(Ordering::Relaxed),
+            level: self.level(),
+        }
+    }
+
+    /// 启动后台采样任务（只启动一次），同时立即采样一次
+    pub fn start_sampler(&'static self) {
+        self.sample();
+        if self.sampler_started.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        TOKIO_RUNTIME.spawn(async move {
+            let mut last_level = PressureLevel::Normal;
+            loop {
+                tokio::time::sleep(RSS_SAMPLE_INTERVAL).await;
+                let rss = self.sample();
+                let level = self.level();
+                if level > last_level {
+                    warn!("Memory pressure rose to {:?}: rss={} MB", level, rss / (1024 * 1024));
+                }
+                last_level = level;
+            }
+        });
+    }
+}
+
+impl Default for MemoryPressureGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 解析 `/proc/<pid>/statm`，第二个字段为常驻页数
+pub fn parse_statm(text: &str, page_size: u64) -> Option<u64> {
+    let resident: u64 = text.split_whitespace().nth(1)?.parse().ok()?;
+    Some(resident * page_size)
+}
+
+pub fn read_self_rss() -> Option<u64> {
+    let text = std::fs::read_to_string("/proc/self/statm").ok()?;
+    parse_statm(&text, *PAGE_SIZE as u64)
+}
+
+/// 按物理内存大小计算默认的 (软限制, 硬限制)
+pub fn default_limits() -> (u64, u64) {
+    let pages = nix::unistd::sysconf(nix::unistd::SysconfVar::_PHYS_PAGES)
+        .ok()
+        .flatten()
+        .filter(|&pages| pages > 0);
+    let Some(pages) = pages else {
+        return (FALLBACK_SOFT_LIMIT, FALLBACK_HARD_LIMIT);
+    };
+    let total = pages as u64 * *PAGE_SIZE as u64;
+    (total / 100 * DEFAULT_SOFT_LIMIT_PERCENT, total / 100 * DEFAULT_HARD_LIMIT_PERCENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    const MB: u64 = 1024 * 1024;
+
+    fn fake_guard(rss: &Arc<AtomicU64>) -> MemoryPressureGuard {
+        let source = Arc::clone(rss);
+        MemoryPressureGuard::with_reader(Box::new(move || Some(source.load(Ordering::Relaxed))), 100 * MB, 200 * MB)
+    }
+
+    #[test]
+    fn test_parse_statm() {
+        assert_eq!(parse_statm("52061 2315 1536 2 0 6131 0\n", 4096), Some(2315 * 4096));
+        assert_eq!(parse_statm("52061", 4096), None);
+        assert_eq!(parse_statm("", 4096), None);
+        assert!(read_self_rss().is_some_and(|rss| rss > 0));
+    }
+
+    #[test]
+    fn test_levels_follow_sampled_rss() {
+        let rss = Arc::new(AtomicU64::new(50 * MB));
+        let guard = fake_guard(&rss);
+
+        // 采样前等级只取决于缓存值
+        assert_eq!(guard.level(), PressureLevel::Normal);
+        rss.store(150 * MB, Ordering::Relaxed);
+        assert_eq!(guard.level(), PressureLevel::Normal);
+        guard.sample();
+        assert_eq!(guard.level(), PressureLevel::Soft);
+        rss.store(200 * MB, Ordering::Relaxed);
+        guard.sample();
+        assert_eq!(guard.level(), PressureLevel::Hard);
+        rss.store(80 * MB, Ordering::Relaxed);
+        guard.sample();
+
+        let stats = guard.stats();
+        assert_eq!((stats.rss, stats.peak_rss, stats.level), (80 * MB, 200 * MB, PressureLevel::Normal));
+    }
+
+    #[test]
+    fn test_set_limits_validation() {
+        let rss = Arc::new(AtomicU64::new(150 * MB));
+        let guard = fake_guard(&rss);
+        guard.sample();
+
+        assert!(guard.set_limits(0, 10).is_err());
+        assert!(guard.set_limits(300 * MB, 200 * MB).is_err());
+        assert_eq!(guard.level(), PressureLevel::Soft);
+
+        guard.set_limits(120 * MB, 140 * MB).unwrap();
+        assert_eq!(guard.level(), PressureLevel::Hard);
+    }
+}
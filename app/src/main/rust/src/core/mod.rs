@@ -3,12 +3,32 @@
 //! This module contains core components for driver management and memory access.
 
 pub mod memory_mode;
+pub mod address_rebase;
+pub mod atomic_write;
+pub mod bind_health;
 pub mod driver_manager;
 pub mod globals;
 pub mod freeze_manager;
+pub mod memory_dump;
+pub mod memory_pressure;
+pub mod process_list;
+pub mod process_pause;
+pub mod qos;
+pub mod read_fallback;
+pub mod region_classifier;
+pub mod region_map;
+pub mod region_snapshot;
+pub mod saved_addresses;
+pub mod secondary_procs;
+pub mod self_regions;
+pub mod shared_header;
+pub mod struct_decode;
+pub mod watch_manager;
+pub mod worker_pool;
 
 // Re-export commonly used items
 pub use memory_mode::MemoryAccessMode;
 pub use driver_manager::DriverManager;
-pub use globals::DRIVER_MANAGER;
-pub use freeze_manager::FreezeManager;
\ No newline at end of file
+pub use globals::{DRIVER_MANAGER, MEMORY_GUARD, MEMORY_QOS};
+pub use freeze_manager::FreezeManager;
+pub use qos::AccessQos;
\ No newline at end of file
@@ -0,0 +1,242 @@
+//! Process list ordering and short-lived cache
+//!
+//! 进程选择器每次刷新都会重新列出进程。大多数进程优先级相同，只按优先级排序时同级进程的
+//! 顺序取决于内核返回的顺序，列表会在用户阅读时跳动。这里定义稳定的排序：主排序可选，
+//! 次排序始终为 pid 升序；缓存与实时列表使用同一排序，两者结果一致。
+
+use crate::wuwa::WuwaGetProcInfoCmd;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 缓存的进程列表有效期
+pub const PROCESS_CACHE_TTL: Duration = Duration::from_secs(1);
+
+/// kthreadd 的 pid，内核线程都是它的子进程
+const KTHREADD_PID: i32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessSortMode {
+    /// 优先级升序（数值越小越靠前），默认
+    Priority,
+    /// RSS 降序
+    RssDesc,
+    /// 名称升序
+    Name,
+    /// 最近启动的在前。进程信息里没有启动时间，pid 按分配顺序递增，以 pid 降序近似
+    RecentlyStarted,
+}
+
+impl ProcessSortMode {
+    #[inline]
+    pub fn from_id(id: i32) -> Option<Self> {
+        match id {
+            0 => Some(ProcessSortMode::Priority),
+            1 => Some(ProcessSortMode::RssDesc),
+            2 => Some(ProcessSortMode::Name),
+            3 => Some(ProcessSortMode::RecentlyStarted),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProcessListOptions {
+    pub sort: ProcessSortMode,
+    /// 过滤内核线程，它们不可能是有效的目标
+    pub hide_kernel_threads: bool,
+}
+
+impl Default for ProcessListOptions {
+    fn default() -> Self {
+        Self {
+            sort: ProcessSortMode::Priority,
+            hide_kernel_threads: false,
+        }
+    }
+}
+
+fn name_bytes(info: &WuwaGetProcInfoCmd) -> &[u8] {
+    let end = info.name.iter().position(|&c| c == 0).unwrap_or(info.name.len());
+    &info.name[..end]
+}
+
+/// 内核线程：kthreadd 本身和它的子进程；没有 mm 的进程 RSS 为 0，也按内核线程处理
+pub fn is_kernel_thread(info: &WuwaGetProcInfoCmd) -> bool {
+    info.pid == KTHREADD_PID || info.ppid == KTHREADD_PID || info.rss == 0
+}
+
+/// 按 `mode` 排序，相同时按 pid 升序，结果与输入顺序无关
+pub fn sort_processes(list: &mut [WuwaGetProcInfoCmd], mode: ProcessSortMode) {
+    list.sort_by(|a, b| {
+        let primary = match mode {
+            ProcessSortMode::Priority => a.prio.cmp(&b.prio),
+            ProcessSortMode::RssDesc => b.rss.cmp(&a.rss),
+            ProcessSortMode::Name => name_bytes(a).cmp(name_bytes(b)),
+            ProcessSortMode::RecentlyStarted => b.pid.cmp(&a.pid),
+        };
+        primary.then(a.pid.cmp(&b.pid))
+    });
+}
+
+/// 过滤并排序
+pub fn arrange_processes(mut list: Vec<WuwaGetProcInfoCmd>, options: ProcessListOptions) -> Vec<WuwaGetProcInfoCmd> {
+    if options.hide_kernel_threads {
+        list.retain(|info| !is_kernel_thread(info));
+    }
+    sort_processes(&mut list, options.sort);
+    list
+}
+
+struct CachedList {
+    fetched_at: Instant,
+    processes: Vec<WuwaGetProcInfoCmd>,
+}
+
+/// 进程列表缓存，选择器频繁刷新时避免每次对所有 pid 发 ioctl
+///
+/// 缓存保存未过滤的原始列表，每次返回时按请求的选项过滤排序。
+pub struct ProcessCache {
+    ttl: Duration,
+    cached: Mutex<Option<CachedList>>,
+}
+
+impl ProcessCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self { ttl, cached: Mutex::new(None) }
+    }
+
+    pub fn list<F>(&self, options: ProcessListOptions, fetch: F) -> Vec<WuwaGetProcInfoCmd>
+    where
+        F: FnOnce() -> Vec<WuwaGetProcInfoCmd>,
+    {
+        self.list_at(Instant::now(), options, fetch)
+    }
+
+    pub(crate) fn list_at<F>(&self, now: Instant, options: ProcessListOptions, fetch: F) -> Vec<WuwaGetProcInfoCmd>
+    where
+        F: FnOnce() -> Vec<WuwaGetProcInfoCmd>,
+    {
+        let mut cached = self.cached.lock().unwrap_or_else(|e| e.into_inner());
+        let fresh = cached.as_ref().is_some_and(|entry| now.saturating_duration_since(entry.fetched_at) < self.ttl);
+        if !fresh {
+            *cached = Some(CachedList {
+                fetched_at: now,
+                processes: fetch(),
+            });
+        }
+        let processes = cached.as_ref().map(|entry| entry.processes.clone()).unwrap_or_default();
+        arrange_processes(processes, options)
+    }
+
+    /// 丢弃缓存，下次列出时重新获取
+    pub fn invalidate(&self) {
+        *self.cached.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+impl Default for ProcessCache {
+    fn default() -> Self {
+        Self::new(PROCESS_CACHE_TTL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proc(pid: i32, ppid: i32, prio: i32, rss: usize, name: &str) -> WuwaGetProcInfoCmd {
+        let mut info = WuwaGetProcInfoCmd {
+            pid,
+            tgid: pid,
+            name: [0; 256],
+            uid: 10000,
+            ppid,
+            prio,
+            rss,
+        };
+        info.name[..name.len()].copy_from_slice(name.as_bytes());
+        info
+    }
+
+    /// 优先级、RSS、名称都有并列
+    fn table() -> Vec<WuwaGetProcInfoCmd> {
+        vec![
+            proc(812, 1, 120, 4096, "surfaceflinger"),
+            proc(2, 0, 120, 0, "kthreadd"),
+            proc(4321, 700, 120, 65536, "com.example.game"),
+            proc(77, 2, 100, 0, "kworker/0:1"),
+            proc(1500, 700, 120, 65536, "com.android.systemui"),
+            proc(1499, 700, 100, 8192, "com.example.game"),
+            proc(700, 1, 120, 8192, "zygote64"),
+        ]
+    }
+
+    fn pids(list: &[WuwaGetProcInfoCmd]) -> Vec<i32> {
+        list.iter().map(|info| info.pid).collect()
+    }
+
+    /// 同一张表的几种不同输入顺序
+    fn permutations() -> Vec<Vec<WuwaGetProcInfoCmd>> {
+        let mut reversed = table();
+        reversed.reverse();
+        let mut rotated = table();
+        rotated.rotate_left(3);
+        vec![table(), reversed, rotated]
+    }
+
+    #[test]
+    fn test_deterministic_for_every_mode() {
+        let expected = [
+            (ProcessSortMode::Priority, vec![77, 1499, 2, 700, 812, 1500, 4321]),
+            (ProcessSortMode::RssDesc, vec![1500, 4321, 700, 1499, 812, 2, 77]),
+            (ProcessSortMode::Name, vec![1500, 1499, 4321, 2, 77, 812, 700]),
+            (ProcessSortMode::RecentlyStarted, vec![4321, 1500, 1499, 812, 700, 77, 2]),
+        ];
+        for (mode, order) in expected {
+            let options = ProcessListOptions {
+                sort: mode,
+                hide_kernel_threads: false,
+            };
+            for input in permutations() {
+                assert_eq!(pids(&arrange_processes(input, options)), order, "{:?}", mode);
+            }
+        }
+    }
+
+    #[test]
+    fn test_kernel_thread_filter() {
+        let options = ProcessListOptions {
+            sort: ProcessSortMode::Priority,
+            hide_kernel_threads: true,
+        };
+        assert_eq!(pids(&arrange_processes(table(), options)), vec![1499, 700, 812, 1500, 4321]);
+        assert_eq!(ProcessSortMode::from_id(1), Some(ProcessSortMode::RssDesc));
+        assert_eq!(ProcessSortMode::from_id(4), None);
+    }
+
+    #[test]
+    fn test_cache_matches_fresh_listing() {
+        let cache = ProcessCache::new(Duration::from_secs(1));
+        let start = Instant::now();
+        let mut inputs = permutations().into_iter();
+
+        for mode in [ProcessSortMode::Priority, ProcessSortMode::Name] {
+            let options = ProcessListOptions {
+                sort: mode,
+                hide_kernel_threads: true,
+            };
+            let fresh = arrange_processes(table(), options);
+            cache.invalidate();
+            let first = cache.list_at(start, options, || inputs.next().unwrap());
+            // 有效期内不重新获取
+            let cached = cache.list_at(start + Duration::from_millis(500), options, || panic!("cache should be fresh"));
+            assert_eq!(pids(&first), pids(&fresh));
+            assert_eq!(pids(&cached), pids(&fresh));
+        }
+
+        // 过期后重新获取，输入顺序不同，输出不变
+        let options = ProcessListOptions::default();
+        let expired = cache.list_at(start + Duration::from_secs(2), options, || inputs.next().unwrap());
+        assert_eq!(pids(&expired), pids(&arrange_processes(table(), options)));
+    }
+}
@@ -0,0 +1,187 @@
+//! Target process pause
+//!
+//! 扫描期间目标进程仍在运行，扫到第 4000 个区域时第 1 个区域里找到的值可能已经变了，之后的改善对不上。
+//! 可选地在扫描开始前向绑定进程发送 SIGSTOP，扫描结束后发送 SIGCONT。
+//!
+//! 恢复由 `PauseGuard` 的 Drop 完成，扫描完成、取消、出错以及阻塞任务 panic 时都会执行。
+//! 同一时间只暂停一个进程，记录在 `PauseRegistry` 中：解绑进程时和本进程正常退出时（atexit）
+//! 也会恢复它。本进程被 SIGKILL 杀死时无法恢复，目标进程需要手动 `kill -CONT`。
+
+use anyhow::{Result, anyhow};
+use log::{error, info};
+use nix::libc;
+use std::sync::Once;
+use std::sync::atomic::{AtomicI32, Ordering};
+
+/// 发送信号的函数，测试中替换为模拟实现
+pub type SendSignal = fn(pid: i32, signal: i32) -> Result<()>;
+
+fn kill(pid: i32, signal: i32) -> Result<()> {
+    if unsafe { libc::kill(pid, signal) } != 0 {
+        return Err(anyhow!("kill({}, {}) failed: {}", pid, signal, std::io::Error::last_os_error()));
+    }
+    Ok(())
+}
+
+/// 当前被暂停的进程，0 表示没有
+#[derive(Debug, Default)]
+pub struct PauseRegistry {
+    paused_pid: AtomicI32,
+}
+
+impl PauseRegistry {
+    pub const fn new() -> Self {
+        Self { paused_pid: AtomicI32::new(0) }
+    }
+
+    pub fn paused_pid(&self) -> Option<i32> {
+        match self.paused_pid.load(Ordering::Acquire) {
+            0 => None,
+            pid => Some(pid),
+        }
+    }
+
+    /// 暂停 pid，返回的 guard 被释放时恢复
+    pub fn pause(&self, pid: i32, send: SendSignal) -> Result<PauseGuard<'_>> {
+        if pid <= 0 {
+            return Err(anyhow!("Invalid pid to pause: {}", pid));
+        }
+        if pid == std::process::id() as i32 {
+            return Err(anyhow!("Refusing to pause our own process"));
+        }
+        if let Err(paused) = self.paused_pid.compare_exchange(0, pid, Ordering::AcqRel, Ordering::Acquire) {
+            return Err(anyhow!("Process {} is already paused", paused));
+        }
+        if let Err(e) = send(pid, libc::SIGSTOP) {
+            self.paused_pid.store(0, Ordering::Release);
+            return Err(e);
+        }
+        info!("Paused process {} for the scan", pid);
+        Ok(PauseGuard { registry: self, pid, send })
+    }
+
+    /// 恢复被暂停的进程（guard 之外的兜底路径：解绑、退出）
+    pub fn resume(&self, send: SendSignal) {
+        let pid = self.paused_pid.swap(0, Ordering::AcqRel);
+        if pid != 0 {
+            if let Err(e) = send(pid, libc::SIGCONT) {
+                error!("Failed to resume process {}: {}", pid, e);
+            } else {
+                info!("Resumed process {}", pid);
+            }
+        }
+    }
+}
+
+/// 暂停期间持有，释放时发送 SIGCONT
+pub struct PauseGuard<'a> {
+    registry: &'a PauseRegistry,
+    pid: i32,
+    send: SendSignal,
+}
+
+impl PauseGuard<'_> {
+    pub fn pid(&self) -> i32 {
+        self.pid
+    }
+}
+
+impl Drop for PauseGuard<'_> {
+    fn drop(&mut self) {
+        // 已经被解绑等兜底路径恢复过时不重复发送
+        if self.registry.paused_pid.compare_exchange(self.pid, 0, Ordering::AcqRel, Ordering::Acquire).is_ok() {
+            match (self.send)(self.pid, libc::SIGCONT) {
+                Ok(()) => info!("Resumed process {} after the scan", self.pid),
+                Err(e) => error!("Failed to resume process {}: {}", self.pid, e),
+            }
+        }
+    }
+}
+
+/// 全局的暂停记录
+pub static PAUSED_TARGET: PauseRegistry = PauseRegistry::new();
+
+extern "C" fn resume_at_exit() {
+    PAUSED_TARGET.resume(kill);
+}
+
+/// 暂停目标进程，第一次调用时注册退出钩子
+pub fn pause_target(pid: i32) -> Result<PauseGuard<'static>> {
+    static AT_EXIT: Once = Once::new();
+    AT_EXIT.call_once(|| unsafe {
+        libc::atexit(resume_at_exit);
+    });
+    PAUSED_TARGET.pause(pid, kill)
+}
+
+/// 解绑或重新绑定进程时调用，避免留下一个停止状态的进程
+pub fn resume_paused_target() {
+    PAUSED_TARGET.resume(kill);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    static SIGNALS: Mutex<Vec<(i32, i32)>> = Mutex::new(Vec::new());
+
+    fn record(pid: i32, signal: i32) -> Result<()> {
+        if pid == 4242 {
+            return Err(anyhow!("no such process"));
+        }
+        SIGNALS.lock().unwrap().push((pid, signal));
+        Ok(())
+    }
+
+    fn take_signals(pid: i32) -> Vec<i32> {
+        let mut signals = SIGNALS.lock().unwrap();
+        let (ours, others): (Vec<_>, Vec<_>) = signals.drain(..).partition(|&(p, _)| p == pid);
+        *signals = others;
+        ours.into_iter().map(|(_, signal)| signal).collect()
+    }
+
+    #[test]
+    fn test_guard_resumes_on_drop_and_panic() {
+        let registry = PauseRegistry::new();
+        {
+            let guard = registry.pause(1001, record).unwrap();
+            assert_eq!(guard.pid(), 1001);
+            assert_eq!(registry.paused_pid(), Some(1001));
+            // 同一时间只暂停一个进程
+            assert!(registry.pause(1002, record).is_err());
+        }
+        assert_eq!(take_signals(1001), vec![libc::SIGSTOP, libc::SIGCONT]);
+        assert_eq!(registry.paused_pid(), None);
+
+        // 扫描任务 panic 时 guard 在栈展开中恢复进程
+        let result = std::panic::catch_unwind(|| {
+            let _guard = registry.pause(1003, record).unwrap();
+            panic!("scan failed");
+        });
+        assert!(result.is_err());
+        assert_eq!(take_signals(1003), vec![libc::SIGSTOP, libc::SIGCONT]);
+        assert_eq!(registry.paused_pid(), None);
+    }
+
+    #[test]
+    fn test_refuses_invalid_targets() {
+        let registry = PauseRegistry::new();
+        assert!(registry.pause(std::process::id() as i32, record).is_err());
+        assert!(registry.pause(0, record).is_err());
+        // SIGSTOP 失败时不记录为已暂停
+        assert!(registry.pause(4242, record).is_err());
+        assert_eq!(registry.paused_pid(), None);
+        assert!(take_signals(std::process::id() as i32).is_empty());
+    }
+
+    #[test]
+    fn test_fallback_resume_is_not_repeated() {
+        let registry = PauseRegistry::new();
+        let guard = registry.pause(1004, record).unwrap();
+        // 扫描中解绑进程：立即恢复，guard 释放时不再发送
+        registry.resume(record);
+        drop(guard);
+        assert_eq!(take_signals(1004), vec![libc::SIGSTOP, libc::SIGCONT]);
+    }
+}
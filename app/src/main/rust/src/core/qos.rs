@@ -0,0 +1,419 @@
+//! Memory access QoS
+//!
+//! 扫描类的批量读取会把驱动占满，交互式读取（保存列表刷新、内存查看器）
+//! 排在一长串扫描块读取后面，UI 明显卡顿。这里把驱动访问分为两类：
+//! - Interactive：直接访问驱动，不受限制
+//! - Bulk：通过信号量限制并发数量，并在有交互式请求等待时主动让出
+//!
+//! 这只是调用方一侧的节流，驱动本身没有优先级：读取经 `DriverManager::read_memory_with_qos` /
+//! `read_target_with_qos` 带上 QoS 等级。按块读取区域的函数（各类首次扫描、指针扫描 Phase 1）
+//! 都有必填的 `AccessQos` 参数，扫描传 `Bulk`。交互式请求不会插到已经进入驱动的批量读取前面，
+//! 只是在它等待期间新的批量读取先休眠，最多 `BULK_MAX_YIELD`。
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// Bulk 请求默认的最大并发驱动操作数
+pub const DEFAULT_BULK_CONCURRENCY: usize = 4;
+
+/// Bulk 请求让出时单次休眠时长
+const BULK_YIELD_INTERVAL: Duration = Duration::from_micros(50);
+
+/// Bulk 请求单次操作最多让出的时长，避免交互式请求持续不断时扫描饿死
+const BULK_MAX_YIELD: Duration = Duration::from_millis(5);
+
+/// 内存访问的 QoS 等级
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessQos {
+    /// 前台交互式读取，绕过信号量
+    Interactive,
+    /// 后台批量读取（扫描块、指针扫描 Phase 1 等）
+    Bulk,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MemoryQosStats {
+    pub enabled: bool,
+    pub interactive_pending: usize,
+    pub interactive_served: u64,
+    pub bulk_in_flight: usize,
+    pub bulk_served: u64,
+    pub bulk_yields: u64,
+}
+
+pub struct MemoryQos {
+    enabled: AtomicBool,
+    bulk_limit: usize,
+    bulk_in_flight: Mutex<usize>,
+    bulk_released: Condvar,
+    interactive_pending: AtomicUsize,
+    interactive_served: AtomicU64,
+    bulk_served: AtomicU64,
+    bulk_yields: AtomicU64,
+}
+
+/// Bulk 请求持有的信号量许可，Drop 时归还
+struct BulkPermit<'a> {
+    qos: &'a MemoryQos,
+}
+
+impl Drop for BulkPermit<'_> {
+    fn drop(&mut self) {
+        let mut in_flight = self.qos.bulk_in_flight.lock().unwrap_or_else(|e| e.into_inner());
+        *in_flight -= 1;
+        drop(in_flight);
+        self.qos.bulk_released.notify_one();
+    }
+}
+
+/// 交互式请求的等待计数，Drop 时减少
+struct InteractiveGuard<'a> {
+    qos: &'a MemoryQos,
+}
+
+impl Drop for InteractiveGuard<'_> {
+    fn drop(&mut self) {
+        self.qos.interactive_pending.fetch_sub(1, Ordering::AcqRel);
+        self.qos.interactive_served.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl MemoryQos {
+    pub fn new(bulk_limit: usize) -> Self {
+        Self {
+            enabled: AtomicBool::new(true),
+            bulk_limit: bulk_limit.max(1),
+            bulk_in_flight: Mutex::new(0),
+            bulk_released: Condvar::new(),
+            interactive_pending: AtomicUsize::new(0),
+            interactive_served: AtomicU64::new(0),
+            bulk_served: AtomicU64::new(0),
+            bulk_yields: AtomicU64::new(0),
+        }
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// 按 QoS 等级执行一次驱动操作
+    pub fn run<R>(&self, qos: AccessQos, op: impl FnOnce() -> R) -> R {
+        match qos {
+            AccessQos::Interactive => self.run_interactive(op),
+            AccessQos::Bulk => self.run_bulk(op),
+        }
+    }
+
+    fn run_interactive<R>(&self, op: impl FnOnce() -> R) -> R {
+        self.interactive_pending.fetch_add(1, Ordering::AcqRel);
+        let _guard = InteractiveGuard { qos: self };
+        op()
+    }
+
+    fn run_bulk<R>(&self, op: impl FnOnce() -> R) -> R {
+        self.bulk_served.fetch_add(1, Ordering::Relaxed);
+        if !self.is_enabled() {
+            return op();
+        }
+
+        let _permit = self.acquire_bulk();
+
+        // 有交互式请求在等待时让出驱动
+        if self.interactive_pending.load(Ordering::Acquire) > 0 {
+            self.bulk_yields.fetch_add(1, Ordering::Relaxed);
+            let deadline = Instant::now() + BULK_MAX_YIELD;
+            while self.interactive_pending.load(Ordering::Acquire) > 0 && Instant::now() < deadline {
+                std::thread::sleep(BULK_YIELD_INTERVAL);
+            }
+        }
+
+        op()
+    }
+
+    fn acquire_bulk(&self) -> BulkPermit<'_> {
+        let mut in_flight = self.bulk_in_flight.lock().unwrap_or_else(|e| e.into_inner());
+        while *in_flight >= self.bulk_limit {
+            in_flight = self.bulk_released.wait(in_flight).unwrap_or_else(|e| e.into_inner());
+        }
+        *in_flight += 1;
+        BulkPermit { qos: self }
+    }
+
+    pub fn stats(&self) -> MemoryQosStats {
+        MemoryQosStats {
+            enabled: self.is_enabled(),
+            interactive_pending: self.interactive_pending.load(Ordering::Relaxed),
+            interactive_served: self.interactive_served.load(Ordering::Relaxed),
+            bulk_in_flight: *self.bulk_in_flight.lock().unwrap_or_else(|e| e.into_inner()),
+            bulk_served: self.bulk_served.load(Ordering::Relaxed),
+            bulk_yields: self.bulk_yields.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// 每次操作耗时固定 OP_LATENCY 的单通道驱动，请求按到达顺序排队（ticket lock），
+    /// 所以交互式请求的等待时间只取决于排在它前面的请求个数，`read` 返回这个个数
+    struct MockDriver {
+        queue: Mutex<DriverQueue>,
+        served: Condvar,
+    }
+
+    struct DriverQueue {
+        next_ticket: usize,
+        now_serving: usize,
+    }
+
+    const OP_LATENCY: Duration = Duration::from_millis(1);
+    const BULK_THREADS: usize = 8;
+    const BULK_LIMIT: usize = 2;
+    const LOAD_WINDOW: Duration = Duration::from_millis(200);
+    const INTERACTIVE_INTERVAL: Duration = Duration::from_millis(5);
+
+    impl MockDriver {
+        fn new() -> Self {
+            Self {
+                queue: Mutex::new(DriverQueue { next_ticket: 0, now_serving: 0 }),
+                served: Condvar::new(),
+            }
+        }
+
+        /// 返回到达时排在前面（正在执行或等待）的请求数
+        fn read(&self) -> usize {
+            let mut queue = self.queue.lock().unwrap();
+            let ticket = queue.next_ticket;
+            queue.next_ticket += 1;
+            let ahead = ticket - queue.now_serving;
+            while queue.now_serving != ticket {
+                queue = self.served.wait(queue).unwrap();
+            }
+            drop(queue);
+
+            std::thread::sleep(OP_LATENCY);
+
+            self.queue.lock().unwrap().now_serving += 1;
+            self.served.notify_all();
+            ahead
+        }
+    }
+
+    struct LoadResult {
+        interactive_p50: Duration,
+        interactive_p95: Duration,
+        /// 交互式请求到达驱动时排在前面的最多请求数
+        interactive_max_ahead: usize,
+        /// LOAD_WINDOW 内完成的批量操作数
+        bulk_ops: u64,
+    }
+
+    /// 后台 BULK_THREADS 个线程持续批量读取，前台在 LOAD_WINDOW 内每隔
+    /// INTERACTIVE_INTERVAL 发起一次交互式读取
+    fn run_load(qos_enabled: bool) -> LoadResult {
+        let qos = Arc::new(MemoryQos::new(BULK_LIMIT));
+        qos.set_enabled(qos_enabled);
+        let driver = Arc::new(MockDriver::new());
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let bulk_workers: Vec<_> = (0..BULK_THREADS)
+            .map(|_| {
+                let qos = Arc::clone(&qos);
+                let driver = Arc::clone(&driver);
+                let stop = Arc::clone(&stop);
+                std::thread::spawn(move || {
+                    while !stop.load(Ordering::Relaxed) {
+                        qos.run(AccessQos::Bulk, || driver.read());
+                    }
+                })
+            })
+            .collect();
+
+        // 等待批量负载占满驱动
+        std::thread::sleep(Duration::from_millis(20));
+
+        let bulk_before = qos.stats().bulk_served;
+        let started = Instant::now();
+        let mut latencies = Vec::new();
+        let mut interactive_max_ahead = 0;
+        while started.elapsed() < LOAD_WINDOW {
+            let start = Instant::now();
+            let ahead = qos.run(AccessQos::Interactive, || driver.read());
+            latencies.push(start.elapsed());
+            interactive_max_ahead = interactive_max_ahead.max(ahead);
+            std::thread::sleep(INTERACTIVE_INTERVAL);
+        }
+        let bulk_ops = qos.stats().bulk_served - bulk_before;
+
+        stop.store(true, Ordering::Relaxed);
+        for worker in bulk_workers {
+            worker.join().unwrap();
+        }
+
+        latencies.sort_unstable();
+        let percentile = |p: usize| latencies[(latencies.len() * p / 100).min(latencies.len() - 1)];
+        LoadResult {
+            interactive_p50: percentile(50),
+            interactive_p95: percentile(95),
+            interactive_max_ahead,
+            bulk_ops,
+        }
+    }
+
+    #[test]
+    fn test_interactive_latency_under_bulk_load() {
+        let without = run_load(false);
+        let with = run_load(true);
+
+        // 不开 QoS 时交互式请求排在所有批量线程后面（约 BULK_THREADS 个操作），
+        // 开启后最多排在 BULK_LIMIT 个已进入驱动的批量请求后面
+        assert!(
+            with.interactive_p50 < without.interactive_p50 && with.interactive_p95 < without.interactive_p95,
+            "QoS should improve interactive latency (p50 {:?} vs {:?}, p95 {:?} vs {:?})",
+            with.interactive_p50,
+            without.interactive_p50,
+            with.interactive_p95,
+            without.interactive_p95
+        );
+        // 上限按排队位置计，不受机器负载影响：批量请求最多 BULK_LIMIT 个同时在驱动里
+        assert!(
+            with.interactive_max_ahead <= BULK_LIMIT,
+            "Interactive request queued behind {} requests with QoS",
+            with.interactive_max_ahead
+        );
+
+        // 驱动在两种情况下都是满载的，批量吞吐至少保留一半
+        assert!(
+            with.bulk_ops * 2 >= without.bulk_ops,
+            "Bulk throughput dropped too much ({} vs {} ops in {:?})",
+            with.bulk_ops,
+            without.bulk_ops,
+            LOAD_WINDOW
+        );
+    }
+
+    /// 在另一个线程里执行一次交互式操作，操作一直挂起到返回的发送端被丢弃
+    fn hold_interactive(qos: &Arc<MemoryQos>) -> (std::sync::mpsc::Sender<()>, std::thread::JoinHandle<()>) {
+        let (entered_tx, entered_rx) = std::sync::mpsc::channel();
+        let (release_tx, release_rx) = std::sync::mpsc::channel::<()>();
+        let qos = Arc::clone(qos);
+        let handle = std::thread::spawn(move || {
+            qos.run(AccessQos::Interactive, || {
+                entered_tx.send(()).unwrap();
+                let _ = release_rx.recv();
+            });
+        });
+        entered_rx.recv().unwrap();
+        (release_tx, handle)
+    }
+
+    #[test]
+    fn test_bulk_yields_only_while_interactive_pending() {
+        let qos = Arc::new(MemoryQos::new(2));
+
+        for _ in 0..5 {
+            qos.run(AccessQos::Bulk, || ());
+        }
+        assert_eq!(qos.stats().bulk_yields, 0);
+
+        // 交互式请求等待期间，每个批量读取都先让出一次
+        let (release, handle) = hold_interactive(&qos);
+        for _ in 0..3 {
+            qos.run(AccessQos::Bulk, || ());
+        }
+        let stats = qos.stats();
+        assert_eq!(stats.interactive_pending, 1);
+        assert_eq!(stats.bulk_yields, 3);
+
+        drop(release);
+        handle.join().unwrap();
+        for _ in 0..5 {
+            qos.run(AccessQos::Bulk, || ());
+        }
+        let stats = qos.stats();
+        assert_eq!((stats.interactive_pending, stats.interactive_served), (0, 1));
+        assert_eq!((stats.bulk_served, stats.bulk_yields), (13, 3));
+    }
+
+    #[test]
+    fn test_disabled_qos_never_yields() {
+        let qos = Arc::new(MemoryQos::new(2));
+        qos.set_enabled(false);
+
+        let (release, handle) = hold_interactive(&qos);
+        for _ in 0..3 {
+            qos.run(AccessQos::Bulk, || ());
+        }
+        drop(release);
+        handle.join().unwrap();
+
+        let stats = qos.stats();
+        assert_eq!((stats.bulk_served, stats.bulk_yields), (3, 0));
+    }
+
+    #[test]
+    fn test_interactive_bypasses_bulk_limit() {
+        let qos = Arc::new(MemoryQos::new(1));
+        let (entered_tx, entered_rx) = std::sync::mpsc::channel();
+        let (release_tx, release_rx) = std::sync::mpsc::channel::<()>();
+        let bulk = {
+            let qos = Arc::clone(&qos);
+            std::thread::spawn(move || {
+                qos.run(AccessQos::Bulk, || {
+                    entered_tx.send(()).unwrap();
+                    let _ = release_rx.recv();
+                });
+            })
+        };
+        entered_rx.recv().unwrap();
+
+        // 批量许可已经用完，交互式请求不经过信号量
+        assert_eq!(qos.stats().bulk_in_flight, 1);
+        assert_eq!(qos.run(AccessQos::Interactive, || 42), 42);
+
+        drop(release_tx);
+        bulk.join().unwrap();
+        assert_eq!(qos.stats().bulk_in_flight, 0);
+    }
+
+    #[test]
+    fn test_bulk_concurrency_limited() {
+        let qos = Arc::new(MemoryQos::new(2));
+        let in_op = Arc::new(AtomicUsize::new(0));
+        let max_seen = Arc::new(AtomicUsize::new(0));
+
+        let workers: Vec<_> = (0..6)
+            .map(|_| {
+                let qos = Arc::clone(&qos);
+                let in_op = Arc::clone(&in_op);
+                let max_seen = Arc::clone(&max_seen);
+                std::thread::spawn(move || {
+                    for _ in 0..20 {
+                        qos.run(AccessQos::Bulk, || {
+                            let now = in_op.fetch_add(1, Ordering::SeqCst) + 1;
+                            max_seen.fetch_max(now, Ordering::SeqCst);
+                            std::thread::sleep(Duration::from_micros(200));
+                            in_op.fetch_sub(1, Ordering::SeqCst);
+                        });
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+
+        assert!(max_seen.load(Ordering::SeqCst) <= 2);
+        let stats = qos.stats();
+        assert_eq!(stats.bulk_served, 120);
+        assert_eq!(stats.bulk_in_flight, 0);
+    }
+}
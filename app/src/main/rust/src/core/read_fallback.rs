@@ -0,0 +1,290 @@
+//! BindProc read fallback
+//!
+//! BindProc 句柄读取可能因为目标进程 exec（EBADF）或瞬时的 -EFAULT 失败，
+//! 这时整块搜索区域会被跳过。这里在 BindProc 读取失败后用驱动的
+//! get_user_pages_remote 路径重试同一段内存，并按路径统计读取次数和失败次数；
+//! 开启自动切换后，BindProc 连续失败达到阈值时直接改走驱动路径，直到重新绑定或切换访问模式。
+
+use crate::wuwa::PageStatusBitmap;
+use anyhow::Result;
+use log::warn;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+
+/// 两条读取路径，DriverManager 使用真实驱动实现，测试使用模拟实现
+pub trait ReadPaths {
+    /// 通过绑定句柄读取
+    fn read_bound(&self, addr: u64, buf: &mut [u8], page_status: Option<&mut PageStatusBitmap>) -> Result<()>;
+    /// 通过驱动的 get_user_pages_remote 读取（不跟踪页状态）
+    fn read_driver(&self, addr: u64, buf: &mut [u8]) -> Result<()>;
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadPathStats {
+    pub bind_reads: u64,
+    pub bind_failures: u64,
+    pub driver_reads: u64,
+    pub driver_failures: u64,
+    /// BindProc 失败后由驱动路径成功补上的读取次数
+    pub fallback_served: u64,
+    pub consecutive_bind_failures: u32,
+    /// 自动切换阈值，0 表示不自动切换
+    pub auto_switch_threshold: u32,
+    /// 是否已自动切换到驱动路径
+    pub switched: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct ReadFallback {
+    bind_reads: AtomicU64,
+    bind_failures: AtomicU64,
+    driver_reads: AtomicU64,
+    driver_failures: AtomicU64,
+    fallback_served: AtomicU64,
+    consecutive_bind_failures: AtomicU32,
+    auto_switch_threshold: AtomicU32,
+    switched: AtomicBool,
+}
+
+impl ReadFallback {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置自动切换阈值，0 关闭自动切换（已经切换的状态保持到下一次重置）
+    pub fn set_auto_switch_threshold(&self, threshold: u32) {
+        self.auto_switch_threshold.store(threshold, Ordering::Relaxed);
+    }
+
+    pub fn is_switched(&self) -> bool {
+        self.switched.load(Ordering::Acquire)
+    }
+
+    /// 句柄重新绑定或访问模式变化后恢复走 BindProc 路径，统计保留
+    pub fn reset_switch(&self) {
+        self.consecutive_bind_failures.store(0, Ordering::Relaxed);
+        self.switched.store(false, Ordering::Release);
+    }
+
+    /// 绑定新进程或解绑时清空统计
+    pub fn reset(&self) {
+        for counter in [&self.bind_reads, &self.bind_failures, &self.driver_reads, &self.driver_failures, &self.fallback_served] {
+            counter.store(0, Ordering::Relaxed);
+        }
+        self.reset_switch();
+    }
+
+    /// 读取一段内存：先走 BindProc，失败后用驱动路径重试一次；已切换时直接走驱动路径
+    pub fn read<P: ReadPaths + ?Sized>(
+        &self,
+        paths: &P,
+        addr: u64,
+        buf: &mut [u8],
+        mut page_status: Option<&mut PageStatusBitmap>,
+    ) -> Result<()> {
+        if self.is_switched() {
+            return self.read_driver(paths, addr, buf, page_status);
+        }
+
+        self.bind_reads.fetch_add(1, Ordering::Relaxed);
+        let bind_error = match paths.read_bound(addr, buf, page_status.as_deref_mut()) {
+            Ok(()) => {
+                self.consecutive_bind_failures.store(0, Ordering::Relaxed);
+                return Ok(());
+            },
+            Err(e) => e,
+        };
+
+        self.bind_failures.fetch_add(1, Ordering::Relaxed);
+        let consecutive = self.consecutive_bind_failures.fetch_add(1, Ordering::Relaxed) + 1;
+        let threshold = self.auto_switch_threshold.load(Ordering::Relaxed);
+        if threshold > 0 && consecutive >= threshold && !self.switched.swap(true, Ordering::AcqRel) {
+            warn!("BindProc reads failed {} times in a row, switching reads to the driver path", consecutive);
+        }
+
+        match self.read_driver(paths, addr, buf, page_status) {
+            Ok(()) => {
+                self.fallback_served.fetch_add(1, Ordering::Relaxed);
+                Ok(())
+            },
+            Err(e) => Err(bind_error.context(format!("Driver fallback read failed: {}", e))),
+        }
+    }
+
+    fn read_driver<P: ReadPaths + ?Sized>(
+        &self,
+        paths: &P,
+        addr: u64,
+        buf: &mut [u8],
+        page_status: Option<&mut PageStatusBitmap>,
+    ) -> Result<()> {
+        self.driver_reads.fetch_add(1, Ordering::Relaxed);
+        if let Err(e) = paths.read_driver(addr, buf) {
+            self.driver_failures.fetch_add(1, Ordering::Relaxed);
+            return Err(e);
+        }
+        // 驱动路径整段读取成功，所有页都有效
+        if let Some(status) = page_status {
+            status.mark_all_success();
+        }
+        Ok(())
+    }
+
+    pub fn stats(&self) -> ReadPathStats {
+        ReadPathStats {
+            bind_reads: self.bind_reads.load(Ordering::Relaxed),
+            bind_failures: self.bind_failures.load(Ordering::Relaxed),
+            driver_reads: self.driver_reads.load(Ordering::Relaxed),
+            driver_failures: self.driver_failures.load(Ordering::Relaxed),
+            fallback_served: self.fallback_served.load(Ordering::Relaxed),
+            consecutive_bind_failures: self.consecutive_bind_failures.load(Ordering::Relaxed),
+            auto_switch_threshold: self.auto_switch_threshold.load(Ordering::Relaxed),
+            switched: self.is_switched(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use std::cell::Cell;
+
+    /// 模拟驱动：两条路径各自可以失败，读取成功时填充不同的字节以区分来源
+    struct MockPaths {
+        bind_ok: Cell<bool>,
+        driver_ok: Cell<bool>,
+        bind_calls: Cell<u32>,
+        driver_calls: Cell<u32>,
+    }
+
+    impl MockPaths {
+        fn new(bind_ok: bool, driver_ok: bool) -> Self {
+            Self {
+                bind_ok: Cell::new(bind_ok),
+                driver_ok: Cell::new(driver_ok),
+                bind_calls: Cell::new(0),
+                driver_calls: Cell::new(0),
+            }
+        }
+    }
+
+    impl ReadPaths for MockPaths {
+        fn read_bound(&self, addr: u64, buf: &mut [u8], page_status: Option<&mut PageStatusBitmap>) -> Result<()> {
+            self.bind_calls.set(self.bind_calls.get() + 1);
+            if !self.bind_ok.get() {
+                return Err(anyhow!("BindProc read failed: va=0x{:x} size={}", addr, buf.len()));
+            }
+            buf.fill(0xB1);
+            if let Some(status) = page_status {
+                status.mark_all_success();
+            }
+            Ok(())
+        }
+
+        fn read_driver(&self, addr: u64, buf: &mut [u8]) -> Result<()> {
+            self.driver_calls.set(self.driver_calls.get() + 1);
+            if !self.driver_ok.get() {
+                return Err(anyhow!("Memory read failed: va=0x{:x} size={}", addr, buf.len()));
+            }
+            buf.fill(0xD2);
+            Ok(())
+        }
+    }
+
+    const ADDR: u64 = 0x7000_0000;
+
+    fn read(fallback: &ReadFallback, paths: &MockPaths) -> Result<(Vec<u8>, PageStatusBitmap)> {
+        let mut buf = vec![0u8; 64];
+        let mut status = PageStatusBitmap::new(buf.len(), ADDR as usize);
+        fallback.read(paths, ADDR, &mut buf, Some(&mut status))?;
+        Ok((buf, status))
+    }
+
+    #[test]
+    fn test_bind_failure_falls_back_to_driver() {
+        let fallback = ReadFallback::new();
+        let paths = MockPaths::new(true, true);
+
+        let (buf, _) = read(&fallback, &paths).unwrap();
+        assert!(buf.iter().all(|&b| b == 0xB1));
+        assert_eq!(paths.driver_calls.get(), 0);
+
+        paths.bind_ok.set(false);
+        let (buf, status) = read(&fallback, &paths).unwrap();
+        assert!(buf.iter().all(|&b| b == 0xD2));
+        assert_eq!(status.failure_count(), 0);
+        assert_eq!(paths.driver_calls.get(), 1);
+
+        let stats = fallback.stats();
+        assert_eq!((stats.bind_reads, stats.bind_failures), (2, 1));
+        assert_eq!((stats.driver_reads, stats.driver_failures, stats.fallback_served), (1, 0, 1));
+        assert_eq!(stats.consecutive_bind_failures, 1);
+        assert!(!stats.switched);
+    }
+
+    #[test]
+    fn test_both_paths_failing_reports_both_errors() {
+        let fallback = ReadFallback::new();
+        let paths = MockPaths::new(false, false);
+
+        let error = read(&fallback, &paths).map(|_| ()).unwrap_err();
+        let message = format!("{:#}", error);
+        assert!(message.contains("Driver fallback read failed"), "{}", message);
+        assert!(message.contains("BindProc read failed"), "{}", message);
+
+        let stats = fallback.stats();
+        assert_eq!((stats.bind_failures, stats.driver_failures, stats.fallback_served), (1, 1, 0));
+    }
+
+    #[test]
+    fn test_auto_switch_after_consecutive_failures() {
+        let fallback = ReadFallback::new();
+        fallback.set_auto_switch_threshold(3);
+        let paths = MockPaths::new(false, true);
+
+        // 中间一次成功会重新计数
+        read(&fallback, &paths).unwrap();
+        read(&fallback, &paths).unwrap();
+        paths.bind_ok.set(true);
+        read(&fallback, &paths).unwrap();
+        paths.bind_ok.set(false);
+        read(&fallback, &paths).unwrap();
+        read(&fallback, &paths).unwrap();
+        assert!(!fallback.is_switched());
+        read(&fallback, &paths).unwrap();
+        assert!(fallback.is_switched());
+
+        // 切换后不再尝试 BindProc
+        let bind_calls = paths.bind_calls.get();
+        let (buf, _) = read(&fallback, &paths).unwrap();
+        assert!(buf.iter().all(|&b| b == 0xD2));
+        assert_eq!(paths.bind_calls.get(), bind_calls);
+        assert_eq!(fallback.stats().driver_reads, 6);
+
+        // 重新绑定后恢复 BindProc 路径，统计保留
+        fallback.reset_switch();
+        paths.bind_ok.set(true);
+        let (buf, _) = read(&fallback, &paths).unwrap();
+        assert!(buf.iter().all(|&b| b == 0xB1));
+        let stats = fallback.stats();
+        assert!(!stats.switched);
+        assert_eq!((stats.bind_reads, stats.bind_failures), (7, 5));
+
+        fallback.reset();
+        assert_eq!(fallback.stats().bind_reads, 0);
+    }
+
+    #[test]
+    fn test_no_auto_switch_when_disabled() {
+        let fallback = ReadFallback::new();
+        let paths = MockPaths::new(false, true);
+        for _ in 0..100 {
+            read(&fallback, &paths).unwrap();
+        }
+        let stats = fallback.stats();
+        assert!(!stats.switched);
+        assert_eq!(stats.consecutive_bind_failures, 100);
+        assert_eq!(paths.bind_calls.get(), 100);
+    }
+}
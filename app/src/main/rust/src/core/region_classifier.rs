@@ -0,0 +1,285 @@
+//! Memory range classification
+//!
+//! 按区域名称、权限和地址把内存区域分到 GameGuardian 风格的内存范围（Ca、Cd、Jh 等），
+//! 规则与 Kotlin 侧 DevideMemRange.kt 的 classifyRegion 一致。
+//! 搜索前直接在 native 侧按预设过滤，进程有十几万个 VMA 时不必把全部区域传到 Java 再筛选。
+
+use crate::wuwa::{WuWaDriver, WuwaMemRegionEntry, MEM_EXECUTABLE, MEM_READABLE, MEM_SHARED, MEM_WRITABLE};
+use anyhow::Result;
+
+/// 内存范围，声明顺序与 Kotlin MemoryRange 一致，预设掩码的第 n 位对应 ordinal 为 n 的范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum MemoryRange {
+    Jh = 0,
+    Ch = 1,
+    Ca = 2,
+    Cd = 3,
+    Cb = 4,
+    Ps = 5,
+    An = 6,
+    J = 7,
+    S = 8,
+    As = 9,
+    V = 10,
+    O = 11,
+    B = 12,
+    Xa = 13,
+    Xs = 14,
+    Dx = 15,
+    Jc = 16,
+    Oa = 17,
+    Vx = 18,
+    Ts = 19,
+    Xx = 20,
+}
+
+impl MemoryRange {
+    /// 该范围在预设掩码中的位
+    pub fn bit(self) -> u32 {
+        1 << self as u32
+    }
+}
+
+/// 显存 / GPU 设备映射
+const VIDEO_DEVICES: &[&str] = &[
+    "/dev/nv",
+    "/dev/tegra",
+    "/dev/ion",
+    "/dev/pvr",
+    "/dev/render",
+    "/dev/galcore",
+    "/dev/fimg2d",
+    "/dev/quadd",
+    "/dev/graphics",
+    "/dev/mm_",
+    "/dev/dri/",
+];
+
+/// 名称可以按堆、栈等规则细分的区域；ashmem 以外的设备映射和系统映像不参与
+fn is_classifiable_name(name: &str) -> bool {
+    !(name.is_empty()
+        || name.contains("system@")
+        || name.contains("gralloc")
+        || name.starts_with("[vdso]")
+        || name.starts_with("[vectors]")
+        || (name.starts_with("/dev/") && !name.starts_with("/dev/ashmem")))
+}
+
+/// dalvik 区域中真正存放对象的空间，位图、zygote、card table、JIT 和线性分配区除外
+fn is_dalvik_heap(name: &str) -> bool {
+    let hit = ["eap", "dalvik-alloc", "dalvik-main", "dalvik-large", "dalvik-free"]
+        .iter()
+        .any(|s| name.contains(s));
+    hit && !["itmap", "ygote", "ard", "jit", "inear"].iter().any(|s| name.contains(s))
+}
+
+/// 对单个区域分类，空区域返回 None
+///
+/// `proc_name` 是目标进程名，用于识别应用自身的代码和库。
+pub fn classify(start: u64, end: u64, type_: u32, name: &str, proc_name: &str) -> Option<MemoryRange> {
+    if start == end {
+        return None;
+    }
+
+    let readable = type_ & MEM_READABLE != 0;
+    let writable = type_ & MEM_WRITABLE != 0;
+    let executable = type_ & MEM_EXECUTABLE != 0;
+    let shared = type_ & MEM_SHARED != 0;
+
+    if !writable && executable {
+        if name.contains(proc_name) {
+            return Some(MemoryRange::Xa);
+        }
+        if name.ends_with(".oat") && name.starts_with("/data/misc") {
+            return Some(MemoryRange::Oa);
+        }
+        if name.contains("jit-cache") || name.contains("jit-code-cache") || name.contains("dalvik-jit") {
+            return Some(MemoryRange::Jc);
+        }
+        if name.contains("/data/") {
+            return Some(MemoryRange::Xa);
+        }
+        return Some(MemoryRange::Xs);
+    }
+
+    if name.starts_with("/dev/") {
+        let lower = name.to_ascii_lowercase();
+        if lower.contains("/dev/mali") || lower.contains("/dev/kgsl") || VIDEO_DEVICES.iter().any(|d| name.contains(d)) {
+            return Some(MemoryRange::V);
+        }
+        if name.contains("/dev/xLog") {
+            return Some(MemoryRange::B);
+        }
+    }
+
+    if name.starts_with("/system/fonts/")
+        || name.starts_with("/product/fonts/")
+        || name.starts_with("/data/data/com.google.android.gms/files/fonts/")
+    {
+        return Some(if readable && shared { MemoryRange::B } else { MemoryRange::O });
+    }
+    if name.starts_with("anon_inode:dma_buf") {
+        return Some(MemoryRange::B);
+    }
+
+    if start == 0x10001000 && readable && writable {
+        return Some(MemoryRange::S);
+    }
+
+    if !name.is_empty() {
+        if (name.contains("[anon:stack_and_tls:") || name.contains("[anon:thread signal stack]")) && readable && writable {
+            return Some(MemoryRange::Ts);
+        }
+        if name.ends_with(".vdex") && readable {
+            return Some(MemoryRange::Vx);
+        }
+        let dex = name.ends_with(".dex") || name.ends_with(".odex") || name.contains(".dex (del") || name.contains(".odex (del");
+        if dex && readable {
+            return Some(MemoryRange::Dx);
+        }
+        if name.contains("[anon:.bss]") {
+            return Some(MemoryRange::Cb);
+        }
+        if name.starts_with("/system/") || name.starts_with("/dev/zero") {
+            return Some(MemoryRange::O);
+        }
+        if name.contains("PPSSPP_RAM") {
+            return Some(MemoryRange::Ps);
+        }
+
+        if is_classifiable_name(name) {
+            if name.contains("dalvik") {
+                return Some(if is_dalvik_heap(name) { MemoryRange::Jh } else { MemoryRange::J });
+            }
+            if name.contains("/lib") && name.contains(".so") && (name.contains(proc_name) || name.contains("/data/")) {
+                return Some(MemoryRange::Cd);
+            }
+            if name.contains("malloc") || name.contains("anon:scudo:") {
+                return Some(MemoryRange::Ca);
+            }
+            if name.contains("[heap]") {
+                return Some(MemoryRange::Ch);
+            }
+            if name.contains("[stack") {
+                return Some(MemoryRange::S);
+            }
+            if name.starts_with("/dev/ashmem") && !name.contains("MemoryHeapBase") {
+                return Some(MemoryRange::As);
+            }
+        }
+    }
+
+    if name.is_empty() {
+        return Some(MemoryRange::An);
+    }
+    if !readable && !writable && !executable {
+        return Some(MemoryRange::Xx);
+    }
+    Some(MemoryRange::O)
+}
+
+/// 对驱动返回的区域条目分类
+pub fn classify_entry(entry: &WuwaMemRegionEntry, proc_name: &str) -> Option<MemoryRange> {
+    // 与 Kotlin 侧拿到的名字一致，使用还原转义后的名字
+    classify(entry.start, entry.end, entry.type_, &entry.name(), proc_name)
+}
+
+/// 保留范围在 `preset_mask` 中的区域，返回 `(start, end)` 列表
+pub fn filter_entries<'a, I>(entries: I, proc_name: &str, preset_mask: u32) -> Vec<(u64, u64)>
+where
+    I: IntoIterator<Item = &'a WuwaMemRegionEntry>,
+{
+    entries
+        .into_iter()
+        .filter(|entry| classify_entry(entry, proc_name).is_some_and(|range| preset_mask & range.bit() != 0))
+        .map(|entry| (entry.start, entry.end))
+        .collect()
+}
+
+/// 通过驱动查询进程的内存区域并按预设过滤
+pub fn query_filtered_regions(driver: &WuWaDriver, pid: i32, preset_mask: u32) -> Result<Vec<(u64, u64)>> {
+    let info = driver.get_process_info(pid)?;
+    let name_end = info.name.iter().position(|&c| c == 0).unwrap_or(info.name.len());
+    let proc_name = String::from_utf8_lossy(&info.name[..name_end]).into_owned();
+
+    let mapped = driver.query_mem_regions(pid, 0, 0)?.map()?;
+    let regions = filter_entries(mapped.entries(), &proc_name, preset_mask);
+    Ok(regions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROC: &str = "com.example.game";
+    const RW: u32 = MEM_READABLE | MEM_WRITABLE;
+    const RX: u32 = MEM_READABLE | MEM_EXECUTABLE;
+
+    fn entry(start: u64, type_: u32, name: &str) -> WuwaMemRegionEntry {
+        let mut entry = WuwaMemRegionEntry {
+            start,
+            end: start + 0x1000,
+            type_,
+            _reserved: 0,
+            name: [0; 4096],
+        };
+        entry.name[..name.len()].copy_from_slice(name.as_bytes());
+        entry
+    }
+
+    fn range_of(type_: u32, name: &str) -> Option<MemoryRange> {
+        classify_entry(&entry(0x7000_0000, type_, name), PROC)
+    }
+
+    #[test]
+    fn test_classify_heaps_and_stacks() {
+        assert_eq!(range_of(RW, "[anon:libc_malloc]"), Some(MemoryRange::Ca));
+        assert_eq!(range_of(RW, "[anon:scudo:primary]"), Some(MemoryRange::Ca));
+        assert_eq!(range_of(RW, "[heap]"), Some(MemoryRange::Ch));
+        assert_eq!(range_of(RW, "/dev/ashmem/dalvik-main space (deleted)"), Some(MemoryRange::Jh));
+        assert_eq!(range_of(RW, "[anon:dalvik-large object space]"), Some(MemoryRange::Jh));
+        assert_eq!(range_of(RW, "[anon:dalvik-zygote space]"), Some(MemoryRange::J));
+        assert_eq!(range_of(RW, "[anon:dalvik-main space (region space) live-bitmap]"), Some(MemoryRange::J));
+        assert_eq!(range_of(RW, "/dev/ashmem/shared_buffer"), Some(MemoryRange::As));
+        assert_eq!(range_of(RW, "[stack]"), Some(MemoryRange::S));
+        assert_eq!(range_of(RW, "[anon:stack_and_tls:1234]"), Some(MemoryRange::Ts));
+        assert_eq!(range_of(RW, ""), Some(MemoryRange::An));
+        assert_eq!(range_of(0, "[anon:guard]"), Some(MemoryRange::Xx));
+        assert_eq!(classify(0x1000, 0x1000, RW, "[heap]", PROC), None);
+    }
+
+    #[test]
+    fn test_classify_mapped_files() {
+        let app_lib = "/data/app/~~x1/com.example.game-2/lib/arm64/libgame.so";
+        assert_eq!(range_of(RX, app_lib), Some(MemoryRange::Xa));
+        assert_eq!(range_of(RW, app_lib), Some(MemoryRange::Cd));
+        assert_eq!(range_of(MEM_READABLE, app_lib), Some(MemoryRange::Cd));
+        assert_eq!(range_of(RW, "[anon:.bss]"), Some(MemoryRange::Cb));
+        assert_eq!(range_of(RX, "/system/lib64/libc.so"), Some(MemoryRange::Xs));
+        assert_eq!(range_of(RW, "/system/lib64/libc.so"), Some(MemoryRange::O));
+        assert_eq!(range_of(RX, "/data/local/tmp/libhook.so"), Some(MemoryRange::Xa));
+        assert_eq!(range_of(RX, "/data/misc/apexdata/boot.oat"), Some(MemoryRange::Oa));
+        assert_eq!(range_of(MEM_READABLE, "/data/app/base.vdex"), Some(MemoryRange::Vx));
+        assert_eq!(range_of(MEM_READABLE, "/data/app/base.odex"), Some(MemoryRange::Dx));
+        assert_eq!(range_of(RW, "/dev/kgsl-3d0"), Some(MemoryRange::V));
+        assert_eq!(range_of(RW, "/dev/MALI0"), Some(MemoryRange::V));
+        assert_eq!(range_of(MEM_READABLE | MEM_SHARED, "/system/fonts/Roboto.ttf"), Some(MemoryRange::B));
+    }
+
+    #[test]
+    fn test_filter_by_preset_mask() {
+        let entries = [
+            entry(0x1000, RW, "[anon:libc_malloc]"),
+            entry(0x3000, RW, "/data/app/com.example.game-1/lib/arm64/libgame.so"),
+            entry(0x5000, RW, "[anon:.bss]"),
+            entry(0x7000, RW, "[stack]"),
+            entry(0x9000, RX, "/system/lib64/libc.so"),
+        ];
+
+        let mask = MemoryRange::Ca.bit() | MemoryRange::Cd.bit() | MemoryRange::Cb.bit();
+        assert_eq!(filter_entries(&entries, PROC, mask), vec![(0x1000, 0x2000), (0x3000, 0x4000), (0x5000, 0x6000)]);
+        assert_eq!(filter_entries(&entries, PROC, MemoryRange::S.bit()), vec![(0x7000, 0x8000)]);
+        assert!(filter_entries(&entries, PROC, 0).is_empty());
+    }
+}
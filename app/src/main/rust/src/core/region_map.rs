@@ -0,0 +1,573 @@
+//! Cached region map of the bound process
+//!
+//! 用于判断一个 Qword 值是否指向已映射的内存，并生成 "模块+偏移" 字符串，
+//! 以及写入前判断目标页是否可写。
+//! 缓存按 pid 区分，重新绑定进程时失效；游戏分配或释放内存后区域会变化，可以显式失效，
+//! 缓存超过 `REGION_MAP_TTL` 后下一次使用时也会重新查询，新分配的堆不会一直被当成未映射。
+//! 按地址解析所在区域时，找不到区域且缓存已经超过 `REGION_CACHE_MAX_AGE` 会重新查询一次。
+
+use crate::core::globals::REGION_MAP;
+use crate::core::DriverManager;
+use crate::search::ValueType;
+use crate::wuwa::{MEM_EXECUTABLE, MEM_READABLE, MEM_WRITABLE};
+use anyhow::{Result, anyhow};
+use log::debug;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 地址解析找不到区域时，缓存超过这个时间就重新查询
+pub const REGION_CACHE_MAX_AGE: Duration = Duration::from_secs(3);
+
+/// 缓存的最长使用时间，超过后 `current_region_map` 重新查询
+pub const REGION_MAP_TTL: Duration = Duration::from_secs(10);
+
+/// 与 read_memory_unified 一致，去掉 MTE tag 等高位
+const ADDRESS_MASK: u64 = 0x0000_FFFF_FFFF_FFFF;
+
+/// nativeGetPointerTarget 返回的状态码
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
+pub enum PointerStatus {
+    /// 指针有效，目标地址已映射
+    Valid = 0,
+    /// 结果不是 Qword
+    NotQword = 1,
+    /// 读取结果地址失败
+    ReadFailed = 2,
+    /// 值没有指向任何已映射区域（悬空指针或普通整数）
+    Unmapped = 3,
+    /// 结果句柄无效（结果已被删除）
+    InvalidIndex = 4,
+}
+
+/// nativeClassifyAddresses 返回的地址所在区域的访问权限
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
+pub enum PageAccess {
+    Writable = 0,
+    /// 不可写也不可执行，例如 .rodata
+    ReadOnly = 1,
+    /// 不可写的代码段
+    Executable = 2,
+    /// 不在任何已知区域内
+    Unmapped = 3,
+}
+
+/// 目标页不可写时的写入错误，与普通的写入失败区分：UI 可以提示改用物理内存写入
+#[derive(Debug)]
+pub struct PageNotWritableError {
+    pub addr: u64,
+    pub access: PageAccess,
+}
+
+impl fmt::Display for PageNotWritableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = if self.access == PageAccess::Executable { "executable" } else { "read-only" };
+        write!(f, "Target page at 0x{:X} is {} and not writable", self.addr, kind)
+    }
+}
+
+impl std::error::Error for PageNotWritableError {}
+
+pub fn is_page_not_writable(error: &anyhow::Error) -> bool {
+    error.is::<PageNotWritableError>()
+}
+
+#[derive(Debug, Clone)]
+pub struct MappedRegion {
+    pub start: u64,
+    pub end: u64,
+    pub type_: u32,
+    pub name: String,
+}
+
+/// 地址所在的区域：区域名（文件映射只取文件名）、相对区域起始的偏移、区域的权限位
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedAddress {
+    pub name: String,
+    pub offset: u64,
+    pub perms: u32,
+}
+
+impl fmt::Display for ResolvedAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}+0x{:X}", self.name, self.offset)
+    }
+}
+
+/// 进程加载的一个文件：同一路径的所有映射合并为一项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleInfo {
+    /// 完整路径
+    pub name: String,
+    /// 最低的映射起始地址
+    pub base: u64,
+    /// 最高的映射结束地址
+    pub end: u64,
+    /// 是否有可执行的映射
+    pub is_executable_segment: bool,
+}
+
+impl ModuleInfo {
+    pub fn contains(&self, addr: u64) -> bool {
+        addr >= self.base && addr < self.end
+    }
+}
+
+#[derive(Debug)]
+pub struct RegionMap {
+    pid: i32,
+    /// 按起始地址排序
+    regions: Vec<MappedRegion>,
+    /// 文件映射路径 -> 模块基址（该路径起始地址最低的映射），`module_offset` 按结果逐个查询
+    module_bases: HashMap<String, u64>,
+    built_at: Instant,
+}
+
+impl RegionMap {
+    pub fn new(pid: i32, mut regions: Vec<MappedRegion>) -> Self {
+        regions.sort_by_key(|r| r.start);
+        let mut module_bases = HashMap::new();
+        for region in regions.iter().filter(|r| r.name.contains('/')) {
+            module_bases.entry(region.name.clone()).or_insert(region.start);
+        }
+        Self {
+            pid,
+            regions,
+            module_bases,
+            built_at: Instant::now(),
+        }
+    }
+
+    /// 距离查询区域列表过去的时间
+    pub fn age(&self) -> Duration {
+        self.built_at.elapsed()
+    }
+
+    /// 是否可以继续作为 `pid` 的缓存使用
+    pub fn is_fresh_for(&self, pid: i32) -> bool {
+        self.pid == pid && self.age() < REGION_MAP_TTL
+    }
+
+    /// 通过驱动查询进程的内存区域
+    pub fn query(driver_manager: &DriverManager, pid: i32) -> Result<Self> {
+        let driver = driver_manager.get_driver().ok_or_else(|| anyhow!("Driver is not initialized"))?;
+        let mapped = driver.query_mem_regions(pid, 0, 0)?.map()?;
+        let regions: Vec<MappedRegion> = mapped
+            .entries()
+            .iter()
+            .map(|entry| MappedRegion {
+                start: entry.start,
+                end: entry.end,
+                type_: entry.type_,
+                name: entry.name(),
+            })
+            .collect();
+
+        debug!("Region map refreshed for pid {}: {} regions", pid, regions.len());
+        Ok(Self::new(pid, regions))
+    }
+
+    pub fn pid(&self) -> i32 {
+        self.pid
+    }
+
+    pub fn regions(&self) -> &[MappedRegion] {
+        &self.regions
+    }
+
+    pub fn find(&self, addr: u64) -> Option<&MappedRegion> {
+        let addr = addr & ADDRESS_MASK;
+        let idx = self.regions.partition_point(|r| r.start <= addr);
+        let region = self.regions.get(idx.checked_sub(1)?)?;
+        (addr < region.end).then_some(region)
+    }
+
+    /// 不在任何区域内的地址所在的空隙 `[上一个区域的结束, 下一个区域的起始)`
+    pub fn gap_around(&self, addr: u64) -> (u64, u64) {
+        let addr = addr & ADDRESS_MASK;
+        let idx = self.regions.partition_point(|r| r.start <= addr);
+        let start = idx.checked_sub(1).map_or(0, |i| self.regions[i].end);
+        let end = self.regions.get(idx).map_or(u64::MAX, |r| r.start);
+        (start, end)
+    }
+
+    /// 用于验证绑定句柄的已知可读地址：优先第一个可读的文件映射（模块），否则第一个可读区域
+    pub fn probe_address(&self) -> Option<u64> {
+        let readable = || self.regions.iter().filter(|r| r.type_ & MEM_READABLE != 0);
+        readable().find(|r| r.name.contains('/')).or_else(|| readable().next()).map(|r| r.start)
+    }
+
+    /// 地址所在区域的访问权限，可写优先于可执行
+    pub fn classify(&self, addr: u64) -> PageAccess {
+        match self.find(addr) {
+            None => PageAccess::Unmapped,
+            Some(region) if region.type_ & MEM_WRITABLE != 0 => PageAccess::Writable,
+            Some(region) if region.type_ & MEM_EXECUTABLE != 0 => PageAccess::Executable,
+            Some(_) => PageAccess::ReadOnly,
+        }
+    }
+
+    /// 写入 [addr, addr + len) 前检查：落在已知的不可写区域时返回错误
+    ///
+    /// 不在任何区域内的地址不拦截，缓存可能早于新分配的内存，交给实际写入判断。
+    pub fn check_writable(&self, addr: u64, len: usize) -> Result<(), PageNotWritableError> {
+        let addr = addr & ADDRESS_MASK;
+        let last = addr + (len.max(1) as u64 - 1);
+        let mut current = addr;
+        loop {
+            let access = self.classify(current);
+            if matches!(access, PageAccess::ReadOnly | PageAccess::Executable) {
+                return Err(PageNotWritableError { addr: current, access });
+            }
+            // 跨越多个区域时检查每个区域
+            let next = match self.find(current) {
+                Some(region) => region.end,
+                None => self.gap_around(current).1,
+            };
+            if next > last {
+                return Ok(());
+            }
+            current = next;
+        }
+    }
+
+    /// 值是否指向一个可读的已映射区域
+    pub fn is_pointer(&self, value: u64) -> bool {
+        self.find(value).is_some_and(|r| r.type_ & MEM_READABLE != 0)
+    }
+
+    /// 文件映射区域返回 "libxxx.so+0x1234"，偏移相对于该模块的第一个映射
+    pub fn module_offset(&self, addr: u64) -> Option<String> {
+        let addr = addr & ADDRESS_MASK;
+        let region = self.find(addr)?;
+        if !region.name.contains('/') {
+            return None;
+        }
+
+        let module_base = *self.module_bases.get(&region.name)?;
+        let module_name = region.name.rsplit('/').next().unwrap_or(&region.name);
+        Some(format!("{}+0x{:X}", module_name, addr - module_base))
+    }
+
+    /// 地址所在的区域和区域内的偏移，不在任何区域内时返回 None
+    pub fn resolve(&self, addr: u64) -> Option<ResolvedAddress> {
+        let addr = addr & ADDRESS_MASK;
+        let region = self.find(addr)?;
+        let name = match region.name.rsplit_once('/') {
+            Some((_, file)) if region.name.starts_with('/') => file,
+            _ if region.name.is_empty() => "[anon]",
+            _ => &region.name,
+        };
+        Some(ResolvedAddress {
+            name: name.to_string(),
+            offset: addr - region.start,
+            perms: region.type_,
+        })
+    }
+
+    /// 模块的基址（该模块的第一个映射），`module` 可以是完整路径或文件名，与 `module_offset` 对应
+    pub fn module_base(&self, module: &str) -> Option<u64> {
+        self.regions
+            .iter()
+            .find(|r| r.name.contains('/') && (r.name == module || r.name.rsplit('/').next() == Some(module)))
+            .map(|r| r.start)
+    }
+
+    /// 模块从第一个映射起始到最后一个映射结束的地址范围，匹配规则与 `module_base` 相同
+    pub fn module_range(&self, module: &str) -> Option<(u64, u64)> {
+        let mut mappings = self
+            .regions
+            .iter()
+            .filter(|r| r.name.contains('/') && (r.name == module || r.name.rsplit('/').next() == Some(module)));
+        let first = mappings.next()?;
+        let end = mappings.fold(first.end, |end, r| end.max(r.end));
+        Some((first.start, end))
+    }
+
+    /// 按路径合并文件映射得到的模块列表，按基址排序
+    pub fn modules(&self) -> Vec<ModuleInfo> {
+        let mut modules: Vec<ModuleInfo> = Vec::new();
+        let mut by_name: HashMap<&str, usize> = HashMap::new();
+        for region in self.regions.iter().filter(|r| r.name.starts_with('/')) {
+            let executable = region.type_ & MEM_EXECUTABLE != 0;
+            match by_name.get(region.name.as_str()) {
+                Some(&index) => {
+                    let module = &mut modules[index];
+                    module.base = module.base.min(region.start);
+                    module.end = module.end.max(region.end);
+                    module.is_executable_segment |= executable;
+                },
+                None => {
+                    by_name.insert(&region.name, modules.len());
+                    modules.push(ModuleInfo {
+                        name: region.name.clone(),
+                        base: region.start,
+                        end: region.end,
+                        is_executable_segment: executable,
+                    });
+                },
+            }
+        }
+        modules.sort_by_key(|module| module.base);
+        modules
+    }
+
+    /// 结果值的指针信息：(是否指针, 目标的模块+偏移)
+    pub fn pointer_info(&self, value_type: ValueType, value: &[u8]) -> (bool, Option<String>) {
+        if value_type != ValueType::Qword || value.len() < 8 {
+            return (false, None);
+        }
+        let target = u64::from_le_bytes(value[..8].try_into().unwrap());
+        if !self.is_pointer(target) {
+            return (false, None);
+        }
+        (true, self.module_offset(target))
+    }
+
+    /// 重新读取结果地址上的指针并解引用，用于点击跳转
+    pub fn resolve_pointer<R>(&self, addr: u64, value_type: ValueType, read: R) -> (PointerStatus, u64)
+    where
+        R: FnOnce(u64, &mut [u8]) -> Result<()>,
+    {
+        if value_type != ValueType::Qword {
+            return (PointerStatus::NotQword, 0);
+        }
+
+        let mut buffer = [0u8; 8];
+        if read(addr, &mut buffer).is_err() {
+            return (PointerStatus::ReadFailed, 0);
+        }
+
+        let target = u64::from_le_bytes(buffer) & ADDRESS_MASK;
+        if self.is_pointer(target) {
+            (PointerStatus::Valid, target)
+        } else {
+            (PointerStatus::Unmapped, target)
+        }
+    }
+}
+
+/// 获取当前绑定进程的区域映射，pid 变化或缓存超过 `REGION_MAP_TTL` 时重新查询
+pub fn current_region_map(driver_manager: &DriverManager) -> Result<Arc<RegionMap>> {
+    let pid = driver_manager.get_bound_pid();
+    if pid == 0 {
+        return Err(anyhow!("No process is bound"));
+    }
+
+    if let Ok(cached) = REGION_MAP.read()
+        && let Some(map) = cached.as_ref()
+        && map.is_fresh_for(pid)
+    {
+        return Ok(Arc::clone(map));
+    }
+
+    let map = Arc::new(RegionMap::query(driver_manager, pid)?);
+    if let Ok(mut cached) = REGION_MAP.write() {
+        *cached = Some(Arc::clone(&map));
+    }
+    Ok(map)
+}
+
+/// 按缓存的区域列表解析一组地址
+///
+/// 有地址找不到区域、且缓存已经超过 `REGION_CACHE_MAX_AGE` 时重新查询一次区域列表，
+/// 再解析这些地址（新分配的内存）；一批地址最多重新查询一次。
+pub fn resolve_addresses(driver_manager: &DriverManager, addrs: &[u64]) -> Result<Vec<Option<ResolvedAddress>>> {
+    let map = current_region_map(driver_manager)?;
+    let mut resolved: Vec<Option<ResolvedAddress>> = addrs.iter().map(|&addr| map.resolve(addr)).collect();
+    if resolved.iter().all(Option::is_some) || map.age() < REGION_CACHE_MAX_AGE {
+        return Ok(resolved);
+    }
+
+    invalidate_region_map();
+    let map = current_region_map(driver_manager)?;
+    for (slot, &addr) in resolved.iter_mut().zip(addrs) {
+        if slot.is_none() {
+            *slot = map.resolve(addr);
+        }
+    }
+    Ok(resolved)
+}
+
+/// 绑定/解绑进程时使缓存失效
+pub fn invalidate_region_map() {
+    if let Ok(mut cached) = REGION_MAP.write() {
+        *cached = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::tests::mock_memory::MockMemory;
+
+    const LIB_PATH: &str = "/data/app/com.example/lib/arm64/libgame.so";
+    const LIB_BASE: u64 = 0x7A00000000;
+    const HEAP_BASE: u64 = 0x7B00000000;
+    const HOLE: u64 = 0x7C00000000;
+
+    fn test_map() -> RegionMap {
+        RegionMap::new(
+            1234,
+            vec![
+                MappedRegion {
+                    start: HEAP_BASE,
+                    end: HEAP_BASE + 0x10000,
+                    type_: 0b011,
+                    name: "[anon:libc_malloc]".to_string(),
+                },
+                MappedRegion {
+                    start: LIB_BASE + 0x10000,
+                    end: LIB_BASE + 0x20000,
+                    type_: 0b011,
+                    name: LIB_PATH.to_string(),
+                },
+                MappedRegion {
+                    start: LIB_BASE,
+                    end: LIB_BASE + 0x10000,
+                    type_: 0b101,
+                    name: LIB_PATH.to_string(),
+                },
+                MappedRegion {
+                    start: HOLE + 0x10000,
+                    end: HOLE + 0x20000,
+                    type_: 0,
+                    name: String::new(),
+                },
+            ],
+        )
+    }
+
+    #[test]
+    fn test_pointer_flags() {
+        let map = test_map();
+
+        // 指向模块第二个映射内的有效指针
+        let (is_pointer, module) = map.pointer_info(ValueType::Qword, &(LIB_BASE + 0x12340).to_le_bytes());
+        assert!(is_pointer);
+        assert_eq!(module.as_deref(), Some("libgame.so+0x12340"));
+
+        // 带 tag 的堆指针，匿名区域没有模块字符串
+        let tagged = (HEAP_BASE + 0x80) | 0xB400_0000_0000_0000;
+        let (is_pointer, module) = map.pointer_info(ValueType::Qword, &tagged.to_le_bytes());
+        assert!(is_pointer);
+        assert_eq!(module, None);
+
+        // 悬空指针：落在未映射的空洞里
+        assert_eq!(map.pointer_info(ValueType::Qword, &(HOLE + 0x100).to_le_bytes()), (false, None));
+        // 不可读区域
+        assert_eq!(map.pointer_info(ValueType::Qword, &(HOLE + 0x10100).to_le_bytes()), (false, None));
+        // 普通大整数
+        assert_eq!(map.pointer_info(ValueType::Qword, &0x1234_5678_9ABC_DEF0u64.to_le_bytes()), (false, None));
+        // 非 Qword 不判断
+        assert_eq!(map.pointer_info(ValueType::Dword, &(LIB_BASE + 0x100).to_le_bytes()), (false, None));
+    }
+
+    #[test]
+    fn test_resolve_address() {
+        let map = test_map();
+        let resolved = map.resolve(LIB_BASE + 0x12340).unwrap();
+        // 偏移相对于所在的映射，不是模块基址
+        assert_eq!(resolved, ResolvedAddress { name: "libgame.so".to_string(), offset: 0x2340, perms: 0b011 });
+        assert_eq!(resolved.to_string(), "libgame.so+0x2340");
+
+        let tagged = (HEAP_BASE + 0x80) | 0xB400_0000_0000_0000;
+        assert_eq!(map.resolve(tagged).unwrap().to_string(), "[anon:libc_malloc]+0x80");
+        assert_eq!(map.resolve(HOLE + 0x10008).unwrap().to_string(), "[anon]+0x8");
+        assert_eq!(map.resolve(HOLE), None);
+        assert_eq!(map.resolve(LIB_BASE + 0x20000), None);
+        assert!(map.age() < REGION_CACHE_MAX_AGE);
+    }
+
+    #[test]
+    fn test_cache_freshness() {
+        let mut map = test_map();
+        assert!(map.is_fresh_for(1234));
+        assert!(!map.is_fresh_for(4321));
+
+        // 超过 TTL 后不再复用，目标新分配的区域会在下一次查询中出现
+        map.built_at = Instant::now() - REGION_MAP_TTL;
+        assert!(!map.is_fresh_for(1234));
+    }
+
+    #[test]
+    fn test_module_base() {
+        let map = test_map();
+        assert_eq!(map.module_base("libgame.so"), Some(LIB_BASE));
+        assert_eq!(map.module_base(LIB_PATH), Some(LIB_BASE));
+        assert_eq!(map.module_base("libother.so"), None);
+        // 匿名区域不是模块
+        assert_eq!(map.module_base("[anon:libc_malloc]"), None);
+
+        assert_eq!(map.module_range("libgame.so"), Some((LIB_BASE, LIB_BASE + 0x20000)));
+        assert_eq!(map.module_range("libother.so"), None);
+    }
+
+    #[test]
+    fn test_modules_group_mappings_by_path() {
+        let mut regions = test_map().regions().to_vec();
+        regions.push(MappedRegion {
+            start: HOLE + 0x40000,
+            end: HOLE + 0x42000,
+            type_: 0b001,
+            name: "/system/fonts/Roboto.ttf".to_string(),
+        });
+        let map = RegionMap::new(1234, regions);
+
+        assert_eq!(
+            map.modules(),
+            vec![
+                ModuleInfo { name: LIB_PATH.to_string(), base: LIB_BASE, end: LIB_BASE + 0x20000, is_executable_segment: true },
+                ModuleInfo { name: "/system/fonts/Roboto.ttf".to_string(), base: HOLE + 0x40000, end: HOLE + 0x42000, is_executable_segment: false },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_pointer_status() {
+        let map = test_map();
+        let mut mem = MockMemory::new();
+        let base = mem.malloc(0x7000000000, 0x1000).unwrap();
+        mem.mem_write_u64(base, LIB_BASE + 0x40).unwrap();
+        mem.mem_write_u64(base + 8, HOLE + 0x40).unwrap();
+        mem.mem_write_u64(base + 16, 0x1234_5678_9ABC_DEF0).unwrap();
+
+        let read = |addr: u64, buf: &mut [u8]| -> Result<()> {
+            buf.copy_from_slice(&mem.mem_read(addr, buf.len())?);
+            Ok(())
+        };
+
+        assert_eq!(map.resolve_pointer(base, ValueType::Qword, read), (PointerStatus::Valid, LIB_BASE + 0x40));
+        assert_eq!(map.resolve_pointer(base + 8, ValueType::Qword, read), (PointerStatus::Unmapped, HOLE + 0x40));
+        assert_eq!(map.resolve_pointer(base + 16, ValueType::Qword, read).0, PointerStatus::Unmapped);
+        assert_eq!(map.resolve_pointer(0x6000000000, ValueType::Qword, read), (PointerStatus::ReadFailed, 0));
+        assert_eq!(map.resolve_pointer(base, ValueType::Dword, read), (PointerStatus::NotQword, 0));
+    }
+
+    #[test]
+    fn test_classify_and_check_writable() {
+        let map = test_map();
+        assert_eq!(map.classify(HEAP_BASE + 0x10), PageAccess::Writable);
+        assert_eq!(map.classify(LIB_BASE + 0x10), PageAccess::Executable);
+        assert_eq!(map.classify(HOLE + 0x10010), PageAccess::ReadOnly);
+        assert_eq!(map.classify(HOLE), PageAccess::Unmapped);
+        assert_eq!(map.classify((HEAP_BASE + 0x10) | 0xB400_0000_0000_0000), PageAccess::Writable);
+
+        assert!(map.check_writable(HEAP_BASE + 0x10, 8).is_ok());
+        // 未知地址不拦截
+        assert!(map.check_writable(HOLE, 8).is_ok());
+
+        let err = map.check_writable(LIB_BASE + 0x100, 4).unwrap_err();
+        assert_eq!((err.addr, err.access), (LIB_BASE + 0x100, PageAccess::Executable));
+        assert!(is_page_not_writable(&anyhow::Error::new(err)));
+        assert_eq!(map.check_writable(HOLE + 0x10010, 4).unwrap_err().access, PageAccess::ReadOnly);
+
+        // 跨越区域时检查后面的每个区域
+        assert!(map.check_writable(LIB_BASE + 0x1FFFC, 8).is_ok());
+        let err = map.check_writable(HOLE + 0xFFFC, 8).unwrap_err();
+        assert_eq!((err.addr, err.access), (HOLE + 0x10000, PageAccess::ReadOnly));
+    }
+}
@@ -0,0 +1,201 @@
+//! Region snapshots
+//!
+//! 找动态分配的对象时，先记下进程的内存区域列表，在游戏里触发一次操作，再和当前列表比较，
+//! 得到新映射、已释放和大小变化的区域。快照保存在 native 侧，Kotlin 只持有快照 id。
+//!
+//! 区域按 (起始地址, 名称) 对应：结束地址不同的同一区域算作扩大或缩小（例如堆扩展），
+//! 不会被拆成一条新增加一条删除。
+
+use crate::core::region_map::MappedRegion;
+use anyhow::{Result, anyhow};
+use log::warn;
+use std::collections::{HashMap, VecDeque};
+
+/// 最多同时保留的快照数，超出时丢弃最早的快照
+pub const MAX_REGION_SNAPSHOTS: usize = 8;
+
+/// 区域的变化
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
+pub enum RegionChange {
+    /// 快照之后新映射的区域
+    New = 0,
+    /// 快照之后被释放的区域
+    Removed = 1,
+    /// 结束地址变大
+    Grown = 2,
+    /// 结束地址变小
+    Shrunk = 3,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegionDiffEntry {
+    pub start: u64,
+    /// 当前的结束地址，Removed 为快照时的结束地址
+    pub end: u64,
+    /// 快照时的结束地址，New 与 end 相同
+    pub old_end: u64,
+    pub type_: u32,
+    pub name: String,
+    pub change: RegionChange,
+}
+
+/// 比较两次的区域列表，结果按起始地址排序，没有变化的区域不出现
+pub fn diff_regions(old: &[MappedRegion], new: &[MappedRegion]) -> Vec<RegionDiffEntry> {
+    let mut remaining: HashMap<(u64, &str), &MappedRegion> = old.iter().map(|r| ((r.start, r.name.as_str()), r)).collect();
+    let mut diff = Vec::new();
+
+    for region in new {
+        let (change, old_end) = match remaining.remove(&(region.start, region.name.as_str())) {
+            Some(before) if before.end == region.end => continue,
+            Some(before) if before.end < region.end => (RegionChange::Grown, before.end),
+            Some(before) => (RegionChange::Shrunk, before.end),
+            None => (RegionChange::New, region.end),
+        };
+        diff.push(RegionDiffEntry {
+            start: region.start,
+            end: region.end,
+            old_end,
+            type_: region.type_,
+            name: region.name.clone(),
+            change,
+        });
+    }
+
+    diff.extend(remaining.into_values().map(|region| RegionDiffEntry {
+        start: region.start,
+        end: region.end,
+        old_end: region.end,
+        type_: region.type_,
+        name: region.name.clone(),
+        change: RegionChange::Removed,
+    }));
+    diff.sort_by_key(|entry| entry.start);
+    diff
+}
+
+struct RegionSnapshot {
+    id: i32,
+    pid: i32,
+    regions: Vec<MappedRegion>,
+}
+
+#[derive(Default)]
+pub struct RegionSnapshots {
+    next_id: i32,
+    snapshots: VecDeque<RegionSnapshot>,
+}
+
+impl RegionSnapshots {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 保存一次区域列表，返回快照 id
+    pub fn insert(&mut self, pid: i32, regions: Vec<MappedRegion>) -> i32 {
+        if self.snapshots.len() >= MAX_REGION_SNAPSHOTS
+            && let Some(evicted) = self.snapshots.pop_front()
+        {
+            warn!("Too many region snapshots, dropping snapshot {}", evicted.id);
+        }
+        self.next_id = self.next_id.wrapping_add(1).max(1);
+        self.snapshots.push_back(RegionSnapshot { id: self.next_id, pid, regions });
+        self.next_id
+    }
+
+    /// 快照对应的进程
+    pub fn pid(&self, id: i32) -> Option<i32> {
+        self.find(id).map(|snapshot| snapshot.pid)
+    }
+
+    /// 和快照比较，current 是同一进程当前的区域列表
+    pub fn diff(&self, id: i32, current: &[MappedRegion]) -> Result<Vec<RegionDiffEntry>> {
+        let snapshot = self.find(id).ok_or_else(|| anyhow!("Region snapshot {} does not exist", id))?;
+        Ok(diff_regions(&snapshot.regions, current))
+    }
+
+    /// 释放快照，快照不存在时返回 false
+    pub fn release(&mut self, id: i32) -> bool {
+        let before = self.snapshots.len();
+        self.snapshots.retain(|snapshot| snapshot.id != id);
+        self.snapshots.len() != before
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    fn find(&self, id: i32) -> Option<&RegionSnapshot> {
+        self.snapshots.iter().find(|snapshot| snapshot.id == id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn region(start: u64, end: u64, name: &str) -> MappedRegion {
+        MappedRegion { start, end, type_: 3, name: name.to_string() }
+    }
+
+    #[test]
+    fn test_diff_reports_new_removed_and_resized() {
+        let old = vec![
+            region(0x1000, 0x2000, "/system/lib64/libc.so"),
+            region(0x10000, 0x20000, "[anon:libc_malloc]"),
+            region(0x30000, 0x31000, "[anon:scudo:primary]"),
+            region(0x40000, 0x48000, "[stack]"),
+        ];
+        let new = vec![
+            region(0x1000, 0x2000, "/system/lib64/libc.so"),
+            // 堆扩展：同一起始地址和名称，结束地址变大
+            region(0x10000, 0x28000, "[anon:libc_malloc]"),
+            region(0x40000, 0x44000, "[stack]"),
+            region(0x50000, 0x58000, "[anon:scudo:primary]"),
+            // 起始地址相同但名称不同，视为旧区域释放后新映射
+            region(0x30000, 0x31000, "[anon:dalvik-main space]"),
+        ];
+
+        let diff = diff_regions(&old, &new);
+        let summary: Vec<(u64, u64, u64, RegionChange)> = diff.iter().map(|e| (e.start, e.end, e.old_end, e.change)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                (0x10000, 0x28000, 0x20000, RegionChange::Grown),
+                (0x30000, 0x31000, 0x31000, RegionChange::New),
+                (0x30000, 0x31000, 0x31000, RegionChange::Removed),
+                (0x40000, 0x44000, 0x48000, RegionChange::Shrunk),
+                (0x50000, 0x58000, 0x58000, RegionChange::New),
+            ]
+        );
+        assert_eq!(diff[1].name, "[anon:dalvik-main space]");
+        assert_eq!(diff[2].name, "[anon:scudo:primary]");
+
+        assert!(diff_regions(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn test_snapshots_are_bounded_and_released() {
+        let mut snapshots = RegionSnapshots::new();
+        let first = snapshots.insert(100, vec![region(0x1000, 0x2000, "a")]);
+        let ids: Vec<i32> = (0..MAX_REGION_SNAPSHOTS).map(|_| snapshots.insert(100, Vec::new())).collect();
+
+        // 最早的快照被丢弃
+        assert_eq!(snapshots.len(), MAX_REGION_SNAPSHOTS);
+        assert!(snapshots.diff(first, &[]).is_err());
+        assert_eq!(snapshots.pid(ids[0]), Some(100));
+
+        let diff = snapshots.diff(ids[0], &[region(0x1000, 0x2000, "a")]).unwrap();
+        assert_eq!(diff.len(), 1);
+        assert_eq!(diff[0].change, RegionChange::New);
+
+        assert!(snapshots.release(ids[0]));
+        assert!(!snapshots.release(ids[0]));
+        assert_eq!(snapshots.pid(ids[0]), None);
+        assert_eq!(snapshots.len(), MAX_REGION_SNAPSHOTS - 1);
+    }
+}
@@ -0,0 +1,381 @@
+//! Saved Address Manager - 保存的地址列表
+//!
+//! 保存列表中的每个条目是 (地址, 类型, 标签, 冻结标记)，按添加顺序保存，同一地址只有一个条目。
+//! 刷新在 tokio 的阻塞线程上进行：同一页内的地址合并为一次读取（与观察管理器相同），
+//! 读到的值写回条目后递增代数，Kotlin 轮询代数，变化后再取格式化的值，JNI 线程不等待读取。
+//!
+//! 条目不属于某个进程：重新绑定或进程退出后条目保留，读取失败的值显示为 N/A，可读后恢复。
+//! 冻结标记只是记录，实际写入由冻结管理器完成，见 `jni_interface::saved_addresses`。
+
+use crate::core::AccessQos;
+use crate::core::globals::{DRIVER_MANAGER, PAGE_SIZE};
+use crate::core::watch_manager::plan_reads;
+use crate::search::ValueType;
+use crate::search::format::format_value;
+use anyhow::Result;
+use log::{debug, error};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// 保存的条目
+#[derive(Debug, Clone)]
+pub struct SavedEntry {
+    pub address: u64,
+    pub value_type: ValueType,
+    pub label: String,
+    pub frozen: bool,
+    /// 最近一次刷新读到的值，只有前 `value_type.size()` 字节有效；读取失败或还没有刷新时为 None
+    value: Option<[u8; 8]>,
+}
+
+impl SavedEntry {
+    fn new(address: u64, value_type: ValueType, label: String) -> Self {
+        Self {
+            address,
+            value_type,
+            label,
+            frozen: false,
+            value: None,
+        }
+    }
+
+    fn size(&self) -> usize {
+        self.value_type.size().clamp(1, 8)
+    }
+}
+
+/// 条目信息（供 UI 列出）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SavedInfo {
+    pub address: u64,
+    pub value_type: i32,
+    pub label: String,
+    pub frozen: bool,
+}
+
+/// 一次刷新的统计
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RefreshStats {
+    pub readable: usize,
+    pub unreadable: usize,
+    /// 实际发出的读取次数
+    pub reads: usize,
+}
+
+/// 保存的地址列表
+pub struct SavedAddressManager {
+    entries: Arc<RwLock<Vec<SavedEntry>>>,
+    /// 列表或值变化时递增
+    generation: Arc<AtomicU64>,
+    /// 是否有刷新正在进行，进行中时新的刷新请求直接忽略
+    refreshing: Arc<AtomicBool>,
+}
+
+impl SavedAddressManager {
+    pub fn new() -> Self {
+        Self {
+            entries: Arc::new(RwLock::new(Vec::new())),
+            generation: Arc::new(AtomicU64::new(0)),
+            refreshing: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    fn read_entries(&self) -> std::sync::RwLockReadGuard<'_, Vec<SavedEntry>> {
+        self.entries.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write_entries(&self) -> std::sync::RwLockWriteGuard<'_, Vec<SavedEntry>> {
+        self.entries.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn bump(&self) {
+        self.generation.fetch_add(1, Ordering::Release);
+    }
+
+    /// 添加条目；地址已经保存时更新类型和标签，类型变化时旧的值作废
+    pub fn add(&self, address: u64, value_type: ValueType, label: String) {
+        {
+            let mut entries = self.write_entries();
+            match entries.iter_mut().find(|e| e.address == address) {
+                Some(entry) => {
+                    if entry.value_type != value_type {
+                        entry.value_type = value_type;
+                        entry.value = None;
+                    }
+                    entry.label = label;
+                },
+                None => entries.push(SavedEntry::new(address, value_type, label)),
+            }
+        }
+        self.bump();
+    }
+
+    /// 删除条目，返回被删除的条目
+    pub fn remove(&self, address: u64) -> Option<SavedEntry> {
+        let removed = {
+            let mut entries = self.write_entries();
+            let index = entries.iter().position(|e| e.address == address)?;
+            entries.remove(index)
+        };
+        self.bump();
+        Some(removed)
+    }
+
+    /// 清空列表，返回被删除的条目
+    pub fn clear(&self) -> Vec<SavedEntry> {
+        let removed = std::mem::take(&mut *self.write_entries());
+        self.bump();
+        removed
+    }
+
+    /// 设置冻结标记，地址没有保存时返回 false
+    pub fn set_frozen(&self, address: u64, frozen: bool) -> bool {
+        let found = match self.write_entries().iter_mut().find(|e| e.address == address) {
+            Some(entry) => {
+                entry.frozen = frozen;
+                true
+            },
+            None => false,
+        };
+        if found {
+            self.bump();
+        }
+        found
+    }
+
+    /// 条目的副本
+    pub fn get(&self, address: u64) -> Option<SavedEntry> {
+        self.read_entries().iter().find(|e| e.address == address).cloned()
+    }
+
+    /// 最近一次刷新读到的值（`value_type.size()` 字节），读取失败或还没有刷新时返回 None
+    pub fn current_value(&self, address: u64) -> Option<Vec<u8>> {
+        self.read_entries()
+            .iter()
+            .find(|e| e.address == address)
+            .and_then(|e| e.value.map(|bytes| bytes[..e.size()].to_vec()))
+    }
+
+    /// 按添加顺序列出所有条目
+    pub fn list(&self) -> Vec<SavedInfo> {
+        self.read_entries()
+            .iter()
+            .map(|e| SavedInfo {
+                address: e.address,
+                value_type: e.value_type.to_id(),
+                label: e.label.clone(),
+                frozen: e.frozen,
+            })
+            .collect()
+    }
+
+    /// 格式化的值，与 `list` 的顺序相同，读取失败或还没有刷新的值为 N/A
+    pub fn formatted_values(&self) -> Vec<String> {
+        self.read_entries()
+            .iter()
+            .map(|e| {
+                let mut out = String::new();
+                match e.value {
+                    Some(bytes) => format_value(&mut out, &bytes[..e.size()], e.value_type),
+                    None => format_value(&mut out, &[], e.value_type),
+                }
+                out
+            })
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.read_entries().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.read_entries().is_empty()
+    }
+
+    /// 当前代数，列表或值变化时递增
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    /// 在当前 tokio 运行时的阻塞线程上刷新一次，立即返回；已有刷新在进行时返回 false
+    pub fn refresh(&self) -> bool {
+        if self.is_empty() || self.refreshing.swap(true, Ordering::AcqRel) {
+            return false;
+        }
+
+        let entries = Arc::clone(&self.entries);
+        let generation = Arc::clone(&self.generation);
+        let refreshing = Arc::clone(&self.refreshing);
+        tokio::task::spawn_blocking(move || {
+            let manager = match DRIVER_MANAGER.read() {
+                Ok(m) => m,
+                Err(e) => {
+                    error!("SavedAddressManager: 无法获取 DRIVER_MANAGER 读锁: {}", e);
+                    refreshing.store(false, Ordering::Release);
+                    return;
+                },
+            };
+            let bound = manager.is_process_bound();
+            let stats = Self::refresh_with(&entries, *PAGE_SIZE, |addr, buf| {
+                if !bound {
+                    return Err(anyhow::anyhow!("No process is bound"));
+                }
+                manager.read_memory_with_qos(addr, buf, None, AccessQos::Interactive)
+            });
+            drop(manager);
+            debug!("SavedAddressManager: 刷新 {} 个条目，{} 个不可读，{} 次读取", stats.readable + stats.unreadable, stats.unreadable, stats.reads);
+
+            generation.fetch_add(1, Ordering::Release);
+            refreshing.store(false, Ordering::Release);
+        });
+        true
+    }
+
+    /// 刷新一轮：只在取条目快照和写回时持有锁，读取期间被删除或改了类型的条目不写回
+    fn refresh_with<R>(entries: &RwLock<Vec<SavedEntry>>, page_size: usize, mut read: R) -> RefreshStats
+    where
+        R: FnMut(u64, &mut [u8]) -> Result<()>,
+    {
+        let mut targets: Vec<(u64, usize)> = entries
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|e| (e.address, e.size()))
+            .collect();
+        targets.sort_unstable();
+
+        let mut stats = RefreshStats::default();
+        let mut values: HashMap<u64, (usize, Option<[u8; 8]>)> = HashMap::with_capacity(targets.len());
+        let mut buffer = Vec::new();
+        for span in plan_reads(&targets, page_size) {
+            buffer.resize(span.len, 0);
+            let ok = read(span.start, &mut buffer).is_ok();
+            stats.reads += 1;
+
+            for (address, size) in span.addresses {
+                let value = ok.then(|| {
+                    let offset = (address - span.start) as usize;
+                    let mut bytes = [0u8; 8];
+                    bytes[..size].copy_from_slice(&buffer[offset..offset + size]);
+                    bytes
+                });
+                if value.is_some() {
+                    stats.readable += 1;
+                } else {
+                    stats.unreadable += 1;
+                }
+                values.insert(address, (size, value));
+            }
+        }
+
+        for entry in entries.write().unwrap_or_else(|e| e.into_inner()).iter_mut() {
+            if let Some(&(size, value)) = values.get(&entry.address)
+                && size == entry.size()
+            {
+                entry.value = value;
+            }
+        }
+        stats
+    }
+}
+
+impl Default for SavedAddressManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    const PAGE: usize = 4096;
+    const BASE: u64 = 0x7000_0000;
+
+    #[test]
+    fn test_refresh_reads_each_page_once() {
+        let manager = SavedAddressManager::new();
+        manager.add(BASE + 0x10, ValueType::Dword, "HP".to_string());
+        manager.add(BASE + PAGE as u64 + 8, ValueType::Float, String::new());
+        manager.add(BASE, ValueType::Word, "MP".to_string());
+        assert_eq!(manager.formatted_values(), vec!["N/A", "N/A", "N/A"]);
+
+        let stats = SavedAddressManager::refresh_with(&manager.entries, PAGE, |addr, buf| {
+            if addr == BASE {
+                buf[..2].copy_from_slice(&(-5i16).to_le_bytes());
+                buf[0x10..0x14].copy_from_slice(&1000u32.to_le_bytes());
+            } else {
+                buf.copy_from_slice(&1.5f32.to_le_bytes());
+            }
+            Ok(())
+        });
+        assert_eq!(stats, RefreshStats { readable: 3, unreadable: 0, reads: 2 });
+
+        // 值与列表的顺序相同（添加顺序）
+        assert_eq!(manager.formatted_values(), vec!["1000", "1.5", "-5"]);
+        let labels: Vec<String> = manager.list().into_iter().map(|info| info.label).collect();
+        assert_eq!(labels, vec!["HP", "", "MP"]);
+        assert_eq!(manager.current_value(BASE + 0x10), Some(1000u32.to_le_bytes().to_vec()));
+    }
+
+    #[test]
+    fn test_unreadable_entries_are_kept() {
+        let manager = SavedAddressManager::new();
+        manager.add(BASE, ValueType::Dword, String::new());
+        manager.add(BASE + 0x10_0000, ValueType::Dword, String::new());
+        let flaky = |fail: bool| {
+            move |addr: u64, buf: &mut [u8]| -> Result<()> {
+                if fail {
+                    return Err(anyhow!("no process"));
+                }
+                buf[..4].copy_from_slice(&((addr - BASE) as u32).to_le_bytes());
+                Ok(())
+            }
+        };
+
+        SavedAddressManager::refresh_with(&manager.entries, PAGE, flaky(false));
+        assert_eq!(manager.formatted_values(), vec!["0", "1048576"]);
+
+        // 进程退出后值变为 N/A，条目保留，可读后恢复
+        for _ in 0..100 {
+            SavedAddressManager::refresh_with(&manager.entries, PAGE, flaky(true));
+        }
+        assert_eq!(manager.formatted_values(), vec!["N/A", "N/A"]);
+        assert_eq!(manager.current_value(BASE), None);
+        SavedAddressManager::refresh_with(&manager.entries, PAGE, flaky(false));
+        assert_eq!(manager.formatted_values(), vec!["0", "1048576"]);
+    }
+
+    #[test]
+    fn test_edits_bump_generation() {
+        let manager = SavedAddressManager::new();
+        let start = manager.generation();
+        manager.add(BASE, ValueType::Dword, "a".to_string());
+        SavedAddressManager::refresh_with(&manager.entries, PAGE, |_, buf| {
+            buf.fill(1);
+            Ok(())
+        });
+
+        // 重新添加同一地址：只更新标签和类型，类型变化后旧值作废
+        manager.add(BASE, ValueType::Dword, "b".to_string());
+        assert_eq!(manager.len(), 1);
+        assert!(manager.current_value(BASE).is_some());
+        manager.add(BASE, ValueType::Byte, "b".to_string());
+        assert_eq!(manager.current_value(BASE), None);
+
+        assert!(manager.set_frozen(BASE, true));
+        assert!(!manager.set_frozen(BASE + 4, true));
+        assert!(manager.get(BASE).unwrap().frozen);
+        assert_eq!(manager.generation(), start + 4);
+
+        assert_eq!(manager.remove(BASE).map(|e| e.label), Some("b".to_string()));
+        assert!(manager.remove(BASE).is_none());
+        assert_eq!(manager.generation(), start + 5);
+        assert!(manager.clear().is_empty());
+        // 列表为空时不刷新
+        assert!(!manager.refresh());
+    }
+}
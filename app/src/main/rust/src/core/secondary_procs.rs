@@ -0,0 +1,226 @@
+//! Secondary process handles
+//!
+//! 应用只有一个绑定进程，但多进程游戏里搜索或指针扫描可能需要读取另一个进程（例如扫描渲染进程，
+//! 修改仍然写入逻辑进程）。`SecondaryProcesses` 为这些进程各保存一个 BindProc 句柄并按 pid 路由读取：
+//! 有句柄时走句柄，句柄读取失败或没有句柄时用显式 pid 通过驱动读取。
+//!
+//! 句柄表有自己的读写锁，绑定和释放只在 `&self` 上短暂加写锁，读取时加读锁克隆出句柄后立即释放，
+//! 不同 pid 的读取既不会在 DRIVER_MANAGER 的写锁上排队，也不会被正在进行的绑定阻塞。
+
+use crate::wuwa::PageStatusBitmap;
+use anyhow::{Result, anyhow};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// 最多同时绑定的次要进程数
+pub const MAX_SECONDARY_PROCESSES: usize = 4;
+
+/// 按 pid 读取的两条路径，DriverManager 使用真实驱动实现，测试使用模拟实现
+pub trait PidReadPaths {
+    type Handle;
+    /// 通过进程的绑定句柄读取
+    fn read_handle(&self, handle: &Self::Handle, addr: u64, buf: &mut [u8], page_status: Option<&mut PageStatusBitmap>) -> Result<()>;
+    /// 不经过句柄，用显式 pid 通过驱动读取
+    fn read_pid(&self, pid: i32, addr: u64, buf: &mut [u8], page_status: Option<&mut PageStatusBitmap>) -> Result<()>;
+}
+
+/// pid → 句柄表
+#[derive(Debug)]
+pub struct SecondaryProcesses<H> {
+    handles: RwLock<HashMap<i32, Arc<H>>>,
+}
+
+impl<H> Default for SecondaryProcesses<H> {
+    fn default() -> Self {
+        Self {
+            handles: RwLock::new(HashMap::new()),
+        }
+    }
+}
+
+impl<H> SecondaryProcesses<H> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 保存 pid 的句柄，已有句柄时替换；表已满时返回错误
+    pub fn insert(&self, pid: i32, handle: H) -> Result<()> {
+        let mut handles = self.handles.write().map_err(|_| anyhow!("Failed to acquire secondary process lock"))?;
+        if !handles.contains_key(&pid) && handles.len() >= MAX_SECONDARY_PROCESSES {
+            return Err(anyhow!("At most {} secondary processes can be bound", MAX_SECONDARY_PROCESSES));
+        }
+        handles.insert(pid, Arc::new(handle));
+        Ok(())
+    }
+
+    /// 释放 pid 的句柄，正在进行的读取持有的句柄在读取结束后关闭
+    pub fn remove(&self, pid: i32) -> bool {
+        self.handles.write().is_ok_and(|mut handles| handles.remove(&pid).is_some())
+    }
+
+    pub fn get(&self, pid: i32) -> Option<Arc<H>> {
+        self.handles.read().ok()?.get(&pid).cloned()
+    }
+
+    /// 已绑定的次要进程，按 pid 升序
+    pub fn pids(&self) -> Vec<i32> {
+        let mut pids: Vec<i32> = self.handles.read().map(|handles| handles.keys().copied().collect()).unwrap_or_default();
+        pids.sort_unstable();
+        pids
+    }
+
+    /// 所有句柄，用于切换访问模式时重新设置内存类型
+    pub fn handles(&self) -> Vec<Arc<H>> {
+        self.handles.read().map(|handles| handles.values().cloned().collect()).unwrap_or_default()
+    }
+
+    /// 读取 pid 的内存：有句柄时先走句柄，失败后用驱动路径重试一次；没有句柄时直接走驱动路径
+    pub fn read<P: PidReadPaths<Handle = H> + ?Sized>(
+        &self,
+        paths: &P,
+        pid: i32,
+        addr: u64,
+        buf: &mut [u8],
+        mut page_status: Option<&mut PageStatusBitmap>,
+    ) -> Result<()> {
+        let Some(handle) = self.get(pid) else {
+            return paths.read_pid(pid, addr, buf, page_status);
+        };
+        match paths.read_handle(&handle, addr, buf, page_status.as_deref_mut()) {
+            Ok(()) => Ok(()),
+            Err(bind_error) => paths
+                .read_pid(pid, addr, buf, page_status)
+                .map_err(|e| bind_error.context(format!("Driver fallback read for pid {} failed: {}", pid, e))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    struct MockHandle {
+        pid: i32,
+        ok: bool,
+    }
+
+    /// 模拟驱动：句柄读取按 pid 填充字节，驱动路径填充 0xD0 + pid
+    #[derive(Default)]
+    struct MockDriver {
+        driver_ok: bool,
+        handle_calls: AtomicU32,
+        driver_calls: Mutex<Vec<i32>>,
+        /// 句柄读取期间等待这个信号，用于检查读取时没有持有句柄表的锁
+        gate: Mutex<Option<mpsc::Receiver<()>>>,
+    }
+
+    impl PidReadPaths for MockDriver {
+        type Handle = MockHandle;
+
+        fn read_handle(&self, handle: &MockHandle, _addr: u64, buf: &mut [u8], page_status: Option<&mut PageStatusBitmap>) -> Result<()> {
+            self.handle_calls.fetch_add(1, Ordering::Relaxed);
+            if let Some(gate) = self.gate.lock().unwrap().take() {
+                gate.recv_timeout(Duration::from_secs(5)).map_err(|_| anyhow!("Handle table stayed locked during a read"))?;
+            }
+            if !handle.ok {
+                return Err(anyhow!("BindProc read failed for pid {}", handle.pid));
+            }
+            buf.fill(handle.pid as u8);
+            if let Some(status) = page_status {
+                status.mark_all_success();
+            }
+            Ok(())
+        }
+
+        fn read_pid(&self, pid: i32, addr: u64, buf: &mut [u8], _page_status: Option<&mut PageStatusBitmap>) -> Result<()> {
+            self.driver_calls.lock().unwrap().push(pid);
+            if !self.driver_ok {
+                return Err(anyhow!("Memory read failed: pid={} va=0x{:x}", pid, addr));
+            }
+            buf.fill(0xD0 + pid as u8);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_routes_by_pid_with_driver_fallback() {
+        let driver = MockDriver {
+            driver_ok: true,
+            ..Default::default()
+        };
+        let procs = SecondaryProcesses::new();
+        procs.insert(2, MockHandle { pid: 2, ok: true }).unwrap();
+        procs.insert(3, MockHandle { pid: 3, ok: false }).unwrap();
+
+        let mut buf = [0u8; 8];
+        let mut status = PageStatusBitmap::new(buf.len(), 0x1000);
+        procs.read(&driver, 2, 0x1000, &mut buf, Some(&mut status)).unwrap();
+        assert_eq!(buf, [2; 8]);
+        assert!(status.is_page_success(0));
+
+        // 句柄读取失败时用同一个 pid 走驱动路径
+        procs.read(&driver, 3, 0x1000, &mut buf, None).unwrap();
+        assert_eq!(buf, [0xD3; 8]);
+
+        // 没有句柄的 pid 直接走驱动路径
+        procs.read(&driver, 9, 0x1000, &mut buf, None).unwrap();
+        assert_eq!(buf, [0xD9; 8]);
+        assert_eq!(driver.handle_calls.load(Ordering::Relaxed), 2);
+        assert_eq!(*driver.driver_calls.lock().unwrap(), vec![3, 9]);
+
+        // 两条路径都失败时保留两个错误
+        let driver = MockDriver::default();
+        let error = procs.read(&driver, 3, 0x1000, &mut buf, None).unwrap_err();
+        let message = format!("{:#}", error);
+        assert!(message.contains("pid 3") && message.contains("BindProc read failed"), "{}", message);
+    }
+
+    #[test]
+    fn test_bind_limit_and_release() {
+        let procs = SecondaryProcesses::new();
+        for pid in (1..=MAX_SECONDARY_PROCESSES as i32).rev() {
+            procs.insert(pid, MockHandle { pid, ok: true }).unwrap();
+        }
+        assert!(procs.insert(100, MockHandle { pid: 100, ok: true }).is_err());
+        // 替换已有的 pid 不受数量限制
+        procs.insert(1, MockHandle { pid: 1, ok: false }).unwrap();
+        assert!(!procs.get(1).unwrap().ok);
+        assert_eq!(procs.pids(), (1..=MAX_SECONDARY_PROCESSES as i32).collect::<Vec<_>>());
+
+        assert!(procs.remove(1));
+        assert!(!procs.remove(1));
+        procs.insert(100, MockHandle { pid: 100, ok: true }).unwrap();
+        assert_eq!(procs.handles().len(), MAX_SECONDARY_PROCESSES);
+    }
+
+    #[test]
+    fn test_read_does_not_hold_table_lock() {
+        let (tx, rx) = mpsc::channel();
+        let driver = MockDriver {
+            gate: Mutex::new(Some(rx)),
+            ..Default::default()
+        };
+        let procs = SecondaryProcesses::new();
+        procs.insert(2, MockHandle { pid: 2, ok: true }).unwrap();
+
+        std::thread::scope(|scope| {
+            let reader = scope.spawn(|| {
+                let mut buf = [0u8; 4];
+                procs.read(&driver, 2, 0x1000, &mut buf, None).map(|_| buf)
+            });
+            // 读取还在进行时绑定和释放其他进程，读取再继续
+            while driver.handle_calls.load(Ordering::Relaxed) == 0 {
+                std::thread::yield_now();
+            }
+            procs.insert(5, MockHandle { pid: 5, ok: true }).unwrap();
+            assert!(procs.remove(2));
+            tx.send(()).unwrap();
+            assert_eq!(reader.join().unwrap().unwrap(), [2; 4]);
+        });
+        assert_eq!(procs.pids(), vec![5]);
+    }
+}
@@ -0,0 +1,162 @@
+//! Self regions
+//!
+//! 物理内存读取模式下，Kotlin 传入的区域里可能包含同时映射在本进程里的共享页（ashmem、dmabuf），
+//! 扫描会读到我们自己的 SharedBuffer 和结果文件 mmap，得到指向自身的结果。这里记录本进程的这些映射，
+//! 搜索开始前从区域列表中减去。
+//!
+//! 登记表是独立的全局对象而不是 DriverManager 的字段：结果文件在搜索过程中扩容重新映射时
+//! 搜索线程可能正持有 DRIVER_MANAGER 的读锁，不能再去取它的锁。
+
+use crate::core::globals::SELF_REGIONS;
+use memmap2::MmapMut;
+use std::fs::File;
+use std::ops::{Deref, DerefMut};
+use std::sync::Mutex;
+
+/// 本进程需要从扫描中排除的地址范围 [start, end)
+#[derive(Debug, Default)]
+pub struct SelfRegions {
+    ranges: Mutex<Vec<(u64, u64)>>,
+}
+
+impl SelfRegions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&self, start: u64, len: usize) {
+        if len == 0 {
+            return;
+        }
+        if let Ok(mut ranges) = self.ranges.lock() {
+            ranges.retain(|&(s, _)| s != start);
+            ranges.push((start, start + len as u64));
+        }
+    }
+
+    pub fn unregister(&self, start: u64) {
+        if let Ok(mut ranges) = self.ranges.lock() {
+            ranges.retain(|&(s, _)| s != start);
+        }
+    }
+
+    pub fn ranges(&self) -> Vec<(u64, u64)> {
+        self.ranges.lock().map(|ranges| ranges.clone()).unwrap_or_default()
+    }
+
+    /// 从区域列表中减去已登记的范围
+    pub fn exclude(&self, regions: Vec<(u64, u64)>) -> Vec<(u64, u64)> {
+        let excluded = self.ranges();
+        if excluded.is_empty() {
+            return regions;
+        }
+        subtract_ranges(&regions, &excluded)
+    }
+}
+
+/// 登记本进程的一段映射
+pub fn register_self_region(ptr: *const u8, len: usize) {
+    SELF_REGIONS.register(ptr as u64, len);
+}
+
+/// 取消登记，`ptr` 与登记时相同
+pub fn unregister_self_region(ptr: *const u8) {
+    SELF_REGIONS.unregister(ptr as u64);
+}
+
+/// 每个区域去掉与 excluded 重叠的部分，被切开的区域拆成多段，顺序保持不变
+pub fn subtract_ranges(regions: &[(u64, u64)], excluded: &[(u64, u64)]) -> Vec<(u64, u64)> {
+    let mut excluded = excluded.to_vec();
+    excluded.sort_unstable();
+
+    let mut result = Vec::with_capacity(regions.len());
+    for &(start, end) in regions {
+        let mut cursor = start;
+        for &(ex_start, ex_end) in &excluded {
+            if ex_end <= cursor || ex_start >= end {
+                continue;
+            }
+            if ex_start > cursor {
+                result.push((cursor, ex_start));
+            }
+            cursor = cursor.max(ex_end);
+            if cursor >= end {
+                break;
+            }
+        }
+        if cursor < end {
+            result.push((cursor, end));
+        }
+    }
+    result
+}
+
+/// 创建时登记、释放时取消登记的文件映射，用于结果文件和 MapQueue 临时文件
+pub struct SelfMmap(MmapMut);
+
+impl SelfMmap {
+    /// # Safety
+    /// 与 `MmapMut::map_mut` 相同，映射期间文件不能被其他地方截断
+    pub unsafe fn map_mut(file: &File) -> std::io::Result<Self> {
+        let mmap = unsafe { MmapMut::map_mut(file)? };
+        register_self_region(mmap.as_ptr(), mmap.len());
+        Ok(Self(mmap))
+    }
+}
+
+impl Deref for SelfMmap {
+    type Target = MmapMut;
+
+    fn deref(&self) -> &MmapMut {
+        &self.0
+    }
+}
+
+impl DerefMut for SelfMmap {
+    fn deref_mut(&mut self) -> &mut MmapMut {
+        &mut self.0
+    }
+}
+
+impl Drop for SelfMmap {
+    fn drop(&mut self) {
+        unregister_self_region(self.0.as_ptr());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_region_overlapping_self_range_is_split() {
+        let regions = vec![(0x1000, 0x9000), (0x10000, 0x12000), (0x20000, 0x21000)];
+        let excluded = vec![
+            (0x3000, 0x4000),
+            // 覆盖区域结尾
+            (0x8000, 0xA000),
+            // 覆盖整个区域
+            (0x1F000, 0x22000),
+            (0x2000, 0x3800),
+        ];
+
+        assert_eq!(
+            subtract_ranges(&regions, &excluded),
+            vec![(0x1000, 0x2000), (0x4000, 0x8000), (0x10000, 0x12000)]
+        );
+        assert_eq!(subtract_ranges(&regions, &[]), regions);
+    }
+
+    #[test]
+    fn test_register_and_unregister() {
+        let self_regions = SelfRegions::new();
+        self_regions.register(0x5000, 0x1000);
+        self_regions.register(0x5000, 0x2000);
+        self_regions.register(0x9000, 0);
+        assert_eq!(self_regions.ranges(), vec![(0x5000, 0x7000)]);
+        assert_eq!(self_regions.exclude(vec![(0x4000, 0x8000)]), vec![(0x4000, 0x5000), (0x7000, 0x8000)]);
+
+        self_regions.unregister(0x5000);
+        assert_eq!(self_regions.exclude(vec![(0x4000, 0x8000)]), vec![(0x4000, 0x8000)]);
+    }
+}
@@ -0,0 +1,94 @@
+//! Shared buffer header
+//!
+//! 搜索和指针扫描的共享缓冲区都以同一个 16 字节的头开始，由 Rust 在缓冲区设置后第一次写入：
+//! ```text
+//! [0-3]   magic          协议标识，区分两种缓冲区，也用来识别还没有写入头的缓冲区
+//! [4-7]   layout_version 字段布局版本，字段增加或移动时递增
+//! [8-11]  capabilities   支持的功能位，由各协议定义
+//! [12-15] size           布局使用的字节数
+//! ```
+//! 头的位置在所有版本中固定不变，Kotlin 先读取头确认版本一致再解释后面的字段，
+//! 旧版本的 APK 不会再按错误的偏移读取进度。
+
+/// 头占用的字节数，各协议的字段从这里开始
+pub const HEADER_SIZE: usize = 16;
+
+pub mod offsets {
+    pub const MAGIC: usize = 0;
+    pub const LAYOUT_VERSION: usize = 4;
+    pub const CAPABILITIES: usize = 8;
+    pub const SIZE: usize = 12;
+}
+
+/// 一个协议的头
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SharedHeader {
+    pub magic: u32,
+    pub layout_version: i32,
+    pub capabilities: i32,
+    pub size: usize,
+}
+
+impl SharedHeader {
+    /// 通过缓冲区的 i32 写入函数写入头
+    pub fn write(&self, mut write_i32: impl FnMut(usize, i32)) {
+        write_i32(offsets::MAGIC, self.magic as i32);
+        write_i32(offsets::LAYOUT_VERSION, self.layout_version);
+        write_i32(offsets::CAPABILITIES, self.capabilities);
+        write_i32(offsets::SIZE, self.size as i32);
+    }
+
+    /// 缓冲区中的布局版本，magic 不匹配（未设置或不是这个协议）时为 0
+    pub fn read_version(&self, read_i32: impl Fn(usize) -> i32) -> i32 {
+        if read_i32(offsets::MAGIC) as u32 != self.magic {
+            return 0;
+        }
+        read_i32(offsets::LAYOUT_VERSION)
+    }
+}
+
+/// 字段 `(offset, size)` 按偏移升序排列、互不重叠、都在头之后且不超过 `size`，用于编译期断言
+pub const fn fields_fit(fields: &[(usize, usize)], size: usize) -> bool {
+    let mut end = HEADER_SIZE;
+    let mut i = 0;
+    while i < fields.len() {
+        let (offset, len) = fields[i];
+        if offset < end || len == 0 {
+            return false;
+        }
+        end = offset + len;
+        i += 1;
+    }
+    end <= size
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fields_fit() {
+        assert!(fields_fit(&[(16, 4), (20, 8), (28, 4)], 32));
+        assert!(!fields_fit(&[(16, 4), (20, 8), (28, 4)], 31));
+        // 与头重叠、字段重叠或乱序
+        assert!(!fields_fit(&[(12, 4)], 32));
+        assert!(!fields_fit(&[(16, 8), (20, 4)], 32));
+        assert!(!fields_fit(&[(20, 4), (16, 4)], 32));
+    }
+
+    #[test]
+    fn test_header_round_trip() {
+        let header = SharedHeader { magic: u32::from_le_bytes(*b"TEST"), layout_version: 7, capabilities: 5, size: 64 };
+        let mut memory = [0u8; HEADER_SIZE];
+        let read = |memory: &[u8], offset: usize| i32::from_le_bytes(memory[offset..offset + 4].try_into().unwrap());
+        assert_eq!(header.read_version(|offset| read(&memory, offset)), 0);
+
+        header.write(|offset, value| memory[offset..offset + 4].copy_from_slice(&value.to_le_bytes()));
+        assert_eq!(&memory[..4], b"TEST");
+        assert_eq!(header.read_version(|offset| read(&memory, offset)), 7);
+        assert_eq!((read(&memory, offsets::CAPABILITIES), read(&memory, offsets::SIZE)), (5, 64));
+
+        let other = SharedHeader { magic: u32::from_le_bytes(*b"ELSE"), ..header };
+        assert_eq!(other.read_version(|offset| read(&memory, offset)), 0);
+    }
+}
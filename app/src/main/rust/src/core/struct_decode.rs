@@ -0,0 +1,298 @@
+//! Typed struct decoder
+//!
+//! 按 Kotlin 传入的布局（字段名、偏移、类型）解析一块内存，相当于一个简单的结构体查看器。
+//! 每一层只读取一次覆盖所有字段的区间，再从中切出各字段，字段之间可以重叠（例如 union）。
+//!
+//! `Pointer` 伪类型读取 8 字节作为地址，在指向的位置按子布局继续解析，最多解引用 `MAX_POINTER_DEPTH` 层。
+//! 空指针或子结构读取失败只影响这个字段，不会让整个解析失败。
+
+use crate::search::format::format_value;
+use crate::search::types::ValueType;
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+
+/// 与 read_memory_unified 一致，去掉 MTE tag 等高位
+const ADDRESS_MASK: u64 = 0x0000_FFFF_FFFF_FFFF;
+
+/// 布局中表示指针字段的类型 id，不与 ValueType 的 id 冲突
+pub const POINTER_TYPE_ID: i32 = 100;
+
+/// 最多解引用的指针层数
+pub const MAX_POINTER_DEPTH: usize = 3;
+
+/// 单层结构体覆盖区间的上限，防止错误的偏移导致一次读取过大的内存
+pub const MAX_STRUCT_SPAN: u64 = 0x10000;
+
+/// 布局中的一个字段
+#[derive(Debug, Clone, Deserialize)]
+pub struct FieldLayout {
+    pub name: String,
+    pub offset: u64,
+    /// ValueType 的 id，或 `POINTER_TYPE_ID`
+    #[serde(rename = "type")]
+    pub type_id: i32,
+    /// 变长类型（特征码、字符串）的字节数，定长类型忽略
+    #[serde(default)]
+    pub size: Option<usize>,
+    /// 指针字段指向的子布局
+    #[serde(default)]
+    pub fields: Vec<FieldLayout>,
+}
+
+impl FieldLayout {
+    fn kind(&self) -> Result<FieldKind> {
+        if self.type_id == POINTER_TYPE_ID {
+            return Ok(FieldKind::Pointer);
+        }
+        let typ = ValueType::from_id(self.type_id).ok_or_else(|| anyhow!("Field {}: invalid type id {}", self.name, self.type_id))?;
+        let size = if typ.is_variable_len() {
+            self.size.filter(|&size| size > 0).ok_or_else(|| anyhow!("Field {}: {} requires a size", self.name, typ))?
+        } else {
+            typ.size()
+        };
+        Ok(FieldKind::Value(typ, size))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum FieldKind {
+    Value(ValueType, usize),
+    Pointer,
+}
+
+impl FieldKind {
+    fn size(&self) -> usize {
+        match self {
+            FieldKind::Value(_, size) => *size,
+            FieldKind::Pointer => 8,
+        }
+    }
+}
+
+/// 解析后的字段
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedField {
+    pub name: String,
+    pub address: u64,
+    pub value: String,
+    pub raw: Vec<u8>,
+    /// 指针字段解引用后的子字段，空指针或超过深度限制时为空
+    pub children: Vec<DecodedField>,
+}
+
+/// 解析 JSON 布局：字段数组，每个字段为 `{"name", "offset", "type", "size"?, "fields"?}`
+pub fn parse_layout(json: &str) -> Result<Vec<FieldLayout>> {
+    let layout: Vec<FieldLayout> = serde_json::from_str(json).map_err(|e| anyhow!("Invalid struct layout: {}", e))?;
+    if layout.is_empty() {
+        return Err(anyhow!("Struct layout has no fields"));
+    }
+    Ok(layout)
+}
+
+/// 按布局解析 addr 处的结构体，`read` 读取一段完整的内存
+pub fn decode_struct<F>(addr: u64, layout: &[FieldLayout], read: &F) -> Result<Vec<DecodedField>>
+where
+    F: Fn(u64, &mut [u8]) -> Result<()>,
+{
+    decode_level(addr & ADDRESS_MASK, layout, 0, read)
+}
+
+fn decode_level<F>(addr: u64, layout: &[FieldLayout], depth: usize, read: &F) -> Result<Vec<DecodedField>>
+where
+    F: Fn(u64, &mut [u8]) -> Result<()>,
+{
+    let kinds = layout.iter().map(FieldLayout::kind).collect::<Result<Vec<_>>>()?;
+    if layout.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let start = layout.iter().map(|field| field.offset).min().unwrap_or(0);
+    let end = layout.iter().zip(&kinds).map(|(field, kind)| field.offset.saturating_add(kind.size() as u64)).max().unwrap_or(0);
+    if end - start > MAX_STRUCT_SPAN {
+        return Err(anyhow!("Struct span 0x{:X} exceeds the limit of 0x{:X} bytes", end - start, MAX_STRUCT_SPAN));
+    }
+
+    let span_addr = addr.checked_add(start).ok_or_else(|| anyhow!("Struct at 0x{:X} overflows the address space", addr))?;
+    let mut span = vec![0u8; (end - start) as usize];
+    read(span_addr, &mut span).map_err(|e| anyhow!("Failed to read struct at 0x{:X}: {}", span_addr, e))?;
+
+    let mut fields = Vec::with_capacity(layout.len());
+    let mut value = String::new();
+    for (field, kind) in layout.iter().zip(kinds) {
+        let begin = (field.offset - start) as usize;
+        let raw = span[begin..begin + kind.size()].to_vec();
+        let children = match kind {
+            FieldKind::Value(typ, _) => {
+                format_value(&mut value, &raw, typ);
+                Vec::new()
+            },
+            FieldKind::Pointer => {
+                let target = u64::from_le_bytes(raw[..8].try_into().unwrap()) & ADDRESS_MASK;
+                if target == 0 {
+                    value = "NULL".to_string();
+                    Vec::new()
+                } else {
+                    value = format!("0x{:X}", target);
+                    if depth < MAX_POINTER_DEPTH && !field.fields.is_empty() {
+                        match decode_level(target, &field.fields, depth + 1, read) {
+                            Ok(children) => children,
+                            Err(e) => {
+                                value = format!("0x{:X} ({})", target, e);
+                                Vec::new()
+                            },
+                        }
+                    } else {
+                        Vec::new()
+                    }
+                }
+            },
+        };
+        fields.push(DecodedField {
+            name: field.name.clone(),
+            address: addr + field.offset,
+            value: value.clone(),
+            raw,
+            children,
+        });
+    }
+    Ok(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::tests::mock_memory::MockMemory;
+    use std::cell::Cell;
+
+    fn field(name: &str, offset: u64, type_id: i32) -> FieldLayout {
+        FieldLayout {
+            name: name.to_string(),
+            offset,
+            type_id,
+            size: None,
+            fields: Vec::new(),
+        }
+    }
+
+    fn pointer(name: &str, offset: u64, fields: Vec<FieldLayout>) -> FieldLayout {
+        FieldLayout {
+            fields,
+            ..field(name, offset, POINTER_TYPE_ID)
+        }
+    }
+
+    #[test]
+    fn test_overlapping_fields_single_read() {
+        let mut mem = MockMemory::new();
+        let base = mem.malloc(0x7000000000, 0x1000).unwrap();
+        mem.mem_write_i32(base + 0x10, -2).unwrap();
+        mem.mem_write_f32(base + 0x14, 1.5).unwrap();
+        mem.mem_write(base + 0x18, b"hp").unwrap();
+
+        let reads = Cell::new(0);
+        let read = |addr: u64, buf: &mut [u8]| -> Result<()> {
+            reads.set(reads.get() + 1);
+            buf.copy_from_slice(&mem.mem_read(addr, buf.len())?);
+            Ok(())
+        };
+
+        // 0x10 处的 Qword 与后面的 Dword、Float 重叠
+        let layout = parse_layout(
+            r#"[
+                {"name": "all", "offset": 16, "type": 3},
+                {"name": "hp", "offset": 16, "type": 2},
+                {"name": "low", "offset": 16, "type": 0},
+                {"name": "speed", "offset": 20, "type": 4},
+                {"name": "tag", "offset": 24, "type": 9, "size": 2}
+            ]"#,
+        )
+        .unwrap();
+        let fields = decode_struct(base, &layout, &read).unwrap();
+        assert_eq!(reads.get(), 1);
+
+        let values: Vec<_> = fields.iter().map(|f| (f.name.as_str(), f.value.as_str())).collect();
+        let all = ((1.5f32.to_bits() as i64) << 32) | 0xFFFF_FFFE;
+        assert_eq!(
+            values,
+            vec![("all", all.to_string().as_str()), ("hp", "-2"), ("low", "-2"), ("speed", "1.5"), ("tag", "hp")]
+        );
+        assert_eq!(fields[1].address, base + 0x10);
+        assert_eq!(fields[1].raw, (-2i32).to_le_bytes());
+        assert_eq!(fields[3].address, base + 0x14);
+    }
+
+    #[test]
+    fn test_nested_pointers() {
+        let mut mem = MockMemory::new();
+        let base = mem.malloc(0x7000000000, 0x1000).unwrap();
+        let inner = mem.malloc(0x7100000000, 0x1000).unwrap();
+        // 带 MTE tag 的指针
+        mem.mem_write_u64(base, inner | 0xB400_0000_0000_0000).unwrap();
+        mem.mem_write_u64(base + 8, 0).unwrap();
+        mem.mem_write_u64(base + 16, 0x7F00000000).unwrap();
+        mem.mem_write_i32(inner + 4, 100).unwrap();
+
+        let read = |addr: u64, buf: &mut [u8]| -> Result<()> {
+            buf.copy_from_slice(&mem.mem_read(addr, buf.len())?);
+            Ok(())
+        };
+
+        let layout = vec![
+            pointer("player", 0, vec![field("hp", 4, 2)]),
+            pointer("target", 8, vec![field("hp", 4, 2)]),
+            pointer("dangling", 16, vec![field("hp", 4, 2)]),
+        ];
+        let fields = decode_struct(base, &layout, &read).unwrap();
+
+        assert_eq!(fields[0].value, format!("0x{:X}", inner));
+        assert_eq!(fields[0].children.len(), 1);
+        assert_eq!(fields[0].children[0].address, inner + 4);
+        assert_eq!(fields[0].children[0].value, "100");
+
+        // 空指针不解引用
+        assert_eq!(fields[1].value, "NULL");
+        assert_eq!(fields[1].raw, vec![0; 8]);
+        assert!(fields[1].children.is_empty());
+
+        // 指向未映射内存只影响这个字段
+        assert!(fields[2].value.starts_with("0x7F00000000 ("));
+        assert!(fields[2].children.is_empty());
+    }
+
+    #[test]
+    fn test_pointer_depth_limit() {
+        let mut mem = MockMemory::new();
+        let base = mem.malloc(0x7000000000, 0x1000).unwrap();
+        // 指向自身的链表节点
+        mem.mem_write_u64(base, base).unwrap();
+
+        let read = |addr: u64, buf: &mut [u8]| -> Result<()> {
+            buf.copy_from_slice(&mem.mem_read(addr, buf.len())?);
+            Ok(())
+        };
+
+        let mut layout = vec![pointer("next", 0, Vec::new())];
+        for _ in 0..MAX_POINTER_DEPTH + 1 {
+            layout = vec![pointer("next", 0, layout)];
+        }
+        let mut fields = decode_struct(base, &layout, &read).unwrap();
+        let mut depth = 0;
+        while let Some(next) = fields.pop() {
+            fields = next.children;
+            depth += 1;
+        }
+        assert_eq!(depth, MAX_POINTER_DEPTH + 1);
+    }
+
+    #[test]
+    fn test_invalid_layout() {
+        assert!(parse_layout("[]").is_err());
+        assert!(parse_layout("{}").is_err());
+
+        let read = |_: u64, _: &mut [u8]| -> Result<()> { Ok(()) };
+        assert!(decode_struct(0x1000, &[field("x", 0, 42)], &read).is_err());
+        // 变长类型需要 size
+        assert!(decode_struct(0x1000, &[field("name", 0, 9)], &read).is_err());
+        assert!(decode_struct(0x1000, &[field("a", 0, 0), field("b", MAX_STRUCT_SPAN, 0)], &read).is_err());
+    }
+}
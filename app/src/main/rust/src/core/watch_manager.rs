@@ -0,0 +1,365 @@
+//! Watch Manager - 观察地址的值历史
+//!
+//! 按固定间隔采样一组 (地址, 类型)，每个地址保留最近 K 个采样，UI 据此画出数值走势，
+//! 用来从几个候选地址中找出真正的变量。
+//!
+//! 同一页内的地址合并为一次读取。搜索进行中时采样自动暂停，避免与扫描争抢驱动带宽；
+//! 连续读取失败 `MAX_CONSECUTIVE_FAILURES` 次的地址被移除。
+
+use crate::core::AccessQos;
+use crate::core::globals::{DRIVER_MANAGER, PAGE_SIZE};
+use crate::search::ValueType;
+use crate::search::engine::SEARCH_ENGINE_MANAGER;
+use anyhow::Result;
+use dashmap::DashMap;
+use log::{debug, error, info};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::task::JoinHandle;
+
+/// 默认采样间隔
+pub const DEFAULT_WATCH_INTERVAL_MS: u64 = 100;
+
+/// 默认每个地址保留的采样数
+pub const DEFAULT_WATCH_HISTORY: usize = 300;
+
+/// 连续读取失败多少次后移除地址
+pub const MAX_CONSECUTIVE_FAILURES: u32 = 10;
+
+/// 一次采样
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatchSample {
+    /// 采样时间（Unix 毫秒）
+    pub timestamp_ms: u64,
+    /// 读取到的值，只有前 `value_type.size()` 字节有效
+    pub bytes: [u8; 8],
+}
+
+/// 观察条目
+#[derive(Clone)]
+pub struct WatchEntry {
+    pub value_type: ValueType,
+    pub history: VecDeque<WatchSample>,
+    /// 连续读取失败次数，成功后清零
+    pub consecutive_failures: u32,
+}
+
+impl WatchEntry {
+    fn new(value_type: ValueType) -> Self {
+        Self {
+            value_type,
+            history: VecDeque::new(),
+            consecutive_failures: 0,
+        }
+    }
+}
+
+/// 一次采样的统计
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SampleStats {
+    pub sampled: usize,
+    pub failed: usize,
+    /// 因连续失败被移除的地址数
+    pub dropped: usize,
+    /// 实际发出的读取次数
+    pub reads: usize,
+}
+
+/// 一次合并读取：覆盖 [start, start + len)，包含若干地址
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ReadSpan {
+    pub(crate) start: u64,
+    pub(crate) len: usize,
+    pub(crate) addresses: Vec<(u64, usize)>,
+}
+
+/// 把按地址排序的 (地址, 大小) 合并为读取：同一页内的值合并，跨页的值单独读取
+pub(crate) fn plan_reads(targets: &[(u64, usize)], page_size: usize) -> Vec<ReadSpan> {
+    let page_of = |addr: u64| addr / page_size as u64;
+    let mut spans: Vec<ReadSpan> = Vec::new();
+    for &(address, size) in targets {
+        let end = address + size as u64;
+        let single_page = page_of(address) == page_of(end - 1);
+        match spans.last_mut() {
+            Some(span) if single_page && page_of(span.start) == page_of(address) && page_of(span.start) == page_of(span.start + span.len as u64 - 1) => {
+                span.len = span.len.max((end - span.start) as usize);
+                span.addresses.push((address, size));
+            },
+            _ => spans.push(ReadSpan {
+                start: address,
+                len: size,
+                addresses: vec![(address, size)],
+            }),
+        }
+    }
+    spans
+}
+
+/// 观察管理器
+pub struct WatchManager {
+    /// 观察地址映射表：地址 -> 条目
+    entries: Arc<DashMap<u64, WatchEntry>>,
+    /// 采样间隔（毫秒）
+    interval_ms: Arc<AtomicU64>,
+    /// 每个地址保留的采样数
+    history_size: Arc<AtomicUsize>,
+    /// 是否正在运行
+    running: Arc<AtomicBool>,
+    /// 后台任务句柄
+    task_handle: Option<JoinHandle<()>>,
+}
+
+impl WatchManager {
+    pub fn new() -> Self {
+        Self {
+            entries: Arc::new(DashMap::new()),
+            interval_ms: Arc::new(AtomicU64::new(DEFAULT_WATCH_INTERVAL_MS)),
+            history_size: Arc::new(AtomicUsize::new(DEFAULT_WATCH_HISTORY)),
+            running: Arc::new(AtomicBool::new(false)),
+            task_handle: None,
+        }
+    }
+
+    /// 替换观察的地址并启动采样，已经在观察的地址保留历史
+    pub fn start(&mut self, targets: &[(u64, ValueType)], interval_ms: u64, history_size: usize) {
+        self.interval_ms.store(interval_ms.max(1), Ordering::Relaxed);
+        self.history_size.store(history_size.max(1), Ordering::Relaxed);
+        self.set_targets(targets);
+
+        if self.running.load(Ordering::SeqCst) {
+            return;
+        }
+        self.running.store(true, Ordering::SeqCst);
+
+        let entries = Arc::clone(&self.entries);
+        let interval_ms = Arc::clone(&self.interval_ms);
+        let history_size = Arc::clone(&self.history_size);
+        let running = Arc::clone(&self.running);
+
+        let handle = tokio::spawn(async move {
+            debug!("WatchManager: 采样循环已启动");
+
+            loop {
+                if !running.load(Ordering::SeqCst) {
+                    break;
+                }
+
+                if !entries.is_empty() && !Self::search_in_progress() {
+                    Self::sample_bound_process(&entries, history_size.load(Ordering::Relaxed));
+                }
+
+                tokio::time::sleep(Duration::from_millis(interval_ms.load(Ordering::Relaxed))).await;
+            }
+
+            debug!("WatchManager: 采样循环已停止");
+        });
+
+        self.task_handle = Some(handle);
+    }
+
+    /// 停止采样并清空观察的地址
+    pub fn stop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        // 采样循环只在睡眠时让出，直接取消不会留下写了一半的历史
+        if let Some(handle) = self.task_handle.take() {
+            handle.abort();
+        }
+        self.entries.clear();
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    /// 替换观察的地址，类型没变的地址保留历史
+    fn set_targets(&self, targets: &[(u64, ValueType)]) {
+        self.entries
+            .retain(|address, entry| targets.iter().any(|&(addr, typ)| addr == *address && typ == entry.value_type));
+        for &(address, value_type) in targets {
+            self.entries.entry(address).or_insert_with(|| WatchEntry::new(value_type));
+        }
+    }
+
+    /// 地址的采样历史，按时间从旧到新；地址没有在观察（或已被移除）时返回 None
+    pub fn get_history(&self, address: u64) -> Option<(ValueType, Vec<WatchSample>)> {
+        self.entries
+            .get(&address)
+            .map(|entry| (entry.value_type, entry.history.iter().copied().collect()))
+    }
+
+    /// 正在观察的地址，按地址排序
+    pub fn get_watched_addresses(&self) -> Vec<u64> {
+        let mut addresses: Vec<u64> = self.entries.iter().map(|e| *e.key()).collect();
+        addresses.sort_unstable();
+        addresses
+    }
+
+    /// 搜索进行中时暂停采样；拿不到锁说明搜索管理器正忙，同样跳过
+    fn search_in_progress() -> bool {
+        match SEARCH_ENGINE_MANAGER.try_read() {
+            Ok(manager) => manager.is_searching(),
+            Err(_) => true,
+        }
+    }
+
+    fn sample_bound_process(entries: &DashMap<u64, WatchEntry>, history_size: usize) {
+        let manager = match DRIVER_MANAGER.read() {
+            Ok(m) => m,
+            Err(e) => {
+                error!("WatchManager: 无法获取 DRIVER_MANAGER 读锁: {}", e);
+                return;
+            },
+        };
+        if !manager.is_process_bound() {
+            return;
+        }
+
+        let timestamp_ms = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0);
+        let stats = Self::sample_with(entries, history_size, timestamp_ms, *PAGE_SIZE, |addr, buf| {
+            manager.read_memory_with_qos(addr, buf, None, AccessQos::Interactive)
+        });
+        if stats.dropped > 0 {
+            info!("WatchManager: {} 个地址连续读取失败，已移除", stats.dropped);
+        }
+    }
+
+    /// 采样一轮：同一页内的地址合并读取，失败的地址累计连续失败次数，达到上限后移除
+    fn sample_with<R>(entries: &DashMap<u64, WatchEntry>, history_size: usize, timestamp_ms: u64, page_size: usize, mut read: R) -> SampleStats
+    where
+        R: FnMut(u64, &mut [u8]) -> Result<()>,
+    {
+        let mut targets: Vec<(u64, usize)> = entries.iter().map(|e| (*e.key(), e.value_type.size().clamp(1, 8))).collect();
+        targets.sort_unstable();
+
+        let mut stats = SampleStats::default();
+        let mut buffer = Vec::new();
+        for span in plan_reads(&targets, page_size) {
+            buffer.resize(span.len, 0);
+            let ok = read(span.start, &mut buffer).is_ok();
+            stats.reads += 1;
+
+            for (address, size) in span.addresses {
+                let Some(mut entry) = entries.get_mut(&address) else {
+                    continue;
+                };
+                if !ok {
+                    stats.failed += 1;
+                    entry.consecutive_failures += 1;
+                    continue;
+                }
+
+                let offset = (address - span.start) as usize;
+                let mut bytes = [0u8; 8];
+                bytes[..size].copy_from_slice(&buffer[offset..offset + size]);
+                entry.consecutive_failures = 0;
+                entry.history.push_back(WatchSample { timestamp_ms, bytes });
+                while entry.history.len() > history_size {
+                    entry.history.pop_front();
+                }
+                stats.sampled += 1;
+            }
+        }
+
+        let before = entries.len();
+        entries.retain(|_, entry| entry.consecutive_failures < MAX_CONSECUTIVE_FAILURES);
+        stats.dropped = before - entries.len();
+        stats
+    }
+}
+
+impl Default for WatchManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for WatchManager {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    const PAGE: usize = 4096;
+    const BASE: u64 = 0x7000_0000;
+
+    fn manager_with(targets: &[(u64, ValueType)]) -> WatchManager {
+        let manager = WatchManager::new();
+        manager.set_targets(targets);
+        manager
+    }
+
+    #[test]
+    fn test_plan_reads_merges_within_page() {
+        let targets = [(BASE, 4), (BASE + 0x10, 8), (BASE + PAGE as u64 - 2, 4), (BASE + PAGE as u64 + 8, 4)];
+        let spans = plan_reads(&targets, PAGE);
+        let layout: Vec<(u64, usize, usize)> = spans.iter().map(|s| (s.start, s.len, s.addresses.len())).collect();
+        // 跨页的值单独读取，之后的值另起一次读取
+        assert_eq!(layout, vec![(BASE, 0x18, 2), (BASE + PAGE as u64 - 2, 4, 1), (BASE + PAGE as u64 + 8, 4, 1)]);
+    }
+
+    #[test]
+    fn test_history_ring_buffer() {
+        let manager = manager_with(&[(BASE, ValueType::Dword), (BASE + 8, ValueType::Word)]);
+        let mut counter = 0u32;
+        for tick in 0..5u64 {
+            let stats = WatchManager::sample_with(&manager.entries, 3, 1000 + tick * 100, PAGE, |addr, buf| {
+                assert_eq!(addr, BASE);
+                counter += 1;
+                buf[..4].copy_from_slice(&counter.to_le_bytes());
+                buf[8..10].copy_from_slice(&7u16.to_le_bytes());
+                Ok(())
+            });
+            assert_eq!((stats.sampled, stats.reads), (2, 1));
+        }
+
+        let (value_type, history) = manager.get_history(BASE).unwrap();
+        assert_eq!(value_type, ValueType::Dword);
+        let samples: Vec<(u64, u32)> = history
+            .iter()
+            .map(|s| (s.timestamp_ms, u32::from_le_bytes(s.bytes[..4].try_into().unwrap())))
+            .collect();
+        assert_eq!(samples, vec![(1200, 3), (1300, 4), (1400, 5)]);
+        assert_eq!(manager.get_history(BASE + 8).unwrap().1[0].bytes, [7, 0, 0, 0, 0, 0, 0, 0]);
+
+        // 重新设置地址：类型没变的保留历史
+        manager.set_targets(&[(BASE, ValueType::Dword), (BASE + 8, ValueType::Dword)]);
+        assert_eq!(manager.get_history(BASE).unwrap().1.len(), 3);
+        assert!(manager.get_history(BASE + 8).unwrap().1.is_empty());
+    }
+
+    #[test]
+    fn test_drop_after_consecutive_failures() {
+        let far = BASE + 0x10_0000;
+        let manager = manager_with(&[(BASE, ValueType::Dword), (far, ValueType::Dword)]);
+        let flaky = |fail_far: bool| {
+            move |addr: u64, _: &mut [u8]| -> Result<()> {
+                if fail_far && addr == far {
+                    return Err(anyhow!("unmapped"));
+                }
+                Ok(())
+            }
+        };
+
+        for _ in 0..MAX_CONSECUTIVE_FAILURES - 1 {
+            WatchManager::sample_with(&manager.entries, 10, 0, PAGE, flaky(true));
+        }
+        // 一次成功清零连续失败次数
+        WatchManager::sample_with(&manager.entries, 10, 0, PAGE, flaky(false));
+        assert_eq!(manager.entries.get(&far).unwrap().consecutive_failures, 0);
+
+        let mut dropped = 0;
+        for _ in 0..MAX_CONSECUTIVE_FAILURES {
+            dropped += WatchManager::sample_with(&manager.entries, 10, 0, PAGE, flaky(true)).dropped;
+        }
+        assert_eq!(dropped, 1);
+        assert_eq!(manager.get_watched_addresses(), vec![BASE]);
+        assert!(manager.get_history(far).is_none());
+    }
+}
@@ -0,0 +1,157 @@
+//! Scan worker pools
+//!
+//! 扫描在 rayon 全局线程池里跑满所有核心，8 核手机上被扫描的游戏明显掉帧，有时还会触发反作弊的看门狗。
+//! 搜索和指针扫描各自持有一个 `WorkerPool`，可以限制线程数，也可以把工作线程降到后台调度优先级；
+//! 扫描的阻塞任务通过 `ScanPool::spawn_blocking` 在 `ThreadPool::install` 中运行，里面的 par_iter 都使用这个线程池。
+//!
+//! 两项都未设置时使用全局线程池，行为与之前相同。扫描开始时取出当前的线程池，修改设置只会让下一次扫描
+//! 使用新建的线程池，正在进行的扫描继续使用原来的线程池直到结束。
+
+use anyhow::{Result, anyhow};
+use log::{debug, info};
+use nix::libc;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+
+/// 后台工作线程的 nice 值，与 Android 的 THREAD_PRIORITY_BACKGROUND 相同
+pub const BACKGROUND_NICE: i32 = 10;
+
+/// 一次扫描使用的线程池，None 表示 rayon 全局线程池
+#[derive(Clone, Default)]
+pub struct ScanPool(Option<Arc<ThreadPool>>);
+
+impl ScanPool {
+    /// 在线程池中运行 f，其中的 par_iter 使用这个线程池
+    pub fn install<R: Send>(&self, f: impl FnOnce() -> R + Send) -> R {
+        match &self.0 {
+            Some(pool) => pool.install(f),
+            None => f(),
+        }
+    }
+
+    /// `tokio::task::spawn_blocking`，阻塞任务在线程池中运行
+    pub fn spawn_blocking<F, R>(self, f: F) -> JoinHandle<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        tokio::task::spawn_blocking(move || self.install(f))
+    }
+}
+
+/// 扫描线程池的设置，按需创建线程池
+pub struct WorkerPool {
+    name: &'static str,
+    /// 0 表示 rayon 的默认线程数
+    threads: usize,
+    background: bool,
+    pool: Option<Arc<ThreadPool>>,
+}
+
+impl WorkerPool {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            threads: 0,
+            background: false,
+            pool: None,
+        }
+    }
+
+    /// 设置工作线程数，0 恢复默认
+    pub fn set_threads(&mut self, threads: usize) {
+        if self.threads != threads {
+            self.threads = threads;
+            self.pool = None;
+        }
+    }
+
+    /// 工作线程是否使用后台调度优先级
+    pub fn set_background(&mut self, background: bool) {
+        if self.background != background {
+            self.background = background;
+            self.pool = None;
+        }
+    }
+
+    /// 下一次扫描使用的线程池
+    pub fn current(&mut self) -> Result<ScanPool> {
+        if self.threads == 0 && !self.background {
+            return Ok(ScanPool::default());
+        }
+        if self.pool.is_none() {
+            let mut builder = ThreadPoolBuilder::new().num_threads(self.threads).thread_name({
+                let name = self.name;
+                move |index| format!("{}-{}", name, index)
+            });
+            if self.background {
+                builder = builder.start_handler(|_| lower_current_thread_priority());
+            }
+            let pool = builder.build().map_err(|e| anyhow!("Failed to build {} thread pool: {}", self.name, e))?;
+            info!("Created {} thread pool: threads={}, background={}", self.name, pool.current_num_threads(), self.background);
+            self.pool = Some(Arc::new(pool));
+        }
+        Ok(ScanPool(self.pool.clone()))
+    }
+}
+
+/// 把当前线程降到后台：SCHED_BATCH 调度类，nice 值 `BACKGROUND_NICE`
+fn lower_current_thread_priority() {
+    unsafe {
+        let param = libc::sched_param { sched_priority: 0 };
+        if libc::sched_setscheduler(0, libc::SCHED_BATCH, &param) != 0 {
+            debug!("Failed to switch worker thread to SCHED_BATCH: {}", std::io::Error::last_os_error());
+        }
+        if libc::setpriority(libc::PRIO_PROCESS, 0, BACKGROUND_NICE) != 0 {
+            debug!("Failed to lower worker thread priority: {}", std::io::Error::last_os_error());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn thread_nice() -> i32 {
+        unsafe { libc::getpriority(libc::PRIO_PROCESS, 0) }
+    }
+
+    #[test]
+    fn test_default_uses_global_pool() {
+        let mut workers = WorkerPool::new("test");
+        let pool = workers.current().unwrap();
+        assert!(pool.0.is_none());
+        assert_eq!(pool.install(rayon::current_num_threads), rayon::current_num_threads());
+    }
+
+    #[test]
+    fn test_thread_count_and_background_priority() {
+        let mut workers = WorkerPool::new("test");
+        workers.set_threads(2);
+        workers.set_background(true);
+        let pool = workers.current().unwrap();
+
+        let (threads, nice) = pool.install(|| (rayon::current_num_threads(), thread_nice()));
+        assert_eq!(threads, 2);
+        assert_eq!(nice, BACKGROUND_NICE);
+        // 调用方线程的优先级不变
+        assert_ne!(thread_nice(), BACKGROUND_NICE);
+    }
+
+    #[test]
+    fn test_changes_apply_to_next_scan() {
+        let mut workers = WorkerPool::new("test");
+        workers.set_threads(3);
+        let running = workers.current().unwrap();
+        assert!(Arc::ptr_eq(running.0.as_ref().unwrap(), workers.current().unwrap().0.as_ref().unwrap()));
+
+        workers.set_threads(1);
+        let next = workers.current().unwrap();
+        assert_eq!(running.install(rayon::current_num_threads), 3);
+        assert_eq!(next.install(rayon::current_num_threads), 1);
+
+        workers.set_threads(0);
+        assert!(workers.current().unwrap().0.is_none());
+    }
+}
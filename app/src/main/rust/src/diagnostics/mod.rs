@@ -0,0 +1,26 @@
+//! Diagnostics
+//!
+//! Self-test used by support triage to check whether the native stack works on a device.
+
+pub mod self_test;
+
+pub use self_test::{SelfTestReport, SelfTestStep, run_self_test};
+
+use anyhow::Result;
+use serde_json::json;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 扫描报告日志的文件名，每行一条 JSON 报告，只追加不改写
+pub const SCAN_REPORT_LOG: &str = "scan_report.log";
+
+/// 向 `dir` 下的扫描报告日志追加一条报告：`{"kind", "timestamp_ms", "report"}`
+pub fn append_scan_report(dir: &Path, kind: &str, report: serde_json::Value) -> Result<()> {
+    let timestamp_ms = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0);
+    let line = json!({ "kind": kind, "timestamp_ms": timestamp_ms, "report": report });
+    let mut file = OpenOptions::new().create(true).append(true).open(dir.join(SCAN_REPORT_LOG))?;
+    writeln!(file, "{}", line)?;
+    Ok(())
+}
@@ -0,0 +1,583 @@
+//! Native stack self-test
+//!
+//! 在本进程内分配一块测试内存并写入已知数据，然后对本进程跑一遍完整流程：
+//! 精确搜索、细化、模糊搜索、特征码搜索、指针扫描和写入校验。每一步单独计时、单独判定。
+//!
+//! 驱动已加载时走驱动读写本进程，否则直接访问内存。精确搜索、特征码搜索调用引擎的按块区域扫描，
+//! 指针扫描运行完整的 `BfsV3Scanner`，细化和模糊搜索调用引擎的细化函数，读取都通过同一个内存源。
+//! 模糊搜索和指针扫描使用独立的临时目录（模糊搜索另有临时结果管理器），不会影响当前的会话，结束后删除所有缓存文件。
+//! 报告追加到缓存目录下的扫描报告日志。
+
+use super::append_scan_report;
+use crate::core::DRIVER_MANAGER;
+use crate::core::globals::PAGE_SIZE;
+use crate::pointer_scan::chain_builder::BfsV3Scanner;
+use crate::pointer_scan::samples::ChainSample;
+use crate::pointer_scan::scanner::ScanRegion;
+use crate::pointer_scan::types::{PointerScanConfig, VmStaticData};
+use crate::search::engine::fuzzy_search::{fuzzy_refine_search_with, scan_buffer_parallel};
+use crate::search::engine::manager::ValuePair;
+use crate::search::engine::pattern_search::search_region_pattern_with;
+use crate::search::engine::read_stats::ReadStats;
+use crate::search::engine::result_stream::REFINE_BATCH_SIZE;
+use crate::search::engine::single_search::{refine_values_stream_with, search_region_single_with};
+use crate::search::result_manager::{SearchResultManager, SearchResultMode};
+use crate::search::{FuzzyCondition, SearchValue, ValueType, parse_pattern};
+use crate::wuwa::PageStatusBitmap;
+use anyhow::{Result, anyhow, bail};
+use log::{info, warn};
+use serde::Serialize;
+use std::alloc::{Layout, alloc_zeroed, dealloc};
+use std::path::Path;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// 测试内存大小（64KB，是常见页大小的整数倍）
+pub(crate) const SELF_TEST_SIZE: usize = 64 * 1024;
+
+const EXACT_SEED: u32 = 0x1357_2468;
+const EXACT_OFFSETS: [u64; 4] = [0x0100, 0x1100, 0x2100, 0x3100];
+const REFINE_CHANGED: [usize; 2] = [1, 3];
+const REFINE_VALUE: u32 = 0x0BAD_F00D;
+
+const FUZZY_START: u64 = 0x4000;
+const FUZZY_LEN: usize = 0x1000;
+const FUZZY_INCREASED: [u64; 3] = [0x4010, 0x4800, 0x4FFC];
+
+const PATTERN_OFFSET: u64 = 0x6010;
+const PATTERN_BYTES: [u8; 4] = [0xDE, 0xAD, 0x42, 0xEF];
+const PATTERN: &str = "DE AD ?? EF";
+
+/// 合成指针链：root(0x8000) -> node(0x9000) +0x10 -> leaf(0xA000) +0x20 -> 目标值，
+/// root 所在的 [CHAIN_ROOT, CHAIN_NODE) 作为静态模块
+const CHAIN_ROOT: u64 = 0x8000;
+const CHAIN_NODE: u64 = 0x9000;
+const CHAIN_LEAF: u64 = 0xA000;
+const CHAIN_OFFSETS: [u64; 2] = [0x10, 0x20];
+const CHAIN_TARGET_VALUE: u32 = 0x2468_ACE0;
+const CHAIN_MODULE: &str = "libmamu_selftest.so";
+const CHAIN_MAX_OFFSET: u32 = 0x100;
+
+/// 区域扫描每次读取的块大小，小于测试内存以覆盖跨块
+const SCAN_CHUNK_SIZE: usize = 16 * 1024;
+
+const WRITE_OFFSET: u64 = 0xB000;
+const WRITE_VALUE: u64 = 0x1122_3344_5566_7788;
+
+/// 自检使用的内存访问方式
+pub(crate) trait SelfTestMemory: Sync {
+    fn name(&self) -> &'static str;
+    /// 按页记录读取结果，不可读的页在 `page_status` 中保持未标记
+    fn read(&self, addr: u64, buf: &mut [u8], page_status: &mut PageStatusBitmap) -> Result<()>;
+    fn write(&self, addr: u64, data: &[u8]) -> Result<()>;
+}
+
+/// 通过驱动读写本进程，读取走 `read_memory_of`（本进程已绑定时即 `read_memory_unified`）
+struct DriverMemory {
+    pid: i32,
+}
+
+impl SelfTestMemory for DriverMemory {
+    fn name(&self) -> &'static str {
+        "driver"
+    }
+
+    fn read(&self, addr: u64, buf: &mut [u8], page_status: &mut PageStatusBitmap) -> Result<()> {
+        let manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
+        manager.read_memory_of(self.pid, addr, buf, Some(page_status))
+    }
+
+    fn write(&self, addr: u64, data: &[u8]) -> Result<()> {
+        let manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
+        let driver = manager.get_driver().ok_or_else(|| anyhow!("Driver not initialized"))?;
+        driver.write_memory(self.pid, data.as_ptr() as usize, addr as usize, data.len())?;
+        Ok(())
+    }
+}
+
+/// 直接访问本进程内存（没有加载驱动时使用）
+struct LocalMemory {
+    start: u64,
+    end: u64,
+}
+
+impl LocalMemory {
+    fn check_range(&self, addr: u64, len: usize) -> Result<()> {
+        if addr < self.start || addr + len as u64 > self.end {
+            bail!("Address 0x{:X} (+{}) outside self-test buffer", addr, len);
+        }
+        Ok(())
+    }
+}
+
+impl SelfTestMemory for LocalMemory {
+    fn name(&self) -> &'static str {
+        "direct"
+    }
+
+    fn read(&self, addr: u64, buf: &mut [u8], page_status: &mut PageStatusBitmap) -> Result<()> {
+        self.check_range(addr, buf.len())?;
+        unsafe { std::ptr::copy_nonoverlapping(addr as *const u8, buf.as_mut_ptr(), buf.len()) };
+        // 测试内存都可读，只标记实际复制过的页
+        for page in 0..covered_pages(addr, buf.len()) {
+            page_status.mark_success(page);
+        }
+        Ok(())
+    }
+
+    fn write(&self, addr: u64, data: &[u8]) -> Result<()> {
+        self.check_range(addr, data.len())?;
+        unsafe { std::ptr::copy_nonoverlapping(data.as_ptr(), addr as *mut u8, data.len()) };
+        Ok(())
+    }
+}
+
+/// [addr, addr + len) 覆盖的页数（位图按 64 页取整，多出的页不属于本次读取）
+fn covered_pages(addr: u64, len: usize) -> usize {
+    ((addr as usize & (*PAGE_SIZE - 1)) + len).div_ceil(*PAGE_SIZE)
+}
+
+/// 页对齐的测试内存，Drop 时释放
+struct TestBuffer {
+    ptr: *mut u8,
+    layout: Layout,
+}
+
+impl TestBuffer {
+    fn new(size: usize) -> Result<Self> {
+        let layout = Layout::from_size_align(size, *PAGE_SIZE)?;
+        let ptr = unsafe { alloc_zeroed(layout) };
+        if ptr.is_null() {
+            bail!("Failed to allocate self-test buffer");
+        }
+        Ok(Self { ptr, layout })
+    }
+
+    fn addr(&self) -> u64 {
+        self.ptr as u64
+    }
+}
+
+impl Drop for TestBuffer {
+    fn drop(&mut self) {
+        unsafe { dealloc(self.ptr, self.layout) };
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfTestStep {
+    pub name: &'static str,
+    pub passed: bool,
+    pub duration_us: u64,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfTestReport {
+    /// 内存访问方式：driver / direct
+    pub source: &'static str,
+    pub pid: u32,
+    pub passed: bool,
+    pub duration_us: u64,
+    pub steps: Vec<SelfTestStep>,
+}
+
+/// 对本进程运行自检，`cache_dir` 用于模糊搜索的临时结果管理器，报告追加到其中的扫描报告日志
+pub fn run_self_test(cache_dir: &Path) -> Result<SelfTestReport> {
+    let buffer = TestBuffer::new(SELF_TEST_SIZE)?;
+    let base = buffer.addr();
+
+    let driver_loaded = DRIVER_MANAGER.read().map(|m| m.is_driver_loaded()).unwrap_or(false);
+    let report = if driver_loaded {
+        let mem = DriverMemory {
+            pid: std::process::id() as i32,
+        };
+        run_self_test_on(&mem, base, SELF_TEST_SIZE, cache_dir)
+    } else {
+        let mem = LocalMemory {
+            start: base,
+            end: base + SELF_TEST_SIZE as u64,
+        };
+        run_self_test_on(&mem, base, SELF_TEST_SIZE, cache_dir)
+    };
+
+    match serde_json::to_value(&report) {
+        Ok(json) => {
+            info!("Self-test report: {}", json);
+            if let Err(e) = append_scan_report(cache_dir, "self_test", json) {
+                warn!("Failed to append self-test report: {:?}", e);
+            }
+        },
+        Err(e) => warn!("Failed to serialize self-test report: {:?}", e),
+    }
+
+    Ok(report)
+}
+
+/// 在 [base, base + len) 上运行自检的全部步骤
+pub(crate) fn run_self_test_on<M: SelfTestMemory>(mem: &M, base: u64, len: usize, cache_dir: &Path) -> SelfTestReport {
+    let started = Instant::now();
+    let target = Target { mem, base, len, cache_dir };
+
+    type Step<'a, M> = fn(&Target<'a, M>) -> Result<String>;
+    let steps: Vec<(&'static str, Step<M>)> = vec![
+        ("seed", Target::seed),
+        ("exact_search", Target::exact_search),
+        ("refine", Target::refine),
+        ("fuzzy", Target::fuzzy),
+        ("pattern_search", Target::pattern_search),
+        ("pointer_scan", Target::pointer_scan),
+        ("verified_write", Target::verified_write),
+    ];
+
+    let steps: Vec<SelfTestStep> = steps
+        .into_iter()
+        .map(|(name, step)| {
+            let step_started = Instant::now();
+            let result = step(&target);
+            let duration_us = step_started.elapsed().as_micros() as u64;
+            match result {
+                Ok(detail) => SelfTestStep {
+                    name,
+                    passed: true,
+                    duration_us,
+                    detail,
+                },
+                Err(e) => {
+                    warn!("Self-test step {} failed: {:?}", name, e);
+                    SelfTestStep {
+                        name,
+                        passed: false,
+                        duration_us,
+                        detail: e.to_string(),
+                    }
+                },
+            }
+        })
+        .collect();
+
+    SelfTestReport {
+        source: mem.name(),
+        pid: std::process::id(),
+        passed: steps.iter().all(|s| s.passed),
+        duration_us: started.elapsed().as_micros() as u64,
+        steps,
+    }
+}
+
+struct Target<'a, M: SelfTestMemory> {
+    mem: &'a M,
+    base: u64,
+    len: usize,
+    cache_dir: &'a Path,
+}
+
+impl<M: SelfTestMemory> Target<'_, M> {
+    /// 读取 `buf.len()` 字节，任何一页不可读都算失败
+    fn read_into(&self, addr: u64, buf: &mut [u8]) -> Result<PageStatusBitmap> {
+        let mut page_status = PageStatusBitmap::new(buf.len(), addr as usize);
+        self.mem.read(addr, buf, &mut page_status)?;
+        let pages = covered_pages(addr, buf.len());
+        if let Some(page) = (0..pages).find(|&i| !page_status.is_page_success(i)) {
+            bail!("Page {} of {} unreadable at 0x{:X}", page, pages, addr);
+        }
+        Ok(page_status)
+    }
+
+    /// 引擎细化函数使用的读取闭包
+    fn reader(&self) -> impl Fn(u64, &mut [u8]) -> bool + Sync + '_ {
+        |addr, buf| self.read_into(addr, buf).is_ok()
+    }
+
+    /// 引擎区域扫描使用的读取闭包，按页记录读取结果
+    fn chunk_reader(&self) -> impl Fn(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()> + Sync + '_ {
+        |addr, buf, page_status| self.mem.read(addr, buf, page_status)
+    }
+
+    /// 在缓存目录下的独立临时目录中运行 `step`，结束后删除该目录
+    fn in_session_dir(&self, step: impl FnOnce(&Path) -> Result<String>) -> Result<String> {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+        let session_dir = self.cache_dir.join(format!("mamu_selftest_{}", nanos));
+        std::fs::create_dir_all(&session_dir)?;
+
+        let result = step(&session_dir);
+
+        if let Err(e) = std::fs::remove_dir_all(&session_dir) {
+            warn!("Failed to remove self-test session dir {:?}: {:?}", session_dir, e);
+        }
+        result
+    }
+
+    fn read_range(&self, offset: u64, len: usize) -> Result<(Vec<u8>, PageStatusBitmap)> {
+        let mut buffer = vec![0u8; len];
+        let page_status = self.read_into(self.base + offset, &mut buffer)?;
+        Ok((buffer, page_status))
+    }
+
+    fn write_at(&self, offset: u64, data: &[u8]) -> Result<()> {
+        self.mem.write(self.base + offset, data)
+    }
+
+    fn read_u32(&self, offset: u64) -> Result<u32> {
+        let (bytes, _) = self.read_range(offset, 4)?;
+        Ok(u32::from_le_bytes(bytes[..4].try_into()?))
+    }
+
+    /// 清零整块测试内存并写入各步骤用到的已知数据
+    fn seed(&self) -> Result<String> {
+        self.write_at(0, &vec![0u8; self.len])?;
+
+        for offset in EXACT_OFFSETS {
+            self.write_at(offset, &EXACT_SEED.to_le_bytes())?;
+        }
+        for i in 0..FUZZY_LEN / 4 {
+            self.write_at(FUZZY_START + (i * 4) as u64, &(i as u32).to_le_bytes())?;
+        }
+        self.write_at(PATTERN_OFFSET, &PATTERN_BYTES)?;
+        self.write_at(CHAIN_ROOT, &(self.base + CHAIN_NODE).to_le_bytes())?;
+        self.write_at(CHAIN_NODE + CHAIN_OFFSETS[0], &(self.base + CHAIN_LEAF).to_le_bytes())?;
+        self.write_at(CHAIN_LEAF + CHAIN_OFFSETS[1], &CHAIN_TARGET_VALUE.to_le_bytes())?;
+
+        if self.read_u32(EXACT_OFFSETS[0])? != EXACT_SEED {
+            bail!("Seed value not visible after write");
+        }
+        Ok(format!("{} bytes at 0x{:X}", self.len, self.base))
+    }
+
+    fn exact_search(&self) -> Result<String> {
+        let target = SearchValue::fixed(EXACT_SEED as i128, ValueType::Dword);
+        let results = search_region_single_with(
+            &target,
+            self.base,
+            self.base + self.len as u64,
+            SCAN_CHUNK_SIZE,
+            None,
+            self.chunk_reader(),
+            &|| false,
+            &ReadStats::new(),
+        )?;
+
+        let mut found: Vec<u64> = results.iter().map(|p| p.addr - self.base).collect();
+        found.sort_unstable();
+        if found != EXACT_OFFSETS {
+            bail!("Expected {:X?}, found {:X?}", EXACT_OFFSETS, found);
+        }
+        Ok(format!("{} results", found.len()))
+    }
+
+    /// 修改部分精确搜索结果后用原值细化，只剩未修改的地址
+    fn refine(&self) -> Result<String> {
+        for &i in &REFINE_CHANGED {
+            self.write_at(EXACT_OFFSETS[i], &REFINE_VALUE.to_le_bytes())?;
+        }
+
+        let target = SearchValue::fixed(EXACT_SEED as i128, ValueType::Dword);
+        let seeds = EXACT_OFFSETS.iter().map(|offset| ValuePair::new(self.base + offset, ValueType::Dword));
+        let refined = refine_values_stream_with(seeds, &target, REFINE_BATCH_SIZE, self.reader(), None, None, &|| false, &|_, _| {});
+        let remaining: Vec<u64> = refined.iter().map(|pair| pair.addr - self.base).collect();
+
+        let expected: Vec<u64> = (0..EXACT_OFFSETS.len())
+            .filter(|i| !REFINE_CHANGED.contains(i))
+            .map(|i| EXACT_OFFSETS[i])
+            .collect();
+        if remaining != expected {
+            bail!("Expected {:X?}, remaining {:X?}", expected, remaining);
+        }
+        Ok(format!("{} -> {} results", EXACT_OFFSETS.len(), remaining.len()))
+    }
+
+    /// 在独立的临时结果管理器中做一轮模糊搜索（首次扫描 + 增大）
+    fn fuzzy(&self) -> Result<String> {
+        self.in_session_dir(|session_dir| self.fuzzy_in(session_dir))
+    }
+
+    fn fuzzy_in(&self, session_dir: &Path) -> Result<String> {
+        let mut manager = SearchResultManager::new(1024 * 1024, session_dir.to_path_buf());
+        manager.set_mode(SearchResultMode::Fuzzy)?;
+
+        let region_start = self.base + FUZZY_START;
+        let region_end = region_start + FUZZY_LEN as u64;
+        let (buffer, page_status) = self.read_range(FUZZY_START, FUZZY_LEN)?;
+        let initial = scan_buffer_parallel(&buffer, region_start, region_start, region_end, 4, ValueType::Dword, *PAGE_SIZE, &page_status);
+        if initial.len() != FUZZY_LEN / 4 {
+            bail!("Initial scan recorded {} of {} values", initial.len(), FUZZY_LEN / 4);
+        }
+        manager.add_fuzzy_results_batch(initial)?;
+
+        for offset in FUZZY_INCREASED {
+            let value = self.read_u32(offset)?;
+            self.write_at(offset, &(value + 5).to_le_bytes())?;
+        }
+
+        let current = manager.get_all_fuzzy_results()?;
+        let refined = fuzzy_refine_search_with(&current, FuzzyCondition::Increased, 0, 0, self.reader(), None, None, &|_, _| {}, Some(&|| false));
+        manager.replace_all_fuzzy_results(refined)?;
+
+        let mut increased: Vec<u64> = manager.get_all_fuzzy_results()?.iter().map(|item| item.address - self.base).collect();
+        increased.sort_unstable();
+
+        if increased != FUZZY_INCREASED {
+            bail!("Expected {:X?}, found {:X?}", FUZZY_INCREASED, increased);
+        }
+        Ok(format!("{} -> {} results", FUZZY_LEN / 4, increased.len()))
+    }
+
+    fn pattern_search(&self) -> Result<String> {
+        let pattern = parse_pattern(PATTERN).map_err(|e| anyhow!(e))?;
+        let results = search_region_pattern_with(&pattern, self.base, self.base + self.len as u64, SCAN_CHUNK_SIZE, self.chunk_reader(), &|| false)?;
+
+        let found: Vec<u64> = results.iter().map(|addr| addr - self.base).collect();
+        if found != [PATTERN_OFFSET] {
+            bail!("Expected [{:X}], found {:X?}", PATTERN_OFFSET, found);
+        }
+        Ok(format!("\"{}\" at +0x{:X}", PATTERN, PATTERN_OFFSET))
+    }
+
+    /// 对测试内存运行深度 2 的完整指针扫描，输出中必须有合成指针链，再沿该链解析到目标值
+    fn pointer_scan(&self) -> Result<String> {
+        self.in_session_dir(|session_dir| self.pointer_scan_in(session_dir))
+    }
+
+    fn pointer_scan_in(&self, session_dir: &Path) -> Result<String> {
+        let target = self.base + CHAIN_LEAF + CHAIN_OFFSETS[1];
+        let mut config = PointerScanConfig::new(target).with_depth(CHAIN_OFFSETS.len() as u32);
+        config.max_offset = CHAIN_MAX_OFFSET;
+        config.align = 8;
+        let regions = vec![ScanRegion {
+            start: self.base,
+            end: self.base + self.len as u64,
+            name: "[anon:mamu_selftest]".to_string(),
+        }];
+        let modules = vec![VmStaticData::new(CHAIN_MODULE.to_string(), self.base + CHAIN_ROOT, self.base + CHAIN_NODE, true)];
+
+        let output = session_dir.join("pointer_scan.txt");
+        let scanner = BfsV3Scanner::new(config, regions, modules);
+        let result = scanner.run_with(output.clone(), usize::MAX, self.chunk_reader(), |_, _, _, _| {}, || false)?;
+
+        let chains: Vec<ChainSample> = std::fs::read_to_string(&output)?.lines().filter_map(ChainSample::parse).collect();
+        let expected_offsets: Vec<i64> = CHAIN_OFFSETS.iter().map(|&offset| offset as i64).collect();
+        let chain = chains
+            .iter()
+            .find(|chain| chain.module == CHAIN_MODULE && chain.base_offset == 0 && chain.offsets == expected_offsets)
+            .ok_or_else(|| anyhow!("Synthetic chain not in {} chains: {:?}", result.total_count, chains))?;
+
+        let mut current = self.base + CHAIN_ROOT + chain.base_offset;
+        for &offset in &chain.offsets {
+            let (bytes, _) = self.read_range(current - self.base, 8)?;
+            current = u64::from_le_bytes(bytes[..8].try_into()?) + offset as u64;
+        }
+
+        let value = self.read_u32(current - self.base)?;
+        if value != CHAIN_TARGET_VALUE {
+            bail!("Chain resolved to 0x{:X} holding 0x{:X}", current, value);
+        }
+        Ok(format!("{} chains, {}", result.total_count, chain.to_chain_string()))
+    }
+
+    fn verified_write(&self) -> Result<String> {
+        self.write_at(WRITE_OFFSET, &WRITE_VALUE.to_le_bytes())?;
+        let (bytes, _) = self.read_range(WRITE_OFFSET, 8)?;
+        let value = u64::from_le_bytes(bytes[..8].try_into()?);
+        if value != WRITE_VALUE {
+            bail!("Wrote 0x{:X}, read back 0x{:X}", WRITE_VALUE, value);
+        }
+        Ok(format!("0x{:X}", WRITE_VALUE))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::SCAN_REPORT_LOG;
+    use crate::search::tests::mock_memory::MockMemory;
+    use crate::search::tests::temp_dir::TempDir;
+    use std::sync::Mutex;
+
+    const MOCK_BASE: u64 = 0x7F0000000000;
+
+    struct MockSource {
+        mem: Mutex<MockMemory>,
+    }
+
+    impl SelfTestMemory for MockSource {
+        fn name(&self) -> &'static str {
+            "mock"
+        }
+
+        fn read(&self, addr: u64, buf: &mut [u8], page_status: &mut PageStatusBitmap) -> Result<()> {
+            self.mem.lock().unwrap().mem_read_with_status(addr, buf, page_status)
+        }
+
+        fn write(&self, addr: u64, data: &[u8]) -> Result<()> {
+            self.mem.lock().unwrap().mem_write(addr, data)
+        }
+    }
+
+    fn list_dir(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_self_test_mock_memory() {
+        let mut mem = MockMemory::new();
+        let base = mem.malloc(MOCK_BASE, SELF_TEST_SIZE).unwrap();
+        let source = MockSource { mem: Mutex::new(mem) };
+
+        let cache_dir = TempDir::new("selftest_cache");
+        std::fs::write(cache_dir.join("existing_session.bin"), b"keep").unwrap();
+        let before = list_dir(&cache_dir);
+
+        let report = run_self_test_on(&source, base, SELF_TEST_SIZE, &cache_dir);
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+
+        for step in &report.steps {
+            assert!(step.passed, "Step {} failed: {}", step.name, step.detail);
+        }
+        assert!(report.passed);
+        assert_eq!(report.source, "mock");
+        assert_eq!(report.steps.len(), 7);
+
+        assert_eq!(list_dir(&cache_dir), before);
+    }
+
+    #[test]
+    fn test_self_test_steps_fail_independently() {
+        let mut mem = MockMemory::new();
+        let base = mem.malloc(MOCK_BASE, SELF_TEST_SIZE).unwrap();
+        // 指针链所在页不可读
+        mem.set_faulty_pages(base, &[(CHAIN_ROOT as usize) / 4096]).unwrap();
+        let source = MockSource { mem: Mutex::new(mem) };
+
+        let cache_dir = TempDir::new("selftest_faulty");
+        let report = run_self_test_on(&source, base, SELF_TEST_SIZE, &cache_dir);
+
+        let failed: Vec<&str> = report.steps.iter().filter(|s| !s.passed).map(|s| s.name).collect();
+        assert!(!report.passed);
+        assert!(failed.contains(&"pointer_scan"));
+        assert!(!failed.contains(&"fuzzy"));
+        assert!(!failed.contains(&"verified_write"));
+    }
+
+    #[test]
+    fn test_self_test_direct_memory() {
+        let cache_dir = TempDir::new("selftest_direct");
+        let report = run_self_test(&cache_dir).unwrap();
+        for step in &report.steps {
+            assert!(step.passed, "Step {} failed: {}", step.name, step.detail);
+        }
+        assert_eq!(report.source, "direct");
+
+        // 只留下扫描报告日志，每次运行追加一行
+        run_self_test(&cache_dir).unwrap();
+        assert_eq!(list_dir(&cache_dir), [SCAN_REPORT_LOG]);
+        let log = std::fs::read_to_string(cache_dir.join(SCAN_REPORT_LOG)).unwrap();
+        let entries: Vec<serde_json::Value> = log.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["kind"], "self_test");
+        assert_eq!(entries[0]["report"]["steps"].as_array().unwrap().len(), 7);
+    }
+}
@@ -0,0 +1,445 @@
+//! GameGuardian saved list import
+//!
+//! 解析 GameGuardian 导出的保存列表（文本格式），每行一个条目，字段以 `|` 分隔：
+//!
+//! ```text
+//! name|address|flags|value|frozen[|...]
+//! ```
+//!
+//! - `address`：十六进制绝对地址（可带 `0x`），或模块相对地址 `libX.so+0x1234`
+//! - `flags`：GG 的类型标志（gg.TYPE_*），只支持单一类型
+//! - `value`：十进制或十六进制（`0x` 前缀 / `h` 后缀）整数，浮点类型为小数；可以为空
+//! - `frozen`：`1` / `0`（也接受 `true` / `false`），省略时为 0
+//!
+//! 第五个字段之后的内容（冻结方式、区域名等）忽略。空行、`#` 开头的注释行和开头的条目数行跳过。
+//! 格式错误或不支持的条目跳过并给出带行号的警告，不会中断整个导入。
+
+use crate::core::freeze_manager::FreezeManager;
+use crate::core::globals::{DRIVER_MANAGER, FREEZE_MANAGER};
+use crate::core::region_map::current_region_map;
+use crate::search::result_manager::SearchResultMode;
+use crate::search::{SEARCH_ENGINE_MANAGER, SearchResultItem, ValueType};
+use anyhow::{Result, anyhow};
+use log::info;
+use serde::Serialize;
+
+/// GG 的类型标志（gg.TYPE_*）
+const GG_TYPE_BYTE: u32 = 1;
+const GG_TYPE_WORD: u32 = 2;
+const GG_TYPE_DWORD: u32 = 4;
+const GG_TYPE_XOR: u32 = 8;
+const GG_TYPE_FLOAT: u32 = 16;
+const GG_TYPE_QWORD: u32 = 32;
+const GG_TYPE_DOUBLE: u32 = 64;
+const GG_TYPE_AUTO: u32 = 127;
+
+/// 条目地址
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GgLocation {
+    Absolute(u64),
+    /// 模块基址 + 偏移，导入时按当前进程的映射解析
+    ModuleRelative {
+        module: String,
+        offset: u64,
+    },
+}
+
+/// 解析后的一个条目
+#[derive(Debug, Clone, PartialEq)]
+pub struct GgEntry {
+    /// 行号（从 1 开始），用于警告
+    pub line: usize,
+    pub name: String,
+    pub location: GgLocation,
+    pub value_type: ValueType,
+    /// 按 value_type 编码的值（小端），值字段为空时为 None
+    pub value: Option<Vec<u8>>,
+    pub frozen: bool,
+}
+
+/// 解析结果
+#[derive(Debug, Default)]
+pub struct ParsedSavedList {
+    pub entries: Vec<GgEntry>,
+    /// 被跳过的条目数（格式错误或不支持）
+    pub skipped: usize,
+    pub warnings: Vec<String>,
+}
+
+/// 导入的条目名称，目前没有标签存储，交给上层展示
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ImportedLabel {
+    pub address: u64,
+    pub name: String,
+}
+
+/// 导入报告，序列化后返回给 Java 层
+#[derive(Debug, Default, Serialize)]
+pub struct ImportReport {
+    pub imported: usize,
+    pub skipped: usize,
+    pub frozen_created: usize,
+    pub warnings: Vec<String>,
+    pub labels: Vec<ImportedLabel>,
+}
+
+/// GG 类型标志 -> ValueType，组合标志和 Auto 不支持
+pub fn value_type_from_gg_flags(flags: u32) -> Option<ValueType> {
+    match flags {
+        GG_TYPE_BYTE => Some(ValueType::Byte),
+        GG_TYPE_WORD => Some(ValueType::Word),
+        GG_TYPE_DWORD => Some(ValueType::Dword),
+        GG_TYPE_XOR => Some(ValueType::Xor),
+        GG_TYPE_FLOAT => Some(ValueType::Float),
+        GG_TYPE_QWORD => Some(ValueType::Qword),
+        GG_TYPE_DOUBLE => Some(ValueType::Double),
+        _ => None,
+    }
+}
+
+/// 解析保存列表文本
+pub fn parse_saved_list(text: &str) -> ParsedSavedList {
+    let mut parsed = ParsedSavedList::default();
+    let mut seen_entry = false;
+
+    for (idx, raw) in text.lines().enumerate() {
+        let line_no = idx + 1;
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        // 文件开头的条目数
+        if !seen_entry && !line.contains('|') && line.parse::<u64>().is_ok() {
+            continue;
+        }
+        seen_entry = true;
+
+        match parse_line(line, line_no) {
+            Ok(entry) => parsed.entries.push(entry),
+            Err(e) => {
+                parsed.skipped += 1;
+                parsed.warnings.push(format!("line {}: {}", line_no, e));
+            },
+        }
+    }
+
+    parsed
+}
+
+fn parse_line(line: &str, line_no: usize) -> Result<GgEntry> {
+    let fields: Vec<&str> = line.split('|').map(str::trim).collect();
+    if fields.len() < 4 {
+        return Err(anyhow!("expected at least 4 fields, got {}", fields.len()));
+    }
+
+    let location = parse_location(fields[1])?;
+
+    let flags: u32 = fields[2].parse().map_err(|_| anyhow!("invalid type flags '{}'", fields[2]))?;
+    let value_type = match value_type_from_gg_flags(flags) {
+        Some(value_type) => value_type,
+        None if flags == GG_TYPE_AUTO => return Err(anyhow!("type Auto (127) is not supported")),
+        None => return Err(anyhow!("unsupported type flags {}", flags)),
+    };
+
+    let value = match fields[3] {
+        "" => None,
+        text => Some(encode_value(text, value_type)?),
+    };
+
+    let frozen = match fields.get(4).copied().unwrap_or("") {
+        "" | "0" => false,
+        "1" => true,
+        flag if flag.eq_ignore_ascii_case("true") => true,
+        flag if flag.eq_ignore_ascii_case("false") => false,
+        flag => return Err(anyhow!("invalid frozen flag '{}'", flag)),
+    };
+
+    Ok(GgEntry {
+        line: line_no,
+        name: fields[0].to_string(),
+        location,
+        value_type,
+        value,
+        frozen,
+    })
+}
+
+fn parse_hex(text: &str) -> Option<u64> {
+    let digits = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")).unwrap_or(text);
+    if digits.is_empty() {
+        return None;
+    }
+    u64::from_str_radix(digits, 16).ok()
+}
+
+fn parse_location(text: &str) -> Result<GgLocation> {
+    if let Some((module, offset)) = text.rsplit_once('+') {
+        let module = module.trim();
+        if module.is_empty() {
+            return Err(anyhow!("missing module name in '{}'", text));
+        }
+        let offset = parse_hex(offset.trim()).ok_or_else(|| anyhow!("invalid module offset in '{}'", text))?;
+        return Ok(GgLocation::ModuleRelative {
+            module: module.to_string(),
+            offset,
+        });
+    }
+
+    parse_hex(text).map(GgLocation::Absolute).ok_or_else(|| anyhow!("invalid address '{}'", text))
+}
+
+fn parse_int(text: &str) -> Option<i128> {
+    let (negative, body) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let magnitude = if let Some(hex) = body.strip_prefix("0x").or_else(|| body.strip_prefix("0X")) {
+        i128::from_str_radix(hex, 16).ok()?
+    } else if let Some(hex) = body.strip_suffix('h').or_else(|| body.strip_suffix('H')) {
+        i128::from_str_radix(hex, 16).ok()?
+    } else {
+        body.parse::<i128>().ok()?
+    };
+    Some(if negative { -magnitude } else { magnitude })
+}
+
+/// 按 value_type 把值编码为小端字节，整数接受有符号和无符号两种范围
+fn encode_value(text: &str, value_type: ValueType) -> Result<Vec<u8>> {
+    let invalid = || anyhow!("invalid {:?} value '{}'", value_type, text);
+
+    match value_type {
+        ValueType::Float => text.parse::<f32>().map(|v| v.to_le_bytes().to_vec()).map_err(|_| invalid()),
+        ValueType::Double => text.parse::<f64>().map(|v| v.to_le_bytes().to_vec()).map_err(|_| invalid()),
+        _ => {
+            let size = value_type.size();
+            let value = parse_int(text).ok_or_else(invalid)?;
+            let bits = (size * 8) as u32;
+            let min = -(1i128 << (bits - 1));
+            let max = (1i128 << bits) - 1;
+            if value < min || value > max {
+                return Err(anyhow!("{:?} value '{}' out of range", value_type, text));
+            }
+            Ok(value.to_le_bytes()[..size].to_vec())
+        },
+    }
+}
+
+/// 把解析结果应用到冻结管理器，返回要加入结果列表的条目和报告
+///
+/// 所有条目都作为精确结果导入；冻结条目额外创建为暂停状态的冻结项，需要用户确认后恢复。
+/// 模块相对地址通过 `resolve_module` 解析，模块未加载的条目跳过。
+pub fn apply_saved_list<R>(parsed: ParsedSavedList, resolve_module: R, freeze_manager: &FreezeManager, pid: i32) -> (Vec<SearchResultItem>, ImportReport)
+where
+    R: Fn(&str) -> Option<u64>,
+{
+    let mut report = ImportReport {
+        skipped: parsed.skipped,
+        warnings: parsed.warnings,
+        ..Default::default()
+    };
+    let mut results = Vec::with_capacity(parsed.entries.len());
+
+    for entry in parsed.entries {
+        let address = match &entry.location {
+            GgLocation::Absolute(address) => *address,
+            GgLocation::ModuleRelative { module, offset } => match resolve_module(module) {
+                Some(base) => base + offset,
+                None => {
+                    report.skipped += 1;
+                    report.warnings.push(format!("line {}: module '{}' is not loaded", entry.line, module));
+                    continue;
+                },
+            },
+        };
+
+        if entry.frozen {
+            match (&entry.value, entry.value_type) {
+                (_, ValueType::Xor) => {
+                    report
+                        .warnings
+                        .push(format!("line {}: freezing Xor values is not supported, imported without freeze", entry.line));
+                },
+                (None, _) => {
+                    report
+                        .warnings
+                        .push(format!("line {}: frozen entry has no value, imported without freeze", entry.line));
+                },
+                (Some(value), value_type) => {
+                    freeze_manager.add_frozen_paused(address, value.clone(), value_type.to_id(), pid);
+                    report.frozen_created += 1;
+                },
+            }
+        }
+
+        results.push(SearchResultItem::new_exact(address, entry.value_type));
+        if !entry.name.is_empty() {
+            report.labels.push(ImportedLabel { address, name: entry.name });
+        }
+        report.imported += 1;
+    }
+
+    (results, report)
+}
+
+/// 从文件导入 GG 保存列表：替换当前结果，冻结条目以暂停状态加入冻结管理器
+pub fn import_gg_saved_list(path: &str) -> Result<ImportReport> {
+    let bytes = std::fs::read(path).map_err(|e| anyhow!("Failed to read {}: {}", path, e))?;
+    let parsed = parse_saved_list(&String::from_utf8_lossy(&bytes));
+
+    let (pid, region_map) = {
+        let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+        if !driver_manager.is_process_bound() {
+            return Err(anyhow!("No process is bound"));
+        }
+        (driver_manager.get_bound_pid(), current_region_map(&driver_manager)?)
+    };
+
+    let (results, report) = {
+        let freeze_manager = FREEZE_MANAGER.read().map_err(|_| anyhow!("Failed to acquire FreezeManager read lock"))?;
+        apply_saved_list(parsed, |module| region_map.module_base(module), &freeze_manager, pid)
+    };
+
+    let mut manager = SEARCH_ENGINE_MANAGER
+        .write()
+        .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
+    manager.clear_results()?;
+    manager.set_result_mode(SearchResultMode::Exact)?;
+    if !results.is_empty() {
+        manager.add_results_batch(results)?;
+    }
+
+    info!(
+        "Imported GG saved list {}: imported={}, skipped={}, frozen={}",
+        path, report.imported, report.skipped, report.frozen_created
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = include_str!("testdata/gg_savedlist.txt");
+    const PID: i32 = 1000;
+    const LIB_BASE: u64 = 0x7A00000000;
+
+    fn resolve(module: &str) -> Option<u64> {
+        (module == "libgame.so").then_some(LIB_BASE)
+    }
+
+    #[test]
+    fn test_gg_type_mapping() {
+        let table = [
+            (1, Some(ValueType::Byte)),
+            (2, Some(ValueType::Word)),
+            (4, Some(ValueType::Dword)),
+            (8, Some(ValueType::Xor)),
+            (16, Some(ValueType::Float)),
+            (32, Some(ValueType::Qword)),
+            (64, Some(ValueType::Double)),
+            (0, None),
+            (127, None),
+            (4 | 16, None),
+            (128, None),
+        ];
+        for (flags, expected) in table {
+            assert_eq!(value_type_from_gg_flags(flags), expected, "flags {}", flags);
+        }
+    }
+
+    #[test]
+    fn test_parse_fixture() {
+        let parsed = parse_saved_list(FIXTURE);
+        assert_eq!(parsed.entries.len(), 8, "{:?}", parsed.warnings);
+        assert_eq!(parsed.skipped, 2);
+        assert_eq!(parsed.warnings.len(), 2);
+        assert!(parsed.warnings.iter().any(|w| w.contains("Auto")));
+        assert!(parsed.warnings.iter().any(|w| w.contains("unsupported type flags 20")));
+
+        let coins = &parsed.entries[0];
+        assert_eq!(coins.name, "Coins");
+        assert_eq!(coins.location, GgLocation::Absolute(0x7B00001000));
+        assert_eq!(coins.value_type, ValueType::Dword);
+        assert_eq!(coins.value, Some(1000i32.to_le_bytes().to_vec()));
+        assert!(!coins.frozen);
+
+        let health = &parsed.entries[1];
+        assert_eq!(health.value_type, ValueType::Float);
+        assert_eq!(health.value, Some(100.5f32.to_le_bytes().to_vec()));
+        assert!(health.frozen);
+
+        let module_entry = parsed.entries.iter().find(|e| e.name == "Speed").unwrap();
+        assert_eq!(
+            module_entry.location,
+            GgLocation::ModuleRelative {
+                module: "libgame.so".to_string(),
+                offset: 0x1A2B0,
+            }
+        );
+
+        let hex = parsed.entries.iter().find(|e| e.name == "Flags").unwrap();
+        assert_eq!(hex.value, Some(vec![0xFF]));
+        let negative = parsed.entries.iter().find(|e| e.name == "Delta").unwrap();
+        assert_eq!(negative.value, Some((-2i16).to_le_bytes().to_vec()));
+        let empty = parsed.entries.iter().find(|e| e.name.is_empty()).unwrap();
+        assert_eq!(empty.value, None);
+    }
+
+    #[test]
+    fn test_malformed_lines_are_skipped_with_line_numbers() {
+        let text = "3\n\
+                    ok|1000|4|1|0\n\
+                    too|few\n\
+                    bad addr|xyz|4|1|0\n\
+                    bad type|1000|abc|1|0\n\
+                    overflow|1000|1|300|0\n\
+                    bad float|1000|16|fast|0\n\
+                    bad frozen|1000|4|1|maybe\n\
+                    |+0x10|4|1|0\n";
+        let parsed = parse_saved_list(text);
+        assert_eq!(parsed.entries.len(), 1);
+        assert_eq!(parsed.skipped, 7);
+        let lines: Vec<&str> = parsed.warnings.iter().map(|w| w.split(':').next().unwrap()).collect();
+        assert_eq!(lines, ["line 3", "line 4", "line 5", "line 6", "line 7", "line 8", "line 9"]);
+    }
+
+    #[test]
+    fn test_apply_creates_paused_freezes_with_correct_bytes() {
+        let manager = FreezeManager::new();
+        let (results, report) = apply_saved_list(parse_saved_list(FIXTURE), resolve, &manager, PID);
+
+        // Missing 所在的模块没有加载
+        assert_eq!(report.imported, 7);
+        assert_eq!(report.skipped, 3);
+        assert_eq!(results.len(), 7);
+        assert!(report.warnings.iter().any(|w| w.contains("libmissing.so")));
+        assert!(report.warnings.iter().any(|w| w.contains("Xor")));
+
+        // Health 和 Speed 冻结，Key（Xor）只作为结果导入
+        assert_eq!(report.frozen_created, 2);
+        assert_eq!(manager.get_frozen_count(), 0);
+
+        let health = manager.get_entry(0x7B00001004).unwrap();
+        assert!(manager.is_paused(0x7B00001004));
+        assert_eq!(health.value, 100.5f32.to_le_bytes().to_vec());
+        assert_eq!(health.value_type, ValueType::Float.to_id());
+        assert_eq!(health.pid, PID);
+
+        let speed_addr = LIB_BASE + 0x1A2B0;
+        assert!(manager.is_paused(speed_addr));
+        assert_eq!(manager.get_entry(speed_addr).unwrap().value, 2.25f64.to_le_bytes().to_vec());
+        assert!(manager.get_entry(0x7B00003000).is_none());
+
+        assert!(
+            results
+                .iter()
+                .any(|item| matches!(item, SearchResultItem::Exact(e) if e.address == speed_addr && e.typ == ValueType::Double))
+        );
+        assert!(report.labels.contains(&ImportedLabel {
+            address: 0x7B00001000,
+            name: "Coins".to_string(),
+        }));
+        // 空名称不产生标签
+        assert_eq!(report.labels.len(), 6);
+    }
+}
@@ -0,0 +1,7 @@
+//! Import / export
+//!
+//! 与其他工具交换地址列表，方便用户迁移已有的存档。
+
+pub mod gg_savedlist;
+
+pub use gg_savedlist::{ImportReport, import_gg_saved_list};
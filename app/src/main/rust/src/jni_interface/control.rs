@@ -0,0 +1,53 @@
+//! JNI methods for the local control server
+
+use crate::control::{CONTROL_SERVER, ControlServer, EngineBackend};
+use crate::ext::jni::{JniResult, JniResultExt};
+use anyhow::anyhow;
+use jni::JNIEnv;
+use jni::objects::{JObject, JString};
+use jni::sys::{JNI_FALSE, JNI_TRUE, jboolean};
+use jni_macro::jni_method;
+use log::error;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// 启动控制服务，socket_path 应位于应用私有目录下
+/// 已经在运行时返回 false
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/ControlServer", "nativeStartControlServer", "(Ljava/lang/String;)Z")]
+pub fn jni_start_control_server(mut env: JNIEnv, _class: JObject, socket_path: JString) -> jboolean {
+    (|| -> JniResult<jboolean> {
+        let socket_path: String = env.get_string(&socket_path)?.into();
+
+        let mut server = CONTROL_SERVER.lock().map_err(|_| anyhow!("Failed to acquire control server lock"))?;
+        if server.is_some() {
+            return Ok(JNI_FALSE);
+        }
+
+        *server = Some(ControlServer::start(PathBuf::from(socket_path), Arc::new(EngineBackend))?);
+        Ok(JNI_TRUE)
+    })()
+    .or_throw(&mut env)
+}
+
+/// 停止控制服务，等待所有连接退出
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/ControlServer", "nativeStopControlServer", "()V")]
+pub fn jni_stop_control_server(_env: JNIEnv, _class: JObject) {
+    let server = match CONTROL_SERVER.lock() {
+        Ok(mut server) => server.take(),
+        Err(e) => {
+            error!("Failed to acquire control server lock: {:?}", e);
+            return;
+        },
+    };
+    if let Some(server) = server {
+        server.stop();
+    }
+}
+
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/ControlServer", "nativeIsControlServerRunning", "()Z")]
+pub fn jni_is_control_server_running(_env: JNIEnv, _class: JObject) -> jboolean {
+    match CONTROL_SERVER.lock() {
+        Ok(server) if server.is_some() => JNI_TRUE,
+        _ => JNI_FALSE,
+    }
+}
@@ -0,0 +1,23 @@
+//! JNI methods for diagnostics
+
+use crate::diagnostics::run_self_test;
+use crate::ext::jni::{JniResult, JniResultExt};
+use jni::JNIEnv;
+use jni::objects::{JObject, JString};
+use jni::sys::jstring;
+use jni_macro::jni_method;
+use std::path::PathBuf;
+
+/// 运行本进程自检，返回 JSON 报告
+///
+/// - cache_dir: 模糊搜索临时结果管理器使用的缓存目录，自检结束后只在其中追加扫描报告日志
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeRunSelfTest", "(Ljava/lang/String;)Ljava/lang/String;")]
+pub fn jni_run_self_test(mut env: JNIEnv, _obj: JObject, cache_dir: JString) -> jstring {
+    (|| -> JniResult<jstring> {
+        let cache_dir: String = env.get_string(&cache_dir)?.into();
+        let report = run_self_test(&PathBuf::from(cache_dir))?;
+        let json = serde_json::to_string(&report)?;
+        Ok(env.new_string(&json)?.into_raw())
+    })()
+    .or_throw(&mut env)
+}
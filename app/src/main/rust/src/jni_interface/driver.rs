@@ -1,20 +1,27 @@
 //! JNI methods for WuwaDriver
 
-use crate::core::{MemoryAccessMode, DRIVER_MANAGER};
+use crate::core::address_rebase::AddressRebase;
+use crate::core::bind_health::ensure_watchdog;
+use crate::core::globals::{FREEZE_MANAGER, MEMORY_DUMP, PAGE_SIZE, PROCESS_CACHE, REGION_SNAPSHOTS};
+use crate::core::memory_dump::{DumpReport, dump_to_file};
+use crate::core::process_list::{ProcessListOptions, ProcessSortMode};
+use crate::core::region_classifier;
+use crate::core::region_map::{MappedRegion, RegionMap, is_page_not_writable};
+use crate::core::struct_decode::{DecodedField, decode_struct, parse_layout};
+use crate::core::{AccessQos, MemoryAccessMode, DRIVER_MANAGER, MEMORY_QOS};
 use crate::ext::jni::{JniResult, JniResultExt};
-use crate::wuwa::{WuWaDriver, WuwaMemRegionEntry};
+use crate::search::{SEARCH_ENGINE_MANAGER, ValueType, XorKey, parse_typed_value};
+use crate::wuwa::{MemRegionFilter, WuWaDriver, WuwaMemRegionEntry};
 use anyhow::anyhow;
 use jni::JNIEnv;
-use jni::objects::{JByteArray, JClass, JIntArray, JLongArray, JObject, JObjectArray, JString};
-use jni::sys::{JNI_FALSE, JNI_TRUE, jboolean, jint, jlong, jsize, jlongArray, jintArray, jobjectArray};
+use jni::objects::{JBooleanArray, JByteArray, JClass, JIntArray, JLongArray, JObject, JObjectArray, JString, JValue};
+use jni::sys::{JNI_FALSE, JNI_TRUE, jboolean, jint, jlong, jsize, jlongArray, jintArray, jobjectArray, jstring};
 use jni_macro::jni_method;
 use log::{debug, error, info, log_enabled, Level};
-use nix::libc::close;
-use nix::sys::mman::{MapFlags, ProtFlags, mmap, munmap};
 use obfstr::obfstr as s;
 use obfstr::obfstring as ss;
-use std::num::NonZeroUsize;
-use std::os::fd::BorrowedFd;
+use std::cell::RefCell;
+use std::path::Path;
 
 mod conversions {
     use super::*;
@@ -57,10 +64,9 @@ mod conversions {
         entry: &WuwaMemRegionEntry,
         mem_region_class: &JClass<'l>,
     ) -> JniResult<JObject<'l>> {
-        let name = extract_cstring(&entry.name);
-        let jname = env.new_string(&name)?;
+        let jname = env.new_string(entry.name())?;
 
-        Ok(env.new_object(
+        let entry_obj = env.new_object(
             mem_region_class,
             "(JJILjava/lang/String;)V",
             &[
@@ -69,7 +75,9 @@ mod conversions {
                 (entry.type_ as jint).into(),
                 (&jname).into(),
             ],
-        )?)
+        )?;
+        env.delete_local_ref(jname)?;
+        Ok(entry_obj)
     }
 }
 
@@ -201,24 +209,28 @@ pub fn jni_get_proc_info<'l>(mut env: JNIEnv<'l>, _obj: JObject, pid: jint) -> J
     .or_throw(&mut env)
 }
 
-#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeGetProcessListWithInfo", "()[Lmoe/fuqiuluo/mamu/driver/CProcInfo;")]
-pub fn jni_get_proc_list_with_info<'l>(mut env: JNIEnv<'l>, _obj: JObject) -> JObjectArray<'l> {
+/// 按 sort_mode 排序的进程列表，相同时按 pid 升序；短时间内重复调用返回缓存
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeGetProcessListWithInfo", "(IZ)[Lmoe/fuqiuluo/mamu/driver/CProcInfo;")]
+pub fn jni_get_proc_list_with_info<'l>(mut env: JNIEnv<'l>, _obj: JObject, sort_mode: jint, hide_kernel_threads: jboolean) -> JObjectArray<'l> {
     (|| -> JniResult<JObjectArray<'l>> {
+        let sort = ProcessSortMode::from_id(sort_mode)
+            .ok_or_else(|| anyhow!("Invalid process sort mode: {}", sort_mode))?;
+        let options = ProcessListOptions {
+            sort,
+            hide_kernel_threads: hide_kernel_threads != JNI_FALSE,
+        };
+
         let manager = DRIVER_MANAGER.read()
             .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
         let driver = manager.get_driver()
             .ok_or_else(|| anyhow!("Driver is not initialized"))?;
 
-        let proc_list = driver.list_processes();
+        let proc_list = PROCESS_CACHE.list(options, || driver.list_processes_with_info());
         let process_info_class = env.find_class("moe/fuqiuluo/mamu/driver/CProcInfo")?;
         let result_array = env.new_object_array(proc_list.len() as jsize, &process_info_class, JObject::null())?;
 
-        for (i, &pid) in proc_list.iter().enumerate() {
-            let proc_info = driver
-                .get_process_info(pid)
-                .map_err(|_| anyhow!("Unable to get process info for pid {}", pid))?;
-
-            let proc_info_obj = conversions::proc_info_to_jobject(&mut env, &proc_info)?;
+        for (i, proc_info) in proc_list.iter().enumerate() {
+            let proc_info_obj = conversions::proc_info_to_jobject(&mut env, proc_info)?;
             env.set_object_array_element(&result_array, i as jsize, proc_info_obj)?;
         }
 
@@ -230,26 +242,78 @@ pub fn jni_get_proc_list_with_info<'l>(mut env: JNIEnv<'l>, _obj: JObject) -> JO
 #[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeBindProcess", "(I)Z")]
 pub fn jni_bind_proc(mut env: JNIEnv, _obj: JObject, pid: jint) -> jboolean {
     (|| -> JniResult<jboolean> {
-        let manager_read = DRIVER_MANAGER.read()
+        Ok(if bind_pid(pid)? { JNI_TRUE } else { JNI_FALSE })
+    })()
+    .or_throw(&mut env)
+}
+
+/// 按进程名查找重启后的进程并绑定，返回新的 pid，找不到或绑定失败时返回 0
+///
+/// 保存的地址列表在 Kotlin 侧，不受影响，绑定后可以按模块重新定位。
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeRebindByName", "(Ljava/lang/String;)I")]
+pub fn jni_rebind_by_name(mut env: JNIEnv, _obj: JObject, process_name: JString) -> jint {
+    (|| -> JniResult<jint> {
+        let process_name: String = env.get_string(&process_name)?.into();
+        let manager = DRIVER_MANAGER.read()
             .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
-        let driver = manager_read.get_driver()
+        let driver = manager.get_driver()
             .ok_or_else(|| anyhow!("Driver is not initialized"))?;
-
-        let Ok(bind_proc) = driver.bind_process(pid) else {
-            return Ok(JNI_FALSE);
+        let Ok(pid) = driver.find_process(&process_name) else {
+            return Ok(0);
         };
-        drop(manager_read);
-
-        let mut manager_write = DRIVER_MANAGER.write()
-            .map_err(|_| anyhow!("Failed to acquire DriverManager write lock"))?;
-        manager_write.bind_process(bind_proc, pid)?;
+        drop(manager);
 
-        debug!("{}: {}", s!("绑定进程成功，PID"), pid);
-        Ok(JNI_TRUE)
+        if !bind_pid(pid)? {
+            return Ok(0);
+        }
+        info!("{}: {} -> {}", s!("按进程名重新绑定"), process_name, pid);
+        Ok(pid)
     })()
     .or_throw(&mut env)
 }
 
+/// 绑定的进程是否仍然存活；未绑定或看门狗已发现进程退出并解绑时返回 false
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeIsBoundProcessAlive", "()Z")]
+pub fn jni_is_bound_proc_alive(_env: JNIEnv, _obj: JObject) -> jboolean {
+    let Ok(manager) = DRIVER_MANAGER.read() else {
+        return JNI_FALSE;
+    };
+    let alive = manager.is_process_bound()
+        && manager.get_driver().is_some_and(|driver| driver.is_process_alive(manager.get_bound_pid()).unwrap_or(true));
+    if alive { JNI_TRUE } else { JNI_FALSE }
+}
+
+/// 绑定进程并启动看门狗，驱动拒绝绑定时返回 false
+fn bind_pid(pid: jint) -> anyhow::Result<bool> {
+    let manager_read = DRIVER_MANAGER.read()
+        .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+    let driver = manager_read.get_driver()
+        .ok_or_else(|| anyhow!("Driver is not initialized"))?;
+
+    let Ok(bind_proc) = driver.bind_process(pid) else {
+        return Ok(false);
+    };
+    drop(manager_read);
+
+    let mut manager_write = DRIVER_MANAGER.write()
+        .map_err(|_| anyhow!("Failed to acquire DriverManager write lock"))?;
+    manager_write.bind_process(bind_proc, pid)?;
+    drop(manager_write);
+    // 目标进程 exec 后自动重新绑定，退出后自动解绑
+    ensure_watchdog();
+
+    // 其他进程的冻结条目立即转为孤立，不必等下一次冻结循环
+    if let Ok(freeze_manager) = FREEZE_MANAGER.read() {
+        let orphans = freeze_manager.sync_with_process(pid);
+        if orphans > 0 {
+            info!("{}: {}", s!("冻结条目已孤立"), orphans);
+        }
+    }
+
+    debug!("{}: {}", s!("绑定进程成功，PID"), pid);
+    Ok(true)
+}
+
 #[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeGetCurrentBindPid", "()I")]
 pub fn jni_get_current_bind_pid(_env: JNIEnv, _obj: JObject) -> jint {
     if let Ok(manager) = DRIVER_MANAGER.read() {
@@ -272,25 +336,91 @@ pub fn jni_is_proc_bound(_env: JNIEnv, _obj: JObject) -> jboolean {
     }
 }
 
+/// 绑定句柄状态，见 BindStatus
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeGetBindStatus", "()I")]
+pub fn jni_get_bind_status(_env: JNIEnv, _obj: JObject) -> jint {
+    match DRIVER_MANAGER.read() {
+        Ok(manager) => manager.bind_health().status() as jint,
+        Err(_) => 0,
+    }
+}
+
 #[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeUnbindProcess", "()Z")]
 pub fn jni_unbind_proc(mut env: JNIEnv, _obj: JObject) -> jboolean {
     (|| -> JniResult<jboolean> {
         let mut manager = DRIVER_MANAGER.write()
             .map_err(|_| anyhow!("Failed to acquire DriverManager write lock"))?;
+        let pid = manager.get_bound_pid();
         manager.unbind_process();
+        drop(manager);
+
+        // 解绑的进程不会再写入，它的冻结条目直接清除
+        if pid != 0 && let Ok(freeze_manager) = FREEZE_MANAGER.read() {
+            freeze_manager.clear_for_pid(pid);
+        }
+
         debug!("{}", s!("释放进程绑定成功"));
         Ok(JNI_TRUE)
     })()
     .or_throw(&mut env)
 }
 
-#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeQueryMemRegions", "(I)[Lmoe/fuqiuluo/mamu/driver/MemRegionEntry;")]
+/// 绑定一个次要进程供 nativeReadMemoryOf 和指定目标进程的搜索读取，不改变当前绑定的进程
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeBindSecondaryProcess", "(I)Z")]
+pub fn jni_bind_secondary_process(mut env: JNIEnv, _obj: JObject, pid: jint) -> jboolean {
+    (|| -> JniResult<jboolean> {
+        let manager = DRIVER_MANAGER.read()
+            .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+        manager.bind_secondary_process(pid)?;
+        debug!("Bound secondary process {}", pid);
+        Ok(JNI_TRUE)
+    })()
+    .or_throw(&mut env)
+}
+
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeReleaseSecondaryProcess", "(I)Z")]
+pub fn jni_release_secondary_process(_env: JNIEnv, _obj: JObject, pid: jint) -> jboolean {
+    match DRIVER_MANAGER.read() {
+        Ok(manager) if manager.release_secondary(pid) => JNI_TRUE,
+        _ => JNI_FALSE,
+    }
+}
+
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeGetSecondaryPids", "()[I")]
+pub fn jni_get_secondary_pids<'l>(mut env: JNIEnv<'l>, _obj: JObject) -> JIntArray<'l> {
+    (|| -> JniResult<JIntArray<'l>> {
+        let pids = DRIVER_MANAGER.read()
+            .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?
+            .secondary_pids();
+        let result = env.new_int_array(pids.len() as jsize)?;
+        env.set_int_array_region(&result, 0, &pids)?;
+        Ok(result)
+    })()
+    .or_throw(&mut env)
+}
+
+/// Queries the memory regions of `pid`, keeping only regions that have every permission bit in
+/// `perm_mask`, whose name contains `name_filter` and that intersect `[start, end)` (`end` 0 means
+/// no upper bound). A zero/null/empty filter returns every region. Names are demangled.
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeQueryMemRegions", "(IILjava/lang/String;JJ)[Lmoe/fuqiuluo/mamu/driver/MemRegionEntry;")]
 pub fn jni_query_mem_regions<'l>(
     mut env: JNIEnv<'l>,
     _obj: JObject,
     pid: jint,
+    perm_mask: jint,
+    name_filter: JString<'l>,
+    start: jlong,
+    end: jlong,
 ) -> JObjectArray<'l> {
     (|| -> JniResult<JObjectArray<'l>> {
+        let name: String = if name_filter.is_null() { String::new() } else { env.get_string(&name_filter)?.into() };
+        let filter = MemRegionFilter {
+            perm_mask: perm_mask as u32,
+            name,
+            start: start as u64,
+            end: end as u64,
+        };
+
         let manager = DRIVER_MANAGER.read()
             .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
 
@@ -310,56 +440,30 @@ pub fn jni_query_mem_regions<'l>(
             result.fd, result.buffer_size, result.entry_count
         );
 
-        let borrowed_fd = unsafe { BorrowedFd::borrow_raw(result.fd) };
-
-        let mapped = unsafe {
-            mmap(
-                None,
-                NonZeroUsize::new(result.buffer_size).ok_or_else(|| anyhow!("Invalid buffer size"))?,
-                ProtFlags::PROT_READ,
-                MapFlags::MAP_PRIVATE,
-                borrowed_fd,
-                0,
-            )
-        };
+        // 映射在返回时释放，下面任何一步出错都不会泄漏
+        let mapped = result.map()?;
+        let entries = mapped.entries();
 
-        let mapped_ptr = match mapped {
-            Ok(ptr) => ptr,
-            Err(e) => {
-                unsafe { close(result.fd) };
-                return Err(anyhow!("Failed to mmap memory regions buffer: {}", e));
-            },
+        // 先过滤再构造 Java 对象
+        let filtered_entries: Vec<&WuwaMemRegionEntry> = if filter.is_empty() {
+            entries.iter().collect()
+        } else {
+            entries.iter().filter(|entry| filter.matches(entry)).collect()
         };
 
-        let entries = mapped_ptr.as_ptr() as *const WuwaMemRegionEntry;
-
-        // 收集过滤后的内存区域
-        let mut filtered_entries = Vec::new();
-        for i in 0..result.entry_count {
-            let entry = unsafe { &*entries.add(i) };
-            filtered_entries.push(entry);
-        }
-
         let mem_region_class = env.find_class("moe/fuqiuluo/mamu/driver/MemRegionEntry")?;
-        let result_array = env.new_object_array(filtered_entries.len() as jsize, &mem_region_class, JObject::null());
-
-        let result_array = match result_array {
-            Ok(arr) => arr,
-            Err(e) => {
-                unsafe {
-                    let _ = munmap(mapped_ptr, result.buffer_size);
-                    close(result.fd);
-                };
-                return Err(anyhow!("Failed to create MemRegionEntry array: {}", e));
-            },
-        };
+        let result_array = env
+            .new_object_array(filtered_entries.len() as jsize, &mem_region_class, JObject::null())
+            .map_err(|e| anyhow!("Failed to create MemRegionEntry array: {}", e))?;
 
         for (i, entry) in filtered_entries.iter().enumerate() {
+            // 局部引用随数组元素数增长，逐个释放避免 10 万以上的区域撑满局部引用表
             match conversions::mem_region_to_jobject(&mut env, entry, &mem_region_class) {
                 Ok(entry_obj) => {
-                    if let Err(e) = env.set_object_array_element(&result_array, i as jsize, entry_obj) {
+                    if let Err(e) = env.set_object_array_element(&result_array, i as jsize, &entry_obj) {
                         error!("Failed to set array element at index {}: {}", i, e);
                     }
+                    let _ = env.delete_local_ref(entry_obj);
                 },
                 Err(e) => {
                     error!("Failed to create MemRegionEntry object at index {}: {}", i, e);
@@ -367,14 +471,113 @@ pub fn jni_query_mem_regions<'l>(
             }
         }
 
-        unsafe {
-            let _ = munmap(mapped_ptr, result.buffer_size);
-            close(result.fd);
+        debug!("Successfully returned {} memory regions (filtered from {})", filtered_entries.len(), entries.len());
+
+        Ok(result_array)
+    })()
+    .or_throw(&mut env)
+}
+
+/// 按预设掩码过滤内存区域，返回 `[start0, end0, start1, end1, ...]`，可直接传给 nativeStartSearchAsync
+///
+/// 掩码的第 n 位对应 Kotlin MemoryRange 中 ordinal 为 n 的范围，分类规则见 `core::region_classifier`。
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeGetFilteredRegions", "(II)[J")]
+pub fn jni_get_filtered_regions(mut env: JNIEnv, _obj: JObject, pid: jint, preset_mask: jint) -> jlongArray {
+    (|| -> JniResult<jlongArray> {
+        let manager = DRIVER_MANAGER.read()
+            .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+
+        if !manager.is_process_bound() {
+            return Err(anyhow!("No process is bound. Please bind a process before querying memory regions."));
         }
 
-        debug!("Successfully returned {} memory regions (filtered from {})", filtered_entries.len(), result.entry_count);
+        let driver = manager.get_driver()
+            .ok_or_else(|| anyhow!("Driver is not initialized"))?;
 
-        Ok(result_array)
+        let regions = region_classifier::query_filtered_regions(driver, pid, preset_mask as u32)
+            .map_err(|e| anyhow!("Unable to get memory regions for pid {}: {}", pid, e))?;
+        drop(manager);
+
+        let flat: Vec<jlong> = regions.iter().flat_map(|&(start, end)| [start as jlong, end as jlong]).collect();
+        let array = env.new_long_array(flat.len() as jsize)?;
+        env.set_long_array_region(&array, 0, &flat)?;
+
+        debug!("Filtered {} memory regions with preset mask 0x{:X}", regions.len(), preset_mask);
+        Ok(array.into_raw())
+    })()
+    .or_throw(&mut env)
+}
+
+fn query_regions(pid: i32) -> JniResult<Vec<MappedRegion>> {
+    let manager = DRIVER_MANAGER.read()
+        .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+    let map = RegionMap::query(&manager, pid)
+        .map_err(|e| anyhow!("Unable to get memory regions for pid {}: {}", pid, e))?;
+    Ok(map.regions().to_vec())
+}
+
+/// 保存进程当前的内存区域列表，返回快照 id，用 nativeDiffRegions 比较、nativeReleaseSnapshot 释放
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeSnapshotRegions", "(I)I")]
+pub fn jni_snapshot_regions(mut env: JNIEnv, _obj: JObject, pid: jint) -> jint {
+    (|| -> JniResult<jint> {
+        let regions = query_regions(pid)?;
+        let count = regions.len();
+        let id = REGION_SNAPSHOTS.lock()
+            .map_err(|_| anyhow!("Failed to acquire RegionSnapshots lock"))?
+            .insert(pid, regions);
+
+        debug!("Region snapshot {} for pid {}: {} regions", id, pid, count);
+        Ok(id)
+    })()
+    .or_throw(&mut env)
+}
+
+/// 重新查询快照所属进程的内存区域，返回新增、释放和大小变化的区域
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeDiffRegions", "(I)[Lmoe/fuqiuluo/mamu/driver/RegionDiffEntry;")]
+pub fn jni_diff_regions<'l>(mut env: JNIEnv<'l>, _obj: JObject, snapshot_id: jint) -> JObjectArray<'l> {
+    (|| -> JniResult<JObjectArray<'l>> {
+        let pid = REGION_SNAPSHOTS.lock()
+            .map_err(|_| anyhow!("Failed to acquire RegionSnapshots lock"))?
+            .pid(snapshot_id)
+            .ok_or_else(|| anyhow!("Region snapshot {} does not exist", snapshot_id))?;
+        let current = query_regions(pid)?;
+        let diff = REGION_SNAPSHOTS.lock()
+            .map_err(|_| anyhow!("Failed to acquire RegionSnapshots lock"))?
+            .diff(snapshot_id, &current)?;
+
+        let class = env.find_class("moe/fuqiuluo/mamu/driver/RegionDiffEntry")?;
+        let array = env.new_object_array(diff.len() as jsize, &class, JObject::null())?;
+        for (i, entry) in diff.iter().enumerate() {
+            let name = env.new_string(&entry.name)?;
+            let obj = env.new_object(
+                &class,
+                "(JJJILjava/lang/String;I)V",
+                &[
+                    (entry.start as jlong).into(),
+                    (entry.end as jlong).into(),
+                    (entry.old_end as jlong).into(),
+                    (entry.type_ as jint).into(),
+                    (&name).into(),
+                    (entry.change as jint).into(),
+                ],
+            )?;
+            env.set_object_array_element(&array, i as jsize, obj)?;
+        }
+
+        debug!("Region snapshot {} diff: {} changed regions", snapshot_id, diff.len());
+        Ok(array)
+    })()
+    .or_throw(&mut env)
+}
+
+/// 释放快照，快照不存在时返回 false
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeReleaseSnapshot", "(I)Z")]
+pub fn jni_release_snapshot(mut env: JNIEnv, _obj: JObject, snapshot_id: jint) -> jboolean {
+    (|| -> JniResult<jboolean> {
+        let released = REGION_SNAPSHOTS.lock()
+            .map_err(|_| anyhow!("Failed to acquire RegionSnapshots lock"))?
+            .release(snapshot_id);
+        Ok(released as jboolean)
     })()
     .or_throw(&mut env)
 }
@@ -401,7 +604,7 @@ pub fn jni_read_memory<'l>(
         }
 
         let mut buffer = vec![0u8; size as usize];
-        manager.read_memory_unified(addr as u64, &mut buffer, None)
+        manager.read_memory_with_qos(addr as u64, &mut buffer, None, AccessQos::Interactive)
             .map_err(|e| anyhow!("Failed to read memory at 0x{:x}: {}", addr, e))?;
 
         let result = env.byte_array_from_slice(&buffer)
@@ -412,6 +615,228 @@ pub fn jni_read_memory<'l>(
     .or_throw(&mut env)
 }
 
+/// 读取指定进程的内存：绑定进程照常读取，次要进程走它的句柄，没有句柄时用 pid 通过驱动读取
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeReadMemoryOf", "(IJI)[B")]
+pub fn jni_read_memory_of<'l>(mut env: JNIEnv<'l>, _obj: JObject, pid: jint, addr: jlong, size: jint) -> JObject<'l> {
+    (|| -> JniResult<JObject<'l>> {
+        if size <= 0 {
+            return Err(anyhow!("Invalid size: {}", size));
+        }
+        if pid <= 0 {
+            return Err(anyhow!("Invalid pid: {}", pid));
+        }
+
+        let manager = DRIVER_MANAGER.read()
+            .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+
+        let mut buffer = vec![0u8; size as usize];
+        manager.read_target_with_qos(Some(pid), addr as u64, &mut buffer, None, AccessQos::Interactive)
+            .map_err(|e| anyhow!("Failed to read memory of pid {} at 0x{:x}: {}", pid, addr, e))?;
+        drop(manager);
+
+        Ok(env.byte_array_from_slice(&buffer)?.into())
+    })()
+    .or_throw(&mut env)
+}
+
+/// Reads a window of memory for the hex viewer. Unreadable pages don't fail the read;
+/// they are reported in `MemoryWindow.pageValid` (index 0 is the page containing `addr`) and read as 0.
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeReadMemoryWindow", "(JI)Lmoe/fuqiuluo/mamu/driver/MemoryWindow;")]
+pub fn jni_read_memory_window<'l>(
+    mut env: JNIEnv<'l>,
+    _obj: JObject,
+    addr: jlong,
+    size: jint,
+) -> JObject<'l> {
+    (|| -> JniResult<JObject<'l>> {
+        if size <= 0 {
+            return Err(anyhow!("Invalid size: {}", size));
+        }
+
+        let manager = DRIVER_MANAGER.read()
+            .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+
+        if !manager.is_process_bound() {
+            return Err(anyhow!("No process is bound. Please bind a process first."));
+        }
+
+        let window = manager.read_memory_window(addr as u64, size as usize)
+            .map_err(|e| anyhow!("Failed to read memory window at 0x{:x}: {}", addr, e))?;
+        drop(manager);
+
+        let data = env.byte_array_from_slice(&window.data)?;
+        let page_valid = env.new_boolean_array(window.page_valid.len() as jsize)?;
+        let flags: Vec<jboolean> = window.page_valid.iter().map(|&valid| valid as jboolean).collect();
+        env.set_boolean_array_region(&page_valid, 0, &flags)?;
+
+        let window_class = env.find_class("moe/fuqiuluo/mamu/driver/MemoryWindow")?;
+        Ok(env.new_object(
+            window_class,
+            "(J[B[ZI)V",
+            &[
+                addr.into(),
+                (&data).into(),
+                (&page_valid).into(),
+                (*PAGE_SIZE as jint).into(),
+            ],
+        )?)
+    })()
+    .or_throw(&mut env)
+}
+
+thread_local! {
+    /// nativeReadMemoryInto 的目标 ByteBuffer 不按页对齐时驱动先读入这里，每个线程复用
+    static READ_STAGING: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+    /// 本线程最近一次 nativeReadMemoryInto 覆盖的各页是否有效
+    static LAST_READ_VALIDITY: RefCell<Vec<bool>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Reads `size` bytes at `addr` into a caller-provided direct ByteBuffer, without allocating per call.
+/// Like nativeReadMemoryWindow, unreadable pages don't fail the read and are zero-filled; their validity
+/// is available from nativeGetReadValidity on the same thread. Returns the number of bytes in readable pages.
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeReadMemoryInto", "(JLjava/nio/ByteBuffer;I)I")]
+pub fn jni_read_memory_into(mut env: JNIEnv, _obj: JObject, addr: jlong, buffer: JObject, size: jint) -> jint {
+    (|| -> JniResult<jint> {
+        if size <= 0 {
+            return Err(anyhow!("Invalid size: {}", size));
+        }
+        let buffer = (&buffer).into();
+        let ptr = env.get_direct_buffer_address(buffer)?;
+        let capacity = env.get_direct_buffer_capacity(buffer)?;
+        if size as usize > capacity {
+            return Err(anyhow!("Read size {} exceeds buffer capacity {}", size, capacity));
+        }
+
+        let manager = DRIVER_MANAGER.read()
+            .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+        if !manager.is_process_bound() {
+            return Err(anyhow!("No process is bound. Please bind a process first."));
+        }
+
+        // 调用方保证读取期间不会再访问这个 ByteBuffer
+        let out = unsafe { std::slice::from_raw_parts_mut(ptr, size as usize) };
+        let valid = READ_STAGING.with_borrow_mut(|staging| {
+            LAST_READ_VALIDITY.with_borrow_mut(|page_valid| manager.read_memory_window_into(addr as u64, out, staging, page_valid))
+        })
+        .map_err(|e| anyhow!("Failed to read memory at 0x{:x}: {}", addr, e))?;
+        Ok(valid as jint)
+    })()
+    .or_throw(&mut env)
+}
+
+/// Writes the page validity of the last nativeReadMemoryInto on this thread into `out`, one byte per page
+/// (1 = readable, index 0 is the page containing `addr`). Returns the number of pages.
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeGetReadValidity", "(Ljava/nio/ByteBuffer;)I")]
+pub fn jni_get_read_validity(mut env: JNIEnv, _obj: JObject, out: JObject) -> jint {
+    (|| -> JniResult<jint> {
+        let out = (&out).into();
+        let ptr = env.get_direct_buffer_address(out)?;
+        let capacity = env.get_direct_buffer_capacity(out)?;
+
+        LAST_READ_VALIDITY.with_borrow(|page_valid| {
+            if page_valid.len() > capacity {
+                return Err(anyhow!("Validity needs {} bytes, buffer capacity is {}", page_valid.len(), capacity));
+            }
+            let flags = unsafe { std::slice::from_raw_parts_mut(ptr, page_valid.len()) };
+            for (flag, &valid) in flags.iter_mut().zip(page_valid.iter()) {
+                *flag = valid as u8;
+            }
+            Ok(page_valid.len() as jint)
+        })
+    })()
+    .or_throw(&mut env)
+}
+
+/// Reads an Unreal Engine FString (TCHAR* + ArrayNum) at `addr` of the bound process.
+/// Invalid UTF-16 is replaced with U+FFFD; fails if the string is longer than `max_len` characters.
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeReadFString", "(JI)Ljava/lang/String;")]
+pub fn jni_read_fstring(mut env: JNIEnv, _obj: JObject, addr: jlong, max_len: jint) -> jstring {
+    (|| -> JniResult<jstring> {
+        if max_len <= 0 {
+            return Err(anyhow!("Invalid max length: {}", max_len));
+        }
+
+        let manager = DRIVER_MANAGER.read()
+            .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+        if !manager.is_process_bound() {
+            return Err(anyhow!("No process is bound. Please bind a process first."));
+        }
+
+        let value = manager.read_fstring(addr as u64, max_len as usize)?;
+        Ok(env.new_string(&value)?.into_raw())
+    })()
+    .or_throw(&mut env)
+}
+
+/// Reads a NUL-terminated ANSI/UTF-8 string of at most `max_len` bytes at `addr` of the bound process.
+/// A string running into an unreadable page returns the readable prefix.
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeReadCString", "(JI)Ljava/lang/String;")]
+pub fn jni_read_cstring(mut env: JNIEnv, _obj: JObject, addr: jlong, max_len: jint) -> jstring {
+    (|| -> JniResult<jstring> {
+        if max_len <= 0 {
+            return Err(anyhow!("Invalid max length: {}", max_len));
+        }
+
+        let manager = DRIVER_MANAGER.read()
+            .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+        if !manager.is_process_bound() {
+            return Err(anyhow!("No process is bound. Please bind a process first."));
+        }
+
+        let value = manager.read_cstring(addr as u64, max_len as usize)?;
+        Ok(env.new_string(&value)?.into_raw())
+    })()
+    .or_throw(&mut env)
+}
+
+/// Decodes the struct at `addr` using a JSON layout, see `core::struct_decode` for the format.
+/// Each level is read with a single memory read; pointer fields are followed up to 3 levels deep.
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeDecodeStruct", "(JLjava/lang/String;)[Lmoe/fuqiuluo/mamu/driver/StructField;")]
+pub fn jni_decode_struct<'l>(mut env: JNIEnv<'l>, _obj: JObject, addr: jlong, layout_json: JString) -> JObjectArray<'l> {
+    (|| -> JniResult<JObjectArray<'l>> {
+        let layout_json: String = env.get_string(&layout_json)?.into();
+        let layout = parse_layout(&layout_json)?;
+
+        let manager = DRIVER_MANAGER.read()
+            .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+        if !manager.is_process_bound() {
+            return Err(anyhow!("No process is bound. Please bind a process first."));
+        }
+
+        let fields = decode_struct(addr as u64, &layout, &|va, buf: &mut [u8]| {
+            manager.read_memory_with_qos(va, buf, None, AccessQos::Interactive)
+        })?;
+        drop(manager);
+
+        let class = env.find_class("moe/fuqiuluo/mamu/driver/StructField")?;
+        new_struct_field_array(&mut env, &class, &fields)
+    })()
+    .or_throw(&mut env)
+}
+
+fn new_struct_field_array<'l>(env: &mut JNIEnv<'l>, class: &JClass, fields: &[DecodedField]) -> JniResult<JObjectArray<'l>> {
+    let array = env.new_object_array(fields.len() as jsize, class, JObject::null())?;
+    for (i, field) in fields.iter().enumerate() {
+        let name = env.new_string(&field.name)?;
+        let value = env.new_string(&field.value)?;
+        let raw = env.byte_array_from_slice(&field.raw)?;
+        let children = new_struct_field_array(env, class, &field.children)?;
+        let obj = env.new_object(
+            class,
+            "(Ljava/lang/String;JLjava/lang/String;[B[Lmoe/fuqiuluo/mamu/driver/StructField;)V",
+            &[
+                (&name).into(),
+                (field.address as jlong).into(),
+                (&value).into(),
+                (&raw).into(),
+                (&children).into(),
+            ],
+        )?;
+        env.set_object_array_element(&array, i as jsize, obj)?;
+    }
+    Ok(array)
+}
+
 #[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeBatchReadMemory", "([J[I)[[B")]
 pub fn jni_batch_read_memory<'l>(
     mut env: JNIEnv<'l>,
@@ -467,7 +892,7 @@ pub fn jni_batch_read_memory<'l>(
             }
 
             let mut buffer = vec![0u8; size];
-            match manager.read_memory_unified(addr, &mut buffer, None) {
+            match manager.read_memory_with_qos(addr, &mut buffer, None, AccessQos::Interactive) {
                 Ok(_) => {
                     let byte_array = env.byte_array_from_slice(&buffer)
                         .map_err(|e| anyhow!("Failed to create byte array for index {}: {}", i, e))?;
@@ -486,6 +911,15 @@ pub fn jni_batch_read_memory<'l>(
         .or_throw(&mut env)
 }
 
+/// 目标页不可写时抛出 PageNotWritableException，界面据此提示改用 pte_mapping/mprotect 写入；其他错误照常抛出 RuntimeException
+fn write_failed(env: &mut JNIEnv, error: anyhow::Error) -> JniResult<jboolean> {
+    if !is_page_not_writable(&error) {
+        return Err(error);
+    }
+    env.throw_new(s!("moe/fuqiuluo/mamu/driver/PageNotWritableException"), format!("{:#}", error))?;
+    Ok(JNI_FALSE)
+}
+
 #[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeWriteMemory", "(J[B)Z")]
 pub fn jni_write_memory(
     mut env: JNIEnv,
@@ -514,8 +948,9 @@ pub fn jni_write_memory(
 
         let bytes: &[u8] = unsafe { std::slice::from_raw_parts(buffer.as_ptr() as *const u8, len) };
 
-        manager.write_memory_unified(addr as u64, bytes)
-            .map_err(|e| anyhow!("Failed to write memory at 0x{:x}: {}", addr, e))?;
+        if let Err(e) = manager.write_memory_unified(addr as u64, bytes) {
+            return write_failed(&mut env, e.context(format!("Failed to write memory at 0x{:x}", addr)));
+        }
 
         if log_enabled!(Level::Debug) {
             debug!("{}: 0x{:x}, size={}", s!("写入内存成功"), addr, len);
@@ -615,6 +1050,419 @@ pub fn jni_batch_write_memory<'l>(
         .or_throw(&mut env)
 }
 
+/// 当前搜索结果使用的 Xor 密钥，写入 Xor 值前用它编码
+fn current_xor_key() -> anyhow::Result<XorKey> {
+    let manager = SEARCH_ENGINE_MANAGER.read().map_err(|_| anyhow!("Failed to acquire SearchEngineManager read lock"))?;
+    Ok(manager.xor_key())
+}
+
+/// 按类型解析字符串并写入，解析规则与搜索输入一致，整数超出目标宽度时抛出异常
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeWriteTypedValue", "(JLjava/lang/String;I)Z")]
+pub fn jni_write_typed_value(mut env: JNIEnv, _obj: JObject, addr: jlong, value: JString, type_id: jint) -> jboolean {
+    (|| -> JniResult<jboolean> {
+        let value: String = env.get_string(&value)?.into();
+        let value_type = ValueType::from_id(type_id).ok_or_else(|| anyhow!("Invalid value type: {}", type_id))?;
+        let mut bytes = parse_typed_value(&value, value_type).map_err(|e| anyhow!("Failed to parse '{}': {}", value, e))?;
+        if value_type == ValueType::Xor {
+            current_xor_key()?.apply(&mut bytes, addr as u64);
+        }
+
+        let manager = DRIVER_MANAGER.read()
+            .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+
+        if !manager.is_process_bound() {
+            return Err(anyhow!("No process is bound. Please bind a process first."));
+        }
+
+        if let Err(e) = manager.write_memory_unified(addr as u64, &bytes) {
+            return write_failed(&mut env, e.context(format!("Failed to write memory at 0x{:x}", addr)));
+        }
+
+        if log_enabled!(Level::Debug) {
+            debug!("{}: 0x{:x}, {} as {}", s!("写入内存成功"), addr, value, value_type);
+        }
+        Ok(JNI_TRUE)
+    })()
+    .or_throw(&mut env)
+}
+
+/// 批量按类型写入，返回每一项是否成功；解析失败或写入失败的项为 false 并记录原因
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeWriteTypedValues", "([J[Ljava/lang/String;[I)[Z")]
+pub fn jni_write_typed_values<'l>(
+    mut env: JNIEnv<'l>,
+    _obj: JObject,
+    addrs: JLongArray,
+    values: JObjectArray<'l>,
+    type_ids: JIntArray,
+) -> JObject<'l> {
+    (|| -> JniResult<JObject<'l>> {
+        let len = env.get_array_length(&addrs)? as usize;
+        if env.get_array_length(&values)? as usize != len || env.get_array_length(&type_ids)? as usize != len {
+            return Err(anyhow!("Address, value and type arrays must have the same length"));
+        }
+
+        let mut addresses = vec![0i64; len];
+        env.get_long_array_region(&addrs, 0, &mut addresses)?;
+        let mut types = vec![0i32; len];
+        env.get_int_array_region(&type_ids, 0, &mut types)?;
+
+        let xor_key = current_xor_key()?;
+        let mut encoded = Vec::with_capacity(len);
+        for (i, &type_id) in types.iter().enumerate() {
+            let value_obj = env.get_object_array_element(&values, i as jsize)?;
+            if value_obj.is_null() {
+                encoded.push(Err("null value".to_string()));
+                continue;
+            }
+            let value: String = env.get_string(&JString::from(value_obj))?.into();
+            encoded.push(match ValueType::from_id(type_id) {
+                Some(ValueType::Xor) => parse_typed_value(&value, ValueType::Xor).map(|mut bytes| {
+                    xor_key.apply(&mut bytes, addresses[i] as u64);
+                    bytes
+                }),
+                Some(value_type) => parse_typed_value(&value, value_type),
+                None => Err(format!("Invalid value type: {}", type_id)),
+            });
+        }
+
+        let manager = DRIVER_MANAGER.read()
+            .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+
+        if !manager.is_process_bound() {
+            return Err(anyhow!("No process is bound. Please bind a process first."));
+        }
+
+        let results: Vec<u8> = addresses
+            .iter()
+            .zip(&encoded)
+            .enumerate()
+            .map(|(i, (&addr, bytes))| {
+                let written = bytes
+                    .as_ref()
+                    .map_err(|e| e.clone())
+                    .and_then(|bytes| manager.write_memory_unified(addr as u64, bytes).map_err(|e| e.to_string()));
+                if let Err(e) = &written {
+                    debug!("Failed to write typed value at 0x{:x} (index {}): {}", addr, i, e);
+                }
+                written.is_ok() as u8
+            })
+            .collect();
+
+        let result_array = env.new_boolean_array(len as jsize)?;
+        env.set_boolean_array_region(&result_array, 0, &results)?;
+        Ok(result_array.into())
+    })()
+    .or_throw(&mut env)
+}
+
+/// 原子组写入：全部写入在 max_window_us 内完成，否则（或任一写入失败时）回滚，返回 JSON 报告
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeWriteAtomicGroup", "([J[[BJ)Ljava/lang/String;")]
+pub fn jni_write_atomic_group<'l>(
+    mut env: JNIEnv<'l>,
+    _obj: JObject,
+    addrs: JLongArray,
+    data_array: JObjectArray<'l>,
+    max_window_us: jlong,
+) -> jstring {
+    (|| -> JniResult<jstring> {
+        let addr_len = env.get_array_length(&addrs)
+            .map_err(|e| anyhow!("Failed to get address array length: {}", e))? as usize;
+        let data_len = env.get_array_length(&data_array)
+            .map_err(|e| anyhow!("Failed to get data array length: {}", e))? as usize;
+
+        if addr_len != data_len {
+            return Err(anyhow!("Address and data arrays must have the same length: {} vs {}", addr_len, data_len));
+        }
+
+        let mut addresses = vec![0i64; addr_len];
+        env.get_long_array_region(&addrs, 0, &mut addresses)
+            .map_err(|e| anyhow!("Failed to get address array region: {}", e))?;
+
+        let mut writes = Vec::with_capacity(addr_len);
+        for (i, &addr) in addresses.iter().enumerate() {
+            let data_obj = env.get_object_array_element(&data_array, i as jsize)
+                .map_err(|e| anyhow!("Failed to get data array element at index {}: {}", i, e))?;
+            if data_obj.is_null() {
+                return Err(anyhow!("Data at index {} is null", i));
+            }
+            let bytes = env.convert_byte_array(JByteArray::from(data_obj))
+                .map_err(|e| anyhow!("Failed to get byte array at index {}: {}", i, e))?;
+            writes.push((addr as u64, bytes));
+        }
+
+        let manager = DRIVER_MANAGER.read()
+            .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+
+        let report = manager.write_atomic_group(&writes, max_window_us.max(0) as u64)?;
+        if log_enabled!(Level::Debug) {
+            debug!("{}: {:?}", s!("原子组写入"), report);
+        }
+
+        let json = serde_json::to_string(&report)?;
+        Ok(env.new_string(&json)?.into_raw())
+    })()
+    .or_throw(&mut env)
+}
+
+/// 获取内存访问统计（QoS 等待/完成计数），返回 JSON
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeGetMemoryStats", "()Ljava/lang/String;")]
+pub fn jni_get_memory_stats(mut env: JNIEnv, _obj: JObject) -> jstring {
+    (|| -> JniResult<jstring> {
+        let json = serde_json::to_string(&MEMORY_QOS.stats())?;
+        Ok(env.new_string(&json)?.into_raw())
+    })()
+    .or_throw(&mut env)
+}
+
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeSetMemoryQosEnabled", "(Z)V")]
+pub fn jni_set_memory_qos_enabled(_env: JNIEnv, _obj: JObject, enabled: jboolean) {
+    MEMORY_QOS.set_enabled(enabled != JNI_FALSE);
+}
+
+/// BindProc 与驱动两条读取路径的统计，JSON 格式，见 ReadPathStats
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeGetReadPathStats", "()Ljava/lang/String;")]
+pub fn jni_get_read_path_stats(mut env: JNIEnv, _obj: JObject) -> jstring {
+    (|| -> JniResult<jstring> {
+        let manager = DRIVER_MANAGER.read()
+            .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+        let json = serde_json::to_string(&manager.read_fallback().stats())?;
+        Ok(env.new_string(&json)?.into_raw())
+    })()
+    .or_throw(&mut env)
+}
+
+/// BindProc 连续读取失败达到 threshold 次后自动改走驱动路径，0 关闭
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeSetReadFallbackAutoSwitch", "(I)V")]
+pub fn jni_set_read_fallback_auto_switch(_env: JNIEnv, _obj: JObject, threshold: jint) {
+    if let Ok(manager) = DRIVER_MANAGER.read() {
+        manager.read_fallback().set_auto_switch_threshold(threshold.max(0) as u32);
+    }
+}
+
+fn dump_range(start: u64, end: u64, path: &str) -> JniResult<DumpReport> {
+    let pid = {
+        let manager = DRIVER_MANAGER.read()
+            .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+        if !manager.is_process_bound() {
+            return Err(anyhow!("No process is bound. Please bind a process first."));
+        }
+        manager.get_bound_pid()
+    };
+
+    // 每块单独加读锁，转储期间不阻塞绑定和解绑
+    dump_to_file(&MEMORY_DUMP, start, end, Path::new(path), |addr, buf, page_status| {
+        let manager = DRIVER_MANAGER.read()
+            .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+        if manager.get_bound_pid() != pid {
+            return Err(anyhow!("Bound process changed during dump"));
+        }
+        manager.read_memory_with_qos(addr, buf, Some(page_status), AccessQos::Bulk)
+    })
+}
+
+/// 把 [start, end) 的内存写入文件，不可读的页写 0，返回 JSON 格式的 DumpReport
+/// （status 0 完成，1 被 nativeCancelDump 取消，文件只包含已写入的部分）
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeDumpMemoryToFile", "(JJLjava/lang/String;)Ljava/lang/String;")]
+pub fn jni_dump_memory_to_file(mut env: JNIEnv, _obj: JObject, start: jlong, end: jlong, path: JString) -> jstring {
+    (|| -> JniResult<jstring> {
+        let path: String = env.get_string(&path)?.into();
+        let report = dump_range(start as u64, end as u64, &path)?;
+        let json = serde_json::to_string(&report)?;
+        Ok(env.new_string(&json)?.into_raw())
+    })()
+    .or_throw(&mut env)
+}
+
+/// 转储绑定进程中的一个模块（第一个映射到最后一个映射），moduleName 可以是文件名或完整路径
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeDumpModule", "(Ljava/lang/String;Ljava/lang/String;)Ljava/lang/String;")]
+pub fn jni_dump_module(mut env: JNIEnv, _obj: JObject, module_name: JString, path: JString) -> jstring {
+    (|| -> JniResult<jstring> {
+        let module_name: String = env.get_string(&module_name)?.into();
+        let path: String = env.get_string(&path)?.into();
+
+        let (start, end) = {
+            let manager = DRIVER_MANAGER.read()
+                .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+            if !manager.is_process_bound() {
+                return Err(anyhow!("No process is bound. Please bind a process first."));
+            }
+            let pid = manager.get_bound_pid();
+            RegionMap::query(&manager, pid)
+                .map_err(|e| anyhow!("Unable to get memory regions for pid {}: {}", pid, e))?
+                .module_range(&module_name)
+                .ok_or_else(|| anyhow!("Module {} is not loaded", module_name))?
+        };
+
+        let report = dump_range(start, end, &path)?;
+        let json = serde_json::to_string(&report)?;
+        Ok(env.new_string(&json)?.into_raw())
+    })()
+    .or_throw(&mut env)
+}
+
+/// 把按 `module` 旧基址 `old_base` 记录的地址平移到模块在绑定进程中的当前基址
+///
+/// 不在旧模块范围内的地址原样返回，并在 `outside`（与 addrs 等长）的对应位置写入 true。
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeRebaseAddresses", "([JLjava/lang/String;J[Z)[J")]
+pub fn jni_rebase_addresses(
+    mut env: JNIEnv,
+    _obj: JObject,
+    addrs: JLongArray,
+    module_name: JString,
+    old_base: jlong,
+    outside: JBooleanArray,
+) -> jlongArray {
+    (|| -> JniResult<jlongArray> {
+        let module_name: String = env.get_string(&module_name)?.into();
+        let len = env.get_array_length(&addrs)? as usize;
+        if env.get_array_length(&outside)? as usize != len {
+            return Err(anyhow!("Address and flag arrays must have the same length"));
+        }
+        let mut addresses = vec![0i64; len];
+        env.get_long_array_region(&addrs, 0, &mut addresses)?;
+
+        let rebase = {
+            let manager = DRIVER_MANAGER.read()
+                .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+            AddressRebase::for_module(&manager, &module_name, old_base as u64)?
+        };
+
+        let addresses: Vec<u64> = addresses.iter().map(|&addr| addr as u64).collect();
+        let (rebased, flags) = rebase.apply_all(&addresses);
+        let rebased: Vec<jlong> = rebased.iter().map(|&addr| addr as jlong).collect();
+        let flags: Vec<jboolean> = flags.iter().map(|&flag| flag as jboolean).collect();
+
+        env.set_boolean_array_region(&outside, 0, &flags)?;
+        let array = env.new_long_array(len as jsize)?;
+        env.set_long_array_region(&array, 0, &rebased)?;
+        Ok(array.into_raw())
+    })()
+    .or_throw(&mut env)
+}
+
+/// 返回每个地址所在区域的访问属性：0 可写，1 只读，2 可执行，3 未映射
+///
+/// 区域列表按绑定进程缓存，进程映射变化后调用 `nativeInvalidateRegionCache` 重新读取。
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeClassifyAddresses", "([J)[I")]
+pub fn jni_classify_addresses(mut env: JNIEnv, _obj: JObject, addrs: JLongArray) -> jintArray {
+    (|| -> JniResult<jintArray> {
+        let len = env.get_array_length(&addrs)? as usize;
+        let mut addresses = vec![0i64; len];
+        env.get_long_array_region(&addrs, 0, &mut addresses)?;
+
+        let access = {
+            let manager = DRIVER_MANAGER.read()
+                .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+            if !manager.is_process_bound() {
+                return Err(anyhow!("No process is bound. Please bind a process first."));
+            }
+            let addresses: Vec<u64> = addresses.iter().map(|&addr| addr as u64).collect();
+            manager.classify_addresses(&addresses)?
+        };
+
+        let codes: Vec<jint> = access.iter().map(|&access| access as jint).collect();
+        let array = env.new_int_array(len as jsize)?;
+        env.set_int_array_region(&array, 0, &codes)?;
+        Ok(array.into_raw())
+    })()
+    .or_throw(&mut env)
+}
+
+/// 把每个地址格式化为 "区域名+0x偏移"（偏移相对于所在区域的起始），不在任何区域内的地址为 null
+///
+/// 与地址分类共用区域缓存；有地址找不到区域且缓存超过几秒时自动重新读取一次。
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeResolveAddresses", "([J)[Ljava/lang/String;")]
+pub fn jni_resolve_addresses<'l>(mut env: JNIEnv<'l>, _obj: JObject, addrs: JLongArray) -> JObjectArray<'l> {
+    (|| -> JniResult<JObjectArray<'l>> {
+        let len = env.get_array_length(&addrs)? as usize;
+        let mut addresses = vec![0i64; len];
+        env.get_long_array_region(&addrs, 0, &mut addresses)?;
+
+        let resolved = {
+            let manager = DRIVER_MANAGER.read()
+                .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+            if !manager.is_process_bound() {
+                return Err(anyhow!("No process is bound. Please bind a process first."));
+            }
+            let addresses: Vec<u64> = addresses.iter().map(|&addr| addr as u64).collect();
+            manager.resolve_addresses(&addresses)?
+        };
+
+        let array = env.new_object_array(len as jsize, "java/lang/String", JObject::null())?;
+        for (i, resolved) in resolved.iter().enumerate() {
+            if let Some(resolved) = resolved {
+                let name = env.new_string(resolved.to_string())?;
+                env.set_object_array_element(&array, i as jsize, &name)?;
+                env.delete_local_ref(name)?;
+            }
+        }
+        Ok(array)
+    })()
+    .or_throw(&mut env)
+}
+
+/// 进程加载的文件列表：同一路径的映射合并为一项（最低起始地址到最高结束地址），按基址排序
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeListModules", "(I)[Lmoe/fuqiuluo/mamu/driver/ModuleInfo;")]
+pub fn jni_list_modules<'l>(mut env: JNIEnv<'l>, _obj: JObject, pid: jint) -> JObjectArray<'l> {
+    (|| -> JniResult<JObjectArray<'l>> {
+        let modules = {
+            let manager = DRIVER_MANAGER.read()
+                .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+            manager.list_modules(pid)?
+        };
+
+        let class = env.find_class("moe/fuqiuluo/mamu/driver/ModuleInfo")?;
+        let array = env.new_object_array(modules.len() as jsize, &class, JObject::null())?;
+        for (i, module) in modules.iter().enumerate() {
+            let name = env.new_string(&module.name)?;
+            let obj = env.new_object(
+                &class,
+                "(Ljava/lang/String;JJZ)V",
+                &[
+                    (&name).into(),
+                    (module.base as jlong).into(),
+                    (module.end as jlong).into(),
+                    JValue::Bool(module.is_executable_segment as jboolean),
+                ],
+            )?;
+            env.set_object_array_element(&array, i as jsize, &obj)?;
+            env.delete_local_ref(obj)?;
+            env.delete_local_ref(name)?;
+        }
+
+        debug!("Listed {} modules for pid {}", modules.len(), pid);
+        Ok(array)
+    })()
+    .or_throw(&mut env)
+}
+
+/// 丢弃缓存的区域列表，下一次地址分类或写入检查重新读取
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeInvalidateRegionCache", "()V")]
+pub fn jni_invalidate_region_cache(mut env: JNIEnv, _obj: JObject) {
+    (|| -> JniResult<()> {
+        let manager = DRIVER_MANAGER.read()
+            .map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+        manager.invalidate_region_cache();
+        Ok(())
+    })()
+    .or_throw(&mut env)
+}
+
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeCancelDump", "()V")]
+pub fn jni_cancel_dump(_env: JNIEnv, _obj: JObject) {
+    MEMORY_DUMP.cancel();
+}
+
+/// 当前转储的进度，JSON 格式，见 DumpProgress
+#[jni_method(80, "moe/fuqiuluo/mamu/driver/WuwaDriver", "nativeGetDumpProgress", "()Ljava/lang/String;")]
+pub fn jni_get_dump_progress(mut env: JNIEnv, _obj: JObject) -> jstring {
+    (|| -> JniResult<jstring> {
+        let json = serde_json::to_string(&MEMORY_DUMP.progress())?;
+        Ok(env.new_string(&json)?.into_raw())
+    })()
+    .or_throw(&mut env)
+}
+
 #[jni_method(
     90,
     "moe/fuqiuluo/mamu/driver/WuwaDriver",
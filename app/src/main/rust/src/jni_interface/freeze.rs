@@ -1,12 +1,14 @@
 //! JNI methods for FreezeManager
 
-use jni::objects::{JByteArray, JObject};
-use jni::sys::{jboolean, jint, jlong, JNI_FALSE, JNI_TRUE};
+use jni::objects::{JByteArray, JLongArray, JObject};
+use jni::sys::{jboolean, jint, jlong, jstring, JNI_FALSE, JNI_TRUE};
 use jni::JNIEnv;
 use jni_macro::jni_method;
 use log::error;
 
-use crate::core::globals::{FREEZE_MANAGER, TOKIO_RUNTIME};
+use crate::core::freeze_manager::DEFAULT_FREEZE_INTERVAL_MS;
+use crate::core::globals::{DRIVER_MANAGER, FREEZE_MANAGER, TOKIO_RUNTIME};
+use std::time::Duration;
 
 /// 启动冻结循环
 #[jni_method(70, "moe/fuqiuluo/mamu/driver/FreezeManager", "nativeStart", "()V")]
@@ -56,9 +58,55 @@ pub fn jni_freeze_add(mut env: JNIEnv, _obj: JObject, address: jlong, value: JBy
     // 转换为 u8
     let value_bytes: Vec<u8> = buffer.iter().map(|&b| b as u8).collect();
 
+    // 条目归属于当前绑定的进程
+    let pid = match DRIVER_MANAGER.read() {
+        Ok(driver_manager) => driver_manager.get_bound_pid(),
+        Err(e) => {
+            error!("FreezeManager JNI: 无法获取 DRIVER_MANAGER 读锁: {}", e);
+            return JNI_FALSE;
+        },
+    };
+
     match FREEZE_MANAGER.read() {
         Ok(manager) => {
-            manager.add_frozen(address as u64, value_bytes, value_type);
+            manager.add_frozen(address as u64, value_bytes, value_type, pid);
+            JNI_TRUE
+        },
+        Err(e) => {
+            error!("FreezeManager JNI: 无法获取读锁: {}", e);
+            JNI_FALSE
+        },
+    }
+}
+
+/// 以指定间隔（毫秒）冻结地址，间隔不大于 0 时使用默认的 50ms
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/FreezeManager", "nativeAddFreeze", "(J[BI)Z")]
+pub fn jni_freeze_add_with_interval(env: JNIEnv, _obj: JObject, address: jlong, value: JByteArray, interval_ms: jint) -> jboolean {
+    let value_bytes = match env.convert_byte_array(&value) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            error!("FreezeManager JNI: 读取字节数组失败: {}", e);
+            return JNI_FALSE;
+        },
+    };
+    let interval = if interval_ms > 0 {
+        Duration::from_millis(interval_ms as u64)
+    } else {
+        Duration::from_millis(DEFAULT_FREEZE_INTERVAL_MS)
+    };
+
+    let pid = match DRIVER_MANAGER.read() {
+        Ok(driver_manager) => driver_manager.get_bound_pid(),
+        Err(e) => {
+            error!("FreezeManager JNI: 无法获取 DRIVER_MANAGER 读锁: {}", e);
+            return JNI_FALSE;
+        },
+    };
+
+    match FREEZE_MANAGER.read() {
+        Ok(manager) => {
+            // 只有字节没有类型，类型记为 -1
+            manager.add_frozen_with_interval(address as u64, value_bytes, -1, pid, interval);
             JNI_TRUE
         },
         Err(e) => {
@@ -69,7 +117,7 @@ pub fn jni_freeze_add(mut env: JNIEnv, _obj: JObject, address: jlong, value: JBy
 }
 
 /// 移除冻结地址
-#[jni_method(70, "moe/fuqiuluo/mamu/driver/FreezeManager", "nativeRemoveFrozen", "(J)Z")]
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/FreezeManager", "nativeRemoveFreeze", "(J)Z")]
 pub fn jni_freeze_remove(_env: JNIEnv, _obj: JObject, address: jlong) -> jboolean {
     match FREEZE_MANAGER.read() {
         Ok(manager) => {
@@ -87,7 +135,7 @@ pub fn jni_freeze_remove(_env: JNIEnv, _obj: JObject, address: jlong) -> jboolea
 }
 
 /// 清空所有冻结
-#[jni_method(70, "moe/fuqiuluo/mamu/driver/FreezeManager", "nativeClearAll", "()V")]
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/FreezeManager", "nativeClearFreezes", "()V")]
 pub fn jni_freeze_clear_all(_env: JNIEnv, _obj: JObject) {
     match FREEZE_MANAGER.read() {
         Ok(manager) => {
@@ -99,6 +147,38 @@ pub fn jni_freeze_clear_all(_env: JNIEnv, _obj: JObject) {
     }
 }
 
+/// 列出所有冻结条目，返回 JSON 数组 [{address, value, value_type, pid, state, interval_ms, failed_writes, consecutive_failures}]
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/FreezeManager", "nativeListFreezes", "()Ljava/lang/String;")]
+pub fn jni_freeze_list(env: JNIEnv, _obj: JObject) -> jstring {
+    let json = match FREEZE_MANAGER.read() {
+        Ok(manager) => serde_json::to_string(&manager.list_entries()).unwrap_or_else(|_| "[]".to_string()),
+        Err(e) => {
+            error!("FreezeManager JNI: 无法获取读锁: {}", e);
+            "[]".to_string()
+        },
+    };
+
+    match env.new_string(json) {
+        Ok(s) => s.into_raw(),
+        Err(e) => {
+            error!("FreezeManager JNI: 创建字符串失败: {}", e);
+            std::ptr::null_mut()
+        },
+    }
+}
+
+/// 获取累计写入失败次数
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/FreezeManager", "nativeGetFailedWriteCount", "()J")]
+pub fn jni_freeze_get_failed_write_count(_env: JNIEnv, _obj: JObject) -> jlong {
+    match FREEZE_MANAGER.read() {
+        Ok(manager) => manager.get_failed_write_count() as jlong,
+        Err(e) => {
+            error!("FreezeManager JNI: 无法获取读锁: {}", e);
+            0
+        },
+    }
+}
+
 /// 设置冻结间隔（微秒）
 #[jni_method(70, "moe/fuqiuluo/mamu/driver/FreezeManager", "nativeSetInterval", "(J)V")]
 pub fn jni_freeze_set_interval(_env: JNIEnv, _obj: JObject, microseconds: jlong) {
@@ -141,3 +221,141 @@ pub fn jni_freeze_is_frozen(_env: JNIEnv, _obj: JObject, address: jlong) -> jboo
         },
     }
 }
+
+/// 获取孤立冻结条目数量（创建时的进程已不再绑定）
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/FreezeManager", "nativeGetOrphanCount", "()I")]
+pub fn jni_freeze_get_orphan_count(_env: JNIEnv, _obj: JObject) -> jint {
+    match FREEZE_MANAGER.read() {
+        Ok(manager) => manager.get_orphan_count() as jint,
+        Err(e) => {
+            error!("FreezeManager JNI: 无法获取读锁: {}", e);
+            0
+        },
+    }
+}
+
+/// 列出孤立冻结条目，返回 JSON 数组 [{address, value_type, pid}]
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/FreezeManager", "nativeListOrphaned", "()Ljava/lang/String;")]
+pub fn jni_freeze_list_orphaned(env: JNIEnv, _obj: JObject) -> jstring {
+    let json = match FREEZE_MANAGER.read() {
+        Ok(manager) => serde_json::to_string(&manager.list_orphaned()).unwrap_or_else(|_| "[]".to_string()),
+        Err(e) => {
+            error!("FreezeManager JNI: 无法获取读锁: {}", e);
+            "[]".to_string()
+        },
+    };
+
+    match env.new_string(json) {
+        Ok(s) => s.into_raw(),
+        Err(e) => {
+            error!("FreezeManager JNI: 创建字符串失败: {}", e);
+            std::ptr::null_mut()
+        },
+    }
+}
+
+/// 把孤立条目按 oldAddrs[i] -> newAddrs[i] 映射到当前绑定的进程，返回重新激活的数量
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/FreezeManager", "nativeRebindEntries", "([J[J)I")]
+pub fn jni_freeze_rebind_entries(env: JNIEnv, _obj: JObject, old_addrs: JLongArray, new_addrs: JLongArray) -> jint {
+    let len = match (env.get_array_length(&old_addrs), env.get_array_length(&new_addrs)) {
+        (Ok(old_len), Ok(new_len)) if old_len == new_len => old_len as usize,
+        _ => {
+            error!("FreezeManager JNI: 映射数组长度不一致");
+            return 0;
+        },
+    };
+
+    let mut old_buf = vec![0i64; len];
+    let mut new_buf = vec![0i64; len];
+    if let Err(e) = env
+        .get_long_array_region(&old_addrs, 0, &mut old_buf)
+        .and_then(|_| env.get_long_array_region(&new_addrs, 0, &mut new_buf))
+    {
+        error!("FreezeManager JNI: 读取映射数组失败: {}", e);
+        return 0;
+    }
+    let mapping: Vec<(u64, u64)> = old_buf.iter().zip(&new_buf).map(|(&old, &new)| (old as u64, new as u64)).collect();
+
+    let pid = match DRIVER_MANAGER.read() {
+        Ok(driver_manager) => driver_manager.get_bound_pid(),
+        Err(e) => {
+            error!("FreezeManager JNI: 无法获取 DRIVER_MANAGER 读锁: {}", e);
+            return 0;
+        },
+    };
+
+    match FREEZE_MANAGER.read() {
+        Ok(manager) => manager.rebind_entries(&mapping, pid) as jint,
+        Err(e) => {
+            error!("FreezeManager JNI: 无法获取读锁: {}", e);
+            0
+        },
+    }
+}
+
+/// 丢弃所有孤立冻结条目
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/FreezeManager", "nativeClearOrphaned", "()V")]
+pub fn jni_freeze_clear_orphaned(_env: JNIEnv, _obj: JObject) {
+    match FREEZE_MANAGER.read() {
+        Ok(manager) => {
+            manager.clear_orphaned();
+        },
+        Err(e) => {
+            error!("FreezeManager JNI: 无法获取读锁: {}", e);
+        },
+    }
+}
+
+/// 恢复暂停的冻结条目（例如导入的冻结项），地址不是暂停条目时返回 false
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/FreezeManager", "nativeResumeFrozen", "(J)Z")]
+pub fn jni_freeze_resume(_env: JNIEnv, _obj: JObject, address: jlong) -> jboolean {
+    match FREEZE_MANAGER.read() {
+        Ok(manager) => {
+            if manager.resume_frozen(address as u64) {
+                JNI_TRUE
+            } else {
+                JNI_FALSE
+            }
+        },
+        Err(e) => {
+            error!("FreezeManager JNI: 无法获取读锁: {}", e);
+            JNI_FALSE
+        },
+    }
+}
+
+/// 检查地址是否是暂停的冻结条目
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/FreezeManager", "nativeIsPaused", "(J)Z")]
+pub fn jni_freeze_is_paused(_env: JNIEnv, _obj: JObject, address: jlong) -> jboolean {
+    match FREEZE_MANAGER.read() {
+        Ok(manager) => {
+            if manager.is_paused(address as u64) {
+                JNI_TRUE
+            } else {
+                JNI_FALSE
+            }
+        },
+        Err(e) => {
+            error!("FreezeManager JNI: 无法获取读锁: {}", e);
+            JNI_FALSE
+        },
+    }
+}
+
+/// 冻结写入是否因绑定的进程退出而暂停，进程恢复存活或重新绑定后恢复
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/FreezeManager", "nativeIsProcessPaused", "()Z")]
+pub fn jni_freeze_is_process_paused(_env: JNIEnv, _obj: JObject) -> jboolean {
+    match FREEZE_MANAGER.read() {
+        Ok(manager) => {
+            if manager.is_process_paused() {
+                JNI_TRUE
+            } else {
+                JNI_FALSE
+            }
+        },
+        Err(e) => {
+            error!("FreezeManager JNI: 无法获取读锁: {}", e);
+            JNI_FALSE
+        },
+    }
+}
@@ -9,4 +9,8 @@ pub mod mem_ops;
 pub mod disassembler;
 pub mod driver_installer;
 pub mod pointer_scan;
-pub mod freeze;
\ No newline at end of file
+pub mod freeze;
+pub mod watch;
+pub mod saved_addresses;
+pub mod diagnostics;
+pub mod control;
\ No newline at end of file
@@ -1,17 +1,18 @@
 //! JNI methods for PointerScanner.
 
-use std::collections::HashMap;
 use crate::ext::jni::{JniResult, JniResultExt};
-use crate::pointer_scan::manager::POINTER_SCAN_MANAGER;
+use crate::pointer_scan::manager::{native_static_modules, POINTER_SCAN_MANAGER};
+use crate::pointer_scan::samples::ChainSample;
 use crate::pointer_scan::scanner::ScanRegion;
 use crate::pointer_scan::shared_buffer::SHARED_BUFFER_SIZE;
-use crate::pointer_scan::types::{ScanPhase, VmStaticData};
+use crate::pointer_scan::types::{assign_module_indices, ChainOutputFormat, PointerScanConfigError, ScanPhase, VmStaticData};
 use anyhow::anyhow;
-use jni::objects::{JIntArray, JLongArray, JObject, JObjectArray, JString};
-use jni::sys::{jboolean, jint, jlong, jobjectArray, JNI_FALSE, JNI_TRUE};
+use jni::objects::{JByteArray, JIntArray, JLongArray, JObject, JObjectArray, JString};
+use jni::sys::{jboolean, jint, jintArray, jlong, jobjectArray, jsize, JNI_FALSE, JNI_TRUE};
 use jni::JNIEnv;
 use jni_macro::jni_method;
-use log::{error, info, log_enabled, Level};
+use log::{info, log_enabled, warn, Level};
+use std::path::PathBuf;
 
 /// Initialize the pointer scanner with a cache directory.
 #[jni_method(70, "moe/fuqiuluo/mamu/driver/PointerScanner", "nativeInit", "(Ljava/lang/String;)Z")]
@@ -56,6 +57,103 @@ pub fn jni_set_pointer_scan_buffer(mut env: JNIEnv, _class: JObject, buffer: JOb
     .or_throw(&mut env)
 }
 
+/// Parse the region arrays passed from Kotlin into scan regions and static modules with assigned indices.
+///
+/// Regions that are neither readable nor writable are skipped. When `static_flags` is null the static
+/// modules are derived from the bound process module list instead.
+fn read_regions(
+    env: &mut JNIEnv,
+    regions: &JLongArray,
+    region_names: &JObjectArray,
+    static_flags: JObject, // jbooleanArray
+    perm_flags: &JIntArray,
+) -> JniResult<(Vec<ScanRegion>, Vec<VmStaticData>)> {
+    // Parse regions
+    let regions_len = env.get_array_length(regions)? as usize;
+    let region_count = regions_len / 2;
+
+    let names_count = env.get_array_length(region_names)? as usize;
+    if names_count != region_count {
+        return Err(anyhow!("Region count mismatch: {} regions but {} names", region_count, names_count));
+    }
+
+    // Get region data
+    let mut region_data = vec![0i64; regions_len];
+    env.get_long_array_region(regions, 0, &mut region_data)?;
+
+    // Get static flags; null means static segments are derived natively from the module list
+    let static_data = if static_flags.is_null() {
+        None
+    } else {
+        let static_flags_jarray = unsafe { jni::objects::JBooleanArray::from_raw(static_flags.as_raw()) };
+        let flags_len = env.get_array_length(&static_flags_jarray)? as usize;
+        let mut static_data = vec![0u8; flags_len];
+        env.get_boolean_array_region(&static_flags_jarray, 0, &mut static_data)?;
+        Some(static_data)
+    };
+
+    // Get permission flags
+    let perm_len = env.get_array_length(perm_flags)? as usize;
+    let mut perm_data = vec![0i32; perm_len];
+    env.get_int_array_region(perm_flags, 0, &mut perm_data)?;
+
+    const MEM_READABLE: i32 = 0x01;
+    const MEM_WRITABLE: i32 = 0x02;
+
+    let mut scan_regions = Vec::with_capacity(region_count);
+    let mut static_modules = Vec::new();
+
+    for i in 0..region_count {
+        let start = region_data[i * 2] as u64;
+        let end = region_data[i * 2 + 1] as u64;
+
+        let name_obj = env.get_object_array_element(region_names, i as i32)?;
+        let name_jstr = JString::from(name_obj);
+        let name: String = env.get_string(&name_jstr)?.into();
+
+        let is_static = static_data.as_ref().is_some_and(|flags| flags[i] != 0);
+        let perms = if i < perm_len { perm_data[i] } else { 0 };
+        let is_readable = (perms & MEM_READABLE) != 0;
+        let is_writable = (perms & MEM_WRITABLE) != 0;
+
+        // 跳过不可读也不可写的段
+        if !is_readable && !is_writable {
+            continue;
+        }
+
+        scan_regions.push(ScanRegion {
+            start,
+            end,
+            name: name.clone(),
+        });
+
+        if is_static {
+            static_modules.push(VmStaticData::new(name, start, end, true));
+        }
+    }
+
+    if static_data.is_none() {
+        let static_modules = native_static_modules(&scan_regions)?;
+        return Ok((scan_regions, static_modules));
+    }
+    assign_module_indices(&mut static_modules);
+    Ok((scan_regions, static_modules))
+}
+
+/// Read the module filter passed from Kotlin; null or an empty array means no filter.
+fn read_module_filter(env: &mut JNIEnv, modules: &JObjectArray) -> JniResult<Option<Vec<String>>> {
+    if modules.is_null() {
+        return Ok(None);
+    }
+    let len = env.get_array_length(modules)?;
+    let mut names = Vec::with_capacity(len as usize);
+    for i in 0..len {
+        let name = JString::from(env.get_object_array_element(modules, i)?);
+        names.push(env.get_string(&name)?.into());
+    }
+    Ok((!names.is_empty()).then_some(names))
+}
+
 /// Start a pointer scan asynchronously.
 ///
 /// # Arguments
@@ -65,8 +163,11 @@ pub fn jni_set_pointer_scan_buffer(mut env: JNIEnv, _class: JObject, buffer: JOb
 /// * `align` - Pointer alignment
 /// * `regions` - Memory regions as [start1, end1, start2, end2, ...]
 /// * `region_names` - Names of the regions
-/// * `static_flags` - Boolean flags indicating if each region is static
-#[jni_method(70, "moe/fuqiuluo/mamu/driver/PointerScanner", "nativeStartScan", "(JIII[J[Ljava/lang/String;[Z[IZI)Z")]
+/// * `static_flags` - Boolean flags indicating if each region is static; null to derive them from the module list
+/// * `force` - Scan even if the estimated chain search space is too large
+/// * `output_format` - `ChainOutputFormat` id of the output file lines
+/// * `module_filter` - Only emit chains rooted in these modules; null or empty for all
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/PointerScanner", "nativeStartScan", "(JIII[J[Ljava/lang/String;[Z[IZIZI[Ljava/lang/String;ZZ)Z")]
 pub fn jni_start_pointer_scan(
     mut env: JNIEnv,
     _class: JObject,
@@ -79,84 +180,18 @@ pub fn jni_start_pointer_scan(
     static_flags: JObject, // jbooleanArray
     perm_flags: JIntArray,
     is_layer_bfs: jboolean,
-    max_results: jint
+    max_results: jint,
+    force: jboolean,
+    output_format: jint,
+    module_filter: JObjectArray,
+    live_vma_check: jboolean,
+    require_present_pages: jboolean,
 ) -> jboolean {
     (|| -> JniResult<jboolean> {
-        // Parse regions
-        let regions_len = env.get_array_length(&regions)? as usize;
-        let region_count = regions_len / 2;
-
-        let names_count = env.get_array_length(&region_names)? as usize;
-        if names_count != region_count {
-            return Err(anyhow!("Region count mismatch: {} regions but {} names", region_count, names_count));
-        }
-
-        // Get region data
-        let mut region_data = vec![0i64; regions_len];
-        env.get_long_array_region(&regions, 0, &mut region_data)?;
-
-        // Get static flags
-        let static_flags_array: JObject = static_flags;
-        let static_flags_jarray = unsafe { jni::objects::JBooleanArray::from_raw(static_flags_array.as_raw()) };
-        let flags_len = env.get_array_length(&static_flags_jarray)? as usize;
-        let mut static_data = vec![0u8; flags_len];
-        env.get_boolean_array_region(&static_flags_jarray, 0, &mut static_data)?;
-
-        // Get permission flags
-        let perm_len = env.get_array_length(&perm_flags)? as usize;
-        let mut perm_data = vec![0i32; perm_len];
-        env.get_int_array_region(&perm_flags, 0, &mut perm_data)?;
-
-        const MEM_READABLE: i32 = 0x01;
-        const MEM_WRITABLE: i32 = 0x02;
-
-        let mut scan_regions = Vec::with_capacity(region_count);
-        let mut static_modules = Vec::new();
-
-        for i in 0..region_count {
-            let start = region_data[i * 2] as u64;
-            let end = region_data[i * 2 + 1] as u64;
-
-            let name_obj = env.get_object_array_element(&region_names, i as i32)?;
-            let name_jstr = JString::from(name_obj);
-            let name: String = env.get_string(&name_jstr)?.into();
-
-            let is_static = static_data[i] != 0;
-            let perms = if i < perm_len { perm_data[i] } else { 0 };
-            let is_readable = (perms & MEM_READABLE) != 0;
-            let is_writable = (perms & MEM_WRITABLE) != 0;
-
-            // 跳过不可读也不可写的段
-            if !is_readable && !is_writable {
-                continue;
-            }
-
-            scan_regions.push(ScanRegion {
-                start,
-                end,
-                name: name.clone(),
-            });
-
-            if is_static {
-                static_modules.push(VmStaticData::new(name, start, end, true));
-            }
-        }
-
-        // Assign indices and first_module_base_addr to static modules with duplicate names
-        // 同名模块共享第一个段的基址，用于计算统一的偏移
-        let mut name_counts: HashMap<String, u32> = HashMap::new();
-        let mut first_base_addrs: HashMap<String, u64> = HashMap::new();
-        for module in &mut static_modules {
-            let count = name_counts.entry(module.name.clone()).or_insert(0);
-            module.index = *count;
-            if *count == 0 {
-                // 记录该名称第一个模块的基址
-                first_base_addrs.insert(module.name.clone(), module.base_address);
-            }
-            // 所有同名模块共享第一个段的基址
-            module.first_module_base_addr = *first_base_addrs.get(&module.name).unwrap();
-            *count += 1;
-        }
+        let (scan_regions, static_modules) = read_regions(&mut env, &regions, &region_names, static_flags, &perm_flags)?;
+        let output_format = ChainOutputFormat::from_id(output_format)
+            .ok_or_else(|| anyhow!("Invalid chain output format: {}", output_format))?;
+        let module_filter = read_module_filter(&mut env, &module_filter)?;
 
         if log_enabled!(Level::Debug) {
             info!("Static modules:");
@@ -178,7 +213,7 @@ pub fn jni_start_pointer_scan(
             .write()
             .map_err(|_| anyhow!("Failed to acquire PointerScanManager write lock"))?;
 
-        manager.start_scan_async(
+        let started = manager.start_scan_async(
             target_address as u64,
             max_depth as u32,
             max_offset as u32,
@@ -186,9 +221,53 @@ pub fn jni_start_pointer_scan(
             scan_regions,
             static_modules,
             is_layer_bfs == 1u8,
-            max_results as u32
-        )?;
+            max_results as u32,
+            force != JNI_FALSE,
+            output_format,
+            module_filter,
+            live_vma_check != JNI_FALSE,
+            require_present_pages != JNI_FALSE,
+        );
+        match started {
+            Ok(()) => Ok(JNI_TRUE),
+            // 参数被拒绝：错误码和说明已经写入，由 Kotlin 侧展示
+            Err(e) if e.downcast_ref::<PointerScanConfigError>().is_some() => {
+                warn!("Pointer scan rejected: {}", e);
+                Ok(JNI_FALSE)
+            },
+            Err(e) => Err(e),
+        }
+    })()
+    .or_throw(&mut env)
+}
+
+/// Rescan a previous output file, keeping only chains that still resolve to `new_target`.
+///
+/// With `adjust_last_offset`, chains whose last pointer lands within the previous scan's max offset
+/// of `new_target` are kept with their last offset rewritten. Region arguments are the same as
+/// `nativeStartScan`, taken from the current process so that module bases after a restart are used.
+/// Progress is reported in the BuildingChains phase, then WritingFile; the result replaces the current one.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/PointerScanner", "nativeStartRescan", "(Ljava/lang/String;JZ[J[Ljava/lang/String;[Z[I)Z")]
+#[allow(clippy::too_many_arguments)] // 参数由 Java 侧签名决定
+pub fn jni_start_pointer_rescan(
+    mut env: JNIEnv,
+    _class: JObject,
+    previous_file: JString,
+    new_target: jlong,
+    adjust_last_offset: jboolean,
+    regions: JLongArray,
+    region_names: JObjectArray,
+    static_flags: JObject, // jbooleanArray
+    perm_flags: JIntArray,
+) -> jboolean {
+    (|| -> JniResult<jboolean> {
+        let previous_file: String = env.get_string(&previous_file)?.into();
+        let (_, static_modules) = read_regions(&mut env, &regions, &region_names, static_flags, &perm_flags)?;
 
+        let mut manager = POINTER_SCAN_MANAGER
+            .write()
+            .map_err(|_| anyhow!("Failed to acquire PointerScanManager write lock"))?;
+        manager.start_rescan_async(PathBuf::from(previous_file), new_target as u64, adjust_last_offset != JNI_FALSE, static_modules)?;
         Ok(JNI_TRUE)
     })()
     .or_throw(&mut env)
@@ -217,21 +296,45 @@ pub fn jni_cancel_pointer_scan(_env: JNIEnv, _class: JObject) {
     }
 }
 
-/// Get the number of chains found.
+/// Get the number of chains in the output file, read from its index.
 #[jni_method(70, "moe/fuqiuluo/mamu/driver/PointerScanner", "nativeGetChainCount", "()J")]
 pub fn jni_get_chain_count(_env: JNIEnv, _class: JObject) -> jlong {
     match POINTER_SCAN_MANAGER.read() {
-        Ok(manager) => {
-            if let Some(result) = manager.get_scan_result() {
-                result.total_count as jlong
-            } else {
-                0
-            }
-        },
+        Ok(manager) => manager.get_chain_count() as jlong,
         Err(_) => 0,
     }
 }
 
+/// Open a previously saved output file as the current result, rebuilding its index if needed.
+///
+/// Returns the number of chains in the file.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/PointerScanner", "nativeOpenResults", "(Ljava/lang/String;)J")]
+pub fn jni_open_pointer_scan_results(mut env: JNIEnv, _class: JObject, path: JString) -> jlong {
+    (|| -> JniResult<jlong> {
+        let path: String = env.get_string(&path)?.into();
+        let mut manager = POINTER_SCAN_MANAGER
+            .write()
+            .map_err(|_| anyhow!("Failed to acquire PointerScanManager write lock"))?;
+        Ok(manager.open_output_file(PathBuf::from(path))? as jlong)
+    })()
+    .or_throw(&mut env)
+}
+
+/// Delete the current result's output file and its index, leaving no result.
+///
+/// Fails while a scan or validation is running.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/PointerScanner", "nativeDeleteResults", "()V")]
+pub fn jni_delete_pointer_scan_results(mut env: JNIEnv, _class: JObject) {
+    (|| -> JniResult<()> {
+        let mut manager = POINTER_SCAN_MANAGER
+            .write()
+            .map_err(|_| anyhow!("Failed to acquire PointerScanManager write lock"))?;
+        manager.delete_output_file()?;
+        Ok(())
+    })()
+    .or_throw(&mut env)
+}
+
 /// Get the output file path of the scan result.
 #[jni_method(70, "moe/fuqiuluo/mamu/driver/PointerScanner", "nativeGetOutputFilePath", "()Ljava/lang/String;")]
 pub fn jni_get_output_file_path(mut env: JNIEnv, _class: JObject) -> jni::sys::jstring {
@@ -250,21 +353,151 @@ pub fn jni_get_output_file_path(mut env: JNIEnv, _class: JObject) -> jni::sys::j
     .or_throw(&mut env)
 }
 
-/// Get a range of chain results.
-/// @deprecated Results are now written directly to file. Use nativeGetOutputFilePath() instead.
+/// Get a range of chain results, paged from the output file through its index.
 #[jni_method(
     70,
     "moe/fuqiuluo/mamu/driver/PointerScanner",
     "nativeGetChains",
     "(II)[Lmoe/fuqiuluo/mamu/driver/PointerChainResult;"
 )]
-pub fn jni_get_chains(mut env: JNIEnv, _class: JObject, _start: jint, _count: jint) -> jobjectArray {
+pub fn jni_get_chains(mut env: JNIEnv, _class: JObject, start: jint, count: jint) -> jobjectArray {
+    (|| -> JniResult<jobjectArray> {
+        let (chains, target) = {
+            let manager = POINTER_SCAN_MANAGER
+                .read()
+                .map_err(|_| anyhow!("Failed to acquire PointerScanManager read lock"))?;
+            (manager.get_chain_results(start.max(0) as u64, count.max(0) as usize)?, manager.target_address())
+        };
+        new_chain_array(&mut env, &chains, target)
+    })()
+    .or_throw(&mut env)
+}
+
+/// Build a `PointerChainResult[]` from chains read out of the output file.
+fn new_chain_array(env: &mut JNIEnv, chains: &[ChainSample], target: u64) -> JniResult<jobjectArray> {
+    let chain_class = env.find_class("moe/fuqiuluo/mamu/driver/PointerChainResult")?;
+    let result_array = env.new_object_array(chains.len() as jsize, &chain_class, JObject::null())?;
+    for (i, chain) in chains.iter().enumerate() {
+        let chain_string = env.new_string(chain.to_chain_string())?;
+        let module_name = env.new_string(&chain.module)?;
+        // offsets 的第一项是基址偏移
+        let offsets: Vec<jlong> = std::iter::once(chain.base_offset as jlong).chain(chain.offsets.iter().copied()).collect();
+        let offsets_array = env.new_long_array(offsets.len() as jsize)?;
+        env.set_long_array_region(&offsets_array, 0, &offsets)?;
+
+        // PointerChainResult(chainString: String, moduleName: String, moduleIndex: Int, offsets: LongArray, targetAddress: Long)
+        let object = env.new_object(
+            &chain_class,
+            "(Ljava/lang/String;Ljava/lang/String;I[JJ)V",
+            &[
+                (&chain_string).into(),
+                (&module_name).into(),
+                chain.module_index.into(),
+                (&offsets_array).into(),
+                (target as jlong).into(),
+            ],
+        )?;
+        env.set_object_array_element(&result_array, i as jsize, &object)?;
+        env.delete_local_ref(object)?;
+        env.delete_local_ref(offsets_array)?;
+        env.delete_local_ref(module_name)?;
+        env.delete_local_ref(chain_string)?;
+    }
+    Ok(result_array.into_raw())
+}
+
+/// Start validating chains `[start, start + count)` of the current result in the background.
+///
+/// A chain is marked invalid when a dereference fails, or when `expected_value` is non-null and
+/// the bytes at its final address differ. Progress is reported in the Validating phase with the
+/// surviving chain count in chains_found; marks from earlier validations are kept.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/PointerScanner", "nativeStartValidation", "(JJ[B)Z")]
+pub fn jni_start_pointer_validation(mut env: JNIEnv, _class: JObject, start: jlong, count: jlong, expected_value: JByteArray) -> jboolean {
+    (|| -> JniResult<jboolean> {
+        let expected_value = if expected_value.is_null() {
+            None
+        } else {
+            Some(env.convert_byte_array(&expected_value)?)
+        };
+        let start = start.max(0) as u64;
+        let end = start.saturating_add(count.max(0) as u64);
+
+        let mut manager = POINTER_SCAN_MANAGER
+            .write()
+            .map_err(|_| anyhow!("Failed to acquire PointerScanManager write lock"))?;
+        manager.start_validation_async(start..end, expected_value)?;
+        Ok(JNI_TRUE)
+    })()
+    .or_throw(&mut env)
+}
+
+/// Get the number of chains not marked invalid by validation, or all chains if none ran.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/PointerScanner", "nativeGetValidChainCount", "()J")]
+pub fn jni_get_valid_chain_count(_env: JNIEnv, _class: JObject) -> jlong {
+    match POINTER_SCAN_MANAGER.read() {
+        Ok(manager) => manager.get_valid_chain_count() as jlong,
+        Err(_) => 0,
+    }
+}
+
+/// Get a range of chains that survived validation; `start` counts surviving chains only.
+#[jni_method(
+    70,
+    "moe/fuqiuluo/mamu/driver/PointerScanner",
+    "nativeGetValidChains",
+    "(II)[Lmoe/fuqiuluo/mamu/driver/PointerChainResult;"
+)]
+pub fn jni_get_valid_chains(mut env: JNIEnv, _class: JObject, start: jint, count: jint) -> jobjectArray {
     (|| -> JniResult<jobjectArray> {
-        // 结果现在直接写入文件，此方法返回空数组
-        // Results are now written directly to file, this method returns empty array
-        let chain_class = env.find_class("moe/fuqiuluo/mamu/driver/PointerChainResult")?;
-        let result_array = env.new_object_array(0, &chain_class, JObject::null())?;
-        Ok(result_array.into_raw())
+        let (chains, target) = {
+            let manager = POINTER_SCAN_MANAGER
+                .read()
+                .map_err(|_| anyhow!("Failed to acquire PointerScanManager read lock"))?;
+            (manager.get_valid_chain_results(start.max(0) as u64, count.max(0) as usize)?, manager.target_address())
+        };
+        new_chain_array(&mut env, &chains, target)
+    })()
+    .or_throw(&mut env)
+}
+
+/// Get example chains found so far, callable while the scan runs.
+///
+/// Returns JSON: `{"seen":120,"samples":[{"module":"libgame.so","module_index":0,"base_offset":416,"offsets":[16,-8],"depth":2}],"shallowest":{...}}`
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/PointerScanner", "nativeGetPointerScanSamples", "()Ljava/lang/String;")]
+pub fn jni_get_pointer_scan_samples(mut env: JNIEnv, _class: JObject) -> jni::sys::jstring {
+    (|| -> JniResult<jni::sys::jstring> {
+        let manager = POINTER_SCAN_MANAGER
+            .read()
+            .map_err(|_| anyhow!("Failed to acquire PointerScanManager read lock"))?;
+
+        let json = serde_json::to_string(&manager.get_samples())?;
+        Ok(env.new_string(&json)?.into_raw())
+    })()
+    .or_throw(&mut env)
+}
+
+/// Re-resolve chains of the last scan against live memory.
+///
+/// `chain_ids` are line numbers in the output file, not counting comments and blank lines.
+/// Each returned status has the status in the low 8 bits (0 valid, 1 moved, 2 broken, 3 unresolved, 4 skipped)
+/// and, for broken chains, the failing dereference index above them.
+/// Setting the shared buffer cancel flag skips the remaining chains.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/PointerScanner", "nativeValidateChains", "([J)[I")]
+pub fn jni_validate_chains(mut env: JNIEnv, _class: JObject, chain_ids: JLongArray) -> jintArray {
+    (|| -> JniResult<jintArray> {
+        let len = env.get_array_length(&chain_ids)? as usize;
+        let mut ids = vec![0i64; len];
+        env.get_long_array_region(&chain_ids, 0, &mut ids)?;
+        let ids: Vec<u64> = ids.into_iter().map(|id| id as u64).collect();
+
+        let manager = POINTER_SCAN_MANAGER
+            .read()
+            .map_err(|_| anyhow!("Failed to acquire PointerScanManager read lock"))?;
+        let codes: Vec<jint> = manager.validate_chain_ids(&ids)?.iter().map(|status| status.code()).collect();
+
+        let result = env.new_int_array(codes.len() as jsize)?;
+        env.set_int_array_region(&result, 0, &codes)?;
+        Ok(result.into_raw())
     })()
     .or_throw(&mut env)
 }
@@ -277,6 +510,53 @@ pub fn jni_clear_pointer_scan(_env: JNIEnv, _class: JObject) {
     }
 }
 
+/// 设置指针扫描的工作线程数，0 使用全局线程池，从下一次扫描开始生效
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/PointerScanner", "nativeSetThreadCount", "(I)V")]
+pub fn jni_set_pointer_scan_thread_count(mut env: JNIEnv, _class: JObject, threads: jint) {
+    (|| -> JniResult<()> {
+        if threads < 0 {
+            return Err(anyhow!("Invalid thread count: {}", threads));
+        }
+        let mut manager = POINTER_SCAN_MANAGER
+            .write()
+            .map_err(|_| anyhow!("Failed to acquire PointerScanManager write lock"))?;
+
+        manager.set_worker_threads(threads as usize);
+        Ok(())
+    })()
+    .or_throw(&mut env)
+}
+
+/// 指针扫描的工作线程是否使用后台调度优先级，从下一次扫描开始生效
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/PointerScanner", "nativeSetBackgroundPriority", "(Z)V")]
+pub fn jni_set_pointer_scan_background_priority(mut env: JNIEnv, _class: JObject, background: jboolean) {
+    (|| -> JniResult<()> {
+        let mut manager = POINTER_SCAN_MANAGER
+            .write()
+            .map_err(|_| anyhow!("Failed to acquire PointerScanManager write lock"))?;
+
+        manager.set_background_priority(background != JNI_FALSE);
+        Ok(())
+    })()
+    .or_throw(&mut env)
+}
+
+/// Get the message of the last error, empty if none.
+///
+/// Rejected parameters carry a suggestion, e.g. which depth or offset would fit.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/PointerScanner", "nativeGetErrorMessage", "()Ljava/lang/String;")]
+pub fn jni_get_error_message(mut env: JNIEnv, _class: JObject) -> jni::sys::jstring {
+    (|| -> JniResult<jni::sys::jstring> {
+        let manager = POINTER_SCAN_MANAGER
+            .read()
+            .map_err(|_| anyhow!("Failed to acquire PointerScanManager read lock"))?;
+
+        let message = manager.get_error_message().unwrap_or_default();
+        Ok(env.new_string(&message)?.into_raw())
+    })()
+    .or_throw(&mut env)
+}
+
 /// Get the current scan phase.
 #[jni_method(70, "moe/fuqiuluo/mamu/driver/PointerScanner", "nativeGetPhase", "()I")]
 pub fn jni_get_phase(_env: JNIEnv, _class: JObject) -> jint {
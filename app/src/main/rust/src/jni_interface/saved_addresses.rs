@@ -0,0 +1,154 @@
+//! JNI methods for SavedAddressManager
+
+use jni::JNIEnv;
+use jni::objects::{JObject, JString};
+use jni::sys::{JNI_FALSE, JNI_TRUE, jboolean, jint, jlong, jobjectArray, jsize, jstring};
+use jni_macro::jni_method;
+use log::error;
+
+use crate::core::globals::{DRIVER_MANAGER, FREEZE_MANAGER, SAVED_ADDRESSES, TOKIO_RUNTIME};
+use crate::search::ValueType;
+
+/// 删除冻结中的条目时一起移除冻结
+fn unfreeze(address: u64) {
+    match FREEZE_MANAGER.read() {
+        Ok(manager) => {
+            manager.remove_frozen(address);
+        },
+        Err(e) => {
+            error!("SavedAddressManager JNI: 无法获取 FREEZE_MANAGER 读锁: {}", e);
+        },
+    }
+}
+
+/// 保存地址；地址已经保存时更新类型和标签
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SavedAddressManager", "nativeAddSaved", "(JILjava/lang/String;)Z")]
+pub fn jni_saved_add(mut env: JNIEnv, _obj: JObject, address: jlong, value_type: jint, label: JString) -> jboolean {
+    let value_type = match ValueType::from_id(value_type) {
+        Some(value_type) if !value_type.is_variable_len() => value_type,
+        _ => {
+            error!("SavedAddressManager JNI: 不支持的值类型 {}", value_type);
+            return JNI_FALSE;
+        },
+    };
+    let label: String = match env.get_string(&label) {
+        Ok(label) => label.into(),
+        Err(e) => {
+            error!("SavedAddressManager JNI: 读取标签失败: {}", e);
+            return JNI_FALSE;
+        },
+    };
+
+    SAVED_ADDRESSES.add(address as u64, value_type, label);
+    JNI_TRUE
+}
+
+/// 删除保存的地址，冻结中的条目同时取消冻结
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SavedAddressManager", "nativeRemoveSaved", "(J)Z")]
+pub fn jni_saved_remove(_env: JNIEnv, _obj: JObject, address: jlong) -> jboolean {
+    match SAVED_ADDRESSES.remove(address as u64) {
+        Some(entry) => {
+            if entry.frozen {
+                unfreeze(entry.address);
+            }
+            JNI_TRUE
+        },
+        None => JNI_FALSE,
+    }
+}
+
+/// 清空保存的地址，冻结中的条目同时取消冻结
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SavedAddressManager", "nativeClearSaved", "()V")]
+pub fn jni_saved_clear(_env: JNIEnv, _obj: JObject) {
+    for entry in SAVED_ADDRESSES.clear().into_iter().filter(|e| e.frozen) {
+        unfreeze(entry.address);
+    }
+}
+
+/// 列出保存的地址，返回 JSON 数组 [{address, value_type, label, frozen}]（按添加顺序）
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SavedAddressManager", "nativeListSaved", "()Ljava/lang/String;")]
+pub fn jni_saved_list(env: JNIEnv, _obj: JObject) -> jstring {
+    let json = serde_json::to_string(&SAVED_ADDRESSES.list()).unwrap_or_else(|_| "[]".to_string());
+
+    match env.new_string(json) {
+        Ok(s) => s.into_raw(),
+        Err(e) => {
+            error!("SavedAddressManager JNI: 创建字符串失败: {}", e);
+            std::ptr::null_mut()
+        },
+    }
+}
+
+/// 冻结或取消冻结保存的地址，冻结时写入最近一次刷新读到的值；地址没有保存或值还不可读时返回 false
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SavedAddressManager", "nativeSetSavedFrozen", "(JZ)Z")]
+pub fn jni_saved_set_frozen(_env: JNIEnv, _obj: JObject, address: jlong, frozen: jboolean) -> jboolean {
+    let address = address as u64;
+    let Some(entry) = SAVED_ADDRESSES.get(address) else {
+        return JNI_FALSE;
+    };
+
+    if frozen == JNI_FALSE {
+        unfreeze(address);
+        SAVED_ADDRESSES.set_frozen(address, false);
+        return JNI_TRUE;
+    }
+
+    let Some(value) = SAVED_ADDRESSES.current_value(address) else {
+        return JNI_FALSE;
+    };
+    let pid = match DRIVER_MANAGER.read() {
+        Ok(driver_manager) => driver_manager.get_bound_pid(),
+        Err(e) => {
+            error!("SavedAddressManager JNI: 无法获取 DRIVER_MANAGER 读锁: {}", e);
+            return JNI_FALSE;
+        },
+    };
+
+    let _guard = TOKIO_RUNTIME.enter();
+    match FREEZE_MANAGER.write() {
+        Ok(mut manager) => {
+            manager.add_frozen(address, value, entry.value_type.to_id(), pid);
+            manager.start();
+        },
+        Err(e) => {
+            error!("SavedAddressManager JNI: 无法获取 FREEZE_MANAGER 写锁: {}", e);
+            return JNI_FALSE;
+        },
+    }
+    SAVED_ADDRESSES.set_frozen(address, true);
+    JNI_TRUE
+}
+
+/// 在后台开始一次批量刷新（已有刷新在进行时不重复），立即返回最近一次刷新的格式化值，
+/// 顺序与 nativeListSaved 相同，不可读的值为 "N/A"；刷新完成后代数递增
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SavedAddressManager", "nativeRefreshSavedValues", "()[Ljava/lang/String;")]
+pub fn jni_saved_refresh_values(mut env: JNIEnv, _obj: JObject) -> jobjectArray {
+    {
+        let _guard = TOKIO_RUNTIME.enter();
+        SAVED_ADDRESSES.refresh();
+    }
+
+    let values = SAVED_ADDRESSES.formatted_values();
+    let result = (|| -> jni::errors::Result<jobjectArray> {
+        let array = env.new_object_array(values.len() as jsize, "java/lang/String", JObject::null())?;
+        for (i, value) in values.iter().enumerate() {
+            let value = env.new_string(value)?;
+            env.set_object_array_element(&array, i as jsize, &value)?;
+            env.delete_local_ref(value)?;
+        }
+        Ok(array.into_raw())
+    })();
+    match result {
+        Ok(array) => array,
+        Err(e) => {
+            error!("SavedAddressManager JNI: 创建数组失败: {}", e);
+            std::ptr::null_mut()
+        },
+    }
+}
+
+/// 当前代数，列表变化或一次刷新完成时递增
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SavedAddressManager", "nativeGetSavedGeneration", "()J")]
+pub fn jni_saved_get_generation(_env: JNIEnv, _obj: JObject) -> jlong {
+    SAVED_ADDRESSES.generation() as jlong
+}
@@ -1,20 +1,26 @@
 //! JNI methods for SearchEngine.
 
-use crate::core::DRIVER_MANAGER;
+use crate::core::{AccessQos, DRIVER_MANAGER, MEMORY_GUARD};
+use crate::core::region_map::{PointerStatus, current_region_map};
 use crate::ext::jni::{JniResult, JniResultExt};
+use crate::import_export::import_gg_saved_list;
 use crate::search::SearchResultItem;
-use crate::search::engine::{SEARCH_ENGINE_MANAGER, SHARED_BUFFER_SIZE, SearchProgressCallback};
+use crate::search::engine::refine_strategy::RefineStrategy;
+use crate::search::engine::{ResultOrder, SEARCH_ENGINE_MANAGER, SHARED_BUFFER_SIZE, SearchEngineManager, SearchProgressCallback};
 use crate::search::parser::parse_search_query;
-use crate::search::result_manager::SearchResultMode;
-use crate::search::types::ValueType;
+use crate::search::pattern::parse_pattern;
+use crate::search::result_manager::{ResultExportFormat, SearchResultMode};
+use crate::search::format::{ValueDisplay, format_value_with};
+use crate::search::types::{SearchMode, SearchQuery, SearchValue, ValueType};
 use anyhow::anyhow;
 use jni::objects::{GlobalRef, JIntArray, JLongArray, JObject, JString, JValue};
-use jni::sys::{JNI_FALSE, JNI_TRUE, jboolean, jint, jlong, jobjectArray};
+use jni::sys::{JNI_FALSE, JNI_TRUE, jboolean, jint, jlong, jlongArray, jobject, jobjectArray, jstring};
 use jni::{JNIEnv, JavaVM};
 use jni_macro::jni_method;
 use log::{Level, error, log_enabled, warn};
-use std::ops::Not;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 struct JniCallback {
     vm: JavaVM,
@@ -53,82 +59,12 @@ fn jint_to_value_type(value: jint) -> Option<ValueType> {
         6 => Some(ValueType::Auto),
         7 => Some(ValueType::Xor),
         8 => Some(ValueType::Pattern),
+        9 => Some(ValueType::Utf8String),
+        10 => Some(ValueType::Utf16String),
         _ => None,
     }
 }
 
-fn format_value(bytes: &[u8], typ: ValueType) -> String {
-    match typ {
-        ValueType::Byte => {
-            if bytes.len() >= 1 {
-                // 使用有符号类型以正确显示负数
-                format!("{}", bytes[0] as i8)
-            } else {
-                "N/A".to_string()
-            }
-        },
-        ValueType::Word => {
-            if bytes.len() >= 2 {
-                // 使用有符号类型以正确显示负数
-                let value = i16::from_le_bytes([bytes[0], bytes[1]]);
-                format!("{}", value)
-            } else {
-                "N/A".to_string()
-            }
-        },
-        ValueType::Dword | ValueType::Auto | ValueType::Xor => {
-            if bytes.len() >= 4 {
-                // 使用有符号类型以正确显示负数
-                let value = i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
-                format!("{}", value)
-            } else {
-                "N/A".to_string()
-            }
-        },
-        ValueType::Qword => {
-            if bytes.len() >= 8 {
-                // 使用有符号类型以正确显示负数
-                let value = i64::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7]]);
-                format!("{}", value)
-            } else {
-                "N/A".to_string()
-            }
-        },
-        ValueType::Float => {
-            if bytes.len() >= 4 {
-                let value = f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
-                format!("{}", value)
-            } else {
-                "N/A".to_string()
-            }
-        },
-        ValueType::Double => {
-            if bytes.len() >= 8 {
-                let value = f64::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7]]);
-                format!("{}", value)
-            } else {
-                "N/A".to_string()
-            }
-        },
-        ValueType::Pattern => {
-            // Pattern 类型显示十六进制内容，最多显示 16 字节
-            const MAX_DISPLAY_BYTES: usize = 16;
-            let display_len = bytes.len().min(MAX_DISPLAY_BYTES);
-            let hex_str: String = bytes[..display_len]
-                .iter()
-                .map(|b| format!("{:02X}", b))
-                .collect::<Vec<_>>()
-                .join(" ");
-            
-            if bytes.len() > MAX_DISPLAY_BYTES {
-                format!("{}...", hex_str)
-            } else {
-                hex_str
-            }
-        },
-    }
-}
-
 #[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeInitSearchEngine", "(JLjava/lang/String;J)Z")]
 pub fn jni_init_search_engine(mut env: JNIEnv, _class: JObject, memory_buffer_size: jlong, cache_dir: JString, chunk_size: jlong) -> jboolean {
     (|| -> JniResult<jboolean> {
@@ -185,7 +121,11 @@ pub fn jni_clear_shared_buffer(mut env: JNIEnv, _class: JObject) {
 }
 
 /// Starts an async search. Returns immediately. Progress is communicated via the shared buffer.
-#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeStartSearchAsync", "(Ljava/lang/String;I[JZZ)Z")]
+///
+/// `max_results` caps the initial scan, 0 for no cap; a scan that reaches it stops early and
+/// completes with status CompletedTruncated.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeStartSearchAsync", "(Ljava/lang/String;I[JZZZIJ)Z")]
+#[allow(clippy::too_many_arguments)] // 参数由 Java 侧签名决定
 pub fn jni_start_search_async(
     mut env: JNIEnv,
     _class: JObject,
@@ -194,13 +134,20 @@ pub fn jni_start_search_async(
     regions: JLongArray,
     use_deep_search: jboolean,
     keep_results: jboolean,
+    pause_target: jboolean,
+    target_pid: jint,
+    max_results: jlong,
 ) -> jboolean {
     (|| -> JniResult<jboolean> {
         let query: String = env.get_string(&query_str)?.into();
 
         let value_type = jint_to_value_type(default_type).ok_or_else(|| anyhow!("Invalid value type: {}", default_type))?;
 
-        let search_query = parse_search_query(&query, value_type).map_err(|e| anyhow!("Parse error: {}", e))?;
+        // 0 表示绑定的进程
+        let search_query = parse_search_query(&query, value_type)
+            .map_err(|e| anyhow!("Parse error: {}", e))?
+            .with_target_pid((target_pid > 0).then_some(target_pid))
+            .with_max_results(max_results.max(0) as usize);
 
         let regions_len = env.get_array_length(&regions)? as usize;
         if regions_len % 2 != 0 {
@@ -216,7 +163,13 @@ pub fn jni_start_search_async(
             .write()
             .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
 
-        manager.start_search_async(search_query, memory_regions, use_deep_search != JNI_FALSE, keep_results != JNI_FALSE)?;
+        manager.start_search_async(
+            search_query,
+            memory_regions,
+            use_deep_search != JNI_FALSE,
+            keep_results != JNI_FALSE,
+            pause_target != JNI_FALSE,
+        )?;
 
         Ok(JNI_TRUE)
     })()
@@ -224,6 +177,9 @@ pub fn jni_start_search_async(
 }
 
 /// Starts an async refine search. Returns immediately.
+///
+/// With the Pattern type the query is a pattern string like "1A ?B ??", matched at exactly each result address;
+/// an empty query re-matches the pattern of the current pattern results.
 #[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeStartRefineAsync", "(Ljava/lang/String;I)Z")]
 pub fn jni_start_refine_async(mut env: JNIEnv, _class: JObject, query_str: JString, default_type: jint) -> jboolean {
     (|| -> JniResult<jboolean> {
@@ -231,13 +187,22 @@ pub fn jni_start_refine_async(mut env: JNIEnv, _class: JObject, query_str: JStri
 
         let value_type = jint_to_value_type(default_type).ok_or_else(|| anyhow!("Invalid value type: {}", default_type))?;
 
-        let search_query = parse_search_query(&query, value_type).map_err(|e| anyhow!("Parse error: {}", e))?;
-
         let mut manager = SEARCH_ENGINE_MANAGER
             .write()
             .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
 
-        manager.start_refine_async(search_query)?;
+        let search_query = if value_type == ValueType::Pattern {
+            let pattern = if query.trim().is_empty() {
+                manager.current_pattern().map(<[_]>::to_vec).ok_or_else(|| anyhow!("Current results have no pattern to re-match"))?
+            } else {
+                parse_pattern(&query).map_err(|e| anyhow!("Pattern parse error: {}", e))?
+            };
+            SearchQuery::new(vec![SearchValue::Pattern { pattern }], SearchMode::Ordered, 0)
+        } else {
+            parse_search_query(&query, value_type).map_err(|e| anyhow!("Parse error: {}", e))?
+        };
+
+        manager.start_refine_async(search_query, None)?;
 
         Ok(JNI_TRUE)
     })()
@@ -271,6 +236,36 @@ pub fn jni_request_cancel(mut env: JNIEnv, _class: JObject) {
     .or_throw(&mut env)
 }
 
+/// 手动重置：中止正在运行（或已经卡住）的搜索并清空结果，之后可以开始新的搜索
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeForceResetSearchEngine", "()V")]
+pub fn jni_force_reset_search_engine(mut env: JNIEnv, _class: JObject) {
+    (|| -> JniResult<()> {
+        let mut manager = SEARCH_ENGINE_MANAGER
+            .write()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
+
+        manager.force_reset()
+    })()
+    .or_throw(&mut env)
+}
+
+/// 设置看门狗判定搜索卡死的时间（毫秒），0 关闭看门狗，从下一次搜索开始生效
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeSetStallTimeout", "(J)V")]
+pub fn jni_set_stall_timeout(mut env: JNIEnv, _class: JObject, timeout_millis: jlong) {
+    (|| -> JniResult<()> {
+        if timeout_millis < 0 {
+            return Err(anyhow!("Invalid stall timeout: {}", timeout_millis));
+        }
+        let mut manager = SEARCH_ENGINE_MANAGER
+            .write()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
+
+        manager.set_stall_timeout(Duration::from_millis(timeout_millis as u64));
+        Ok(())
+    })()
+    .or_throw(&mut env)
+}
+
 /// Legacy synchronous search method. Kept for backward compatibility.
 #[jni_method(
     70,
@@ -346,132 +341,302 @@ pub fn jni_get_results(mut env: JNIEnv, _class: JObject, start: jint, size: jint
             // Diagnostic log - always print to help debug timing issues
             warn!("[DIAG] jni_get_results: mode={:?}, total_count={}, requesting start={}, size={}", current_mode, total_count, start, size);
         }
-        let mut results = search_manager
-            .get_results(start as usize, size as usize)?
-            .into_iter()
-            .enumerate()
-            .map(|(index, value)| (index, value))
-            .collect::<Vec<(usize, SearchResultItem)>>();
+        // 按当前显示顺序取页（过滤器启用时在过滤视图上分页），nativePosition 为结果的句柄
+        let results = search_manager.get_filtered_results(start as usize, size as usize)?;
 
         if log_enabled!(Level::Debug) {
             warn!("[DIAG] jni_get_results: got {} results", results.len());
         }
-        let filter = search_manager.get_filter();
-        if filter.is_active() {
-            results = results
-                .into_iter()
-                .filter(|(_idx, item)| {
-                    if filter.enable_address_filter {
-                        let addr = match item {
-                            SearchResultItem::Exact(exact) => exact.address,
-                            SearchResultItem::Fuzzy(fuzzy) => fuzzy.address,
-                        };
-                        if addr < filter.address_start || addr > filter.address_end {
-                            return false;
-                        }
-                    }
 
-                    if filter.enable_type_filter && filter.type_ids.is_empty().not() {
-                        let typ = match item {
-                            SearchResultItem::Exact(exact) => exact.typ,
-                            SearchResultItem::Fuzzy(fuzzy) => {
-                                // 先拷贝 packed 字段
-                                let vt = fuzzy.value_type;
-                                vt
-                            },
-                        };
-                        if !filter.type_ids.contains(&typ) {
-                            return false;
-                        }
-                    }
+        new_result_array(&mut env, &search_manager, current_mode, results)
+    })()
+    .or_throw(&mut env)
+}
 
-                    true
-                })
-                .collect::<Vec<(usize, SearchResultItem)>>();
+/// Results whose address is in [start, end), ordered by address, regardless of the filter and display order.
+///
+/// Returns a ResultRange with at most maxCount items; `truncated` is set when the range holds more.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeGetResultsInRange", "(JJI)Lmoe/fuqiuluo/mamu/driver/ResultRange;")]
+pub fn jni_get_results_in_range(mut env: JNIEnv, _class: JObject, start: jlong, end: jlong, max_count: jint) -> jobject {
+    (|| -> JniResult<jobject> {
+        if max_count < 0 {
+            return Err(anyhow!("Invalid max count: {}", max_count));
         }
 
-        // 根据模式选择不同的 Java 类
-        let (class, is_fuzzy) = match current_mode {
-            SearchResultMode::Exact => {
-                (env.find_class("moe/fuqiuluo/mamu/driver/ExactSearchResultItem")?, false)
-            },
-            SearchResultMode::Fuzzy => {
-                (env.find_class("moe/fuqiuluo/mamu/driver/FuzzySearchResultItem")?, true)
-            },
-        };
+        let search_manager = SEARCH_ENGINE_MANAGER
+            .read()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager read lock"))?;
+        let current_mode = search_manager.get_current_mode()?;
+        let range = search_manager.get_results_in_range(start as u64, end as u64, max_count as usize)?;
+
+        let items = new_result_array(&mut env, &search_manager, current_mode, range.items)?;
+        let items = unsafe { JObject::from_raw(items) };
+        // ResultRange(items: Array<SearchResultItem>, truncated: Boolean)
+        let obj = env.new_object(
+            "moe/fuqiuluo/mamu/driver/ResultRange",
+            "([Lmoe/fuqiuluo/mamu/driver/SearchResultItem;Z)V",
+            &[JValue::Object(&items), JValue::Bool(range.truncated as jboolean)],
+        )?;
+        Ok(obj.into_raw())
+    })()
+    .or_throw(&mut env)
+}
+
+/// Results whose address is in [startAddr, endAddr) plus where startAddr falls in the result store.
+///
+/// Returns a ResultWindow with at most `limit` items (nativePosition is the result handle) and
+/// `startIndex`, the store index of the first result at or after startAddr, or the result count
+/// when there is none. Both are found by binary search, so jumping into a large result set does not page from 0.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeGetResultsByAddressRange", "(JJI)Lmoe/fuqiuluo/mamu/driver/ResultWindow;")]
+pub fn jni_get_results_by_address_range(mut env: JNIEnv, _class: JObject, start_addr: jlong, end_addr: jlong, limit: jint) -> jobject {
+    (|| -> JniResult<jobject> {
+        if limit < 0 {
+            return Err(anyhow!("Invalid limit: {}", limit));
+        }
 
-        let array = env.new_object_array(results.len() as jint, &class, JObject::null())?;
+        let search_manager = SEARCH_ENGINE_MANAGER
+            .read()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager read lock"))?;
+        let current_mode = search_manager.get_current_mode()?;
+        let start_index = search_manager.find_index_by_address(start_addr as u64)?;
+        let range = search_manager.get_results_in_range(start_addr as u64, end_addr as u64, limit as usize)?;
+
+        let items = new_result_array(&mut env, &search_manager, current_mode, range.items)?;
+        let items = unsafe { JObject::from_raw(items) };
+        // ResultWindow(items: Array<SearchResultItem>, truncated: Boolean, startIndex: Long)
+        let obj = env.new_object(
+            "moe/fuqiuluo/mamu/driver/ResultWindow",
+            "([Lmoe/fuqiuluo/mamu/driver/SearchResultItem;ZJ)V",
+            &[JValue::Object(&items), JValue::Bool(range.truncated as jboolean), JValue::Long(start_index as jlong)],
+        )?;
+        Ok(obj.into_raw())
+    })()
+    .or_throw(&mut env)
+}
 
+/// 下拉刷新：重新读取当前显示顺序中 [start, start + count) 的精确结果的当前值，更新缓存的值，返回刷新的数量
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeRefreshResultValues", "(II)I")]
+pub fn jni_refresh_result_values(mut env: JNIEnv, _class: JObject, start: jint, count: jint) -> jint {
+    (|| -> JniResult<jint> {
+        if start < 0 || count <= 0 {
+            return Ok(0);
+        }
+        let mut search_manager = SEARCH_ENGINE_MANAGER
+            .write()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
         let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
 
-        // 获取当前 pattern 长度（用于 Pattern 类型）
-        let pattern_len = search_manager.get_current_pattern_len().unwrap_or(0);
-
-        for (i, (native_position, item)) in results.into_iter().enumerate() {
-            let obj = match item {
-                SearchResultItem::Exact(exact) => {
-                    let value_str = {
-                        // Pattern 类型使用 pattern_len，其他类型使用 typ.size()
-                        let size = if exact.typ == ValueType::Pattern {
-                            pattern_len
-                        } else {
-                            exact.typ.size()
-                        };
-                        
-                        if size == 0 {
-                            "N/A".to_string()
-                        } else {
-                            let mut buffer = vec![0u8; size];
-                            if driver_manager.read_memory_unified(exact.address, &mut buffer, None).is_ok() {
-                                format_value(&buffer, exact.typ)
-                            } else {
-                                "N/A".to_string()
-                            }
-                        }
+        let refreshed = search_manager.refresh_result_values(start as usize, count as usize, |addr, buf| {
+            driver_manager.read_memory_with_qos(addr, buf, None, AccessQos::Interactive)
+        })?;
+        Ok(refreshed as jint)
+    })()
+    .or_throw(&mut env)
+}
+
+/// 把 (结果存储中的索引, 结果) 转换为 Java 的 SearchResultItem 数组，优先使用缓存的值并标注指针
+/// nativePosition 为结果的句柄，见 `SearchEngineManager::result_handle`
+fn new_result_array(
+    env: &mut JNIEnv,
+    search_manager: &SearchEngineManager,
+    current_mode: SearchResultMode,
+    results: Vec<(usize, SearchResultItem)>,
+) -> JniResult<jobjectArray> {
+    // 根据模式选择不同的 Java 类
+    let class = match current_mode {
+        SearchResultMode::Exact => env.find_class("moe/fuqiuluo/mamu/driver/ExactSearchResultItem")?,
+        SearchResultMode::Fuzzy => env.find_class("moe/fuqiuluo/mamu/driver/FuzzySearchResultItem")?,
+    };
+
+    let array = env.new_object_array(results.len() as jint, &class, JObject::null())?;
+
+    let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+
+    // 获取当前匹配长度（用于 Pattern 和字符串类型）
+    let pattern_len = search_manager.get_current_pattern_len().unwrap_or(0);
+    // Xor 结果显示解码后的值
+    let xor_key = search_manager.xor_key();
+
+    // 只有存在 Qword 结果时才需要区域映射来判断指针
+    let has_qword = results.iter().any(|(_, item)| match item {
+        SearchResultItem::Exact(exact) => exact.typ == ValueType::Qword,
+        SearchResultItem::Fuzzy(fuzzy) => {
+            let vt = fuzzy.value_type;
+            vt == ValueType::Qword
+        },
+    });
+    let region_map = if has_qword {
+        current_region_map(&driver_manager).ok()
+    } else {
+        None
+    };
+
+    // 所有行复用同一个读取缓冲区和格式化缓冲区
+    let display = search_manager.value_display();
+    let mut buffer = Vec::new();
+    let mut value_str = String::new();
+    for (i, (index, item)) in results.into_iter().enumerate() {
+        // nativePosition 返回稳定句柄，结果集变化后删除 / 保留仍然指向同一个结果
+        let native_position = search_manager.result_handle(index).map_or(-1, |handle| handle as i64);
+        let obj = match item {
+            SearchResultItem::Exact(exact) => {
+                let (is_pointer, pointer_module) = {
+                    // 变长类型使用当前匹配长度，其他类型使用 typ.size()
+                    let size = if exact.typ.is_variable_len() {
+                        pattern_len
+                    } else {
+                        exact.typ.size()
                     };
+                    buffer.resize(size, 0);
+
+                    // 优先使用扫描时缓存的值，没有缓存时读取当前内存
+                    let value_ready = match search_manager.exact_value(exact.address) {
+                        Some(cached) if size > 0 && cached.len() >= size => {
+                            buffer.copy_from_slice(&cached[..size]);
+                            true
+                        },
+                        _ => size > 0 && driver_manager.read_memory_unified(exact.address, &mut buffer, None).is_ok(),
+                    };
+                    if value_ready {
+                        if exact.typ == ValueType::Xor {
+                            xor_key.apply(&mut buffer, exact.address);
+                        }
+                        format_value_with(&mut value_str, &buffer, exact.typ, display);
+                        region_map
+                            .as_ref()
+                            .map(|map| map.pointer_info(exact.typ, &buffer))
+                            .unwrap_or((false, None))
+                    } else {
+                        value_str.clear();
+                        value_str.push_str("N/A");
+                        (false, None)
+                    }
+                };
+
+                let value_jstring = env.new_string(&value_str)?;
+                let module_jstring = match pointer_module {
+                    Some(module) => env.new_string(&module)?.into(),
+                    None => JObject::null(),
+                };
+                let label_jstring = match search_manager.result_label(exact.address, exact.typ) {
+                    Some(label) => env.new_string(label)?.into(),
+                    None => JObject::null(),
+                };
+
+                env.new_object(
+                    &class,
+                    "(JJILjava/lang/String;ZLjava/lang/String;ILjava/lang/String;)V",
+                    &[
+                        JValue::Long(native_position),
+                        JValue::Long(exact.address as i64),
+                        JValue::Int(exact.typ.to_id()),
+                        JValue::Object(&value_jstring),
+                        JValue::Bool(is_pointer as jboolean),
+                        JValue::Object(&module_jstring),
+                        JValue::Int(exact.pass as jint),
+                        JValue::Object(&label_jstring),
+                    ],
+                )?
+            },
+            SearchResultItem::Fuzzy(fuzzy) => {
+                // 先拷贝 packed 字段
+                let fuzzy_addr = fuzzy.address;
+                let mut fuzzy_value = fuzzy.value;
+                let fuzzy_vt = fuzzy.value_type;
+                let fuzzy_pass = fuzzy.pass;
+                
+                if fuzzy_vt == ValueType::Xor {
+                    xor_key.apply(&mut fuzzy_value, fuzzy_addr);
+                }
+                let value_bytes = fuzzy_value.as_ref();
+                if fuzzy_vt.is_variable_len() {
+                    // 长于 8 字节的变长值只保存了摘要，显示当前内存
+                    buffer.resize(pattern_len, 0);
+                    if pattern_len > 0 && driver_manager.read_memory_unified(fuzzy_addr, &mut buffer, None).is_ok() {
+                        format_value_with(&mut value_str, &buffer, fuzzy_vt, display);
+                    } else {
+                        value_str.clear();
+                        value_str.push_str("N/A");
+                    }
+                } else {
+                    format_value_with(&mut value_str, value_bytes, fuzzy_vt, display);
+                }
+
+                let current_value_jstring = env.new_string(&value_str)?;
+                let (is_pointer, pointer_module) = region_map
+                    .as_ref()
+                    .map(|map| map.pointer_info(fuzzy_vt, value_bytes))
+                    .unwrap_or((false, None));
+                let module_jstring = match pointer_module {
+                    Some(module) => env.new_string(&module)?.into(),
+                    None => JObject::null(),
+                };
+                let label_jstring = match search_manager.result_label(fuzzy_addr, fuzzy_vt) {
+                    Some(label) => env.new_string(label)?.into(),
+                    None => JObject::null(),
+                };
+
+                // data class FuzzySearchResultItem(
+                //     override val nativePosition: Long,
+                //     val address: Long,
+                //     val value: String,
+                //     val valueType: Int,
+                //     val isPointer: Boolean,
+                //     val pointerModule: String?,
+                //     val pass: Int,
+                //     val label: String?
+                // ): SearchResultItem
+                env.new_object(
+                    &class,
+                    "(JJLjava/lang/String;IZLjava/lang/String;ILjava/lang/String;)V",
+                    &[
+                        JValue::Long(native_position),
+                        JValue::Long(fuzzy_addr as i64),
+                        JValue::Object(&current_value_jstring),
+                        JValue::Int(fuzzy_vt.to_id()),
+                        JValue::Bool(is_pointer as jboolean),
+                        JValue::Object(&module_jstring),
+                        JValue::Int(fuzzy_pass as jint),
+                        JValue::Object(&label_jstring),
+                    ],
+                )?
+            },
+        };
+        env.set_object_array_element(&array, i as jint, obj)?;
+    }
 
-                    let value_jstring = env.new_string(&value_str)?;
-
-                    env.new_object(
-                        &class,
-                        "(JJILjava/lang/String;)V",
-                        &[
-                            JValue::Long(native_position as i64),
-                            JValue::Long(exact.address as i64),
-                            JValue::Int(exact.typ.to_id()),
-                            JValue::Object(&value_jstring),
-                        ],
-                    )?
-                },
-                SearchResultItem::Fuzzy(fuzzy) => {
-                    // 先拷贝 packed 字段
-                    let fuzzy_addr = fuzzy.address;
-                    let fuzzy_value = fuzzy.value;
-                    let fuzzy_vt = fuzzy.value_type;
-                    
-                    let buffer = fuzzy_value.as_ref();
-                    let current_value_str = format_value(&buffer, fuzzy_vt);
-
-                    let current_value_jstring = env.new_string(&current_value_str)?;
-
-                    // data class FuzzySearchResultItem(
-                    //     override val nativePosition: Long,
-                    //     val address: Long,
-                    //     val value: String,
-                    //     val valueType: Int
-                    // ): SearchResultItem
-                    env.new_object(
-                        &class,
-                        "(JJLjava/lang/String;I)V",
-                        &[
-                            JValue::Long(native_position as i64),
-                            JValue::Long(fuzzy_addr as i64),
-                            JValue::Object(&current_value_jstring),
-                            JValue::Int(fuzzy_vt.to_id()),
-                        ],
-                    )?
-                },
-            };
+    Ok(array.into_raw())
+}
+
+/// Groups the current results by memory region of the bound process.
+///
+/// Groups are ordered by store index; the array index is the group index for nativeGetResultsForGroup.
+/// Results whose region is no longer mapped are grouped per gap with `mapped == false`.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeGetRegionGroups", "()[Lmoe/fuqiuluo/mamu/driver/RegionGroup;")]
+pub fn jni_get_region_groups(mut env: JNIEnv, _class: JObject) -> jobjectArray {
+    (|| -> JniResult<jobjectArray> {
+        let manager = SEARCH_ENGINE_MANAGER
+            .read()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager read lock"))?;
+        let groups = manager.get_region_groups()?;
+
+        let class = env.find_class("moe/fuqiuluo/mamu/driver/RegionGroup")?;
+        let array = env.new_object_array(groups.len() as jint, &class, JObject::null())?;
+        for (i, group) in groups.iter().enumerate() {
+            let name = env.new_string(&group.name)?;
+            // RegionGroup(start: Long, end: Long, name: String, count: Long, firstIndex: Long, mapped: Boolean)
+            let obj = env.new_object(
+                &class,
+                "(JJLjava/lang/String;JJZ)V",
+                &[
+                    JValue::Long(group.start as jlong),
+                    JValue::Long(group.end as jlong),
+                    JValue::Object(&name),
+                    JValue::Long(group.count as jlong),
+                    JValue::Long(group.first_index as jlong),
+                    JValue::Bool(group.mapped as jboolean),
+                ],
+            )?;
             env.set_object_array_element(&array, i as jint, obj)?;
         }
 
@@ -480,6 +645,30 @@ pub fn jni_get_results(mut env: JNIEnv, _class: JObject, start: jint, size: jint
     .or_throw(&mut env)
 }
 
+/// Pages within one region group; `nativePosition` of each item is its handle.
+#[jni_method(
+    70,
+    "moe/fuqiuluo/mamu/driver/SearchEngine",
+    "nativeGetResultsForGroup",
+    "(III)[Lmoe/fuqiuluo/mamu/driver/SearchResultItem;"
+)]
+pub fn jni_get_results_for_group(mut env: JNIEnv, _class: JObject, group_index: jint, start: jint, size: jint) -> jobjectArray {
+    (|| -> JniResult<jobjectArray> {
+        if group_index < 0 || start < 0 || size < 0 {
+            return Err(anyhow!("Invalid group page: group={}, start={}, size={}", group_index, start, size));
+        }
+
+        let search_manager = SEARCH_ENGINE_MANAGER
+            .read()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager read lock"))?;
+        let current_mode = search_manager.get_current_mode()?;
+        let results = search_manager.get_results_for_group(group_index as usize, start as usize, size as usize)?;
+
+        new_result_array(&mut env, &search_manager, current_mode, results)
+    })()
+    .or_throw(&mut env)
+}
+
 #[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeGetTotalResultCount", "()J")]
 pub fn jni_get_total_result_count(mut env: JNIEnv, _class: JObject) -> jlong {
     (|| -> JniResult<jlong> {
@@ -568,7 +757,118 @@ pub fn jni_keep_only_results(mut env: JNIEnv, _class: JObject, indices_array: JI
     .or_throw(&mut env)
 }
 
-#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeSetFilter", "(ZJJZ[I)V")]
+/// 按句柄（SearchResultItem.nativePosition）删除结果，已经不存在的句柄忽略，返回删除的数量
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeRemoveByHandles", "([J)I")]
+pub fn jni_remove_by_handles(mut env: JNIEnv, _class: JObject, handles_array: JLongArray) -> jint {
+    (|| -> JniResult<jint> {
+        let handles = read_handles(&mut env, &handles_array)?;
+
+        let mut manager = SEARCH_ENGINE_MANAGER
+            .write()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
+
+        Ok(manager.remove_by_handles(handles)? as jint)
+    })()
+    .or_throw(&mut env)
+}
+
+/// 只保留句柄对应的结果，返回保留的数量；选中的结果都已不存在时抛出异常
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeKeepOnlyHandles", "([J)I")]
+pub fn jni_keep_only_handles(mut env: JNIEnv, _class: JObject, handles_array: JLongArray) -> jint {
+    (|| -> JniResult<jint> {
+        let handles = read_handles(&mut env, &handles_array)?;
+
+        let mut manager = SEARCH_ENGINE_MANAGER
+            .write()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
+
+        Ok(manager.keep_only_handles(handles)? as jint)
+    })()
+    .or_throw(&mut env)
+}
+
+/// Whether the last refine can be undone (no search is running and the previous result set is kept).
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeCanUndoRefine", "()Z")]
+pub fn jni_can_undo_refine(mut env: JNIEnv, _class: JObject) -> jboolean {
+    (|| -> JniResult<jboolean> {
+        let manager = SEARCH_ENGINE_MANAGER
+            .read()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager read lock"))?;
+
+        Ok(if manager.can_undo_refine() { JNI_TRUE } else { JNI_FALSE })
+    })()
+    .or_throw(&mut env)
+}
+
+/// Restores the result set from before the last refine, returns the restored result count.
+/// Throws if there is nothing to undo or a search is running.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeUndoRefine", "()J")]
+pub fn jni_undo_refine(mut env: JNIEnv, _class: JObject) -> jlong {
+    (|| -> JniResult<jlong> {
+        let mut manager = SEARCH_ENGINE_MANAGER
+            .write()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
+
+        Ok(manager.undo_last_refine()? as jlong)
+    })()
+    .or_throw(&mut env)
+}
+
+/// 给句柄（SearchResultItem.nativePosition）对应的结果设置标签，空字符串删除标签；结果已不存在时抛出异常
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeSetResultLabel", "(JLjava/lang/String;)V")]
+pub fn jni_set_result_label(mut env: JNIEnv, _class: JObject, handle: jlong, label: JString) {
+    (|| -> JniResult<()> {
+        if handle < 0 {
+            return Err(anyhow!("Invalid result handle: {}", handle));
+        }
+        let label: String = env.get_string(&label)?.into();
+
+        let mut manager = SEARCH_ENGINE_MANAGER
+            .write()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
+
+        manager.set_result_label(handle as u64, label)
+    })()
+    .or_throw(&mut env)
+}
+
+/// 句柄对应结果的标签，与 handles 一一对应，没有标签或结果已不存在时为 null
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeGetResultLabels", "([J)[Ljava/lang/String;")]
+pub fn jni_get_result_labels(mut env: JNIEnv, _class: JObject, handles_array: JLongArray) -> jobjectArray {
+    (|| -> JniResult<jobjectArray> {
+        let len = env.get_array_length(&handles_array)? as usize;
+        let mut raw = vec![0i64; len];
+        env.get_long_array_region(&handles_array, 0, &mut raw)?;
+
+        let manager = SEARCH_ENGINE_MANAGER
+            .read()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager read lock"))?;
+        // 负数句柄不对应任何结果，用一个不存在的句柄占位保持一一对应
+        let handles: Vec<u64> = raw.iter().map(|&handle| u64::try_from(handle).unwrap_or(u64::MAX)).collect();
+        let labels = manager.get_result_labels(&handles)?;
+
+        let array = env.new_object_array(len as jint, "java/lang/String", JObject::null())?;
+        for (i, label) in labels.iter().enumerate() {
+            if let Some(label) = label {
+                let label = env.new_string(label)?;
+                env.set_object_array_element(&array, i as jint, label)?;
+            }
+        }
+        Ok(array.into_raw())
+    })()
+    .or_throw(&mut env)
+}
+
+fn read_handles(env: &mut JNIEnv, handles_array: &JLongArray) -> JniResult<Vec<u64>> {
+    let len = env.get_array_length(handles_array)? as usize;
+    let mut handles = vec![0i64; len];
+    env.get_long_array_region(handles_array, 0, &mut handles)?;
+    Ok(handles.into_iter().filter(|&handle| handle >= 0).map(|handle| handle as u64).collect())
+}
+
+/// min_pass / max_pass: 轮次过滤范围（包含两端），负数表示该端不限制
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeSetFilter", "(ZJJZ[III)V")]
+#[allow(clippy::too_many_arguments)] // 参数由 Java 侧签名决定
 pub fn jni_set_filter(
     mut env: JNIEnv,
     _class: JObject,
@@ -577,6 +877,8 @@ pub fn jni_set_filter(
     address_end: jlong,
     enable_type_ids_filter: jboolean,
     type_ids: JIntArray,
+    min_pass: jint,
+    max_pass: jint,
 ) {
     (|| -> JniResult<()> {
         let type_ids_len = env.get_array_length(&type_ids)? as usize;
@@ -594,7 +896,24 @@ pub fn jni_set_filter(
             enable_type_ids_filter != JNI_FALSE,
             type_ids_buf,
         )?;
+        let pass_bound = |pass: jint| (pass >= 0).then(|| pass.min(u8::MAX as jint) as u8);
+        manager.set_pass_filter(pass_bound(min_pass), pass_bound(max_pass));
+
+        Ok(())
+    })()
+    .or_throw(&mut env)
+}
+
+/// Sets the display order of nativeGetResults: 0 = storage order, 1 = oldest pass first.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeSetResultOrder", "(I)V")]
+pub fn jni_set_result_order(mut env: JNIEnv, _class: JObject, order: jint) {
+    (|| -> JniResult<()> {
+        let order = ResultOrder::from_id(order).ok_or_else(|| anyhow!("Invalid result order: {}", order))?;
+        let mut manager = SEARCH_ENGINE_MANAGER
+            .write()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
 
+        manager.set_result_order(order);
         Ok(())
     })()
     .or_throw(&mut env)
@@ -656,60 +975,264 @@ pub fn jni_get_compatibility_mode(mut env: JNIEnv, _class: JObject) -> jboolean
             .read()
             .map_err(|_| anyhow!("Failed to acquire SearchEngineManager read lock"))?;
 
-        Ok(if manager.get_compatibility_mode() { JNI_TRUE } else { JNI_FALSE })
+        Ok(if manager.get_compatibility_mode().requested { JNI_TRUE } else { JNI_FALSE })
     })()
     .or_throw(&mut env)
 }
 
-/// Legacy synchronous refine search method.
-#[jni_method(
-    70,
-    "moe/fuqiuluo/mamu/driver/SearchEngine",
-    "nativeRefineSearch",
-    "(Ljava/lang/String;ILmoe/fuqiuluo/mamu/driver/SearchProgressCallback;)J"
-)]
-pub fn jni_refine_search(mut env: JNIEnv, _class: JObject, query_str: JString, default_type: jint, callback_obj: JObject) -> jlong {
-    (|| -> JniResult<jlong> {
-        let query: String = env.get_string(&query_str)?.into();
+/// Gets the effective compatibility mode state as JSON:
+/// `{"requested":true,"active":false,"deferred":true,"auto_threshold":5000000}`
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeGetCompatibilityState", "()Ljava/lang/String;")]
+pub fn jni_get_compatibility_state(mut env: JNIEnv, _class: JObject) -> jstring {
+    (|| -> JniResult<jstring> {
+        let manager = SEARCH_ENGINE_MANAGER
+            .read()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager read lock"))?;
 
-        let value_type = jint_to_value_type(default_type).ok_or_else(|| anyhow!("Invalid value type: {}", default_type))?;
+        let json = serde_json::to_string(&manager.get_compatibility_mode())?;
+        Ok(env.new_string(&json)?.into_raw())
+    })()
+    .or_throw(&mut env)
+}
 
-        let search_query = parse_search_query(&query, value_type).map_err(|e| anyhow!("Parse error: {}", e))?;
+/// Gets the read statistics of the current or last scan as a JSON object.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeGetSearchStats", "()Ljava/lang/String;")]
+pub fn jni_get_search_stats(mut env: JNIEnv, _class: JObject) -> jstring {
+    (|| -> JniResult<jstring> {
+        let manager = SEARCH_ENGINE_MANAGER
+            .read()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager read lock"))?;
 
-        let callback: Option<Arc<dyn SearchProgressCallback>> = if callback_obj.is_null() {
-            None
-        } else {
-            let vm = env.get_java_vm()?;
-            let global_ref = env.new_global_ref(callback_obj)?;
-            Some(Arc::new(JniCallback { vm, callback: global_ref }))
-        };
+        let json = serde_json::to_string(&manager.get_search_stats())?;
+        Ok(env.new_string(&json)?.into_raw())
+    })()
+    .or_throw(&mut env)
+}
 
+/// Sets the result count above which compatibility mode capture is deferred until a refine.
+/// 0 disables the automatic policy.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeSetCompatAutoThreshold", "(J)V")]
+pub fn jni_set_compat_auto_threshold(mut env: JNIEnv, _class: JObject, threshold: jlong) {
+    (|| -> JniResult<()> {
         let mut manager = SEARCH_ENGINE_MANAGER
             .write()
             .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
 
-        let count = manager.refine_search(&search_query, callback)?;
-
-        Ok(count as jlong)
+        manager.set_compat_auto_threshold(threshold.max(0) as usize);
+        Ok(())
     })()
     .or_throw(&mut env)
 }
 
-/// Legacy method for backward compatibility. Use nativeSetSharedBuffer instead.
-#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeSetProgressBuffer", "(Ljava/nio/ByteBuffer;)Z")]
-pub fn jni_set_progress_buffer(mut env: JNIEnv, _class: JObject, buffer: JObject) -> jboolean {
-    jni_set_shared_buffer(env, _class, buffer)
-}
+/// Estimates a scan over the given regions without starting it.
+/// Returns a ScanEstimate JSON; the first call on a device runs a 16MB probe read.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeEstimateScan", "(I[JZ)Ljava/lang/String;")]
+pub fn jni_estimate_scan(mut env: JNIEnv, _class: JObject, value_type_id: jint, regions: JLongArray, fuzzy: jboolean) -> jstring {
+    (|| -> JniResult<jstring> {
+        let value_type = jint_to_value_type(value_type_id).ok_or_else(|| anyhow!("Invalid value type: {}", value_type_id))?;
 
-/// Legacy method for backward compatibility. Use nativeClearSharedBuffer instead.
-#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeClearProgressBuffer", "()V")]
-pub fn jni_clear_progress_buffer(mut env: JNIEnv, _class: JObject) {
-    jni_clear_shared_buffer(env, _class)
-}
+        let regions_len = env.get_array_length(&regions)? as usize;
+        if !regions_len.is_multiple_of(2) {
+            return Err(anyhow!("Regions array length must be even"));
+        }
 
-/// Adds results from saved addresses. Clears existing results and adds new ones.
-#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeAddResultsFromAddresses", "([J[I)Z")]
-pub fn jni_add_results_from_addresses(mut env: JNIEnv, _class: JObject, addresses_array: JLongArray, types_array: JIntArray) -> jboolean {
+        let mut regions_buf = vec![0i64; regions_len];
+        env.get_long_array_region(&regions, 0, &mut regions_buf)?;
+
+        let memory_regions: Vec<(u64, u64)> = regions_buf.chunks(2).map(|chunk| (chunk[0] as u64, chunk[1] as u64)).collect();
+
+        // 探测读取期间不持有引擎锁，见 SearchEngineManager::estimate_scan
+        let estimate = SearchEngineManager::estimate_scan(value_type, &memory_regions, fuzzy != JNI_FALSE)?;
+        let json = serde_json::to_string(&estimate)?;
+        Ok(env.new_string(&json)?.into_raw())
+    })()
+    .or_throw(&mut env)
+}
+
+/// Sets the disk budget (bytes) and result limit used for scan estimate warnings.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeSetScanLimits", "(JJ)V")]
+pub fn jni_set_scan_limits(mut env: JNIEnv, _class: JObject, disk_budget: jlong, max_results: jlong) {
+    (|| -> JniResult<()> {
+        let mut manager = SEARCH_ENGINE_MANAGER
+            .write()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
+
+        manager.set_scan_limits(disk_budget.max(0) as u64, max_results.max(0) as u64);
+        Ok(())
+    })()
+    .or_throw(&mut env)
+}
+
+/// Enables the opt-in per-region first scan cache.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeSetScanCacheEnabled", "(Z)V")]
+pub fn jni_set_scan_cache_enabled(mut env: JNIEnv, _class: JObject, enabled: jboolean) {
+    (|| -> JniResult<()> {
+        let mut manager = SEARCH_ENGINE_MANAGER
+            .write()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
+
+        manager.set_scan_cache_enabled(enabled != JNI_FALSE);
+        Ok(())
+    })()
+    .or_throw(&mut env)
+}
+
+/// 模糊首次扫描完成后是否把结果按地址整理并去重，默认开启
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeSetSortFuzzyResults", "(Z)V")]
+pub fn jni_set_sort_fuzzy_results(mut env: JNIEnv, _class: JObject, enabled: jboolean) {
+    (|| -> JniResult<()> {
+        let mut manager = SEARCH_ENGINE_MANAGER
+            .write()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
+
+        manager.set_sort_fuzzy_results(enabled != JNI_FALSE);
+        Ok(())
+    })()
+    .or_throw(&mut env)
+}
+
+/// 设置搜索的工作线程数，0 使用全局线程池，从下一次搜索开始生效
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeSetSearchThreadCount", "(I)V")]
+pub fn jni_set_search_thread_count(mut env: JNIEnv, _class: JObject, threads: jint) {
+    (|| -> JniResult<()> {
+        if threads < 0 {
+            return Err(anyhow!("Invalid thread count: {}", threads));
+        }
+        let mut manager = SEARCH_ENGINE_MANAGER
+            .write()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
+
+        manager.set_worker_threads(threads as usize);
+        Ok(())
+    })()
+    .or_throw(&mut env)
+}
+
+/// 搜索的工作线程是否使用后台调度优先级，从下一次搜索开始生效
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeSetSearchBackgroundPriority", "(Z)V")]
+pub fn jni_set_search_background_priority(mut env: JNIEnv, _class: JObject, background: jboolean) {
+    (|| -> JniResult<()> {
+        let mut manager = SEARCH_ENGINE_MANAGER
+            .write()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
+
+        manager.set_background_priority(background != JNI_FALSE);
+        Ok(())
+    })()
+    .or_throw(&mut env)
+}
+
+/// 设置单值改善搜索策略：-1 自动选择，0 逐地址读取，1 重新扫描并归并
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeSetRefineStrategy", "(I)V")]
+pub fn jni_set_refine_strategy(mut env: JNIEnv, _class: JObject, strategy: jint) {
+    (|| -> JniResult<()> {
+        let strategy = match strategy {
+            -1 => None,
+            id => Some(RefineStrategy::from_id(id).ok_or_else(|| anyhow!("Invalid refine strategy: {}", id))?),
+        };
+
+        let mut manager = SEARCH_ENGINE_MANAGER
+            .write()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
+
+        manager.set_refine_strategy(strategy);
+        Ok(())
+    })()
+    .or_throw(&mut env)
+}
+
+/// 设置逐地址改善搜索合并读取的窗口（字节）：相邻结果一次读出，0 关闭合并，其他值限制在 4KB 到 64KB 之间
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeSetRefineReadWindow", "(I)V")]
+pub fn jni_set_refine_read_window(mut env: JNIEnv, _class: JObject, window: jint) {
+    (|| -> JniResult<()> {
+        let mut manager = SEARCH_ENGINE_MANAGER
+            .write()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
+
+        manager.set_refine_read_window(window.max(0) as usize);
+        Ok(())
+    })()
+    .or_throw(&mut env)
+}
+
+/// 设置结果列表中整数值的显示方式：`signed` 非 0 为有符号，`radix` 为 10 或 16。浮点数不受影响
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeSetValueDisplay", "(II)V")]
+pub fn jni_set_value_display(mut env: JNIEnv, _class: JObject, signed: jint, radix: jint) {
+    (|| -> JniResult<()> {
+        let display = ValueDisplay::from_ids(signed, radix).ok_or_else(|| anyhow!("Invalid value display radix: {}", radix))?;
+        let mut manager = SEARCH_ENGINE_MANAGER
+            .write()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
+
+        manager.set_value_display(display);
+        Ok(())
+    })()
+    .or_throw(&mut env)
+}
+
+/// 设置改善搜索逐地址读取时页缓存的页数：同一页在一次改善中只读取一次，0 关闭缓存
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeSetRefineCachePages", "(I)V")]
+pub fn jni_set_refine_cache_pages(mut env: JNIEnv, _class: JObject, pages: jint) {
+    (|| -> JniResult<()> {
+        let mut manager = SEARCH_ENGINE_MANAGER
+            .write()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
+
+        manager.set_refine_cache_pages(pages.max(0) as usize);
+        Ok(())
+    })()
+    .or_throw(&mut env)
+}
+
+/// Legacy synchronous refine search method.
+#[jni_method(
+    70,
+    "moe/fuqiuluo/mamu/driver/SearchEngine",
+    "nativeRefineSearch",
+    "(Ljava/lang/String;ILmoe/fuqiuluo/mamu/driver/SearchProgressCallback;)J"
+)]
+pub fn jni_refine_search(mut env: JNIEnv, _class: JObject, query_str: JString, default_type: jint, callback_obj: JObject) -> jlong {
+    (|| -> JniResult<jlong> {
+        let query: String = env.get_string(&query_str)?.into();
+
+        let value_type = jint_to_value_type(default_type).ok_or_else(|| anyhow!("Invalid value type: {}", default_type))?;
+
+        let search_query = parse_search_query(&query, value_type).map_err(|e| anyhow!("Parse error: {}", e))?;
+
+        let callback: Option<Arc<dyn SearchProgressCallback>> = if callback_obj.is_null() {
+            None
+        } else {
+            let vm = env.get_java_vm()?;
+            let global_ref = env.new_global_ref(callback_obj)?;
+            Some(Arc::new(JniCallback { vm, callback: global_ref }))
+        };
+
+        let mut manager = SEARCH_ENGINE_MANAGER
+            .write()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
+
+        let count = manager.refine_search(&search_query, callback)?;
+
+        Ok(count as jlong)
+    })()
+    .or_throw(&mut env)
+}
+
+/// Legacy method for backward compatibility. Use nativeSetSharedBuffer instead.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeSetProgressBuffer", "(Ljava/nio/ByteBuffer;)Z")]
+pub fn jni_set_progress_buffer(mut env: JNIEnv, _class: JObject, buffer: JObject) -> jboolean {
+    jni_set_shared_buffer(env, _class, buffer)
+}
+
+/// Legacy method for backward compatibility. Use nativeClearSharedBuffer instead.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeClearProgressBuffer", "()V")]
+pub fn jni_clear_progress_buffer(mut env: JNIEnv, _class: JObject) {
+    jni_clear_shared_buffer(env, _class)
+}
+
+/// Adds results from saved addresses. Clears existing results and adds new ones.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeAddResultsFromAddresses", "([J[I)Z")]
+pub fn jni_add_results_from_addresses(mut env: JNIEnv, _class: JObject, addresses_array: JLongArray, types_array: JIntArray) -> jboolean {
     (|| -> JniResult<jboolean> {
         let addr_len = env.get_array_length(&addresses_array)? as usize;
         let type_len = env.get_array_length(&types_array)? as usize;
@@ -757,11 +1280,23 @@ pub fn jni_add_results_from_addresses(mut env: JNIEnv, _class: JObject, addresse
 /// Starts async fuzzy initial search. Records all values in memory regions.
 ///
 /// Parameters:
-/// - value_type: The value type to search for (0=Byte, 1=Word, 2=Dword, 3=Qword, 4=Float, 5=Double)
+/// - value_type: The value type to search for (0=Byte, 1=Word, 2=Dword, 3=Qword, 4=Float, 5=Double, 6=Auto)
+///   Auto records every 4-byte aligned address once and narrows it to Dword, Float or Qword during refines.
+///   Pattern (8) needs keep_results after a pattern search; its results only refine by Changed / Unchanged.
 /// - regions: Array of [start1, end1, start2, end2, ...] memory region pairs
 /// - keep_results: If true and currently in exact mode, convert exact results to fuzzy results
-#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeStartFuzzySearchAsync", "(I[JZ)Z")]
-pub fn jni_start_fuzzy_search_async(mut env: JNIEnv, _class: JObject, value_type_id: jint, regions: JLongArray, keep_results: jboolean) -> jboolean {
+/// - pause_target: If true, stop the bound process until the initial scan finishes
+/// - alignment: Alignment of the recorded addresses, 0 for the value type size
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeStartFuzzySearchAsync", "(I[JZZI)Z")]
+pub fn jni_start_fuzzy_search_async(
+    mut env: JNIEnv,
+    _class: JObject,
+    value_type_id: jint,
+    regions: JLongArray,
+    keep_results: jboolean,
+    pause_target: jboolean,
+    alignment: jint,
+) -> jboolean {
     (|| -> JniResult<jboolean> {
         let value_type = jint_to_value_type(value_type_id).ok_or_else(|| anyhow!("Invalid value type: {}", value_type_id))?;
 
@@ -779,7 +1314,29 @@ pub fn jni_start_fuzzy_search_async(mut env: JNIEnv, _class: JObject, value_type
             .write()
             .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
 
-        manager.start_fuzzy_search_async(value_type, memory_regions, keep_results != JNI_FALSE)?;
+        if alignment < 0 {
+            return Err(anyhow!("Invalid alignment: {}", alignment));
+        }
+        let alignment = (alignment > 0).then_some(alignment as usize);
+        manager.start_fuzzy_search_async(value_type, alignment, memory_regions, keep_results != JNI_FALSE, pause_target != JNI_FALSE)?;
+
+        Ok(JNI_TRUE)
+    })()
+    .or_throw(&mut env)
+}
+
+/// Resumes a cancelled fuzzy initial scan (status PartiallyCompleted), scanning only the regions it did not finish.
+///
+/// Parameters:
+/// - pause_target: If true, stop the bound process until the scan finishes
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeResumeFuzzySearchAsync", "(Z)Z")]
+pub fn jni_resume_fuzzy_search_async(mut env: JNIEnv, _class: JObject, pause_target: jboolean) -> jboolean {
+    (|| -> JniResult<jboolean> {
+        let mut manager = SEARCH_ENGINE_MANAGER
+            .write()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
+
+        manager.resume_fuzzy_search_async(pause_target != JNI_FALSE)?;
 
         Ok(JNI_TRUE)
     })()
@@ -787,6 +1344,8 @@ pub fn jni_start_fuzzy_search_async(mut env: JNIEnv, _class: JObject, value_type
 }
 
 /// Starts async fuzzy refine search with a condition.
+/// In exact mode the results stay exact and are compared against the values read at search time; when
+/// none are available the call only captures a snapshot and ends with `SearchStatus::SnapshotOnly`.
 ///
 /// Parameters:
 /// - condition_id: The fuzzy condition type
@@ -818,7 +1377,7 @@ pub fn jni_start_fuzzy_refine_async(mut env: JNIEnv, _class: JObject, condition_
             .write()
             .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
 
-        manager.start_fuzzy_refine_async(condition)?;
+        manager.start_condition_refine_async(condition)?;
 
         Ok(JNI_TRUE)
     })()
@@ -826,6 +1385,280 @@ pub fn jni_start_fuzzy_refine_async(mut env: JNIEnv, _class: JObject, condition_
 }
 
 
+/// Starts async fuzzy refine against an earlier result generation.
+///
+/// Parameters:
+/// - generation_id: Id from nativeListResultGenerations
+/// - condition_id / param1 / param2: Same as nativeStartFuzzyRefineAsync
+/// - keep_missing: Keep results that do not exist in the generation (their values are refreshed)
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeStartFuzzyRefineAgainstGeneration", "(IIJJZ)Z")]
+pub fn jni_start_fuzzy_refine_against_generation(
+    mut env: JNIEnv,
+    _class: JObject,
+    generation_id: jint,
+    condition_id: jint,
+    param1: jlong,
+    param2: jlong,
+    keep_missing: jboolean,
+) -> jboolean {
+    use crate::search::types::FuzzyCondition;
+
+    (|| -> JniResult<jboolean> {
+        let condition = FuzzyCondition::from_id(condition_id, param1, param2).ok_or_else(|| anyhow!("Invalid fuzzy condition id: {}", condition_id))?;
+
+        if condition.is_initial() {
+            return Err(anyhow!("Cannot use Initial condition for refine search"));
+        }
+
+        let mut manager = SEARCH_ENGINE_MANAGER
+            .write()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
+
+        manager.start_fuzzy_refine_against_generation(generation_id as u32, condition, keep_missing != JNI_FALSE)?;
+
+        Ok(JNI_TRUE)
+    })()
+    .or_throw(&mut env)
+}
+
+/// Starts async "unchanged for N ms" refine on the current fuzzy results.
+///
+/// Parameters:
+/// - duration_ms: Observation window
+/// - sample_ms: Sampling interval, changes shorter than this may be missed
+///
+/// Progress: `regions_done` is elapsed milliseconds, `found_count` the remaining results.
+/// Cancelling keeps the results filtered so far.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeRefineStableFor", "(JJ)Z")]
+pub fn jni_refine_stable_for(mut env: JNIEnv, _class: JObject, duration_ms: jlong, sample_ms: jlong) -> jboolean {
+    (|| -> JniResult<jboolean> {
+        if duration_ms <= 0 || sample_ms <= 0 {
+            return Err(anyhow!("Invalid stable refine window: duration={}ms, sample={}ms", duration_ms, sample_ms));
+        }
+
+        let mut manager = SEARCH_ENGINE_MANAGER
+            .write()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
+
+        manager.refine_stable_for(duration_ms as u64, sample_ms as u64)?;
+
+        Ok(JNI_TRUE)
+    })()
+    .or_throw(&mut env)
+}
+
+/// Imports a GameGuardian saved list (text variant) from `path`.
+///
+/// Replaces the current results with the imported addresses; frozen entries are added to
+/// FreezeManager paused. Returns a JSON report:
+/// `{"imported":3,"skipped":1,"frozen_created":1,"warnings":["line 4: ..."],"labels":[{"address":123,"name":"Coins"}]}`
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeImportGGSavedList", "(Ljava/lang/String;)Ljava/lang/String;")]
+pub fn jni_import_gg_saved_list(mut env: JNIEnv, _class: JObject, path: JString) -> jstring {
+    (|| -> JniResult<jstring> {
+        let path: String = env.get_string(&path)?.into();
+        let report = import_gg_saved_list(&path)?;
+        let json = serde_json::to_string(&report)?;
+        Ok(env.new_string(&json)?.into_raw())
+    })()
+    .or_throw(&mut env)
+}
+
+/// Exports the current result set, its mode and pattern length to a binary file at `path`.
+/// Returns the number of exported results.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeExportResults", "(Ljava/lang/String;)J")]
+pub fn jni_export_results(mut env: JNIEnv, _class: JObject, path: JString) -> jlong {
+    (|| -> JniResult<jlong> {
+        let path: String = env.get_string(&path)?.into();
+        let manager = SEARCH_ENGINE_MANAGER
+            .read()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager read lock"))?;
+
+        Ok(manager.export_results(PathBuf::from(path))? as jlong)
+    })()
+    .or_throw(&mut env)
+}
+
+/// Exports the current result set as text to `path`: `format` 0 is CSV, 1 is JSON Lines.
+/// Each row has the index, hex address and value type id, plus the stored value for fuzzy results.
+/// Returns the number of rows written.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeExportResults", "(Ljava/lang/String;I)J")]
+pub fn jni_export_results_text(mut env: JNIEnv, _class: JObject, path: JString, format: jint) -> jlong {
+    (|| -> JniResult<jlong> {
+        let path: String = env.get_string(&path)?.into();
+        let format = ResultExportFormat::from_id(format).ok_or_else(|| anyhow!("Invalid export format: {}", format))?;
+        let manager = SEARCH_ENGINE_MANAGER
+            .read()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager read lock"))?;
+
+        Ok(manager.export_results_text(PathBuf::from(path), format)? as jlong)
+    })()
+    .or_throw(&mut env)
+}
+
+/// Replaces the current results with a file written by `nativeExportResults`, switching the
+/// result mode if the file was exported in the other one. Returns the number of imported results.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeImportResults", "(Ljava/lang/String;)J")]
+pub fn jni_import_results(mut env: JNIEnv, _class: JObject, path: JString) -> jlong {
+    (|| -> JniResult<jlong> {
+        let path: String = env.get_string(&path)?.into();
+        let mut manager = SEARCH_ENGINE_MANAGER
+            .write()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
+
+        Ok(manager.import_results(PathBuf::from(path))? as jlong)
+    })()
+    .or_throw(&mut env)
+}
+
+/// Saves the current search session (results, result mode, pattern length and compatibility mode state)
+/// to a directory; relative paths go under `sessions` in the cache dir. Returns the number of saved results.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeSaveSession", "(Ljava/lang/String;)J")]
+pub fn jni_save_session(mut env: JNIEnv, _class: JObject, path: JString) -> jlong {
+    (|| -> JniResult<jlong> {
+        let path: String = env.get_string(&path)?.into();
+        let manager = SEARCH_ENGINE_MANAGER
+            .read()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager read lock"))?;
+
+        Ok(manager.save_session(PathBuf::from(path))? as jlong)
+    })()
+    .or_throw(&mut env)
+}
+
+/// Replaces the current results with a session written by `nativeSaveSession` and restores its
+/// result mode, pattern length and compatibility mode state. Returns the number of loaded results.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeLoadSession", "(Ljava/lang/String;)J")]
+pub fn jni_load_session(mut env: JNIEnv, _class: JObject, path: JString) -> jlong {
+    (|| -> JniResult<jlong> {
+        let path: String = env.get_string(&path)?.into();
+        let mut manager = SEARCH_ENGINE_MANAGER
+            .write()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
+
+        Ok(manager.load_session(PathBuf::from(path))? as jlong)
+    })()
+    .or_throw(&mut env)
+}
+
+/// Moves results recorded against `module` loaded at `old_base` to `new_base`, e.g. after importing
+/// a result file from an earlier run of the game. Returns the number of rebased results.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeRebaseResults", "(Ljava/lang/String;JJ)J")]
+pub fn jni_rebase_results(mut env: JNIEnv, _class: JObject, module_name: JString, old_base: jlong, new_base: jlong) -> jlong {
+    (|| -> JniResult<jlong> {
+        let module_name: String = env.get_string(&module_name)?.into();
+        let mut manager = SEARCH_ENGINE_MANAGER
+            .write()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
+
+        Ok(manager.rebase_results(&module_name, old_base as u64, new_base as u64)? as jlong)
+    })()
+    .or_throw(&mut env)
+}
+
+/// Lists recorded fuzzy result generations as a JSON array:
+/// `[{"id":1,"timestamp":1700000000000,"count":123,"condition":"Initial"}, ...]`
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeListResultGenerations", "()Ljava/lang/String;")]
+pub fn jni_list_result_generations(mut env: JNIEnv, _class: JObject) -> jstring {
+    (|| -> JniResult<jstring> {
+        let manager = SEARCH_ENGINE_MANAGER
+            .read()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager read lock"))?;
+
+        let json = serde_json::to_string(&manager.list_result_generations()?)?;
+        Ok(env.new_string(&json)?.into_raw())
+    })()
+    .or_throw(&mut env)
+}
+
+/// Verifies the mmap-backed result store of the current mode against its checksum manifest.
+///
+/// Returns JSON: `{"mode":"Exact","store":{"ok":true,"verified_records":131072,"unchecked_records":12,...},"recovery":null}`.
+/// `recovery` is the report of the leftover store adopted at init after an unclean shutdown.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeVerifyResultStore", "()Ljava/lang/String;")]
+pub fn jni_verify_result_store(mut env: JNIEnv, _class: JObject) -> jstring {
+    (|| -> JniResult<jstring> {
+        let manager = SEARCH_ENGINE_MANAGER
+            .read()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager read lock"))?;
+
+        let json = serde_json::to_string(&manager.verify_result_store()?)?;
+        Ok(env.new_string(&json)?.into_raw())
+    })()
+    .or_throw(&mut env)
+}
+
+/// Gets our own RSS and the memory pressure limits consulted by scans.
+///
+/// Returns JSON: `{"rss":734003200,"peak_rss":901775360,"soft_limit":1288490188,"hard_limit":1932735283,"level":"Normal"}`.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeGetProcessMemoryStats", "()Ljava/lang/String;")]
+pub fn jni_get_process_memory_stats(mut env: JNIEnv, _class: JObject) -> jstring {
+    (|| -> JniResult<jstring> {
+        MEMORY_GUARD.sample();
+        let json = serde_json::to_string(&MEMORY_GUARD.stats())?;
+        Ok(env.new_string(&json)?.into_raw())
+    })()
+    .or_throw(&mut env)
+}
+
+/// Sets the memory pressure limits in bytes: above `soft_limit` scans stream results to the store
+/// and shrink their buffers, above `hard_limit` they stop starting new regions.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeSetMemoryLimits", "(JJ)Z")]
+pub fn jni_set_memory_limits(mut env: JNIEnv, _class: JObject, soft_limit: jlong, hard_limit: jlong) -> jboolean {
+    (|| -> JniResult<jboolean> {
+        if soft_limit <= 0 || hard_limit <= 0 {
+            return Err(anyhow!("Invalid memory limits: soft={} hard={}", soft_limit, hard_limit));
+        }
+        MEMORY_GUARD.set_limits(soft_limit as u64, hard_limit as u64)?;
+        Ok(JNI_TRUE)
+    })()
+    .or_throw(&mut env)
+}
+
+/// Dereferences a Qword result for click-through navigation.
+///
+/// Re-reads the pointer at the result address (the value may have changed since the scan)
+/// and returns `[status, target]`, status codes see `PointerStatus`:
+/// 0=valid, 1=not a Qword, 2=read failed, 3=target unmapped, 4=result no longer exists.
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeGetPointerTarget", "(J)[J")]
+pub fn jni_get_pointer_target(mut env: JNIEnv, _class: JObject, handle: jlong) -> jlongArray {
+    (|| -> JniResult<jlongArray> {
+        let search_manager = SEARCH_ENGINE_MANAGER
+            .read()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager read lock"))?;
+
+        let item = if handle < 0 {
+            None
+        } else {
+            search_manager.get_result_by_handle(handle as u64)?
+        };
+        drop(search_manager);
+
+        let (status, target) = match item {
+            None => (PointerStatus::InvalidIndex, 0),
+            Some(item) => {
+                let (address, value_type) = match item {
+                    SearchResultItem::Exact(exact) => (exact.address, exact.typ),
+                    SearchResultItem::Fuzzy(fuzzy) => {
+                        let vt = fuzzy.value_type;
+                        (fuzzy.address, vt)
+                    },
+                };
+
+                let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+                let region_map = current_region_map(&driver_manager)?;
+                region_map.resolve_pointer(address, value_type, |addr, buf| {
+                    driver_manager.read_memory_with_qos(addr, buf, None, AccessQos::Interactive)
+                })
+            },
+        };
+
+        let array = env.new_long_array(2)?;
+        env.set_long_array_region(&array, 0, &[status as i32 as jlong, target as jlong])?;
+        Ok(array.into_raw())
+    })()
+    .or_throw(&mut env)
+}
+
 /// Starts async pattern search.
 /// 
 /// Parameters:
@@ -870,6 +1703,55 @@ pub fn jni_start_pattern_search_async(
     .or_throw(&mut env)
 }
 
+/// Starts async pattern replace: patches every match of the pattern.
+///
+/// Parameters:
+/// - pattern: Pattern string like "1A 2B ?C D? ?? FF"
+/// - replacement: Replacement string like "90 ?? 1F", "??" keeps the original byte; must not be longer than the pattern
+/// - regions: Array of [start1, end1, start2, end2, ...] memory region pairs
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeStartPatternReplaceAsync", "(Ljava/lang/String;Ljava/lang/String;[J)Z")]
+pub fn jni_start_pattern_replace_async(
+    mut env: JNIEnv,
+    _class: JObject,
+    pattern_str: JString,
+    replacement_str: JString,
+    regions: JLongArray,
+) -> jboolean {
+    use crate::search::{parse_pattern, parse_replacement};
+
+    (|| -> JniResult<jboolean> {
+        let pattern_input: String = env.get_string(&pattern_str)?.into();
+        let replacement_input: String = env.get_string(&replacement_str)?.into();
+
+        let pattern = parse_pattern(&pattern_input)
+            .map_err(|e| anyhow!("Pattern parse error: {}", e))?;
+        let replacement = parse_replacement(&replacement_input)
+            .map_err(|e| anyhow!("Replacement parse error: {}", e))?;
+
+        let regions_len = env.get_array_length(&regions)? as usize;
+        if !regions_len.is_multiple_of(2) {
+            return Err(anyhow!("Regions array length must be even"));
+        }
+
+        let mut regions_buf = vec![0i64; regions_len];
+        env.get_long_array_region(&regions, 0, &mut regions_buf)?;
+
+        let memory_regions: Vec<(u64, u64)> = regions_buf
+            .chunks(2)
+            .map(|chunk| (chunk[0] as u64, chunk[1] as u64))
+            .collect();
+
+        let mut manager = SEARCH_ENGINE_MANAGER
+            .write()
+            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
+
+        manager.start_pattern_replace_async(pattern, replacement, memory_regions)?;
+
+        Ok(JNI_TRUE)
+    })()
+    .or_throw(&mut env)
+}
+
 /// Gets the current pattern length (for UI display).
 /// Returns -1 if no pattern search has been performed.
 #[jni_method(70, "moe/fuqiuluo/mamu/driver/SearchEngine", "nativeGetCurrentPatternLen", "()I")]
@@ -0,0 +1,153 @@
+//! JNI methods for WatchManager
+
+use jni::JNIEnv;
+use jni::objects::{JIntArray, JLongArray, JObject};
+use jni::sys::{JNI_FALSE, JNI_TRUE, jboolean, jint, jlong, jlongArray, jstring};
+use jni_macro::jni_method;
+use log::error;
+use serde::Serialize;
+
+use crate::core::globals::{TOKIO_RUNTIME, WATCH_MANAGER};
+use crate::core::watch_manager::{DEFAULT_WATCH_HISTORY, DEFAULT_WATCH_INTERVAL_MS};
+use crate::search::format::format_value;
+use crate::search::ValueType;
+
+/// 一次采样（JSON）
+#[derive(Serialize)]
+struct WatchPoint {
+    timestamp_ms: u64,
+    value: String,
+}
+
+/// 观察一组地址并启动采样，替换之前观察的地址；intervalMs / historySize 不大于 0 时使用默认值（100ms / 300）
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/WatchManager", "nativeStartWatch", "([J[III)Z")]
+pub fn jni_watch_start(env: JNIEnv, _obj: JObject, addrs: JLongArray, types: JIntArray, interval_ms: jint, history_size: jint) -> jboolean {
+    let len = match (env.get_array_length(&addrs), env.get_array_length(&types)) {
+        (Ok(addr_len), Ok(type_len)) if addr_len == type_len => addr_len as usize,
+        _ => {
+            error!("WatchManager JNI: 地址和类型数组长度不一致");
+            return JNI_FALSE;
+        },
+    };
+
+    let mut addr_buf = vec![0i64; len];
+    let mut type_buf = vec![0i32; len];
+    if let Err(e) = env
+        .get_long_array_region(&addrs, 0, &mut addr_buf)
+        .and_then(|_| env.get_int_array_region(&types, 0, &mut type_buf))
+    {
+        error!("WatchManager JNI: 读取数组失败: {}", e);
+        return JNI_FALSE;
+    }
+
+    let mut targets = Vec::with_capacity(len);
+    for (&addr, &type_id) in addr_buf.iter().zip(&type_buf) {
+        match ValueType::from_id(type_id) {
+            Some(value_type) if !value_type.is_variable_len() => targets.push((addr as u64, value_type)),
+            _ => {
+                error!("WatchManager JNI: 不支持的值类型 {}", type_id);
+                return JNI_FALSE;
+            },
+        }
+    }
+
+    let interval_ms = if interval_ms > 0 { interval_ms as u64 } else { DEFAULT_WATCH_INTERVAL_MS };
+    let history_size = if history_size > 0 { history_size as usize } else { DEFAULT_WATCH_HISTORY };
+
+    let _guard = TOKIO_RUNTIME.enter();
+    match WATCH_MANAGER.write() {
+        Ok(mut manager) => {
+            manager.start(&targets, interval_ms, history_size);
+            JNI_TRUE
+        },
+        Err(e) => {
+            error!("WatchManager JNI: 无法获取写锁: {}", e);
+            JNI_FALSE
+        },
+    }
+}
+
+/// 停止采样并清空观察的地址
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/WatchManager", "nativeStopWatch", "()V")]
+pub fn jni_watch_stop(_env: JNIEnv, _obj: JObject) {
+    match WATCH_MANAGER.write() {
+        Ok(mut manager) => {
+            manager.stop();
+        },
+        Err(e) => {
+            error!("WatchManager JNI: 无法获取写锁: {}", e);
+        },
+    }
+}
+
+/// 地址的采样历史，返回 JSON 数组 [{timestamp_ms, value}]（从旧到新）；地址没有在观察或已被移除时返回 null
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/WatchManager", "nativeGetWatchHistory", "(J)Ljava/lang/String;")]
+pub fn jni_watch_get_history(env: JNIEnv, _obj: JObject, address: jlong) -> jstring {
+    let history = match WATCH_MANAGER.read() {
+        Ok(manager) => manager.get_history(address as u64),
+        Err(e) => {
+            error!("WatchManager JNI: 无法获取读锁: {}", e);
+            None
+        },
+    };
+    let Some((value_type, samples)) = history else {
+        return std::ptr::null_mut();
+    };
+
+    let points: Vec<WatchPoint> = samples
+        .iter()
+        .map(|sample| {
+            let mut value = String::new();
+            format_value(&mut value, &sample.bytes, value_type);
+            WatchPoint {
+                timestamp_ms: sample.timestamp_ms,
+                value,
+            }
+        })
+        .collect();
+    let json = serde_json::to_string(&points).unwrap_or_else(|_| "[]".to_string());
+
+    match env.new_string(json) {
+        Ok(s) => s.into_raw(),
+        Err(e) => {
+            error!("WatchManager JNI: 创建字符串失败: {}", e);
+            std::ptr::null_mut()
+        },
+    }
+}
+
+/// 正在观察的地址（连续读取失败的地址会被移除）
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/WatchManager", "nativeGetWatchedAddresses", "()[J")]
+pub fn jni_watch_get_addresses(env: JNIEnv, _obj: JObject) -> jlongArray {
+    let addresses: Vec<jlong> = match WATCH_MANAGER.read() {
+        Ok(manager) => manager.get_watched_addresses().into_iter().map(|addr| addr as jlong).collect(),
+        Err(e) => {
+            error!("WatchManager JNI: 无法获取读锁: {}", e);
+            Vec::new()
+        },
+    };
+
+    let result = env
+        .new_long_array(addresses.len() as i32)
+        .and_then(|array| env.set_long_array_region(&array, 0, &addresses).map(|_| array));
+    match result {
+        Ok(array) => array.into_raw(),
+        Err(e) => {
+            error!("WatchManager JNI: 创建数组失败: {}", e);
+            std::ptr::null_mut()
+        },
+    }
+}
+
+/// 是否正在采样
+#[jni_method(70, "moe/fuqiuluo/mamu/driver/WatchManager", "nativeIsWatching", "()Z")]
+pub fn jni_watch_is_running(_env: JNIEnv, _obj: JObject) -> jboolean {
+    match WATCH_MANAGER.read() {
+        Ok(manager) if manager.is_running() => JNI_TRUE,
+        Ok(_) => JNI_FALSE,
+        Err(e) => {
+            error!("WatchManager JNI: 无法获取读锁: {}", e);
+            JNI_FALSE
+        },
+    }
+}
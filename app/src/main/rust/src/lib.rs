@@ -1,7 +1,10 @@
 #![allow(non_snake_case)]
+pub mod control;
 pub mod core;
+pub mod diagnostics;
 pub mod disasm;
 pub mod ext;
+pub mod import_export;
 pub mod jni_interface;
 pub mod pointer_scan;
 pub mod search;
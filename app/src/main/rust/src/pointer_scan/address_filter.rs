@@ -0,0 +1,116 @@
+//! Phase 1 指针候选的地址空间过滤
+//!
+//! 扫描区域来自 Kotlin，静态模块的区域有时不完整（真实的指针被拒绝），范围过宽时又会放过
+//! 恰好落在范围内的普通整数。开启 `live_vma_check` 时，候选指针的目标改为按扫描开始时通过驱动查询的
+//! 实时 VMA 列表判断；开启 `require_present_pages` 时再对目标页抽样做地址翻译，丢弃目标页不在内存中
+//! （已换出或从未访问）的候选。
+
+use crate::core::DriverManager;
+use crate::core::region_map::RegionMap;
+use crate::pointer_scan::types::PointerData;
+use crate::wuwa::MEM_READABLE;
+use anyhow::Result;
+use rayon::prelude::*;
+
+/// 每次扫描最多翻译的目标页数，目标页更多时均匀抽样，未抽到的页不过滤
+pub const MAX_PRESENCE_PROBES: usize = 1 << 16;
+
+/// 每个过滤器拒绝的候选数
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FilterStats {
+    /// 在扫描区域内、但不在任何可读的实时 VMA 内
+    pub outside_vma: usize,
+    /// 抽样翻译时目标页不在内存中
+    pub not_present: usize,
+}
+
+/// 通过驱动查询绑定进程的实时 VMA，返回按起始地址排序并合并后的可读范围
+pub fn live_vma_ranges(driver_manager: &DriverManager) -> Result<Vec<(u64, u64)>> {
+    let map = RegionMap::query(driver_manager, driver_manager.get_bound_pid())?;
+    let mut ranges: Vec<(u64, u64)> = Vec::new();
+    for region in map.regions().iter().filter(|r| r.type_ & MEM_READABLE != 0) {
+        match ranges.last_mut() {
+            Some(last) if region.start <= last.1 => last.1 = last.1.max(region.end),
+            _ => ranges.push((region.start, region.end)),
+        }
+    }
+    Ok(ranges)
+}
+
+/// 目标页是否在内存中：先用硬件 AT S1E0R，失败时用软件页表遍历确认
+pub fn is_page_present(driver_manager: &DriverManager, page: u64) -> bool {
+    let Some(driver) = driver_manager.get_driver() else {
+        return true;
+    };
+    let pid = driver_manager.get_bound_pid();
+    driver.at_s1e0r(pid, page as usize).is_ok() || driver.addr_translate(pid, page as usize).is_ok()
+}
+
+/// 抽样检查指针目标页是否在内存中，删除指向缺页的指针，返回删除数
+///
+/// 不同的目标页去重后最多检查 `max_probes` 个（均匀抽样），`is_present` 在 rayon 线程上并行调用。
+pub fn retain_present_targets<P>(pointers: &mut Vec<PointerData>, page_size: u64, max_probes: usize, is_present: P) -> usize
+where
+    P: Fn(u64) -> bool + Sync,
+{
+    let mask = !(page_size - 1);
+    let mut pages: Vec<u64> = pointers.iter().map(|p| p.value & mask).collect();
+    pages.par_sort_unstable();
+    pages.dedup();
+    if pages.is_empty() {
+        return 0;
+    }
+
+    let stride = pages.len().div_ceil(max_probes.max(1));
+    // 抽样后的页仍然有序，可以二分查找
+    let missing: Vec<u64> = pages.par_iter().step_by(stride).copied().filter(|&page| !is_present(page)).collect();
+    if missing.is_empty() {
+        return 0;
+    }
+
+    let before = pointers.len();
+    pointers.retain(|p| missing.binary_search(&(p.value & mask)).is_err());
+    before - pointers.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const PAGE: u64 = 0x1000;
+
+    fn pointers(values: &[u64]) -> Vec<PointerData> {
+        values.iter().enumerate().map(|(i, &value)| PointerData::new(0x1000 + i as u64 * 8, value)).collect()
+    }
+
+    #[test]
+    fn test_drops_pointers_into_missing_pages() {
+        let mut list = pointers(&[0x7000_0010, 0x7000_0FF8, 0x7000_1000, 0x7000_2008, 0x7000_2010]);
+        let probes = AtomicUsize::new(0);
+        let removed = retain_present_targets(&mut list, PAGE, MAX_PRESENCE_PROBES, |page| {
+            probes.fetch_add(1, Ordering::Relaxed);
+            page != 0x7000_2000
+        });
+
+        assert_eq!(removed, 2);
+        assert_eq!(list.iter().map(|p| p.value).collect::<Vec<_>>(), vec![0x7000_0010, 0x7000_0FF8, 0x7000_1000]);
+        // 同一页只检查一次
+        assert_eq!(probes.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn test_only_sampled_pages_are_checked() {
+        // 8 个目标页，最多检查 4 个：每隔一页抽一页，未抽到的缺页保留
+        let values: Vec<u64> = (0..8).map(|i| 0x7000_0000 + i * PAGE).collect();
+        let mut list = pointers(&values);
+        let removed = retain_present_targets(&mut list, PAGE, 4, |_| false);
+
+        assert_eq!(removed, 4);
+        let kept: Vec<u64> = list.iter().map(|p| p.value).collect();
+        assert_eq!(kept, vec![0x7000_1000, 0x7000_3000, 0x7000_5000, 0x7000_7000]);
+
+        let mut empty = Vec::new();
+        assert_eq!(retain_present_targets(&mut empty, PAGE, 4, |_| false), 0);
+    }
+}
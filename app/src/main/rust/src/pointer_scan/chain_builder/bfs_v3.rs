@@ -6,10 +6,11 @@
 //! - 前缀和树优化 O(1) 链计数
 //! - &str prefix 拼接替代 Vec<String> clone
 
+use std::borrow::Cow;
 use std::cmp::min;
 use std::fs::File;
-use std::io::{BufWriter, Write};
-use std::path::PathBuf;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Instant;
@@ -19,21 +20,33 @@ use log::{debug, info, log_enabled, warn, Level};
 use rayon::prelude::*;
 
 use crate::core::globals::PAGE_SIZE;
-use crate::core::DRIVER_MANAGER;
+use crate::core::{AccessQos, DRIVER_MANAGER};
+use crate::pointer_scan::address_filter::{
+    is_page_present, live_vma_ranges, retain_present_targets, FilterStats, MAX_PRESENCE_PROBES,
+};
+use crate::pointer_scan::chain_index::ChainIndexWriter;
 use crate::pointer_scan::mapqueue_v2::MapQueue;
+use crate::pointer_scan::samples::{write_chain, ChainSample, ChainSampler};
 use crate::pointer_scan::scanner::ScanRegion;
 use crate::pointer_scan::types::{
-    ChainInfo, PointerData, PointerDir, PointerRange,
+    ChainInfo, ChainOutputFormat, PointerData, PointerDir, PointerRange,
     PointerScanConfig, VmAreaData, VmStaticData,
 };
+use crate::pointer_scan::validate::{resolve_address, ModuleBases};
 use crate::wuwa::PageStatusBitmap;
 
 /// 每层最大候选数，防止内存爆炸
 const MAX_CANDIDATES_PER_LAYER: usize = 5_000_000;
 
+/// MapQueue 数据不超过该大小时留在内存中，小规模扫描不创建临时文件
+const QUEUE_MEMORY_THRESHOLD: usize = 8 * 1024 * 1024;
+
 /// Phase 1 读取分块大小
 const CHUNK_SIZE: usize = 512 * 1024;
 
+/// 重新扫描时每批读取并验证的链数
+const RESCAN_BATCH: usize = 64 * 1024;
+
 /// 进度回调的阶段标识
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ProgressPhase {
@@ -58,6 +71,8 @@ pub struct BfsV3Scanner {
     config: PointerScanConfig,
     regions: Vec<ScanRegion>,
     static_modules: Vec<VmStaticData>,
+    /// Phase 2 中找到的链提交到这里抽样，扫描结束前即可查看
+    sampler: Option<Arc<ChainSampler>>,
 }
 
 impl BfsV3Scanner {
@@ -66,10 +81,15 @@ impl BfsV3Scanner {
         regions: Vec<ScanRegion>,
         static_modules: Vec<VmStaticData>,
     ) -> Self {
-        Self { config, regions, static_modules }
+        Self { config, regions, static_modules, sampler: None }
     }
 
-    /// 主入口：执行完整的指针扫描流程
+    pub fn with_sampler(mut self, sampler: Arc<ChainSampler>) -> Self {
+        self.sampler = Some(sampler);
+        self
+    }
+
+    /// 主入口：执行完整的指针扫描流程，Phase 1 按 `Bulk` 读取绑定的进程
     pub fn run<F, C>(
         &self,
         output_path: PathBuf,
@@ -80,6 +100,29 @@ impl BfsV3Scanner {
     where
         F: Fn(ProgressPhase, u32, u32, i64) + Sync,
         C: Fn() -> bool + Sync,
+    {
+        let read = |addr: u64, buf: &mut [u8], page_status: &mut PageStatusBitmap| {
+            DRIVER_MANAGER
+                .read()
+                .map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?
+                .read_memory_with_qos(addr, buf, Some(page_status), AccessQos::Bulk)
+        };
+        self.run_with(output_path, max_chains, read, progress_callback, check_cancelled)
+    }
+
+    /// 与 `run` 相同，但 Phase 1 每块通过 `read` 读取（按页记录到 `page_status`）
+    pub fn run_with<R, F, C>(
+        &self,
+        output_path: PathBuf,
+        max_chains: usize,
+        read: R,
+        progress_callback: F,
+        check_cancelled: C,
+    ) -> Result<ScanResult>
+    where
+        R: Fn(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()> + Sync,
+        F: Fn(ProgressPhase, u32, u32, i64) + Sync,
+        C: Fn() -> bool + Sync,
     {
         let timer = Instant::now();
         let target = self.config.target_address;
@@ -92,7 +135,7 @@ impl BfsV3Scanner {
         );
 
         // ========== Phase 1: 扫描所有指针 ==========
-        let global_pointers = self.scan_all_pointers(&progress_callback, &check_cancelled)?;
+        let global_pointers = self.scan_all_pointers(&read, &progress_callback, &check_cancelled)?;
 
         if check_cancelled() {
             return Err(anyhow!("扫描被取消"));
@@ -104,6 +147,9 @@ impl BfsV3Scanner {
             timer.elapsed().as_secs_f64()
         );
 
+        // 用真实的指针数估算 Phase 2 规模，过大时在建链之前拒绝
+        self.check_search_space(global_pointers.len())?;
+
         // ========== Phase 2: BFS 链构建 ==========
         self.build_chains(
             global_pointers,
@@ -114,39 +160,40 @@ impl BfsV3Scanner {
         )
     }
 
+    /// 按 Phase 1 的指针数估算 Phase 2 规模，超过阈值且没有 force 时返回 `PointerScanConfigError`
+    fn check_search_space(&self, pointer_count: usize) -> Result<()> {
+        let address_span = merge_ranges(&self.regions).iter().map(|(start, end)| end - start).sum();
+        let estimate = self.config.check_search_space(pointer_count, address_span)?;
+        info!(
+            "Phase 2 规模估算: 分支数={:.1}, 最深层候选数={:.1e}{}",
+            estimate.branching,
+            estimate.candidates,
+            if self.config.force { " (force)" } else { "" }
+        );
+        Ok(())
+    }
+
     // ========== Phase 1: 指针收集 ==========
 
     /// 扫描所有内存区域，收集有效指针，按 address 排序后存入 MapQueue
-    fn scan_all_pointers<F, C>(
+    fn scan_all_pointers<R, F, C>(
         &self,
+        read: &R,
         progress_callback: &F,
         check_cancelled: &C,
     ) -> Result<MapQueue<PointerData>>
     where
+        R: Fn(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()> + Sync,
         F: Fn(ProgressPhase, u32, u32, i64) + Sync,
         C: Fn() -> bool + Sync,
     {
         // 构建合并后的 valid_ranges 用于二分查找验证
-        let mut valid_ranges: Vec<(u64, u64)> = self.regions.iter()
-            .map(|r| (r.start, r.end))
-            .collect();
-        valid_ranges.sort_unstable_by_key(|r| r.0);
-
-        // 合并重叠区间
-        if !valid_ranges.is_empty() {
-            let mut merged = Vec::with_capacity(valid_ranges.len());
-            let mut current = valid_ranges[0];
-            for &next in &valid_ranges[1..] {
-                if next.0 <= current.1 {
-                    current.1 = current.1.max(next.1);
-                } else {
-                    merged.push(current);
-                    current = next;
-                }
-            }
-            merged.push(current);
-            valid_ranges = merged;
-        }
+        let scan_ranges = merge_ranges(&self.regions);
+        let live_ranges = if self.config.live_vma_check { query_live_ranges() } else { None };
+        let valid_ranges = live_ranges.as_deref().unwrap_or(&scan_ranges);
+        // 按实时 VMA 验证时，统计扫描区域接受但实时 VMA 拒绝的候选
+        let rejected_from = live_ranges.is_some().then_some(scan_ranges.as_slice());
+        let outside_vma = AtomicUsize::new(0);
 
         let total_regions = self.regions.len();
         let completed = Arc::new(AtomicUsize::new(0));
@@ -162,7 +209,7 @@ impl BfsV3Scanner {
                     return None;
                 }
 
-                let pointers = scan_region(region, align, &valid_ranges, &cancelled);
+                let pointers = scan_region(region, align, read, valid_ranges, rejected_from, &outside_vma, &cancelled);
 
                 let count = pointers.len();
                 let found = total_found.fetch_add(count, Ordering::Relaxed) + count;
@@ -190,8 +237,26 @@ impl BfsV3Scanner {
         // 按 address 排序（一次排序，不再按 value 排）
         all_pointers.par_sort_unstable_by_key(|p| p.address);
 
+        let mut stats = FilterStats { outside_vma: outside_vma.into_inner(), not_present: 0 };
+        if self.config.require_present_pages {
+            let driver_manager = DRIVER_MANAGER
+                .read()
+                .map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
+            stats.not_present = retain_present_targets(&mut all_pointers, *PAGE_SIZE as u64, MAX_PRESENCE_PROBES, |page| {
+                is_page_present(&driver_manager, page)
+            });
+        }
+        if live_ranges.is_some() || self.config.require_present_pages {
+            info!(
+                "Phase 1 过滤: 实时 VMA 拒绝 {} 个, 缺页拒绝 {} 个, 保留 {} 个指针",
+                stats.outside_vma, stats.not_present, all_pointers.len()
+            );
+            // 过滤之后的指针数
+            progress_callback(ProgressPhase::ScanningPointers, total_regions as u32, total_regions as u32, all_pointers.len() as i64);
+        }
+
         // 移入 MapQueue
-        let mut queue = MapQueue::with_capacity(all_pointers.len())?;
+        let mut queue = MapQueue::with_memory_threshold(QUEUE_MEMORY_THRESHOLD);
         queue.extend_from_slice(&all_pointers)?;
 
         Ok(queue)
@@ -224,10 +289,9 @@ impl BfsV3Scanner {
 
         // 初始化 dirs 和 ranges
         let mut dirs: Vec<MapQueue<PointerDir>> = (0..=depth)
-            .map(|_| MapQueue::new())
+            .map(|_| MapQueue::with_memory_threshold(QUEUE_MEMORY_THRESHOLD))
             .collect();
         let mut ranges: Vec<PointerRange> = Vec::new();
-        let mut first_range_idx = 0;
 
         // BFS 展开
         for level in 0..=depth {
@@ -246,14 +310,22 @@ impl BfsV3Scanner {
                     break;
                 }
 
+                let new_ranges = ranges.len();
                 filter_pointer_ranges(
                     &self.static_modules,
+                    &self.config,
                     &mut dirs,
                     &mut ranges,
                     curr,
                     level as i32,
                 )?;
 
+                // 上一层已经完整，新找到的静态模块指针可以立即建立索引并抽样
+                for range in &mut ranges[new_ranges..] {
+                    create_assoc_range_index(&dirs[level - 1], &mut range.results, offset);
+                }
+                self.sample_ranges(&dirs, &ranges[new_ranges..]);
+
                 // 创建层间索引
                 let (left, right) = dirs.split_at_mut(level);
                 let prev = &left[level - 1];
@@ -273,27 +345,19 @@ impl BfsV3Scanner {
                 let curr = vec![PointerData::new(target, 0)];
                 filter_pointer_ranges(
                     &self.static_modules,
+                    &self.config,
                     &mut dirs,
                     &mut ranges,
                     curr,
                     0,
                 )?;
-                first_range_idx = ranges.len();
+                self.sample_ranges(&dirs, &ranges);
             }
 
             // Phase 2 进度
             progress_callback(ProgressPhase::BuildingChains, level as u32, depth as u32, ranges.len() as i64);
         }
 
-        // 补充静态模块索引
-        for idx in first_range_idx..ranges.len() {
-            let level = ranges[idx].level;
-            if level > 0 {
-                let prev = &dirs[level as usize - 1];
-                create_assoc_range_index(prev, &mut ranges[idx].results, offset);
-            }
-        }
-
         if ranges.is_empty() {
             info!("BFS V3 扫描完成: 未找到指针链");
             File::create(&output_path)?;
@@ -339,9 +403,7 @@ impl BfsV3Scanner {
             &chain_info,
             &ranges,
             &output_path,
-            target,
-            depth,
-            offset,
+            &self.config,
             max_chains,
             &|w| progress_callback(ProgressPhase::WritingFile, w as u32, effective_total as u32, w as i64),
             check_cancelled,
@@ -357,27 +419,177 @@ impl BfsV3Scanner {
 
         Ok(ScanResult { total_count, output_file: output_path })
     }
+
+    /// 增量重新扫描：读取上一次的输出文件，只保留在当前进程中仍然指向 `new_target` 的链
+    ///
+    /// 游戏重启后模块基址和目标地址都会变化，模块基址取自本扫描器的静态模块（需要重新获取），
+    /// 不再执行 Phase 1 / Phase 2。解引用的进度按 `BuildingChains` 汇报：current=已检查链数，
+    /// total=原文件链数，extra=保留的链数；最后保存索引时汇报 `WritingFile`。
+    /// `adjust_last_offset` 时最后一层指针与新目标相距不超过原扫描的最大偏移的链也保留，最后一个偏移改写为新的距离。
+    /// 输出文件与扫描结果格式相同，可以继续重新扫描。
+    pub fn rescan<F, C>(
+        &self,
+        previous_file: PathBuf,
+        new_target: u64,
+        adjust_last_offset: bool,
+        output_path: PathBuf,
+        progress_callback: F,
+        check_cancelled: C,
+    ) -> Result<ScanResult>
+    where
+        F: Fn(ProgressPhase, u32, u32, i64) + Sync,
+        C: Fn() -> bool + Sync,
+    {
+        let timer = Instant::now();
+        let driver_manager = DRIVER_MANAGER
+            .read()
+            .map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
+        let bases = ModuleBases::new(&self.static_modules);
+
+        let kept = rescan_chains_with(
+            &previous_file,
+            &output_path,
+            new_target,
+            adjust_last_offset,
+            &bases,
+            |addr, buf| driver_manager.read_memory_with_qos(addr, buf, None, AccessQos::Bulk),
+            &progress_callback,
+            &check_cancelled,
+        )?;
+
+        info!(
+            "重新扫描完成: 保留 {} 条链, 新目标=0x{:X}, 耗时 {:.3}s",
+            kept, new_target, timer.elapsed().as_secs_f64()
+        );
+
+        Ok(ScanResult { total_count: kept, output_file: output_path })
+    }
+}
+
+impl BfsV3Scanner {
+    /// 把新找到的静态模块指针作为链提交给抽样器，每个指针取按子节点顺序找到的第一条完整链
+    fn sample_ranges(&self, dirs: &[MapQueue<PointerDir>], ranges: &[PointerRange]) {
+        let Some(sampler) = &self.sampler else {
+            return;
+        };
+
+        for range in ranges {
+            let depth = range.level as u32;
+            for dir in range.results.iter() {
+                sampler.offer_with(depth, || first_chain(dirs, range, dir));
+            }
+        }
+    }
 }
 
 // ============================================================================
 // 独立辅助函数
 // ============================================================================
 
+/// 构造示例链时最多访问的节点数，避免在死路很多的子树里耗时过久
+const SAMPLE_VISIT_BUDGET: usize = 4096;
+
+/// 从 range 中的指针出发，按子节点顺序深度优先找到第一条到达目标的链，子节点索引需要已经建立
+fn first_chain(dirs: &[MapQueue<PointerDir>], range: &PointerRange, dir: &PointerDir) -> Option<ChainSample> {
+    let level = range.level as usize;
+    let mut offsets = Vec::with_capacity(level);
+    let mut budget = SAMPLE_VISIT_BUDGET;
+    if !descend_first_chain(dirs, dir, level, &mut offsets, &mut budget) {
+        return None;
+    }
+
+    Some(ChainSample {
+        module: range.vma.name.rsplit('/').next().unwrap_or(&range.vma.name).to_string(),
+        module_index: range.vma.count,
+        base_offset: dir.address - range.vma.start,
+        offsets,
+        depth: level as u32,
+    })
+}
+
+fn descend_first_chain(dirs: &[MapQueue<PointerDir>], dir: &PointerDir, level: usize, offsets: &mut Vec<i64>, budget: &mut usize) -> bool {
+    if level == 0 {
+        return true;
+    }
+
+    let children = dirs[level - 1].as_slice();
+    let end = (dir.end as usize).min(children.len());
+    for child in children.get(dir.start as usize..end).unwrap_or(&[]) {
+        if *budget == 0 {
+            return false;
+        }
+        *budget -= 1;
+
+        offsets.push(child.address.wrapping_sub(dir.value) as i64);
+        if descend_first_chain(dirs, child, level - 1, offsets, budget) {
+            return true;
+        }
+        offsets.pop();
+    }
+    false
+}
+
 /// 扫描单个 region 的所有指针
-fn scan_region(
+/// 按起始地址排序并合并重叠的区域
+fn merge_ranges(regions: &[ScanRegion]) -> Vec<(u64, u64)> {
+    let mut ranges: Vec<(u64, u64)> = regions.iter()
+        .map(|r| (r.start, r.end))
+        .collect();
+    ranges.sort_unstable_by_key(|r| r.0);
+
+    let Some(&first) = ranges.first() else {
+        return ranges;
+    };
+    let mut merged = Vec::with_capacity(ranges.len());
+    let mut current = first;
+    for &next in &ranges[1..] {
+        if next.0 <= current.1 {
+            current.1 = current.1.max(next.1);
+        } else {
+            merged.push(current);
+            current = next;
+        }
+    }
+    merged.push(current);
+    merged
+}
+
+/// 按实时 VMA 验证时的有效范围，查询失败或为空时返回 None，退回按扫描区域验证
+fn query_live_ranges() -> Option<Vec<(u64, u64)>> {
+    let driver_manager = DRIVER_MANAGER.read().ok()?;
+    match live_vma_ranges(&driver_manager) {
+        Ok(ranges) if !ranges.is_empty() => {
+            info!("按实时 VMA 验证指针: {} 个可读范围", ranges.len());
+            Some(ranges)
+        },
+        Ok(_) => {
+            warn!("实时 VMA 列表为空，按扫描区域验证指针");
+            None
+        },
+        Err(e) => {
+            warn!("查询实时 VMA 失败，按扫描区域验证指针: {:?}", e);
+            None
+        },
+    }
+}
+
+/// 每块通过 `read` 读取。`rejected_from` 不为 None 时，把落在其中但不在 `valid_ranges` 内的值计入 `rejected`
+fn scan_region<R>(
     region: &ScanRegion,
     align: u32,
+    read: &R,
     valid_ranges: &[(u64, u64)],
+    rejected_from: Option<&[(u64, u64)]>,
+    rejected: &AtomicUsize,
     cancelled: &AtomicBool,
-) -> Vec<PointerData> {
-    let driver_manager = match DRIVER_MANAGER.read() {
-        Ok(dm) => dm,
-        Err(_) => return Vec::new(),
-    };
-
+) -> Vec<PointerData>
+where
+    R: Fn(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+{
     let mut buffer = vec![0u8; CHUNK_SIZE];
     let mut current_addr = region.start;
     let mut pointers = Vec::new();
+    let mut rejected_count = 0usize;
     let step = align as usize;
 
     while current_addr < region.end {
@@ -388,10 +600,7 @@ fn scan_region(
         let read_size = min(CHUNK_SIZE as u64, region.end - current_addr) as usize;
         let mut page_bitmap = PageStatusBitmap::new(read_size, current_addr as usize);
 
-        if driver_manager
-            .read_memory_unified(current_addr, &mut buffer[..read_size], Some(&mut page_bitmap))
-            .is_ok()
-        {
+        if read(current_addr, &mut buffer[..read_size], &mut page_bitmap).is_ok() {
             let num_pages = page_bitmap.num_pages();
             for page_idx in 0..num_pages {
                 if !page_bitmap.is_page_success(page_idx) {
@@ -413,6 +622,8 @@ fn scan_region(
                     if is_valid_pointer(masked, valid_ranges) {
                         let addr = current_addr + off as u64;
                         pointers.push(PointerData::new(addr, masked));
+                    } else if rejected_from.is_some_and(|ranges| is_valid_pointer(masked, ranges)) {
+                        rejected_count += 1;
                     }
                 }
             }
@@ -421,6 +632,7 @@ fn scan_region(
         current_addr += read_size as u64;
     }
 
+    rejected.fetch_add(rejected_count, Ordering::Relaxed);
     pointers
 }
 
@@ -479,8 +691,12 @@ fn search_pointer(
 }
 
 /// 过滤指针范围：静态区域加入 ranges，其余加入 dirs
+///
+/// 不在 `config.module_filter` 中的静态模块里的指针直接丢弃：不作为链的根，也不继续向上展开，
+/// 这些链不计入总数，也不占用 max_chains。
 fn filter_pointer_ranges(
     static_modules: &[VmStaticData],
+    config: &PointerScanConfig,
     dirs: &mut Vec<MapQueue<PointerDir>>,
     ranges: &mut Vec<PointerRange>,
     curr: Vec<PointerData>,
@@ -497,8 +713,13 @@ fn filter_pointer_ranges(
         if module_pointers.is_empty() {
             continue;
         }
+        if !config.accepts_module(&module.name) {
+            matched_addrs.extend(module_pointers.iter().map(|p| p.address));
+            continue;
+        }
 
-        let mut results = MapQueue::with_capacity(module_pointers.len())?;
+        let mut results = MapQueue::with_memory_threshold(QUEUE_MEMORY_THRESHOLD);
+        results.reserve(module_pointers.len())?;
         for p in &module_pointers {
             results.push(PointerDir::from_data(p))?;
             matched_addrs.push(p.address);
@@ -570,10 +791,10 @@ fn build_pointer_dirs_tree(
     let max_level = ranges.iter().map(|r| r.level).max().unwrap_or(0) as usize;
 
     let mut counts: Vec<MapQueue<usize>> = (0..=max_level)
-        .map(|_| MapQueue::new())
+        .map(|_| MapQueue::with_memory_threshold(QUEUE_MEMORY_THRESHOLD))
         .collect();
     let mut contents: Vec<MapQueue<*const PointerDir>> = (0..=max_level)
-        .map(|_| MapQueue::new())
+        .map(|_| MapQueue::with_memory_threshold(QUEUE_MEMORY_THRESHOLD))
         .collect();
 
     // 收集每层 dirs 的指针
@@ -607,14 +828,12 @@ fn build_pointer_dirs_tree(
     Ok(ChainInfo::new(counts, contents))
 }
 
-/// 写入文本文件
+/// 按 `config.output_format` 写入文本文件，同时在旁边保存链索引
 fn write_to_text<F, C>(
     chain_info: &ChainInfo,
     ranges: &[PointerRange],
     output_path: &PathBuf,
-    target: u64,
-    depth: usize,
-    offset: u64,
+    config: &PointerScanConfig,
     max_chains: usize,
     progress_callback: &F,
     check_cancelled: &C,
@@ -624,20 +843,14 @@ where
     C: Fn() -> bool,
 {
     let file = File::create(output_path)?;
-    let mut writer = BufWriter::with_capacity(1024 * 1024, file);
-
-    // 文件头
-    writeln!(writer, "# Pointer Scan Results")?;
-    writeln!(writer, "# Target: 0x{:X}", target)?;
-    writeln!(writer, "# Depth: {}", depth)?;
-    writeln!(writer, "# Offset: 0x{:X}", offset)?;
-    writeln!(writer, "# Generated by Mamu Pointer Scanner V3")?;
-    writeln!(writer, "#")?;
-    writeln!(writer, "# Format: module_name[index]+base_offset->offset1->offset2->...")?;
-    writeln!(writer)?;
+    let mut writer = ChainIndexWriter::new(BufWriter::with_capacity(1024 * 1024, file));
+    let format = config.output_format;
+    write_header(&mut writer, config.target_address, config.max_depth as usize, config.max_offset as u64, format)?;
+    writer.start_chains();
 
     let mut written = 0usize;
     let mut last_reported = 0usize;
+    let mut offsets = Vec::with_capacity(config.max_depth as usize);
 
     'outer: for range in ranges {
         for dir in range.results.iter() {
@@ -645,16 +858,14 @@ where
                 break 'outer;
             }
 
-            let base_offset = dir.address - range.vma.start;
-            let short_name = range.vma.name.rsplit('/').next().unwrap_or(&range.vma.name);
-            let prefix = format!("{}[{}]+0x{:X}", short_name, range.vma.count, base_offset);
-
+            let root = ChainRoot::new(range, dir, format);
             written += write_chain_recursive_text(
                 &mut writer,
                 chain_info,
                 dir,
                 range.level as usize,
-                &prefix,
+                &root,
+                &mut offsets,
                 max_chains - written,
             )?;
 
@@ -666,17 +877,204 @@ where
         }
     }
 
-    writer.flush()?;
+    writer.finish()?.save(output_path)?;
     Ok(written)
 }
 
-/// 递归输出指针链（使用 &str prefix 避免 Vec<String> clone）
+/// 写入文件头，重新扫描时从中读回深度、偏移和格式
+fn write_header<W: Write>(writer: &mut W, target: u64, depth: usize, offset: u64, format: ChainOutputFormat) -> Result<()> {
+    writeln!(writer, "# Pointer Scan Results")?;
+    writeln!(writer, "# Target: 0x{:X}", target)?;
+    writeln!(writer, "# Depth: {}", depth)?;
+    writeln!(writer, "# Offset: 0x{:X}", offset)?;
+    writeln!(writer, "# Generated by Mamu Pointer Scanner V3")?;
+    writeln!(writer, "#")?;
+    writeln!(writer, "# Format: {}", format.description())?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+/// 输出文件的扫描参数和链数
+#[derive(Debug, Default, PartialEq, Eq)]
+struct ChainFileSummary {
+    depth: usize,
+    offset: u64,
+    format: ChainOutputFormat,
+    /// 去掉注释和空行后的行数
+    chains: usize,
+}
+
+/// 读一遍输出文件：从头部注释取深度和偏移，统计链的行数（没有找到链时文件为空）
+fn read_chain_file_summary(path: &Path) -> Result<ChainFileSummary> {
+    let file = File::open(path).map_err(|e| anyhow!("Failed to open {:?}: {}", path, e))?;
+    let mut summary = ChainFileSummary::default();
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        let trimmed = line.trim();
+        if let Some(comment) = trimmed.strip_prefix('#') {
+            let comment = comment.trim();
+            if let Some(depth) = comment.strip_prefix("Depth:") {
+                summary.depth = depth.trim().parse().unwrap_or(0);
+            } else if let Some(offset) = comment.strip_prefix("Offset:") {
+                let offset = offset.trim();
+                summary.offset = u64::from_str_radix(offset.strip_prefix("0x").unwrap_or(offset), 16).unwrap_or(0);
+            } else if let Some(format) = comment.strip_prefix("Format:") {
+                summary.format = ChainOutputFormat::from_description(format.trim()).unwrap_or_default();
+            }
+        } else if !trimmed.is_empty() {
+            summary.chains += 1;
+        }
+    }
+    Ok(summary)
+}
+
+/// 分批读取上一次的输出文件，并行解引用，把仍然指向 `target` 的链按原顺序写入新文件
+///
+/// 返回保留的链数。格式不对的行计入已检查数但不保留；每批之间检查取消，取消时返回错误。
+#[allow(clippy::too_many_arguments)]
+fn rescan_chains_with<R, F, C>(
+    previous_file: &Path,
+    output_path: &Path,
+    target: u64,
+    adjust_last_offset: bool,
+    bases: &ModuleBases,
+    read: R,
+    progress_callback: &F,
+    check_cancelled: &C,
+) -> Result<usize>
+where
+    R: Fn(u64, &mut [u8]) -> Result<()> + Sync,
+    F: Fn(ProgressPhase, u32, u32, i64),
+    C: Fn() -> bool + Sync,
+{
+    if output_path.exists() && previous_file.canonicalize()? == output_path.canonicalize()? {
+        return Err(anyhow!("Rescan output must differ from the previous scan file"));
+    }
+
+    let summary = read_chain_file_summary(previous_file)?;
+    let total = summary.chains;
+
+    let file = File::create(output_path)?;
+    let mut writer = ChainIndexWriter::new(BufWriter::with_capacity(1024 * 1024, file));
+    write_header(&mut writer, target, summary.depth, summary.offset, summary.format)?;
+    writer.start_chains();
+    progress_callback(ProgressPhase::BuildingChains, 0, total as u32, 0);
+
+    // 只改写最后一个偏移时，新的偏移不能超出原扫描的范围
+    let max_last_offset = adjust_last_offset.then_some(summary.offset);
+    let mut lines = BufReader::new(File::open(previous_file)?).lines();
+    let mut batch: Vec<String> = Vec::with_capacity(min(total, RESCAN_BATCH));
+    let mut checked = 0usize;
+    let mut kept = 0usize;
+    let malformed = AtomicUsize::new(0);
+
+    loop {
+        batch.clear();
+        for line in lines.by_ref() {
+            let line = line?;
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            batch.push(trimmed.to_string());
+            if batch.len() >= RESCAN_BATCH {
+                break;
+            }
+        }
+        if batch.is_empty() {
+            break;
+        }
+        if check_cancelled() {
+            return Err(anyhow!("扫描被取消"));
+        }
+
+        let survivors: Vec<Cow<str>> = batch
+            .par_iter()
+            .filter_map(|line| {
+                if check_cancelled() {
+                    return None;
+                }
+                let Some(chain) = ChainSample::parse(line) else {
+                    malformed.fetch_add(1, Ordering::Relaxed);
+                    return None;
+                };
+                match resolve_address(&chain, bases, &read) {
+                    Ok(address) if address == target => Some(Cow::Borrowed(line.as_str())),
+                    Ok(address) => {
+                        let chain = retarget_last_offset(chain, address, target, max_last_offset?)?;
+                        let mut text = Vec::with_capacity(line.len());
+                        write_chain(&mut text, summary.format, &chain.module, chain.module_index, chain.base_offset, &chain.offsets).ok()?;
+                        String::from_utf8(text).ok().map(Cow::Owned)
+                    },
+                    Err(_) => None,
+                }
+            })
+            .collect();
+        // 批次中途取消时结果不完整，不写入
+        if check_cancelled() {
+            return Err(anyhow!("扫描被取消"));
+        }
+
+        for line in &survivors {
+            writeln!(writer, "{}", line)?;
+        }
+        kept += survivors.len();
+        checked += batch.len();
+        progress_callback(ProgressPhase::BuildingChains, checked as u32, total as u32, kept as i64);
+    }
+
+    progress_callback(ProgressPhase::WritingFile, 0, kept as u32, kept as i64);
+    writer.finish()?.save(output_path)?;
+    progress_callback(ProgressPhase::WritingFile, kept as u32, kept as u32, kept as i64);
+    let malformed = malformed.into_inner();
+    if malformed > 0 {
+        warn!("重新扫描: 跳过 {} 行格式不对的链", malformed);
+    }
+    Ok(kept)
+}
+
+/// 链解引用到 `address` 而不是 `target` 时，把最后一个偏移改为最后一层指针到 `target` 的距离
+///
+/// 距离的绝对值超过 `max_offset` 或链没有偏移时返回 None。
+fn retarget_last_offset(mut chain: ChainSample, address: u64, target: u64, max_offset: u64) -> Option<ChainSample> {
+    let last = chain.offsets.last_mut()?;
+    let pointer = address.wrapping_add_signed(-*last);
+    let offset = target.wrapping_sub(pointer) as i64;
+    if offset.unsigned_abs() > max_offset {
+        return None;
+    }
+    *last = offset;
+    Some(chain)
+}
+
+/// 一条链的根：模块和基址偏移，同一个静态指针下的所有链共用
+struct ChainRoot<'a> {
+    format: ChainOutputFormat,
+    module: &'a str,
+    module_index: i32,
+    base_offset: u64,
+}
+
+impl<'a> ChainRoot<'a> {
+    fn new(range: &'a PointerRange, dir: &PointerDir, format: ChainOutputFormat) -> Self {
+        let module = range.vma.name.rsplit('/').next().unwrap_or(&range.vma.name);
+        // Cheat Engine 的 "module"+offset 没有段序号，偏移相对同名模块的第一个段
+        let (module_index, base) = match format {
+            ChainOutputFormat::CheatEngine => (0, range.vma.first_base),
+            _ => (range.vma.count, range.vma.start),
+        };
+        Self { format, module, module_index, base_offset: dir.address - base }
+    }
+}
+
+/// 递归输出指针链，offsets 记录从根到当前节点的偏移，到达目标时按格式写出一行
 fn write_chain_recursive_text<W: Write>(
     writer: &mut W,
     chain_info: &ChainInfo,
     dir: &PointerDir,
     level: usize,
-    prefix: &str,
+    root: &ChainRoot,
+    offsets: &mut Vec<i64>,
     max_chains: usize,
 ) -> Result<usize> {
     if max_chains == 0 {
@@ -684,7 +1082,8 @@ fn write_chain_recursive_text<W: Write>(
     }
 
     if level == 0 {
-        writeln!(writer, "{}", prefix)?;
+        write_chain(writer, root.format, root.module, root.module_index, root.base_offset, offsets)?;
+        writeln!(writer)?;
         return Ok(1);
     }
 
@@ -697,23 +1096,276 @@ fn write_chain_recursive_text<W: Write>(
         }
 
         let child = unsafe { &*content[i as usize] };
-        let child_offset = child.address.wrapping_sub(dir.value) as i64;
-
-        let new_prefix = if child_offset >= 0 {
-            format!("{}->+0x{:X}", prefix, child_offset)
-        } else {
-            format!("{}->-0x{:X}", prefix, child_offset.unsigned_abs())
-        };
-
-        count += write_chain_recursive_text(
+        offsets.push(child.address.wrapping_sub(dir.value) as i64);
+        let result = write_chain_recursive_text(
             writer,
             chain_info,
             child,
             level - 1,
-            &new_prefix,
+            root,
+            offsets,
             max_chains - count,
-        )?;
+        );
+        offsets.pop();
+        count += result?;
     }
 
     Ok(count)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pointer_scan::mapqueue_v2;
+    use crate::pointer_scan::chain_index::ChainIndex;
+    use crate::pointer_scan::samples::SamplesSnapshot;
+    use crate::pointer_scan::types::{assign_module_indices, PointerScanConfigError};
+    use std::sync::Mutex;
+
+    const MODULE_BASE: u64 = 0x5000_0000_0000;
+    const TARGET: u64 = 0x7000_0000_1000;
+    const HEAP_1: u64 = 0x7000_0000_2000;
+    const HEAP_2: u64 = 0x7000_0000_3000;
+
+    #[test]
+    fn test_samples_available_before_scan_completes() {
+        let nanos = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_nanos();
+        let dir = std::env::temp_dir().join(format!("mamu_bfs_samples_{}", nanos));
+        mapqueue_v2::set_cache_dir(dir.to_str().unwrap()).unwrap();
+
+        // 三条链，深度分别为 1、2、3
+        let mut pointers = vec![
+            PointerData::new(MODULE_BASE + 0x100, TARGET),
+            PointerData::new(MODULE_BASE + 0x200, HEAP_1),
+            PointerData::new(MODULE_BASE + 0x300, HEAP_2 - 0x4),
+            PointerData::new(HEAP_1, TARGET - 0x10),
+            PointerData::new(HEAP_2, HEAP_1 - 0x8),
+        ];
+        pointers.sort_unstable_by_key(|p| p.address);
+        let mut global_pointers = MapQueue::with_capacity(pointers.len()).unwrap();
+        global_pointers.extend_from_slice(&pointers).unwrap();
+
+        let mut config = PointerScanConfig::new(TARGET).with_depth(3);
+        config.max_offset = 0x100;
+        let modules = vec![VmStaticData::new("/data/app/lib/libgame.so".to_string(), MODULE_BASE, MODULE_BASE + 0x10000, true)];
+        let sampler = Arc::new(ChainSampler::with_seed(16, 1));
+        let scanner = BfsV3Scanner::new(config, Vec::new(), modules).with_sampler(Arc::clone(&sampler));
+
+        // 每层结束时记录一次快照，此时输出文件还没有开始写入
+        let level_snapshots: Mutex<Vec<(u32, SamplesSnapshot)>> = Mutex::new(Vec::new());
+        let progress = |phase: ProgressPhase, current: u32, _total: u32, _extra: i64| {
+            if phase == ProgressPhase::BuildingChains {
+                level_snapshots.lock().unwrap().push((current, sampler.snapshot()));
+            }
+        };
+
+        let output = dir.join("chains.txt");
+        let result = scanner.build_chains(global_pointers, output.clone(), usize::MAX, &progress, &|| false).unwrap();
+        assert_eq!(result.total_count, 3);
+
+        let level_snapshots = level_snapshots.into_inner().unwrap();
+        let (_, after_level_1) = level_snapshots.iter().find(|(level, _)| *level == 1).unwrap();
+        assert_eq!(after_level_1.seen, 1);
+        assert_eq!(after_level_1.samples[0].to_chain_string(), "libgame.so[0]+0x100->+0x0");
+        assert_eq!(after_level_1.shallowest.as_ref().map(|s| s.depth), Some(1));
+
+        let snapshot = sampler.snapshot();
+        assert_eq!(snapshot.seen, 3);
+        assert_eq!(snapshot.shallowest.as_ref().map(|s| s.depth), Some(1));
+
+        // 样本与输出文件中的链一致
+        let text = std::fs::read_to_string(&output).unwrap();
+        let lines: Vec<&str> = text.lines().filter(|line| !line.is_empty() && !line.starts_with('#')).collect();
+        assert_eq!(lines.len(), 3);
+        let mut sampled: Vec<String> = snapshot.samples.iter().map(|s| s.to_chain_string()).collect();
+        sampled.sort();
+        let mut written: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+        written.sort();
+        assert_eq!(sampled, written);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_output_formats_and_module_filter() {
+        const OTHER_BASE: u64 = 0x5100_0000_0000;
+        let nanos = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_nanos();
+        let dir = std::env::temp_dir().join(format!("mamu_bfs_formats_{}", nanos));
+        mapqueue_v2::set_cache_dir(dir.to_str().unwrap()).unwrap();
+
+        let mut pointers = vec![
+            PointerData::new(MODULE_BASE + 0x100, TARGET),
+            PointerData::new(MODULE_BASE + 0x200, HEAP_1),
+            PointerData::new(OTHER_BASE + 0x40, HEAP_1 - 0x8),
+            PointerData::new(HEAP_1, TARGET - 0x10),
+        ];
+        pointers.sort_unstable_by_key(|p| p.address);
+        let mut modules = vec![
+            VmStaticData::new("/data/app/lib/libgame.so".to_string(), MODULE_BASE, MODULE_BASE + 0x10000, true),
+            VmStaticData::new("/data/app/lib/libother.so".to_string(), OTHER_BASE, OTHER_BASE + 0x10000, true),
+        ];
+        assign_module_indices(&mut modules);
+        let mut config = PointerScanConfig::new(TARGET).with_depth(3);
+        config.max_offset = 0x100;
+
+        let scan = |config: PointerScanConfig, max_chains: usize, name: &str| {
+            let mut global_pointers = MapQueue::with_capacity(pointers.len()).unwrap();
+            global_pointers.extend_from_slice(&pointers).unwrap();
+            let output = dir.join(name);
+            let scanner = BfsV3Scanner::new(config, Vec::new(), modules.clone());
+            let result = scanner.build_chains(global_pointers, output.clone(), max_chains, &|_, _, _, _| {}, &|| false).unwrap();
+            let text = std::fs::read_to_string(&output).unwrap();
+            let chains: Vec<ChainSample> = text.lines().filter_map(ChainSample::parse).collect();
+            assert_eq!(read_chain_file_summary(&output).unwrap().format, scanner.config.output_format);
+            (result.total_count, text, chains)
+        };
+
+        // 同一次扫描在各种格式下输出相同的链
+        let (total, native_text, native) = scan(config.clone(), usize::MAX, "native.txt");
+        assert_eq!(total, 3);
+        assert!(native_text.contains("libgame.so[0]+0x200->+0x0->+0x10"));
+        for format in ChainOutputFormat::ALL {
+            let (total, text, chains) = scan(config.clone().with_output_format(format), usize::MAX, "formatted.txt");
+            assert_eq!(total, 3);
+            assert_eq!(chains, native, "{:?}", format);
+            // 每条链一行，头部注释之外没有其他内容
+            assert_eq!(text.lines().filter(|line| !line.is_empty() && !line.starts_with('#')).count(), 3);
+        }
+        let (_, ce_text, _) = scan(config.clone().with_output_format(ChainOutputFormat::CheatEngine), usize::MAX, "ce.txt");
+        assert!(ce_text.contains("\"libother.so\"+0x40 -> +0x8 -> +0x10\n"));
+
+        // 过滤掉的模块不占用 max_chains
+        let filtered = config.clone().with_module_filter(Some(vec!["libother.so".to_string()]));
+        let (total, _, chains) = scan(filtered, 1, "filtered.txt");
+        assert_eq!(total, 1);
+        assert_eq!(chains.iter().map(|c| c.to_chain_string()).collect::<Vec<_>>(), vec!["libother.so[0]+0x40->+0x8->+0x10"]);
+        let (total, _, chains) = scan(config.with_module_filter(Some(Vec::new())), usize::MAX, "none.txt");
+        assert_eq!((total, chains.len()), (0, 0));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_dense_pointers_rejected_between_phases() {
+        const HEAP: u64 = 0x7000_0000_0000;
+        const HEAP_SIZE: u64 = 0x10_0000;
+        let regions = vec![
+            ScanRegion { start: HEAP, end: HEAP + HEAP_SIZE, name: "[anon:scudo:primary]".to_string() },
+            // 与上一个区域重叠，只计算一次
+            ScanRegion { start: HEAP + 0x8_0000, end: HEAP + HEAP_SIZE, name: String::new() },
+        ];
+        assert_eq!(merge_ranges(&regions), vec![(HEAP, HEAP + HEAP_SIZE)]);
+
+        // 整个堆每 8 字节都是指向堆内的指针
+        let dense: Vec<PointerData> = (0..HEAP_SIZE / 8)
+            .map(|i| PointerData::new(HEAP + i * 8, HEAP + (i * 0x1238) % HEAP_SIZE))
+            .collect();
+        let config = PointerScanConfig::new(HEAP + 0x100).with_depth(7).with_offset(0x1000);
+        assert!(config.validate().is_ok());
+
+        let scanner = BfsV3Scanner::new(config.clone(), regions.clone(), Vec::new());
+        let err = scanner.check_search_space(dense.len()).unwrap_err();
+        let rejected = err.downcast_ref::<PointerScanConfigError>().expect("rejection should keep its type");
+        assert_eq!(rejected.error_code(), crate::pointer_scan::types::ScanErrorCode::SearchSpaceTooLarge);
+
+        // 同样的参数，稀疏的指针通过
+        assert!(scanner.check_search_space(100).is_ok());
+
+        // force 时不拒绝
+        let forced = BfsV3Scanner::new(config.with_force(true), regions, Vec::new());
+        assert!(forced.check_search_space(dense.len()).is_ok());
+    }
+
+    #[test]
+    fn test_rescan_keeps_chains_resolving_to_new_target() {
+        const NEW_BASE: u64 = 0x5100_0000_0000;
+        const NEW_TARGET: u64 = 0x7100_0000_5000;
+        const NEW_HEAP: u64 = 0x7100_0000_8000;
+
+        let nanos = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_nanos();
+        let dir = std::env::temp_dir().join(format!("mamu_bfs_rescan_{}", nanos));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let previous = dir.join("previous.txt");
+        let mut text = Vec::new();
+        write_header(&mut text, TARGET, 3, 0x100, ChainOutputFormat::Native).unwrap();
+        text.extend_from_slice(
+            b"libgame.so[0]+0x100->+0x0\nlibgame.so[0]+0x300->+0x4->+0x8->+0x10\nnot a chain\nlibgame.so[0]+0x200->+0x0->+0x10\nlibother.so[0]+0x40->+0x0\n",
+        );
+        std::fs::write(&previous, text).unwrap();
+        assert_eq!(
+            read_chain_file_summary(&previous).unwrap(),
+            ChainFileSummary { depth: 3, offset: 0x100, format: ChainOutputFormat::Native, chains: 5 }
+        );
+
+        // 重启后模块和目标都换了地址，+0x300 那条链的最后一级指针偏了 0x20，libother.so 不再加载
+        let modules = vec![VmStaticData::new("/data/app/lib/libgame.so".to_string(), NEW_BASE, NEW_BASE + 0x10000, true)];
+        let bases = ModuleBases::new(&modules);
+        let memory = std::collections::HashMap::from([
+            (NEW_BASE + 0x100, NEW_TARGET),
+            (NEW_BASE + 0x200, NEW_HEAP),
+            (NEW_HEAP, NEW_TARGET - 0x10),
+            (NEW_BASE + 0x300, NEW_HEAP + 0x100),
+            (NEW_HEAP + 0x104, NEW_HEAP + 0x200),
+            (NEW_HEAP + 0x208, NEW_TARGET - 0x30),
+        ]);
+        let read = |address: u64, buf: &mut [u8]| -> Result<()> {
+            let value = memory.get(&address).ok_or_else(|| anyhow!("unmapped 0x{:X}", address))?;
+            buf.copy_from_slice(&value.to_le_bytes());
+            Ok(())
+        };
+
+        let reports = Mutex::new(Vec::new());
+        let progress = |phase: ProgressPhase, current: u32, total: u32, extra: i64| {
+            reports.lock().unwrap().push((phase, current, total, extra));
+        };
+        let output = dir.join("rescan.txt");
+        let kept = rescan_chains_with(&previous, &output, NEW_TARGET, false, &bases, read, &progress, &|| false).unwrap();
+        assert_eq!(kept, 2);
+        assert_eq!(
+            reports.into_inner().unwrap(),
+            vec![
+                (ProgressPhase::BuildingChains, 0, 5, 0),
+                (ProgressPhase::BuildingChains, 5, 5, 2),
+                (ProgressPhase::WritingFile, 0, 2, 2),
+                (ProgressPhase::WritingFile, 2, 2, 2),
+            ]
+        );
+
+        // 输出文件保留原顺序和扫描参数，可以再次重新扫描
+        let text = std::fs::read_to_string(&output).unwrap();
+        assert!(text.contains(&format!("# Target: 0x{:X}", NEW_TARGET)));
+        let chains: Vec<&str> = text.lines().filter(|line| !line.is_empty() && !line.starts_with('#')).collect();
+        assert_eq!(chains, vec!["libgame.so[0]+0x100->+0x0", "libgame.so[0]+0x200->+0x0->+0x10"]);
+        assert_eq!(
+            read_chain_file_summary(&output).unwrap(),
+            ChainFileSummary { depth: 3, offset: 0x100, format: ChainOutputFormat::Native, chains: 2 }
+        );
+        // 写入时保存的索引可以直接分页
+        let index = ChainIndex::load(&output).unwrap().expect("index written with the output");
+        assert_eq!(index.count(), 2);
+        assert_eq!(index.read_lines(&output, 1, 5).unwrap(), vec![chains[1].to_string()]);
+        let again = dir.join("again.txt");
+        assert_eq!(rescan_chains_with(&output, &again, NEW_TARGET, false, &bases, read, &|_, _, _, _| {}, &|| false).unwrap(), 2);
+
+        // 调整最后一级偏移：偏差在原扫描的最大偏移内的链改写后保留
+        let adjusted = dir.join("adjusted.txt");
+        assert_eq!(rescan_chains_with(&previous, &adjusted, NEW_TARGET, true, &bases, read, &|_, _, _, _| {}, &|| false).unwrap(), 3);
+        let text_adjusted = std::fs::read_to_string(&adjusted).unwrap();
+        let chains_adjusted: Vec<&str> = text_adjusted.lines().filter(|line| !line.is_empty() && !line.starts_with('#')).collect();
+        assert_eq!(
+            chains_adjusted,
+            vec!["libgame.so[0]+0x100->+0x0", "libgame.so[0]+0x300->+0x4->+0x8->+0x30", "libgame.so[0]+0x200->+0x0->+0x10"]
+        );
+        // 偏差超过最大偏移的 +0x300 链不保留
+        assert_eq!(rescan_chains_with(&previous, &adjusted, NEW_TARGET + 0xF0, true, &bases, read, &|_, _, _, _| {}, &|| false).unwrap(), 2);
+
+        // 取消、覆盖输入文件、输入文件不存在都返回错误
+        assert!(rescan_chains_with(&previous, &again, NEW_TARGET, false, &bases, read, &|_, _, _, _| {}, &|| true).is_err());
+        assert!(rescan_chains_with(&output, &output, NEW_TARGET, false, &bases, read, &|_, _, _, _| {}, &|| false).is_err());
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), text);
+        assert!(rescan_chains_with(&dir.join("missing.txt"), &again, NEW_TARGET, false, &bases, read, &|_, _, _, _| {}, &|| false).is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
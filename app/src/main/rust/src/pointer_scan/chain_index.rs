@@ -0,0 +1,386 @@
+//! Chain file index
+//!
+//! 输出文件是文本格式，几百万条链时从头解析到第 N 行很慢。写入文件时每隔
+//! `CHAIN_INDEX_STRIDE` 条链记录一次该链所在的字节偏移，保存在输出文件旁边的 `.idx` 文件里；
+//! 分页读取时先跳到最近的索引项，再向后解析需要的几行。
+//!
+//! 索引文件缺失或和输出文件长度不一致（例如下一次启动时重新打开了被改动过的文件）时重新扫描构建。
+//!
+//! 当前结果通过 `ChainFile` 只读映射输出文件，几千万条链的文件也只占用访问过的页，
+//! 链的内容从不整体读进内存。
+
+use anyhow::{anyhow, Result};
+use log::warn;
+use memmap2::Mmap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// 每隔多少条链记录一次文件偏移
+pub const CHAIN_INDEX_STRIDE: u64 = 1024;
+
+const INDEX_MAGIC: &[u8; 8] = b"MAMUCIX1";
+
+/// 链的行号（去掉注释和空行后，从 0 开始）到文件偏移的稀疏索引
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainIndex {
+    stride: u64,
+    count: u64,
+    /// 建立索引时输出文件的长度，用来发现文件被替换
+    file_len: u64,
+    /// 第 `i * stride` 条链的起始偏移
+    offsets: Vec<u64>,
+}
+
+/// 索引文件路径：`<输出文件>.idx`
+pub fn index_path(output: &Path) -> PathBuf {
+    let mut path = output.as_os_str().to_owned();
+    path.push(".idx");
+    PathBuf::from(path)
+}
+
+fn is_chain_line(line: &[u8]) -> bool {
+    let trimmed = line.trim_ascii();
+    !trimmed.is_empty() && trimmed[0] != b'#'
+}
+
+impl ChainIndex {
+    /// 链的总数
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// 扫描整个输出文件建立索引
+    pub fn build(output: &Path) -> Result<Self> {
+        let file = File::open(output).map_err(|e| anyhow!("Failed to open {:?}: {}", output, e))?;
+        let mut reader = BufReader::with_capacity(1024 * 1024, file);
+        let mut index = Self { stride: CHAIN_INDEX_STRIDE, count: 0, file_len: 0, offsets: Vec::new() };
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            let read = reader.read_until(b'\n', &mut line)?;
+            if read == 0 {
+                break;
+            }
+            if is_chain_line(&line) {
+                if index.count.is_multiple_of(index.stride) {
+                    index.offsets.push(index.file_len);
+                }
+                index.count += 1;
+            }
+            index.file_len += read as u64;
+        }
+        Ok(index)
+    }
+
+    /// 读取输出文件旁边的索引，不存在、格式不对或已经过期时返回 None
+    pub fn load(output: &Path) -> Result<Option<Self>> {
+        let Ok(mut file) = File::open(index_path(output)) else {
+            return Ok(None);
+        };
+        let file_len = std::fs::metadata(output)?.len();
+
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        if bytes.len() < 32 || &bytes[..8] != INDEX_MAGIC || (bytes.len() - 32) % 8 != 0 {
+            return Ok(None);
+        }
+        let field = |at: usize| u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap());
+        let index = Self {
+            stride: field(8),
+            count: field(16),
+            file_len: field(24),
+            offsets: bytes[32..].chunks_exact(8).map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap())).collect(),
+        };
+        let consistent = index.stride > 0 && index.offsets.len() as u64 == index.count.div_ceil(index.stride);
+        if !consistent || index.file_len != file_len {
+            return Ok(None);
+        }
+        Ok(Some(index))
+    }
+
+    /// 把索引保存到输出文件旁边
+    pub fn save(&self, output: &Path) -> Result<()> {
+        let mut bytes = Vec::with_capacity(32 + self.offsets.len() * 8);
+        bytes.extend_from_slice(INDEX_MAGIC);
+        for value in [self.stride, self.count, self.file_len].into_iter().chain(self.offsets.iter().copied()) {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        std::fs::write(index_path(output), bytes)?;
+        Ok(())
+    }
+
+    /// 读取已有的索引，没有可用的索引时重新构建并保存
+    pub fn open(output: &Path) -> Result<Self> {
+        if let Some(index) = Self::load(output)? {
+            return Ok(index);
+        }
+        let index = Self::build(output)?;
+        if let Err(e) = index.save(output) {
+            warn!("Failed to save chain index for {:?}: {}", output, e);
+        }
+        Ok(index)
+    }
+
+    /// 读取第 `start` 条开始的最多 `count` 条链的文本
+    pub fn read_lines(&self, output: &Path, start: u64, count: usize) -> Result<Vec<String>> {
+        if start >= self.count || count == 0 {
+            return Ok(Vec::new());
+        }
+
+        let slot = (start / self.stride) as usize;
+        let mut file = File::open(output).map_err(|e| anyhow!("Failed to open {:?}: {}", output, e))?;
+        file.seek(SeekFrom::Start(self.offsets[slot]))?;
+        let mut reader = BufReader::new(file);
+
+        let mut skip = start - slot as u64 * self.stride;
+        let wanted = count.min((self.count - start) as usize);
+        let mut lines = Vec::with_capacity(wanted);
+        let mut line = String::new();
+        while lines.len() < wanted {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                break;
+            }
+            if !is_chain_line(line.as_bytes()) {
+                continue;
+            }
+            if skip > 0 {
+                skip -= 1;
+                continue;
+            }
+            lines.push(line.trim().to_string());
+        }
+        Ok(lines)
+    }
+}
+
+/// 映射后的输出文件和它的索引，分页读取时直接在映射上从最近的索引项向后解析
+///
+/// 输出文件写完之后不会再被修改；删除文件前先丢掉所有引用它的 `ChainFile`。
+pub struct ChainFile {
+    path: PathBuf,
+    index: ChainIndex,
+    /// 空文件无法映射，这时没有链
+    map: Option<Mmap>,
+}
+
+impl ChainFile {
+    /// 打开索引（没有可用的索引时重新构建）并映射输出文件
+    pub fn open(output: &Path) -> Result<Self> {
+        let index = ChainIndex::open(output)?;
+        let file = File::open(output).map_err(|e| anyhow!("Failed to open {:?}: {}", output, e))?;
+        let len = file.metadata()?.len();
+        if len != index.file_len {
+            return Err(anyhow!("{:?} changed while opening: {} bytes, index covers {}", output, len, index.file_len));
+        }
+        // Safety: 输出文件写完后只读，映射存在期间不会被截断
+        let map = if len == 0 { None } else { Some(unsafe { Mmap::map(&file)? }) };
+        Ok(Self { path: output.to_path_buf(), index, map })
+    }
+
+    /// 链的总数，来自索引
+    pub fn count(&self) -> u64 {
+        self.index.count
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 第 `start` 条开始的最多 `count` 条链的文本
+    pub fn read_lines(&self, start: u64, count: usize) -> Vec<String> {
+        let Some(map) = &self.map else {
+            return Vec::new();
+        };
+        if start >= self.index.count || count == 0 {
+            return Vec::new();
+        }
+
+        let slot = (start / self.index.stride) as usize;
+        let mut skip = start - slot as u64 * self.index.stride;
+        let wanted = count.min((self.index.count - start) as usize);
+        let mut lines = Vec::with_capacity(wanted);
+        for line in map[self.index.offsets[slot] as usize..].split(|&byte| byte == b'\n') {
+            if lines.len() >= wanted {
+                break;
+            }
+            if !is_chain_line(line) {
+                continue;
+            }
+            if skip > 0 {
+                skip -= 1;
+                continue;
+            }
+            lines.push(String::from_utf8_lossy(line.trim_ascii()).into_owned());
+        }
+        lines
+    }
+}
+
+/// 写入输出文件时顺带建立索引
+///
+/// 文件头通过它写入时不计入链；`start_chains` 之后写入的每一行都是一条链。
+pub struct ChainIndexWriter<W: Write> {
+    inner: W,
+    index: ChainIndex,
+    counting: bool,
+    at_line_start: bool,
+}
+
+impl<W: Write> ChainIndexWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            index: ChainIndex { stride: CHAIN_INDEX_STRIDE, count: 0, file_len: 0, offsets: Vec::new() },
+            counting: false,
+            at_line_start: true,
+        }
+    }
+
+    /// 文件头写完，之后的每一行都是链
+    pub fn start_chains(&mut self) {
+        self.counting = true;
+        self.at_line_start = true;
+    }
+
+    /// 刷新底层写入器并返回索引
+    pub fn finish(mut self) -> Result<ChainIndex> {
+        self.inner.flush()?;
+        Ok(self.index)
+    }
+}
+
+impl<W: Write> Write for ChainIndexWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        if !self.counting {
+            self.index.file_len += written as u64;
+            return Ok(written);
+        }
+
+        let index = &mut self.index;
+        for &byte in &buf[..written] {
+            if self.at_line_start {
+                if index.count.is_multiple_of(index.stride) {
+                    index.offsets.push(index.file_len);
+                }
+                index.count += 1;
+                self.at_line_start = false;
+            }
+            if byte == b'\n' {
+                self.at_line_start = true;
+            }
+            index.file_len += 1;
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufWriter;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("mamu_chain_index_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn chain_line(i: u64) -> String {
+        format!("libgame.so[0]+0x{:X}->+0x{:X}", 0x1000 + i * 8, i % 0x100)
+    }
+
+    fn write_chains(path: &Path, count: u64) -> ChainIndex {
+        let mut writer = ChainIndexWriter::new(BufWriter::new(File::create(path).unwrap()));
+        writeln!(writer, "# Pointer Scan Results").unwrap();
+        writeln!(writer, "# Target: 0x1000").unwrap();
+        writeln!(writer).unwrap();
+        writer.start_chains();
+        for i in 0..count {
+            writeln!(writer, "{}", chain_line(i)).unwrap();
+        }
+        writer.finish().unwrap()
+    }
+
+    #[test]
+    fn test_written_index_matches_rebuilt_and_pages() {
+        let dir = temp_dir("pages");
+        let path = dir.join("chains.txt");
+        let total = CHAIN_INDEX_STRIDE * 3 + 17;
+        let index = write_chains(&path, total);
+
+        assert_eq!(index.count(), total);
+        assert_eq!(index, ChainIndex::build(&path).unwrap());
+
+        for (start, count) in [(0, 5), (CHAIN_INDEX_STRIDE - 2, 4), (CHAIN_INDEX_STRIDE * 2 + 100, 1500), (total - 3, 10)] {
+            let lines = index.read_lines(&path, start, count).unwrap();
+            let expected: Vec<String> = (start..(start + count as u64).min(total)).map(chain_line).collect();
+            assert_eq!(lines, expected, "window {}+{}", start, count);
+        }
+        assert!(index.read_lines(&path, total, 10).unwrap().is_empty());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_index_file_rebuilt_when_stale() {
+        let dir = temp_dir("stale");
+        let path = dir.join("chains.txt");
+        let index = write_chains(&path, 10);
+        index.save(&path).unwrap();
+        assert_eq!(ChainIndex::load(&path).unwrap(), Some(index));
+
+        // 下一次启动时文件已被替换，索引过期
+        write_chains(&path, 2500);
+        assert_eq!(ChainIndex::load(&path).unwrap(), None);
+        let reopened = ChainIndex::open(&path).unwrap();
+        assert_eq!(reopened.count(), 2500);
+        assert_eq!(ChainIndex::load(&path).unwrap(), Some(reopened.clone()));
+        assert_eq!(reopened.read_lines(&path, 2048, 1).unwrap(), vec![chain_line(2048)]);
+
+        // 没有找到链时输出文件为空
+        std::fs::write(&path, "").unwrap();
+        assert_eq!(ChainIndex::open(&path).unwrap().count(), 0);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_mapped_file_pages_random_windows() {
+        let dir = temp_dir("mapped");
+        let path = dir.join("chains.txt");
+        let total = 1_000_000;
+        write_chains(&path, total).save(&path).unwrap();
+
+        let file = ChainFile::open(&path).unwrap();
+        assert_eq!(file.count(), total);
+        let mut seed = 0x9E3779B97F4A7C15u64;
+        let mut next = |bound: u64| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed % bound
+        };
+        for _ in 0..200 {
+            let start = next(total + 10);
+            let count = next(3000) as usize;
+            let expected: Vec<String> = (start.min(total)..(start + count as u64).min(total)).map(chain_line).collect();
+            assert_eq!(file.read_lines(start, count), expected, "window {}+{}", start, count);
+        }
+        assert_eq!(file.read_lines(total - 1, 5), vec![chain_line(total - 1)]);
+
+        // 没有找到链时输出文件为空，无法映射
+        std::fs::write(&path, "").unwrap();
+        let empty = ChainFile::open(&path).unwrap();
+        assert_eq!(empty.count(), 0);
+        assert!(empty.read_lines(0, 10).is_empty());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
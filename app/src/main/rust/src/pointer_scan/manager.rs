@@ -5,23 +5,41 @@
 //! manages async execution, and provides JNI-accessible state.
 
 use crate::core::globals::TOKIO_RUNTIME;
-use crate::pointer_scan::chain_builder::{BfsV3Scanner, ProgressPhase};
+use crate::core::worker_pool::{ScanPool, WorkerPool};
+use crate::core::{AccessQos, DRIVER_MANAGER};
+use crate::pointer_scan::chain_builder::{BfsV3Scanner, ProgressPhase, ScanResult};
+use crate::pointer_scan::chain_index::{index_path, ChainFile};
 use crate::pointer_scan::mapqueue_v2;
+use crate::pointer_scan::samples::{ChainSample, ChainSampler, SamplesSnapshot};
 use crate::pointer_scan::scanner::ScanRegion;
 use crate::pointer_scan::shared_buffer::PointerScanSharedBuffer;
-use crate::pointer_scan::types::{PointerScanConfig, ScanErrorCode, ScanPhase, VmStaticData};
+use crate::pointer_scan::types::{static_modules_from_list, ChainOutputFormat, PointerScanConfig, PointerScanConfigError, ScanErrorCode, ScanPhase, VmStaticData};
+use crate::pointer_scan::validate::{self, ChainStatus, ChainValidity, ModuleBases, VALIDATION_BATCH};
 use anyhow::{anyhow, Result};
 use lazy_static::lazy_static;
 use log::{error, info, log_enabled, Level};
-use std::path::PathBuf;
-use std::sync::RwLock;
-use tokio::task::JoinHandle;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use tokio::task::{JoinError, JoinHandle};
 use tokio_util::sync::CancellationToken;
 
 lazy_static! {
     pub static ref POINTER_SCAN_MANAGER: RwLock<PointerScanManager> = RwLock::new(PointerScanManager::new());
 }
 
+/// 按绑定进程的模块列表确定扫描区域中的静态段，Kotlin 不传静态标记时使用
+pub fn native_static_modules(regions: &[ScanRegion]) -> Result<Vec<VmStaticData>> {
+    let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
+    if !driver_manager.is_process_bound() {
+        return Err(anyhow!("No process bound"));
+    }
+    let modules = driver_manager.list_modules(driver_manager.get_bound_pid())?;
+    Ok(static_modules_from_list(regions, &modules))
+}
+
 /// 扫描完成结果
 #[derive(Debug, Clone)]
 pub struct ScanCompleteResult {
@@ -47,8 +65,20 @@ pub struct PointerScanManager {
     current_phase: ScanPhase,
     /// Last error code
     last_error: ScanErrorCode,
+    /// 最近一次错误的说明（参数被拒绝时包含修改建议）
+    last_error_message: Option<String>,
     /// 扫描完成结果
     scan_result: Option<ScanCompleteResult>,
+    /// 扫描过程中的示例链
+    samples: Arc<ChainSampler>,
+    /// 最近一次扫描的静态模块，验证链时按 `module[index]` 查找基址
+    static_modules: Vec<VmStaticData>,
+    /// 映射后的输出文件和链索引，分页读取结果时使用
+    chain_file: Option<Arc<ChainFile>>,
+    /// 验证标记为失效的链，没有验证过时为 None，见 `start_validation_async`
+    validity: Option<Arc<ChainValidity>>,
+    /// 扫描使用的线程池，修改后从下一次扫描开始生效
+    worker_pool: WorkerPool,
 }
 
 impl PointerScanManager {
@@ -62,7 +92,13 @@ impl PointerScanManager {
             cache_dir: PathBuf::from("/data/data/moe.fuqiuluo.mamu/cache"),
             current_phase: ScanPhase::Idle,
             last_error: ScanErrorCode::None,
+            last_error_message: None,
             scan_result: None,
+            samples: Arc::new(ChainSampler::default()),
+            static_modules: Vec::new(),
+            chain_file: None,
+            validity: None,
+            worker_pool: WorkerPool::new("pointer-scan-worker"),
         }
     }
 
@@ -101,6 +137,15 @@ impl PointerScanManager {
         }
     }
 
+    /// 绑定的进程退出：取消正在进行的扫描，任务结束时记为 Error / ProcessDied 而不是 Cancelled
+    pub fn abort_for_process_death(&mut self) {
+        if !self.is_scanning() {
+            return;
+        }
+        self.last_error = ScanErrorCode::ProcessDied;
+        self.request_cancel();
+    }
+
     /// Get the current scan phase.
     pub fn get_phase(&self) -> ScanPhase {
         self.current_phase
@@ -111,17 +156,308 @@ impl PointerScanManager {
         self.last_error
     }
 
+    /// Get the message of the last error, if any.
+    pub fn get_error_message(&self) -> Option<String> {
+        self.last_error_message.clone()
+    }
+
     /// Get scan result (total count and output file path)
     pub fn get_scan_result(&self) -> Option<ScanCompleteResult> {
         self.scan_result.clone()
     }
 
+    /// 当前扫描的示例链，扫描进行中也可以调用
+    pub fn get_samples(&self) -> SamplesSnapshot {
+        self.samples.snapshot()
+    }
+
+    /// 重新解引用一批链，检查它们是否仍然指向扫描目标
+    ///
+    /// `None` 表示链不存在。并行验证，共享缓冲区的取消标志置位后剩下的链不再检查。
+    pub fn validate_chains(&self, chains: &[Option<ChainSample>]) -> Result<Vec<ChainStatus>> {
+        let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
+        let bases = ModuleBases::new(&self.static_modules);
+        self.shared_buffer.clear_cancel_flag();
+
+        let statuses = validate::validate_chains_with(
+            chains,
+            &bases,
+            self.config.target_address,
+            |addr, buf| driver_manager.read_memory_with_qos(addr, buf, None, AccessQos::Bulk),
+            &|| self.shared_buffer.is_cancel_requested(),
+        );
+        if log_enabled!(Level::Debug) {
+            let valid = statuses.iter().filter(|status| **status == ChainStatus::Valid).count();
+            info!("Validated {} chains: {} still valid", statuses.len(), valid);
+        }
+        Ok(statuses)
+    }
+
+    /// 输出文件中的链数，来自索引，不扫描文件
+    pub fn get_chain_count(&self) -> u64 {
+        self.chain_file.as_ref().map_or(0, |file| file.count())
+    }
+
+    /// 分页读取输出文件中第 `start` 条开始的最多 `count` 条链，格式不对的行跳过
+    ///
+    /// 直接在映射上定位，只有这一页的链被解析。
+    pub fn get_chain_results(&self, start: u64, count: usize) -> Result<Vec<ChainSample>> {
+        let Some(ref file) = self.chain_file else {
+            return Ok(Vec::new());
+        };
+        Ok(file.read_lines(start, count).iter().filter_map(|line| ChainSample::parse(line)).collect())
+    }
+
+    /// 重新打开之前保存的输出文件作为当前结果，索引过期或不存在时重新构建
+    ///
+    /// 扫描目标取自文件头；验证链还需要之后扫描时的静态模块。
+    pub fn open_output_file(&mut self, path: PathBuf) -> Result<u64> {
+        if self.is_scanning() {
+            self.last_error = ScanErrorCode::AlreadyScanning;
+            return Err(anyhow!("Scan already in progress"));
+        }
+        let file = ChainFile::open(&path)?;
+        let count = file.count();
+
+        self.clear();
+        if let Some(target) = read_header_target(&path)? {
+            self.config.target_address = target;
+        }
+        self.scan_result = Some(ScanCompleteResult {
+            total_count: count as usize,
+            output_file: path.to_string_lossy().to_string(),
+        });
+        self.chain_file = Some(Arc::new(file));
+        self.current_phase = ScanPhase::Completed;
+        Ok(count)
+    }
+
+    /// 删除当前结果的输出文件和索引文件，之后没有结果
+    ///
+    /// 先丢掉映射再删除。扫描或验证进行中不能删除，任务还在读写这个文件；文件已经不存在时不算错误。
+    pub fn delete_output_file(&mut self) -> Result<()> {
+        if self.is_scanning() {
+            self.last_error = ScanErrorCode::AlreadyScanning;
+            return Err(anyhow!("Scan already in progress"));
+        }
+        let Some(result) = self.scan_result.take() else {
+            return Ok(());
+        };
+        self.clear();
+
+        let output = PathBuf::from(result.output_file);
+        for path in [index_path(&output), output] {
+            match std::fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(anyhow!("Failed to delete {:?}: {}", path, e)),
+                _ => {},
+            }
+        }
+        Ok(())
+    }
+
+    /// 扫描目标地址
+    pub fn target_address(&self) -> u64 {
+        self.config.target_address
+    }
+
+    /// 按输出文件中的编号验证链
+    pub fn validate_chain_ids(&self, ids: &[u64]) -> Result<Vec<ChainStatus>> {
+        let result = self.scan_result.as_ref().ok_or_else(|| anyhow!("No pointer scan result to validate"))?;
+        let chains = validate::read_chains_by_id(Path::new(&result.output_file), ids)?;
+        self.validate_chains(&chains)
+    }
+
+    /// 异步验证当前结果中编号在 `chains` 内的链，失效的链之后不再由 `get_valid_chain_results` 返回
+    ///
+    /// 解引用失败、或给了 `expected_value` 而最终地址上的值不同的链记为失效。进度在 Validating 阶段写入共享缓冲区，
+    /// chains_found 为存活的链数；取消与扫描相同，已经检查的标记保留。多次验证的标记累积，新的扫描或打开输出文件后清空。
+    pub fn start_validation_async(&mut self, chains: Range<u64>, expected_value: Option<Vec<u8>>) -> Result<()> {
+        if self.is_scanning() {
+            self.last_error = ScanErrorCode::AlreadyScanning;
+            return Err(anyhow!("Scan already in progress"));
+        }
+        let Some(ref file) = self.chain_file else {
+            return Err(anyhow!("No pointer scan result to validate"));
+        };
+        let file = Arc::clone(file);
+        let range = chains.start.min(file.count())..chains.end.min(file.count());
+        let expected_value = expected_value.filter(|value| !value.is_empty());
+
+        let pool = self.worker_pool.current()?;
+        let validity = Arc::clone(self.validity.get_or_insert_with(|| Arc::new(ChainValidity::new(file.count()))));
+        let static_modules = self.static_modules.clone();
+
+        self.last_error = ScanErrorCode::None;
+        self.last_error_message = None;
+        self.shared_buffer.clear_cancel_flag();
+        self.current_phase = ScanPhase::Validating;
+        self.shared_buffer.write_phase(ScanPhase::Validating);
+        self.shared_buffer.update_validating_progress(0, range.end - range.start, validity.valid_count() as i64);
+
+        let cancel_token = CancellationToken::new();
+        self.cancel_token = Some(cancel_token.clone());
+
+        info!(
+            "Starting pointer chain validation: chains {}..{} of {}, expected value {:?}",
+            range.start,
+            range.end,
+            file.count(),
+            expected_value
+        );
+
+        let handle = TOKIO_RUNTIME.spawn(async move {
+            Self::run_validation_task(file, validity, range, static_modules, expected_value, pool, cancel_token).await;
+        });
+
+        self.scan_handle = Some(handle);
+        Ok(())
+    }
+
+    /// The async validation task: re-resolves chains batch by batch and marks the failed ones.
+    async fn run_validation_task(
+        file: Arc<ChainFile>,
+        validity: Arc<ChainValidity>,
+        range: Range<u64>,
+        static_modules: Vec<VmStaticData>,
+        expected_value: Option<Vec<u8>>,
+        pool: ScanPool,
+        cancel_token: CancellationToken,
+    ) {
+        let cancel_token_clone = cancel_token.clone();
+        let task_validity = Arc::clone(&validity);
+
+        let validation_result = pool.spawn_blocking(move || {
+            let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
+            let bases = ModuleBases::new(&static_modules);
+            validate::validate_range(
+                &task_validity,
+                range,
+                |start, count| Ok(file.read_lines(start, count).iter().map(|line| ChainSample::parse(line)).collect()),
+                &bases,
+                expected_value.as_deref(),
+                |addr, buf| driver_manager.read_memory_with_qos(addr, buf, None, AccessQos::Bulk),
+                &|| cancel_token_clone.is_cancelled(),
+                |checked, total| {
+                    // 每批之后汇报进度，共享缓冲区的取消标志也在这里转为取消令牌
+                    if let Ok(manager) = POINTER_SCAN_MANAGER.read() {
+                        if manager.shared_buffer.is_cancel_requested() {
+                            cancel_token_clone.cancel();
+                        }
+                        manager.shared_buffer.update_validating_progress(checked, total, task_validity.valid_count() as i64);
+                    }
+                },
+            )
+        })
+        .await;
+
+        Self::finish_validation(&cancel_token, &validity, validation_result);
+    }
+
+    /// 验证任务结束：已经标记的失效链保留，记录完成、取消或错误
+    fn finish_validation(cancel_token: &CancellationToken, validity: &ChainValidity, validation_result: std::result::Result<Result<bool>, JoinError>) {
+        let Ok(mut manager) = POINTER_SCAN_MANAGER.write() else {
+            return;
+        };
+        let valid = validity.valid_count();
+        manager.shared_buffer.write_chains_found(valid as i64);
+
+        match validation_result {
+            Ok(Ok(true)) if !cancel_token.is_cancelled() => {
+                info!("Pointer chain validation completed: {} of {} chains still valid", valid, validity.count());
+                manager.current_phase = ScanPhase::Completed;
+                manager.shared_buffer.write_progress(100);
+                manager.shared_buffer.write_phase(ScanPhase::Completed);
+            },
+            Ok(Ok(_)) => {
+                info!("Pointer chain validation cancelled, {} chains still valid", valid);
+                if manager.last_error == ScanErrorCode::ProcessDied {
+                    manager.current_phase = ScanPhase::Error;
+                    manager.shared_buffer.write_error_code(ScanErrorCode::ProcessDied);
+                    manager.shared_buffer.write_phase(ScanPhase::Error);
+                } else {
+                    manager.current_phase = ScanPhase::Cancelled;
+                    manager.shared_buffer.write_phase(ScanPhase::Cancelled);
+                }
+            },
+            Ok(Err(e)) => {
+                error!("Pointer chain validation failed: {}", e);
+                manager.current_phase = ScanPhase::Error;
+                manager.last_error = ScanErrorCode::InternalError;
+                manager.last_error_message = Some(e.to_string());
+                manager.shared_buffer.write_phase(ScanPhase::Error);
+                manager.shared_buffer.write_error_code(ScanErrorCode::InternalError);
+            },
+            Err(e) => {
+                error!("Pointer chain validation task panic: {}", e);
+                manager.current_phase = ScanPhase::Error;
+                manager.last_error = ScanErrorCode::InternalError;
+                manager.shared_buffer.write_phase(ScanPhase::Error);
+                manager.shared_buffer.write_error_code(ScanErrorCode::InternalError);
+            },
+        }
+    }
+
+    /// 存活的链数：验证过时为没有标记失效的链数，否则为输出文件中的链数
+    pub fn get_valid_chain_count(&self) -> u64 {
+        match self.validity {
+            Some(ref validity) => validity.valid_count(),
+            None => self.get_chain_count(),
+        }
+    }
+
+    /// 分页读取存活的链：从第 `start` 条存活的链开始最多 `count` 条，跳过验证标记为失效的链
+    ///
+    /// 没有验证过时与 `get_chain_results` 相同。验证进行中也可以调用，还没有检查的链算作存活。
+    pub fn get_valid_chain_results(&self, start: u64, count: usize) -> Result<Vec<ChainSample>> {
+        let Some(ref validity) = self.validity else {
+            return self.get_chain_results(start, count);
+        };
+        let Some(ref file) = self.chain_file else {
+            return Ok(Vec::new());
+        };
+        let Some(mut id) = validity.nth_valid(start) else {
+            return Ok(Vec::new());
+        };
+
+        let mut chains = Vec::with_capacity(count.min(VALIDATION_BATCH));
+        while chains.len() < count && id < file.count() {
+            let lines = file.read_lines(id, (count - chains.len()).clamp(64, VALIDATION_BATCH));
+            if lines.is_empty() {
+                break;
+            }
+            for line in &lines {
+                if chains.len() >= count {
+                    break;
+                }
+                if !validity.is_invalid(id) {
+                    chains.extend(ChainSample::parse(line));
+                }
+                id += 1;
+            }
+        }
+        Ok(chains)
+    }
+
     /// Clear all results and reset state.
     pub fn clear(&mut self) {
         self.current_phase = ScanPhase::Idle;
         self.last_error = ScanErrorCode::None;
+        self.last_error_message = None;
         self.shared_buffer.reset();
         self.scan_result = None;
+        self.chain_file = None;
+        self.validity = None;
+        self.samples.clear();
+    }
+
+    /// 设置扫描的工作线程数，0 使用全局线程池的线程数
+    pub fn set_worker_threads(&mut self, threads: usize) {
+        self.worker_pool.set_threads(threads);
+    }
+
+    /// 扫描的工作线程是否降到后台调度优先级
+    pub fn set_background_priority(&mut self, background: bool) {
+        self.worker_pool.set_background(background);
     }
 
     /// Start an async pointer scan.
@@ -137,6 +473,11 @@ impl PointerScanManager {
         static_modules: Vec<VmStaticData>,
         _is_layer_bfs: bool, // 不再使用，保留参数兼容性
         max_results: u32,
+        force: bool,
+        output_format: ChainOutputFormat,
+        module_filter: Option<Vec<String>>,
+        live_vma_check: bool,
+        require_present_pages: bool,
     ) -> Result<()> {
         if self.is_scanning() {
             self.last_error = ScanErrorCode::AlreadyScanning;
@@ -148,8 +489,7 @@ impl PointerScanManager {
             return Err(anyhow!("No memory regions provided"));
         }
 
-        // Update config
-        self.config = PointerScanConfig {
+        let config = PointerScanConfig {
             target_address,
             max_depth,
             max_offset,
@@ -157,10 +497,27 @@ impl PointerScanManager {
             is_layer_bfs: true, // 始终使用 BFS V2
             data_start: true,
             bss_start: false,
+            force,
+            output_format,
+            module_filter,
+            live_vma_check,
+            require_present_pages,
+            ..Default::default()
         };
+        if let Err(e) = config.validate() {
+            self.clear();
+            self.fail(&e);
+            return Err(e.into());
+        }
+
+        let pool = self.worker_pool.current()?;
+
+        // Update config
+        self.config = config;
 
         // Reset state
         self.clear();
+        self.static_modules = static_modules.clone();
         self.current_phase = ScanPhase::ScanningPointers;
         self.shared_buffer.write_phase(ScanPhase::ScanningPointers);
 
@@ -171,6 +528,7 @@ impl PointerScanManager {
         // Clone data for the async task
         let config = self.config.clone();
         let cache_dir = self.cache_dir.clone();
+        let samples = Arc::clone(&self.samples);
 
         if log_enabled!(Level::Debug) {
             info!(
@@ -184,7 +542,7 @@ impl PointerScanManager {
 
         // Spawn the scan task
         let handle = TOKIO_RUNTIME.spawn(async move {
-            Self::run_scan_task(config, regions, static_modules, cache_dir, cancel_token, max_results).await;
+            Self::run_scan_task(config, regions, static_modules, cache_dir, pool, cancel_token, max_results, samples).await;
         });
 
         self.scan_handle = Some(handle);
@@ -192,30 +550,25 @@ impl PointerScanManager {
     }
 
     /// The async scan task that runs V3 scanner (merged Phase 1 + Phase 2).
+    #[allow(clippy::too_many_arguments)]
     async fn run_scan_task(
         config: PointerScanConfig,
         regions: Vec<ScanRegion>,
         static_modules: Vec<VmStaticData>,
         _cache_dir: PathBuf,
+        pool: ScanPool,
         cancel_token: CancellationToken,
         max_results: u32,
+        samples: Arc<ChainSampler>,
     ) {
         // 生成输出文件路径
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_secs())
-            .unwrap_or(0);
-        let output_path = PathBuf::from(format!(
-            "/sdcard/pointer_scan_0x{:X}_{}.txt",
-            config.target_address,
-            timestamp
-        ));
+        let output_path = Self::output_path("pointer_scan", config.target_address);
 
         let cancel_token_clone = cancel_token.clone();
         let output_path_clone = output_path.clone();
 
-        let scan_result = tokio::task::spawn_blocking(move || {
-            let scanner = BfsV3Scanner::new(config, regions, static_modules);
+        let scan_result = pool.spawn_blocking(move || {
+            let scanner = BfsV3Scanner::new(config, regions, static_modules).with_sampler(samples);
 
             // 0 表示无限制
             let effective_max = if max_results == 0 { usize::MAX } else { max_results as usize };
@@ -223,53 +576,153 @@ impl PointerScanManager {
             scanner.run(
                 output_path_clone,
                 effective_max,
-                |phase, current, total, extra| {
-                    if let Ok(manager) = POINTER_SCAN_MANAGER.read() {
-                        match phase {
-                            ProgressPhase::ScanningPointers => {
-                                manager.shared_buffer.update_scanning_progress(
-                                    current as i32,
-                                    total as i32,
-                                    extra,
-                                );
-                            }
-                            ProgressPhase::BuildingChains => {
-                                // 首次进入 Phase 2 时更新阶段
-                                if current == 0 {
-                                    manager.shared_buffer.write_phase(ScanPhase::BuildingChains);
-                                }
-                                manager.shared_buffer.update_building_progress(
-                                    current as i32,
-                                    total as i32,
-                                    extra,
-                                );
-                            }
-                            ProgressPhase::WritingFile => {
-                                if current == 0 {
-                                    manager.shared_buffer.write_phase(ScanPhase::WritingFile);
-                                }
-                                manager.shared_buffer.update_writing_progress(
-                                    current as i32,
-                                    total as i32,
-                                    extra,
-                                );
-                            }
-                        }
-                    }
-                },
+                Self::report_progress,
                 || cancel_token_clone.is_cancelled(),
             )
         })
         .await;
 
+        Self::finish_task(&cancel_token, scan_result);
+    }
+
+    /// 异步重新扫描上一次的输出文件，只保留仍然指向 `new_target` 的链
+    ///
+    /// `static_modules` 是当前进程重新获取的静态模块，`adjust_last_offset` 见 `BfsV3Scanner::rescan`。
+    /// 进度和取消与 `start_scan_async` 相同，另外也检查共享缓冲区的取消标志。完成后结果替换为新的输出文件。
+    pub fn start_rescan_async(
+        &mut self,
+        previous_file: PathBuf,
+        new_target: u64,
+        adjust_last_offset: bool,
+        static_modules: Vec<VmStaticData>,
+    ) -> Result<()> {
+        if self.is_scanning() {
+            self.last_error = ScanErrorCode::AlreadyScanning;
+            return Err(anyhow!("Scan already in progress"));
+        }
+        if !previous_file.is_file() {
+            return Err(anyhow!("Previous scan file {:?} not found", previous_file));
+        }
+
+        let pool = self.worker_pool.current()?;
+
+        // 之后验证链时按新目标比较
+        self.config.target_address = new_target;
+        self.clear();
+        self.static_modules = static_modules.clone();
+        self.shared_buffer.clear_cancel_flag();
+        self.current_phase = ScanPhase::BuildingChains;
+        self.shared_buffer.write_phase(ScanPhase::BuildingChains);
+
+        let cancel_token = CancellationToken::new();
+        self.cancel_token = Some(cancel_token.clone());
+
+        info!(
+            "Starting pointer rescan: previous={:?}, new_target=0x{:X}, adjust_last_offset={}, static_modules={}",
+            previous_file,
+            new_target,
+            adjust_last_offset,
+            static_modules.len()
+        );
+
+        let handle = TOKIO_RUNTIME.spawn(async move {
+            Self::run_rescan_task(previous_file, new_target, adjust_last_offset, static_modules, pool, cancel_token).await;
+        });
+
+        self.scan_handle = Some(handle);
+        Ok(())
+    }
+
+    /// The async rescan task: re-resolves the chains of a previous output file.
+    async fn run_rescan_task(
+        previous_file: PathBuf,
+        new_target: u64,
+        adjust_last_offset: bool,
+        static_modules: Vec<VmStaticData>,
+        pool: ScanPool,
+        cancel_token: CancellationToken,
+    ) {
+        let output_path = Self::output_path("pointer_rescan", new_target);
+        let cancel_token_clone = cancel_token.clone();
+
+        let scan_result = pool.spawn_blocking(move || {
+            let config = PointerScanConfig { target_address: new_target, ..Default::default() };
+            let scanner = BfsV3Scanner::new(config, Vec::new(), static_modules);
+            scanner.rescan(previous_file, new_target, adjust_last_offset, output_path, Self::report_progress, || {
+                // 共享缓冲区的取消标志也转为取消令牌，结束时按取消处理
+                let requested = POINTER_SCAN_MANAGER.read().map(|manager| manager.shared_buffer.is_cancel_requested()).unwrap_or(false);
+                if requested {
+                    cancel_token_clone.cancel();
+                }
+                cancel_token_clone.is_cancelled()
+            })
+        })
+        .await;
+
+        Self::finish_task(&cancel_token, scan_result);
+    }
+
+    /// 输出文件路径：`/sdcard/<prefix>_0x<target>_<timestamp>.txt`
+    fn output_path(prefix: &str, target: u64) -> PathBuf {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        PathBuf::from(format!("/sdcard/{}_0x{:X}_{}.txt", prefix, target, timestamp))
+    }
+
+    /// 把扫描器的进度写入共享缓冲区
+    fn report_progress(phase: ProgressPhase, current: u32, total: u32, extra: i64) {
+        if let Ok(manager) = POINTER_SCAN_MANAGER.read() {
+            match phase {
+                ProgressPhase::ScanningPointers => {
+                    manager.shared_buffer.update_scanning_progress(
+                        current as i32,
+                        total as i32,
+                        extra,
+                    );
+                }
+                ProgressPhase::BuildingChains => {
+                    // 首次进入 Phase 2 时更新阶段
+                    if current == 0 {
+                        manager.shared_buffer.write_phase(ScanPhase::BuildingChains);
+                    }
+                    manager.shared_buffer.update_building_progress(
+                        current as i32,
+                        total as i32,
+                        extra,
+                    );
+                }
+                ProgressPhase::WritingFile => {
+                    if current == 0 {
+                        manager.shared_buffer.write_phase(ScanPhase::WritingFile);
+                    }
+                    manager.shared_buffer.update_writing_progress(
+                        current as i32,
+                        total as i32,
+                        extra,
+                    );
+                }
+            }
+        }
+    }
+
+    /// 扫描或重新扫描任务结束：记录取消、结果或错误
+    fn finish_task(cancel_token: &CancellationToken, scan_result: std::result::Result<Result<ScanResult>, JoinError>) {
         // 检查取消
         if cancel_token.is_cancelled() {
             if log_enabled!(Level::Debug) {
                 info!("Scan cancelled");
             }
             if let Ok(mut manager) = POINTER_SCAN_MANAGER.write() {
-                manager.current_phase = ScanPhase::Cancelled;
-                manager.shared_buffer.write_phase(ScanPhase::Cancelled);
+                if manager.last_error == ScanErrorCode::ProcessDied {
+                    manager.current_phase = ScanPhase::Error;
+                    manager.shared_buffer.write_error_code(ScanErrorCode::ProcessDied);
+                    manager.shared_buffer.write_phase(ScanPhase::Error);
+                } else {
+                    manager.current_phase = ScanPhase::Cancelled;
+                    manager.shared_buffer.write_phase(ScanPhase::Cancelled);
+                }
             }
             return;
         }
@@ -282,7 +735,16 @@ impl PointerScanManager {
                     result.total_count,
                     result.output_file.display()
                 );
+                // 写入时已经保存了索引，这里只是读回并映射输出文件；空结果没有索引文件，重新构建
+                let chain_file = match ChainFile::open(&result.output_file) {
+                    Ok(file) => Some(Arc::new(file)),
+                    Err(e) => {
+                        error!("Failed to open chain index for {}: {}", result.output_file.display(), e);
+                        None
+                    },
+                };
                 if let Ok(mut manager) = POINTER_SCAN_MANAGER.write() {
+                    manager.chain_file = chain_file;
                     manager.scan_result = Some(ScanCompleteResult {
                         total_count: result.total_count,
                         output_file: result.output_file.to_string_lossy().to_string(),
@@ -296,10 +758,16 @@ impl PointerScanManager {
             Ok(Err(e)) => {
                 error!("V3 扫描失败: {}", e);
                 if let Ok(mut manager) = POINTER_SCAN_MANAGER.write() {
-                    manager.current_phase = ScanPhase::Error;
-                    manager.last_error = ScanErrorCode::InternalError;
-                    manager.shared_buffer.write_phase(ScanPhase::Error);
-                    manager.shared_buffer.write_error_code(ScanErrorCode::InternalError);
+                    match e.downcast_ref::<PointerScanConfigError>() {
+                        Some(rejected) => manager.fail(rejected),
+                        None => {
+                            manager.current_phase = ScanPhase::Error;
+                            manager.last_error = ScanErrorCode::InternalError;
+                            manager.last_error_message = Some(e.to_string());
+                            manager.shared_buffer.write_phase(ScanPhase::Error);
+                            manager.shared_buffer.write_error_code(ScanErrorCode::InternalError);
+                        },
+                    }
                 }
             },
             Err(e) => {
@@ -317,6 +785,32 @@ impl PointerScanManager {
             info!("Pointer scan task completed");
         }
     }
+
+    /// 参数被拒绝：记录错误码和说明
+    fn fail(&mut self, e: &PointerScanConfigError) {
+        let code = e.error_code();
+        self.current_phase = ScanPhase::Error;
+        self.last_error = code;
+        self.last_error_message = Some(e.to_string());
+        self.shared_buffer.write_phase(ScanPhase::Error);
+        self.shared_buffer.write_error_code(code);
+    }
+}
+
+/// 从输出文件头部的 `# Target: 0x...` 注释读取扫描目标
+fn read_header_target(path: &Path) -> Result<Option<u64>> {
+    let reader = BufReader::new(File::open(path)?);
+    for line in reader.lines() {
+        let line = line?;
+        let Some(comment) = line.trim().strip_prefix('#') else {
+            break;
+        };
+        if let Some(target) = comment.trim().strip_prefix("Target:") {
+            let target = target.trim();
+            return Ok(u64::from_str_radix(target.strip_prefix("0x").unwrap_or(target), 16).ok());
+        }
+    }
+    Ok(None)
 }
 
 impl Default for PointerScanManager {
@@ -324,3 +818,37 @@ impl Default for PointerScanManager {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::tests::temp_dir::TempDir;
+    use std::io::Write;
+
+    #[test]
+    fn test_open_page_and_delete_output_file() {
+        let dir = TempDir::new("pointer_results");
+        let path = dir.join("chains.txt");
+        let mut file = File::create(&path).unwrap();
+        writeln!(file, "# Target: 0x7F00001000").unwrap();
+        for i in 0..3000u64 {
+            writeln!(file, "libgame.so[0]+0x{:X}->+0x10", 0x1000 + i * 8).unwrap();
+        }
+        drop(file);
+
+        let mut manager = PointerScanManager::new();
+        assert_eq!(manager.open_output_file(path.clone()).unwrap(), 3000);
+        assert_eq!(manager.target_address(), 0x7F00001000);
+        let chains = manager.get_chain_results(2998, 10).unwrap();
+        assert_eq!(chains.iter().map(|chain| chain.base_offset).collect::<Vec<_>>(), vec![0x1000 + 2998 * 8, 0x1000 + 2999 * 8]);
+        assert!(index_path(&path).exists());
+
+        // 映射先被丢掉，输出文件和索引文件都被删除，之后没有结果
+        manager.delete_output_file().unwrap();
+        assert!(!path.exists() && !index_path(&path).exists());
+        assert_eq!(manager.get_chain_count(), 0);
+        assert!(manager.get_chain_results(0, 10).unwrap().is_empty());
+        assert!(manager.get_scan_result().is_none());
+        manager.delete_output_file().unwrap();
+    }
+}
@@ -14,6 +14,11 @@
 //! - 无 rkyv 序列化开销
 //! - 直接内存映射，零拷贝访问
 //! - 类型约束简单：只需 T: Copy
+//!
+//! 内存阈值：
+//! - `with_memory_threshold` 创建的队列在数据不超过阈值时放在堆上的 Vec 中，
+//!   超过后复制到临时文件，小规模的指针扫描不需要创建文件
+//! - 临时文件创建或映射失败时（如缓存目录只读）记录警告，之后全部数据留在内存中
 
 use std::fs::{File, OpenOptions};
 use std::marker::PhantomData;
@@ -25,9 +30,11 @@ use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::RwLock;
 
 use anyhow::{anyhow, Result};
-use memmap2::MmapMut;
+use log::warn;
 use once_cell::sync::Lazy;
 
+use crate::core::self_regions::SelfMmap;
+
 /// 全局缓存目录配置
 /// 在 JNI 初始化时设置，例如: /data/data/moe.fuqiuluo.mamu/cache
 static CACHE_DIR: Lazy<RwLock<Option<PathBuf>>> = Lazy::new(|| RwLock::new(None));
@@ -49,9 +56,12 @@ fn get_cache_dir() -> Result<PathBuf> {
     cache_dir.clone().ok_or_else(|| anyhow!("Cache directory not set. Call set_cache_dir() first."))
 }
 
-/// 创建临时文件
-fn create_temp_file() -> Result<(File, PathBuf)> {
-    let cache_dir = get_cache_dir()?;
+/// 创建临时文件，dir 为 None 时使用全局缓存目录
+fn create_temp_file(dir: Option<&PathBuf>) -> Result<(File, PathBuf)> {
+    let cache_dir = match dir {
+        Some(dir) => dir.clone(),
+        None => get_cache_dir()?,
+    };
 
     // 确保目录存在
     std::fs::create_dir_all(&cache_dir).map_err(|e| anyhow!("Failed to create cache dir: {}", e))?;
@@ -81,8 +91,14 @@ pub struct MapQueue<T: Copy> {
     /// 文件路径（用于删除）
     file_path: Option<PathBuf>,
     /// mmap 映射区域
-    mmap: Option<MmapMut>,
-    /// 数据指针
+    mmap: Option<SelfMmap>,
+    /// 内存后端，只使用其容量，元素通过 data 读写（Vec 的长度始终为 0）
+    heap: Vec<T>,
+    /// 数据不超过该字节数时使用内存后端
+    memory_threshold: usize,
+    /// 临时文件目录，None 时使用全局缓存目录
+    cache_dir: Option<PathBuf>,
+    /// 数据指针，指向 mmap 或 heap
     data: Option<NonNull<T>>,
     /// 当前元素数量
     len: usize,
@@ -92,17 +108,25 @@ pub struct MapQueue<T: Copy> {
     _marker: PhantomData<T>,
 }
 
-// Safety: MapQueue 内部数据通过 mmap 管理，可以安全地跨线程发送
+// Safety: MapQueue 内部数据通过 mmap 或自有的 Vec 管理，可以安全地跨线程发送
 unsafe impl<T: Copy + Send> Send for MapQueue<T> {}
 unsafe impl<T: Copy + Sync> Sync for MapQueue<T> {}
 
 impl<T: Copy> MapQueue<T> {
     /// 创建空的 MapQueue
     pub fn new() -> Self {
+        Self::with_memory_threshold(0)
+    }
+
+    /// 创建空的 MapQueue，数据不超过 bytes 字节时留在内存中，超过后转存到临时文件
+    pub fn with_memory_threshold(bytes: usize) -> Self {
         Self {
             file: None,
             file_path: None,
             mmap: None,
+            heap: Vec::new(),
+            memory_threshold: bytes,
+            cache_dir: None,
             data: None,
             len: 0,
             capacity: 0,
@@ -135,6 +159,12 @@ impl<T: Copy> MapQueue<T> {
         self.capacity
     }
 
+    /// 数据是否已在临时文件中
+    #[inline]
+    pub fn is_file_backed(&self) -> bool {
+        self.mmap.is_some()
+    }
+
     /// 清空数据（不释放内存）
     pub fn clear(&mut self) {
         self.len = 0;
@@ -153,16 +183,65 @@ impl<T: Copy> MapQueue<T> {
             return Ok(());
         }
 
-        let new_size = new_capacity * size_of::<T>();
+        let new_size = new_capacity
+            .checked_mul(size_of::<T>())
+            .ok_or_else(|| anyhow!("MapQueue capacity overflow: {}", new_capacity))?;
 
-        // 创建新的临时文件
-        let (file, file_path) = create_temp_file()?;
+        // 转存到文件后不再回到内存
+        if self.mmap.is_none() && new_size <= self.memory_threshold {
+            return self.reserve_in_memory(new_capacity);
+        }
+
+        match self.reserve_in_file(new_capacity, new_size) {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                warn!("MapQueue falls back to memory ({} bytes): {}", new_size, e);
+                self.memory_threshold = usize::MAX;
+                self.reserve_in_memory(new_capacity)
+            },
+        }
+    }
+
+    /// 在堆上分配新容量并复制现有元素
+    fn reserve_in_memory(&mut self, new_capacity: usize) -> Result<()> {
+        let mut heap: Vec<T> = Vec::new();
+        heap.try_reserve_exact(new_capacity)
+            .map_err(|e| anyhow!("Failed to allocate {} elements: {}", new_capacity, e))?;
 
-        // 设置文件大小
-        file.set_len(new_size as u64).map_err(|e| anyhow!("Failed to set file length: {}", e))?;
+        if let Some(old_data) = self.data {
+            unsafe {
+                std::ptr::copy_nonoverlapping(old_data.as_ptr(), heap.as_mut_ptr(), self.len);
+            }
+        }
+
+        let new_data = NonNull::new(heap.as_mut_ptr()).ok_or_else(|| anyhow!("Allocation returned null pointer"))?;
+
+        // 先复制再释放旧的后备存储
+        self.cleanup_old_file();
+        self.heap = heap;
+        self.data = Some(new_data);
+        self.capacity = new_capacity;
+
+        Ok(())
+    }
 
-        // 创建 mmap 映射
-        let mut new_mmap = unsafe { MmapMut::map_mut(&file).map_err(|e| anyhow!("Failed to mmap: {}", e))? };
+    /// 创建新的临时文件映射并复制现有元素
+    fn reserve_in_file(&mut self, new_capacity: usize, new_size: usize) -> Result<()> {
+        // 创建新的临时文件
+        let (file, file_path) = create_temp_file(self.cache_dir.as_ref())?;
+
+        // 设置文件大小，失败时删除刚创建的文件
+        let mapped = file
+            .set_len(new_size as u64)
+            .map_err(|e| anyhow!("Failed to set file length: {}", e))
+            .and_then(|_| unsafe { SelfMmap::map_mut(&file).map_err(|e| anyhow!("Failed to mmap: {}", e)) });
+        let mut new_mmap = match mapped {
+            Ok(mmap) => mmap,
+            Err(e) => {
+                let _ = std::fs::remove_file(&file_path);
+                return Err(e);
+            },
+        };
 
         // 复制旧数据
         if let Some(old_data) = self.data {
@@ -175,8 +254,9 @@ impl<T: Copy> MapQueue<T> {
         // 获取新数据指针
         let new_data = NonNull::new(new_mmap.as_mut_ptr() as *mut T).ok_or_else(|| anyhow!("Mmap returned null pointer"))?;
 
-        // 删除旧文件
+        // 删除旧文件，释放内存后端
         self.cleanup_old_file();
+        self.heap = Vec::new();
 
         self.file = Some(file);
         self.file_path = Some(file_path);
@@ -384,7 +464,8 @@ impl<T: Copy> Drop for MapQueue<T> {
 
 impl<T: Copy> Clone for MapQueue<T> {
     fn clone(&self) -> Self {
-        let mut new_queue = Self::new();
+        let mut new_queue = Self::with_memory_threshold(self.memory_threshold);
+        new_queue.cache_dir = self.cache_dir.clone();
         if self.len > 0 {
             new_queue.reserve(self.len).expect("clone reserve failed");
             unsafe {
@@ -395,3 +476,109 @@ impl<T: Copy> Clone for MapQueue<T> {
         new_queue
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct Entry {
+        address: u64,
+        value: u32,
+    }
+
+    fn entry(i: usize) -> Entry {
+        Entry { address: 0x7000_0000 + (i as u64 * 0x9E37) % 0x10_0000, value: i as u32 }
+    }
+
+    fn queue_in(dir: &std::path::Path, threshold: usize) -> MapQueue<Entry> {
+        let mut queue = MapQueue::with_memory_threshold(threshold);
+        queue.cache_dir = Some(dir.to_path_buf());
+        queue
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let nanos = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_nanos();
+        std::env::temp_dir().join(format!("mamu_mapqueue_{}_{}", name, nanos))
+    }
+
+    fn file_count(dir: &std::path::Path) -> usize {
+        std::fs::read_dir(dir).map(|entries| entries.count()).unwrap_or(0)
+    }
+
+    #[test]
+    fn test_spill_mid_stream_keeps_order_and_contents() {
+        let dir = temp_dir("spill");
+        let threshold = 4096 * size_of::<Entry>();
+        let mut queue = queue_in(&dir, threshold);
+
+        for i in 0..3000 {
+            queue.push(entry(i)).unwrap();
+        }
+        assert!(!queue.is_file_backed());
+        assert_eq!(file_count(&dir), 0);
+
+        // 超过阈值的批量添加触发转存
+        let batch: Vec<Entry> = (3000..10_000).map(entry).collect();
+        queue.extend_from_slice(&batch).unwrap();
+        assert!(queue.is_file_backed());
+        assert_eq!(file_count(&dir), 1);
+        for i in 10_000..20_000 {
+            queue.push(entry(i)).unwrap();
+        }
+
+        assert_eq!(queue.len(), 20_000);
+        assert!(queue.iter().enumerate().all(|(i, e)| *e == entry(i)));
+        assert_eq!(queue[2999], entry(2999));
+        assert_eq!(queue.get(20_000), None);
+
+        // 内存中的队列排序、截断和转存后的结果一致
+        let mut in_memory = queue_in(&dir, usize::MAX);
+        in_memory.extend_from_slice(queue.as_slice()).unwrap();
+        assert!(!in_memory.is_file_backed());
+        queue.sort_unstable_by_key(|e| (e.address, e.value));
+        in_memory.sort_unstable_by_key(|e| (e.address, e.value));
+        queue.truncate(15_000);
+        in_memory.truncate(15_000);
+        assert_eq!(queue.as_slice(), in_memory.as_slice());
+        assert_eq!(queue.pop(), in_memory.pop());
+
+        let cloned = in_memory.clone();
+        assert!(!cloned.is_file_backed());
+        assert_eq!(cloned.as_slice(), in_memory.as_slice());
+
+        drop(queue);
+        assert_eq!(file_count(&dir), 0);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_falls_back_to_memory_when_temp_file_fails() {
+        // 缓存目录是一个普通文件，创建目录和临时文件都会失败
+        let blocker = temp_dir("blocker");
+        std::fs::write(&blocker, b"").unwrap();
+
+        let mut queue = queue_in(&blocker, 0);
+        for i in 0..5000 {
+            queue.push(entry(i)).unwrap();
+        }
+        assert!(!queue.is_file_backed());
+        assert_eq!(queue.len(), 5000);
+        assert!(queue.iter().enumerate().all(|(i, e)| *e == entry(i)));
+
+        // 已转存到文件的队列在之后扩容失败时也迁回内存
+        let dir = temp_dir("later");
+        let mut queue = queue_in(&dir, 0);
+        queue.extend_from_slice(&(0..100).map(entry).collect::<Vec<_>>()).unwrap();
+        assert!(queue.is_file_backed());
+        queue.cache_dir = Some(blocker.clone());
+        queue.resize(5000, entry(0)).unwrap();
+        assert!(!queue.is_file_backed());
+        assert_eq!(file_count(&dir), 0);
+        assert!(queue.iter().take(100).enumerate().all(|(i, e)| *e == entry(i)));
+        assert!(queue.iter().skip(100).all(|e| *e == entry(0)));
+
+        let _ = std::fs::remove_file(&blocker);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -12,9 +12,13 @@
 //! - `storage`: Memory-mapped storage for large pointer datasets (legacy, uses rkyv)
 //! - `mapqueue_v2`: New MapQueue implementation (tmpfile + mmap, no serialization)
 //! - `shared_buffer`: Progress communication with Kotlin via shared memory
-//! - `scanner`: Phase 1 - Scan all memory for valid pointers
+//! - `scanner`: Phase 1 - Memory regions to scan for pointers
+//! - `address_filter`: Optional Phase 1 checks against the live VMA list and present pages
 //! - `chain_builder`: Phase 2 - Build pointer chains from target address
 //!   - `bfs_v2`: BFS algorithm from PointerScan-rust (implicit tree structure)
+//! - `chain_index`: Sparse line index and read-only mapping of the output file for paging chain results
+//! - `samples`: Reservoir-sampled example chains available while the scan runs
+//! - `validate`: Re-resolve saved chains against live memory
 //! - `manager`: Async task management and coordination
 //!
 //! # Usage
@@ -36,13 +40,17 @@
 //! let chains = manager.get_chain_results(0, 100);
 //! ```
 
+pub mod address_filter;
 pub mod chain_builder;
+pub mod chain_index;
 pub mod manager;
 pub mod mapqueue_v2;
+pub mod samples;
 pub mod scanner;
 pub mod shared_buffer;
 pub mod storage;
 pub mod types;
+pub mod validate;
 
 // Re-export commonly used types
 pub use manager::POINTER_SCAN_MANAGER;
@@ -0,0 +1,376 @@
+//! Pointer chain samples
+//!
+//! Phase 2 可能持续很久，用户希望在扫描结束前看到几条示例链来判断参数是否合理。
+//! 链在构建过程中按 BFS 层级产生，浅层的链总是先出现，所以用蓄水池抽样保留最多 K 条，
+//! 使每条产生过的链被保留的概率相同；另外单独记录目前最浅的一条。
+//!
+//! 抽样只持有自己的锁，不涉及输出文件的写入。
+//!
+//! 输出文件中每行一条链，行的格式由 `ChainOutputFormat` 决定；`write_chain` 和 `ChainSample::parse`
+//! 在这里成对实现，结果列表、验证和重新扫描读取任何一种格式的输出文件。
+
+use crate::pointer_scan::types::ChainOutputFormat;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+use std::sync::Mutex;
+
+/// 默认保留的示例链数量
+pub const DEFAULT_SAMPLE_CAPACITY: usize = 16;
+
+/// 一条示例链：`module[index]+base_offset->offset1->...`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainSample {
+    pub module: String,
+    pub module_index: i32,
+    pub base_offset: u64,
+    pub offsets: Vec<i64>,
+    /// 链的深度（解引用次数）
+    #[serde(default)]
+    pub depth: u32,
+}
+
+impl ChainSample {
+    /// 默认输出格式的文本
+    pub fn to_chain_string(&self) -> String {
+        let mut text = Vec::with_capacity(64);
+        let _ = write_chain(&mut text, ChainOutputFormat::Native, &self.module, self.module_index, self.base_offset, &self.offsets);
+        String::from_utf8(text).unwrap_or_default()
+    }
+
+    /// 解析输出文件中的一行（任一输出格式），注释、空行和格式不对的行返回 None
+    ///
+    /// Cheat Engine 格式不带模块序号，按序号 0 返回；写出时基址偏移已经相对同名模块的第一个段。
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let (module, module_index, base_offset, offsets) = if line.starts_with('{') {
+            let chain: ChainSample = serde_json::from_str(line).ok()?;
+            (chain.module, chain.module_index, chain.base_offset, chain.offsets)
+        } else if let Some(rest) = line.strip_prefix('"') {
+            let (module, rest) = rest.split_once("\"+0x")?;
+            let mut parts = rest.split(" -> ");
+            let base_offset = u64::from_str_radix(parts.next()?, 16).ok()?;
+            let offsets = parts.map(|part| parse_offset(part, true)).collect::<Option<Vec<i64>>>()?;
+            (module.to_string(), 0, base_offset, offsets)
+        } else if line.contains(", ") {
+            let mut parts = line.split(", ");
+            let (module, module_index) = parse_module(parts.next()?)?;
+            let base_offset = u64::from_str_radix(parts.next()?.strip_prefix("0x")?, 16).ok()?;
+            let offsets = parts.map(|part| parse_offset(part, false)).collect::<Option<Vec<i64>>>()?;
+            (module, module_index, base_offset, offsets)
+        } else {
+            let mut parts = line.split("->");
+            let (module_part, base_part) = parts.next()?.rsplit_once("+0x")?;
+            let (module, module_index) = parse_module(module_part)?;
+            let base_offset = u64::from_str_radix(base_part, 16).ok()?;
+            let offsets = parts.map(|part| parse_offset(part, true)).collect::<Option<Vec<i64>>>()?;
+            (module, module_index, base_offset, offsets)
+        };
+
+        Some(Self {
+            module,
+            module_index,
+            base_offset,
+            depth: offsets.len() as u32,
+            offsets,
+        })
+    }
+}
+
+/// JSON lines 格式的一行，字段与 `ChainSample` 相同（不含 depth）
+#[derive(Serialize)]
+struct ChainLine<'a> {
+    module: &'a str,
+    module_index: i32,
+    base_offset: u64,
+    offsets: &'a [i64],
+}
+
+/// 按输出格式写一条链，不含换行
+pub fn write_chain<W: Write>(
+    writer: &mut W,
+    format: ChainOutputFormat,
+    module: &str,
+    module_index: i32,
+    base_offset: u64,
+    offsets: &[i64],
+) -> io::Result<()> {
+    match format {
+        ChainOutputFormat::Native => {
+            write!(writer, "{}[{}]+0x{:X}", module, module_index, base_offset)?;
+            for &offset in offsets {
+                write_signed_offset(writer, "->", offset, true)?;
+            }
+        },
+        ChainOutputFormat::CheatEngine => {
+            write!(writer, "\"{}\"+0x{:X}", module, base_offset)?;
+            for &offset in offsets {
+                write_signed_offset(writer, " -> ", offset, true)?;
+            }
+        },
+        ChainOutputFormat::GameGuardian => {
+            write!(writer, "{}[{}], 0x{:X}", module, module_index, base_offset)?;
+            for &offset in offsets {
+                write_signed_offset(writer, ", ", offset, false)?;
+            }
+        },
+        ChainOutputFormat::JsonLines => {
+            let line = ChainLine { module, module_index, base_offset, offsets };
+            serde_json::to_writer(&mut *writer, &line)?;
+        },
+    }
+    Ok(())
+}
+
+fn write_signed_offset<W: Write>(writer: &mut W, separator: &str, offset: i64, plus: bool) -> io::Result<()> {
+    if offset < 0 {
+        write!(writer, "{}-0x{:X}", separator, offset.unsigned_abs())
+    } else if plus {
+        write!(writer, "{}+0x{:X}", separator, offset)
+    } else {
+        write!(writer, "{}0x{:X}", separator, offset)
+    }
+}
+
+/// `module[index]`
+fn parse_module(text: &str) -> Option<(String, i32)> {
+    let (module, index) = text.strip_suffix(']')?.rsplit_once('[')?;
+    Some((module.to_string(), index.parse().ok()?))
+}
+
+/// `+0x10` / `-0x8`；`plus` 为 false 时正数不带加号（`0x10`）
+fn parse_offset(text: &str, plus: bool) -> Option<i64> {
+    let (negative, digits) = match text.strip_prefix("-0x") {
+        Some(digits) => (true, digits),
+        None if plus => (false, text.strip_prefix("+0x")?),
+        None => (false, text.strip_prefix("0x")?),
+    };
+    let magnitude = i64::from_str_radix(digits, 16).ok()?;
+    Some(if negative { -magnitude } else { magnitude })
+}
+
+/// 抽样结果快照
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SamplesSnapshot {
+    /// 到目前为止提交的链数（每个静态模块指针提交一次）
+    pub seen: u64,
+    pub samples: Vec<ChainSample>,
+    pub shallowest: Option<ChainSample>,
+}
+
+struct SamplerState {
+    seen: u64,
+    samples: Vec<ChainSample>,
+    shallowest: Option<ChainSample>,
+    rng: StdRng,
+}
+
+/// 扫描过程中产生的示例链（蓄水池抽样）
+pub struct ChainSampler {
+    capacity: usize,
+    state: Mutex<SamplerState>,
+}
+
+impl ChainSampler {
+    pub fn new(capacity: usize) -> Self {
+        Self::with_seed(capacity, rand::random())
+    }
+
+    pub fn with_seed(capacity: usize, seed: u64) -> Self {
+        Self {
+            capacity,
+            state: Mutex::new(SamplerState {
+                seen: 0,
+                samples: Vec::with_capacity(capacity),
+                shallowest: None,
+                rng: StdRng::seed_from_u64(seed),
+            }),
+        }
+    }
+
+    /// 清空样本，扫描开始时调用
+    pub fn clear(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.seen = 0;
+            state.samples.clear();
+            state.shallowest = None;
+        }
+    }
+
+    /// 提交一条深度为 `depth` 的链，只有被保留（进入样本或成为最浅链）时才调用 `build` 构造
+    pub fn offer_with<B>(&self, depth: u32, build: B)
+    where
+        B: FnOnce() -> Option<ChainSample>,
+    {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+
+        state.seen += 1;
+        let slot = if state.samples.len() < self.capacity {
+            Some(state.samples.len())
+        } else {
+            let seen = state.seen;
+            let j = state.rng.random_range(0..seen) as usize;
+            (j < self.capacity).then_some(j)
+        };
+        let shallower = state.shallowest.as_ref().is_none_or(|s| depth < s.depth);
+
+        if slot.is_none() && !shallower {
+            return;
+        }
+        let Some(sample) = build() else {
+            return;
+        };
+
+        if shallower {
+            state.shallowest = Some(sample.clone());
+        }
+        match slot {
+            Some(i) if i == state.samples.len() => state.samples.push(sample),
+            Some(i) => state.samples[i] = sample,
+            None => {},
+        }
+    }
+
+    pub fn snapshot(&self) -> SamplesSnapshot {
+        match self.state.lock() {
+            Ok(state) => SamplesSnapshot {
+                seen: state.seen,
+                samples: state.samples.clone(),
+                shallowest: state.shallowest.clone(),
+            },
+            Err(_) => SamplesSnapshot::default(),
+        }
+    }
+}
+
+impl Default for ChainSampler {
+    fn default() -> Self {
+        Self::new(DEFAULT_SAMPLE_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(id: u64, depth: u32) -> ChainSample {
+        ChainSample {
+            module: "libtest.so".to_string(),
+            module_index: 0,
+            base_offset: id,
+            offsets: vec![0; depth as usize],
+            depth,
+        }
+    }
+
+    #[test]
+    fn test_chain_string_format() {
+        let chain = ChainSample {
+            module: "libgame.so".to_string(),
+            module_index: 1,
+            base_offset: 0x1A0,
+            offsets: vec![0x10, -0x8],
+            depth: 2,
+        };
+        assert_eq!(chain.to_chain_string(), "libgame.so[1]+0x1A0->+0x10->-0x8");
+        assert_eq!(ChainSample::parse(&chain.to_chain_string()), Some(chain));
+
+        let root = ChainSample::parse("libc++_shared.so[0]+0x20\n").unwrap();
+        assert_eq!((root.module.as_str(), root.base_offset, root.depth), ("libc++_shared.so", 0x20, 0));
+        for line in ["", "# Target: 0x1234", "libgame.so+0x10", "libgame.so[0]+0x10->0x8", "libgame.so[x]+0x10"] {
+            assert_eq!(ChainSample::parse(line), None, "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn test_output_formats_round_trip() {
+        let chain = ChainSample {
+            module: "libgame.so".to_string(),
+            module_index: 0,
+            base_offset: 0x1A0,
+            offsets: vec![0x10, -0x8, 0],
+            depth: 3,
+        };
+        let expected = [
+            (ChainOutputFormat::Native, "libgame.so[0]+0x1A0->+0x10->-0x8->+0x0"),
+            (ChainOutputFormat::CheatEngine, "\"libgame.so\"+0x1A0 -> +0x10 -> -0x8 -> +0x0"),
+            (ChainOutputFormat::GameGuardian, "libgame.so[0], 0x1A0, 0x10, -0x8, 0x0"),
+            (ChainOutputFormat::JsonLines, r#"{"module":"libgame.so","module_index":0,"base_offset":416,"offsets":[16,-8,0]}"#),
+        ];
+        for (format, text) in expected {
+            let mut line = Vec::new();
+            write_chain(&mut line, format, &chain.module, chain.module_index, chain.base_offset, &chain.offsets).unwrap();
+            assert_eq!(String::from_utf8(line).unwrap(), text);
+            assert_eq!(ChainSample::parse(text), Some(chain.clone()), "{:?}", format);
+        }
+
+        // Cheat Engine 格式不带序号
+        assert_eq!(ChainSample::parse("\"libgame.so\"+0x20").map(|c| (c.module_index, c.depth)), Some((0, 0)));
+        for line in ["\"libgame.so\"+0x20->+0x8", "libgame.so[0], 0x20, +0x8", "{\"module\":1}"] {
+            assert_eq!(ChainSample::parse(line), None, "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn test_reservoir_is_uniform() {
+        const ITEMS: u64 = 100;
+        const CAPACITY: usize = 10;
+        const RUNS: u64 = 2000;
+
+        let mut hits = vec![0u32; ITEMS as usize];
+        for run in 0..RUNS {
+            let sampler = ChainSampler::with_seed(CAPACITY, run);
+            for id in 0..ITEMS {
+                // 深度随 id 增加，模拟浅层链先出现
+                sampler.offer_with((id / 10) as u32, || Some(sample(id, (id / 10) as u32)));
+            }
+            let snapshot = sampler.snapshot();
+            assert_eq!(snapshot.seen, ITEMS);
+            assert_eq!(snapshot.samples.len(), CAPACITY);
+            for s in snapshot.samples {
+                hits[s.base_offset as usize] += 1;
+            }
+        }
+
+        // 每条链期望被保留 RUNS * CAPACITY / ITEMS = 200 次，标准差约 13
+        let expected = (RUNS * CAPACITY as u64 / ITEMS) as f64;
+        for (id, &count) in hits.iter().enumerate() {
+            assert!(
+                (count as f64 - expected).abs() < 80.0,
+                "item {} kept {} times, expected ~{}",
+                id,
+                count,
+                expected
+            );
+        }
+        let early: u32 = hits[..50].iter().sum();
+        let late: u32 = hits[50..].iter().sum();
+        assert!((early as f64 / late as f64 - 1.0).abs() < 0.1, "early={} late={}", early, late);
+    }
+
+    #[test]
+    fn test_shallowest_chain_tracking() {
+        let sampler = ChainSampler::with_seed(2, 7);
+        for (id, depth) in [(0, 3), (1, 2), (2, 5), (3, 1), (4, 4), (5, 1)] {
+            sampler.offer_with(depth, || Some(sample(id, depth)));
+        }
+
+        let snapshot = sampler.snapshot();
+        // 同样深度的链不替换已有的最浅链
+        assert_eq!(snapshot.shallowest, Some(sample(3, 1)));
+        assert_eq!(snapshot.seen, 6);
+        assert_eq!(snapshot.samples.len(), 2);
+
+        sampler.clear();
+        let snapshot = sampler.snapshot();
+        assert_eq!(snapshot.seen, 0);
+        assert!(snapshot.samples.is_empty() && snapshot.shallowest.is_none());
+    }
+}
@@ -4,36 +4,90 @@
 //! and status between the Rust native code and the Kotlin UI layer.
 //! The buffer is a direct ByteBuffer allocated on the Kotlin side and passed
 //! to Rust via JNI.
+//!
+//! Memory layout (60 bytes, version 1):
+//! ```text
+//! [0-15]  header         (Rust writes)  magic, layout version, capabilities, size, see `core::shared_header`
+//! [16-19] phase          (Rust writes)  ScanPhase enum
+//! [20-23] progress       (Rust writes)  0-100
+//! [24-27] regions_done   (Rust writes)  completed region count
+//! [28-35] pointers_found (Rust writes)  total pointers found (i64)
+//! [36-43] chains_found   (Rust writes)  total chains found (i64)
+//! [44-47] current_depth  (Rust writes)  current search depth
+//! [48-51] heartbeat      (Rust writes)  changes periodically to indicate liveness
+//! [52-55] cancel_flag    (Kotlin writes) 1 = cancel requested
+//! [56-59] error_code     (Rust writes)  error code when phase is Error
+//! ```
 
+use crate::core::shared_header::{self, HEADER_SIZE, SharedHeader};
 use std::sync::atomic::{AtomicPtr, Ordering};
 
 /// Size of the shared buffer in bytes.
-pub const SHARED_BUFFER_SIZE: usize = 48;
+pub const SHARED_BUFFER_SIZE: usize = 60;
+
+/// Layout version, bumped whenever fields are added or moved.
+pub const SHARED_BUFFER_VERSION: i32 = 1;
+
+/// Header magic of the pointer scan buffer, "MPTR".
+pub const SHARED_BUFFER_MAGIC: u32 = u32::from_le_bytes(*b"MPTR");
 
 /// Offsets for fields in the shared buffer.
-pub mod offsets {
+pub mod layout {
     /// Scan phase (i32): Idle, ScanningPointers, BuildingChains, Completed, etc.
-    pub const PHASE: usize = 0;
+    pub const PHASE: usize = 16;
     /// Progress percentage (i32): 0-100
-    pub const PROGRESS: usize = 4;
+    pub const PROGRESS: usize = 20;
     /// Number of memory regions processed (i32)
-    pub const REGIONS_DONE: usize = 8;
+    pub const REGIONS_DONE: usize = 24;
     /// Total pointers found (i64)
-    pub const POINTERS_FOUND: usize = 12;
+    pub const POINTERS_FOUND: usize = 28;
     /// Total pointer chains found (i64)
-    pub const CHAINS_FOUND: usize = 20;
+    pub const CHAINS_FOUND: usize = 36;
     /// Current search depth (i32)
-    pub const CURRENT_DEPTH: usize = 28;
+    pub const CURRENT_DEPTH: usize = 44;
     /// Heartbeat value (i32): Changes periodically to indicate liveness
-    pub const HEARTBEAT: usize = 32;
+    pub const HEARTBEAT: usize = 48;
     /// Cancel flag (i32): Set to 1 by Kotlin to request cancellation
-    pub const CANCEL_FLAG: usize = 36;
+    pub const CANCEL_FLAG: usize = 52;
     /// Error code (i32)
-    pub const ERROR_CODE: usize = 40;
-    /// Reserved for future use
-    pub const RESERVED: usize = 44;
+    pub const ERROR_CODE: usize = 56;
+
+    /// Every field as (offset, size) in layout order.
+    pub const FIELDS: [(usize, usize); 9] = [
+        (PHASE, 4),
+        (PROGRESS, 4),
+        (REGIONS_DONE, 4),
+        (POINTERS_FOUND, 8),
+        (CHAINS_FOUND, 8),
+        (CURRENT_DEPTH, 4),
+        (HEARTBEAT, 4),
+        (CANCEL_FLAG, 4),
+        (ERROR_CODE, 4),
+    ];
 }
 
+const _: () = assert!(shared_header::fields_fit(&layout::FIELDS, SHARED_BUFFER_SIZE));
+const _: () = assert!(layout::ERROR_CODE + 4 == SHARED_BUFFER_SIZE);
+const _: () = assert!(layout::PHASE == HEADER_SIZE);
+
+/// Bits of the capabilities field in the header.
+pub mod capabilities {
+    /// current_depth is reported while building chains.
+    pub const DEPTH_PROGRESS: i32 = 1;
+    /// The WritingFile phase reports its own 0-100 progress and the chains written so far.
+    pub const WRITE_PROGRESS: i32 = 2;
+    /// The Validating phase reports its 0-100 progress and the surviving chain count in chains_found.
+    pub const VALIDATION_PROGRESS: i32 = 4;
+}
+
+/// Header written when the buffer is set.
+pub const SHARED_BUFFER_HEADER: SharedHeader = SharedHeader {
+    magic: SHARED_BUFFER_MAGIC,
+    layout_version: SHARED_BUFFER_VERSION,
+    capabilities: capabilities::DEPTH_PROGRESS | capabilities::WRITE_PROGRESS | capabilities::VALIDATION_PROGRESS,
+    size: SHARED_BUFFER_SIZE,
+};
+
 /// Shared buffer for communicating with Kotlin.
 pub struct PointerScanSharedBuffer {
     ptr: AtomicPtr<u8>,
@@ -62,15 +116,21 @@ impl PointerScanSharedBuffer {
         }
         self.ptr.store(ptr, Ordering::SeqCst);
         self.len = len;
+        SHARED_BUFFER_HEADER.write(|offset, value| self.write_i32(offset, value));
         true
     }
 
+    /// Layout version in the header, 0 when the buffer is not set.
+    pub fn layout_version(&self) -> i32 {
+        SHARED_BUFFER_HEADER.read_version(|offset| self.read_i32(offset))
+    }
+
     /// Check if the buffer is initialized.
     pub fn is_initialized(&self) -> bool {
         !self.ptr.load(Ordering::Relaxed).is_null()
     }
 
-    /// Reset the buffer contents to initial state.
+    /// Reset the buffer contents to initial state, keeping the header.
     pub fn reset(&self) {
         let ptr = self.ptr.load(Ordering::Relaxed);
         if ptr.is_null() {
@@ -78,8 +138,8 @@ impl PointerScanSharedBuffer {
         }
 
         unsafe {
-            // Zero out the entire buffer
-            std::ptr::write_bytes(ptr, 0, SHARED_BUFFER_SIZE);
+            // Zero out every field after the header
+            std::ptr::write_bytes(ptr.add(HEADER_SIZE), 0, SHARED_BUFFER_SIZE - HEADER_SIZE);
         }
     }
 
@@ -124,54 +184,54 @@ impl PointerScanSharedBuffer {
 
     /// Write the current scan phase.
     pub fn write_phase(&self, phase: crate::pointer_scan::types::ScanPhase) {
-        self.write_i32(offsets::PHASE, phase as i32);
+        self.write_i32(layout::PHASE, phase as i32);
     }
 
     /// Write the progress percentage (0-100).
     pub fn write_progress(&self, progress: i32) {
         let clamped = progress.clamp(0, 100);
-        self.write_i32(offsets::PROGRESS, clamped);
+        self.write_i32(layout::PROGRESS, clamped);
     }
 
     /// Write the number of memory regions processed.
     pub fn write_regions_done(&self, count: i32) {
-        self.write_i32(offsets::REGIONS_DONE, count);
+        self.write_i32(layout::REGIONS_DONE, count);
     }
 
     /// Write the total number of pointers found.
     pub fn write_pointers_found(&self, count: i64) {
-        self.write_i64(offsets::POINTERS_FOUND, count);
+        self.write_i64(layout::POINTERS_FOUND, count);
     }
 
     /// Write the total number of chains found.
     pub fn write_chains_found(&self, count: i64) {
-        self.write_i64(offsets::CHAINS_FOUND, count);
+        self.write_i64(layout::CHAINS_FOUND, count);
     }
 
     /// Write the current search depth.
     pub fn write_current_depth(&self, depth: i32) {
-        self.write_i32(offsets::CURRENT_DEPTH, depth);
+        self.write_i32(layout::CURRENT_DEPTH, depth);
     }
 
     /// Write the error code.
     pub fn write_error_code(&self, code: crate::pointer_scan::types::ScanErrorCode) {
-        self.write_i32(offsets::ERROR_CODE, code as i32);
+        self.write_i32(layout::ERROR_CODE, code as i32);
     }
 
     /// Update the heartbeat value.
     pub fn update_heartbeat(&self) {
         let value = self.heartbeat_counter.fetch_add(1, Ordering::Relaxed);
-        self.write_i32(offsets::HEARTBEAT, value as i32);
+        self.write_i32(layout::HEARTBEAT, value as i32);
     }
 
     /// Check if cancellation was requested.
     pub fn is_cancel_requested(&self) -> bool {
-        self.read_i32(offsets::CANCEL_FLAG) != 0
+        self.read_i32(layout::CANCEL_FLAG) != 0
     }
 
     /// Clear the cancel flag.
     pub fn clear_cancel_flag(&self) {
-        self.write_i32(offsets::CANCEL_FLAG, 0);
+        self.write_i32(layout::CANCEL_FLAG, 0);
     }
 
     /// Update progress for Phase 1 (pointer scanning).
@@ -213,6 +273,18 @@ impl PointerScanSharedBuffer {
         self.write_chains_found(chains_written);
         self.update_heartbeat();
     }
+
+    /// Update progress of chain validation, `valid` is the surviving chain count.
+    pub fn update_validating_progress(&self, checked: u64, total: u64, valid: i64) {
+        let progress = if total > 0 {
+            (checked as f64 / total as f64 * 100.0) as i32
+        } else {
+            0
+        };
+        self.write_progress(progress);
+        self.write_chains_found(valid);
+        self.update_heartbeat();
+    }
 }
 
 impl Default for PointerScanSharedBuffer {
@@ -225,3 +297,59 @@ impl Default for PointerScanSharedBuffer {
 unsafe impl Send for PointerScanSharedBuffer {}
 unsafe impl Sync for PointerScanSharedBuffer {}
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pointer_scan::types::{ScanErrorCode, ScanPhase};
+
+    fn raw_i32(memory: &[u8], offset: usize) -> i32 {
+        i32::from_le_bytes(memory[offset..offset + 4].try_into().unwrap())
+    }
+
+    fn raw_i64(memory: &[u8], offset: usize) -> i64 {
+        i64::from_le_bytes(memory[offset..offset + 8].try_into().unwrap())
+    }
+
+    /// 每个字段写入后按 Kotlin 的方式从原始字节读取，确认偏移和宽度
+    #[test]
+    fn test_every_field_round_trips_through_raw_bytes() {
+        let mut memory = [0xAAu8; SHARED_BUFFER_SIZE];
+        let mut buffer = PointerScanSharedBuffer::new();
+        assert!(!buffer.set(memory.as_mut_ptr(), 48));
+        assert!(buffer.set(memory.as_mut_ptr(), memory.len()));
+        assert_eq!(buffer.layout_version(), SHARED_BUFFER_VERSION);
+        buffer.reset();
+
+        buffer.write_phase(ScanPhase::BuildingChains);
+        buffer.write_progress(130);
+        buffer.write_regions_done(12);
+        buffer.write_pointers_found(0x2_0000_0001);
+        buffer.write_chains_found(0x3_0000_0002);
+        buffer.write_current_depth(4);
+        buffer.update_heartbeat();
+        buffer.update_heartbeat();
+        buffer.write_error_code(ScanErrorCode::MemoryReadFailed);
+        memory[layout::CANCEL_FLAG..layout::CANCEL_FLAG + 4].copy_from_slice(&1i32.to_le_bytes());
+        assert!(buffer.is_cancel_requested());
+
+        assert_eq!(&memory[..4], b"MPTR");
+        assert_eq!(raw_i32(&memory, shared_header::offsets::LAYOUT_VERSION), SHARED_BUFFER_VERSION);
+        assert_eq!(raw_i32(&memory, shared_header::offsets::CAPABILITIES), SHARED_BUFFER_HEADER.capabilities);
+        assert_eq!(raw_i32(&memory, shared_header::offsets::SIZE), SHARED_BUFFER_SIZE as i32);
+        assert_eq!(raw_i32(&memory, layout::PHASE), ScanPhase::BuildingChains as i32);
+        assert_eq!(raw_i32(&memory, layout::PROGRESS), 100);
+        assert_eq!(raw_i32(&memory, layout::REGIONS_DONE), 12);
+        assert_eq!(raw_i64(&memory, layout::POINTERS_FOUND), 0x2_0000_0001);
+        assert_eq!(raw_i64(&memory, layout::CHAINS_FOUND), 0x3_0000_0002);
+        assert_eq!(raw_i32(&memory, layout::CURRENT_DEPTH), 4);
+        assert_eq!(raw_i32(&memory, layout::HEARTBEAT), 1);
+        assert_eq!(raw_i32(&memory, layout::CANCEL_FLAG), 1);
+        assert_eq!(raw_i32(&memory, layout::ERROR_CODE), ScanErrorCode::MemoryReadFailed as i32);
+
+        // reset 清空所有字段但保留头
+        buffer.reset();
+        assert_eq!(buffer.layout_version(), SHARED_BUFFER_VERSION);
+        assert!(memory[HEADER_SIZE..].iter().all(|&b| b == 0));
+    }
+}
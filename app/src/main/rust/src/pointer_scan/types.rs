@@ -1,6 +1,10 @@
+use crate::core::region_map::ModuleInfo;
+use crate::pointer_scan::scanner::ScanRegion;
 use rkyv::rancor::Error;
 use rkyv::util::AlignedVec;
 use rkyv::{deserialize, Archive, Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
 
 #[repr(C)]
 #[derive(Archive, Deserialize, Serialize, Debug, PartialEq, Clone, Copy)]
@@ -67,6 +71,52 @@ impl VmStaticData {
     }
 }
 
+/// Assign indices and first_module_base_addr to static modules with duplicate names
+/// 同名模块共享第一个段的基址，用于计算统一的偏移
+pub fn assign_module_indices(modules: &mut [VmStaticData]) {
+    let mut name_counts: HashMap<String, u32> = HashMap::new();
+    let mut first_base_addrs: HashMap<String, u64> = HashMap::new();
+    for module in modules {
+        let count = name_counts.entry(module.name.clone()).or_insert(0);
+        module.index = *count;
+        if *count == 0 {
+            // 记录该名称第一个模块的基址
+            first_base_addrs.insert(module.name.clone(), module.base_address);
+        }
+        // 所有同名模块共享第一个段的基址
+        module.first_module_base_addr = *first_base_addrs.get(&module.name).unwrap();
+        *count += 1;
+    }
+}
+
+/// 由进程的模块列表（`DriverManager::list_modules`）确定扫描区域中的静态段，代替 Kotlin 逐个区域传入的标记
+///
+/// 有可执行段的模块范围内的同名区域是静态段；紧接在模块末尾的 `[anon:.bss]` 区域也是静态段，
+/// 按所属模块命名，偏移相对模块基址计算。
+pub fn static_modules_from_list(regions: &[ScanRegion], modules: &[ModuleInfo]) -> Vec<VmStaticData> {
+    let modules: Vec<&ModuleInfo> = modules.iter().filter(|module| module.is_executable_segment).collect();
+    let mut static_modules = Vec::new();
+    for region in regions {
+        let index = modules.partition_point(|module| module.base <= region.start);
+        if let Some(module) = index.checked_sub(1).map(|i| modules[i])
+            && module.contains(region.start)
+            && region.end <= module.end
+            && region.name == module.name
+        {
+            static_modules.push(VmStaticData::new(region.name.clone(), region.start, region.end, true));
+            continue;
+        }
+        if region.name == "[anon:.bss]"
+            && let Some(module) = modules.iter().find(|module| module.end == region.start)
+        {
+            static_modules.push(VmStaticData::new(module.name.clone(), region.start, region.end, true));
+        }
+    }
+    static_modules.sort_by_key(|module| module.base_address);
+    assign_module_indices(&mut static_modules);
+    static_modules
+}
+
 /// A single step in a pointer chain.
 #[derive(Debug, Clone)]
 pub struct PointerChainStep {
@@ -164,6 +214,55 @@ impl PointerChain {
     }
 }
 
+/// Maximum accepted chain depth.
+pub const MAX_SCAN_DEPTH: u32 = 10;
+
+/// Default ceiling for `max_offset`.
+pub const DEFAULT_MAX_OFFSET_CEILING: u32 = 0x10000;
+
+/// Phase 2 估算的候选数超过此值时拒绝扫描（除非 force）
+pub const SEARCH_SPACE_THRESHOLD: f64 = 1e9;
+
+/// 用户空间地址上限（arm64 48 位 VA），Phase 1 收集指针时同样去掉高 16 位
+const USER_ADDRESS_LIMIT: u64 = 1 << 48;
+
+/// 输出文件中链的格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(i32)]
+pub enum ChainOutputFormat {
+    /// `libgame.so[0]+0x1A0->+0x10->-0x8`
+    #[default]
+    Native = 0,
+    /// Cheat Engine 风格：`"libgame.so"+0x1A0 -> +0x10 -> -0x8`，基址偏移相对同名模块的第一个段
+    CheatEngine = 1,
+    /// GameGuardian 风格：`libgame.so[0], 0x1A0, 0x10, -0x8`，基址偏移和每层偏移依次列出
+    GameGuardian = 2,
+    /// 每行一个 JSON 对象：`{"module","module_index","base_offset","offsets"}`
+    JsonLines = 3,
+}
+
+impl ChainOutputFormat {
+    pub const ALL: [ChainOutputFormat; 4] = [Self::Native, Self::CheatEngine, Self::GameGuardian, Self::JsonLines];
+
+    pub fn from_id(id: i32) -> Option<Self> {
+        Self::ALL.into_iter().find(|format| *format as i32 == id)
+    }
+
+    /// 写在输出文件头部 `# Format:` 之后的说明，重新扫描时据此识别格式
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Native => "module_name[index]+base_offset->offset1->offset2->...",
+            Self::CheatEngine => "\"module_name\"+base_offset -> offset1 -> offset2 -> ... (Cheat Engine)",
+            Self::GameGuardian => "module_name[index], base_offset, offset1, offset2, ... (GameGuardian)",
+            Self::JsonLines => "JSON lines {module, module_index, base_offset, offsets}",
+        }
+    }
+
+    pub fn from_description(description: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|format| format.description() == description)
+    }
+}
+
 /// Configuration for pointer scanning.
 #[derive(Debug, Clone)]
 pub struct PointerScanConfig {
@@ -181,6 +280,18 @@ pub struct PointerScanConfig {
     pub data_start: bool,
     /// lookup Base Addr from start of .bss
     pub bss_start: bool,
+    /// Upper bound accepted for `max_offset` (default: 0x10000)
+    pub max_offset_ceiling: u32,
+    /// Skip the search space estimate between Phase 1 and Phase 2
+    pub force: bool,
+    /// Line format of the output file
+    pub output_format: ChainOutputFormat,
+    /// Only emit chains rooted in these static modules (full path or file name), None for all
+    pub module_filter: Option<Vec<String>>,
+    /// Check pointer targets against the live VMA list queried at scan start instead of the scan regions
+    pub live_vma_check: bool,
+    /// Drop pointers whose target page is not present, checked by address translation on a sample of pages
+    pub require_present_pages: bool,
 }
 
 impl Default for PointerScanConfig {
@@ -193,6 +304,12 @@ impl Default for PointerScanConfig {
             is_layer_bfs: false,
             data_start: true,
             bss_start: false,
+            max_offset_ceiling: DEFAULT_MAX_OFFSET_CEILING,
+            force: false,
+            output_format: ChainOutputFormat::default(),
+            module_filter: None,
+            live_vma_check: false,
+            require_present_pages: false,
         }
     }
 }
@@ -219,8 +336,203 @@ impl PointerScanConfig {
         self.align = align;
         self
     }
+
+    pub fn with_force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    pub fn with_output_format(mut self, format: ChainOutputFormat) -> Self {
+        self.output_format = format;
+        self
+    }
+
+    pub fn with_module_filter(mut self, modules: Option<Vec<String>>) -> Self {
+        self.module_filter = modules;
+        self
+    }
+
+    pub fn with_live_vma_check(mut self, enabled: bool) -> Self {
+        self.live_vma_check = enabled;
+        self
+    }
+
+    pub fn with_require_present_pages(mut self, enabled: bool) -> Self {
+        self.require_present_pages = enabled;
+        self
+    }
+
+    /// 以该静态模块为根的链是否输出，过滤项与模块的完整路径或文件名相同即匹配
+    pub fn accepts_module(&self, name: &str) -> bool {
+        let Some(filter) = &self.module_filter else {
+            return true;
+        };
+        let short_name = name.rsplit('/').next().unwrap_or(name);
+        filter.iter().any(|module| module == name || module == short_name)
+    }
+
+    /// 检查参数范围，错误信息说明如何修改
+    pub fn validate(&self) -> Result<(), PointerScanConfigError> {
+        let target = self.target_address;
+        if target == 0 {
+            return Err(PointerScanConfigError::ZeroTarget);
+        }
+        if target >= USER_ADDRESS_LIMIT {
+            return Err(PointerScanConfigError::NonCanonicalTarget(target));
+        }
+        if self.max_depth == 0 || self.max_depth > MAX_SCAN_DEPTH {
+            return Err(PointerScanConfigError::DepthOutOfRange(self.max_depth));
+        }
+        if self.max_offset == 0 || self.max_offset > self.max_offset_ceiling {
+            return Err(PointerScanConfigError::OffsetOutOfRange {
+                offset: self.max_offset,
+                ceiling: self.max_offset_ceiling,
+            });
+        }
+        if self.align != 4 && self.align != 8 {
+            return Err(PointerScanConfigError::UnsupportedAlign(self.align));
+        }
+        Ok(())
+    }
+
+    /// 按 Phase 1 的指针数估算 Phase 2 的规模
+    ///
+    /// 每个节点的子节点是值落在它前方 `max_offset` 字节内的指针。指针值在 `address_span` 字节内
+    /// 均匀分布时，子节点数约为 `pointer_count × max_offset / address_span`，且不超过窗口内的
+    /// 对齐位置数 `max_offset / align`。最深一层的候选数按 `branching ^ max_depth` 估算，只用于判断数量级。
+    pub fn estimate_search_space(&self, pointer_count: usize, address_span: u64) -> SearchSpaceEstimate {
+        let window = self.max_offset as f64;
+        let slots = window / self.align.max(1) as f64;
+        let branching = if address_span == 0 {
+            slots
+        } else {
+            (pointer_count as f64 * window / address_span as f64).min(slots)
+        };
+        SearchSpaceEstimate {
+            pointer_count,
+            address_span,
+            branching,
+            candidates: branching.powi(self.max_depth as i32),
+        }
+    }
+
+    /// Phase 1 结束后调用：估算规模超过阈值且没有设置 force 时拒绝
+    pub fn check_search_space(&self, pointer_count: usize, address_span: u64) -> Result<SearchSpaceEstimate, PointerScanConfigError> {
+        let estimate = self.estimate_search_space(pointer_count, address_span);
+        if self.force || estimate.candidates <= SEARCH_SPACE_THRESHOLD {
+            return Ok(estimate);
+        }
+        Err(PointerScanConfigError::SearchSpaceTooLarge {
+            estimate,
+            max_depth: self.max_depth,
+            suggested_depth: estimate.max_depth_within(SEARCH_SPACE_THRESHOLD),
+            suggested_offset: estimate.max_offset_within(SEARCH_SPACE_THRESHOLD, self.max_depth, self.align),
+        })
+    }
+}
+
+/// Phase 2 规模估算
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchSpaceEstimate {
+    /// Phase 1 找到的指针数
+    pub pointer_count: usize,
+    /// 指针值可能落入的地址范围总大小
+    pub address_span: u64,
+    /// 每层平均分支数
+    pub branching: f64,
+    /// 最深一层的候选数
+    pub candidates: f64,
+}
+
+impl SearchSpaceEstimate {
+    /// 在阈值内可用的最大深度（至少为 1）
+    fn max_depth_within(&self, threshold: f64) -> u32 {
+        if self.branching <= 1.0 {
+            return MAX_SCAN_DEPTH;
+        }
+        ((threshold.ln() / self.branching.ln()).floor() as u32).clamp(1, MAX_SCAN_DEPTH)
+    }
+
+    /// 保持深度不变时阈值内可用的最大偏移（按 align 向下取整，至少为 align）
+    fn max_offset_within(&self, threshold: f64, depth: u32, align: u32) -> u32 {
+        let branching = threshold.powf(1.0 / depth.max(1) as f64);
+        let density = if self.address_span == 0 {
+            1.0 / align.max(1) as f64
+        } else {
+            self.pointer_count as f64 / self.address_span as f64
+        };
+        let offset = (branching / density).min(u32::MAX as f64) as u32;
+        (offset - offset % align.max(1)).max(align)
+    }
+}
+
+/// Pointer scan parameter errors.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PointerScanConfigError {
+    ZeroTarget,
+    NonCanonicalTarget(u64),
+    DepthOutOfRange(u32),
+    OffsetOutOfRange {
+        offset: u32,
+        ceiling: u32,
+    },
+    UnsupportedAlign(u32),
+    /// Phase 1 之后估算的搜索空间过大
+    SearchSpaceTooLarge {
+        estimate: SearchSpaceEstimate,
+        max_depth: u32,
+        suggested_depth: u32,
+        suggested_offset: u32,
+    },
+}
+
+impl PointerScanConfigError {
+    pub fn error_code(&self) -> ScanErrorCode {
+        match self {
+            PointerScanConfigError::SearchSpaceTooLarge { .. } => ScanErrorCode::SearchSpaceTooLarge,
+            _ => ScanErrorCode::InvalidConfig,
+        }
+    }
 }
 
+impl fmt::Display for PointerScanConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PointerScanConfigError::ZeroTarget => write!(f, "Target address is 0, pick the address of a search result as the target"),
+            PointerScanConfigError::NonCanonicalTarget(target) => write!(
+                f,
+                "Target address 0x{:X} is not a user-space address; if it carries a pointer tag, use 0x{:X}",
+                target,
+                target & (USER_ADDRESS_LIMIT - 1)
+            ),
+            PointerScanConfigError::DepthOutOfRange(depth) => write!(
+                f,
+                "Max depth {} is out of range, use 1..={} (chains deeper than 5 rarely survive a restart)",
+                depth, MAX_SCAN_DEPTH
+            ),
+            PointerScanConfigError::OffsetOutOfRange { offset, ceiling } => write!(
+                f,
+                "Max offset 0x{:X} is out of range, use 0x1..=0x{:X} (most struct fields are within 0x1000)",
+                offset, ceiling
+            ),
+            PointerScanConfigError::UnsupportedAlign(align) => write!(f, "Alignment {} is not supported, use 4 or 8", align),
+            PointerScanConfigError::SearchSpaceTooLarge {
+                estimate,
+                max_depth,
+                suggested_depth,
+                suggested_offset,
+            } => write!(
+                f,
+                "Search space too large: {} pointers give ~{:.1} candidates per level, ~{:.1e} at depth {}. \
+                 Reduce max depth to {} or max offset to 0x{:X}, or force the scan",
+                estimate.pointer_count, estimate.branching, estimate.candidates, max_depth, suggested_depth, suggested_offset
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PointerScanConfigError {}
+
 /// Scan phase enumeration for progress tracking.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(i32)]
@@ -239,6 +551,8 @@ pub enum ScanPhase {
     Error = 5,
     /// Phase 3: Writing chains to file
     WritingFile = 6,
+    /// Re-resolving the chains of the current result against live memory
+    Validating = 7,
 }
 
 impl From<i32> for ScanPhase {
@@ -251,6 +565,7 @@ impl From<i32> for ScanPhase {
             4 => ScanPhase::Cancelled,
             5 => ScanPhase::Error,
             6 => ScanPhase::WritingFile,
+            7 => ScanPhase::Validating,
             _ => ScanPhase::Idle,
         }
     }
@@ -276,6 +591,12 @@ pub enum ScanErrorCode {
     NoProcessBound = 6,
     /// Storage error (mmap failed)
     StorageError = 7,
+    /// Scan parameters out of range
+    InvalidConfig = 8,
+    /// Estimated chain search space exceeds the threshold after Phase 1
+    SearchSpaceTooLarge = 9,
+    /// The bound process exited during the scan
+    ProcessDied = 10,
 }
 
 // ============================================================================
@@ -571,6 +892,33 @@ pub struct ChainLevelLen {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_static_modules_from_list() {
+        const LIB: &str = "/data/app/com.example/lib/arm64/libgame.so";
+        const BASE: u64 = 0x7A00000000;
+        let region = |start: u64, end: u64, name: &str| ScanRegion { start, end, name: name.to_string() };
+        let regions = vec![
+            region(BASE, BASE + 0x10000, LIB),
+            region(BASE + 0x10000, BASE + 0x12000, LIB),
+            region(BASE + 0x12000, BASE + 0x13000, "[anon:.bss]"),
+            region(0x7B00000000, 0x7B00010000, "[anon:libc_malloc]"),
+            region(0x7C00000000, 0x7C00001000, "/system/fonts/Roboto.ttf"),
+            region(0x7D00000000, 0x7D00001000, "[anon:.bss]"),
+        ];
+        let modules = vec![
+            ModuleInfo { name: LIB.to_string(), base: BASE, end: BASE + 0x12000, is_executable_segment: true },
+            ModuleInfo { name: "/system/fonts/Roboto.ttf".to_string(), base: 0x7C00000000, end: 0x7C00001000, is_executable_segment: false },
+        ];
+
+        let statics = static_modules_from_list(&regions, &modules);
+        let summary: Vec<(&str, u64, u32, u64)> = statics
+            .iter()
+            .map(|m| (m.name.as_str(), m.base_address - BASE, m.index, m.first_module_base_addr))
+            .collect();
+        // 两个文件段和 .bss 都属于 libgame.so，偏移相对第一个段计算；字体文件没有可执行段，孤立的 .bss 不属于任何模块
+        assert_eq!(summary, vec![(LIB, 0, 0, BASE), (LIB, 0x10000, 1, BASE), (LIB, 0x12000, 2, BASE)]);
+    }
+
     #[test]
     fn test_pointer_dir_size() {
         // 确保结构体大小符合预期（用于二进制兼容）
@@ -585,6 +933,105 @@ mod tests {
         assert_eq!(sym.get_name(), "libtest.so");
     }
 
+    #[test]
+    fn test_config_bounds() {
+        let base = PointerScanConfig::new(0x7000_0000_1000);
+        assert_eq!(base.validate(), Ok(()));
+
+        // 目标地址
+        assert_eq!(PointerScanConfig::new(0).validate(), Err(PointerScanConfigError::ZeroTarget));
+        assert!(PointerScanConfig::new(USER_ADDRESS_LIMIT - 8).validate().is_ok());
+        let tagged = 0xB400_7000_0000_1000;
+        assert_eq!(
+            PointerScanConfig::new(tagged).validate(),
+            Err(PointerScanConfigError::NonCanonicalTarget(tagged))
+        );
+        assert!(PointerScanConfigError::NonCanonicalTarget(tagged).to_string().contains("0x700000001000"));
+
+        // 深度 1..=10
+        assert!(base.clone().with_depth(1).validate().is_ok());
+        assert!(base.clone().with_depth(MAX_SCAN_DEPTH).validate().is_ok());
+        assert_eq!(base.clone().with_depth(0).validate(), Err(PointerScanConfigError::DepthOutOfRange(0)));
+        assert_eq!(base.clone().with_depth(11).validate(), Err(PointerScanConfigError::DepthOutOfRange(11)));
+
+        // 偏移 1..=ceiling，ceiling 可配置
+        assert!(base.clone().with_offset(0xFFFF).validate().is_ok());
+        assert!(base.clone().with_offset(DEFAULT_MAX_OFFSET_CEILING).validate().is_ok());
+        assert!(base.clone().with_offset(0).validate().is_err());
+        let too_far = base.clone().with_offset(DEFAULT_MAX_OFFSET_CEILING + 4);
+        assert_eq!(
+            too_far.validate(),
+            Err(PointerScanConfigError::OffsetOutOfRange {
+                offset: DEFAULT_MAX_OFFSET_CEILING + 4,
+                ceiling: DEFAULT_MAX_OFFSET_CEILING,
+            })
+        );
+        let mut raised = too_far.clone();
+        raised.max_offset_ceiling = 0x100000;
+        assert!(raised.validate().is_ok());
+
+        // 对齐只支持 4 和 8
+        assert!(base.clone().with_align(8).validate().is_ok());
+        for align in [0, 1, 2, 16] {
+            assert_eq!(base.clone().with_align(align).validate(), Err(PointerScanConfigError::UnsupportedAlign(align)));
+        }
+        assert_eq!(PointerScanConfigError::UnsupportedAlign(2).error_code(), ScanErrorCode::InvalidConfig);
+    }
+
+    #[test]
+    fn test_search_space_estimate_and_force() {
+        const MB: u64 = 1024 * 1024;
+        const POINTERS: usize = 1 << 20;
+        // 256MB 内 2^20 个指针，偏移 0x1000：每层 16 个分支，深度 5 约 10^6
+        let config = PointerScanConfig::new(0x7000_0000_1000).with_depth(5).with_offset(0x1000);
+        let estimate = config.check_search_space(POINTERS, 256 * MB).unwrap();
+        assert_eq!((estimate.branching, estimate.candidates), (16.0, 16f64.powi(5)));
+
+        // 0xFFFF + 深度 7 是常见的错误组合
+        let wide = config.clone().with_depth(7).with_offset(0xFFFF);
+        let err = wide.check_search_space(POINTERS, 256 * MB).unwrap_err();
+        assert_eq!(err.error_code(), ScanErrorCode::SearchSpaceTooLarge);
+        let PointerScanConfigError::SearchSpaceTooLarge {
+            suggested_depth,
+            suggested_offset,
+            ..
+        } = err
+        else {
+            panic!("unexpected error: {}", err);
+        };
+        // 建议的深度和偏移都能通过估算
+        assert!(wide.clone().with_depth(suggested_depth).check_search_space(POINTERS, 256 * MB).is_ok());
+        assert!(wide.clone().with_offset(suggested_offset).check_search_space(POINTERS, 256 * MB).is_ok());
+        assert!(wide.clone().with_depth(suggested_depth + 1).check_search_space(POINTERS, 256 * MB).is_err());
+        assert!(err.to_string().contains("force"));
+
+        // force 跳过拒绝，估算值不变
+        let forced = wide.clone().with_force(true).check_search_space(POINTERS, 256 * MB).unwrap();
+        assert_eq!(forced, wide.estimate_search_space(POINTERS, 256 * MB));
+        assert!(forced.candidates > SEARCH_SPACE_THRESHOLD);
+
+        // 分支数不超过窗口内的对齐位置数
+        let dense = config.estimate_search_space(usize::MAX / 2, MB);
+        assert_eq!(dense.branching, 0x1000 as f64 / 4.0);
+    }
+
+    #[test]
+    fn test_output_format_and_module_filter() {
+        for format in ChainOutputFormat::ALL {
+            assert_eq!(ChainOutputFormat::from_id(format as i32), Some(format));
+            assert_eq!(ChainOutputFormat::from_description(format.description()), Some(format));
+        }
+        assert_eq!(ChainOutputFormat::from_id(4), None);
+
+        let config = PointerScanConfig::new(0x7000_0000_1000);
+        assert!(config.accepts_module("/data/app/lib/libgame.so"));
+        let config = config.with_module_filter(Some(vec!["libgame.so".to_string(), "/system/lib64/libc.so".to_string()]));
+        assert!(config.accepts_module("/data/app/lib/libgame.so"));
+        assert!(config.accepts_module("/system/lib64/libc.so"));
+        assert!(!config.accepts_module("/apex/lib64/libc.so"));
+        assert!(!config.accepts_module("/data/app/lib/libgame.so.1"));
+    }
+
     #[test]
     fn test_mem_range_detect() {
         assert_eq!(MemRange::detect("", "rw-p"), MemRange::Anonymous);
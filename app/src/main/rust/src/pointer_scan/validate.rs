@@ -0,0 +1,458 @@
+//! Pointer chain validation
+//!
+//! 扫描结果写入文件后，目标进程重新分配内存会让其中很多链失效。这里按链逐层解引用：
+//! 从 `module[index]` 的基址加上基址偏移开始，每层读取指针并加上偏移，最后和扫描目标比较。
+//! 链之间互不依赖，用 rayon 并行，每条链开始前检查一次取消。
+//!
+//! 整个输出文件的验证（`validate_range`）按批读取链，失效的链记在 `ChainValidity` 的位图里，
+//! 不改写输出文件；分页读取存活的链时跳过位图中标记的链。
+
+use crate::pointer_scan::samples::ChainSample;
+use crate::pointer_scan::types::VmStaticData;
+use anyhow::Result;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// 与扫描时一致，去掉指针高位的 tag
+const POINTER_MASK: u64 = 0x0000_FFFF_FFFF_FFFF;
+
+/// 验证整个输出文件时每批读取的链数
+pub const VALIDATION_BATCH: usize = 4096;
+
+/// 一条链的验证结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChainStatus {
+    /// 仍然指向扫描目标
+    Valid,
+    /// 能完整解引用，但指向了别的地址
+    Moved(u64),
+    /// 第 `depth` 次解引用（从 0 开始）读取失败
+    Broken { depth: u32 },
+    /// 找不到链或链的模块
+    Unresolved,
+    /// 验证被取消，没有检查
+    Skipped,
+}
+
+impl ChainStatus {
+    /// JNI 返回的状态码：低 8 位是状态，`Broken` 的失败层级在 8 位以上
+    pub fn code(&self) -> i32 {
+        match self {
+            ChainStatus::Valid => 0,
+            ChainStatus::Moved(_) => 1,
+            ChainStatus::Broken { depth } => 2 | ((*depth as i32) << 8),
+            ChainStatus::Unresolved => 3,
+            ChainStatus::Skipped => 4,
+        }
+    }
+}
+
+/// 按 `module[index]` 查找模块基址，模块名只比较文件名部分（与输出文件一致）
+pub struct ModuleBases {
+    bases: HashMap<(String, i32), u64>,
+}
+
+impl ModuleBases {
+    pub fn new(modules: &[VmStaticData]) -> Self {
+        let bases = modules
+            .iter()
+            .map(|module| {
+                let short_name = module.name.rsplit('/').next().unwrap_or(&module.name);
+                ((short_name.to_string(), module.index as i32), module.base_address)
+            })
+            .collect();
+        Self { bases }
+    }
+
+    pub fn get(&self, module: &str, index: i32) -> Option<u64> {
+        self.bases.get(&(module.to_string(), index)).copied()
+    }
+}
+
+/// 逐层解引用一条链，返回最终地址；失败时返回 `Broken` 或 `Unresolved`
+pub fn resolve_address<R>(chain: &ChainSample, bases: &ModuleBases, read: &R) -> std::result::Result<u64, ChainStatus>
+where
+    R: Fn(u64, &mut [u8]) -> Result<()>,
+{
+    let Some(base) = bases.get(&chain.module, chain.module_index) else {
+        return Err(ChainStatus::Unresolved);
+    };
+
+    let mut address = base.wrapping_add(chain.base_offset);
+    for (depth, &offset) in chain.offsets.iter().enumerate() {
+        let mut bytes = [0u8; 8];
+        if read(address, &mut bytes).is_err() {
+            return Err(ChainStatus::Broken { depth: depth as u32 });
+        }
+        let pointer = u64::from_le_bytes(bytes) & POINTER_MASK;
+        if pointer == 0 {
+            return Err(ChainStatus::Broken { depth: depth as u32 });
+        }
+        address = pointer.wrapping_add_signed(offset);
+    }
+    Ok(address)
+}
+
+/// 逐层解引用一条链，和扫描目标比较
+pub fn resolve_chain<R>(chain: &ChainSample, bases: &ModuleBases, target: u64, read: &R) -> ChainStatus
+where
+    R: Fn(u64, &mut [u8]) -> Result<()>,
+{
+    match resolve_address(chain, bases, read) {
+        Ok(address) if address == target => ChainStatus::Valid,
+        Ok(address) => ChainStatus::Moved(address),
+        Err(status) => status,
+    }
+}
+
+/// 链是否仍然可用：能完整解引用，给了期望值时最终地址上的字节还要与它相同
+///
+/// 不和扫描目标比较，游戏更新后目标地址通常会变，能解引用到期望值的链仍然有用。
+pub fn chain_survives<R>(chain: &ChainSample, bases: &ModuleBases, expected_value: Option<&[u8]>, read: &R) -> bool
+where
+    R: Fn(u64, &mut [u8]) -> Result<()>,
+{
+    let Ok(address) = resolve_address(chain, bases, read) else {
+        return false;
+    };
+    match expected_value {
+        Some(expected) => {
+            let mut bytes = vec![0u8; expected.len()];
+            read(address, &mut bytes).is_ok() && bytes == expected
+        },
+        None => true,
+    }
+}
+
+/// 输出文件中每条链是否已被验证为失效，验证任务写入、分页读取时查询，不需要锁
+pub struct ChainValidity {
+    /// 按链编号的失效位图
+    invalid: Vec<AtomicU64>,
+    count: u64,
+    /// 没有被标记失效的链数
+    valid: AtomicU64,
+}
+
+impl ChainValidity {
+    pub fn new(count: u64) -> Self {
+        Self {
+            invalid: (0..count.div_ceil(64)).map(|_| AtomicU64::new(0)).collect(),
+            count,
+            valid: AtomicU64::new(count),
+        }
+    }
+
+    /// 输出文件中的链数
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// 没有被标记失效的链数，验证进行中逐渐减少
+    pub fn valid_count(&self) -> u64 {
+        self.valid.load(Ordering::Relaxed)
+    }
+
+    pub fn is_invalid(&self, id: u64) -> bool {
+        self.invalid.get((id / 64) as usize).is_some_and(|word| word.load(Ordering::Relaxed) & (1 << (id % 64)) != 0)
+    }
+
+    /// 标记链失效，之前已经标记过时返回 false
+    pub fn mark_invalid(&self, id: u64) -> bool {
+        let Some(word) = self.invalid.get((id / 64) as usize) else {
+            return false;
+        };
+        let bit = 1 << (id % 64);
+        let newly = word.fetch_or(bit, Ordering::Relaxed) & bit == 0;
+        if newly {
+            self.valid.fetch_sub(1, Ordering::Relaxed);
+        }
+        newly
+    }
+
+    /// 第 n 条（从 0 开始）存活链的编号，按位图逐字计数
+    pub fn nth_valid(&self, mut n: u64) -> Option<u64> {
+        for (slot, word) in self.invalid.iter().enumerate() {
+            let first = slot as u64 * 64;
+            let bits = (self.count - first).min(64);
+            let mask = if bits == 64 { u64::MAX } else { (1 << bits) - 1 };
+            let valid = !word.load(Ordering::Relaxed) & mask;
+            let ones = valid.count_ones() as u64;
+            if n < ones {
+                let mut valid = valid;
+                for _ in 0..n {
+                    valid &= valid - 1;
+                }
+                return Some(first + valid.trailing_zeros() as u64);
+            }
+            n -= ones;
+        }
+        None
+    }
+}
+
+/// 验证输出文件中 `range` 内的链，失效的链标记到 `validity`，返回是否全部检查完（取消时为 false）
+///
+/// `load(start, count)` 读取从编号 start 开始的一批链；每批并行检查后调用 `on_progress(已检查, 总数)`。
+#[allow(clippy::too_many_arguments)]
+pub fn validate_range<L, R, C, P>(
+    validity: &ChainValidity,
+    range: Range<u64>,
+    mut load: L,
+    bases: &ModuleBases,
+    expected_value: Option<&[u8]>,
+    read: R,
+    check_cancelled: &C,
+    mut on_progress: P,
+) -> Result<bool>
+where
+    L: FnMut(u64, usize) -> Result<Vec<Option<ChainSample>>>,
+    R: Fn(u64, &mut [u8]) -> Result<()> + Sync,
+    C: Fn() -> bool + Sync,
+    P: FnMut(u64, u64),
+{
+    let total = range.end.saturating_sub(range.start);
+    let mut next = range.start;
+    while next < range.end {
+        if check_cancelled() {
+            return Ok(false);
+        }
+        let size = (range.end - next).min(VALIDATION_BATCH as u64) as usize;
+        let chains = load(next, size)?;
+        if chains.is_empty() {
+            break;
+        }
+        let loaded = chains.len() as u64;
+        chains.par_iter().enumerate().for_each(|(i, chain)| {
+            if check_cancelled() {
+                return;
+            }
+            let survives = chain.as_ref().is_some_and(|chain| chain_survives(chain, bases, expected_value, &read));
+            if !survives {
+                validity.mark_invalid(next + i as u64);
+            }
+        });
+        if check_cancelled() {
+            return Ok(false);
+        }
+        next += loaded;
+        on_progress(next - range.start, total);
+    }
+    Ok(true)
+}
+
+/// 并行验证一批链，`None` 表示链不存在；取消后未检查的链为 `Skipped`
+pub fn validate_chains_with<R, C>(chains: &[Option<ChainSample>], bases: &ModuleBases, target: u64, read: R, check_cancelled: &C) -> Vec<ChainStatus>
+where
+    R: Fn(u64, &mut [u8]) -> Result<()> + Sync,
+    C: Fn() -> bool + Sync,
+{
+    chains
+        .par_iter()
+        .map(|chain| {
+            if check_cancelled() {
+                return ChainStatus::Skipped;
+            }
+            match chain {
+                Some(chain) => resolve_chain(chain, bases, target, &read),
+                None => ChainStatus::Unresolved,
+            }
+        })
+        .collect()
+}
+
+/// 从输出文件读取指定编号的链，编号为去掉注释和空行后的行号（从 0 开始）
+pub fn read_chains_by_id(path: &Path, ids: &[u64]) -> Result<Vec<Option<ChainSample>>> {
+    let mut wanted: Vec<(u64, usize)> = ids.iter().enumerate().map(|(slot, &id)| (id, slot)).collect();
+    wanted.sort_unstable();
+
+    let mut chains = vec![None; ids.len()];
+    let mut next = 0;
+    let mut id = 0u64;
+    let reader = BufReader::new(File::open(path)?);
+    for line in reader.lines() {
+        if next >= wanted.len() {
+            break;
+        }
+        let line = line?;
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        while next < wanted.len() && wanted[next].0 == id {
+            chains[wanted[next].1] = ChainSample::parse(trimmed);
+            next += 1;
+        }
+        id += 1;
+    }
+    Ok(chains)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use std::collections::HashMap;
+
+    const MODULE_BASE: u64 = 0x7000_0000;
+    const TARGET: u64 = 0x9000_0040;
+
+    fn chain(text: &str) -> ChainSample {
+        ChainSample::parse(text).unwrap()
+    }
+
+    fn bases() -> ModuleBases {
+        let mut module = VmStaticData::new("/data/app/lib/arm64/libgame.so".to_string(), MODULE_BASE, MODULE_BASE + 0x10000, true);
+        module.index = 1;
+        ModuleBases::new(&[module])
+    }
+
+    /// libgame.so[1]+0x100 -> 0x8000_0000，+0x20 -> 0x9000_0000，+0x40 = TARGET
+    fn memory() -> HashMap<u64, u64> {
+        HashMap::from([(MODULE_BASE + 0x100, 0x8000_0000), (0x8000_0020, 0xB400_0000_9000_0000)])
+    }
+
+    fn reader(memory: &HashMap<u64, u64>) -> impl Fn(u64, &mut [u8]) -> Result<()> + Sync + '_ {
+        |address, buf| {
+            let value = memory.get(&address).ok_or_else(|| anyhow!("unmapped 0x{:X}", address))?;
+            buf.copy_from_slice(&value.to_le_bytes());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_resolve_chain_statuses() {
+        let memory = memory();
+        let read = reader(&memory);
+        let bases = bases();
+
+        // 中间的指针带有 tag，解引用前去掉
+        assert_eq!(
+            resolve_chain(&chain("libgame.so[1]+0x100->+0x20->+0x40"), &bases, TARGET, &read),
+            ChainStatus::Valid
+        );
+        assert_eq!(
+            resolve_chain(&chain("libgame.so[1]+0x100->+0x20->+0x48"), &bases, TARGET, &read),
+            ChainStatus::Moved(TARGET + 8)
+        );
+        assert_eq!(
+            resolve_chain(&chain("libgame.so[1]+0x100->+0x28->+0x40"), &bases, TARGET, &read),
+            ChainStatus::Broken { depth: 1 }
+        );
+        assert_eq!(
+            resolve_chain(&chain("libgame.so[1]+0x108->+0x20"), &bases, TARGET, &read),
+            ChainStatus::Broken { depth: 0 }
+        );
+        assert_eq!(
+            resolve_chain(&chain("libgame.so[0]+0x100->+0x20"), &bases, TARGET, &read),
+            ChainStatus::Unresolved
+        );
+        // 目标本身在模块内，没有解引用
+        assert_eq!(
+            resolve_chain(&chain("libgame.so[1]+0x40"), &bases, MODULE_BASE + 0x40, &read),
+            ChainStatus::Valid
+        );
+
+        assert_eq!(ChainStatus::Broken { depth: 3 }.code(), 0x302);
+        assert_eq!(ChainStatus::Moved(0).code(), 1);
+    }
+
+    #[test]
+    fn test_validate_chains_by_id_and_cancel() {
+        let memory = memory();
+        let bases = bases();
+        let dir = std::env::temp_dir().join(format!("mamu_validate_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("chains.txt");
+        std::fs::write(
+            &path,
+            "# Pointer Scan Results\n# Target: 0x90000040\n\nlibgame.so[1]+0x100->+0x20->+0x40\nlibgame.so[1]+0x100->+0x28->+0x40\nlibgame.so[1]+0x100->+0x20->+0x48\n",
+        )
+        .unwrap();
+
+        // 编号可以乱序、重复或超出范围
+        let chains = read_chains_by_id(&path, &[2, 0, 7, 1, 0]).unwrap();
+        assert!(chains[2].is_none());
+        let statuses = validate_chains_with(&chains, &bases, TARGET, reader(&memory), &|| false);
+        assert_eq!(
+            statuses,
+            vec![
+                ChainStatus::Moved(TARGET + 8),
+                ChainStatus::Valid,
+                ChainStatus::Unresolved,
+                ChainStatus::Broken { depth: 1 },
+                ChainStatus::Valid
+            ]
+        );
+
+        let statuses = validate_chains_with(&chains, &bases, TARGET, reader(&memory), &|| true);
+        assert!(statuses.iter().all(|status| *status == ChainStatus::Skipped));
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_chain_validity_bitmap() {
+        let validity = ChainValidity::new(130);
+        for id in (0..64).chain([65]) {
+            assert!(validity.mark_invalid(id));
+        }
+        assert!(!validity.mark_invalid(65));
+        assert!(!validity.mark_invalid(200));
+        assert_eq!(validity.valid_count(), 65);
+        assert!(validity.is_invalid(63) && !validity.is_invalid(64));
+
+        // 跨位图字计数，最后一个字只有 2 位有效
+        assert_eq!(validity.nth_valid(0), Some(64));
+        assert_eq!(validity.nth_valid(1), Some(66));
+        assert_eq!(validity.nth_valid(64), Some(129));
+        assert_eq!(validity.nth_valid(65), None);
+    }
+
+    #[test]
+    fn test_validate_range_with_expected_value_and_cancel() {
+        let mut memory = memory();
+        memory.insert(TARGET, 0x1234);
+        let bases = bases();
+        // 有效、中途断开、最终地址偏了 8 字节、无法解析
+        let chains = [
+            Some(chain("libgame.so[1]+0x100->+0x20->+0x40")),
+            Some(chain("libgame.so[1]+0x100->+0x28->+0x40")),
+            Some(chain("libgame.so[1]+0x100->+0x20->+0x48")),
+            None,
+        ];
+        let load = |start: u64, count: usize| Ok(chains.iter().skip(start as usize).take(count).cloned().collect());
+
+        // 只检查解引用：偏移后的链仍然存活
+        let validity = ChainValidity::new(4);
+        let mut progress = Vec::new();
+        let completed = validate_range(&validity, 0..4, load, &bases, None, reader(&memory), &|| false, |checked, total| {
+            progress.push((checked, total))
+        })
+        .unwrap();
+        assert!(completed);
+        assert_eq!(progress, vec![(4, 4)]);
+        assert_eq!(validity.valid_count(), 2);
+        assert_eq!((validity.nth_valid(0), validity.nth_valid(1)), (Some(0), Some(2)));
+
+        // 给了期望值后，最终地址读不到或值不同的链也失效；标记在多次验证之间累积
+        let expected = 0x1234u64.to_le_bytes();
+        assert!(validate_range(&validity, 0..4, load, &bases, Some(&expected), reader(&memory), &|| false, |_, _| {}).unwrap());
+        assert_eq!(validity.valid_count(), 1);
+        assert_eq!(validity.nth_valid(0), Some(0));
+
+        let wrong = 0x5678u64.to_le_bytes();
+        assert!(validate_range(&validity, 0..1, load, &bases, Some(&wrong), reader(&memory), &|| false, |_, _| {}).unwrap());
+        assert_eq!(validity.valid_count(), 0);
+        assert_eq!(validity.nth_valid(0), None);
+
+        // 取消时不标记任何链
+        let validity = ChainValidity::new(4);
+        assert!(!validate_range(&validity, 0..4, load, &bases, None, reader(&memory), &|| true, |_, _| {}).unwrap());
+        assert_eq!(validity.valid_count(), 4);
+    }
+}
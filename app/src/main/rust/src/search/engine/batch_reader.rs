@@ -1,10 +1,10 @@
-use crate::core::DRIVER_MANAGER;
+use crate::search::PAGE_SIZE;
 use crate::search::result_manager::FuzzySearchResultItem;
 use crate::search::types::ValueType;
 use crate::search::FuzzyCondition;
-use anyhow::{anyhow, Result};
 use log::{debug, log_enabled, Level};
 use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 
@@ -19,27 +19,37 @@ const BATCH_MAX_SIZE: usize = 64 * 1024;
 const PROGRESS_UPDATE_BATCH_SIZE: usize = 1;
 
 /// 读取结果项 - 使用固定大小数组避免 Vec 分配开销
-/// 每个 FuzzySearchResultItem 最大值为 8 字节（Qword/Double）
+/// 每个 FuzzySearchResultItem 最大值为 8 字节（Qword/Double），变长值换算为 `FuzzySearchResultItem::variable_value`
 #[derive(Clone, Copy)]
 pub struct ReadResultItem {
     pub address: u64,
     pub value_type: ValueType,
     pub old_value: [u8; 8],    // 旧值
     pub current_value: [u8; 8], // 当前值
+    pub pass: u8,
+    pub auto_types: u8,
 }
 
 impl ReadResultItem {
     #[inline]
     pub fn new(item: &FuzzySearchResultItem, current: &[u8]) -> Self {
-        let mut current_value = [0u8; 8];
-        let len = current.len().min(8);
-        current_value[..len].copy_from_slice(&current[..len]);
-        
+        let value_type = item.value_type;
+        let current_value = if value_type.is_variable_len() {
+            FuzzySearchResultItem::variable_value(current)
+        } else {
+            let mut current_value = [0u8; 8];
+            let len = current.len().min(8);
+            current_value[..len].copy_from_slice(&current[..len]);
+            current_value
+        };
+
         Self {
             address: item.address,
-            value_type: item.value_type,
+            value_type,
             old_value: item.value,
             current_value,
+            pass: item.pass,
+            auto_types: item.auto_types,
         }
     }
     
@@ -53,18 +63,42 @@ impl ReadResultItem {
     #[inline]
     pub fn to_fuzzy_item(&self) -> FuzzySearchResultItem {
         FuzzySearchResultItem::new(self.address, self.current_value, self.value_type)
+            .with_pass(self.pass)
+            .with_auto_types(self.auto_types)
     }
     
     /// 获取旧的 FuzzySearchResultItem（用于条件比较）
     #[inline]
     pub fn old_fuzzy_item(&self) -> FuzzySearchResultItem {
         FuzzySearchResultItem::new(self.address, self.old_value, self.value_type)
+            .with_pass(self.pass)
+            .with_auto_types(self.auto_types)
+    }
+
+    /// 满足条件时返回带当前值的结果项，未定型的 Auto 项同时收窄可能的解释
+    #[inline]
+    pub fn refine(&self, condition: FuzzyCondition) -> Option<FuzzySearchResultItem> {
+        if self.is_undecided_auto() {
+            let old = self.old_fuzzy_item();
+            return old.refine_auto(&self.current_value[..old.value_size()], condition);
+        }
+        self.matches_condition(condition).then(|| self.to_fuzzy_item())
+    }
+
+    #[inline]
+    fn is_undecided_auto(&self) -> bool {
+        self.value_type == ValueType::Auto && self.auto_types != 0
     }
     
     /// 直接在 ReadResultItem 上检查条件，避免创建临时对象
     #[inline]
     pub fn matches_condition(&self, condition: FuzzyCondition) -> bool {
-        if self.value_type.is_float_type() {
+        if self.is_undecided_auto() {
+            return self.refine(condition).is_some();
+        }
+        if self.value_type.is_variable_len() {
+            FuzzySearchResultItem::matches_variable(self.old_value == self.current_value, condition)
+        } else if self.value_type.is_float_type() {
             self.matches_condition_float(condition)
         } else {
             self.matches_condition_int(condition)
@@ -84,7 +118,7 @@ impl ReadResultItem {
                 f32::from_le_bytes([self.old_value[0], self.old_value[1], self.old_value[2], self.old_value[3]]) as i64
             },
             ValueType::Double => f64::from_le_bytes(self.old_value) as i64,
-            ValueType::Pattern => 0, // Pattern 类型不支持模糊搜索
+            ValueType::Pattern | ValueType::Utf8String | ValueType::Utf16String => 0, // 变长类型不支持模糊搜索
         }
     }
     
@@ -101,7 +135,7 @@ impl ReadResultItem {
                 f32::from_le_bytes([self.current_value[0], self.current_value[1], self.current_value[2], self.current_value[3]]) as i64
             },
             ValueType::Double => f64::from_le_bytes(self.current_value) as i64,
-            ValueType::Pattern => 0, // Pattern 类型不支持模糊搜索
+            ValueType::Pattern | ValueType::Utf8String | ValueType::Utf16String => 0, // 变长类型不支持模糊搜索
         }
     }
     
@@ -118,7 +152,7 @@ impl ReadResultItem {
                 f32::from_le_bytes([self.old_value[0], self.old_value[1], self.old_value[2], self.old_value[3]]) as f64
             },
             ValueType::Double => f64::from_le_bytes(self.old_value),
-            ValueType::Pattern => 0.0, // Pattern 类型不支持模糊搜索
+            ValueType::Pattern | ValueType::Utf8String | ValueType::Utf16String => 0.0, // 变长类型不支持模糊搜索
         }
     }
     
@@ -135,7 +169,7 @@ impl ReadResultItem {
                 f32::from_le_bytes([self.current_value[0], self.current_value[1], self.current_value[2], self.current_value[3]]) as f64
             },
             ValueType::Double => f64::from_le_bytes(self.current_value),
-            ValueType::Pattern => 0.0, // Pattern 类型不支持模糊搜索
+            ValueType::Pattern | ValueType::Utf8String | ValueType::Utf16String => 0.0, // 变长类型不支持模糊搜索
         }
     }
     
@@ -257,10 +291,11 @@ struct BatchItemRef {
 ///
 /// # 参数
 /// * `items` - 有序的地址列表
+/// * `pattern_len` - 变长结果（特征码、文本）的字节数，定长结果不使用
 ///
 /// # 返回
 /// 返回地址批次列表
-pub fn cluster_addresses(items: &[FuzzySearchResultItem]) -> Vec<AddressBatch> {
+pub fn cluster_addresses(items: &[FuzzySearchResultItem], pattern_len: usize) -> Vec<AddressBatch> {
     if items.is_empty() {
         return Vec::new();
     }
@@ -270,9 +305,8 @@ pub fn cluster_addresses(items: &[FuzzySearchResultItem]) -> Vec<AddressBatch> {
 
     for (idx, item) in items.iter().enumerate() {
         let addr = item.address;
-        // 先拷贝 packed 字段再调用方法
-        let vt = item.value_type;
-        let size = vt.size();
+        // 未定型的 Auto 项可能要读 8 字节
+        let size = item.read_size(pattern_len);
 
         match &mut current_batch {
             Some(batch) => {
@@ -313,11 +347,14 @@ pub fn cluster_addresses(items: &[FuzzySearchResultItem]) -> Vec<AddressBatch> {
 /// 并行批量读取内存
 ///
 /// 使用 Rayon 并行处理各个批次，每个批次单次读取整段内存
-/// 批量读取失败时自动降级为逐个读取
+/// 批量读取失败时自动降级为逐个读取，逐个读取经过每个工作分片自己的页缓存
 ///
 /// # 参数
 /// * `batches` - 地址批次列表
 /// * `items` - 原始地址列表
+/// * `cache_pages` - 逐个读取时页缓存的页数，0 表示不缓存
+/// * `cache_stats` - 页缓存的命中统计
+/// * `read` - 读取一段内存，全部读到返回 true；测试中用模拟内存代替驱动
 /// * `processed_counter` - 已处理计数器
 /// * `total_found_counter` - 找到总数计数器
 /// * `update_progress` - 进度更新回调
@@ -325,24 +362,28 @@ pub fn cluster_addresses(items: &[FuzzySearchResultItem]) -> Vec<AddressBatch> {
 ///
 /// # 返回
 /// 返回成功读取的 ReadResultItem 列表（使用固定大小数组，避免 Vec 分配开销）
-pub fn parallel_batch_read<P, F>(
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn parallel_batch_read_with<R, P, F>(
     batches: &[AddressBatch],
     items: &[FuzzySearchResultItem],
+    cache_pages: usize,
+    cache_stats: &PageCacheStats,
+    read: R,
     processed_counter: Option<&Arc<AtomicUsize>>,
     total_found_counter: Option<&Arc<AtomicUsize>>,
     update_progress: &P,
     check_cancelled: Option<&F>,
-) -> Result<Vec<ReadResultItem>>
+) -> Vec<ReadResultItem>
 where
+    R: Fn(u64, &mut [u8]) -> bool + Sync,
     P: Fn(usize, usize) + Sync,
     F: Fn() -> bool + Sync,
 {
-    let _total_items = items.len();
     let cancelled = Arc::new(AtomicBool::new(false));
     let cancelled_clone = Arc::clone(&cancelled);
 
     // 并行处理批次
-    let results: Result<Vec<ReadResultItem>> = batches
+    batches
         .par_iter()
         .enumerate()
         .take_any_while(|&(_idx, _batch)| {
@@ -358,50 +399,37 @@ where
             }
             true
         })
-        .try_fold(
-            || Vec::new(), // 线程本地累加器
-            |mut acc, (batch_idx, batch)| -> Result<Vec<ReadResultItem>> {
-                let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
-
+        .fold(
+            || (Vec::new(), PageCache::new(cache_pages, *PAGE_SIZE, cache_stats)), // 线程本地累加器和页缓存
+            |(mut acc, mut cache), (batch_idx, batch)| {
                 // 分配批次缓冲区
                 let mut buffer = vec![0u8; batch.total_size];
 
                 // 单次批量读取整个段
-                match driver_manager.read_memory_unified(
-                    batch.start_addr,
-                    &mut buffer,
-                    None, // 不跟踪页状态
-                ) {
-                    Ok(_) => {
-                        // 从批次缓冲区提取各个地址的值
-                        for item_ref in &batch.items {
-                            let value_bytes = &buffer[item_ref.offset..item_ref.offset + item_ref.value_size];
-                            let original_item = &items[item_ref.item_index];
-                            acc.push(ReadResultItem::new(original_item, value_bytes));
-                        }
-                    },
-                    Err(e) => {
-                        if log_enabled!(Level::Debug) {
-                            debug!(
-                                "Batch read failed at 0x{:X} (size {}), falling back to individual reads: {:?}",
-                                batch.start_addr, batch.total_size, e
-                            );
-                        }
+                if read(batch.start_addr, &mut buffer) {
+                    // 从批次缓冲区提取各个地址的值
+                    for item_ref in &batch.items {
+                        let value_bytes = &buffer[item_ref.offset..item_ref.offset + item_ref.value_size];
+                        let original_item = &items[item_ref.item_index];
+                        acc.push(ReadResultItem::new(original_item, value_bytes));
+                    }
+                } else {
+                    if log_enabled!(Level::Debug) {
+                        debug!("Batch read failed at 0x{:X} (size {}), falling back to individual reads", batch.start_addr, batch.total_size);
+                    }
 
-                        // 逐个读取批次内的地址
-                        for item_ref in &batch.items {
-                            let original_item = &items[item_ref.item_index];
-                            let mut small_buffer = vec![0u8; item_ref.value_size];
+                    // 逐个读取批次内的地址，同一页只读取一次，不可读的页不再重试
+                    let mut small_buffer = [0u8; 8];
+                    for item_ref in &batch.items {
+                        let original_item = &items[item_ref.item_index];
+                        let value_bytes = &mut small_buffer[..item_ref.value_size];
 
-                            if driver_manager.read_memory_unified(original_item.address, &mut small_buffer, None).is_ok() {
-                                acc.push(ReadResultItem::new(original_item, &small_buffer));
-                            }
+                        if cache.read(original_item.address, value_bytes, &read) {
+                            acc.push(ReadResultItem::new(original_item, value_bytes));
                         }
-                    },
+                    }
                 }
 
-                drop(driver_manager); // 显式释放读锁
-
                 if batch_idx % PROGRESS_UPDATE_BATCH_SIZE == 0 {
                     if let Some(counter) = processed_counter {
                         let processed = counter.fetch_add(batch.items.len(), Ordering::Relaxed) + batch.items.len();
@@ -413,16 +441,158 @@ where
                     counter.fetch_add(batch.items.len(), Ordering::Relaxed);
                 }
 
-                Ok(acc)
+                (acc, cache)
             },
         )
-        .try_reduce(
+        .map(|(acc, _)| acc)
+        .reduce(
             || Vec::new(),
             |mut a, b| {
                 a.extend(b);
-                Ok(a)
+                a
             },
-        );
+        )
+}
 
-    results
+/// 改善搜索页缓存的默认页数
+pub const DEFAULT_REFINE_CACHE_PAGES: usize = 256;
+/// 页缓存页数的上限
+pub const MAX_REFINE_CACHE_PAGES: usize = 4096;
+
+/// 页缓存的命中统计，可以被多个线程的缓存共享
+///
+/// 命中包括已知读取失败的页；未命中是实际发出的整页读取，其中失败的计入 `failed_pages`。
+#[derive(Debug, Default)]
+pub struct PageCacheStats {
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+    failed_pages: AtomicUsize,
+}
+
+impl PageCacheStats {
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    pub fn misses(&self) -> usize {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    pub fn failed_pages(&self) -> usize {
+        self.failed_pages.load(Ordering::Relaxed)
+    }
+}
+
+impl std::fmt::Display for PageCacheStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "hits={}, misses={}, failed pages={}", self.hits(), self.misses(), self.failed_pages())
+    }
+}
+
+/// 改善搜索逐地址读取时的页缓存
+///
+/// 按页对齐地址缓存最近读取的整页，超过容量时淘汰最久未用的页；读取失败的页记录下来，
+/// 同一次改善内不再重试。两次改善之间值会变化，所以每次改善新建缓存，不跨改善复用。
+/// 调用方自己整段读取到缓冲区时（批量读取、合并读取的段）不经过缓存。容量为 0 时直接读取。
+pub(crate) struct PageCache<'a> {
+    capacity: usize,
+    page_size: usize,
+    /// 页地址 -> `slots` 下标
+    index: HashMap<u64, usize>,
+    slots: Vec<CachedPage>,
+    failed: HashSet<u64>,
+    /// 每次访问递增，用于找出最久未用的页
+    clock: u64,
+    stats: &'a PageCacheStats,
+}
+
+struct CachedPage {
+    page: u64,
+    last_used: u64,
+    data: Box<[u8]>,
+}
+
+impl<'a> PageCache<'a> {
+    pub fn new(capacity: usize, page_size: usize, stats: &'a PageCacheStats) -> Self {
+        Self {
+            capacity,
+            page_size,
+            index: HashMap::new(),
+            slots: Vec::new(),
+            failed: HashSet::new(),
+            clock: 0,
+            stats,
+        }
+    }
+
+    /// 读取 addr 处 buffer.len() 字节，跨页时逐页复制；任一页不可读时返回 false
+    ///
+    /// `read_page` 读取一整页，返回是否成功。
+    pub fn read<R>(&mut self, addr: u64, buffer: &mut [u8], mut read_page: R) -> bool
+    where
+        R: FnMut(u64, &mut [u8]) -> bool,
+    {
+        if self.capacity == 0 {
+            return read_page(addr, buffer);
+        }
+
+        let page_size = self.page_size as u64;
+        let mut copied = 0;
+        while copied < buffer.len() {
+            let current = addr + copied as u64;
+            let page = current & !(page_size - 1);
+            let offset = (current - page) as usize;
+            let len = (self.page_size - offset).min(buffer.len() - copied);
+            let Some(data) = self.page(page, &mut read_page) else {
+                return false;
+            };
+            buffer[copied..copied + len].copy_from_slice(&data[offset..offset + len]);
+            copied += len;
+        }
+        true
+    }
+
+    fn page<R>(&mut self, page: u64, read_page: &mut R) -> Option<&[u8]>
+    where
+        R: FnMut(u64, &mut [u8]) -> bool,
+    {
+        self.clock += 1;
+        if self.failed.contains(&page) {
+            self.stats.hits.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+        if let Some(&slot) = self.index.get(&page) {
+            self.stats.hits.fetch_add(1, Ordering::Relaxed);
+            self.slots[slot].last_used = self.clock;
+            return Some(&self.slots[slot].data);
+        }
+
+        self.stats.misses.fetch_add(1, Ordering::Relaxed);
+        let slot = if self.slots.len() < self.capacity {
+            self.slots.push(CachedPage {
+                page,
+                last_used: 0,
+                data: vec![0u8; self.page_size].into_boxed_slice(),
+            });
+            self.slots.len() - 1
+        } else {
+            let (slot, oldest) = self.slots.iter().enumerate().min_by_key(|(_, cached)| cached.last_used)?;
+            self.index.remove(&oldest.page);
+            slot
+        };
+
+        let cached = &mut self.slots[slot];
+        if !read_page(page, &mut cached.data) {
+            self.stats.failed_pages.fetch_add(1, Ordering::Relaxed);
+            self.failed.insert(page);
+            // 槽位留给下一次未命中
+            cached.last_used = 0;
+            cached.page = u64::MAX;
+            return None;
+        }
+        cached.page = page;
+        cached.last_used = self.clock;
+        self.index.insert(page, slot);
+        Some(&self.slots[slot].data)
+    }
 }
@@ -0,0 +1,243 @@
+//! Byte search fast path
+//!
+//! 单个 Byte 值几乎在每页都有大量命中，逐条生成 16 字节的结果再排序去重，扫描本身反而不是瓶颈。
+//! 预计结果数超过阈值时改为按页记录命中（见 `ByteHitSet`）：扫描时直接生成每页的偏移，
+//! 改善搜索按页读取命中所在的那一段再求交集，结果只在分页显示时展开。
+//! 预计结果数由均匀抽样若干页得到。
+
+use super::read_stats::ReadStats;
+use crate::search::result_manager::{ByteHitSet, PageHits};
+use crate::search::types::{SearchQuery, SearchValue, ValueType};
+use crate::wuwa::PageStatusBitmap;
+use anyhow::Result;
+use memchr::memchr_iter;
+use rayon::prelude::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// 预计结果数超过该值时使用按页存储，0 表示禁用
+pub const DEFAULT_BYTE_BITMAP_THRESHOLD: usize = 20_000_000;
+
+/// 预计结果数时抽样的页数
+pub const PROJECTION_SAMPLES: usize = 64;
+
+/// 改善搜索每处理这么多页检查一次取消并更新进度
+const REFINE_PROGRESS_PAGES: usize = 1024;
+
+/// 每个字节值是否匹配
+pub type ByteMatcher = [bool; 256];
+
+/// Byte 类型的非特征码搜索值可以预先算出匹配表
+pub fn byte_matcher(value: &SearchValue) -> Option<ByteMatcher> {
+    if value.value_type() != ValueType::Byte || value.is_pattern() {
+        return None;
+    }
+    let mut matcher = [false; 256];
+    for (byte, matched) in matcher.iter_mut().enumerate() {
+        *matched = value.matched(&[byte as u8]).unwrap_or(false);
+    }
+    Some(matcher)
+}
+
+/// 只有单值搜索可以走按页存储
+pub fn query_byte_matcher(query: &SearchQuery) -> Option<ByteMatcher> {
+    match query.values.as_slice() {
+        [value] => byte_matcher(value),
+        _ => None,
+    }
+}
+
+/// 按页生成命中的扫描器
+#[derive(Debug, Clone)]
+pub struct ByteScanner {
+    matcher: ByteMatcher,
+    /// 只有一个字节值匹配时用 memchr
+    single: Option<u8>,
+    page_size: usize,
+    chunk_size: usize,
+}
+
+impl ByteScanner {
+    pub fn new(matcher: ByteMatcher, page_size: usize, chunk_size: usize) -> Self {
+        let mut matching = (0..=255u8).filter(|&byte| matcher[byte as usize]);
+        let single = match (matching.next(), matching.next()) {
+            (Some(byte), None) => Some(byte),
+            _ => None,
+        };
+        Self {
+            matcher,
+            single,
+            page_size,
+            chunk_size: chunk_size.max(page_size),
+        }
+    }
+
+    pub fn page_size(&self) -> usize {
+        self.page_size
+    }
+
+    /// 页内匹配的偏移，`skip` 之前和 `limit` 之后的字节不计
+    fn page_offsets(&self, page: &[u8], skip: usize, limit: usize) -> Vec<u16> {
+        let limit = limit.min(page.len());
+        if skip >= limit {
+            return Vec::new();
+        }
+        let slice = &page[skip..limit];
+        match self.single {
+            Some(byte) => memchr_iter(byte, slice).map(|offset| (skip + offset) as u16).collect(),
+            None => slice
+                .iter()
+                .enumerate()
+                .filter(|(_, byte)| self.matcher[**byte as usize])
+                .map(|(offset, _)| (skip + offset) as u16)
+                .collect(),
+        }
+    }
+
+    /// 扫描 [start, end)，按块读取，读取失败的页跳过
+    pub fn scan_region<R, F>(&self, start: u64, end: u64, mut read: R, check_cancelled: &F, read_stats: &ReadStats) -> Vec<PageHits>
+    where
+        R: FnMut(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+        F: Fn() -> bool,
+    {
+        let page_size = self.page_size as u64;
+        let mut pages = Vec::new();
+        let mut current = start & !(page_size - 1); // 当前的页对齐地址
+        let mut chunk_buffer = vec![0u8; self.chunk_size];
+
+        while current < end {
+            if check_cancelled() {
+                break;
+            }
+
+            let chunk_end = (current + self.chunk_size as u64).min(end);
+            let chunk_len = (chunk_end - current) as usize;
+            let mut page_status = PageStatusBitmap::new(chunk_len, current as usize);
+            let read_result = read(current, &mut chunk_buffer[..chunk_len], &mut page_status);
+            read_stats.record_chunk(current, chunk_len, read_result.is_ok(), &page_status, self.page_size);
+
+            if read_result.is_ok() {
+                for (page_index, page) in chunk_buffer[..chunk_len].chunks(self.page_size).enumerate() {
+                    if !page_status.is_page_success(page_index) {
+                        continue;
+                    }
+                    let base = current + page_index as u64 * page_size;
+                    let skip = start.saturating_sub(base) as usize;
+                    let offsets = self.page_offsets(page, skip, (end - base) as usize);
+                    pages.extend(PageHits::from_offsets(base, &offsets, self.page_size));
+                }
+            }
+
+            current = chunk_end;
+        }
+        pages
+    }
+
+    /// 用新条件改善已有命中，每页只读取第一个到最后一个命中之间的字节；读取失败的页整页丢弃
+    pub fn refine<R, F, P>(&self, hits: &ByteHitSet, read: R, check_cancelled: &F, update_progress: &P) -> ByteHitSet
+    where
+        R: Fn(u64, &mut [u8]) -> Result<()> + Sync,
+        F: Fn() -> bool + Sync,
+        P: Fn(usize, usize) + Sync,
+    {
+        let processed = AtomicUsize::new(0);
+        let found = AtomicUsize::new(0);
+
+        let pages: Vec<PageHits> = hits
+            .pages()
+            .par_iter()
+            .enumerate()
+            .filter_map(|(index, page)| {
+                if index.is_multiple_of(REFINE_PROGRESS_PAGES) {
+                    if check_cancelled() {
+                        return None;
+                    }
+                    update_progress(processed.load(Ordering::Relaxed), found.load(Ordering::Relaxed));
+                }
+
+                let offsets = page.offsets();
+                processed.fetch_add(offsets.len(), Ordering::Relaxed);
+                let first = offsets[0] as usize;
+                let last = offsets[offsets.len() - 1] as usize;
+                let mut buffer = vec![0u8; last - first + 1];
+                read(page.base() + first as u64, &mut buffer).ok()?;
+
+                let kept: Vec<u16> = offsets
+                    .into_iter()
+                    .filter(|&offset| self.matcher[buffer[offset as usize - first] as usize])
+                    .collect();
+                found.fetch_add(kept.len(), Ordering::Relaxed);
+                PageHits::from_offsets(page.base(), &kept, self.page_size)
+            })
+            .collect();
+
+        if check_cancelled() {
+            return ByteHitSet::from_pages(self.page_size, Vec::new());
+        }
+        ByteHitSet::from_pages(self.page_size, pages).with_pass(hits.pass())
+    }
+
+    /// 均匀抽样 `PROJECTION_SAMPLES` 页估算命中总数，读取失败的页按没有命中计算
+    pub fn project<R>(&self, regions: &[(u64, u64)], mut read: R) -> u64
+    where
+        R: FnMut(u64, &mut [u8]) -> Result<()>,
+    {
+        let total_bytes: u64 = regions.iter().map(|(start, end)| end.saturating_sub(*start)).sum();
+        if total_bytes == 0 {
+            return 0;
+        }
+
+        let page_size = self.page_size as u64;
+        let mut buffer = vec![0u8; self.page_size];
+        let mut sampled_bytes = 0u64;
+        let mut sampled_hits = 0u64;
+        for sample in 0..PROJECTION_SAMPLES as u64 {
+            // 取第 sample 段的中点
+            let mut position = total_bytes / PROJECTION_SAMPLES as u64 * sample + total_bytes / PROJECTION_SAMPLES as u64 / 2;
+            let Some(&(start, end)) = regions.iter().find(|(start, end)| {
+                let len = end.saturating_sub(*start);
+                if position < len {
+                    true
+                } else {
+                    position -= len;
+                    false
+                }
+            }) else {
+                continue;
+            };
+
+            let base = (start + position) & !(page_size - 1);
+            let skip = start.saturating_sub(base) as usize;
+            let limit = (end - base).min(page_size) as usize;
+            sampled_bytes += (limit - skip) as u64;
+            if read(base, &mut buffer).is_ok() {
+                sampled_hits += self.page_offsets(&buffer, skip, limit).len() as u64;
+            }
+        }
+
+        if sampled_bytes == 0 {
+            return 0;
+        }
+        (sampled_hits as f64 / sampled_bytes as f64 * total_bytes as f64) as u64
+    }
+}
+
+/// 查询是单个 Byte 值且预计结果数超过阈值时返回扫描器和预计结果数
+pub fn plan_byte_search<R>(
+    query: &SearchQuery,
+    regions: &[(u64, u64)],
+    threshold: usize,
+    page_size: usize,
+    chunk_size: usize,
+    read: R,
+) -> Option<(ByteScanner, u64)>
+where
+    R: FnMut(u64, &mut [u8]) -> Result<()>,
+{
+    // 按页命中集合记录每个字节，指定了更大的对齐时走普通路径
+    if threshold == 0 || query.alignment.is_some_and(|alignment| alignment > 1) {
+        return None;
+    }
+    let scanner = ByteScanner::new(query_byte_matcher(query)?, page_size, chunk_size);
+    let projected = scanner.project(regions, read);
+    (projected > threshold as u64).then_some((scanner, projected))
+}
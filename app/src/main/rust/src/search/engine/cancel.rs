@@ -0,0 +1,88 @@
+//! Unified cancellation source for search tasks.
+//!
+//! 搜索任务的取消信号有三个来源：
+//! - `CancellationToken`（nativeRequestCancel）
+//! - 任务内部的 `AtomicBool`（任一并行分支观察到取消后置位，传播给其他分支）
+//! - 共享缓冲区中 Kotlin 写入的 cancel_flag
+//!
+//! 前两者是无锁的，可以在内层扫描循环中高频检查；共享缓冲区需要经过
+//! `SEARCH_ENGINE_MANAGER` 的读锁，所以使用 `try_read`，拿不到锁时跳过，
+//! 绝不在热路径上阻塞。
+//!
+//! 首次扫描的结果数达到上限时扫描同样要尽快停下来，但已经找到的结果照常排序保存，
+//! 搜索以 `CompletedTruncated` 结束。这用单独的停止标志表示：扫描循环检查 `poll_scan`，
+//! 扫描之后的阶段只检查真正的取消。
+
+use super::manager::SEARCH_ENGINE_MANAGER;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+
+/// 内层扫描循环每处理多少个候选位置检查一次取消
+pub(crate) const CANCEL_CHECK_CANDIDATES: usize = 4096;
+
+#[derive(Clone)]
+pub(crate) struct CancelSource {
+    token: CancellationToken,
+    flag: Arc<AtomicBool>,
+    /// 结果数达到上限，扫描提前结束
+    stopped: Arc<AtomicBool>,
+}
+
+impl CancelSource {
+    pub fn new(token: CancellationToken) -> Self {
+        Self {
+            token,
+            flag: Arc::new(AtomicBool::new(false)),
+            stopped: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// 无锁检查，只看 token 与本地标志
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled() || self.flag.load(Ordering::Relaxed)
+    }
+
+    /// 标记为已取消，其他并行分支会在下一个检查点观察到
+    #[inline]
+    pub fn cancel(&self) {
+        self.flag.store(true, Ordering::Relaxed);
+    }
+
+    /// 完整检查：在无锁检查之外，再查询共享缓冲区中的取消标志。
+    /// 观察到取消后会置位本地标志，后续的 `is_cancelled` 都会返回 true。
+    pub fn poll(&self) -> bool {
+        if self.is_cancelled() {
+            self.cancel();
+            return true;
+        }
+        if let Ok(manager) = SEARCH_ENGINE_MANAGER.try_read()
+            && manager.is_cancel_requested()
+        {
+            self.cancel();
+            return true;
+        }
+        false
+    }
+
+    /// 结果数达到上限，停止扫描但保留结果
+    #[inline]
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
+    }
+
+    /// 扫描循环的检查点：已取消、或者 `found` 达到 `max_results`（0 表示不限制）时返回 true，
+    /// 后者同时置位停止标志
+    pub fn poll_scan(&self, found: u64, max_results: usize) -> bool {
+        if max_results > 0 && found >= max_results as u64 {
+            self.stop();
+        }
+        self.is_stopped() || self.poll()
+    }
+}
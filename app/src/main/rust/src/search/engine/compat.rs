@@ -0,0 +1,177 @@
+//! Automatic compatibility mode policy
+//!
+//! 兼容模式把精确搜索结果以模糊格式存储（每项 18 字节并且需要逐个读取当前值），
+//! 结果量小时很方便，但上亿结果的首次扫描代价太大。
+//! 超过阈值的扫描先只存精确结果并记录 "deferred"，
+//! 等后续改善搜索把结果数降到阈值以下时再补做一次精确→模糊的值捕获。
+
+use super::cancel::CANCEL_CHECK_CANDIDATES;
+use super::manager::ValuePair;
+use crate::search::PAGE_MASK;
+use crate::search::result_manager::FuzzySearchResultItem;
+use anyhow::Result;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// 默认自动阈值：超过 5M 结果的扫描延迟兼容格式存储
+pub const DEFAULT_COMPAT_AUTO_THRESHOLD: usize = 5_000_000;
+
+/// 兼容模式的实际状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CompatibilityState {
+    /// 用户是否开启了兼容模式
+    pub requested: bool,
+    /// 当前结果是否以兼容格式存储
+    pub active: bool,
+    /// 结果数超过阈值，值捕获被延迟
+    pub deferred: bool,
+    /// 自动阈值，0 表示不延迟
+    pub auto_threshold: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CompatPolicy {
+    requested: bool,
+    auto_threshold: usize,
+    deferred: bool,
+}
+
+impl Default for CompatPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CompatPolicy {
+    pub fn new() -> Self {
+        Self {
+            requested: false,
+            auto_threshold: DEFAULT_COMPAT_AUTO_THRESHOLD,
+            deferred: false,
+        }
+    }
+
+    pub fn set_requested(&mut self, requested: bool) {
+        self.requested = requested;
+        if !requested {
+            self.deferred = false;
+        }
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.requested
+    }
+
+    /// 设置自动阈值，0 表示始终按兼容格式存储
+    pub fn set_auto_threshold(&mut self, threshold: usize) {
+        self.auto_threshold = threshold;
+    }
+
+    pub fn is_deferred(&self) -> bool {
+        self.deferred
+    }
+
+    fn within_threshold(&self, count: usize) -> bool {
+        self.auto_threshold == 0 || count <= self.auto_threshold
+    }
+
+    /// 首次扫描得到 count 个结果时是否以模糊格式存储
+    pub fn should_store_fuzzy(&self, count: usize) -> bool {
+        self.requested && self.within_threshold(count)
+    }
+
+    /// 扫描结果入库后更新延迟标记
+    pub fn on_scan_stored(&mut self, stored_fuzzy: bool) {
+        self.deferred = self.requested && !stored_fuzzy;
+    }
+
+    /// 改善搜索剩下 count 个精确结果时是否需要补做值捕获
+    pub fn should_capture(&self, count: usize) -> bool {
+        self.requested && self.deferred && self.within_threshold(count)
+    }
+
+    /// 值捕获完成，之后不再重复
+    pub fn mark_captured(&mut self) {
+        self.deferred = false;
+    }
+
+    /// 结果被清空时调用
+    pub fn reset(&mut self) {
+        self.deferred = false;
+    }
+
+    /// 载入保存的会话时恢复请求和延迟状态，阈值保持当前设置
+    pub fn restore(&mut self, requested: bool, deferred: bool) {
+        self.requested = requested;
+        self.deferred = requested && deferred;
+    }
+
+    pub fn state(&self, stored_fuzzy: bool) -> CompatibilityState {
+        CompatibilityState {
+            requested: self.requested,
+            active: self.requested && !self.deferred && stored_fuzzy,
+            deferred: self.deferred,
+            auto_threshold: self.auto_threshold,
+        }
+    }
+}
+
+/// 读取精确结果的当前值并转换为模糊格式，按块并行读取，每块前检查取消
+///
+/// 块内相邻且在同一页上的结果合并为一次读取，再从缓冲区切出各自的值；
+/// 整段读取失败时（例如跨页的值落在不可读的页上）退回逐个读取。
+/// 读取失败的地址会被丢弃，与兼容模式首次扫描的行为一致。
+/// 每处理完一块调用 `on_progress(已处理数量)`。变长结果（特征码、文本）读取 `pattern_len` 字节。
+pub fn capture_fuzzy_values<R, F, P>(pairs: &[ValuePair], pattern_len: usize, read: R, check_cancelled: &F, on_progress: &P) -> Vec<FuzzySearchResultItem>
+where
+    R: Fn(u64, &mut [u8]) -> Result<()> + Sync,
+    F: Fn() -> bool + Sync,
+    P: Fn(usize) + Sync,
+{
+    let page_mask = *PAGE_MASK as u64;
+    let processed = AtomicUsize::new(0);
+    pairs
+        .par_chunks(CANCEL_CHECK_CANDIDATES)
+        .flat_map_iter(|chunk| {
+            let mut local = Vec::with_capacity(chunk.len());
+            if check_cancelled() {
+                return local;
+            }
+            let mut buffer = Vec::new();
+            for group in chunk.chunk_by(|a, b| a.addr & page_mask == b.addr & page_mask) {
+                capture_page_group(group, pattern_len, &read, &mut buffer, &mut local);
+            }
+            on_progress(processed.fetch_add(chunk.len(), Ordering::Relaxed) + chunk.len());
+            local
+        })
+        .collect()
+}
+
+/// 一次读取覆盖整组结果的区间，失败时逐个读取
+fn capture_page_group<R>(group: &[ValuePair], pattern_len: usize, read: &R, buffer: &mut Vec<u8>, out: &mut Vec<FuzzySearchResultItem>)
+where
+    R: Fn(u64, &mut [u8]) -> Result<()>,
+{
+    let value_len = |pair: &ValuePair| if pair.value_type.is_variable_len() { pattern_len } else { pair.value_type.size() };
+    if group.len() > 1 {
+        let start = group.iter().map(|pair| pair.addr).min().unwrap_or(0);
+        let end = group.iter().map(|pair| pair.addr + value_len(pair) as u64).max().unwrap_or(start);
+        buffer.resize((end - start) as usize, 0);
+        if read(start, buffer).is_ok() {
+            for pair in group {
+                let offset = (pair.addr - start) as usize;
+                out.push(FuzzySearchResultItem::from_bytes(pair.addr, &buffer[offset..offset + value_len(pair)], pair.value_type));
+            }
+            return;
+        }
+    }
+
+    let mut value = vec![0u8; group.iter().map(value_len).max().unwrap_or(0)];
+    for pair in group {
+        let size = value_len(pair);
+        if read(pair.addr, &mut value[..size]).is_ok() {
+            out.push(FuzzySearchResultItem::from_bytes(pair.addr, &value[..size], pair.value_type));
+        }
+    }
+}
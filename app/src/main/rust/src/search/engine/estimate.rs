@@ -0,0 +1,236 @@
+//! Scan dry-run estimator
+//!
+//! 在开始扫描前预估耗时和存储占用，避免用户启动一个要跑十分钟、写满磁盘的扫描后再取消。
+//! 耗时基于设备的滚动读取吞吐统计：每次扫描完成后更新并持久化到缓存目录，
+//! 没有历史记录时先做一次 16MB 的探测读取。
+
+use crate::search::result_manager::FuzzySearchResultItem;
+use crate::search::ValueType;
+use anyhow::Result;
+use log::{debug, warn};
+use nix::libc;
+use serde::{Deserialize, Serialize};
+use std::ffi::CString;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// 吞吐统计持久化文件名
+pub const THROUGHPUT_FILE_NAME: &str = "scan_throughput.json";
+
+/// 没有历史记录时探测读取的总大小
+pub const PROBE_BYTES: usize = 16 * 1024 * 1024;
+
+/// 探测读取的单次读取大小
+const PROBE_CHUNK: usize = 1024 * 1024;
+
+/// 新样本在滚动平均中的权重
+const THROUGHPUT_EWMA_WEIGHT: f64 = 0.3;
+
+/// 太短的扫描计时不可靠，不计入统计
+const MIN_SAMPLE_DURATION: Duration = Duration::from_millis(50);
+
+/// 剩余磁盘空间低于该值时给出警告
+pub const LOW_FREE_DISK_BYTES: u64 = 512 * 1024 * 1024;
+
+/// 模糊扫描默认的磁盘预算
+pub const DEFAULT_DISK_BUDGET: u64 = 2 * 1024 * 1024 * 1024;
+
+/// 默认的最大结果数
+pub const DEFAULT_MAX_RESULTS: u64 = 100_000_000;
+
+/// 设备读取吞吐的滚动统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ThroughputStats {
+    /// 滚动平均的读取吞吐（字节/秒）
+    pub bytes_per_sec: f64,
+    pub samples: u32,
+}
+
+impl ThroughputStats {
+    pub fn has_history(&self) -> bool {
+        self.samples > 0 && self.bytes_per_sec > 0.0
+    }
+
+    /// 记录一次完成的扫描（或探测读取）
+    pub fn record(&mut self, bytes: u64, elapsed: Duration) {
+        if bytes == 0 || elapsed < MIN_SAMPLE_DURATION {
+            return;
+        }
+        let sample = bytes as f64 / elapsed.as_secs_f64();
+        self.bytes_per_sec = if self.has_history() {
+            self.bytes_per_sec * (1.0 - THROUGHPUT_EWMA_WEIGHT) + sample * THROUGHPUT_EWMA_WEIGHT
+        } else {
+            sample
+        };
+        self.samples = self.samples.saturating_add(1);
+    }
+
+    pub fn load(cache_dir: &Path) -> Self {
+        std::fs::read(cache_dir.join(THROUGHPUT_FILE_NAME))
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, cache_dir: &Path) -> Result<()> {
+        std::fs::write(cache_dir.join(THROUGHPUT_FILE_NAME), serde_json::to_vec(self)?)?;
+        Ok(())
+    }
+}
+
+/// 预估时的资源限制
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScanLimits {
+    /// 模糊扫描结果允许占用的磁盘字节数
+    pub disk_budget: u64,
+    pub max_results: u64,
+}
+
+impl Default for ScanLimits {
+    fn default() -> Self {
+        Self {
+            disk_budget: DEFAULT_DISK_BUDGET,
+            max_results: DEFAULT_MAX_RESULTS,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanWarning {
+    /// 预计存储超过磁盘预算
+    ExceedsDiskBudget,
+    /// 预计结果数超过上限
+    ExceedsMaxResults,
+    /// 剩余磁盘空间不足
+    LowFreeDisk,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScanEstimate {
+    pub total_bytes: u64,
+    pub region_count: usize,
+    pub throughput_bytes_per_sec: f64,
+    /// 没有吞吐统计（探测读取一个字节都没读到）时为 None
+    pub estimated_millis: Option<u64>,
+    /// 仅模糊扫描：预计结果数
+    pub projected_results: Option<u64>,
+    /// 仅模糊扫描：预计存储字节数
+    pub projected_storage_bytes: Option<u64>,
+    pub free_disk_bytes: Option<u64>,
+    /// 本次预估是否做了探测读取
+    pub probed: bool,
+    pub warnings: Vec<ScanWarning>,
+}
+
+/// 区域总字节数
+pub fn scan_bytes(regions: &[(u64, u64)]) -> u64 {
+    regions.iter().map(|&(start, end)| end.saturating_sub(start)).sum()
+}
+
+/// 对给定区域做一次最多 PROBE_BYTES 的探测读取，返回 (读取字节数, 耗时)
+pub fn probe_throughput<R>(regions: &[(u64, u64)], mut read: R) -> (u64, Duration)
+where
+    R: FnMut(u64, &mut [u8]) -> Result<()>,
+{
+    let mut buffer = vec![0u8; PROBE_CHUNK];
+    let mut remaining = PROBE_BYTES as u64;
+    let mut bytes_read = 0u64;
+    let start = Instant::now();
+
+    'regions: for &(region_start, region_end) in regions {
+        let mut addr = region_start;
+        while addr < region_end {
+            if remaining == 0 {
+                break 'regions;
+            }
+            let len = (region_end - addr).min(PROBE_CHUNK as u64).min(remaining) as usize;
+            if read(addr, &mut buffer[..len]).is_ok() {
+                bytes_read += len as u64;
+            }
+            remaining -= len as u64;
+            addr += len as u64;
+        }
+    }
+
+    (bytes_read, start.elapsed())
+}
+
+/// 预估一次扫描
+///
+/// - `value_type`: 模糊扫描的值类型，决定扫描步长
+/// - `stats`: 吞吐统计，没有历史时会用 `probe` 做探测读取并写入统计
+/// - `free_disk`: 缓存目录所在分区的剩余空间
+pub fn estimate_scan<R>(
+    value_type: ValueType,
+    regions: &[(u64, u64)],
+    fuzzy: bool,
+    stats: &mut ThroughputStats,
+    limits: ScanLimits,
+    free_disk: Option<u64>,
+    probe: R,
+) -> ScanEstimate
+where
+    R: FnMut(u64, &mut [u8]) -> Result<()>,
+{
+    let total_bytes = scan_bytes(regions);
+
+    let mut probed = false;
+    if !stats.has_history() {
+        let (bytes, elapsed) = probe_throughput(regions, probe);
+        // 探测太快时也要得到一个样本
+        stats.record(bytes, elapsed.max(MIN_SAMPLE_DURATION));
+        probed = true;
+        debug!("Throughput probe: {} bytes in {:?}", bytes, elapsed);
+    }
+
+    let estimated_millis = stats
+        .has_history()
+        .then(|| (total_bytes as f64 / stats.bytes_per_sec * 1000.0).ceil() as u64);
+
+    let mut warnings = Vec::new();
+    let (projected_results, projected_storage_bytes) = if fuzzy {
+        let stride = value_type.size().max(1) as u64;
+        let results = total_bytes / stride;
+        let storage = results * size_of::<FuzzySearchResultItem>() as u64;
+        if storage > limits.disk_budget {
+            warnings.push(ScanWarning::ExceedsDiskBudget);
+        }
+        if results > limits.max_results {
+            warnings.push(ScanWarning::ExceedsMaxResults);
+        }
+        (Some(results), Some(storage))
+    } else {
+        (None, None)
+    };
+
+    if let Some(free) = free_disk {
+        let needed = projected_storage_bytes.unwrap_or(0);
+        if free < LOW_FREE_DISK_BYTES || free < needed {
+            warnings.push(ScanWarning::LowFreeDisk);
+        }
+    }
+
+    ScanEstimate {
+        total_bytes,
+        region_count: regions.len(),
+        throughput_bytes_per_sec: stats.bytes_per_sec,
+        estimated_millis,
+        projected_results,
+        projected_storage_bytes,
+        free_disk_bytes: free_disk,
+        probed,
+        warnings,
+    }
+}
+
+/// 获取路径所在分区的剩余空间
+pub fn free_disk_bytes(path: &Path) -> Option<u64> {
+    let c_path = CString::new(path.as_os_str().as_encoded_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        warn!("statvfs failed for {:?}", path);
+        return None;
+    }
+    Some(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
@@ -0,0 +1,113 @@
+//! Value snapshot for condition refine on exact results
+//!
+//! 精确结果只有地址和类型，没有值，不能直接用"变大/变小/改变"这类模糊条件改善。旧值按以下顺序取得：
+//! - 上一次带条件的改善留下的快照，快照绑定结果集版本号，结果集被其他操作改变后自动失效；
+//! - 扫描时缓存的值（`ExactValueCache`），第一次带条件的改善直接以它为旧值比较，
+//!   没有缓存值的地址（扫描时读取失败、变长类型）不比较，只刷新当前值，仍可读的保留；
+//! - 两者都没有时（结果太多没有缓存、或者结果已被改善过）只读取所有结果的当前值作为快照，
+//!   读取失败的地址丢弃，搜索以 `SnapshotOnly` 结束，条件从下一次改善开始生效。
+//!
+//! 结果在任务中用游标分批取出，按页批量读取；幸存者的当前值成为新的快照。
+
+use crate::search::SearchResultItem;
+use crate::search::result_manager::{ExactSearchResultItem, ExactValueCache, FuzzySearchResultItem};
+use anyhow::Result;
+
+/// 某个版本的精确结果集对应的值
+#[derive(Debug, Clone)]
+pub(crate) struct ExactSnapshot {
+    revision: u64,
+    items: Vec<FuzzySearchResultItem>,
+}
+
+impl ExactSnapshot {
+    pub fn new(revision: u64, items: Vec<FuzzySearchResultItem>) -> Self {
+        Self { revision, items }
+    }
+
+    /// 快照是否对应结果集的当前版本
+    pub fn is_current(&self, revision: u64) -> bool {
+        self.revision == revision
+    }
+
+    pub fn into_items(self) -> Vec<FuzzySearchResultItem> {
+        self.items
+    }
+}
+
+/// 待读取快照的项：按地址排序以便批量读取聚类，值留空，保留轮次
+pub(crate) fn snapshot_seeds(results: &[ExactSearchResultItem]) -> Vec<FuzzySearchResultItem> {
+    let mut seeds: Vec<FuzzySearchResultItem> = results
+        .iter()
+        .map(|result| FuzzySearchResultItem::new(result.address, [0; 8], result.typ).with_pass(result.pass))
+        .collect();
+    if !seeds.is_sorted() {
+        seeds.sort_unstable();
+    }
+    seeds
+}
+
+/// 分批取出全部精确结果并转成待读取快照的项，`next_batch` 按地址顺序返回，取完时返回空批次
+pub(crate) fn read_seeds<B>(total: usize, mut next_batch: B) -> Result<Vec<FuzzySearchResultItem>>
+where
+    B: FnMut() -> Result<Vec<ExactSearchResultItem>>,
+{
+    let mut seeds = Vec::with_capacity(total);
+    loop {
+        let batch = next_batch()?;
+        if batch.is_empty() {
+            break;
+        }
+        seeds.extend(snapshot_seeds(&batch));
+    }
+    // 批次之间也要保持地址顺序，批量读取按顺序聚类
+    if !seeds.is_sorted() {
+        seeds.sort_unstable();
+    }
+    Ok(seeds)
+}
+
+/// 以扫描时缓存的值为旧值的待改善项，按批加入，每批在持有结果管理器的锁时加入
+#[derive(Debug, Default)]
+pub(crate) struct CachedSeeds {
+    compared: Vec<FuzzySearchResultItem>,
+    carried: Vec<FuzzySearchResultItem>,
+}
+
+impl CachedSeeds {
+    /// 有缓存值的定长结果以缓存值为旧值参与比较，其余只刷新当前值
+    pub fn extend(&mut self, results: &[ExactSearchResultItem], cache: &ExactValueCache) {
+        for result in results {
+            let cached = cache.get(result.address).filter(|_| !result.typ.is_variable_len());
+            match cached {
+                Some(bytes) => {
+                    let len = result.typ.size().min(bytes.len());
+                    self.compared
+                        .push(FuzzySearchResultItem::from_bytes(result.address, &bytes[..len], result.typ).with_pass(result.pass));
+                },
+                None => self
+                    .carried
+                    .push(FuzzySearchResultItem::new(result.address, [0; 8], result.typ).with_pass(result.pass)),
+            }
+        }
+    }
+
+    /// 参与比较的项和只刷新的项，各自按地址排序
+    pub fn finish(self) -> (Vec<FuzzySearchResultItem>, Vec<FuzzySearchResultItem>) {
+        let Self { mut compared, mut carried } = self;
+        for items in [&mut compared, &mut carried] {
+            if !items.is_sorted() {
+                items.sort_unstable();
+            }
+        }
+        (compared, carried)
+    }
+}
+
+/// 改善后的快照项转回精确结果，保留轮次
+pub(crate) fn to_exact_results(items: &[FuzzySearchResultItem]) -> Vec<SearchResultItem> {
+    items
+        .iter()
+        .map(|item| SearchResultItem::new_exact(item.address, item.value_type).with_pass(item.pass))
+        .collect()
+}
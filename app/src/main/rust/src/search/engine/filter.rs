@@ -1,4 +1,7 @@
+use super::super::result_manager::SearchResultItem;
 use super::super::types::ValueType;
+use anyhow::{Result, anyhow};
+use std::sync::{Arc, Mutex};
 
 /// 搜索过滤器
 /// 用于在搜索过程中应用地址范围和类型过滤
@@ -16,6 +19,31 @@ pub struct SearchFilter {
 
     /// 类型ID列表
     pub type_ids: Vec<ValueType>,
+
+    /// 只显示轮次不小于 min_pass 的结果
+    pub min_pass: Option<u8>,
+    /// 只显示轮次不大于 max_pass 的结果
+    pub max_pass: Option<u8>,
+}
+
+/// 结果的显示顺序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResultOrder {
+    /// 存储顺序
+    #[default]
+    Storage,
+    /// 按进入结果集的轮次，最旧的在前，同一轮次内保持存储顺序
+    OldestFirst,
+}
+
+impl ResultOrder {
+    pub fn from_id(id: i32) -> Option<Self> {
+        match id {
+            0 => Some(ResultOrder::Storage),
+            1 => Some(ResultOrder::OldestFirst),
+            _ => None,
+        }
+    }
 }
 
 impl SearchFilter {
@@ -25,11 +53,68 @@ impl SearchFilter {
 
     #[inline]
     pub fn is_active(&self) -> bool {
-        self.enable_address_filter || self.enable_type_filter || !self.type_ids.is_empty()
+        self.enable_address_filter || self.enable_type_filter || !self.type_ids.is_empty() || self.min_pass.is_some() || self.max_pass.is_some()
+    }
+
+    /// 结果是否满足过滤条件
+    pub fn matches(&self, item: &SearchResultItem) -> bool {
+        // packed 字段先拷贝
+        let (addr, typ) = match item {
+            SearchResultItem::Exact(exact) => (exact.address, exact.typ),
+            SearchResultItem::Fuzzy(fuzzy) => (fuzzy.address, fuzzy.value_type),
+        };
+
+        if self.enable_address_filter && (addr < self.address_start || addr > self.address_end) {
+            return false;
+        }
+
+        if self.enable_type_filter && !self.type_ids.is_empty() && !self.type_ids.contains(&typ) {
+            return false;
+        }
+
+        let pass = item.pass();
+        self.min_pass.is_none_or(|min| pass >= min) && self.max_pass.is_none_or(|max| pass <= max)
     }
 
     #[inline]
     pub fn clear(&mut self) {
         *self = Self::default();
     }
-}
\ No newline at end of file
+}
+
+/// 过滤视图的索引：满足过滤条件的结果按显示顺序排列的存储索引
+///
+/// 按结果集版本和显示顺序缓存；过滤条件变化时由调用方 `invalidate`。
+#[derive(Default)]
+pub(crate) struct FilteredIndexCache {
+    cached: Mutex<Option<CachedIndex>>,
+}
+
+struct CachedIndex {
+    revision: u64,
+    order: ResultOrder,
+    index: Arc<Vec<u32>>,
+}
+
+impl FilteredIndexCache {
+    pub fn get_or_build<B>(&self, revision: u64, order: ResultOrder, build: B) -> Result<Arc<Vec<u32>>>
+    where
+        B: FnOnce() -> Result<Vec<u32>>,
+    {
+        let mut cached = self.cached.lock().map_err(|_| anyhow!("Failed to acquire filtered index cache lock"))?;
+        if let Some(entry) = cached.as_ref()
+            && entry.revision == revision
+            && entry.order == order
+        {
+            return Ok(Arc::clone(&entry.index));
+        }
+
+        let index = Arc::new(build()?);
+        *cached = Some(CachedIndex { revision, order, index: Arc::clone(&index) });
+        Ok(index)
+    }
+
+    pub fn invalidate(&mut self) {
+        *self = Self::default();
+    }
+}
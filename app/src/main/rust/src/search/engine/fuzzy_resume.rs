@@ -0,0 +1,63 @@
+//! Resumable fuzzy initial scan
+//!
+//! 模糊首次扫描按区域顺序进行，每扫描完一个区域就写入结果集。取消时已写入的区域保留，
+//! 可以直接在这部分结果上改善；剩余的区域记录在 `FuzzyResume` 中，恢复时只扫描剩余区域并追加结果。
+//! 被取消打断的区域结果不完整，不写入结果集，恢复时重新扫描。
+//!
+//! 记录对应结果集的一个版本，之后结果集发生任何变化（改善、删除、新的搜索）都不能再恢复。
+
+use crate::search::ValueType;
+use crate::search::result_manager::FuzzySearchResultItem;
+
+/// 取消的模糊首次扫描中尚未扫描的区域
+#[derive(Debug, Clone)]
+pub(crate) struct FuzzyResume {
+    revision: u64,
+    pub value_type: ValueType,
+    pub alignment: usize,
+    pub remaining: Vec<(u64, u64)>,
+    /// 之前各次扫描完成的区域数，恢复后进度从这里继续
+    pub regions_done: usize,
+}
+
+impl FuzzyResume {
+    pub fn new(revision: u64, value_type: ValueType, alignment: usize, remaining: Vec<(u64, u64)>, regions_done: usize) -> Self {
+        Self {
+            revision,
+            value_type,
+            alignment,
+            remaining,
+            regions_done,
+        }
+    }
+
+    /// 记录是否对应结果集的当前版本
+    pub fn is_current(&self, revision: u64) -> bool {
+        self.revision == revision
+    }
+
+    pub fn total_regions(&self) -> usize {
+        self.regions_done + self.remaining.len()
+    }
+}
+
+/// 按顺序扫描区域，完整扫描的区域结果交给 `commit`，返回完成的区域数
+///
+/// 扫描前和扫描后都检查取消，扫描后发现已取消时丢弃该区域的结果，它仍算作未完成。
+pub(crate) fn scan_regions_in_order<S, C>(regions: &[(u64, u64)], is_cancelled: impl Fn() -> bool, mut scan: S, mut commit: C) -> usize
+where
+    S: FnMut(usize, u64, u64) -> Vec<FuzzySearchResultItem>,
+    C: FnMut(usize, Vec<FuzzySearchResultItem>),
+{
+    for (idx, &(start, end)) in regions.iter().enumerate() {
+        if is_cancelled() {
+            return idx;
+        }
+        let results = scan(idx, start, end);
+        if is_cancelled() {
+            return idx;
+        }
+        commit(idx, results);
+    }
+    regions.len()
+}
@@ -1,7 +1,8 @@
-use super::super::result_manager::FuzzySearchResultItem;
+use super::super::result_manager::{FuzzySearchResultItem, RefineJournal, SearchResultManager};
 use super::super::types::{FuzzyCondition, ValueType};
-use crate::core::DRIVER_MANAGER;
-use crate::search::engine::batch_reader::{cluster_addresses, parallel_batch_read};
+use super::read_stats::ReadStats;
+use crate::core::{AccessQos, DRIVER_MANAGER};
+use crate::search::engine::batch_reader::{cluster_addresses, parallel_batch_read_with, PageCacheStats};
 use crate::search::PAGE_SIZE;
 use crate::wuwa::PageStatusBitmap;
 use anyhow::{anyhow, Result};
@@ -9,6 +10,7 @@ use log::{debug, info, log_enabled, warn, Level};
 use rayon::prelude::*;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 /// 模糊搜索初始扫描
 /// 记录指定内存区域内所有地址的当前值
@@ -16,19 +18,26 @@ use std::sync::Arc;
 ///
 /// # 参数
 /// * `value_type` - 要搜索的值类型
+/// * `alignment` - 记录的地址的对齐，通常是类型大小
 /// * `start` - 区域起始地址
 /// * `end` - 区域结束地址
 /// * `chunk_size` - 每次读取的块大小
+/// * `qos` - 读取每块时的 QoS 等级
 /// * `check_cancelled` - 取消检查闭包（可选）
+/// * `read_stats` - 读取字节统计
 ///
 /// # 返回
 /// 返回所有成功读取的地址及其值
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn fuzzy_initial_scan<F>(
     value_type: ValueType,
+    alignment: usize,
     start: u64,
     end: u64,
     chunk_size: usize,
+    qos: AccessQos,
     check_cancelled: Option<&F>,
+    read_stats: &ReadStats,
 ) -> Result<Vec<FuzzySearchResultItem>>
 where
     F: Fn() -> bool,
@@ -37,10 +46,12 @@ where
 
     let element_size = value_type.size();
     let page_size = *PAGE_SIZE;
+    // 非自然对齐的值可能跨块，每块多读 element_size - 1 字节，起始地址仍只接受本块内的
+    let overlap = if alignment < element_size { element_size - 1 } else { 0 };
 
     // 预估结果数量，避免频繁扩容
     let region_size = end.saturating_sub(start) as usize;
-    let estimated_count = region_size / element_size;
+    let estimated_count = region_size / alignment;
     // 限制预分配大小，避免预分配过大
     let initial_capacity = estimated_count.min(1024 * 1024); // 最多预分配 1M 个元素
     let mut results = Vec::with_capacity(initial_capacity);
@@ -49,7 +60,7 @@ where
     let mut read_failed = 0usize;
 
     let mut current = start & !(*PAGE_SIZE as u64 - 1); // 页对齐
-    let mut chunk_buffer = vec![0u8; chunk_size];
+    let mut chunk_buffer = vec![0u8; chunk_size + overlap];
 
     while current < end {
         // Check cancellation at each chunk
@@ -63,11 +74,12 @@ where
         }
 
         let chunk_end = (current + chunk_size as u64).min(end);
-        let chunk_len = (chunk_end - current) as usize;
+        let chunk_len = ((chunk_end + overlap as u64).min(end) - current) as usize;
 
         let mut page_status = PageStatusBitmap::new(chunk_len, current as usize);
 
-        let read_result = driver_manager.read_memory_unified(current, &mut chunk_buffer[..chunk_len], Some(&mut page_status));
+        let read_result = driver_manager.read_memory_with_qos(current, &mut chunk_buffer[..chunk_len], Some(&mut page_status), qos);
+        read_stats.record_chunk(current, chunk_len, read_result.is_ok(), &page_status, page_size);
 
         match read_result {
             Ok(_) => {
@@ -76,19 +88,20 @@ where
                     read_success += 1;
 
                     // 使用 rayon 并行处理 buffer，收集到临时 Vec
-                    let chunk_results = scan_buffer_parallel(
+                    let chunk_results = scan_buffer_parallel_aligned(
                         &chunk_buffer[..chunk_len],
                         current,
                         start,
                         end,
                         element_size,
+                        alignment,
                         value_type,
                         page_size,
                         &page_status,
                     );
 
-                    // 直接追加到结果 Vec
-                    results.extend(chunk_results);
+                    // 直接追加到结果 Vec，从多读部分开始的值留给下一块
+                    results.extend(chunk_results.into_iter().filter(|item| item.address < chunk_end));
                 } else {
                     read_failed += 1;
                 }
@@ -120,8 +133,9 @@ where
 
 /// 使用 rayon 并行处理缓冲区，按页分割任务
 /// 每个成功的页独立并行处理，无需比较操作
+#[allow(clippy::too_many_arguments)]
 #[inline]
-fn scan_buffer_parallel(
+pub(crate) fn scan_buffer_parallel(
     buffer: &[u8],
     buffer_addr: u64,
     region_start: u64,
@@ -130,6 +144,23 @@ fn scan_buffer_parallel(
     value_type: ValueType,
     page_size: usize,
     page_status: &PageStatusBitmap,
+) -> Vec<FuzzySearchResultItem> {
+    scan_buffer_parallel_aligned(buffer, buffer_addr, region_start, region_end, element_size, element_size, value_type, page_size, page_status)
+}
+
+/// 同 `scan_buffer_parallel`，地址按 `align` 对齐；`align` 小于元素大小时页尾的值可以跨入下一个成功页
+#[allow(clippy::too_many_arguments)]
+#[inline]
+pub(crate) fn scan_buffer_parallel_aligned(
+    buffer: &[u8],
+    buffer_addr: u64,
+    region_start: u64,
+    region_end: u64,
+    element_size: usize,
+    align: usize,
+    value_type: ValueType,
+    page_size: usize,
+    page_status: &PageStatusBitmap,
 ) -> Vec<FuzzySearchResultItem> {
     let buffer_end = buffer_addr + buffer.len() as u64;
     let search_start = buffer_addr.max(region_start);
@@ -151,11 +182,12 @@ fn scan_buffer_parallel(
     // 使用 rayon 并行处理每个成功的页
     success_pages
         .par_iter()
-        .flat_map(|&page_idx| scan_single_page(buffer, buffer_addr, search_start, search_end, element_size, value_type, page_size, page_idx))
+        .flat_map(|&page_idx| scan_single_page(buffer, buffer_addr, search_start, search_end, element_size, align, value_type, page_size, page_idx, page_status))
         .collect()
 }
 
 /// 扫描单个页内的所有元素
+#[allow(clippy::too_many_arguments)]
 #[inline]
 fn scan_single_page(
     buffer: &[u8],
@@ -163,9 +195,11 @@ fn scan_single_page(
     search_start: u64,
     search_end: u64,
     element_size: usize,
+    align: usize,
     value_type: ValueType,
     page_size: usize,
     page_idx: usize,
+    page_status: &PageStatusBitmap,
 ) -> Vec<FuzzySearchResultItem> {
     let page_start_addr = buffer_addr + (page_idx * page_size) as u64;
     let page_end_addr = page_start_addr + page_size as u64;
@@ -179,11 +213,11 @@ fn scan_single_page(
     }
 
     // 对齐到元素边界
-    let rem = effective_start % element_size as u64;
+    let rem = effective_start % align as u64;
     let first_addr = if rem == 0 {
         effective_start
     } else {
-        effective_start + element_size as u64 - rem
+        effective_start + align as u64 - rem
     };
 
     if first_addr >= effective_end {
@@ -191,7 +225,7 @@ fn scan_single_page(
     }
 
     // 预计算元素数量，一次性分配
-    let elements_count = ((effective_end - first_addr) as usize) / element_size;
+    let elements_count = ((effective_end - first_addr) as usize) / align;
     let mut results = Vec::with_capacity(elements_count);
 
     // 批量处理：直接遍历字节切片，无需逐元素检查页状态
@@ -200,69 +234,88 @@ fn scan_single_page(
 
     // 确保不越界
     let safe_end = end_offset.min(buffer.len());
+    // 非自然对齐时页尾的值跨入下一页，下一页读取成功才能读完整
+    let read_end = if align < element_size && page_status.is_page_success(page_idx + 1) {
+        ((search_end - buffer_addr) as usize).min(buffer.len())
+    } else {
+        safe_end
+    };
 
     let mut offset = start_offset;
     let mut addr = first_addr;
 
-    while offset + element_size <= safe_end {
-        // 直接从 buffer 切片创建结果项
-        let item = FuzzySearchResultItem::from_bytes(addr, &buffer[offset..offset + element_size], value_type);
+    while offset < safe_end && offset + element_size <= read_end {
+        // 直接从 buffer 切片创建结果项；Auto 在 8 字节对齐处多保存 4 字节供 Qword 解释
+        let item = if value_type == ValueType::Auto {
+            let len = if addr.is_multiple_of(8) && offset + 8 <= safe_end { 8 } else { element_size };
+            FuzzySearchResultItem::auto(addr, &buffer[offset..offset + len])
+        } else {
+            FuzzySearchResultItem::from_bytes(addr, &buffer[offset..offset + element_size], value_type)
+        };
         results.push(item);
 
-        offset += element_size;
-        addr += element_size as u64;
+        offset += align;
+        addr += align as u64;
     }
 
     results
 }
 
 /// 模糊搜索细化
-/// 读取已有结果的当前值，并根据条件过滤
-/// 直接返回 Vec，避免 BPlusTree 插入开销
+/// 读取已有结果的当前值，并根据条件过滤，直接返回 Vec，避免 BPlusTree 插入开销。
+/// 当前值从 `read` 读取，测试中用模拟内存代替驱动
 ///
 /// # 参数
 /// * `items` - 之前的搜索结果
 /// * `condition` - 模糊搜索条件
+/// * `pattern_len` - 变长结果（特征码、文本）的字节数，定长结果为 0
+/// * `cache_pages` - 批量读取失败后逐个读取时页缓存的页数，0 表示不缓存
 /// * `processed_counter` - 已处理计数器（可选）
 /// * `total_found_counter` - 找到总数计数器（可选）
 /// * `update_progress` - 进度更新回调
 /// * `check_cancelled` - 取消检查闭包（可选）
-///
-/// # 返回
-/// 返回满足条件的结果项（包含新值）
-pub(crate) fn fuzzy_refine_search<P, F>(
-    items: &Vec<FuzzySearchResultItem>,
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn fuzzy_refine_search_with<R, P, F>(
+    items: &[FuzzySearchResultItem],
     condition: FuzzyCondition,
+    pattern_len: usize,
+    cache_pages: usize,
+    read: R,
     processed_counter: Option<&Arc<AtomicUsize>>,
     total_found_counter: Option<&Arc<AtomicUsize>>,
     update_progress: &P,
     check_cancelled: Option<&F>,
-) -> Result<Vec<FuzzySearchResultItem>>
+) -> Vec<FuzzySearchResultItem>
 where
+    R: Fn(u64, &mut [u8]) -> bool + Sync,
     P: Fn(usize, usize) + Sync,
     F: Fn() -> bool + Sync,
 {
     if items.is_empty() {
-        return Ok(Vec::new());
+        return Vec::new();
     }
 
     let total_items = items.len();
 
     let cluster_start = std::time::Instant::now();
-    let batches = cluster_addresses(items);
+    let batches = cluster_addresses(items, pattern_len);
     info!("[PERF] fuzzy_refine: cluster took {:?}, {} items -> {} batches (avg {:.1} items/batch)", 
         cluster_start.elapsed(), items.len(), batches.len(), items.len() as f64 / batches.len() as f64);
 
     let batch_read_start = std::time::Instant::now();
-    let items_with_current_value = parallel_batch_read(&batches, items, processed_counter, total_found_counter, update_progress, check_cancelled)?;
+    // 每次改善新建页缓存，不使用上一次改善读到的值
+    let cache_stats = PageCacheStats::default();
+    let items_with_current_value =
+        parallel_batch_read_with(&batches, items, cache_pages, &cache_stats, read, processed_counter, total_found_counter, update_progress, check_cancelled);
     info!("[PERF] fuzzy_refine: batch_read took {:?}, read {} / {} items", batch_read_start.elapsed(), items_with_current_value.len(), total_items);
+    debug!("fuzzy_refine: page cache {}", cache_stats);
 
     let cancelled = Arc::new(AtomicBool::new(false));
     let cancelled_clone = Arc::clone(&cancelled);
 
     // 使用 ReadResultItem 直接进行条件匹配，避免创建临时对象
     let filter_start = std::time::Instant::now();
-    let matched: Vec<FuzzySearchResultItem> = items_with_current_value
+    let mut matched: Vec<FuzzySearchResultItem> = items_with_current_value
         .par_chunks(8192)  // 分块处理，减少取消检查频率
         .flat_map(|chunk| {
             // 每个 chunk 开始时检查一次取消状态
@@ -277,18 +330,10 @@ where
             }
             
             // 批量处理 chunk 内的元素，直接在 ReadResultItem 上匹配
-            chunk
-                .iter()
-                .filter_map(|read_item| {
-                    if read_item.matches_condition(condition) {
-                        Some(read_item.to_fuzzy_item())
-                    } else {
-                        None
-                    }
-                })
-                .collect::<Vec<_>>()
+            chunk.iter().filter_map(|read_item| read_item.refine(condition)).collect::<Vec<_>>()
         })
         .collect();
+    dedup_auto_overlaps(&mut matched);
     info!("[PERF] fuzzy_refine: filter took {:?}, matched {} / {}", filter_start.elapsed(), matched.len(), items_with_current_value.len());
 
     if log_enabled!(Level::Debug) {
@@ -301,5 +346,438 @@ where
     }
     update_progress(total_items, matched.len());
 
-    Ok(matched)
+    matched
+}
+
+/// Auto 模糊搜索中定型为 Qword 的地址覆盖了下一个 4 字节槽，删除该槽上同一次 Auto 扫描产生的项
+///
+/// 定型前两者都保留：Qword 解释可能被后续条件排除，那时高 4 字节仍可能是独立的 Dword/Float。
+/// `items` 按地址升序。
+pub(crate) fn dedup_auto_overlaps(items: &mut Vec<FuzzySearchResultItem>) {
+    let mut covered_until = 0u64;
+    items.retain(|item| {
+        if !item.is_auto() {
+            return true;
+        }
+        let address = item.address;
+        if address < covered_until {
+            return false;
+        }
+        let value_type = item.value_type;
+        if value_type == ValueType::Qword {
+            covered_until = address + 8;
+        }
+        true
+    });
+}
+
+/// 就地细化时每段处理的结果数
+pub(crate) const FUZZY_REFINE_CHUNK: usize = 1024 * 1024;
+
+/// 对照存储中的一段结果 `[base, base + items.len())` 和它细化后的存活项
+///
+/// 返回存活项的 (存储索引, 新值) 和被删除项的存储索引（均升序）。
+/// `covered_until` 跨段传递，与 `dedup_auto_overlaps` 相同：定型为 Qword 的 Auto 项覆盖的下一个槽被删除。
+pub(crate) fn diff_refined_chunk(
+    base: usize,
+    items: &[FuzzySearchResultItem],
+    mut matched: Vec<FuzzySearchResultItem>,
+    covered_until: &mut u64,
+) -> (Vec<(usize, FuzzySearchResultItem)>, Vec<usize>) {
+    if !matched.is_sorted() {
+        matched.par_sort_unstable();
+    }
+
+    let mut updates = Vec::with_capacity(matched.len());
+    let mut removed = Vec::with_capacity(items.len() - matched.len().min(items.len()));
+    for (i, item) in items.iter().enumerate() {
+        let address = item.address;
+        let Ok(pos) = matched.binary_search_by_key(&address, |m| m.address) else {
+            removed.push(base + i);
+            continue;
+        };
+        let refined = matched[pos];
+        if refined.is_auto() {
+            if address < *covered_until {
+                removed.push(base + i);
+                continue;
+            }
+            let value_type = refined.value_type;
+            if value_type == ValueType::Qword {
+                *covered_until = address + 8;
+            }
+        }
+        updates.push((base + i, refined));
+    }
+    (updates, removed)
+}
+
+/// 就地细化的结果
+pub(crate) struct InPlaceRefine {
+    /// 细化开始时的结果数
+    pub total_items: usize,
+    /// 细化（或回滚）之后的结果数
+    pub final_count: usize,
+    /// 遍历了整个结果集；为 false 时细化被取消，结果集已回滚到细化之前
+    pub completed: bool,
+}
+
+/// 按段就地细化存储中的模糊结果
+///
+/// 每段在 `lock` 内复制出来，锁外读取当前值、比较并把旧值记入 `RefineJournal`，再回到锁内只写回存活项的新值；
+/// 删除的项记下索引。遍历完成后一次性压缩，日志成为撤销槽；取消或出错时不压缩，已写回的段按日志改回旧值，
+/// 结果集与细化之前相同，之前的撤销槽保留。
+///
+/// `lock` 持有结果管理器的写锁调用传入的闭包；`chunk_size` 是每段的结果数，通常是 `FUZZY_REFINE_CHUNK`；
+/// `read` 与 `fuzzy_refine_search_with` 相同。
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn refine_in_place_with<L, R, P, F>(
+    lock: L,
+    condition: FuzzyCondition,
+    chunk_size: usize,
+    cache_pages: usize,
+    read: R,
+    processed_counter: &Arc<AtomicUsize>,
+    found_counter: &AtomicUsize,
+    update_progress: &P,
+    check_cancelled: &F,
+) -> Result<InPlaceRefine>
+where
+    L: Fn(&mut dyn FnMut(&mut SearchResultManager) -> Result<()>) -> Result<()>,
+    R: Fn(u64, &mut [u8]) -> bool + Sync,
+    P: Fn(usize, usize) + Sync,
+    F: Fn() -> bool + Sync,
+{
+    let mut start = None;
+    lock(&mut |result_mgr| {
+        start = Some((result_mgr.begin_refine_journal()?, result_mgr.total_count(), result_mgr.revision(), result_mgr.fuzzy_pattern_len().unwrap_or(0)));
+        Ok(())
+    })?;
+    let Some((mut journal, total_items, mut revision, pattern_len)) = start else {
+        return Err(anyhow!("Result manager lock did not run"));
+    };
+
+    let mut removed = Vec::new();
+    let mut covered_until = 0u64;
+    let mut refine_chunks = |journal: &mut RefineJournal| -> Result<()> {
+        while journal.recorded() < total_items && !check_cancelled() {
+            let offset = journal.recorded();
+            let mut items = Vec::new();
+            lock(&mut |result_mgr| {
+                items = result_mgr.get_fuzzy_results(offset, chunk_size)?;
+                Ok(())
+            })?;
+            if items.is_empty() {
+                return Err(anyhow!("Fuzzy results ended at {} of {}", offset, total_items));
+            }
+
+            let matched = fuzzy_refine_search_with(
+                &items,
+                condition,
+                pattern_len,
+                cache_pages,
+                &read,
+                Some(processed_counter),
+                None,
+                update_progress,
+                Some(check_cancelled),
+            );
+            // 被取消的段只比较了一部分，不写回
+            if check_cancelled() {
+                break;
+            }
+            let (updates, chunk_removed) = diff_refined_chunk(offset, &items, matched, &mut covered_until);
+            found_counter.fetch_add(updates.len(), Ordering::Relaxed);
+            // 写回之前记下旧值，取消时回滚、完成后撤销都从日志读取
+            journal.record(&items)?;
+
+            lock(&mut |result_mgr| {
+                if result_mgr.revision() != revision {
+                    return Err(anyhow!("Fuzzy results were modified during refine"));
+                }
+                result_mgr.update_fuzzy_results(&updates)?;
+                revision = result_mgr.revision();
+                Ok(())
+            })?;
+            removed.extend(chunk_removed);
+        }
+        Ok(())
+    };
+    let outcome = refine_chunks(&mut journal);
+    let completed = outcome.is_ok() && journal.recorded() == total_items;
+    let mut journal = Some(journal);
+
+    let mut final_count = total_items;
+    lock(&mut |result_mgr| {
+        if result_mgr.revision() != revision {
+            return Err(anyhow!("Fuzzy results were modified during refine"));
+        }
+        let Some(journal) = journal.take() else {
+            return Ok(());
+        };
+        if completed {
+            if let Err(e) = result_mgr.commit_refine_journal(journal) {
+                warn!("Failed to keep fuzzy results for undo, this refine cannot be undone: {:?}", e);
+            }
+            let compact_start = std::time::Instant::now();
+            let removed = std::mem::take(&mut removed);
+            let removed_count = removed.len();
+            result_mgr.remove_results_batch(removed)?;
+            info!("[PERF] fuzzy_refine: compacted {} removed items in {:?}", removed_count, compact_start.elapsed());
+        } else {
+            result_mgr.rollback_refine_journal(journal)?;
+        }
+        final_count = result_mgr.total_count();
+        Ok(())
+    })?;
+    outcome?;
+
+    Ok(InPlaceRefine { total_items, final_count, completed })
+}
+
+/// 当前结果集与历史代按地址连接的结果
+pub(crate) struct GenerationJoin {
+    /// 同时存在于两者中的项，值替换为历史代中的值，作为比较基线
+    pub compared: Vec<FuzzySearchResultItem>,
+    /// 历史代中不存在的项（保留当前存储的值）
+    pub missing: Vec<FuzzySearchResultItem>,
+}
+
+impl GenerationJoin {
+    /// 拆成参与比较的项和不比较、只刷新当前值的项；`keep_missing` 为 false 时历史代中不存在的项被丢弃
+    pub(crate) fn split(self, keep_missing: bool) -> (Vec<FuzzySearchResultItem>, Vec<FuzzySearchResultItem>) {
+        let carried = if keep_missing { self.missing } else { Vec::new() };
+        (self.compared, carried)
+    }
+}
+
+/// 对照基线细化：`compared` 按条件与自身保存的值比较，`carried` 不比较、只刷新当前值，
+/// 仍可读的保留；结果按地址排序。`read` 与 `fuzzy_refine_search_with` 相同。
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn refine_against_baseline_with<R, P, F>(
+    compared: &[FuzzySearchResultItem],
+    carried: &[FuzzySearchResultItem],
+    condition: FuzzyCondition,
+    pattern_len: usize,
+    cache_pages: usize,
+    read: R,
+    processed_counter: Option<&Arc<AtomicUsize>>,
+    total_found_counter: Option<&Arc<AtomicUsize>>,
+    update_progress: &P,
+    check_cancelled: &F,
+) -> Vec<FuzzySearchResultItem>
+where
+    R: Fn(u64, &mut [u8]) -> bool + Sync,
+    P: Fn(usize, usize) + Sync,
+    F: Fn() -> bool + Sync,
+{
+    let mut refined = fuzzy_refine_search_with(
+        compared,
+        condition,
+        pattern_len,
+        cache_pages,
+        &read,
+        processed_counter,
+        total_found_counter,
+        update_progress,
+        Some(check_cancelled),
+    );
+
+    if !carried.is_empty() && !check_cancelled() {
+        // Initial 条件对所有可读地址成立
+        let kept = fuzzy_refine_search_with(
+            carried,
+            FuzzyCondition::Initial,
+            pattern_len,
+            cache_pages,
+            &read,
+            processed_counter,
+            None,
+            update_progress,
+            Some(check_cancelled),
+        );
+        refined.extend(kept);
+        refined.par_sort_unstable();
+    }
+    refined
+}
+
+/// 将当前结果集与指定历史代按地址连接
+///
+/// 只有两者都存在的地址参与条件比较；历史代中不存在的地址放入 `missing`，
+/// 由调用方决定保留还是丢弃。
+pub(crate) fn join_with_generation(current: &[FuzzySearchResultItem], generation: Vec<FuzzySearchResultItem>) -> GenerationJoin {
+    let mut baseline = generation;
+    baseline.sort_unstable();
+
+    let mut compared = Vec::with_capacity(current.len().min(baseline.len()));
+    let mut missing = Vec::new();
+
+    for item in current {
+        // packed 字段先拷贝再使用
+        let address = item.address;
+        let value_type = item.value_type;
+        match baseline.binary_search_by(|probe| {
+            let probe_addr = probe.address;
+            probe_addr.cmp(&address)
+        }) {
+            Ok(pos) => {
+                let old = baseline[pos];
+                let old_type = old.value_type;
+                // 类型不一致（例如中间重新扫描过）时不能比较；同一次 Auto 扫描的项按当前剩下的解释比较
+                if old_type == value_type || (old.is_auto() && item.is_auto()) {
+                    compared.push(
+                        FuzzySearchResultItem::new(address, old.value, value_type)
+                            .with_pass(item.pass)
+                            .with_auto_types(item.auto_types),
+                    );
+                } else {
+                    missing.push(*item);
+                }
+            },
+            Err(_) => missing.push(*item),
+        }
+    }
+
+    GenerationJoin { compared, missing }
+}
+
+/// 等待下一次采样时每次最多睡眠的时长，保证取消能及时生效
+const STABLE_WAIT_SLICE: Duration = Duration::from_millis(50);
+
+/// 每检查多少项检查一次取消
+const STABLE_CANCEL_CHECK_INTERVAL: usize = 4096;
+
+/// "持续不变" 观察窗口
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct StableWindow {
+    /// 观察总时长
+    pub duration: Duration,
+    /// 采样间隔
+    pub interval: Duration,
+}
+
+/// "持续不变" 观察的结果
+pub(crate) struct StableOutcome {
+    /// 到停止时为止一直保持窗口起点值的项
+    pub survivors: Vec<FuzzySearchResultItem>,
+    /// 实际完成的采样次数（含窗口起点）
+    pub samples: u32,
+    pub cancelled: bool,
+}
+
+/// 在 `window.duration` 内保持不变的项
+///
+/// 窗口起点读取一次作为基准值，之后每隔 `window.interval` 采样一次（窗口终点额外采样一次），
+/// 值与基准不同或读取失败的项立即剔除。窗口结束或结果集为空时停止；
+/// 取消时返回过滤到当前为止的结果集，正在进行的那次采样中尚未检查的项保留；
+/// 基准值还没读完就取消时 `samples` 为 0，调用方保持原结果集不变。
+///
+/// `items` 只在窗口起点遍历一次，可以边读边交给这里；之后整个窗口内只保留仍然不变的项。
+///
+/// 两次采样之间短暂变化又恢复的值无法发现，采样间隔决定了精度。
+///
+/// # 参数
+/// * `read` - 读取地址当前值，失败返回 false
+/// * `elapsed` - 自窗口起点经过的时间
+/// * `sleep` - 睡眠指定时长
+/// * `update_progress` - 每次采样后回调 (已观察时长, 剩余项数)
+pub(crate) fn refine_stable_with<I, R, E, S, C, P>(
+    items: I,
+    window: StableWindow,
+    mut read: R,
+    elapsed: E,
+    mut sleep: S,
+    check_cancelled: C,
+    mut update_progress: P,
+) -> StableOutcome
+where
+    I: IntoIterator<Item = FuzzySearchResultItem>,
+    R: FnMut(u64, &mut [u8]) -> bool,
+    E: Fn() -> Duration,
+    S: FnMut(Duration),
+    C: Fn() -> bool,
+    P: FnMut(Duration, usize),
+{
+    let StableWindow { duration, interval } = window;
+    let mut buffer = [0u8; 8];
+    let mut cancelled = false;
+
+    // 窗口起点：读取基准值
+    let mut survivors = Vec::new();
+    let mut total = 0usize;
+    for item in items {
+        if total.is_multiple_of(STABLE_CANCEL_CHECK_INTERVAL) && check_cancelled() {
+            cancelled = true;
+            break;
+        }
+        let size = item.value_size();
+        if read(item.address, &mut buffer[..size]) {
+            survivors.push(item.with_new_value(&buffer[..size]));
+        }
+        total += 1;
+    }
+    if cancelled {
+        // 基准值都没有读完，由调用方保持原结果集不变
+        return StableOutcome {
+            survivors: Vec::new(),
+            samples: 0,
+            cancelled,
+        };
+    }
+
+    let mut samples = 1;
+    update_progress(elapsed(), survivors.len());
+
+    let mut next_sample = interval.min(duration);
+    while !survivors.is_empty() {
+        // 分片等待，期间响应取消
+        loop {
+            let now = elapsed();
+            if now >= next_sample {
+                break;
+            }
+            if check_cancelled() {
+                cancelled = true;
+                break;
+            }
+            sleep((next_sample - now).min(STABLE_WAIT_SLICE));
+        }
+        if cancelled {
+            break;
+        }
+
+        let mut checked = 0usize;
+        survivors.retain(|item| {
+            if cancelled {
+                return true;
+            }
+            checked += 1;
+            if checked.is_multiple_of(STABLE_CANCEL_CHECK_INTERVAL) && check_cancelled() {
+                cancelled = true;
+                return true;
+            }
+            let size = item.value_size();
+            read(item.address, &mut buffer[..size]) && buffer[..size] == item.value[..size]
+        });
+        if cancelled {
+            break;
+        }
+
+        samples += 1;
+        update_progress(elapsed(), survivors.len());
+
+        if next_sample >= duration {
+            break;
+        }
+        next_sample = (next_sample + interval).min(duration);
+    }
+
+    debug!("Stable refine: {} -> {} items after {} samples (cancelled={})", total, survivors.len(), samples, cancelled);
+
+    StableOutcome {
+        survivors,
+        samples,
+        cancelled,
+    }
 }
@@ -0,0 +1,357 @@
+//! Group match constraints shared by scan and refine
+//!
+//! 组搜索的 range 有两种解释（见 `SpanMode`）。首次扫描（普通/深度）和改善搜索都在这里
+//! 收集锚点窗口内的候选地址，再用同一个回溯判断组合是否成立，这样同一份没有变化的内存
+//! 在扫描和改善之间不会出现结果忽增忽减。
+//!
+//! 所有距离都按元素起始地址计算，边界包含在内：
+//! - FromAnchor：每个元素满足 |addr - anchor| <= range
+//! - GroupSpan：max(addr) - min(addr) <= range
+//!
+//! Ordered 模式额外要求元素按查询顺序递增且互不重叠；Unordered 只要求地址互不相同。
+//!
+//! 查询带固定偏移（`100;200@+0x10;3.5@+0x14`）时锚点是第一个值，其余每个值只在
+//! `anchor + offset ± tolerance` 内找候选，mode / range / span_mode 都不参与，只要求地址互不相同。
+//! 偏移都没有误差时每个值只有一个候选，扫描和改善都直接探测偏移处（`probe_*_offsets`），不再回溯。
+
+use super::super::types::{SearchMode, SearchQuery, SpanMode};
+use crate::search::PAGE_SIZE;
+use crate::wuwa::PageStatusBitmap;
+
+/// 回溯每迭代多少次检查一次取消
+const DFS_CANCEL_CHECK_INTERVAL: u64 = 500;
+
+/// 元素起始地址离锚点的最大距离：range，或者固定偏移中最远的一个
+#[inline]
+fn window_radius(query: &SearchQuery) -> u64 {
+    if query.has_offsets() {
+        query.offsets.iter().flatten().map(|offset| offset.reach()).max().unwrap_or(0)
+    } else {
+        query.range as u64
+    }
+}
+
+/// 锚点周围元素起始地址的闭区间，两种 SpanMode 下以及固定偏移的合法组合都落在其中
+#[inline]
+pub(crate) fn anchor_window(query: &SearchQuery, anchor_addr: u64) -> (u64, u64) {
+    let radius = window_radius(query);
+    (anchor_addr.saturating_sub(radius), anchor_addr.saturating_add(radius))
+}
+
+/// 第 idx 个值的候选起始地址闭区间：有固定偏移时是 `anchor + offset ± tolerance`，否则是整个锚点窗口
+#[inline]
+fn value_window(query: &SearchQuery, idx: usize, anchor_addr: u64) -> (u64, u64) {
+    match query.value_offset(idx) {
+        Some(offset) => {
+            let target = anchor_addr as i128 + offset.offset as i128;
+            let tolerance = offset.tolerance as i128;
+            let clamp = |addr: i128| addr.clamp(0, u64::MAX as i128) as u64;
+            (clamp(target - tolerance), clamp(target + tolerance))
+        },
+        None => anchor_window(query, anchor_addr),
+    }
+}
+
+/// 锚点两侧完整窗口覆盖的字节数，分块扫描的重叠区至少要这么大，
+/// 否则块边界附近的锚点只能看到半个窗口
+pub(crate) fn window_len(query: &SearchQuery) -> usize {
+    let max_size = query.values.iter().map(|v| v.value_type().size()).max().unwrap_or(1);
+    2 * window_radius(query) as usize + max_size
+}
+
+/// 锚点之后候选元素最远能延伸到的字节数（不含锚点本身），锚点加上它超过块末尾时
+/// 窗口右侧在本块里不完整
+pub(crate) fn window_right_reach(query: &SearchQuery) -> u64 {
+    let max_size = query.values.iter().map(|v| v.value_type().size()).max().unwrap_or(1);
+    window_radius(query) + max_size as u64
+}
+
+/// 元素覆盖的页是否都读取成功，`buffer_page_start` 为 page_status 第 0 页的起始地址
+#[inline]
+fn element_readable(page_status: &PageStatusBitmap, buffer_page_start: u64, addr: u64, size: usize) -> bool {
+    let first_page = ((addr - buffer_page_start) as usize) / *PAGE_SIZE;
+    let last_page = ((addr + size.max(1) as u64 - 1 - buffer_page_start) as usize) / *PAGE_SIZE;
+    (first_page..=last_page).all(|page| page_status.is_page_success(page))
+}
+
+/// 在缓冲区中收集锚点窗口内每个值的候选地址（升序），锚点值（`query.anchor_index()`）只有锚点本身。
+/// 元素必须按 `query.alignment_for` 对齐（默认是自身大小）、完整位于 `region` 和缓冲区内、覆盖的页都读取成功。
+/// 某个值没有任何候选时返回 false
+pub(crate) fn collect_buffer_candidates(
+    buffer: &[u8],
+    buffer_addr: u64,
+    (region_start, region_end): (u64, u64),
+    query: &SearchQuery,
+    page_status: &PageStatusBitmap,
+    anchor_addr: u64,
+    candidates: &mut Vec<Vec<u64>>,
+) -> bool {
+    let anchor_idx = query.anchor_index();
+    let buffer_end = buffer_addr + buffer.len() as u64;
+    let limit_end = buffer_end.min(region_end);
+    let buffer_page_start = buffer_addr & !(*PAGE_SIZE as u64 - 1);
+
+    candidates.resize_with(query.values.len(), Vec::new);
+    for (idx, value) in query.values.iter().enumerate() {
+        let list = &mut candidates[idx];
+        list.clear();
+
+        if idx == anchor_idx {
+            list.push(anchor_addr);
+            continue;
+        }
+
+        let size = value.value_type().size().max(1);
+        let align = query.alignment_for(value.value_type()) as u64;
+        let (window_start, window_end) = value_window(query, idx, anchor_addr);
+        let lo = window_start.max(region_start).max(buffer_addr);
+        let mut addr = lo.div_ceil(align) * align;
+
+        while addr <= window_end && addr + size as u64 <= limit_end {
+            let offset = (addr - buffer_addr) as usize;
+            if element_readable(page_status, buffer_page_start, addr, size) && value.matched(&buffer[offset..offset + size]).unwrap_or(false) {
+                list.push(addr);
+            }
+            addr += align;
+        }
+
+        if list.is_empty() {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// 固定偏移都没有误差时（`query.has_exact_offsets()`）每个值只有 `anchor + offset` 一个候选，
+/// 直接探测它，不收集候选也不回溯。规则与 `collect_buffer_candidates` 加回溯相同：对齐、完整位于
+/// `region` 和缓冲区内、覆盖的页都读取成功、地址互不相同。成立时 `addrs` 为按查询值顺序的地址
+pub(crate) fn probe_buffer_offsets(
+    buffer: &[u8],
+    buffer_addr: u64,
+    (region_start, region_end): (u64, u64),
+    query: &SearchQuery,
+    page_status: &PageStatusBitmap,
+    anchor_addr: u64,
+    addrs: &mut Vec<u64>,
+) -> bool {
+    let anchor_idx = query.anchor_index();
+    let buffer_end = buffer_addr + buffer.len() as u64;
+    let limit_start = buffer_addr.max(region_start);
+    let limit_end = buffer_end.min(region_end);
+    let buffer_page_start = buffer_addr & !(*PAGE_SIZE as u64 - 1);
+
+    addrs.clear();
+    for (idx, value) in query.values.iter().enumerate() {
+        if idx == anchor_idx {
+            addrs.push(anchor_addr);
+            continue;
+        }
+
+        let Some(addr) = query.value_offset(idx).and_then(|offset| anchor_addr.checked_add_signed(offset.offset)) else {
+            return false;
+        };
+        let size = value.value_type().size().max(1);
+        let align = query.alignment_for(value.value_type()) as u64;
+        // 偏移处的值越过区域末尾时整组不成立
+        if !addr.is_multiple_of(align) || addr < limit_start || addr + size as u64 > limit_end || addrs.contains(&addr) {
+            return false;
+        }
+        let offset = (addr - buffer_addr) as usize;
+        if !element_readable(page_status, buffer_page_start, addr, size) || !value.matched(&buffer[offset..offset + size]).unwrap_or(false) {
+            return false;
+        }
+        addrs.push(addr);
+    }
+
+    true
+}
+
+/// 改善搜索中的 `probe_buffer_offsets`：在已读取的结果值（按地址升序）中按地址查找每个偏移处的值
+pub(crate) fn probe_result_offsets(addr_values: &[(u64, Vec<u8>)], query: &SearchQuery, anchor_addr: u64, addrs: &mut Vec<u64>) -> bool {
+    let anchor_idx = query.anchor_index();
+
+    addrs.clear();
+    for (idx, value) in query.values.iter().enumerate() {
+        if idx == anchor_idx {
+            addrs.push(anchor_addr);
+            continue;
+        }
+
+        let Some(addr) = query.value_offset(idx).and_then(|offset| anchor_addr.checked_add_signed(offset.offset)) else {
+            return false;
+        };
+        let Ok(pos) = addr_values.binary_search_by_key(&addr, |(addr, _)| *addr) else {
+            return false;
+        };
+        let bytes = &addr_values[pos].1;
+        let size = value.value_type().size();
+        if addrs.contains(&addr) || size > bytes.len() || !value.matched(&bytes[..size]).unwrap_or(false) {
+            return false;
+        }
+        addrs.push(addr);
+    }
+
+    true
+}
+
+/// 在已读取的结果值（按地址升序）中收集锚点窗口内每个值的候选地址，规则与
+/// `collect_buffer_candidates` 相同，只是候选来自已有结果而不是连续内存
+pub(crate) fn collect_result_candidates(addr_values: &[(u64, Vec<u8>)], query: &SearchQuery, anchor_addr: u64, candidates: &mut Vec<Vec<u64>>) -> bool {
+    let anchor_idx = query.anchor_index();
+    let (window_start, window_end) = anchor_window(query, anchor_addr);
+    let from = addr_values.partition_point(|(addr, _)| *addr < window_start);
+    let to = addr_values.partition_point(|(addr, _)| *addr <= window_end);
+    let window = &addr_values[from..to];
+
+    candidates.resize_with(query.values.len(), Vec::new);
+    for (idx, value) in query.values.iter().enumerate() {
+        let list = &mut candidates[idx];
+        list.clear();
+
+        if idx == anchor_idx {
+            list.push(anchor_addr);
+            continue;
+        }
+
+        let size = value.value_type().size();
+        let (value_start, value_end) = value_window(query, idx, anchor_addr);
+        list.extend(
+            window
+                .iter()
+                .filter(|(addr, _)| (value_start..=value_end).contains(addr))
+                .filter(|(_, bytes)| size <= bytes.len() && value.matched(&bytes[..size]).unwrap_or(false))
+                .map(|(addr, _)| *addr),
+        );
+
+        if list.is_empty() {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// 回溯候选地址，找出满足 mode 和 span_mode 的组合，每找到一组（按查询值顺序的地址）
+/// 调用一次 `on_match`，`on_match` 返回 false 时停止。
+/// 返回 false 表示回溯过程中观察到了取消
+pub(crate) fn find_combinations<F, M>(query: &SearchQuery, candidates: &[Vec<u64>], check_cancelled: &F, on_match: &mut M) -> bool
+where
+    F: Fn() -> bool,
+    M: FnMut(&[u64]) -> bool,
+{
+    !find_combinations_bounded(query, candidates, u64::MAX, check_cancelled, on_match).cancelled
+}
+
+/// 一次回溯的结果
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DfsOutcome {
+    /// 展开的节点数（尝试过的候选地址）
+    pub expansions: u64,
+    /// 展开数达到上限，剩余的组合没有枚举
+    pub truncated: bool,
+    pub cancelled: bool,
+}
+
+/// 与 `find_combinations` 相同，展开 `max_expansions` 个节点后停止。
+/// 密集的数据里同一个锚点的组合数随值的个数指数增长，上限保证单个锚点的回溯有界
+pub(crate) fn find_combinations_bounded<F, M>(query: &SearchQuery, candidates: &[Vec<u64>], max_expansions: u64, check_cancelled: &F, on_match: &mut M) -> DfsOutcome
+where
+    F: Fn() -> bool,
+    M: FnMut(&[u64]) -> bool,
+{
+    let mut search = CombinationSearch {
+        query,
+        candidates,
+        check_cancelled,
+        on_match,
+        fixed_offsets: query.has_offsets(),
+        chosen: Vec::with_capacity(query.values.len()),
+        iterations: 0,
+        max_iterations: max_expansions,
+        stopped: false,
+        truncated: false,
+        cancelled: false,
+    };
+    search.dfs(0, 0, 0);
+    DfsOutcome {
+        expansions: search.iterations,
+        truncated: search.truncated,
+        cancelled: search.cancelled,
+    }
+}
+
+struct CombinationSearch<'a, F, M> {
+    query: &'a SearchQuery,
+    candidates: &'a [Vec<u64>],
+    check_cancelled: &'a F,
+    on_match: &'a mut M,
+    /// 固定偏移时候选已经按偏移筛过，不再检查 mode 和 span_mode
+    fixed_offsets: bool,
+    chosen: Vec<u64>,
+    iterations: u64,
+    max_iterations: u64,
+    stopped: bool,
+    truncated: bool,
+    cancelled: bool,
+}
+
+impl<F, M> CombinationSearch<'_, F, M>
+where
+    F: Fn() -> bool,
+    M: FnMut(&[u64]) -> bool,
+{
+    /// `lo` / `hi` 为已选元素的最小/最大地址
+    fn dfs(&mut self, idx: usize, lo: u64, hi: u64) {
+        if idx == self.query.values.len() {
+            if !(self.on_match)(&self.chosen) {
+                self.stopped = true;
+            }
+            return;
+        }
+
+        let list = &self.candidates[idx];
+        let start = if !self.fixed_offsets && self.query.mode == SearchMode::Ordered && idx > 0 {
+            let prev_end = self.chosen[idx - 1] + self.query.values[idx - 1].value_type().size() as u64;
+            list.partition_point(|&addr| addr < prev_end)
+        } else {
+            0
+        };
+        let range = self.query.range as u64;
+
+        for &addr in &list[start..] {
+            if self.iterations >= self.max_iterations {
+                self.truncated = true;
+                self.stopped = true;
+                return;
+            }
+            self.iterations += 1;
+            if self.iterations.is_multiple_of(DFS_CANCEL_CHECK_INTERVAL) && (self.check_cancelled)() {
+                self.cancelled = true;
+                self.stopped = true;
+                return;
+            }
+
+            let (next_lo, next_hi) = if idx == 0 { (addr, addr) } else { (lo.min(addr), hi.max(addr)) };
+            if !self.fixed_offsets && self.query.span_mode == SpanMode::GroupSpan && next_hi - next_lo > range {
+                // 候选升序，超出上界之后的地址只会更远
+                if idx > 0 && addr > hi {
+                    break;
+                }
+                continue;
+            }
+
+            if (self.fixed_offsets || self.query.mode == SearchMode::Unordered) && self.chosen.contains(&addr) {
+                continue;
+            }
+
+            self.chosen.push(addr);
+            self.dfs(idx + 1, next_lo, next_hi);
+            self.chosen.pop();
+
+            if self.stopped {
+                return;
+            }
+        }
+    }
+}
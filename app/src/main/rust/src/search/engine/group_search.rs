@@ -1,6 +1,15 @@
-use super::super::types::{SearchMode, SearchQuery, SearchValue, ValueType};
+use super::super::types::{SearchQuery, SearchValue, ValueType};
+#[cfg(test)]
+use super::super::types::SearchMode;
+use super::cancel::CANCEL_CHECK_CANDIDATES;
+use super::group_match::{
+    anchor_window, collect_buffer_candidates, collect_result_candidates, find_combinations, find_combinations_bounded, probe_buffer_offsets, probe_result_offsets,
+    window_len, window_right_reach,
+};
 use super::manager::{ValuePair, BPLUS_TREE_ORDER};
-use crate::core::DRIVER_MANAGER;
+use super::read_stats::ReadStats;
+use super::result_stream::REFINE_BATCH_SIZE;
+use crate::core::{AccessQos, DRIVER_MANAGER};
 use crate::search::{PAGE_MASK, PAGE_SIZE};
 use crate::wuwa::PageStatusBitmap;
 use anyhow::anyhow;
@@ -8,168 +17,54 @@ use anyhow::Result;
 use bplustree::BPlusTreeSet;
 use log::{debug, log_enabled, warn, Level};
 use memchr::memmem;
-use std::collections::HashSet;
-use std::sync::atomic::{AtomicBool, AtomicI64, AtomicUsize};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize};
 use std::sync::Arc;
 
-pub(crate) fn search_region_group(query: &SearchQuery, start: u64, end: u64, per_chunk_size: usize) -> Result<Vec<ValuePair>> {
-    let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
-
-    let mut results = Vec::new();
-    let mut read_success = 0usize;
-    let mut read_failed = 0usize;
-    let mut matches_checked = 0usize;
-
-    let min_element_size = query.values.iter().map(|v| v.value_type().size()).min().unwrap_or(1);
-    let search_range = query.range as usize;
-
-    let mut current = start & *PAGE_MASK as u64;
-    let mut sliding_buffer = vec![0u8; per_chunk_size * 2]; // 双倍大小的滑动窗口缓冲区
-    let mut is_first_chunk = true; // 是否是第一个chunk
-    let mut prev_chunk_valid = false; // 前半部分是否有效（读取成功）
-
-    while current < end {
-        let chunk_end = (current + per_chunk_size as u64).min(end);
-        let chunk_len = (chunk_end - current) as usize;
-
-        let mut page_status = PageStatusBitmap::new(chunk_len, current as usize);
-
-        // 读取数据到滑动窗口的后半部分
-        let read_result = driver_manager.read_memory_unified(current, &mut sliding_buffer[per_chunk_size..per_chunk_size + chunk_len], Some(&mut page_status));
-
-        match read_result {
-            Ok(_) => {
-                let success_pages = page_status.success_count();
-                if success_pages > 0 {
-                    read_success += 1;
-
-                    if is_first_chunk {
-                        // 第一个chunk：只搜索前半部分（刚读取的数据）
-                        search_in_buffer_group(
-                            &sliding_buffer[per_chunk_size..per_chunk_size + chunk_len],
-                            current,
-                            start,
-                            chunk_end,
-                            min_element_size,
-                            query,
-                            &page_status,
-                            &mut results,
-                            &mut matches_checked,
-                        );
-                        is_first_chunk = false;
-                    } else if prev_chunk_valid {
-                        // 非第一个chunk且前一个chunk有效：搜索重叠区域（从前半部分尾部到后半部分末尾）
-                        let overlap_start_offset = per_chunk_size.saturating_sub(search_range);
-                        let overlap_start_addr = current - search_range as u64;
-                        let overlap_len = search_range + chunk_len;
-
-                        // 创建一个组合的page_status用于重叠区域搜索
-                        // 前半部分（重叠部分）假定已成功，后半部分使用实际的page_status
-                        let mut combined_status = PageStatusBitmap::new(overlap_len, overlap_start_addr as usize);
-
-                        let overlap_start_page = (overlap_start_addr as usize) / *PAGE_SIZE;
-                        let overlap_end = overlap_start_addr as usize + search_range;
-                        let overlap_end_page = (overlap_end + *PAGE_SIZE - 1) / *PAGE_SIZE;
-                        let num_overlap_pages = overlap_end_page - overlap_start_page;
-
-                        // 标记前半部分（重叠部分）为成功
-                        for i in 0..num_overlap_pages {
-                            combined_status.mark_success(i);
-                        }
-
-                        // 将后半部分的page_status映射到combined_status
-                        let page_status_base = (current as usize) & *PAGE_MASK;
-                        let combined_base = (overlap_start_addr as usize) & *PAGE_MASK;
-                        let page_offset = (page_status_base - combined_base) / *PAGE_SIZE;
-
-                        for i in 0..page_status.num_pages() {
-                            if page_status.is_page_success(i) {
-                                let combined_page_index = page_offset + i;
-                                if combined_page_index < combined_status.num_pages() {
-                                    combined_status.mark_success(combined_page_index);
-                                }
-                            }
-                        }
-
-                        search_in_buffer_group(
-                            &sliding_buffer[overlap_start_offset..per_chunk_size + chunk_len],
-                            overlap_start_addr,
-                            start,
-                            chunk_end,
-                            min_element_size,
-                            query,
-                            &combined_status,
-                            &mut results,
-                            &mut matches_checked,
-                        );
-                    } else {
-                        // 前一个chunk无效：只搜索当前chunk（后半部分）
-                        search_in_buffer_group(
-                            &sliding_buffer[per_chunk_size..per_chunk_size + chunk_len],
-                            current,
-                            start,
-                            chunk_end,
-                            min_element_size,
-                            query,
-                            &page_status,
-                            &mut results,
-                            &mut matches_checked,
-                        );
-                    }
-
-                    prev_chunk_valid = true;
-                } else {
-                    read_failed += 1;
-                    prev_chunk_valid = false;
-                }
-            },
-            Err(error) => {
-                if log_enabled!(Level::Debug) {
-                    warn!("Failed to read memory at 0x{:X} - 0x{:X}, err: {:?}", current, chunk_end, error);
-                }
-                read_failed += 1;
-                prev_chunk_valid = false;
-            },
-        }
-
-        // 滑动窗口：把后半部分移动到前半部分
-        if chunk_end < end {
-            sliding_buffer.copy_within(per_chunk_size..per_chunk_size + chunk_len, 0);
-        }
-
-        current = chunk_end;
-    }
+/// 组改善时每个锚点最多展开的回溯节点数，超过后保留已经找到的组合
+pub(crate) const MAX_DFS_EXPANSIONS_PER_ANCHOR: u64 = 1 << 20;
 
-    if log_enabled!(Level::Debug) {
-        let region_size = end - start;
-        debug!(
-            "Group search stats: size={}MB, reads={} success + {} failed, matches_checked={}, found={}",
-            region_size / 1024 / 1024,
-            read_success,
-            read_failed,
-            matches_checked,
-            results.len()
-        );
-    }
+pub(crate) fn search_region_group(query: &SearchQuery, start: u64, end: u64, per_chunk_size: usize) -> Result<Vec<ValuePair>> {
+    search_region_group_with_cancel(query, start, end, per_chunk_size, AccessQos::Bulk, &|| false, &ReadStats::new())
+}
 
-    Ok(results)
+/// Group search with cancellation support, every chunk is read with `qos`.
+/// Cancellation is checked before every chunk read and every `CANCEL_CHECK_CANDIDATES` candidates inside a chunk.
+pub(crate) fn search_region_group_with_cancel<F>(
+    query: &SearchQuery,
+    start: u64,
+    end: u64,
+    per_chunk_size: usize,
+    qos: AccessQos,
+    check_cancelled: &F,
+    read_stats: &ReadStats,
+) -> Result<Vec<ValuePair>>
+where
+    F: Fn() -> bool + Sync,
+{
+    let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
+    let read = |addr: u64, buf: &mut [u8], page_status: &mut PageStatusBitmap| {
+        driver_manager.read_target_with_qos(query.target_pid, addr, buf, Some(page_status), qos)
+    };
+    Ok(scan_region_group_chunks(query, start, end, per_chunk_size, false, read, check_cancelled, read_stats))
 }
 
 /// Deep group search for a memory region - finds ALL possible combinations
 /// This is the deep search version of search_region_group
 pub(crate) fn search_region_group_deep(query: &SearchQuery, start: u64, end: u64, per_chunk_size: usize) -> Result<Vec<ValuePair>> {
     // Use a no-op cancel check for backward compatibility.
-    search_region_group_deep_with_cancel(query, start, end, per_chunk_size, &|| false)
+    search_region_group_deep_with_cancel(query, start, end, per_chunk_size, AccessQos::Bulk, &|| false, &ReadStats::new())
 }
 
-/// Deep group search with cancellation support.
+/// Deep group search with cancellation support, every chunk is read with `qos`.
 /// The `check_cancelled` closure is called periodically to check if the search should be cancelled.
 pub(crate) fn search_region_group_deep_with_cancel<F>(
     query: &SearchQuery,
     start: u64,
     end: u64,
     per_chunk_size: usize,
+    qos: AccessQos,
     check_cancelled: &F,
+    read_stats: &ReadStats,
 ) -> Result<Vec<ValuePair>>
 where
     F: Fn() -> bool,
@@ -180,135 +75,139 @@ where
     }
 
     let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
+    let read = |addr: u64, buf: &mut [u8], page_status: &mut PageStatusBitmap| {
+        driver_manager.read_target_with_qos(query.target_pid, addr, buf, Some(page_status), qos)
+    };
+    Ok(scan_region_group_chunks(query, start, end, per_chunk_size, true, read, check_cancelled, read_stats))
+}
+
+/// 普通和深度组搜索共用的分块读取。每块前面保留上一块末尾至少 `window_len` 字节（按页取整），
+/// 每个锚点只在能看到完整窗口的那一块里扫描一次：窗口右侧超出当前块的锚点留给下一块，
+/// 下一块读取失败时用保留的数据补扫。返回的地址是绝对地址，按地址升序且不重复。
+///
+/// `per_chunk_size` 必须是页大小的整数倍，`read` 按驱动读取的语义填充缓冲区和页状态
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn scan_region_group_chunks<R, F>(
+    query: &SearchQuery,
+    start: u64,
+    end: u64,
+    per_chunk_size: usize,
+    deep: bool,
+    mut read: R,
+    check_cancelled: &F,
+    read_stats: &ReadStats,
+) -> Vec<ValuePair>
+where
+    R: FnMut(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+    F: Fn() -> bool,
+{
+    debug_assert!(per_chunk_size.is_multiple_of(*PAGE_SIZE));
 
     let mut results = Vec::new();
     let mut read_success = 0usize;
     let mut read_failed = 0usize;
     let mut matches_checked = 0usize;
 
-    let min_element_size = query.values.iter().map(|v| v.value_type().size()).min().unwrap_or(1);
-    let search_range = query.range as usize;
+    let right_reach = window_right_reach(query);
+    let overlap = window_len(query).next_multiple_of(*PAGE_SIZE);
 
+    // 缓冲区前 `held` 字节是上一块末尾保留下来的数据，`held_pages` 是这些页的读取状态
+    let mut buffer = vec![0u8; overlap + per_chunk_size];
+    let mut held = 0usize;
+    let mut held_pages: Vec<bool> = Vec::new();
+    // [anchor_from, 当前块) 之间的锚点还没有扫描过
     let mut current = start & *PAGE_MASK as u64;
-    let mut sliding_buffer = vec![0u8; per_chunk_size * 2];
-    let mut is_first_chunk = true;
-    let mut prev_chunk_valid = false;
+    let mut anchor_from = start;
+
+    let mut scan = |buffer: &[u8], buffer_addr: u64, page_status: &PageStatusBitmap, anchor_range: (u64, u64), results: &mut Vec<ValuePair>| {
+        scan_buffer_group(
+            buffer,
+            buffer_addr,
+            start,
+            end,
+            anchor_range,
+            query,
+            page_status,
+            deep,
+            &mut matches_checked,
+            check_cancelled,
+            &mut |addr, value_type| results.push(ValuePair::new(addr, value_type)),
+        );
+    };
 
     while current < end {
-        // Check cancellation at each chunk.
         if check_cancelled() {
-            return Ok(results);
+            break;
         }
 
         let chunk_end = (current + per_chunk_size as u64).min(end);
         let chunk_len = (chunk_end - current) as usize;
 
         let mut page_status = PageStatusBitmap::new(chunk_len, current as usize);
+        let read_result = read(current, &mut buffer[held..held + chunk_len], &mut page_status);
+        read_stats.record_chunk(current, chunk_len, read_result.is_ok(), &page_status, *PAGE_SIZE);
 
-        let read_result = driver_manager.read_memory_unified(current, &mut sliding_buffer[per_chunk_size..per_chunk_size + chunk_len], Some(&mut page_status));
-
-        match read_result {
-            Ok(_) => {
-                let success_pages = page_status.success_count();
-                if success_pages > 0 {
-                    read_success += 1;
-
-                    if is_first_chunk {
-                        search_in_buffer_group_deep_with_cancel(
-                            &sliding_buffer[per_chunk_size..per_chunk_size + chunk_len],
-                            current,
-                            start,
-                            chunk_end,
-                            min_element_size,
-                            query,
-                            &page_status,
-                            &mut results,
-                            &mut matches_checked,
-                            check_cancelled,
-                        );
-                        is_first_chunk = false;
-                    } else if prev_chunk_valid {
-                        let overlap_start_offset = per_chunk_size.saturating_sub(search_range);
-                        let overlap_start_addr = current - search_range as u64;
-                        let overlap_len = search_range + chunk_len;
-
-                        let mut combined_status = PageStatusBitmap::new(overlap_len, overlap_start_addr as usize);
-
-                        let overlap_start_page = (overlap_start_addr as usize) / *PAGE_SIZE;
-                        let overlap_end = overlap_start_addr as usize + search_range;
-                        let overlap_end_page = (overlap_end + *PAGE_SIZE - 1) / *PAGE_SIZE;
-                        let num_overlap_pages = overlap_end_page - overlap_start_page;
-
-                        for i in 0..num_overlap_pages {
-                            combined_status.mark_success(i);
-                        }
-
-                        let page_status_base = (current as usize) & *PAGE_MASK;
-                        let combined_base = (overlap_start_addr as usize) & *PAGE_MASK;
-                        let page_offset = (page_status_base - combined_base) / *PAGE_SIZE;
-
-                        for idx in 0..page_status.num_pages() {
-                            if page_status.is_page_success(idx) {
-                                let combined_page_index = page_offset + idx;
-                                if combined_page_index < combined_status.num_pages() {
-                                    combined_status.mark_success(combined_page_index);
-                                }
-                            }
-                        }
-
-                        search_in_buffer_group_deep_with_cancel(
-                            &sliding_buffer[overlap_start_offset..per_chunk_size + chunk_len],
-                            overlap_start_addr,
-                            start,
-                            chunk_end,
-                            min_element_size,
-                            query,
-                            &combined_status,
-                            &mut results,
-                            &mut matches_checked,
-                            check_cancelled,
-                        );
-                    } else {
-                        search_in_buffer_group_deep_with_cancel(
-                            &sliding_buffer[per_chunk_size..per_chunk_size + chunk_len],
-                            current,
-                            start,
-                            chunk_end,
-                            min_element_size,
-                            query,
-                            &page_status,
-                            &mut results,
-                            &mut matches_checked,
-                            check_cancelled,
-                        );
-                    }
-
-                    prev_chunk_valid = true;
-                } else {
-                    read_failed += 1;
-                    prev_chunk_valid = false;
-                }
-            },
+        let chunk_ok = match read_result {
+            Ok(_) => page_status.success_count() > 0,
             Err(error) => {
                 if log_enabled!(Level::Debug) {
                     warn!("Failed to read memory at 0x{:X} - 0x{:X}, err: {:?}", current, chunk_end, error);
                 }
-                read_failed += 1;
-                prev_chunk_valid = false;
+                false
             },
+        };
+
+        if !chunk_ok {
+            read_failed += 1;
+            // 留给这一块的锚点只能用保留下来的数据扫描，窗口右侧本来就读不到
+            if held > 0 && anchor_from < current {
+                let held_addr = current - held as u64;
+                let held_status = page_status_from(&held_pages, held, held_addr);
+                let found_before = results.len();
+                scan(&buffer[..held], held_addr, &held_status, (anchor_from, current), &mut results);
+                read_stats.record_matches(results.len() - found_before);
+            }
+            anchor_from = chunk_end;
+            held = 0;
+            held_pages.clear();
+            current = chunk_end;
+            continue;
         }
+        read_success += 1;
+
+        let buffer_addr = current - held as u64;
+        let window = held + chunk_len;
+        let mut window_pages = held_pages.clone();
+        window_pages.extend((0..chunk_len.div_ceil(*PAGE_SIZE)).map(|idx| page_status.is_page_success(idx)));
+        let window_status = page_status_from(&window_pages, window, buffer_addr);
 
+        let anchor_to = if chunk_end >= end { end } else { chunk_end.saturating_sub(right_reach).max(anchor_from) };
+        let found_before = results.len();
+        scan(&buffer[..window], buffer_addr, &window_status, (anchor_from, anchor_to), &mut results);
+        read_stats.record_matches(results.len() - found_before);
+        anchor_from = anchor_to;
+
+        // 把窗口末尾的整页移到缓冲区开头，下一块从这里接着看
         if chunk_end < end {
-            sliding_buffer.copy_within(per_chunk_size..per_chunk_size + chunk_len, 0);
+            let keep = window.min(overlap);
+            buffer.copy_within(window - keep..window, 0);
+            held_pages.clear();
+            held_pages.extend_from_slice(&window_pages[(window - keep) / *PAGE_SIZE..window / *PAGE_SIZE]);
+            held = keep;
         }
 
         current = chunk_end;
     }
 
+    // 同一个地址可能属于多个锚点的组，按地址去重
+    results.sort_unstable_by_key(|pair| pair.addr);
+    results.dedup();
+
     if log_enabled!(Level::Debug) {
         let region_size = end - start;
         debug!(
-            "Deep group search stats: size={}MB, reads={} success + {} failed, matches_checked={}, found={}",
+            "{} search stats: size={}MB, reads={} success + {} failed, matches_checked={}, found={}",
+            if deep { "Deep group" } else { "Group" },
             region_size / 1024 / 1024,
             read_success,
             read_failed,
@@ -317,284 +216,207 @@ where
         );
     }
 
-    Ok(results)
+    results
 }
 
+/// 按每页的读取状态构造从 `addr` 开始、长 `len` 字节的页状态
+fn page_status_from(pages: &[bool], len: usize, addr: u64) -> PageStatusBitmap {
+    let mut status = PageStatusBitmap::new(len, addr as usize);
+    for (idx, _) in pages.iter().enumerate().filter(|(_, ok)| **ok) {
+        status.mark_success(idx);
+    }
+    status
+}
+
+#[allow(clippy::too_many_arguments)]
 #[inline]
-pub(crate) fn search_in_buffer_group(
+pub(crate) fn search_in_buffer_group<F>(
     buffer: &[u8],
     buffer_addr: u64,
     region_start: u64,
     region_end: u64,
-    min_element_size: usize,
+    _min_element_size: usize,
     query: &SearchQuery,
     page_status: &PageStatusBitmap,
     results: &mut Vec<ValuePair>,
     matches_checked: &mut usize,
-) {
-    // anchor-first 优化：尝试使用第一个 Fixed 值作为 anchor 进行 SIMD 扫描
-    let mut anchor_index = None;
-    let mut anchor_bytes_storage = [0u8; 8]; // 最大 8 字节（Qword/Double）
-    let mut anchor_bytes_len = 0;
-
-    for (idx, value) in query.values.iter().enumerate() {
-        match value {
-            SearchValue::FixedInt { value, value_type } => {
-                let size = value_type.size();
-                anchor_bytes_storage[..size].copy_from_slice(&value[..size]);
-                anchor_bytes_len = size;
-                anchor_index = Some(idx);
-                break;
-            },
-            SearchValue::FixedFloat { value, value_type } => {
-                match value_type {
-                    ValueType::Float => {
-                        let f32_val = *value as f32;
-                        let bytes = f32_val.to_le_bytes();
-                        anchor_bytes_storage[..4].copy_from_slice(&bytes);
-                        anchor_bytes_len = 4;
-                    },
-                    ValueType::Double => {
-                        let bytes = value.to_le_bytes();
-                        anchor_bytes_storage[..8].copy_from_slice(&bytes);
-                        anchor_bytes_len = 8;
-                    },
-                    _ => continue,
-                }
-                anchor_index = Some(idx);
-                break;
-            },
-            _ => continue,
-        }
-    }
-
-    // 如果没有找到 Fixed 值作为 anchor，回退到传统逐地址扫描
-    if anchor_index.is_none() {
-        search_in_buffer_group_fallback(
-            buffer,
-            buffer_addr,
-            region_start,
-            region_end,
-            min_element_size,
-            query,
-            page_status,
-            results,
-            matches_checked,
-        );
-        return;
-    }
-
-    // 使用 anchor-first SIMD 优化
-    let anchor_bytes = &anchor_bytes_storage[..anchor_bytes_len];
-    let finder = memmem::Finder::new(anchor_bytes);
-    let mut candidates = Vec::new();
-    let mut pos = 0;
-
-    let buffer_end = buffer_addr + buffer.len() as u64;
-    let search_start = buffer_addr.max(region_start);
-    let search_end = buffer_end.min(region_end);
-
-    // 计算第一个对齐地址
-    let rem = search_start % min_element_size as u64;
-    let first_addr = if rem == 0 {
-        search_start
-    } else {
-        search_start + min_element_size as u64 - rem
-    };
+    check_cancelled: &F,
+) where
+    F: Fn() -> bool + Sync,
+{
+    // 普通模式每个锚点只取第一组匹配
+    scan_buffer_group(
+        buffer,
+        buffer_addr,
+        region_start,
+        region_end,
+        (region_start, region_end),
+        query,
+        page_status,
+        false,
+        matches_checked,
+        check_cancelled,
+        &mut |addr, value_type| results.push((addr, value_type).into()),
+    );
+}
 
-    // 预先构建成功页的地址范围
-    let page_ranges = page_status.get_success_page_ranges();
-    if page_ranges.is_empty() {
-        return;
+/// 锚点值的精确字节表示，可以用 SIMD 扫描；范围值等无法转成固定字节时返回 None
+fn anchor_bytes(value: &SearchValue) -> Option<Vec<u8>> {
+    match value {
+        SearchValue::FixedInt { value, value_type } => Some(value[..value_type.size()].to_vec()),
+        SearchValue::FixedFloat { value, value_type } => match value_type {
+            ValueType::Float => Some((*value as f32).to_le_bytes().to_vec()),
+            ValueType::Double => Some(value.to_le_bytes().to_vec()),
+            _ => None,
+        },
+        _ => None,
     }
+}
 
+/// 找出缓冲区中 [search_start, search_end) 内所有按 `align` 对齐、所在页可读的锚点值地址。
+/// 锚点是固定值时用 memmem 做 SIMD 扫描，否则逐个对齐位置比较
+fn find_anchor_addrs(
+    buffer: &[u8],
+    buffer_addr: u64,
+    search_start: u64,
+    search_end: u64,
+    anchor: &SearchValue,
+    align: usize,
+    page_status: &PageStatusBitmap,
+) -> Vec<u64> {
+    let size = anchor.value_type().size().max(1);
+    let align = align as u64;
     let buffer_page_start = buffer_addr & !(*PAGE_SIZE as u64 - 1);
-    let anchor_alignment = anchor_bytes_len;
-
-    // SIMD 快速扫描找到所有 anchor 候选位置
-    while pos < buffer.len() {
-        if let Some(offset) = finder.find(&buffer[pos..]) {
-            let absolute_offset = pos + offset;
-            let addr = buffer_addr + absolute_offset as u64;
-
-            // 过滤1: 检查对齐（使用 anchor 的大小）
-            if addr % anchor_alignment as u64 == 0 && addr >= first_addr && addr < search_end {
-                candidates.push(absolute_offset);
-            }
+    let mut anchors = Vec::new();
 
-            pos = absolute_offset + 1;
-        } else {
-            break;
-        }
-    }
-
-    // 对候选位置做页面过滤和完整校验
-    let anchor_idx = anchor_index.unwrap();
-
-    for &offset in &candidates {
-        let anchor_addr = buffer_addr + offset as u64;
-
-        // 根据搜索模式计算需要验证的区域
-        let (start_addr, _start_offset) = if query.mode == SearchMode::Ordered {
-            // Ordered 模式：根据 anchor 在 query 中的位置，反推序列起始位置
-            let anchor_offset_in_sequence = query.values[..anchor_idx].iter().map(|v| v.value_type().size()).sum::<usize>();
-
-            let seq_start_addr = anchor_addr.saturating_sub(anchor_offset_in_sequence as u64);
-            let seq_start_offset = offset.saturating_sub(anchor_offset_in_sequence);
-
-            (seq_start_addr, seq_start_offset)
-        } else {
-            // Unordered 模式：从 anchor_addr - range 开始
-            let range_start = anchor_addr.saturating_sub(query.range as u64);
-            let range_start_offset = if range_start < buffer_addr { 0 } else { (range_start - buffer_addr) as usize };
-            (range_start, range_start_offset)
-        };
-
-        // 检查地址是否在有效范围内
-        let check_range_addr = if query.mode == SearchMode::Ordered { start_addr } else { anchor_addr };
-        if check_range_addr < region_start || check_range_addr >= region_end {
-            continue;
-        }
-
-        // 检查地址是否在有效页范围内
-        let check_addr = if query.mode == SearchMode::Ordered { start_addr } else { anchor_addr };
-        let mut in_valid_page = false;
-        for (start_page, end_page) in &page_ranges {
-            let page_range_start = buffer_page_start + (start_page * *PAGE_SIZE) as u64;
-            let page_range_end = buffer_page_start + (end_page * *PAGE_SIZE) as u64;
-
-            if check_addr >= page_range_start && check_addr < page_range_end {
-                in_valid_page = true;
-                break;
-            }
-        }
-
-        if !in_valid_page {
+    // 只在成功读取的页范围内查找
+    for (start_page, end_page) in page_status.get_success_page_ranges() {
+        let range_start = (buffer_page_start + (start_page * *PAGE_SIZE) as u64).max(search_start);
+        let range_end = (buffer_page_start + (end_page * *PAGE_SIZE) as u64).min(search_end);
+        if range_start >= range_end {
             continue;
         }
 
-        // 完整校验
-        let total_values_size: usize = query.values.iter().map(|v| v.value_type().size()).sum();
-        let min_buffer_size = (total_values_size as u64).max(query.range as u64);
+        // 锚点必须完整落在连续的成功页范围内
+        let start_offset = (range_start - buffer_addr) as usize;
+        let end_offset = (range_end - buffer_addr) as usize;
 
-        let (check_start, check_end) = if query.mode == SearchMode::Ordered {
-            // Ordered 模式：序列必须完整在 buffer 内才能验证
-            if start_addr < buffer_addr {
-                continue;
+        if let Some(bytes) = anchor_bytes(anchor) {
+            let finder = memmem::Finder::new(&bytes);
+            for offset in finder.find_iter(&buffer[start_offset..end_offset]) {
+                let addr = range_start + offset as u64;
+                if addr.is_multiple_of(align) {
+                    anchors.push(addr);
+                }
             }
-            (start_addr, (start_addr + min_buffer_size).min(buffer_end).min(region_end))
         } else {
-            // Unordered 模式：需要覆盖 anchor 前后的范围
-            let unordered_start = anchor_addr.saturating_sub(query.range as u64).max(buffer_addr);
-            let unordered_end = (anchor_addr + query.range as u64).min(buffer_end).min(region_end);
-            (unordered_start, unordered_end)
-        };
-
-        let check_start_offset = (check_start - buffer_addr) as usize;
-        let range_size = (check_end - check_start) as usize;
-
-        if check_start_offset + range_size <= buffer.len() {
-            *matches_checked += 1;
-
-            if let Some(offsets) = try_match_group_at_address(&buffer[check_start_offset..check_start_offset + range_size], check_start, query) {
-                for (idx, value_offset) in offsets.iter().enumerate() {
-                    let value_addr = check_start + *value_offset as u64;
-                    let value_type = query.values[idx].value_type();
-                    results.push((value_addr, value_type).into());
+            let mut addr = range_start.div_ceil(align) * align;
+            while addr + size as u64 <= range_end {
+                let offset = (addr - buffer_addr) as usize;
+                if anchor.matched(&buffer[offset..offset + size]).unwrap_or(false) {
+                    anchors.push(addr);
                 }
+                addr += align;
             }
         }
     }
+
+    anchors
 }
 
-/// 传统逐地址扫描方法（用于没有 Fixed 值作为 anchor 时的降级）
-#[inline]
-pub(crate) fn search_in_buffer_group_fallback(
+/// 普通和深度组搜索共用的缓冲区扫描：枚举锚点，收集锚点窗口内的候选，回溯出满足
+/// range 语义（见 group_match）的组合。`deep` 为 false 时每个锚点只取第一组，
+/// 为 true 时取所有组合。每个锚点的结果整组写入，取消时不会留下残缺的组。
+/// 只有落在 `anchor_range` 内的锚点会被扫描，候选仍可以落在整个区域内
+#[allow(clippy::too_many_arguments)]
+fn scan_buffer_group<F, E>(
     buffer: &[u8],
     buffer_addr: u64,
     region_start: u64,
     region_end: u64,
-    min_element_size: usize,
+    (anchor_start, anchor_end): (u64, u64),
     query: &SearchQuery,
     page_status: &PageStatusBitmap,
-    results: &mut Vec<ValuePair>,
+    deep: bool,
     matches_checked: &mut usize,
-) {
+    check_cancelled: &F,
+    emit: &mut E,
+) where
+    F: Fn() -> bool,
+    E: FnMut(u64, ValueType),
+{
+    if query.values.is_empty() {
+        return;
+    }
+
     let buffer_end = buffer_addr + buffer.len() as u64;
-    let search_start = buffer_addr.max(region_start);
+    let search_start = buffer_addr.max(region_start).max(anchor_start);
     let search_end = buffer_end.min(region_end);
-    let search_range = query.range as u64;
-
-    let rem = search_start % min_element_size as u64;
-    let first_addr = if rem == 0 {
-        search_start
-    } else {
-        search_start + min_element_size as u64 - rem
-    };
-
-    // 优化：预先构建成功页的地址范围
-    let page_ranges = page_status.get_success_page_ranges();
-    if page_ranges.is_empty() {
+    if search_start >= search_end.min(anchor_end) {
         return;
     }
 
-    // buffer_addr 所在页的起始地址（页对齐）
-    let buffer_page_start = buffer_addr & !(*PAGE_SIZE as u64 - 1);
-
-    for (start_page, end_page) in page_ranges {
-        // 将相对页索引转换为绝对地址范围
-        let page_range_start = buffer_page_start + (start_page * *PAGE_SIZE) as u64;
-        let page_range_end = buffer_page_start + (end_page * *PAGE_SIZE) as u64;
+    let anchor_idx = query.anchor_index();
+    let anchor = &query.values[anchor_idx];
+    let anchors = find_anchor_addrs(buffer, buffer_addr, search_start, search_end, anchor, query.alignment_for(anchor.value_type()), page_status);
+    // 锚点本身可以跨过 anchor_end，只要求起始地址在范围内
+    let anchors = &anchors[..anchors.partition_point(|&addr| addr < anchor_end)];
 
-        // 限制在 buffer 和搜索范围内
-        let range_start = page_range_start.max(buffer_addr);
-        let range_end = page_range_end.min(search_end).min(buffer_end);
+    // 没有误差的固定偏移每个锚点最多一组，直接探测偏移处
+    let exact_offsets = query.has_exact_offsets();
+    let mut candidates = Vec::with_capacity(query.values.len());
+    let mut probed = Vec::with_capacity(query.values.len());
+    let mut matched: Vec<(u64, ValueType)> = Vec::new();
 
-        if range_start >= range_end {
-            continue;
+    for (candidate_idx, &anchor_addr) in anchors.iter().enumerate() {
+        if candidate_idx.is_multiple_of(CANCEL_CHECK_CANDIDATES) && check_cancelled() {
+            return;
         }
 
-        // 找到这个范围内第一个 >= first_addr 且对齐的地址
-        let mut addr = if range_start <= first_addr {
-            first_addr // first_addr 已经对齐
+        *matches_checked += 1;
+        matched.clear();
+        if exact_offsets {
+            if !probe_buffer_offsets(buffer, buffer_addr, (region_start, region_end), query, page_status, anchor_addr, &mut probed) {
+                continue;
+            }
+            matched.extend(probed.iter().zip(&query.values).map(|(addr, value)| (*addr, value.value_type())));
         } else {
-            // range_start > first_addr，需要对齐
-            let rem = range_start % min_element_size as u64;
-            if rem == 0 { range_start } else { range_start + min_element_size as u64 - rem }
-        };
+            if !collect_buffer_candidates(buffer, buffer_addr, (region_start, region_end), query, page_status, anchor_addr, &mut candidates) {
+                continue;
+            }
 
-        // 在这个有效页范围内搜索
-        while addr < range_end {
-            let offset = (addr - buffer_addr) as usize;
-            if offset < buffer.len() {
-                let range_end_check = (addr + search_range).min(buffer_end).min(search_end);
-                let range_size = (range_end_check - addr) as usize;
-
-                if range_size >= query.range as usize && offset + range_size <= buffer.len() {
-                    *matches_checked += 1;
-
-                    if let Some(offsets) = try_match_group_at_address(&buffer[offset..offset + range_size], addr, query) {
-                        // 保存所有匹配值的地址
-                        for (idx, value_offset) in offsets.iter().enumerate() {
-                            let value_addr = addr + *value_offset as u64;
-                            let value_type = query.values[idx].value_type();
-                            results.push((value_addr, value_type).into());
-                        }
-                    }
-                }
+            let completed = find_combinations(query, &candidates, check_cancelled, &mut |addrs| {
+                matched.extend(addrs.iter().zip(&query.values).map(|(addr, value)| (*addr, value.value_type())));
+                deep
+            });
+            if !completed {
+                return;
             }
-            addr += min_element_size as u64;
+        }
+
+        if deep {
+            matched.sort_unstable_by_key(|(addr, _)| *addr);
+            matched.dedup_by_key(|(addr, _)| *addr);
+        }
+        for (addr, value_type) in matched.drain(..) {
+            emit(addr, value_type);
         }
     }
 }
 
+/// 贪心的首个匹配，只用于测试中与旧的扫描方式对照
+#[cfg(test)]
 pub(crate) fn try_match_group_at_address(buffer: &[u8], start_addr: u64, query: &SearchQuery) -> Option<Vec<usize>> {
+    if query.has_offsets() {
+        return try_match_fixed_offsets(buffer, start_addr, query);
+    }
     match query.mode {
         SearchMode::Ordered => try_match_ordered(buffer, start_addr, query),
         SearchMode::Unordered => try_match_unordered(buffer, start_addr, query),
     }
 }
 
+#[cfg(test)]
 pub(crate) fn try_match_ordered(buffer: &[u8], _start_addr: u64, query: &SearchQuery) -> Option<Vec<usize>> {
     let mut offsets = Vec::with_capacity(query.values.len());
     let mut current_offset = 0usize;
@@ -625,6 +447,41 @@ pub(crate) fn try_match_ordered(buffer: &[u8], _start_addr: u64, query: &SearchQ
     Some(offsets)
 }
 
+/// 固定偏移：锚点在缓冲区开头，其余值只在 `offset ± tolerance` 内按自身大小对齐查找。
+/// 偏移为负的值落在缓冲区之前，这里无法匹配
+#[cfg(test)]
+pub(crate) fn try_match_fixed_offsets(buffer: &[u8], start_addr: u64, query: &SearchQuery) -> Option<Vec<usize>> {
+    let mut offsets = Vec::with_capacity(query.values.len());
+
+    for (idx, target_value) in query.values.iter().enumerate() {
+        let value_size = target_value.value_type().size().max(1);
+        let (lo, hi) = match query.value_offset(idx) {
+            Some(offset) => (offset.offset - offset.tolerance as i64, offset.offset + offset.tolerance as i64),
+            None => (0, 0),
+        };
+
+        let mut relative = lo.max(0);
+        let misalignment = (start_addr + relative as u64) % value_size as u64;
+        if misalignment != 0 {
+            relative += (value_size as u64 - misalignment) as i64;
+        }
+
+        let mut found = None;
+        while relative <= hi && relative as usize + value_size <= buffer.len() {
+            let offset = relative as usize;
+            if !offsets.contains(&offset) && target_value.matched(&buffer[offset..offset + value_size]).unwrap_or(false) {
+                found = Some(offset);
+                break;
+            }
+            relative += value_size as i64;
+        }
+        offsets.push(found?);
+    }
+
+    Some(offsets)
+}
+
+#[cfg(test)]
 pub(crate) fn try_match_unordered(buffer: &[u8], _start_addr: u64, query: &SearchQuery) -> Option<Vec<usize>> {
     let mut offsets = vec![None; query.values.len()];
     let mut found_count = 0;
@@ -662,7 +519,7 @@ pub(crate) fn try_match_unordered(buffer: &[u8], _start_addr: u64, query: &Searc
 
 /// Deep group search - finds ALL possible combinations when there are duplicate values
 ///
-/// Unlike standard group search which uses a first-match greedy strategy,
+/// Unlike standard group search which takes the first match for every anchor,
 /// deep search exhaustively finds all valid combinations using DFS backtracking.
 ///
 /// # Use Cases
@@ -679,36 +536,27 @@ pub(crate) fn search_in_buffer_group_deep(
     buffer_addr: u64,
     region_start: u64,
     region_end: u64,
-    min_element_size: usize,
+    _min_element_size: usize,
     query: &SearchQuery,
     page_status: &PageStatusBitmap,
     results: &mut BPlusTreeSet<ValuePair>,
     matches_checked: &mut usize,
 ) {
-    match query.mode {
-        SearchMode::Ordered => search_ordered_deep(
-            buffer,
-            buffer_addr,
-            region_start,
-            region_end,
-            min_element_size,
-            query,
-            page_status,
-            results,
-            matches_checked,
-        ),
-        SearchMode::Unordered => search_unordered_deep(
-            buffer,
-            buffer_addr,
-            region_start,
-            region_end,
-            min_element_size,
-            query,
-            page_status,
-            results,
-            matches_checked,
-        ),
-    }
+    scan_buffer_group(
+        buffer,
+        buffer_addr,
+        region_start,
+        region_end,
+        (region_start, region_end),
+        query,
+        page_status,
+        true,
+        matches_checked,
+        &|| false,
+        &mut |addr, value_type| {
+            results.insert(ValuePair::new(addr, value_type));
+        },
+    );
 }
 
 /// Deep group search with cancellation support.
@@ -717,7 +565,7 @@ pub(crate) fn search_in_buffer_group_deep_with_cancel<F>(
     buffer_addr: u64,
     region_start: u64,
     region_end: u64,
-    min_element_size: usize,
+    _min_element_size: usize,
     query: &SearchQuery,
     page_status: &PageStatusBitmap,
     results: &mut Vec<ValuePair>,
@@ -726,1165 +574,325 @@ pub(crate) fn search_in_buffer_group_deep_with_cancel<F>(
 ) where
     F: Fn() -> bool,
 {
-    match query.mode {
-        SearchMode::Ordered => search_ordered_deep_with_cancel(
-            buffer,
-            buffer_addr,
-            region_start,
-            region_end,
-            min_element_size,
-            query,
-            page_status,
-            results,
-            matches_checked,
-            check_cancelled,
-        ),
-        SearchMode::Unordered => search_unordered_deep_with_cancel(
-            buffer,
-            buffer_addr,
-            region_start,
-            region_end,
-            min_element_size,
-            query,
-            page_status,
-            results,
-            matches_checked,
-            check_cancelled,
-        ),
-    }
+    scan_buffer_group(
+        buffer,
+        buffer_addr,
+        region_start,
+        region_end,
+        (region_start, region_end),
+        query,
+        page_status,
+        true,
+        matches_checked,
+        check_cancelled,
+        &mut |addr, value_type| results.push(ValuePair::new(addr, value_type)),
+    );
 }
 
-/// Deep search for ordered mode using DFS backtracking
-fn search_ordered_deep(
-    buffer: &[u8],
-    buffer_addr: u64,
-    region_start: u64,
-    region_end: u64,
-    min_element_size: usize,
+// ==================== Refine Search (Result Improvement) ====================
+
+/// Group refine search with DFS algorithm, with cancel and progress callbacks.
+/// Results must arrive in address order; see `refine_group_stream_with` for how much of them is kept in memory.
+///
+/// Cancellation is checked after every read batch, before every anchor, and inside the DFS recursion
+/// every `DFS_CANCEL_CHECK_INTERVAL` node expansions, so one anchor with a huge combination count
+/// still stops promptly.
+pub(crate) fn refine_search_group_with_dfs_and_cancel<I, F, P>(
+    existing_results: I,
     query: &SearchQuery,
-    page_status: &PageStatusBitmap,
-    results: &mut BPlusTreeSet<ValuePair>,
-    matches_checked: &mut usize,
-) {
-    use std::collections::HashSet;
+    processed_counter: Option<&Arc<AtomicUsize>>,
+    total_found_counter: Option<&Arc<AtomicUsize>>,
+    check_cancelled: &F,
+    update_progress: &P,
+) -> Result<BPlusTreeSet<ValuePair>>
+where
+    I: IntoIterator<Item = ValuePair>,
+    F: Fn() -> bool + Sync,
+    P: Fn(usize, usize) + Sync,
+{
+    // Check cancellation before starting.
+    if check_cancelled() {
+        return Ok(BPlusTreeSet::new(BPLUS_TREE_ORDER));
+    }
 
-    let buffer_end = buffer_addr + buffer.len() as u64;
-    let search_start = buffer_addr.max(region_start);
-    let search_end = buffer_end.min(region_end);
+    let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
 
-    // Calculate first aligned address
-    let rem = search_start % min_element_size as u64;
-    let first_addr = if rem == 0 {
-        search_start
-    } else {
-        search_start + min_element_size as u64 - rem
-    };
-
-    // Get successful page ranges
-    let page_ranges = page_status.get_success_page_ranges();
-    if page_ranges.is_empty() {
-        return;
-    }
-
-    let buffer_page_start = buffer_addr & *PAGE_MASK as u64;
-    let search_range = query.range as u64;
-
-    // Iterate through each aligned address as potential starting point
-    for (start_page, end_page) in page_ranges {
-        let page_range_start = buffer_page_start + (start_page * *PAGE_SIZE) as u64;
-        let page_range_end = buffer_page_start + (end_page * *PAGE_SIZE) as u64;
-
-        let range_start = page_range_start.max(buffer_addr);
-        let range_end = page_range_end.min(search_end).min(buffer_end);
-
-        if range_start >= range_end {
-            continue;
-        }
-
-        let mut addr = if range_start <= first_addr {
-            first_addr
-        } else {
-            let rem = range_start % min_element_size as u64;
-            if rem == 0 { range_start } else { range_start + min_element_size as u64 - rem }
-        };
-
-        while addr < range_end {
-            let offset = (addr - buffer_addr) as usize;
-            if offset < buffer.len() {
-                let range_end_check = (addr + search_range).min(buffer_end).min(search_end);
-                let range_size = (range_end_check - addr) as usize;
-
-                if range_size >= query.range as usize && offset + range_size <= buffer.len() {
-                    *matches_checked += 1;
-
-                    // Use DFS to find all combinations
-                    let mut chosen = Vec::with_capacity(query.values.len());
-                    let mut used = HashSet::new();
-
-                    dfs_ordered(
-                        &buffer[offset..offset + range_size],
-                        addr,
-                        0, // Start from first query value
-                        0, // Start searching from offset 0
-                        query,
-                        &mut chosen,
-                        &mut used,
-                        results,
-                    );
-                }
-            }
-            addr += min_element_size as u64;
-        }
-    }
-}
-
-/// DFS backtracking for ordered search
-fn dfs_ordered(
-    buffer: &[u8],
-    base_addr: u64,
-    query_idx: usize,
-    search_offset: usize,
-    query: &SearchQuery,
-    chosen: &mut Vec<(u64, ValueType)>,
-    used: &mut HashSet<u64>,
-    results: &mut BPlusTreeSet<ValuePair>,
-) {
-    // Found complete match
-    if query_idx == query.values.len() {
-        for (addr, vt) in chosen.iter() {
-            results.insert(ValuePair::new(*addr, *vt));
-        }
-        return;
-    }
-
-    let target_value = &query.values[query_idx];
-    let value_size = target_value.value_type().size();
-    let alignment = value_size;
-
-    let mut offset = search_offset;
-    while offset + value_size <= buffer.len() {
-        let addr = base_addr + offset as u64;
-
-        // Check if address is already used
-        if used.contains(&addr) {
-            offset += alignment;
-            continue;
-        }
-
-        // Check if value matches
-        let element_bytes = &buffer[offset..offset + value_size];
-        if let Ok(true) = target_value.matched(element_bytes) {
-            // Choose this position
-            chosen.push((addr, target_value.value_type()));
-            used.insert(addr);
-
-            // Recurse to next query value (search from next aligned position)
-            dfs_ordered(
-                buffer,
-                base_addr,
-                query_idx + 1,
-                offset + value_size, // Continue from next position
-                query,
-                chosen,
-                used,
-                results,
-            );
-
-            // Backtrack
-            chosen.pop();
-            used.remove(&addr);
-        }
-
-        offset += alignment;
-    }
-}
-
-/// Deep search for unordered mode using DFS backtracking
-fn search_unordered_deep(
-    buffer: &[u8],
-    buffer_addr: u64,
-    region_start: u64,
-    region_end: u64,
-    min_element_size: usize,
-    query: &SearchQuery,
-    page_status: &PageStatusBitmap,
-    results: &mut BPlusTreeSet<ValuePair>,
-    matches_checked: &mut usize,
-) {
-    use std::collections::HashSet;
-
-    let buffer_end = buffer_addr + buffer.len() as u64;
-    let search_start = buffer_addr.max(region_start);
-    let search_end = buffer_end.min(region_end);
-
-    let rem = search_start % min_element_size as u64;
-    let first_addr = if rem == 0 {
-        search_start
-    } else {
-        search_start + min_element_size as u64 - rem
-    };
-
-    let page_ranges = page_status.get_success_page_ranges();
-    if page_ranges.is_empty() {
-        return;
-    }
-
-    let buffer_page_start = buffer_addr & *PAGE_MASK as u64;
-    let search_range = query.range as u64;
-
-    for (start_page, end_page) in page_ranges {
-        let page_range_start = buffer_page_start + (start_page * *PAGE_SIZE) as u64;
-        let page_range_end = buffer_page_start + (end_page * *PAGE_SIZE) as u64;
-
-        let range_start = page_range_start.max(buffer_addr);
-        let range_end = page_range_end.min(search_end).min(buffer_end);
-
-        if range_start >= range_end {
-            continue;
-        }
-
-        let mut addr = if range_start <= first_addr {
-            first_addr
-        } else {
-            let rem = range_start % min_element_size as u64;
-            if rem == 0 { range_start } else { range_start + min_element_size as u64 - rem }
-        };
-
-        while addr < range_end {
-            let offset = (addr - buffer_addr) as usize;
-            if offset < buffer.len() {
-                let unordered_start = addr.saturating_sub(search_range).max(buffer_addr);
-                let unordered_end = (addr + search_range).min(buffer_end).min(search_end);
-                let start_offset = (unordered_start - buffer_addr) as usize;
-                let range_size = (unordered_end - unordered_start) as usize;
-
-                if range_size >= query.range as usize && start_offset + range_size <= buffer.len() {
-                    *matches_checked += 1;
-
-                    let mut chosen = Vec::with_capacity(query.values.len());
-                    let mut used = HashSet::new();
-
-                    dfs_unordered(
-                        &buffer[start_offset..start_offset + range_size],
-                        unordered_start,
-                        0, // Start from first query value
-                        0, // Start searching from offset 0
-                        query,
-                        &mut chosen,
-                        &mut used,
-                        results,
-                    );
-                }
-            }
-            addr += min_element_size as u64;
-        }
-    }
-}
-
-/// DFS backtracking for unordered search
-fn dfs_unordered(
-    buffer: &[u8],
-    base_addr: u64,
-    query_idx: usize,
-    search_offset: usize,
-    query: &SearchQuery,
-    chosen: &mut Vec<(u64, ValueType)>,
-    used: &mut HashSet<u64>,
-    results: &mut BPlusTreeSet<ValuePair>,
-) {
-    // Found complete match
-    if query_idx == query.values.len() {
-        for (addr, vt) in chosen.iter() {
-            results.insert(ValuePair::new(*addr, *vt));
-        }
-        return;
-    }
-
-    let target_value = &query.values[query_idx];
-    let value_size = target_value.value_type().size();
-    let alignment = value_size;
-
-    let mut offset = search_offset;
-    while offset + value_size <= buffer.len() {
-        let addr = base_addr + offset as u64;
-
-        if used.contains(&addr) {
-            offset += alignment;
-            continue;
-        }
-
-        let element_bytes = &buffer[offset..offset + value_size];
-        if let Ok(true) = target_value.matched(element_bytes) {
-            chosen.push((addr, target_value.value_type()));
-            used.insert(addr);
-
-            // For unordered mode, continue searching from next position (not necessarily adjacent)
-            dfs_unordered(
-                buffer,
-                base_addr,
-                query_idx + 1,
-                offset + alignment, // Continue from next aligned position
-                query,
-                chosen,
-                used,
-                results,
-            );
-
-            chosen.pop();
-            used.remove(&addr);
-        }
-
-        offset += alignment;
-    }
+    Ok(refine_group_stream_with(
+        existing_results,
+        query,
+        REFINE_BATCH_SIZE,
+        |addr, buffer| driver_manager.read_target_with_qos(query.target_pid, addr, buffer, None, AccessQos::Bulk).is_ok(),
+        processed_counter,
+        total_found_counter,
+        check_cancelled,
+        update_progress,
+    ))
 }
 
-/// Deep search for ordered mode with cancellation support.
-fn search_ordered_deep_with_cancel<F>(
-    buffer: &[u8],
-    buffer_addr: u64,
-    region_start: u64,
-    region_end: u64,
-    min_element_size: usize,
+/// 按地址升序流式读取结果做组改善，结果与把全部值交给 `refine_group_values_with_cancel` 相同
+///
+/// 每次读取 `batch_size` 个结果的当前值，读到某个锚点窗口终点之后的地址时这个锚点的窗口就完整了，
+/// 完整的锚点并行回溯；窗口起点早于所有未处理锚点的值随即丢弃。内存中只保留最近一个 range 半径内的值
+/// 和一批输入，不随结果总数增长。输入中重复的地址只取第一个。取消时返回空集合
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn refine_group_stream_with<I, R, F, P>(
+    results: I,
     query: &SearchQuery,
-    page_status: &PageStatusBitmap,
-    results: &mut Vec<ValuePair>,
-    matches_checked: &mut usize,
+    batch_size: usize,
+    mut read: R,
+    processed_counter: Option<&Arc<AtomicUsize>>,
+    total_found_counter: Option<&Arc<AtomicUsize>>,
     check_cancelled: &F,
-) where
-    F: Fn() -> bool,
+    update_progress: &P,
+) -> BPlusTreeSet<ValuePair>
+where
+    I: IntoIterator<Item = ValuePair>,
+    R: FnMut(u64, &mut [u8]) -> bool,
+    F: Fn() -> bool + Sync,
+    P: Fn(usize, usize) + Sync,
 {
-    use std::collections::HashSet;
-    use std::sync::atomic::{AtomicBool, Ordering};
-
-    let buffer_end = buffer_addr + buffer.len() as u64;
-    let search_start = buffer_addr.max(region_start);
-    let search_end = buffer_end.min(region_end);
-
-    let rem = search_start % min_element_size as u64;
-    let first_addr = if rem == 0 {
-        search_start
-    } else {
-        search_start + min_element_size as u64 - rem
-    };
+    use std::collections::VecDeque;
+    use std::sync::atomic::Ordering;
 
-    let page_ranges = page_status.get_success_page_ranges();
-    if page_ranges.is_empty() {
-        return;
+    let mut refined_results = BPlusTreeSet::new(BPLUS_TREE_ORDER);
+    if query.values.is_empty() || check_cancelled() {
+        return refined_results;
     }
 
-    let buffer_page_start = buffer_addr & *PAGE_MASK as u64;
-    let search_range = query.range as u64;
-
-    // Use AtomicBool to propagate cancellation from DFS.
-    let cancelled = AtomicBool::new(false);
-
-    for (start_page, end_page) in page_ranges {
-        // Check cancellation at page range level.
-        if check_cancelled() || cancelled.load(Ordering::Relaxed) {
-            return;
-        }
-
-        let page_range_start = buffer_page_start + (start_page * *PAGE_SIZE) as u64;
-        let page_range_end = buffer_page_start + (end_page * *PAGE_SIZE) as u64;
-
-        let range_start = page_range_start.max(buffer_addr);
-        let range_end = page_range_end.min(search_end).min(buffer_end);
-
-        if range_start >= range_end {
-            continue;
+    let anchor_target = &query.values[query.anchor_index()];
+    let anchor_size = anchor_target.value_type().size();
+    let batch_size = batch_size.max(1);
+    let skip = || {
+        if let Some(counter) = processed_counter {
+            counter.fetch_add(1, Ordering::Relaxed);
         }
+    };
 
-        let mut addr = if range_start <= first_addr {
-            first_addr
-        } else {
-            let rem = range_start % min_element_size as u64;
-            if rem == 0 { range_start } else { range_start + min_element_size as u64 - rem }
-        };
-
-        let mut iteration_count = 0u64;
-        while addr < range_end {
-            // Check cancellation periodically (every 1000 iterations).
-            iteration_count += 1;
-            if iteration_count % 1000 == 0 && check_cancelled() {
-                cancelled.store(true, Ordering::Relaxed);
-                return;
+    let mut results = results.into_iter();
+    // 已读取的值（地址升序）和还没回溯的锚点
+    let mut window: VecDeque<(u64, Vec<u8>)> = VecDeque::new();
+    let mut pending: VecDeque<u64> = VecDeque::new();
+    let mut last_seen: Option<u64> = None;
+    let mut total_anchors = 0usize;
+
+    loop {
+        let mut taken = 0;
+        for pair in results.by_ref().take(batch_size) {
+            taken += 1;
+            if last_seen.is_some_and(|last| pair.addr <= last) {
+                skip();
+                continue;
             }
+            last_seen = Some(pair.addr);
 
-            let offset = (addr - buffer_addr) as usize;
-            if offset < buffer.len() {
-                let range_end_check = (addr + search_range).min(buffer_end).min(search_end);
-                let range_size = (range_end_check - addr) as usize;
-
-                if range_size >= query.range as usize && offset + range_size <= buffer.len() {
-                    *matches_checked += 1;
-
-                    let mut chosen = Vec::with_capacity(query.values.len());
-                    let mut used = HashSet::new();
-
-                    dfs_ordered_with_cancel(
-                        &buffer[offset..offset + range_size],
-                        addr,
-                        0,
-                        0,
-                        query,
-                        &mut chosen,
-                        &mut used,
-                        results,
-                        check_cancelled,
-                        &cancelled,
-                    );
-
-                    if cancelled.load(Ordering::Relaxed) {
-                        return;
-                    }
+            let mut bytes = vec![0u8; pair.value_type.size()];
+            if !read(pair.addr, &mut bytes) {
+                skip();
+                if log_enabled!(Level::Debug) {
+                    warn!("Failed to read memory during refine search, addr: {:x}, size = {}", pair.addr, bytes.len())
                 }
+                continue;
             }
-            addr += min_element_size as u64;
-        }
-    }
-}
-
-/// DFS backtracking for ordered search with cancellation support.
-fn dfs_ordered_with_cancel<F>(
-    buffer: &[u8],
-    base_addr: u64,
-    query_idx: usize,
-    search_offset: usize,
-    query: &SearchQuery,
-    chosen: &mut Vec<(u64, ValueType)>,
-    used: &mut HashSet<u64>,
-    results: &mut Vec<ValuePair>,
-    check_cancelled: &F,
-    cancelled: &AtomicBool,
-) where
-    F: Fn() -> bool,
-{
-    use std::sync::atomic::Ordering;
-
-    // Check cancellation flag.
-    if cancelled.load(Ordering::Relaxed) {
-        return;
-    }
-
-    // Found complete match.
-    if query_idx == query.values.len() {
-        for (addr, vt) in chosen.iter() {
-            results.push(ValuePair::new(*addr, *vt));
-        }
-        return;
-    }
-
-    let target_value = &query.values[query_idx];
-    let value_size = target_value.value_type().size();
-    let alignment = value_size;
-
-    let mut offset = search_offset;
-    let mut iteration_count = 0u64;
-    while offset + value_size <= buffer.len() {
-        // Check cancellation periodically (every 500 iterations in DFS).
-        iteration_count += 1;
-        if iteration_count % 500 == 0 {
-            if check_cancelled() {
-                cancelled.store(true, Ordering::Relaxed);
-                return;
-            }
-        }
-
-        let addr = base_addr + offset as u64;
-
-        if used.contains(&addr) {
-            offset += alignment;
-            continue;
-        }
-
-        let element_bytes = &buffer[offset..offset + value_size];
-        if let Ok(true) = target_value.matched(element_bytes) {
-            chosen.push((addr, target_value.value_type()));
-            used.insert(addr);
-
-            dfs_ordered_with_cancel(
-                buffer,
-                base_addr,
-                query_idx + 1,
-                offset + value_size,
-                query,
-                chosen,
-                used,
-                results,
-                check_cancelled,
-                cancelled,
-            );
-
-            // Check if we should stop.
-            if cancelled.load(Ordering::Relaxed) {
-                return;
+            if anchor_size <= bytes.len() && anchor_target.matched(&bytes[..anchor_size]).unwrap_or(false) {
+                pending.push_back(pair.addr);
+            } else {
+                skip();
             }
-
-            chosen.pop();
-            used.remove(&addr);
+            window.push_back((pair.addr, bytes));
         }
+        let exhausted = taken < batch_size;
 
-        offset += alignment;
-    }
-}
-
-/// Deep search for unordered mode with cancellation support.
-fn search_unordered_deep_with_cancel<F>(
-    buffer: &[u8],
-    buffer_addr: u64,
-    region_start: u64,
-    region_end: u64,
-    min_element_size: usize,
-    query: &SearchQuery,
-    page_status: &PageStatusBitmap,
-    results: &mut Vec<ValuePair>,
-    matches_checked: &mut usize,
-    check_cancelled: &F,
-) where
-    F: Fn() -> bool,
-{
-    use std::collections::HashSet;
-    use std::sync::atomic::{AtomicBool, Ordering};
-
-    let buffer_end = buffer_addr + buffer.len() as u64;
-    let search_start = buffer_addr.max(region_start);
-    let search_end = buffer_end.min(region_end);
-
-    let rem = search_start % min_element_size as u64;
-    let first_addr = if rem == 0 {
-        search_start
-    } else {
-        search_start + min_element_size as u64 - rem
-    };
-
-    let page_ranges = page_status.get_success_page_ranges();
-    if page_ranges.is_empty() {
-        return;
-    }
-
-    let buffer_page_start = buffer_addr & *PAGE_MASK as u64;
-    let search_range = query.range as u64;
-
-    let cancelled = AtomicBool::new(false);
-
-    for (start_page, end_page) in page_ranges {
-        // Check cancellation at page range level.
-        if check_cancelled() || cancelled.load(Ordering::Relaxed) {
-            return;
-        }
-
-        let page_range_start = buffer_page_start + (start_page * *PAGE_SIZE) as u64;
-        let page_range_end = buffer_page_start + (end_page * *PAGE_SIZE) as u64;
-
-        let range_start = page_range_start.max(buffer_addr);
-        let range_end = page_range_end.min(search_end).min(buffer_end);
-
-        if range_start >= range_end {
-            continue;
+        if check_cancelled() {
+            return BPlusTreeSet::new(BPLUS_TREE_ORDER);
         }
 
-        let mut addr = if range_start <= first_addr {
-            first_addr
+        // 窗口终点不晚于已读到的最后一个地址的锚点可以回溯，之后的输入都在窗口外
+        let Some(last) = last_seen else {
+            break;
+        };
+        let ready = if exhausted {
+            pending.len()
         } else {
-            let rem = range_start % min_element_size as u64;
-            if rem == 0 { range_start } else { range_start + min_element_size as u64 - rem }
+            pending.partition_point(|&anchor| anchor_window(query, anchor).1 <= last)
         };
-
-        let mut iteration_count = 0u64;
-        while addr < range_end {
-            // Check cancellation periodically.
-            iteration_count += 1;
-            if iteration_count % 1000 == 0 && check_cancelled() {
-                cancelled.store(true, Ordering::Relaxed);
-                return;
+        if ready > 0 {
+            let anchors: Vec<u64> = pending.drain(..ready).collect();
+            total_anchors += anchors.len();
+            if !match_anchors(window.make_contiguous(), &anchors, query, processed_counter, total_found_counter, check_cancelled, update_progress, &mut refined_results) {
+                return BPlusTreeSet::new(BPLUS_TREE_ORDER);
             }
-
-            let offset = (addr - buffer_addr) as usize;
-            if offset < buffer.len() {
-                let unordered_start = addr.saturating_sub(search_range).max(buffer_addr);
-                let unordered_end = (addr + search_range).min(buffer_end).min(search_end);
-                let start_offset = (unordered_start - buffer_addr) as usize;
-                let range_size = (unordered_end - unordered_start) as usize;
-
-                if range_size >= query.range as usize && start_offset + range_size <= buffer.len() {
-                    *matches_checked += 1;
-
-                    let mut chosen = Vec::with_capacity(query.values.len());
-                    let mut used = HashSet::new();
-
-                    dfs_unordered_with_cancel(
-                        &buffer[start_offset..start_offset + range_size],
-                        unordered_start,
-                        0,
-                        0,
-                        query,
-                        &mut chosen,
-                        &mut used,
-                        results,
-                        check_cancelled,
-                        &cancelled,
-                    );
-
-                    if cancelled.load(Ordering::Relaxed) {
-                        return;
-                    }
-                }
+            let found = refined_results.len();
+            if let Some(counter) = total_found_counter {
+                counter.store(found, Ordering::Relaxed);
             }
-            addr += min_element_size as u64;
+            update_progress(processed_counter.map(|c| c.load(Ordering::Relaxed)).unwrap_or(total_anchors), found);
         }
-    }
-}
 
-/// DFS backtracking for unordered search with cancellation support.
-fn dfs_unordered_with_cancel<F>(
-    buffer: &[u8],
-    base_addr: u64,
-    query_idx: usize,
-    search_offset: usize,
-    query: &SearchQuery,
-    chosen: &mut Vec<(u64, ValueType)>,
-    used: &mut HashSet<u64>,
-    results: &mut Vec<ValuePair>,
-    check_cancelled: &F,
-    cancelled: &AtomicBool,
-) where
-    F: Fn() -> bool,
-{
-    use std::sync::atomic::Ordering;
-
-    if cancelled.load(Ordering::Relaxed) {
-        return;
-    }
-
-    if query_idx == query.values.len() {
-        for (addr, vt) in chosen.iter() {
-            results.push(ValuePair::new(*addr, *vt));
-        }
-        return;
-    }
-
-    let target_value = &query.values[query_idx];
-    let value_size = target_value.value_type().size();
-    let alignment = value_size;
-
-    let mut offset = search_offset;
-    let mut iteration_count = 0u64;
-    while offset + value_size <= buffer.len() {
-        iteration_count += 1;
-        if iteration_count % 500 == 0 {
-            if check_cancelled() {
-                cancelled.store(true, Ordering::Relaxed);
-                return;
-            }
-        }
-
-        let addr = base_addr + offset as u64;
-
-        if used.contains(&addr) {
-            offset += alignment;
-            continue;
+        if exhausted {
+            break;
         }
-
-        let element_bytes = &buffer[offset..offset + value_size];
-        if let Ok(true) = target_value.matched(element_bytes) {
-            chosen.push((addr, target_value.value_type()));
-            used.insert(addr);
-
-            dfs_unordered_with_cancel(
-                buffer,
-                base_addr,
-                query_idx + 1,
-                offset + alignment,
-                query,
-                chosen,
-                used,
-                results,
-                check_cancelled,
-                cancelled,
-            );
-
-            if cancelled.load(Ordering::Relaxed) {
-                return;
-            }
-
-            chosen.pop();
-            used.remove(&addr);
+        let keep_from = anchor_window(query, pending.front().copied().unwrap_or(last)).0;
+        while window.front().is_some_and(|(addr, _)| *addr < keep_from) {
+            window.pop_front();
         }
+    }
 
-        offset += alignment;
+    if log_enabled!(Level::Debug) {
+        debug!("Group refine stream: {} anchors -> {} results", total_anchors, refined_results.len());
     }
+    refined_results
 }
 
-// ==================== Refine Search (Result Improvement) ====================
-
-/// 使用 DFS 算法对已有搜索结果进行组搜索改善
-///
-/// 在已有的搜索结果中，找到所有满足组搜索条件的地址组合
-/// 使用深度优先搜索 (DFS) 回溯算法，可以找到所有可能的有效组合
-///
-/// # 参数
-/// - `existing_results`: 已有的搜索结果集合 (B+树，已按地址排序)
-/// - `query`: 组搜索查询条件
-/// - `processed_counter`: 可选的已处理地址计数器（用于进度追踪）
-///
-/// # 返回值
-/// 返回满足条件的所有地址的集合
-#[deprecated]
-pub(crate) fn refine_search_group_with_dfs(
-    existing_results: &Vec<ValuePair>,
+/// 对已读取的结果值做组改善，与首次扫描使用相同的锚点和 range 语义（见 group_match），
+/// 内存没有变化时改善结果与输入完全一致。改善搜索使用流式的 `refine_group_stream_with`，这里只用于测试中对照
+#[cfg(test)]
+pub(crate) fn refine_group_values_with_cancel<F, P>(
+    mut addr_values: Vec<(u64, Vec<u8>)>,
     query: &SearchQuery,
     processed_counter: Option<&Arc<AtomicUsize>>,
     total_found_counter: Option<&Arc<AtomicUsize>>,
-) -> Result<BPlusTreeSet<ValuePair>> {
+    check_cancelled: &F,
+    update_progress: &P,
+) -> BPlusTreeSet<ValuePair>
+where
+    F: Fn() -> bool + Sync,
+    P: Fn(usize, usize) + Sync,
+{
     use rayon::prelude::*;
-    use std::collections::HashSet;
     use std::sync::atomic::Ordering;
 
-    if log_enabled!(Level::Debug) {
-        debug!("当前结果数量: {}", existing_results.len())
-    }
-
-    let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
-
     let mut refined_results = BPlusTreeSet::new(BPLUS_TREE_ORDER);
 
-    if query.values.is_empty() {
-        return Ok(refined_results);
-    }
-
-    // 读取全部地址与当前值
-    let mut addr_values: Vec<(u64, Vec<u8>)> = Vec::with_capacity(existing_results.len());
-    for pair in existing_results.iter() {
-        let addr = pair.addr;
-        let value_size = pair.value_type.size();
-        let mut buffer = vec![0u8; value_size];
-
-        if driver_manager.read_memory_unified(addr, &mut buffer, None).is_ok() {
-            addr_values.push((addr, buffer));
-        } else {
-            // 读取失败也要更新计数器
-            if let Some(counter) = processed_counter {
-                counter.fetch_add(1, Ordering::Relaxed);
-            }
-            if log_enabled!(Level::Debug) {
-                warn!("读取内存失败在改善搜索的时候, addr: {:x}, size = {}", addr, value_size)
-            }
-        }
+    // Check cancellation.
+    if query.values.is_empty() || check_cancelled() {
+        return refined_results;
     }
 
-    if addr_values.is_empty() {
-        if log_enabled!(Level::Debug) {
-            debug!("当前结果数量: {}, 但是都读取失败了", existing_results.len())
-        }
-        return Ok(refined_results);
-    }
+    // 候选窗口按地址二分查找
+    addr_values.sort_unstable_by_key(|(addr, _)| *addr);
+    addr_values.dedup_by_key(|(addr, _)| *addr);
 
-    if log_enabled!(Level::Debug) {
-        debug!("结果数量: {}, 可读地址数: {}", existing_results.len(), addr_values.len());
-    }
-
-    // 找所有锚点
-    let first_query_target = &query.values[0];
+    // Find all anchor points.
+    let anchor_idx = query.anchor_index();
+    let anchor_target = &query.values[anchor_idx];
+    let anchor_size = anchor_target.value_type().size();
     let anchors: Vec<u64> = addr_values
         .par_iter()
         .filter_map(|(addr, bytes)| {
-            if let Ok(true) = first_query_target.matched(&bytes) {
-                Some(*addr) // 是锚点，不更新计数器
+            if anchor_size <= bytes.len() && anchor_target.matched(&bytes[..anchor_size]).unwrap_or(false) {
+                Some(*addr)
             } else {
-                // 更新已处理计数器 (非锚点更新)
                 if let Some(counter) = &processed_counter {
                     counter.fetch_add(1, Ordering::Relaxed);
                 }
-
                 None
             }
         })
         .collect();
 
     if log_enabled!(Level::Debug) {
-        debug!("锚点数量: {}, 可读地址数: {}", anchors.len(), addr_values.len());
+        debug!("Anchor count: {}, readable addresses: {}", anchors.len(), addr_values.len());
     }
 
     if anchors.is_empty() {
-        return Ok(refined_results);
+        return refined_results;
     }
 
-    if query.values.len() == 1 {
-        // 单值改善, 直接返回锚点结果
-        let value_type = query.values[0].value_type();
-        for anchor_addr in anchors {
-            refined_results.insert(ValuePair::new(anchor_addr, value_type));
-        }
-        return Ok(refined_results);
+    if !match_anchors(&addr_values, &anchors, query, processed_counter, total_found_counter, check_cancelled, update_progress, &mut refined_results) {
+        return BPlusTreeSet::new(BPLUS_TREE_ORDER);
     }
 
-    // 主循环：每个锚点执行 DFS
-    for anchor_addr in anchors {
-        let (min_addr, max_addr) = match query.mode {
-            SearchMode::Unordered => (anchor_addr.saturating_sub(query.range as u64), anchor_addr + query.range as u64),
-            SearchMode::Ordered => (anchor_addr, anchor_addr + query.range as u64),
-        };
-
-        // 候选（不含锚点本身，避免重复使用）
-        let mut candidates: Vec<(u64, &Vec<u8>)> = Vec::new();
-        for (addr, bytes) in &addr_values {
-            if *addr >= min_addr && *addr <= max_addr && *addr != anchor_addr {
-                candidates.push((*addr, bytes));
-            }
-        }
-
-        // 剪枝：如果候选数量 < (query.values.len() - 1) 不可能成功
-        if candidates.len() < query.values.len() - 1 {
-            if let Some(counter) = &processed_counter {
-                counter.fetch_add(1, Ordering::Relaxed); // 锚点被放弃，更新计数器
-            }
-            continue;
-        }
-
-        // DFS：寻找所有满足的组合
-        let mut used: HashSet<u64> = HashSet::new();
-        used.insert(anchor_addr);
-
-        // 当前选择的地址（含锚点）
-        let mut chosen: Vec<(u64, ValueType)> = Vec::with_capacity(query.values.len());
-        chosen.push((anchor_addr, query.values[0].value_type()));
-
-        // 回溯函数
-        fn dfs(
-            cand_idx: usize,
-            candidates: &[(u64, &Vec<u8>)],
-            query: &SearchQuery,
-            chosen: &mut Vec<(u64, ValueType)>,
-            used: &mut HashSet<u64>,
-            refined_results: &mut BPlusTreeSet<ValuePair>,
-        ) -> Result<()> {
-            let need_total = query.values.len();
-            let have = chosen.len();
-
-            // 成功匹配全部查询值
-            if have == need_total {
-                for (addr, vt) in chosen.iter() {
-                    refined_results.insert(ValuePair::new(*addr, *vt));
-                }
-                return Ok(());
-            }
-
-            // 剩余还需要匹配的查询值数量
-            let remaining_need = need_total - have;
-
-            // 剩余候选是否足够（剪枝）
-            let remaining_candidates = candidates.len().saturating_sub(cand_idx);
-            if remaining_candidates < remaining_need {
-                return Ok(());
-            }
-
-            // 当前要匹配的查询值
-            let sv = &query.values[have];
-
-            // 遍历从 cand_idx 开始的候选
-            for i in cand_idx..candidates.len() {
-                let (addr, bytes) = candidates[i];
-
-                // 安全检查：确保缓冲区大小足够
-                if sv.value_type().size() > bytes.len() {
-                    continue;
-                }
-
-                // 如果不匹配则跳过
-                if !sv.matched(bytes).unwrap_or(false) {
-                    continue;
-                }
-
-                // 地址唯一约束
-                if used.contains(&addr) {
-                    continue;
-                }
-
-                // 选择
-                used.insert(addr);
-                chosen.push((addr, sv.value_type()));
-
-                // 下一层从 i+1 开始（保证组合不重复）
-                dfs(i + 1, candidates, query, chosen, used, refined_results)?;
-
-                // 回溯
-                chosen.pop();
-                used.remove(&addr);
-            }
-
-            Ok(())
-        }
-
-        dfs(0, &candidates, query, &mut chosen, &mut used, &mut refined_results)?;
-
-        // 更新已处理计数器
-        if let Some(counter) = &processed_counter {
-            let cur = counter.fetch_add(1, Ordering::Relaxed);
-            if log_enabled!(Level::Debug) {
-                debug!("DFS结束 已处理地址数: {}", cur);
-            }
-        }
-
-        if let Some(counter) = &total_found_counter {
-            counter.store(refined_results.len(), Ordering::Relaxed);
-        }
+    // Final progress update.
+    let final_count = refined_results.len();
+    if let Some(counter) = &total_found_counter {
+        counter.store(final_count, Ordering::Relaxed);
     }
+    update_progress(anchors.len(), final_count);
 
-    Ok(refined_results)
+    refined_results
 }
 
-/// Group refine search with DFS algorithm, with cancel and progress callbacks.
-/// This version supports cancellation checking and progress updates during the search.
-pub(crate) fn refine_search_group_with_dfs_and_cancel<F, P>(
-    existing_results: &Vec<ValuePair>,
+/// 在按地址升序的值中为每个锚点回溯组合，结果并入 `refined_results`。
+/// `addr_values` 必须包含每个锚点窗口内的全部值。返回 false 表示被取消
+///
+/// `query.first_match_only` 时每个锚点找到第一组就停止，否则枚举所有组合，但最多展开
+/// `MAX_DFS_EXPANSIONS_PER_ANCHOR` 个节点。同一锚点的多个组合中重复的地址只保留一次
+#[allow(clippy::too_many_arguments)]
+fn match_anchors<F, P>(
+    addr_values: &[(u64, Vec<u8>)],
+    anchors: &[u64],
     query: &SearchQuery,
     processed_counter: Option<&Arc<AtomicUsize>>,
     total_found_counter: Option<&Arc<AtomicUsize>>,
     check_cancelled: &F,
     update_progress: &P,
-) -> Result<BPlusTreeSet<ValuePair>>
+    refined_results: &mut BPlusTreeSet<ValuePair>,
+) -> bool
 where
     F: Fn() -> bool + Sync,
     P: Fn(usize, usize) + Sync,
 {
     use rayon::prelude::*;
-    use std::collections::HashSet;
     use std::sync::atomic::Ordering;
 
-    if log_enabled!(Level::Debug) {
-        debug!("Group refine with cancel - result count: {}", existing_results.len())
-    }
-
-    // Check cancellation before starting.
-    if check_cancelled() {
-        return Ok(BPlusTreeSet::new(BPLUS_TREE_ORDER));
-    }
-
-    let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
-
-    let mut refined_results = BPlusTreeSet::new(BPLUS_TREE_ORDER);
-
-    if query.values.is_empty() {
-        return Ok(refined_results);
-    }
-
-    // Read all address values.
-    let mut addr_values: Vec<(u64, Vec<u8>)> = Vec::with_capacity(existing_results.len());
-    for (idx, pair) in existing_results.iter().enumerate() {
-        // Check cancellation periodically.
-        if idx % 1000 == 0 && check_cancelled() {
-            return Ok(BPlusTreeSet::new(BPLUS_TREE_ORDER));
-        }
-
-        let addr = pair.addr;
-        let value_size = pair.value_type.size();
-        let mut buffer = vec![0u8; value_size];
-
-        if driver_manager.read_memory_unified(addr, &mut buffer, None).is_ok() {
-            addr_values.push((addr, buffer));
-        } else {
-            if let Some(counter) = processed_counter {
-                counter.fetch_add(1, Ordering::Relaxed);
-            }
-            if log_enabled!(Level::Debug) {
-                warn!("Failed to read memory during refine search, addr: {:x}, size = {}", addr, value_size)
-            }
-        }
-    }
-
-    if addr_values.is_empty() {
-        if log_enabled!(Level::Debug) {
-            debug!("Result count: {}, but all reads failed", existing_results.len())
-        }
-        return Ok(refined_results);
-    }
-
-    if log_enabled!(Level::Debug) {
-        debug!("Result count: {}, readable addresses: {}", existing_results.len(), addr_values.len());
-    }
-
-    // Check cancellation.
-    if check_cancelled() {
-        return Ok(BPlusTreeSet::new(BPLUS_TREE_ORDER));
-    }
-
-    // Find all anchor points.
-    let first_query_target = &query.values[0];
-    let anchors: Vec<u64> = addr_values
-        .par_iter()
-        .filter_map(|(addr, bytes)| {
-            if let Ok(true) = first_query_target.matched(&bytes) {
-                Some(*addr)
-            } else {
-                if let Some(counter) = &processed_counter {
-                    counter.fetch_add(1, Ordering::Relaxed);
-                }
-                None
-            }
-        })
-        .collect();
-
-    if log_enabled!(Level::Debug) {
-        debug!("Anchor count: {}, readable addresses: {}", anchors.len(), addr_values.len());
-    }
-
-    if anchors.is_empty() {
-        return Ok(refined_results);
-    }
-
     if query.values.len() == 1 {
         // Single value refine, return anchor results directly.
         let value_type = query.values[0].value_type();
-        for anchor_addr in anchors {
+        for &anchor_addr in anchors {
             refined_results.insert(ValuePair::new(anchor_addr, value_type));
         }
-        return Ok(refined_results);
+        if let Some(counter) = processed_counter {
+            counter.fetch_add(anchors.len(), Ordering::Relaxed);
+        }
+        return true;
     }
 
-    let total_anchors = anchors.len();
-
     // Use AtomicBool to propagate cancellation across parallel tasks.
     let cancelled = AtomicBool::new(false);
-
-    // Inner DFS function with cancellation support.
-    fn dfs_with_cancel<FC>(
-        cand_idx: usize,
-        candidates: &[(u64, &Vec<u8>)],
-        query: &SearchQuery,
-        chosen: &mut Vec<(u64, ValueType)>,
-        used: &mut HashSet<u64>,
-        local_results: &mut Vec<(u64, ValueType)>,
-        check_cancelled: &FC,
-        cancelled: &AtomicBool,
-        iteration_count: &mut u64,
-    ) where
-        FC: Fn() -> bool,
-    {
-        // Check cancellation flag.
-        if cancelled.load(Ordering::Relaxed) {
-            return;
-        }
-
-        let need_total = query.values.len();
-        let have = chosen.len();
-
-        if have == need_total {
-            for (addr, vt) in chosen.iter() {
-                local_results.push((*addr, *vt));
-            }
-            return;
-        }
-
-        let remaining_need = need_total - have;
-        let remaining_candidates = candidates.len().saturating_sub(cand_idx);
-        if remaining_candidates < remaining_need {
-            return;
-        }
-
-        let sv = &query.values[have];
-
-        for i in cand_idx..candidates.len() {
-            // Check cancellation periodically.
-            *iteration_count += 1;
-            if *iteration_count % 500 == 0 {
-                if check_cancelled() || cancelled.load(Ordering::Relaxed) {
-                    cancelled.store(true, Ordering::Relaxed);
-                    return;
-                }
-            }
-
-            let (addr, bytes) = candidates[i];
-
-            if sv.value_type().size() > bytes.len() {
-                continue;
-            }
-
-            if !sv.matched(bytes).unwrap_or(false) {
-                continue;
-            }
-
-            if used.contains(&addr) {
-                continue;
-            }
-
-            used.insert(addr);
-            chosen.push((addr, sv.value_type()));
-
-            dfs_with_cancel(
-                i + 1,
-                candidates,
-                query,
-                chosen,
-                used,
-                local_results,
-                check_cancelled,
-                cancelled,
-                iteration_count,
-            );
-
-            // Check if we should stop.
-            if cancelled.load(Ordering::Relaxed) {
-                return;
-            }
-
-            chosen.pop();
-            used.remove(&addr);
-        }
-    }
+    let check_cancelled_shared = || cancelled.load(Ordering::Relaxed) || check_cancelled();
+    let expansions = AtomicU64::new(0);
+    let truncated_anchors = AtomicUsize::new(0);
+    // 没有误差的固定偏移直接按地址查找偏移处的值，不回溯
+    let exact_offsets = query.has_exact_offsets();
 
     // Parallel processing of anchors using rayon.
     let all_results: Vec<Vec<(u64, ValueType)>> = anchors
         .par_iter()
         .filter_map(|anchor_addr| {
             // Check cancellation.
-            if check_cancelled() || cancelled.load(Ordering::Relaxed) {
+            if check_cancelled_shared() {
                 cancelled.store(true, Ordering::Relaxed);
                 return None;
             }
 
-            let (min_addr, max_addr) = match query.mode {
-                SearchMode::Unordered => (anchor_addr.saturating_sub(query.range as u64), anchor_addr + query.range as u64),
-                SearchMode::Ordered => (*anchor_addr, anchor_addr + query.range as u64),
-            };
+            let mut candidates = Vec::with_capacity(query.values.len());
+            let mut local_results: Vec<(u64, ValueType)> = Vec::new();
 
-            // Candidates (excluding anchor itself to avoid duplicate usage).
-            let mut candidates: Vec<(u64, &Vec<u8>)> = Vec::new();
-            for (addr, bytes) in &addr_values {
-                if *addr >= min_addr && *addr <= max_addr && *addr != *anchor_addr {
-                    candidates.push((*addr, bytes));
+            if exact_offsets {
+                let mut probed = Vec::with_capacity(query.values.len());
+                if probe_result_offsets(addr_values, query, *anchor_addr, &mut probed) {
+                    local_results.extend(probed.iter().zip(&query.values).map(|(addr, value)| (*addr, value.value_type())));
+                    local_results.sort_unstable_by_key(|(addr, _)| *addr);
                 }
-            }
-
-            // Pruning: if candidate count < (query.values.len() - 1), cannot succeed.
-            if candidates.len() < query.values.len() - 1 {
-                if let Some(counter) = &processed_counter {
-                    counter.fetch_add(1, Ordering::Relaxed);
+            } else if collect_result_candidates(addr_values, query, *anchor_addr, &mut candidates) {
+                // DFS: the first combination only, or every combination up to the expansion cap.
+                let outcome = find_combinations_bounded(query, &candidates, MAX_DFS_EXPANSIONS_PER_ANCHOR, &check_cancelled_shared, &mut |addrs| {
+                    local_results.extend(addrs.iter().zip(&query.values).map(|(addr, value)| (*addr, value.value_type())));
+                    !query.first_match_only
+                });
+                if outcome.cancelled {
+                    cancelled.store(true, Ordering::Relaxed);
+                    return None;
                 }
-                return None;
+                expansions.fetch_add(outcome.expansions, Ordering::Relaxed);
+                if outcome.truncated {
+                    truncated_anchors.fetch_add(1, Ordering::Relaxed);
+                }
+                // An address appears once per anchor however many combinations include it.
+                local_results.sort_unstable_by_key(|(addr, _)| *addr);
+                local_results.dedup_by_key(|(addr, _)| *addr);
             }
 
-            // DFS: find all valid combinations.
-            let mut used: HashSet<u64> = HashSet::new();
-            used.insert(*anchor_addr);
-
-            let mut chosen: Vec<(u64, ValueType)> = Vec::with_capacity(query.values.len());
-            chosen.push((*anchor_addr, query.values[0].value_type()));
-
-            let mut local_results: Vec<(u64, ValueType)> = Vec::new();
-            let mut iteration_count = 0u64;
-
-            dfs_with_cancel(
-                0,
-                &candidates,
-                query,
-                &mut chosen,
-                &mut used,
-                &mut local_results,
-                check_cancelled,
-                &cancelled,
-                &mut iteration_count,
-            );
-
             // Update processed counter and progress.
             if let Some(counter) = &processed_counter {
                 let processed = counter.fetch_add(1, Ordering::Relaxed) + 1;
@@ -1904,7 +912,20 @@ where
 
     // Check if cancelled.
     if cancelled.load(Ordering::Relaxed) {
-        return Ok(refined_results);
+        return false;
+    }
+
+    let truncated_anchors = truncated_anchors.into_inner();
+    if truncated_anchors > 0 {
+        warn!(
+            "Group refine: {} of {} anchors hit the DFS expansion cap ({}), remaining combinations skipped",
+            truncated_anchors,
+            anchors.len(),
+            MAX_DFS_EXPANSIONS_PER_ANCHOR
+        );
+    }
+    if log_enabled!(Level::Debug) {
+        debug!("Group refine DFS: {} anchors, {} node expansions", anchors.len(), expansions.into_inner());
     }
 
     // Merge all results into the final result set.
@@ -1913,13 +934,5 @@ where
             refined_results.insert(ValuePair::new(addr, vt));
         }
     }
-
-    // Final progress update.
-    let final_count = refined_results.len();
-    if let Some(counter) = &total_found_counter {
-        counter.store(final_count, Ordering::Relaxed);
-    }
-    update_progress(total_anchors, final_count);
-
-    Ok(refined_results)
+    true
 }
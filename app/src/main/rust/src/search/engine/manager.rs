@@ -1,13 +1,39 @@
-use super::super::result_manager::{FuzzySearchResultItem, SearchResultManager, SearchResultMode};
-use super::super::types::{FuzzyCondition, SearchQuery, ValueType};
+use super::super::format::ValueDisplay;
+use super::super::result_manager::{
+    AddressRangeResults, ByteHitSet, ByteHitStats, ExactSearchResultItem, ExactValueCache, FuzzySearchResultItem, PageHits, ResultCursor, ResultExportFormat, ResultGeneration, ResultStoreReport, SearchResultManager,
+    SearchResultMode,
+};
+use super::super::types::{check_alignment, FuzzyCondition, SearchQuery, SearchValue, ValueType, XorKey};
 use super::super::SearchResultItem;
-use super::filter::SearchFilter;
+use super::batch_reader::{DEFAULT_REFINE_CACHE_PAGES, MAX_REFINE_CACHE_PAGES};
+use super::byte_search::{self, ByteScanner, DEFAULT_BYTE_BITMAP_THRESHOLD};
+use super::cancel::CancelSource;
+use super::compat::{capture_fuzzy_values, CompatPolicy, CompatibilityState};
+use super::estimate::{self, ScanEstimate, ScanLimits, ThroughputStats};
+use super::exact_snapshot::{self, CachedSeeds, ExactSnapshot};
+use super::filter::{FilteredIndexCache, ResultOrder, SearchFilter};
+use super::fuzzy_resume::{self, FuzzyResume};
 use super::fuzzy_search;
 use super::group_search;
-use super::shared_buffer::{SearchErrorCode, SearchStatus, SharedBuffer};
-use super::single_search;
-use crate::core::globals::TOKIO_RUNTIME;
-use crate::core::DRIVER_MANAGER;
+use super::pattern_replace::{self, PatchOutcome, PatchStats};
+use super::pressure::{PressureLadder, PressureReport, ScanStage};
+use super::result_commit::{self, CommitOutcome, RegionBatch, ResultCommitter, COMMIT_QUEUE_DEPTH, REORDER_LIMIT, RESULT_RETAIN_LIMIT};
+use super::provenance::{PassLookup, PassOrder, PassOrderCache};
+use super::read_stats::{self, ReadStats};
+use super::refine_strategy::{self, RefineCostModel, RefineStrategy};
+use super::result_stream::{ResultStream, REFINE_BATCH_SIZE};
+use super::region_groups::{RegionGroup, RegionGroupBuilder, RegionGroupCache};
+use super::scan_cache::{self, ScanCache, DEFAULT_SCAN_CACHE_MAX_BYTES, SCAN_CACHE_DIR_NAME};
+use super::session::{self, SessionMeta, SESSION_META_FILE, SESSION_RESULTS_FILE};
+use super::shared_buffer::{flags, SearchErrorCode, SearchPhase, SearchStats, SearchStatus, SharedBuffer};
+use super::single_search::{self, DEFAULT_REFINE_READ_WINDOW, MAX_REFINE_READ_WINDOW, MIN_REFINE_READ_WINDOW};
+use super::watchdog::{self, WatchdogVerdict, DEFAULT_STALL_TIMEOUT};
+use crate::core::globals::{MEMORY_GUARD, PAGE_SIZE, TOKIO_RUNTIME};
+use crate::core::address_rebase::AddressRebase;
+use crate::core::process_pause::{self, PauseGuard};
+use crate::core::worker_pool::{ScanPool, WorkerPool};
+use crate::core::region_map::{current_region_map, RegionMap};
+use crate::core::{AccessQos, DRIVER_MANAGER};
 use anyhow::{anyhow, Result};
 use bplustree::BPlusTreeSet;
 use lazy_static::lazy_static;
@@ -15,12 +41,16 @@ use log::{debug, error, info, log_enabled, warn, Level};
 use rayon::prelude::*;
 use std::cmp::Ordering as CmpOrdering;
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, AtomicI64, AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::atomic::{AtomicI64, AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::mpsc;
 use std::sync::{Arc, RwLock};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tokio::task::JoinHandle;
 use tokio_util::sync::CancellationToken;
 
+/// 改善任务的输出：改善后的结果、模糊结果、是否是兼容模式捕获、精确结果幸存者的当前值、输入结果的轮次
+type RefineOutcome = (Vec<ValuePair>, Option<Vec<FuzzySearchResultItem>>, bool, Option<ExactValueCache>, PassLookup);
+
 /// Address and value type pair for storing search results.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct ValuePair {
@@ -55,6 +85,35 @@ impl From<(u64, ValueType)> for ValuePair {
 /// B+ tree order for search results. Large value to avoid splits.
 pub const BPLUS_TREE_ORDER: u16 = 256;
 
+/// Output of the blocking part of `run_search_task`.
+/// Compatibility mode reads current values before the manager write lock is taken.
+enum SearchOutput {
+    /// 精确结果，以及扫描后读取的值（结果太多或内存压力下为 None）
+    Exact(Vec<ValuePair>, Option<ExactValueCache>),
+    Fuzzy(Vec<FuzzySearchResultItem>),
+    /// 结果太多，扫描过程中已经写入结果管理器
+    Committed,
+}
+
+/// Previous values for a condition refine on exact results.
+enum ConditionSeeds {
+    /// 上一次条件改善留下的快照
+    Snapshot(Vec<FuzzySearchResultItem>),
+    /// 没有快照，任务中用游标读取全部结果，以扫描时缓存的值为旧值
+    Cached(ResultCursor),
+    /// 没有快照也没有缓存的值，任务中用游标读取全部结果，只建立快照
+    Results(ResultCursor),
+}
+
+impl ConditionSeeds {
+    fn total(&self) -> usize {
+        match self {
+            Self::Snapshot(items) => items.len(),
+            Self::Cached(cursor) | Self::Results(cursor) => cursor.total(),
+        }
+    }
+}
+
 /// Legacy callback interface for search progress. Kept for backward compatibility.
 pub trait SearchProgressCallback: Send + Sync {
     fn on_search_complete(&self, total_found: usize, total_regions: usize, elapsed_millis: u64);
@@ -69,9 +128,55 @@ pub struct SearchEngineManager {
     cancel_token: Option<CancellationToken>,
     search_handle: Option<JoinHandle<()>>,
     /// 兼容模式：所有搜索结果都以模糊搜索格式存储，支持精确搜索和模糊搜索互相切换
-    compatibility_mode: bool,
-    /// 当前特征码搜索的 pattern 长度（用于 UI 显示）
+    /// 结果数超过自动阈值时延迟到改善搜索后再转换
+    compat: CompatPolicy,
+    /// 当前特征码或字符串搜索结果的匹配长度（用于 UI 显示和改善搜索）
     current_pattern_len: Option<usize>,
+    /// 当前特征码结果使用的特征码，改善时不指定新特征码则重新匹配它
+    current_pattern: Option<Vec<(u8, u8)>>,
+    /// 当前 Xor 结果使用的密钥（用于改善搜索、显示和写入）
+    xor_key: XorKey,
+    /// 缓存目录，吞吐统计持久化在这里
+    cache_dir: Option<PathBuf>,
+    /// 扫描吞吐的滚动统计，用于预估扫描耗时
+    throughput: ThroughputStats,
+    scan_limits: ScanLimits,
+    /// 按区域缓存首次扫描结果（需要显式开启）
+    scan_cache_enabled: bool,
+    /// 手动指定的单值改善搜索策略，None 时由成本模型自动选择
+    refine_strategy_override: Option<RefineStrategy>,
+    /// 逐地址改善搜索合并读取的窗口（字节），0 表示逐个地址读取
+    refine_read_window: usize,
+    /// 改善搜索逐地址读取时页缓存的页数，0 表示不缓存
+    refine_cache_pages: usize,
+    /// 结果列表中整数值的显示方式
+    value_display: ValueDisplay,
+    /// 按区域分组的结果索引范围，结果集变化后重新计算
+    region_groups: RegionGroupCache,
+    /// 结果的显示顺序
+    result_order: ResultOrder,
+    /// "最旧的在前"显示顺序，结果集变化后重新计算
+    pass_order: PassOrderCache,
+    /// 过滤器启用时的过滤视图，结果集、过滤条件或显示顺序变化后重新计算
+    filtered_index: FilteredIndexCache,
+    /// 单个 Byte 值搜索预计结果数超过该值时按页存储，0 表示禁用
+    byte_bitmap_threshold: usize,
+    /// 精确结果上次带条件改善后的值，下一次带条件改善以此为旧值
+    exact_snapshot: Option<ExactSnapshot>,
+    /// 扫描使用的线程池，修改后从下一次扫描开始生效
+    worker_pool: WorkerPool,
+    /// 每次启动搜索任务时递增，看门狗据此判断它监视的搜索是否已被替换
+    search_id: u64,
+    /// 超过该时间没有心跳时看门狗中止搜索，0 表示不启动看门狗
+    stall_timeout: Duration,
+    /// 取消的模糊首次扫描剩余的区域，结果集变化后失效
+    fuzzy_resume: Option<FuzzyResume>,
+    /// 当前结果来自深度组搜索：组改善时取每个锚点的所有组合，而不是第一组
+    deep_group_results: bool,
+    /// 模糊首次扫描完成后把结果按地址整理并去重
+    sort_fuzzy_results: bool,
+    /// 当前结果所属的进程，None 表示绑定进程。由首次搜索的查询设置，改善、值捕获和模糊读取都读这个进程
+    target_pid: Option<i32>,
 }
 
 impl SearchEngineManager {
@@ -83,8 +188,31 @@ impl SearchEngineManager {
             shared_buffer: SharedBuffer::new(),
             cancel_token: None,
             search_handle: None,
-            compatibility_mode: false,
+            compat: CompatPolicy::new(),
             current_pattern_len: None,
+            current_pattern: None,
+            xor_key: XorKey::default(),
+            cache_dir: None,
+            throughput: ThroughputStats::default(),
+            scan_limits: ScanLimits::default(),
+            scan_cache_enabled: false,
+            refine_strategy_override: None,
+            refine_read_window: DEFAULT_REFINE_READ_WINDOW,
+            refine_cache_pages: DEFAULT_REFINE_CACHE_PAGES,
+            value_display: ValueDisplay::default(),
+            region_groups: RegionGroupCache::default(),
+            result_order: ResultOrder::Storage,
+            pass_order: PassOrderCache::default(),
+            filtered_index: FilteredIndexCache::default(),
+            byte_bitmap_threshold: DEFAULT_BYTE_BITMAP_THRESHOLD,
+            exact_snapshot: None,
+            worker_pool: WorkerPool::new("search-worker"),
+            search_id: 0,
+            stall_timeout: DEFAULT_STALL_TIMEOUT,
+            fuzzy_resume: None,
+            deep_group_results: false,
+            sort_fuzzy_results: true,
+            target_pid: None,
         }
     }
 
@@ -92,12 +220,27 @@ impl SearchEngineManager {
     /// When enabled, all search results are stored in fuzzy format,
     /// allowing seamless switching between exact and fuzzy searches.
     pub fn set_compatibility_mode(&mut self, enabled: bool) {
-        self.compatibility_mode = enabled;
+        self.compat.set_requested(enabled);
+    }
+
+    /// Read statistics of the current or last scan
+    pub fn get_search_stats(&self) -> SearchStats {
+        self.shared_buffer.read_search_stats()
+    }
+
+    /// Get compatibility mode (requested, active and deferred state)
+    pub fn get_compatibility_mode(&self) -> CompatibilityState {
+        let stored_fuzzy = self
+            .result_manager
+            .as_ref()
+            .is_some_and(|mgr| mgr.get_mode() == SearchResultMode::Fuzzy);
+        self.compat.state(stored_fuzzy)
     }
 
-    /// Get compatibility mode
-    pub fn get_compatibility_mode(&self) -> bool {
-        self.compatibility_mode
+    /// Sets the result count above which compatibility mode capture is deferred.
+    /// 0 disables the automatic policy.
+    pub fn set_compat_auto_threshold(&mut self, threshold: usize) {
+        self.compat.set_auto_threshold(threshold);
     }
 
     /// Get current pattern length (for UI display)
@@ -105,6 +248,26 @@ impl SearchEngineManager {
         self.current_pattern_len
     }
 
+    /// 当前特征码结果使用的特征码，其他结果（或导入的结果）为 None
+    pub fn current_pattern(&self) -> Option<&[(u8, u8)]> {
+        self.current_pattern.as_deref()
+    }
+
+    /// Xor 结果的密钥，显示时解码、写入时编码都用它
+    pub fn xor_key(&self) -> XorKey {
+        self.xor_key
+    }
+
+    /// Xor 改善搜索沿用首次搜索的密钥；改善时显式给出固定密钥则改用新密钥
+    fn resolve_xor_key(&mut self, query: &mut SearchQuery) {
+        if let [SearchValue::Xor { key, .. }] = query.values.as_mut_slice() {
+            match key {
+                XorKey::Address => *key = self.xor_key,
+                XorKey::Fixed(_) => self.xor_key = *key,
+            }
+        }
+    }
+
     /// Sets the shared buffer for progress communication.
     pub fn set_shared_buffer(&mut self, ptr: *mut u8, len: usize) -> bool {
         self.shared_buffer.set(ptr, len)
@@ -124,6 +287,16 @@ impl SearchEngineManager {
         }
     }
 
+    /// Returns the status and progress last written to the shared buffer.
+    pub fn search_status(&self) -> (SearchStatus, i32) {
+        (self.shared_buffer.read_status(), self.shared_buffer.read_progress())
+    }
+
+    /// Checks the cancel flag written by Kotlin into the shared buffer.
+    pub(crate) fn is_cancel_requested(&self) -> bool {
+        self.shared_buffer.is_cancel_requested()
+    }
+
     /// Requests cancellation of the current search.
     pub fn request_cancel(&self) {
         if let Some(ref token) = self.cancel_token {
@@ -131,13 +304,88 @@ impl SearchEngineManager {
         }
     }
 
+    /// 记录新启动的搜索任务，并为它启动看门狗
+    fn track_search(&mut self, handle: JoinHandle<()>) {
+        self.search_handle = Some(handle);
+        self.search_id = self.search_id.wrapping_add(1);
+        if !self.stall_timeout.is_zero() {
+            watchdog::spawn(self.search_id, self.stall_timeout);
+        }
+    }
+
+    /// 设置看门狗判定搜索卡死的时间，0 关闭看门狗，从下一次搜索开始生效
+    pub fn set_stall_timeout(&mut self, timeout: Duration) {
+        self.stall_timeout = timeout;
+    }
+
+    /// 看门狗对编号为 `search_id` 的搜索的检查结果
+    pub(crate) fn watchdog_verdict(&self, search_id: u64, timeout: Duration) -> WatchdogVerdict {
+        let running = search_id == self.search_id && self.is_searching();
+        watchdog::verdict(running, self.shared_buffer.read_status(), self.shared_buffer.heartbeat_age(), timeout)
+    }
+
+    /// 中止搜索任务并触发取消令牌，返回是否有正在运行的任务
+    fn abort_search(&mut self) -> bool {
+        let running = self.is_searching();
+        if let Some(handle) = self.search_handle.take() {
+            handle.abort();
+        }
+        if let Some(token) = self.cancel_token.take() {
+            token.cancel();
+        }
+        running
+    }
+
+    /// 看门狗判定搜索卡死后调用：中止任务，状态写为 Error / Stalled，之后可以开始新的搜索
+    pub fn recover_stalled_search(&mut self) {
+        self.abort_search();
+        self.shared_buffer.write_error_code(SearchErrorCode::Stalled);
+        self.shared_buffer.write_status(SearchStatus::Error);
+    }
+
+    /// 绑定的进程退出后调用：中止正在运行的搜索，状态写为 Error / ProcessDied
+    ///
+    /// 没有搜索在运行时只写入错误码，界面下一次读取共享缓冲区时可以看到进程已退出。
+    pub fn abort_for_process_death(&mut self) {
+        let running = self.abort_search();
+        self.fuzzy_resume = None;
+        self.shared_buffer.write_error_code(SearchErrorCode::ProcessDied);
+        if running {
+            self.shared_buffer.write_status(SearchStatus::Error);
+        }
+    }
+
+    /// 手动重置：与看门狗相同的清理，另外清空结果（卡住的搜索可能已经写入了一部分）
+    ///
+    /// 有搜索在运行时状态写为 Error / Stalled，等待这次搜索的界面会结束等待。
+    pub fn force_reset(&mut self) -> Result<()> {
+        if self.abort_search() {
+            warn!("Force resetting the search engine while a search is running");
+            self.shared_buffer.write_error_code(SearchErrorCode::Stalled);
+            self.shared_buffer.write_status(SearchStatus::Error);
+        }
+        self.exact_snapshot = None;
+        self.current_pattern_len = None;
+        self.current_pattern = None;
+        if self.is_initialized() {
+            self.clear_results()?;
+        }
+        self.shared_buffer.write_found_count(0);
+        Ok(())
+    }
+
     pub fn init(&mut self, memory_buffer_size: usize, cache_dir: String, chunk_size: usize) -> Result<()> {
         if self.result_manager.is_some() {
             warn!("SearchEngineManager already initialized, reinitializing...");
         }
 
         let cache_path = PathBuf::from(cache_dir);
+        self.throughput = ThroughputStats::load(&cache_path);
+        self.cache_dir = Some(cache_path.clone());
+        // 先释放旧的结果文件，新的管理器只接管上次进程遗留的文件
+        self.result_manager = None;
         self.result_manager = Some(SearchResultManager::new(memory_buffer_size, cache_path));
+        self.region_groups = RegionGroupCache::default();
         self.chunk_size = if chunk_size == 0 { 512 * 1024 } else { chunk_size };
 
         Ok(())
@@ -147,12 +395,243 @@ impl SearchEngineManager {
         self.result_manager.is_some()
     }
 
+    /// 预估一次扫描的耗时和存储占用，不会启动扫描
+    /// 没有吞吐历史时会先对目标区域做一次探测读取
+    ///
+    /// 只在复制吞吐统计和写回探测结果时短暂持有 `SEARCH_ENGINE_MANAGER` 的锁，探测读取期间不持锁，
+    /// 不阻塞进度和结果查询。搜索进行中时返回错误，探测会与扫描争抢驱动，测出的吞吐不准
+    pub fn estimate_scan(value_type: ValueType, regions: &[(u64, u64)], fuzzy: bool) -> Result<ScanEstimate> {
+        let (mut throughput, scan_limits, cache_dir) = {
+            let manager = SEARCH_ENGINE_MANAGER.read().map_err(|_| anyhow!("Failed to acquire SearchEngineManager lock"))?;
+            let cache_dir = manager.cache_dir.clone().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+            if manager.is_searching() {
+                return Err(anyhow!("Search already in progress"));
+            }
+            (manager.throughput, manager.scan_limits, cache_dir)
+        };
+        let had_history = throughput.has_history();
+
+        // 探测读取与扫描走同样的读取路径
+        let probe = |addr: u64, buf: &mut [u8]| -> Result<()> {
+            let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
+            driver_manager.read_memory_with_qos(addr, buf, None, AccessQos::Bulk)
+        };
+        let free_disk = estimate::free_disk_bytes(&cache_dir);
+        // 扫描缓存与模糊结果共用磁盘预算
+        let cache_bytes = scan_cache::dir_size(&cache_dir.join(SCAN_CACHE_DIR_NAME));
+        let limits = ScanLimits {
+            disk_budget: scan_limits.disk_budget.saturating_sub(cache_bytes),
+            ..scan_limits
+        };
+        let estimate = estimate::estimate_scan(value_type, regions, fuzzy, &mut throughput, limits, free_disk, probe);
+
+        if !had_history {
+            let mut manager = SEARCH_ENGINE_MANAGER.write().map_err(|_| anyhow!("Failed to acquire SearchEngineManager lock"))?;
+            // 探测期间完成的扫描已经记录了真实吞吐，不覆盖
+            if !manager.throughput.has_history() {
+                manager.throughput = throughput;
+                if let Err(e) = throughput.save(&cache_dir) {
+                    warn!("Failed to save scan throughput: {:?}", e);
+                }
+            }
+        }
+        Ok(estimate)
+    }
+
+    /// Sets the disk budget and result limit used by `estimate_scan` warnings.
+    pub fn set_scan_limits(&mut self, disk_budget: u64, max_results: u64) {
+        self.scan_limits = ScanLimits { disk_budget, max_results };
+    }
+
+    /// Enables the per-region first scan cache. Disabling it also deletes the cached files.
+    pub fn set_scan_cache_enabled(&mut self, enabled: bool) {
+        self.scan_cache_enabled = enabled;
+        if !enabled
+            && let Some(ref dir) = self.cache_dir
+        {
+            let _ = std::fs::remove_dir_all(dir.join(SCAN_CACHE_DIR_NAME));
+        }
+    }
+
+    pub fn is_scan_cache_enabled(&self) -> bool {
+        self.scan_cache_enabled
+    }
+
+    fn open_scan_cache(&self) -> Option<ScanCache> {
+        if !self.scan_cache_enabled {
+            return None;
+        }
+        let dir = self.cache_dir.as_ref()?.join(SCAN_CACHE_DIR_NAME);
+        ScanCache::new(dir, DEFAULT_SCAN_CACHE_MAX_BYTES)
+            .inspect_err(|e| warn!("Scan cache unavailable: {:?}", e))
+            .ok()
+    }
+
+    /// Overrides the single-value refine strategy. `None` restores automatic selection.
+    pub fn set_refine_strategy(&mut self, strategy: Option<RefineStrategy>) {
+        self.refine_strategy_override = strategy;
+    }
+
+    /// 设置逐地址改善搜索合并读取的窗口（字节），0 关闭合并，其他值限制在 4KB 到 64KB 之间
+    pub fn set_refine_read_window(&mut self, window: usize) {
+        self.refine_read_window = if window == 0 { 0 } else { window.clamp(MIN_REFINE_READ_WINDOW, MAX_REFINE_READ_WINDOW) };
+    }
+
+    /// 设置结果列表中整数值的显示方式，只影响显示，不影响搜索和导出
+    pub fn set_value_display(&mut self, display: ValueDisplay) {
+        self.value_display = display;
+    }
+
+    pub fn value_display(&self) -> ValueDisplay {
+        self.value_display
+    }
+
+    /// 设置改善搜索逐地址读取时页缓存的页数，0 关闭缓存，最多 `MAX_REFINE_CACHE_PAGES` 页
+    pub fn set_refine_cache_pages(&mut self, pages: usize) {
+        self.refine_cache_pages = pages.min(MAX_REFINE_CACHE_PAGES);
+    }
+
+    /// 选择单值改善搜索的策略：比较逐地址读取和重新扫描占用页的预计耗时
+    /// `current_results` 需要按地址排序
+    /// 选择单值改善搜索的策略，需要统计结果占用的页时遍历一次游标，之后回到开头
+    fn plan_refine_strategy(&self, query: &SearchQuery, cursor: &mut ResultCursor) -> Result<RefineStrategy> {
+        // 特征码和字符串结果的类型与普通值不同，重新扫描无法与它们求交集
+        if query.values.len() != 1 || !refine_strategy::supports_rescan(query.values[0].value_type()) || self.current_pattern_len.is_some() {
+            return Ok(RefineStrategy::PerItem);
+        }
+        if let Some(strategy) = self.refine_strategy_override {
+            return Ok(strategy);
+        }
+
+        let mut model = RefineCostModel::default();
+        if self.throughput.has_history() {
+            model.scan_bytes_per_sec = self.throughput.bytes_per_sec;
+        }
+        let result_mgr = self.result_manager.as_ref().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+        let result_count = cursor.total();
+        let mut results = ResultStream::new(cursor, |cursor| cursor.next_batch(result_mgr));
+        let occupied_bytes = refine_strategy::occupied_bytes(&mut results, *PAGE_SIZE);
+        results.finish()?;
+        cursor.rewind();
+
+        let strategy = model.choose(result_count, occupied_bytes);
+        debug!(
+            "Refine strategy {:?}: {} results, {} occupied bytes, {:.0} B/s",
+            strategy,
+            result_count,
+            occupied_bytes,
+            model.scan_bytes_per_sec
+        );
+        Ok(strategy)
+    }
+
+    /// 设置扫描的工作线程数，0 使用全局线程池的线程数
+    pub fn set_worker_threads(&mut self, threads: usize) {
+        self.worker_pool.set_threads(threads);
+    }
+
+    /// 扫描的工作线程是否降到后台调度优先级
+    pub fn set_background_priority(&mut self, background: bool) {
+        self.worker_pool.set_background(background);
+    }
+
+    /// 设置 Byte 搜索按页存储的阈值，0 表示禁用
+    pub fn set_byte_bitmap_threshold(&mut self, threshold: usize) {
+        self.byte_bitmap_threshold = threshold;
+    }
+
+    /// 模糊首次扫描完成后是否按地址整理结果，从下一次扫描开始生效
+    pub fn set_sort_fuzzy_results(&mut self, enabled: bool) {
+        self.sort_fuzzy_results = enabled;
+    }
+
+    /// 当前结果按页存储时返回存储占用
+    pub fn get_byte_hit_stats(&self) -> Option<ByteHitStats> {
+        self.result_manager.as_ref()?.byte_hits().map(ByteHitSet::stats)
+    }
+
+    /// 单个 Byte 值的首次扫描，抽样预计结果数超过阈值时返回按页扫描器
+    fn plan_byte_search(&self, query: &SearchQuery, regions: &[(u64, u64)]) -> Option<ByteScanner> {
+        let driver_manager = DRIVER_MANAGER.read().ok()?;
+        let (scanner, projected) = byte_search::plan_byte_search(query, regions, self.byte_bitmap_threshold, *PAGE_SIZE, self.chunk_size, |addr, buf| {
+            driver_manager.read_memory_with_qos(addr, buf, None, AccessQos::Bulk)
+        })?;
+        // 兼容模式要求存储模糊结果时不能按页存储
+        if self.compat.should_store_fuzzy(projected as usize) {
+            return None;
+        }
+        info!("Byte search projected {} results, storing hits per page", projected);
+        Some(scanner)
+    }
+
+    /// 扫描完成后更新吞吐统计
+    fn record_scan_throughput(&mut self, bytes: u64, elapsed: Duration) {
+        self.throughput.record(bytes, elapsed);
+        if let Some(ref dir) = self.cache_dir
+            && let Err(e) = self.throughput.save(dir)
+        {
+            warn!("Failed to save scan throughput: {:?}", e);
+        }
+    }
+
+    /// 写入扫描的读取统计，0 个结果且几乎没有读到内存时标记 NOTHING_READABLE
+    fn publish_read_stats(&self, read_stats: &ReadStats, result_count: usize, requested_bytes: u64, elapsed: Duration) {
+        Self::write_scan_stats(&self.shared_buffer, read_stats, elapsed);
+        let (readable, failed) = read_stats.snapshot();
+        if read_stats::is_nothing_readable(result_count, readable, requested_bytes) {
+            warn!("Scan found nothing and read only {} of {} bytes ({} failed)", readable, requested_bytes, failed);
+            self.shared_buffer.set_flag(flags::NOTHING_READABLE);
+        }
+    }
+
+    /// 写入扫描到目前为止的字节数、失败页数和平均吞吐，每个区域完成时调用
+    fn write_scan_stats(shared_buffer: &SharedBuffer, read_stats: &ReadStats, elapsed: Duration) {
+        let (readable, failed) = read_stats.snapshot();
+        let scanned = readable + failed;
+        shared_buffer.write_read_bytes(readable, failed);
+        shared_buffer.write_bytes_scanned(scanned);
+        shared_buffer.write_failed_pages(read_stats.failed_pages());
+        shared_buffer.write_throughput(read_stats::mb_per_sec(scanned, elapsed));
+    }
+
+    /// 分批写入结果，每批后更新 Storing 阶段的进度，避免 UI 在写入大量结果时停在 100%
+    fn store_in_batches<T, I, S>(items: I, shared_buffer: &SharedBuffer, mut store: S) -> Result<()>
+    where
+        I: ExactSizeIterator<Item = T>,
+        S: FnMut(Vec<T>) -> Result<()>,
+    {
+        const STORE_BATCH: usize = 256 * 1024;
+
+        let total = items.len().max(1);
+        let mut items = items;
+        let mut stored = 0;
+        shared_buffer.write_phase(SearchPhase::Storing, 0);
+        loop {
+            let batch: Vec<T> = items.by_ref().take(STORE_BATCH).collect();
+            if batch.is_empty() {
+                return Ok(());
+            }
+            stored += batch.len();
+            store(batch)?;
+            shared_buffer.write_phase_progress((stored * 100 / total) as i32);
+            shared_buffer.tick_heartbeat();
+        }
+    }
+
     /// Starts an async memory search. Returns immediately.
     /// Progress and status are communicated via the shared buffer.
     ///
     /// # Parameters
     /// * `keep_results` - If true and currently in fuzzy mode, convert fuzzy results to exact results
-    pub fn start_search_async(&mut self, query: SearchQuery, regions: Vec<(u64, u64)>, use_deep_search: bool, keep_results: bool) -> Result<()> {
+    /// * `pause_target` - If true, stop the bound process (SIGSTOP) until the scan task finishes
+    pub fn start_search_async(
+        &mut self,
+        query: SearchQuery,
+        regions: Vec<(u64, u64)>,
+        use_deep_search: bool,
+        keep_results: bool,
+        pause_target: bool,
+    ) -> Result<()> {
         if !self.is_initialized() {
             self.shared_buffer.write_status(SearchStatus::Error);
             self.shared_buffer.write_error_code(SearchErrorCode::NotInitialized);
@@ -165,20 +644,28 @@ impl SearchEngineManager {
             return Err(anyhow!("Search already in progress"));
         }
 
+        // 物理读取时共享页可能同时映射在本进程里，跳过自己的缓冲区和结果文件
+        let regions = match DRIVER_MANAGER.read() {
+            Ok(driver_manager) => driver_manager.exclude_self_regions(regions),
+            Err(_) => regions,
+        };
+
         // Prepare result manager.
         let result_mgr = self
             .result_manager
             .as_mut()
             .ok_or_else(|| anyhow!("SearchEngineManager's result_manager not initialized"))?;
 
+        result_mgr.discard_undo();
+
         // Check if we need to convert fuzzy results to exact results
         if keep_results && result_mgr.get_mode() == SearchResultMode::Fuzzy {
             let fuzzy_results = result_mgr.get_all_fuzzy_results()?;
             if !fuzzy_results.is_empty() {
-                // Convert fuzzy to exact: just take address and type
+                // Convert fuzzy to exact: just take address, type and pass
                 let exact_results: Vec<_> = fuzzy_results
                     .into_iter()
-                    .map(|fuzzy| SearchResultItem::new_exact(fuzzy.address, fuzzy.value_type))
+                    .map(|fuzzy| SearchResultItem::new_exact(fuzzy.address, fuzzy.value_type).with_pass(fuzzy.pass))
                     .collect();
 
                 result_mgr.clear()?;
@@ -191,9 +678,17 @@ impl SearchEngineManager {
                 result_mgr.set_mode(SearchResultMode::Exact)?;
             }
         } else {
+            // 新的搜索不再沿用之前结果的标签
             result_mgr.clear()?;
+            result_mgr.clear_labels();
             result_mgr.set_mode(SearchResultMode::Exact)?;
         }
+        result_mgr.begin_pass();
+        let kept_count = result_mgr.total_count();
+        let has_results = kept_count > 0;
+        let byte_scanner = if has_results { None } else { self.plan_byte_search(&query, &regions) };
+
+        let pool = self.worker_pool.current()?;
 
         // Reset shared buffer and set searching status.
         self.shared_buffer.reset();
@@ -205,29 +700,80 @@ impl SearchEngineManager {
         self.cancel_token = Some(cancel_token.clone());
 
         let chunk_size = self.chunk_size;
-        let compatibility_mode = self.compatibility_mode;
+        // 字符串结果是变长的，不做兼容模式的值捕获
+        let compat = if query.is_text() { CompatPolicy::new() } else { self.compat };
+        self.current_pattern_len = query.is_text().then(|| query.values[0].byte_len());
+        self.current_pattern = None;
+        self.deep_group_results = use_deep_search && query.values.len() > 1;
+        self.target_pid = query.target_pid;
+        if let [SearchValue::Xor { key, .. }] = query.values.as_slice() {
+            self.xor_key = *key;
+        }
+        let scan_cache = self.open_scan_cache();
+        MEMORY_GUARD.start_sampler();
+        let target_pid = query.target_pid;
+        let pause_guard = Self::pause_target_for_scan(pause_target, target_pid);
 
         // Spawn async search task.
-        let handle = TOKIO_RUNTIME.spawn(async move {
-            Self::run_search_task(query, regions, use_deep_search, chunk_size, compatibility_mode, cancel_token).await;
-        });
+        let handle = match byte_scanner {
+            Some(scanner) => TOKIO_RUNTIME.spawn(async move {
+                let _pause_guard = pause_guard;
+                Self::run_byte_search_task(scanner, regions, target_pid, pool, cancel_token).await;
+            }),
+            None => TOKIO_RUNTIME.spawn(async move {
+                let _pause_guard = pause_guard;
+                Self::run_search_task(query, regions, use_deep_search, chunk_size, compat, scan_cache, kept_count, pool, cancel_token).await;
+            }),
+        };
 
-        self.search_handle = Some(handle);
+        self.track_search(handle);
         Ok(())
     }
 
+    /// 暂停扫描的进程（target_pid 为 None 时是绑定的进程），guard 随扫描任务一起释放（完成、取消、出错或阻塞任务 panic 后都会恢复）。
+    /// 暂停失败时只记录警告，扫描照常进行。
+    fn pause_target_for_scan(pause_target: bool, target_pid: Option<i32>) -> Option<PauseGuard<'static>> {
+        if !pause_target {
+            return None;
+        }
+        let paused = match target_pid {
+            Some(pid) => process_pause::pause_target(pid),
+            None => DRIVER_MANAGER
+                .read()
+                .map_err(|_| anyhow!("Failed to acquire DriverManager lock"))
+                .and_then(|driver_manager| driver_manager.pause_target()),
+        };
+        match paused {
+            Ok(guard) => Some(guard),
+            Err(e) => {
+                warn!("Scanning without pausing the target: {:?}", e);
+                None
+            },
+        }
+    }
+
     /// Internal async search task that runs in tokio runtime.
+    ///
+    /// `kept_count` 为扫描前保留的结果数，取消时去掉之后已经提交的结果。
+    #[allow(clippy::too_many_arguments)]
     async fn run_search_task(
         query: SearchQuery,
         regions: Vec<(u64, u64)>,
         use_deep_search: bool,
         chunk_size: usize,
-        compatibility_mode: bool,
+        compat: CompatPolicy,
+        scan_cache: Option<ScanCache>,
+        kept_count: usize,
+        pool: ScanPool,
         cancel_token: CancellationToken,
     ) {
+        let compatibility_mode = compat.is_requested();
         let start_time = Instant::now();
         let total_regions = regions.len();
+        let total_bytes = estimate::scan_bytes(&regions);
         let is_group_search = query.values.len() > 1;
+        let pattern_len = if query.is_text() { query.values[0].byte_len() } else { 0 };
+        let max_results = query.max_results;
 
         if log_enabled!(Level::Debug) {
             debug!(
@@ -244,111 +790,250 @@ impl SearchEngineManager {
 
         // Shared state for progress tracking.
         let completed_regions = Arc::new(AtomicUsize::new(0));
-        let total_found_count = Arc::new(AtomicI64::new(0));
-        let cancelled = Arc::new(AtomicBool::new(false));
+        let reused_regions = Arc::new(AtomicUsize::new(0));
+        let read_stats = Arc::new(ReadStats::new());
+        let cancel = CancelSource::new(cancel_token);
 
         // Clone for the blocking task.
         let completed_regions_clone = Arc::clone(&completed_regions);
-        let total_found_clone = Arc::clone(&total_found_count);
-        let cancelled_clone = Arc::clone(&cancelled);
-        let cancel_token_clone = cancel_token.clone();
+        let reused_regions_clone = Arc::clone(&reused_regions);
+        let read_stats_clone = Arc::clone(&read_stats);
+        let cancel_clone = cancel.clone();
+
+        // 区域按起始地址编号，按编号顺序提交的结果整体有序
+        let mut regions = regions;
+        regions.sort_unstable();
 
         // Run the CPU-intensive search in a blocking task with rayon.
-        let search_result = tokio::task::spawn_blocking(move || {
-            let mut all_results: Vec<_> = regions
-                .par_iter()
-                .enumerate()
-                .filter_map(|(idx, (start, end))| {
-                    // Check cancellation from both CancellationToken and shared buffer.
-                    if cancel_token_clone.is_cancelled() || cancelled_clone.load(AtomicOrdering::Relaxed) {
-                        cancelled_clone.store(true, AtomicOrdering::Relaxed);
-                        return None;
-                    }
+        let search_result = pool.spawn_blocking(move || -> Option<(SearchOutput, PressureReport)> {
+            // The same check is used between regions, between chunks and inside the scan loops,
+            // so a single huge region observes cancellation as quickly as many small ones.
+            let check_cancelled = || cancel_clone.poll();
+            // 扫描循环还要在结果数达到上限时停下来；之后的排序、捕获和保存只检查取消
+            let check_scan_stopped = || cancel_clone.poll_scan(read_stats_clone.matches(), max_results);
+            let report_phase = |phase: SearchPhase, progress: i32| {
+                if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
+                    manager.shared_buffer.write_phase(phase, progress);
+                    manager.shared_buffer.tick_heartbeat();
+                }
+            };
+            let fingerprint = scan_cache::query_fingerprint(&query, use_deep_search);
+            let sample_read = |addr: u64, buf: &mut [u8]| -> Result<()> {
+                let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
+                driver_manager.read_target_with_qos(query.target_pid, addr, buf, None, AccessQos::Bulk)
+            };
 
-                    // Check cancel flag from shared buffer.
-                    if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
-                        if manager.shared_buffer.is_cancel_requested() {
-                            cancelled_clone.store(true, AtomicOrdering::Relaxed);
-                            return None;
+            // 内存压力升高时提前把结果写入结果管理器，超过硬限制后不再开始新的区域
+            let ladder = PressureLadder::new(&MEMORY_GUARD, chunk_size);
+            let (sender, receiver) = mpsc::sync_channel::<RegionBatch>(COMMIT_QUEUE_DEPTH);
+
+            let committed = std::thread::scope(|scope| {
+                let ladder = &ladder;
+                let check_cancelled = &check_cancelled;
+                let check_scan_stopped = &check_scan_stopped;
+                // 唯一的提交线程：结果少时留在内存里，多了按区域顺序写入结果管理器
+                let committer = scope.spawn(move || {
+                    let committer = ResultCommitter::new(RESULT_RETAIN_LIMIT, REORDER_LIMIT, |batch: Vec<ValuePair>| -> Result<()> {
+                        let mut manager = SEARCH_ENGINE_MANAGER
+                            .write()
+                            .map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
+                        let result_mgr = manager.result_manager.as_mut().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+                        let pass = result_mgr.current_pass();
+                        result_mgr.add_results_batch(batch.iter().map(|pair| SearchResultItem::from(pair).with_pass(pass)).collect())
+                    });
+                    result_commit::run_committer(committer, receiver, || ladder.stage() != ScanStage::Buffering, check_cancelled, |found| {
+                        if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
+                            manager.shared_buffer.write_found_count(found as i64);
                         }
-                    }
-
-                    // if log_enabled!(Level::Debug) {
-                    //     debug!("Searching region {}: 0x{:X} - 0x{:X}", idx, start, end);
-                    // }
+                    })
+                });
 
-                    // Create a cancel check closure for deep search.
-                    // This closure also sets cancelled_clone to propagate cancellation to other parallel tasks.
-                    let check_cancelled_for_region = || -> bool {
-                        if cancel_token_clone.is_cancelled() || cancelled_clone.load(AtomicOrdering::Relaxed) {
-                            cancelled_clone.store(true, AtomicOrdering::Relaxed);
-                            return true;
+                regions
+                    .par_iter()
+                    .enumerate()
+                    .for_each_with(sender, |sender, (idx, (start, end))| {
+                        if check_scan_stopped() {
+                            // 达到结果数上限时后面的区域还要按顺序提交
+                            if !check_cancelled() {
+                                let _ = sender.send(RegionBatch::empty(idx));
+                            }
+                            return;
                         }
-                        if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
-                            if manager.shared_buffer.is_cancel_requested() {
-                                cancelled_clone.store(true, AtomicOrdering::Relaxed);
-                                return true;
+                        let Some(chunk_size) = ladder.before_region() else {
+                            let _ = sender.send(RegionBatch::empty(idx));
+                            return;
+                        };
+
+                        // if log_enabled!(Level::Debug) {
+                        //     debug!("Searching region {}: 0x{:X} - 0x{:X}", idx, start, end);
+                        // }
+
+                        let scan = || {
+                            if is_group_search {
+                                if use_deep_search {
+                                    group_search::search_region_group_deep_with_cancel(&query, *start, *end, chunk_size, AccessQos::Bulk, check_scan_stopped, &read_stats_clone)
+                                } else {
+                                    group_search::search_region_group_with_cancel(&query, *start, *end, chunk_size, AccessQos::Bulk, check_scan_stopped, &read_stats_clone)
+                                }
+                            } else {
+                                single_search::search_region_single_with_cancel(&query.values[0], *start, *end, chunk_size, query.alignment, query.target_pid, AccessQos::Bulk, check_scan_stopped, &read_stats_clone)
+                            }
+                        };
+                        let (result, reused) = scan_cache::scan_region_cached(
+                            scan_cache.as_ref(),
+                            fingerprint,
+                            (*start, *end),
+                            *PAGE_SIZE,
+                            sample_read,
+                            scan,
+                            // 提前停止的区域结果不完整，不写入缓存
+                            check_scan_stopped,
+                        );
+                        if reused {
+                            reused_regions_clone.fetch_add(1, AtomicOrdering::Relaxed);
+                            // 复用的区域在上次扫描时读取成功，采样也全部成功
+                            read_stats_clone.add(end - start, 0);
+                            if let Ok(ref results) = result {
+                                read_stats_clone.record_matches(results.len());
                             }
                         }
-                        false
-                    };
 
-                    let result = if is_group_search {
-                        if use_deep_search {
-                            // Use cancellable version for deep search.
-                            group_search::search_region_group_deep_with_cancel(&query, *start, *end, chunk_size, &check_cancelled_for_region)
-                        } else {
-                            group_search::search_region_group(&query, *start, *end, chunk_size)
+                        let region_results = match result {
+                            Ok(results) => results,
+                            Err(e) => {
+                                error!("Failed to search region {}: {:?}", idx, e);
+                                Vec::new()
+                            },
+                        };
+
+                        // Update progress counters; the found count is written by the committer.
+                        let completed = completed_regions_clone.fetch_add(1, AtomicOrdering::Relaxed) + 1;
+
+                        // Update shared buffer with progress information.
+                        if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
+                            let progress = ((completed as f64 / total_regions as f64) * 100.0) as i32;
+                            manager.shared_buffer.write_progress(progress);
+                            manager.shared_buffer.write_regions_done(completed as i32);
+                            Self::write_scan_stats(&manager.shared_buffer, &read_stats_clone, start_time.elapsed());
+                            manager.shared_buffer.tick_heartbeat();
                         }
-                    } else {
-                        single_search::search_region_single(&query.values[0], *start, *end, chunk_size)
-                    };
 
-                    let region_results = match result {
-                        Ok(results) => results,
-                        Err(e) => {
-                            error!("Failed to search region {}: {:?}", idx, e);
-                            Vec::new()
-                        },
-                    };
+                        if log_enabled!(Level::Debug) && completed.is_multiple_of(100) {
+                            let progress = ((completed as f64 / total_regions as f64) * 100.0) as i32;
+                            debug!("Search progress: {}% ({}/{})", progress, completed, total_regions);
+                        }
 
-                    // Update progress counters.
-                    let completed = completed_regions_clone.fetch_add(1, AtomicOrdering::Relaxed) + 1;
-                    let found_in_region = region_results.len() as i64;
-                    let total_found = total_found_clone.fetch_add(found_in_region, AtomicOrdering::Relaxed) + found_in_region;
+                        // 提交线程已经退出（取消）时发送失败，直接丢弃
+                        let _ = sender.send(RegionBatch::new(idx, region_results));
+                    });
 
-                    // Update shared buffer with progress information.
-                    if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
-                        let progress = ((completed as f64 / total_regions as f64) * 100.0) as i32;
-                        manager.shared_buffer.update_progress(progress, completed as i32, total_found);
-                        manager.shared_buffer.tick_heartbeat();
-                    }
+                committer.join().unwrap_or_else(|_| Some(Err(anyhow!("Result committer panicked"))))
+            });
+            let pressure = ladder.finish();
 
-                    if log_enabled!(Level::Debug) && completed % 100 == 0 {
-                        let progress = ((completed as f64 / total_regions as f64) * 100.0) as i32;
-                        debug!("Search progress: {}% ({}/{})", progress, completed, total_regions);
-                    }
+            // 排序去重和兼容模式转换都可能很耗时，每个阶段前都检查取消
+            if check_cancelled() {
+                return None;
+            }
 
-                    Some(region_results)
-                })
-                .reduce(Vec::new, |mut a, mut b| {
-                    a.append(&mut b);
-                    a
-                });
+            if let Some(ref cache) = scan_cache
+                && let Err(e) = cache.trim()
+            {
+                warn!("Failed to trim scan cache: {:?}", e);
+            }
+
+            let mut all_results = match committed? {
+                Ok(CommitOutcome::Retained(results)) => results,
+                Ok(CommitOutcome::Committed { count, runs }) => {
+                    if runs > 1 {
+                        info!("Committed {} results in {} sorted runs", count, runs);
+                    }
+                    return Some((SearchOutput::Committed, pressure));
+                },
+                Err(e) => {
+                    error!("Failed to commit search results: {:?}", e);
+                    return None;
+                },
+            };
 
             let start = Instant::now();
-            all_results.sort_unstable_by(|a, b| a.addr.cmp(&b.addr));
+            report_phase(SearchPhase::Sorting, 0);
+            // 各区域的结果已经排序去重，按区域顺序拼接后通常整体有序，只有区域重叠时才需要重新排序
+            if !all_results.is_sorted_by_key(|pair| (pair.addr, pair.value_type.to_id())) {
+                all_results.par_sort_unstable_by_key(|pair| (pair.addr, pair.value_type.to_id()));
+            }
+            if check_cancelled() {
+                return None;
+            }
+            report_phase(SearchPhase::Sorting, 50);
             all_results.dedup();
+            report_phase(SearchPhase::Sorting, 100);
             if log_enabled!(Level::Debug) {
                 info!("搜索排序去重复耗时: {:?}", start.elapsed())
             }
 
-            all_results
+            // 内存压力已经升高，兼容模式的值捕获也需要额外内存，这次只存精确结果
+            if pressure.stage != ScanStage::Buffering {
+                return Some((SearchOutput::Exact(all_results, None), pressure));
+            }
+
+            if !compat.should_store_fuzzy(all_results.len()) {
+                if compatibility_mode {
+                    info!(
+                        "Compat capture deferred: {} results exceed auto threshold",
+                        all_results.len()
+                    );
+                }
+                // 精确结果只有地址，趁值还是匹配时的值读取一次供显示使用
+                report_phase(SearchPhase::Capturing, 0);
+                let value_len = |value_type: ValueType| if value_type.is_variable_len() { pattern_len } else { value_type.size() };
+                let entries: Vec<(u64, usize)> = all_results.iter().map(|pair| (pair.addr, value_len(pair.value_type))).collect();
+                let values = ExactValueCache::capture(&entries, sample_read, &check_cancelled);
+                if check_cancelled() {
+                    return None;
+                }
+                return Some((SearchOutput::Exact(all_results, values), pressure));
+            }
+
+            // 兼容模式：在获取写锁之前读取当前值，按块并行读取并检查取消
+            let driver_manager = DRIVER_MANAGER.read().ok()?;
+            let total = all_results.len().max(1);
+            report_phase(SearchPhase::Capturing, 0);
+            let fuzzy_results = capture_fuzzy_values(
+                &all_results,
+                0,
+                |addr, buf| driver_manager.read_target_with_qos(query.target_pid, addr, buf, None, AccessQos::Bulk),
+                &check_cancelled,
+                &|done| report_phase(SearchPhase::Capturing, (done * 100 / total) as i32),
+            );
+
+            if check_cancelled() {
+                return None;
+            }
+            Some((SearchOutput::Fuzzy(fuzzy_results), pressure))
         })
         .await;
 
         // Check if cancelled.
-        if cancel_token.is_cancelled() || cancelled.load(AtomicOrdering::Relaxed) {
+        if cancel.poll() {
+            // 内存压力下已经按区域提交的结果不完整，回到扫描前保留的结果
+            if let Ok(mut manager) = SEARCH_ENGINE_MANAGER.write()
+                && let Some(ref mut result_mgr) = manager.result_manager
+            {
+                let total = result_mgr.total_count();
+                if total > kept_count {
+                    info!("Discarding {} results committed before the search was cancelled", total - kept_count);
+                    let rolled_back = if kept_count == 0 {
+                        result_mgr.clear()
+                    } else {
+                        result_mgr.remove_results_batch((kept_count..total).collect())
+                    };
+                    if let Err(e) = rolled_back {
+                        error!("Failed to discard committed results of the cancelled search: {:?}", e);
+                    }
+                    manager.shared_buffer.write_found_count(kept_count as i64);
+                }
+            }
             // Update shared buffer via the global manager.
             if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
                 manager.shared_buffer.write_status(SearchStatus::Cancelled);
@@ -357,46 +1042,66 @@ impl SearchEngineManager {
             return;
         }
 
+        // 结果数达到上限时扫描提前结束，结果照常保存
+        let truncated = cancel.is_stopped();
+
         // Process results.
         // IMPORTANT: We must release the write lock BEFORE setting status to COMPLETED.
         // This ensures that when Kotlin sees COMPLETED status and calls getResults(),
         // the read lock can be acquired immediately.
         let (final_count, elapsed, success) = match search_result {
-            Ok(all_results) => {
+            Ok(Some((output, pressure))) => {
                 match SEARCH_ENGINE_MANAGER.write() {
                     Ok(mut manager) => {
+                        let stored_fuzzy = matches!(output, SearchOutput::Fuzzy(_));
+                        manager.compat.on_scan_stored(stored_fuzzy);
+                        let reused = reused_regions.load(AtomicOrdering::Relaxed);
+                        if reused > 0 {
+                            info!("Scan cache reused {} of {} regions", reused, total_regions);
+                            manager.shared_buffer.set_flag(flags::SCAN_CACHE_REUSED);
+                        }
+                        if pressure.stage >= ScanStage::Streaming {
+                            manager.shared_buffer.set_flag(flags::MEMORY_PRESSURE_STREAMED);
+                        }
+                        if pressure.stage == ScanStage::Stopped {
+                            warn!(
+                                "Search stopped under memory pressure: {} of {} regions skipped",
+                                pressure.skipped_regions, total_regions
+                            );
+                            manager.shared_buffer.set_flag(flags::PARTIAL_RESULTS);
+                        }
+                        if truncated {
+                            info!("Search stopped after reaching the result cap of {}", max_results);
+                            manager.shared_buffer.set_flag(flags::RESULTS_TRUNCATED);
+                        }
+                        let manager = &mut *manager;
                         if let Some(ref mut result_mgr) = manager.result_manager {
-                            if compatibility_mode {
-                                // 兼容模式：转换为模糊搜索格式存储
-                                if let Err(e) = result_mgr.set_mode(SearchResultMode::Fuzzy) {
-                                    error!("Failed to set mode: {:?}", e);
-                                }
-                                if let Ok(driver_manager) = DRIVER_MANAGER.read() {
-                                    let fuzzy_results: Vec<FuzzySearchResultItem> = all_results
-                                        .into_iter() // todo 可以并行吗?
-                                        .filter_map(|pair| {
-                                            let size = pair.value_type.size();
-                                            let mut buffer = vec![0u8; size];
-                                            if driver_manager.read_memory_unified(pair.addr, &mut buffer, None).is_ok() {
-                                                Some(FuzzySearchResultItem::from_bytes(pair.addr, &buffer, pair.value_type))
-                                            } else {
-                                                None
-                                            }
-                                        })
-                                        .collect();
-                                    if let Err(e) = result_mgr.add_fuzzy_results_batch(fuzzy_results) {
+                            let pass = result_mgr.current_pass();
+                            let shared_buffer = &manager.shared_buffer;
+                            match output {
+                                SearchOutput::Fuzzy(fuzzy_results) => {
+                                    // 兼容模式：转换为模糊搜索格式存储
+                                    if let Err(e) = result_mgr.set_mode(SearchResultMode::Fuzzy) {
+                                        error!("Failed to set mode: {:?}", e);
+                                    }
+                                    let fuzzy_results = fuzzy_results.into_iter().map(|item| item.with_pass(pass));
+                                    if let Err(e) = Self::store_in_batches(fuzzy_results, shared_buffer, |batch| result_mgr.add_fuzzy_results_batch(batch)) {
                                         error!("Failed to add fuzzy results: {:?}", e);
                                     }
-                                }
-                            } else {
-                                // 标准模式：存储为精确搜索格式
-                                let converted_results: Vec<_> = all_results
-                                    .into_iter()
-                                    .map(|pair| SearchResultItem::new_exact(pair.addr, pair.value_type))
-                                    .collect();
-                                if let Err(e) = result_mgr.add_results_batch(converted_results) {
-                                    error!("Failed to add results: {:?}", e);
-                                }
+                                },
+                                SearchOutput::Exact(all_results, values) => {
+                                    // 标准模式：存储为精确搜索格式
+                                    let converted_results = all_results
+                                        .into_iter()
+                                        .map(|pair| SearchResultItem::new_exact(pair.addr, pair.value_type).with_pass(pass));
+                                    if let Err(e) = Self::store_in_batches(converted_results, shared_buffer, |batch| result_mgr.add_results_batch(batch)) {
+                                        error!("Failed to add results: {:?}", e);
+                                    }
+                                    if let Some(values) = values {
+                                        result_mgr.set_exact_values(values);
+                                    }
+                                },
+                                SearchOutput::Committed => {},
                             }
 
                             let elapsed = start_time.elapsed().as_millis() as u64;
@@ -408,9 +1113,11 @@ impl SearchEngineManager {
                             );
 
                             // Update progress info but NOT status yet (write lock still held).
+                            manager.record_scan_throughput(total_bytes, start_time.elapsed());
                             manager.shared_buffer.write_found_count(final_count as i64);
                             manager.shared_buffer.write_progress(100);
                             manager.shared_buffer.write_regions_done(total_regions as i32);
+                            manager.publish_read_stats(&read_stats, final_count, total_bytes, start_time.elapsed());
 
                             (final_count as i64, elapsed, true)
                         } else {
@@ -425,6 +1132,10 @@ impl SearchEngineManager {
                 }
                 // Write lock is released here when `manager` goes out of scope.
             },
+            Ok(None) => {
+                error!("Search task produced no output");
+                (0, 0, false)
+            },
             Err(e) => {
                 error!("Search task failed: {:?}", e);
                 (0, 0, false)
@@ -434,7 +1145,9 @@ impl SearchEngineManager {
         // Now set status AFTER the write lock is released.
         // This ensures Kotlin can immediately acquire read lock when it sees COMPLETED.
         if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
-            if success {
+            if success && truncated {
+                manager.shared_buffer.write_status(SearchStatus::CompletedTruncated);
+            } else if success {
                 manager.shared_buffer.write_status(SearchStatus::Completed);
             } else {
                 manager.shared_buffer.write_status(SearchStatus::Error);
@@ -443,44 +1156,189 @@ impl SearchEngineManager {
         }
     }
 
-    /// Starts async refine search. Returns immediately.
-    /// Supports both Exact and Fuzzy modes. When in Fuzzy mode, results will be converted back to Fuzzy after refinement.
-    pub fn start_refine_async(&mut self, query: SearchQuery) -> Result<()> {
-        if !self.is_initialized() {
-            self.shared_buffer.write_status(SearchStatus::Error);
-            self.shared_buffer.write_error_code(SearchErrorCode::NotInitialized);
-            return Err(anyhow!("SearchEngineManager not initialized"));
-        }
+    /// 按页存储的 Byte 首次扫描，各区域并行扫描后直接生成每页的命中
+    async fn run_byte_search_task(scanner: ByteScanner, regions: Vec<(u64, u64)>, target_pid: Option<i32>, pool: ScanPool, cancel_token: CancellationToken) {
+        let start_time = Instant::now();
+        let total_regions = regions.len();
+        let total_bytes = estimate::scan_bytes(&regions);
 
-        if self.is_searching() {
-            self.shared_buffer.write_status(SearchStatus::Error);
-            self.shared_buffer.write_error_code(SearchErrorCode::AlreadySearching);
-            return Err(anyhow!("Search already in progress"));
-        }
+        let read_stats = Arc::new(ReadStats::new());
+        let cancel = CancelSource::new(cancel_token);
+        let read_stats_clone = Arc::clone(&read_stats);
+        let cancel_clone = cancel.clone();
 
-        let result_mgr = self.result_manager.as_ref().unwrap();
-        let original_mode = result_mgr.get_mode();
+        let search_result = pool.spawn_blocking(move || -> Option<ByteHitSet> {
+            let check_cancelled = || cancel_clone.poll();
+            let completed_regions = AtomicUsize::new(0);
+            let total_found = AtomicI64::new(0);
 
-        let current_results: Vec<ValuePair> = match original_mode {
-            SearchResultMode::Exact => result_mgr
-                .get_all_exact_results()?
-                .into_iter()
-                .map(|result| ValuePair::new(result.address, result.typ))
-                .collect(),
-            SearchResultMode::Fuzzy => result_mgr
-                .get_all_fuzzy_results()?
-                .into_iter()
-                .map(|fuzzy| ValuePair::new(fuzzy.address, fuzzy.value_type))
-                .collect(),
-        };
+            let pages: Vec<PageHits> = regions
+                .par_iter()
+                .flat_map_iter(|&(start, end)| {
+                    if check_cancelled() {
+                        return Vec::new();
+                    }
+                    let pages = match DRIVER_MANAGER.read() {
+                        Ok(driver_manager) => scanner.scan_region(
+                            start,
+                            end,
+                            |addr, buf, page_status| driver_manager.read_target_with_qos(target_pid, addr, buf, Some(page_status), AccessQos::Bulk),
+                            &check_cancelled,
+                            &read_stats_clone,
+                        ),
+                        Err(e) => {
+                            error!("Failed to acquire DriverManager lock: {:?}", e);
+                            Vec::new()
+                        },
+                    };
 
-        if current_results.is_empty() {
-            warn!("No results to refine");
+                    let completed = completed_regions.fetch_add(1, AtomicOrdering::Relaxed) + 1;
+                    let found_in_region = pages.iter().map(PageHits::len).sum::<usize>() as i64;
+                    let found = total_found.fetch_add(found_in_region, AtomicOrdering::Relaxed) + found_in_region;
+                    if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
+                        let progress = ((completed as f64 / total_regions as f64) * 100.0) as i32;
+                        manager.shared_buffer.update_progress(progress, completed as i32, found);
+                        Self::write_scan_stats(&manager.shared_buffer, &read_stats_clone, start_time.elapsed());
+                        manager.shared_buffer.tick_heartbeat();
+                    }
+                    pages
+                })
+                .collect();
+
+            if check_cancelled() {
+                return None;
+            }
+            Some(ByteHitSet::from_pages(scanner.page_size(), pages))
+        })
+        .await;
+
+        if cancel.poll() {
+            if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
+                manager.shared_buffer.write_status(SearchStatus::Cancelled);
+            }
+            info!("Byte search cancelled");
+            return;
+        }
+
+        // IMPORTANT: Release write lock BEFORE setting status to COMPLETED.
+        let success = match search_result {
+            Ok(Some(hits)) => match SEARCH_ENGINE_MANAGER.write() {
+                Ok(mut manager) => {
+                    manager.compat.on_scan_stored(false);
+                    if let Some(ref mut result_mgr) = manager.result_manager {
+                        let pass = result_mgr.current_pass();
+                        let stats = hits.stats();
+                        if let Err(e) = result_mgr.set_byte_hits(hits.with_pass(pass)) {
+                            error!("Failed to store byte hits: {:?}", e);
+                        }
+                        let final_count = result_mgr.total_count();
+                        info!(
+                            "Byte search completed: {} results on {} pages ({} dense, {} KB) in {} ms",
+                            final_count,
+                            stats.pages,
+                            stats.dense_pages,
+                            stats.heap_bytes / 1024,
+                            start_time.elapsed().as_millis()
+                        );
+
+                        manager.record_scan_throughput(total_bytes, start_time.elapsed());
+                        manager.shared_buffer.write_found_count(final_count as i64);
+                        manager.shared_buffer.write_progress(100);
+                        manager.shared_buffer.write_regions_done(total_regions as i32);
+                        manager.publish_read_stats(&read_stats, final_count, total_bytes, start_time.elapsed());
+                        true
+                    } else {
+                        error!("result_manager is None when processing byte search results");
+                        false
+                    }
+                },
+                Err(e) => {
+                    error!("Failed to acquire write lock for byte search results: {:?}", e);
+                    false
+                },
+            },
+            Ok(None) => {
+                error!("Byte search task produced no output");
+                false
+            },
+            Err(e) => {
+                error!("Byte search task failed: {:?}", e);
+                false
+            },
+        };
+
+        if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
+            if success {
+                manager.shared_buffer.write_status(SearchStatus::Completed);
+            } else {
+                manager.shared_buffer.write_status(SearchStatus::Error);
+                manager.shared_buffer.write_error_code(SearchErrorCode::InternalError);
+            }
+        }
+    }
+
+    /// Starts async refine search. Returns immediately.
+    /// Supports both Exact and Fuzzy modes. When in Fuzzy mode, results will be converted back to Fuzzy after refinement.
+    ///
+    /// With a `condition` the query is ignored and the results are refined by comparing values instead,
+    /// see `start_condition_refine_async`. On exact results the old values come from the values read at
+    /// search time, see `start_exact_condition_refine`.
+    pub fn start_refine_async(&mut self, mut query: SearchQuery, condition: Option<FuzzyCondition>) -> Result<()> {
+        if let Some(condition) = condition {
+            return self.start_condition_refine_async(condition);
+        }
+
+        if !self.is_initialized() {
+            self.shared_buffer.write_status(SearchStatus::Error);
+            self.shared_buffer.write_error_code(SearchErrorCode::NotInitialized);
+            return Err(anyhow!("SearchEngineManager not initialized"));
+        }
+
+        if self.is_searching() {
+            self.shared_buffer.write_status(SearchStatus::Error);
+            self.shared_buffer.write_error_code(SearchErrorCode::AlreadySearching);
+            return Err(anyhow!("Search already in progress"));
+        }
+
+        let result_mgr = self.result_manager.as_ref().unwrap();
+        let original_mode = result_mgr.get_mode();
+
+        // 按页存储的结果用 Byte 条件改善时直接按页求交集，其他条件展开成精确结果走普通路径
+        if let Some(hits) = result_mgr.byte_hits()
+            && let Some(matcher) = byte_search::query_byte_matcher(&query)
+        {
+            let scanner = ByteScanner::new(matcher, hits.page_size(), self.chunk_size);
+            let hits = hits.clone();
+            let threshold = self.byte_bitmap_threshold;
+            let target_pid = self.target_pid;
+
+            let pool = self.worker_pool.current()?;
+
+            self.shared_buffer.reset();
+            self.shared_buffer.clear_cancel_flag();
+            self.shared_buffer.write_status(SearchStatus::Searching);
+
+            let cancel_token = CancellationToken::new();
+            self.cancel_token = Some(cancel_token.clone());
+
+            let handle = TOKIO_RUNTIME.spawn(async move {
+                Self::run_byte_refine_task(scanner, hits, threshold, target_pid, pool, cancel_token).await;
+            });
+            self.track_search(handle);
+            return Ok(());
+        }
+
+        // 已有结果在任务中按地址顺序分批读取，不再一次性展开，轮次也在读取时记录
+        let mut cursor = result_mgr.cursor(REFINE_BATCH_SIZE)?;
+        if cursor.total() == 0 {
+            warn!("No results to refine");
             self.shared_buffer.write_status(SearchStatus::Completed);
             self.shared_buffer.write_found_count(0);
             return Ok(());
         }
 
+        let pool = self.worker_pool.current()?;
+
         // Reset shared buffer.
         self.shared_buffer.reset();
         self.shared_buffer.clear_cancel_flag();
@@ -489,52 +1347,399 @@ impl SearchEngineManager {
         let cancel_token = CancellationToken::new();
         self.cancel_token = Some(cancel_token.clone());
 
+        // 兼容模式被延迟时，精确结果的改善搜索可能需要补做值捕获；变长的结果不捕获
+        let variable_len = query.is_text() || query.values[0].is_pattern() || self.current_pattern_len.is_some();
+        let compat = (original_mode == SearchResultMode::Exact && !variable_len).then_some(self.compat);
+        if query.is_text() {
+            self.current_pattern_len = Some(query.values[0].byte_len());
+        }
+        // 用新的特征码改善时，之后的显示和改善使用新特征码；用普通值改善特征码结果时保留原来的特征码
+        if let [SearchValue::Pattern { pattern }] = query.values.as_slice() {
+            self.current_pattern_len = Some(pattern.len());
+            self.current_pattern = Some(pattern.clone());
+        }
+        self.resolve_xor_key(&mut query);
+        // 改善读取首次搜索的进程
+        query.target_pid = self.target_pid;
+        // 与首次扫描相同：普通组搜索每个锚点只取第一组，深度搜索取所有组合
+        query.first_match_only = !self.deep_group_results;
+
+        let strategy = match self.plan_refine_strategy(&query, &mut cursor) {
+            Ok(strategy) => strategy,
+            Err(e) => {
+                self.cancel_token = None;
+                self.shared_buffer.write_status(SearchStatus::Error);
+                self.shared_buffer.write_error_code(SearchErrorCode::InternalError);
+                return Err(e);
+            },
+        };
+        let chunk_size = self.chunk_size;
+        let read_window = self.refine_read_window;
+        let cache_pages = self.refine_cache_pages;
+
         let handle = TOKIO_RUNTIME.spawn(async move {
-            Self::run_refine_task(query, current_results, original_mode, cancel_token).await;
+            Self::run_refine_task(query, cursor, original_mode, compat, strategy, chunk_size, read_window, cache_pages, pool, cancel_token).await;
         });
 
-        self.search_handle = Some(handle);
+        self.track_search(handle);
+        Ok(())
+    }
+
+    /// Starts async refine by a fuzzy condition in either mode.
+    /// Fuzzy mode runs a normal fuzzy refine, Exact mode keeps the results exact, see `start_exact_condition_refine`.
+    pub fn start_condition_refine_async(&mut self, condition: FuzzyCondition) -> Result<()> {
+        let in_fuzzy_mode = self.result_manager.as_ref().is_some_and(|result_mgr| result_mgr.get_mode() == SearchResultMode::Fuzzy);
+        if in_fuzzy_mode {
+            self.start_fuzzy_refine_async(condition)
+        } else {
+            self.start_exact_condition_refine(condition)
+        }
+    }
+
+    /// Refines exact results by a fuzzy condition without switching to fuzzy mode.
+    ///
+    /// The previous values come from the snapshot left by the last condition refine of the same result set,
+    /// otherwise from the values cached when the results were found or last refined by value; addresses without a cached value are
+    /// only re-read and kept if readable. With neither, the condition cannot be applied: the task only takes
+    /// the snapshot (dropping unreadable addresses), sets `flags::SNAPSHOT_CAPTURED` and ends with
+    /// `SearchStatus::SnapshotOnly`; the next condition refine compares against it. See `exact_snapshot`.
+    fn start_exact_condition_refine(&mut self, condition: FuzzyCondition) -> Result<()> {
+        if !self.is_initialized() {
+            self.shared_buffer.write_status(SearchStatus::Error);
+            self.shared_buffer.write_error_code(SearchErrorCode::NotInitialized);
+            return Err(anyhow!("SearchEngineManager not initialized"));
+        }
+
+        if self.is_searching() {
+            self.shared_buffer.write_status(SearchStatus::Error);
+            self.shared_buffer.write_error_code(SearchErrorCode::AlreadySearching);
+            return Err(anyhow!("Search already in progress"));
+        }
+
+        let result_mgr = self.result_manager.as_ref().unwrap();
+        let revision = result_mgr.revision();
+        // 没有快照时不在这里展开结果，任务中按地址顺序分批读取
+        let seeds = match self.exact_snapshot.take() {
+            Some(snapshot) if snapshot.is_current(revision) => ConditionSeeds::Snapshot(snapshot.into_items()),
+            _ if !result_mgr.exact_values().is_empty() => ConditionSeeds::Cached(result_mgr.cursor(REFINE_BATCH_SIZE)?),
+            _ => ConditionSeeds::Results(result_mgr.cursor(REFINE_BATCH_SIZE)?),
+        };
+
+        if seeds.total() == 0 {
+            warn!("No exact results to refine");
+            self.shared_buffer.write_status(SearchStatus::Completed);
+            self.shared_buffer.write_found_count(0);
+            return Ok(());
+        }
+
+        let pool = self.worker_pool.current()?;
+
+        // Reset shared buffer.
+        self.shared_buffer.reset();
+        self.shared_buffer.clear_cancel_flag();
+        self.shared_buffer.write_status(SearchStatus::Searching);
+
+        let cancel_token = CancellationToken::new();
+        self.cancel_token = Some(cancel_token.clone());
+
+        let cache_pages = self.refine_cache_pages;
+        let target_pid = self.target_pid;
+        let handle = TOKIO_RUNTIME.spawn(async move {
+            Self::run_exact_condition_refine_task(seeds, condition, cache_pages, target_pid, pool, cancel_token).await;
+        });
+
+        self.track_search(handle);
         Ok(())
     }
 
+    /// 精确结果的条件改善，幸存者写回精确结果并成为新的快照
+    async fn run_exact_condition_refine_task(
+        seeds: ConditionSeeds,
+        condition: FuzzyCondition,
+        cache_pages: usize,
+        target_pid: Option<i32>,
+        pool: ScanPool,
+        cancel_token: CancellationToken,
+    ) {
+        let start_time = Instant::now();
+        let total_items = seeds.total();
+        // 没有任何旧值时只读取当前值，条件从下一次改善开始生效
+        let snapshot_only = matches!(seeds, ConditionSeeds::Results(_));
+        let cancel = CancelSource::new(cancel_token);
+        let cancel_clone = cancel.clone();
+
+        let refine_result = pool.spawn_blocking(move || -> Result<Vec<FuzzySearchResultItem>> {
+            let check_cancelled = || cancel_clone.poll();
+            // 每批读取时短暂获取读锁
+            let next_batch = |cursor: &mut ResultCursor, seeds: &mut dyn FnMut(&[ExactSearchResultItem], &SearchResultManager)| -> Result<bool> {
+                let manager = SEARCH_ENGINE_MANAGER
+                    .read()
+                    .map_err(|_| anyhow!("Failed to acquire SearchEngineManager read lock"))?;
+                let result_mgr = manager.result_manager.as_ref().ok_or_else(|| anyhow!("result_manager is None during condition refine"))?;
+                let batch = cursor.next_batch(result_mgr)?;
+                seeds(&batch, result_mgr);
+                Ok(!batch.is_empty())
+            };
+            // 参与比较的项以自身的值为旧值，只刷新的项不比较
+            let (compared, carried) = match seeds {
+                ConditionSeeds::Snapshot(items) => (items, Vec::new()),
+                ConditionSeeds::Cached(mut cursor) => {
+                    let mut cached = CachedSeeds::default();
+                    while next_batch(&mut cursor, &mut |batch, result_mgr| cached.extend(batch, result_mgr.exact_values()))? {}
+                    cached.finish()
+                },
+                ConditionSeeds::Results(mut cursor) => {
+                    let items = exact_snapshot::read_seeds(total_items, || {
+                        let mut results = Vec::new();
+                        next_batch(&mut cursor, &mut |batch, _| results.extend_from_slice(batch))?;
+                        Ok(results)
+                    })?;
+                    (Vec::new(), items)
+                },
+            };
+            let update_progress = |processed: usize, found: usize| {
+                if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
+                    let progress = ((processed as f64 / total_items as f64) * 100.0) as i32;
+                    manager.shared_buffer.update_progress(progress, processed as i32, found as i64);
+                    manager.shared_buffer.tick_heartbeat();
+                }
+            };
+            let read = |addr: u64, buffer: &mut [u8]| {
+                DRIVER_MANAGER
+                    .read()
+                    .is_ok_and(|driver_manager| driver_manager.read_target_with_qos(target_pid, addr, buffer, None, AccessQos::Bulk).is_ok())
+            };
+
+            let processed = Arc::new(AtomicUsize::new(0));
+            let mut refined = fuzzy_search::refine_against_baseline_with(
+                &compared,
+                &carried,
+                condition,
+                0,
+                cache_pages,
+                read,
+                Some(&processed),
+                None,
+                &update_progress,
+                &check_cancelled,
+            );
+            if !refined.is_sorted() {
+                refined.par_sort_unstable();
+            }
+            Ok(refined)
+        })
+        .await;
+
+        if cancel.poll() {
+            if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
+                manager.shared_buffer.write_status(SearchStatus::Cancelled);
+            }
+            info!("Exact condition refine cancelled");
+            return;
+        }
+
+        // IMPORTANT: Release write lock BEFORE setting status to COMPLETED.
+        let success = match refine_result {
+            Ok(Ok(refined)) => match SEARCH_ENGINE_MANAGER.write() {
+                Ok(mut manager) => {
+                    if let Some(ref mut result_mgr) = manager.result_manager {
+                        Self::keep_for_undo(result_mgr);
+                        let stored = result_mgr
+                            .clear()
+                            .and_then(|_| result_mgr.set_mode(SearchResultMode::Exact))
+                            .and_then(|_| result_mgr.add_results_batch(exact_snapshot::to_exact_results(&refined)));
+                        if let Err(e) = stored {
+                            error!("Failed to store exact condition refine results: {:?}", e);
+                        }
+                        let final_count = result_mgr.total_count();
+                        let revision = result_mgr.revision();
+                        info!(
+                            "Exact condition refine completed: {} -> {} results in {} ms (condition={:?}, snapshot_only={})",
+                            total_items,
+                            final_count,
+                            start_time.elapsed().as_millis(),
+                            condition,
+                            snapshot_only
+                        );
+
+                        manager.exact_snapshot = Some(ExactSnapshot::new(revision, refined));
+                        manager.shared_buffer.write_found_count(final_count as i64);
+                        manager.shared_buffer.write_progress(100);
+                        if snapshot_only {
+                            manager.shared_buffer.set_flag(flags::SNAPSHOT_CAPTURED);
+                        }
+                        true
+                    } else {
+                        error!("result_manager is None when processing exact condition refine results");
+                        false
+                    }
+                },
+                Err(e) => {
+                    error!("Failed to acquire write lock for exact condition refine results: {:?}", e);
+                    false
+                },
+            },
+            Ok(Err(e)) => {
+                error!("Exact condition refine failed: {:?}", e);
+                false
+            },
+            Err(e) => {
+                error!("Exact condition refine task failed: {:?}", e);
+                false
+            },
+        };
+
+        if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
+            if !success {
+                manager.shared_buffer.write_status(SearchStatus::Error);
+                manager.shared_buffer.write_error_code(SearchErrorCode::InternalError);
+            } else if snapshot_only {
+                manager.shared_buffer.write_status(SearchStatus::SnapshotOnly);
+            } else {
+                manager.shared_buffer.write_status(SearchStatus::Completed);
+            }
+        }
+    }
+
+    /// 按页存储的 Byte 结果的改善搜索，剩余结果不超过阈值时展开为普通精确结果
+    async fn run_byte_refine_task(
+        scanner: ByteScanner,
+        hits: ByteHitSet,
+        threshold: usize,
+        target_pid: Option<i32>,
+        pool: ScanPool,
+        cancel_token: CancellationToken,
+    ) {
+        let start_time = Instant::now();
+        let total_hits = hits.len();
+        let cancel = CancelSource::new(cancel_token);
+        let cancel_clone = cancel.clone();
+
+        let refine_result = pool.spawn_blocking(move || -> Option<ByteHitSet> {
+            let check_cancelled = || cancel_clone.poll();
+            let update_progress = |processed: usize, found: usize| {
+                if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
+                    let progress = ((processed as f64 / total_hits as f64) * 100.0) as i32;
+                    manager.shared_buffer.update_progress(progress, processed as i32, found as i64);
+                    manager.shared_buffer.tick_heartbeat();
+                }
+            };
+
+            let driver_manager = DRIVER_MANAGER.read().ok()?;
+            let refined = scanner.refine(
+                &hits,
+                |addr, buf| driver_manager.read_target_with_qos(target_pid, addr, buf, None, AccessQos::Bulk),
+                &check_cancelled,
+                &update_progress,
+            );
+            (!check_cancelled()).then_some(refined)
+        })
+        .await;
+
+        if cancel.poll() {
+            if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
+                manager.shared_buffer.write_status(SearchStatus::Cancelled);
+            }
+            info!("Byte refine cancelled");
+            return;
+        }
+
+        // IMPORTANT: Release write lock BEFORE setting status to COMPLETED.
+        let success = match refine_result {
+            Ok(Some(refined)) => match SEARCH_ENGINE_MANAGER.write() {
+                Ok(mut manager) => {
+                    if let Some(ref mut result_mgr) = manager.result_manager {
+                        let materialize = refined.len() <= threshold;
+                        Self::keep_for_undo(result_mgr);
+                        let stored = result_mgr
+                            .set_byte_hits(refined)
+                            .and_then(|_| if materialize { result_mgr.materialize_byte_hits() } else { Ok(()) });
+                        if let Err(e) = stored {
+                            error!("Failed to store refined byte hits: {:?}", e);
+                        }
+                        let final_count = result_mgr.total_count();
+                        info!(
+                            "Byte refine completed: {} -> {} results in {} ms (materialized={})",
+                            total_hits,
+                            final_count,
+                            start_time.elapsed().as_millis(),
+                            materialize
+                        );
+
+                        manager.shared_buffer.write_found_count(final_count as i64);
+                        manager.shared_buffer.write_progress(100);
+                        true
+                    } else {
+                        error!("result_manager is None when processing byte refine results");
+                        false
+                    }
+                },
+                Err(e) => {
+                    error!("Failed to acquire write lock for byte refine results: {:?}", e);
+                    false
+                },
+            },
+            Ok(None) => {
+                error!("Byte refine task produced no output");
+                false
+            },
+            Err(e) => {
+                error!("Byte refine task failed: {:?}", e);
+                false
+            },
+        };
+
+        if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
+            if success {
+                manager.shared_buffer.write_status(SearchStatus::Completed);
+            } else {
+                manager.shared_buffer.write_status(SearchStatus::Error);
+                manager.shared_buffer.write_error_code(SearchErrorCode::InternalError);
+            }
+        }
+    }
+
     /// Internal async refine task.
-    async fn run_refine_task(query: SearchQuery, current_results: Vec<ValuePair>, original_mode: SearchResultMode, cancel_token: CancellationToken) {
+    #[allow(clippy::too_many_arguments)]
+    async fn run_refine_task(
+        query: SearchQuery,
+        mut cursor: ResultCursor,
+        original_mode: SearchResultMode,
+        compat: Option<CompatPolicy>,
+        strategy: RefineStrategy,
+        chunk_size: usize,
+        read_window: usize,
+        cache_pages: usize,
+        pool: ScanPool,
+        cancel_token: CancellationToken,
+    ) {
         let start_time = Instant::now();
-        let total_addresses = current_results.len();
+        let total_addresses = cursor.total();
+        // 用特征码或文本改善模糊结果时，幸存者按这个长度读取当前值
+        let pattern_len = if query.is_text() || query.values[0].is_pattern() { query.values[0].byte_len() } else { 0 };
 
         debug!(
-            "Starting async refine search: {} values, mode={:?}, existing results={}",
+            "Starting async refine search: {} values, mode={:?}, existing results={} in {} runs, strategy={:?}",
             query.values.len(),
             query.mode,
-            total_addresses
+            total_addresses,
+            cursor.run_count(),
+            strategy
         );
 
         let processed_counter = Arc::new(AtomicUsize::new(0));
         let total_found_counter = Arc::new(AtomicUsize::new(0));
-        let cancelled = Arc::new(AtomicBool::new(false));
+        let cancel = CancelSource::new(cancel_token);
 
         let processed_clone = Arc::clone(&processed_counter);
         let found_clone = Arc::clone(&total_found_counter);
-        let cancelled_clone = Arc::clone(&cancelled);
-        let cancel_token_clone = cancel_token.clone();
-
-        let refine_result = tokio::task::spawn_blocking(move || {
-            // Check cancellation from both CancellationToken and shared buffer.
-            let check_cancelled = || -> bool {
-                if cancel_token_clone.is_cancelled() || cancelled_clone.load(AtomicOrdering::Relaxed) {
-                    return true;
-                }
-                if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
-                    if manager.shared_buffer.is_cancel_requested() {
-                        cancelled_clone.store(true, AtomicOrdering::Relaxed);
-                        return true;
-                    }
-                }
-                false
-            };
+        let cancel_clone = cancel.clone();
+
+        let refine_result = pool.spawn_blocking(move || -> Result<RefineOutcome> {
+            let check_cancelled = || cancel_clone.poll();
 
             if check_cancelled() {
-                return Vec::new();
+                return Ok((Vec::new(), None, false, None, PassLookup::Uniform(0)));
             }
 
             // Progress update callback for refine search.
@@ -546,10 +1751,42 @@ impl SearchEngineManager {
                 }
             };
 
-            let refined_results = if query.values.len() == 1 {
+            // 每批读取时短暂获取读锁，与进度回调相同
+            let mut current_results = ResultStream::new(&mut cursor, |cursor| {
+                let manager = SEARCH_ENGINE_MANAGER
+                    .read()
+                    .map_err(|_| anyhow!("Failed to acquire SearchEngineManager read lock"))?;
+                let result_mgr = manager.result_manager.as_ref().ok_or_else(|| anyhow!("result_manager is None during refine"))?;
+                cursor.next_batch(result_mgr)
+            });
+
+            let refined_results = if query.values.len() == 1 && strategy == RefineStrategy::RescanAndIntersect {
+                match DRIVER_MANAGER.read() {
+                    Ok(driver_manager) => refine_strategy::rescan_and_intersect_stream_with(
+                        &mut current_results,
+                        &query.values[0],
+                        chunk_size,
+                        REFINE_BATCH_SIZE,
+                        |addr, buf, page_status| driver_manager.read_target_with_qos(query.target_pid, addr, buf, Some(page_status), AccessQos::Bulk),
+                        &check_cancelled,
+                        &update_progress,
+                    )
+                    .unwrap_or_else(|e| {
+                        error!("Rescan refine search failed: {:?}", e);
+                        Vec::new()
+                    }),
+                    Err(e) => {
+                        error!("Failed to acquire DriverManager lock: {:?}", e);
+                        Vec::new()
+                    },
+                }
+            } else if query.values.len() == 1 {
                 single_search::refine_single_search_with_cancel(
-                    &current_results,
+                    &mut current_results,
                     &query.values[0],
+                    query.target_pid,
+                    read_window,
+                    cache_pages,
                     Some(&processed_clone),
                     Some(&found_clone),
                     &check_cancelled,
@@ -561,7 +1798,7 @@ impl SearchEngineManager {
                 })
             } else {
                 match group_search::refine_search_group_with_dfs_and_cancel(
-                    &current_results,
+                    &mut current_results,
                     &query,
                     Some(&processed_clone),
                     Some(&found_clone),
@@ -575,12 +1812,50 @@ impl SearchEngineManager {
                     },
                 }
             };
+            // 没有完整读取已有结果时不能写回，否则未读到的结果会丢失
+            let passes = current_results.finish()?;
+
+            // 在获取写锁之前按页批量读取，并响应取消
+            let (captured, compat_capture, values) = match DRIVER_MANAGER.read() {
+                Ok(driver_manager) => {
+                    let total = refined_results.len();
+                    let read = |addr: u64, buf: &mut [u8]| driver_manager.read_target_with_qos(query.target_pid, addr, buf, None, AccessQos::Bulk);
+                    let (captured, compat_capture) = Self::capture_refined_values(
+                        &refined_results,
+                        pattern_len,
+                        original_mode,
+                        compat,
+                        read,
+                        &check_cancelled,
+                        &|done| {
+                            if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
+                                manager.shared_buffer.write_phase(SearchPhase::Capturing, (done * 100 / total) as i32);
+                                manager.shared_buffer.tick_heartbeat();
+                            }
+                        },
+                    );
+                    // 仍以精确格式存储时重新缓存幸存者的当前值，之后的条件改善以它为旧值
+                    let values = if captured.is_none() && original_mode == SearchResultMode::Exact {
+                        let value_len = |value_type: ValueType| if value_type.is_variable_len() { pattern_len } else { value_type.size() };
+                        let entries: Vec<(u64, usize)> = refined_results
+                            .iter()
+                            .map(|pair| (pair.addr, value_len(pair.value_type)))
+                            .filter(|&(_, len)| len > 0)
+                            .collect();
+                        ExactValueCache::capture(&entries, read, &check_cancelled)
+                    } else {
+                        None
+                    };
+                    (captured, compat_capture, values)
+                },
+                Err(_) => (None, false, None),
+            };
 
-            refined_results
+            Ok((refined_results, captured, compat_capture, values, passes))
         })
         .await;
 
-        if cancel_token.is_cancelled() || cancelled.load(AtomicOrdering::Relaxed) {
+        if cancel.poll() {
             if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
                 manager.shared_buffer.write_status(SearchStatus::Cancelled);
             }
@@ -590,61 +1865,26 @@ impl SearchEngineManager {
 
         // IMPORTANT: Release write lock BEFORE setting status to COMPLETED.
         let success = match refine_result {
-            Ok(refined_results) => {
+            Ok(Ok((refined_results, captured, compat_captured, values, passes))) => {
                 match SEARCH_ENGINE_MANAGER.write() {
-                    Ok(mut manager) => {
-                        if let Some(ref mut result_mgr) = manager.result_manager {
-                            // Clear and update results.
-                            let _ = result_mgr.clear();
-
-                            if !refined_results.is_empty() {
-                                match original_mode {
-                                    SearchResultMode::Exact => {
-                                        let _ = result_mgr.set_mode(SearchResultMode::Exact);
-                                        let converted_results: Vec<SearchResultItem> = refined_results
-                                            .into_iter()
-                                            .map(|pair| SearchResultItem::new_exact(pair.addr, pair.value_type))
-                                            .collect();
-                                        let _ = result_mgr.add_results_batch(converted_results);
-                                    },
-                                    SearchResultMode::Fuzzy => {
-                                        let _ = result_mgr.set_mode(SearchResultMode::Fuzzy);
-                                        // Convert to FuzzySearchResultItem by reading current memory values
-                                        if let Ok(driver_manager) = DRIVER_MANAGER.read() {
-                                            let fuzzy_results: Vec<_> = refined_results
-                                                .into_iter() // todo 是否需要优化成并行的？
-                                                .filter_map(|pair| {
-                                                    let size = pair.value_type.size();
-                                                    let mut buffer = vec![0u8; size];
-                                                    if driver_manager.read_memory_unified(pair.addr, &mut buffer, None).is_ok() {
-                                                        Some(FuzzySearchResultItem::from_bytes(pair.addr, &buffer, pair.value_type))
-                                                    } else {
-                                                        None
-                                                    }
-                                                })
-                                                .collect();
-                                            let _ = result_mgr.add_fuzzy_results_batch(fuzzy_results);
-                                        }
-                                    },
-                                }
-                            } else {
-                                let _ = result_mgr.set_mode(original_mode);
+                    Ok(mut manager) => match manager.store_refined_results(refined_results, captured, compat_captured, &passes, original_mode, pattern_len) {
+                        Some(final_count) => {
+                            if let (Some(values), Some(result_mgr)) = (values, manager.result_manager.as_mut()) {
+                                result_mgr.set_exact_values(values);
                             }
-
-                            let elapsed = start_time.elapsed().as_millis() as u64;
-                            let final_count = result_mgr.total_count();
-
-                            info!("Refine search completed: {} -> {} results in {} ms", total_addresses, final_count, elapsed);
-
-                            // Update progress info but NOT status yet.
-                            manager.shared_buffer.write_found_count(final_count as i64);
-                            manager.shared_buffer.write_progress(100);
-
+                            info!(
+                                "Refine search completed: {} -> {} results in {} ms (compat_captured={})",
+                                total_addresses,
+                                final_count,
+                                start_time.elapsed().as_millis(),
+                                compat_captured
+                            );
                             true
-                        } else {
+                        },
+                        None => {
                             error!("result_manager is None when processing refine results");
                             false
-                        }
+                        },
                     },
                     Err(e) => {
                         error!("Failed to acquire write lock for refine results: {:?}", e);
@@ -653,6 +1893,10 @@ impl SearchEngineManager {
                 }
                 // Write lock released here.
             },
+            Ok(Err(e)) => {
+                error!("Failed to read results for refine: {:?}", e);
+                false
+            },
             Err(e) => {
                 error!("Refine task failed: {:?}", e);
                 false
@@ -670,11 +1914,91 @@ impl SearchEngineManager {
         }
     }
 
+
+    /// 改善搜索的幸存者需要读取当前值时读取：结果数降到阈值以下时补做延迟的兼容模式值捕获，
+    /// 原来是模糊结果时同样要读取。返回读到的值和是否是兼容模式捕获
+    pub(crate) fn capture_refined_values<R, F, P>(
+        refined_results: &[ValuePair],
+        pattern_len: usize,
+        original_mode: SearchResultMode,
+        compat: Option<CompatPolicy>,
+        read: R,
+        check_cancelled: &F,
+        on_progress: &P,
+    ) -> (Option<Vec<FuzzySearchResultItem>>, bool)
+    where
+        R: Fn(u64, &mut [u8]) -> Result<()> + Sync,
+        F: Fn() -> bool + Sync,
+        P: Fn(usize) + Sync,
+    {
+        let compat_capture = compat.is_some_and(|compat| !refined_results.is_empty() && compat.should_capture(refined_results.len()));
+        let needs_values = !refined_results.is_empty() && (compat_capture || original_mode == SearchResultMode::Fuzzy);
+        if !needs_values {
+            return (None, false);
+        }
+        (Some(capture_fuzzy_values(refined_results, pattern_len, read, check_cancelled, on_progress)), compat_capture)
+    }
+
+    /// 写回改善搜索的幸存者，幸存的结果保留原来的轮次。有读到的值时以模糊格式存储
+    /// （延迟的兼容模式转换之后可以切换到模糊搜索），兼容模式捕获还会清除延迟标记并设置 `flags::COMPAT_CAPTURED`。
+    /// 返回最终结果数，结果管理器未初始化时返回 None
+    pub(crate) fn store_refined_results(
+        &mut self,
+        refined_results: Vec<ValuePair>,
+        captured: Option<Vec<FuzzySearchResultItem>>,
+        compat_captured: bool,
+        passes: &PassLookup,
+        original_mode: SearchResultMode,
+        pattern_len: usize,
+    ) -> Option<usize> {
+        let result_mgr = self.result_manager.as_mut()?;
+        if compat_captured {
+            self.compat.mark_captured();
+        }
+
+        // Clear and update results.
+        Self::keep_for_undo(result_mgr);
+        let _ = result_mgr.clear();
+
+        if let Some(fuzzy_results) = captured {
+            let fuzzy_results = fuzzy_results.into_iter().map(|item| item.with_pass(passes.get(item.address))).collect();
+            let _ = result_mgr.set_mode(SearchResultMode::Fuzzy);
+            let _ = result_mgr.add_fuzzy_results_batch(fuzzy_results);
+            result_mgr.set_fuzzy_pattern_len((pattern_len > 0).then_some(pattern_len));
+        } else if !refined_results.is_empty() && original_mode == SearchResultMode::Exact {
+            let _ = result_mgr.set_mode(SearchResultMode::Exact);
+            let converted_results: Vec<SearchResultItem> = refined_results
+                .into_iter()
+                .map(|pair| SearchResultItem::new_exact(pair.addr, pair.value_type).with_pass(passes.get(pair.addr)))
+                .collect();
+            let _ = result_mgr.add_results_batch(converted_results);
+        } else {
+            let _ = result_mgr.set_mode(original_mode);
+        }
+        let final_count = result_mgr.total_count();
+
+        // Update progress info but NOT status yet.
+        self.shared_buffer.write_found_count(final_count as i64);
+        self.shared_buffer.write_progress(100);
+        if compat_captured {
+            self.shared_buffer.set_flag(flags::COMPAT_CAPTURED);
+        }
+        Some(final_count)
+    }
     /// Starts async fuzzy initial search. Records all values in memory regions.
     ///
     /// # Parameters
+    /// * `alignment` - Alignment of the recorded addresses, None for the value type size
     /// * `keep_results` - If true and currently in exact mode, convert exact results to fuzzy results
-    pub fn start_fuzzy_search_async(&mut self, value_type: ValueType, regions: Vec<(u64, u64)>, keep_results: bool) -> Result<()> {
+    /// * `pause_target` - If true, stop the bound process (SIGSTOP) until the initial scan finishes
+    pub fn start_fuzzy_search_async(
+        &mut self,
+        value_type: ValueType,
+        alignment: Option<usize>,
+        regions: Vec<(u64, u64)>,
+        keep_results: bool,
+        pause_target: bool,
+    ) -> Result<()> {
         if !self.is_initialized() {
             self.shared_buffer.write_status(SearchStatus::Error);
             self.shared_buffer.write_error_code(SearchErrorCode::NotInitialized);
@@ -687,53 +2011,71 @@ impl SearchEngineManager {
             return Err(anyhow!("Search already in progress"));
         }
 
+        let alignment = alignment.unwrap_or(value_type.size());
+        if value_type.is_variable_len() {
+            // 特征码和文本没有固定大小，不能从头记录，只能用 keep_results 转换特征码或文本搜索的精确结果
+            let has_exact_results = self
+                .result_manager
+                .as_ref()
+                .is_some_and(|result_mgr| result_mgr.get_mode() == SearchResultMode::Exact && result_mgr.total_count() > 0);
+            if !keep_results || !has_exact_results {
+                self.shared_buffer.write_status(SearchStatus::Error);
+                self.shared_buffer.write_error_code(SearchErrorCode::InvalidQuery);
+                return Err(anyhow!("Fuzzy search over {} values needs exact results to keep, run a pattern search first", value_type));
+            }
+        } else if let Err(e) = check_alignment(alignment, value_type.size()) {
+            self.shared_buffer.write_status(SearchStatus::Error);
+            self.shared_buffer.write_error_code(SearchErrorCode::InvalidQuery);
+            return Err(anyhow!(e));
+        }
+
         // Prepare result manager for fuzzy mode.
         let result_mgr = self
             .result_manager
             .as_mut()
             .ok_or_else(|| anyhow!("SearchEngineManager's result_manager not initialized"))?;
 
-        // Check if we need to convert exact results to fuzzy results
+        // 保留精确结果时在任务中分批读取当前值转换为模糊结果，结果集在转换完成后才替换
         if keep_results && result_mgr.get_mode() == SearchResultMode::Exact {
-            let exact_results = result_mgr.get_all_exact_results()?;
-            if !exact_results.is_empty() {
-                // Convert exact to fuzzy: need to read current values
-                let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
-
-                let mut fuzzy_results = Vec::with_capacity(exact_results.len());
-                for exact in exact_results {
-                    let size = exact.typ.size();
-                    let mut buffer = vec![0u8; size];
-
-                    if driver_manager.read_memory_unified(exact.address, &mut buffer, None).is_ok() {
-                        let fuzzy = FuzzySearchResultItem::from_bytes(exact.address, &buffer, exact.typ);
-                        fuzzy_results.push(fuzzy);
-                    }
-                }
-
-                drop(driver_manager); // Release lock before modifying result_mgr
+            let cursor = result_mgr.cursor(REFINE_BATCH_SIZE)?;
+            if cursor.total() > 0 {
+                // 特征码、文本结果按搜索时的长度读取，之后只能按 Changed / Unchanged 改善
+                let pattern_len = self.current_pattern_len.filter(|&len| len > 0);
+                let target_pid = self.target_pid;
+                let pool = self.worker_pool.current()?;
 
-                result_mgr.clear()?;
-                result_mgr.set_mode(SearchResultMode::Fuzzy)?;
-                result_mgr.add_fuzzy_results_batch(fuzzy_results)?;
+                self.shared_buffer.reset();
+                self.shared_buffer.clear_cancel_flag();
+                self.shared_buffer.write_status(SearchStatus::Searching);
 
-                info!("Converted {} exact results to fuzzy results", result_mgr.total_count());
+                let cancel_token = CancellationToken::new();
+                self.cancel_token = Some(cancel_token.clone());
 
-                // Since we already have results, just complete immediately
-                self.shared_buffer.reset();
-                self.shared_buffer.write_status(SearchStatus::Completed);
-                self.shared_buffer.write_found_count(result_mgr.total_count() as i64);
-                self.shared_buffer.write_progress(100);
+                let handle = TOKIO_RUNTIME.spawn(async move {
+                    Self::run_fuzzy_convert_task(cursor, pattern_len, target_pid, pool, cancel_token).await;
+                });
+                self.track_search(handle);
                 return Ok(());
-            } else {
-                result_mgr.clear()?;
-                result_mgr.set_mode(SearchResultMode::Fuzzy)?;
             }
-        } else {
-            result_mgr.clear()?;
-            result_mgr.set_mode(SearchResultMode::Fuzzy)?;
         }
 
+        // 模糊搜索结果本身就是模糊格式，不再有延迟的兼容转换
+        self.compat.reset();
+        self.fuzzy_resume = None;
+        result_mgr.discard_undo();
+        self.deep_group_results = false;
+
+        result_mgr.clear()?;
+        if !keep_results {
+            result_mgr.clear_labels();
+        }
+        result_mgr.set_mode(SearchResultMode::Fuzzy)?;
+        result_mgr.begin_pass();
+        // 模糊首次扫描读取绑定进程
+        self.target_pid = None;
+
+        let pool = self.worker_pool.current()?;
+
         // Reset shared buffer.
         self.shared_buffer.reset();
         self.shared_buffer.clear_cancel_flag();
@@ -743,247 +2085,940 @@ impl SearchEngineManager {
         self.cancel_token = Some(cancel_token.clone());
 
         let chunk_size = self.chunk_size;
+        let pause_guard = Self::pause_target_for_scan(pause_target, None);
 
         let handle = TOKIO_RUNTIME.spawn(async move {
-            Self::run_fuzzy_initial_task(value_type, regions, chunk_size, cancel_token).await;
+            let _pause_guard = pause_guard;
+            Self::run_fuzzy_initial_task(FuzzyResume::new(0, value_type, alignment, regions, 0), chunk_size, pool, cancel_token).await;
         });
 
-        self.search_handle = Some(handle);
+        self.track_search(handle);
+        Ok(())
+    }
+
+    /// Resumes a cancelled fuzzy initial scan, scanning only the regions it did not finish and appending results.
+    ///
+    /// Fails when there is nothing to resume or the results changed since the scan was cancelled.
+    pub fn resume_fuzzy_search_async(&mut self, pause_target: bool) -> Result<()> {
+        if !self.is_initialized() {
+            self.shared_buffer.write_status(SearchStatus::Error);
+            self.shared_buffer.write_error_code(SearchErrorCode::NotInitialized);
+            return Err(anyhow!("SearchEngineManager not initialized"));
+        }
+
+        if self.is_searching() {
+            self.shared_buffer.write_status(SearchStatus::Error);
+            self.shared_buffer.write_error_code(SearchErrorCode::AlreadySearching);
+            return Err(anyhow!("Search already in progress"));
+        }
+
+        let result_mgr = self
+            .result_manager
+            .as_mut()
+            .ok_or_else(|| anyhow!("SearchEngineManager's result_manager not initialized"))?;
+        let resume = match self.fuzzy_resume.take() {
+            Some(resume) if resume.is_current(result_mgr.revision()) && result_mgr.get_mode() == SearchResultMode::Fuzzy => resume,
+            _ => {
+                self.shared_buffer.write_status(SearchStatus::Error);
+                self.shared_buffer.write_error_code(SearchErrorCode::InvalidQuery);
+                return Err(anyhow!("No cancelled fuzzy scan to resume, or the results changed since it was cancelled"));
+            },
+        };
+        // 恢复的结果与已有的结果属于同一轮次
+        info!(
+            "Resuming fuzzy initial scan: {} of {} regions remaining, {} results kept",
+            resume.remaining.len(),
+            resume.total_regions(),
+            result_mgr.total_count()
+        );
+
+        let pool = self.worker_pool.current()?;
+
+        self.shared_buffer.reset();
+        self.shared_buffer.clear_cancel_flag();
+        self.shared_buffer.write_status(SearchStatus::Searching);
+
+        let cancel_token = CancellationToken::new();
+        self.cancel_token = Some(cancel_token.clone());
+
+        let chunk_size = self.chunk_size;
+        let pause_guard = Self::pause_target_for_scan(pause_target, None);
+
+        let handle = TOKIO_RUNTIME.spawn(async move {
+            let _pause_guard = pause_guard;
+            Self::run_fuzzy_initial_task(resume, chunk_size, pool, cancel_token).await;
+        });
+
+        self.track_search(handle);
         Ok(())
     }
 
+    /// 把精确结果转换为模糊结果：按地址顺序分批读取结果（每批短暂获取读锁），并行读取当前值，
+    /// 全部读取完成后才在写锁下替换结果集。取消或失败时精确结果保持不变。
+    ///
+    /// 转换后的模糊结果在替换前整体保存在内存中，与模糊改善的输出相同。
+    async fn run_fuzzy_convert_task(mut cursor: ResultCursor, pattern_len: Option<usize>, target_pid: Option<i32>, pool: ScanPool, cancel_token: CancellationToken) {
+        let start_time = Instant::now();
+        let total_items = cursor.total();
+        let cancel = CancelSource::new(cancel_token);
+        let cancel_clone = cancel.clone();
+
+        let convert_result = pool.clone().spawn_blocking(move || -> std::result::Result<(Vec<FuzzySearchResultItem>, bool), (SearchErrorCode, anyhow::Error)> {
+            let internal = |e: anyhow::Error| (SearchErrorCode::InternalError, e);
+            let check_cancelled = || cancel_clone.poll();
+            let read = |addr: u64, buf: &mut [u8]| {
+                DRIVER_MANAGER
+                    .read()
+                    .map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?
+                    .read_target_with_qos(target_pid, addr, buf, None, AccessQos::Bulk)
+            };
+
+            let mut converted = Vec::with_capacity(total_items);
+            let mut has_variable_len = false;
+            let mut processed = 0;
+            while !check_cancelled() {
+                let batch = {
+                    let manager = SEARCH_ENGINE_MANAGER
+                        .read()
+                        .map_err(|_| internal(anyhow!("Failed to acquire SearchEngineManager read lock")))?;
+                    let result_mgr = manager
+                        .result_manager
+                        .as_ref()
+                        .ok_or_else(|| internal(anyhow!("result_manager is None during fuzzy conversion")))?;
+                    cursor.next_batch(result_mgr).map_err(internal)?
+                };
+                if batch.is_empty() {
+                    break;
+                }
+
+                if batch.iter().any(|exact| exact.typ.is_variable_len()) {
+                    if pattern_len.is_none() {
+                        return Err((SearchErrorCode::InvalidQuery, anyhow!("Pattern length of the exact results is unknown")));
+                    }
+                    has_variable_len = true;
+                }
+                let passes = PassLookup::from_pairs(batch.iter().map(|exact| (exact.address, exact.pass)));
+                let pairs: Vec<ValuePair> = batch.iter().map(|exact| ValuePair::new(exact.address, exact.typ)).collect();
+                let update_progress = |done: usize| {
+                    if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
+                        let done = processed + done;
+                        let progress = ((done as f64 / total_items as f64) * 100.0) as i32;
+                        manager.shared_buffer.update_progress(progress, done as i32, 0);
+                        manager.shared_buffer.tick_heartbeat();
+                    }
+                };
+                let captured = capture_fuzzy_values(&pairs, pattern_len.unwrap_or(0), read, &check_cancelled, &update_progress);
+                converted.extend(captured.into_iter().map(|item| item.with_pass(passes.get(item.address))));
+                processed += batch.len();
+            }
+            Ok((converted, has_variable_len))
+        })
+        .await;
+
+        if cancel.poll() {
+            if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
+                manager.shared_buffer.write_status(SearchStatus::Cancelled);
+            }
+            info!("Exact to fuzzy conversion cancelled, exact results kept");
+            return;
+        }
+
+        // IMPORTANT: Release write lock BEFORE setting status to COMPLETED.
+        let stored = match convert_result {
+            Ok(Ok((converted, has_variable_len))) => match SEARCH_ENGINE_MANAGER.write() {
+                Ok(mut manager) => {
+                    // 模糊搜索结果本身就是模糊格式，不再有延迟的兼容转换
+                    manager.compat.reset();
+                    manager.fuzzy_resume = None;
+                    manager.deep_group_results = false;
+                    let pattern_len = pattern_len.filter(|_| has_variable_len);
+                    match manager.result_manager.as_mut() {
+                        Some(result_mgr) => {
+                            result_mgr.discard_undo();
+                            let swapped = result_mgr
+                                .clear()
+                                .and_then(|_| result_mgr.set_mode(SearchResultMode::Fuzzy))
+                                .and_then(|_| result_mgr.add_fuzzy_results_batch(converted));
+                            result_mgr.set_fuzzy_pattern_len(pattern_len);
+                            let found = result_mgr.total_count();
+                            match swapped {
+                                Ok(()) => {
+                                    info!(
+                                        "Converted {} exact results to {} fuzzy results in {} ms (pattern_len={:?})",
+                                        total_items,
+                                        found,
+                                        start_time.elapsed().as_millis(),
+                                        pattern_len
+                                    );
+                                    manager.shared_buffer.write_found_count(found as i64);
+                                    Ok(())
+                                },
+                                Err(e) => Err((SearchErrorCode::InternalError, e)),
+                            }
+                        },
+                        None => Err((SearchErrorCode::InternalError, anyhow!("result_manager is None when storing fuzzy conversion"))),
+                    }
+                },
+                Err(e) => Err((SearchErrorCode::InternalError, anyhow!("Failed to acquire write lock for fuzzy conversion: {:?}", e))),
+            },
+            Ok(Err(e)) => Err(e),
+            Err(e) => Err((SearchErrorCode::InternalError, anyhow!("Fuzzy conversion task failed: {:?}", e))),
+        };
+
+        if let Err((code, e)) = stored {
+            error!("Exact to fuzzy conversion failed: {:?}", e);
+            if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
+                manager.shared_buffer.write_status(SearchStatus::Error);
+                manager.shared_buffer.write_error_code(code);
+            }
+            return;
+        }
+
+        // 结果已经替换，只剩记录轮次
+        Self::record_fuzzy_generation_on(pool, format!("{:?}", FuzzyCondition::Initial)).await;
+        if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
+            manager.shared_buffer.write_progress(100);
+            manager.shared_buffer.write_status(SearchStatus::Completed);
+        }
+    }
+
     /// Internal async fuzzy initial scan task.
     /// 
     /// 使用流式写入策略：每个区域扫描完成后立即将结果写入 result_manager，
-    /// 避免所有结果同时存在于内存中导致 OOM。
-    async fn run_fuzzy_initial_task(value_type: ValueType, regions: Vec<(u64, u64)>, chunk_size: usize, cancel_token: CancellationToken) {
+    /// 避免所有结果同时存在于内存中导致 OOM。取消时保留已完成区域的结果，
+    /// 剩余区域记录到 `fuzzy_resume`，见 `fuzzy_resume` 模块。
+    ///
+    /// `scan.regions_done` 为之前各次扫描完成的区域数，首次扫描为 0。
+    async fn run_fuzzy_initial_task(scan: FuzzyResume, chunk_size: usize, pool: ScanPool, cancel_token: CancellationToken) {
         let start_time = Instant::now();
-        let total_regions = regions.len();
+        let FuzzyResume {
+            value_type,
+            alignment,
+            remaining: regions,
+            regions_done: regions_done_before,
+            ..
+        } = scan;
+        let total_regions = regions_done_before + regions.len();
+        let total_bytes = estimate::scan_bytes(&regions);
 
         if log_enabled!(Level::Debug) {
             debug!(
-                "Starting fuzzy initial scan (streaming): value_type={:?}, regions={}, chunk_size={} KB",
+                "Starting fuzzy initial scan (streaming): value_type={:?}, alignment={}, regions={}, chunk_size={} KB",
                 value_type,
+                alignment,
                 regions.len(),
                 chunk_size / 1024
             );
         }
 
-        let completed_regions = Arc::new(AtomicUsize::new(0));
-        let total_found_count = Arc::new(AtomicI64::new(0));
-        let cancelled = Arc::new(AtomicBool::new(false));
+        // 恢复扫描时从保留的结果数继续计数
+        let found_before = SEARCH_ENGINE_MANAGER
+            .read()
+            .ok()
+            .and_then(|manager| manager.result_manager.as_ref().map(|result_mgr| result_mgr.total_count()))
+            .unwrap_or(0);
+        let total_found_count = Arc::new(AtomicI64::new(found_before as i64));
+        let cancel = CancelSource::new(cancel_token);
+        let read_stats = Arc::new(ReadStats::new());
 
-        let completed_regions_clone = Arc::clone(&completed_regions);
         let total_found_clone = Arc::clone(&total_found_count);
-        let cancelled_clone = Arc::clone(&cancelled);
-        let cancel_token_clone = cancel_token.clone();
+        let cancel_clone = cancel.clone();
+        let read_stats_clone = Arc::clone(&read_stats);
 
         // 流式处理：顺序扫描每个区域，扫描完成后立即写入 result_manager
         // 这样可以利用 result_manager 的内存+磁盘混合存储，避免 OOM
-        let scan_result = tokio::task::spawn_blocking(move || {
-            for (idx, (start, end)) in regions.iter().enumerate() {
-                // Check cancellation
-                if cancel_token_clone.is_cancelled() || cancelled_clone.load(AtomicOrdering::Relaxed) {
-                    cancelled_clone.store(true, AtomicOrdering::Relaxed);
-                    break;
-                }
-
-                if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
-                    if manager.shared_buffer.is_cancel_requested() {
-                        cancelled_clone.store(true, AtomicOrdering::Relaxed);
-                        break;
+        let scan_result = pool.clone().spawn_blocking(move || {
+            let check_cancelled = || cancel_clone.poll();
+
+            let completed = fuzzy_resume::scan_regions_in_order(
+                &regions,
+                check_cancelled,
+                |idx, start, end| {
+                    // 扫描单个区域，返回 Vec
+                    match fuzzy_search::fuzzy_initial_scan(value_type, alignment, start, end, chunk_size, AccessQos::Bulk, Some(&check_cancelled), &read_stats_clone) {
+                        Ok(results) => results,
+                        Err(e) => {
+                            error!("Failed to fuzzy scan region {}: {:?}", idx, e);
+                            Vec::new()
+                        },
                     }
-                }
+                },
+                |idx, region_results| {
+                    let found_in_region = region_results.len();
+
+                    // 立即将结果写入 result_manager（支持磁盘溢出）
+                    if !region_results.is_empty()
+                        && let Ok(mut manager) = SEARCH_ENGINE_MANAGER.write()
+                        && let Some(ref mut result_mgr) = manager.result_manager
+                    {
+                        let pass = result_mgr.current_pass();
+                        let region_results = region_results.into_iter().map(|item| item.with_pass(pass)).collect();
+                        if let Err(e) = result_mgr.add_fuzzy_results_batch(region_results) {
+                            error!("Failed to add fuzzy results for region {}: {:?}", idx, e);
+                        }
+                    }
+                    // region_results 在这里被 drop，释放内存
 
-                // Create check_cancelled closure for this region
-                let cancelled_ref = &cancelled_clone;
-                let token_ref = &cancel_token_clone;
-                let check_cancelled_for_region = || -> bool {
-                    if token_ref.is_cancelled() || cancelled_ref.load(AtomicOrdering::Relaxed) {
-                        return true;
+                    // Update progress
+                    let completed = regions_done_before + idx + 1;
+                    let total_found = total_found_clone.fetch_add(found_in_region as i64, AtomicOrdering::Relaxed) + found_in_region as i64;
+
+                    if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
+                        let progress = ((completed as f64 / total_regions as f64) * 100.0) as i32;
+                        manager.shared_buffer.update_progress(progress, completed as i32, total_found);
+                        Self::write_scan_stats(&manager.shared_buffer, &read_stats_clone, start_time.elapsed());
+                        manager.shared_buffer.tick_heartbeat();
                     }
+                },
+            );
+
+            let mut regions = regions;
+            regions.split_off(completed)
+        })
+        .await;
+
+        // Finalize
+        let success = match scan_result {
+            // 被取消且还有未扫描的区域：保留已完成区域的结果，记录剩余区域
+            Ok(remaining) if !remaining.is_empty() && cancel.is_cancelled() => {
+                let regions_done = total_regions - remaining.len();
+                if regions_done == 0 {
                     if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
-                        if manager.shared_buffer.is_cancel_requested() {
-                            cancelled_ref.store(true, AtomicOrdering::Relaxed);
-                            return true;
+                        manager.shared_buffer.write_status(SearchStatus::Cancelled);
+                    }
+                    info!("Fuzzy initial scan cancelled");
+                    return;
+                }
+
+                let Ok(mut manager) = SEARCH_ENGINE_MANAGER.write() else {
+                    error!("Failed to acquire write lock to keep partial fuzzy results");
+                    return;
+                };
+                let Some(ref result_mgr) = manager.result_manager else {
+                    error!("result_manager is None when keeping partial fuzzy results");
+                    return;
+                };
+                let revision = result_mgr.revision();
+                let kept = result_mgr.total_count();
+                info!(
+                    "Fuzzy initial scan cancelled after {} of {} regions, keeping {} results",
+                    regions_done, total_regions, kept
+                );
+                manager.fuzzy_resume = Some(FuzzyResume::new(revision, value_type, alignment, remaining, regions_done));
+                manager.shared_buffer.write_found_count(kept as i64);
+                manager.shared_buffer.write_regions_done(regions_done as i32);
+                manager.publish_read_stats(&read_stats, kept, total_bytes, start_time.elapsed());
+                drop(manager);
+
+                if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
+                    manager.shared_buffer.write_status(SearchStatus::PartiallyCompleted);
+                }
+                return;
+            },
+            Ok(_) => match SEARCH_ENGINE_MANAGER.write() {
+                Ok(mut manager) => {
+                    let sort_results = manager.sort_fuzzy_results;
+                    if let Some(ref mut result_mgr) = manager.result_manager {
+                        // 区域按调用方给出的顺序写入且可能重叠，整理后按地址有序、没有重复地址
+                        if sort_results && let Err(e) = result_mgr.sort_fuzzy_by_address() {
+                            error!("Failed to sort fuzzy results by address: {:?}", e);
                         }
+
+                        let elapsed = start_time.elapsed().as_millis() as u64;
+                        let final_count = result_mgr.total_count();
+
+                        info!("Fuzzy initial scan completed: {} results in {} ms", final_count, elapsed);
+
+                        manager.record_scan_throughput(total_bytes, start_time.elapsed());
+                        manager.shared_buffer.write_found_count(final_count as i64);
+                        manager.shared_buffer.write_progress(100);
+                        manager.shared_buffer.write_regions_done(total_regions as i32);
+                        manager.publish_read_stats(&read_stats, final_count, total_bytes, start_time.elapsed());
+
+                        true
+                    } else {
+                        error!("result_manager is None when finalizing fuzzy results");
+                        false
                     }
+                },
+                Err(e) => {
+                    error!("Failed to acquire write lock for fuzzy finalization: {:?}", e);
                     false
-                };
+                },
+            },
+            Err(e) => {
+                error!("Fuzzy scan task failed: {:?}", e);
+                false
+            },
+        };
+        if success {
+            Self::record_fuzzy_generation_on(pool, format!("{:?}", FuzzyCondition::Initial)).await;
+        }
 
-                // 扫描单个区域，返回 Vec
-                let region_results = match fuzzy_search::fuzzy_initial_scan(
-                    value_type,
-                    *start,
-                    *end,
-                    chunk_size,
-                    Some(&check_cancelled_for_region),
-                ) {
-                    Ok(results) => results,
+        // Set status after releasing write lock.
+        if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
+            if success {
+                manager.shared_buffer.write_status(SearchStatus::Completed);
+            } else {
+                manager.shared_buffer.write_status(SearchStatus::Error);
+                manager.shared_buffer.write_error_code(SearchErrorCode::InternalError);
+            }
+        }
+    }
+
+    /// Starts async fuzzy refine search.
+    pub fn start_fuzzy_refine_async(&mut self, condition: FuzzyCondition) -> Result<()> {
+        if !self.is_initialized() {
+            self.shared_buffer.write_status(SearchStatus::Error);
+            self.shared_buffer.write_error_code(SearchErrorCode::NotInitialized);
+            return Err(anyhow!("SearchEngineManager not initialized"));
+        }
+
+        if self.is_searching() {
+            self.shared_buffer.write_status(SearchStatus::Error);
+            self.shared_buffer.write_error_code(SearchErrorCode::AlreadySearching);
+            return Err(anyhow!("Search already in progress"));
+        }
+
+        let result_mgr = self.result_manager.as_ref().unwrap();
+        if result_mgr.get_mode() != SearchResultMode::Fuzzy {
+            return Err(anyhow!("Not in fuzzy mode"));
+        }
+        self.check_variable_len_condition(condition)?;
+
+        if result_mgr.total_count() == 0 {
+            warn!("No fuzzy results to refine");
+            self.shared_buffer.write_status(SearchStatus::Completed);
+            self.shared_buffer.write_found_count(0);
+            return Ok(());
+        }
+
+        let pool = self.worker_pool.current()?;
+
+        // Reset shared buffer.
+        self.shared_buffer.reset();
+        self.shared_buffer.clear_cancel_flag();
+        self.shared_buffer.write_status(SearchStatus::Searching);
+
+        let cancel_token = CancellationToken::new();
+        self.cancel_token = Some(cancel_token.clone());
+
+        let label = format!("{:?}", condition);
+        let cache_pages = self.refine_cache_pages;
+        let target_pid = self.target_pid;
+        let handle = TOKIO_RUNTIME.spawn(async move {
+            Self::run_fuzzy_refine_in_place_task(condition, label, cache_pages, target_pid, pool, cancel_token).await;
+        });
+
+        self.track_search(handle);
+        Ok(())
+    }
+
+    /// 变长的模糊结果（特征码、文本）只能按 Changed / Unchanged 改善，其它条件在开始前拒绝
+    fn check_variable_len_condition(&self, condition: FuzzyCondition) -> Result<()> {
+        let variable_len = self.result_manager.as_ref().is_some_and(|result_mgr| result_mgr.fuzzy_pattern_len().is_some());
+        if variable_len && !matches!(condition, FuzzyCondition::Initial | FuzzyCondition::Changed | FuzzyCondition::Unchanged) {
+            self.shared_buffer.write_status(SearchStatus::Error);
+            self.shared_buffer.write_error_code(SearchErrorCode::InvalidQuery);
+            return Err(anyhow!("Pattern results only support Changed and Unchanged, got {:?}", condition));
+        }
+        Ok(())
+    }
+
+    /// Starts async fuzzy refine that compares current memory against an earlier result generation
+    /// instead of the values stored by the previous round.
+    ///
+    /// Only addresses present in both the current set and the generation are compared.
+    /// Addresses missing from the generation are kept (with refreshed values) when `keep_missing` is true,
+    /// otherwise dropped. Survivors store the current values, like a normal refine.
+    pub fn start_fuzzy_refine_against_generation(&mut self, generation_id: u32, condition: FuzzyCondition, keep_missing: bool) -> Result<()> {
+        if !self.is_initialized() {
+            self.shared_buffer.write_status(SearchStatus::Error);
+            self.shared_buffer.write_error_code(SearchErrorCode::NotInitialized);
+            return Err(anyhow!("SearchEngineManager not initialized"));
+        }
+
+        if self.is_searching() {
+            self.shared_buffer.write_status(SearchStatus::Error);
+            self.shared_buffer.write_error_code(SearchErrorCode::AlreadySearching);
+            return Err(anyhow!("Search already in progress"));
+        }
+
+        let result_mgr = self.result_manager.as_ref().unwrap();
+        if result_mgr.get_mode() != SearchResultMode::Fuzzy {
+            return Err(anyhow!("Not in fuzzy mode"));
+        }
+        self.check_variable_len_condition(condition)?;
+        let pattern_len = result_mgr.fuzzy_pattern_len().unwrap_or(0);
+
+        let current_results = result_mgr.get_all_fuzzy_results()?;
+        let generation = result_mgr.load_generation(generation_id)?;
+        let join = fuzzy_search::join_with_generation(&current_results, generation);
+        drop(current_results);
+
+        info!(
+            "Fuzzy refine against generation #{}: {} compared, {} missing (keep_missing={})",
+            generation_id,
+            join.compared.len(),
+            join.missing.len(),
+            keep_missing
+        );
+
+        let (compared, carried) = join.split(keep_missing);
+        if compared.is_empty() && carried.is_empty() {
+            warn!("No fuzzy results to refine against generation #{}", generation_id);
+            self.shared_buffer.write_status(SearchStatus::Completed);
+            self.shared_buffer.write_found_count(0);
+            return Ok(());
+        }
+
+        let pool = self.worker_pool.current()?;
+
+        // Reset shared buffer.
+        self.shared_buffer.reset();
+        self.shared_buffer.clear_cancel_flag();
+        self.shared_buffer.write_status(SearchStatus::Searching);
+
+        let cancel_token = CancellationToken::new();
+        self.cancel_token = Some(cancel_token.clone());
+
+        let label = format!("{:?} vs #{}", condition, generation_id);
+        let cache_pages = self.refine_cache_pages;
+        let target_pid = self.target_pid;
+        let handle = TOKIO_RUNTIME.spawn(async move {
+            Self::run_fuzzy_refine_task(compared, carried, condition, pattern_len, cache_pages, target_pid, label, pool, cancel_token).await;
+        });
+
+        self.track_search(handle);
+        Ok(())
+    }
+
+    /// Starts async "unchanged for N ms" refine.
+    ///
+    /// Samples the current fuzzy set every `sample_interval_ms` for `duration_ms` and drops an item
+    /// as soon as its value differs from the one read at window start. Stops early once nothing is left.
+    /// Progress reports elapsed milliseconds in `regions_done` and the remaining count in `found_count`.
+    /// Cancelling keeps the set filtered so far.
+    ///
+    /// The window start reads the stored results in batches; the items still unchanged are then held in memory
+    /// for the whole window, at most the current result count.
+    pub fn refine_stable_for(&mut self, duration_ms: u64, sample_interval_ms: u64) -> Result<()> {
+        if !self.is_initialized() {
+            self.shared_buffer.write_status(SearchStatus::Error);
+            self.shared_buffer.write_error_code(SearchErrorCode::NotInitialized);
+            return Err(anyhow!("SearchEngineManager not initialized"));
+        }
+
+        if self.is_searching() {
+            self.shared_buffer.write_status(SearchStatus::Error);
+            self.shared_buffer.write_error_code(SearchErrorCode::AlreadySearching);
+            return Err(anyhow!("Search already in progress"));
+        }
+
+        if duration_ms == 0 || sample_interval_ms == 0 {
+            self.shared_buffer.write_status(SearchStatus::Error);
+            self.shared_buffer.write_error_code(SearchErrorCode::InvalidQuery);
+            return Err(anyhow!("Duration and sample interval must be positive"));
+        }
+
+        let result_mgr = self.result_manager.as_ref().unwrap();
+        if result_mgr.get_mode() != SearchResultMode::Fuzzy {
+            self.shared_buffer.write_status(SearchStatus::Error);
+            self.shared_buffer.write_error_code(SearchErrorCode::InvalidQuery);
+            return Err(anyhow!("Not in fuzzy mode"));
+        }
+        if result_mgr.fuzzy_pattern_len().is_some() {
+            self.shared_buffer.write_status(SearchStatus::Error);
+            self.shared_buffer.write_error_code(SearchErrorCode::InvalidQuery);
+            return Err(anyhow!("Stable refine is not supported for pattern results, use Unchanged instead"));
+        }
+
+        let total_items = result_mgr.total_count();
+        if total_items == 0 {
+            warn!("No fuzzy results to refine");
+            self.shared_buffer.write_status(SearchStatus::Completed);
+            self.shared_buffer.write_found_count(0);
+            return Ok(());
+        }
+
+        let pool = self.worker_pool.current()?;
+
+        // Reset shared buffer.
+        self.shared_buffer.reset();
+        self.shared_buffer.clear_cancel_flag();
+        self.shared_buffer.write_status(SearchStatus::Searching);
+
+        let cancel_token = CancellationToken::new();
+        self.cancel_token = Some(cancel_token.clone());
+
+        let window = fuzzy_search::StableWindow {
+            duration: Duration::from_millis(duration_ms),
+            interval: Duration::from_millis(sample_interval_ms),
+        };
+        let target_pid = self.target_pid;
+        let handle = TOKIO_RUNTIME.spawn(async move {
+            Self::run_stable_refine_task(total_items, window, target_pid, pool, cancel_token).await;
+        });
+
+        self.track_search(handle);
+        Ok(())
+    }
+
+    /// Internal async "unchanged for N ms" refine task.
+    ///
+    /// 窗口起点按段读取已有结果（每段短暂获取读锁），读取失败时结果集不变。
+    async fn run_stable_refine_task(
+        total_items: usize,
+        window: fuzzy_search::StableWindow,
+        target_pid: Option<i32>,
+        pool: ScanPool,
+        cancel_token: CancellationToken,
+    ) {
+        let duration = window.duration;
+        let cancel = CancelSource::new(cancel_token);
+        let cancel_clone = cancel.clone();
+
+        debug!("Starting stable refine: {:?}, existing results={}", window, total_items);
+
+        let outcome = pool.clone().spawn_blocking(move || {
+            let start_time = Instant::now();
+
+            let read = |addr: u64, buf: &mut [u8]| -> bool {
+                match DRIVER_MANAGER.read() {
+                    Ok(driver_manager) => driver_manager.read_target_with_qos(target_pid, addr, buf, None, AccessQos::Bulk).is_ok(),
+                    Err(_) => false,
+                }
+            };
+
+            let update_progress = |elapsed: Duration, remaining: usize| {
+                if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
+                    let progress = ((elapsed.as_secs_f64() / duration.as_secs_f64()) * 100.0).min(100.0) as i32;
+                    manager
+                        .shared_buffer
+                        .update_progress(progress, elapsed.as_millis().min(i32::MAX as u128) as i32, remaining as i64);
+                    manager.shared_buffer.tick_heartbeat();
+                }
+            };
+
+            let check_cancelled = || cancel_clone.poll();
+
+            let mut fetched = 0;
+            let mut fetch_error = None;
+            let baseline = std::iter::from_fn(|| {
+                if fetched >= total_items || fetch_error.is_some() {
+                    return None;
+                }
+                let batch = SEARCH_ENGINE_MANAGER
+                    .read()
+                    .map_err(|_| anyhow!("Failed to acquire SearchEngineManager read lock"))
+                    .and_then(|manager| {
+                        let result_mgr = manager.result_manager.as_ref().ok_or_else(|| anyhow!("result_manager is None during stable refine"))?;
+                        result_mgr.get_fuzzy_results(fetched, REFINE_BATCH_SIZE.min(total_items - fetched))
+                    });
+                match batch {
+                    Ok(batch) if !batch.is_empty() => {
+                        fetched += batch.len();
+                        Some(batch)
+                    },
+                    Ok(_) => None,
                     Err(e) => {
-                        error!("Failed to fuzzy scan region {}: {:?}", idx, e);
-                        Vec::new()
+                        fetch_error = Some(e);
+                        None
                     },
-                };
+                }
+            })
+            .flatten();
+
+            let outcome = fuzzy_search::refine_stable_with(
+                baseline,
+                window,
+                read,
+                || start_time.elapsed(),
+                std::thread::sleep,
+                check_cancelled,
+                update_progress,
+            );
+            match fetch_error {
+                Some(e) => Err(e),
+                None => Ok(outcome),
+            }
+        })
+        .await;
 
-                let found_in_region = region_results.len();
+        let outcome = match outcome {
+            Ok(Ok(outcome)) => outcome,
+            Ok(Err(e)) => {
+                error!("Stable refine failed to read results: {:?}", e);
+                if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
+                    manager.shared_buffer.write_status(SearchStatus::Error);
+                    manager.shared_buffer.write_error_code(SearchErrorCode::InternalError);
+                }
+                return;
+            },
+            Err(e) => {
+                error!("Stable refine task failed: {:?}", e);
+                if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
+                    manager.shared_buffer.write_status(SearchStatus::Error);
+                    manager.shared_buffer.write_error_code(SearchErrorCode::InternalError);
+                }
+                return;
+            },
+        };
 
-                // 立即将结果写入 result_manager（支持磁盘溢出）
-                if !region_results.is_empty() {
-                    if let Ok(mut manager) = SEARCH_ENGINE_MANAGER.write() {
-                        if let Some(ref mut result_mgr) = manager.result_manager {
-                            if let Err(e) = result_mgr.add_fuzzy_results_batch(region_results) {
-                                error!("Failed to add fuzzy results for region {}: {:?}", idx, e);
-                            }
+        // 基准值还没读完就取消，结果集不变
+        if outcome.cancelled && outcome.samples == 0 {
+            if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
+                manager.shared_buffer.write_status(SearchStatus::Cancelled);
+            }
+            info!("Stable refine cancelled before the window started");
+            return;
+        }
+
+        // 取消时同样保留已经过滤掉的部分
+        let cancelled = outcome.cancelled;
+        let label = if cancelled {
+            format!("StableFor({}ms, cancelled)", duration.as_millis())
+        } else {
+            format!("StableFor({}ms)", duration.as_millis())
+        };
+
+        let success = match SEARCH_ENGINE_MANAGER.write() {
+            Ok(mut manager) => {
+                if let Some(ref mut result_mgr) = manager.result_manager {
+                    Self::keep_for_undo(result_mgr);
+                    if let Err(e) = result_mgr.replace_all_fuzzy_results(outcome.survivors) {
+                        error!("Failed to replace fuzzy results: {:?}", e);
+                        false
+                    } else {
+                        let final_count = result_mgr.total_count();
+                        info!(
+                            "Stable refine {}: {} -> {} results after {} samples",
+                            if cancelled { "cancelled" } else { "completed" },
+                            total_items,
+                            final_count,
+                            outcome.samples
+                        );
+
+                        manager.shared_buffer.write_found_count(final_count as i64);
+                        if !cancelled {
+                            manager.shared_buffer.write_progress(100);
                         }
+                        true
                     }
+                } else {
+                    error!("result_manager is None when processing stable refine results");
+                    false
                 }
-                // region_results 在这里被 drop，释放内存
+            },
+            Err(e) => {
+                error!("Failed to acquire write lock for stable refine: {:?}", e);
+                false
+            },
+        };
+        if success {
+            Self::record_fuzzy_generation_on(pool, label).await;
+        }
 
-                // Update progress
-                let completed = completed_regions_clone.fetch_add(1, AtomicOrdering::Relaxed) + 1;
-                let total_found = total_found_clone.fetch_add(found_in_region as i64, AtomicOrdering::Relaxed) + found_in_region as i64;
+        // Set status after releasing write lock.
+        if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
+            if !success {
+                manager.shared_buffer.write_status(SearchStatus::Error);
+                manager.shared_buffer.write_error_code(SearchErrorCode::InternalError);
+            } else if cancelled {
+                manager.shared_buffer.write_status(SearchStatus::Cancelled);
+            } else {
+                manager.shared_buffer.write_status(SearchStatus::Completed);
+            }
+        }
+    }
+
+    /// Lists recorded fuzzy result generations.
+    pub fn list_result_generations(&self) -> Result<Vec<ResultGeneration>> {
+        let result_mgr = self.result_manager.as_ref().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+        Ok(result_mgr.list_generations().to_vec())
+    }
+
+    /// 把当前模糊结果记录为新的一代，在扫描池的阻塞线程上调用
+    ///
+    /// 只在开始和登记时短暂持有锁，快照分段复制出来、在锁外写文件；记录期间结果被修改时放弃这一代。
+    fn record_fuzzy_generation(condition: String) -> Result<Option<u32>> {
+        let pending = {
+            let manager = SEARCH_ENGINE_MANAGER.read().map_err(|_| anyhow!("Failed to acquire SearchEngineManager read lock"))?;
+            let result_mgr = manager.result_manager.as_ref().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+            result_mgr.begin_fuzzy_generation(condition)?
+        };
+        let Some(mut pending) = pending else {
+            return Ok(None);
+        };
+        loop {
+            let batch = {
+                let manager = SEARCH_ENGINE_MANAGER.read().map_err(|_| anyhow!("Failed to acquire SearchEngineManager read lock"))?;
+                let result_mgr = manager.result_manager.as_ref().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+                result_mgr.fuzzy_generation_batch(&pending)?
+            };
+            if batch.is_empty() {
+                break;
+            }
+            pending.write_all(&batch)?;
+        }
+
+        let mut manager = SEARCH_ENGINE_MANAGER.write().map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
+        let result_mgr = manager.result_manager.as_mut().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+        result_mgr.commit_fuzzy_generation(pending).map(Some)
+    }
+
+    /// 在扫描池上记录新的一代，任务在写入完成状态之前等待它，下一次细化总能对照这一代
+    async fn record_fuzzy_generation_on(pool: ScanPool, condition: String) {
+        match pool.spawn_blocking(move || Self::record_fuzzy_generation(condition)).await {
+            Ok(Ok(_)) => {},
+            Ok(Err(e)) => error!("Failed to record result generation: {:?}", e),
+            Err(e) => error!("Result generation task failed: {:?}", e),
+        }
+    }
 
+    /// Refines the stored fuzzy results in place.
+    ///
+    /// 按段复制结果、读取当前值并比较，存活项只写回新值，删除的项记下索引，最后一次性压缩，
+    /// 不再重建整个结果集。每段只在复制和写回时短暂持有锁；取消时结果集回滚到细化之前，见 `fuzzy_search::refine_in_place_with`。
+    async fn run_fuzzy_refine_in_place_task(
+        condition: FuzzyCondition,
+        label: String,
+        cache_pages: usize,
+        target_pid: Option<i32>,
+        pool: ScanPool,
+        cancel_token: CancellationToken,
+    ) {
+        let start_time = Instant::now();
+        let cancel = CancelSource::new(cancel_token);
+        let cancel_clone = cancel.clone();
+
+        let refine_result = pool.spawn_blocking(move || -> Result<(usize, usize)> {
+            let check_cancelled = || cancel_clone.poll();
+
+            let total_items = {
+                let manager = SEARCH_ENGINE_MANAGER.read().map_err(|_| anyhow!("Failed to acquire SearchEngineManager read lock"))?;
+                manager.result_manager.as_ref().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?.total_count()
+            };
+            debug!("Starting in-place fuzzy refine: condition={:?}, existing results={}", condition, total_items);
+
+            let processed_counter = Arc::new(AtomicUsize::new(0));
+            let found_counter = AtomicUsize::new(0);
+            // 各段内部的进度参数只针对该段，这里按全局计数汇报
+            let update_progress = |_: usize, _: usize| {
                 if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
-                    let progress = ((completed as f64 / total_regions as f64) * 100.0) as i32;
-                    manager.shared_buffer.update_progress(progress, completed as i32, total_found);
+                    let processed = processed_counter.load(AtomicOrdering::Relaxed);
+                    let progress = ((processed as f64 / total_items as f64) * 100.0) as i32;
+                    manager.shared_buffer.update_progress(progress, processed as i32, found_counter.load(AtomicOrdering::Relaxed) as i64);
                     manager.shared_buffer.tick_heartbeat();
                 }
+            };
+            let lock = |f: &mut dyn FnMut(&mut SearchResultManager) -> Result<()>| -> Result<()> {
+                let mut manager = SEARCH_ENGINE_MANAGER.write().map_err(|_| anyhow!("Failed to acquire SearchEngineManager write lock"))?;
+                f(manager.result_manager.as_mut().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?)
+            };
+            let read = |addr: u64, buffer: &mut [u8]| {
+                DRIVER_MANAGER
+                    .read()
+                    .is_ok_and(|driver_manager| driver_manager.read_target_with_qos(target_pid, addr, buffer, None, AccessQos::Bulk).is_ok())
+            };
+
+            let outcome = fuzzy_search::refine_in_place_with(
+                lock,
+                condition,
+                fuzzy_search::FUZZY_REFINE_CHUNK,
+                cache_pages,
+                read,
+                &processed_counter,
+                &found_counter,
+                &update_progress,
+                &check_cancelled,
+            )?;
+            if !outcome.completed {
+                cancel_clone.cancel();
             }
 
-            !cancelled_clone.load(AtomicOrdering::Relaxed)
+            if outcome.completed
+                && let Err(e) = Self::record_fuzzy_generation(label)
+            {
+                error!("Failed to record result generation: {:?}", e);
+            }
+            let manager = SEARCH_ENGINE_MANAGER.read().map_err(|_| anyhow!("Failed to acquire SearchEngineManager read lock"))?;
+            manager.shared_buffer.write_found_count(outcome.final_count as i64);
+            Ok((outcome.total_items, outcome.final_count))
         })
         .await;
 
-        // Check if cancelled
-        if cancel_token.is_cancelled() || cancelled.load(AtomicOrdering::Relaxed) {
-            if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
-                manager.shared_buffer.write_status(SearchStatus::Cancelled);
-            }
-            info!("Fuzzy initial scan cancelled");
-            return;
-        }
-
-        // Finalize
-        let success = match scan_result {
-            Ok(completed_successfully) => {
-                if completed_successfully {
-                    match SEARCH_ENGINE_MANAGER.write() {
-                        Ok(mut manager) => {
-                            if let Some(ref mut result_mgr) = manager.result_manager {
-                                let elapsed = start_time.elapsed().as_millis() as u64;
-                                let final_count = result_mgr.total_count();
-
-                                info!("Fuzzy initial scan completed: {} results in {} ms", final_count, elapsed);
-
-                                manager.shared_buffer.write_found_count(final_count as i64);
-                                manager.shared_buffer.write_progress(100);
-                                manager.shared_buffer.write_regions_done(total_regions as i32);
-
-                                true
-                            } else {
-                                error!("result_manager is None when finalizing fuzzy results");
-                                false
-                            }
-                        },
-                        Err(e) => {
-                            error!("Failed to acquire write lock for fuzzy finalization: {:?}", e);
-                            false
-                        },
-                    }
-                } else {
-                    false
-                }
+        let cancelled = cancel.is_cancelled();
+        let success = match refine_result {
+            Ok(Ok((total_items, final_count))) => {
+                info!(
+                    "Fuzzy refine {}: {} -> {} results in {} ms",
+                    if cancelled { "cancelled" } else { "completed" },
+                    total_items,
+                    final_count,
+                    start_time.elapsed().as_millis()
+                );
+                true
+            },
+            Ok(Err(e)) => {
+                error!("Fuzzy refine failed: {:?}", e);
+                false
             },
             Err(e) => {
-                error!("Fuzzy scan task failed: {:?}", e);
+                error!("Fuzzy refine task failed: {:?}", e);
                 false
             },
         };
 
-        // Set status after releasing write lock.
         if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
-            if success {
-                manager.shared_buffer.write_status(SearchStatus::Completed);
-            } else {
+            if !success {
                 manager.shared_buffer.write_status(SearchStatus::Error);
                 manager.shared_buffer.write_error_code(SearchErrorCode::InternalError);
+            } else if cancelled {
+                manager.shared_buffer.write_status(SearchStatus::Cancelled);
+            } else {
+                manager.shared_buffer.write_progress(100);
+                manager.shared_buffer.write_status(SearchStatus::Completed);
             }
         }
     }
 
-    /// Starts async fuzzy refine search.
-    pub fn start_fuzzy_refine_async(&mut self, condition: FuzzyCondition) -> Result<()> {
-        if !self.is_initialized() {
-            self.shared_buffer.write_status(SearchStatus::Error);
-            self.shared_buffer.write_error_code(SearchErrorCode::NotInitialized);
-            return Err(anyhow!("SearchEngineManager not initialized"));
-        }
-
-        if self.is_searching() {
-            self.shared_buffer.write_status(SearchStatus::Error);
-            self.shared_buffer.write_error_code(SearchErrorCode::AlreadySearching);
-            return Err(anyhow!("Search already in progress"));
-        }
-
-        let result_mgr = self.result_manager.as_ref().unwrap();
-        if result_mgr.get_mode() != SearchResultMode::Fuzzy {
-            return Err(anyhow!("Not in fuzzy mode"));
-        }
-
-        let current_results = result_mgr.get_all_fuzzy_results()?;
-        if current_results.is_empty() {
-            warn!("No fuzzy results to refine");
-            self.shared_buffer.write_status(SearchStatus::Completed);
-            self.shared_buffer.write_found_count(0);
-            return Ok(());
-        }
-
-        // Reset shared buffer.
-        self.shared_buffer.reset();
-        self.shared_buffer.clear_cancel_flag();
-        self.shared_buffer.write_status(SearchStatus::Searching);
-
-        let cancel_token = CancellationToken::new();
-        self.cancel_token = Some(cancel_token.clone());
-
-        let handle = TOKIO_RUNTIME.spawn(async move {
-            Self::run_fuzzy_refine_task(current_results, condition, cancel_token).await;
-        });
-
-        self.search_handle = Some(handle);
-        Ok(())
-    }
-
     /// Internal async fuzzy refine task.
-    async fn run_fuzzy_refine_task(current_results: Vec<FuzzySearchResultItem>, condition: FuzzyCondition, cancel_token: CancellationToken) {
+    ///
+    /// `current_results` carry the baseline values the condition is evaluated against.
+    /// `carried` items are not compared, only re-read and kept if still readable.
+    /// `label` describes the resulting generation.
+    #[allow(clippy::too_many_arguments)]
+    async fn run_fuzzy_refine_task(
+        current_results: Vec<FuzzySearchResultItem>,
+        carried: Vec<FuzzySearchResultItem>,
+        condition: FuzzyCondition,
+        pattern_len: usize,
+        cache_pages: usize,
+        target_pid: Option<i32>,
+        label: String,
+        pool: ScanPool,
+        cancel_token: CancellationToken,
+    ) {
         let start_time = Instant::now();
-        let total_items = current_results.len();
+        let total_items = current_results.len() + carried.len();
 
         debug!("Starting fuzzy refine: condition={:?}, existing results={}", condition, total_items);
 
         let processed_counter = Arc::new(AtomicUsize::new(0));
         let total_found_counter = Arc::new(AtomicUsize::new(0));
-        let cancelled = Arc::new(AtomicBool::new(false));
+        let cancel = CancelSource::new(cancel_token);
 
         let processed_clone = Arc::clone(&processed_counter);
         let found_clone = Arc::clone(&total_found_counter);
-        let cancelled_clone = Arc::clone(&cancelled);
-        let cancel_token_clone = cancel_token.clone();
+        let cancel_clone = cancel.clone();
 
-        let refine_result = tokio::task::spawn_blocking(move || {
-            // Check cancellation.
-            if cancel_token_clone.is_cancelled() || cancelled_clone.load(AtomicOrdering::Relaxed) {
+        let refine_result = pool.clone().spawn_blocking(move || {
+            let check_cancelled = || cancel_clone.poll();
+            if check_cancelled() {
                 return Vec::new();
             }
 
-            if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
-                if manager.shared_buffer.is_cancel_requested() {
-                    cancelled_clone.store(true, AtomicOrdering::Relaxed);
-                    return Vec::new();
-                }
-            }
-
             // Progress update callback for fuzzy refine search.
             let update_progress = |processed: usize, found: usize| {
                 if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
@@ -993,36 +3028,27 @@ impl SearchEngineManager {
                 }
             };
 
-            // Create check_cancelled closure
-            let check_cancelled = || -> bool {
-                if cancel_token_clone.is_cancelled() || cancelled_clone.load(AtomicOrdering::Relaxed) {
-                    return true;
-                }
-                if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
-                    if manager.shared_buffer.is_cancel_requested() {
-                        cancelled_clone.store(true, AtomicOrdering::Relaxed);
-                        return true;
-                    }
-                }
-                false
+            let read = |addr: u64, buffer: &mut [u8]| {
+                DRIVER_MANAGER
+                    .read()
+                    .is_ok_and(|driver_manager| driver_manager.read_target_with_qos(target_pid, addr, buffer, None, AccessQos::Bulk).is_ok())
             };
-
-            fuzzy_search::fuzzy_refine_search(
+            fuzzy_search::refine_against_baseline_with(
                 &current_results,
+                &carried,
                 condition,
+                pattern_len,
+                cache_pages,
+                read,
                 Some(&processed_clone),
                 Some(&found_clone),
                 &update_progress,
-                Some(&check_cancelled),
+                &check_cancelled,
             )
-            .unwrap_or_else(|e| {
-                error!("Fuzzy refine failed: {:?}", e);
-                Vec::new()
-            })
         })
         .await;
 
-        if cancel_token.is_cancelled() || cancelled.load(AtomicOrdering::Relaxed) {
+        if cancel.poll() {
             if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
                 manager.shared_buffer.write_status(SearchStatus::Cancelled);
             }
@@ -1043,6 +3069,7 @@ impl SearchEngineManager {
                         
                         if let Some(ref mut result_mgr) = manager.result_manager {
                             let replace_start = Instant::now();
+                            Self::keep_for_undo(result_mgr);
                             if let Err(e) = result_mgr.replace_all_fuzzy_results(refined_vec) {
                                 error!("Failed to replace fuzzy results: {:?}", e);
                                 false
@@ -1075,6 +3102,9 @@ impl SearchEngineManager {
                 false
             },
         };
+        if success {
+            Self::record_fuzzy_generation_on(pool, label).await;
+        }
 
         // Set status after releasing write lock.
         if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
@@ -1111,8 +3141,9 @@ impl SearchEngineManager {
             return Err(anyhow!("Empty pattern"));
         }
 
-        // 保存 pattern 长度
+        // 保存 pattern，改善时重新匹配
         self.current_pattern_len = Some(pattern.len());
+        self.current_pattern = Some(pattern.clone());
 
         // Prepare result manager
         let result_mgr = self
@@ -1121,7 +3152,15 @@ impl SearchEngineManager {
             .ok_or_else(|| anyhow!("SearchEngineManager's result_manager not initialized"))?;
 
         result_mgr.clear()?;
+        result_mgr.clear_labels();
+        result_mgr.discard_undo();
+        self.deep_group_results = false;
+        self.target_pid = None;
         result_mgr.set_mode(SearchResultMode::Exact)?;
+        result_mgr.begin_pass();
+        self.compat.reset();
+
+        let pool = self.worker_pool.current()?;
 
         // Reset shared buffer
         self.shared_buffer.reset();
@@ -1134,10 +3173,10 @@ impl SearchEngineManager {
         let chunk_size = self.chunk_size;
 
         let handle = TOKIO_RUNTIME.spawn(async move {
-            Self::run_pattern_search_task(pattern, regions, chunk_size, cancel_token).await;
+            Self::run_pattern_search_task(pattern, regions, chunk_size, pool, cancel_token).await;
         });
 
-        self.search_handle = Some(handle);
+        self.track_search(handle);
         Ok(())
     }
 
@@ -1146,12 +3185,12 @@ impl SearchEngineManager {
         pattern: Vec<(u8, u8)>,
         regions: Vec<(u64, u64)>,
         chunk_size: usize,
+        pool: ScanPool,
         cancel_token: CancellationToken,
     ) {
-        use super::pattern_search;
-
         let start_time = Instant::now();
         let total_regions = regions.len();
+        let total_bytes = estimate::scan_bytes(&regions);
 
         if log_enabled!(Level::Debug) {
             debug!(
@@ -1162,91 +3201,31 @@ impl SearchEngineManager {
             );
         }
 
-        let completed_regions = Arc::new(AtomicUsize::new(0));
-        let total_found_count = Arc::new(AtomicI64::new(0));
-        let cancelled = Arc::new(AtomicBool::new(false));
-
-        let completed_regions_clone = Arc::clone(&completed_regions);
-        let total_found_clone = Arc::clone(&total_found_count);
-        let cancelled_clone = Arc::clone(&cancelled);
-        let cancel_token_clone = cancel_token.clone();
-
-        let search_result = tokio::task::spawn_blocking(move || {
-            let mut all_results: Vec<u64> = regions
-                .par_iter()
-                .enumerate()
-                .filter_map(|(idx, (start, end))| {
-                    // Check cancellation
-                    if cancel_token_clone.is_cancelled() || cancelled_clone.load(AtomicOrdering::Relaxed) {
-                        cancelled_clone.store(true, AtomicOrdering::Relaxed);
-                        return None;
-                    }
-
-                    if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
-                        if manager.shared_buffer.is_cancel_requested() {
-                            cancelled_clone.store(true, AtomicOrdering::Relaxed);
-                            return None;
-                        }
-                    }
-
-                    let check_cancelled_for_region = || -> bool {
-                        if cancel_token_clone.is_cancelled() || cancelled_clone.load(AtomicOrdering::Relaxed) {
-                            cancelled_clone.store(true, AtomicOrdering::Relaxed);
-                            return true;
-                        }
-                        if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
-                            if manager.shared_buffer.is_cancel_requested() {
-                                cancelled_clone.store(true, AtomicOrdering::Relaxed);
-                                return true;
-                            }
-                        }
-                        false
-                    };
-
-                    let result = pattern_search::search_region_pattern_with_cancel(
-                        &pattern,
-                        *start,
-                        *end,
-                        chunk_size,
-                        &check_cancelled_for_region,
-                    );
-
-                    let region_results = match result {
-                        Ok(results) => results,
-                        Err(e) => {
-                            error!("Failed to search pattern in region {}: {:?}", idx, e);
-                            Vec::new()
-                        },
-                    };
-
-                    // Update progress
-                    let completed = completed_regions_clone.fetch_add(1, AtomicOrdering::Relaxed) + 1;
-                    let found_in_region = region_results.len() as i64;
-                    let total_found = total_found_clone.fetch_add(found_in_region, AtomicOrdering::Relaxed) + found_in_region;
-
-                    if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
-                        let progress = ((completed as f64 / total_regions as f64) * 100.0) as i32;
-                        manager.shared_buffer.update_progress(progress, completed as i32, total_found);
-                        manager.shared_buffer.tick_heartbeat();
-                    }
-
-                    Some(region_results)
-                })
-                .reduce(Vec::new, |mut a, mut b| {
-                    a.append(&mut b);
-                    a
-                });
-
-            // Sort and dedup
-            all_results.sort_unstable();
-            all_results.dedup();
+        let cancel = CancelSource::new(cancel_token);
+        let cancel_clone = cancel.clone();
 
-            all_results
+        let search_result = pool.spawn_blocking(move || {
+            let addrs = Self::search_pattern_regions(&pattern, &regions, chunk_size, &cancel_clone);
+            let check_cancelled = || cancel_clone.poll();
+            if check_cancelled() {
+                return (addrs, None);
+            }
+            // 缓存匹配到的字节供显示使用
+            let entries: Vec<(u64, usize)> = addrs.iter().map(|&addr| (addr, pattern.len())).collect();
+            let values = ExactValueCache::capture(
+                &entries,
+                |addr, buf| {
+                    let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
+                    driver_manager.read_memory_with_qos(addr, buf, None, AccessQos::Bulk)
+                },
+                &check_cancelled,
+            );
+            (addrs, values)
         })
         .await;
 
         // Check if cancelled
-        if cancel_token.is_cancelled() || cancelled.load(AtomicOrdering::Relaxed) {
+        if cancel.poll() {
             if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
                 manager.shared_buffer.write_status(SearchStatus::Cancelled);
             }
@@ -1256,48 +3235,244 @@ impl SearchEngineManager {
 
         // Process results
         let (final_count, success) = match search_result {
-            Ok(all_results) => {
+            Ok((all_results, values)) => {
                 match SEARCH_ENGINE_MANAGER.write() {
                     Ok(mut manager) => {
                         if let Some(ref mut result_mgr) = manager.result_manager {
                             // Convert addresses to SearchResultItem with Pattern type
+                            let pass = result_mgr.current_pass();
                             let converted_results: Vec<_> = all_results
                                 .into_iter()
-                                .map(|addr| SearchResultItem::new_exact(addr, ValueType::Pattern))
+                                .map(|addr| SearchResultItem::new_exact(addr, ValueType::Pattern).with_pass(pass))
                                 .collect();
 
                             if let Err(e) = result_mgr.add_results_batch(converted_results) {
                                 error!("Failed to add pattern results: {:?}", e);
                             }
+                            if let Some(values) = values {
+                                result_mgr.set_exact_values(values);
+                            }
+
+                            let elapsed = start_time.elapsed().as_millis() as u64;
+                            let final_count = result_mgr.total_count();
+
+                            info!("Pattern search completed: {} results in {} ms", final_count, elapsed);
+
+                            manager.record_scan_throughput(total_bytes, start_time.elapsed());
+                            manager.shared_buffer.write_found_count(final_count as i64);
+                            manager.shared_buffer.write_progress(100);
+                            manager.shared_buffer.write_regions_done(total_regions as i32);
+
+                            (final_count as i64, true)
+                        } else {
+                            error!("result_manager is None when processing pattern results");
+                            (0, false)
+                        }
+                    },
+                    Err(e) => {
+                        error!("Failed to acquire write lock for pattern results: {:?}", e);
+                        (0, false)
+                    },
+                }
+            },
+            Err(e) => {
+                error!("Pattern search task failed: {:?}", e);
+                (0, false)
+            },
+        };
+
+        // Set status after releasing write lock
+        if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
+            if success {
+                manager.shared_buffer.write_status(SearchStatus::Completed);
+            } else {
+                manager.shared_buffer.write_status(SearchStatus::Error);
+                manager.shared_buffer.write_error_code(SearchErrorCode::InternalError);
+            }
+        }
+    }
+
+    /// Starts async pattern replace: searches the pattern, then patches every match.
+    ///
+    /// `None` bytes in `replacement` keep the original byte. Each match is re-read and
+    /// re-checked before patching, and a match whose write fails is restored, so a site is
+    /// either fully patched or left untouched. Patched sites become the result set and
+    /// their count is reported through the shared buffer's found count.
+    ///
+    /// # Parameters
+    /// * `pattern` - Pattern bytes as (value, mask) pairs
+    /// * `replacement` - Replacement bytes, no longer than the pattern
+    /// * `regions` - Memory regions to search
+    pub fn start_pattern_replace_async(&mut self, pattern: Vec<(u8, u8)>, replacement: Vec<Option<u8>>, regions: Vec<(u64, u64)>) -> Result<()> {
+        if !self.is_initialized() {
+            self.shared_buffer.write_status(SearchStatus::Error);
+            self.shared_buffer.write_error_code(SearchErrorCode::NotInitialized);
+            return Err(anyhow!("SearchEngineManager not initialized"));
+        }
+
+        if self.is_searching() {
+            self.shared_buffer.write_status(SearchStatus::Error);
+            self.shared_buffer.write_error_code(SearchErrorCode::AlreadySearching);
+            return Err(anyhow!("Search already in progress"));
+        }
+
+        if let Err(e) = pattern_replace::validate_replacement(&pattern, &replacement) {
+            self.shared_buffer.write_status(SearchStatus::Error);
+            self.shared_buffer.write_error_code(SearchErrorCode::InvalidQuery);
+            return Err(e);
+        }
+
+        self.current_pattern_len = Some(pattern.len());
+        self.current_pattern = Some(pattern.clone());
+
+        let result_mgr = self
+            .result_manager
+            .as_mut()
+            .ok_or_else(|| anyhow!("SearchEngineManager's result_manager not initialized"))?;
+
+        result_mgr.clear()?;
+        result_mgr.clear_labels();
+        result_mgr.discard_undo();
+        self.deep_group_results = false;
+        self.target_pid = None;
+        result_mgr.set_mode(SearchResultMode::Exact)?;
+        result_mgr.begin_pass();
+        self.compat.reset();
+
+        let pool = self.worker_pool.current()?;
+
+        self.shared_buffer.reset();
+        self.shared_buffer.clear_cancel_flag();
+        self.shared_buffer.write_status(SearchStatus::Searching);
+
+        let cancel_token = CancellationToken::new();
+        self.cancel_token = Some(cancel_token.clone());
+
+        let chunk_size = self.chunk_size;
+
+        let handle = TOKIO_RUNTIME.spawn(async move {
+            Self::run_pattern_replace_task(pattern, replacement, regions, chunk_size, pool, cancel_token).await;
+        });
+
+        self.track_search(handle);
+        Ok(())
+    }
+
+    /// Internal async pattern replace task.
+    async fn run_pattern_replace_task(
+        pattern: Vec<(u8, u8)>,
+        replacement: Vec<Option<u8>>,
+        regions: Vec<(u64, u64)>,
+        chunk_size: usize,
+        pool: ScanPool,
+        cancel_token: CancellationToken,
+    ) {
+        let start_time = Instant::now();
+        let total_regions = regions.len();
+
+        let cancel = CancelSource::new(cancel_token);
+        let cancel_clone = cancel.clone();
+
+        let patch_result = pool.spawn_blocking(move || -> Result<(Vec<u64>, PatchStats)> {
+            let matches = Self::search_pattern_regions(&pattern, &regions, chunk_size, &cancel_clone);
+            if cancel_clone.is_cancelled() {
+                return Ok((Vec::new(), PatchStats::default()));
+            }
+
+            let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
+            let mut patched = Vec::new();
+            let mut stats = PatchStats::default();
+            // 按地址顺序逐个修补，重叠的匹配在前一个修补后重新校验
+            for &address in &matches {
+                if cancel_clone.poll() {
+                    break;
+                }
+                let outcome = pattern_replace::patch_match_with(
+                    address,
+                    &pattern,
+                    &replacement,
+                    *PAGE_SIZE,
+                    |addr, buf| {
+                        let mut page_status = crate::wuwa::PageStatusBitmap::new(buf.len(), addr as usize);
+                        driver_manager.read_memory_with_qos(addr, buf, Some(&mut page_status), AccessQos::Interactive)?;
+                        // 匹配跨越的页必须全部读取成功
+                        let spanned_pages = ((addr as usize & (*PAGE_SIZE - 1)) + buf.len()).div_ceil(*PAGE_SIZE);
+                        if page_status.success_count() < spanned_pages {
+                            return Err(anyhow!("Partially unreadable match at 0x{:X}", addr));
+                        }
+                        Ok(())
+                    },
+                    |addr, bytes| driver_manager.write_memory_unified(addr, bytes),
+                );
+                stats.record(outcome);
+                if outcome == PatchOutcome::Patched {
+                    patched.push(address);
+                }
+            }
+            Ok((patched, stats))
+        })
+        .await;
 
-                            let elapsed = start_time.elapsed().as_millis() as u64;
-                            let final_count = result_mgr.total_count();
+        // 取消发生在修补过程中时，已修补的地址仍然保存
+        let (patched, stats) = match patch_result {
+            Ok(Ok(result)) => result,
+            Ok(Err(e)) => {
+                error!("Pattern replace failed: {:?}", e);
+                Self::fail_pattern_replace();
+                return;
+            },
+            Err(e) => {
+                error!("Pattern replace task failed: {:?}", e);
+                Self::fail_pattern_replace();
+                return;
+            },
+        };
 
-                            info!("Pattern search completed: {} results in {} ms", final_count, elapsed);
+        if patched.is_empty() && cancel.is_cancelled() {
+            if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
+                manager.shared_buffer.write_status(SearchStatus::Cancelled);
+            }
+            info!("Pattern replace cancelled");
+            return;
+        }
 
-                            manager.shared_buffer.write_found_count(final_count as i64);
-                            manager.shared_buffer.write_progress(100);
-                            manager.shared_buffer.write_regions_done(total_regions as i32);
+        let success = match SEARCH_ENGINE_MANAGER.write() {
+            Ok(mut manager) => {
+                if let Some(ref mut result_mgr) = manager.result_manager {
+                    let pass = result_mgr.current_pass();
+                    let patched_count = patched.len();
+                    let converted_results: Vec<_> = patched
+                        .into_iter()
+                        .map(|addr| SearchResultItem::new_exact(addr, ValueType::Pattern).with_pass(pass))
+                        .collect();
+                    if let Err(e) = result_mgr.add_results_batch(converted_results) {
+                        error!("Failed to add patched sites: {:?}", e);
+                    }
 
-                            (final_count as i64, true)
-                        } else {
-                            error!("result_manager is None when processing pattern results");
-                            (0, false)
-                        }
-                    },
-                    Err(e) => {
-                        error!("Failed to acquire write lock for pattern results: {:?}", e);
-                        (0, false)
-                    },
+                    info!(
+                        "Pattern replace completed: {} patched, {} unchanged, {} no longer matching, {} failed in {} ms",
+                        stats.patched,
+                        stats.unchanged,
+                        stats.mismatched,
+                        stats.failed,
+                        start_time.elapsed().as_millis()
+                    );
+
+                    manager.shared_buffer.write_found_count(patched_count as i64);
+                    manager.shared_buffer.write_progress(100);
+                    manager.shared_buffer.write_regions_done(total_regions as i32);
+                    true
+                } else {
+                    error!("result_manager is None when processing patched sites");
+                    false
                 }
             },
             Err(e) => {
-                error!("Pattern search task failed: {:?}", e);
-                (0, false)
+                error!("Failed to acquire write lock for patched sites: {:?}", e);
+                false
             },
         };
 
-        // Set status after releasing write lock
         if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
             if success {
                 manager.shared_buffer.write_status(SearchStatus::Completed);
@@ -1308,6 +3483,70 @@ impl SearchEngineManager {
         }
     }
 
+    fn fail_pattern_replace() {
+        if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
+            manager.shared_buffer.write_status(SearchStatus::Error);
+            manager.shared_buffer.write_error_code(SearchErrorCode::InternalError);
+        }
+    }
+
+    /// 并行搜索所有区域的特征码，返回排序去重后的匹配地址，并更新进度
+    fn search_pattern_regions(
+        pattern: &[(u8, u8)],
+        regions: &[(u64, u64)],
+        chunk_size: usize,
+        cancel: &CancelSource,
+    ) -> Vec<u64> {
+        use super::pattern_search;
+
+        let total_regions = regions.len();
+        let completed_regions = AtomicUsize::new(0);
+        let total_found_count = AtomicI64::new(0);
+
+        let mut all_results: Vec<u64> = regions
+            .par_iter()
+            .enumerate()
+            .filter_map(|(idx, (start, end))| {
+                let check_cancelled = || cancel.poll();
+                if check_cancelled() {
+                    return None;
+                }
+
+                let result = pattern_search::search_region_pattern_with_cancel(pattern, *start, *end, chunk_size, AccessQos::Bulk, &check_cancelled);
+
+                let region_results = match result {
+                    Ok(results) => results,
+                    Err(e) => {
+                        error!("Failed to search pattern in region {}: {:?}", idx, e);
+                        Vec::new()
+                    },
+                };
+
+                // Update progress
+                let completed = completed_regions.fetch_add(1, AtomicOrdering::Relaxed) + 1;
+                let found_in_region = region_results.len() as i64;
+                let total_found = total_found_count.fetch_add(found_in_region, AtomicOrdering::Relaxed) + found_in_region;
+
+                if let Ok(manager) = SEARCH_ENGINE_MANAGER.read() {
+                    let progress = ((completed as f64 / total_regions as f64) * 100.0) as i32;
+                    manager.shared_buffer.update_progress(progress, completed as i32, total_found);
+                    manager.shared_buffer.tick_heartbeat();
+                }
+
+                Some(region_results)
+            })
+            .reduce(Vec::new, |mut a, mut b| {
+                a.append(&mut b);
+                a
+            });
+
+        // Sort and dedup
+        all_results.sort_unstable();
+        all_results.dedup();
+
+        all_results
+    }
+
     /// Legacy synchronous search method. Kept for backward compatibility.
     #[deprecated]
     pub fn search_memory(
@@ -1320,7 +3559,12 @@ impl SearchEngineManager {
         let result_mgr = self.result_manager.as_mut().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
 
         result_mgr.clear()?;
+        result_mgr.clear_labels();
+        result_mgr.discard_undo();
+        self.deep_group_results = use_deep_search && query.values.len() > 1;
+        self.target_pid = query.target_pid;
         result_mgr.set_mode(SearchResultMode::Exact)?;
+        let pass = result_mgr.begin_pass();
 
         let start_time = Instant::now();
 
@@ -1390,7 +3634,7 @@ impl SearchEngineManager {
 
         let converted_results: Vec<_> = all_results
             .into_iter()
-            .map(|pair| SearchResultItem::new_exact(pair.addr, pair.value_type))
+            .map(|pair| SearchResultItem::new_exact(pair.addr, pair.value_type).with_pass(pass))
             .collect();
         result_mgr.add_results_batch(converted_results)?;
 
@@ -1414,15 +3658,102 @@ impl SearchEngineManager {
         result_mgr.get_results(start, size)
     }
 
+    /// 地址在 [start_addr, end_addr) 内的结果，不受过滤器和显示顺序影响
+    pub fn get_results_in_range(&self, start_addr: u64, end_addr: u64, max_count: usize) -> Result<AddressRangeResults> {
+        let result_mgr = self.result_manager.as_ref().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+
+        result_mgr.get_results_in_range(start_addr, end_addr, max_count)
+    }
+
+    /// 第一个地址不小于 addr 的结果在结果存储中的索引，没有时为结果总数
+    pub fn find_index_by_address(&self, addr: u64) -> Result<usize> {
+        let result_mgr = self.result_manager.as_ref().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+
+        result_mgr.find_index_by_address(addr)
+    }
+
+    /// 按内存区域分组当前结果（当前绑定进程的区域映射）
+    pub fn get_region_groups(&self) -> Result<Arc<Vec<RegionGroup>>> {
+        self.region_groups_with(&Self::bound_region_map()?)
+    }
+
+    /// 分组内分页，返回 (结果存储中的索引, 结果)
+    pub fn get_results_for_group(&self, group_index: usize, start: usize, size: usize) -> Result<Vec<(usize, SearchResultItem)>> {
+        self.results_for_group_with(&Self::bound_region_map()?, group_index, start, size)
+    }
+
+    fn bound_region_map() -> Result<Arc<RegionMap>> {
+        let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager read lock"))?;
+        current_region_map(&driver_manager)
+    }
+
+    /// 用指定的区域映射分组，按结果集版本缓存
+    pub(crate) fn region_groups_with(&self, map: &Arc<RegionMap>) -> Result<Arc<Vec<RegionGroup>>> {
+        const BATCH: usize = 64 * 1024;
+
+        let result_mgr = self.result_manager.as_ref().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+        self.region_groups.get_or_build(result_mgr.revision(), map, || {
+            let total = result_mgr.total_count();
+            let mut builder = RegionGroupBuilder::new(map);
+            let mut offset = 0;
+            while offset < total {
+                let batch = result_mgr.get_results(offset, BATCH)?;
+                if batch.is_empty() {
+                    break;
+                }
+                offset += batch.len();
+                for item in batch {
+                    builder.push(match item {
+                        SearchResultItem::Exact(exact) => exact.address,
+                        SearchResultItem::Fuzzy(fuzzy) => fuzzy.address,
+                    });
+                }
+            }
+            let groups = builder.finish();
+            debug!("Grouped {} results into {} regions", total, groups.len());
+            Ok(groups)
+        })
+    }
+
+    pub(crate) fn results_for_group_with(&self, map: &Arc<RegionMap>, group_index: usize, start: usize, size: usize) -> Result<Vec<(usize, SearchResultItem)>> {
+        let groups = self.region_groups_with(map)?;
+        let group = groups.get(group_index).ok_or_else(|| anyhow!("Invalid region group index: {}", group_index))?;
+        if start >= group.count {
+            return Ok(Vec::new());
+        }
+
+        let result_mgr = self.result_manager.as_ref().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+        let first = group.first_index + start;
+        let results = result_mgr.get_results(first, size.min(group.count - start))?;
+        Ok(results.into_iter().enumerate().map(|(i, item)| (first + i, item)).collect())
+    }
+
+    /// 校验当前结果文件，附带启动时的恢复报告
+    pub fn verify_result_store(&self) -> Result<ResultStoreReport> {
+        let result_mgr = self.result_manager.as_ref().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+
+        Ok(result_mgr.verify_integrity())
+    }
+
+    /// 结果总数；过滤器启用时为过滤视图中的数量
     pub fn get_total_count(&self) -> Result<usize> {
         let result_mgr = self.result_manager.as_ref().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
 
+        if self.filter.is_active() {
+            return Ok(self.filtered_index()?.len());
+        }
         Ok(result_mgr.total_count())
     }
 
     pub fn clear_results(&mut self) -> Result<()> {
         let result_mgr = self.result_manager.as_mut().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
 
+        self.compat.reset();
+        self.xor_key = XorKey::default();
+        self.deep_group_results = false;
+        self.target_pid = None;
+        result_mgr.clear_labels();
+        result_mgr.discard_undo();
         result_mgr.clear()
     }
 
@@ -1444,16 +3775,208 @@ impl SearchEngineManager {
         result_mgr.keep_only_results(keep_indices)
     }
 
+    /// 按句柄删除结果（句柄见 `result_handle`），返回删除的数量
+    pub fn remove_by_handles(&mut self, handles: Vec<u64>) -> Result<usize> {
+        let result_mgr = self.result_manager.as_mut().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+
+        result_mgr.remove_by_handles(handles)
+    }
+
+    /// 只保留句柄对应的结果，返回保留的数量
+    pub fn keep_only_handles(&mut self, handles: Vec<u64>) -> Result<usize> {
+        let result_mgr = self.result_manager.as_mut().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+
+        result_mgr.keep_only_handles(handles)
+    }
+
+    /// 给句柄对应的结果设置标签，空字符串删除标签
+    pub fn set_result_label(&mut self, handle: u64, label: String) -> Result<()> {
+        let result_mgr = self.result_manager.as_mut().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+
+        result_mgr.set_label(handle, label)
+    }
+
+    /// 句柄对应结果的标签，没有标签或结果已被删除时为 None
+    pub fn get_result_labels(&self, handles: &[u64]) -> Result<Vec<Option<String>>> {
+        let result_mgr = self.result_manager.as_ref().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+
+        result_mgr.get_labels(handles)
+    }
+
+    /// 是否有可以撤销的改善搜索
+    pub fn can_undo_refine(&self) -> bool {
+        !self.is_searching() && self.result_manager.as_ref().is_some_and(|result_mgr| result_mgr.can_undo())
+    }
+
+    /// 撤销最近一次改善搜索，换回改善前的结果集，返回恢复后的结果数量
+    pub fn undo_last_refine(&mut self) -> Result<usize> {
+        if self.is_searching() {
+            return Err(anyhow!("Search already in progress"));
+        }
+        let result_mgr = self.result_manager.as_mut().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+
+        result_mgr.undo_refine()
+    }
+
+    /// 改善搜索写入新结果之前把当前结果集移入撤销槽，失败时只是这次改善不能撤销
+    fn keep_for_undo(result_mgr: &mut SearchResultManager) {
+        if let Err(e) = result_mgr.stash_for_undo() {
+            warn!("Failed to keep results for undo, this refine cannot be undone: {:?}", e);
+        }
+    }
+
+    /// 地址和类型对应结果的标签，用于显示
+    pub fn result_label(&self, address: u64, value_type: ValueType) -> Option<&str> {
+        self.result_manager.as_ref()?.label(address, value_type)
+    }
+
+    /// 结果存储中位置上结果的稳定句柄
+    pub fn result_handle(&self, index: usize) -> Option<u64> {
+        self.result_manager.as_ref()?.handle_at(index)
+    }
+
+    /// 句柄对应的结果，已被删除时返回 None
+    pub fn get_result_by_handle(&self, handle: u64) -> Result<Option<SearchResultItem>> {
+        let result_mgr = self.result_manager.as_ref().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+
+        match result_mgr.position_of_handle(handle) {
+            Some(index) => Ok(result_mgr.get_results(index, 1)?.into_iter().next()),
+            None => Ok(None),
+        }
+    }
+
     pub fn set_result_mode(&mut self, mode: SearchResultMode) -> Result<()> {
         let result_mgr = self.result_manager.as_mut().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
 
         result_mgr.set_mode(mode)
     }
 
+    /// 把当前结果集（连同模式和特征码长度）导出到文件，返回导出的结果数
+    pub fn export_results(&self, path: PathBuf) -> Result<usize> {
+        let result_mgr = self.result_manager.as_ref().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+        if self.is_searching() {
+            return Err(anyhow!("Search already in progress"));
+        }
+
+        result_mgr.export_to_file(&path, self.current_pattern_len)
+    }
+
+    /// 把当前结果集导出为 CSV 或 JSON Lines，Xor 结果按当前密钥解码。返回写出的行数
+    pub fn export_results_text(&self, path: PathBuf, format: ResultExportFormat) -> Result<usize> {
+        let result_mgr = self.result_manager.as_ref().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+        if self.is_searching() {
+            return Err(anyhow!("Search already in progress"));
+        }
+
+        result_mgr.export(&path, format, self.xor_key)
+    }
+
+    /// 从导出文件恢复结果集，替换当前结果；文件中的模式与当前不同时切换模式。返回导入的结果数
+    pub fn import_results(&mut self, path: PathBuf) -> Result<usize> {
+        if self.is_searching() {
+            return Err(anyhow!("Search already in progress"));
+        }
+        let result_mgr = self.result_manager.as_mut().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+
+        self.compat.reset();
+        // 导入的结果属于绑定进程
+        self.target_pid = None;
+        let header = result_mgr.import_from_file(&path)?;
+        if header.mode == SearchResultMode::Fuzzy {
+            // 导出时的特征码长度对定长的模糊结果没有意义
+            let variable_len = result_mgr.get_fuzzy_results(0, 1)?.first().is_some_and(|item| {
+                let vt = item.value_type;
+                vt.is_variable_len()
+            });
+            result_mgr.set_fuzzy_pattern_len(header.pattern_len.filter(|_| variable_len));
+        }
+        self.current_pattern_len = header.pattern_len;
+        self.current_pattern = None;
+        Ok(header.count)
+    }
+
+    /// 把当前会话（结果集、模式、特征码长度和兼容模式状态）保存到目录，返回保存的结果数
+    /// 相对路径保存在缓存目录的 sessions 子目录下，目录中已有的会话被覆盖
+    pub fn save_session(&self, path: PathBuf) -> Result<usize> {
+        let result_mgr = self.result_manager.as_ref().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+        let cache_dir = self.cache_dir.as_ref().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+        if self.is_searching() {
+            return Err(anyhow!("Search already in progress"));
+        }
+
+        let dir = session::session_dir(cache_dir, &path);
+        std::fs::create_dir_all(&dir).map_err(|e| anyhow!("Failed to create session directory {:?}: {}", dir, e))?;
+        // 先删除旧的元数据，结果文件写到一半失败时不会留下看起来完整的会话
+        let _ = std::fs::remove_file(dir.join(SESSION_META_FILE));
+
+        let count = result_mgr.export_to_file(&dir.join(SESSION_RESULTS_FILE), self.current_pattern_len)?;
+        let meta = SessionMeta {
+            mode: result_mgr.get_mode(),
+            compat_requested: self.compat.is_requested(),
+            compat_deferred: self.compat.is_deferred(),
+            pattern_len: self.current_pattern_len,
+            fuzzy_pattern_len: result_mgr.fuzzy_pattern_len(),
+            count,
+        };
+        meta.save(&dir)?;
+        info!("Saved search session with {} {:?} results to {:?}", count, meta.mode, dir);
+        Ok(count)
+    }
+
+    /// 载入 `save_session` 保存的会话，替换当前结果集并恢复模式、特征码长度和兼容模式状态。返回载入的结果数
+    pub fn load_session(&mut self, path: PathBuf) -> Result<usize> {
+        if self.is_searching() {
+            return Err(anyhow!("Search already in progress"));
+        }
+        let cache_dir = self.cache_dir.as_ref().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+        let dir = session::session_dir(cache_dir, &path);
+        let meta = SessionMeta::load(&dir)?;
+        let result_mgr = self.result_manager.as_mut().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+
+        self.compat.reset();
+        self.current_pattern = None;
+        self.target_pid = None;
+        let header = result_mgr.import_from_file(&dir.join(SESSION_RESULTS_FILE))?;
+        if header.mode != meta.mode || header.count != meta.count || result_mgr.total_count() != meta.count {
+            result_mgr.clear()?;
+            self.current_pattern_len = None;
+            return Err(anyhow!(
+                "Search session {:?} does not match its results: expected {} {:?} results, found {} {:?}",
+                dir,
+                meta.count,
+                meta.mode,
+                header.count,
+                header.mode
+            ));
+        }
+
+        result_mgr.set_fuzzy_pattern_len(meta.fuzzy_pattern_len);
+        self.current_pattern_len = meta.pattern_len;
+        self.compat.restore(meta.compat_requested, meta.compat_deferred);
+        info!("Loaded search session with {} {:?} results from {:?}", meta.count, meta.mode, dir);
+        Ok(meta.count)
+    }
+
+    /// 把结果中落在 module 旧映射 [old_base, old_base + 模块大小) 内的地址平移到 new_base，
+    /// 用于进程重启后导入的结果文件。模块大小取绑定进程中的当前映射，返回平移的结果数
+    pub fn rebase_results(&mut self, module: &str, old_base: u64, new_base: u64) -> Result<usize> {
+        if self.is_searching() {
+            return Err(anyhow!("Search already in progress"));
+        }
+        let result_mgr = self.result_manager.as_mut().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+        let size = {
+            let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
+            AddressRebase::for_module(&driver_manager, module, old_base)?.size
+        };
+        result_mgr.rebase(&AddressRebase::new(old_base, new_base, size))
+    }
+
+    /// 手动添加结果，作为新的一轮
     pub fn add_results_batch(&mut self, results: Vec<SearchResultItem>) -> Result<()> {
         let result_mgr = self.result_manager.as_mut().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
 
-        result_mgr.add_results_batch(results)
+        let pass = result_mgr.begin_pass();
+        result_mgr.add_results_batch(results.into_iter().map(|item| item.with_pass(pass)).collect())
     }
 
     pub fn set_filter(
@@ -1470,12 +3993,14 @@ impl SearchEngineManager {
 
         self.filter.enable_type_filter = enable_type_filter;
         self.filter.type_ids = type_ids.iter().filter_map(|&id| ValueType::from_id(id)).collect();
+        self.filtered_index.invalidate();
 
         Ok(())
     }
 
     pub fn clear_filter(&mut self) -> Result<()> {
         self.filter.clear();
+        self.filtered_index.invalidate();
         Ok(())
     }
 
@@ -1483,6 +4008,136 @@ impl SearchEngineManager {
         &self.filter
     }
 
+    /// 设置轮次过滤范围（包含两端），None 表示该端不限制
+    pub fn set_pass_filter(&mut self, min_pass: Option<u8>, max_pass: Option<u8>) {
+        self.filter.min_pass = min_pass;
+        self.filter.max_pass = max_pass;
+        self.filtered_index.invalidate();
+    }
+
+    pub fn set_result_order(&mut self, order: ResultOrder) {
+        self.result_order = order;
+    }
+
+    pub fn get_result_order(&self) -> ResultOrder {
+        self.result_order
+    }
+
+    /// 按显示顺序取一页，返回 (结果存储中的索引, 结果)
+    ///
+    /// 过滤器启用时在过滤视图上分页，`start`/`size` 是过滤视图中的位置，每页都是满的；
+    /// 返回的索引仍是结果存储中的索引，删除结果时直接使用。
+    pub fn get_filtered_results(&self, start: usize, size: usize) -> Result<Vec<(usize, SearchResultItem)>> {
+        let result_mgr = self.result_manager.as_ref().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+
+        if self.filter.is_active() {
+            let index = self.filtered_index()?;
+            let end = start.saturating_add(size).min(index.len());
+            if start >= end {
+                return Ok(Vec::new());
+            }
+            let indices: Vec<usize> = index[start..end].iter().map(|&index| index as usize).collect();
+            return Self::results_at(result_mgr, &indices);
+        }
+
+        match self.result_order {
+            ResultOrder::Storage => Ok(result_mgr
+                .get_results(start, size)?
+                .into_iter()
+                .enumerate()
+                .map(|(i, item)| (start + i, item))
+                .collect()),
+            ResultOrder::OldestFirst => {
+                let indices = self.pass_order()?.page(start, size, result_mgr.total_count());
+                Self::results_at(result_mgr, &indices)
+            },
+        }
+    }
+
+    /// 精确结果扫描时缓存的值（见 ExactValueCache），长度至少为结果值的长度
+    pub fn exact_value(&self, addr: u64) -> Option<&[u8]> {
+        self.result_manager.as_ref()?.exact_value(addr)
+    }
+
+    /// 重新读取当前显示顺序中 [start, start + count) 的精确结果的值并更新缓存，返回更新的数量
+    ///
+    /// 没有缓存值的结果显示时本来就读取当前内存，不需要刷新；模糊结果的值是下一次改善的旧值，不在这里改写。
+    pub fn refresh_result_values<R>(&mut self, start: usize, count: usize, read: R) -> Result<usize>
+    where
+        R: Fn(u64, &mut [u8]) -> Result<()>,
+    {
+        let results = self.get_filtered_results(start, count)?;
+        let pattern_len = self.current_pattern_len.unwrap_or(0);
+        let result_mgr = self.result_manager.as_mut().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+
+        let mut buffer = Vec::new();
+        let mut refreshed = 0;
+        for (_, item) in results {
+            let SearchResultItem::Exact(exact) = item else {
+                continue;
+            };
+            let size = if exact.typ.is_variable_len() { pattern_len } else { exact.typ.size() };
+            if size == 0 || result_mgr.exact_value(exact.address).is_none() {
+                continue;
+            }
+            buffer.resize(size, 0);
+            if read(exact.address, &mut buffer).is_ok() && result_mgr.update_exact_value(exact.address, &buffer) {
+                refreshed += 1;
+            }
+        }
+        Ok(refreshed)
+    }
+
+    /// 过滤视图：满足过滤条件的存储索引，按当前显示顺序排列
+    fn filtered_index(&self) -> Result<Arc<Vec<u32>>> {
+        let result_mgr = self.result_manager.as_ref().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+        self.filtered_index.get_or_build(result_mgr.revision(), self.result_order, || {
+            let positions = result_mgr.build_filtered_index(&self.filter)?;
+            Ok(match self.result_order {
+                ResultOrder::Storage => positions,
+                ResultOrder::OldestFirst => self.pass_order()?.arrange(positions),
+            })
+        })
+    }
+
+    /// "最旧的在前"的显示顺序，按结果集版本缓存
+    fn pass_order(&self) -> Result<Arc<PassOrder>> {
+        const BATCH: usize = 64 * 1024;
+
+        let result_mgr = self.result_manager.as_ref().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
+        self.pass_order.get_or_build(result_mgr.revision(), || {
+            let total = result_mgr.total_count();
+            let mut passes = Vec::with_capacity(total);
+            let mut offset = 0;
+            while offset < total {
+                let batch = result_mgr.get_results(offset, BATCH)?;
+                if batch.is_empty() {
+                    break;
+                }
+                offset += batch.len();
+                passes.extend(batch.iter().map(SearchResultItem::pass));
+            }
+            Ok(PassOrder::build(&passes))
+        })
+    }
+
+    /// 按存储索引读取结果，连续的索引合并为一次读取
+    fn results_at(result_mgr: &SearchResultManager, indices: &[usize]) -> Result<Vec<(usize, SearchResultItem)>> {
+        let mut results = Vec::with_capacity(indices.len());
+        let mut i = 0;
+        while i < indices.len() {
+            let first = indices[i];
+            let mut len = 1;
+            while i + len < indices.len() && indices[i + len] == first + len {
+                len += 1;
+            }
+            let run = result_mgr.get_results(first, len)?;
+            results.extend(run.into_iter().enumerate().map(|(k, item)| (first + k, item)));
+            i += len;
+        }
+        Ok(results)
+    }
+
     pub fn get_current_mode(&self) -> Result<SearchResultMode> {
         let result_mgr = self.result_manager.as_ref().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
 
@@ -1492,14 +4147,16 @@ impl SearchEngineManager {
     /// Legacy synchronous refine search method.
     #[deprecated]
     pub fn refine_search(&mut self, query: &SearchQuery, callback: Option<Arc<dyn SearchProgressCallback>>) -> Result<usize> {
+        // 改善读取首次搜索的进程
+        let query = &query.clone().with_target_pid(self.target_pid);
         let result_mgr = self.result_manager.as_mut().ok_or_else(|| anyhow!("SearchEngineManager not initialized"))?;
 
-        let current_results: Vec<_> = match result_mgr.get_mode() {
-            SearchResultMode::Exact => result_mgr
-                .get_all_exact_results()?
-                .into_iter()
-                .map(|result| ValuePair::new(result.address, result.typ))
-                .collect(),
+        let (current_results, passes): (Vec<_>, PassLookup) = match result_mgr.get_mode() {
+            SearchResultMode::Exact => {
+                let results = result_mgr.get_all_exact_results()?;
+                let passes = PassLookup::from_pairs(results.iter().map(|result| (result.address, result.pass)));
+                (results.into_iter().map(|result| ValuePair::new(result.address, result.typ)).collect(), passes)
+            },
             SearchResultMode::Fuzzy => {
                 return Err(anyhow!("FuzzySearchResultManager not implemented yet"));
             },
@@ -1523,13 +4180,31 @@ impl SearchEngineManager {
         let processed_counter = Arc::new(AtomicUsize::new(0));
         let total_found_counter = Arc::new(AtomicUsize::new(0));
 
+        Self::keep_for_undo(result_mgr);
         result_mgr.clear()?;
         result_mgr.set_mode(SearchResultMode::Exact)?;
 
         let refined_results = if query.values.len() == 1 {
-            single_search::refine_single_search(&current_results, &query.values[0], Some(&processed_counter), Some(&total_found_counter))?
+            single_search::refine_single_search_with_cancel(
+                current_results,
+                &query.values[0],
+                self.target_pid,
+                0,
+                self.refine_cache_pages,
+                Some(&processed_counter),
+                Some(&total_found_counter),
+                &|| false,
+                &|_, _| {},
+            )?
         } else {
-            let results = group_search::refine_search_group_with_dfs(&current_results, query, Some(&processed_counter), Some(&total_found_counter))?;
+            let results = group_search::refine_search_group_with_dfs_and_cancel(
+                current_results,
+                query,
+                Some(&processed_counter),
+                Some(&total_found_counter),
+                &|| false,
+                &|_, _| {},
+            )?;
 
             results.into_iter().cloned().collect()
         };
@@ -1539,7 +4214,7 @@ impl SearchEngineManager {
         if !refined_results.is_empty() {
             let converted_results: Vec<SearchResultItem> = refined_results
                 .into_iter()
-                .map(|pair| SearchResultItem::new_exact(pair.addr, pair.value_type))
+                .map(|pair| SearchResultItem::new_exact(pair.addr, pair.value_type).with_pass(passes.get(pair.addr)))
                 .collect();
             result_mgr.add_results_batch(converted_results)?;
         }
@@ -1583,6 +4258,27 @@ impl SearchEngineManager {
     //     )
     // }
 
+    #[cfg(test)]
+    pub(crate) fn result_manager_mut(&mut self) -> Option<&mut SearchResultManager> {
+        self.result_manager.as_mut()
+    }
+
+    #[cfg(test)]
+    pub(crate) fn compat_mut(&mut self) -> &mut CompatPolicy {
+        &mut self.compat
+    }
+
+    /// 把 handle 当作正在运行的搜索记录下来，返回它的取消令牌
+    #[cfg(test)]
+    pub(crate) fn track_test_search(&mut self, handle: JoinHandle<()>) -> (u64, CancellationToken) {
+        let cancel_token = CancellationToken::new();
+        self.cancel_token = Some(cancel_token.clone());
+        self.shared_buffer.reset();
+        self.shared_buffer.write_status(SearchStatus::Searching);
+        self.track_search(handle);
+        (self.search_id, cancel_token)
+    }
+
     #[cfg(test)]
     pub fn try_match_group_at_address(buffer: &[u8], addr: u64, query: &SearchQuery) -> Option<Vec<usize>> {
         group_search::try_match_group_at_address(buffer, addr, query)
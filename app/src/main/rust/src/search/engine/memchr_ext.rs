@@ -2,36 +2,60 @@ use memchr::*;
 
 pub trait MemchrExt {
     fn find_aligned(&self, needle: &[u8], align: usize) -> Vec<usize>;
+
+    /// 用 memchr 找首字节（`first` 中任一个，忽略大小写时为大小写两种形式），
+    /// 再由 `matches` 验证长度为 `len` 的窗口
+    fn find_aligned_with<M>(&self, first: (u8, u8), len: usize, align: usize, matches: M) -> Vec<usize>
+    where
+        M: Fn(&[u8]) -> bool;
 }
 
 impl MemchrExt for [u8] {
     fn find_aligned(&self, needle: &[u8], align: usize) -> Vec<usize> {
         find_aligned_internal(self, needle, align)
     }
+
+    fn find_aligned_with<M>(&self, first: (u8, u8), len: usize, align: usize, matches: M) -> Vec<usize>
+    where
+        M: Fn(&[u8]) -> bool,
+    {
+        find_aligned_with_internal(self, first, len, align, matches)
+    }
 }
 
 fn find_aligned_internal(haystack: &[u8], needle: &[u8], align: usize) -> Vec<usize> {
     if needle.is_empty() {
         return vec![];
     }
-    if needle.len() > haystack.len() {
+    find_aligned_with_internal(haystack, (needle[0], needle[0]), needle.len(), align, |window| window == needle)
+}
+
+fn find_aligned_with_internal<M>(haystack: &[u8], first: (u8, u8), len: usize, align: usize, matches: M) -> Vec<usize>
+where
+    M: Fn(&[u8]) -> bool,
+{
+    if len == 0 || len > haystack.len() {
         return vec![];
     }
 
-    let first = needle[0];
     let mut out = Vec::new();
-
-    for pos in memchr_iter(first, haystack) {
-        if pos % align != 0 {
-            continue;
+    let mut check = |pos: usize| {
+        if !pos.is_multiple_of(align) {
+            return;
         }
-        let end = pos + needle.len();
+        let end = pos + len;
         if end > haystack.len() {
-            continue;
+            return;
         }
-        if &haystack[pos..end] == needle {
+        if matches(&haystack[pos..end]) {
             out.push(pos);
         }
+    };
+
+    if first.0 == first.1 {
+        memchr_iter(first.0, haystack).for_each(&mut check);
+    } else {
+        memchr2_iter(first.0, first.1, haystack).for_each(&mut check);
     }
     out
 }
@@ -1,16 +1,37 @@
 //! Search engine implementation modules.
 
 pub(crate) mod batch_reader;
+pub mod byte_search;
+pub(crate) mod cancel;
+pub mod compat;
+pub mod estimate;
+pub(crate) mod exact_snapshot;
 pub mod filter;
+pub(crate) mod fuzzy_resume;
 pub mod fuzzy_search;
+pub mod group_match;
 pub mod group_search;
 pub mod manager;
 mod memchr_ext;
+pub mod pattern_replace;
 pub mod pattern_search;
+pub mod pressure;
+pub(crate) mod provenance;
+pub mod read_stats;
+pub mod refine_strategy;
+pub mod region_groups;
+pub(crate) mod result_commit;
+pub(crate) mod result_stream;
+pub mod scan_cache;
+pub mod session;
 pub mod shared_buffer;
+pub(crate) mod simd_scan;
 pub mod single_search;
+pub mod watchdog;
 
 pub use crate::core::globals::{PAGE_MASK, PAGE_SIZE};
-pub use filter::SearchFilter;
+pub use compat::{CompatibilityState, DEFAULT_COMPAT_AUTO_THRESHOLD};
+pub use estimate::{ScanEstimate, ScanWarning};
+pub use filter::{ResultOrder, SearchFilter};
 pub use manager::{SearchEngineManager, SearchProgressCallback, ValuePair, BPLUS_TREE_ORDER, SEARCH_ENGINE_MANAGER};
-pub use shared_buffer::{SearchErrorCode, SearchStatus, SharedBuffer, SHARED_BUFFER_SIZE};
+pub use shared_buffer::{SearchErrorCode, SearchPhase, SearchStatus, SharedBuffer, SHARED_BUFFER_SIZE};
@@ -0,0 +1,116 @@
+//! 特征码替换
+//!
+//! 先用特征码搜索找到所有匹配，再逐个修补。每个匹配重新读取整个特征码范围，确认仍然匹配后
+//! 按替换字节修改（`None` 保留原字节），修改后的范围按页拆开依次写入；某一页写入失败时，
+//! 把已经写入的页恢复为原字节，保证一个匹配要么完整修补，要么保持原样。
+
+use anyhow::{Result, anyhow};
+use log::{debug, warn};
+
+/// 单个匹配的修补结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatchOutcome {
+    /// 已修补
+    Patched,
+    /// 替换后与原字节相同，没有写入
+    Unchanged,
+    /// 搜索之后内存已变化，不再匹配特征码
+    Mismatch,
+    /// 读取失败
+    ReadFailed,
+    /// 写入失败，已写入的部分已恢复
+    WriteFailed,
+}
+
+/// 修补统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PatchStats {
+    pub patched: usize,
+    pub unchanged: usize,
+    pub mismatched: usize,
+    pub failed: usize,
+}
+
+impl PatchStats {
+    pub fn record(&mut self, outcome: PatchOutcome) {
+        match outcome {
+            PatchOutcome::Patched => self.patched += 1,
+            PatchOutcome::Unchanged => self.unchanged += 1,
+            PatchOutcome::Mismatch => self.mismatched += 1,
+            PatchOutcome::ReadFailed | PatchOutcome::WriteFailed => self.failed += 1,
+        }
+    }
+}
+
+/// 替换不能比特征码长，也不能全部是保留字节
+pub fn validate_replacement(pattern: &[(u8, u8)], replacement: &[Option<u8>]) -> Result<()> {
+    if pattern.is_empty() {
+        return Err(anyhow!("Empty pattern"));
+    }
+    if replacement.len() > pattern.len() {
+        return Err(anyhow!("Replacement is longer than pattern ({} > {})", replacement.len(), pattern.len()));
+    }
+    if replacement.iter().all(Option::is_none) {
+        return Err(anyhow!("Replacement keeps every byte"));
+    }
+    Ok(())
+}
+
+/// 按替换修改原字节，`None` 保留原字节
+pub fn apply_replacement(original: &[u8], replacement: &[Option<u8>]) -> Vec<u8> {
+    original.iter().zip(replacement).map(|(&byte, patch)| patch.unwrap_or(byte)).collect()
+}
+
+/// 修补一个匹配
+///
+/// `read` 读取完整的特征码范围，`write` 写入一段连续字节；替换范围跨页时按页写入，失败则回滚。
+pub fn patch_match_with<R, W>(address: u64, pattern: &[(u8, u8)], replacement: &[Option<u8>], page_size: usize, read: R, mut write: W) -> PatchOutcome
+where
+    R: FnOnce(u64, &mut [u8]) -> Result<()>,
+    W: FnMut(u64, &[u8]) -> Result<()>,
+{
+    let mut current = vec![0u8; pattern.len()];
+    if let Err(e) = read(address, &mut current) {
+        debug!("Pattern replace: failed to read 0x{:X}: {:?}", address, e);
+        return PatchOutcome::ReadFailed;
+    }
+    let still_matches = current.iter().zip(pattern).all(|(&byte, &(value, mask))| byte & mask == value & mask);
+    if !still_matches {
+        return PatchOutcome::Mismatch;
+    }
+
+    let original = &current[..replacement.len()];
+    let patched = apply_replacement(original, replacement);
+    if patched == original {
+        return PatchOutcome::Unchanged;
+    }
+
+    let segments = page_segments(address, patched.len(), page_size);
+    for (written, &(offset, len)) in segments.iter().enumerate() {
+        let segment_addr = address + offset as u64;
+        if let Err(e) = write(segment_addr, &patched[offset..offset + len]) {
+            warn!("Pattern replace: failed to write 0x{:X}: {:?}", segment_addr, e);
+            for &(offset, len) in &segments[..written] {
+                if let Err(e) = write(address + offset as u64, &original[offset..offset + len]) {
+                    warn!("Pattern replace: failed to restore 0x{:X}: {:?}", address + offset as u64, e);
+                }
+            }
+            return PatchOutcome::WriteFailed;
+        }
+    }
+    PatchOutcome::Patched
+}
+
+/// 把 [address, address + len) 按页边界切成 (偏移, 长度)
+fn page_segments(address: u64, len: usize, page_size: usize) -> Vec<(usize, usize)> {
+    let mut segments = Vec::new();
+    let mut offset = 0;
+    while offset < len {
+        let addr = address + offset as u64;
+        let to_page_end = page_size - (addr % page_size as u64) as usize;
+        let segment = to_page_end.min(len - offset);
+        segments.push((offset, segment));
+        offset += segment;
+    }
+    segments
+}
@@ -2,7 +2,7 @@
 //!
 //! 在内存中搜索匹配特征码的地址
 
-use crate::core::DRIVER_MANAGER;
+use crate::core::{AccessQos, DRIVER_MANAGER};
 use crate::search::{PAGE_SIZE, PAGE_MASK};
 use crate::wuwa::PageStatusBitmap;
 use anyhow::{anyhow, Result};
@@ -169,12 +169,13 @@ fn match_pattern_at(data: &[u8], pattern: &[(u8, u8)]) -> bool {
     })
 }
 
-/// 搜索单个内存区域
+/// 搜索单个内存区域，每块按 `qos` 读取
 pub fn search_region_pattern(
     pattern: &[(u8, u8)],
     start: u64,
     end: u64,
     chunk_size: usize,
+    qos: AccessQos,
 ) -> Result<Vec<u64>> {
     let driver_manager = DRIVER_MANAGER.read()
         .map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
@@ -194,7 +195,7 @@ pub fn search_region_pattern(
 
         let mut page_status = PageStatusBitmap::new(chunk_len, current as usize);
 
-        match driver_manager.read_memory_unified(current, &mut chunk_buffer[..chunk_len], Some(&mut page_status)) {
+        match driver_manager.read_memory_with_qos(current, &mut chunk_buffer[..chunk_len], Some(&mut page_status), qos) {
             Ok(_) => {
                 if page_status.success_count() > 0 {
                     search_pattern_in_buffer(
@@ -221,12 +222,13 @@ pub fn search_region_pattern(
     Ok(results)
 }
 
-/// 带取消支持的特征码搜索
+/// 带取消支持的特征码搜索，每块按 `qos` 读取
 pub fn search_region_pattern_with_cancel<F>(
     pattern: &[(u8, u8)],
     start: u64,
     end: u64,
     chunk_size: usize,
+    qos: AccessQos,
     check_cancelled: &F,
 ) -> Result<Vec<u64>>
 where
@@ -234,7 +236,29 @@ where
 {
     let driver_manager = DRIVER_MANAGER.read()
         .map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
+    search_region_pattern_with(
+        pattern,
+        start,
+        end,
+        chunk_size,
+        |addr, buf, page_status| driver_manager.read_memory_with_qos(addr, buf, Some(page_status), qos),
+        check_cancelled,
+    )
+}
 
+/// 与 `search_region_pattern_with_cancel` 相同，但每块通过 `read` 读取（按页记录到 `page_status`）
+pub(crate) fn search_region_pattern_with<R, F>(
+    pattern: &[(u8, u8)],
+    start: u64,
+    end: u64,
+    chunk_size: usize,
+    mut read: R,
+    check_cancelled: &F,
+) -> Result<Vec<u64>>
+where
+    R: FnMut(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+    F: Fn() -> bool + Sync,
+{
     let pattern_len = pattern.len();
     if pattern_len == 0 {
         return Err(anyhow!("Empty pattern"));
@@ -254,7 +278,7 @@ where
 
         let mut page_status = PageStatusBitmap::new(chunk_len, current as usize);
 
-        match driver_manager.read_memory_unified(current, &mut chunk_buffer[..chunk_len], Some(&mut page_status)) {
+        match read(current, &mut chunk_buffer[..chunk_len], &mut page_status) {
             Ok(_) => {
                 if page_status.success_count() > 0 {
                     search_pattern_in_buffer(
@@ -0,0 +1,103 @@
+//! Memory pressure ladder for the first scan
+//!
+//! 首次扫描在每个区域开始前查询 `MemoryPressureGuard`，按压力逐级降级，只升不降：
+//! 1. Buffering：结果提交线程可以把结果留在内存里，扫描结束后做值捕获
+//! 2. Streaming（超过软限制）：提交线程把留在内存里的结果立即写入结果管理器，之后到达的结果直接写入，
+//!    块缓冲缩小
+//! 3. Stopped（超过硬限制）：不再开始新的区域，已经开始的区域照常完成并写入
+//!
+//! 结果的写入由 `result_commit::ResultCommitter` 负责，这里只决定阶段。
+
+use crate::core::memory_pressure::{MemoryPressureGuard, PressureLevel};
+use log::info;
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+
+/// 降级后的块大小下限
+const MIN_DEGRADED_CHUNK: usize = 64 * 1024;
+
+/// 降级阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(u8)]
+pub enum ScanStage {
+    Buffering = 0,
+    Streaming = 1,
+    Stopped = 2,
+}
+
+impl ScanStage {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => ScanStage::Buffering,
+            1 => ScanStage::Streaming,
+            _ => ScanStage::Stopped,
+        }
+    }
+}
+
+/// 扫描结束时的降级情况
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PressureReport {
+    pub stage: ScanStage,
+    /// 因硬限制没有扫描的区域数
+    pub skipped_regions: usize,
+}
+
+pub(crate) struct PressureLadder<'a> {
+    guard: &'a MemoryPressureGuard,
+    base_chunk: usize,
+    stage: AtomicU8,
+    skipped_regions: AtomicUsize,
+}
+
+impl<'a> PressureLadder<'a> {
+    pub fn new(guard: &'a MemoryPressureGuard, base_chunk: usize) -> Self {
+        Self {
+            guard,
+            base_chunk,
+            stage: AtomicU8::new(ScanStage::Buffering as u8),
+            skipped_regions: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn stage(&self) -> ScanStage {
+        ScanStage::from_u8(self.stage.load(Ordering::Acquire))
+    }
+
+    /// 区域开始前调用，返回该区域使用的块大小；None 表示不再开始新的区域
+    pub fn before_region(&self) -> Option<usize> {
+        match self.guard.level() {
+            PressureLevel::Hard => self.escalate(ScanStage::Stopped),
+            PressureLevel::Soft => self.escalate(ScanStage::Streaming),
+            PressureLevel::Normal => {},
+        }
+
+        match self.stage() {
+            ScanStage::Buffering => Some(self.base_chunk),
+            ScanStage::Streaming => Some(self.degraded_chunk()),
+            ScanStage::Stopped => {
+                self.skipped_regions.fetch_add(1, Ordering::Relaxed);
+                None
+            },
+        }
+    }
+
+    /// 扫描结束时的降级情况
+    pub fn finish(self) -> PressureReport {
+        PressureReport {
+            stage: self.stage(),
+            skipped_regions: self.skipped_regions.load(Ordering::Relaxed),
+        }
+    }
+
+    fn degraded_chunk(&self) -> usize {
+        (self.base_chunk / 4).max(MIN_DEGRADED_CHUNK).min(self.base_chunk)
+    }
+
+    fn escalate(&self, to: ScanStage) {
+        let previous = ScanStage::from_u8(self.stage.fetch_max(to as u8, Ordering::AcqRel));
+        if previous >= to {
+            return;
+        }
+        info!("Memory pressure: scan {:?} -> {:?} (rss={} MB)", previous, to, self.guard.rss() / (1024 * 1024));
+    }
+}
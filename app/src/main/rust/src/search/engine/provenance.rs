@@ -0,0 +1,151 @@
+//! Per-result pass provenance
+//!
+//! 每条结果记录它在第几轮进入结果集（`SearchResultManager::begin_pass`）：首次扫描为 0，
+//! 之后每次可能新增结果的操作（保留结果的扫描、手动添加）递增。细化搜索不产生新结果，
+//! 幸存的结果保留原来的轮次，所以多次细化后仍能区分"首次扫描就在"和"后来加入"的地址。
+//!
+//! 精确结果的细化以 `ValuePair` 进行，不携带轮次，写回时用 `PassLookup` 按地址找回；
+//! 模糊结果的细化直接携带原来的结果项。
+
+use anyhow::{Result, anyhow};
+use std::sync::{Arc, Mutex};
+
+/// 细化前按地址记录每条结果的轮次，细化后给幸存的结果找回轮次
+pub(crate) enum PassLookup {
+    /// 所有结果同一轮次（最常见：只做过一次扫描），不需要按地址查找
+    Uniform(u8),
+    /// 按地址排序的 (地址, 轮次)
+    ByAddress(Vec<(u64, u8)>),
+    /// 按地址顺序遍历时记录的轮次段：(段内第一个地址, 轮次)，地址属于它之前最近的一段
+    Runs(Vec<(u64, u8)>),
+}
+
+impl PassLookup {
+    pub fn from_pairs<I>(pairs: I) -> Self
+    where
+        I: IntoIterator<Item = (u64, u8)>,
+    {
+        let pairs: Vec<(u64, u8)> = pairs.into_iter().collect();
+        match pairs.first() {
+            None => PassLookup::Uniform(0),
+            Some(&(_, first)) if pairs.iter().all(|&(_, pass)| pass == first) => PassLookup::Uniform(first),
+            Some(_) => {
+                let mut pairs = pairs;
+                pairs.sort_unstable_by_key(|&(address, _)| address);
+                PassLookup::ByAddress(pairs)
+            },
+        }
+    }
+
+    /// 地址对应的轮次；细化结果总是原结果集的子集，找不到时按 0 处理
+    pub fn get(&self, address: u64) -> u8 {
+        match self {
+            PassLookup::Uniform(pass) => *pass,
+            PassLookup::ByAddress(pairs) => pairs.binary_search_by_key(&address, |&(addr, _)| addr).map(|pos| pairs[pos].1).unwrap_or(0),
+            PassLookup::Runs(runs) => match runs.partition_point(|&(start, _)| start <= address) {
+                0 => 0,
+                pos => runs[pos - 1].1,
+            },
+        }
+    }
+}
+
+/// 按地址升序逐条记录轮次，只在轮次变化时新增一段，
+/// 流式改善搜索用它代替 `PassLookup::from_pairs`，不需要保存每条结果的地址
+#[derive(Default)]
+pub(crate) struct PassRuns {
+    runs: Vec<(u64, u8)>,
+}
+
+impl PassRuns {
+    pub fn push(&mut self, address: u64, pass: u8) {
+        if self.runs.last().is_none_or(|&(_, last)| last != pass) {
+            self.runs.push((address, pass));
+        }
+    }
+
+    pub fn finish(self) -> PassLookup {
+        match self.runs.as_slice() {
+            [] => PassLookup::Uniform(0),
+            [(_, pass)] => PassLookup::Uniform(*pass),
+            _ => PassLookup::Runs(self.runs),
+        }
+    }
+}
+
+/// "最旧的在前"的显示顺序：按轮次升序，同一轮次内保持存储顺序
+pub(crate) enum PassOrder {
+    /// 所有结果同一轮次，显示顺序就是存储顺序
+    Identity,
+    /// 第 i 个显示位置对应的存储索引
+    Permutation(Vec<u32>),
+}
+
+impl PassOrder {
+    /// `passes[i]` 为存储索引 i 的轮次
+    pub fn build(passes: &[u8]) -> Self {
+        if passes.windows(2).all(|w| w[0] <= w[1]) {
+            return PassOrder::Identity;
+        }
+
+        // 计数排序：稳定，O(n)
+        let mut starts = [0usize; 257];
+        for &pass in passes {
+            starts[pass as usize + 1] += 1;
+        }
+        for i in 1..starts.len() {
+            starts[i] += starts[i - 1];
+        }
+        let mut order = vec![0u32; passes.len()];
+        for (index, &pass) in passes.iter().enumerate() {
+            let slot = &mut starts[pass as usize];
+            order[*slot] = index as u32;
+            *slot += 1;
+        }
+        PassOrder::Permutation(order)
+    }
+
+    /// 显示位置 [start, start + size) 对应的存储索引
+    pub fn page(&self, start: usize, size: usize, total: usize) -> Vec<usize> {
+        let end = start.saturating_add(size).min(total);
+        if start >= end {
+            return Vec::new();
+        }
+        match self {
+            PassOrder::Identity => (start..end).collect(),
+            PassOrder::Permutation(order) => order[start..end].iter().map(|&index| index as usize).collect(),
+        }
+    }
+
+    /// 把按存储顺序排列的索引子集（升序）改为显示顺序
+    pub fn arrange(&self, positions: Vec<u32>) -> Vec<u32> {
+        match self {
+            PassOrder::Identity => positions,
+            PassOrder::Permutation(order) => order.iter().copied().filter(|index| positions.binary_search(index).is_ok()).collect(),
+        }
+    }
+}
+
+/// 按结果集版本缓存的显示顺序
+#[derive(Default)]
+pub(crate) struct PassOrderCache {
+    cached: Mutex<Option<(u64, Arc<PassOrder>)>>,
+}
+
+impl PassOrderCache {
+    pub fn get_or_build<B>(&self, revision: u64, build: B) -> Result<Arc<PassOrder>>
+    where
+        B: FnOnce() -> Result<PassOrder>,
+    {
+        let mut cached = self.cached.lock().map_err(|_| anyhow!("Failed to acquire pass order cache lock"))?;
+        if let Some((cached_revision, order)) = cached.as_ref()
+            && *cached_revision == revision
+        {
+            return Ok(Arc::clone(order));
+        }
+
+        let order = Arc::new(build()?);
+        *cached = Some((revision, Arc::clone(&order)));
+        Ok(order)
+    }
+}
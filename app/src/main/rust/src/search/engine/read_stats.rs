@@ -0,0 +1,118 @@
+//! Read byte accounting for scans
+//!
+//! 首次扫描得到 0 个结果时，用户无法区分"内存里确实没有这个值"和"几乎什么都没读到"
+//! （驱动读取失败、区域全部不可读）。扫描过程中统计成功/失败读取的字节数，
+//! 结束时据此判断是否属于后者。
+//!
+//! 同时统计读取失败的页数，与扫描字节数、平均吞吐一起通过 SharedBuffer 提供给 Kotlin。
+//! 区域扫描每处理完一块还记录找到的结果数（去重前），结果数上限据此提前结束扫描。
+
+use crate::wuwa::PageStatusBitmap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// 可读字节低于这个值时认为什么都没读到
+pub const NOTHING_READABLE_MIN_BYTES: u64 = 64 * 1024;
+
+/// 可读字节低于请求字节的这个比例（百分比）时认为什么都没读到
+pub const NOTHING_READABLE_PERCENT: u64 = 1;
+
+/// 扫描期间的读取统计，多个区域并行扫描时共享同一份
+#[derive(Debug, Default)]
+pub struct ReadStats {
+    readable: AtomicU64,
+    failed: AtomicU64,
+    failed_pages: AtomicU64,
+    matches: AtomicU64,
+}
+
+impl ReadStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录一次块读取，`chunk_addr` 需要页对齐，`ok` 为读取调用本身是否成功
+    pub fn record_chunk(&self, chunk_addr: u64, chunk_len: usize, ok: bool, page_status: &PageStatusBitmap, page_size: usize) {
+        let readable = if ok {
+            readable_bytes(chunk_addr, chunk_len, page_status, page_size)
+        } else {
+            0
+        };
+        self.add(readable, chunk_len as u64 - readable);
+
+        let page_count = (chunk_len + (chunk_addr as usize & (page_size - 1))).div_ceil(page_size);
+        let failed_pages = if ok {
+            (0..page_count).filter(|&i| !page_status.is_page_success(i)).count()
+        } else {
+            page_count
+        };
+        if failed_pages > 0 {
+            self.failed_pages.fetch_add(failed_pages as u64, Ordering::Relaxed);
+        }
+    }
+
+    /// 直接累加字节数，例如复用缓存的区域按整个区域计为可读
+    pub fn add(&self, readable: u64, failed: u64) {
+        if readable > 0 {
+            self.readable.fetch_add(readable, Ordering::Relaxed);
+        }
+        if failed > 0 {
+            self.failed.fetch_add(failed, Ordering::Relaxed);
+        }
+    }
+
+    /// (可读字节, 读取失败字节)
+    pub fn snapshot(&self) -> (u64, u64) {
+        (self.readable.load(Ordering::Relaxed), self.failed.load(Ordering::Relaxed))
+    }
+
+    /// 读取失败的页数
+    pub fn failed_pages(&self) -> u64 {
+        self.failed_pages.load(Ordering::Relaxed)
+    }
+
+    /// 记录一块（或一个复用缓存的区域）找到的结果数
+    pub fn record_matches(&self, count: usize) {
+        if count > 0 {
+            self.matches.fetch_add(count as u64, Ordering::Relaxed);
+        }
+    }
+
+    /// 目前找到的结果数，各区域的结果还没有去重
+    pub fn matches(&self) -> u64 {
+        self.matches.load(Ordering::Relaxed)
+    }
+}
+
+/// 平均吞吐（MB/s），耗时为 0 时返回 0
+pub fn mb_per_sec(bytes: u64, elapsed: Duration) -> f32 {
+    let secs = elapsed.as_secs_f64();
+    if secs <= 0.0 {
+        return 0.0;
+    }
+    (bytes as f64 / (1024.0 * 1024.0) / secs) as f32
+}
+
+/// 块内读取成功的页覆盖的字节数，最后一页按块的实际长度截断
+pub fn readable_bytes(chunk_addr: u64, chunk_len: usize, page_status: &PageStatusBitmap, page_size: usize) -> u64 {
+    let page_count = (chunk_len + (chunk_addr as usize & (page_size - 1))).div_ceil(page_size);
+    let page_base = chunk_addr & !(page_size as u64 - 1);
+    let chunk_end = chunk_addr + chunk_len as u64;
+
+    (0..page_count)
+        .filter(|&i| page_status.is_page_success(i))
+        .map(|i| {
+            let page_start = (page_base + (i * page_size) as u64).max(chunk_addr);
+            let page_end = (page_base + ((i + 1) * page_size) as u64).min(chunk_end);
+            page_end - page_start
+        })
+        .sum()
+}
+
+/// 0 个结果且可读字节过少（低于固定下限或请求字节的 1%）
+pub fn is_nothing_readable(result_count: usize, readable: u64, requested: u64) -> bool {
+    if result_count > 0 {
+        return false;
+    }
+    readable < NOTHING_READABLE_MIN_BYTES.min(requested) || readable.saturating_mul(100) < requested.saturating_mul(NOTHING_READABLE_PERCENT)
+}
@@ -0,0 +1,288 @@
+//! Refine strategy selection
+//!
+//! 精确结果集很大（上千万条）时，逐个地址读取的改善搜索被每次读取的固定开销拖垮。
+//! 这时重新扫描结果所在的页，再和已有结果做有序归并连接反而快得多。
+//! 成本模型比较两者的预计耗时自动选择，也可以手动指定。
+
+use super::manager::ValuePair;
+use super::single_search::search_in_chunks_with_status;
+use crate::search::types::{SearchValue, ValueType};
+use crate::search::PAGE_SIZE;
+use crate::wuwa::PageStatusBitmap;
+use anyhow::Result;
+use log::debug;
+
+/// 单次逐地址读取的默认开销（纳秒），包含一次驱动调用
+pub const DEFAULT_PER_READ_NANOS: u64 = 2_000;
+
+/// 没有吞吐统计时使用的默认扫描吞吐（字节/秒）
+pub const DEFAULT_SCAN_BYTES_PER_SEC: f64 = 512.0 * 1024.0 * 1024.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefineStrategy {
+    /// 逐个读取结果地址的当前值
+    PerItem,
+    /// 重新扫描结果所在的页，再与已有结果归并
+    RescanAndIntersect,
+}
+
+impl RefineStrategy {
+    #[inline]
+    pub fn from_id(id: i32) -> Option<Self> {
+        match id {
+            0 => Some(Self::PerItem),
+            1 => Some(Self::RescanAndIntersect),
+            _ => None,
+        }
+    }
+}
+
+/// 改善搜索的成本模型
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RefineCostModel {
+    pub per_read_nanos: u64,
+    pub scan_bytes_per_sec: f64,
+}
+
+impl Default for RefineCostModel {
+    fn default() -> Self {
+        Self {
+            per_read_nanos: DEFAULT_PER_READ_NANOS,
+            scan_bytes_per_sec: DEFAULT_SCAN_BYTES_PER_SEC,
+        }
+    }
+}
+
+impl RefineCostModel {
+    /// 逐地址读取的预计耗时（纳秒）
+    pub fn per_item_nanos(&self, result_count: usize) -> f64 {
+        result_count as f64 * self.per_read_nanos as f64
+    }
+
+    /// 重新扫描占用页的预计耗时（纳秒）
+    pub fn rescan_nanos(&self, occupied_bytes: u64) -> f64 {
+        if self.scan_bytes_per_sec <= 0.0 {
+            return f64::INFINITY;
+        }
+        occupied_bytes as f64 / self.scan_bytes_per_sec * 1_000_000_000.0
+    }
+
+    pub fn choose(&self, result_count: usize, occupied_bytes: u64) -> RefineStrategy {
+        if self.rescan_nanos(occupied_bytes) < self.per_item_nanos(result_count) {
+            RefineStrategy::RescanAndIntersect
+        } else {
+            RefineStrategy::PerItem
+        }
+    }
+}
+
+/// 重新扫描只支持定长且按自身大小对齐扫描的类型；Auto 会扫出多种类型的结果，只能逐个读取
+pub fn supports_rescan(value_type: ValueType) -> bool {
+    if value_type == ValueType::Auto {
+        return false;
+    }
+    let size = value_type.size();
+    size > 0 && size.is_power_of_two()
+}
+
+/// 结果的值覆盖的页 [start, end)
+#[inline]
+fn page_span(pair: &ValuePair, page_size: usize) -> (u64, u64) {
+    let mask = !(page_size as u64 - 1);
+    let start = pair.addr & mask;
+    let end = (pair.addr.saturating_add(pair.value_type.size().max(1) as u64) + page_size as u64 - 1) & mask;
+    (start, end)
+}
+
+/// 结果占用的页合并成的区域，`results` 需要按地址排序
+pub fn occupied_regions(results: &[ValuePair], page_size: usize) -> Vec<(u64, u64)> {
+    let mut regions: Vec<(u64, u64)> = Vec::new();
+
+    for pair in results {
+        let (start, end) = page_span(pair, page_size);
+        match regions.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => regions.push((start, end)),
+        }
+    }
+    regions
+}
+
+/// 与 `scan_bytes(&occupied_regions(..))` 相同，但逐条累加，不保存区域，`results` 需要按地址排序
+pub fn occupied_bytes<I>(results: I, page_size: usize) -> u64
+where
+    I: IntoIterator<Item = ValuePair>,
+{
+    let mut total = 0;
+    let mut current: Option<(u64, u64)> = None;
+
+    for pair in results {
+        let (start, end) = page_span(&pair, page_size);
+        match current.as_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => {
+                if let Some((last_start, last_end)) = current.replace((start, end)) {
+                    total += last_end - last_start;
+                }
+            },
+        }
+    }
+    total + current.map_or(0, |(start, end)| end - start)
+}
+
+/// 有序归并：把 `hits` 中同时出现在 `candidates[*cursor..]` 里的地址追加到 `survivors`
+fn merge_join(candidates: &[ValuePair], cursor: &mut usize, hits: &[ValuePair], survivors: &mut Vec<ValuePair>) {
+    for hit in hits {
+        while *cursor < candidates.len() && candidates[*cursor].addr < hit.addr {
+            *cursor += 1;
+        }
+        if *cursor < candidates.len() && candidates[*cursor].addr == hit.addr {
+            survivors.push(candidates[*cursor].clone());
+            *cursor += 1;
+        }
+    }
+}
+
+/// 按地址顺序分批做 `rescan_and_intersect_with`，每批最多 `batch_size` 个结果，内存占用不随结果总数增长。
+/// 相邻两批落在同一页时这一页会被扫描两次，存活集合不受影响。取消时返回空集合
+pub fn rescan_and_intersect_stream_with<I, R, F, P>(
+    current: I,
+    target: &SearchValue,
+    chunk_size: usize,
+    batch_size: usize,
+    mut read: R,
+    check_cancelled: &F,
+    update_progress: &P,
+) -> Result<Vec<ValuePair>>
+where
+    I: IntoIterator<Item = ValuePair>,
+    R: FnMut(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+    F: Fn() -> bool + Sync,
+    P: Fn(usize, usize),
+{
+    let mut current = current.into_iter();
+    let mut survivors = Vec::new();
+    let mut processed = 0;
+    loop {
+        let batch: Vec<ValuePair> = current.by_ref().take(batch_size.max(1)).collect();
+        if batch.is_empty() {
+            break;
+        }
+        let found = survivors.len();
+        let refined = rescan_and_intersect_with(&batch, target, chunk_size, &mut read, check_cancelled, &|done, hits| {
+            update_progress(processed + done, found + hits)
+        })?;
+        if check_cancelled() {
+            return Ok(Vec::new());
+        }
+        survivors.extend(refined);
+        processed += batch.len();
+    }
+    Ok(survivors)
+}
+
+/// 重新扫描 + 归并的改善搜索
+///
+/// - `read`: 带页状态的读取，扫描时地址页对齐，不对齐的结果按值大小单独读取
+/// - `update_progress`: (已处理的结果数, 已找到的结果数)
+///
+/// 扫描器只在按元素大小对齐的地址上匹配，不对齐的已有结果单独逐个读取，
+/// 所以存活集合与逐地址读取完全一致。取消时返回空集合。
+pub fn rescan_and_intersect_with<R, F, P>(
+    current: &[ValuePair],
+    target: &SearchValue,
+    chunk_size: usize,
+    mut read: R,
+    check_cancelled: &F,
+    update_progress: &P,
+) -> Result<Vec<ValuePair>>
+where
+    R: FnMut(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+    F: Fn() -> bool + Sync,
+    P: Fn(usize, usize),
+{
+    let value_type = target.value_type();
+    let element_size = value_type.size();
+    let page_size = *PAGE_SIZE;
+    let chunk_size = chunk_size.max(page_size) & !(page_size - 1);
+
+    let mut candidates: Vec<ValuePair> = current.iter().filter(|p| p.value_type == value_type).cloned().collect();
+    if !candidates.is_sorted() {
+        candidates.sort_unstable();
+    }
+    let (aligned, misaligned): (Vec<ValuePair>, Vec<ValuePair>) =
+        candidates.into_iter().partition(|p| p.addr.is_multiple_of(element_size as u64));
+
+    let regions = occupied_regions(&aligned, page_size);
+    let mut survivors = Vec::new();
+    let mut cursor = 0usize;
+    let mut hits = Vec::new();
+    let mut buffer = vec![0u8; chunk_size];
+
+    for &(start, end) in &regions {
+        let mut current = start;
+        while current < end {
+            if check_cancelled() {
+                return Ok(Vec::new());
+            }
+
+            let chunk_end = (current + chunk_size as u64).min(end);
+            let chunk_len = (chunk_end - current) as usize;
+            let mut page_status = PageStatusBitmap::new(chunk_len, current as usize);
+
+            if read(current, &mut buffer[..chunk_len], &mut page_status).is_ok() && page_status.success_count() > 0 {
+                hits.clear();
+                search_in_chunks_with_status(
+                    &buffer[..chunk_len],
+                    current,
+                    current,
+                    chunk_end,
+                    element_size,
+                    target,
+                    value_type,
+                    &page_status,
+                    &mut hits,
+                    check_cancelled,
+                );
+                if !hits.is_sorted() {
+                    hits.sort_unstable();
+                }
+                merge_join(&aligned, &mut cursor, &hits, &mut survivors);
+            }
+
+            // 读取失败的块里的结果视为不再匹配，与逐地址读取失败时一致
+            cursor += aligned[cursor..].partition_point(|p| p.addr < chunk_end);
+            current = chunk_end;
+            update_progress(cursor, survivors.len());
+        }
+    }
+
+    if !misaligned.is_empty() {
+        let mut value = vec![0u8; element_size];
+        for (idx, pair) in misaligned.iter().enumerate() {
+            if idx.is_multiple_of(1000) && check_cancelled() {
+                return Ok(Vec::new());
+            }
+            // 值跨越的页必须全部读取成功
+            let spanned_pages = ((pair.addr as usize & (page_size - 1)) + element_size).div_ceil(page_size);
+            let mut page_status = PageStatusBitmap::new(element_size, pair.addr as usize);
+            if read(pair.addr, &mut value, &mut page_status).is_ok()
+                && page_status.success_count() == spanned_pages
+                && target.matched_at(&value, pair.addr).unwrap_or(false)
+            {
+                survivors.push(pair.clone());
+            }
+        }
+        survivors.sort_unstable();
+    }
+
+    update_progress(aligned.len() + misaligned.len(), survivors.len());
+    debug!(
+        "Rescan refine: {} regions, {} aligned + {} misaligned -> {} results",
+        regions.len(),
+        aligned.len(),
+        misaligned.len(),
+        survivors.len()
+    );
+    Ok(survivors)
+}
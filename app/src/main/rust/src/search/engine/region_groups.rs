@@ -0,0 +1,133 @@
+//! Result grouping by memory region
+//!
+//! 结果界面按区域折叠显示（"libunity.so [1,234]"）。结果存储按地址升序，同一区域的结果
+//! 在存储中是连续的一段，所以顺序遍历一次就能得到每个区域的索引范围，分组内分页直接按
+//! 索引偏移读取存储，不需要在 Kotlin 层对几十万条结果分组。
+//!
+//! 区域映射中已经不存在的结果（区域被 munmap）按所在的空隙归为 unmapped 分组。
+
+use crate::core::region_map::{MappedRegion, RegionMap};
+use anyhow::{Result, anyhow};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+
+/// 不属于任何区域的结果分组名称
+pub const UNMAPPED_GROUP_NAME: &str = "unmapped";
+
+/// 一个区域内的结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RegionGroup {
+    /// 区域起止地址；unmapped 分组为所在空隙的边界
+    pub start: u64,
+    pub end: u64,
+    pub name: String,
+    pub count: usize,
+    /// 分组第一条结果在结果存储中的索引
+    pub first_index: usize,
+    pub mapped: bool,
+}
+
+/// 区域的显示名称：文件映射取文件名，匿名映射带上起始地址，其余（`[heap]`、`[anon:...]`）保持原样
+pub fn region_display_name(region: &MappedRegion) -> String {
+    if region.name.is_empty() {
+        format!("anonymous:{:x}", region.start)
+    } else if region.name.contains('/') {
+        region.name.rsplit('/').next().unwrap_or(&region.name).to_string()
+    } else {
+        region.name.clone()
+    }
+}
+
+/// 按存储顺序逐条输入结果地址，连续落在同一区域的结果合为一组
+///
+/// 存储按地址升序时每个区域恰好一组；若某段结果未排序，同一区域可能出现多组，
+/// 但每组的索引范围仍然正确。
+pub(crate) struct RegionGroupBuilder<'a> {
+    map: &'a RegionMap,
+    groups: Vec<RegionGroup>,
+    next_index: usize,
+}
+
+impl<'a> RegionGroupBuilder<'a> {
+    pub fn new(map: &'a RegionMap) -> Self {
+        Self {
+            map,
+            groups: Vec::new(),
+            next_index: 0,
+        }
+    }
+
+    pub fn push(&mut self, address: u64) {
+        let index = self.next_index;
+        self.next_index += 1;
+
+        if let Some(last) = self.groups.last_mut()
+            && (last.start..last.end).contains(&address)
+        {
+            last.count += 1;
+            return;
+        }
+
+        let group = match self.map.find(address) {
+            Some(region) => RegionGroup {
+                start: region.start,
+                end: region.end,
+                name: region_display_name(region),
+                count: 1,
+                first_index: index,
+                mapped: true,
+            },
+            None => {
+                let (start, end) = self.map.gap_around(address);
+                RegionGroup {
+                    start,
+                    end,
+                    name: UNMAPPED_GROUP_NAME.to_string(),
+                    count: 1,
+                    first_index: index,
+                    mapped: false,
+                }
+            },
+        };
+        self.groups.push(group);
+    }
+
+    pub fn finish(self) -> Vec<RegionGroup> {
+        self.groups
+    }
+}
+
+struct CachedGroups {
+    revision: u64,
+    map: Arc<RegionMap>,
+    groups: Arc<Vec<RegionGroup>>,
+}
+
+/// 按结果集版本缓存的分组，结果集变化或区域映射刷新（重新绑定进程）后重新计算
+#[derive(Default)]
+pub(crate) struct RegionGroupCache {
+    cached: Mutex<Option<CachedGroups>>,
+}
+
+impl RegionGroupCache {
+    pub fn get_or_build<B>(&self, revision: u64, map: &Arc<RegionMap>, build: B) -> Result<Arc<Vec<RegionGroup>>>
+    where
+        B: FnOnce() -> Result<Vec<RegionGroup>>,
+    {
+        let mut cached = self.cached.lock().map_err(|_| anyhow!("Failed to acquire region group cache lock"))?;
+        if let Some(entry) = cached.as_ref()
+            && entry.revision == revision
+            && Arc::ptr_eq(&entry.map, map)
+        {
+            return Ok(Arc::clone(&entry.groups));
+        }
+
+        let groups = Arc::new(build()?);
+        *cached = Some(CachedGroups {
+            revision,
+            map: Arc::clone(map),
+            groups: Arc::clone(&groups),
+        });
+        Ok(groups)
+    }
+}
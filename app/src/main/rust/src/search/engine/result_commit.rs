@@ -0,0 +1,268 @@
+//! Incremental commit of first-scan results
+//!
+//! 首次扫描不再把所有区域的结果收集进一个大 Vec 再统一排序去重（扫描整个堆上的 Dword 0 时这个 Vec
+//! 本身就有几 GB）。各区域扫描完成后把排序去重过的结果作为一批经有界通道交给唯一的提交线程：
+//! 1. 保留：结果总数不超过 `retain_limit` 时留在内存里，扫描结束后照常做兼容模式或精确值的捕获
+//! 2. 提交：超过上限（或内存压力升高）后按区域顺序写入结果管理器，之后每批到达时直接写入
+//!
+//! 区域按起始地址升序编号，按编号顺序提交的各批首尾相接，结果文件整体仍按地址有序，不需要再排序。
+//! 后面的区域先完成时在这里等待，等待的结果超过 `reorder_limit` 时不再等待、直接写入，
+//! 结果文件分成几段各自有序的结果，读取时由 `ResultCursor` 归并。
+
+use super::manager::ValuePair;
+use anyhow::Result;
+use rayon::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+/// 通道里最多排队的区域批次，提交跟不上时扫描线程在发送处等待
+pub(crate) const COMMIT_QUEUE_DEPTH: usize = 64;
+
+/// 提交线程等待下一批时检查取消的间隔
+const COMMIT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// 扫描结束后仍留在内存里做值捕获的结果数上限（约 64 MB 的 ValuePair）
+pub(crate) const RESULT_RETAIN_LIMIT: usize = 4 * 1024 * 1024;
+
+/// 等待前面区域时最多暂存的结果数
+pub(crate) const REORDER_LIMIT: usize = 4 * 1024 * 1024;
+
+/// 每次写入结果管理器的结果数，写入时持有结果管理器的写锁
+pub(crate) const COMMIT_BATCH: usize = 256 * 1024;
+
+/// 一个区域的结果，按 (地址, 类型) 排序去重
+pub(crate) struct RegionBatch {
+    pub region: usize,
+    pub results: Vec<ValuePair>,
+}
+
+impl RegionBatch {
+    pub fn new(region: usize, mut results: Vec<ValuePair>) -> Self {
+        // Auto 类型下同一地址可能以多种类型命中，按类型排序让重复的结果相邻
+        if !results.is_sorted_by_key(|pair| (pair.addr, pair.value_type.to_id())) {
+            results.par_sort_unstable_by_key(|pair| (pair.addr, pair.value_type.to_id()));
+        }
+        results.dedup();
+        Self { region, results }
+    }
+
+    /// 没有扫描（或扫描失败）的区域也要发送一个空批次，后面的区域才能按顺序提交
+    pub fn empty(region: usize) -> Self {
+        Self { region, results: Vec::new() }
+    }
+}
+
+/// 提交结束时的结果
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum CommitOutcome {
+    /// 所有结果仍在内存里（按区域顺序拼接），没有写入结果管理器
+    Retained(Vec<ValuePair>),
+    /// 结果已经写入结果管理器
+    Committed {
+        count: usize,
+        /// 写入的结果分成几段各自有序的结果，1 表示整体有序
+        runs: usize,
+    },
+}
+
+pub(crate) struct ResultCommitter<C>
+where
+    C: FnMut(Vec<ValuePair>) -> Result<()>,
+{
+    retain_limit: usize,
+    reorder_limit: usize,
+    /// 还在保留阶段
+    retaining: bool,
+    /// 下一个按顺序提交的区域
+    next_region: usize,
+    /// 已经到达、还没有提交的区域
+    waiting: BTreeMap<usize, Vec<ValuePair>>,
+    waiting_len: usize,
+    /// 已经提交（或跳过）的区域编号大于 next_region 的部分，等待被顺序指针越过
+    passed: BTreeSet<usize>,
+    /// 等待写入的结果，攒够 COMMIT_BATCH 再写
+    ready: Vec<ValuePair>,
+    committed: usize,
+    runs: usize,
+    /// 最近写入的结果，用来判断新的一批是否接在后面
+    last: Option<(u64, i32)>,
+    /// 把一批结果写入结果管理器
+    commit: C,
+}
+
+impl<C> ResultCommitter<C>
+where
+    C: FnMut(Vec<ValuePair>) -> Result<()>,
+{
+    pub fn new(retain_limit: usize, reorder_limit: usize, commit: C) -> Self {
+        Self {
+            retain_limit,
+            reorder_limit,
+            retaining: true,
+            next_region: 0,
+            waiting: BTreeMap::new(),
+            waiting_len: 0,
+            passed: BTreeSet::new(),
+            ready: Vec::new(),
+            committed: 0,
+            runs: 0,
+            last: None,
+            commit,
+        }
+    }
+
+    /// 已经找到的结果数（已写入的和仍在内存里的），用于进度显示
+    pub fn found(&self) -> usize {
+        self.committed + self.ready.len() + self.waiting_len
+    }
+
+    pub fn is_retaining(&self) -> bool {
+        self.retaining
+    }
+
+    /// 接收一个区域的结果
+    pub fn accept(&mut self, batch: RegionBatch) -> Result<()> {
+        self.waiting_len += batch.results.len();
+        self.waiting.insert(batch.region, batch.results);
+        if self.retaining {
+            if self.waiting_len <= self.retain_limit {
+                return Ok(());
+            }
+            self.retaining = false;
+        }
+        self.drain()
+    }
+
+    /// 不再保留，开始写入（内存压力升高时调用）
+    pub fn start_committing(&mut self) -> Result<()> {
+        if !self.retaining {
+            return Ok(());
+        }
+        self.retaining = false;
+        self.drain()
+    }
+
+    /// 所有区域都已接收
+    pub fn finish(mut self) -> Result<CommitOutcome> {
+        if self.retaining {
+            let mut results = Vec::with_capacity(self.waiting_len);
+            for (_, batch) in std::mem::take(&mut self.waiting) {
+                results.extend(batch);
+            }
+            return Ok(CommitOutcome::Retained(results));
+        }
+
+        // 剩下的区域前面有永远不会到达的编号（取消或出错），按区域顺序全部写入
+        let waiting = std::mem::take(&mut self.waiting);
+        self.waiting_len = 0;
+        for (_, batch) in waiting {
+            self.push_ready(batch)?;
+        }
+        self.flush_ready()?;
+        Ok(CommitOutcome::Committed { count: self.committed, runs: self.runs })
+    }
+
+    /// 按顺序提交已经到达的区域；等待的结果太多时不再等待前面的区域
+    fn drain(&mut self) -> Result<()> {
+        loop {
+            if let Some(batch) = self.waiting.remove(&self.next_region) {
+                self.waiting_len -= batch.len();
+                self.push_ready(batch)?;
+                self.advance();
+                continue;
+            }
+            if self.waiting_len <= self.reorder_limit {
+                break;
+            }
+            // 最前面的区域迟迟没有完成，先写入后面已经到达的区域
+            let Some((region, batch)) = self.waiting.pop_first() else {
+                break;
+            };
+            self.waiting_len -= batch.len();
+            self.push_ready(batch)?;
+            self.passed.insert(region);
+        }
+        Ok(())
+    }
+
+    /// next_region 已提交，越过之后已经提前提交的区域
+    fn advance(&mut self) {
+        self.next_region += 1;
+        while self.passed.remove(&self.next_region) {
+            self.next_region += 1;
+        }
+    }
+
+    fn push_ready(&mut self, batch: Vec<ValuePair>) -> Result<()> {
+        let (Some(first), Some(end)) = (batch.first(), batch.last()) else {
+            return Ok(());
+        };
+        let first = (first.addr, first.value_type.to_id());
+        let end = (end.addr, end.value_type.to_id());
+        if self.last.is_none_or(|last| last >= first) {
+            self.runs += 1;
+        }
+        self.last = Some(end);
+
+        let mut batch = batch.into_iter();
+        loop {
+            let room = COMMIT_BATCH - self.ready.len();
+            self.ready.extend(batch.by_ref().take(room));
+            if self.ready.len() < COMMIT_BATCH {
+                return Ok(());
+            }
+            self.flush_ready()?;
+        }
+    }
+
+    fn flush_ready(&mut self) -> Result<()> {
+        let ready = std::mem::take(&mut self.ready);
+        if ready.is_empty() {
+            return Ok(());
+        }
+        self.commit_batch(ready)
+    }
+
+    fn commit_batch(&mut self, batch: Vec<ValuePair>) -> Result<()> {
+        let count = batch.len();
+        (self.commit)(batch)?;
+        self.committed += count;
+        Ok(())
+    }
+}
+
+/// 提交线程的主循环：接收各区域的批次直到所有发送端都已丢弃
+///
+/// `should_commit` 返回 true 后（内存压力升高）不再保留结果；每接收一批调用一次 `on_found`。
+/// 取消后立即返回 None 并丢弃接收端，扫描线程的发送随之失败，不会阻塞在满的通道上。
+pub(crate) fn run_committer<C, S, F, P>(mut committer: ResultCommitter<C>, receiver: Receiver<RegionBatch>, should_commit: S, check_cancelled: &F, on_found: P) -> Option<Result<CommitOutcome>>
+where
+    C: FnMut(Vec<ValuePair>) -> Result<()>,
+    S: Fn() -> bool,
+    F: Fn() -> bool,
+    P: Fn(usize),
+{
+    loop {
+        if check_cancelled() {
+            return None;
+        }
+        if committer.is_retaining()
+            && should_commit()
+            && let Err(e) = committer.start_committing()
+        {
+            return Some(Err(e));
+        }
+        match receiver.recv_timeout(COMMIT_POLL_INTERVAL) {
+            Ok(batch) => {
+                if let Err(e) = committer.accept(batch) {
+                    return Some(Err(e));
+                }
+                on_found(committer.found());
+            },
+            Err(RecvTimeoutError::Timeout) => {},
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    Some(committer.finish())
+}
@@ -0,0 +1,74 @@
+//! Streaming refine input
+//!
+//! 改善搜索不再把已有结果一次性展开成 Vec（两亿条结果时这一步就会 OOM），而是通过 `ResultCursor`
+//! 按地址顺序分批读取，逐条交给细化函数，同时按地址记录每条结果的轮次。
+//! 读取失败时遍历提前结束，错误由 `finish` 返回，调用方据此放弃这次改善，不能把不完整的结果写回。
+
+use super::manager::ValuePair;
+use super::provenance::{PassLookup, PassRuns};
+use crate::search::result_manager::{ExactSearchResultItem, ResultCursor};
+use anyhow::Result;
+
+/// 改善搜索每批读取的结果数，细化过程中输入侧的内存占用与它成正比
+pub(crate) const REFINE_BATCH_SIZE: usize = 1024 * 1024;
+
+/// 从游标读取的结果，`fetch` 读取下一批（通常在里面短暂获取结果管理器的读锁）
+pub(crate) struct ResultStream<'c, F> {
+    cursor: &'c mut ResultCursor,
+    fetch: F,
+    batch: std::vec::IntoIter<ExactSearchResultItem>,
+    passes: PassRuns,
+    error: Option<anyhow::Error>,
+    done: bool,
+}
+
+impl<'c, F> ResultStream<'c, F>
+where
+    F: FnMut(&mut ResultCursor) -> Result<Vec<ExactSearchResultItem>>,
+{
+    pub fn new(cursor: &'c mut ResultCursor, fetch: F) -> Self {
+        Self {
+            cursor,
+            fetch,
+            batch: Vec::new().into_iter(),
+            passes: PassRuns::default(),
+            error: None,
+            done: false,
+        }
+    }
+
+    /// 读取过程中的错误，没有错误时返回已读取结果的轮次
+    pub fn finish(self) -> Result<PassLookup> {
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(self.passes.finish()),
+        }
+    }
+}
+
+impl<F> Iterator for ResultStream<'_, F>
+where
+    F: FnMut(&mut ResultCursor) -> Result<Vec<ExactSearchResultItem>>,
+{
+    type Item = ValuePair;
+
+    fn next(&mut self) -> Option<ValuePair> {
+        loop {
+            if let Some(item) = self.batch.next() {
+                self.passes.push(item.address, item.pass);
+                return Some(ValuePair::new(item.address, item.typ));
+            }
+            if self.done {
+                return None;
+            }
+            match (self.fetch)(self.cursor) {
+                Ok(batch) if !batch.is_empty() => self.batch = batch.into_iter(),
+                Ok(_) => self.done = true,
+                Err(e) => {
+                    self.error = Some(e);
+                    self.done = true;
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,243 @@
+//! Per-region scan result cache
+//!
+//! 误操作清空结果后用户经常重新跑一遍完全相同的首次扫描。开启后，
+//! 每个区域的结果按 (查询指纹, 区域, 稀疏采样内容哈希) 存入缓存文件，
+//! 下次相同查询时采样哈希未变的区域直接复用缓存结果，只重新扫描变化的区域。
+//!
+//! 采样只读取每 4MB 的第一页，两个采样页之间的值变化无法发现，所以这是尽力而为的功能，
+//! 默认关闭，需要显式开启。
+
+use super::manager::ValuePair;
+use crate::search::types::{SearchQuery, ValueType};
+use anyhow::{Result, anyhow};
+use log::{debug, warn};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// 缓存目录名（位于搜索缓存目录下）
+pub const SCAN_CACHE_DIR_NAME: &str = "scan_cache";
+
+/// 每隔多少字节采样一页
+pub const SAMPLE_STRIDE: u64 = 4 * 1024 * 1024;
+
+/// 缓存总大小上限，超出后淘汰最旧的区域文件
+pub const DEFAULT_SCAN_CACHE_MAX_BYTES: u64 = 256 * 1024 * 1024;
+
+/// 文件头：content_hash(8) + count(8)
+const HEADER_SIZE: usize = 16;
+
+/// 单条记录：address(8) + value_type(1)
+const RECORD_SIZE: usize = 9;
+
+/// 写入中的临时文件扩展名
+const TMP_EXTENSION: &str = "tmp";
+
+pub(crate) const FNV_OFFSET: u64 = 0xCBF2_9CE4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01B3;
+
+/// FNV-1a，缓存文件跨进程使用，需要稳定的哈希
+pub(crate) fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for &b in bytes {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+/// 查询指纹：查询内容和深度搜索开关都会影响区域结果
+pub fn query_fingerprint(query: &SearchQuery, use_deep_search: bool) -> u64 {
+    let hash = fnv1a(FNV_OFFSET, format!("{:?}", query).as_bytes());
+    fnv1a(hash, &[use_deep_search as u8])
+}
+
+/// 区域内容的稀疏采样哈希，任一采样页读取失败时返回 None（该区域不参与缓存）
+pub fn sample_region_hash<R>(start: u64, end: u64, page_size: usize, mut read: R) -> Option<u64>
+where
+    R: FnMut(u64, &mut [u8]) -> Result<()>,
+{
+    let mut buffer = vec![0u8; page_size];
+    let mut hash = fnv1a(FNV_OFFSET, &start.to_le_bytes());
+    hash = fnv1a(hash, &end.to_le_bytes());
+
+    let mut addr = start;
+    while addr < end {
+        let len = (end - addr).min(page_size as u64) as usize;
+        read(addr, &mut buffer[..len]).ok()?;
+        hash = fnv1a(hash, &buffer[..len]);
+        addr = addr.saturating_add(SAMPLE_STRIDE);
+    }
+    Some(hash)
+}
+
+pub struct ScanCache {
+    dir: PathBuf,
+    max_bytes: u64,
+}
+
+impl ScanCache {
+    pub fn new(dir: PathBuf, max_bytes: u64) -> Result<Self> {
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { dir, max_bytes })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn file_path(&self, fingerprint: u64, start: u64, end: u64) -> PathBuf {
+        self.dir.join(format!("{:016x}_{:x}_{:x}.bin", fingerprint, start, end))
+    }
+
+    /// 查找区域缓存，内容哈希不一致或文件损坏时返回 None
+    pub fn lookup(&self, fingerprint: u64, start: u64, end: u64, content_hash: u64) -> Option<Vec<ValuePair>> {
+        let file = File::open(self.file_path(fingerprint, start, end)).ok()?;
+        let file_len = file.metadata().ok()?.len();
+        let mut reader = BufReader::new(file);
+
+        let mut header = [0u8; HEADER_SIZE];
+        reader.read_exact(&mut header).ok()?;
+        if u64::from_le_bytes(header[..8].try_into().unwrap()) != content_hash {
+            return None;
+        }
+        // 记录数来自文件头，损坏或截断的文件不能按它分配内存
+        let count = u64::from_le_bytes(header[8..].try_into().unwrap());
+        if count > file_len.saturating_sub(HEADER_SIZE as u64) / RECORD_SIZE as u64 {
+            return None;
+        }
+        let count = count as usize;
+
+        let mut results = Vec::with_capacity(count);
+        let mut record = [0u8; RECORD_SIZE];
+        for _ in 0..count {
+            reader.read_exact(&mut record).ok()?;
+            let addr = u64::from_le_bytes(record[..8].try_into().unwrap());
+            let value_type = ValueType::from_id(record[8] as i32)?;
+            results.push(ValuePair::new(addr, value_type));
+        }
+        Some(results)
+    }
+
+    /// 写入区域缓存，覆盖同一查询同一区域的旧缓存
+    ///
+    /// 先写同目录下的临时文件再 rename 覆盖，中途崩溃不会留下写了一半的缓存文件
+    pub fn store(&self, fingerprint: u64, start: u64, end: u64, content_hash: u64, results: &[ValuePair]) -> Result<()> {
+        let path = self.file_path(fingerprint, start, end);
+        let tmp_path = path.with_extension(TMP_EXTENSION);
+        let written = Self::write_file(&tmp_path, content_hash, results).and_then(|_| Ok(std::fs::rename(&tmp_path, &path)?));
+        if written.is_err() {
+            let _ = std::fs::remove_file(&tmp_path);
+        }
+        written
+    }
+
+    fn write_file(path: &Path, content_hash: u64, results: &[ValuePair]) -> Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(&content_hash.to_le_bytes())?;
+        writer.write_all(&(results.len() as u64).to_le_bytes())?;
+
+        let mut record = [0u8; RECORD_SIZE];
+        for pair in results {
+            record[..8].copy_from_slice(&pair.addr.to_le_bytes());
+            record[8] = pair.value_type.to_id() as u8;
+            writer.write_all(&record)?;
+        }
+        writer.into_inner().map_err(|e| anyhow!("Failed to flush scan cache {:?}: {:?}", path, e))?;
+        Ok(())
+    }
+
+    /// 缓存文件占用的字节数
+    pub fn size_bytes(&self) -> u64 {
+        dir_size(&self.dir)
+    }
+
+    /// 淘汰最旧的文件直到总大小不超过上限
+    pub fn trim(&self) -> Result<()> {
+        let mut files: Vec<_> = std::fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let meta = entry.metadata().ok()?;
+                meta.is_file().then(|| (meta.modified().ok(), meta.len(), entry.path()))
+            })
+            .collect();
+
+        let mut total: u64 = files.iter().map(|(_, len, _)| len).sum();
+        if total <= self.max_bytes {
+            return Ok(());
+        }
+
+        files.sort_by_key(|(modified, _, _)| *modified);
+        for (_, len, path) in files {
+            if total <= self.max_bytes {
+                break;
+            }
+            if let Err(e) = std::fs::remove_file(&path) {
+                warn!("Failed to evict scan cache {:?}: {:?}", path, e);
+                continue;
+            }
+            total -= len;
+            debug!("Evicted scan cache {:?}", path);
+        }
+        Ok(())
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        for entry in std::fs::read_dir(&self.dir)?.flatten() {
+            let _ = std::fs::remove_file(entry.path());
+        }
+        Ok(())
+    }
+}
+
+/// 目录下文件的总大小，目录不存在时为 0
+pub fn dir_size(dir: &Path) -> u64 {
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok()?.metadata().ok())
+                .filter(|meta| meta.is_file())
+                .map(|meta| meta.len())
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+/// 扫描一个区域，开启缓存时优先复用采样哈希未变的缓存结果
+///
+/// 返回 (区域结果, 是否复用了缓存)。扫描失败或被取消的区域不写入缓存。
+pub fn scan_region_cached<R, S, F>(
+    cache: Option<&ScanCache>,
+    fingerprint: u64,
+    region: (u64, u64),
+    page_size: usize,
+    sample_read: R,
+    scan: S,
+    check_cancelled: &F,
+) -> (Result<Vec<ValuePair>>, bool)
+where
+    R: FnMut(u64, &mut [u8]) -> Result<()>,
+    S: FnOnce() -> Result<Vec<ValuePair>>,
+    F: Fn() -> bool,
+{
+    let Some(cache) = cache else {
+        return (scan(), false);
+    };
+
+    let (start, end) = region;
+    let Some(content_hash) = sample_region_hash(start, end, page_size, sample_read) else {
+        return (scan(), false);
+    };
+
+    if let Some(cached) = cache.lookup(fingerprint, start, end, content_hash) {
+        return (Ok(cached), true);
+    }
+
+    let result = scan();
+    if let Ok(ref results) = result
+        && !check_cancelled()
+        && let Err(e) = cache.store(fingerprint, start, end, content_hash, results)
+    {
+        warn!("Failed to store scan cache for 0x{:X}-0x{:X}: {:?}", start, end, e);
+    }
+    (result, false)
+}
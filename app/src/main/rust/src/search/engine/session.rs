@@ -0,0 +1,111 @@
+//! Search session persistence
+//!
+//! 长时间的模糊会话（上亿条初始结果加多次改善）在进程崩溃后全部丢失。会话保存为一个目录：
+//!
+//! ```text
+//! results.mres   结果集，与 nativeExportResults 相同的格式，从结果存储分段写出
+//! session.meta   magic "MAMUSESS"(8) | version(1) | mode(1) | compat(1) | 保留(1)
+//!                | pattern_len(4) | fuzzy_pattern_len(4，0 表示无) | count(8)
+//! ```
+//!
+//! compat 的第 0 位是是否请求了兼容模式，第 1 位是值捕获是否仍在延迟。
+//! 结果文件先写完，元数据最后通过改名写入，目录中有合法的元数据就说明结果文件是完整的。
+//! 结果存储的文件之后还会被原地改写，不能直接链接到会话目录，所以结果集总是写出一份。
+
+use crate::search::result_manager::SearchResultMode;
+use anyhow::{Result, anyhow};
+use std::path::{Path, PathBuf};
+
+const MAGIC: [u8; 8] = *b"MAMUSESS";
+const VERSION: u8 = 1;
+const META_SIZE: usize = 28;
+
+const COMPAT_REQUESTED: u8 = 1;
+const COMPAT_DEFERRED: u8 = 2;
+
+/// 会话目录中的结果文件名
+pub const SESSION_RESULTS_FILE: &str = "results.mres";
+/// 会话目录中的元数据文件名
+pub const SESSION_META_FILE: &str = "session.meta";
+/// 相对路径的会话保存在缓存目录下的这个子目录中
+pub const SESSION_DIR_NAME: &str = "sessions";
+
+/// 会话元数据
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionMeta {
+    pub mode: SearchResultMode,
+    pub compat_requested: bool,
+    pub compat_deferred: bool,
+    /// 最近一次特征码或文本搜索的长度
+    pub pattern_len: Option<usize>,
+    /// 模糊结果中变长值的读取长度
+    pub fuzzy_pattern_len: Option<usize>,
+    pub count: usize,
+}
+
+impl SessionMeta {
+    pub fn encode(&self) -> Result<[u8; META_SIZE]> {
+        let len_field = |len: Option<usize>| u32::try_from(len.unwrap_or(0)).map_err(|_| anyhow!("Pattern length too large"));
+        let mut meta = [0u8; META_SIZE];
+        meta[..8].copy_from_slice(&MAGIC);
+        meta[8] = VERSION;
+        meta[9] = match self.mode {
+            SearchResultMode::Exact => 0,
+            SearchResultMode::Fuzzy => 1,
+        };
+        meta[10] = if self.compat_requested { COMPAT_REQUESTED } else { 0 } | if self.compat_deferred { COMPAT_DEFERRED } else { 0 };
+        meta[12..16].copy_from_slice(&len_field(self.pattern_len)?.to_le_bytes());
+        meta[16..20].copy_from_slice(&len_field(self.fuzzy_pattern_len)?.to_le_bytes());
+        meta[20..28].copy_from_slice(&(self.count as u64).to_le_bytes());
+        Ok(meta)
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != META_SIZE || bytes[..8] != MAGIC {
+            return Err(anyhow!("Not a search session: bad magic"));
+        }
+        if bytes[8] != VERSION {
+            return Err(anyhow!("Unsupported search session version {}", bytes[8]));
+        }
+        let mode = match bytes[9] {
+            0 => SearchResultMode::Exact,
+            1 => SearchResultMode::Fuzzy,
+            mode => return Err(anyhow!("Invalid result mode {} in search session", mode)),
+        };
+        let len_at = |offset: usize| {
+            let len = u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap()) as usize;
+            (len > 0).then_some(len)
+        };
+        Ok(Self {
+            mode,
+            compat_requested: bytes[10] & COMPAT_REQUESTED != 0,
+            compat_deferred: bytes[10] & COMPAT_DEFERRED != 0,
+            pattern_len: len_at(12),
+            fuzzy_pattern_len: len_at(16),
+            count: u64::from_le_bytes(bytes[20..28].try_into().unwrap()) as usize,
+        })
+    }
+
+    /// 先写临时文件再改名
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        let path = dir.join(SESSION_META_FILE);
+        let tmp_path = path.with_extension("part");
+        std::fs::write(&tmp_path, self.encode()?).map_err(|e| anyhow!("Failed to write session metadata {:?}: {}", tmp_path, e))?;
+        std::fs::rename(&tmp_path, &path).map_err(|e| anyhow!("Failed to move session metadata to {:?}: {}", path, e))
+    }
+
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(SESSION_META_FILE);
+        let bytes = std::fs::read(&path).map_err(|e| anyhow!("Failed to read session metadata {:?}: {}", path, e))?;
+        Self::decode(&bytes)
+    }
+}
+
+/// 会话目录：绝对路径原样使用，相对路径放在缓存目录的 sessions 子目录下
+pub fn session_dir(cache_dir: &Path, path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        cache_dir.join(SESSION_DIR_NAME).join(path)
+    }
+}
@@ -1,30 +1,131 @@
 //! Shared buffer for lock-free communication between Kotlin and Rust.
 //!
-//! Memory layout (32 bytes):
+//! Memory layout (96 bytes, version 3):
 //! ```text
-//! [0-3]   status         (Rust writes)  SearchStatus enum
-//! [4-7]   progress       (Rust writes)  0-100
-//! [8-11]  regions_done   (Rust writes)  completed region count
-//! [12-19] found_count    (Rust writes)  total results found (i64)
-//! [20-23] heartbeat      (Rust writes)  periodic random value
-//! [24-27] cancel_flag    (Kotlin writes) 1 = cancel requested
-//! [28-31] error_code     (Rust writes)  error code when status is Error
+//! [0-15]  header         (Rust writes)  magic, layout version, capabilities, size, see `core::shared_header`
+//! [16-19] status         (Rust writes)  SearchStatus enum
+//! [20-23] progress       (Rust writes)  0-100
+//! [24-27] regions_done   (Rust writes)  completed region count
+//! [28-35] found_count    (Rust writes)  total results found (i64)
+//! [36-39] heartbeat      (Rust writes)  periodic random value
+//! [40-43] cancel_flag    (Kotlin writes) 1 = cancel requested
+//! [44-47] error_code     (Rust writes)  error code when status is Error
+//! [48-51] flags          (Rust writes)  event bits, see `flags`
+//! [52-59] readable_bytes (Rust writes)  bytes read successfully by the last scan (i64)
+//! [60-67] failed_bytes   (Rust writes)  bytes the last scan failed to read (i64)
+//! [68-71] phase          (Rust writes)  SearchPhase enum, what the search is doing while Searching
+//! [72-75] phase_progress (Rust writes)  0-100 within the current phase after Collecting
+//! [76-83] bytes_scanned  (Rust writes)  bytes the scan has covered so far, readable + failed (i64)
+//! [84-91] failed_pages   (Rust writes)  pages the scan failed to read so far (i64)
+//! [92-95] throughput     (Rust writes)  average MB/s of the scan so far (f32)
 //! ```
+//!
+//! The header is written once when the buffer is set; Kotlin checks it before reading any other field.
+//! The time of the last heartbeat is kept on the Rust side only, for the search watchdog.
 
-use std::sync::atomic::{AtomicPtr, Ordering, fence};
+use crate::core::self_regions::{register_self_region, unregister_self_region};
+use crate::core::shared_header::{self, HEADER_SIZE, SharedHeader};
+use serde::Serialize;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicPtr, AtomicU64, Ordering, fence};
+use std::time::{Duration, Instant};
 
 /// Shared buffer size in bytes.
-pub const SHARED_BUFFER_SIZE: usize = 32;
-
-/// Offsets for shared buffer fields.
-pub mod offsets {
-    pub const STATUS: usize = 0;
-    pub const PROGRESS: usize = 4;
-    pub const REGIONS_DONE: usize = 8;
-    pub const FOUND_COUNT: usize = 12;
-    pub const HEARTBEAT: usize = 20;
-    pub const CANCEL_FLAG: usize = 24;
-    pub const ERROR_CODE: usize = 28;
+pub const SHARED_BUFFER_SIZE: usize = 96;
+
+/// Layout version, bumped whenever fields are added or moved.
+pub const SHARED_BUFFER_VERSION: i32 = 3;
+
+/// Header magic of the search buffer, "MSRC".
+pub const SHARED_BUFFER_MAGIC: u32 = u32::from_le_bytes(*b"MSRC");
+
+/// Field offsets of the shared buffer, the only place that knows the byte layout.
+pub mod layout {
+    pub const STATUS: usize = 16;
+    pub const PROGRESS: usize = 20;
+    pub const REGIONS_DONE: usize = 24;
+    pub const FOUND_COUNT: usize = 28;
+    pub const HEARTBEAT: usize = 36;
+    pub const CANCEL_FLAG: usize = 40;
+    pub const ERROR_CODE: usize = 44;
+    pub const FLAGS: usize = 48;
+    pub const READABLE_BYTES: usize = 52;
+    pub const FAILED_BYTES: usize = 60;
+    pub const PHASE: usize = 68;
+    pub const PHASE_PROGRESS: usize = 72;
+    pub const BYTES_SCANNED: usize = 76;
+    pub const FAILED_PAGES: usize = 84;
+    pub const THROUGHPUT: usize = 92;
+
+    /// Every field as (offset, size) in layout order.
+    pub const FIELDS: [(usize, usize); 15] = [
+        (STATUS, 4),
+        (PROGRESS, 4),
+        (REGIONS_DONE, 4),
+        (FOUND_COUNT, 8),
+        (HEARTBEAT, 4),
+        (CANCEL_FLAG, 4),
+        (ERROR_CODE, 4),
+        (FLAGS, 4),
+        (READABLE_BYTES, 8),
+        (FAILED_BYTES, 8),
+        (PHASE, 4),
+        (PHASE_PROGRESS, 4),
+        (BYTES_SCANNED, 8),
+        (FAILED_PAGES, 8),
+        (THROUGHPUT, 4),
+    ];
+}
+
+const _: () = assert!(shared_header::fields_fit(&layout::FIELDS, SHARED_BUFFER_SIZE));
+const _: () = assert!(layout::THROUGHPUT + 4 == SHARED_BUFFER_SIZE);
+const _: () = assert!(layout::STATUS == HEADER_SIZE);
+
+/// Bits of the capabilities field in the header.
+pub mod capabilities {
+    /// phase and phase_progress are reported.
+    pub const PHASES: i32 = 1;
+    /// readable_bytes, failed_bytes, bytes_scanned, failed_pages and throughput are reported.
+    pub const SCAN_STATS: i32 = 2;
+    /// The flags field carries event bits.
+    pub const EVENT_FLAGS: i32 = 4;
+    /// status can be PartiallyCompleted after a cancelled fuzzy initial scan.
+    pub const PARTIAL_STATUS: i32 = 8;
+    /// status can be CompletedTruncated after an initial scan that reached its result cap.
+    pub const TRUNCATED_STATUS: i32 = 16;
+    /// status can be SnapshotOnly after a condition refine on exact results that had no old values.
+    pub const SNAPSHOT_STATUS: i32 = 32;
+}
+
+/// Header written when the buffer is set.
+pub const SHARED_BUFFER_HEADER: SharedHeader = SharedHeader {
+    magic: SHARED_BUFFER_MAGIC,
+    layout_version: SHARED_BUFFER_VERSION,
+    capabilities: capabilities::PHASES
+        | capabilities::SCAN_STATS
+        | capabilities::EVENT_FLAGS
+        | capabilities::PARTIAL_STATUS
+        | capabilities::TRUNCATED_STATUS
+        | capabilities::SNAPSHOT_STATUS,
+    size: SHARED_BUFFER_SIZE,
+};
+
+/// Bits of the flags field.
+pub mod flags {
+    /// Deferred compatibility mode capture ran after a refine, fuzzy switching is available.
+    pub const COMPAT_CAPTURED: i32 = 1;
+    /// The scan reused cached results for at least one region instead of reading memory.
+    pub const SCAN_CACHE_REUSED: i32 = 2;
+    /// The scan found nothing and read almost no bytes, so the empty result says nothing about the value.
+    pub const NOTHING_READABLE: i32 = 4;
+    /// Memory pressure crossed the soft limit, results were written to the store during the scan.
+    pub const MEMORY_PRESSURE_STREAMED: i32 = 8;
+    /// Memory pressure crossed the hard limit and some regions were not scanned; the results are partial.
+    pub const PARTIAL_RESULTS: i32 = 16;
+    /// A condition refine on exact results only captured the current values; the next one compares against them.
+    pub const SNAPSHOT_CAPTURED: i32 = 32;
+    /// The initial scan stopped early because it reached the result cap of the query; the results are partial.
+    pub const RESULTS_TRUNCATED: i32 = 64;
 }
 
 /// Search status enum.
@@ -41,6 +142,13 @@ pub enum SearchStatus {
     Cancelled = 3,
     /// Search failed with error.
     Error = 4,
+    /// A fuzzy initial scan was cancelled; results of the regions it finished are kept and it can be resumed.
+    PartiallyCompleted = 5,
+    /// An initial scan reached its result cap and stopped early; the results found so far are kept.
+    CompletedTruncated = 6,
+    /// A condition refine on exact results had no old values to compare against; it only recorded the current values
+    /// and did not apply the condition. The next condition refine compares against them.
+    SnapshotOnly = 7,
 }
 
 impl From<i32> for SearchStatus {
@@ -51,11 +159,52 @@ impl From<i32> for SearchStatus {
             2 => SearchStatus::Completed,
             3 => SearchStatus::Cancelled,
             4 => SearchStatus::Error,
+            5 => SearchStatus::PartiallyCompleted,
+            6 => SearchStatus::CompletedTruncated,
+            7 => SearchStatus::SnapshotOnly,
             _ => SearchStatus::Idle,
         }
     }
 }
 
+/// What a running search is doing.
+///
+/// `progress` covers Collecting only; the later phases report through `phase_progress`,
+/// so the UI does not sit at 100% while results are sorted and stored.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchPhase {
+    /// Scanning regions and collecting matches.
+    Collecting = 0,
+    /// Sorting and deduplicating the collected matches.
+    Sorting = 1,
+    /// Reading current values of the matches for compatibility mode.
+    Capturing = 2,
+    /// Writing the results to the result store.
+    Storing = 3,
+}
+
+impl From<i32> for SearchPhase {
+    fn from(value: i32) -> Self {
+        match value {
+            1 => SearchPhase::Sorting,
+            2 => SearchPhase::Capturing,
+            3 => SearchPhase::Storing,
+            _ => SearchPhase::Collecting,
+        }
+    }
+}
+
+/// Read statistics of the current or last scan, see `SharedBuffer::read_search_stats`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SearchStats {
+    pub bytes_scanned: u64,
+    pub readable_bytes: u64,
+    pub failed_bytes: u64,
+    pub failed_pages: u64,
+    pub mb_per_sec: f32,
+}
+
 /// Error codes for search operations.
 #[repr(i32)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -66,6 +215,16 @@ pub enum SearchErrorCode {
     MemoryReadFailed = 3,
     InternalError = 4,
     AlreadySearching = 5,
+    /// No heartbeat within the stall timeout, the search was aborted by the watchdog.
+    Stalled = 6,
+    /// The bound process exited, the search was cancelled and the process unbound.
+    ProcessDied = 7,
+}
+
+/// Milliseconds on a monotonic clock, used for heartbeat timestamps.
+fn monotonic_millis() -> u64 {
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    EPOCH.get_or_init(Instant::now).elapsed().as_millis() as u64
 }
 
 /// Thread-safe shared buffer for Kotlin-Rust communication.
@@ -76,6 +235,8 @@ pub enum SearchErrorCode {
 pub struct SharedBuffer {
     ptr: AtomicPtr<u8>,
     len: usize,
+    /// `monotonic_millis` of the last heartbeat or reset
+    last_heartbeat: AtomicU64,
 }
 
 unsafe impl Send for SharedBuffer {}
@@ -87,6 +248,7 @@ impl SharedBuffer {
         Self {
             ptr: AtomicPtr::new(std::ptr::null_mut()),
             len: 0,
+            last_heartbeat: AtomicU64::new(0),
         }
     }
 
@@ -100,17 +262,30 @@ impl SharedBuffer {
         if ptr.is_null() || len < SHARED_BUFFER_SIZE {
             return false;
         }
-        self.ptr.store(ptr, Ordering::Release);
+        let previous = self.ptr.swap(ptr, Ordering::AcqRel);
+        if !previous.is_null() {
+            unregister_self_region(previous);
+        }
+        register_self_region(ptr, len);
         self.len = len;
 
-        // Initialize buffer to zeros.
+        SHARED_BUFFER_HEADER.write(|offset, value| self.write_i32(offset, value));
+        // Initialize the fields after the header.
         self.reset();
         true
     }
 
+    /// Layout version in the header, 0 when the buffer is not set.
+    pub fn layout_version(&self) -> i32 {
+        SHARED_BUFFER_HEADER.read_version(|offset| self.read_i32(offset))
+    }
+
     /// Clears the buffer reference.
     pub fn clear(&mut self) {
-        self.ptr.store(std::ptr::null_mut(), Ordering::Release);
+        let previous = self.ptr.swap(std::ptr::null_mut(), Ordering::AcqRel);
+        if !previous.is_null() {
+            unregister_self_region(previous);
+        }
         self.len = 0;
     }
 
@@ -121,6 +296,7 @@ impl SharedBuffer {
 
     /// Resets all fields to initial values.
     pub fn reset(&self) {
+        self.stamp_heartbeat();
         if !self.is_set() {
             return;
         }
@@ -130,6 +306,12 @@ impl SharedBuffer {
         self.write_found_count(0);
         self.write_heartbeat(0);
         self.write_error_code(SearchErrorCode::None);
+        self.write_i32(layout::FLAGS, 0);
+        self.write_read_bytes(0, 0);
+        self.write_phase(SearchPhase::Collecting, 0);
+        self.write_bytes_scanned(0);
+        self.write_failed_pages(0);
+        self.write_throughput(0.0);
         // Note: We don't reset cancel_flag here because Kotlin controls it.
     }
 
@@ -141,49 +323,140 @@ impl SharedBuffer {
     pub fn write_status(&self, status: SearchStatus) {
         // Ensure all previous writes are visible before status change.
         fence(Ordering::Release);
-        self.write_i32(offsets::STATUS, status as i32);
+        self.write_i32(layout::STATUS, status as i32);
     }
 
     /// Writes progress value (0-100).
     #[inline]
     pub fn write_progress(&self, progress: i32) {
-        self.write_i32(offsets::PROGRESS, progress.clamp(0, 100));
+        self.write_i32(layout::PROGRESS, progress.clamp(0, 100));
     }
 
     /// Writes completed region count.
     #[inline]
     pub fn write_regions_done(&self, count: i32) {
-        self.write_i32(offsets::REGIONS_DONE, count);
+        self.write_i32(layout::REGIONS_DONE, count);
     }
 
     /// Writes total found count.
     #[inline]
     pub fn write_found_count(&self, count: i64) {
-        self.write_i64(offsets::FOUND_COUNT, count);
+        self.write_i64(layout::FOUND_COUNT, count);
     }
 
     /// Writes heartbeat value.
     #[inline]
     pub fn write_heartbeat(&self, value: i32) {
-        self.write_i32(offsets::HEARTBEAT, value);
+        self.write_i32(layout::HEARTBEAT, value);
     }
 
     /// Writes error code.
     #[inline]
     pub fn write_error_code(&self, code: SearchErrorCode) {
-        self.write_i32(offsets::ERROR_CODE, code as i32);
+        self.write_i32(layout::ERROR_CODE, code as i32);
+    }
+
+    /// Writes readable and failed byte counters of the scan.
+    #[inline]
+    pub fn write_read_bytes(&self, readable: u64, failed: u64) {
+        self.write_i64(layout::READABLE_BYTES, readable as i64);
+        self.write_i64(layout::FAILED_BYTES, failed as i64);
+    }
+
+    /// Writes the number of bytes the scan has covered.
+    #[inline]
+    pub fn write_bytes_scanned(&self, bytes: u64) {
+        self.write_i64(layout::BYTES_SCANNED, bytes as i64);
+    }
+
+    /// Writes the number of pages the scan failed to read.
+    #[inline]
+    pub fn write_failed_pages(&self, pages: u64) {
+        self.write_i64(layout::FAILED_PAGES, pages as i64);
+    }
+
+    /// Writes the average throughput of the scan in MB/s.
+    #[inline]
+    pub fn write_throughput(&self, mb_per_sec: f32) {
+        self.write_i32(layout::THROUGHPUT, mb_per_sec.to_bits() as i32);
+    }
+
+    /// Writes the current phase and the progress within it (0-100).
+    #[inline]
+    pub fn write_phase(&self, phase: SearchPhase, progress: i32) {
+        self.write_i32(layout::PHASE_PROGRESS, progress.clamp(0, 100));
+        self.write_i32(layout::PHASE, phase as i32);
+    }
+
+    /// Writes the progress within the current phase (0-100).
+    #[inline]
+    pub fn write_phase_progress(&self, progress: i32) {
+        self.write_i32(layout::PHASE_PROGRESS, progress.clamp(0, 100));
+    }
+
+    /// Sets bits in the flags field.
+    #[inline]
+    pub fn set_flag(&self, flag: i32) {
+        self.write_i32(layout::FLAGS, self.read_i32(layout::FLAGS) | flag);
+    }
+
+    /// Reads the last written search status (Idle when the buffer is not set).
+    #[inline]
+    pub fn read_status(&self) -> SearchStatus {
+        SearchStatus::from(self.read_i32(layout::STATUS))
+    }
+
+    /// Reads the event bits of the flags field.
+    #[inline]
+    pub fn read_flags(&self) -> i32 {
+        self.read_i32(layout::FLAGS)
+    }
+
+    /// Reads the last written error code.
+    #[inline]
+    pub fn read_error_code(&self) -> i32 {
+        self.read_i32(layout::ERROR_CODE)
+    }
+
+    /// Reads the last written progress value.
+    #[inline]
+    pub fn read_progress(&self) -> i32 {
+        self.read_i32(layout::PROGRESS)
+    }
+
+    /// Reads the last written phase.
+    #[inline]
+    pub fn read_phase(&self) -> SearchPhase {
+        SearchPhase::from(self.read_i32(layout::PHASE))
+    }
+
+    /// Reads the read statistics of the current or last scan (zeros when the buffer is not set).
+    pub fn read_search_stats(&self) -> SearchStats {
+        SearchStats {
+            bytes_scanned: self.read_i64(layout::BYTES_SCANNED) as u64,
+            readable_bytes: self.read_i64(layout::READABLE_BYTES) as u64,
+            failed_bytes: self.read_i64(layout::FAILED_BYTES) as u64,
+            failed_pages: self.read_i64(layout::FAILED_PAGES) as u64,
+            mb_per_sec: f32::from_bits(self.read_i32(layout::THROUGHPUT) as u32),
+        }
     }
 
     /// Reads cancel flag that is set by Kotlin.
     #[inline]
     pub fn is_cancel_requested(&self) -> bool {
-        self.read_i32(offsets::CANCEL_FLAG) != 0
+        self.read_i32(layout::CANCEL_FLAG) != 0
+    }
+
+    /// Sets the cancel flag the way Kotlin does.
+    #[cfg(test)]
+    pub(crate) fn request_cancel(&self) {
+        self.write_i32(layout::CANCEL_FLAG, 1);
     }
 
     /// Clears the cancel flag.
     #[inline]
     pub fn clear_cancel_flag(&self) {
-        self.write_i32(offsets::CANCEL_FLAG, 0);
+        self.write_i32(layout::CANCEL_FLAG, 0);
     }
 
     /// Updates progress information atomically.
@@ -199,6 +472,18 @@ impl SharedBuffer {
     pub fn tick_heartbeat(&self) {
         let heartbeat: i32 = rand::random();
         self.write_heartbeat(heartbeat);
+        self.stamp_heartbeat();
+    }
+
+    /// Time since the last heartbeat or reset.
+    pub fn heartbeat_age(&self) -> Duration {
+        let last = self.last_heartbeat.load(Ordering::Relaxed);
+        Duration::from_millis(monotonic_millis().saturating_sub(last))
+    }
+
+    #[inline]
+    fn stamp_heartbeat(&self) {
+        self.last_heartbeat.store(monotonic_millis(), Ordering::Relaxed);
     }
 
     #[inline]
@@ -231,6 +516,15 @@ impl SharedBuffer {
         }
         unsafe { std::ptr::read_unaligned(ptr.add(offset) as *const i32) }
     }
+
+    #[inline]
+    fn read_i64(&self, offset: usize) -> i64 {
+        let ptr = self.ptr.load(Ordering::Acquire);
+        if ptr.is_null() || offset + 8 > self.len {
+            return 0;
+        }
+        unsafe { std::ptr::read_unaligned(ptr.add(offset) as *const i64) }
+    }
 }
 
 impl Default for SharedBuffer {
@@ -245,14 +539,83 @@ mod tests {
 
     #[test]
     fn test_shared_buffer_layout() {
-        assert_eq!(offsets::STATUS, 0);
-        assert_eq!(offsets::PROGRESS, 4);
-        assert_eq!(offsets::REGIONS_DONE, 8);
-        assert_eq!(offsets::FOUND_COUNT, 12);
-        assert_eq!(offsets::HEARTBEAT, 20);
-        assert_eq!(offsets::CANCEL_FLAG, 24);
-        assert_eq!(offsets::ERROR_CODE, 28);
-        assert_eq!(SHARED_BUFFER_SIZE, 32);
+        assert_eq!(layout::STATUS, 16);
+        assert_eq!(layout::PROGRESS, 20);
+        assert_eq!(layout::REGIONS_DONE, 24);
+        assert_eq!(layout::FOUND_COUNT, 28);
+        assert_eq!(layout::HEARTBEAT, 36);
+        assert_eq!(layout::CANCEL_FLAG, 40);
+        assert_eq!(layout::ERROR_CODE, 44);
+        assert_eq!(layout::FLAGS, 48);
+        assert_eq!(layout::READABLE_BYTES, 52);
+        assert_eq!(layout::FAILED_BYTES, 60);
+        assert_eq!(layout::PHASE, 68);
+        assert_eq!(layout::PHASE_PROGRESS, 72);
+        assert_eq!(layout::BYTES_SCANNED, 76);
+        assert_eq!(layout::FAILED_PAGES, 84);
+        assert_eq!(layout::THROUGHPUT, 92);
+        assert_eq!(SHARED_BUFFER_SIZE, 96);
+    }
+
+    fn raw_i32(memory: &[u8], offset: usize) -> i32 {
+        i32::from_le_bytes(memory[offset..offset + 4].try_into().unwrap())
+    }
+
+    fn raw_i64(memory: &[u8], offset: usize) -> i64 {
+        i64::from_le_bytes(memory[offset..offset + 8].try_into().unwrap())
+    }
+
+    /// 每个字段写入后按 Kotlin 的方式从原始字节读取，确认偏移和宽度
+    #[test]
+    fn test_every_field_round_trips_through_raw_bytes() {
+        let mut memory = [0xAAu8; SHARED_BUFFER_SIZE];
+        let mut buffer = SharedBuffer::new();
+        assert!(buffer.set(memory.as_mut_ptr(), memory.len()));
+
+        buffer.write_status(SearchStatus::Error);
+        buffer.update_progress(42, 7, 0x1_2345_6789);
+        buffer.write_heartbeat(-5);
+        buffer.write_error_code(SearchErrorCode::Stalled);
+        buffer.set_flag(flags::PARTIAL_RESULTS | flags::SCAN_CACHE_REUSED);
+        buffer.write_read_bytes(3 << 32, 5 << 32);
+        buffer.write_phase(SearchPhase::Capturing, 64);
+        buffer.write_bytes_scanned(8 << 32);
+        buffer.write_failed_pages(1 << 33);
+        buffer.write_throughput(2.25);
+        // Kotlin 写入的取消标志
+        memory[layout::CANCEL_FLAG..layout::CANCEL_FLAG + 4].copy_from_slice(&1i32.to_le_bytes());
+        assert!(buffer.is_cancel_requested());
+
+        assert_eq!(&memory[..4], b"MSRC");
+        assert_eq!(raw_i32(&memory, shared_header::offsets::LAYOUT_VERSION), SHARED_BUFFER_VERSION);
+        assert_eq!(raw_i32(&memory, shared_header::offsets::CAPABILITIES), SHARED_BUFFER_HEADER.capabilities);
+        assert_eq!(raw_i32(&memory, shared_header::offsets::SIZE), SHARED_BUFFER_SIZE as i32);
+        assert_eq!(raw_i32(&memory, layout::STATUS), SearchStatus::Error as i32);
+        assert_eq!(raw_i32(&memory, layout::PROGRESS), 42);
+        assert_eq!(raw_i32(&memory, layout::REGIONS_DONE), 7);
+        assert_eq!(raw_i64(&memory, layout::FOUND_COUNT), 0x1_2345_6789);
+        assert_eq!(raw_i32(&memory, layout::HEARTBEAT), -5);
+        assert_eq!(raw_i32(&memory, layout::CANCEL_FLAG), 1);
+        assert_eq!(raw_i32(&memory, layout::ERROR_CODE), SearchErrorCode::Stalled as i32);
+        assert_eq!(raw_i32(&memory, layout::FLAGS), flags::PARTIAL_RESULTS | flags::SCAN_CACHE_REUSED);
+        assert_eq!(raw_i64(&memory, layout::READABLE_BYTES), 3 << 32);
+        assert_eq!(raw_i64(&memory, layout::FAILED_BYTES), 5 << 32);
+        assert_eq!(raw_i32(&memory, layout::PHASE), SearchPhase::Capturing as i32);
+        assert_eq!(raw_i32(&memory, layout::PHASE_PROGRESS), 64);
+        assert_eq!(raw_i64(&memory, layout::BYTES_SCANNED), 8 << 32);
+        assert_eq!(raw_i64(&memory, layout::FAILED_PAGES), 1 << 33);
+        assert_eq!(f32::from_bits(raw_i32(&memory, layout::THROUGHPUT) as u32), 2.25);
+
+        // reset 清空字段但保留头和取消标志
+        buffer.reset();
+        assert_eq!(buffer.layout_version(), SHARED_BUFFER_VERSION);
+        for &(offset, size) in layout::FIELDS.iter().filter(|(offset, _)| *offset != layout::CANCEL_FLAG) {
+            assert!(memory[offset..offset + size].iter().all(|&b| b == 0), "field at {} not reset", offset);
+        }
+        assert!(buffer.is_cancel_requested());
+
+        buffer.clear();
+        assert_eq!(buffer.layout_version(), 0);
     }
 
     #[test]
@@ -262,6 +625,69 @@ mod tests {
         assert_eq!(SearchStatus::from(2), SearchStatus::Completed);
         assert_eq!(SearchStatus::from(3), SearchStatus::Cancelled);
         assert_eq!(SearchStatus::from(4), SearchStatus::Error);
+        assert_eq!(SearchStatus::from(5), SearchStatus::PartiallyCompleted);
+        assert_eq!(SearchStatus::from(6), SearchStatus::CompletedTruncated);
+        assert_eq!(SearchStatus::from(7), SearchStatus::SnapshotOnly);
         assert_eq!(SearchStatus::from(99), SearchStatus::Idle);
     }
+
+    #[test]
+    fn test_phase_round_trip() {
+        let mut memory = [0u8; SHARED_BUFFER_SIZE];
+        let mut buffer = SharedBuffer::new();
+        assert!(buffer.set(memory.as_mut_ptr(), memory.len()));
+
+        buffer.write_phase(SearchPhase::Storing, 140);
+        assert_eq!(buffer.read_phase(), SearchPhase::Storing);
+        assert_eq!(buffer.read_i32(layout::PHASE_PROGRESS), 100);
+
+        buffer.reset();
+        assert_eq!(buffer.read_phase(), SearchPhase::Collecting);
+        assert_eq!(buffer.read_i32(layout::PHASE_PROGRESS), 0);
+        buffer.clear();
+    }
+
+    #[test]
+    fn test_search_stats_round_trip() {
+        let mut memory = [0u8; SHARED_BUFFER_SIZE];
+        let mut buffer = SharedBuffer::new();
+        // 旧版本大小的缓冲区不再接受
+        assert!(!buffer.set(memory.as_mut_ptr(), 60));
+        assert!(buffer.set(memory.as_mut_ptr(), memory.len()));
+        assert_eq!(buffer.layout_version(), SHARED_BUFFER_VERSION);
+
+        buffer.write_read_bytes(3 << 20, 1 << 20);
+        buffer.write_bytes_scanned(4 << 20);
+        buffer.write_failed_pages(256);
+        buffer.write_throughput(12.5);
+        assert_eq!(
+            buffer.read_search_stats(),
+            SearchStats { bytes_scanned: 4 << 20, readable_bytes: 3 << 20, failed_bytes: 1 << 20, failed_pages: 256, mb_per_sec: 12.5 }
+        );
+
+        buffer.reset();
+        assert_eq!(buffer.read_search_stats().bytes_scanned, 0);
+        assert_eq!(buffer.read_search_stats().mb_per_sec, 0.0);
+        buffer.clear();
+        assert_eq!(buffer.read_search_stats().failed_pages, 0);
+    }
+
+    #[test]
+    fn test_heartbeat_age() {
+        let mut memory = [0u8; SHARED_BUFFER_SIZE];
+        let mut buffer = SharedBuffer::new();
+        assert!(buffer.set(memory.as_mut_ptr(), memory.len()));
+
+        std::thread::sleep(Duration::from_millis(30));
+        let stale = buffer.heartbeat_age();
+        assert!(stale >= Duration::from_millis(30));
+        buffer.tick_heartbeat();
+        assert!(buffer.heartbeat_age() < stale);
+
+        std::thread::sleep(Duration::from_millis(30));
+        let stale = buffer.heartbeat_age();
+        buffer.reset();
+        assert!(buffer.heartbeat_age() < stale);
+        buffer.clear();
+    }
 }
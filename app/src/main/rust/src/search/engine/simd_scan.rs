@@ -0,0 +1,148 @@
+//! SIMD comparison kernels for 4-byte scans
+//!
+//! 首次扫描的逐元素比较是热点。对最常见的三种搜索：Dword 精确值、Dword 范围、Float 约等于，
+//! 按 4 字节对齐扫描时每次比较 16 字节（4 个元素），只有存在命中时才逐个取出位置。
+//!
+//! - aarch64：运行时检测到 NEON 时使用 `std::arch::aarch64`，其它平台直接用标量实现
+//! - 标量实现与 `SearchValue::matched` 的语义完全一致，是 SIMD 实现的参考；
+//!   Float 在 SIMD 里同样先转换成 f64 再比较，结果与标量逐位相同
+//! - 不足 16 字节的尾部交给标量实现
+
+use super::super::types::{SearchValue, ValueType};
+
+/// 可以向量化的比较
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum CompareKernel {
+    /// Dword 精确值
+    DwordEq(u32),
+    /// Dword 范围 [lo, hi]，exclude 时取范围外
+    DwordRange { lo: i32, hi: i32, exclude: bool },
+    /// Float 约等于：|value - x| < epsilon，在 f64 下计算
+    FloatNear { value: f64, epsilon: f64 },
+}
+
+impl CompareKernel {
+    /// 按 4 字节对齐扫描的 Dword / Float 搜索值对应的内核，其它情况返回 None
+    pub fn for_target(target: &SearchValue, align: usize) -> Option<Self> {
+        if align != 4 {
+            return None;
+        }
+        match *target {
+            SearchValue::FixedInt { value, value_type: ValueType::Dword } => Some(Self::DwordEq(u32::from_le_bytes([value[0], value[1], value[2], value[3]]))),
+            SearchValue::RangeInt { start, end, value_type: ValueType::Dword, exclude } => {
+                // 范围与 i32 没有交集时交给标量路径
+                let lo = start.max(i32::MIN as i128);
+                let hi = end.min(i32::MAX as i128);
+                (lo <= hi).then_some(Self::DwordRange { lo: lo as i32, hi: hi as i32, exclude })
+            },
+            SearchValue::FixedFloat { value, value_type: ValueType::Float } => Some(Self::FloatNear { value, epsilon: f32::EPSILON as f64 }),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn matches(&self, word: [u8; 4]) -> bool {
+        match *self {
+            Self::DwordEq(value) => u32::from_le_bytes(word) == value,
+            Self::DwordRange { lo, hi, exclude } => {
+                let x = i32::from_le_bytes(word);
+                (lo <= x && x <= hi) != exclude
+            },
+            Self::FloatNear { value, epsilon } => (value - f32::from_le_bytes(word) as f64).abs() < epsilon,
+        }
+    }
+}
+
+/// 标量参考实现：依次比较 `bytes` 中的每个 4 字节元素，命中时把地址（`bytes` 起始对应 `base`）追加到 `out`
+pub(crate) fn scan_words_scalar(kernel: &CompareKernel, bytes: &[u8], base: u64, out: &mut Vec<u64>) {
+    for (i, word) in bytes.chunks_exact(4).enumerate() {
+        if kernel.matches([word[0], word[1], word[2], word[3]]) {
+            out.push(base + (i * 4) as u64);
+        }
+    }
+}
+
+/// 同 `scan_words_scalar`，支持时使用 SIMD
+pub(crate) fn scan_words(kernel: &CompareKernel, bytes: &[u8], base: u64, out: &mut Vec<u64>) {
+    #[cfg(target_arch = "aarch64")]
+    {
+        if std::arch::is_aarch64_feature_detected!("neon") {
+            // SAFETY: 已经检测到 NEON
+            unsafe { neon::scan_words(kernel, bytes, base, out) };
+            return;
+        }
+    }
+    scan_words_scalar(kernel, bytes, base, out);
+}
+
+#[cfg(target_arch = "aarch64")]
+mod neon {
+    use super::{scan_words_scalar, CompareKernel};
+    use std::arch::aarch64::*;
+
+    const LANES: usize = 4;
+    const STEP: usize = 16;
+
+    #[target_feature(enable = "neon")]
+    pub(super) fn scan_words(kernel: &CompareKernel, bytes: &[u8], base: u64, out: &mut Vec<u64>) {
+        let body = bytes.len() / STEP * STEP;
+        match *kernel {
+            CompareKernel::DwordEq(value) => {
+                let needle = vdupq_n_u32(value);
+                scan_body(bytes, body, base, out, |v| vceqq_u32(v, needle));
+            },
+            CompareKernel::DwordRange { lo, hi, exclude } => {
+                let lo = vdupq_n_s32(lo);
+                let hi = vdupq_n_s32(hi);
+                if exclude {
+                    scan_body(bytes, body, base, out, |v| {
+                        let x = vreinterpretq_s32_u32(v);
+                        vmvnq_u32(vandq_u32(vcgeq_s32(x, lo), vcleq_s32(x, hi)))
+                    });
+                } else {
+                    scan_body(bytes, body, base, out, |v| {
+                        let x = vreinterpretq_s32_u32(v);
+                        vandq_u32(vcgeq_s32(x, lo), vcleq_s32(x, hi))
+                    });
+                }
+            },
+            CompareKernel::FloatNear { value, epsilon } => {
+                let value = vdupq_n_f64(value);
+                let epsilon = vdupq_n_f64(epsilon);
+                scan_body(bytes, body, base, out, |v| {
+                    // f32 -> f64 是精确转换，之后的减法、绝对值和比较与标量实现相同
+                    let x = vreinterpretq_f32_u32(v);
+                    let low = vcltq_f64(vabsq_f64(vsubq_f64(value, vcvt_f64_f32(vget_low_f32(x)))), epsilon);
+                    let high = vcltq_f64(vabsq_f64(vsubq_f64(value, vcvt_high_f64_f32(x))), epsilon);
+                    vcombine_u32(vmovn_u64(low), vmovn_u64(high))
+                });
+            },
+        }
+        scan_words_scalar(kernel, &bytes[body..], base + body as u64, out);
+    }
+
+    /// 每次比较 16 字节，`compare` 返回每个元素的全 1 / 全 0 掩码
+    #[target_feature(enable = "neon")]
+    fn scan_body<C>(bytes: &[u8], body: usize, base: u64, out: &mut Vec<u64>, compare: C)
+    where
+        C: Fn(uint32x4_t) -> uint32x4_t,
+    {
+        let mut pos = 0;
+        while pos < body {
+            // SAFETY: pos + 16 <= body <= bytes.len()，vld1q_u8 不要求对齐
+            let v = vreinterpretq_u32_u8(unsafe { vld1q_u8(bytes.as_ptr().add(pos)) });
+            let mask = compare(v);
+            if vmaxvq_u32(mask) != 0 {
+                let mut lanes = [0u32; LANES];
+                // SAFETY: lanes 正好 4 个 u32
+                unsafe { vst1q_u32(lanes.as_mut_ptr(), mask) };
+                for (i, &lane) in lanes.iter().enumerate() {
+                    if lane != 0 {
+                        out.push(base + (pos + i * 4) as u64);
+                    }
+                }
+            }
+            pos += STEP;
+        }
+    }
+}
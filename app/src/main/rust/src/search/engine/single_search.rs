@@ -1,6 +1,10 @@
 use super::super::types::{SearchValue, ValueType};
+use super::batch_reader::{PageCache, PageCacheStats};
 use super::manager::{ValuePair, BPLUS_TREE_ORDER};
-use crate::core::DRIVER_MANAGER;
+use super::read_stats::ReadStats;
+use super::result_stream::REFINE_BATCH_SIZE;
+use super::simd_scan::{self, CompareKernel};
+use crate::core::{AccessQos, DRIVER_MANAGER};
 use crate::search::engine::memchr_ext::MemchrExt;
 use crate::search::{PAGE_MASK, PAGE_SIZE};
 use crate::wuwa::PageStatusBitmap;
@@ -13,9 +17,14 @@ use std::sync::atomic::{AtomicI64, AtomicUsize};
 use std::sync::Arc;
 
 /// 每个 rayon 任务扫描的粒度
-const PAR_SCAN_GRAIN: usize = 64 * 1024;
+pub(crate) const PAR_SCAN_GRAIN: usize = 64 * 1024;
 /// 使用memchr搜索大于1字节的数据
 const MEMCHR_FIND_ANCHOR: bool = true;
+/// 逐地址改善搜索合并读取的默认窗口
+pub const DEFAULT_REFINE_READ_WINDOW: usize = 16 * 1024;
+/// 合并读取窗口的下限和上限
+pub const MIN_REFINE_READ_WINDOW: usize = 4 * 1024;
+pub const MAX_REFINE_READ_WINDOW: usize = 64 * 1024;
 
 #[inline]
 fn first_aligned_pos(base_addr: u64, start_pos: usize, align: usize) -> usize {
@@ -26,8 +35,10 @@ fn first_aligned_pos(base_addr: u64, start_pos: usize, align: usize) -> usize {
     if rem == 0 { start_pos } else { start_pos + (align - rem) }
 }
 
+/// 按元素自身大小对齐搜索缓冲区，见 `search_in_chunks_aligned`
+#[allow(clippy::too_many_arguments)]
 #[inline]
-pub(crate) fn search_in_chunks_with_status(
+pub(crate) fn search_in_chunks_with_status<F>(
     buffer: &[u8],
     buffer_addr: u64,               // 当前读取的缓冲区对应目标进程的一块内存的起始地址
     region_start: u64,              // 搜索区域的起始地址
@@ -37,7 +48,37 @@ pub(crate) fn search_in_chunks_with_status(
     value_type: ValueType,          // 目标值类型
     page_status: &PageStatusBitmap, // 页面状态位图
     results: &mut Vec<ValuePair>,   // 搜索结果
-) {
+    check_cancelled: &F,            // 取消检查，见 `search_in_chunks_aligned`
+) where
+    F: Fn() -> bool + Sync,
+{
+    search_in_chunks_aligned(buffer, buffer_addr, region_start, region_end, element_size, element_size, target, value_type, page_status, results, check_cancelled);
+}
+
+/// 在缓冲区中搜索起始地址按 `align` 对齐的元素，结果按地址升序追加到 `results`
+///
+/// 扫描范围切成最多 `PAR_SCAN_GRAIN` 字节的段并行处理，每段开始前检查一次取消，段内不再检查；
+/// 取消后尚未开始的段直接跳过，已经开始的段会扫完
+///
+/// `align` 小于元素大小时元素可能跨页，跨入的页也必须读取成功；元素可以延伸到
+/// [region_start, region_end) 与缓冲区交集的末尾，调用方读取块时需要多读 `element_size - 1` 字节
+#[allow(clippy::too_many_arguments)]
+#[inline]
+pub(crate) fn search_in_chunks_aligned<F>(
+    buffer: &[u8],
+    buffer_addr: u64,
+    region_start: u64,
+    region_end: u64,
+    element_size: usize,
+    align: usize,
+    target: &SearchValue,
+    value_type: ValueType,
+    page_status: &PageStatusBitmap,
+    results: &mut Vec<ValuePair>,
+    check_cancelled: &F,
+) where
+    F: Fn() -> bool + Sync,
+{
     assert_eq!(buffer_addr as usize % *PAGE_SIZE, 0);
 
     let buffer_end = buffer_addr + buffer.len() as u64; // 结束地址
@@ -71,11 +112,18 @@ pub(crate) fn search_in_chunks_with_status(
     } else {
         false
     };
+    // Dword 精确值 / 范围、Float 约等于按 4 字节对齐扫描时使用 SIMD 内核
+    let kernel = if element_size == 4 { CompareKernel::for_target(target, align) } else { None };
 
     let hits = ranges
         .into_par_iter()
         .map(|(rs, re)| {
-            let estimated_matches = ((re - rs) / element_size) >> 2;
+            // 每个扫描粒度开始前检查一次取消，保证单个大区域内的取消延迟有上界
+            if check_cancelled() {
+                return Vec::new();
+            }
+
+            let estimated_matches = ((re - rs) / align) >> 2;
             let mut local = Vec::with_capacity(estimated_matches);
 
             // 单字节搜索加速
@@ -96,7 +144,7 @@ pub(crate) fn search_in_chunks_with_status(
 
                     for offset in memchr_iter(target_byte, page_slice) {
                         let addr = buffer_addr + (page_start + offset) as u64;
-                        if addr >= search_start && addr < search_end {
+                        if addr >= search_start && addr < search_end && addr.is_multiple_of(align as u64) {
                             local.push(addr);
                         }
                     }
@@ -108,7 +156,7 @@ pub(crate) fn search_in_chunks_with_status(
                 // memchr 多字节加速路径
                 let bytes = target.bytes().unwrap();
                 let first_byte = bytes[0];
-                let align_mask = (element_size - 1) as u64; // 对齐掩码（2^n - 1）
+                let align_mask = (align - 1) as u64; // 对齐掩码（2^n - 1）
 
                 // 按页遍历，只在成功页上搜索
                 let start_page_idx = rs / *PAGE_SIZE;
@@ -135,8 +183,10 @@ pub(crate) fn search_in_chunks_with_status(
                     for offset in memchr_iter(first_byte, page_slice) {
                         let actual_pos = page_start + offset;
 
-                        // 边界检查：确保有足够空间读取完整元素
-                        if actual_pos + element_size > page_end {
+                        // 边界检查：确保有足够空间读取完整元素，非自然对齐的元素跨入的下一页也必须读取成功
+                        if actual_pos + element_size > page_end
+                            && (actual_pos + element_size > scan_end_pos || !page_status.is_page_success((actual_pos + element_size - 1) / *PAGE_SIZE))
+                        {
                             break; // 当前页剩余空间不足
                         }
 
@@ -162,16 +212,34 @@ pub(crate) fn search_in_chunks_with_status(
                 return local; // 早返回，避免执行慢速路径
             }
 
+            if let Some(kernel) = &kernel {
+                // SIMD 比较路径：4 字节对齐的元素不会跨页，按成功页整段比较
+                let word_end = scan_end_pos.saturating_sub(3); // 元素起始必须小于它，元素可以跨过扫描粒度的末尾
+                for page_idx in (rs / *PAGE_SIZE)..re.div_ceil(*PAGE_SIZE) {
+                    if !page_status.is_page_success(page_idx) {
+                        continue;
+                    }
+                    let page_start = first_aligned_pos(buffer_addr, (page_idx * *PAGE_SIZE).max(rs), align);
+                    let page_end = ((page_idx + 1) * *PAGE_SIZE).min(re).min(word_end);
+                    if page_start >= page_end {
+                        continue;
+                    }
+                    let words = (page_end - page_start).div_ceil(4);
+                    simd_scan::scan_words(kernel, &buffer[page_start..page_start + words * 4], buffer_addr + page_start as u64, &mut local);
+                }
+                return local;
+            }
+
             // 这里用 while 方便跳过失败页
             let mut pos = rs;
 
             // 注意：对齐必须按绝对地址算
-            pos = first_aligned_pos(buffer_addr, pos, element_size);
+            pos = first_aligned_pos(buffer_addr, pos, align);
             let mut current_page_end = ((pos / *PAGE_SIZE + 1) * *PAGE_SIZE).min(re);
 
             while pos < re {
-                // 如果越界（比对需要 element_size/needle_len），提前结束
-                if pos + element_size > re {
+                // 如果越界（比对需要 element_size/needle_len），提前结束；元素可以跨过扫描粒度的末尾
+                if pos + element_size > scan_end_pos {
                     break;
                 }
 
@@ -181,12 +249,18 @@ pub(crate) fn search_in_chunks_with_status(
                     let page_idx = pos / *PAGE_SIZE;
                     if !page_status.is_page_success(page_idx) {
                         let next_page = (page_idx + 1) * *PAGE_SIZE;
-                        pos = first_aligned_pos(buffer_addr, next_page, element_size);
+                        pos = first_aligned_pos(buffer_addr, next_page, align);
                         current_page_end = ((pos / *PAGE_SIZE + 1) * *PAGE_SIZE).min(re);
                         continue;
                     }
                 }
 
+                // 非自然对齐的元素跨页时，跨入的页也必须读取成功
+                if pos + element_size > current_page_end && !page_status.is_page_success((pos + element_size - 1) / *PAGE_SIZE) {
+                    pos += align;
+                    continue;
+                }
+
                 let other = &buffer[pos..pos + element_size];
 
                 let ok = if fast_int {
@@ -194,7 +268,7 @@ pub(crate) fn search_in_chunks_with_status(
                     // （这里假设 bytes.len()==element_size，否则你要按真实逻辑调整）
                     if let Ok(bytes) = target.bytes() { other == bytes } else { false }
                 } else {
-                    target.matched(other).unwrap_or_else(|e| {
+                    target.matched_at(other, buffer_addr + pos as u64).unwrap_or_else(|e| {
                         error!("target.matched error, {}", e);
                         false
                     })
@@ -204,7 +278,7 @@ pub(crate) fn search_in_chunks_with_status(
                     local.push(buffer_addr + pos as u64);
                 }
 
-                pos += element_size;
+                pos += align;
             }
 
             local
@@ -219,49 +293,218 @@ pub(crate) fn search_in_chunks_with_status(
     }
 }
 
+/// 在缓冲区中搜索字符串，只接受起始地址在 `search_range` [start, end) 内的匹配
+///
+/// 字符串不按自身长度对齐，可能跨页：按连续的成功页分段，用 memchr 找首字节后验证整段内容。
+/// UTF-16 按 2 字节对齐。
+pub(crate) fn search_text_in_buffer<F>(
+    buffer: &[u8],
+    buffer_addr: u64,
+    search_range: (u64, u64),
+    target: &SearchValue,
+    page_status: &PageStatusBitmap,
+    results: &mut Vec<ValuePair>,
+    check_cancelled: &F,
+) where
+    F: Fn() -> bool,
+{
+    let SearchValue::Text { bytes, value_type, ignore_case } = target else {
+        return;
+    };
+    assert_eq!(buffer_addr as usize % *PAGE_SIZE, 0);
+
+    let first = if *ignore_case {
+        (bytes[0].to_ascii_lowercase(), bytes[0].to_ascii_uppercase())
+    } else {
+        (bytes[0], bytes[0])
+    };
+    let align = if *value_type == ValueType::Utf16String { 2 } else { 1 };
+    let page_count = buffer.len().div_ceil(*PAGE_SIZE);
+
+    let mut page_idx = 0;
+    while page_idx < page_count {
+        if !page_status.is_page_success(page_idx) {
+            page_idx += 1;
+            continue;
+        }
+        if check_cancelled() {
+            return;
+        }
+
+        // 连续的成功页作为一段搜索，段内的匹配可以跨页
+        let run_start = page_idx;
+        while page_idx < page_count && page_status.is_page_success(page_idx) {
+            page_idx += 1;
+        }
+        let start_pos = run_start * *PAGE_SIZE;
+        let end_pos = (page_idx * *PAGE_SIZE).min(buffer.len());
+
+        for pos in buffer[start_pos..end_pos].find_aligned_with(first, bytes.len(), align, |window| target.match_text(window)) {
+            let addr = buffer_addr + (start_pos + pos) as u64;
+            if addr >= search_range.0 && addr < search_range.1 {
+                results.push(ValuePair::new(addr, *value_type));
+            }
+        }
+    }
+}
+
+/// 在一块读取缓冲区中按每种解释（`SearchValue::auto_candidates`）扫描，候选地址按 `alignment` 对齐（None 为各自的类型大小）。
+/// 只保留从 `chunk_end` 之前开始的值，之后的留给下一块；多种解释的命中按地址和类型排序后追加到 `results`
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn search_candidates_in_chunk<F>(
+    buffer: &[u8],
+    buffer_addr: u64,
+    (region_start, region_end): (u64, u64),
+    chunk_end: u64,
+    alignment: Option<usize>,
+    candidates: &[SearchValue],
+    page_status: &PageStatusBitmap,
+    results: &mut Vec<ValuePair>,
+    check_cancelled: &F,
+) where
+    F: Fn() -> bool + Sync,
+{
+    let mut candidate_results = Vec::new();
+    for candidate in candidates {
+        let value_type = candidate.value_type();
+        let from = candidate_results.len();
+        search_in_chunks_aligned(
+            buffer,
+            buffer_addr,
+            region_start,
+            region_end,
+            value_type.size(),
+            alignment.unwrap_or(value_type.size()).max(1),
+            candidate,
+            value_type,
+            page_status,
+            &mut candidate_results,
+            check_cancelled,
+        );
+        // 多读的部分只用来读完整跨块的值，从那里开始的值留给下一块
+        candidate_results.truncate(from + candidate_results[from..].partition_point(|pair| pair.addr < chunk_end));
+    }
+    if candidates.len() > 1 {
+        // 各解释的命中各自按地址有序，合并后按地址和类型排序
+        candidate_results.sort_unstable_by_key(|pair| (pair.addr, pair.value_type.to_id()));
+    }
+    results.append(&mut candidate_results);
+}
+
 pub(crate) fn search_region_single(
     target: &SearchValue,
     start: u64,        // 区域起始地址
     end: u64,          // 区域结束地址
     chunk_size: usize, // 每次读取的块大小
 ) -> Result<Vec<ValuePair>> {
+    search_region_single_with_cancel(target, start, end, chunk_size, None, None, AccessQos::Bulk, &|| false, &ReadStats::new())
+}
+
+/// 带取消支持的单值区域搜索，候选地址按 `alignment` 对齐（None 为类型大小，字符串忽略），`target_pid` 为 None 时读取绑定的进程，
+/// 每块按 `qos` 读取
+/// 每个 chunk 读取前以及 chunk 内每个扫描粒度都会检查取消，每次读取都计入 `read_stats`
+/// Auto 类型的值按 `auto_candidates` 的每种解释分别扫描，同一地址可以以多种类型命中
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn search_region_single_with_cancel<F>(
+    target: &SearchValue,
+    start: u64,
+    end: u64,
+    chunk_size: usize,
+    alignment: Option<usize>,
+    target_pid: Option<i32>,
+    qos: AccessQos,
+    check_cancelled: &F,
+    read_stats: &ReadStats,
+) -> Result<Vec<ValuePair>>
+where
+    F: Fn() -> bool + Sync,
+{
     let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
+    search_region_single_with(
+        target,
+        start,
+        end,
+        chunk_size,
+        alignment,
+        |addr, buf, page_status| driver_manager.read_target_with_qos(target_pid, addr, buf, Some(page_status), qos),
+        check_cancelled,
+        read_stats,
+    )
+}
 
-    let value_type = target.value_type();
-    let element_size = value_type.size();
+/// 与 `search_region_single_with_cancel` 相同，但每块通过 `read` 读取（按页记录到 `page_status`）
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn search_region_single_with<R, F>(
+    target: &SearchValue,
+    start: u64,
+    end: u64,
+    chunk_size: usize,
+    alignment: Option<usize>,
+    mut read: R,
+    check_cancelled: &F,
+    read_stats: &ReadStats,
+) -> Result<Vec<ValuePair>>
+where
+    R: FnMut(u64, &mut [u8], &mut PageStatusBitmap) -> Result<()>,
+    F: Fn() -> bool + Sync,
+{
+    let candidates = target.auto_candidates();
+    let element_size = candidates.iter().map(|candidate| candidate.value_type().size()).max().unwrap_or(0);
+    // 字符串、非自然对齐的值以及块大小不是元素大小整数倍时值都可能跨块，每块多读 len - 1 字节，
+    // 起始地址仍只接受本块内的
+    let overlap = if target.is_text() { target.byte_len() - 1 } else { element_size.saturating_sub(1) };
 
     let mut results = Vec::new();
     let mut read_success = 0usize;
     let mut read_failed = 0usize;
 
     let mut current = start & !(*PAGE_SIZE as u64 - 1); // 当前的页对齐地址
-    let mut chunk_buffer = vec![0u8; chunk_size]; // 读取缓冲区
+    let mut chunk_buffer = vec![0u8; chunk_size + overlap]; // 读取缓冲区
 
     while current < end {
+        if check_cancelled() {
+            break;
+        }
+
         let chunk_end = (current + chunk_size as u64).min(end); // 当前块的结束地址，如果超过end则取end
-        let chunk_len = (chunk_end - current) as usize; // 当前块的实际长度
+        let chunk_len = ((chunk_end + overlap as u64).min(end) - current) as usize; // 当前块的实际读取长度
 
         let mut page_status = PageStatusBitmap::new(chunk_len, current as usize);
 
         // 这里读取内存，这里的current一定页对齐的
-        let read_result = driver_manager.read_memory_unified(current, &mut chunk_buffer[..chunk_len], Some(&mut page_status));
+        let read_result = read(current, &mut chunk_buffer[..chunk_len], &mut page_status);
+        read_stats.record_chunk(current, chunk_len, read_result.is_ok(), &page_status, *PAGE_SIZE);
 
         match read_result {
             Ok(_) => {
                 let success_pages = page_status.success_count();
                 if success_pages > 0 {
                     read_success += 1;
-                    search_in_chunks_with_status(
-                        &chunk_buffer[..chunk_len],
-                        current,
-                        start,
-                        end,
-                        element_size,
-                        target,
-                        value_type,
-                        &page_status,
-                        &mut results,
-                    );
+                    let found_before = results.len();
+                    if target.is_text() {
+                        search_text_in_buffer(
+                            &chunk_buffer[..chunk_len],
+                            current,
+                            (current.max(start), chunk_end),
+                            target,
+                            &page_status,
+                            &mut results,
+                            check_cancelled,
+                        );
+                    } else {
+                        search_candidates_in_chunk(
+                            &chunk_buffer[..chunk_len],
+                            current,
+                            (start, end),
+                            chunk_end,
+                            alignment,
+                            &candidates,
+                            &page_status,
+                            &mut results,
+                            check_cancelled,
+                        );
+                    }
+                    read_stats.record_matches(results.len() - found_before);
                 } else {
                     read_failed += 1;
                 }
@@ -292,168 +535,344 @@ pub(crate) fn search_region_single(
     Ok(results)
 }
 
-/// 单值细化搜索
-/// 逐个读取地址的值，再用rayon并行判断
-/// 返回仍然匹配的地址列表
-#[deprecated]
-pub(crate) fn refine_single_search(
-    addresses: &[ValuePair],
+/// Single value refine search with cancel and progress callbacks.
+/// Addresses are consumed in batches of `REFINE_BATCH_SIZE`, so the whole input never has to be materialized.
+/// Nearby addresses are read together in spans of at most `read_window` bytes; 0 reads each address on its own
+/// through a page cache of `cache_pages` pages that lives for this refine only. Reads go to `target_pid`, None for the bound process.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn refine_single_search_with_cancel<I, F, P>(
+    addresses: I,
     target: &SearchValue,
+    target_pid: Option<i32>,
+    read_window: usize,
+    cache_pages: usize,
     processed_counter: Option<&Arc<AtomicUsize>>,
     total_found_counter: Option<&Arc<AtomicUsize>>,
-) -> Result<Vec<ValuePair>> {
-    use rayon::prelude::*;
-    use std::sync::atomic::Ordering;
-
-    if addresses.is_empty() {
+    check_cancelled: &F,
+    update_progress: &P,
+) -> Result<Vec<ValuePair>>
+where
+    I: IntoIterator<Item = ValuePair>,
+    F: Fn() -> bool + Sync,
+    P: Fn(usize, usize) + Sync,
+{
+    // Check cancellation before starting.
+    if check_cancelled() {
         return Ok(Vec::new());
     }
 
     let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
 
-    let target_type = target.value_type();
-    let element_size = target_type.size();
+    if read_window == 0 {
+        let cache_stats = PageCacheStats::default();
+        let mut cache = PageCache::new(cache_pages, *PAGE_SIZE, &cache_stats);
+        let results = refine_values_stream_with(
+            addresses,
+            target,
+            REFINE_BATCH_SIZE,
+            |addr, buffer| cache.read(addr, buffer, |page, buf| driver_manager.read_target_with_qos(target_pid, page, buf, None, AccessQos::Bulk).is_ok()),
+            processed_counter,
+            total_found_counter,
+            check_cancelled,
+            update_progress,
+        );
+        debug!("Refine single search: page cache {}", cache_stats);
+        return Ok(results);
+    }
 
-    // 过滤类型不匹配的地址
-    let filtered_addresses: Vec<_> = addresses.iter().filter(|p| p.value_type == target_type).cloned().collect();
+    Ok(refine_values_coalesced_stream_with(
+        addresses,
+        target,
+        REFINE_BATCH_SIZE,
+        read_window,
+        |addr, buffer, page_status| driver_manager.read_target_with_qos(target_pid, addr, buffer, Some(page_status), AccessQos::Bulk).is_ok(),
+        processed_counter,
+        total_found_counter,
+        check_cancelled,
+        update_progress,
+    ))
+}
 
-    if filtered_addresses.is_empty() {
-        return Ok(Vec::new());
+/// 按地址顺序分批调用 `refine_values_with`，每批最多 `batch_size` 个地址，幸存结果保持输入顺序。取消时返回空集合
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn refine_values_stream_with<I, R, F, P>(
+    addresses: I,
+    target: &SearchValue,
+    batch_size: usize,
+    mut read: R,
+    processed_counter: Option<&Arc<AtomicUsize>>,
+    total_found_counter: Option<&Arc<AtomicUsize>>,
+    check_cancelled: &F,
+    update_progress: &P,
+) -> Vec<ValuePair>
+where
+    I: IntoIterator<Item = ValuePair>,
+    R: FnMut(u64, &mut [u8]) -> bool,
+    F: Fn() -> bool + Sync,
+    P: Fn(usize, usize) + Sync,
+{
+    refine_batches(addresses, batch_size, check_cancelled, |batch| {
+        refine_values_with(batch, target, &mut read, processed_counter, total_found_counter, check_cancelled, update_progress)
+    })
+}
+
+/// 与 `refine_values_stream_with` 相同，但每批内相邻的地址用 `CoalescedReader` 合并读取
+///
+/// `read_span` 按页状态读取一段内存，返回整段是否读取失败以外的结果。
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn refine_values_coalesced_stream_with<I, S, F, P>(
+    addresses: I,
+    target: &SearchValue,
+    batch_size: usize,
+    window: usize,
+    mut read_span: S,
+    processed_counter: Option<&Arc<AtomicUsize>>,
+    total_found_counter: Option<&Arc<AtomicUsize>>,
+    check_cancelled: &F,
+    update_progress: &P,
+) -> Vec<ValuePair>
+where
+    I: IntoIterator<Item = ValuePair>,
+    S: FnMut(u64, &mut [u8], &mut PageStatusBitmap) -> bool,
+    F: Fn() -> bool + Sync,
+    P: Fn(usize, usize) + Sync,
+{
+    let value_len = target.auto_candidates().iter().map(SearchValue::byte_len).max().unwrap_or(0);
+    refine_batches(addresses, batch_size, check_cancelled, |batch| {
+        let mut reader = CoalescedReader::new(batch, value_len, window, &mut read_span);
+        refine_values_with(
+            batch,
+            target,
+            |addr, buffer| reader.read(addr, buffer),
+            processed_counter,
+            total_found_counter,
+            check_cancelled,
+            update_progress,
+        )
+    })
+}
+
+/// 按地址顺序每次取出最多 `batch_size` 个地址交给 `refine_batch`，幸存结果保持输入顺序。取消时返回空集合
+fn refine_batches<I, F, B>(addresses: I, batch_size: usize, check_cancelled: &F, mut refine_batch: B) -> Vec<ValuePair>
+where
+    I: IntoIterator<Item = ValuePair>,
+    F: Fn() -> bool + Sync,
+    B: FnMut(&[ValuePair]) -> Vec<ValuePair>,
+{
+    let mut addresses = addresses.into_iter();
+    let mut results = Vec::new();
+    loop {
+        let batch: Vec<ValuePair> = addresses.by_ref().take(batch_size.max(1)).collect();
+        if batch.is_empty() {
+            break;
+        }
+        results.extend(refine_batch(&batch));
+        if check_cancelled() {
+            return Vec::new();
+        }
     }
+    results
+}
+
+/// 改善搜索的合并读取：按地址顺序读取时，一次读出覆盖后续若干地址的一段内存
+///
+/// 一段从未命中的地址开始，向后包含值结束位置不超过 `window` 的地址，只有一个地址时就是该地址的值。
+/// 段内读取失败的页上的地址视为读取失败；整段读取失败时逐个地址单独读取，结果与逐地址读取相同。
+pub(crate) struct CoalescedReader<'a, S> {
+    /// 有序的地址，用于向后查看
+    addresses: &'a [ValuePair],
+    /// 下一个尚未被缓存段覆盖的地址下标
+    next: usize,
+    value_len: usize,
+    window: usize,
+    read_span: S,
+    span_start: u64,
+    span: Vec<u8>,
+    page_status: PageStatusBitmap,
+    /// 缓存段整体读取失败
+    span_failed: bool,
+    /// 发出的读取次数
+    reads: usize,
+}
 
-    // 逐个读取每个地址的值
-    let mut address_values: Vec<(ValuePair, Vec<u8>)> = Vec::with_capacity(filtered_addresses.len());
+impl<'a, S> CoalescedReader<'a, S>
+where
+    S: FnMut(u64, &mut [u8], &mut PageStatusBitmap) -> bool,
+{
+    pub(crate) fn new(addresses: &'a [ValuePair], value_len: usize, window: usize, read_span: S) -> Self {
+        Self {
+            addresses,
+            next: 0,
+            value_len,
+            window,
+            read_span,
+            span_start: 0,
+            span: Vec::new(),
+            page_status: PageStatusBitmap::new(0, 0),
+            span_failed: false,
+            reads: 0,
+        }
+    }
 
-    for pair in &filtered_addresses {
-        let mut buffer = vec![0u8; element_size];
-        if driver_manager.read_memory_unified(pair.addr, &mut buffer, None).is_ok() {
-            address_values.push((pair.clone(), buffer));
+    /// 读取 addr 处 buffer.len() 字节，地址需要按升序请求
+    pub(crate) fn read(&mut self, addr: u64, buffer: &mut [u8]) -> bool {
+        let end = addr + buffer.len() as u64;
+        if addr < self.span_start || end > self.span_start + self.span.len() as u64 {
+            self.fill(addr, buffer.len());
         }
 
-        // 更新已处理计数器
-        if let Some(counter) = &processed_counter {
-            counter.fetch_add(1, Ordering::Relaxed);
+        if self.span_failed {
+            self.reads += 1;
+            let mut page_status = PageStatusBitmap::new(buffer.len(), addr as usize);
+            return (self.read_span)(addr, buffer, &mut page_status) && pages_readable(&page_status, addr, addr, buffer.len());
+        }
+        if !pages_readable(&self.page_status, self.span_start, addr, buffer.len()) {
+            return false;
         }
+        let offset = (addr - self.span_start) as usize;
+        buffer.copy_from_slice(&self.span[offset..offset + buffer.len()]);
+        true
     }
 
-    drop(driver_manager);
+    /// 发出的读取次数
+    pub(crate) fn reads(&self) -> usize {
+        self.reads
+    }
 
-    // 用rayon并行判断
-    let results: Vec<ValuePair> = address_values
-        .into_par_iter()
-        .filter_map(|(pair, bytes)| {
-            if let Ok(true) = target.matched(&bytes) {
-                if let Some(counter) = &total_found_counter {
-                    counter.fetch_add(1, Ordering::Relaxed);
-                }
-                Some(pair)
-            } else {
-                None
+    /// 从 addr 开始读取一段，向后包含窗口内的地址
+    fn fill(&mut self, addr: u64, len: usize) {
+        while self.next < self.addresses.len() && self.addresses[self.next].addr <= addr {
+            self.next += 1;
+        }
+        let mut end = addr + len as u64;
+        let limit = addr + self.window.max(len) as u64;
+        while let Some(pair) = self.addresses.get(self.next) {
+            let pair_end = pair.addr + self.value_len as u64;
+            if pair_end > limit {
+                break;
             }
-        })
-        .collect();
+            end = end.max(pair_end);
+            self.next += 1;
+        }
 
-    if log_enabled!(Level::Debug) {
-        debug!("Refine single search: {} -> {} results", filtered_addresses.len(), results.len());
+        self.span_start = addr;
+        self.span.clear();
+        self.span.resize((end - addr) as usize, 0);
+        self.page_status = PageStatusBitmap::new(self.span.len(), addr as usize);
+        self.reads += 1;
+        self.span_failed = !(self.read_span)(addr, &mut self.span, &mut self.page_status);
     }
+}
 
-    Ok(results)
+/// 从 span_start 开始按页状态读取的一段中，[addr, addr + len) 覆盖的页是否都读取成功
+fn pages_readable(page_status: &PageStatusBitmap, span_start: u64, addr: u64, len: usize) -> bool {
+    let page_mask = *PAGE_MASK as u64;
+    let first_page = span_start & page_mask;
+    let start_index = ((addr & page_mask) - first_page) as usize / *PAGE_SIZE;
+    let end_index = (((addr + len as u64 - 1) & page_mask) - first_page) as usize / *PAGE_SIZE;
+    (start_index..=end_index).all(|index| page_status.is_page_success(index))
 }
 
-/// Single value refine search with cancel and progress callbacks.
-/// This version supports cancellation checking and progress updates during the search.
-pub(crate) fn refine_single_search_with_cancel<F, P>(
+/// 逐地址读取的改善搜索核心，`read` 把地址的当前值读入缓冲区并返回是否成功
+///
+/// 值按匹配长度（字符串为其字节数）读入一块连续缓冲区，每个地址不再单独分配缓冲区。取消时返回空集合。
+/// Auto 类型的值按结果自身的类型取对应的解释比较，没有对应解释的结果不再保留。
+pub(crate) fn refine_values_with<R, F, P>(
     addresses: &[ValuePair],
     target: &SearchValue,
+    mut read: R,
     processed_counter: Option<&Arc<AtomicUsize>>,
     total_found_counter: Option<&Arc<AtomicUsize>>,
     check_cancelled: &F,
     update_progress: &P,
-) -> Result<Vec<ValuePair>>
+) -> Vec<ValuePair>
 where
+    R: FnMut(u64, &mut [u8]) -> bool,
     F: Fn() -> bool + Sync,
     P: Fn(usize, usize) + Sync,
 {
     use rayon::prelude::*;
     use std::sync::atomic::Ordering;
 
-    if addresses.is_empty() {
-        return Ok(Vec::new());
-    }
-
-    // Check cancellation before starting.
-    if check_cancelled() {
-        return Ok(Vec::new());
-    }
-
-    let driver_manager = DRIVER_MANAGER.read().map_err(|_| anyhow!("Failed to acquire DriverManager lock"))?;
-
-    let target_type = target.value_type();
-    let element_size = target_type.size();
+    let candidates = target.auto_candidates();
+    let element_size = candidates.iter().map(SearchValue::byte_len).max().unwrap_or(0);
 
     // Filter addresses with non-matching types.
-    let filtered_addresses: Vec<_> = addresses.iter().filter(|p| p.value_type == target_type).cloned().collect();
+    // 特征码结果也可以用普通值改善，按匹配起始处的值比较，幸存的结果仍是特征码结果
+    let filtered_addresses: Vec<_> = addresses
+        .iter()
+        .filter_map(|p| {
+            candidates
+                .iter()
+                .find(|candidate| {
+                    let value_type = candidate.value_type();
+                    value_type == p.value_type || (p.value_type == ValueType::Pattern && !value_type.is_variable_len())
+                })
+                .map(|candidate| (p, candidate))
+        })
+        .collect();
 
-    if filtered_addresses.is_empty() {
-        return Ok(Vec::new());
+    if filtered_addresses.is_empty() || element_size == 0 {
+        return Vec::new();
     }
 
     let total_addresses = filtered_addresses.len();
 
-    // Read values for each address sequentially.
-    let mut address_values: Vec<(ValuePair, Vec<u8>)> = Vec::with_capacity(filtered_addresses.len());
+    // Read values for each address sequentially into one buffer, element_size bytes each.
+    let mut read_pairs: Vec<(&ValuePair, &SearchValue)> = Vec::with_capacity(filtered_addresses.len());
+    let mut values = vec![0u8; filtered_addresses.len() * element_size];
 
-    for (idx, pair) in filtered_addresses.iter().enumerate() {
+    for (idx, &(pair, candidate)) in filtered_addresses.iter().enumerate() {
         // Check cancellation periodically.
-        if idx % 1000 == 0 && check_cancelled() {
-            return Ok(Vec::new());
+        if idx.is_multiple_of(1000) && check_cancelled() {
+            return Vec::new();
         }
 
-        let mut buffer = vec![0u8; element_size];
-        if driver_manager.read_memory_unified(pair.addr, &mut buffer, None).is_ok() {
-            address_values.push((pair.clone(), buffer));
+        let offset = read_pairs.len() * element_size;
+        if read(pair.addr, &mut values[offset..offset + candidate.byte_len()]) {
+            read_pairs.push((pair, candidate));
         }
 
         // Update processed counter and progress.
         if let Some(counter) = &processed_counter {
             let processed = counter.fetch_add(1, Ordering::Relaxed) + 1;
             // Update progress every 100 addresses.
-            if processed % 100 == 0 {
+            if processed.is_multiple_of(100) {
                 let found = total_found_counter.map(|c| c.load(Ordering::Relaxed)).unwrap_or(0);
                 update_progress(processed, found);
             }
         }
     }
 
-    drop(driver_manager);
-
     // Check cancellation before parallel matching.
     if check_cancelled() {
-        return Ok(Vec::new());
+        return Vec::new();
     }
 
     // Use rayon for parallel matching.
-    let results: Vec<ValuePair> = address_values
+    let results: Vec<ValuePair> = read_pairs
         .into_par_iter()
-        .filter_map(|(pair, bytes)| {
-            if let Ok(true) = target.matched(&bytes) {
+        .zip(values.par_chunks(element_size))
+        .filter_map(|((pair, candidate), bytes)| {
+            if let Ok(true) = candidate.matched_at(&bytes[..candidate.byte_len()], pair.addr) {
                 if let Some(counter) = &total_found_counter {
                     counter.fetch_add(1, Ordering::Relaxed);
                 }
-                Some(pair)
+                Some(pair.clone())
             } else {
                 None
             }
         })
         .collect();
 
-    // Final progress update.
+    // Final progress update, cumulative when the counters are shared across batches.
+    let processed = processed_counter.map(|c| c.load(Ordering::Relaxed)).unwrap_or(total_addresses);
     let found_count = total_found_counter.map(|c| c.load(Ordering::Relaxed)).unwrap_or(results.len());
-    update_progress(total_addresses, found_count);
+    update_progress(processed, found_count);
 
     if log_enabled!(Level::Debug) {
-        debug!("Refine single search with cancel: {} -> {} results", filtered_addresses.len(), results.len());
+        debug!("Refine single search with cancel: {} -> {} results", total_addresses, results.len());
     }
 
-    Ok(results)
+    results
 }
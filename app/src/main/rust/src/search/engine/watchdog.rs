@@ -0,0 +1,99 @@
+//! Search watchdog
+//!
+//! 阻塞的搜索任务偶尔会卡死（rayon 线程被 OOM kill、驱动 fd 被回收），共享缓冲区一直停在 Searching，
+//! JoinHandle 永远不结束，`is_searching()` 一直返回 true，重启应用前无法再搜索。
+//!
+//! 每次搜索开始时启动一个轻量的看门狗任务，定期检查共享缓冲区的心跳：状态为 Searching 且超过
+//! 超时时间没有心跳时，由 `SearchEngineManager::recover_stalled_search` 中止任务、触发取消令牌并清空
+//! 任务句柄，状态写为 Error / `SearchErrorCode::Stalled`，之后可以开始新的搜索。
+//!
+//! 只能中止等待阻塞任务的异步任务，卡住的阻塞线程无法强制结束；它恢复后会在下一个检查点看到取消。
+//! 看门狗只用 try_read / try_write 访问管理器，拿不到锁时等下一次检查，不会阻塞 tokio 的工作线程。
+
+use super::manager::SEARCH_ENGINE_MANAGER;
+use super::shared_buffer::SearchStatus;
+use crate::core::globals::TOKIO_RUNTIME;
+use log::warn;
+use std::time::Duration;
+
+/// 默认的卡死判定时间
+pub const DEFAULT_STALL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 两次检查之间最长的间隔
+const MAX_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 一次检查的结论
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchdogVerdict {
+    /// 搜索仍在进行且有心跳
+    Healthy,
+    /// 搜索仍在进行但超时没有心跳
+    Stalled,
+    /// 搜索已经结束或被新的搜索替换，看门狗退出
+    Finished,
+}
+
+/// 根据任务是否仍在运行、共享缓冲区状态和心跳间隔判断
+pub fn verdict(running: bool, status: SearchStatus, heartbeat_age: Duration, timeout: Duration) -> WatchdogVerdict {
+    if !running {
+        WatchdogVerdict::Finished
+    } else if status == SearchStatus::Searching && heartbeat_age > timeout {
+        WatchdogVerdict::Stalled
+    } else {
+        WatchdogVerdict::Healthy
+    }
+}
+
+/// 检查间隔：超时时间的四分之一，最长 5 秒
+pub fn check_interval(timeout: Duration) -> Duration {
+    (timeout / 4).clamp(Duration::from_millis(10), MAX_CHECK_INTERVAL)
+}
+
+/// 为编号为 `search_id` 的搜索启动看门狗，搜索结束或被替换后退出
+pub fn spawn(search_id: u64, timeout: Duration) {
+    TOKIO_RUNTIME.spawn(async move {
+        let interval = check_interval(timeout);
+        loop {
+            tokio::time::sleep(interval).await;
+            let current = match SEARCH_ENGINE_MANAGER.try_read() {
+                Ok(manager) => manager.watchdog_verdict(search_id, timeout),
+                Err(_) => continue,
+            };
+            match current {
+                WatchdogVerdict::Healthy => {},
+                WatchdogVerdict::Finished => return,
+                WatchdogVerdict::Stalled => {
+                    let Ok(mut manager) = SEARCH_ENGINE_MANAGER.try_write() else {
+                        continue;
+                    };
+                    // 拿到写锁之前任务可能刚好恢复或结束
+                    if manager.watchdog_verdict(search_id, timeout) == WatchdogVerdict::Stalled {
+                        warn!("Search {} has had no heartbeat for over {:?}, aborting it", search_id, timeout);
+                        manager.recover_stalled_search();
+                    }
+                    return;
+                },
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verdict() {
+        let timeout = DEFAULT_STALL_TIMEOUT;
+        let quiet = Duration::from_secs(31);
+        assert_eq!(verdict(true, SearchStatus::Searching, Duration::from_secs(2), timeout), WatchdogVerdict::Healthy);
+        assert_eq!(verdict(true, SearchStatus::Searching, quiet, timeout), WatchdogVerdict::Stalled);
+        // 状态已经写为完成、任务正在退出时不算卡死
+        assert_eq!(verdict(true, SearchStatus::Completed, quiet, timeout), WatchdogVerdict::Healthy);
+        assert_eq!(verdict(false, SearchStatus::Searching, quiet, timeout), WatchdogVerdict::Finished);
+
+        assert_eq!(check_interval(timeout), Duration::from_secs(5));
+        assert_eq!(check_interval(Duration::from_secs(8)), Duration::from_secs(2));
+        assert_eq!(check_interval(Duration::ZERO), Duration::from_millis(10));
+    }
+}
@@ -0,0 +1,161 @@
+//! 结果值的显示格式
+//!
+//! 整数默认按有符号十进制显示。查看位标志或 RGBA 颜色时可以切换为无符号十进制或十六进制，
+//! 十六进制按类型宽度补零（Dword 8 位），忽略有无符号。浮点数、特征码和字符串不受影响。
+
+use crate::search::types::ValueType;
+use std::fmt::Write;
+
+/// 十进制整数是否按有符号解释
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Signedness {
+    #[default]
+    Signed,
+    Unsigned,
+}
+
+/// 整数的进制
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Radix {
+    #[default]
+    Decimal,
+    Hex,
+}
+
+/// 整数值的显示方式，默认有符号十进制
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ValueDisplay {
+    pub signedness: Signedness,
+    pub radix: Radix,
+}
+
+impl ValueDisplay {
+    /// `signed` 非 0 表示有符号，`radix` 为 10 或 16
+    pub fn from_ids(signed: i32, radix: i32) -> Option<Self> {
+        let radix = match radix {
+            10 => Radix::Decimal,
+            16 => Radix::Hex,
+            _ => return None,
+        };
+        let signedness = if signed != 0 { Signedness::Signed } else { Signedness::Unsigned };
+        Some(Self { signedness, radix })
+    }
+}
+
+/// 把值按默认方式格式化到 `out`（先清空），批量生成结果行时复用同一个 String
+pub fn format_value(out: &mut String, bytes: &[u8], typ: ValueType) {
+    format_value_with(out, bytes, typ, ValueDisplay::default());
+}
+
+/// 把值按 `display` 格式化到 `out`（先清空）
+pub fn format_value_with(out: &mut String, bytes: &[u8], typ: ValueType, display: ValueDisplay) {
+    out.clear();
+    if !typ.is_variable_len() && bytes.len() < typ.size() {
+        out.push_str("N/A");
+        return;
+    }
+
+    let _ = match typ {
+        ValueType::Byte | ValueType::Word | ValueType::Dword | ValueType::Qword | ValueType::Auto | ValueType::Xor => {
+            write_integer(out, &bytes[..typ.size()], display)
+        },
+        ValueType::Float => write!(out, "{}", f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])),
+        ValueType::Double => write!(
+            out,
+            "{}",
+            f64::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7]])
+        ),
+        ValueType::Pattern => {
+            // Pattern 类型显示十六进制内容，最多显示 16 字节
+            const MAX_DISPLAY_BYTES: usize = 16;
+            for (i, b) in bytes.iter().take(MAX_DISPLAY_BYTES).enumerate() {
+                if i > 0 {
+                    out.push(' ');
+                }
+                let _ = write!(out, "{:02X}", b);
+            }
+            if bytes.len() > MAX_DISPLAY_BYTES {
+                out.push_str("...");
+            }
+            Ok(())
+        },
+        // 字符串显示解码后的文本，无效的编码显示为替换字符
+        ValueType::Utf8String => {
+            out.push_str(&String::from_utf8_lossy(bytes));
+            Ok(())
+        },
+        ValueType::Utf16String => {
+            let units: Vec<u16> = bytes.chunks_exact(2).map(|unit| u16::from_le_bytes([unit[0], unit[1]])).collect();
+            out.push_str(&String::from_utf16_lossy(&units));
+            Ok(())
+        },
+    };
+}
+
+/// 1 到 8 字节的小端整数
+fn write_integer(out: &mut String, bytes: &[u8], display: ValueDisplay) -> std::fmt::Result {
+    let mut raw = [0u8; 8];
+    raw[..bytes.len()].copy_from_slice(bytes);
+    let unsigned = u64::from_le_bytes(raw);
+    let bits = bytes.len() as u32 * 8;
+    match (display.radix, display.signedness) {
+        (Radix::Hex, _) => write!(out, "0x{:0width$X}", unsigned, width = bytes.len() * 2),
+        (Radix::Decimal, Signedness::Unsigned) => write!(out, "{}", unsigned),
+        // 左移再算术右移完成符号扩展
+        (Radix::Decimal, Signedness::Signed) => write!(out, "{}", ((unsigned << (64 - bits)) as i64) >> (64 - bits)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UNSIGNED: ValueDisplay = ValueDisplay { signedness: Signedness::Unsigned, radix: Radix::Decimal };
+    const HEX: ValueDisplay = ValueDisplay { signedness: Signedness::Signed, radix: Radix::Hex };
+
+    fn formatted(bytes: &[u8], typ: ValueType, display: ValueDisplay) -> String {
+        let mut out = String::new();
+        format_value_with(&mut out, bytes, typ, display);
+        out
+    }
+
+    #[test]
+    fn test_integer_display() {
+        // 默认仍是有符号十进制
+        assert_eq!(formatted(&[0xFF], ValueType::Byte, ValueDisplay::default()), "-1");
+        assert_eq!(formatted(&(-2i16).to_le_bytes(), ValueType::Word, ValueDisplay::default()), "-2");
+        assert_eq!(formatted(&i64::MIN.to_le_bytes(), ValueType::Qword, ValueDisplay::default()), i64::MIN.to_string());
+
+        assert_eq!(formatted(&[0xFF], ValueType::Byte, UNSIGNED), "255");
+        assert_eq!(formatted(&(-1i32).to_le_bytes(), ValueType::Dword, UNSIGNED), "4294967295");
+        assert_eq!(formatted(&u64::MAX.to_le_bytes(), ValueType::Qword, UNSIGNED), "18446744073709551615");
+
+        // 十六进制按类型宽度补零，忽略有无符号
+        assert_eq!(formatted(&[0x0F], ValueType::Byte, HEX), "0x0F");
+        assert_eq!(formatted(&0xFFu32.to_le_bytes(), ValueType::Dword, HEX), "0x000000FF");
+        assert_eq!(formatted(&(-1i16).to_le_bytes(), ValueType::Word, ValueDisplay { signedness: Signedness::Unsigned, ..HEX }), "0xFFFF");
+        assert_eq!(formatted(&u64::MAX.to_le_bytes(), ValueType::Qword, HEX), "0xFFFFFFFFFFFFFFFF");
+        // Auto 和 Xor 按 Dword 显示，多余的字节不参与
+        assert_eq!(formatted(&[0x78, 0x56, 0x34, 0x12, 0xAA, 0xBB, 0xCC, 0xDD], ValueType::Auto, HEX), "0x12345678");
+        assert_eq!(formatted(&(-5i32).to_le_bytes(), ValueType::Xor, UNSIGNED), "4294967291");
+    }
+
+    #[test]
+    fn test_non_integer_display_unchanged() {
+        for display in [ValueDisplay::default(), UNSIGNED, HEX] {
+            assert_eq!(formatted(&(-1.5f32).to_le_bytes(), ValueType::Float, display), "-1.5");
+            assert_eq!(formatted(&2.25f64.to_le_bytes(), ValueType::Double, display), "2.25");
+            assert_eq!(formatted(&[0xDE, 0xAD], ValueType::Pattern, display), "DE AD");
+            assert_eq!(formatted(b"hi", ValueType::Utf8String, display), "hi");
+            assert_eq!(formatted(&[1, 2], ValueType::Dword, display), "N/A");
+        }
+    }
+
+    #[test]
+    fn test_display_from_ids() {
+        assert_eq!(ValueDisplay::from_ids(1, 10), Some(ValueDisplay::default()));
+        assert_eq!(ValueDisplay::from_ids(0, 10), Some(UNSIGNED));
+        assert_eq!(ValueDisplay::from_ids(1, 16), Some(HEX));
+        assert_eq!(ValueDisplay::from_ids(1, 8), None);
+    }
+}
@@ -10,6 +10,16 @@ pub enum Token<'a> {
     DoubleColon,
     Tilde,
     DoubleTilde,
+    /// `!` 不等于，例如 `!0`
+    Bang,
+    /// `#` 后缀选项，例如 `#span`
+    Suffix(&'a str),
+    /// `:a` 后的候选地址对齐（十进制），例如 `:a1`、`:a8`
+    Align(&'a str),
+    /// `@` 后的相对锚点偏移，例如 `@+0x10`、`@-8`、`@14h`
+    Offset(&'a str),
+    /// 引号内未转义的字符串、是否忽略大小写、是否按 UTF-16 编码，例如 `"PlayerName"i`、`u"PlayerName"`
+    Text(&'a str, bool, bool),
 }
 
 pub struct Lexer<'a> {
@@ -81,6 +91,18 @@ impl<'a> Lexer<'a> {
         false
     }
 
+    /// 当前位置是否为 `e`/`E` 加可选正负号和数字组成的指数，返回其长度
+    ///
+    /// 不带正负号的 `1E5` 也按指数处理：E 是 Double 的类型后缀，但类型后缀之后不能直接跟数字。
+    fn exponent_len(&self) -> Option<usize> {
+        if !matches!(self.peek(), Some(b'e' | b'E')) {
+            return None;
+        }
+        let sign = usize::from(matches!(self.peek_at(1), Some(b'+' | b'-')));
+        let digits = self.bytes[self.pos + 1 + sign..].iter().take_while(|c| c.is_ascii_digit()).count();
+        (digits > 0).then_some(1 + sign + digits)
+    }
+
     fn read_number(&mut self) -> Result<Token<'a>, String> {
         let start = self.pos;
         let mut is_hex = false;
@@ -91,6 +113,19 @@ impl<'a> Lexer<'a> {
             self.pos += 1;
         }
 
+        // 0x 前缀的十六进制，读取所有十六进制数字，所以后面只能跟 Q、W、X 等非十六进制的类型后缀
+        // 前缀后必须有十六进制数字，单独的 0X 仍是 0 加 Xor 类型后缀
+        if self.peek() == Some(b'0')
+            && matches!(self.peek_at(1), Some(b'x' | b'X'))
+            && self.peek_at(2).is_some_and(|c| c.is_ascii_hexdigit())
+        {
+            self.pos += 2;
+            while self.peek().is_some_and(|c| c.is_ascii_hexdigit()) {
+                self.pos += 1;
+            }
+            return Ok(Token::Number(&self.input[start..self.pos], true));
+        }
+
         while let Some(ch) = self.peek() {
             match ch {
                 b'0'..=b'9' | b',' => {
@@ -111,6 +146,10 @@ impl<'a> Lexer<'a> {
                     if self.has_hex_suffix(self.pos) {
                         self.pos += 1;
                     } else {
+                        // 数字之后的指数写法 `1.4e-45`，之后只能是类型后缀
+                        if let Some(len) = self.exponent_len().filter(|_| self.pos > start) {
+                            self.pos += len;
+                        }
                         break;
                     }
                 }
@@ -134,6 +173,51 @@ impl<'a> Lexer<'a> {
         Ok(Token::Number(num_str, is_hex))
     }
 
+    /// 读取引号字符串，`\` 之后的字符不会结束字符串；结束引号后可以跟 `i` 表示忽略大小写，
+    /// utf16 表示字符串前有 `u` 前缀
+    fn read_text(&mut self, utf16: bool) -> Result<Token<'a>, String> {
+        self.advance();
+        let start = self.pos;
+        loop {
+            match self.advance() {
+                None => return Err("Unterminated string".to_string()),
+                Some(b'\\') => {
+                    if self.advance().is_none() {
+                        return Err("Unterminated string".to_string());
+                    }
+                }
+                Some(b'"') => break,
+                Some(_) => {}
+            }
+        }
+        let raw = &self.input[start..self.pos - 1];
+
+        let mut ignore_case = false;
+        while let Some(flag) = self.peek().filter(|c| c.is_ascii_alphabetic()) {
+            match flag {
+                b'i' | b'I' => ignore_case = true,
+                _ => return Err(format!("Unknown string flag: {}", flag as char)),
+            }
+            self.pos += 1;
+        }
+        Ok(Token::Text(raw, ignore_case, utf16))
+    }
+
+    /// `:` 之后的 `a` 加十进制数字，后面不能再跟字母或数字（`:a0h` 仍是十六进制的 range）
+    fn read_align(&mut self) -> Option<&'a str> {
+        if !matches!(self.peek(), Some(b'a' | b'A')) {
+            return None;
+        }
+        let digits = self.bytes[self.pos + 1..].iter().take_while(|c| c.is_ascii_digit()).count();
+        let end = self.pos + 1 + digits;
+        if digits == 0 || self.bytes.get(end).is_some_and(|c| c.is_ascii_alphanumeric()) {
+            return None;
+        }
+        let align = &self.input[self.pos + 1..end];
+        self.pos = end;
+        Some(align)
+    }
+
     pub fn next_token(&mut self) -> Result<Option<Token<'a>>, String> {
         self.skip_whitespace();
 
@@ -149,6 +233,8 @@ impl<'a> Lexer<'a> {
                     if self.peek() == Some(b':') {
                         self.advance();
                         Ok(Some(Token::DoubleColon))
+                    } else if let Some(align) = self.read_align() {
+                        Ok(Some(Token::Align(align)))
                     } else {
                         Ok(Some(Token::Colon))
                     }
@@ -162,6 +248,41 @@ impl<'a> Lexer<'a> {
                         Ok(Some(Token::Tilde))
                     }
                 }
+                b'!' => {
+                    self.advance();
+                    Ok(Some(Token::Bang))
+                }
+                b'#' => {
+                    self.advance();
+                    let start = self.pos;
+                    while self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+                        self.pos += 1;
+                    }
+                    if start == self.pos {
+                        return Err("Expected suffix name after '#'".to_string());
+                    }
+                    Ok(Some(Token::Suffix(&self.input[start..self.pos])))
+                }
+                b'@' => {
+                    self.advance();
+                    let start = self.pos;
+                    if matches!(self.peek(), Some(b'+') | Some(b'-')) {
+                        self.pos += 1;
+                    }
+                    let digits_start = self.pos;
+                    while self.peek().is_some_and(|c| c.is_ascii_alphanumeric()) {
+                        self.pos += 1;
+                    }
+                    if digits_start == self.pos {
+                        return Err("Expected offset after '@'".to_string());
+                    }
+                    Ok(Some(Token::Offset(&self.input[start..self.pos])))
+                }
+                b'"' => self.read_text(false).map(Some),
+                b'u' | b'U' if self.peek_at(1) == Some(b'"') => {
+                    self.advance();
+                    self.read_text(true).map(Some)
+                }
                 b'0'..=b'9' => self.read_number().map(Some),
                 b'-' => {
                     // 检查下一个字符是否为数字（支持负数）
@@ -212,7 +333,12 @@ pub fn parse_number(s: &str, is_hex: bool) -> Result<i128, String> {
     };
 
     if is_hex {
-        i128::from_str_radix(cleaned, 16)
+        let (sign, digits) = match cleaned.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", cleaned),
+        };
+        let digits = digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")).unwrap_or(digits);
+        i128::from_str_radix(&format!("{}{}", sign, digits), 16)
             .map_err(|_| format!("Invalid hex number: {}", s))
     } else {
         cleaned.parse::<i128>()
@@ -220,6 +346,47 @@ pub fn parse_number(s: &str, is_hex: bool) -> Result<i128, String> {
     }
 }
 
+/// 解析 `@` 偏移：可选的 +/- 号，之后是十进制、`0x` 前缀或 `h` 后缀的十六进制
+pub fn parse_offset(s: &str) -> Result<i64, String> {
+    let (negative, digits) = match s.as_bytes().first() {
+        Some(b'-') => (true, &s[1..]),
+        Some(b'+') => (false, &s[1..]),
+        _ => (false, s),
+    };
+
+    let magnitude = if let Some(hex) = digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+        i64::from_str_radix(hex, 16)
+    } else if let Some(hex) = digits.strip_suffix('h').or_else(|| digits.strip_suffix('H')) {
+        i64::from_str_radix(hex, 16)
+    } else {
+        digits.parse::<i64>()
+    }
+    .map_err(|_| format!("Invalid offset: {}", s))?;
+
+    Ok(if negative { -magnitude } else { magnitude })
+}
+
+/// 处理字符串中的转义：`\"`、`\\`、`\;`、`\n`、`\t`、`\0`
+pub fn parse_text(raw: &str) -> Result<String, String> {
+    let mut text = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            text.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some(escaped @ ('"' | '\\' | ';')) => text.push(escaped),
+            Some('n') => text.push('\n'),
+            Some('t') => text.push('\t'),
+            Some('0') => text.push('\0'),
+            Some(other) => return Err(format!("Invalid escape in string: \\{}", other)),
+            None => return Err("Invalid escape at end of string".to_string()),
+        }
+    }
+    Ok(text)
+}
+
 pub fn parse_float(s: &str, is_hex: bool) -> Result<f64, String> {
     if is_hex {
         return Err("Hex notation not supported for floating point".to_string());
@@ -234,6 +401,18 @@ pub fn parse_float(s: &str, is_hex: bool) -> Result<f64, String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_tokenize_hex_prefix() {
+        let tokens = Lexer::new("0x1FQ;-0X10;0X").tokenize().unwrap();
+        assert_eq!(tokens[0], Token::Number("0x1F", true));
+        assert_eq!(tokens[1], Token::Type(ValueType::Qword));
+        assert_eq!(tokens[3], Token::Number("-0X10", true));
+        assert_eq!(tokens[5], Token::Number("0", false));
+        assert_eq!(tokens[6], Token::Type(ValueType::Xor));
+        assert_eq!(parse_number("0x1F", true), Ok(0x1F));
+        assert_eq!(parse_number("-0X10", true), Ok(-0x10));
+    }
+
     #[test]
     fn test_tokenize_simple() {
         let mut lexer = Lexer::new("100D;200F");
@@ -256,6 +435,21 @@ mod tests {
         assert_eq!(parse_number("FFh", true).unwrap(), 255);
     }
 
+    #[test]
+    fn test_tokenize_exponent() {
+        let tokens = Lexer::new("1.4e-45F;2E+3;5E;1e5h;7E~").tokenize().unwrap();
+        assert_eq!(tokens[0], Token::Number("1.4e-45", false));
+        assert_eq!(tokens[1], Token::Type(ValueType::Float));
+        assert_eq!(tokens[3], Token::Number("2E+3", false));
+        assert_eq!(tokens[5], Token::Number("5", false));
+        assert_eq!(tokens[6], Token::Type(ValueType::Double));
+        assert_eq!(tokens[8], Token::Number("1e5h", true));
+        assert_eq!(tokens[10], Token::Number("7", false));
+        assert_eq!(tokens[11], Token::Type(ValueType::Double));
+        assert_eq!(tokens[12], Token::Tilde);
+        assert_eq!(parse_float("1.4e-45", false).unwrap(), 1.4e-45);
+    }
+
     #[test]
     fn test_parse_float() {
         assert_eq!(parse_float("1.0", false).unwrap(), 1.0);
@@ -318,6 +512,58 @@ mod tests {
         assert!(matches!(tokens2[2], Token::Number("-50", false)));
     }
 
+    #[test]
+    fn test_tokenize_suffix() {
+        let mut lexer = Lexer::new("100D;200D::64#span");
+        let tokens = lexer.tokenize().unwrap();
+        assert_eq!(tokens.len(), 8);
+        assert!(matches!(tokens[6], Token::Number("64", false)));
+        assert!(matches!(tokens[7], Token::Suffix("span")));
+
+        assert!(Lexer::new("100D;200D#").tokenize().is_err());
+    }
+
+    #[test]
+    fn test_tokenize_offset() {
+        let mut lexer = Lexer::new("100;200@+0x10;3.5F@-8~2");
+        let tokens = lexer.tokenize().unwrap();
+        assert_eq!(tokens.len(), 10);
+        assert!(matches!(tokens[3], Token::Offset("+0x10")));
+        assert!(matches!(tokens[6], Token::Type(ValueType::Float)));
+        assert!(matches!(tokens[7], Token::Offset("-8")));
+        assert!(matches!(tokens[8], Token::Tilde));
+
+        assert_eq!(parse_offset("+0x10").unwrap(), 16);
+        assert_eq!(parse_offset("-8").unwrap(), -8);
+        assert_eq!(parse_offset("14h").unwrap(), 20);
+        assert!(parse_offset("+16D").is_err());
+        assert!(Lexer::new("100;200@").tokenize().is_err());
+        assert!(Lexer::new("100;200@+").tokenize().is_err());
+    }
+
+    #[test]
+    fn test_tokenize_text() {
+        let mut lexer = Lexer::new(r#""Player\"Name\;1"i"#);
+        let tokens = lexer.tokenize().unwrap();
+        assert_eq!(tokens.len(), 1);
+        assert!(matches!(tokens[0], Token::Text(r#"Player\"Name\;1"#, true, false)));
+        assert_eq!(parse_text(r#"Player\"Name\;1"#).unwrap(), "Player\"Name;1");
+
+        // 引号内的分号和非 ASCII 字符原样保留
+        let tokens = Lexer::new(r#""a;b" "名字""#).tokenize().unwrap();
+        assert!(matches!(tokens[..], [Token::Text("a;b", false, false), Token::Text("名字", false, false)]));
+
+        // u 前缀按 UTF-16 编码，~ 前缀单独成为 token
+        let tokens = Lexer::new(r#"u"Hp" ~U"Hp"i"#).tokenize().unwrap();
+        assert!(matches!(tokens[..], [Token::Text("Hp", false, true), Token::Tilde, Token::Text("Hp", true, true)]));
+        assert_eq!(parse_text(r"\\\n\t\0").unwrap(), "\\\n\t\0");
+
+        assert!(Lexer::new(r#""abc"#).tokenize().is_err());
+        assert!(Lexer::new(r#""abc\""#).tokenize().is_err());
+        assert!(Lexer::new(r#""abc"x"#).tokenize().is_err());
+        assert!(parse_text(r"\q").is_err());
+    }
+
     #[test]
     fn test_negative_number_group() {
         // 测试负数组搜索
@@ -330,4 +576,14 @@ mod tests {
         assert!(matches!(tokens[3], Token::Semicolon));
         assert!(matches!(tokens[4], Token::Number("10", false)));
     }
+
+    #[test]
+    fn test_tokenize_operators() {
+        let tokens = Lexer::new("100~150;!0;~~-5D").tokenize().unwrap();
+        assert_eq!(tokens.len(), 10);
+        assert!(matches!(tokens[4], Token::Bang));
+        assert!(matches!(tokens[5], Token::Number("0", false)));
+        assert!(matches!(tokens[7], Token::DoubleTilde));
+        assert!(matches!(tokens[8], Token::Number("-5", false)));
+    }
 }
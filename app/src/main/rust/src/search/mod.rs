@@ -1,4 +1,5 @@
 pub mod types;
+pub mod format;
 pub mod lexer;
 pub mod parser;
 pub mod pattern;
@@ -8,8 +9,8 @@ pub mod result_manager;
 #[cfg(test)]
 pub mod tests;
 
-pub use types::{FuzzyCondition, SearchMode, SearchQuery, SearchValue, ValueType};
-pub use parser::parse_search_query;
-pub use pattern::{parse_pattern, create_pattern_search_value};
+pub use types::{FloatTolerance, FuzzyCondition, SearchMode, SearchQuery, SearchValue, SpanMode, ValueOffset, ValueType, XorKey};
+pub use parser::{parse_search_query, parse_typed_value};
+pub use pattern::{parse_pattern, parse_replacement, create_pattern_search_value};
 pub use engine::{SearchEngineManager, SEARCH_ENGINE_MANAGER, SearchProgressCallback, BPLUS_TREE_ORDER, PAGE_SIZE, PAGE_MASK, ValuePair};
 pub use result_manager::SearchResultItem;
\ No newline at end of file
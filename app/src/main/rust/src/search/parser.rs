@@ -1,5 +1,5 @@
-use super::lexer::{Lexer, Token, parse_number, parse_float};
-use super::types::{SearchMode, SearchQuery, SearchValue, ValueType};
+use super::lexer::{Lexer, Token, parse_number, parse_float, parse_offset, parse_text};
+use super::types::{FloatTolerance, SearchMode, SearchQuery, SearchValue, SpanMode, ValueOffset, ValueType, XorKey};
 
 pub struct Parser<'a> {
     tokens: Vec<Token<'a>>,
@@ -49,12 +49,31 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_value(&mut self) -> Result<SearchValue, String> {
+        // `~"PlayerName"`：忽略大小写，与 i 后缀相同
+        if matches!(self.peek(), Some(Token::Tilde))
+            && let Some(&Token::Text(raw, _, utf16)) = self.peek_at(1)
+        {
+            self.pos += 2;
+            return self.text_value(raw, true, utf16);
+        }
+
         let num_token = match self.advance() {
             Some(Token::Number(s, is_hex)) => (*s, *is_hex),
+            Some(&Token::Text(raw, ignore_case, utf16)) => return self.text_value(raw, ignore_case, utf16),
+            Some(Token::Bang) => return self.parse_not_equal(),
+            Some(Token::Tilde) => return self.parse_open_range(false),
+            Some(Token::DoubleTilde) => return self.parse_open_range(true),
             Some(token) => return Err(format!("Expected number, got {:?}", token)),
             None => return Err("Expected number, got EOF".to_string()),
         };
 
+        // `123:X<密钥>`：使用固定密钥的 XOR 值
+        if matches!(self.peek(), Some(Token::Colon)) && matches!(self.peek_at(1), Some(Token::Type(ValueType::Xor))) {
+            self.advance();
+            self.advance();
+            return self.parse_xor_key(num_token);
+        }
+
         let next_token = self.peek();
 
         match next_token {
@@ -82,7 +101,42 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// 字符串值：有 `u` 前缀或默认类型为 UTF-16 时按 UTF-16 LE 编码，否则按 UTF-8
+    fn text_value(&self, raw: &str, ignore_case: bool, utf16: bool) -> Result<SearchValue, String> {
+        let text_type = if utf16 || self.default_type == ValueType::Utf16String {
+            ValueType::Utf16String
+        } else {
+            ValueType::Utf8String
+        };
+        Ok(SearchValue::text(&parse_text(raw)?, text_type, ignore_case))
+    }
+
+    fn parse_xor_key(&mut self, num_token: (&'a str, bool)) -> Result<SearchValue, String> {
+        let key = match self.advance() {
+            Some(Token::Number(s, is_hex)) => parse_number(s, *is_hex)?,
+            Some(token) => return Err(format!("Expected Xor key, got {:?}", token)),
+            None => return Err("Expected Xor key, got EOF".to_string()),
+        };
+        let key = xor_word(key).ok_or_else(|| format!("Xor key {} does not fit in 32 bits", key))?;
+        match self.create_fixed_value(num_token, ValueType::Xor)? {
+            SearchValue::Xor { value, .. } => Ok(SearchValue::xor(value, XorKey::Fixed(key))),
+            value => Err(format!("Unsupported Xor value: {:?}", value)),
+        }
+    }
+
     fn parse_range(&mut self, start_token: (&'a str, bool), exclude: bool) -> Result<SearchValue, String> {
+        if self.at_value_end() {
+            let value_type = match self.peek() {
+                Some(Token::Type(vt)) => {
+                    let vt = *vt;
+                    self.advance();
+                    vt
+                }
+                _ => self.default_type,
+            };
+            return self.create_approx_value(start_token, value_type, exclude);
+        }
+
         let end_token = match self.advance() {
             Some(Token::Number(s, is_hex)) => (*s, *is_hex),
             Some(token) => return Err(format!("Expected number after range operator, got {:?}", token)),
@@ -107,6 +161,10 @@ impl<'a> Parser<'a> {
         value_type: ValueType,
         exclude: bool,
     ) -> Result<SearchValue, String> {
+        if self.at_value_end() && !matches!(self.peek(), Some(Token::Type(_))) {
+            return self.create_approx_value(start_token, value_type, exclude);
+        }
+
         let end_token = match self.advance() {
             Some(Token::Number(s, is_hex)) => (*s, *is_hex),
             Some(token) => return Err(format!("Expected number after range operator, got {:?}", token)),
@@ -126,12 +184,92 @@ impl<'a> Parser<'a> {
         self.create_range_value(start_token, end_token, value_type, exclude)
     }
 
+    /// `~` 之后没有数值：当前值到此结束，可能跟着类型后缀
+    fn at_value_end(&self) -> bool {
+        matches!(
+            self.peek(),
+            None | Some(Token::Type(_) | Token::Semicolon | Token::Colon | Token::DoubleColon | Token::Offset(_) | Token::Suffix(_) | Token::Align(_))
+        )
+    }
+
+    /// `v~`（`v~~` 排除）：按默认相对误差约等于 v
+    fn create_approx_value(&self, num_token: (&'a str, bool), value_type: ValueType, exclude: bool) -> Result<SearchValue, String> {
+        let value_type = approx_type(value_type)?;
+        let value = parse_float(num_token.0, num_token.1)?;
+        Ok(SearchValue::approx_float(value, FloatTolerance::Relative(FloatTolerance::DEFAULT_RELATIVE), value_type, exclude))
+    }
+
+    /// 前缀运算符之后的数值和可选的类型后缀
+    fn parse_operand(&mut self, operator: &str) -> Result<((&'a str, bool), ValueType), String> {
+        let num_token = match self.advance() {
+            Some(Token::Number(s, is_hex)) => (*s, *is_hex),
+            Some(token) => return Err(format!("Expected number after '{}', got {:?}", operator, token)),
+            None => return Err(format!("Expected number after '{}', got EOF", operator)),
+        };
+
+        let value_type = match self.peek() {
+            Some(Token::Type(vt)) => {
+                let vt = *vt;
+                self.advance();
+                vt
+            }
+            _ => self.default_type,
+        };
+        if value_type.is_variable_len() || value_type == ValueType::Xor {
+            return Err(format!("'{}' is not supported for {}", operator, value_type));
+        }
+
+        Ok((num_token, value_type))
+    }
+
+    /// `!v`：不等于 v
+    fn parse_not_equal(&mut self) -> Result<SearchValue, String> {
+        let (num_token, value_type) = self.parse_operand("!")?;
+        match self.create_fixed_value(num_token, value_type)? {
+            SearchValue::FixedInt { value, value_type } => Ok(SearchValue::not_equal(i128::from_le_bytes(value), value_type)),
+            SearchValue::FixedFloat { value, value_type: ValueType::Auto } => Ok(SearchValue::not_equal_float(value, ValueType::Float)),
+            SearchValue::FixedFloat { value, value_type } => Ok(SearchValue::not_equal_float(value, value_type)),
+            value => Err(format!("Unsupported value after '!': {:?}", value)),
+        }
+    }
+
+    /// `~v`（`~~v` 排除）：下界为类型的最小值
+    fn parse_open_range(&mut self, exclude: bool) -> Result<SearchValue, String> {
+        let operator = if exclude { "~~" } else { "~" };
+        let ((end_str, end_is_hex), value_type) = self.parse_operand(operator)?;
+
+        if value_type.is_float_type() {
+            let start = if value_type == ValueType::Float { f32::MIN as f64 } else { f64::MIN };
+            let end = parse_float(end_str, end_is_hex)?;
+            if end < start {
+                return Err(format!("Range end ({}) is below the minimum of {}", end, value_type));
+            }
+            return Ok(SearchValue::range_float(start, end, value_type, exclude));
+        }
+
+        let start = -(1i128 << (value_type.size() * 8 - 1));
+        let end = parse_number(end_str, end_is_hex)?;
+        if end > i64::MAX as i128 {
+            return Err(format!("Range end exceeds maximum for integer range search: {}", end));
+        }
+        if end < start {
+            return Err(format!("Range end ({}) is below the minimum of {}", end, value_type));
+        }
+        Ok(SearchValue::range(start, end, value_type, exclude))
+    }
+
     fn create_fixed_value(&self, num_token: (&'a str, bool), value_type: ValueType) -> Result<SearchValue, String> {
         let (num_str, is_hex) = num_token;
 
-        if value_type.is_float_type() {
+        // Auto 下带小数的值只可能是浮点数，具体类型在扫描时由 `auto_candidates` 决定
+        if value_type.is_float_type() || (value_type == ValueType::Auto && is_decimal(num_token)) {
             let value = parse_float(num_str, is_hex)?;
             Ok(SearchValue::fixed_float(value, value_type))
+        } else if value_type == ValueType::Xor {
+            // 默认使用地址作为密钥，固定密钥由 `parse_xor_key` 替换
+            let value = parse_number(num_str, is_hex)?;
+            let value = xor_word(value).ok_or_else(|| format!("Xor value {} does not fit in 32 bits", value))?;
+            Ok(SearchValue::xor(value, XorKey::Address))
         } else {
             let value = parse_number(num_str, is_hex)?;
             if value > u64::MAX as i128  {
@@ -154,11 +292,26 @@ impl<'a> Parser<'a> {
         let (start_str, start_is_hex) = start_token;
         let (end_str, end_is_hex) = end_token;
 
+        if value_type == ValueType::Xor {
+            return Err("Range search is not supported for Xor".to_string());
+        }
+
+        // Auto 下带小数的区间按 Float 搜索
+        let value_type = if value_type == ValueType::Auto && (is_decimal(start_token) || is_decimal(end_token)) {
+            ValueType::Float
+        } else {
+            value_type
+        };
+
         if value_type.is_float_type() {
             let start = parse_float(start_str, start_is_hex)?;
             let end = parse_float(end_str, end_is_hex)?;
 
+            // `v~t` 中 t 小于 v 时 t 是绝对误差；中心为负或误差不小于中心时与区间写法无法区分，按区间解析
             if start > end {
+                if end >= 0.0 {
+                    return Ok(SearchValue::approx_float(start, FloatTolerance::Absolute(end), value_type, exclude));
+                }
                 return Err(format!("Range start ({}) must be <= end ({})", start, end));
             }
 
@@ -183,17 +336,57 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn parse_values(&mut self) -> Result<Vec<SearchValue>, String> {
+    /// 可选的 `@偏移` 和 `~误差`，例如 `@+0x10`、`@-8~2`
+    fn parse_value_offset(&mut self) -> Result<Option<ValueOffset>, String> {
+        let offset = match self.peek() {
+            Some(Token::Offset(s)) => parse_offset(s)?,
+            _ => return Ok(None),
+        };
+        self.advance();
+
+        let tolerance = if matches!(self.peek(), Some(Token::Tilde)) {
+            self.advance();
+            match self.advance() {
+                Some(Token::Number(s, is_hex)) => {
+                    let tolerance = parse_number(s, *is_hex)?;
+                    if !(0..=ValueOffset::MAX_REACH as i128).contains(&tolerance) {
+                        return Err(format!("Invalid offset tolerance: {}", tolerance));
+                    }
+                    tolerance as u32
+                }
+                Some(token) => return Err(format!("Expected number for offset tolerance, got {:?}", token)),
+                None => return Err("Expected number for offset tolerance, got EOF".to_string()),
+            }
+        } else {
+            0
+        };
+
+        Ok(Some(ValueOffset::new(offset, tolerance)))
+    }
+
+    fn parse_values(&mut self) -> Result<(Vec<SearchValue>, Vec<Option<ValueOffset>>), String> {
         let mut values = Vec::new();
+        let mut offsets = Vec::new();
 
         values.push(self.parse_value()?);
+        offsets.push(self.parse_value_offset()?);
 
         while matches!(self.peek(), Some(Token::Semicolon)) {
             self.advance();
             values.push(self.parse_value()?);
+            offsets.push(self.parse_value_offset()?);
+        }
+
+        // 锚点可以显式写成 `@0`，与不写相同
+        if offsets[0] == Some(ValueOffset::new(0, 0)) {
+            offsets[0] = None;
+        }
+
+        if offsets.iter().all(Option::is_none) {
+            offsets.clear();
         }
 
-        Ok(values)
+        Ok((values, offsets))
     }
 
     fn parse_range_specifier(&mut self) -> Result<(SearchMode, u16), String> {
@@ -208,7 +401,7 @@ impl<'a> Parser<'a> {
                 let range = self.parse_range_size()?;
                 Ok((SearchMode::Ordered, range))
             }
-            None => {
+            None | Some(Token::Suffix(_) | Token::Align(_)) => {
                 Ok((SearchMode::Unordered, 512))
             }
             Some(token) => Err(format!("Expected colon or end of input, got {:?}", token)),
@@ -216,6 +409,10 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_range_size(&mut self) -> Result<u16, String> {
+        if matches!(self.peek(), Some(Token::Suffix(_) | Token::Align(_))) {
+            return Ok(512);
+        }
+
         match self.advance() {
             Some(Token::Number(s, is_hex)) => {
                 let size = parse_number(s, *is_hex)?;
@@ -229,26 +426,135 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// 可选的 `:aN` 对齐，例如 `100D:a1` 按 1 字节对齐搜索 Dword，合法性在 validate 中检查
+    fn parse_alignment(&mut self) -> Result<Option<usize>, String> {
+        let Some(Token::Align(digits)) = self.peek() else {
+            return Ok(None);
+        };
+        let alignment = digits.parse::<usize>().map_err(|_| format!("Invalid alignment: {}", digits))?;
+        self.advance();
+        Ok(Some(alignment))
+    }
+
+    /// 单值搜索的 range 没有意义，`123F:1` 这样紧跟十进制数字并结束的写法等同于 `123F:a1`
+    fn parse_single_alignment(&mut self, value_count: usize) -> Result<Option<usize>, String> {
+        if value_count != 1 || !matches!(self.peek(), Some(Token::Colon)) {
+            return Ok(None);
+        }
+        let Some(&Token::Number(digits, false)) = self.peek_at(1) else {
+            return Ok(None);
+        };
+        if !matches!(self.peek_at(2), None | Some(Token::Suffix(_))) {
+            return Ok(None);
+        }
+        let alignment = digits.parse::<usize>().map_err(|_| format!("Invalid alignment: {}", digits))?;
+        self.pos += 2;
+        Ok(Some(alignment))
+    }
+
+    /// 可选的 `#span` / `#anchor` 后缀，决定 range 按整组跨度还是按到锚点的距离计算
+    fn parse_span_suffix(&mut self) -> Result<SpanMode, String> {
+        match self.peek() {
+            Some(Token::Suffix(name)) => {
+                let span_mode = if name.eq_ignore_ascii_case("span") {
+                    SpanMode::GroupSpan
+                } else if name.eq_ignore_ascii_case("anchor") {
+                    SpanMode::FromAnchor
+                } else {
+                    return Err(format!("Unknown suffix: #{}", name));
+                };
+                self.advance();
+                Ok(span_mode)
+            }
+            _ => Ok(SpanMode::FromAnchor),
+        }
+    }
+
     pub fn parse(&mut self) -> Result<SearchQuery, String> {
-        let values = self.parse_values()?;
-        let (mode, range) = self.parse_range_specifier()?;
+        let (values, offsets) = self.parse_values()?;
+        let (mode, range, alignment) = match self.parse_single_alignment(values.len())? {
+            Some(alignment) => (SearchMode::Unordered, 512, Some(alignment)),
+            None => {
+                let (mode, range) = self.parse_range_specifier()?;
+                (mode, range, self.parse_alignment()?)
+            }
+        };
+        let span_mode = self.parse_span_suffix()?;
 
         if self.pos < self.tokens.len() {
             return Err(format!("Unexpected tokens after query: {:?}", &self.tokens[self.pos..]));
         }
 
-        let query = SearchQuery::new(values, mode, range);
+        let query = SearchQuery::new(values, mode, range)
+            .with_span_mode(span_mode)
+            .with_offsets(offsets)
+            .with_alignment(alignment);
         query.validate()?;
 
         Ok(query)
     }
 }
 
+/// 约等于搜索的类型：Auto 按 Float 搜索
+fn approx_type(value_type: ValueType) -> Result<ValueType, String> {
+    match value_type {
+        ValueType::Float | ValueType::Double => Ok(value_type),
+        ValueType::Auto => Ok(ValueType::Float),
+        _ => Err(format!("Approximate search is only supported for Float and Double, got {}", value_type)),
+    }
+}
+
+/// 十进制小数（包括指数写法）
+fn is_decimal((num_str, is_hex): (&str, bool)) -> bool {
+    !is_hex && num_str.contains(['.', 'e', 'E'])
+}
+
+/// Xor 的值和密钥都是 32 位，负数按补码处理
+fn xor_word(value: i128) -> Option<u32> {
+    (i32::MIN as i128..=u32::MAX as i128).contains(&value).then_some(value as u32)
+}
+
 pub fn parse_search_query(input: &str, default_type: ValueType) -> Result<SearchQuery, String> {
     let mut parser = Parser::new(input, default_type)?;
     parser.parse()
 }
 
+/// 把写入值解析成目标类型的小端字节，输入规则与搜索相同（十进制、`0x`/`h` 十六进制、浮点、负数、字符串）
+///
+/// 只接受单个精确值。整数允许目标宽度的有符号最小值到无符号最大值，超出时报错而不是截断。
+pub fn parse_typed_value(input: &str, value_type: ValueType) -> Result<Vec<u8>, String> {
+    if value_type == ValueType::Pattern {
+        return Err("Pattern values cannot be written as typed values".to_string());
+    }
+
+    let query = parse_search_query(input, value_type)?;
+    match query.values.as_slice() {
+        [value] if value.value_type() != value_type => {
+            Err(format!("Value type {} does not match target type {}", value.value_type(), value_type))
+        },
+        [SearchValue::FixedInt { value, .. }] => {
+            let value = i128::from_le_bytes(*value);
+            let bits = value_type.size() * 8;
+            let (min, max) = (-(1i128 << (bits - 1)), (1i128 << bits) - 1);
+            if value < min || value > max {
+                return Err(format!("Value {} is out of range for {} ({}..={})", value, value_type, min, max));
+            }
+            Ok(value.to_le_bytes()[..value_type.size()].to_vec())
+        },
+        [SearchValue::FixedFloat { value, .. }] if value_type == ValueType::Float => {
+            if value.is_finite() && value.abs() > f32::MAX as f64 {
+                return Err(format!("Value {} is out of range for Float", value));
+            }
+            Ok((*value as f32).to_le_bytes().to_vec())
+        },
+        [SearchValue::FixedFloat { value, .. }] => Ok(value.to_le_bytes().to_vec()),
+        [SearchValue::Text { bytes, .. }] => Ok(bytes.clone()),
+        // 返回明文，写入方按结果的密钥和地址编码
+        [SearchValue::Xor { value, .. }] => Ok(value.to_le_bytes().to_vec()),
+        _ => Err(format!("Not a single fixed value: {}", input)),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -295,6 +601,157 @@ mod tests {
         assert_eq!(query.range, 512);
     }
 
+    #[test]
+    fn test_parse_span_suffix() {
+        let query = parse_search_query("100D;200D::64", ValueType::Dword).unwrap();
+        assert_eq!(query.span_mode, SpanMode::FromAnchor);
+
+        let query = parse_search_query("100D;200D::64#span", ValueType::Dword).unwrap();
+        assert_eq!(query.mode, SearchMode::Ordered);
+        assert_eq!(query.range, 64);
+        assert_eq!(query.span_mode, SpanMode::GroupSpan);
+
+        let query = parse_search_query("100D;200D#SPAN", ValueType::Dword).unwrap();
+        assert_eq!(query.mode, SearchMode::Unordered);
+        assert_eq!(query.range, 512);
+        assert_eq!(query.span_mode, SpanMode::GroupSpan);
+
+        let query = parse_search_query("100D;200D:#anchor", ValueType::Dword).unwrap();
+        assert_eq!(query.range, 512);
+        assert_eq!(query.span_mode, SpanMode::FromAnchor);
+
+        assert!(parse_search_query("100D;200D:64#wide", ValueType::Dword).is_err());
+        assert!(parse_search_query("100D;200D:64#span#span", ValueType::Dword).is_err());
+    }
+
+    #[test]
+    fn test_parse_alignment() {
+        let query = parse_search_query("100D:a1", ValueType::Dword).unwrap();
+        assert_eq!(query.alignment, Some(1));
+        assert_eq!(query.alignment_for(ValueType::Dword), 1);
+        assert_eq!(parse_search_query("100D", ValueType::Dword).unwrap().alignment_for(ValueType::Dword), 4);
+
+        let query = parse_search_query("100D;200D::64:a2#span", ValueType::Dword).unwrap();
+        assert_eq!((query.mode, query.range, query.alignment, query.span_mode), (SearchMode::Ordered, 64, Some(2), SpanMode::GroupSpan));
+
+        let query = parse_search_query("100D;200D:A8", ValueType::Dword).unwrap();
+        assert_eq!((query.range, query.alignment), (512, Some(8)));
+
+        // `:a0h` 是十六进制的 range
+        let query = parse_search_query("100D;200D:a0h", ValueType::Dword).unwrap();
+        assert_eq!((query.range, query.alignment), (0xA0, None));
+
+        // 不是 2 的幂、超过类型大小的 8 倍（组搜索按最小的类型）、字符串
+        assert!(parse_search_query("100D:a3", ValueType::Dword).is_err());
+        assert!(parse_search_query("100D:a0", ValueType::Dword).is_err());
+        assert!(parse_search_query("100Q:a64", ValueType::Qword).is_ok());
+        assert!(parse_search_query("100D:a64", ValueType::Dword).is_err());
+        assert!(parse_search_query("1B;100Q:a16", ValueType::Dword).is_err());
+        assert!(parse_search_query("\"abc\":a1", ValueType::Dword).is_err());
+        assert!(parse_search_query("100D:a4:a4", ValueType::Dword).is_err());
+    }
+
+    #[test]
+    fn test_parse_single_value_alignment_shorthand() {
+        let query = parse_search_query("123F:1", ValueType::Dword).unwrap();
+        assert_eq!((query.values[0].value_type(), query.alignment), (ValueType::Float, Some(1)));
+        assert_eq!(query.alignment_for(ValueType::Float), 1);
+        assert_eq!(parse_search_query("123.5:2", ValueType::Float).unwrap().alignment, Some(2));
+        assert_eq!(parse_search_query("100D:1#span", ValueType::Dword).unwrap().alignment, Some(1));
+
+        // 组搜索中 `:N` 仍然是 range
+        let query = parse_search_query("100D;200D:16", ValueType::Dword).unwrap();
+        assert_eq!((query.range, query.alignment), (16, None));
+        // 十六进制不是对齐
+        assert_eq!(parse_search_query("100D:10h", ValueType::Dword).unwrap().alignment, None);
+
+        assert!(parse_search_query("123F:0", ValueType::Dword).is_err());
+        assert!(parse_search_query("123F:3", ValueType::Dword).is_err());
+        assert!(parse_search_query("123F:64", ValueType::Dword).is_err());
+        assert!(parse_search_query("123F:1:a1", ValueType::Dword).is_err());
+        assert!(parse_search_query("\"abc\":1", ValueType::Dword).is_err());
+    }
+
+    #[test]
+    fn test_parse_offsets() {
+        let query = parse_search_query("100;200@+0x10;3.5F@+0x14", ValueType::Dword).unwrap();
+        assert_eq!(query.values.len(), 3);
+        assert_eq!(query.values[2].value_type(), ValueType::Float);
+        assert_eq!(query.offsets, vec![None, Some(ValueOffset::new(0x10, 0)), Some(ValueOffset::new(0x14, 0))]);
+        assert!(query.has_offsets());
+
+        // 负偏移和误差；有偏移时锚点总是第一个值，模式被忽略
+        let query = parse_search_query("100~200D;5@-8~2;7@14h::64", ValueType::Dword).unwrap();
+        assert_eq!(query.offsets, vec![None, Some(ValueOffset::new(-8, 2)), Some(ValueOffset::new(0x14, 0))]);
+        assert_eq!(query.anchor_index(), 0);
+
+        // 锚点显式写 `@0`
+        let query = parse_search_query("100D@0;200D@8;5W@0x14", ValueType::Dword).unwrap();
+        assert_eq!(query.offsets, vec![None, Some(ValueOffset::new(8, 0)), Some(ValueOffset::new(0x14, 0))]);
+        assert_eq!(query.values[2].value_type(), ValueType::Word);
+        assert!(query.has_exact_offsets());
+        assert!(!parse_search_query("100;5@-8~2", ValueType::Dword).unwrap().has_exact_offsets());
+        assert!(parse_search_query("100@0", ValueType::Dword).unwrap().offsets.is_empty());
+        assert!(parse_search_query("100@0~4;200@+8", ValueType::Dword).is_err());
+
+        assert!(parse_search_query("100;200", ValueType::Dword).unwrap().offsets.is_empty());
+        assert!(parse_search_query("100@+4;200@+8", ValueType::Dword).is_err());
+        assert!(parse_search_query("100;200@+8;300", ValueType::Dword).is_err());
+        assert!(parse_search_query("100;200@+0x10001", ValueType::Dword).is_err());
+        assert!(parse_search_query("100;200@+8~", ValueType::Dword).is_err());
+    }
+
+    #[test]
+    fn test_parse_text() {
+        let query = parse_search_query(r#""Player\"1\;""#, ValueType::Dword).unwrap();
+        assert!(query.is_text());
+        assert_eq!(query.values[0].value_type(), ValueType::Utf8String);
+        assert_eq!(query.values[0].byte_len(), 9);
+        assert!(query.values[0].match_text(b"Player\"1;"));
+        assert!(!query.values[0].match_text(b"player\"1;"));
+
+        // UTF-16 由 UTF-8 输入生成；i 后缀忽略 ASCII 大小写
+        let query = parse_search_query(r#""Hp名"i"#, ValueType::Utf16String).unwrap();
+        let SearchValue::Text { ref bytes, value_type, ignore_case } = query.values[0] else {
+            panic!("expected text value");
+        };
+        assert_eq!((bytes.as_slice(), value_type, ignore_case), (&[0x48, 0, 0x70, 0, 0x0D, 0x54][..], ValueType::Utf16String, true));
+        assert!(query.values[0].match_text(&[0x68, 0, 0x50, 0, 0x0D, 0x54]));
+        // 高字节恰好是 ASCII 字母的码元不参与折叠
+        assert!(!query.values[0].match_text(&[0x48, 0, 0x70, 0, 0x0D, 0x74]));
+
+        assert!(parse_search_query(r#""""#, ValueType::Dword).is_err());
+        assert!(parse_search_query(r#""abc";100"#, ValueType::Dword).is_err());
+        assert!(parse_search_query(r#"100;"abc""#, ValueType::Dword).is_err());
+        assert!(parse_search_query(r#""abc"@+4"#, ValueType::Dword).is_err());
+        assert!(parse_search_query(r#""a\qb""#, ValueType::Dword).is_err());
+    }
+
+    #[test]
+    fn test_parse_typed_value() {
+        assert_eq!(parse_typed_value("-1", ValueType::Word).unwrap(), vec![0xFF, 0xFF]);
+        assert_eq!(parse_typed_value("65535", ValueType::Word).unwrap(), vec![0xFF, 0xFF]);
+        assert_eq!(parse_typed_value("0x1234", ValueType::Dword).unwrap(), vec![0x34, 0x12, 0, 0]);
+        assert_eq!(parse_typed_value("1Ah", ValueType::Byte).unwrap(), vec![0x1A]);
+        assert_eq!(parse_typed_value("-2", ValueType::Qword).unwrap(), (-2i64).to_le_bytes().to_vec());
+        assert_eq!(parse_typed_value("18446744073709551615", ValueType::Qword).unwrap(), vec![0xFF; 8]);
+        assert_eq!(parse_typed_value("1.5", ValueType::Float).unwrap(), 1.5f32.to_le_bytes().to_vec());
+        assert_eq!(parse_typed_value("-0.25", ValueType::Double).unwrap(), (-0.25f64).to_le_bytes().to_vec());
+        assert_eq!(parse_typed_value(r#""Hp""#, ValueType::Utf16String).unwrap(), vec![0x48, 0, 0x70, 0]);
+
+        let err = parse_typed_value("70000", ValueType::Word).unwrap_err();
+        assert!(err.contains("out of range for Word"), "{}", err);
+        assert!(parse_typed_value("-129", ValueType::Byte).is_err());
+        assert!(parse_typed_value("256", ValueType::Byte).is_err());
+        assert!(parse_typed_value("1e39", ValueType::Float).is_err());
+        // 类型后缀与目标类型不同、范围值、多个值都不能写入
+        assert!(parse_typed_value("5F", ValueType::Dword).is_err());
+        assert!(parse_typed_value("1~5", ValueType::Dword).is_err());
+        assert!(parse_typed_value("1;2", ValueType::Dword).is_err());
+        assert!(parse_typed_value(r#""abc""#, ValueType::Dword).is_err());
+        assert!(parse_typed_value("1A 2B", ValueType::Pattern).is_err());
+    }
+
     #[test]
     fn test_single_value_search() {
         let query = parse_search_query("100D", ValueType::Dword).unwrap();
@@ -337,4 +794,161 @@ mod tests {
         assert_eq!(query.values.len(), 1);
         assert!(matches!(query.values[0], SearchValue::FixedFloat { .. }));
     }
+
+    #[test]
+    fn test_parse_not_equal() {
+        let query = parse_search_query("!0", ValueType::Dword).unwrap();
+        assert!(matches!(query.values[0], SearchValue::RangeInt { start: 0, end: 0, exclude: true, .. }));
+        assert!(!query.values[0].matched(&0u32.to_le_bytes()).unwrap());
+        assert!(query.values[0].matched(&1u32.to_le_bytes()).unwrap());
+
+        // 无符号写法与内存中的有符号值比较
+        let query = parse_search_query("!4294967295D", ValueType::Dword).unwrap();
+        assert!(!query.values[0].matched(&u32::MAX.to_le_bytes()).unwrap());
+        let query = parse_search_query("!255B", ValueType::Dword).unwrap();
+        assert!(!query.values[0].matched(&[0xFF]).unwrap());
+        assert!(query.values[0].matched(&[0x7F]).unwrap());
+
+        let query = parse_search_query("!1.5F", ValueType::Dword).unwrap();
+        assert!(!query.values[0].matched(&1.5f32.to_le_bytes()).unwrap());
+        assert!(query.values[0].matched(&1.5001f32.to_le_bytes()).unwrap());
+        let query = parse_search_query("!0.1E", ValueType::Dword).unwrap();
+        assert!(!query.values[0].matched(&0.1f64.to_le_bytes()).unwrap());
+        assert!(query.values[0].matched(&0.2f64.to_le_bytes()).unwrap());
+
+        assert!(parse_search_query("!", ValueType::Dword).is_err());
+        assert!(parse_search_query("!!1", ValueType::Dword).is_err());
+        assert!(parse_search_query(r#"!"abc""#, ValueType::Dword).is_err());
+    }
+
+    #[test]
+    fn test_parse_open_range() {
+        let cases = [
+            ("~100B", i8::MIN as i128, [0x80u8, 0, 0, 0, 0, 0, 0, 0], [0x7Fu8, 0, 0, 0, 0, 0, 0, 0]),
+            ("~100W", i16::MIN as i128, [0x00, 0x80, 0, 0, 0, 0, 0, 0], [0xFF, 0x7F, 0, 0, 0, 0, 0, 0]),
+            ("~100", i32::MIN as i128, [0, 0, 0, 0x80, 0, 0, 0, 0], [0xFF, 0xFF, 0xFF, 0x7F, 0, 0, 0, 0]),
+            ("~100Q", i64::MIN as i128, [0, 0, 0, 0, 0, 0, 0, 0x80], [0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x7F]),
+        ];
+        for (input, min, lowest, highest) in cases {
+            let query = parse_search_query(input, ValueType::Dword).unwrap();
+            let value = &query.values[0];
+            assert!(matches!(value, SearchValue::RangeInt { start, end: 100, exclude: false, .. } if *start == min), "{}", input);
+            assert!(value.matched(&lowest).unwrap(), "{}", input);
+            assert!(!value.matched(&highest).unwrap(), "{}", input);
+        }
+
+        let query = parse_search_query("~1.5F", ValueType::Dword).unwrap();
+        assert!(matches!(query.values[0], SearchValue::RangeFloat { start, .. } if start == f32::MIN as f64));
+        assert!(query.values[0].matched(&f32::MIN.to_le_bytes()).unwrap());
+        assert!(query.values[0].matched(&1.5f32.to_le_bytes()).unwrap());
+        assert!(!query.values[0].matched(&1.6f32.to_le_bytes()).unwrap());
+        let query = parse_search_query("~0E", ValueType::Dword).unwrap();
+        assert!(matches!(query.values[0], SearchValue::RangeFloat { start, .. } if start == f64::MIN));
+
+        // 排除：大于 100
+        let query = parse_search_query("~~100", ValueType::Dword).unwrap();
+        assert!(query.values[0].matched(&101u32.to_le_bytes()).unwrap());
+        assert!(!query.values[0].matched(&100u32.to_le_bytes()).unwrap());
+
+        assert!(parse_search_query("~", ValueType::Dword).is_err());
+        assert!(parse_search_query("~-200B", ValueType::Dword).is_err());
+    }
+
+    #[test]
+    fn test_parse_malformed_ranges() {
+        assert!(parse_search_query("150~100", ValueType::Dword).is_err());
+        assert!(parse_search_query("1.5~-0.5F", ValueType::Dword).is_err());
+        assert!(parse_search_query("100~", ValueType::Dword).is_err());
+        assert!(parse_search_query("100~~", ValueType::Dword).is_err());
+        assert!(parse_search_query("100~!5", ValueType::Dword).is_err());
+    }
+
+    #[test]
+    fn test_parse_approx_float() {
+        // 绝对误差：边界本身在范围内，刚越过边界的值不在
+        for (input, value_type) in [("1.25~0.01F", ValueType::Dword), ("1.25F~0.01", ValueType::Dword), ("1.25~0.01", ValueType::Double)] {
+            let query = parse_search_query(input, value_type).unwrap();
+            let value = &query.values[0];
+            let matches = |x: f64| match value.value_type() {
+                ValueType::Float => value.matched(&(x as f32).to_le_bytes()).unwrap(),
+                _ => value.matched(&x.to_le_bytes()).unwrap(),
+            };
+            for x in [1.24, 1.2401, 1.25, 1.2599, 1.26] {
+                assert!(matches(x), "{} {}", input, x);
+            }
+            for x in [1.2399, 1.2601, -1.25] {
+                assert!(!matches(x), "{} {}", input, x);
+            }
+        }
+
+        // 默认相对误差 1e-6
+        let query = parse_search_query("1000~", ValueType::Float).unwrap();
+        assert!(query.values[0].matched(&1000.0009f32.to_le_bytes()).unwrap());
+        assert!(!query.values[0].matched(&1000.002f32.to_le_bytes()).unwrap());
+        let query = parse_search_query("0.1~E", ValueType::Dword).unwrap();
+        assert_eq!(query.values[0].value_type(), ValueType::Double);
+        assert!(query.values[0].matched(&(0.1f64 + 1e-8).to_le_bytes()).unwrap());
+        assert!(!query.values[0].matched(&0.1000002f64.to_le_bytes()).unwrap());
+
+        // 排除，以及联合搜索中与其他值组合
+        let query = parse_search_query("1.25~~0.01F;100D;2.5~:64", ValueType::Float).unwrap();
+        assert!(!query.values[0].matched(&1.255f32.to_le_bytes()).unwrap());
+        assert!(query.values[0].matched(&1.3f32.to_le_bytes()).unwrap());
+        assert!(query.values[2].matched(&2.500_001f32.to_le_bytes()).unwrap());
+        assert_eq!(query.range, 64);
+
+        // Auto 按 Float 搜索，也接受指数写法
+        let query = parse_search_query("1.25~", ValueType::Auto).unwrap();
+        assert_eq!(query.values[0].value_type(), ValueType::Float);
+        let query = parse_search_query("1.25~0.01", ValueType::Auto).unwrap();
+        assert!(query.values[0].matched(&1.2599f32.to_le_bytes()).unwrap());
+        let query = parse_search_query("1.4e-45~F", ValueType::Dword).unwrap();
+        assert!(query.values[0].matched(&f32::from_bits(1).to_le_bytes()).unwrap());
+        assert!(query.values[0].matched(&0f32.to_le_bytes()).unwrap());
+        assert!(!query.values[0].matched(&f32::from_bits(3).to_le_bytes()).unwrap());
+
+        // 中心为负时按区间解析
+        let query = parse_search_query("-1.25~0.01F", ValueType::Dword).unwrap();
+        assert!(query.values[0].matched(&(-1.0f32).to_le_bytes()).unwrap());
+
+        assert!(parse_search_query("100~", ValueType::Dword).is_err());
+        assert!(parse_search_query("100~D", ValueType::Float).is_err());
+        assert!(parse_search_query("1.25F~E", ValueType::Dword).is_err());
+    }
+
+    #[test]
+    fn test_parse_mixed_operators() {
+        let query = parse_search_query("100~150;!0;3.5F", ValueType::Dword).unwrap();
+        assert_eq!(query.values.len(), 3);
+        assert!(matches!(query.values[0], SearchValue::RangeInt { start: 100, end: 150, exclude: false, .. }));
+        assert!(matches!(query.values[1], SearchValue::RangeInt { exclude: true, .. }));
+        assert!(matches!(query.values[2], SearchValue::FixedFloat { value_type: ValueType::Float, .. }));
+    }
+
+    #[test]
+    fn test_parse_xor() {
+        let query = parse_search_query("123X", ValueType::Dword).unwrap();
+        assert!(matches!(query.values[0], SearchValue::Xor { value: 123, key: XorKey::Address }));
+
+        let query = parse_search_query("123", ValueType::Xor).unwrap();
+        assert!(matches!(query.values[0], SearchValue::Xor { value: 123, key: XorKey::Address }));
+
+        let query = parse_search_query("-1:X0x5A5A", ValueType::Dword).unwrap();
+        assert!(matches!(query.values[0], SearchValue::Xor { value: u32::MAX, key: XorKey::Fixed(0x5A5A) }));
+        assert!(query.values[0].matched(&(u32::MAX ^ 0x5A5A).to_le_bytes()).unwrap());
+
+        // 地址密钥必须知道地址
+        let query = parse_search_query("100X", ValueType::Dword).unwrap();
+        assert!(query.values[0].matched(&100u32.to_le_bytes()).is_err());
+        assert!(query.values[0].matched_at(&(100u32 ^ 0x1234_5678).to_le_bytes(), 0x7F_1234_5678).unwrap());
+
+        assert!(parse_search_query("4294967296X", ValueType::Dword).is_err());
+        assert!(parse_search_query("1:X4294967296", ValueType::Dword).is_err());
+        assert!(parse_search_query("1:X", ValueType::Dword).is_err());
+        assert!(parse_search_query("1~10X", ValueType::Dword).is_err());
+        assert!(parse_search_query("!1X", ValueType::Dword).is_err());
+        assert!(parse_search_query("1X;2D", ValueType::Dword).is_err());
+
+        assert_eq!(parse_typed_value("-2", ValueType::Xor).unwrap(), (-2i32).to_le_bytes());
+    }
 }
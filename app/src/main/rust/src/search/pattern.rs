@@ -41,6 +41,24 @@ pub fn parse_pattern(input: &str) -> Result<Vec<(u8, u8)>, String> {
     Ok(result)
 }
 
+/// 解析特征码替换字符串
+///
+/// 格式与特征码相同，"??" 表示保留原字节；替换是整字节写入，不支持半字节通配
+///
+/// # 返回
+/// * `Ok(Vec<Option<u8>>)` - 解析成功，`None` 为保留的字节
+/// * `Err(String)` - 解析失败，返回错误信息
+pub fn parse_replacement(input: &str) -> Result<Vec<Option<u8>>, String> {
+    parse_pattern(input)?
+        .into_iter()
+        .map(|(value, mask)| match mask {
+            0xFF => Ok(Some(value)),
+            0x00 => Ok(None),
+            _ => Err("Half-byte wildcards are not supported in replacement".to_string()),
+        })
+        .collect()
+}
+
 /// 解析单个字节（两个十六进制字符）
 fn parse_byte(high: char, low: char) -> Result<(u8, u8), String> {
     let (high_val, high_mask) = parse_nibble(high)?;
@@ -128,6 +146,13 @@ mod tests {
         assert!(parse_pattern("1A 2").is_err());   // 混合有效无效
     }
 
+    #[test]
+    fn test_parse_replacement() {
+        assert_eq!(parse_replacement("90 ?? 1f").unwrap(), vec![Some(0x90), None, Some(0x1F)]);
+        assert!(parse_replacement("90 1?").is_err());
+        assert!(parse_replacement("").is_err());
+    }
+
     #[test]
     fn test_match_pattern() {
         let sv = create_pattern_search_value("1A ?B C? ??").unwrap();
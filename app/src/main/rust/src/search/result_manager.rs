@@ -1,14 +1,52 @@
+mod address_range;
+pub(crate) mod address_sort;
+mod byte_hits;
+pub(crate) mod cursor;
 mod exact;
 mod fuzzy;
+mod generation;
+mod handles;
+mod labels;
+pub(crate) mod integrity;
+mod results_file;
+mod text_export;
+mod undo;
+mod value_cache;
 
-use super::types::ValueType;
+use super::types::{ValueType, XorKey};
+use crate::core::address_rebase::AddressRebase;
+pub use crate::search::result_manager::address_range::AddressRangeResults;
+use crate::search::result_manager::address_range::AscendingRunCache;
+pub use crate::search::result_manager::byte_hits::{ByteHitSet, ByteHitStats, PageHits};
+pub(crate) use crate::search::result_manager::cursor::ResultCursor;
 pub use crate::search::result_manager::exact::ExactSearchResultItem;
 use crate::search::result_manager::exact::ExactSearchResultManager;
 pub use crate::search::result_manager::fuzzy::{FuzzySearchResultItem, FuzzySearchResultManager};
+pub use crate::search::result_manager::generation::ResultGeneration;
+pub use crate::search::result_manager::integrity::IntegrityReport;
+pub use crate::search::result_manager::results_file::ResultFileHeader;
+pub use crate::search::result_manager::value_cache::{ExactValueCache, VALUE_CACHE_MAX_BYTES};
+pub(crate) use crate::search::result_manager::undo::RefineJournal;
+use crate::search::result_manager::results_file::{ResultFileReader, ResultFileWriter};
+pub use crate::search::result_manager::text_export::ResultExportFormat;
+use crate::search::result_manager::text_export::TextExportWriter;
+use crate::search::result_manager::generation::{GenerationStore, MAX_GENERATION_ITEMS};
+pub(crate) use crate::search::result_manager::generation::PendingGeneration;
+use crate::search::result_manager::handles::ResultHandles;
+use crate::search::result_manager::labels::ResultLabels;
+use crate::search::result_manager::undo::{PreviousResults, StashedResults};
+pub use crate::search::result_manager::labels::MAX_LABEL_BYTES;
 use anyhow::{Result, anyhow};
-use log::{debug, error, info};
-use std::path::PathBuf;
+use log::{debug, error, info, warn};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
 use crate::search::engine::ValuePair;
+use crate::search::engine::filter::SearchFilter;
+
+/// 导出 / 导入结果文件时每批处理的记录数
+const RESULT_FILE_BATCH: usize = 1024 * 1024;
+/// 文本导出时每批格式化的记录数
+const TEXT_EXPORT_BATCH: usize = 64 * 1024;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SearchResultMode {
@@ -33,6 +71,21 @@ impl SearchResultItem {
     pub fn new_fuzzy_from_bytes(address: u64, bytes: &[u8], value_type: ValueType) -> Self {
         SearchResultItem::Fuzzy(FuzzySearchResultItem::from_bytes(address, bytes, value_type))
     }
+
+    /// 结果进入结果集的轮次
+    pub fn pass(&self) -> u8 {
+        match self {
+            SearchResultItem::Exact(exact) => exact.pass,
+            SearchResultItem::Fuzzy(fuzzy) => fuzzy.pass,
+        }
+    }
+
+    pub fn with_pass(self, pass: u8) -> Self {
+        match self {
+            SearchResultItem::Exact(exact) => SearchResultItem::Exact(exact.with_pass(pass)),
+            SearchResultItem::Fuzzy(fuzzy) => SearchResultItem::Fuzzy(fuzzy.with_pass(pass)),
+        }
+    }
 }
 
 impl From<(u64, ValueType)> for SearchResultItem {
@@ -47,30 +100,268 @@ impl From<&ValuePair> for SearchResultItem {
     }
 }
 
+/// 当前结果文件的校验结果，以及启动时接管遗留文件的恢复报告
+#[derive(Debug, Clone, Serialize)]
+pub struct ResultStoreReport {
+    pub mode: String,
+    pub store: IntegrityReport,
+    pub recovery: Option<IntegrityReport>,
+}
+
 pub(crate) struct SearchResultManager {
     current_mode: SearchResultMode,
     exact: ExactSearchResultManager,
     fuzzy: FuzzySearchResultManager,
+    /// 模糊搜索的历史代，用于与任意一代比较
+    generations: GenerationStore,
+    /// 结果集每次变化递增，派生数据（如区域分组）据此判断缓存是否过期
+    revision: u64,
+    /// 最近一次新增结果操作的轮次
+    current_pass: u8,
+    /// 按页存储的 Byte 结果，存在时代替精确结果
+    byte_hits: Option<ByteHitSet>,
+    /// 精确结果匹配时的值，用于显示
+    exact_values: ExactValueCache,
+    /// 当前结果的稳定句柄，删除 / 保留按句柄定位
+    handles: ResultHandles,
+    /// 按地址范围查找时使用的升序段
+    ascending_runs: AscendingRunCache,
+    /// 用户给结果加的标签，按 (地址, 类型) 保存，见 `labels`
+    labels: ResultLabels,
+    /// 最近一次改善搜索之前的结果集，用于撤销，见 `undo`
+    undo: Option<PreviousResults>,
 }
 
 impl SearchResultManager {
     pub fn new(memory_buffer_size: usize, cache_dir: PathBuf) -> Self {
+        let exact = ExactSearchResultManager::new(memory_buffer_size, cache_dir.clone());
+        let fuzzy = FuzzySearchResultManager::new(memory_buffer_size, cache_dir.clone());
+
+        // 接管了上次遗留的模糊结果而没有精确结果时，从模糊模式开始
+        let current_mode = if exact.total_count() == 0 && fuzzy.total_count() > 0 {
+            SearchResultMode::Fuzzy
+        } else {
+            SearchResultMode::Exact
+        };
+        for report in [exact.recovery_report(), fuzzy.recovery_report()].into_iter().flatten() {
+            if !report.ok {
+                warn!(
+                    "Result store recovered after unclean shutdown: kept {}, dropped {} records",
+                    report.verified_records, report.dropped_records
+                );
+            }
+        }
+
         Self {
-            current_mode: SearchResultMode::Exact,
-            exact: ExactSearchResultManager::new(memory_buffer_size, cache_dir.clone()),
-            fuzzy: FuzzySearchResultManager::new(memory_buffer_size, cache_dir),
+            current_mode,
+            exact,
+            fuzzy,
+            generations: GenerationStore::new(cache_dir),
+            revision: 0,
+            current_pass: 0,
+            byte_hits: None,
+            exact_values: ExactValueCache::default(),
+            handles: ResultHandles::default(),
+            ascending_runs: AscendingRunCache::default(),
+            labels: ResultLabels::default(),
+            undo: None,
         }
     }
 
     pub fn clear(&mut self) -> Result<()> {
+        // 清空结果意味着开始新的会话，历史代不再有意义
+        self.generations.clear();
+        self.byte_hits = None;
+        self.exact_values = ExactValueCache::default();
+        self.handles.clear();
+        match self.current_mode {
+            SearchResultMode::Exact => self.exact.clear()?,
+            SearchResultMode::Fuzzy => self.fuzzy.clear()?,
+        }
+        self.seal()
+    }
+
+    /// 一次批量写入/改写完成，封存当前结果文件的校验清单，为新增的结果分配句柄并清理失效的标签
+    fn seal(&mut self) -> Result<()> {
+        self.revision += 1;
+        self.handles.sync(self.total_count());
         match self.current_mode {
-            SearchResultMode::Exact => self.exact.clear(),
-            SearchResultMode::Fuzzy => self.fuzzy.clear(),
+            SearchResultMode::Exact => self.exact.seal()?,
+            SearchResultMode::Fuzzy => self.fuzzy.seal()?,
+        }
+        self.prune_labels()
+    }
+
+    /// 删除地址已经不在结果集里的标签；结果集为空时跳过，改善搜索会先清空再写入幸存的结果
+    fn prune_labels(&mut self) -> Result<()> {
+        if self.labels.is_empty() || self.total_count() == 0 {
+            return Ok(());
+        }
+        let mut present = Vec::new();
+        for (address, value_type, _) in self.labels.entries() {
+            let found = self.get_results_in_range(address, address.saturating_add(1), usize::MAX)?;
+            let has_type = found.items.iter().any(|(_, item)| match item {
+                SearchResultItem::Exact(exact) => exact.typ == value_type,
+                SearchResultItem::Fuzzy(fuzzy) => {
+                    // 模糊结果是 packed 结构，按值读取字段
+                    let fuzzy_type = fuzzy.value_type;
+                    fuzzy_type == value_type
+                },
+            });
+            if has_type {
+                present.push((address, value_type));
+            }
+        }
+        let removed = self.labels.retain(|address, value_type| present.contains(&(address, value_type)));
+        if removed > 0 {
+            debug!("Dropped {} labels of removed results", removed);
+        }
+        Ok(())
+    }
+
+    /// 给句柄对应的结果设置标签，空字符串删除标签；结果已被删除时返回错误
+    pub fn set_label(&mut self, handle: u64, label: String) -> Result<()> {
+        let position = self.handles.position_of(handle).ok_or_else(|| anyhow!("Result handle {} no longer exists", handle))?;
+        let item = self.records(position, 1)?.into_iter().next().ok_or_else(|| anyhow!("Result {} not found", position))?;
+        self.labels.set(item.address, item.typ, label)
+    }
+
+    /// 句柄对应结果的标签，没有标签或结果已被删除时为 None
+    pub fn get_labels(&self, handles: &[u64]) -> Result<Vec<Option<String>>> {
+        let mut labels = Vec::with_capacity(handles.len());
+        for &handle in handles {
+            let label = match self.handles.position_of(handle) {
+                Some(position) => self
+                    .records(position, 1)?
+                    .first()
+                    .and_then(|item| self.labels.get(item.address, item.typ))
+                    .map(str::to_string),
+                None => None,
+            };
+            labels.push(label);
+        }
+        Ok(labels)
+    }
+
+    /// 地址和类型对应的标签
+    pub fn label(&self, address: u64, value_type: ValueType) -> Option<&str> {
+        self.labels.get(address, value_type)
+    }
+
+    /// 丢弃所有标签（用户清空结果、开始新的搜索）
+    pub fn clear_labels(&mut self) {
+        self.labels.clear();
+    }
+
+    /// 改善搜索替换结果之前调用：当前结果集整体移入撤销槽（磁盘文件改名，不复制），之前的撤销槽丢弃
+    pub fn stash_for_undo(&mut self) -> Result<()> {
+        self.undo = None;
+        let results = match self.current_mode {
+            SearchResultMode::Exact => StashedResults::Exact(self.exact.stash()?),
+            SearchResultMode::Fuzzy => StashedResults::Fuzzy(self.fuzzy.stash()?),
+        };
+        self.undo = Some(PreviousResults {
+            results,
+            byte_hits: self.byte_hits.take(),
+            exact_values: std::mem::take(&mut self.exact_values),
+            labels: self.labels.clone(),
+            current_pass: self.current_pass,
+        });
+        self.handles.clear();
+        self.seal()
+    }
+
+    /// 就地细化之前调用：返回旧值日志，细化写回每一段之前先把该段记入日志，当前结果和撤销槽不变
+    pub fn begin_refine_journal(&self) -> Result<RefineJournal> {
+        if self.current_mode != SearchResultMode::Fuzzy {
+            return Err(anyhow!("Not in fuzzy mode"));
+        }
+        Ok(self.fuzzy.begin_journal())
+    }
+
+    /// 就地细化遍历了整个结果集：日志记下的旧结果集成为撤销槽，之前的撤销槽丢弃
+    pub fn commit_refine_journal(&mut self, journal: RefineJournal) -> Result<()> {
+        self.undo = None;
+        self.undo = Some(PreviousResults {
+            results: StashedResults::Fuzzy(journal.into_stashed()?),
+            byte_hits: None,
+            exact_values: ExactValueCache::default(),
+            labels: self.labels.clone(),
+            current_pass: self.current_pass,
+        });
+        Ok(())
+    }
+
+    /// 就地细化被取消：把已经写回的段改回日志中的旧值，结果集和撤销槽与细化之前相同
+    pub fn rollback_refine_journal(&mut self, journal: RefineJournal) -> Result<()> {
+        if self.current_mode != SearchResultMode::Fuzzy {
+            return Err(anyhow!("Not in fuzzy mode"));
+        }
+        let restored = journal.recorded();
+        self.revision += 1;
+        journal.replay(|base, items| {
+            for (i, &item) in items.iter().enumerate() {
+                self.fuzzy.update_result(base + i, item)?;
+            }
+            Ok(())
+        })?;
+        debug!("Rolled back {} refined fuzzy results", restored);
+        Ok(())
+    }
+
+    /// 是否有可以撤销的改善搜索
+    pub fn can_undo(&self) -> bool {
+        self.undo.is_some()
+    }
+
+    /// 丢弃撤销槽并删除它的文件（开始新的搜索、导入、平移地址）
+    pub fn discard_undo(&mut self) {
+        if self.undo.take().is_some() {
+            debug!("Discarded refine undo slot");
+        }
+    }
+
+    /// 换回最近一次改善搜索之前的结果集，返回恢复后的结果数量
+    ///
+    /// 改善之后对结果的删除 / 保留一起撤销；历史代不回退。
+    pub fn undo_refine(&mut self) -> Result<usize> {
+        let previous = self.undo.take().ok_or_else(|| anyhow!("No refine to undo"))?;
+        let mode = previous.results.mode();
+        // 改善可能切换了模式，旧模式的存储随之清理
+        self.set_mode(mode)?;
+        self.handles.clear();
+        match previous.results {
+            StashedResults::Exact(stashed) => self.exact.restore(stashed)?,
+            StashedResults::Fuzzy(stashed) => self.fuzzy.restore(stashed)?,
+        }
+        self.byte_hits = previous.byte_hits;
+        self.exact_values = previous.exact_values;
+        self.labels = previous.labels;
+        self.current_pass = previous.current_pass;
+        self.seal()?;
+
+        let total = self.total_count();
+        info!("Undid last refine: restored {} {:?} results", total, mode);
+        Ok(total)
+    }
+
+    /// 校验当前模式的结果文件
+    pub fn verify_integrity(&self) -> ResultStoreReport {
+        let (store, recovery) = match self.current_mode {
+            SearchResultMode::Exact => (self.exact.verify_integrity(), self.exact.recovery_report()),
+            SearchResultMode::Fuzzy => (self.fuzzy.verify_integrity(), self.fuzzy.recovery_report()),
+        };
+        ResultStoreReport {
+            mode: format!("{:?}", self.current_mode),
+            store,
+            recovery: recovery.cloned(),
         }
     }
 
     pub fn set_mode(&mut self, mode: SearchResultMode) -> Result<()> {
         if mode != self.current_mode {
+            self.byte_hits = None;
+            self.handles.clear();
             // 清理旧模式的磁盘资源
             match self.current_mode {
                 SearchResultMode::Exact => {
@@ -84,14 +375,18 @@ impl SearchResultManager {
                     if let Err(e) = self.fuzzy.clear_disk() {
                         error!("clear_disk failed for fuzzy: {:?}", e);
                     }
+                    self.generations.clear();
                 },
             }
         }
         self.current_mode = mode;
+        self.revision += 1;
         Ok(())
     }
 
     pub fn add_result(&mut self, item: SearchResultItem) -> Result<()> {
+        self.materialize_byte_hits()?;
+        self.revision += 1;
         match (self.current_mode, item) {
             (SearchResultMode::Exact, SearchResultItem::Exact(exact_item)) => {
                 self.exact.add_result(exact_item)
@@ -107,7 +402,7 @@ impl SearchResultManager {
         for result in results {
             self.add_result(result)?;
         }
-        Ok(())
+        self.seal()
     }
 
     /// 添加模糊搜索结果（直接使用 FuzzySearchResultItem）
@@ -115,6 +410,7 @@ impl SearchResultManager {
         if self.current_mode != SearchResultMode::Fuzzy {
             return Err(anyhow!("Not in fuzzy mode"));
         }
+        self.revision += 1;
         self.fuzzy.add_result(item)
     }
 
@@ -126,10 +422,13 @@ impl SearchResultManager {
         for item in results {
             self.fuzzy.add_result(item)?;
         }
-        Ok(())
+        self.seal()
     }
 
     pub fn get_results(&self, start: usize, size: usize) -> Result<Vec<SearchResultItem>> {
+        if let Some(ref hits) = self.byte_hits {
+            return Ok(hits.results(start, size).into_iter().map(SearchResultItem::Exact).collect());
+        }
         match self.current_mode {
             SearchResultMode::Exact => {
                 let exact_results = self.exact.get_results(start, size)?;
@@ -142,7 +441,20 @@ impl SearchResultManager {
         }
     }
 
+    /// 地址在 [start_addr, end_addr) 内的结果，按地址升序，最多 max_count 条，见 `address_range`
+    pub fn get_results_in_range(&self, start_addr: u64, end_addr: u64, max_count: usize) -> Result<AddressRangeResults> {
+        address_range::results_in_range(self, &self.ascending_runs, start_addr, end_addr, max_count)
+    }
+
+    /// 第一个地址不小于 addr 的结果的索引，地址不在结果集中时就是它的插入位置，见 `address_range`
+    pub fn find_index_by_address(&self, addr: u64) -> Result<usize> {
+        address_range::find_index_by_address(self, &self.ascending_runs, addr)
+    }
+
     pub fn total_count(&self) -> usize {
+        if let Some(ref hits) = self.byte_hits {
+            return hits.len();
+        }
         match self.current_mode {
             SearchResultMode::Exact => self.exact.total_count(),
             SearchResultMode::Fuzzy => self.fuzzy.total_count(),
@@ -150,37 +462,219 @@ impl SearchResultManager {
     }
 
     pub fn remove_result(&mut self, index: usize) -> Result<()> {
+        if index < self.total_count() {
+            self.handles.remove_positions(&[index]);
+        }
+        if let Some(ref mut hits) = self.byte_hits {
+            hits.remove_indices(vec![index]);
+            return self.seal();
+        }
         match self.current_mode {
-            SearchResultMode::Exact => self.exact.remove_result(index),
-            SearchResultMode::Fuzzy => self.fuzzy.remove_result(index),
+            SearchResultMode::Exact => self.exact.remove_result(index)?,
+            SearchResultMode::Fuzzy => self.fuzzy.remove_result(index)?,
         }
+        self.seal()
     }
 
     pub fn remove_results_batch(&mut self, indices: Vec<usize>) -> Result<()> {
+        let indices = self.valid_positions(indices);
+        self.handles.remove_positions(&indices);
+        if let Some(ref mut hits) = self.byte_hits {
+            hits.remove_indices(indices);
+            return self.seal();
+        }
         match self.current_mode {
-            SearchResultMode::Exact => self.exact.remove_results_batch(indices),
-            SearchResultMode::Fuzzy => self.fuzzy.remove_results_batch(indices),
+            SearchResultMode::Exact => self.exact.remove_results_batch(indices)?,
+            SearchResultMode::Fuzzy => self.fuzzy.remove_results_batch(indices)?,
         }
+        self.seal()
     }
 
     pub fn keep_only_results(&mut self, keep_indices: Vec<usize>) -> Result<()> {
+        let keep_indices = self.valid_positions(keep_indices);
+        self.handles.keep_positions(&keep_indices);
+        if let Some(ref mut hits) = self.byte_hits {
+            *hits = hits.keep_indices(keep_indices);
+            return self.seal();
+        }
         match self.current_mode {
-            SearchResultMode::Exact => self.exact.keep_only_results(keep_indices),
-            SearchResultMode::Fuzzy => self.fuzzy.keep_only_results(keep_indices),
+            SearchResultMode::Exact => self.exact.keep_only_results(keep_indices)?,
+            SearchResultMode::Fuzzy => self.fuzzy.keep_only_results(keep_indices)?,
+        }
+        self.seal()
+    }
+
+    /// 排序、去重并去掉越界的位置
+    fn valid_positions(&self, mut positions: Vec<usize>) -> Vec<usize> {
+        let total = self.total_count();
+        positions.sort_unstable();
+        positions.dedup();
+        positions.retain(|&position| position < total);
+        positions
+    }
+
+    /// 位置上结果的句柄，返回给 UI 代替位置
+    pub fn handle_at(&self, index: usize) -> Option<u64> {
+        self.handles.handle_at(index)
+    }
+
+    /// 句柄对应结果的当前位置，结果已被删除时返回 None
+    pub fn position_of_handle(&self, handle: u64) -> Option<usize> {
+        self.handles.position_of(handle)
+    }
+
+    /// 按句柄删除结果，已经不存在的句柄忽略，返回删除的数量
+    pub fn remove_by_handles(&mut self, handles: Vec<u64>) -> Result<usize> {
+        let indices: Vec<usize> = handles.into_iter().filter_map(|handle| self.handles.position_of(handle)).collect();
+        let indices = self.valid_positions(indices);
+        let removed = indices.len();
+        if removed > 0 {
+            self.remove_results_batch(indices)?;
         }
+        Ok(removed)
+    }
+
+    /// 只保留句柄对应的结果，返回保留的数量
+    ///
+    /// 句柄都已失效（结果集在选择之后被整体替换）时返回错误而不是清空结果。
+    pub fn keep_only_handles(&mut self, handles: Vec<u64>) -> Result<usize> {
+        let requested = handles.len();
+        let indices: Vec<usize> = handles.into_iter().filter_map(|handle| self.handles.position_of(handle)).collect();
+        if indices.is_empty() && requested > 0 {
+            return Err(anyhow!("None of the {} selected results exist anymore", requested));
+        }
+        let indices = self.valid_positions(indices);
+        let kept = indices.len();
+        self.keep_only_results(indices)?;
+        Ok(kept)
     }
 
     pub fn get_mode(&self) -> SearchResultMode {
         self.current_mode
     }
 
+    /// 扫描全部结果，返回满足过滤条件的存储索引（升序）
+    ///
+    /// 结果集变化后索引失效，调用方按 `revision` 判断是否需要重建。
+    pub fn build_filtered_index(&self, filter: &SearchFilter) -> Result<Vec<u32>> {
+        const BATCH: usize = 64 * 1024;
+
+        let total = self.total_count();
+        let mut index = Vec::new();
+        let mut offset = 0;
+        while offset < total {
+            let batch = self.get_results(offset, BATCH)?;
+            if batch.is_empty() {
+                break;
+            }
+            index.extend(batch.iter().enumerate().filter(|(_, item)| filter.matches(item)).map(|(i, _)| (offset + i) as u32));
+            offset += batch.len();
+        }
+        Ok(index)
+    }
+
+    /// 结果集版本号，任何增删改都会改变
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// 开始一次可能新增结果的操作（首次扫描、保留结果的扫描、手动添加），返回本次新增结果的轮次
+    ///
+    /// 结果集为空时从 0 重新计数，所以首次扫描的结果轮次总是 0。细化搜索不调用，
+    /// 幸存的结果保留原来的轮次。超过 255 次后停在 255。
+    pub fn begin_pass(&mut self) -> u8 {
+        self.current_pass = if self.total_count() == 0 {
+            0
+        } else {
+            self.current_pass.saturating_add(1)
+        };
+        self.current_pass
+    }
+
+    /// 当前操作新增的结果应标记的轮次，异步扫描在写入时读取
+    pub fn current_pass(&self) -> u8 {
+        self.current_pass
+    }
+
+    /// 保存扫描时读取的精确结果的值，替换之前的缓存
+    pub fn set_exact_values(&mut self, cache: ExactValueCache) {
+        self.exact_values = cache;
+    }
+
+    /// 扫描时读取的精确结果的值，清空结果后为空
+    pub fn exact_values(&self) -> &ExactValueCache {
+        &self.exact_values
+    }
+
+    /// 精确结果匹配时（或最近一次刷新时）的值，没有缓存时返回 None
+    pub fn exact_value(&self, addr: u64) -> Option<&[u8]> {
+        self.exact_values.get(addr)
+    }
+
+    /// 用重新读取的当前值更新缓存，地址没有缓存时返回 false
+    pub fn update_exact_value(&mut self, addr: u64, bytes: &[u8]) -> bool {
+        self.exact_values.update(addr, bytes)
+    }
+
+    /// 读取 [start, start + size) 的结果，模糊结果只取地址、类型和轮次
+    pub fn records(&self, start: usize, size: usize) -> Result<Vec<ExactSearchResultItem>> {
+        if let Some(ref hits) = self.byte_hits {
+            return Ok(hits.results(start, size));
+        }
+        match self.current_mode {
+            SearchResultMode::Exact => self.exact.get_results(start, size),
+            SearchResultMode::Fuzzy => Ok(self
+                .fuzzy
+                .get_results(start, size)?
+                .into_iter()
+                .map(|fuzzy| ExactSearchResultItem::new(fuzzy.address, fuzzy.value_type).with_pass(fuzzy.pass))
+                .collect()),
+        }
+    }
+
+    /// 按地址顺序分批读取当前结果的游标，见 `ResultCursor`
+    pub fn cursor(&self, batch_size: usize) -> Result<ResultCursor> {
+        ResultCursor::new(self, batch_size)
+    }
+
     pub fn get_all_exact_results(&self) -> Result<Vec<ExactSearchResultItem>> {
+        if let Some(ref hits) = self.byte_hits {
+            return Ok(hits.results(0, hits.len()));
+        }
         match self.current_mode {
             SearchResultMode::Exact => self.exact.get_all_results(),
             SearchResultMode::Fuzzy => Err(anyhow!("Cannot get exact results in fuzzy mode")),
         }
     }
 
+    /// 用按页存储的 Byte 结果替换当前结果集，切换到精确模式
+    pub fn set_byte_hits(&mut self, hits: ByteHitSet) -> Result<()> {
+        self.clear()?;
+        self.set_mode(SearchResultMode::Exact)?;
+        self.byte_hits = Some(hits);
+        self.seal()
+    }
+
+    pub fn byte_hits(&self) -> Option<&ByteHitSet> {
+        self.byte_hits.as_ref()
+    }
+
+    /// 把按页存储的 Byte 结果展开为普通精确结果，之后可以混入其他类型的结果
+    pub fn materialize_byte_hits(&mut self) -> Result<()> {
+        let Some(hits) = self.byte_hits.take() else {
+            return Ok(());
+        };
+        const BATCH: usize = 1024 * 1024;
+        let mut start = 0;
+        while start < hits.len() {
+            for item in hits.results(start, BATCH) {
+                self.exact.add_result(item)?;
+            }
+            start += BATCH;
+        }
+        self.seal()
+    }
+
     /// 获取所有模糊搜索结果
     pub fn get_all_fuzzy_results(&self) -> Result<Vec<FuzzySearchResultItem>> {
         match self.current_mode {
@@ -189,11 +683,319 @@ impl SearchResultManager {
         }
     }
 
+    /// 读取一段模糊搜索结果（就地细化按段处理时使用）
+    pub fn get_fuzzy_results(&self, start: usize, size: usize) -> Result<Vec<FuzzySearchResultItem>> {
+        match self.current_mode {
+            SearchResultMode::Exact => Err(anyhow!("Cannot get fuzzy results in exact mode")),
+            SearchResultMode::Fuzzy => self.fuzzy.get_results(start, size),
+        }
+    }
+
+    /// 就地写回细化后存活项的新值，结果数量不变；删除的项之后用 `remove_results_batch` 一次性压缩
+    pub fn update_fuzzy_results(&mut self, updates: &[(usize, FuzzySearchResultItem)]) -> Result<()> {
+        if self.current_mode != SearchResultMode::Fuzzy {
+            return Err(anyhow!("Not in fuzzy mode"));
+        }
+        self.revision += 1;
+        for &(index, item) in updates {
+            self.fuzzy.update_result(index, item)?;
+        }
+        Ok(())
+    }
+
     /// 批量替换所有模糊搜索结果（用于细化搜索后）
     pub fn replace_all_fuzzy_results(&mut self, results: Vec<FuzzySearchResultItem>) -> Result<()> {
         if self.current_mode != SearchResultMode::Fuzzy {
             return Err(anyhow!("Not in fuzzy mode"));
         }
-        self.fuzzy.replace_all(results)
+        self.fuzzy.replace_all(results)?;
+        // 幸存结果的位置未知，整体重新分配句柄
+        self.handles.clear();
+        self.seal()
+    }
+
+    /// 把模糊结果整理为按地址严格升序并去掉重复地址，返回去掉的结果数
+    ///
+    /// 位置改变，句柄整体重新分配；之后按地址查找直接在整个存储上二分，见 `is_address_sorted`。
+    pub fn sort_fuzzy_by_address(&mut self) -> Result<usize> {
+        if self.current_mode != SearchResultMode::Fuzzy {
+            return Err(anyhow!("Not in fuzzy mode"));
+        }
+        if self.fuzzy.is_address_sorted() {
+            return Ok(0);
+        }
+        let dropped = self.fuzzy.sort_by_address()?;
+        self.handles.clear();
+        self.seal()?;
+        Ok(dropped)
+    }
+
+    /// 模糊结果是变长值（特征码、文本）时的字节数
+    pub fn fuzzy_pattern_len(&self) -> Option<usize> {
+        self.fuzzy.pattern_len()
+    }
+
+    pub fn set_fuzzy_pattern_len(&mut self, pattern_len: Option<usize>) {
+        self.fuzzy.set_pattern_len(pattern_len);
+    }
+
+    /// 当前结果按地址严格升序存储（目前只有整理过的模糊结果）
+    pub fn is_address_sorted(&self) -> bool {
+        self.byte_hits.is_none() && self.current_mode == SearchResultMode::Fuzzy && self.fuzzy.is_address_sorted()
+    }
+
+    /// 将当前模糊结果集记录为新的一代
+    /// 结果集过大时不记录，返回 None
+    ///
+    /// 一次写完整个快照；引擎在锁外分段写入，见 `begin_fuzzy_generation`。
+    pub fn record_fuzzy_generation(&mut self, condition: String) -> Result<Option<u32>> {
+        let Some(mut pending) = self.begin_fuzzy_generation(condition)? else {
+            return Ok(None);
+        };
+        loop {
+            let batch = self.fuzzy_generation_batch(&pending)?;
+            if batch.is_empty() {
+                break;
+            }
+            pending.write_all(&batch)?;
+        }
+        self.commit_fuzzy_generation(pending).map(Some)
+    }
+
+    /// 开始把当前模糊结果集记录为新的一代，结果集过大时返回 None
+    ///
+    /// 之后用 `fuzzy_generation_batch` 分段读取、`PendingGeneration::write_all` 写入（不需要持有管理器），
+    /// 最后 `commit_fuzzy_generation` 登记。期间结果集被修改时放弃这一代。
+    pub(crate) fn begin_fuzzy_generation(&self, condition: String) -> Result<Option<PendingGeneration>> {
+        if self.current_mode != SearchResultMode::Fuzzy {
+            return Err(anyhow!("Not in fuzzy mode"));
+        }
+
+        let total = self.fuzzy.total_count();
+        if total > MAX_GENERATION_ITEMS {
+            info!("Skip recording generation: {} items exceeds limit {}", total, MAX_GENERATION_ITEMS);
+            return Ok(None);
+        }
+        self.generations.begin(condition, self.revision, total).map(Some)
+    }
+
+    /// 正在记录的一代的下一段，写完时返回空
+    pub(crate) fn fuzzy_generation_batch(&self, pending: &PendingGeneration) -> Result<Vec<FuzzySearchResultItem>> {
+        const BATCH: usize = 1024 * 1024;
+        if self.revision != pending.revision || self.current_mode != SearchResultMode::Fuzzy {
+            return Err(anyhow!("Fuzzy results changed while recording generation"));
+        }
+        if pending.written() >= pending.total {
+            return Ok(Vec::new());
+        }
+        self.fuzzy.get_results(pending.written(), BATCH.min(pending.total - pending.written()))
+    }
+
+    /// 登记写完的一代
+    pub(crate) fn commit_fuzzy_generation(&mut self, pending: PendingGeneration) -> Result<u32> {
+        if self.revision != pending.revision || pending.written() != pending.total {
+            return Err(anyhow!("Fuzzy results changed while recording generation"));
+        }
+        self.generations.commit(pending)
+    }
+
+    /// 列出所有历史代
+    pub fn list_generations(&self) -> &[ResultGeneration] {
+        self.generations.list()
+    }
+
+    /// 读取指定代的结果集快照
+    pub fn load_generation(&self, id: u32) -> Result<Vec<FuzzySearchResultItem>> {
+        self.generations.load(id)
+    }
+
+    /// 把当前结果集导出到文件，先写临时文件再改名，失败时不会留下半个文件
+    /// 模糊结果直接从存储分段写出，不在内存中复制整个结果集
+    pub fn export_to_file(&self, path: &Path, pattern_len: Option<usize>) -> Result<usize> {
+        let header = ResultFileHeader {
+            mode: self.current_mode,
+            pattern_len,
+            count: self.total_count(),
+        };
+        let tmp_path = path.with_extension("part");
+        let mut writer = ResultFileWriter::create(&tmp_path, header, &self.labels.entries())?;
+
+        let written = match self.current_mode {
+            SearchResultMode::Exact => self.for_each_exact_chunk(RESULT_FILE_BATCH, |chunk| writer.write_exact(chunk)),
+            SearchResultMode::Fuzzy => self.fuzzy.for_each_chunk(RESULT_FILE_BATCH, |chunk| writer.write_fuzzy(chunk)),
+        }
+        .and_then(|_| writer.finish());
+
+        match written {
+            Ok(count) => {
+                std::fs::rename(&tmp_path, path).map_err(|e| anyhow!("Failed to move result file to {:?}: {}", path, e))?;
+                info!("Exported {} {:?} results to {:?}", count, header.mode, path);
+                Ok(count)
+            },
+            Err(e) => {
+                let _ = std::fs::remove_file(&tmp_path);
+                Err(e)
+            },
+        }
+    }
+
+    /// 把当前结果集导出为 CSV 或 JSON Lines，返回写出的行数。同样先写临时文件再改名
+    /// 结果分批从内存缓冲区和磁盘存储中读出并格式化，不在内存中复制整个结果集
+    pub fn export(&self, path: &Path, format: ResultExportFormat, xor_key: XorKey) -> Result<usize> {
+        let tmp_path = path.with_extension("part");
+        let fuzzy = self.current_mode == SearchResultMode::Fuzzy;
+        let mut writer = TextExportWriter::create(&tmp_path, format, fuzzy, self.fuzzy_pattern_len(), xor_key, self.total_count())?;
+
+        let written = match self.current_mode {
+            SearchResultMode::Exact => self.for_each_exact_chunk(TEXT_EXPORT_BATCH, |chunk| writer.write_exact(chunk)),
+            SearchResultMode::Fuzzy => self.fuzzy.for_each_chunk(TEXT_EXPORT_BATCH, |chunk| writer.write_fuzzy(chunk)),
+        }
+        .and_then(|_| writer.finish());
+
+        match written {
+            Ok(count) => {
+                std::fs::rename(&tmp_path, path).map_err(|e| anyhow!("Failed to move export file to {:?}: {}", path, e))?;
+                info!("Exported {} {:?} results as {:?} to {:?}", count, self.current_mode, format, path);
+                Ok(count)
+            },
+            Err(e) => {
+                let _ = std::fs::remove_file(&tmp_path);
+                Err(e)
+            },
+        }
+    }
+
+    /// 分批访问精确结果，按页存储的 Byte 结果也按普通精确结果给出
+    fn for_each_exact_chunk<F>(&self, chunk_size: usize, mut visit: F) -> Result<()>
+    where
+        F: FnMut(&[ExactSearchResultItem]) -> Result<()>,
+    {
+        let total = self.total_count();
+        let mut offset = 0;
+        while offset < total {
+            let batch = match self.byte_hits {
+                Some(ref hits) => hits.results(offset, chunk_size),
+                None => self.exact.get_results(offset, chunk_size)?,
+            };
+            if batch.is_empty() {
+                break;
+            }
+            visit(&batch)?;
+            offset += batch.len();
+        }
+        Ok(())
+    }
+
+    /// 从导出文件载入结果集，替换当前结果并切换到文件中的模式
+    /// 头部或长度不对时不改动当前结果；记录损坏时清空已载入的部分并返回错误
+    pub fn import_from_file(&mut self, path: &Path) -> Result<ResultFileHeader> {
+        let mut reader = ResultFileReader::open(path)?;
+        let header = reader.header();
+
+        self.discard_undo();
+        self.clear()?;
+        self.clear_labels();
+        self.set_mode(header.mode)?;
+
+        let max_pass = match self.read_records(&mut reader) {
+            Ok(max_pass) => max_pass,
+            Err(e) => {
+                warn!("Failed to import results from {:?}: {:?}", path, e);
+                self.clear()?;
+                return Err(e);
+            },
+        };
+
+        // 之后新增的结果接着文件中最后一轮往后数
+        self.current_pass = max_pass;
+        for (address, value_type, label) in reader.take_labels() {
+            if let Err(e) = self.labels.set(address, value_type, label) {
+                warn!("Skipping label of 0x{:X} in {:?}: {:?}", address, path, e);
+            }
+        }
+        self.seal()?;
+        info!("Imported {} {:?} results from {:?}", header.count, header.mode, path);
+        Ok(header)
+    }
+
+    /// 把落在旧模块范围内的结果地址平移到新基址，返回平移的结果数
+    ///
+    /// 平移后按地址重新排序写回，轮次和模糊结果的值保留；地址变了，历史代和缓存的值一起丢弃。
+    pub fn rebase(&mut self, rebase: &AddressRebase) -> Result<usize> {
+        let moved = match self.current_mode {
+            SearchResultMode::Exact => {
+                let mut results = self.get_all_exact_results()?;
+                let mut moved = 0;
+                for item in results.iter_mut() {
+                    if let Some(rebased) = rebase.apply(item.address) {
+                        item.address = rebased;
+                        moved += 1;
+                    }
+                }
+                if moved > 0 {
+                    results.sort_by_key(|item| item.address);
+                    self.clear()?;
+                    for item in results {
+                        self.exact.add_result(item)?;
+                    }
+                }
+                moved
+            },
+            SearchResultMode::Fuzzy => {
+                let mut results = self.fuzzy.get_all_results()?;
+                let mut moved = 0;
+                for item in results.iter_mut() {
+                    // 模糊结果是 packed 结构，按值读写字段
+                    let address = item.address;
+                    if let Some(rebased) = rebase.apply(address) {
+                        item.address = rebased;
+                        moved += 1;
+                    }
+                }
+                if moved > 0 {
+                    results.sort_by_key(|item| item.address);
+                    let pattern_len = self.fuzzy.pattern_len();
+                    self.clear()?;
+                    self.fuzzy.replace_all(results)?;
+                    self.fuzzy.set_pattern_len(pattern_len);
+                }
+                moved
+            },
+        };
+        if moved > 0 {
+            self.discard_undo();
+            self.labels.rebase(rebase);
+            self.seal()?;
+            info!("Rebased {} results from 0x{:X} to 0x{:X}", moved, rebase.old_base, rebase.new_base);
+        }
+        Ok(moved)
+    }
+
+    /// 分批读取记录加入当前模式的结果集，返回最大的轮次
+    fn read_records(&mut self, reader: &mut ResultFileReader) -> Result<u8> {
+        let mut max_pass = 0u8;
+        loop {
+            let loaded = match self.current_mode {
+                SearchResultMode::Exact => {
+                    let batch = reader.read_exact_batch(RESULT_FILE_BATCH)?;
+                    for item in &batch {
+                        max_pass = max_pass.max(item.pass);
+                        self.exact.add_result(*item)?;
+                    }
+                    batch.len()
+                },
+                SearchResultMode::Fuzzy => {
+                    let batch = reader.read_fuzzy_batch(RESULT_FILE_BATCH)?;
+                    for item in &batch {
+                        max_pass = max_pass.max(item.pass);
+                        self.fuzzy.add_result(*item)?;
+                    }
+                    batch.len()
+                },
+            };
+            if loaded == 0 {
+                return Ok(max_pass);
+            }
+        }
     }
 }
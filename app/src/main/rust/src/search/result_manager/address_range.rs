@@ -0,0 +1,196 @@
+//! Address range lookup
+//!
+//! 内存编辑器翻到一段地址时需要知道其中哪些地址在结果集里。结果存储由若干按地址升序的段组成
+//! （见 `cursor::ascending_runs`，一次扫描写入一段，跨越内存缓冲区和磁盘 mmap），
+//! 在每段内二分查找起始地址，再从那里顺序读取到结束地址为止，不需要遍历全部结果。
+//!
+//! 跳转到地址时同样在每段内二分查找，得到该地址在结果存储中的位置。
+//!
+//! 升序段按结果集版本缓存；段数过多时（大量零散的手动添加）退化为分批顺序扫描。
+
+use crate::search::SearchResultItem;
+use crate::search::result_manager::SearchResultManager;
+use crate::search::result_manager::cursor::ascending_runs;
+use anyhow::{Result, anyhow};
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+
+/// 查找升序段和顺序扫描时每次读取的结果数
+const SCAN_BATCH: usize = 4096;
+
+/// 一段地址内的结果
+pub struct AddressRangeResults {
+    /// (结果存储中的索引, 结果)，按地址升序，最多 max_count 条
+    pub items: Vec<(usize, SearchResultItem)>,
+    /// 范围内的结果多于 max_count，只返回了地址最小的部分
+    pub truncated: bool,
+}
+
+/// 存储中的升序段，段数过多时为 None
+type Runs = Arc<Option<Vec<Range<usize>>>>;
+
+/// 按结果集版本缓存的升序段
+#[derive(Default)]
+pub(crate) struct AscendingRunCache {
+    cached: Mutex<Option<(u64, Runs)>>,
+}
+
+impl AscendingRunCache {
+    fn get_or_build(&self, store: &SearchResultManager) -> Result<Runs> {
+        let mut cached = self.cached.lock().map_err(|_| anyhow!("Failed to acquire ascending run cache lock"))?;
+        if let Some((revision, runs)) = cached.as_ref()
+            && *revision == store.revision()
+        {
+            return Ok(Arc::clone(runs));
+        }
+
+        let runs = Arc::new(ascending_runs(store, SCAN_BATCH)?);
+        *cached = Some((store.revision(), Arc::clone(&runs)));
+        Ok(runs)
+    }
+}
+
+fn item_address(item: &SearchResultItem) -> u64 {
+    match item {
+        SearchResultItem::Exact(exact) => exact.address,
+        SearchResultItem::Fuzzy(fuzzy) => fuzzy.address,
+    }
+}
+
+/// 存储中 [start_addr, end_addr) 内的结果，最多 max_count 条
+pub(crate) fn results_in_range(
+    store: &SearchResultManager,
+    runs: &AscendingRunCache,
+    start_addr: u64,
+    end_addr: u64,
+    max_count: usize,
+) -> Result<AddressRangeResults> {
+    if start_addr >= end_addr {
+        return Ok(AddressRangeResults { items: Vec::new(), truncated: false });
+    }
+
+    // 每段最多取 max_count + 1 条，合并后多出来的部分说明被截断
+    let limit = max_count.saturating_add(1);
+    let mut items = Vec::new();
+    match runs.get_or_build(store)?.as_ref() {
+        Some(runs) => {
+            for run in runs {
+                let first = lower_bound(store, run.clone(), start_addr)?;
+                collect_from(store, first..run.end, end_addr, limit, &mut items)?;
+            }
+        },
+        None => {
+            let mut pos = 0;
+            let total = store.total_count();
+            while pos < total {
+                let batch = store.get_results(pos, SCAN_BATCH)?;
+                if batch.is_empty() {
+                    break;
+                }
+                let len = batch.len();
+                items.extend(
+                    batch
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, item)| (pos + i, item))
+                        .filter(|(_, item)| (start_addr..end_addr).contains(&item_address(item))),
+                );
+                pos += len;
+                if items.len() > limit.saturating_mul(2) {
+                    items.sort_by_key(|(index, item)| (item_address(item), *index));
+                    items.truncate(limit);
+                }
+            }
+        },
+    }
+
+    items.sort_by_key(|(index, item)| (item_address(item), *index));
+    let truncated = items.len() > max_count;
+    items.truncate(max_count);
+    Ok(AddressRangeResults { items, truncated })
+}
+
+/// 第一个地址不小于 addr 的结果在存储中的索引，没有时为结果总数
+///
+/// 结果按地址排序（只有一个升序段）时就是 addr 的插入位置；有多个升序段时取各段候选中地址最小的，
+/// 地址相同时取索引小的。
+pub(crate) fn find_index_by_address(store: &SearchResultManager, runs: &AscendingRunCache, addr: u64) -> Result<usize> {
+    // (地址, 索引)
+    let mut best: Option<(u64, usize)> = None;
+    let mut consider = |address: u64, index: usize| {
+        if address >= addr && best.is_none_or(|best| (address, index) < best) {
+            best = Some((address, index));
+        }
+    };
+    match runs.get_or_build(store)?.as_ref() {
+        Some(runs) => {
+            for run in runs {
+                let index = lower_bound(store, run.clone(), addr)?;
+                if index < run.end
+                    && let Some(record) = store.records(index, 1)?.first()
+                {
+                    consider(record.address, index);
+                }
+            }
+        },
+        None => {
+            let mut pos = 0;
+            let total = store.total_count();
+            while pos < total {
+                let records = store.records(pos, SCAN_BATCH)?;
+                if records.is_empty() {
+                    break;
+                }
+                for (i, record) in records.iter().enumerate() {
+                    consider(record.address, pos + i);
+                }
+                pos += records.len();
+            }
+        },
+    }
+    Ok(best.map_or(store.total_count(), |(_, index)| index))
+}
+
+/// 升序段内第一个地址不小于 addr 的索引
+fn lower_bound(store: &SearchResultManager, run: Range<usize>, addr: u64) -> Result<usize> {
+    let (mut low, mut high) = (run.start, run.end);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        let record = store.records(mid, 1)?;
+        let mid_addr = record.first().ok_or_else(|| anyhow!("Result store ended at {} before the expected {}", mid, run.end))?.address;
+        if mid_addr < addr {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    Ok(low)
+}
+
+/// 从升序段的 positions.start 开始顺序读取地址小于 end_addr 的结果，本段最多 limit 条
+fn collect_from(
+    store: &SearchResultManager,
+    positions: Range<usize>,
+    end_addr: u64,
+    limit: usize,
+    items: &mut Vec<(usize, SearchResultItem)>,
+) -> Result<()> {
+    let mut pos = positions.start;
+    let mut taken = 0;
+    while pos < positions.end && taken < limit {
+        let size = (limit - taken).min(SCAN_BATCH).min(positions.end - pos);
+        let batch = store.get_results(pos, size)?;
+        if batch.is_empty() {
+            break;
+        }
+        for item in batch {
+            if item_address(&item) >= end_addr {
+                return Ok(());
+            }
+            items.push((pos, item));
+            pos += 1;
+            taken += 1;
+        }
+    }
+    Ok(())
+}
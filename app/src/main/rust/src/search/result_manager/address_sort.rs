@@ -0,0 +1,70 @@
+//! Address-sorted compaction
+//!
+//! 模糊首次扫描按区域写入结果，每个区域内按地址升序，但区域的顺序由调用方决定，区域之间还可能重叠，
+//! 地址相近的结果散落在存储各处，同一地址也可能出现多次。扫描完成后把存储中的升序段多路归并成一段：
+//! 堆里只放每段的下一个地址，结果逐个交给写出端，内存占用只与段数有关，与结果数无关。
+//! 相同地址只保留存储中最靠前的一项。
+
+use anyhow::Result;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::ops::Range;
+
+/// 按地址不降的段（相邻段首尾相接，覆盖 [0, len)），以及段内是否有相同地址相邻
+pub(crate) fn address_runs(len: usize, address_at: impl Fn(usize) -> u64) -> (Vec<Range<usize>>, bool) {
+    let mut runs = Vec::new();
+    let mut has_duplicates = false;
+    let mut run_start = 0;
+    let mut last = None;
+    for index in 0..len {
+        let address = address_at(index);
+        match last {
+            Some(last) if address < last => {
+                runs.push(run_start..index);
+                run_start = index;
+            },
+            Some(last) if address == last => has_duplicates = true,
+            _ => {},
+        }
+        last = Some(address);
+    }
+    if len > run_start {
+        runs.push(run_start..len);
+    }
+    (runs, has_duplicates)
+}
+
+/// 多路归并升序段，按地址严格升序把项交给 `emit`，返回因地址重复丢弃的项数
+///
+/// 地址相同时段号小的先出堆，同一段内靠前的先出堆，所以保留的总是存储中最靠前的一项。
+pub(crate) fn merge_runs<T, I, E>(runs: &[Range<usize>], item_at: I, address_of: impl Fn(&T) -> u64, mut emit: E) -> Result<usize>
+where
+    I: Fn(usize) -> T,
+    E: FnMut(T) -> Result<()>,
+{
+    let mut next: Vec<usize> = runs.iter().map(|run| run.start).collect();
+    let mut heap = BinaryHeap::with_capacity(runs.len());
+    for (idx, run) in runs.iter().enumerate() {
+        if !run.is_empty() {
+            heap.push(Reverse((address_of(&item_at(run.start)), idx)));
+        }
+    }
+
+    let mut last = None;
+    let mut dropped = 0;
+    while let Some(Reverse((address, idx))) = heap.pop() {
+        let item = item_at(next[idx]);
+        next[idx] += 1;
+        if next[idx] < runs[idx].end {
+            heap.push(Reverse((address_of(&item_at(next[idx])), idx)));
+        }
+
+        if last == Some(address) {
+            dropped += 1;
+            continue;
+        }
+        last = Some(address);
+        emit(item)?;
+    }
+    Ok(dropped)
+}
@@ -0,0 +1,286 @@
+//! Page-indexed byte hit set
+//!
+//! 单个 Byte 值的搜索平均每 256 字节就有一个命中，整个进程可能有上亿个结果。按 16 字节一条的精确结果
+//! 存储既慢又占空间，单条结果本身也没有意义。这里按页记录命中：每个有命中的页只存页内偏移，
+//! 命中少时存 u16 偏移列表，命中多到位图更小时改存每字节一位的位图；同时记录之前所有页的累计命中数，
+//! 按索引取结果时二分找到所在页，只展开需要的那几页。
+
+use crate::search::ValueType;
+use crate::search::result_manager::ExactSearchResultItem;
+use serde::Serialize;
+use std::mem::{size_of, size_of_val};
+
+/// 一页内的命中偏移
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PageBits {
+    /// 升序的页内偏移
+    Offsets(Box<[u16]>),
+    /// 每个字节一位
+    Bitmap(Box<[u64]>),
+}
+
+/// 一个有命中的页
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageHits {
+    base: u64,
+    /// 之前所有页的命中数之和，即本页第一个命中的全局索引
+    before: usize,
+    count: u32,
+    bits: PageBits,
+}
+
+impl PageHits {
+    /// `offsets` 为升序且不重复的页内偏移，为空时返回 None
+    pub fn from_offsets(base: u64, offsets: &[u16], page_size: usize) -> Option<Self> {
+        if offsets.is_empty() {
+            return None;
+        }
+        debug_assert!(offsets.windows(2).all(|w| w[0] < w[1]));
+        debug_assert!((offsets[offsets.len() - 1] as usize) < page_size);
+
+        // 位图固定 page_size / 8 字节，偏移列表每个命中 2 字节
+        let bits = if size_of_val(offsets) > page_size / 8 {
+            let mut words = vec![0u64; page_size.div_ceil(64)];
+            for &offset in offsets {
+                words[offset as usize / 64] |= 1 << (offset % 64);
+            }
+            PageBits::Bitmap(words.into_boxed_slice())
+        } else {
+            PageBits::Offsets(offsets.into())
+        };
+        Some(Self {
+            base,
+            before: 0,
+            count: offsets.len() as u32,
+            bits,
+        })
+    }
+
+    pub fn base(&self) -> u64 {
+        self.base
+    }
+
+    pub fn len(&self) -> usize {
+        self.count as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    pub fn is_dense(&self) -> bool {
+        matches!(self.bits, PageBits::Bitmap(_))
+    }
+
+    /// 升序的页内偏移
+    pub fn offsets(&self) -> Vec<u16> {
+        match &self.bits {
+            PageBits::Offsets(offsets) => offsets.to_vec(),
+            PageBits::Bitmap(words) => {
+                let mut offsets = Vec::with_capacity(self.len());
+                for (i, &word) in words.iter().enumerate() {
+                    let mut word = word;
+                    while word != 0 {
+                        offsets.push((i * 64) as u16 + word.trailing_zeros() as u16);
+                        word &= word - 1;
+                    }
+                }
+                offsets
+            },
+        }
+    }
+
+    fn heap_bytes(&self) -> usize {
+        match &self.bits {
+            PageBits::Offsets(offsets) => size_of_val(&**offsets),
+            PageBits::Bitmap(words) => size_of_val(&**words),
+        }
+    }
+}
+
+/// 存储占用统计
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ByteHitStats {
+    pub hits: usize,
+    pub pages: usize,
+    /// 以位图存储的页数
+    pub dense_pages: usize,
+    /// 页索引和页内数据占用的堆内存
+    pub heap_bytes: usize,
+}
+
+/// 按页存储的 Byte 命中集合，按地址升序编号
+#[derive(Debug, Clone)]
+pub struct ByteHitSet {
+    page_size: usize,
+    pages: Vec<PageHits>,
+    total: usize,
+    /// 所有命中进入结果集的轮次
+    pass: u8,
+}
+
+impl ByteHitSet {
+    /// 页可以是任意顺序；同一页出现多次时合并
+    pub fn from_pages(page_size: usize, mut pages: Vec<PageHits>) -> Self {
+        pages.sort_unstable_by_key(|page| page.base);
+
+        let mut merged: Vec<PageHits> = Vec::with_capacity(pages.len());
+        for page in pages {
+            match merged.last_mut() {
+                Some(last) if last.base == page.base => {
+                    let mut offsets = last.offsets();
+                    offsets.extend(page.offsets());
+                    offsets.sort_unstable();
+                    offsets.dedup();
+                    if let Some(union) = PageHits::from_offsets(page.base, &offsets, page_size) {
+                        *last = union;
+                    }
+                },
+                _ => merged.push(page),
+            }
+        }
+
+        let mut set = Self {
+            page_size,
+            pages: merged,
+            total: 0,
+            pass: 0,
+        };
+        set.reindex();
+        set
+    }
+
+    pub fn with_pass(mut self, pass: u8) -> Self {
+        self.pass = pass;
+        self
+    }
+
+    pub fn pass(&self) -> u8 {
+        self.pass
+    }
+
+    pub fn page_size(&self) -> usize {
+        self.page_size
+    }
+
+    pub fn len(&self) -> usize {
+        self.total
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+
+    pub fn pages(&self) -> &[PageHits] {
+        &self.pages
+    }
+
+    /// 索引 [start, start + size) 的地址，只展开覆盖到的页
+    pub fn addresses(&self, start: usize, size: usize) -> Vec<u64> {
+        let end = start.saturating_add(size).min(self.total);
+        if start >= end {
+            return Vec::new();
+        }
+
+        let mut addresses = Vec::with_capacity(end - start);
+        for page in &self.pages[self.page_of(start)..] {
+            if page.before >= end {
+                break;
+            }
+            let from = start.saturating_sub(page.before);
+            let to = (end - page.before).min(page.len());
+            addresses.extend(page.offsets()[from..to].iter().map(|&offset| page.base + offset as u64));
+        }
+        addresses
+    }
+
+    /// 索引 [start, start + size) 的结果，类型为 Byte
+    pub fn results(&self, start: usize, size: usize) -> Vec<ExactSearchResultItem> {
+        self.addresses(start, size)
+            .into_iter()
+            .map(|address| ExactSearchResultItem::new(address, ValueType::Byte).with_pass(self.pass))
+            .collect()
+    }
+
+    /// 删除指定索引的命中，只重建涉及的页
+    pub fn remove_indices(&mut self, indices: Vec<usize>) {
+        let page_size = self.page_size;
+        let groups = self.group_by_page(indices);
+        for (page_index, positions) in groups {
+            let page = &mut self.pages[page_index];
+            let offsets: Vec<u16> = page
+                .offsets()
+                .into_iter()
+                .enumerate()
+                .filter(|(position, _)| positions.binary_search(position).is_err())
+                .map(|(_, offset)| offset)
+                .collect();
+            match PageHits::from_offsets(page.base, &offsets, page_size) {
+                Some(rebuilt) => *page = rebuilt,
+                // 清空的页由 reindex 移除
+                None => page.count = 0,
+            }
+        }
+        self.reindex();
+    }
+
+    /// 只保留指定索引的命中
+    pub fn keep_indices(&self, indices: Vec<usize>) -> Self {
+        let pages = self
+            .group_by_page(indices)
+            .into_iter()
+            .filter_map(|(page_index, positions)| {
+                let page = &self.pages[page_index];
+                let offsets = page.offsets();
+                let kept: Vec<u16> = positions.iter().map(|&position| offsets[position]).collect();
+                PageHits::from_offsets(page.base, &kept, self.page_size)
+            })
+            .collect();
+        Self::from_pages(self.page_size, pages).with_pass(self.pass)
+    }
+
+    pub fn stats(&self) -> ByteHitStats {
+        ByteHitStats {
+            hits: self.total,
+            pages: self.pages.len(),
+            dense_pages: self.pages.iter().filter(|page| page.is_dense()).count(),
+            heap_bytes: self.pages.capacity() * size_of::<PageHits>() + self.pages.iter().map(PageHits::heap_bytes).sum::<usize>(),
+        }
+    }
+
+    /// 包含索引 `index` 的页（index < total）
+    fn page_of(&self, index: usize) -> usize {
+        self.pages.partition_point(|page| page.before + page.len() <= index)
+    }
+
+    /// 把索引按所在页分组，返回 (页下标, 升序的页内位置)；越界和重复的索引被忽略
+    fn group_by_page(&self, mut indices: Vec<usize>) -> Vec<(usize, Vec<usize>)> {
+        indices.retain(|&index| index < self.total);
+        indices.sort_unstable();
+        indices.dedup();
+
+        let mut groups: Vec<(usize, Vec<usize>)> = Vec::new();
+        for index in indices {
+            let page_index = match groups.last() {
+                Some((last, _)) if index < self.pages[*last].before + self.pages[*last].len() => *last,
+                _ => self.page_of(index),
+            };
+            let position = index - self.pages[page_index].before;
+            match groups.last_mut() {
+                Some((last, positions)) if *last == page_index => positions.push(position),
+                _ => groups.push((page_index, vec![position])),
+            }
+        }
+        groups
+    }
+
+    fn reindex(&mut self) {
+        self.pages.retain(|page| !page.is_empty());
+        let mut before = 0;
+        for page in &mut self.pages {
+            page.before = before;
+            before += page.len();
+        }
+        self.total = before;
+    }
+}
@@ -0,0 +1,193 @@
+//! Address-ordered result cursor
+//!
+//! 改善搜索需要按地址顺序遍历已有结果。结果按写入顺序存储（先内存缓冲区，再磁盘 mmap），
+//! 一次扫描写入的结果按地址升序，保留结果的扫描和手动添加会在后面追加新的升序段。
+//! 游标先顺序读一遍找出这些升序段，之后按段多路归并，每次取出一批，
+//! 内存占用与批大小成正比，不需要把整个结果集展开成一个 Vec。
+//!
+//! 游标不借用结果管理器，每批读取时传入，调用方可以只在读取时短暂持有锁。
+//! 遍历期间结果集发生变化（版本号不同）时返回错误。
+
+use crate::search::result_manager::{ExactSearchResultItem, SearchResultManager};
+use anyhow::{Result, anyhow};
+use log::warn;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use std::ops::Range;
+
+/// 升序段超过这个数量时（通常是大量零散的手动添加）退化为一次性读取后排序
+pub(crate) const MAX_MERGE_RUNS: usize = 64;
+
+/// 每个升序段的最小预读条数
+const MIN_RUN_BUFFER: usize = 1024;
+
+/// 存储中的一个升序段 [start, end)
+struct Run {
+    start: usize,
+    end: usize,
+    next: usize,
+    buffer: VecDeque<ExactSearchResultItem>,
+}
+
+impl Run {
+    fn new(start: usize, end: usize) -> Self {
+        Self {
+            start,
+            end,
+            next: start,
+            buffer: VecDeque::new(),
+        }
+    }
+
+    fn refill(&mut self, store: &SearchResultManager, size: usize) -> Result<()> {
+        if self.next < self.end {
+            let records = store.records(self.next, size.min(self.end - self.next))?;
+            if records.is_empty() {
+                return Err(anyhow!("Result store ended at {} before the expected {}", self.next, self.end));
+            }
+            self.next += records.len();
+            self.buffer.extend(records);
+        }
+        Ok(())
+    }
+}
+
+/// 顺序读一遍存储，找出按地址升序的段（相邻段首尾相接，覆盖全部结果）
+///
+/// 存储已经按地址整理过时整体就是一段，不需要读取。段数达到 `MAX_MERGE_RUNS` 时停止并返回 None。
+pub(crate) fn ascending_runs(store: &SearchResultManager, batch_size: usize) -> Result<Option<Vec<Range<usize>>>> {
+    let batch_size = batch_size.max(1);
+    let total = store.total_count();
+    if store.is_address_sorted() {
+        return Ok(Some((total > 0).then_some(0..total).into_iter().collect()));
+    }
+
+    let mut runs = Vec::new();
+    let mut run_start = 0;
+    let mut last = None;
+    let mut pos = 0;
+    while pos < total {
+        let records = store.records(pos, batch_size)?;
+        if records.is_empty() {
+            break;
+        }
+        for (i, item) in records.iter().enumerate() {
+            if last.is_some_and(|last| item.address < last) {
+                runs.push(run_start..pos + i);
+                run_start = pos + i;
+                if runs.len() >= MAX_MERGE_RUNS {
+                    return Ok(None);
+                }
+            }
+            last = Some(item.address);
+        }
+        pos += records.len();
+    }
+    if pos > run_start {
+        runs.push(run_start..pos);
+    }
+    Ok(Some(runs))
+}
+
+/// 按地址升序分批读取结果，模糊结果只取地址、类型和轮次
+pub(crate) struct ResultCursor {
+    revision: u64,
+    total: usize,
+    batch_size: usize,
+    runs: Vec<Run>,
+    heap: BinaryHeap<Reverse<(u64, usize)>>,
+    started: bool,
+    /// 升序段过多时一次性排好序的结果和读取位置
+    sorted: Option<(Vec<ExactSearchResultItem>, usize)>,
+}
+
+impl ResultCursor {
+    pub fn new(store: &SearchResultManager, batch_size: usize) -> Result<Self> {
+        let batch_size = batch_size.max(1);
+        let total = store.total_count();
+
+        let (runs, sorted) = match ascending_runs(store, batch_size)? {
+            Some(runs) => (runs.into_iter().map(|run| Run::new(run.start, run.end)).collect(), None),
+            None => {
+                warn!("Result store has more than {} ascending runs, sorting {} results in memory", MAX_MERGE_RUNS, total);
+                let mut records = store.records(0, total)?;
+                records.sort_unstable_by_key(|item| item.address);
+                (Vec::new(), Some((records, 0)))
+            },
+        };
+
+        Ok(Self {
+            revision: store.revision(),
+            total,
+            batch_size,
+            runs,
+            heap: BinaryHeap::new(),
+            started: false,
+            sorted,
+        })
+    }
+
+    /// 创建游标时的结果总数
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// 存储中的升序段数，一次性排序时为 0
+    pub fn run_count(&self) -> usize {
+        self.runs.len()
+    }
+
+    /// 回到开头重新遍历
+    pub fn rewind(&mut self) {
+        for run in &mut self.runs {
+            run.next = run.start;
+            run.buffer.clear();
+        }
+        self.heap.clear();
+        self.started = false;
+        if let Some((_, position)) = &mut self.sorted {
+            *position = 0;
+        }
+    }
+
+    /// 读取下一批（最多 batch_size 条），遍历结束时返回空
+    pub fn next_batch(&mut self, store: &SearchResultManager) -> Result<Vec<ExactSearchResultItem>> {
+        if store.revision() != self.revision {
+            return Err(anyhow!("Result set changed while it was being read"));
+        }
+
+        if let Some((records, position)) = &mut self.sorted {
+            let end = (*position + self.batch_size).min(records.len());
+            let batch = records[*position..end].to_vec();
+            *position = end;
+            return Ok(batch);
+        }
+
+        let run_buffer = (self.batch_size / self.runs.len().max(1)).max(MIN_RUN_BUFFER);
+        if !self.started {
+            for (idx, run) in self.runs.iter_mut().enumerate() {
+                run.refill(store, run_buffer)?;
+                if let Some(front) = run.buffer.front() {
+                    self.heap.push(Reverse((front.address, idx)));
+                }
+            }
+            self.started = true;
+        }
+
+        let mut batch = Vec::with_capacity(self.batch_size.min(self.total));
+        while batch.len() < self.batch_size {
+            let Some(Reverse((_, idx))) = self.heap.pop() else {
+                break;
+            };
+            let run = &mut self.runs[idx];
+            batch.extend(run.buffer.pop_front());
+            if run.buffer.is_empty() {
+                run.refill(store, run_buffer)?;
+            }
+            if let Some(front) = run.buffer.front() {
+                self.heap.push(Reverse((front.address, idx)));
+            }
+        }
+        Ok(batch)
+    }
+}
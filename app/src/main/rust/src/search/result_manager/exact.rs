@@ -1,7 +1,8 @@
+use crate::core::self_regions::SelfMmap;
 use crate::search::{SearchResultItem, ValueType};
-use crate::search::result_manager::SearchResultManager;
-use log::{debug, info};
-use memmap2::MmapMut;
+use crate::search::result_manager::integrity::{INTEGRITY_BATCH_RECORDS, IntegrityReport, IntegrityTracker, RecordLayout, reopen_leftover};
+use crate::search::result_manager::undo::{self, StashedFile, StashedStore};
+use log::{debug, error, info};
 use std::fs::{File, OpenOptions};
 use std::path::PathBuf;
 
@@ -10,14 +11,30 @@ use std::path::PathBuf;
 pub struct ExactSearchResultItem {
     pub address: u64,
     pub typ: ValueType,
+    /// 结果进入结果集的轮次，见 `SearchResultManager::begin_pass`
+    pub pass: u8,
 }
 
 impl ExactSearchResultItem {
     pub fn new(address: u64, typ: ValueType) -> Self {
-        ExactSearchResultItem { address, typ }
+        ExactSearchResultItem { address, typ, pass: 0 }
+    }
+
+    #[inline]
+    pub fn with_pass(self, pass: u8) -> Self {
+        ExactSearchResultItem { pass, ..self }
     }
 }
 
+/// 磁盘文件中的记录布局：address(8) + typ(4) + pass(1) + 填充(3)
+const RECORD_LAYOUT: RecordLayout = RecordLayout {
+    size: size_of::<ExactSearchResultItem>(),
+    value_offset: None,
+    type_offset: std::mem::offset_of!(ExactSearchResultItem, typ),
+};
+
+const DISK_FILE_NAME: &str = "mamu_search_results.bin";
+
 impl From<(u64, ValueType)> for ExactSearchResultItem {
     fn from(tuple: (u64, ValueType)) -> Self {
         Self::new(tuple.0, tuple.1)
@@ -30,9 +47,13 @@ pub struct ExactSearchResultManager {
     cache_dir: PathBuf,
     disk_file_path: Option<PathBuf>,
     disk_file: Option<File>,
-    mmap: Option<MmapMut>,
+    mmap: Option<SelfMmap>,
     disk_count: usize,
     total_count: usize,
+    /// 结果文件的校验清单，磁盘文件存在时才有
+    integrity: Option<IntegrityTracker>,
+    /// 启动时接管上次遗留结果文件的校验报告
+    recovery: Option<IntegrityReport>,
 }
 
 impl ExactSearchResultManager {
@@ -57,7 +78,7 @@ impl ExactSearchResultManager {
             );
         }
 
-        ExactSearchResultManager {
+        let mut manager = ExactSearchResultManager {
             memory_buffer: Vec::with_capacity(capacity),
             memory_buffer_capacity: capacity,
             cache_dir,
@@ -66,6 +87,32 @@ impl ExactSearchResultManager {
             mmap: None,
             disk_count: 0,
             total_count: 0,
+            integrity: None,
+            recovery: None,
+        };
+        undo::remove_leftover(&manager.cache_dir.join(DISK_FILE_NAME));
+        manager.reopen_disk_file();
+        manager
+    }
+
+    /// 接管上次进程遗留的结果文件（内存缓冲区中的结果无法恢复）
+    fn reopen_disk_file(&mut self) {
+        let file_path = self.cache_dir.join(DISK_FILE_NAME);
+        let Some((file, count, manifest, report)) = reopen_leftover(&file_path, &RECORD_LAYOUT) else {
+            return;
+        };
+
+        match unsafe { SelfMmap::map_mut(&file) } {
+            Ok(mmap) => {
+                self.integrity = Some(IntegrityTracker::adopt(&file_path, RECORD_LAYOUT, manifest, &mmap[..]));
+                self.disk_file_path = Some(file_path);
+                self.disk_file = Some(file);
+                self.mmap = Some(mmap);
+                self.disk_count = count;
+                self.total_count = count;
+                self.recovery = report;
+            },
+            Err(e) => error!("Failed to map recovered result store {:?}: {:?}", file_path, e),
         }
     }
 
@@ -73,6 +120,7 @@ impl ExactSearchResultManager {
         self.memory_buffer.clear();
         self.total_count = 0;
         self.disk_count = 0;
+        self.invalidate_integrity()?;
 
         debug!("Search results cleared (disk file and resources preserved for reuse)");
         Ok(())
@@ -91,6 +139,9 @@ impl ExactSearchResultManager {
                 debug!("Removed disk file: {:?}", path);
             }
         }
+        if let Some(tracker) = self.integrity.take() {
+            tracker.remove();
+        }
 
         self.disk_file_path = None;
         info!("CompactSearchResultManager destroyed");
@@ -100,7 +151,8 @@ impl ExactSearchResultManager {
     pub fn add_result(&mut self, item: ExactSearchResultItem) -> anyhow::Result<()> {
         if self.memory_buffer_capacity == 0 {
             self.write_to_disk(&item)?;
-        } else if self.memory_buffer.len() < self.memory_buffer_capacity {
+        } else if self.disk_count == 0 && self.memory_buffer.len() < self.memory_buffer_capacity {
+            // 磁盘上已有结果时继续追加到磁盘，删除后腾出的内存空间不再使用，保证新结果排在最后
             self.memory_buffer.push(item);
         } else {
             self.write_to_disk(&item)?;
@@ -125,7 +177,7 @@ impl ExactSearchResultManager {
                 if let Some(ref file) = self.disk_file {
                     file.set_len(new_size as u64)?;
                 }
-                self.mmap = Some(unsafe { MmapMut::map_mut(self.disk_file.as_ref().unwrap())? });
+                self.mmap = Some(unsafe { SelfMmap::map_mut(self.disk_file.as_ref().unwrap())? });
             }
 
             let mmap = self.mmap.as_mut().unwrap();
@@ -135,13 +187,16 @@ impl ExactSearchResultManager {
             }
 
             self.disk_count += 1;
+            if let Some(ref mut tracker) = self.integrity {
+                tracker.on_append(&mmap[..], self.disk_count)?;
+            }
         }
 
         Ok(())
     }
 
     fn init_disk_file(&mut self) -> anyhow::Result<()> {
-        let file_path = self.cache_dir.join("mamu_search_results.bin");
+        let file_path = self.cache_dir.join(DISK_FILE_NAME);
 
         debug!("Creating disk file: {:?}", file_path);
 
@@ -155,8 +210,12 @@ impl ExactSearchResultManager {
 
         file.set_len(initial_size as u64)?;
 
-        let mmap = unsafe { MmapMut::map_mut(&file)? };
+        let mmap = unsafe { SelfMmap::map_mut(&file)? };
+
+        let tracker = IntegrityTracker::new(&file_path, RECORD_LAYOUT, INTEGRITY_BATCH_RECORDS);
+        tracker.remove();
 
+        self.integrity = Some(tracker);
         self.disk_file_path = Some(file_path);
         self.disk_file = Some(file);
         self.mmap = Some(mmap);
@@ -175,6 +234,9 @@ impl ExactSearchResultManager {
                 debug!("Removed disk file: {:?}", path);
             }
         }
+        if let Some(tracker) = self.integrity.take() {
+            tracker.remove();
+        }
 
         self.disk_file_path = None;
         self.disk_count = 0;
@@ -279,7 +341,7 @@ impl ExactSearchResultManager {
             self.disk_count -= 1;
         }
 
-        Ok(())
+        self.invalidate_integrity()
     }
 
     pub fn remove_results_batch(&mut self, mut indices: Vec<usize>) -> anyhow::Result<()> {
@@ -418,7 +480,7 @@ impl ExactSearchResultManager {
         }
 
         self.disk_count = write_pos;
-        Ok(())
+        self.invalidate_integrity()
     }
 
     /// Keep only the specified results, remove all others
@@ -429,6 +491,7 @@ impl ExactSearchResultManager {
             self.memory_buffer.clear();
             self.disk_count = 0;
             self.total_count = 0;
+            self.invalidate_integrity()?;
             debug!("Kept 0 results, cleared all");
             return Ok(());
         }
@@ -478,6 +541,7 @@ impl ExactSearchResultManager {
             self.memory_buffer.clear();
             self.disk_count = 0;
             self.total_count = 0;
+            self.invalidate_integrity()?;
 
             // 重新添加保留的项（全部放入内存，因为数量较少）
             for item in kept_items {
@@ -515,9 +579,86 @@ impl ExactSearchResultManager {
     }
 
     /// Get all results (used for refine search)
+    /// 把当前结果整体移出，磁盘文件改名而不复制；之后的写入从空的存储开始，见 `undo`
+    pub fn stash(&mut self) -> anyhow::Result<StashedStore<ExactSearchResultItem>> {
+        let disk = match self.disk_file_path {
+            Some(ref path) if self.disk_count > 0 => Some(StashedFile::move_from(path)?),
+            _ => None,
+        };
+        if disk.is_some() {
+            drop(self.mmap.take());
+            drop(self.disk_file.take());
+            if let Some(tracker) = self.integrity.take() {
+                tracker.remove();
+            }
+            self.disk_file_path = None;
+        }
+
+        let memory = std::mem::replace(&mut self.memory_buffer, Vec::with_capacity(self.memory_buffer_capacity));
+        let stashed = StashedStore {
+            memory,
+            disk,
+            disk_count: self.disk_count,
+            total_count: self.total_count,
+        };
+        self.disk_count = 0;
+        self.total_count = 0;
+        self.invalidate_integrity()?;
+        Ok(stashed)
+    }
+
+    /// 丢弃当前结果，换回 `stash` 移出的结果；校验清单在下次封存时重新计算
+    pub fn restore(&mut self, stashed: StashedStore<ExactSearchResultItem>) -> anyhow::Result<()> {
+        self.clear()?;
+        self.clear_disk()?;
+
+        let StashedStore { memory, disk, disk_count, total_count } = stashed;
+        if let Some(disk) = disk {
+            let file_path = self.cache_dir.join(DISK_FILE_NAME);
+            let file = disk.restore(&file_path)?;
+            let mmap = unsafe { SelfMmap::map_mut(&file)? };
+            self.integrity = Some(IntegrityTracker::new(&file_path, RECORD_LAYOUT, INTEGRITY_BATCH_RECORDS));
+            self.disk_file_path = Some(file_path);
+            self.disk_file = Some(file);
+            self.mmap = Some(mmap);
+        }
+        self.memory_buffer = memory;
+        self.disk_count = disk_count;
+        self.total_count = total_count;
+        Ok(())
+    }
+
     pub fn get_all_results(&self) -> anyhow::Result<Vec<ExactSearchResultItem>> {
         self.get_results(0, self.total_count)
     }
+
+    fn invalidate_integrity(&mut self) -> anyhow::Result<()> {
+        match self.integrity {
+            Some(ref mut tracker) => tracker.invalidate(),
+            None => Ok(()),
+        }
+    }
+
+    /// 一次批量操作完成后封存磁盘文件的校验清单
+    pub fn seal(&mut self) -> anyhow::Result<()> {
+        if let (Some(tracker), Some(mmap)) = (self.integrity.as_mut(), self.mmap.as_ref()) {
+            tracker.seal(&mmap[..], self.disk_count)?;
+        }
+        Ok(())
+    }
+
+    /// 校验磁盘文件中的结果，没有磁盘文件时直接通过
+    pub fn verify_integrity(&self) -> IntegrityReport {
+        match (self.integrity.as_ref(), self.mmap.as_ref()) {
+            (Some(tracker), Some(mmap)) => tracker.verify(&mmap[..], self.disk_count),
+            _ => IntegrityReport { ok: true, clean_shutdown: true, ..Default::default() },
+        }
+    }
+
+    /// 启动时接管遗留结果文件的校验报告
+    pub fn recovery_report(&self) -> Option<&IntegrityReport> {
+        self.recovery.as_ref()
+    }
 }
 
 impl Drop for ExactSearchResultManager {
@@ -1,10 +1,15 @@
+use crate::core::self_regions::SelfMmap;
 use crate::search::FuzzyCondition;
+use crate::search::engine::scan_cache::{fnv1a, FNV_OFFSET};
+use crate::search::result_manager::address_sort;
+use crate::search::result_manager::integrity::{INTEGRITY_BATCH_RECORDS, IntegrityReport, IntegrityTracker, RecordLayout, reopen_leftover};
 use crate::search::types::ValueType;
 use anyhow::{Result, anyhow};
-use log::{debug, info};
-use memmap2::MmapMut;
+use crate::search::result_manager::undo::{self, RefineJournal, StashedFile, StashedStore};
+use log::{debug, error, info};
 use std::cmp::Ordering;
 use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
 use std::mem::size_of;
 use std::path::PathBuf;
 
@@ -15,9 +20,11 @@ use std::path::PathBuf;
 pub struct FuzzySearchResultItem {
     pub address: u64,          // 8 bytes
     pub value: [u8; 8],        // 8 bytes - 原始字节存储
-    pub value_type: ValueType, // 1 byte
+    pub value_type: ValueType, // 4 bytes
+    pub pass: u8,              // 1 byte - 进入结果集的轮次
+    pub auto_types: u8,        // 1 byte - Auto 模糊搜索尚未排除的解释，其他结果为 0
 }
-// 总共 17 字节 (packed)
+// 总共 22 字节 (packed)
 
 // 为 packed 结构体手动实现比较 trait（按地址排序）
 impl PartialEq for FuzzySearchResultItem {
@@ -49,23 +56,101 @@ impl Ord for FuzzySearchResultItem {
 }
 
 impl FuzzySearchResultItem {
+    /// `auto_types` 的位：按 Dword 解释
+    pub const AUTO_DWORD: u8 = 1;
+    /// `auto_types` 的位：按 Float 解释
+    pub const AUTO_FLOAT: u8 = 2;
+    /// `auto_types` 的位：按 Qword 解释，只有 8 字节对齐的地址才有
+    pub const AUTO_QWORD: u8 = 4;
+
+    const AUTO_INTERPRETATIONS: [(u8, ValueType); 3] =
+        [(Self::AUTO_DWORD, ValueType::Dword), (Self::AUTO_FLOAT, ValueType::Float), (Self::AUTO_QWORD, ValueType::Qword)];
+
     #[inline]
     pub fn new(address: u64, value: [u8; 8], value_type: ValueType) -> Self {
-        FuzzySearchResultItem { address, value, value_type }
+        FuzzySearchResultItem { address, value, value_type, pass: 0, auto_types: 0 }
+    }
+
+    #[inline]
+    pub fn with_pass(self, pass: u8) -> Self {
+        FuzzySearchResultItem { pass, ..self }
+    }
+
+    #[inline]
+    pub fn with_auto_types(self, auto_types: u8) -> Self {
+        FuzzySearchResultItem { auto_types, ..self }
     }
 
-    /// 从字节切片创建结果项
+    /// Auto 模糊搜索的初始项，保存原始 8 字节（不足时补 0）
+    ///
+    /// Dword 和 Float 两种解释总是可能的；只有 8 字节对齐且读到了 8 字节的地址才可能是 Qword，
+    /// 这样 Qword 解释不会跨页，也不会和前一个地址的 Qword 重叠。
+    #[inline]
+    pub fn auto(address: u64, bytes: &[u8]) -> Self {
+        let mut auto_types = Self::AUTO_DWORD | Self::AUTO_FLOAT;
+        if address.is_multiple_of(8) && bytes.len() >= 8 {
+            auto_types |= Self::AUTO_QWORD;
+        }
+        Self::from_bytes(address, bytes, ValueType::Auto).with_auto_types(auto_types)
+    }
+
+    /// 是否来自 Auto 模糊搜索（包括已经定型的项）
+    #[inline]
+    pub fn is_auto(&self) -> bool {
+        self.auto_types != 0
+    }
+
+    /// 从字节切片创建结果项，变长类型按 `variable_value` 保存
     #[inline]
     pub fn from_bytes(address: u64, bytes: &[u8], value_type: ValueType) -> Self {
+        if value_type.is_variable_len() {
+            return FuzzySearchResultItem::new(address, Self::variable_value(bytes), value_type);
+        }
         let mut value = [0u8; 8];
         let len = bytes.len().min(8);
         value[..len].copy_from_slice(&bytes[..len]);
-        FuzzySearchResultItem { address, value, value_type }
+        FuzzySearchResultItem { address, value, value_type, pass: 0, auto_types: 0 }
+    }
+
+    /// 变长值（特征码、文本）在 `value` 中的表示
+    ///
+    /// 不超过 8 字节时就是原始字节（不足补 0），更长时是 64 位 FNV-1a 摘要。同一结果集的长度相同，
+    /// 这类项只比较是否变化，比较两次读到的表示即可。
+    #[inline]
+    pub fn variable_value(bytes: &[u8]) -> [u8; 8] {
+        let mut value = [0u8; 8];
+        if bytes.len() <= 8 {
+            value[..bytes.len()].copy_from_slice(bytes);
+        } else {
+            value = fnv1a(FNV_OFFSET, bytes).to_le_bytes();
+        }
+        value
+    }
+
+    /// 细化时需要读取的字节数，变长类型为结果集的 `pattern_len`
+    #[inline]
+    pub fn read_size(&self, pattern_len: usize) -> usize {
+        let vt = self.value_type;
+        if vt.is_variable_len() { pattern_len } else { self.value_size() }
+    }
+
+    /// 变长值只能判断是否变化，其它条件都不成立
+    #[inline]
+    pub fn matches_variable(unchanged: bool, condition: FuzzyCondition) -> bool {
+        match condition {
+            FuzzyCondition::Initial => true,
+            FuzzyCondition::Unchanged => unchanged,
+            FuzzyCondition::Changed => !unchanged,
+            _ => false,
+        }
     }
 
-    /// 获取值的有效字节数
+    /// 获取值的有效字节数，仍可能是 Qword 的 Auto 项需要 8 字节
     #[inline]
     pub fn value_size(&self) -> usize {
+        if self.auto_types & Self::AUTO_QWORD != 0 {
+            return 8;
+        }
         let vt = self.value_type;
         vt.size()
     }
@@ -81,7 +166,7 @@ impl FuzzySearchResultItem {
             ValueType::Qword => i64::from_le_bytes(self.value),
             ValueType::Float => f32::from_le_bytes(self.value[..4].try_into().unwrap()) as i64,
             ValueType::Double => f64::from_le_bytes(self.value) as i64,
-            ValueType::Pattern => 0, // Pattern 类型不支持模糊搜索
+            ValueType::Pattern | ValueType::Utf8String | ValueType::Utf16String => 0, // 变长类型不支持模糊搜索
         }
     }
 
@@ -96,17 +181,23 @@ impl FuzzySearchResultItem {
             ValueType::Qword => i64::from_le_bytes(self.value) as f64,
             ValueType::Float => f32::from_le_bytes(self.value[..4].try_into().unwrap()) as f64,
             ValueType::Double => f64::from_le_bytes(self.value),
-            ValueType::Pattern => 0.0, // Pattern 类型不支持模糊搜索
+            ValueType::Pattern | ValueType::Utf8String | ValueType::Utf16String => 0.0, // 变长类型不支持模糊搜索
         }
     }
 
-    /// 检查新值是否满足模糊搜索条件
+    /// 检查新值是否满足模糊搜索条件，未定型的 Auto 项有任一解释满足即可
     #[inline]
     pub fn matches_condition(&self, new_bytes: &[u8], condition: FuzzyCondition) -> bool {
         let vt = self.value_type;
+        if vt == ValueType::Auto && self.is_auto() {
+            return self.refine_auto(new_bytes, condition).is_some();
+        }
         let new_item = FuzzySearchResultItem::from_bytes(self.address, new_bytes, vt);
 
-        if vt.is_float_type() {
+        if vt.is_variable_len() {
+            let (old_value, new_value) = (self.value, new_item.value);
+            Self::matches_variable(old_value == new_value, condition)
+        } else if vt.is_float_type() {
             self.matches_condition_float(&new_item, condition)
         } else {
             self.matches_condition_int(&new_item, condition)
@@ -188,12 +279,44 @@ impl FuzzySearchResultItem {
         }
     }
 
-    /// 更新值（用于细化搜索后保存新值）
+    /// 未定型的 Auto 项的细化：逐个解释比较，只保留满足条件的解释
+    ///
+    /// 都不满足时返回 None；只剩一个解释时定型为该类型，`auto_types` 保留这一位。
+    pub fn refine_auto(&self, new_bytes: &[u8], condition: FuzzyCondition) -> Option<Self> {
+        let address = self.address;
+        let old_value = self.value;
+        let matched = Self::AUTO_INTERPRETATIONS
+            .iter()
+            .filter(|(bit, value_type)| self.auto_types & bit != 0 && Self::new(address, old_value, *value_type).matches_condition(new_bytes, condition))
+            .fold(0, |acc, (bit, _)| acc | bit);
+
+        let value_type = match matched {
+            0 => return None,
+            Self::AUTO_DWORD => ValueType::Dword,
+            Self::AUTO_FLOAT => ValueType::Float,
+            Self::AUTO_QWORD => ValueType::Qword,
+            _ => ValueType::Auto,
+        };
+        Some(Self::from_bytes(address, new_bytes, value_type).with_pass(self.pass).with_auto_types(matched))
+    }
+
+    /// 更新值（用于细化搜索后保存新值），保留原来的轮次
     pub fn with_new_value(&self, new_bytes: &[u8]) -> Self {
-        FuzzySearchResultItem::from_bytes(self.address, new_bytes, self.value_type)
+        FuzzySearchResultItem::from_bytes(self.address, new_bytes, self.value_type).with_pass(self.pass).with_auto_types(self.auto_types)
     }
 }
 
+/// 磁盘文件中的记录布局：address(8) + value(8) + value_type(4) + pass(1) + auto_types(1)
+const RECORD_LAYOUT: RecordLayout = RecordLayout {
+    size: size_of::<FuzzySearchResultItem>(),
+    value_offset: Some(std::mem::offset_of!(FuzzySearchResultItem, value)),
+    type_offset: std::mem::offset_of!(FuzzySearchResultItem, value_type),
+};
+
+const DISK_FILE_NAME: &str = "mamu_fuzzy_results.bin";
+/// 按地址整理时磁盘部分先写到这个文件，完成后替换 `DISK_FILE_NAME`
+const SORTED_FILE_NAME: &str = "mamu_fuzzy_results.sorted";
+
 /// 模糊搜索结果管理器 - 内存 + 磁盘混合存储
 pub struct FuzzySearchResultManager {
     memory_buffer: Vec<FuzzySearchResultItem>,
@@ -201,9 +324,17 @@ pub struct FuzzySearchResultManager {
     cache_dir: PathBuf,
     disk_file_path: Option<PathBuf>,
     disk_file: Option<File>,
-    mmap: Option<MmapMut>,
+    mmap: Option<SelfMmap>,
     disk_count: usize,
     total_count: usize,
+    /// 结果文件的校验清单，磁盘文件存在时才有
+    integrity: Option<IntegrityTracker>,
+    /// 启动时接管上次遗留结果文件的校验报告
+    recovery: Option<IntegrityReport>,
+    /// 存储按地址严格升序（内存缓冲区在前，磁盘在后），由 `sort_by_address` 设置，乱序追加或改写地址后清除
+    address_sorted: bool,
+    /// 变长结果（特征码、文本）的字节数，细化时按这个长度读取，清空结果时一起清除
+    pattern_len: Option<usize>,
 }
 
 impl FuzzySearchResultManager {
@@ -226,7 +357,7 @@ impl FuzzySearchResultManager {
             );
         }
 
-        FuzzySearchResultManager {
+        let mut manager = FuzzySearchResultManager {
             memory_buffer: Vec::with_capacity(capacity),
             memory_buffer_capacity: capacity,
             cache_dir,
@@ -235,6 +366,34 @@ impl FuzzySearchResultManager {
             mmap: None,
             disk_count: 0,
             total_count: 0,
+            integrity: None,
+            recovery: None,
+            address_sorted: false,
+            pattern_len: None,
+        };
+        undo::remove_leftover(&manager.cache_dir.join(DISK_FILE_NAME));
+        manager.reopen_disk_file();
+        manager
+    }
+
+    /// 接管上次进程遗留的结果文件（内存缓冲区中的结果无法恢复）
+    fn reopen_disk_file(&mut self) {
+        let file_path = self.cache_dir.join(DISK_FILE_NAME);
+        let Some((file, count, manifest, report)) = reopen_leftover(&file_path, &RECORD_LAYOUT) else {
+            return;
+        };
+
+        match unsafe { SelfMmap::map_mut(&file) } {
+            Ok(mmap) => {
+                self.integrity = Some(IntegrityTracker::adopt(&file_path, RECORD_LAYOUT, manifest, &mmap[..]));
+                self.disk_file_path = Some(file_path);
+                self.disk_file = Some(file);
+                self.mmap = Some(mmap);
+                self.disk_count = count;
+                self.total_count = count;
+                self.recovery = report;
+            },
+            Err(e) => error!("Failed to map recovered fuzzy result store {:?}: {:?}", file_path, e),
         }
     }
 
@@ -242,6 +401,9 @@ impl FuzzySearchResultManager {
         self.memory_buffer.clear();
         self.total_count = 0;
         self.disk_count = 0;
+        self.address_sorted = false;
+        self.pattern_len = None;
+        self.invalidate_integrity()?;
         debug!("Fuzzy search results cleared");
         Ok(())
     }
@@ -256,6 +418,9 @@ impl FuzzySearchResultManager {
                 debug!("Removed fuzzy disk file: {:?}", path);
             }
         }
+        if let Some(tracker) = self.integrity.take() {
+            tracker.remove();
+        }
 
         self.disk_file_path = None;
         self.disk_count = 0;
@@ -267,6 +432,8 @@ impl FuzzySearchResultManager {
         self.memory_buffer.clear();
         self.total_count = 0;
         self.disk_count = 0;
+        self.address_sorted = false;
+        self.pattern_len = None;
 
         if let Some(ref path) = self.disk_file_path {
             drop(self.mmap.take());
@@ -276,6 +443,9 @@ impl FuzzySearchResultManager {
                 debug!("Removed fuzzy disk file: {:?}", path);
             }
         }
+        if let Some(tracker) = self.integrity.take() {
+            tracker.remove();
+        }
 
         self.disk_file_path = None;
         info!("FuzzySearchResultManager destroyed");
@@ -283,9 +453,14 @@ impl FuzzySearchResultManager {
     }
 
     pub fn add_result(&mut self, item: FuzzySearchResultItem) -> Result<()> {
+        if self.address_sorted && self.total_count > 0 && self.item_at(self.total_count - 1).address >= item.address {
+            self.address_sorted = false;
+        }
+
         if self.memory_buffer_capacity == 0 {
             self.write_to_disk(&item)?;
-        } else if self.memory_buffer.len() < self.memory_buffer_capacity {
+        } else if self.disk_count == 0 && self.memory_buffer.len() < self.memory_buffer_capacity {
+            // 磁盘上已有结果时继续追加到磁盘，删除后腾出的内存空间不再使用，保证新结果排在最后
             self.memory_buffer.push(item);
         } else {
             self.write_to_disk(&item)?;
@@ -310,7 +485,7 @@ impl FuzzySearchResultManager {
                 if let Some(ref file) = self.disk_file {
                     file.set_len(new_size as u64)?;
                 }
-                self.mmap = Some(unsafe { MmapMut::map_mut(self.disk_file.as_ref().unwrap())? });
+                self.mmap = Some(unsafe { SelfMmap::map_mut(self.disk_file.as_ref().unwrap())? });
             }
 
             let mmap = self.mmap.as_mut().unwrap();
@@ -320,13 +495,16 @@ impl FuzzySearchResultManager {
             }
 
             self.disk_count += 1;
+            if let Some(ref mut tracker) = self.integrity {
+                tracker.on_append(&mmap[..], self.disk_count)?;
+            }
         }
 
         Ok(())
     }
 
     fn init_disk_file(&mut self) -> Result<()> {
-        let file_path = self.cache_dir.join("mamu_fuzzy_results.bin");
+        let file_path = self.cache_dir.join(DISK_FILE_NAME);
 
         debug!("Creating fuzzy disk file: {:?}", file_path);
 
@@ -335,8 +513,12 @@ impl FuzzySearchResultManager {
 
         file.set_len(initial_size as u64)?;
 
-        let mmap = unsafe { MmapMut::map_mut(&file)? };
+        let mmap = unsafe { SelfMmap::map_mut(&file)? };
+
+        let tracker = IntegrityTracker::new(&file_path, RECORD_LAYOUT, INTEGRITY_BATCH_RECORDS);
+        tracker.remove();
 
+        self.integrity = Some(tracker);
         self.disk_file_path = Some(file_path);
         self.disk_file = Some(file);
         self.mmap = Some(mmap);
@@ -392,6 +574,86 @@ impl FuzzySearchResultManager {
         self.get_results(0, self.total_count)
     }
 
+    /// 按存储顺序把结果分段交给 `visit`，磁盘部分直接引用 mmap，不复制整个结果集
+    pub fn for_each_chunk<F>(&self, chunk_size: usize, mut visit: F) -> Result<()>
+    where
+        F: FnMut(&[FuzzySearchResultItem]) -> Result<()>,
+    {
+        let chunk_size = chunk_size.max(1);
+        for chunk in self.memory_buffer.chunks(chunk_size) {
+            visit(chunk)?;
+        }
+
+        if self.disk_count == 0 {
+            return Ok(());
+        }
+        let mmap = self.mmap.as_ref().ok_or_else(|| anyhow!("Fuzzy disk file is not mapped"))?;
+        // 记录是 packed 的，对齐为 1，可以直接从 mmap 的任意偏移解释
+        let disk = unsafe { std::slice::from_raw_parts(mmap.as_ptr() as *const FuzzySearchResultItem, self.disk_count) };
+        for chunk in disk.chunks(chunk_size) {
+            visit(chunk)?;
+        }
+        Ok(())
+    }
+
+    /// 把当前结果整体移出，磁盘文件改名而不复制；之后的写入从空的存储开始，见 `undo`
+    pub fn stash(&mut self) -> Result<StashedStore<FuzzySearchResultItem>> {
+        let disk = match self.disk_file_path {
+            Some(ref path) if self.disk_count > 0 => Some(StashedFile::move_from(path)?),
+            _ => None,
+        };
+        if disk.is_some() {
+            drop(self.mmap.take());
+            drop(self.disk_file.take());
+            if let Some(tracker) = self.integrity.take() {
+                tracker.remove();
+            }
+            self.disk_file_path = None;
+        }
+
+        let memory = std::mem::replace(&mut self.memory_buffer, Vec::with_capacity(self.memory_buffer_capacity));
+        let stashed = StashedStore {
+            memory,
+            disk,
+            disk_count: self.disk_count,
+            total_count: self.total_count,
+        };
+        self.disk_count = 0;
+        self.total_count = 0;
+        self.address_sorted = false;
+        self.invalidate_integrity()?;
+        Ok(stashed)
+    }
+
+    /// 丢弃当前结果，换回 `stash` 移出的结果；校验清单在下次封存时重新计算
+    pub fn restore(&mut self, stashed: StashedStore<FuzzySearchResultItem>) -> Result<()> {
+        // 撤销换回的是同一个结果集的早先状态，变长结果的长度不变
+        let pattern_len = self.pattern_len;
+        self.clear()?;
+        self.pattern_len = pattern_len;
+        self.clear_disk()?;
+
+        let StashedStore { memory, disk, disk_count, total_count } = stashed;
+        if let Some(disk) = disk {
+            let file_path = self.cache_dir.join(DISK_FILE_NAME);
+            let file = disk.restore(&file_path)?;
+            let mmap = unsafe { SelfMmap::map_mut(&file)? };
+            self.integrity = Some(IntegrityTracker::new(&file_path, RECORD_LAYOUT, INTEGRITY_BATCH_RECORDS));
+            self.disk_file_path = Some(file_path);
+            self.disk_file = Some(file);
+            self.mmap = Some(mmap);
+        }
+        self.memory_buffer = memory;
+        self.disk_count = disk_count;
+        self.total_count = total_count;
+        Ok(())
+    }
+
+    /// 就地细化的旧值日志，按当前的内存 / 磁盘布局记录；就地细化会改写存储，不能像 `stash` 那样只改名
+    pub fn begin_journal(&self) -> RefineJournal {
+        RefineJournal::new(&self.cache_dir.join(DISK_FILE_NAME), self.memory_buffer.len(), self.total_count)
+    }
+
     pub fn total_count(&self) -> usize {
         self.total_count
     }
@@ -404,12 +666,125 @@ impl FuzzySearchResultManager {
         self.disk_count
     }
 
+    /// 存储按地址严格升序、没有重复地址
+    pub fn is_address_sorted(&self) -> bool {
+        self.address_sorted
+    }
+
+    /// 变长结果的字节数，结果是定长类型时为 None
+    pub fn pattern_len(&self) -> Option<usize> {
+        self.pattern_len
+    }
+
+    pub fn set_pattern_len(&mut self, pattern_len: Option<usize>) {
+        self.pattern_len = pattern_len;
+    }
+
+    /// 存储中的第 index 项（先内存缓冲区，再磁盘），调用方保证 index < total_count
+    fn item_at(&self, index: usize) -> FuzzySearchResultItem {
+        match index.checked_sub(self.memory_buffer.len()) {
+            None => self.memory_buffer[index],
+            Some(disk_index) => self.disk_items()[disk_index],
+        }
+    }
+
+    fn disk_items(&self) -> &[FuzzySearchResultItem] {
+        match self.mmap {
+            // 记录是 packed 的，对齐为 1，可以直接从 mmap 的任意偏移解释
+            Some(ref mmap) if self.disk_count > 0 => unsafe { std::slice::from_raw_parts(mmap.as_ptr() as *const FuzzySearchResultItem, self.disk_count) },
+            _ => &[],
+        }
+    }
+
+    /// 把存储整理为按地址严格升序，相同地址只保留存储中最靠前的一项，返回去掉的项数，见 `address_sort`
+    ///
+    /// 已经有序且没有重复时只设置标志。否则多路归并各升序段：内存缓冲区仍放前面同样多的项，
+    /// 其余顺序写到临时文件后替换磁盘文件，整理期间额外占用与磁盘部分相当的磁盘空间。
+    pub fn sort_by_address(&mut self) -> Result<usize> {
+        let (runs, has_duplicates) = address_sort::address_runs(self.total_count, |index| self.item_at(index).address);
+        if runs.len() <= 1 && !has_duplicates {
+            self.address_sorted = true;
+            return Ok(0);
+        }
+
+        let memory_len = self.memory_buffer.len();
+        let sorted_path = self.cache_dir.join(SORTED_FILE_NAME);
+        let mut memory = Vec::with_capacity(memory_len);
+        let mut writer: Option<BufWriter<File>> = None;
+        let mut disk_count = 0;
+        let merged = address_sort::merge_runs(&runs, |index| self.item_at(index), |item| item.address, |item| {
+            if memory.len() < memory_len {
+                memory.push(item);
+                return Ok(());
+            }
+            let writer = match writer {
+                Some(ref mut writer) => writer,
+                None => writer.insert(BufWriter::new(File::create(&sorted_path)?)),
+            };
+            // 按 packed 布局原样写出，与 mmap 中的记录相同
+            let bytes = unsafe { std::slice::from_raw_parts(&item as *const FuzzySearchResultItem as *const u8, Self::ITEM_SIZE) };
+            writer.write_all(bytes)?;
+            disk_count += 1;
+            Ok(())
+        })
+        .and_then(|dropped| {
+            if let Some(writer) = writer {
+                writer.into_inner().map_err(|e| anyhow!("Failed to flush sorted fuzzy results: {}", e.error()))?;
+            }
+            Ok(dropped)
+        });
+        let dropped = match merged {
+            Ok(dropped) => dropped,
+            Err(e) => {
+                let _ = std::fs::remove_file(&sorted_path);
+                return Err(e);
+            },
+        };
+
+        drop(self.mmap.take());
+        drop(self.disk_file.take());
+        if let Some(tracker) = self.integrity.take() {
+            tracker.remove();
+        }
+        let file_path = self.cache_dir.join(DISK_FILE_NAME);
+        if disk_count > 0 {
+            std::fs::rename(&sorted_path, &file_path)?;
+            let file = OpenOptions::new().read(true).write(true).open(&file_path)?;
+            let mmap = unsafe { SelfMmap::map_mut(&file)? };
+            self.integrity = Some(IntegrityTracker::new(&file_path, RECORD_LAYOUT, INTEGRITY_BATCH_RECORDS));
+            self.disk_file_path = Some(file_path);
+            self.disk_file = Some(file);
+            self.mmap = Some(mmap);
+        } else {
+            if file_path.exists() {
+                std::fs::remove_file(&file_path)?;
+            }
+            self.disk_file_path = None;
+        }
+
+        self.memory_buffer = memory;
+        self.disk_count = disk_count;
+        self.total_count = self.memory_buffer.len() + disk_count;
+        self.address_sorted = true;
+        info!(
+            "Sorted fuzzy results by address: merged {} runs, dropped {} duplicates, {} results",
+            runs.len(),
+            dropped,
+            self.total_count
+        );
+        Ok(dropped)
+    }
+
     /// 更新指定索引的结果项（用于细化搜索后更新值）
     pub fn update_result(&mut self, index: usize, item: FuzzySearchResultItem) -> Result<()> {
         if index >= self.total_count {
             return Err(anyhow!("Index out of bounds: {} >= {}", index, self.total_count));
         }
 
+        if self.item_at(index).address != item.address {
+            self.address_sorted = false;
+        }
+
         if index < self.memory_buffer.len() {
             self.memory_buffer[index] = item;
         } else {
@@ -421,6 +796,7 @@ impl FuzzySearchResultManager {
                     ptr.write(item);
                 }
             }
+            self.invalidate_integrity()?;
         }
 
         Ok(())
@@ -433,6 +809,8 @@ impl FuzzySearchResultManager {
         self.memory_buffer.clear();
         self.total_count = 0;
         self.disk_count = 0;
+        self.address_sorted = false;
+        self.invalidate_integrity()?;
 
         if results.is_empty() {
             // 清理磁盘文件（如果存在）
@@ -444,6 +822,9 @@ impl FuzzySearchResultManager {
                         let _ = std::fs::remove_file(path);
                     }
                 }
+                if let Some(tracker) = self.integrity.take() {
+                    tracker.remove();
+                }
                 self.disk_file_path = None;
             }
             return Ok(());
@@ -462,6 +843,9 @@ impl FuzzySearchResultManager {
                         let _ = std::fs::remove_file(path);
                     }
                 }
+                if let Some(tracker) = self.integrity.take() {
+                    tracker.remove();
+                }
                 self.disk_file_path = None;
             }
             self.memory_buffer = results;
@@ -501,6 +885,9 @@ impl FuzzySearchResultManager {
                             let _ = std::fs::remove_file(path);
                         }
                     }
+                    if let Some(tracker) = self.integrity.take() {
+                        tracker.remove();
+                    }
                     self.disk_file_path = None;
                 }
             }
@@ -534,7 +921,7 @@ impl FuzzySearchResultManager {
                 let new_size = ((required_size / (128 * 1024 * 1024)) + 1) * 128 * 1024 * 1024;
                 if let Some(ref file) = self.disk_file {
                     file.set_len(new_size as u64)?;
-                    self.mmap = Some(unsafe { MmapMut::map_mut(file)? });
+                    self.mmap = Some(unsafe { SelfMmap::map_mut(file)? });
                 } else {
                     return Err(anyhow!("Disk file handle is None"));
                 }
@@ -591,7 +978,7 @@ impl FuzzySearchResultManager {
             self.disk_count -= 1;
         }
 
-        Ok(())
+        self.invalidate_integrity()
     }
 
     pub fn remove_results_batch(&mut self, mut indices: Vec<usize>) -> Result<()> {
@@ -696,7 +1083,7 @@ impl FuzzySearchResultManager {
         }
 
         self.disk_count = write_pos;
-        Ok(())
+        self.invalidate_integrity()
     }
 
     pub fn keep_only_results(&mut self, mut keep_indices: Vec<usize>) -> Result<()> {
@@ -704,6 +1091,7 @@ impl FuzzySearchResultManager {
             self.memory_buffer.clear();
             self.disk_count = 0;
             self.total_count = 0;
+            self.invalidate_integrity()?;
             debug!("Kept 0 fuzzy results, cleared all");
             return Ok(());
         }
@@ -746,6 +1134,7 @@ impl FuzzySearchResultManager {
             self.memory_buffer.clear();
             self.disk_count = 0;
             self.total_count = 0;
+            self.invalidate_integrity()?;
 
             for item in kept_items {
                 self.add_result(item)?;
@@ -773,6 +1162,34 @@ impl FuzzySearchResultManager {
 
         Ok(())
     }
+
+    fn invalidate_integrity(&mut self) -> Result<()> {
+        match self.integrity {
+            Some(ref mut tracker) => tracker.invalidate(),
+            None => Ok(()),
+        }
+    }
+
+    /// 一次批量操作完成后封存磁盘文件的校验清单
+    pub fn seal(&mut self) -> Result<()> {
+        if let (Some(tracker), Some(mmap)) = (self.integrity.as_mut(), self.mmap.as_ref()) {
+            tracker.seal(&mmap[..], self.disk_count)?;
+        }
+        Ok(())
+    }
+
+    /// 校验磁盘文件中的结果，没有磁盘文件时直接通过
+    pub fn verify_integrity(&self) -> IntegrityReport {
+        match (self.integrity.as_ref(), self.mmap.as_ref()) {
+            (Some(tracker), Some(mmap)) => tracker.verify(&mmap[..], self.disk_count),
+            _ => IntegrityReport { ok: true, clean_shutdown: true, ..Default::default() },
+        }
+    }
+
+    /// 启动时接管遗留结果文件的校验报告
+    pub fn recovery_report(&self) -> Option<&IntegrityReport> {
+        self.recovery.as_ref()
+    }
 }
 
 impl Drop for FuzzySearchResultManager {
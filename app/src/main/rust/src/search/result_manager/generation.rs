@@ -0,0 +1,189 @@
+use super::fuzzy::FuzzySearchResultItem;
+use crate::search::types::ValueType;
+use anyhow::{Result, anyhow};
+use log::{debug, info, warn};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 最多保留的历史代数量，超出后淘汰最旧的一代
+const MAX_GENERATIONS: usize = 16;
+
+/// 单代快照的最大条目数（约 272MB 磁盘），超出的结果集不记录快照
+pub(crate) const MAX_GENERATION_ITEMS: usize = 16 * 1024 * 1024;
+
+/// 单条记录在快照文件中的大小：address(8) + value(8) + value_type(低 4 位) | auto_types(高 4 位)
+const RECORD_SIZE: usize = 17;
+
+/// 模糊搜索结果的一代：每次模糊扫描/细化完成后记录一次
+#[derive(Debug, Clone, Serialize)]
+pub struct ResultGeneration {
+    pub id: u32,
+    /// 记录时间（毫秒时间戳）
+    pub timestamp: u64,
+    pub count: usize,
+    /// 产生这一代的条件描述
+    pub condition: String,
+}
+
+/// 历史代存储，每一代的结果集快照单独写入缓存目录
+pub(crate) struct GenerationStore {
+    cache_dir: PathBuf,
+    generations: Vec<ResultGeneration>,
+    next_id: u32,
+}
+
+/// 正在记录的一代，分段流式写入快照文件，避免为记录快照而复制整个结果集
+///
+/// 写文件不需要持有结果管理器；登记之前被丢弃时删除快照文件。
+pub(crate) struct PendingGeneration {
+    id: u32,
+    path: PathBuf,
+    writer: Option<BufWriter<File>>,
+    condition: String,
+    count: usize,
+    /// 开始记录时结果集的修订号，分段读取时核对
+    pub revision: u64,
+    /// 开始记录时的结果数
+    pub total: usize,
+}
+
+impl PendingGeneration {
+    /// 已经写入的结果数，也是下一段的起始位置
+    pub fn written(&self) -> usize {
+        self.count
+    }
+
+    pub fn write_all(&mut self, items: &[FuzzySearchResultItem]) -> Result<()> {
+        let writer = self.writer.as_mut().ok_or_else(|| anyhow!("Generation #{} already committed", self.id))?;
+        let mut record = [0u8; RECORD_SIZE];
+        for item in items {
+            let address = item.address;
+            let value = item.value;
+            let value_type = item.value_type;
+            record[..8].copy_from_slice(&address.to_le_bytes());
+            record[8..16].copy_from_slice(&value);
+            record[16] = value_type.to_id() as u8 | (item.auto_types << 4);
+            writer.write_all(&record)?;
+        }
+        self.count += items.len();
+        Ok(())
+    }
+}
+
+impl Drop for PendingGeneration {
+    fn drop(&mut self) {
+        if self.writer.take().is_some() {
+            let _ = std::fs::remove_file(&self.path);
+            debug!("Aborted result generation #{}", self.id);
+        }
+    }
+}
+
+impl GenerationStore {
+    pub fn new(cache_dir: PathBuf) -> Self {
+        Self {
+            cache_dir,
+            generations: Vec::new(),
+            next_id: 1,
+        }
+    }
+
+    fn file_path(&self, id: u32) -> PathBuf {
+        self.cache_dir.join(format!("mamu_fuzzy_gen_{}.bin", id))
+    }
+
+    /// 开始记录新的一代，`revision` / `total` 是当前结果集的修订号和结果数
+    pub fn begin(&self, condition: String, revision: u64, total: usize) -> Result<PendingGeneration> {
+        let id = self.next_id;
+        let path = self.file_path(id);
+        let file = File::create(&path)?;
+        Ok(PendingGeneration {
+            id,
+            path,
+            writer: Some(BufWriter::new(file)),
+            condition,
+            count: 0,
+            revision,
+            total,
+        })
+    }
+
+    /// 完成记录，登记元数据并淘汰过旧的代
+    pub fn commit(&mut self, mut pending: PendingGeneration) -> Result<u32> {
+        let id = pending.id;
+        if id != self.next_id {
+            return Err(anyhow!("Generation #{} was superseded by #{}", id, self.next_id));
+        }
+        let count = pending.count;
+        let writer = pending.writer.take().ok_or_else(|| anyhow!("Generation #{} already committed", id))?;
+        if let Err(e) = writer.into_inner() {
+            let _ = std::fs::remove_file(&pending.path);
+            return Err(anyhow!("Failed to flush generation {}: {:?}", id, e));
+        }
+
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0);
+        self.generations.push(ResultGeneration {
+            id,
+            timestamp,
+            count,
+            condition: std::mem::take(&mut pending.condition),
+        });
+        self.next_id = id + 1;
+
+        while self.generations.len() > MAX_GENERATIONS {
+            let evicted = self.generations.remove(0);
+            let _ = std::fs::remove_file(self.file_path(evicted.id));
+            debug!("Evicted result generation #{}", evicted.id);
+        }
+
+        info!("Recorded result generation #{} ({} items)", id, count);
+        Ok(id)
+    }
+
+    pub fn list(&self) -> &[ResultGeneration] {
+        &self.generations
+    }
+
+    /// 读取指定代的结果集快照
+    pub fn load(&self, id: u32) -> Result<Vec<FuzzySearchResultItem>> {
+        let generation = self
+            .generations
+            .iter()
+            .find(|g| g.id == id)
+            .ok_or_else(|| anyhow!("Result generation #{} not found", id))?;
+
+        let file = File::open(self.file_path(id))?;
+        let mut reader = BufReader::new(file);
+        let mut items = Vec::with_capacity(generation.count);
+        let mut record = [0u8; RECORD_SIZE];
+
+        for _ in 0..generation.count {
+            reader.read_exact(&mut record)?;
+            let address = u64::from_le_bytes(record[..8].try_into().unwrap());
+            let value_type = ValueType::from_id((record[16] & 0x0F) as i32)
+                .ok_or_else(|| anyhow!("Corrupted generation #{}: invalid value type {}", id, record[16]))?;
+            items.push(FuzzySearchResultItem::from_bytes(address, &record[8..16], value_type).with_auto_types(record[16] >> 4));
+        }
+
+        Ok(items)
+    }
+
+    /// 清除所有历史代（新的搜索会话开始时调用）
+    pub fn clear(&mut self) {
+        for generation in std::mem::take(&mut self.generations) {
+            let path = self.file_path(generation.id);
+            if let Err(e) = std::fs::remove_file(&path) {
+                warn!("Failed to remove generation file {:?}: {:?}", path, e);
+            }
+        }
+    }
+}
+
+impl Drop for GenerationStore {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
@@ -0,0 +1,180 @@
+//! Stable result handles
+//!
+//! UI 取一页结果、用户多选、再发起删除，这期间另一次改善搜索可能已经移动了结果的位置，按位置删除会删错行。
+//! 每个结果进入结果集时分配一个单调递增的句柄，删除 / 保留按句柄定位到当前位置。
+//!
+//! 删除不改变剩余结果的相对顺序，句柄随位置单调递增。这里不逐项保存句柄，而是保存句柄连续的区段
+//! （起始位置, 起始句柄）：一次扫描的结果只有一个区段，每删除一段连续的结果最多多出一个区段。
+//! 位置和句柄的互查都是对区段的二分查找。
+//!
+//! 整体替换结果集（清空、导入、重新排序、整体替换的改善搜索）时重新分配句柄，旧句柄不再对应任何结果。
+
+/// 从 `position` 开始的结果句柄连续递增，直到下一个区段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct HandleRun {
+    position: usize,
+    handle: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct ResultHandles {
+    /// 按位置（同时也按句柄）升序
+    runs: Vec<HandleRun>,
+    len: usize,
+    /// 下一个分配的句柄，清空结果后也不回退
+    next: u64,
+}
+
+impl ResultHandles {
+    /// 丢弃所有句柄，之后新增的结果从新的句柄开始
+    pub fn clear(&mut self) {
+        self.runs.clear();
+        self.len = 0;
+    }
+
+    /// 结果数变为 total：新增的结果分配新句柄；未经 `remove_positions` 的减少视为从末尾删除
+    pub fn sync(&mut self, total: usize) {
+        if total > self.len {
+            let count = total - self.len;
+            self.append(self.next, count);
+            self.next += count as u64;
+        } else if total < self.len {
+            self.runs.retain(|run| run.position < total);
+            self.len = total;
+        }
+    }
+
+    /// 位置上结果的句柄
+    pub fn handle_at(&self, position: usize) -> Option<u64> {
+        if position >= self.len {
+            return None;
+        }
+        let run = self.runs[self.runs.partition_point(|run| run.position <= position) - 1];
+        Some(run.handle + (position - run.position) as u64)
+    }
+
+    /// 句柄对应结果的当前位置，结果已被删除或句柄来自之前的结果集时返回 None
+    pub fn position_of(&self, handle: u64) -> Option<usize> {
+        let index = self.runs.partition_point(|run| run.handle <= handle).checked_sub(1)?;
+        let run = self.runs[index];
+        let offset = handle - run.handle;
+        (offset < (self.run_end(index) - run.position) as u64).then(|| run.position + offset as usize)
+    }
+
+    /// 删除位置（升序、去重）上的结果，其余结果保留句柄
+    pub fn remove_positions(&mut self, sorted_positions: &[usize]) {
+        if sorted_positions.is_empty() {
+            return;
+        }
+        let mut kept = Self { next: self.next, ..Self::default() };
+        let mut removed = sorted_positions.iter().copied().peekable();
+        for (index, run) in self.runs.iter().enumerate() {
+            let end = self.run_end(index);
+            let mut start = run.position;
+            while start < end {
+                while removed.next_if(|&position| position < start).is_some() {}
+                let segment_end = match removed.peek() {
+                    Some(&position) if position < end => position,
+                    _ => end,
+                };
+                if segment_end > start {
+                    kept.append(run.handle + (start - run.position) as u64, segment_end - start);
+                }
+                if segment_end < end {
+                    removed.next();
+                    start = segment_end + 1;
+                } else {
+                    start = end;
+                }
+            }
+        }
+        *self = kept;
+    }
+
+    /// 只保留位置（升序、去重）上的结果
+    pub fn keep_positions(&mut self, sorted_positions: &[usize]) {
+        let mut kept = Self { next: self.next, ..Self::default() };
+        for handle in sorted_positions.iter().filter_map(|&position| self.handle_at(position)) {
+            kept.append(handle, 1);
+        }
+        *self = kept;
+    }
+
+    fn run_end(&self, index: usize) -> usize {
+        self.runs.get(index + 1).map_or(self.len, |run| run.position)
+    }
+
+    /// 在末尾追加 count 个从 handle 开始连续的句柄，与上一区段连续时合并
+    fn append(&mut self, handle: u64, count: usize) {
+        if count == 0 {
+            return;
+        }
+        let continues = self.runs.last().is_some_and(|last| last.handle + (self.len - last.position) as u64 == handle);
+        if !continues {
+            self.runs.push(HandleRun { position: self.len, handle });
+        }
+        self.len += count;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn handles(table: &ResultHandles) -> Vec<u64> {
+        (0..table.len).map(|position| table.handle_at(position).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_remove_and_keep_preserve_handles() {
+        let mut table = ResultHandles::default();
+        table.sync(10);
+        assert_eq!(table.runs.len(), 1);
+
+        table.remove_positions(&[0, 3, 4, 9]);
+        assert_eq!(handles(&table), vec![1, 2, 5, 6, 7, 8]);
+        assert_eq!(table.runs.len(), 2);
+        assert_eq!(table.position_of(5), Some(2));
+        for gone in [0, 3, 4, 9, 10] {
+            assert_eq!(table.position_of(gone), None);
+        }
+
+        // 新增的结果接着分配，不复用删除的句柄
+        table.sync(8);
+        assert_eq!(handles(&table), vec![1, 2, 5, 6, 7, 8, 10, 11]);
+        assert_eq!(table.runs.len(), 3);
+
+        table.keep_positions(&[1, 4, 5, 6]);
+        assert_eq!(handles(&table), vec![2, 7, 8, 10]);
+        assert_eq!(table.position_of(8), Some(2));
+        assert_eq!(table.position_of(1), None);
+    }
+
+    #[test]
+    fn test_clear_does_not_reuse_handles() {
+        let mut table = ResultHandles::default();
+        table.sync(4);
+        table.clear();
+        assert_eq!(table.position_of(0), None);
+        table.sync(2);
+        assert_eq!(handles(&table), vec![4, 5]);
+
+        // 未记录的减少从末尾截断
+        table.sync(1);
+        assert_eq!(handles(&table), vec![4]);
+        assert_eq!(table.position_of(5), None);
+    }
+
+    #[test]
+    fn test_scattered_removal_run_count() {
+        let mut table = ResultHandles::default();
+        table.sync(1_000_000);
+        let removed: Vec<usize> = (0..1_000_000).step_by(1000).collect();
+        table.remove_positions(&removed);
+
+        assert_eq!(table.len, 999_000);
+        assert_eq!(table.runs.len(), 1000);
+        assert_eq!(table.position_of(1001), Some(999));
+        assert_eq!(table.handle_at(998_999), Some(999_999));
+    }
+}
@@ -0,0 +1,495 @@
+//! Result store integrity
+//!
+//! 结果文件通过 mmap 写入，进程被杀或设备掉电时文件里可能留下写了一半的记录。每个结果文件旁边
+//! 有一个很小的清单文件（`<store>.manifest`），记录已校验的记录数和按批次串联的 CRC32：
+//! 第 i 批的校验和以第 i-1 批的校验和为初值，只在写满一批（或封存）时计算一次，查询时不再计算。
+//!
+//! 清单的 clean 标志在封存（一次批量操作完成）时置位，之后第一次追加前清除。启动时发现遗留的
+//! 结果文件且清单未封存，说明上次没有正常结束，先校验再截断到最后一个一致的批次边界。
+
+use crate::search::types::ValueType;
+use anyhow::{Result, anyhow};
+use log::{info, warn};
+use memmap2::Mmap;
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+
+/// 每批记录数，写满一批计算一次校验和
+pub(crate) const INTEGRITY_BATCH_RECORDS: usize = 64 * 1024;
+
+const MANIFEST_MAGIC: &[u8; 4] = b"MRIS";
+const MANIFEST_VERSION: u32 = 1;
+const MANIFEST_HEADER_SIZE: usize = 32;
+
+const FLAG_CLEAN: u32 = 1;
+const FLAG_SORTED: u32 = 2;
+
+/// 结果文件中单条记录的布局，参与校验的只有地址、值和类型字段（不含填充字节）
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RecordLayout {
+    pub size: usize,
+    pub value_offset: Option<usize>,
+    pub type_offset: usize,
+}
+
+impl RecordLayout {
+    #[inline]
+    fn address(&self, record: &[u8]) -> u64 {
+        u64::from_le_bytes(record[..8].try_into().unwrap())
+    }
+
+    #[inline]
+    fn type_id(&self, record: &[u8]) -> i32 {
+        i32::from_le_bytes(record[self.type_offset..self.type_offset + 4].try_into().unwrap())
+    }
+
+    /// 参与校验的字节：address(8) + value(8，可选) + type(4)
+    #[inline]
+    fn digest_bytes<'a>(&self, record: &[u8], out: &'a mut [u8; 20]) -> &'a [u8] {
+        out[..8].copy_from_slice(&record[..8]);
+        let mut len = 8;
+        if let Some(offset) = self.value_offset {
+            out[8..16].copy_from_slice(&record[offset..offset + 8]);
+            len = 16;
+        }
+        out[len..len + 4].copy_from_slice(&record[self.type_offset..self.type_offset + 4]);
+        &out[..len + 4]
+    }
+
+    /// 记录本身是否合理：地址非零（预分配的空白区域全是 0）、类型合法
+    fn check(&self, record: &[u8]) -> Option<String> {
+        if self.address(record) == 0 {
+            return Some("zero address".to_string());
+        }
+        let type_id = self.type_id(record);
+        if ValueType::from_id(type_id).is_none() {
+            return Some(format!("invalid value type {}", type_id));
+        }
+        None
+    }
+}
+
+/// 清单文件内容
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct StoreManifest {
+    pub record_size: u32,
+    pub batch_records: u32,
+    pub clean: bool,
+    /// 已校验的记录地址单调不减，校验时要求保持
+    pub sorted: bool,
+    pub record_count: u64,
+    /// 第 i 项为前 i+1 批串联的校验和，最后一批可能不满
+    pub chain: Vec<u32>,
+}
+
+impl StoreManifest {
+    fn new(layout: &RecordLayout, batch_records: usize) -> Self {
+        Self {
+            record_size: layout.size as u32,
+            batch_records: batch_records as u32,
+            clean: true,
+            sorted: true,
+            record_count: 0,
+            chain: Vec::new(),
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(MANIFEST_HEADER_SIZE + self.chain.len() * 4);
+        bytes.extend_from_slice(MANIFEST_MAGIC);
+        bytes.extend_from_slice(&MANIFEST_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&self.record_size.to_le_bytes());
+        bytes.extend_from_slice(&self.batch_records.to_le_bytes());
+        let flags = if self.clean { FLAG_CLEAN } else { 0 } | if self.sorted { FLAG_SORTED } else { 0 };
+        bytes.extend_from_slice(&flags.to_le_bytes());
+        bytes.extend_from_slice(&self.record_count.to_le_bytes());
+        bytes.extend_from_slice(&(self.chain.len() as u32).to_le_bytes());
+        for crc in &self.chain {
+            bytes.extend_from_slice(&crc.to_le_bytes());
+        }
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < MANIFEST_HEADER_SIZE || &bytes[..4] != MANIFEST_MAGIC {
+            return Err(anyhow!("Not a result store manifest"));
+        }
+        let u32_at = |offset: usize| u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+        let version = u32_at(4);
+        if version != MANIFEST_VERSION {
+            return Err(anyhow!("Unsupported manifest version {}", version));
+        }
+
+        let flags = u32_at(16);
+        let batch_count = u32_at(28) as usize;
+        if bytes.len() != MANIFEST_HEADER_SIZE + batch_count * 4 {
+            return Err(anyhow!("Manifest truncated: {} batches declared, {} bytes", batch_count, bytes.len()));
+        }
+
+        Ok(Self {
+            record_size: u32_at(8),
+            batch_records: u32_at(12),
+            clean: flags & FLAG_CLEAN != 0,
+            sorted: flags & FLAG_SORTED != 0,
+            record_count: u64::from_le_bytes(bytes[20..28].try_into().unwrap()),
+            chain: (0..batch_count).map(|i| u32_at(MANIFEST_HEADER_SIZE + i * 4)).collect(),
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::decode(&std::fs::read(path)?)
+    }
+
+    /// 先写临时文件再改名，掉电时清单本身不会写一半
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let tmp = path.with_extension("manifest.tmp");
+        std::fs::write(&tmp, self.encode())?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
+
+/// 校验报告
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IntegrityReport {
+    pub ok: bool,
+    /// 清单是否处于封存状态（上次正常结束）
+    pub clean_shutdown: bool,
+    /// 清单记录的记录数
+    pub expected_records: u64,
+    /// 校验通过的记录数（批次边界）
+    pub verified_records: u64,
+    /// 恢复时丢弃的记录数
+    pub dropped_records: u64,
+    /// 尚未写满一批、还没有校验和的记录数
+    pub unchecked_records: u64,
+    pub batches: u64,
+    pub issues: Vec<String>,
+}
+
+pub(crate) fn manifest_path(store_path: &Path) -> PathBuf {
+    let mut name = store_path.as_os_str().to_owned();
+    name.push(".manifest");
+    PathBuf::from(name)
+}
+
+/// 计算 `[start, end)` 记录串联后的校验和，同时检查记录合理性和单调性。
+/// 失败时返回出问题的记录序号和原因
+fn hash_batch(
+    data: &[u8],
+    layout: &RecordLayout,
+    (start, end): (usize, usize),
+    initial: u32,
+    last_address: &mut Option<u64>,
+    require_sorted: bool,
+) -> std::result::Result<u32, (usize, String)> {
+    let mut hasher = crc32fast::Hasher::new_with_initial(initial);
+    let mut digest = [0u8; 20];
+    for index in start..end {
+        let record = &data[index * layout.size..(index + 1) * layout.size];
+        if let Some(reason) = layout.check(record) {
+            return Err((index, reason));
+        }
+        let address = layout.address(record);
+        if require_sorted && last_address.is_some_and(|last| address < last) {
+            return Err((index, format!("address 0x{:X} out of order", address)));
+        }
+        *last_address = Some(address);
+        hasher.update(layout.digest_bytes(record, &mut digest));
+    }
+    Ok(hasher.finalize())
+}
+
+/// 按清单逐批校验 `data` 中的记录，遇到第一个不一致的批次停止，之前的批次视为可用
+pub(crate) fn verify_records(data: &[u8], manifest: &StoreManifest, layout: &RecordLayout) -> IntegrityReport {
+    let mut report = IntegrityReport {
+        clean_shutdown: manifest.clean,
+        expected_records: manifest.record_count,
+        batches: manifest.chain.len() as u64,
+        ..Default::default()
+    };
+
+    if manifest.record_size as usize != layout.size || manifest.batch_records == 0 {
+        report.issues.push(format!(
+            "manifest layout mismatch: record_size={} batch_records={}",
+            manifest.record_size, manifest.batch_records
+        ));
+        report.dropped_records = manifest.record_count;
+        return report;
+    }
+
+    let batch = manifest.batch_records as usize;
+    let count = manifest.record_count as usize;
+    if manifest.chain.len() != count.div_ceil(batch) {
+        report
+            .issues
+            .push(format!("record count {} disagrees with {} checksummed batches", count, manifest.chain.len()));
+    }
+
+    let available = data.len() / layout.size;
+    let mut verified = 0usize;
+    let mut previous = 0u32;
+    let mut last_address = None;
+    for (index, &expected) in manifest.chain.iter().enumerate() {
+        let start = index * batch;
+        let end = (start + batch).min(count);
+        if start >= end {
+            break;
+        }
+        if end > available {
+            report.issues.push(format!("store truncated: {} of {} records present", available, count));
+            break;
+        }
+
+        match hash_batch(data, layout, (start, end), previous, &mut last_address, manifest.sorted) {
+            Ok(crc) if crc == expected => {
+                previous = crc;
+                verified = end;
+            },
+            Ok(_) => {
+                report.issues.push(format!("checksum mismatch in batch {} (records {}..{})", index, start, end));
+                break;
+            },
+            Err((record, reason)) => {
+                report.issues.push(format!("record {}: {} (batch {})", record, reason, index));
+                break;
+            },
+        }
+    }
+
+    report.verified_records = verified as u64;
+    report.dropped_records = manifest.record_count.saturating_sub(verified as u64);
+    report.ok = report.issues.is_empty();
+    report
+}
+
+fn map_store(file: &File) -> Result<Option<Mmap>> {
+    if file.metadata()?.len() == 0 {
+        return Ok(None);
+    }
+    Ok(Some(unsafe { Mmap::map(file)? }))
+}
+
+/// 校验磁盘上的结果文件
+pub(crate) fn verify_store_file(store_path: &Path, layout: &RecordLayout) -> Result<(StoreManifest, IntegrityReport)> {
+    let manifest = StoreManifest::load(&manifest_path(store_path))?;
+    let file = File::open(store_path)?;
+    let mmap = map_store(&file)?;
+    let report = verify_records(mmap.as_deref().unwrap_or(&[]), &manifest, layout);
+    Ok((manifest, report))
+}
+
+/// 校验并把结果文件截断到最后一个一致的批次边界，清单同步改写为封存状态
+pub(crate) fn recover_store_file(store_path: &Path, layout: &RecordLayout) -> Result<(StoreManifest, IntegrityReport)> {
+    let (mut manifest, report) = verify_store_file(store_path, layout)?;
+    if report.ok {
+        return Ok((manifest, report));
+    }
+
+    let verified = report.verified_records;
+    let file = OpenOptions::new().write(true).open(store_path)?;
+    file.set_len(verified * layout.size as u64)?;
+    file.sync_all()?;
+
+    manifest.chain.truncate((verified as usize).div_ceil(manifest.batch_records as usize));
+    manifest.record_count = verified;
+    manifest.clean = true;
+    manifest.save(&manifest_path(store_path))?;
+
+    warn!(
+        "Recovered result store {:?}: kept {} records, dropped {} ({})",
+        store_path,
+        verified,
+        report.dropped_records,
+        report.issues.join("; ")
+    );
+    Ok((manifest, report))
+}
+
+/// 维护结果文件的清单：追加时只在写满一批时计算校验和，删除/替换等就地改写之后下次追加或封存时整体重建
+pub(crate) struct IntegrityTracker {
+    path: PathBuf,
+    layout: RecordLayout,
+    manifest: StoreManifest,
+    last_address: Option<u64>,
+    needs_rebuild: bool,
+    /// 累计计算过校验和的批次数
+    hashed_batches: u64,
+}
+
+impl IntegrityTracker {
+    pub fn new(store_path: &Path, layout: RecordLayout, batch_records: usize) -> Self {
+        Self {
+            path: manifest_path(store_path),
+            layout,
+            manifest: StoreManifest::new(&layout, batch_records),
+            last_address: None,
+            needs_rebuild: false,
+            hashed_batches: 0,
+        }
+    }
+
+    /// 接管恢复后的结果文件，清单需要已经与文件内容一致
+    pub fn adopt(store_path: &Path, layout: RecordLayout, manifest: StoreManifest, data: &[u8]) -> Self {
+        let count = manifest.record_count as usize;
+        let last_address = (count > 0).then(|| layout.address(&data[(count - 1) * layout.size..count * layout.size]));
+        Self {
+            path: manifest_path(store_path),
+            layout,
+            manifest,
+            last_address,
+            needs_rebuild: false,
+            hashed_batches: 0,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn manifest(&self) -> &StoreManifest {
+        &self.manifest
+    }
+
+    #[cfg(test)]
+    pub fn hashed_batches(&self) -> u64 {
+        self.hashed_batches
+    }
+
+    fn batch(&self) -> usize {
+        self.manifest.batch_records as usize
+    }
+
+    fn hash_next(&mut self, data: &[u8], end: usize) {
+        let start = self.manifest.record_count as usize;
+        let initial = self.manifest.chain.last().copied().unwrap_or(0);
+        match hash_batch(data, &self.layout, (start, end), initial, &mut self.last_address, self.manifest.sorted) {
+            Ok(crc) => self.manifest.chain.push(crc),
+            Err(_) => {
+                // 写入端自己产生的记录不按地址排序，这一批起不再要求单调
+                self.manifest.sorted = false;
+                let crc = hash_batch(data, &self.layout, (start, end), initial, &mut self.last_address, false).unwrap_or(0);
+                self.manifest.chain.push(crc);
+            },
+        }
+        self.manifest.record_count = end as u64;
+        self.hashed_batches += 1;
+    }
+
+    /// 追加记录后调用，`count` 为文件中的记录总数
+    pub fn on_append(&mut self, data: &[u8], count: usize) -> Result<()> {
+        let batch = self.batch();
+        let mut changed = false;
+
+        if self.needs_rebuild || count < self.manifest.record_count as usize {
+            self.manifest = StoreManifest {
+                clean: false,
+                ..StoreManifest::new(&self.layout, batch)
+            };
+            self.last_address = None;
+            self.needs_rebuild = false;
+            changed = true;
+        } else if self.manifest.clean {
+            // 封存时最后一批可能不满，继续追加前撤掉它，等写满后重新计算
+            let tail = self.manifest.record_count as usize % batch;
+            if tail != 0 {
+                self.manifest.chain.pop();
+                self.manifest.record_count -= tail as u64;
+                let covered = self.manifest.record_count as usize;
+                self.last_address = (covered > 0).then(|| self.layout.address(&data[(covered - 1) * self.layout.size..covered * self.layout.size]));
+            }
+            self.manifest.clean = false;
+            changed = true;
+        }
+
+        while count.saturating_sub(self.manifest.record_count as usize) >= batch {
+            let end = self.manifest.record_count as usize + batch;
+            self.hash_next(data, end);
+            changed = true;
+        }
+
+        if changed {
+            self.manifest.save(&self.path)?;
+        }
+        Ok(())
+    }
+
+    /// 删除、替换等就地改写之后调用，已有的校验和作废，下次追加或封存时从头重新计算
+    pub fn invalidate(&mut self) -> Result<()> {
+        if self.needs_rebuild {
+            return Ok(());
+        }
+        self.needs_rebuild = true;
+        if self.manifest.clean {
+            self.manifest.clean = false;
+            self.manifest.save(&self.path)?;
+        }
+        Ok(())
+    }
+
+    /// 一次批量操作完成：补上最后不满的一批并标记为封存
+    pub fn seal(&mut self, data: &[u8], count: usize) -> Result<()> {
+        if self.manifest.clean && !self.needs_rebuild && self.manifest.record_count as usize == count {
+            return Ok(());
+        }
+        self.on_append(data, count)?;
+        if count > self.manifest.record_count as usize {
+            self.hash_next(data, count);
+        }
+        self.manifest.clean = true;
+        self.manifest.save(&self.path)
+    }
+
+    /// 校验当前文件内容，尚未写满一批的记录只计数不校验
+    pub fn verify(&self, data: &[u8], count: usize) -> IntegrityReport {
+        let mut report = verify_records(data, &self.manifest, &self.layout);
+        report.unchecked_records = (count as u64).saturating_sub(self.manifest.record_count);
+        report
+    }
+
+    pub fn remove(&self) {
+        if self.path.exists()
+            && let Err(e) = std::fs::remove_file(&self.path)
+        {
+            warn!("Failed to remove result store manifest {:?}: {:?}", self.path, e);
+        }
+    }
+}
+
+/// 上次进程遗留的结果文件：清单已封存直接接管，未封存先校验并截断。
+/// 返回可继续使用的文件、记录数、清单和恢复报告（只有校验过才有）；没有可用记录时删除遗留文件
+pub(crate) fn reopen_leftover(store_path: &Path, layout: &RecordLayout) -> Option<(File, usize, StoreManifest, Option<IntegrityReport>)> {
+    let manifest_file = manifest_path(store_path);
+    if !store_path.exists() || !manifest_file.exists() {
+        return None;
+    }
+
+    let attempt = || -> Result<(File, usize, StoreManifest, Option<IntegrityReport>)> {
+        let manifest = StoreManifest::load(&manifest_file)?;
+        // 旧版本写入的记录布局不同，无法接管
+        if manifest.record_size as usize != layout.size {
+            return Err(anyhow!("record size {} does not match {}", manifest.record_size, layout.size));
+        }
+        let (manifest, report) = if manifest.clean {
+            (manifest, None)
+        } else {
+            let (manifest, report) = recover_store_file(store_path, layout)?;
+            (manifest, Some(report))
+        };
+        let file = OpenOptions::new().read(true).write(true).open(store_path)?;
+        Ok((file, manifest.record_count as usize, manifest, report))
+    };
+
+    match attempt() {
+        Ok((file, count, manifest, report)) if count > 0 => {
+            info!("Reopened result store {:?} with {} records", store_path, count);
+            Some((file, count, manifest, report))
+        },
+        result => {
+            if let Err(e) = result {
+                warn!("Discarding unreadable result store {:?}: {:?}", store_path, e);
+            }
+            let _ = std::fs::remove_file(store_path);
+            let _ = std::fs::remove_file(&manifest_file);
+            None
+        },
+    }
+}
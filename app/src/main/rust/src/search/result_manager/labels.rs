@@ -0,0 +1,73 @@
+//! Result labels
+//!
+//! 用户给结果加的备注（"HP"、"金币"）。改善搜索会清空结果集再写入幸存的结果，句柄随之重新分配，
+//! 所以标签按 (地址, 类型) 保存：只要地址还在结果集里，标签就跟着它。
+//!
+//! 结果集变化后把不在结果集里的标签删掉，避免无限增长；结果集为空时不清理
+//! （改善搜索先清空再写入），由显式的清空结果 / 新搜索丢弃。
+
+use crate::core::address_rebase::AddressRebase;
+use crate::search::types::ValueType;
+use anyhow::{Result, anyhow};
+use std::collections::HashMap;
+
+/// 单个标签的最大字节数
+pub const MAX_LABEL_BYTES: usize = 256;
+
+#[derive(Debug, Default, Clone)]
+pub struct ResultLabels {
+    labels: HashMap<(u64, ValueType), String>,
+}
+
+impl ResultLabels {
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// 设置标签，空字符串删除标签
+    pub fn set(&mut self, address: u64, value_type: ValueType, label: String) -> Result<()> {
+        if label.len() > MAX_LABEL_BYTES {
+            return Err(anyhow!("Label too long: {} bytes, max {}", label.len(), MAX_LABEL_BYTES));
+        }
+        if label.is_empty() {
+            self.labels.remove(&(address, value_type));
+        } else {
+            self.labels.insert((address, value_type), label);
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, address: u64, value_type: ValueType) -> Option<&str> {
+        self.labels.get(&(address, value_type)).map(String::as_str)
+    }
+
+    pub fn clear(&mut self) {
+        self.labels.clear();
+    }
+
+    /// 只保留 `present` 返回 true 的标签，返回删除的数量
+    pub fn retain<F>(&mut self, mut present: F) -> usize
+    where
+        F: FnMut(u64, ValueType) -> bool,
+    {
+        let before = self.labels.len();
+        self.labels.retain(|&(address, value_type), _| present(address, value_type));
+        before - self.labels.len()
+    }
+
+    /// 落在旧模块范围内的标签随结果一起平移到新基址
+    pub fn rebase(&mut self, rebase: &AddressRebase) {
+        self.labels = self
+            .labels
+            .drain()
+            .map(|((address, value_type), label)| ((rebase.apply(address).unwrap_or(address), value_type), label))
+            .collect();
+    }
+
+    /// 按地址升序列出所有标签，用于导出
+    pub fn entries(&self) -> Vec<(u64, ValueType, &str)> {
+        let mut entries: Vec<_> = self.labels.iter().map(|(&(address, value_type), label)| (address, value_type, label.as_str())).collect();
+        entries.sort_unstable_by_key(|&(address, value_type, _)| (address, value_type.to_id()));
+        entries
+    }
+}
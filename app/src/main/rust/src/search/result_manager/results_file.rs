@@ -0,0 +1,284 @@
+//! 结果集导出文件
+//!
+//! 游戏崩溃或进程重启后可以把缩小到的结果重新载入。文件为小端二进制：
+//!
+//! ```text
+//! magic "MAMURSLT"(8) | version(1) | mode(1) | pattern_len(4，0 表示无) | count(8) | labels_len(4) | labels | records
+//! ```
+//!
+//! - 标签记录：address(8) + value_type(1) + len(2) + UTF-8 文本（len 字节）
+//! - 精确记录：address(8) + value_type(1) + pass(1)
+//! - 模糊记录：address(8) + value(8) + value_type(1) + pass(1) + auto_types(1)
+//!
+//! 版本 1 的文件没有 labels_len 和标签，仍然可以导入。
+//! 打开时先校验头部、标签和文件长度，截断或头部损坏的文件在修改结果集之前就会报错。
+
+use super::SearchResultMode;
+use super::exact::ExactSearchResultItem;
+use super::fuzzy::FuzzySearchResultItem;
+use crate::search::types::ValueType;
+use anyhow::{Result, anyhow};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+const MAGIC: [u8; 8] = *b"MAMURSLT";
+const VERSION: u8 = 2;
+/// 没有标签的旧版本
+const VERSION_NO_LABELS: u8 = 1;
+const HEADER_SIZE: usize = 22;
+
+const LABEL_RECORD_HEADER: usize = 11;
+
+const EXACT_RECORD_SIZE: usize = 10;
+const FUZZY_RECORD_SIZE: usize = 19;
+
+/// 导出文件的头部
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResultFileHeader {
+    pub mode: SearchResultMode,
+    /// 特征码搜索结果的特征码长度
+    pub pattern_len: Option<usize>,
+    pub count: usize,
+}
+
+impl ResultFileHeader {
+    fn record_size(&self) -> usize {
+        match self.mode {
+            SearchResultMode::Exact => EXACT_RECORD_SIZE,
+            SearchResultMode::Fuzzy => FUZZY_RECORD_SIZE,
+        }
+    }
+
+    fn encode(&self) -> Result<[u8; HEADER_SIZE]> {
+        let pattern_len = u32::try_from(self.pattern_len.unwrap_or(0)).map_err(|_| anyhow!("Pattern length too large"))?;
+        let mut header = [0u8; HEADER_SIZE];
+        header[..8].copy_from_slice(&MAGIC);
+        header[8] = VERSION;
+        header[9] = match self.mode {
+            SearchResultMode::Exact => 0,
+            SearchResultMode::Fuzzy => 1,
+        };
+        header[10..14].copy_from_slice(&pattern_len.to_le_bytes());
+        header[14..22].copy_from_slice(&(self.count as u64).to_le_bytes());
+        Ok(header)
+    }
+
+    fn decode(header: &[u8; HEADER_SIZE]) -> Result<Self> {
+        if header[..8] != MAGIC {
+            return Err(anyhow!("Not a result file: bad magic"));
+        }
+        if header[8] != VERSION && header[8] != VERSION_NO_LABELS {
+            return Err(anyhow!("Unsupported result file version {}", header[8]));
+        }
+        let mode = match header[9] {
+            0 => SearchResultMode::Exact,
+            1 => SearchResultMode::Fuzzy,
+            other => return Err(anyhow!("Invalid result mode {} in result file", other)),
+        };
+        let pattern_len = u32::from_le_bytes(header[10..14].try_into().unwrap()) as usize;
+        let count = u64::from_le_bytes(header[14..22].try_into().unwrap());
+        Ok(ResultFileHeader {
+            mode,
+            pattern_len: (pattern_len > 0).then_some(pattern_len),
+            count: usize::try_from(count).map_err(|_| anyhow!("Result count {} too large", count))?,
+        })
+    }
+}
+
+fn value_type_from_record(id: u8) -> Result<ValueType> {
+    ValueType::from_id(id as i32).ok_or_else(|| anyhow!("Corrupted result file: invalid value type {}", id))
+}
+
+/// 标签段：labels_len(4) + 标签记录
+fn encode_labels(labels: &[(u64, ValueType, &str)]) -> Result<Vec<u8>> {
+    let mut bytes = vec![0u8; 4];
+    for &(address, value_type, label) in labels {
+        let len = u16::try_from(label.len()).map_err(|_| anyhow!("Label too long: {} bytes", label.len()))?;
+        bytes.extend_from_slice(&address.to_le_bytes());
+        bytes.push(value_type.to_id() as u8);
+        bytes.extend_from_slice(&len.to_le_bytes());
+        bytes.extend_from_slice(label.as_bytes());
+    }
+    let labels_len = u32::try_from(bytes.len() - 4).map_err(|_| anyhow!("Labels too large"))?;
+    bytes[..4].copy_from_slice(&labels_len.to_le_bytes());
+    Ok(bytes)
+}
+
+fn decode_labels(mut bytes: &[u8]) -> Result<Vec<(u64, ValueType, String)>> {
+    let mut labels = Vec::new();
+    while !bytes.is_empty() {
+        if bytes.len() < LABEL_RECORD_HEADER {
+            return Err(anyhow!("Corrupted result file: truncated label"));
+        }
+        let address = u64::from_le_bytes(bytes[..8].try_into().unwrap());
+        let value_type = value_type_from_record(bytes[8])?;
+        let len = u16::from_le_bytes(bytes[9..11].try_into().unwrap()) as usize;
+        let text = bytes
+            .get(LABEL_RECORD_HEADER..LABEL_RECORD_HEADER + len)
+            .ok_or_else(|| anyhow!("Corrupted result file: truncated label"))?;
+        let label = String::from_utf8(text.to_vec()).map_err(|_| anyhow!("Corrupted result file: label is not UTF-8"))?;
+        labels.push((address, value_type, label));
+        bytes = &bytes[LABEL_RECORD_HEADER + len..];
+    }
+    Ok(labels)
+}
+
+/// 写入导出文件，记录数必须与头部一致
+pub(crate) struct ResultFileWriter {
+    writer: BufWriter<File>,
+    header: ResultFileHeader,
+    written: usize,
+}
+
+impl ResultFileWriter {
+    /// 创建文件并写入头部和标签，之后按头部的模式写入记录
+    pub fn create(path: &Path, header: ResultFileHeader, labels: &[(u64, ValueType, &str)]) -> Result<Self> {
+        let labels = encode_labels(labels)?;
+        let file = File::create(path).map_err(|e| anyhow!("Failed to create {:?}: {}", path, e))?;
+        let mut writer = BufWriter::new(file);
+        writer.write_all(&header.encode()?)?;
+        writer.write_all(&labels)?;
+        Ok(ResultFileWriter { writer, header, written: 0 })
+    }
+
+    pub fn write_exact(&mut self, items: &[ExactSearchResultItem]) -> Result<()> {
+        let mut record = [0u8; EXACT_RECORD_SIZE];
+        for item in items {
+            record[..8].copy_from_slice(&item.address.to_le_bytes());
+            record[8] = item.typ.to_id() as u8;
+            record[9] = item.pass;
+            self.writer.write_all(&record)?;
+        }
+        self.written += items.len();
+        Ok(())
+    }
+
+    pub fn write_fuzzy(&mut self, items: &[FuzzySearchResultItem]) -> Result<()> {
+        let mut record = [0u8; FUZZY_RECORD_SIZE];
+        for item in items {
+            let address = item.address;
+            let value = item.value;
+            let value_type = item.value_type;
+            record[..8].copy_from_slice(&address.to_le_bytes());
+            record[8..16].copy_from_slice(&value);
+            record[16] = value_type.to_id() as u8;
+            record[17] = item.pass;
+            record[18] = item.auto_types;
+            self.writer.write_all(&record)?;
+        }
+        self.written += items.len();
+        Ok(())
+    }
+
+    pub fn finish(self) -> Result<usize> {
+        if self.written != self.header.count {
+            return Err(anyhow!(
+                "Result set changed during export: expected {}, wrote {}",
+                self.header.count,
+                self.written
+            ));
+        }
+        let file = self.writer.into_inner().map_err(|e| anyhow!("Failed to flush result file: {:?}", e))?;
+        file.sync_all()?;
+        Ok(self.written)
+    }
+}
+
+/// 读取导出文件
+pub(crate) struct ResultFileReader {
+    reader: BufReader<File>,
+    header: ResultFileHeader,
+    labels: Vec<(u64, ValueType, String)>,
+    remaining: usize,
+}
+
+impl ResultFileReader {
+    /// 打开并校验头部、标签和文件长度
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path).map_err(|e| anyhow!("Failed to open {:?}: {}", path, e))?;
+        let file_len = file.metadata()?.len();
+        let mut reader = BufReader::new(file);
+
+        let mut raw = [0u8; HEADER_SIZE];
+        reader.read_exact(&mut raw).map_err(|_| anyhow!("Result file is truncated: missing header"))?;
+        let header = ResultFileHeader::decode(&raw)?;
+
+        let mut prefix_len = HEADER_SIZE as u64;
+        let mut labels = Vec::new();
+        if raw[8] != VERSION_NO_LABELS {
+            let mut labels_len = [0u8; 4];
+            reader.read_exact(&mut labels_len).map_err(|_| anyhow!("Result file is truncated: missing labels"))?;
+            let labels_len = u32::from_le_bytes(labels_len) as u64;
+            prefix_len += 4 + labels_len;
+            if prefix_len > file_len {
+                return Err(anyhow!("Result file is truncated: labels need {} bytes", labels_len));
+            }
+            let mut raw_labels = vec![0u8; labels_len as usize];
+            reader.read_exact(&mut raw_labels)?;
+            labels = decode_labels(&raw_labels)?;
+        }
+
+        let expected = (header.count as u64)
+            .checked_mul(header.record_size() as u64)
+            .and_then(|len| len.checked_add(prefix_len))
+            .ok_or_else(|| anyhow!("Result count {} too large", header.count))?;
+        if file_len != expected {
+            return Err(anyhow!(
+                "Result file size mismatch: expected {} bytes for {} results, got {}",
+                expected,
+                header.count,
+                file_len
+            ));
+        }
+
+        Ok(ResultFileReader {
+            reader,
+            header,
+            labels,
+            remaining: header.count,
+        })
+    }
+
+    pub fn header(&self) -> ResultFileHeader {
+        self.header
+    }
+
+    /// 文件中的标签，版本 1 的文件为空
+    pub fn take_labels(&mut self) -> Vec<(u64, ValueType, String)> {
+        std::mem::take(&mut self.labels)
+    }
+
+    /// 读取最多 `max` 条精确记录，读完时返回空
+    pub fn read_exact_batch(&mut self, max: usize) -> Result<Vec<ExactSearchResultItem>> {
+        let n = max.min(self.remaining);
+        let mut items = Vec::with_capacity(n);
+        let mut record = [0u8; EXACT_RECORD_SIZE];
+        for _ in 0..n {
+            self.reader.read_exact(&mut record)?;
+            let address = u64::from_le_bytes(record[..8].try_into().unwrap());
+            let value_type = value_type_from_record(record[8])?;
+            items.push(ExactSearchResultItem::new(address, value_type).with_pass(record[9]));
+        }
+        self.remaining -= n;
+        Ok(items)
+    }
+
+    /// 读取最多 `max` 条模糊记录，读完时返回空
+    pub fn read_fuzzy_batch(&mut self, max: usize) -> Result<Vec<FuzzySearchResultItem>> {
+        let n = max.min(self.remaining);
+        let mut items = Vec::with_capacity(n);
+        let mut record = [0u8; FUZZY_RECORD_SIZE];
+        for _ in 0..n {
+            self.reader.read_exact(&mut record)?;
+            let address = u64::from_le_bytes(record[..8].try_into().unwrap());
+            let value_type = value_type_from_record(record[16])?;
+            let item = FuzzySearchResultItem::from_bytes(address, &record[8..16], value_type)
+                .with_pass(record[17])
+                .with_auto_types(record[18]);
+            items.push(item);
+        }
+        self.remaining -= n;
+        Ok(items)
+    }
+}
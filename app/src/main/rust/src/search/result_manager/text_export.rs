@@ -0,0 +1,165 @@
+//! 结果集的文本导出
+//!
+//! 导出到表格或分享给别人。每个结果一行：序号、十六进制地址、值类型 id，模糊结果还有保存的值，
+//! 格式与结果列表相同。Xor 结果写出解码后的值；长于 8 字节的变长值只保存了摘要，值留空。
+//!
+//! ```text
+//! CSV:         index,address,type,value
+//!              0,0x7A00001000,2,100
+//! JSON Lines:  {"index":0,"address":"0x7A00001000","type":2,"value":"100"}
+//! ```
+
+use super::exact::ExactSearchResultItem;
+use super::fuzzy::FuzzySearchResultItem;
+use crate::search::format::format_value;
+use crate::search::types::{ValueType, XorKey};
+use anyhow::{Result, anyhow};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// 文本导出的格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(i32)]
+pub enum ResultExportFormat {
+    #[default]
+    Csv = 0,
+    JsonLines = 1,
+}
+
+impl ResultExportFormat {
+    pub const ALL: [ResultExportFormat; 2] = [Self::Csv, Self::JsonLines];
+
+    pub fn from_id(id: i32) -> Option<Self> {
+        Self::ALL.into_iter().find(|format| *format as i32 == id)
+    }
+}
+
+/// 逐批写出结果行，记录数必须与创建时给出的一致
+pub(crate) struct TextExportWriter {
+    writer: BufWriter<File>,
+    format: ResultExportFormat,
+    /// 变长值的长度，超过 8 字节时保存的是摘要
+    pattern_len: Option<usize>,
+    xor_key: XorKey,
+    expected: usize,
+    written: usize,
+    value: String,
+}
+
+impl TextExportWriter {
+    /// 创建文件，CSV 先写表头；`with_value` 为 false 时（精确结果）没有值这一列
+    pub fn create(
+        path: &Path,
+        format: ResultExportFormat,
+        with_value: bool,
+        pattern_len: Option<usize>,
+        xor_key: XorKey,
+        expected: usize,
+    ) -> Result<Self> {
+        let file = File::create(path).map_err(|e| anyhow!("Failed to create {:?}: {}", path, e))?;
+        let mut writer = BufWriter::new(file);
+        if format == ResultExportFormat::Csv {
+            let header: &[u8] = if with_value { b"index,address,type,value\n" } else { b"index,address,type\n" };
+            writer.write_all(header)?;
+        }
+        Ok(TextExportWriter {
+            writer,
+            format,
+            pattern_len,
+            xor_key,
+            expected,
+            written: 0,
+            value: String::new(),
+        })
+    }
+
+    pub fn write_exact(&mut self, items: &[ExactSearchResultItem]) -> Result<()> {
+        for item in items {
+            let index = self.written;
+            match self.format {
+                ResultExportFormat::Csv => writeln!(self.writer, "{},0x{:X},{}", index, item.address, item.typ.to_id())?,
+                ResultExportFormat::JsonLines => writeln!(
+                    self.writer,
+                    r#"{{"index":{},"address":"0x{:X}","type":{}}}"#,
+                    index,
+                    item.address,
+                    item.typ.to_id()
+                )?,
+            }
+            self.written += 1;
+        }
+        Ok(())
+    }
+
+    pub fn write_fuzzy(&mut self, items: &[FuzzySearchResultItem]) -> Result<()> {
+        for item in items {
+            // 先拷贝 packed 字段
+            let address = item.address;
+            let value_type = item.value_type;
+            let has_value = self.format_stored_value(item);
+            let index = self.written;
+            match self.format {
+                ResultExportFormat::Csv => {
+                    write!(self.writer, "{},0x{:X},{},", index, address, value_type.to_id())?;
+                    write_csv_field(&mut self.writer, &self.value)?;
+                    self.writer.write_all(b"\n")?;
+                },
+                ResultExportFormat::JsonLines => {
+                    let value = if has_value { serde_json::to_string(&self.value)? } else { "null".to_string() };
+                    writeln!(
+                        self.writer,
+                        r#"{{"index":{},"address":"0x{:X}","type":{},"value":{}}}"#,
+                        index,
+                        address,
+                        value_type.to_id(),
+                        value
+                    )?;
+                },
+            }
+            self.written += 1;
+        }
+        Ok(())
+    }
+
+    /// 把保存的值格式化到 `self.value`，只有摘要时清空并返回 false
+    fn format_stored_value(&mut self, item: &FuzzySearchResultItem) -> bool {
+        let address = item.address;
+        let mut value = item.value;
+        let value_type = item.value_type;
+        if value_type.is_variable_len() {
+            match self.pattern_len {
+                Some(len) if len <= value.len() => format_value(&mut self.value, &value[..len], value_type),
+                _ => {
+                    self.value.clear();
+                    return false;
+                },
+            }
+            return true;
+        }
+        if value_type == ValueType::Xor {
+            self.xor_key.apply(&mut value, address);
+        }
+        format_value(&mut self.value, &value, value_type);
+        true
+    }
+
+    pub fn finish(self) -> Result<usize> {
+        if self.written != self.expected {
+            return Err(anyhow!("Result set changed during export: expected {}, wrote {}", self.expected, self.written));
+        }
+        let file = self.writer.into_inner().map_err(|e| anyhow!("Failed to flush export file: {:?}", e))?;
+        file.sync_all()?;
+        Ok(self.written)
+    }
+}
+
+/// 含逗号、引号或换行的字段加引号，引号写两次
+fn write_csv_field<W: Write>(writer: &mut W, field: &str) -> std::io::Result<()> {
+    if !field.contains([',', '"', '\n', '\r']) {
+        return writer.write_all(field.as_bytes());
+    }
+    writer.write_all(b"\"")?;
+    writer.write_all(field.replace('"', "\"\"").as_bytes())?;
+    writer.write_all(b"\"")
+}
@@ -0,0 +1,242 @@
+//! Refine undo slot
+//!
+//! 改善搜索是破坏性的：一个错误的条件就会丢掉缩小了很久的结果集。改善替换结果之前，
+//! 当前结果集整体移入撤销槽，撤销时换回来，只保留一层。
+//!
+//! 结果存储的磁盘部分通过改名移出（`*.undo`），不复制；之后的写入从新文件开始。撤销文件由
+//! `StashedFile` 持有，撤销槽被丢弃（新的改善 / 新的搜索 / 管理器销毁）时删除，
+//! 结果存储的 Drop 只删除自己当前的文件，不会提前删掉撤销文件。
+//!
+//! 就地细化会改写存储，不能改名移出。它在写回每一段之前把该段的旧值追加到日志（`RefineJournal`），
+//! 写日志不持有管理器的锁；细化完成时日志就是整个旧结果集，改名为撤销文件，取消时用它把已写回的段改回去。
+
+use crate::search::result_manager::byte_hits::ByteHitSet;
+use crate::search::result_manager::exact::ExactSearchResultItem;
+use crate::search::result_manager::fuzzy::FuzzySearchResultItem;
+use crate::search::result_manager::labels::ResultLabels;
+use crate::search::result_manager::value_cache::ExactValueCache;
+use crate::search::result_manager::SearchResultMode;
+use anyhow::{Result, anyhow};
+use log::{debug, warn};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+fn undo_path(store_path: &Path) -> PathBuf {
+    let mut name = store_path.as_os_str().to_owned();
+    name.push(".undo");
+    PathBuf::from(name)
+}
+
+fn journal_path(store_path: &Path) -> PathBuf {
+    let mut name = store_path.as_os_str().to_owned();
+    name.push(".journal");
+    PathBuf::from(name)
+}
+
+/// 删除上次进程遗留的撤销文件和细化日志，撤销槽不跨进程保留
+pub(crate) fn remove_leftover(store_path: &Path) {
+    for path in [undo_path(store_path), journal_path(store_path)] {
+        if path.exists()
+            && let Err(e) = std::fs::remove_file(&path)
+        {
+            warn!("Failed to remove leftover undo file {:?}: {:?}", path, e);
+        }
+    }
+}
+
+/// 撤销槽持有的结果文件，Drop 时删除
+pub struct StashedFile {
+    /// 换回之后为 None
+    path: Option<PathBuf>,
+}
+
+impl StashedFile {
+    /// 把结果文件改名为撤销文件，调用方随后释放映射
+    pub fn move_from(store_path: &Path) -> Result<Self> {
+        let path = undo_path(store_path);
+        std::fs::rename(store_path, &path)?;
+        debug!("Stashed result store {:?} as {:?}", store_path, path);
+        Ok(Self { path: Some(path) })
+    }
+
+    /// 改名为 `store_path` 的撤销文件，之前的撤销槽需要已经丢弃
+    fn move_to_undo(mut self, store_path: &Path) -> Result<Self> {
+        let Some(path) = self.path.take() else {
+            return Err(anyhow!("Undo file already restored"));
+        };
+        let target = undo_path(store_path);
+        std::fs::rename(&path, &target)?;
+        Ok(Self { path: Some(target) })
+    }
+
+    /// 改名回结果文件并打开，结果文件需要已经删除
+    pub fn restore(mut self, store_path: &Path) -> Result<File> {
+        let Some(path) = self.path.take() else {
+            return Err(anyhow!("Undo file already restored"));
+        };
+        std::fs::rename(&path, store_path)?;
+        Ok(OpenOptions::new().read(true).write(true).open(store_path)?)
+    }
+}
+
+impl Drop for StashedFile {
+    fn drop(&mut self) {
+        if let Some(ref path) = self.path
+            && path.exists()
+            && let Err(e) = std::fs::remove_file(path)
+        {
+            warn!("Failed to remove undo file {:?}: {:?}", path, e);
+        }
+    }
+}
+
+/// 从一个结果存储移出的全部结果：内存部分和磁盘文件
+pub struct StashedStore<T> {
+    pub memory: Vec<T>,
+    pub disk: Option<StashedFile>,
+    pub disk_count: usize,
+    pub total_count: usize,
+}
+
+pub enum StashedResults {
+    Exact(StashedStore<ExactSearchResultItem>),
+    Fuzzy(StashedStore<FuzzySearchResultItem>),
+}
+
+impl StashedResults {
+    pub fn mode(&self) -> SearchResultMode {
+        match self {
+            StashedResults::Exact(_) => SearchResultMode::Exact,
+            StashedResults::Fuzzy(_) => SearchResultMode::Fuzzy,
+        }
+    }
+}
+
+/// 最近一次改善之前的结果集，以及随结果集变化的派生状态
+pub struct PreviousResults {
+    pub results: StashedResults,
+    pub byte_hits: Option<ByteHitSet>,
+    pub exact_values: ExactValueCache,
+    pub labels: ResultLabels,
+    pub current_pass: u8,
+}
+
+/// 回放日志时每次读取的结果数
+const JOURNAL_READ_ITEMS: usize = 64 * 1024;
+
+/// 就地细化的旧值日志
+///
+/// 细化从第 0 项开始按段顺序写回，每段写回之前调用 `record` 记下该段的旧值：前 `memory_count`
+/// 项留在内存，其余追加到日志文件，内容与结果存储的磁盘部分逐字节相同。
+/// 日志被丢弃（未提交）时删除文件。
+pub struct RefineJournal {
+    store_path: PathBuf,
+    memory_count: usize,
+    total_count: usize,
+    memory: Vec<FuzzySearchResultItem>,
+    writer: Option<BufWriter<File>>,
+    file: StashedFile,
+    recorded: usize,
+}
+
+impl RefineJournal {
+    const ITEM_SIZE: usize = size_of::<FuzzySearchResultItem>();
+
+    /// `memory_count` / `total_count` 是细化开始时结果存储内存部分和全部的结果数
+    pub fn new(store_path: &Path, memory_count: usize, total_count: usize) -> Self {
+        Self {
+            store_path: store_path.to_path_buf(),
+            memory_count,
+            total_count,
+            memory: Vec::with_capacity(memory_count),
+            writer: None,
+            file: StashedFile { path: Some(journal_path(store_path)) },
+            recorded: 0,
+        }
+    }
+
+    /// 已经记下的结果数，即已经（或即将）写回的前缀长度
+    pub fn recorded(&self) -> usize {
+        self.recorded
+    }
+
+    /// 追加紧接着已记录部分的一段旧值
+    pub fn record(&mut self, items: &[FuzzySearchResultItem]) -> Result<()> {
+        if self.recorded + items.len() > self.total_count {
+            return Err(anyhow!("Refine journal overflow: {} + {} > {}", self.recorded, items.len(), self.total_count));
+        }
+        let in_memory = self.memory_count.saturating_sub(self.recorded).min(items.len());
+        self.memory.extend_from_slice(&items[..in_memory]);
+
+        let on_disk = &items[in_memory..];
+        if !on_disk.is_empty() {
+            let writer = match self.writer {
+                Some(ref mut writer) => writer,
+                None => {
+                    let path = self.file.path.as_ref().ok_or_else(|| anyhow!("Refine journal already committed"))?;
+                    self.writer.insert(BufWriter::new(File::create(path)?))
+                },
+            };
+            // 记录是 packed 的，可以直接按字节写出
+            let bytes = unsafe { std::slice::from_raw_parts(on_disk.as_ptr() as *const u8, on_disk.len() * Self::ITEM_SIZE) };
+            writer.write_all(bytes)?;
+        }
+        self.recorded += items.len();
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        if let Some(mut writer) = self.writer.take() {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+
+    /// 按存储顺序回放已记下的旧值，`apply` 收到一段的起始索引和旧值
+    pub fn replay<F>(mut self, mut apply: F) -> Result<()>
+    where
+        F: FnMut(usize, &[FuzzySearchResultItem]) -> Result<()>,
+    {
+        self.flush()?;
+        apply(0, &self.memory)?;
+
+        let disk_recorded = self.recorded - self.memory.len();
+        if disk_recorded == 0 {
+            return Ok(());
+        }
+        let path = self.file.path.as_ref().ok_or_else(|| anyhow!("Refine journal already committed"))?;
+        let mut file = File::open(path)?;
+        let mut buffer = vec![0u8; JOURNAL_READ_ITEMS * Self::ITEM_SIZE];
+        let mut base = self.memory.len();
+        let mut remaining = disk_recorded;
+        while remaining > 0 {
+            let count = remaining.min(JOURNAL_READ_ITEMS);
+            let bytes = &mut buffer[..count * Self::ITEM_SIZE];
+            file.read_exact(bytes)?;
+            let items = unsafe { std::slice::from_raw_parts(bytes.as_ptr() as *const FuzzySearchResultItem, count) };
+            apply(base, items)?;
+            base += count;
+            remaining -= count;
+        }
+        Ok(())
+    }
+
+    /// 细化完成、整个结果集都已记下时转为撤销槽的存储，日志文件改名为撤销文件
+    pub fn into_stashed(mut self) -> Result<StashedStore<FuzzySearchResultItem>> {
+        if self.recorded != self.total_count {
+            return Err(anyhow!("Refine journal is incomplete: {} / {}", self.recorded, self.total_count));
+        }
+        self.flush()?;
+        let disk_count = self.total_count - self.memory.len();
+        let file = std::mem::replace(&mut self.file, StashedFile { path: None });
+        let disk = if disk_count > 0 { Some(file.move_to_undo(&self.store_path)?) } else { None };
+        debug!("Refine journal kept {} memory and {} disk results for undo", self.memory.len(), disk_count);
+        Ok(StashedStore {
+            memory: std::mem::take(&mut self.memory),
+            disk,
+            disk_count,
+            total_count: self.total_count,
+        })
+    }
+}
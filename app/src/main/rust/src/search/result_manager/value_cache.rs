@@ -0,0 +1,209 @@
+//! Matched values of exact results
+//!
+//! 精确结果只存地址和类型，翻页时逐个读取当前内存来显示值，列表滚动慢，显示的也不是匹配时的值。
+//! 扫描排序去重之后（与兼容模式的值捕获在同一时机）按页批量读取一次结果的值保存在这里，
+//! 显示时优先使用；用户下拉刷新时只重新读取可见的一页并更新。
+//!
+//! 按地址查找，改善搜索删掉的结果留在缓存里不影响查找；清空结果时一起清空。
+//! 值的总大小超过 `VALUE_CACHE_MAX_BYTES` 时不缓存，显示退回读取当前内存。
+
+use crate::search::engine::cancel::CANCEL_CHECK_CANDIDATES;
+use crate::search::PAGE_MASK;
+use anyhow::Result;
+use rayon::prelude::*;
+
+/// 缓存的地址和值占用的内存上限
+pub const VALUE_CACHE_MAX_BYTES: usize = 64 * 1024 * 1024;
+
+#[derive(Debug, Default)]
+pub struct ExactValueCache {
+    /// 每个值占用的字节数，为本次结果中最长的值
+    width: usize,
+    /// 按地址升序
+    addrs: Vec<u64>,
+    values: Vec<u8>,
+}
+
+impl ExactValueCache {
+    /// 读取 entries（(地址, 值长度)）的值，读取失败的地址不缓存
+    ///
+    /// 同一地址的多个结果（如自动类型的 Dword 和 Float）只读取一次，长度取最大值。
+    /// 超出内存上限或读取中途取消时返回 None。
+    pub fn capture<R, F>(entries: &[(u64, usize)], read: R, check_cancelled: &F) -> Option<Self>
+    where
+        R: Fn(u64, &mut [u8]) -> Result<()> + Sync,
+        F: Fn() -> bool + Sync,
+    {
+        let mut sorted;
+        let entries = if entries.is_sorted_by_key(|&(addr, _)| addr) {
+            entries
+        } else {
+            sorted = entries.to_vec();
+            sorted.par_sort_unstable();
+            &sorted[..]
+        };
+
+        let mut merged: Vec<(u64, usize)> = Vec::with_capacity(entries.len());
+        for &(addr, len) in entries {
+            match merged.last_mut() {
+                Some(last) if last.0 == addr => last.1 = last.1.max(len),
+                _ => merged.push((addr, len)),
+            }
+        }
+        let width = merged.iter().map(|&(_, len)| len).max().unwrap_or(0);
+        if merged.is_empty() || width == 0 || merged.len().saturating_mul(8 + width) > VALUE_CACHE_MAX_BYTES {
+            return None;
+        }
+
+        let page_mask = *PAGE_MASK as u64;
+        let chunks: Vec<(Vec<u64>, Vec<u8>)> = merged
+            .par_chunks(CANCEL_CHECK_CANDIDATES)
+            .map(|chunk| {
+                let mut addrs = Vec::with_capacity(chunk.len());
+                let mut values = Vec::with_capacity(chunk.len() * width);
+                if check_cancelled() {
+                    return (addrs, values);
+                }
+                let mut buffer = Vec::new();
+                for group in chunk.chunk_by(|a, b| a.0 & page_mask == b.0 & page_mask) {
+                    read_page_group(group, width, &read, &mut buffer, &mut addrs, &mut values);
+                }
+                (addrs, values)
+            })
+            .collect();
+        if check_cancelled() {
+            return None;
+        }
+
+        let mut cache = Self { width, addrs: Vec::with_capacity(merged.len()), values: Vec::with_capacity(merged.len() * width) };
+        for (addrs, values) in chunks {
+            cache.addrs.extend(addrs);
+            cache.values.extend(values);
+        }
+        Some(cache)
+    }
+
+    /// 地址的缓存值，长度为 `width`，调用方按类型截取
+    pub fn get(&self, addr: u64) -> Option<&[u8]> {
+        let index = self.addrs.binary_search(&addr).ok()?;
+        Some(&self.values[index * self.width..(index + 1) * self.width])
+    }
+
+    /// 用重新读取的值更新已缓存的地址，未缓存的地址返回 false
+    pub fn update(&mut self, addr: u64, bytes: &[u8]) -> bool {
+        let Ok(index) = self.addrs.binary_search(&addr) else {
+            return false;
+        };
+        let len = bytes.len().min(self.width);
+        self.values[index * self.width..index * self.width + len].copy_from_slice(&bytes[..len]);
+        true
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn len(&self) -> usize {
+        self.addrs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.addrs.is_empty()
+    }
+}
+
+/// 一次读取覆盖同一页上的一组地址，失败时逐个读取
+fn read_page_group<R>(group: &[(u64, usize)], width: usize, read: &R, buffer: &mut Vec<u8>, addrs: &mut Vec<u64>, values: &mut Vec<u8>)
+where
+    R: Fn(u64, &mut [u8]) -> Result<()>,
+{
+    if group.len() > 1 {
+        let start = group[0].0;
+        let end = group.iter().map(|&(addr, len)| addr + len as u64).max().unwrap_or(start);
+        buffer.resize((end - start) as usize, 0);
+        if read(start, buffer).is_ok() {
+            for &(addr, len) in group {
+                let offset = (addr - start) as usize;
+                addrs.push(addr);
+                values.extend_from_slice(&buffer[offset..offset + len]);
+                values.resize(values.len() + width - len, 0);
+            }
+            return;
+        }
+    }
+
+    for &(addr, len) in group {
+        buffer.resize(len, 0);
+        if read(addr, buffer).is_ok() {
+            addrs.push(addr);
+            values.extend_from_slice(buffer);
+            values.resize(values.len() + width - len, 0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const BASE: u64 = 0x7000_0000;
+    const BAD: u64 = BASE + 0x2000;
+
+    /// 内存内容为地址的低字节；BAD 所在的页不可读
+    fn read(addr: u64, buf: &mut [u8]) -> Result<()> {
+        let page_mask = *PAGE_MASK as u64;
+        if (addr & page_mask..addr + buf.len() as u64).contains(&BAD) {
+            return Err(anyhow!("unreadable"));
+        }
+        for (i, byte) in buf.iter_mut().enumerate() {
+            *byte = (addr as usize + i) as u8;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_capture_and_lookup() {
+        // 乱序输入（如特征码搜索的结果）也能查找
+        let entries = vec![(BASE + 0x3000, 4), (BASE, 4), (BASE, 8), (BASE + 0x10, 2), (BAD, 4), (BAD + 0x10, 4)];
+        let cache = ExactValueCache::capture(&entries, read, &|| false).unwrap();
+
+        assert_eq!(cache.width(), 8);
+        assert_eq!(cache.len(), 3);
+        assert_eq!(cache.get(BASE), Some(&[0, 1, 2, 3, 4, 5, 6, 7][..]));
+        assert_eq!(cache.get(BASE + 0x10), Some(&[0x10, 0x11, 0, 0, 0, 0, 0, 0][..]));
+        // 不可读页上的结果不缓存，显示时退回读取当前内存
+        assert_eq!(cache.get(BAD), None);
+        assert_eq!(cache.get(BAD + 0x10), None);
+        assert!(cache.get(BASE + 0x3000).is_some());
+        assert_eq!(cache.get(BASE + 0x4000), None);
+    }
+
+    #[test]
+    fn test_update_only_cached_addresses() {
+        let mut cache = ExactValueCache::capture(&[(BASE, 16)], read, &|| false).unwrap();
+        assert_eq!(cache.width(), 16);
+
+        assert!(cache.update(BASE, &[0xAA; 4]));
+        assert_eq!(&cache.get(BASE).unwrap()[..6], &[0xAA, 0xAA, 0xAA, 0xAA, 4, 5]);
+        assert!(!cache.update(BASE + 0x100, &[0xAA; 4]));
+    }
+
+    #[test]
+    fn test_capture_limits() {
+        let reads = AtomicUsize::new(0);
+        let counting_read = |addr: u64, buf: &mut [u8]| {
+            reads.fetch_add(1, Ordering::Relaxed);
+            read(addr, buf)
+        };
+
+        // 超出内存上限时不读取
+        let huge = vec![(BASE, VALUE_CACHE_MAX_BYTES)];
+        assert!(ExactValueCache::capture(&huge, counting_read, &|| false).is_none());
+        assert_eq!(reads.load(Ordering::Relaxed), 0);
+
+        assert!(ExactValueCache::capture(&[(BASE, 4)], counting_read, &|| true).is_none());
+        assert!(ExactValueCache::capture(&[], counting_read, &|| false).is_none());
+    }
+}
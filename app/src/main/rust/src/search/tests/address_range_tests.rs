@@ -0,0 +1,145 @@
+//! Address range lookup tests
+//!
+//! 按地址范围查找结果：结果跨越内存缓冲区和磁盘、由多次扫描追加成多个升序段、升序段过多退化为
+//! 顺序扫描时，返回的结果都按地址升序、索引正确，超过上限时截断并标记；结果集变化后不使用旧的升序段。
+//! 按地址查找索引时，不在结果集中的地址返回插入位置，结果为空或都在地址之前时返回结果总数。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::result_manager::cursor::MAX_MERGE_RUNS;
+    use crate::search::result_manager::{FuzzySearchResultItem, SearchResultManager, SearchResultMode};
+    use crate::search::{SearchResultItem, ValueType};
+    use crate::search::tests::result_fixture::{memory_buffer_for, split_result_manager, MEMORY_ITEMS};
+    use crate::search::tests::temp_dir::TempDir;
+
+    const BASE: u64 = 0x7A00000000;
+
+    fn address(item: &SearchResultItem) -> u64 {
+        match item {
+            SearchResultItem::Exact(exact) => exact.address,
+            SearchResultItem::Fuzzy(fuzzy) => fuzzy.address,
+        }
+    }
+
+    /// 范围内结果的 (索引, 地址) 和是否截断
+    fn lookup(mgr: &SearchResultManager, start: u64, end: u64, max_count: usize) -> (Vec<(usize, u64)>, bool) {
+        let range = mgr.get_results_in_range(BASE + start, BASE + end, max_count).unwrap();
+        let items = range.items.iter().map(|(index, item)| (*index, address(item) - BASE)).collect();
+        (items, range.truncated)
+    }
+
+    /// 每个返回的索引在存储中确实是这个地址
+    fn assert_indices_match(mgr: &SearchResultManager, items: &[(usize, u64)]) {
+        for &(index, offset) in items {
+            assert_eq!(address(&mgr.get_results(index, 1).unwrap()[0]), BASE + offset);
+        }
+    }
+
+    #[test]
+    fn test_range_across_memory_disk_and_runs() {
+        let dir = TempDir::new("address_range_runs");
+        let mut mgr = split_result_manager(&dir, SearchResultMode::Exact, MEMORY_ITEMS);
+        // 第一段每 0x10 一条，一半在内存一半在磁盘；保留结果的扫描追加交错的第二段
+        mgr.add_results_batch((0..10).map(|i| SearchResultItem::new_exact(BASE + i * 0x10, ValueType::Dword)).collect()).unwrap();
+        mgr.add_results_batch((0..10).map(|i| SearchResultItem::new_exact(BASE + i * 0x10 + 8, ValueType::Dword)).collect()).unwrap();
+
+        // 结束地址不包含，起始地址落在两条结果之间
+        let (items, truncated) = lookup(&mgr, 0x24, 0x50, 100);
+        assert_eq!(items.iter().map(|&(_, offset)| offset).collect::<Vec<_>>(), vec![0x28, 0x30, 0x38, 0x40, 0x48]);
+        assert!(!truncated);
+        assert_indices_match(&mgr, &items);
+
+        // 超过上限时只返回地址最小的部分
+        let (items, truncated) = lookup(&mgr, 0, 0x1000, 3);
+        assert_eq!(items.iter().map(|&(_, offset)| offset).collect::<Vec<_>>(), vec![0, 8, 0x10]);
+        assert!(truncated);
+        let (items, truncated) = lookup(&mgr, 0, 0x1000, 20);
+        assert_eq!(items.len(), 20);
+        assert!(!truncated);
+        assert_indices_match(&mgr, &items);
+
+        // 多个升序段时取地址最小的候选：0x28 在第二段
+        assert_eq!(mgr.find_index_by_address(BASE + 0x24).unwrap(), 12);
+        assert_eq!(mgr.find_index_by_address(BASE + 0x20).unwrap(), 2);
+
+        // 空范围、范围外、上限为 0
+        assert_eq!(lookup(&mgr, 0x50, 0x50, 10), (Vec::new(), false));
+        assert_eq!(lookup(&mgr, 0x1000, 0x2000, 10), (Vec::new(), false));
+        assert_eq!(lookup(&mgr, 0, 0x10, 0), (Vec::new(), true));
+
+        // 删除结果后索引随之移动
+        mgr.remove_result(0).unwrap();
+        let (items, _) = lookup(&mgr, 0, 0x20, 10);
+        assert_eq!(items.iter().map(|&(_, offset)| offset).collect::<Vec<_>>(), vec![8, 0x10, 0x18]);
+        assert_indices_match(&mgr, &items);
+
+    }
+
+    #[test]
+    fn test_range_fuzzy_results() {
+        let dir = TempDir::new("address_range_fuzzy");
+        let mut mgr = split_result_manager(&dir, SearchResultMode::Fuzzy, MEMORY_ITEMS);
+        let fuzzy = (0..16).map(|i| FuzzySearchResultItem::new(BASE + i * 4, i.to_le_bytes(), ValueType::Dword)).collect();
+        mgr.add_fuzzy_results_batch(fuzzy).unwrap();
+
+        let range = mgr.get_results_in_range(BASE + 0x10, BASE + 0x20, 100).unwrap();
+        assert!(!range.truncated);
+        let values: Vec<(usize, u64)> = range
+            .items
+            .iter()
+            .map(|(index, item)| match item {
+                SearchResultItem::Fuzzy(fuzzy) => (*index, u64::from_le_bytes(fuzzy.value)),
+                SearchResultItem::Exact(_) => panic!("Expected a fuzzy result"),
+            })
+            .collect();
+        assert_eq!(values, (4..8).map(|i| (i, i as u64)).collect::<Vec<_>>());
+
+    }
+
+    #[test]
+    fn test_range_with_too_many_runs() {
+        let dir = TempDir::new("address_range_scan");
+        let mut mgr = split_result_manager(&dir, SearchResultMode::Exact, MEMORY_ITEMS);
+        // 地址降序，每条结果自成一段
+        let count = MAX_MERGE_RUNS as u64 + 10;
+        mgr.add_results_batch((0..count).rev().map(|i| SearchResultItem::new_exact(BASE + i * 4, ValueType::Dword)).collect()).unwrap();
+        assert_eq!(mgr.cursor(16).unwrap().run_count(), 0);
+
+        let (items, truncated) = lookup(&mgr, 0x20, 0x40, 100);
+        assert_eq!(items.iter().map(|&(_, offset)| offset).collect::<Vec<_>>(), (8..16).map(|i| i * 4).collect::<Vec<_>>());
+        assert!(!truncated);
+        assert_indices_match(&mgr, &items);
+
+        let (items, truncated) = lookup(&mgr, 0, count * 4, 5);
+        assert_eq!(items.iter().map(|&(_, offset)| offset).collect::<Vec<_>>(), vec![0, 4, 8, 12, 16]);
+        assert!(truncated);
+
+        // 顺序扫描同样找到地址最小的候选
+        assert_eq!(mgr.find_index_by_address(BASE + 0x21).unwrap(), (count - 1 - 9) as usize);
+        assert_eq!(mgr.find_index_by_address(BASE).unwrap(), (count - 1) as usize);
+        assert_eq!(mgr.find_index_by_address(BASE + count * 4).unwrap(), count as usize);
+
+    }
+
+    #[test]
+    fn test_find_index_by_address() {
+        let dir = TempDir::new("address_range_find");
+        // 全部在内存、全部在磁盘、跨越内存缓冲区和磁盘
+        for (name, memory_buffer) in [("memory", 1024 * 1024), ("disk", 0), ("straddle", memory_buffer_for(SearchResultMode::Exact, MEMORY_ITEMS))] {
+            let store_dir = dir.join(name);
+            std::fs::create_dir_all(&store_dir).unwrap();
+            let mut mgr = SearchResultManager::new(memory_buffer, store_dir);
+            mgr.set_mode(SearchResultMode::Exact).unwrap();
+            assert_eq!(mgr.find_index_by_address(BASE).unwrap(), 0, "{}", name);
+
+            mgr.add_results_batch((0..10).map(|i| SearchResultItem::new_exact(BASE + i * 0x10, ValueType::Dword)).collect()).unwrap();
+            for i in 0..10u64 {
+                // 命中的地址返回它的索引，之间的地址返回下一条的索引
+                assert_eq!(mgr.find_index_by_address(BASE + i * 0x10).unwrap(), i as usize, "{}", name);
+                assert_eq!(mgr.find_index_by_address(BASE + i * 0x10 + 1).unwrap(), i as usize + 1, "{}", name);
+            }
+            assert_eq!(mgr.find_index_by_address(0).unwrap(), 0, "{}", name);
+            assert_eq!(mgr.find_index_by_address(u64::MAX).unwrap(), 10, "{}", name);
+        }
+    }
+}
@@ -0,0 +1,127 @@
+//! Address-sorted compaction tests
+//!
+//! 三个互相重叠的区域批次按写入顺序分散在内存缓冲区和磁盘上，整理后整体按地址严格升序，
+//! 重复的地址只保留最先写入的一项；之后按地址范围查找整个存储就是一段，乱序追加会清除有序标志。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::{SearchResultItem, ValueType};
+    use crate::search::result_manager::address_sort::{address_runs, merge_runs};
+    use crate::search::result_manager::{FuzzySearchResultItem, FuzzySearchResultManager, SearchResultManager, SearchResultMode};
+    use crate::search::tests::temp_dir::TempDir;
+
+    const BASE: u64 = 0x7600000000;
+    /// 内存缓冲区只放得下 10 项，其余写到磁盘
+    const MEMORY_ITEMS: usize = 10;
+
+    /// 第 batch 批在 [start, end) 内每隔 step 字节的结果，值记录批号
+    fn batch(batch: u8, start: u64, end: u64, step: u64) -> Vec<FuzzySearchResultItem> {
+        (start..end).step_by(step as usize).map(|offset| FuzzySearchResultItem::new(BASE + offset, [batch; 8], ValueType::Dword)).collect()
+    }
+
+    /// 三个重叠的批次，第二批整体在第一批前面
+    fn batches() -> Vec<Vec<FuzzySearchResultItem>> {
+        vec![batch(0, 0x40, 0x80, 4), batch(1, 0x00, 0x60, 8), batch(2, 0x20, 0xA0, 4)]
+    }
+
+    /// 按写入顺序保留每个地址第一次出现的批号
+    fn expected(batches: &[Vec<FuzzySearchResultItem>]) -> Vec<(u64, u8)> {
+        let mut first = std::collections::BTreeMap::new();
+        for item in batches.iter().flatten() {
+            first.entry(item.address).or_insert(item.value[0]);
+        }
+        first.into_iter().collect()
+    }
+
+    fn address_and_batch(items: &[FuzzySearchResultItem]) -> Vec<(u64, u8)> {
+        items.iter().map(|item| (item.address, item.value[0])).collect()
+    }
+
+    #[test]
+    fn test_merge_three_overlapping_batches() {
+        let dir = TempDir::new("address_sort");
+        let mut store = FuzzySearchResultManager::new(MEMORY_ITEMS * size_of::<FuzzySearchResultItem>(), dir.to_path_buf());
+        let batches = batches();
+        for item in batches.iter().flatten() {
+            store.add_result(*item).unwrap();
+        }
+        let written = store.total_count();
+        assert!(store.disk_count() > 0, "the batches should spill to disk");
+        assert!(!store.is_address_sorted());
+
+        let dropped = store.sort_by_address().unwrap();
+        let expected = expected(&batches);
+        assert_eq!(dropped, written - expected.len());
+        assert_eq!(store.total_count(), expected.len());
+        assert_eq!(store.memory_count(), MEMORY_ITEMS);
+        assert_eq!(store.memory_count() + store.disk_count(), expected.len());
+        assert!(store.is_address_sorted());
+
+        let merged = store.get_all_results().unwrap();
+        assert!(merged.windows(2).all(|pair| pair[0].address < pair[1].address));
+        assert_eq!(address_and_batch(&merged), expected);
+        assert!(!dir.join("mamu_fuzzy_results.sorted").exists());
+
+        // 已经有序时不再改写
+        assert_eq!(store.sort_by_address().unwrap(), 0);
+        assert_eq!(address_and_batch(&store.get_all_results().unwrap()), expected);
+
+        // 追加更大的地址保持有序，追加更小的地址清除标志
+        store.add_result(FuzzySearchResultItem::new(BASE + 0x100, [3; 8], ValueType::Dword)).unwrap();
+        assert!(store.is_address_sorted());
+        store.add_result(FuzzySearchResultItem::new(BASE, [3; 8], ValueType::Dword)).unwrap();
+        assert!(!store.is_address_sorted());
+
+        drop(store);
+    }
+
+    #[test]
+    fn test_merge_runs_keeps_first_occurrence() {
+        let items: Vec<(u64, char)> = vec![(1, 'a'), (4, 'a'), (4, 'b'), (9, 'a'), (0, 'c'), (4, 'c'), (2, 'd'), (9, 'd')];
+        let (runs, has_duplicates) = address_runs(items.len(), |index| items[index].0);
+        assert_eq!(runs, vec![0..4, 4..6, 6..8]);
+        assert!(has_duplicates);
+
+        let mut merged = Vec::new();
+        let dropped = merge_runs(&runs, |index| items[index], |item| item.0, |item| {
+            merged.push(item);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(dropped, 3);
+        assert_eq!(merged, vec![(0, 'c'), (1, 'a'), (2, 'd'), (4, 'a'), (9, 'a')]);
+    }
+
+    #[test]
+    fn test_sorted_store_is_one_range_run() {
+        let dir = TempDir::new("address_sort_range");
+        let mut mgr = SearchResultManager::new(MEMORY_ITEMS * size_of::<FuzzySearchResultItem>(), dir.to_path_buf());
+        mgr.set_mode(SearchResultMode::Fuzzy).unwrap();
+        let batches = batches();
+        for batch in batches.iter().cloned() {
+            mgr.add_fuzzy_results_batch(batch).unwrap();
+        }
+        assert!(!mgr.is_address_sorted());
+        let before = mgr.get_results_in_range(BASE + 0x40, BASE + 0x60, usize::MAX).unwrap();
+
+        mgr.sort_fuzzy_by_address().unwrap();
+        assert!(mgr.is_address_sorted());
+        assert_eq!(mgr.cursor(4).unwrap().run_count(), 1);
+
+        // 范围查找在整个存储上二分，结果是去重后的地址，位置就是存储中的顺序
+        let after = mgr.get_results_in_range(BASE + 0x40, BASE + 0x60, usize::MAX).unwrap();
+        let addresses: Vec<u64> = after
+            .items
+            .iter()
+            .map(|(_, item)| match item {
+                SearchResultItem::Fuzzy(fuzzy) => fuzzy.address,
+                SearchResultItem::Exact(exact) => exact.address,
+            })
+            .collect();
+        assert_eq!(addresses, (0x40..0x60).step_by(4).map(|offset| BASE + offset).collect::<Vec<_>>());
+        assert!(before.items.len() > after.items.len(), "overlapping batches had duplicates in range");
+        assert!(after.items.windows(2).all(|pair| pair[0].0 + 1 == pair[1].0));
+
+        drop(mgr);
+    }
+}
@@ -0,0 +1,215 @@
+//! Search alignment tests
+//!
+//! 值放在不按类型大小对齐的地址上（奇数地址、跨页、跨扫描粒度），只有指定更小的对齐（`:a1`）时
+//! 单值、组和模糊首次扫描才能找到；跨入读取失败页的值不能出现在结果中。按 1 字节对齐找到的结果
+//! 用逐地址读取和重新扫描两种改善方式都能正确保留或排除。不对齐的深度组搜索不会读出区域末尾。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::fuzzy_search::scan_buffer_parallel_aligned;
+    use crate::search::engine::group_search::{search_in_buffer_group, search_in_buffer_group_deep};
+    use crate::search::engine::refine_strategy::rescan_and_intersect_with;
+    use crate::search::engine::single_search::{refine_values_with, search_in_chunks_aligned};
+    use crate::search::tests::mock_memory::MockMemory;
+    use crate::search::{parse_search_query, SearchValue, ValuePair, ValueType};
+    use crate::wuwa::PageStatusBitmap;
+    use bplustree::BPlusTreeSet;
+
+    const BASE: u64 = 0x7A00000000;
+    const PAGE: usize = 4096;
+    const PAGES: usize = 20;
+    const SIZE: usize = PAGES * PAGE;
+    const FAULTY_PAGE: usize = 18;
+    /// 跨入失败页的值，后两个字节读成 0
+    const INTO_FAULTY: u64 = (FAULTY_PAGE * PAGE) as u64 - 3;
+    /// 奇数、2 字节对齐、4 字节对齐、跨页（2 字节对齐）、跨 64KB 扫描粒度和页（奇数）
+    const PLANTED: [u64; 5] = [0x101, 0x202, 0x300, 0x1FFE, 0xFFFF];
+
+    fn no_cancel() -> bool {
+        false
+    }
+
+    /// 填充 0x11，在 PLANTED 和 INTO_FAULTY 处写入 value 的小端字节
+    fn memory_with(value: u32) -> MockMemory {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, SIZE).unwrap();
+        mem.mem_write(BASE, &vec![0x11; SIZE]).unwrap();
+        for offset in PLANTED.iter().chain([&INTO_FAULTY]) {
+            mem.mem_write(BASE + offset, &value.to_le_bytes()).unwrap();
+        }
+        mem.set_faulty_pages(BASE, &[FAULTY_PAGE]).unwrap();
+        mem
+    }
+
+    fn read_all(mem: &MockMemory) -> (Vec<u8>, PageStatusBitmap) {
+        let mut buffer = vec![0u8; SIZE];
+        let mut page_status = PageStatusBitmap::new(SIZE, BASE as usize);
+        mem.mem_read_with_status(BASE, &mut buffer, &mut page_status).unwrap();
+        (buffer, page_status)
+    }
+
+    fn scan(mem: &MockMemory, target: &SearchValue, align: usize) -> Vec<u64> {
+        let (buffer, page_status) = read_all(mem);
+        let value_type = target.value_type();
+        let mut results = Vec::new();
+        search_in_chunks_aligned(&buffer, BASE, BASE, BASE + SIZE as u64, value_type.size(), align, target, value_type, &page_status, &mut results, &no_cancel);
+        assert!(results.is_sorted());
+        results.into_iter().map(|pair| pair.addr - BASE).collect()
+    }
+
+    #[test]
+    fn test_single_scan_finds_unaligned_values_only_at_smaller_alignment() {
+        // memchr 首字节路径、首字节为 0 的逐个比较路径、范围值的 matched_at 路径
+        let cases = [
+            (0x0000_5678, SearchValue::fixed(0x5678, ValueType::Dword)),
+            (0x0056_7800, SearchValue::fixed(0x0056_7800, ValueType::Dword)),
+            (0x0056_7800, SearchValue::range(0x0056_7800, 0x0056_7800, ValueType::Dword, false)),
+        ];
+        for (value, target) in cases {
+            let mem = memory_with(value);
+            assert_eq!(scan(&mem, &target, 4), vec![0x300], "{:?}", target);
+            assert_eq!(scan(&mem, &target, 2), vec![0x202, 0x300, 0x1FFE], "{:?}", target);
+            assert_eq!(scan(&mem, &target, 1), PLANTED.to_vec(), "{:?}", target);
+        }
+
+        // Byte 按更大的对齐只保留对齐的地址
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, PAGE).unwrap();
+        mem.mem_write(BASE + 0x101, &[0x42]).unwrap();
+        mem.mem_write(BASE + 0x104, &[0x42]).unwrap();
+        let (buffer, page_status) = {
+            let mut buffer = vec![0u8; PAGE];
+            let mut page_status = PageStatusBitmap::new(PAGE, BASE as usize);
+            mem.mem_read_with_status(BASE, &mut buffer, &mut page_status).unwrap();
+            (buffer, page_status)
+        };
+        let target = SearchValue::fixed(0x42, ValueType::Byte);
+        for (align, expected) in [(1, vec![BASE + 0x101, BASE + 0x104]), (4, vec![BASE + 0x104])] {
+            let mut results = Vec::new();
+            search_in_chunks_aligned(&buffer, BASE, BASE, BASE + PAGE as u64, 1, align, &target, ValueType::Byte, &page_status, &mut results, &no_cancel);
+            assert_eq!(results.iter().map(|pair| pair.addr).collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn test_group_scan_uses_query_alignment() {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, SIZE).unwrap();
+        mem.mem_write(BASE, &vec![0x11; SIZE]).unwrap();
+        mem.mem_write_u32(BASE + 0x501, 100).unwrap();
+        mem.mem_write_u32(BASE + 0x509, 200).unwrap();
+        // 锚点跨页
+        mem.mem_write_u32(BASE + 0x2FFE, 100).unwrap();
+        mem.mem_write_u32(BASE + 0x3003, 200).unwrap();
+        mem.set_faulty_pages(BASE, &[FAULTY_PAGE]).unwrap();
+        let (buffer, page_status) = read_all(&mem);
+
+        let group = |input: &str| {
+            let query = parse_search_query(input, ValueType::Dword).unwrap();
+            let mut results = Vec::new();
+            let mut checked = 0;
+            search_in_buffer_group(&buffer, BASE, BASE, BASE + SIZE as u64, 4, &query, &page_status, &mut results, &mut checked, &no_cancel);
+            results.iter().map(|pair| pair.addr - BASE).collect::<Vec<_>>()
+        };
+        assert!(group("100D;200D:16").is_empty());
+        assert_eq!(group("100D;200D:16:a1"), vec![0x501, 0x509, 0x2FFE, 0x3003]);
+        // 2 字节对齐时只有跨页的锚点对齐，另一个值仍不对齐
+        assert!(group("100D;200D:16:a2").is_empty());
+    }
+
+    #[test]
+    fn test_float_in_packed_struct_found_with_alignment_suffix() {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, PAGE).unwrap();
+        // 1 字节对齐的结构体：一个 Byte 之后紧跟 Float
+        mem.mem_write(BASE + 0x40, &[0x01]).unwrap();
+        mem.mem_write(BASE + 0x41, &123.0f32.to_le_bytes()).unwrap();
+        let mut buffer = vec![0u8; PAGE];
+        let mut page_status = PageStatusBitmap::new(PAGE, BASE as usize);
+        mem.mem_read_with_status(BASE, &mut buffer, &mut page_status).unwrap();
+
+        let scan_query = |input: &str| {
+            let query = parse_search_query(input, ValueType::Dword).unwrap();
+            let target = &query.values[0];
+            let mut results = Vec::new();
+            let align = query.alignment_for(target.value_type());
+            search_in_chunks_aligned(&buffer, BASE, BASE, BASE + PAGE as u64, 4, align, target, ValueType::Float, &page_status, &mut results, &no_cancel);
+            results.iter().map(|pair| pair.addr - BASE).collect::<Vec<_>>()
+        };
+        assert!(scan_query("123F").is_empty());
+        assert_eq!(scan_query("123F:1"), vec![0x41]);
+        assert_eq!(scan_query("123F:a1"), vec![0x41]);
+    }
+
+    #[test]
+    fn test_unaligned_deep_group_stays_inside_region() {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, PAGE).unwrap();
+        mem.mem_write(BASE, &vec![0x11; PAGE]).unwrap();
+        mem.mem_write_u32(BASE + 0x101, 100).unwrap();
+        mem.mem_write_u32(BASE + 0x105, 200).unwrap();
+        mem.mem_write_u32(BASE + 0x109, 200).unwrap();
+        // 第二个 200 跨过 region_end，缓冲区里虽然有完整的值也不能匹配
+        mem.mem_write_u32(BASE + 0x201, 100).unwrap();
+        mem.mem_write_u32(BASE + 0x205, 200).unwrap();
+        mem.mem_write_u32(BASE + 0x20B, 200).unwrap();
+        let mut buffer = vec![0u8; PAGE];
+        let mut page_status = PageStatusBitmap::new(PAGE, BASE as usize);
+        mem.mem_read_with_status(BASE, &mut buffer, &mut page_status).unwrap();
+        let region_end = BASE + 0x20D;
+
+        let query = parse_search_query("100D;200D:16:a1", ValueType::Dword).unwrap();
+        let mut results = BPlusTreeSet::new(16);
+        let mut checked = 0;
+        search_in_buffer_group_deep(&buffer, BASE, BASE, region_end, 4, &query, &page_status, &mut results, &mut checked);
+        let found: Vec<u64> = results.iter().map(|pair| pair.addr - BASE).collect();
+        assert_eq!(found, vec![0x101, 0x105, 0x109, 0x201, 0x205]);
+        assert!(found.iter().all(|&offset| BASE + offset + 4 <= region_end));
+    }
+
+    #[test]
+    fn test_fuzzy_scan_records_every_aligned_address() {
+        let mem = memory_with(0x0000_5678);
+        let (buffer, page_status) = read_all(&mem);
+        let readable_pages = PAGES - 1;
+
+        let items = scan_buffer_parallel_aligned(&buffer, BASE, BASE, BASE + SIZE as u64, 4, 4, ValueType::Dword, PAGE, &page_status);
+        assert_eq!(items.len(), readable_pages * PAGE / 4);
+
+        let items = scan_buffer_parallel_aligned(&buffer, BASE, BASE, BASE + SIZE as u64, 4, 1, ValueType::Dword, PAGE, &page_status);
+        // 每个成功页的每个字节都是起始地址，跨入失败页和区域末尾的 3 个除外
+        assert_eq!(items.len(), readable_pages * PAGE - 3 - 3);
+        assert!(items.windows(2).all(|w| { w[0].address } < { w[1].address }));
+        for offset in PLANTED {
+            let item = items.iter().find(|item| item.address == BASE + offset).unwrap();
+            assert_eq!(item.value[..4], 0x5678u32.to_le_bytes(), "0x{:X}", offset);
+        }
+        assert!(!items.iter().any(|item| item.address == BASE + INTO_FAULTY));
+    }
+
+    #[test]
+    fn test_unaligned_results_refine_correctly() {
+        let mut mem = memory_with(0x0000_5678);
+        let target = SearchValue::fixed(0x5678, ValueType::Dword);
+        let pairs: Vec<ValuePair> = scan(&mem, &target, 1).into_iter().map(|offset| ValuePair::new(BASE + offset, ValueType::Dword)).collect();
+
+        // 两个不对齐的值改变之后，逐地址读取和重新扫描都只保留其余三个
+        mem.mem_write_u32(BASE + 0x101, 7).unwrap();
+        mem.mem_write_u32(BASE + 0xFFFF, 7).unwrap();
+        let expected: Vec<u64> = [0x202, 0x300, 0x1FFE].iter().map(|offset| BASE + offset).collect();
+
+        let per_item = refine_values_with(&pairs, &target, |addr, buf| mem.mem_read_into(addr, buf).is_ok(), None, None, &no_cancel, &|_, _| {});
+        assert_eq!(per_item.iter().map(|pair| pair.addr).collect::<Vec<_>>(), expected);
+
+        let rescanned = rescan_and_intersect_with(
+            &pairs,
+            &target,
+            0x4000,
+            |addr, buf, page_status| mem.mem_read_with_status(addr, buf, page_status),
+            &no_cancel,
+            &|_, _| {},
+        )
+        .unwrap();
+        assert_eq!(rescanned.iter().map(|pair| pair.addr).collect::<Vec<_>>(), expected);
+    }
+}
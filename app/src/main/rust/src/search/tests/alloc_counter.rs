@@ -0,0 +1,54 @@
+//! Allocation counter for hot-path tests
+//!
+//! 测试二进制的全局分配器包装，按线程统计分配次数（alloc / alloc_zeroed / realloc）。
+//! 只统计调用线程，rayon 工作线程上的分配和并行运行的其他测试不会计入。
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+struct CountingAllocator;
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<u64> = const { Cell::new(0) };
+}
+
+#[inline]
+fn bump() {
+    let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        bump();
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        bump();
+        unsafe { System.alloc_zeroed(layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        bump();
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+/// 当前线程到目前为止的分配次数
+pub fn thread_allocations() -> u64 {
+    ALLOCATIONS.with(Cell::get)
+}
+
+/// 执行 `f` 并返回其间当前线程的分配次数
+pub fn count_allocations<T>(f: impl FnOnce() -> T) -> (T, u64) {
+    let before = thread_allocations();
+    let value = f();
+    (value, thread_allocations() - before)
+}
@@ -0,0 +1,81 @@
+//! Hot-path allocation tests
+//!
+//! 逐地址改善搜索的读取循环不应该为每个结果分配缓冲区。用旧的做法（每个地址一个 Vec）
+//! 作为对照，比较结果、调用线程上的分配次数和耗时。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::manager::ValuePair;
+    use crate::search::engine::single_search::refine_values_with;
+    use crate::search::tests::alloc_counter::count_allocations;
+    use crate::search::tests::mock_memory::MockMemory;
+    use crate::search::{SearchValue, ValueType};
+    use std::time::Instant;
+
+    const BASE: u64 = 0x7900000000;
+    const ITEMS: usize = 100_000;
+    const TARGET: u32 = 42;
+
+    fn setup() -> (MockMemory, Vec<ValuePair>) {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, ITEMS * 4).unwrap();
+        let mut pairs = Vec::with_capacity(ITEMS);
+        for i in 0..ITEMS {
+            let addr = BASE + (i * 4) as u64;
+            let value = if i.is_multiple_of(3) { TARGET } else { i as u32 + 1000 };
+            mem.mem_write_u32(addr, value).unwrap();
+            pairs.push(ValuePair::new(addr, ValueType::Dword));
+        }
+        (mem, pairs)
+    }
+
+    /// 旧的读取方式：每个地址分配一个 Vec
+    fn refine_with_vec_per_item(mem: &MockMemory, pairs: &[ValuePair], target: &SearchValue) -> Vec<ValuePair> {
+        let mut values = Vec::with_capacity(pairs.len());
+        for pair in pairs {
+            if let Ok(bytes) = mem.mem_read(pair.addr, pair.value_type.size()) {
+                values.push((pair.clone(), bytes));
+            }
+        }
+        values
+            .into_iter()
+            .filter(|(_, bytes)| target.matched(bytes).unwrap_or(false))
+            .map(|(pair, _)| pair)
+            .collect()
+    }
+
+    #[test]
+    fn test_refine_per_item_allocations() {
+        let (mem, pairs) = setup();
+        let target = SearchValue::fixed(TARGET as i128, ValueType::Dword);
+
+        let started = Instant::now();
+        let (expected, before_allocs) = count_allocations(|| refine_with_vec_per_item(&mem, &pairs, &target));
+        let before_time = started.elapsed();
+
+        let started = Instant::now();
+        let (results, after_allocs) = count_allocations(|| {
+            refine_values_with(
+                &pairs,
+                &target,
+                |addr, buffer| mem.mem_read_into(addr, buffer).is_ok(),
+                None,
+                None,
+                &|| false,
+                &|_, _| {},
+            )
+        });
+        let after_time = started.elapsed();
+
+        println!(
+            "refine over {} items: Vec per item {} allocs in {:?}, fixed buffer {} allocs in {:?}",
+            ITEMS, before_allocs, before_time, after_allocs, after_time
+        );
+
+        assert_eq!(results.len(), ITEMS.div_ceil(3));
+        assert_eq!(results, expected);
+        assert!(before_allocs >= ITEMS as u64);
+        // 只剩与结果数量无关的少量分配（收集用的 Vec、rayon 的调度）
+        assert!(after_allocs < (ITEMS / 1000) as u64, "{} allocations for {} items", after_allocs, ITEMS);
+    }
+}
@@ -0,0 +1,155 @@
+//! Auto type fuzzy search tests
+//!
+//! Auto 初始扫描每 4 字节一项，8 字节对齐处额外保存 Qword 解释需要的高 4 字节；
+//! 细化时按 Dword / Float / Qword 分别比较，收窄到一种解释后定型，
+//! 定型为 Qword 的地址吞掉下一个 4 字节槽。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::batch_reader::ReadResultItem;
+    use crate::search::engine::fuzzy_search::{dedup_auto_overlaps, join_with_generation, scan_buffer_parallel};
+    use crate::search::result_manager::{FuzzySearchResultItem, SearchResultManager, SearchResultMode};
+    use crate::search::tests::mock_memory::MockMemory;
+    use crate::search::{FuzzyCondition, ValueType};
+    use crate::wuwa::PageStatusBitmap;
+    use crate::search::tests::temp_dir::TempDir;
+
+    const BASE: u64 = 0x7500000000;
+    const PAGE: usize = 4096;
+
+    const DF: u8 = FuzzySearchResultItem::AUTO_DWORD | FuzzySearchResultItem::AUTO_FLOAT;
+    const DFQ: u8 = DF | FuzzySearchResultItem::AUTO_QWORD;
+
+    fn refine(mem: &MockMemory, item: &FuzzySearchResultItem, condition: FuzzyCondition) -> Option<FuzzySearchResultItem> {
+        let current = mem.mem_read(item.address, item.value_size()).unwrap();
+        ReadResultItem::new(item, &current).refine(condition)
+    }
+
+    fn initial(mem: &MockMemory, address: u64) -> FuzzySearchResultItem {
+        FuzzySearchResultItem::auto(address, &mem.mem_read(address, 8).unwrap())
+    }
+
+    fn summary(item: &FuzzySearchResultItem) -> (u64, ValueType, u8) {
+        (item.address, item.value_type, item.auto_types)
+    }
+
+    #[test]
+    fn test_auto_initial_scan_alignment() {
+        let mut buffer = vec![0u8; PAGE];
+        buffer[8..16].copy_from_slice(&0x1122_3344_5566_7788u64.to_le_bytes());
+        let mut page_status = PageStatusBitmap::new(PAGE, BASE as usize);
+        page_status.mark_all_success();
+
+        // 区域从 4-mod-8 开始，最后一个 8 字节对齐的槽只剩 4 字节
+        let items = scan_buffer_parallel(&buffer, BASE, BASE + 4, BASE + 28, 4, ValueType::Auto, PAGE, &page_status);
+        let layout: Vec<(u64, ValueType, u8)> = items.iter().map(summary).collect();
+        assert_eq!(
+            layout,
+            vec![
+                (BASE + 4, ValueType::Auto, DF),
+                (BASE + 8, ValueType::Auto, DFQ),
+                (BASE + 12, ValueType::Auto, DF),
+                (BASE + 16, ValueType::Auto, DFQ),
+                (BASE + 20, ValueType::Auto, DF),
+                (BASE + 24, ValueType::Auto, DF),
+            ]
+        );
+        assert_eq!(items[1].value_size(), 8);
+        assert_eq!(items[1].as_i64(), 0x5566_7788);
+        let value = items[1].value;
+        assert_eq!(u64::from_le_bytes(value), 0x1122_3344_5566_7788);
+        assert_eq!(items[2].value_size(), 4);
+    }
+
+    #[test]
+    fn test_auto_refine_narrows_type() {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, PAGE).unwrap();
+        mem.mem_write_u64(BASE, 100).unwrap();
+        mem.mem_write_f32(BASE + 12, 1.5).unwrap();
+        let dword = initial(&mem, BASE);
+        let float = FuzzySearchResultItem::auto(BASE + 12, &mem.mem_read(BASE + 12, 4).unwrap());
+
+        // 高 4 字节为 0 时 Dword 和 Qword 都加了 1，Float 解释是非规格化数，被排除
+        mem.mem_write_u64(BASE, 101).unwrap();
+        mem.mem_write_f32(BASE + 12, 2.5).unwrap();
+        let dword = refine(&mem, &dword, FuzzyCondition::IncreasedBy(1)).unwrap();
+        assert_eq!(
+            summary(&dword),
+            (BASE, ValueType::Auto, FuzzySearchResultItem::AUTO_DWORD | FuzzySearchResultItem::AUTO_QWORD)
+        );
+        let float = refine(&mem, &float, FuzzyCondition::IncreasedBy(1)).unwrap();
+        assert_eq!(summary(&float), (BASE + 12, ValueType::Float, FuzzySearchResultItem::AUTO_FLOAT));
+
+        // 只改高 4 字节：Qword 变了，Dword 没变
+        mem.mem_write_u64(BASE, (7 << 32) | 101).unwrap();
+        let narrowed = refine(&mem, &dword, FuzzyCondition::Unchanged).unwrap();
+        assert_eq!(summary(&narrowed), (BASE, ValueType::Dword, FuzzySearchResultItem::AUTO_DWORD));
+        assert_eq!(narrowed.value_size(), 4);
+
+        // 定型后只按 Dword 比较：低 4 字节没变，高 4 字节的变化不再让地址留下
+        assert!(refine(&mem, &narrowed, FuzzyCondition::Changed).is_none());
+        assert!(narrowed.matches_condition(&102i32.to_le_bytes(), FuzzyCondition::IncreasedBy(1)));
+    }
+
+    #[test]
+    fn test_auto_qword_swallows_high_half() {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, PAGE).unwrap();
+        mem.mem_write_u64(BASE, 1 << 32).unwrap();
+        mem.mem_write_u32(BASE + 8, 5).unwrap();
+        let items = [initial(&mem, BASE), initial(&mem, BASE + 4), initial(&mem, BASE + 8)];
+
+        // 低 4 字节不变、高 4 字节加 1：只有 Qword 解释加了 1 << 32
+        mem.mem_write_u64(BASE, 2 << 32).unwrap();
+        let condition = FuzzyCondition::IncreasedBy(1 << 32);
+        let qword = refine(&mem, &items[0], condition).unwrap();
+        assert_eq!(summary(&qword), (BASE, ValueType::Qword, FuzzySearchResultItem::AUTO_QWORD));
+
+        // 高半部分单独按 Changed 仍然成立，定型后由去重删除；不相邻的项和非 Auto 项不受影响
+        let high = refine(&mem, &items[1], FuzzyCondition::Changed).unwrap();
+        let plain = FuzzySearchResultItem::from_bytes(BASE + 4, &[0; 4], ValueType::Dword);
+        let next = items[2];
+        let mut refined = vec![qword, high, plain, next];
+        dedup_auto_overlaps(&mut refined);
+        let addresses: Vec<(u64, ValueType)> = refined.iter().map(|item| (item.address, item.value_type)).collect();
+        assert_eq!(
+            addresses,
+            vec![(BASE, ValueType::Qword), (BASE + 4, ValueType::Dword), (BASE + 8, ValueType::Auto)]
+        );
+        assert!(!refined[1].is_auto());
+
+        // 未定型时高半部分保留
+        let mut undecided = vec![items[0], items[1]];
+        dedup_auto_overlaps(&mut undecided);
+        assert_eq!(undecided.len(), 2);
+    }
+
+    #[test]
+    fn test_auto_generation_round_trip() {
+        let dir = TempDir::new("auto_gen");
+
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, PAGE).unwrap();
+        mem.mem_write_u64(BASE, 40).unwrap();
+        let baseline = initial(&mem, BASE);
+
+        let mut mgr = SearchResultManager::new(1024 * 1024, dir.to_path_buf());
+        mgr.set_mode(SearchResultMode::Fuzzy).unwrap();
+        mgr.add_fuzzy_results_batch(vec![baseline]).unwrap();
+        let id = mgr.record_fuzzy_generation("Initial".to_string()).unwrap().unwrap();
+        let generation = mgr.load_generation(id).unwrap();
+        assert_eq!(summary(&generation[0]), (BASE, ValueType::Auto, DFQ));
+        let stored = mgr.get_all_fuzzy_results().unwrap();
+        assert_eq!(summary(&stored[0]), (BASE, ValueType::Auto, DFQ));
+
+        // 当前项已定型为 Dword，与未定型的历史代仍然可以比较，按当前的解释
+        let current = FuzzySearchResultItem::from_bytes(BASE, &41u32.to_le_bytes(), ValueType::Dword).with_auto_types(FuzzySearchResultItem::AUTO_DWORD);
+        let join = join_with_generation(&[current], generation);
+        assert!(join.missing.is_empty());
+        assert_eq!(summary(&join.compared[0]), (BASE, ValueType::Dword, FuzzySearchResultItem::AUTO_DWORD));
+        assert_eq!(join.compared[0].as_i64(), 40);
+
+        drop(mgr);
+    }
+}
@@ -0,0 +1,142 @@
+//! Auto type exact search tests
+//!
+//! Auto 精确值按数值能放下的每种类型扫描：整数取 Byte / Word / Dword（都放不下时 Qword），
+//! Float 能精确表示时再加上 Float；带小数点的值只按 Float。命中以具体类型保存，同一地址可以有多种类型，
+//! 改善时每个结果按自己的类型比较。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::single_search::{refine_values_with, search_candidates_in_chunk};
+    use crate::search::tests::mock_memory::MockMemory;
+    use crate::search::{parse_search_query, SearchValue, ValuePair, ValueType};
+    use crate::wuwa::PageStatusBitmap;
+
+    const BASE: u64 = 0x7E00000000;
+    const PAGE: usize = 4096;
+    const SIZE: usize = 2 * PAGE;
+
+    fn no_cancel() -> bool {
+        false
+    }
+
+    fn types_of(value: &SearchValue) -> Vec<ValueType> {
+        value.auto_candidates().iter().map(SearchValue::value_type).collect()
+    }
+
+    fn auto(input: &str) -> SearchValue {
+        parse_search_query(input, ValueType::Auto).unwrap().values[0].clone()
+    }
+
+    /// 与 search_region_single_with_cancel 相同的分块方式：每页一块，多读最大类型大小 - 1 字节
+    fn scan(mem: &MockMemory, target: &SearchValue) -> Vec<(u64, ValueType)> {
+        let candidates = target.auto_candidates();
+        let overlap = candidates.iter().map(|candidate| candidate.value_type().size()).max().unwrap() - 1;
+        let end = BASE + SIZE as u64;
+        let mut buffer = vec![0u8; PAGE + overlap];
+        let mut results = Vec::new();
+        let mut current = BASE;
+        while current < end {
+            let chunk_end = current + PAGE as u64;
+            let chunk_len = ((chunk_end + overlap as u64).min(end) - current) as usize;
+            let mut page_status = PageStatusBitmap::new(chunk_len, current as usize);
+            mem.mem_read_with_status(current, &mut buffer[..chunk_len], &mut page_status).unwrap();
+            search_candidates_in_chunk(&buffer[..chunk_len], current, (BASE, end), chunk_end, None, &candidates, &page_status, &mut results, &no_cancel);
+            current = chunk_end;
+        }
+        results.into_iter().map(|pair| (pair.addr - BASE, pair.value_type)).collect()
+    }
+
+    fn memory() -> MockMemory {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, SIZE).unwrap();
+        mem.mem_write(BASE, &vec![0xEE; SIZE]).unwrap();
+        mem
+    }
+
+    #[test]
+    fn test_candidates_follow_value_range() {
+        use ValueType::*;
+        assert_eq!(types_of(&auto("7")), vec![Byte, Word, Dword, Float]);
+        assert_eq!(types_of(&auto("-1")), vec![Byte, Word, Dword, Float]);
+        assert_eq!(types_of(&auto("255")), vec![Byte, Word, Dword, Float]);
+        assert_eq!(types_of(&auto("300")), vec![Word, Dword, Float]);
+        assert_eq!(types_of(&auto("-40000")), vec![Dword, Float]);
+        // Float 不能精确表示的整数不按 Float 搜索
+        assert_eq!(types_of(&auto("16777217")), vec![Dword]);
+        assert_eq!(types_of(&auto("5000000000")), vec![Qword, Float]);
+        // 带小数点的值只可能是浮点数
+        assert_eq!(types_of(&auto("7.5")), vec![Float]);
+        assert_eq!(types_of(&auto("1e300")), vec![Double]);
+        // 不是 Auto 的值只有自身
+        let dword = parse_search_query("7", ValueType::Dword).unwrap().values[0].clone();
+        assert_eq!(types_of(&dword), vec![Dword]);
+    }
+
+    #[test]
+    fn test_scan_reports_each_matching_type() {
+        let mut mem = memory();
+        // Dword 7：同一地址也是 Word 7 和 Byte 7
+        mem.mem_write(BASE + 0x100, &7u32.to_le_bytes()).unwrap();
+        // 单独的 Byte 7，后面不是 0
+        mem.mem_write(BASE + 0x201, &[7]).unwrap();
+        // Float 7.0
+        mem.mem_write(BASE + 0x300, &7.0f32.to_le_bytes()).unwrap();
+        // 块末尾的 Word 7，Dword 不按 4 字节对齐
+        mem.mem_write(BASE + 0xFFE, &7u32.to_le_bytes()).unwrap();
+
+        let results = scan(&mem, &auto("7"));
+        use ValueType::*;
+        assert_eq!(
+            results,
+            vec![
+                (0x100, Byte),
+                (0x100, Word),
+                (0x100, Dword),
+                (0x201, Byte),
+                (0x300, Float),
+                (0xFFE, Byte),
+                (0xFFE, Word),
+            ]
+        );
+
+        // 300 放不下 Byte，低字节 0x2C 不会以 Byte 命中
+        mem.mem_write(BASE + 0x100, &300u32.to_le_bytes()).unwrap();
+        let results = scan(&mem, &auto("300"));
+        assert_eq!(results, vec![(0x100, Word), (0x100, Dword)]);
+    }
+
+    #[test]
+    fn test_refine_compares_each_result_by_its_own_type() {
+        let target = auto("7");
+        let mut memory = [0xEEu8; 0x40];
+        memory[0x00..0x04].copy_from_slice(&7u32.to_le_bytes());
+        // 低两个字节仍然是 7，但作为 Dword 已经变了
+        memory[0x10..0x14].copy_from_slice(&0x0001_0007u32.to_le_bytes());
+        memory[0x20..0x24].copy_from_slice(&7.0f32.to_le_bytes());
+        let read = |addr: u64, buffer: &mut [u8]| {
+            let offset = (addr - BASE) as usize;
+            buffer.copy_from_slice(&memory[offset..offset + buffer.len()]);
+            true
+        };
+
+        let pairs: Vec<ValuePair> = [
+            (0x00, ValueType::Byte),
+            (0x00, ValueType::Dword),
+            (0x10, ValueType::Word),
+            (0x10, ValueType::Dword),
+            (0x20, ValueType::Float),
+            // 没有对应解释的类型不再保留
+            (0x30, ValueType::Qword),
+        ]
+        .iter()
+        .map(|&(offset, value_type)| ValuePair::new(BASE + offset, value_type))
+        .collect();
+
+        let survivors = refine_values_with(&pairs, &target, read, None, None, &no_cancel, &|_, _| {});
+        let survivors: Vec<_> = survivors.iter().map(|pair| (pair.addr - BASE, pair.value_type)).collect();
+        assert_eq!(
+            survivors,
+            vec![(0x00, ValueType::Byte), (0x00, ValueType::Dword), (0x10, ValueType::Word), (0x20, ValueType::Float)]
+        );
+    }
+}
@@ -0,0 +1,276 @@
+//! Byte search fast path tests
+//!
+//! 同一块 MockMemory 分别用普通的逐条扫描和按页扫描搜索同一个 Byte 值，分页结果、删除/保留和改善搜索后的结果必须一致；
+//! 存储统计要明显小于逐条存储，抽样预计只在超过阈值时启用按页存储。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::byte_search::{ByteScanner, byte_matcher, plan_byte_search};
+    use crate::search::engine::manager::ValuePair;
+    use crate::search::engine::read_stats::ReadStats;
+    use crate::search::engine::single_search::{refine_values_with, search_in_chunks_with_status};
+    use crate::search::result_manager::{ByteHitSet, SearchResultMode};
+    use crate::search::tests::mock_memory::MockMemory;
+    use crate::search::{SearchEngineManager, SearchMode, SearchQuery, SearchResultItem, SearchValue, ValueType};
+    use crate::wuwa::PageStatusBitmap;
+    use crate::search::tests::temp_dir::TempDir;
+
+    const BASE: u64 = 0x7800000000;
+    const PAGES: usize = 32;
+    const PAGE_SIZE: usize = 4096;
+    const SIZE: usize = PAGES * PAGE_SIZE;
+    const CHUNK_SIZE: usize = 0x4000;
+    const FAULTY_PAGE: usize = 5;
+    /// 只有两个命中的稀疏页
+    const SPARSE_PAGE: usize = 9;
+    /// 区域起止都不在页边界上
+    const START: u64 = BASE + 100;
+    const END: u64 = BASE + SIZE as u64 - 50;
+
+    fn no_cancel() -> bool {
+        false
+    }
+
+    fn target() -> SearchValue {
+        SearchValue::fixed(3, ValueType::Byte)
+    }
+
+    /// 大约五分之一的字节是 3
+    fn setup_memory() -> MockMemory {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, SIZE).unwrap();
+        let data: Vec<u8> = (0..SIZE).map(|i| ((i * 7 + i / 13) % 5) as u8).collect();
+        mem.mem_write(BASE, &data).unwrap();
+
+        let mut sparse = vec![0u8; PAGE_SIZE];
+        sparse[17] = 3;
+        sparse[4000] = 3;
+        mem.mem_write(BASE + (SPARSE_PAGE * PAGE_SIZE) as u64, &sparse).unwrap();
+        mem.set_faulty_pages(BASE, &[FAULTY_PAGE]).unwrap();
+        mem
+    }
+
+    /// 普通路径：按块读取后逐条生成结果
+    fn naive_scan(mem: &MockMemory, target: &SearchValue) -> Vec<u64> {
+        let mut results: Vec<ValuePair> = Vec::new();
+        let mut buffer = vec![0u8; CHUNK_SIZE];
+        let mut current = START & !(PAGE_SIZE as u64 - 1);
+        while current < END {
+            let chunk_end = (current + CHUNK_SIZE as u64).min(END);
+            let chunk_len = (chunk_end - current) as usize;
+            let mut page_status = PageStatusBitmap::new(chunk_len, current as usize);
+            if mem.mem_read_with_status(current, &mut buffer[..chunk_len], &mut page_status).is_ok() {
+                search_in_chunks_with_status(
+                    &buffer[..chunk_len],
+                    current,
+                    START,
+                    END,
+                    1,
+                    target,
+                    ValueType::Byte,
+                    &page_status,
+                    &mut results,
+                    &no_cancel,
+                );
+            }
+            current = chunk_end;
+        }
+        results.sort();
+        results.into_iter().map(|pair| pair.addr).collect()
+    }
+
+    fn bitmap_scan(mem: &MockMemory, target: &SearchValue) -> ByteHitSet {
+        let scanner = ByteScanner::new(byte_matcher(target).unwrap(), PAGE_SIZE, CHUNK_SIZE);
+        let stats = ReadStats::new();
+        let pages = scanner.scan_region(
+            START,
+            END,
+            |addr, buf, page_status| mem.mem_read_with_status(addr, buf, page_status),
+            &no_cancel,
+            &stats,
+        );
+        ByteHitSet::from_pages(PAGE_SIZE, pages)
+    }
+
+    fn paged(hits: &ByteHitSet, page: usize) -> Vec<u64> {
+        (0..hits.len()).step_by(page).flat_map(|start| hits.addresses(start, page)).collect()
+    }
+
+    #[test]
+    fn test_paged_results_match_naive() {
+        let mem = setup_memory();
+        let expected = naive_scan(&mem, &target());
+        let hits = bitmap_scan(&mem, &target());
+
+        assert!(expected.len() > 20_000);
+        assert_eq!(hits.len(), expected.len());
+        assert_eq!(paged(&hits, 1000), expected);
+        assert_eq!(paged(&hits, 4097), expected);
+        assert_eq!(hits.addresses(1234, 3000), expected[1234..4234]);
+        assert_eq!(hits.addresses(expected.len() - 5, 100), expected[expected.len() - 5..]);
+        assert!(hits.addresses(expected.len(), 10).is_empty());
+
+        // 失败页和区域外的字节没有命中
+        let faulty = BASE + (FAULTY_PAGE * PAGE_SIZE) as u64;
+        assert!(expected.iter().all(|&addr| addr < faulty || addr >= faulty + PAGE_SIZE as u64));
+        assert!(*expected.first().unwrap() >= START && *expected.last().unwrap() < END);
+    }
+
+    #[test]
+    fn test_storage_stats_bound_memory() {
+        let mem = setup_memory();
+        let hits = bitmap_scan(&mem, &target());
+        let stats = hits.stats();
+
+        assert_eq!(stats.hits, hits.len());
+        // 失败页没有命中
+        assert_eq!(stats.pages, PAGES - 1);
+        // 稀疏页存偏移列表，其他页存位图
+        assert_eq!(stats.dense_pages, PAGES - 2);
+        // 每页位图 512 字节，平均每个命中不到 1 字节；逐条存储每个命中 16 字节
+        assert!(stats.heap_bytes * 10 < stats.hits * 16, "{:?}", stats);
+    }
+
+    #[test]
+    fn test_refine_matches_naive() {
+        let mut mem = setup_memory();
+        let before = naive_scan(&mem, &target());
+        let hits = bitmap_scan(&mem, &target()).with_pass(2);
+
+        // 每三个命中改掉一个，另外把一个非命中改成 3（改善搜索不应该把它加进来）
+        for &addr in before.iter().step_by(3) {
+            mem.mem_write(addr, &[4]).unwrap();
+        }
+        let outsider = (START..END).find(|addr| before.binary_search(addr).is_err()).unwrap();
+        mem.mem_write(outsider, &[3]).unwrap();
+
+        for refine_target in [
+            target(),
+            SearchValue::range(3, 4, ValueType::Byte, false),
+            SearchValue::fixed(4, ValueType::Byte),
+        ] {
+            let pairs: Vec<ValuePair> = before.iter().map(|&addr| ValuePair::new(addr, ValueType::Byte)).collect();
+            let expected: Vec<u64> = refine_values_with(
+                &pairs,
+                &refine_target,
+                |addr, buf| mem.mem_read_into(addr, buf).is_ok(),
+                None,
+                None,
+                &no_cancel,
+                &|_, _| {},
+            )
+            .into_iter()
+            .map(|pair| pair.addr)
+            .collect();
+
+            let scanner = ByteScanner::new(byte_matcher(&refine_target).unwrap(), PAGE_SIZE, CHUNK_SIZE);
+            let refined = scanner.refine(&hits, |addr, buf| mem.mem_read_into(addr, buf), &no_cancel, &|_, _| {});
+
+            assert_eq!(refined.addresses(0, refined.len()), expected, "{:?}", refine_target);
+            assert_eq!(refined.pass(), 2);
+        }
+    }
+
+    #[test]
+    fn test_manager_pages_and_edits_byte_hits() {
+        let mem = setup_memory();
+        let mut expected = naive_scan(&mem, &target());
+        let dir = TempDir::new("byte_hits");
+        let mut manager = SearchEngineManager::new();
+        manager.init(0, dir.to_string_lossy().into_owned(), 0).unwrap();
+        manager
+            .result_manager_mut()
+            .unwrap()
+            .set_byte_hits(bitmap_scan(&mem, &target()).with_pass(1))
+            .unwrap();
+
+        assert_eq!(manager.get_total_count().unwrap(), expected.len());
+        assert_eq!(manager.get_current_mode().unwrap(), SearchResultMode::Exact);
+        let page: Vec<(u64, ValueType, u8)> = manager
+            .get_results(500, 3)
+            .unwrap()
+            .into_iter()
+            .map(|item| match item {
+                SearchResultItem::Exact(exact) => (exact.address, exact.typ, exact.pass),
+                SearchResultItem::Fuzzy(_) => panic!("byte hits must page as exact results"),
+            })
+            .collect();
+        assert_eq!(page, expected[500..503].iter().map(|&addr| (addr, ValueType::Byte, 1)).collect::<Vec<_>>());
+
+        // 删除跨多个页的索引
+        let removed = vec![0, 1, 700, 701, 702, 5000, expected.len() - 1];
+        manager.remove_results_batch(removed.clone()).unwrap();
+        for &index in removed.iter().rev() {
+            expected.remove(index);
+        }
+        let all: Vec<u64> = manager
+            .result_manager_mut()
+            .unwrap()
+            .get_all_exact_results()
+            .unwrap()
+            .iter()
+            .map(|item| item.address)
+            .collect();
+        assert_eq!(all, expected);
+
+        let kept = vec![3, 4, 2000, 9000, 9001];
+        manager.keep_only_results(kept.clone()).unwrap();
+        let expected: Vec<u64> = kept.iter().map(|&index| expected[index]).collect();
+        assert_eq!(manager.get_byte_hit_stats().unwrap().hits, kept.len());
+
+        // 手动添加结果前展开为普通精确结果
+        manager.add_results_batch(vec![SearchResultItem::new_exact(BASE, ValueType::Dword)]).unwrap();
+        assert!(manager.get_byte_hit_stats().is_none());
+        let all: Vec<u64> = manager
+            .result_manager_mut()
+            .unwrap()
+            .get_all_exact_results()
+            .unwrap()
+            .iter()
+            .map(|item| item.address)
+            .collect();
+        assert_eq!(all[..kept.len()], expected[..]);
+        assert_eq!(all[kept.len()], BASE);
+
+    }
+
+    #[test]
+    fn test_projection_triggers_above_threshold() {
+        let mem = setup_memory();
+        let actual = naive_scan(&mem, &target()).len() as u64;
+        let read = |addr: u64, buf: &mut [u8]| {
+            let mut page_status = PageStatusBitmap::new(buf.len(), addr as usize);
+            mem.mem_read_with_status(addr, buf, &mut page_status)
+        };
+        let regions = [(START, END)];
+        let query = |value: SearchValue| SearchQuery::new(vec![value], SearchMode::Ordered, 0);
+
+        let (_, projected) = plan_byte_search(&query(target()), &regions, 1000, PAGE_SIZE, CHUNK_SIZE, read).unwrap();
+        assert!(
+            projected > actual * 3 / 4 && projected < actual * 5 / 4,
+            "projected {} actual {}",
+            projected,
+            actual
+        );
+
+        assert!(plan_byte_search(&query(target()), &regions, (actual * 2) as usize, PAGE_SIZE, CHUNK_SIZE, read).is_none());
+        assert!(plan_byte_search(&query(target()), &regions, 0, PAGE_SIZE, CHUNK_SIZE, read).is_none());
+        assert!(plan_byte_search(&query(SearchValue::fixed(3, ValueType::Dword)), &regions, 1, PAGE_SIZE, CHUNK_SIZE, read).is_none());
+        assert!(plan_byte_search(&query(SearchValue::fixed(9, ValueType::Byte)), &regions, 1, PAGE_SIZE, CHUNK_SIZE, read).is_none());
+
+        // 范围条件按匹配的字节值计数
+        let (_, range_projected) = plan_byte_search(
+            &query(SearchValue::range(2, 3, ValueType::Byte, false)),
+            &regions,
+            1000,
+            PAGE_SIZE,
+            CHUNK_SIZE,
+            read,
+        )
+        .unwrap();
+        assert!(range_projected > projected * 3 / 2, "range {} single {}", range_projected, projected);
+
+        let group = SearchQuery::new(vec![target(), target()], SearchMode::Ordered, 16);
+        assert!(plan_byte_search(&group, &regions, 1, PAGE_SIZE, CHUNK_SIZE, read).is_none());
+    }
+}
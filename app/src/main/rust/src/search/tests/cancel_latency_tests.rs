@@ -0,0 +1,253 @@
+//! Cancellation latency tests
+//!
+//! A single huge region filled with matching values keeps the inner scan
+//! loops busy. Cancellation is triggered at a random checkpoint and the
+//! scan must stop within a bounded amount of work, leaving only complete
+//! results behind. The bounds are counted in checkpoints, not wall-clock time.
+//!
+//! The refine tasks are also driven through the global engine, where the
+//! cancel request comes from the shared buffer like it does from Kotlin.
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::cancel::CANCEL_CHECK_CANDIDATES;
+    use crate::search::engine::group_search::{refine_group_stream_with, search_in_buffer_group, search_in_buffer_group_deep_with_cancel};
+    use crate::search::engine::result_stream::REFINE_BATCH_SIZE;
+    use crate::search::engine::single_search::{PAR_SCAN_GRAIN, search_in_chunks_with_status};
+    use crate::search::engine::{SEARCH_ENGINE_MANAGER, SearchStatus};
+    use crate::search::result_manager::SearchResultMode;
+    use crate::search::tests::engine_fixture::EngineFixture;
+    use crate::search::tests::mock_memory::MockMemory;
+    use crate::search::{FuzzyCondition, SearchMode, SearchQuery, SearchValue, ValuePair, ValueType, parse_search_query};
+    use crate::wuwa::PageStatusBitmap;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Allocates one big region where every dword is `value`.
+    fn filled_region(size: usize, value: u32) -> (MockMemory, u64, Vec<u8>, PageStatusBitmap) {
+        let mut mem = MockMemory::new();
+        let base_addr = mem.malloc(0x7000000000, size).unwrap();
+        let pattern: Vec<u8> = value.to_le_bytes().iter().copied().cycle().take(size).collect();
+        mem.mem_write(base_addr, &pattern).unwrap();
+
+        let mut buffer = vec![0u8; size];
+        let mut page_status = PageStatusBitmap::new(size, base_addr as usize);
+        mem.mem_read_with_status(base_addr, &mut buffer, &mut page_status).unwrap();
+
+        (mem, base_addr, buffer, page_status)
+    }
+
+    /// Cancellation check that fires from the `trigger_at`-th call on and counts every call.
+    struct Trigger {
+        calls: AtomicUsize,
+        trigger_at: usize,
+    }
+
+    impl Trigger {
+        fn random(max: usize) -> Self {
+            Self::at((rand::random::<u32>() as usize % max) + 1)
+        }
+
+        fn at(trigger_at: usize) -> Self {
+            Self {
+                calls: AtomicUsize::new(0),
+                trigger_at,
+            }
+        }
+
+        fn check(&self) -> bool {
+            self.calls.fetch_add(1, Ordering::Relaxed) + 1 >= self.trigger_at
+        }
+
+        fn calls(&self) -> usize {
+            self.calls.load(Ordering::Relaxed)
+        }
+    }
+
+    fn assert_all_values(mem: &MockMemory, results: &[ValuePair], value: u32) {
+        for pair in results {
+            let bytes = mem.mem_read(pair.addr, 4).unwrap();
+            assert_eq!(u32::from_le_bytes(bytes.try_into().unwrap()), value, "Corrupted result at 0x{:X}", pair.addr);
+        }
+    }
+
+    #[test]
+    fn test_single_search_cancel_latency() {
+        let size = 32 * 1024 * 1024;
+        let (mem, base_addr, buffer, page_status) = filled_region(size, 100);
+        let target = SearchValue::fixed(100, ValueType::Dword);
+        let trigger = Trigger::random(64);
+
+        let mut results = Vec::new();
+        search_in_chunks_with_status(
+            &buffer,
+            base_addr,
+            base_addr,
+            base_addr + size as u64,
+            4,
+            &target,
+            ValueType::Dword,
+            &page_status,
+            &mut results,
+            &|| trigger.check(),
+        );
+
+        // 每段开始前检查一次，只有检查通过的段被扫描，每段都是满的
+        assert!(trigger.calls() >= trigger.trigger_at);
+        assert_eq!(results.len(), (trigger.trigger_at - 1) * PAR_SCAN_GRAIN / 4);
+        assert!(results.iter().all(|p| p.addr % 4 == 0));
+        assert_all_values(&mem, &results, 100);
+    }
+
+    #[test]
+    fn test_group_search_cancel_bounded_work() {
+        let size = 8 * 1024 * 1024;
+        let (mem, base_addr, buffer, page_status) = filled_region(size, 100);
+        let values = vec![
+            SearchValue::fixed(100, ValueType::Dword),
+            SearchValue::fixed(100, ValueType::Dword),
+        ];
+        let query = SearchQuery::new(values, SearchMode::Unordered, 16);
+        let trigger = Trigger::random(32);
+
+        let mut results = Vec::new();
+        let mut matches_checked = 0usize;
+        search_in_buffer_group(
+            &buffer,
+            base_addr,
+            base_addr,
+            base_addr + size as u64,
+            4,
+            &query,
+            &page_status,
+            &mut results,
+            &mut matches_checked,
+            &|| trigger.check(),
+        );
+
+        // 触发取消的那次检查之后不应再有任何候选被处理
+        assert_eq!(trigger.calls(), trigger.trigger_at);
+        assert!(matches_checked <= (trigger.trigger_at - 1) * CANCEL_CHECK_CANDIDATES);
+
+        // 组结果是整组写入的
+        assert_eq!(results.len() % query.values.len(), 0);
+        assert_all_values(&mem, &results, 100);
+    }
+
+    #[test]
+    fn test_deep_group_search_cancel_latency() {
+        let size = 4 * 1024 * 1024;
+        let (mem, base_addr, buffer, page_status) = filled_region(size, 100);
+        let values = vec![
+            SearchValue::fixed(100, ValueType::Dword),
+            SearchValue::fixed(100, ValueType::Dword),
+            SearchValue::fixed(100, ValueType::Dword),
+        ];
+        // 全部相同的值让 DFS 组合数爆炸
+        let query = SearchQuery::new(values, SearchMode::Ordered, 128);
+        let trigger = Trigger::random(16);
+
+        let mut results = Vec::new();
+        let mut matches_checked = 0usize;
+        search_in_buffer_group_deep_with_cancel(
+            &buffer,
+            base_addr,
+            base_addr,
+            base_addr + size as u64,
+            4,
+            &query,
+            &page_status,
+            &mut results,
+            &mut matches_checked,
+            &|| trigger.check(),
+        );
+
+        // 锚点循环和回溯里的检查点都在同一个线程上，触发之后不再检查
+        assert_eq!(trigger.calls(), trigger.trigger_at);
+        assert!(matches_checked <= trigger.trigger_at * CANCEL_CHECK_CANDIDATES);
+        assert!(matches_checked < size / 4);
+        assert_all_values(&mem, &results, 100);
+    }
+
+    #[test]
+    fn test_group_refine_cancels_inside_dfs() {
+        let base_addr = 0x7000000000u64;
+        let pairs: Vec<ValuePair> = (0..256).map(|i| ValuePair::new(base_addr + i * 4, ValueType::Dword)).collect();
+        let values = vec![SearchValue::fixed(100, ValueType::Dword); 4];
+        // 全部相同的值，单个锚点的窗口里就有几十万种组合
+        let query = SearchQuery::new(values, SearchMode::Ordered, 256).with_first_match_only(false);
+        let read = |_addr: u64, buf: &mut [u8]| {
+            buf.copy_from_slice(&100u32.to_le_bytes());
+            true
+        };
+
+        // 前三次检查：开始前、读完一批后、第一个锚点回溯前，之后的检查都在回溯里
+        let dfs_checks = (rand::random::<u32>() as usize % 8) + 1;
+        let trigger = Trigger::at(3 + dfs_checks);
+        let processed = Arc::new(AtomicUsize::new(0));
+        // 单线程执行，锚点逐个回溯，检查次数是确定的
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+        let refined = pool.install(|| {
+            refine_group_stream_with(pairs.iter().cloned(), &query, REFINE_BATCH_SIZE, read, Some(&processed), None, &|| trigger.check(), &|_, _| {})
+        });
+
+        assert!(refined.is_empty());
+        assert_eq!(trigger.calls(), trigger.trigger_at);
+        // 第一个锚点在回溯中途停止，没有任何锚点处理完
+        assert_eq!(processed.load(Ordering::Relaxed), 0);
+    }
+
+    fn total_count() -> usize {
+        SEARCH_ENGINE_MANAGER.read().unwrap().get_total_count().unwrap()
+    }
+
+    fn current_mode() -> SearchResultMode {
+        SEARCH_ENGINE_MANAGER.read().unwrap().get_current_mode().unwrap()
+    }
+
+    /// 改善任务开始读取前在共享缓冲区请求取消：任务中的读取被模拟内存的锁挡住，
+    /// 取消请求一定先于任何读取完成，改善以 Cancelled 结束，结果集不变
+    fn refine_cancelled_from_shared_buffer(fixture: &EngineFixture, query: SearchQuery, condition: Option<FuzzyCondition>) {
+        let before = total_count();
+        let guard = fixture.memory.lock().unwrap();
+        SEARCH_ENGINE_MANAGER.write().unwrap().start_refine_async(query, condition).unwrap();
+        fixture.request_cancel();
+        drop(guard);
+        assert_eq!(fixture.wait_idle(), SearchStatus::Cancelled);
+        assert_eq!(total_count(), before);
+    }
+
+    #[test]
+    fn test_refine_task_observes_shared_buffer_cancel() {
+        let size = 64 * 1024;
+        let (mem, base_addr, _, _) = filled_region(size, 100);
+        let fixture = EngineFixture::new("refine_cancel", mem);
+        let regions = vec![(base_addr, base_addr + size as u64)];
+
+        let query = parse_search_query("100", ValueType::Dword).unwrap();
+        SEARCH_ENGINE_MANAGER.write().unwrap().start_search_async(query.clone(), regions.clone(), false, false, false).unwrap();
+        assert_eq!(fixture.wait_idle(), SearchStatus::Completed);
+        assert_eq!(total_count(), size / 4);
+        refine_cancelled_from_shared_buffer(&fixture, query.clone(), None);
+        refine_cancelled_from_shared_buffer(&fixture, query.clone(), Some(FuzzyCondition::Unchanged));
+
+        // 保留结果转换为模糊结果，取消后精确结果不变
+        let guard = fixture.memory.lock().unwrap();
+        SEARCH_ENGINE_MANAGER.write().unwrap().start_fuzzy_search_async(ValueType::Dword, None, regions.clone(), true, false).unwrap();
+        fixture.request_cancel();
+        drop(guard);
+        assert_eq!(fixture.wait_idle(), SearchStatus::Cancelled);
+        assert_eq!(current_mode(), SearchResultMode::Exact);
+        assert_eq!(total_count(), size / 4);
+
+        SEARCH_ENGINE_MANAGER.write().unwrap().start_fuzzy_search_async(ValueType::Dword, None, regions.clone(), true, false).unwrap();
+        assert_eq!(fixture.wait_idle(), SearchStatus::Completed);
+        assert_eq!(current_mode(), SearchResultMode::Fuzzy);
+        assert_eq!(total_count(), size / 4);
+
+        // 模糊结果原地改善，取消后回滚
+        SEARCH_ENGINE_MANAGER.write().unwrap().start_fuzzy_search_async(ValueType::Dword, None, regions, false, false).unwrap();
+        assert_eq!(fixture.wait_idle(), SearchStatus::Completed);
+        refine_cancelled_from_shared_buffer(&fixture, query, Some(FuzzyCondition::Unchanged));
+    }
+}
@@ -0,0 +1,127 @@
+//! Chunk boundary group search tests
+//!
+//! 区域按块读取时，锚点靠近块末尾、其余值落在下一块开头（或反过来）的组也必须被找到，
+//! 并且在相邻两块的重叠区里只报告一次。分别用 64K 和 512K 的块大小在块边界两侧放置
+//! Qword / Double 组，普通和深度模式的结果都要和整块扫描一致。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::group_search::{scan_region_group_chunks, search_in_buffer_group_deep_with_cancel};
+    use crate::search::engine::read_stats::ReadStats;
+    use crate::search::tests::mock_memory::MockMemory;
+    use crate::search::{parse_search_query, SearchQuery, ValueType};
+    use crate::wuwa::PageStatusBitmap;
+    use std::collections::BTreeSet;
+
+    const BASE: u64 = 0x7B00000000;
+    const CHUNK_SIZES: [usize; 2] = [64 * 1024, 512 * 1024];
+    const CHUNKS: usize = 3;
+
+    fn memory(chunk: usize) -> MockMemory {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, chunk * CHUNKS).unwrap();
+        mem
+    }
+
+    fn scan(mem: &MockMemory, query: &SearchQuery, chunk: usize, deep: bool) -> Vec<u64> {
+        let end = BASE + (chunk * CHUNKS) as u64;
+        let read = |addr: u64, buf: &mut [u8], page_status: &mut PageStatusBitmap| mem.mem_read_with_status(addr, buf, page_status);
+        let results = scan_region_group_chunks(query, BASE, end, chunk, deep, read, &|| false, &ReadStats::new());
+        results.iter().map(|pair| pair.addr).collect()
+    }
+
+    /// 不分块，一次读取整个区域做深度扫描的结果，作为对照
+    fn scan_whole(mem: &MockMemory, query: &SearchQuery, chunk: usize) -> BTreeSet<u64> {
+        let size = chunk * CHUNKS;
+        let mut buffer = vec![0u8; size];
+        let mut page_status = PageStatusBitmap::new(size, BASE as usize);
+        mem.mem_read_with_status(BASE, &mut buffer, &mut page_status).unwrap();
+        let mut results = Vec::new();
+        search_in_buffer_group_deep_with_cancel(&buffer, BASE, BASE, BASE + size as u64, 8, query, &page_status, &mut results, &mut 0, &|| false);
+        results.iter().map(|pair| pair.addr).collect()
+    }
+
+    /// 结果必须严格递增（没有重复），并且正好是 expected
+    fn assert_unique(found: &[u64], expected: &[u64]) {
+        assert!(found.windows(2).all(|pair| pair[0] < pair[1]), "duplicate or unsorted results: {:X?}", found);
+        assert_eq!(found, expected);
+    }
+
+    fn write_qwords(mem: &mut MockMemory, addrs: &[u64], values: &[u64]) {
+        for (&addr, &value) in addrs.iter().zip(values) {
+            mem.mem_write_u64(addr, value).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_qword_group_straddling_chunk_boundary() {
+        let query = parse_search_query("1111111111;2222222222;3333333333::64", ValueType::Qword).unwrap();
+        for chunk in CHUNK_SIZES {
+            let boundary = BASE + chunk as u64;
+            for (anchor_offset, deep) in [(-8i64, false), (-8, true), (-24, false), (-24, true)] {
+                let mut mem = memory(chunk);
+                let anchor = boundary.checked_add_signed(anchor_offset).unwrap();
+                let addrs = [anchor, anchor + 16, anchor + 40];
+                write_qwords(&mut mem, &addrs, &[1111111111, 2222222222, 3333333333]);
+
+                let found = scan(&mem, &query, chunk, deep);
+                assert_unique(&found, &addrs);
+            }
+        }
+    }
+
+    #[test]
+    fn test_double_group_with_anchor_after_boundary() {
+        // 第一个值在上一块末尾，锚点和其余值在下一块开头
+        let query = parse_search_query("1.5;2.5;3.5:64", ValueType::Double).unwrap();
+        for chunk in CHUNK_SIZES {
+            for boundary in [BASE + chunk as u64, BASE + 2 * chunk as u64] {
+                let mut mem = memory(chunk);
+                let addrs = [boundary - 8, boundary, boundary + 24];
+                for (&addr, value) in addrs.iter().zip([1.5f64, 2.5, 3.5]) {
+                    mem.mem_write_f64(addr, value).unwrap();
+                }
+
+                for deep in [false, true] {
+                    let found = scan(&mem, &query, chunk, deep);
+                    assert_unique(&found, &addrs);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_deep_duplicates_across_boundary_match_whole_scan() {
+        // 重复的值分布在边界两侧，深度模式的组合必须和不分块扫描完全一样
+        let query = parse_search_query("7;9::48", ValueType::Qword).unwrap();
+        for chunk in CHUNK_SIZES {
+            let boundary = BASE + chunk as u64;
+            let mut mem = memory(chunk);
+            write_qwords(&mut mem, &[boundary - 32, boundary - 8, boundary + 8, boundary + 16, boundary + 40], &[7, 9, 7, 9, 9]);
+
+            let found = scan(&mem, &query, chunk, true);
+            let whole: Vec<u64> = scan_whole(&mem, &query, chunk).into_iter().collect();
+            assert!(!whole.is_empty());
+            assert_unique(&found, &whole);
+        }
+    }
+
+    #[test]
+    fn test_group_before_unreadable_chunk_still_found() {
+        // 下一块读取失败时，留给它的锚点用上一块保留的数据补扫
+        let query = parse_search_query("1111111111;2222222222::64", ValueType::Qword).unwrap();
+        for chunk in CHUNK_SIZES {
+            let boundary = BASE + chunk as u64;
+            let mut mem = memory(chunk);
+            let addrs = [boundary - 16, boundary - 8];
+            write_qwords(&mut mem, &addrs, &[1111111111, 2222222222]);
+            let pages: Vec<usize> = (chunk / 4096..2 * chunk / 4096).collect();
+            mem.set_faulty_pages(BASE, &pages).unwrap();
+
+            for deep in [false, true] {
+                let found = scan(&mem, &query, chunk, deep);
+                assert_unique(&found, &addrs);
+            }
+        }
+    }
+}
@@ -0,0 +1,268 @@
+//! Automatic compatibility mode tests
+//!
+//! A first scan above the auto threshold keeps exact results only; the
+//! deferred value capture must run exactly once when a refine brings the
+//! count under the threshold. The policy is tested directly, and the capture
+//! branch runs through the same capture and store steps as `run_refine_task`.
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::compat::{CompatPolicy, capture_fuzzy_values};
+    use crate::search::engine::provenance::PassLookup;
+    use crate::search::engine::result_stream::REFINE_BATCH_SIZE;
+    use crate::search::engine::shared_buffer::{flags, layout};
+    use crate::search::engine::single_search::refine_values_stream_with;
+    use crate::search::engine::SHARED_BUFFER_SIZE;
+    use crate::search::result_manager::SearchResultMode;
+    use crate::search::tests::mock_memory::MockMemory;
+    use crate::search::tests::temp_dir::TempDir;
+    use crate::search::{SearchEngineManager, SearchResultItem, SearchValue, ValuePair, ValueType};
+
+    const BASE: u64 = 0x7200000000;
+    const REGION_SIZE: usize = 64 * 1024;
+    const CELLS: usize = REGION_SIZE / 4;
+    const THRESHOLD: usize = 1000;
+
+    fn addr(idx: usize) -> u64 {
+        BASE + (idx * 4) as u64
+    }
+
+    fn no_cancel() -> bool {
+        false
+    }
+
+    fn read_with(mem: &MockMemory) -> impl Fn(u64, &mut [u8]) -> anyhow::Result<()> + Sync + '_ {
+        |addr, buf| mem.mem_read_into(addr, buf)
+    }
+
+    fn filled_memory(value: u32) -> MockMemory {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, REGION_SIZE).unwrap();
+        let pattern: Vec<u8> = value.to_le_bytes().iter().copied().cycle().take(REGION_SIZE).collect();
+        mem.mem_write(BASE, &pattern).unwrap();
+        mem
+    }
+
+    fn requested_policy(threshold: usize) -> CompatPolicy {
+        let mut policy = CompatPolicy::new();
+        policy.set_requested(true);
+        policy.set_auto_threshold(threshold);
+        policy
+    }
+
+    #[test]
+    fn test_huge_first_scan_defers_capture() {
+        let mut auto = requested_policy(THRESHOLD);
+        assert!(!auto.should_store_fuzzy(CELLS));
+        auto.on_scan_stored(false);
+        let state = auto.state(false);
+        assert!(state.requested && state.deferred && !state.active);
+
+        // 阈值为 0 时始终按兼容格式存储
+        let mut always = requested_policy(0);
+        assert!(always.should_store_fuzzy(CELLS));
+        always.on_scan_stored(true);
+        let state = always.state(true);
+        assert!(state.requested && state.active && !state.deferred);
+        assert!(!always.should_capture(1));
+
+        // 没有开启兼容模式时不延迟也不捕获
+        let mut off = CompatPolicy::new();
+        off.set_auto_threshold(THRESHOLD);
+        assert!(!off.should_store_fuzzy(1));
+        off.on_scan_stored(false);
+        assert!(!off.is_deferred() && !off.should_capture(1));
+    }
+
+    #[test]
+    fn test_capture_only_below_threshold_and_once() {
+        let mut policy = requested_policy(THRESHOLD);
+        policy.on_scan_stored(false);
+        assert!(!policy.should_capture(THRESHOLD + 1));
+        assert!(policy.should_capture(THRESHOLD));
+
+        policy.mark_captured();
+        assert!(!policy.is_deferred());
+        assert!(!policy.should_capture(1));
+        assert!(policy.state(true).active);
+
+        // 关闭兼容模式、清空结果都会清除延迟标记；恢复会话时只有开启兼容模式才恢复延迟
+        policy.on_scan_stored(false);
+        policy.reset();
+        assert!(!policy.is_deferred());
+        policy.restore(false, true);
+        assert!(!policy.is_deferred());
+        policy.restore(true, true);
+        assert!(policy.should_capture(1));
+        policy.set_requested(false);
+        assert!(!policy.is_deferred());
+    }
+
+    /// 与 `run_refine_task` 相同：按条件改善精确结果后，用同样的捕获和写回步骤处理幸存者
+    fn refine_and_store(manager: &mut SearchEngineManager, mem: &MockMemory, value: i64) -> bool {
+        let original_mode = manager.get_current_mode().unwrap();
+        let compat = Some(*manager.compat_mut());
+        let result_mgr = manager.result_manager_mut().unwrap();
+        let mut cursor = result_mgr.cursor(REFINE_BATCH_SIZE).unwrap();
+        let addresses: Vec<ValuePair> = std::iter::from_fn(|| Some(cursor.next_batch(result_mgr).unwrap()).filter(|batch| !batch.is_empty()))
+            .flatten()
+            .map(|item| ValuePair::new(item.address, item.typ))
+            .collect();
+        let target = SearchValue::fixed(value as i128, ValueType::Dword);
+        let read = |addr: u64, buf: &mut [u8]| mem.mem_read_into(addr, buf).is_ok();
+        let refined = refine_values_stream_with(addresses, &target, REFINE_BATCH_SIZE, read, None, None, &no_cancel, &|_, _| {});
+
+        let (captured, compat_captured) =
+            SearchEngineManager::capture_refined_values(&refined, 0, original_mode, compat, read_with(mem), &no_cancel, &|_| {});
+        manager.store_refined_results(refined, captured, compat_captured, &PassLookup::Uniform(0), original_mode, 0).unwrap();
+        compat_captured
+    }
+
+    fn flags(memory: &[u8]) -> i32 {
+        i32::from_le_bytes(memory[layout::FLAGS..layout::FLAGS + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_refine_below_threshold_captures_once() {
+        let mut mem = filled_memory(100);
+        let dir = TempDir::new("compat_capture");
+        let mut memory = [0u8; SHARED_BUFFER_SIZE];
+        let mut manager = SearchEngineManager::new();
+        manager.init(0, dir.to_string_lossy().into_owned(), 0).unwrap();
+        assert!(manager.set_shared_buffer(memory.as_mut_ptr(), memory.len()));
+        manager.set_compatibility_mode(true);
+        manager.set_compat_auto_threshold(THRESHOLD);
+
+        // 首次扫描超过阈值：只存精确结果并延迟捕获
+        manager.set_result_mode(SearchResultMode::Exact).unwrap();
+        manager.add_results_batch((0..CELLS).map(|idx| SearchResultItem::new_exact(addr(idx), ValueType::Dword)).collect()).unwrap();
+        manager.compat_mut().on_scan_stored(false);
+        assert!(manager.get_compatibility_mode().deferred);
+
+        // 一半的值改变：仍然超过阈值，不捕获
+        for idx in (0..CELLS).step_by(2) {
+            mem.mem_write_u32(addr(idx), 200).unwrap();
+        }
+        assert!(!refine_and_store(&mut manager, &mem, 100));
+        assert_eq!(manager.get_current_mode().unwrap(), SearchResultMode::Exact);
+        assert_eq!(manager.get_total_count().unwrap(), CELLS / 2);
+        assert_eq!(flags(&memory) & flags::COMPAT_CAPTURED, 0);
+
+        // 600 个变成 300：结果数降到阈值以下，捕获当前值并切换到模糊格式
+        let hot: Vec<usize> = (1..CELLS).step_by(2).take(600).collect();
+        for &idx in &hot {
+            mem.mem_write_u32(addr(idx), 300).unwrap();
+        }
+        assert!(refine_and_store(&mut manager, &mem, 300));
+        assert_eq!(manager.get_current_mode().unwrap(), SearchResultMode::Fuzzy);
+        assert_eq!(manager.get_total_count().unwrap(), hot.len());
+        let state = manager.get_compatibility_mode();
+        assert!(state.active && !state.deferred);
+        assert_ne!(flags(&memory) & flags::COMPAT_CAPTURED, 0);
+        let values = manager.result_manager_mut().unwrap().get_all_fuzzy_results().unwrap();
+        assert!(values.iter().all(|item| item.as_i64() == 300));
+
+        // 之后的改善不再是兼容模式捕获，模糊结果照常读取当前值
+        mem.mem_write_u32(addr(hot[0]), 301).unwrap();
+        assert!(!refine_and_store(&mut manager, &mem, 300));
+        assert_eq!(manager.get_current_mode().unwrap(), SearchResultMode::Fuzzy);
+        assert_eq!(manager.get_total_count().unwrap(), hot.len() - 1);
+        manager.clear_shared_buffer();
+    }
+
+    #[test]
+    fn test_capture_reports_progress_and_honors_cancel() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let mem = filled_memory(100);
+        let pairs: Vec<ValuePair> = (0..CELLS).map(|idx| ValuePair::new(addr(idx), ValueType::Dword)).collect();
+        let read = |addr: u64, buf: &mut [u8]| -> anyhow::Result<()> {
+            buf.copy_from_slice(&mem.mem_read(addr, buf.len())?);
+            Ok(())
+        };
+
+        let reported = AtomicUsize::new(0);
+        let captured = capture_fuzzy_values(&pairs, 0, read, &no_cancel, &|done| {
+            reported.fetch_max(done, Ordering::Relaxed);
+        });
+        assert_eq!(captured.len(), CELLS);
+        assert_eq!(reported.load(Ordering::Relaxed), CELLS);
+
+        let reported = AtomicUsize::new(0);
+        let captured = capture_fuzzy_values(&pairs, 0, read, &|| true, &|done| {
+            reported.fetch_max(done, Ordering::Relaxed);
+        });
+        assert!(captured.is_empty());
+        assert_eq!(reported.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_batched_capture_matches_naive_reads() {
+        use crate::search::PAGE_SIZE;
+        use crate::search::result_manager::FuzzySearchResultItem;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::time::Instant;
+
+        const PAGES: usize = 256;
+        let page_size = *PAGE_SIZE;
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, PAGES * page_size).unwrap();
+        let data: Vec<u8> = (0..PAGES * page_size).map(|i| (i * 31 % 251) as u8).collect();
+        mem.mem_write(BASE, &data).unwrap();
+
+        // 第 7 页和第 100 页不可读：读取范围碰到这些页就整体失败
+        let faulty = [7usize, 100];
+        let reads = AtomicUsize::new(0);
+        let read = |addr: u64, buf: &mut [u8]| -> anyhow::Result<()> {
+            reads.fetch_add(1, Ordering::Relaxed);
+            let first = ((addr - BASE) as usize) / page_size;
+            let last = ((addr - BASE) as usize + buf.len() - 1) / page_size;
+            if faulty.iter().any(|page| (first..=last).contains(page)) {
+                anyhow::bail!("faulty page");
+            }
+            mem.mem_read_into(addr, buf)
+        };
+
+        // 混合类型，包括跨页、跨进不可读页的值
+        let types = [ValueType::Dword, ValueType::Qword, ValueType::Byte, ValueType::Float, ValueType::Word, ValueType::Double];
+        let mut pairs: Vec<ValuePair> = (0..PAGES * page_size / 6)
+            .map(|i| ValuePair::new(BASE + (i * 6) as u64, types[i % types.len()]))
+            .filter(|pair| pair.addr + pair.value_type.size() as u64 <= BASE + (PAGES * page_size) as u64)
+            .collect();
+        pairs.push(ValuePair::new(BASE + (8 * page_size - 2) as u64, ValueType::Dword));
+        pairs.sort_by_key(|pair| pair.addr);
+
+        let started = Instant::now();
+        let mut buffer = [0u8; 8];
+        let naive: Vec<FuzzySearchResultItem> = pairs
+            .iter()
+            .filter_map(|pair| {
+                let size = pair.value_type.size();
+                read(pair.addr, &mut buffer[..size]).ok()?;
+                Some(FuzzySearchResultItem::from_bytes(pair.addr, &buffer[..size], pair.value_type))
+            })
+            .collect();
+        let naive_time = started.elapsed();
+        let naive_reads = reads.swap(0, Ordering::Relaxed);
+
+        let started = Instant::now();
+        let batched = capture_fuzzy_values(&pairs, 0, read, &no_cancel, &|_| {});
+        let batched_time = started.elapsed();
+        let batched_reads = reads.load(Ordering::Relaxed);
+
+        println!(
+            "capture {} values: naive {} reads in {:?}, batched {} reads in {:?}",
+            pairs.len(),
+            naive_reads,
+            naive_time,
+            batched_reads,
+            batched_time
+        );
+
+        // 结果项的相等只比较地址，这里连同值和类型一起比较
+        let key = |item: &FuzzySearchResultItem| (item.address, item.value, item.value_type);
+        assert!(naive.len() < pairs.len());
+        assert_eq!(batched.iter().map(key).collect::<Vec<_>>(), naive.iter().map(key).collect::<Vec<_>>());
+        assert!(batched_reads * 10 < naive_reads, "{} batched reads vs {} naive", batched_reads, naive_reads);
+    }
+}
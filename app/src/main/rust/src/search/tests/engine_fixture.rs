@@ -0,0 +1,84 @@
+//! Global engine fixture
+//!
+//! `SEARCH_ENGINE_MANAGER` 和 `DRIVER_MANAGER` 是进程内的全局对象，用到它们的测试先取得 `lock_engine()`，
+//! 串行执行。`EngineFixture` 初始化引擎、设置共享缓冲区并把驱动读写换成 MockMemory，销毁时全部还原。
+
+use crate::core::DRIVER_MANAGER;
+use crate::search::engine::{SearchEngineManager, SearchStatus, SharedBuffer, SEARCH_ENGINE_MANAGER, SHARED_BUFFER_SIZE};
+use crate::search::tests::mock_memory::MockMemory;
+use crate::search::tests::temp_dir::TempDir;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+static ENGINE_LOCK: Mutex<()> = Mutex::new(());
+
+/// 取得全局引擎的测试锁，之前的测试失败不影响后面的测试
+pub fn lock_engine() -> MutexGuard<'static, ()> {
+    ENGINE_LOCK.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// 把全局引擎和驱动管理器恢复成未初始化、未绑定的状态
+pub fn reset_engine() {
+    *SEARCH_ENGINE_MANAGER.write().unwrap_or_else(PoisonError::into_inner) = SearchEngineManager::new();
+    DRIVER_MANAGER.write().unwrap_or_else(PoisonError::into_inner).set_test_memory(None);
+}
+
+pub struct EngineFixture {
+    pub memory: Arc<Mutex<MockMemory>>,
+    // 与引擎共用 `shared` 的内存，只用来读取状态
+    view: SharedBuffer,
+    _shared: Vec<u8>,
+    _dir: TempDir,
+    _guard: MutexGuard<'static, ()>,
+}
+
+impl EngineFixture {
+    pub fn new(name: &str, memory: MockMemory) -> Self {
+        let guard = lock_engine();
+        let dir = TempDir::new(name);
+        let memory = Arc::new(Mutex::new(memory));
+        let mut shared = vec![0u8; SHARED_BUFFER_SIZE];
+        let mut view = SharedBuffer::default();
+        assert!(view.set(shared.as_mut_ptr(), shared.len()));
+
+        DRIVER_MANAGER.write().unwrap().set_test_memory(Some(Arc::clone(&memory)));
+        {
+            let mut manager = SEARCH_ENGINE_MANAGER.write().unwrap();
+            manager.init(0, dir.to_string_lossy().into_owned(), 0).unwrap();
+            assert!(manager.set_shared_buffer(shared.as_mut_ptr(), shared.len()));
+        }
+
+        Self { memory, view, _shared: shared, _dir: dir, _guard: guard }
+    }
+
+    /// 等待当前任务结束，返回最终状态
+    pub fn wait_idle(&self) -> SearchStatus {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while SEARCH_ENGINE_MANAGER.read().unwrap().is_searching() {
+            assert!(Instant::now() < deadline, "search did not finish");
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        self.view.read_status()
+    }
+
+    /// 像 Kotlin 一样在共享缓冲区里请求取消
+    pub fn request_cancel(&self) {
+        self.view.request_cancel();
+    }
+
+    pub fn flags(&self) -> i32 {
+        self.view.read_flags()
+    }
+
+    pub fn error_code(&self) -> i32 {
+        self.view.read_error_code()
+    }
+}
+
+impl Drop for EngineFixture {
+    fn drop(&mut self) {
+        // 先让引擎放开共享缓冲区，再释放内存
+        reset_engine();
+        self.view.clear();
+    }
+}
@@ -0,0 +1,143 @@
+//! Scan dry-run estimator tests
+//!
+//! Throughput is simulated with a MockMemory read that sleeps per MiB, so the
+//! probe-seeded statistic and the resulting duration estimate are predictable.
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::estimate::{
+        estimate_scan, ScanLimits, ScanWarning, ThroughputStats, LOW_FREE_DISK_BYTES, PROBE_BYTES,
+    };
+    use crate::search::result_manager::FuzzySearchResultItem;
+    use crate::search::tests::mock_memory::MockMemory;
+    use crate::search::ValueType;
+    use crate::search::tests::temp_dir::TempDir;
+    use std::cell::Cell;
+    use std::time::Duration;
+
+    const BASE: u64 = 0x7300000000;
+    const MIB: u64 = 1024 * 1024;
+    const MILLIS_PER_MIB: u64 = 10;
+    const ITEM_SIZE: u64 = size_of::<FuzzySearchResultItem>() as u64;
+
+    fn seeded_stats() -> ThroughputStats {
+        let mut stats = ThroughputStats::default();
+        stats.record(100 * MIB, Duration::from_secs(1));
+        stats
+    }
+
+    fn no_probe(_: u64, _: &mut [u8]) -> anyhow::Result<()> {
+        panic!("probe must not run when history exists");
+    }
+
+    #[test]
+    fn test_probe_seeds_throughput_when_history_empty() {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, PROBE_BYTES).unwrap();
+
+        let reads = Cell::new(0u64);
+        let probe = |addr: u64, buf: &mut [u8]| -> anyhow::Result<()> {
+            buf.copy_from_slice(&mem.mem_read(addr, buf.len())?);
+            let mib = (buf.len() as u64).div_ceil(MIB);
+            std::thread::sleep(Duration::from_millis(mib * MILLIS_PER_MIB));
+            reads.set(reads.get() + buf.len() as u64);
+            Ok(())
+        };
+
+        // 16MB 已分配，后面 48MB 只参与预估，探测读取不会碰到
+        let regions = [(BASE, BASE + PROBE_BYTES as u64), (BASE + 0x1000_0000, BASE + 0x1000_0000 + 48 * MIB)];
+        let mut stats = ThroughputStats::default();
+        let estimate = estimate_scan(ValueType::Dword, &regions, false, &mut stats, ScanLimits::default(), None, probe);
+
+        assert!(estimate.probed);
+        assert_eq!(reads.get(), PROBE_BYTES as u64);
+        assert_eq!(estimate.total_bytes, 64 * MIB);
+        assert_eq!(estimate.region_count, 2);
+        assert_eq!(estimate.projected_results, None);
+        assert_eq!(stats.samples, 1);
+
+        // 64MiB at 10ms/MiB，允许调度和拷贝开销带来的误差
+        let expected = 64 * MILLIS_PER_MIB;
+        let estimated = estimate.estimated_millis.unwrap();
+        assert!(
+            estimated >= expected * 8 / 10 && estimated <= expected * 3 / 2,
+            "estimated {} ms, expected about {} ms",
+            estimated,
+            expected
+        );
+
+        // 有历史后不再探测
+        let again = estimate_scan(ValueType::Dword, &regions, false, &mut stats, ScanLimits::default(), None, no_probe);
+        assert!(!again.probed);
+        assert_eq!(again.estimated_millis, estimate.estimated_millis);
+    }
+
+    #[test]
+    fn test_unreadable_probe_leaves_duration_unknown() {
+        let probe = |addr: u64, _: &mut [u8]| -> anyhow::Result<()> { anyhow::bail!("unreadable at 0x{:X}", addr) };
+
+        let regions = [(BASE, BASE + 64 * MIB)];
+        let mut stats = ThroughputStats::default();
+        let estimate = estimate_scan(ValueType::Dword, &regions, false, &mut stats, ScanLimits::default(), None, probe);
+
+        assert!(estimate.probed);
+        assert_eq!(estimate.total_bytes, 64 * MIB);
+        assert_eq!(estimate.estimated_millis, None);
+        assert!(!stats.has_history());
+        assert_eq!(serde_json::to_value(&estimate).unwrap()["estimated_millis"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_throughput_rolls_and_persists() {
+        let dir = TempDir::new("estimate");
+
+        assert!(!ThroughputStats::load(&dir).has_history());
+
+        let mut stats = seeded_stats();
+        // 太短的计时不计入
+        stats.record(MIB, Duration::from_millis(1));
+        assert_eq!(stats.samples, 1);
+        stats.record(200 * MIB, Duration::from_secs(1));
+        assert_eq!(stats.samples, 2);
+        assert!(stats.bytes_per_sec > (100 * MIB) as f64 && stats.bytes_per_sec < (200 * MIB) as f64);
+
+        stats.save(&dir).unwrap();
+        assert_eq!(ThroughputStats::load(&dir), stats);
+    }
+
+    #[test]
+    fn test_budget_warnings_at_thresholds() {
+        let limits = ScanLimits {
+            disk_budget: 1000 * ITEM_SIZE,
+            max_results: 2000,
+        };
+        let estimate = |bytes: u64, free_disk: Option<u64>| {
+            let mut stats = seeded_stats();
+            estimate_scan(ValueType::Dword, &[(BASE, BASE + bytes)], true, &mut stats, limits, free_disk, no_probe)
+        };
+
+        // 正好等于预算：不警告
+        let at_budget = estimate(1000 * 4, None);
+        assert_eq!(at_budget.projected_results, Some(1000));
+        assert_eq!(at_budget.projected_storage_bytes, Some(1000 * ITEM_SIZE));
+        assert!(at_budget.warnings.is_empty());
+
+        let over_budget = estimate(1001 * 4, None);
+        assert_eq!(over_budget.warnings, vec![ScanWarning::ExceedsDiskBudget]);
+
+        let at_max = estimate(2000 * 4, None);
+        assert_eq!(at_max.warnings, vec![ScanWarning::ExceedsDiskBudget]);
+
+        let over_max = estimate(2001 * 4, None);
+        assert_eq!(over_max.warnings, vec![ScanWarning::ExceedsDiskBudget, ScanWarning::ExceedsMaxResults]);
+
+        assert!(estimate(4, Some(LOW_FREE_DISK_BYTES)).warnings.is_empty());
+        assert_eq!(estimate(4, Some(LOW_FREE_DISK_BYTES - 1)).warnings, vec![ScanWarning::LowFreeDisk]);
+
+        // 精确扫描不预估存储
+        let mut stats = seeded_stats();
+        let exact = estimate_scan(ValueType::Dword, &[(BASE, BASE + 2001 * 4)], false, &mut stats, limits, None, no_probe);
+        assert_eq!(exact.projected_storage_bytes, None);
+        assert!(exact.warnings.is_empty());
+    }
+}
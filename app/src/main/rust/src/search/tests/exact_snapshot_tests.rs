@@ -0,0 +1,119 @@
+//! Condition refine on exact results tests
+//!
+//! 通过全局引擎在 mock 内存上做带条件的改善：第一次改善以搜索时缓存的值为旧值直接应用条件，
+//! 不可读的地址被丢弃，幸存者以原来的轮次留在精确结果里，之后的改善以上一次的快照为旧值。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::exact_snapshot::{ExactSnapshot, read_seeds};
+    use crate::search::engine::shared_buffer::flags;
+    use crate::search::engine::{SearchStatus, SEARCH_ENGINE_MANAGER};
+    use crate::search::result_manager::{FuzzySearchResultItem, SearchResultManager, SearchResultMode};
+    use crate::search::tests::engine_fixture::EngineFixture;
+    use crate::search::tests::mock_memory::MockMemory;
+    use crate::search::tests::temp_dir::TempDir;
+    use crate::search::{parse_search_query, FuzzyCondition, SearchResultItem, ValueType};
+
+    const BASE: u64 = 0x7400000000;
+    const OTHER: u64 = 0x7400100000;
+
+    fn addr(idx: usize) -> u64 {
+        BASE + (idx * 4) as u64
+    }
+
+    fn exact_results() -> Vec<(u64, u8)> {
+        let manager = SEARCH_ENGINE_MANAGER.read().unwrap();
+        let total = manager.get_total_count().unwrap();
+        manager
+            .get_results(0, total)
+            .unwrap()
+            .iter()
+            .map(|result| match result {
+                SearchResultItem::Exact(item) => (item.address, item.pass),
+                SearchResultItem::Fuzzy(_) => panic!("expected exact result"),
+            })
+            .collect()
+    }
+
+    fn condition_refine(fixture: &EngineFixture, condition: FuzzyCondition) -> SearchStatus {
+        let query = parse_search_query("0", ValueType::Dword).unwrap();
+        SEARCH_ENGINE_MANAGER.write().unwrap().start_refine_async(query, Some(condition)).unwrap();
+        fixture.wait_idle()
+    }
+
+    #[test]
+    fn test_condition_refine_on_exact_results() {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, 4096).unwrap();
+        mem.malloc(OTHER, 4096).unwrap();
+        for idx in 0..3 {
+            mem.mem_write_i32(addr(idx), 100).unwrap();
+        }
+        mem.mem_write_i32(OTHER + 0x40, 100).unwrap();
+        let fixture = EngineFixture::new("exact_condition_refine", mem);
+
+        let query = parse_search_query("100", ValueType::Dword).unwrap();
+        let regions = vec![(BASE, BASE + 4096), (OTHER, OTHER + 4096)];
+        SEARCH_ENGINE_MANAGER.write().unwrap().start_search_async(query, regions, false, false, false).unwrap();
+        assert_eq!(fixture.wait_idle(), SearchStatus::Completed);
+        assert_eq!(exact_results(), vec![(addr(0), 0), (addr(1), 0), (addr(2), 0), (OTHER + 0x40, 0)]);
+
+        // 第二个区域在改善前被取消映射
+        {
+            let mut mem = fixture.memory.lock().unwrap();
+            mem.mem_write_i32(addr(0), 90).unwrap();
+            mem.mem_write_i32(addr(1), 150).unwrap();
+            mem.free(OTHER).unwrap();
+        }
+
+        // 第一次调用就以搜索时的值为旧值应用条件，不只是建立快照
+        assert_eq!(condition_refine(&fixture, FuzzyCondition::Increased), SearchStatus::Completed);
+        assert_eq!(fixture.flags() & flags::SNAPSHOT_CAPTURED, 0);
+        assert_eq!(exact_results(), vec![(addr(1), 0)]);
+
+        // 幸存者的当前值成为下一次的旧值
+        fixture.memory.lock().unwrap().mem_write_i32(addr(1), 120).unwrap();
+        assert_eq!(condition_refine(&fixture, FuzzyCondition::Decreased), SearchStatus::Completed);
+        assert_eq!(exact_results(), vec![(addr(1), 0)]);
+
+        // 按值改善后幸存者的值重新缓存，下一次条件改善仍直接应用条件
+        let query = parse_search_query("120", ValueType::Dword).unwrap();
+        SEARCH_ENGINE_MANAGER.write().unwrap().start_refine_async(query, None).unwrap();
+        assert_eq!(fixture.wait_idle(), SearchStatus::Completed);
+        fixture.memory.lock().unwrap().mem_write_i32(addr(1), 130).unwrap();
+        assert_eq!(condition_refine(&fixture, FuzzyCondition::Increased), SearchStatus::Completed);
+        assert_eq!(fixture.flags() & flags::SNAPSHOT_CAPTURED, 0);
+        assert_eq!(exact_results(), vec![(addr(1), 0)]);
+    }
+
+    #[test]
+    fn test_snapshot_bound_to_revision() {
+        let snapshot = ExactSnapshot::new(7, vec![FuzzySearchResultItem::from_bytes(addr(0), &5i32.to_le_bytes(), ValueType::Dword)]);
+        assert!(snapshot.is_current(7));
+        assert!(!snapshot.is_current(8));
+        assert_eq!(snapshot.into_items().len(), 1);
+    }
+
+    #[test]
+    fn test_seeds_read_from_cursor_in_batches() {
+        let dir = TempDir::new("exact_snapshot_seeds");
+        let mut mgr = SearchResultManager::new(4096, dir.to_path_buf());
+        mgr.set_mode(SearchResultMode::Exact).unwrap();
+        // 两个升序段，游标按地址归并
+        let first = [5, 7, 9].map(|idx| SearchResultItem::new_exact(addr(idx), ValueType::Dword));
+        let second = [0, 6, 8, 10].map(|idx| SearchResultItem::new_exact(addr(idx), ValueType::Dword).with_pass(1));
+        mgr.add_results_batch(Vec::from(first)).unwrap();
+        mgr.add_results_batch(Vec::from(second)).unwrap();
+
+        let mut cursor = mgr.cursor(2).unwrap();
+        let mut batches = 0;
+        let seeds = read_seeds(cursor.total(), || {
+            batches += 1;
+            cursor.next_batch(&mgr)
+        })
+        .unwrap();
+        assert!(batches > 2);
+        let seeds: Vec<(u64, u8)> = seeds.iter().map(|seed| (seed.address, seed.pass)).collect();
+        assert_eq!(seeds, [0, 5, 6, 7, 8, 9, 10].map(|idx| (addr(idx), u8::from(idx % 2 == 0))));
+    }
+}
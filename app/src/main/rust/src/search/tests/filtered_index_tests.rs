@@ -0,0 +1,104 @@
+//! Filtered index tests
+//!
+//! 过滤器启用时在过滤视图上分页：每页都是满的，总数为过滤后的数量，
+//! 返回的索引是结果存储中的索引；结果集或过滤条件变化后过滤视图重新计算。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::filter::SearchFilter;
+    use crate::search::result_manager::SearchResultMode;
+    use crate::search::{SearchEngineManager, SearchResultItem, ValueType};
+    use crate::search::tests::temp_dir::TempDir;
+
+    const BASE: u64 = 0x7500000000;
+
+    fn address(item: &SearchResultItem) -> u64 {
+        match item {
+            SearchResultItem::Exact(exact) => exact.address,
+            SearchResultItem::Fuzzy(fuzzy) => fuzzy.address,
+        }
+    }
+
+    /// 100 个结果，Dword 和 Float 交替，地址间隔 0x10
+    fn setup(name: &str) -> (SearchEngineManager, TempDir) {
+        let dir = TempDir::new(name);
+        let mut manager = SearchEngineManager::new();
+        manager.init(0, dir.to_string_lossy().into_owned(), 0).unwrap();
+        manager.set_result_mode(SearchResultMode::Exact).unwrap();
+
+        let items = (0..100u64)
+            .map(|i| {
+                let value_type = if i % 2 == 0 { ValueType::Dword } else { ValueType::Float };
+                SearchResultItem::new_exact(BASE + i * 0x10, value_type)
+            })
+            .collect();
+        manager.add_results_batch(items).unwrap();
+        (manager, dir)
+    }
+
+    #[test]
+    fn test_build_filtered_index() {
+        let (mut manager, _dir) = setup("filtered_index_build");
+        let result_mgr = manager.result_manager_mut().unwrap();
+
+        let filter = SearchFilter {
+            enable_address_filter: true,
+            address_start: BASE + 0x100,
+            address_end: BASE + 0x1ff,
+            enable_type_filter: true,
+            type_ids: vec![ValueType::Float],
+            ..Default::default()
+        };
+        assert_eq!(result_mgr.build_filtered_index(&filter).unwrap(), (17..32).step_by(2).collect::<Vec<u32>>());
+        assert_eq!(result_mgr.build_filtered_index(&SearchFilter::new()).unwrap().len(), 100);
+
+        drop(manager);
+    }
+
+    #[test]
+    fn test_pages_are_full_under_filter() {
+        let (mut manager, _dir) = setup("filtered_index_pages");
+
+        manager.set_filter(false, 0, 0, true, vec![ValueType::Float.to_id()]).unwrap();
+        assert_eq!(manager.get_total_count().unwrap(), 50);
+
+        let page = manager.get_filtered_results(10, 20).unwrap();
+        assert_eq!(page.len(), 20);
+        assert_eq!(page.iter().map(|(index, _)| *index).collect::<Vec<_>>(), (21..61).step_by(2).collect::<Vec<usize>>());
+        assert!(page.iter().all(|(index, item)| address(item) == BASE + *index as u64 * 0x10));
+
+        let tail = manager.get_filtered_results(45, 20).unwrap();
+        assert_eq!(tail.len(), 5);
+        assert!(manager.get_filtered_results(50, 20).unwrap().is_empty());
+
+        // 更换过滤条件后重新计算
+        manager.set_filter(true, BASE, BASE + 0x4f, false, Vec::new()).unwrap();
+        assert_eq!(manager.get_total_count().unwrap(), 5);
+        manager.clear_filter().unwrap();
+        assert_eq!(manager.get_total_count().unwrap(), 100);
+
+        drop(manager);
+    }
+
+    #[test]
+    fn test_remove_through_filtered_view() {
+        let (mut manager, _dir) = setup("filtered_index_remove");
+
+        manager.set_filter(false, 0, 0, true, vec![ValueType::Dword.to_id()]).unwrap();
+        let page = manager.get_filtered_results(0, 3).unwrap();
+        let removed: Vec<usize> = page.iter().map(|(index, _)| *index).collect();
+        assert_eq!(removed, vec![0, 2, 4]);
+
+        // 删除使用返回的存储索引，之后过滤视图跟随结果集重建
+        manager.remove_results_batch(removed).unwrap();
+        assert_eq!(manager.get_total_count().unwrap(), 47);
+        let page = manager.get_filtered_results(0, 1).unwrap();
+        assert_eq!(address(&page[0].1), BASE + 0x60);
+        assert_eq!(page[0].0, 3);
+
+        manager.clear_filter().unwrap();
+        assert_eq!(manager.get_total_count().unwrap(), 97);
+
+        drop(manager);
+    }
+}
@@ -0,0 +1,198 @@
+//! Fixed offset group search tests
+//!
+//! `100;200@+0x10;3.5F@+0x14` 形式的查询：锚点是第一个值，其余值只在相对锚点的固定偏移
+//! （可带 ±误差）处匹配。按页分块扫描，块之间重叠 `window_len`，覆盖负偏移和跨块的组。
+//! 偏移都没有误差时直接探测偏移处，结果与收集候选再回溯相同。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::group_match::{collect_buffer_candidates, find_combinations, probe_buffer_offsets, window_len};
+    use crate::search::engine::group_search::{refine_group_values_with_cancel, search_in_buffer_group, try_match_group_at_address};
+    use crate::search::tests::mock_memory::MockMemory;
+    use crate::search::{SearchQuery, ValueType, parse_search_query};
+    use crate::wuwa::PageStatusBitmap;
+    use std::collections::BTreeSet;
+
+    const BASE: u64 = 0x7700000000;
+    const SIZE: usize = 0x4000;
+    const CHUNK: usize = 0x1000;
+
+    fn query(input: &str) -> SearchQuery {
+        parse_search_query(input, ValueType::Dword).unwrap()
+    }
+
+    /// 与 search_region_group 相同的分块方式：第一块从区域开头搜索，之后每块向前重叠 window_len
+    fn scan_chunked(mem: &MockMemory, query: &SearchQuery) -> BTreeSet<u64> {
+        let end = BASE + SIZE as u64;
+        let overlap = window_len(query).min(CHUNK) as u64;
+        let mut results = Vec::new();
+        let mut matches_checked = 0usize;
+
+        let mut current = BASE;
+        while current < end {
+            let chunk_end = (current + CHUNK as u64).min(end);
+            let buffer_addr = if current == BASE { BASE } else { current - overlap };
+            let len = (chunk_end - buffer_addr) as usize;
+            let mut buffer = vec![0u8; len];
+            let mut page_status = PageStatusBitmap::new(len, buffer_addr as usize);
+            mem.mem_read_with_status(buffer_addr, &mut buffer, &mut page_status).unwrap();
+
+            search_in_buffer_group(
+                &buffer,
+                buffer_addr,
+                BASE,
+                chunk_end,
+                4,
+                query,
+                &page_status,
+                &mut results,
+                &mut matches_checked,
+                &|| false,
+            );
+            current = chunk_end;
+        }
+
+        results.iter().map(|pair| pair.addr).collect()
+    }
+
+    fn refine(mem: &MockMemory, query: &SearchQuery, addrs: &BTreeSet<u64>) -> BTreeSet<u64> {
+        let addr_values = addrs.iter().map(|&addr| (addr, mem.mem_read(addr, 4).unwrap())).collect();
+        let refined = refine_group_values_with_cancel(addr_values, query, None, None, &|| false, &|_, _| {});
+        refined.iter().map(|pair| pair.addr).collect()
+    }
+
+    /// 扫描一次、在内存不变时改善一次，两次结果必须相同，返回相对 BASE 的偏移
+    fn scan_then_refine(mem: &MockMemory, query: &SearchQuery) -> BTreeSet<u64> {
+        let scanned = scan_chunked(mem, query);
+        assert_eq!(refine(mem, query, &scanned), scanned);
+        scanned.iter().map(|addr| addr - BASE).collect()
+    }
+
+    fn write_group(mem: &mut MockMemory, anchor: u64, fields: &[(i64, u32)]) {
+        mem.mem_write_u32(BASE + anchor, 100).unwrap();
+        for &(offset, bits) in fields {
+            mem.mem_write_u32((BASE + anchor).checked_add_signed(offset).unwrap(), bits).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_fixed_offsets_match_exact_layout() {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, SIZE).unwrap();
+        let float = 3.5f32.to_bits();
+        write_group(&mut mem, 0x100, &[(0x10, 200), (0x14, float)]);
+        // 3.5 偏了 4 字节，200 离锚点 0x10 但 3.5 不在 +0x14
+        write_group(&mut mem, 0x300, &[(0x10, 200), (0x18, float)]);
+        // 跨块：锚点在第一块末尾，其余两个值在第二块
+        write_group(&mut mem, 0xFF8, &[(0x10, 200), (0x14, float)]);
+
+        let fixed = query("100;200@+0x10;3.5F@+0x14");
+        assert_eq!(scan_then_refine(&mem, &fixed), BTreeSet::from([0x100, 0x110, 0x114, 0xFF8, 0x1008, 0x100C]));
+
+        // 同样的值不带偏移时按 range 窗口匹配，偏了 4 字节的那组也成立
+        let windowed = query("100;200;3.5F:64");
+        assert!(scan_then_refine(&mem, &windowed).contains(&0x318));
+
+        let buffer = mem.mem_read(BASE + 0x100, 0x20).unwrap();
+        assert_eq!(try_match_group_at_address(&buffer, BASE + 0x100, &fixed), Some(vec![0, 0x10, 0x14]));
+        let buffer = mem.mem_read(BASE + 0x300, 0x20).unwrap();
+        assert_eq!(try_match_group_at_address(&buffer, BASE + 0x300, &fixed), None);
+    }
+
+    #[test]
+    fn test_negative_offsets_and_tolerance() {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, SIZE).unwrap();
+        write_group(&mut mem, 0x200, &[(-8, 5), (0x20, 7)]);
+        // 负偏移跨块：锚点在第三块开头，5 在第二块末尾
+        write_group(&mut mem, 0x2004, &[(-8, 5), (0x20, 7)]);
+        // 7 比 +0x20 多 4 字节，只有带误差时成立
+        write_group(&mut mem, 0x3000, &[(-8, 5), (0x24, 7)]);
+
+        // 有偏移时 Ordered 被忽略，在锚点之前的值也能匹配
+        let exact = query("100;5@-8;7@+0x20::64");
+        assert_eq!(scan_then_refine(&mem, &exact), BTreeSet::from([0x1F8, 0x200, 0x220, 0x1FFC, 0x2004, 0x2024]));
+
+        let tolerant = query("100;5@-8;7@+0x20~4");
+        assert_eq!(
+            scan_then_refine(&mem, &tolerant),
+            BTreeSet::from([0x1F8, 0x200, 0x220, 0x1FFC, 0x2004, 0x2024, 0x2FF8, 0x3000, 0x3024])
+        );
+    }
+
+    #[test]
+    fn test_overlap_covers_farthest_offset() {
+        let offsets = query("100;200@+0x10;300@-0x800~8");
+        assert_eq!(window_len(&offsets), 2 * (0x800 + 8) + 4);
+
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, SIZE).unwrap();
+        // 锚点在第二块，-0x800 落回第一块
+        write_group(&mut mem, 0x1100, &[(0x10, 200), (-0x800, 300)]);
+        assert_eq!(scan_then_refine(&mem, &offsets), BTreeSet::from([0x900, 0x1100, 0x1110]));
+    }
+
+    #[test]
+    fn test_exact_offsets_rejected_past_region_end() {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, 0x100).unwrap();
+        write_group(&mut mem, 0x70, &[(0x8, 200)]);
+        mem.mem_write(BASE + 0x7E, &5u16.to_le_bytes()).unwrap();
+
+        let buffer = mem.mem_read(BASE, 0x100).unwrap();
+        let mut page_status = PageStatusBitmap::new(buffer.len(), BASE as usize);
+        page_status.mark_success(0);
+        let fixed = query("100D@0;200D@8;5W@0xE");
+        assert!(fixed.has_exact_offsets());
+
+        let scan = |region_end: u64| {
+            let mut results = Vec::new();
+            search_in_buffer_group(&buffer, BASE, BASE, BASE + region_end, 4, &fixed, &page_status, &mut results, &mut 0, &|| false);
+            results.iter().map(|pair| pair.addr - BASE).collect::<BTreeSet<u64>>()
+        };
+        // 5W 正好在区域末尾结束
+        assert_eq!(scan(0x80), BTreeSet::from([0x70, 0x78, 0x7E]));
+        // 5W 越过区域末尾，即使缓冲区里读得到也不成立
+        assert!(scan(0x7F).is_empty());
+    }
+
+    #[test]
+    fn test_exact_probe_matches_dfs() {
+        // 只有几种取值的密集内存，很多锚点只有部分偏移处匹配
+        let mut buffer = vec![0u8; CHUNK];
+        let mut seed = 0x2545F491u32;
+        for word in buffer.chunks_exact_mut(2) {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            word.copy_from_slice(&[100u16, 200, 5, 7][(seed % 4) as usize].to_le_bytes());
+        }
+        let mut page_status = PageStatusBitmap::new(buffer.len(), BASE as usize);
+        page_status.mark_success(0);
+        let region = (BASE + 0x10, BASE + CHUNK as u64 - 0x10);
+
+        // 后两个永远不成立：同一地址上的两个值，以及没有按 Word 对齐的偏移
+        for (input, any) in [("100W;200W@2;5W@-4", true), ("100W;7W@+0x20;200W@-0x10;5W@6", true), ("100W;100W@0;5W@2", false), ("100W;5W@3;200W@2", false)] {
+            let fixed = parse_search_query(input, ValueType::Word).unwrap();
+            let (mut probed, mut candidates, mut matched) = (Vec::new(), Vec::new(), 0);
+            for anchor in (region.0..region.1).step_by(2) {
+                let offset = (anchor - BASE) as usize;
+                if !fixed.values[0].matched(&buffer[offset..offset + 2]).unwrap() {
+                    continue;
+                }
+                let mut combinations = Vec::new();
+                if collect_buffer_candidates(&buffer, BASE, region, &fixed, &page_status, anchor, &mut candidates) {
+                    find_combinations(&fixed, &candidates, &|| false, &mut |addrs| {
+                        combinations.push(addrs.to_vec());
+                        true
+                    });
+                }
+                let probe = probe_buffer_offsets(&buffer, BASE, region, &fixed, &page_status, anchor, &mut probed).then(|| probed.clone());
+                assert!(combinations.len() <= 1, "{} at 0x{:X}", input, anchor);
+                assert_eq!(probe, combinations.pop(), "{} at 0x{:X}", input, anchor);
+                matched += probe.is_some() as usize;
+            }
+            assert_eq!(matched > 0, any, "{}", input);
+        }
+    }
+}
@@ -0,0 +1,127 @@
+//! Resumable fuzzy initial scan tests
+//!
+//! 取消模糊首次扫描时只保留完整扫描的区域，被打断的区域不写入；从剩余区域恢复并追加后，
+//! 结果与一次扫描完的结果相同。恢复记录只对取消时的结果集版本有效。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::fuzzy_resume::{scan_regions_in_order, FuzzyResume};
+    use crate::search::engine::fuzzy_search::scan_buffer_parallel_aligned;
+    use crate::search::result_manager::{FuzzySearchResultItem, SearchResultManager, SearchResultMode};
+    use crate::search::tests::mock_memory::MockMemory;
+    use crate::search::ValueType;
+    use crate::wuwa::PageStatusBitmap;
+    use crate::search::tests::temp_dir::TempDir;
+    use std::cell::Cell;
+
+    const BASE: u64 = 0x7B00000000;
+    const PAGE: usize = 4096;
+    /// 5 个区域，每个区域 1 页，区域之间隔 1 页
+    const REGIONS: usize = 5;
+
+    fn memory() -> (MockMemory, Vec<(u64, u64)>) {
+        let mut mem = MockMemory::new();
+        let regions: Vec<(u64, u64)> = (0..REGIONS as u64).map(|i| (BASE + i * 2 * PAGE as u64, BASE + (i * 2 + 1) * PAGE as u64)).collect();
+        for &(start, _) in &regions {
+            mem.malloc(start, PAGE).unwrap();
+            for offset in (0..PAGE as u64).step_by(4) {
+                mem.mem_write_u32(start + offset, (start + offset) as u32).unwrap();
+            }
+        }
+        (mem, regions)
+    }
+
+    fn scan_region(mem: &MockMemory, start: u64, end: u64) -> Vec<FuzzySearchResultItem> {
+        let mut buffer = vec![0u8; (end - start) as usize];
+        let mut page_status = PageStatusBitmap::new(buffer.len(), start as usize);
+        mem.mem_read_with_status(start, &mut buffer, &mut page_status).unwrap();
+        scan_buffer_parallel_aligned(&buffer, start, start, end, 4, 4, ValueType::Dword, PAGE, &page_status)
+    }
+
+    fn addresses(mgr: &SearchResultManager) -> Vec<u64> {
+        mgr.get_all_fuzzy_results().unwrap().iter().map(|item| item.address).collect()
+    }
+
+    #[test]
+    fn test_cancel_keeps_finished_regions_and_resume_completes() {
+        let (mem, regions) = memory();
+        let dir = TempDir::new("fuzzy_resume");
+        let mut mgr = SearchResultManager::new(PAGE, dir.to_path_buf());
+        mgr.set_mode(SearchResultMode::Fuzzy).unwrap();
+
+        // 第 3 个区域扫描期间取消
+        let cancelled = Cell::new(false);
+        let completed = scan_regions_in_order(
+            &regions,
+            || cancelled.get(),
+            |idx, start, end| {
+                if idx == 2 {
+                    cancelled.set(true);
+                }
+                scan_region(&mem, start, end)
+            },
+            |_, results| mgr.add_fuzzy_results_batch(results).unwrap(),
+        );
+        assert_eq!(completed, 2);
+        assert_eq!(mgr.get_mode(), SearchResultMode::Fuzzy);
+        assert_eq!(mgr.total_count(), 2 * PAGE / 4);
+        assert!(addresses(&mgr).iter().all(|&addr| addr < regions[2].0));
+
+        let resume = FuzzyResume::new(mgr.revision(), ValueType::Dword, 4, regions[completed..].to_vec(), completed);
+        assert_eq!(resume.total_regions(), REGIONS);
+        assert!(resume.is_current(mgr.revision()));
+
+        // 从剩余区域恢复，结果与一次扫描完的相同
+        let committed = Cell::new(Vec::new());
+        let done = scan_regions_in_order(
+            &resume.remaining,
+            || false,
+            |_, start, end| scan_region(&mem, start, end),
+            |idx, results| {
+                let mut indices = committed.take();
+                indices.push(resume.regions_done + idx);
+                committed.set(indices);
+                mgr.add_fuzzy_results_batch(results).unwrap();
+            },
+        );
+        assert_eq!(done, resume.remaining.len());
+        assert_eq!(committed.take(), vec![2, 3, 4]);
+
+        let expected: Vec<u64> = regions.iter().flat_map(|&(start, end)| (start..end).step_by(4)).collect();
+        assert_eq!(addresses(&mgr), expected);
+        for item in mgr.get_all_fuzzy_results().unwrap() {
+            let address = item.address;
+            assert_eq!(item.value[..4], (address as u32).to_le_bytes());
+        }
+        // 结果集变化后恢复记录失效
+        assert!(!resume.is_current(mgr.revision()));
+
+    }
+
+    #[test]
+    fn test_cancel_before_and_after_every_region() {
+        let (mem, regions) = memory();
+        let scans = Cell::new(0);
+
+        // 开始前已取消：不扫描任何区域
+        let completed = scan_regions_in_order(&regions, || true, |_, _, _| unreachable!(), |_, _| unreachable!());
+        assert_eq!(completed, 0);
+
+        // 每个区域扫描完都在提交前检查取消
+        for cancel_after in 1..=REGIONS {
+            scans.set(0);
+            let commits = Cell::new(0);
+            let completed = scan_regions_in_order(
+                &regions,
+                || scans.get() >= cancel_after,
+                |_, start, end| {
+                    scans.set(scans.get() + 1);
+                    scan_region(&mem, start, end)
+                },
+                |_, _| commits.set(commits.get() + 1),
+            );
+            assert_eq!(completed, cancel_after - 1);
+            assert_eq!(commits.get(), cancel_after - 1);
+        }
+    }
+}
@@ -0,0 +1,184 @@
+//! Result generation tests
+//!
+//! Multi-round fuzzy sessions comparing current memory against an
+//! arbitrary earlier generation instead of the previous round.
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::fuzzy_search::{join_with_generation, refine_against_baseline_with};
+    use crate::search::result_manager::FuzzySearchResultItem;
+    use crate::search::result_manager::SearchResultManager;
+    use crate::search::result_manager::SearchResultMode;
+    use crate::search::tests::mock_memory::MockMemory;
+    use crate::search::{FuzzyCondition, ValueType};
+    use crate::search::tests::temp_dir::TempDir;
+    use std::path::Path;
+
+    const BASE: u64 = 0x7100000000;
+
+    fn addr(idx: usize) -> u64 {
+        BASE + (idx * 4) as u64
+    }
+
+    fn write_round(mem: &mut MockMemory, values: &[i32]) {
+        for (idx, value) in values.iter().enumerate() {
+            mem.mem_write_i32(addr(idx), *value).unwrap();
+        }
+    }
+
+    fn snapshot(mem: &MockMemory, indices: &[usize]) -> Vec<FuzzySearchResultItem> {
+        indices
+            .iter()
+            .map(|&idx| FuzzySearchResultItem::from_bytes(addr(idx), &mem.mem_read(addr(idx), 4).unwrap(), ValueType::Dword))
+            .collect()
+    }
+
+    /// The engine's refine-against-generation path with MockMemory reads.
+    fn refine_against(
+        mem: &MockMemory,
+        current: &[FuzzySearchResultItem],
+        generation: Vec<FuzzySearchResultItem>,
+        condition: FuzzyCondition,
+        keep_missing: bool,
+    ) -> Vec<u64> {
+        let (compared, carried) = join_with_generation(current, generation).split(keep_missing);
+        let read = |addr: u64, buffer: &mut [u8]| mem.mem_read_into(addr, buffer).is_ok();
+        let refined = refine_against_baseline_with(&compared, &carried, condition, 0, 0, read, None, None, &|_, _| {}, &|| false);
+        refined.iter().map(|item| item.address).collect()
+    }
+
+    fn new_fuzzy_manager(dir: &Path) -> SearchResultManager {
+        let mut mgr = SearchResultManager::new(1024 * 1024, dir.to_path_buf());
+        mgr.set_mode(SearchResultMode::Fuzzy).unwrap();
+        mgr
+    }
+
+    #[test]
+    fn test_refine_against_round_one_vs_round_two() {
+        let dir = TempDir::new("gen_rounds");
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, 4096).unwrap();
+        let all = [0, 1, 2, 3];
+
+        // Round 1: baseline
+        write_round(&mut mem, &[10, 10, 10, 10]);
+        let mut mgr = new_fuzzy_manager(&dir);
+        mgr.add_fuzzy_results_batch(snapshot(&mem, &all)).unwrap();
+        let gen1 = mgr.record_fuzzy_generation("Initial".to_string()).unwrap().unwrap();
+
+        // Round 2
+        write_round(&mut mem, &[20, 10, 20, 10]);
+        mgr.replace_all_fuzzy_results(snapshot(&mem, &all)).unwrap();
+        let gen2 = mgr.record_fuzzy_generation("Unchanged".to_string()).unwrap().unwrap();
+
+        // Round 3: current memory
+        write_round(&mut mem, &[20, 30, 10, 10]);
+        let current = mgr.get_all_fuzzy_results().unwrap();
+
+        let vs_gen1 = refine_against(&mem, &current, mgr.load_generation(gen1).unwrap(), FuzzyCondition::Changed, false);
+        let vs_gen2 = refine_against(&mem, &current, mgr.load_generation(gen2).unwrap(), FuzzyCondition::Changed, false);
+
+        assert_eq!(vs_gen1, vec![addr(0), addr(1)]);
+        assert_eq!(vs_gen2, vec![addr(1), addr(2)]);
+        assert_ne!(vs_gen1, vs_gen2);
+
+        let generations = mgr.list_generations();
+        assert_eq!(generations.len(), 2);
+        assert_eq!(generations[0].id, gen1);
+        assert_eq!(generations[0].count, 4);
+        assert_eq!(generations[1].condition, "Unchanged");
+
+        drop(mgr);
+    }
+
+    #[test]
+    fn test_refine_against_generation_divergence_flag() {
+        let dir = TempDir::new("gen_divergence");
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, 4096).unwrap();
+
+        // Round 1 only knows addresses 0..3
+        write_round(&mut mem, &[10, 10, 10, 10, 10]);
+        let mut mgr = new_fuzzy_manager(&dir);
+        mgr.add_fuzzy_results_batch(snapshot(&mem, &[0, 1, 2, 3])).unwrap();
+        let gen1 = mgr.record_fuzzy_generation("Initial".to_string()).unwrap().unwrap();
+
+        // Current set gained address 4 (e.g. added manually)
+        write_round(&mut mem, &[11, 10, 12, 10, 99]);
+        mgr.replace_all_fuzzy_results(snapshot(&mem, &[0, 1, 2, 3, 4])).unwrap();
+        let current = mgr.get_all_fuzzy_results().unwrap();
+
+        let join = join_with_generation(&current, mgr.load_generation(gen1).unwrap());
+        assert_eq!(join.compared.len(), 4);
+        assert_eq!(join.missing.len(), 1);
+        // compared items carry the old generation's values
+        assert!(join.compared.iter().all(|item| item.as_i64() == 10));
+
+        let dropped = refine_against(&mem, &current, mgr.load_generation(gen1).unwrap(), FuzzyCondition::Changed, false);
+        let kept = refine_against(&mem, &current, mgr.load_generation(gen1).unwrap(), FuzzyCondition::Changed, true);
+
+        assert_eq!(dropped, vec![addr(0), addr(2)]);
+        assert_eq!(kept, vec![addr(0), addr(2), addr(4)]);
+
+        drop(mgr);
+    }
+
+    #[test]
+    fn test_pending_generation_dropped_when_results_change() {
+        let dir = TempDir::new("gen_pending");
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, 4096).unwrap();
+        write_round(&mut mem, &[1, 2, 3]);
+
+        let mut mgr = new_fuzzy_manager(&dir);
+        mgr.add_fuzzy_results_batch(snapshot(&mem, &[0, 1, 2])).unwrap();
+
+        // Results change while the snapshot is written: the next batch and the commit fail, the file is removed
+        let mut pending = mgr.begin_fuzzy_generation("Initial".to_string()).unwrap().unwrap();
+        let batch = mgr.fuzzy_generation_batch(&pending).unwrap();
+        pending.write_all(&batch).unwrap();
+        mgr.remove_results_batch(vec![1]).unwrap();
+        assert!(mgr.fuzzy_generation_batch(&pending).is_err());
+        assert!(mgr.commit_fuzzy_generation(pending).is_err());
+        assert!(mgr.list_generations().is_empty());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+
+        // Unchanged results commit normally
+        let mut pending = mgr.begin_fuzzy_generation("Changed".to_string()).unwrap().unwrap();
+        loop {
+            let batch = mgr.fuzzy_generation_batch(&pending).unwrap();
+            if batch.is_empty() {
+                break;
+            }
+            pending.write_all(&batch).unwrap();
+        }
+        let id = mgr.commit_fuzzy_generation(pending).unwrap();
+        assert_eq!(mgr.load_generation(id).unwrap().len(), 2);
+
+        drop(mgr);
+    }
+
+    #[test]
+    fn test_generations_cleared_with_session() {
+        let dir = TempDir::new("gen_clear");
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, 4096).unwrap();
+        write_round(&mut mem, &[1, 2, 3]);
+
+        let mut mgr = new_fuzzy_manager(&dir);
+        mgr.add_fuzzy_results_batch(snapshot(&mem, &[0, 1, 2])).unwrap();
+        let id = mgr.record_fuzzy_generation("Initial".to_string()).unwrap().unwrap();
+
+        let loaded = mgr.load_generation(id).unwrap();
+        assert_eq!(loaded.len(), 3);
+        assert_eq!(loaded[2].as_i64(), 3);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        mgr.clear().unwrap();
+        assert!(mgr.list_generations().is_empty());
+        assert!(mgr.load_generation(id).is_err());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+
+        drop(mgr);
+    }
+}
@@ -0,0 +1,89 @@
+//! Group refine DFS tests
+//!
+//! 密集布局（0x0/0x4/0x8/0xC 相邻的值）下同一个锚点有很多组合：默认每个锚点只取第一组，
+//! 与普通组搜索相同；`first_match_only` 关闭时取所有组合（深度搜索之后的改善），
+//! 回溯展开的节点数都有上限。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::group_match::{collect_result_candidates, find_combinations_bounded};
+    use crate::search::engine::group_search::refine_group_values_with_cancel;
+    use crate::search::{SearchMode, SearchQuery, SearchValue, ValueType};
+    use std::collections::BTreeSet;
+
+    const BASE: u64 = 0x7F00000000;
+
+    fn dword(value: i128) -> SearchValue {
+        SearchValue::fixed(value, ValueType::Dword)
+    }
+
+    fn values(layout: &[(u64, u32)]) -> Vec<(u64, Vec<u8>)> {
+        layout.iter().map(|(offset, value)| (BASE + offset, value.to_le_bytes().to_vec())).collect()
+    }
+
+    fn refine(layout: &[(u64, u32)], query: &SearchQuery) -> BTreeSet<u64> {
+        let refined = refine_group_values_with_cancel(values(layout), query, None, None, &|| false, &|_, _| {});
+        refined.iter().map(|pair| pair.addr - BASE).collect()
+    }
+
+    #[test]
+    fn test_dense_layout_takes_first_combination_per_anchor() {
+        // 两个 200 都能与两个 300 组合
+        let layout = [(0x0, 200), (0x4, 300), (0x8, 300), (0xC, 200), (0x10, 100), (0x14, 100), (0x2000, 400)];
+        let query = SearchQuery::new(vec![dword(200), dword(300)], SearchMode::Unordered, 128);
+        assert!(query.first_match_only);
+
+        // 每个锚点取地址最小的 300
+        assert_eq!(refine(&layout, &query), BTreeSet::from([0x0, 0x4, 0xC]));
+        assert_eq!(refine(&layout, &query.with_first_match_only(false)), BTreeSet::from([0x0, 0x4, 0x8, 0xC]));
+    }
+
+    #[test]
+    fn test_overlapping_pairs() {
+        let layout = [(0x0, 200), (0x4, 300), (0x8, 200), (0xC, 300), (0x100, 100)];
+        let query = SearchQuery::new(vec![dword(200), dword(300)], SearchMode::Unordered, 16);
+
+        assert_eq!(refine(&layout, &query), BTreeSet::from([0x0, 0x4, 0x8]));
+        assert_eq!(refine(&layout, &query.clone().with_first_match_only(false)), BTreeSet::from([0x0, 0x4, 0x8, 0xC]));
+
+        // Ordered 要求 300 在 200 之后
+        let ordered = SearchQuery::new(vec![dword(200), dword(300)], SearchMode::Ordered, 16);
+        assert_eq!(refine(&layout, &ordered), BTreeSet::from([0x0, 0x4, 0x8, 0xC]));
+    }
+
+    #[test]
+    fn test_expansions_stay_bounded_on_uniform_data() {
+        // 64 个相同的值，5 个值的无序组合每个锚点有上千万种
+        let layout: Vec<(u64, u32)> = (0..64).map(|i| (i * 4, 100)).collect();
+        let addr_values = values(&layout);
+        let query = SearchQuery::new(vec![dword(100); 5], SearchMode::Unordered, 256);
+
+        let mut candidates = Vec::new();
+        assert!(collect_result_candidates(&addr_values, &query, BASE + 0x80, &mut candidates));
+
+        let mut matches = 0;
+        let outcome = find_combinations_bounded(&query, &candidates, u64::MAX, &|| false, &mut |_| {
+            matches += 1;
+            false
+        });
+        assert_eq!(matches, 1);
+        assert!(!outcome.truncated);
+        // 第一组沿着每层的第一个可用候选直接到达
+        assert!(outcome.expansions <= 16, "{:?}", outcome);
+
+        let budget = 10_000;
+        let mut matches = 0u64;
+        let outcome = find_combinations_bounded(&query, &candidates, budget, &|| false, &mut |_| {
+            matches += 1;
+            true
+        });
+        assert!(outcome.truncated);
+        assert!(!outcome.cancelled);
+        assert_eq!(outcome.expansions, budget);
+        assert!(matches > 0 && matches < budget);
+
+        // 每个地址都是某一组的成员，结果中各出现一次
+        let all: BTreeSet<u64> = layout.iter().map(|(offset, _)| *offset).collect();
+        assert_eq!(refine(&layout, &query), all);
+    }
+}
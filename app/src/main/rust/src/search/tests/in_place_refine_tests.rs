@@ -0,0 +1,160 @@
+//! In-place fuzzy refine tests
+//!
+//! 就地细化按段比较，存活项写回新值，删除的项最后一次性压缩。
+//! 结果必须与整体重建一致，包括跨越内存/磁盘边界和段边界的情况；取消时结果集回滚到细化之前。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::fuzzy_search::{fuzzy_refine_search_with, refine_in_place_with, InPlaceRefine};
+    use crate::search::result_manager::{FuzzySearchResultItem, SearchResultManager, SearchResultMode};
+    use crate::search::tests::mock_memory::MockMemory;
+    use crate::search::{FuzzyCondition, ValueType};
+    use crate::search::tests::temp_dir::TempDir;
+    use anyhow::Result;
+    use std::path::Path;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    const BASE: u64 = 0x7600000000;
+    const PAGE: usize = 4096;
+    /// 内存缓冲区只放得下前几项，其余写入磁盘
+    const MEMORY_ITEMS: usize = 10;
+
+    fn new_manager(dir: &Path, items: &[FuzzySearchResultItem]) -> SearchResultManager {
+        let mut mgr = SearchResultManager::new(MEMORY_ITEMS * size_of::<FuzzySearchResultItem>(), dir.to_path_buf());
+        mgr.set_mode(SearchResultMode::Fuzzy).unwrap();
+        mgr.add_fuzzy_results_batch(items.to_vec()).unwrap();
+        mgr
+    }
+
+    fn read(mem: &MockMemory) -> impl Fn(u64, &mut [u8]) -> bool + Sync + '_ {
+        |addr, buffer| mem.mem_read_into(addr, buffer).is_ok()
+    }
+
+    /// 整体重建：一次比较全部结果
+    fn refine_items(mem: &MockMemory, items: &[FuzzySearchResultItem], condition: FuzzyCondition) -> Vec<FuzzySearchResultItem> {
+        fuzzy_refine_search_with(items, condition, 0, 0, read(mem), None, None, &|_, _| {}, Some(&|| false))
+    }
+
+    /// 就地细化，`cancel_after` 个结果比较过之后取消
+    fn refine_in_place(
+        mgr: SearchResultManager,
+        mem: &MockMemory,
+        condition: FuzzyCondition,
+        chunk: usize,
+        cancel_after: Option<usize>,
+    ) -> (SearchResultManager, InPlaceRefine) {
+        let store = Mutex::new(mgr);
+        let lock = |f: &mut dyn FnMut(&mut SearchResultManager) -> Result<()>| f(&mut store.lock().unwrap());
+        let processed = Arc::new(AtomicUsize::new(0));
+        let check_cancelled = || cancel_after.is_some_and(|limit| processed.load(Ordering::Relaxed) > limit);
+        let outcome = refine_in_place_with(lock, condition, chunk, 0, read(mem), &processed, &AtomicUsize::new(0), &|_, _| {}, &check_cancelled).unwrap();
+        (store.into_inner().unwrap(), outcome)
+    }
+
+    fn summary(items: &[FuzzySearchResultItem]) -> Vec<(u64, ValueType, i64)> {
+        items.iter().map(|item| (item.address, item.value_type, item.as_i64())).collect()
+    }
+
+    fn dword_items(mem: &mut MockMemory, count: u64) -> Vec<FuzzySearchResultItem> {
+        mem.malloc(BASE, PAGE).unwrap();
+        (0..count)
+            .map(|i| {
+                mem.mem_write_i32(BASE + i * 4, i as i32).unwrap();
+                FuzzySearchResultItem::from_bytes(BASE + i * 4, &(i as i32).to_le_bytes(), ValueType::Dword)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_in_place_refine_matches_rebuild_across_boundaries() {
+        let mut mem = MockMemory::new();
+        let items = dword_items(&mut mem, 40);
+
+        let dir = TempDir::new("boundaries");
+        let in_place = new_manager(&dir, &items);
+        assert_eq!(in_place.total_count(), 40);
+
+        // 每 3 项改一次，其中一部分在内存缓冲区，其余在磁盘
+        for i in (0..40u64).step_by(3) {
+            mem.mem_write_i32(BASE + i * 4, 1000 + i as i32).unwrap();
+        }
+        let expected = refine_items(&mem, &items, FuzzyCondition::Changed);
+        let (in_place, outcome) = refine_in_place(in_place, &mem, FuzzyCondition::Changed, 7, None);
+        assert!(outcome.completed);
+        assert_eq!((outcome.total_items, outcome.final_count), (40, 14));
+        assert_eq!(summary(&in_place.get_all_fuzzy_results().unwrap()), summary(&expected));
+
+        // 第二轮在压缩后的集合上继续：存活项保存的是上一轮写回的新值
+        for i in [0u64, 21, 39] {
+            mem.mem_write_i32(BASE + i * 4, 1001 + i as i32).unwrap();
+        }
+        let (in_place, _) = refine_in_place(in_place, &mem, FuzzyCondition::IncreasedBy(1), 4, None);
+        let remaining = in_place.get_all_fuzzy_results().unwrap();
+        assert_eq!(summary(&remaining), vec![(BASE, ValueType::Dword, 1001), (BASE + 84, ValueType::Dword, 1022), (BASE + 156, ValueType::Dword, 1040)]);
+
+        // 全部删除
+        let (in_place, _) = refine_in_place(in_place, &mem, FuzzyCondition::Changed, 2, None);
+        assert_eq!(in_place.total_count(), 0);
+
+        drop(in_place);
+    }
+
+    #[test]
+    fn test_cancelled_in_place_refine_leaves_results_unchanged() {
+        let mut mem = MockMemory::new();
+        let items = dword_items(&mut mem, 40);
+        let dir = TempDir::new("cancel");
+        let mgr = new_manager(&dir, &items);
+
+        // 先完成一次细化，留下撤销槽
+        mem.mem_write_i32(BASE + 39 * 4, 2000).unwrap();
+        let (mgr, outcome) = refine_in_place(mgr, &mem, FuzzyCondition::Unchanged, 7, None);
+        assert!(outcome.completed);
+        assert_eq!(mgr.total_count(), 39);
+        let before = summary(&mgr.get_all_fuzzy_results().unwrap());
+
+        // 所有值都变了；前两段（跨过内存/磁盘边界）写回新值之后，第三段比较时取消
+        for i in 0..40u64 {
+            mem.mem_write_i32(BASE + i * 4, 3000 + i as i32).unwrap();
+        }
+        let (mut mgr, outcome) = refine_in_place(mgr, &mem, FuzzyCondition::Changed, 7, Some(14));
+        assert!(!outcome.completed);
+        assert_eq!((outcome.total_items, outcome.final_count), (39, 39));
+        assert_eq!(summary(&mgr.get_all_fuzzy_results().unwrap()), before);
+        assert!(mgr.verify_integrity().store.ok);
+        let journals = std::fs::read_dir(&dir).unwrap().filter(|entry| entry.as_ref().unwrap().path().to_string_lossy().ends_with(".journal")).count();
+        assert_eq!(journals, 0);
+
+        // 之前的撤销槽保留，撤销回到第一次细化之前
+        assert!(mgr.can_undo());
+        assert_eq!(mgr.undo_refine().unwrap(), 40);
+        assert_eq!(summary(&mgr.get_all_fuzzy_results().unwrap()), summary(&items));
+
+        drop(mgr);
+    }
+
+    #[test]
+    fn test_auto_overlap_dedup_across_chunks() {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, PAGE).unwrap();
+        mem.mem_write_u64(BASE + 8, 1 << 32).unwrap();
+        let items: Vec<FuzzySearchResultItem> =
+            [BASE, BASE + 4, BASE + 8, BASE + 12, BASE + 16].iter().map(|&addr| FuzzySearchResultItem::auto(addr, &mem.mem_read(addr, 8).unwrap())).collect();
+
+        // BASE + 8 定型为 Qword，覆盖的 BASE + 12 正好是下一段的第一项
+        mem.mem_write_u64(BASE + 8, 2 << 32).unwrap();
+        let condition = FuzzyCondition::IncreasedBy(1 << 32);
+        let expected = refine_items(&mem, &items, condition);
+
+        let dir = TempDir::new("auto");
+        let (mgr, _) = refine_in_place(new_manager(&dir, &items), &mem, condition, 3, None);
+        let refined = mgr.get_all_fuzzy_results().unwrap();
+        assert_eq!(summary(&refined), summary(&expected));
+        assert_eq!(refined.len(), 1);
+        let (address, value_type) = (refined[0].address, refined[0].value_type);
+        assert_eq!((address, value_type), (BASE + 8, ValueType::Qword));
+
+        drop(mgr);
+    }
+}
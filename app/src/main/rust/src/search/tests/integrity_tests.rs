@@ -0,0 +1,231 @@
+//! Result store integrity tests
+//!
+//! 用很小的批次构造结果文件，再按几种掉电场景破坏文件或清单：尾部截断、文件中间字节翻转、
+//! 清单记录数不对，断言能检测出来并截断到最后一个一致的批次边界。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::ValueType;
+    use crate::search::result_manager::integrity::{
+        INTEGRITY_BATCH_RECORDS, IntegrityTracker, RecordLayout, StoreManifest, manifest_path, recover_store_file, verify_store_file,
+    };
+    use crate::search::result_manager::{SearchResultItem, SearchResultManager, SearchResultMode};
+    use crate::search::tests::temp_dir::TempDir;
+    use std::path::{Path, PathBuf};
+
+    const BATCH: usize = 4;
+    const BASE: u64 = 0x7200000000;
+
+    /// 与模糊结果相同的布局：address(8) + value(8) + type(4)
+    const LAYOUT: RecordLayout = RecordLayout {
+        size: 20,
+        value_offset: Some(8),
+        type_offset: 16,
+    };
+
+    fn records(count: usize) -> Vec<u8> {
+        let mut data = Vec::with_capacity(count * LAYOUT.size);
+        for i in 0..count {
+            data.extend_from_slice(&(BASE + i as u64 * 4).to_le_bytes());
+            data.extend_from_slice(&(i as u64 * 10).to_le_bytes());
+            data.extend_from_slice(&ValueType::Dword.to_id().to_le_bytes());
+        }
+        data
+    }
+
+    /// 逐条追加 `count` 条记录，`sealed` 为 false 时模拟写到一半掉电
+    fn write_store(dir: &Path, count: usize, sealed: bool) -> (PathBuf, Vec<u8>) {
+        let path = dir.join("store.bin");
+        let data = records(count);
+        let mut tracker = IntegrityTracker::new(&path, LAYOUT, BATCH);
+        for n in 1..=count {
+            tracker.on_append(&data, n).unwrap();
+        }
+        if sealed {
+            tracker.seal(&data, count).unwrap();
+        }
+        std::fs::write(&path, &data).unwrap();
+        (path, data)
+    }
+
+    fn file_records(path: &Path) -> u64 {
+        std::fs::metadata(path).unwrap().len() / LAYOUT.size as u64
+    }
+
+    #[test]
+    fn test_clean_store_verifies() {
+        let dir = TempDir::new("integrity_clean");
+        let (path, _) = write_store(&dir, 10, true);
+
+        let (manifest, report) = verify_store_file(&path, &LAYOUT).unwrap();
+        assert!(report.ok, "{:?}", report.issues);
+        assert!(report.clean_shutdown && manifest.sorted);
+        assert_eq!((report.expected_records, report.verified_records, report.batches), (10, 10, 3));
+
+        // 已经一致的文件不会被改动
+        let (_, report) = recover_store_file(&path, &LAYOUT).unwrap();
+        assert_eq!(report.dropped_records, 0);
+        assert_eq!(file_records(&path), 10);
+    }
+
+    #[test]
+    fn test_checksums_only_at_batch_boundaries() {
+        let dir = TempDir::new("integrity_batches");
+        let path = dir.join("store.bin");
+        let data = records(10);
+        let mut tracker = IntegrityTracker::new(&path, LAYOUT, BATCH);
+
+        for n in 1..=10 {
+            tracker.on_append(&data, n).unwrap();
+            assert_eq!(tracker.hashed_batches(), (n / BATCH) as u64);
+        }
+        tracker.seal(&data, 10).unwrap();
+        assert_eq!(tracker.hashed_batches(), 3);
+
+        // 校验和重复封存都不再重新计算
+        for _ in 0..100 {
+            assert!(tracker.verify(&data, 10).ok);
+        }
+        tracker.seal(&data, 10).unwrap();
+        assert_eq!(tracker.hashed_batches(), 3);
+
+        // 封存后继续追加，只重新计算不满的最后一批
+        let data = records(13);
+        for n in 11..=13 {
+            tracker.on_append(&data, n).unwrap();
+        }
+        tracker.seal(&data, 13).unwrap();
+        assert_eq!(tracker.hashed_batches(), 5);
+        assert_eq!(tracker.manifest().chain.len(), 4);
+    }
+
+    #[test]
+    fn test_truncated_tail_recovers_to_batch_boundary() {
+        let dir = TempDir::new("integrity_truncated");
+        // 10 条记录未封存：清单只覆盖前两批（8 条）
+        let (path, data) = write_store(&dir, 10, false);
+        std::fs::write(&path, &data[..6 * LAYOUT.size]).unwrap();
+
+        let (_, report) = verify_store_file(&path, &LAYOUT).unwrap();
+        assert!(!report.ok && !report.clean_shutdown);
+        assert!(report.issues[0].contains("truncated"), "{:?}", report.issues);
+        assert_eq!((report.expected_records, report.verified_records, report.dropped_records), (8, 4, 4));
+
+        recover_store_file(&path, &LAYOUT).unwrap();
+        assert_eq!(file_records(&path), 4);
+        let manifest = StoreManifest::load(&manifest_path(&path)).unwrap();
+        assert!(manifest.clean);
+        assert_eq!((manifest.record_count, manifest.chain.len()), (4, 1));
+        assert!(verify_store_file(&path, &LAYOUT).unwrap().1.ok);
+    }
+
+    #[test]
+    fn test_flipped_bytes_are_detected() {
+        let dir = TempDir::new("integrity_flipped");
+
+        // 值字节翻转：记录本身合理，只有校验和能发现
+        let (path, mut data) = write_store(&dir, 12, true);
+        data[5 * LAYOUT.size + 9] ^= 0x40;
+        std::fs::write(&path, &data).unwrap();
+        let (_, report) = verify_store_file(&path, &LAYOUT).unwrap();
+        assert_eq!(report.issues, vec!["checksum mismatch in batch 1 (records 4..8)".to_string()]);
+        assert_eq!((report.verified_records, report.dropped_records), (4, 8));
+
+        recover_store_file(&path, &LAYOUT).unwrap();
+        assert_eq!(file_records(&path), 4);
+
+        // 类型字段被破坏
+        let (path, mut data) = write_store(&dir, 12, true);
+        data[9 * LAYOUT.size + LAYOUT.type_offset] = 0x7F;
+        std::fs::write(&path, &data).unwrap();
+        let (_, report) = verify_store_file(&path, &LAYOUT).unwrap();
+        assert_eq!(report.issues, vec!["record 9: invalid value type 127 (batch 2)".to_string()]);
+        assert_eq!(report.verified_records, 8);
+    }
+
+    #[test]
+    fn test_bad_count_is_detected() {
+        let dir = TempDir::new("integrity_count");
+        let (path, _) = write_store(&dir, 8, true);
+        let manifest_file = manifest_path(&path);
+        let good = StoreManifest::load(&manifest_file).unwrap();
+
+        // 记录数偏大：校验和只有两批，多出的记录丢弃
+        let mut manifest = good.clone();
+        manifest.record_count = 12;
+        manifest.save(&manifest_file).unwrap();
+        let (_, report) = verify_store_file(&path, &LAYOUT).unwrap();
+        assert!(report.issues[0].contains("disagrees"), "{:?}", report.issues);
+        assert_eq!((report.verified_records, report.dropped_records), (8, 4));
+
+        // 记录数偏小：最后一批的校验和对不上
+        let mut manifest = good.clone();
+        manifest.record_count = 6;
+        manifest.save(&manifest_file).unwrap();
+        let (_, report) = verify_store_file(&path, &LAYOUT).unwrap();
+        assert_eq!(report.issues, vec!["checksum mismatch in batch 1 (records 4..6)".to_string()]);
+        assert_eq!(report.verified_records, 4);
+
+        recover_store_file(&path, &LAYOUT).unwrap();
+        assert_eq!(StoreManifest::load(&manifest_file).unwrap().record_count, 4);
+    }
+
+    #[test]
+    fn test_unsorted_writer_does_not_require_order() {
+        let dir = TempDir::new("integrity_unsorted");
+        let path = dir.join("store.bin");
+        let mut data = records(8);
+        // 交换第 1、2 条记录，地址不再单调
+        let (first, second) = data.split_at_mut(2 * LAYOUT.size);
+        first[LAYOUT.size..].swap_with_slice(&mut second[..LAYOUT.size]);
+
+        let mut tracker = IntegrityTracker::new(&path, LAYOUT, BATCH);
+        tracker.seal(&data, 8).unwrap();
+        std::fs::write(&path, &data).unwrap();
+
+        let (manifest, report) = verify_store_file(&path, &LAYOUT).unwrap();
+        assert!(!manifest.sorted);
+        assert!(report.ok, "{:?}", report.issues);
+    }
+
+    #[test]
+    fn test_unclean_shutdown_is_recovered_at_init() {
+        let dir = TempDir::new("integrity_session");
+        let total = 2 * INTEGRITY_BATCH_RECORDS + 100;
+        let addr = |i: usize| BASE + i as u64 * 4;
+
+        let mut manager = SearchResultManager::new(0, dir.to_path_buf());
+        let batch: Vec<_> = (0..total).map(|i| SearchResultItem::new_exact(addr(i), ValueType::Dword)).collect();
+        manager.add_results_batch(batch).unwrap();
+        assert!(manager.verify_integrity().store.ok);
+
+        // 继续追加但没有完成这次批量写入，然后进程被杀
+        for i in total..total + 50 {
+            manager.add_result(SearchResultItem::new_exact(addr(i), ValueType::Dword)).unwrap();
+        }
+        let report = manager.verify_integrity().store;
+        assert!(report.ok && !report.clean_shutdown);
+        assert_eq!(report.unchecked_records, 150);
+        std::mem::forget(manager);
+
+        // 第二批中间的一条记录写坏
+        let store = dir.join("mamu_search_results.bin");
+        let mut bytes = std::fs::read(&store).unwrap();
+        bytes[(INTEGRITY_BATCH_RECORDS + 7) * 16] ^= 0x01;
+        std::fs::write(&store, &bytes).unwrap();
+
+        let manager = SearchResultManager::new(0, dir.to_path_buf());
+        assert_eq!(manager.get_mode(), SearchResultMode::Exact);
+        assert_eq!(manager.total_count(), INTEGRITY_BATCH_RECORDS);
+        let report = manager.verify_integrity();
+        let recovery = report.recovery.unwrap();
+        assert!(!recovery.ok && !recovery.clean_shutdown);
+        assert_eq!(recovery.expected_records, (2 * INTEGRITY_BATCH_RECORDS) as u64);
+        assert_eq!(recovery.dropped_records, INTEGRITY_BATCH_RECORDS as u64);
+        assert!(report.store.ok);
+
+        let results = manager.get_all_exact_results().unwrap();
+        assert!(results.iter().enumerate().all(|(i, item)| item.address == addr(i)));
+        drop(manager);
+    }
+}
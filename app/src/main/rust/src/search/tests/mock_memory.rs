@@ -10,6 +10,8 @@ use crate::wuwa::PageStatusBitmap;
 use anyhow::{anyhow, Result};
 use std::collections::BTreeMap;
 use std::ops::Not;
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
 
 const DEFAULT_PAGE_SIZE: usize = 4096;
 
@@ -132,6 +134,19 @@ impl MockMemory {
         Ok(region.data[offset..offset + size].to_vec())
     }
 
+    /// Read data into an existing buffer, without page fault simulation
+    pub fn mem_read_into(&self, addr: u64, buf: &mut [u8]) -> Result<()> {
+        let region = self.find_region(addr, buf.len())?;
+
+        if !region.readable {
+            return Err(anyhow!("Memory region at 0x{:X} is not readable", addr));
+        }
+
+        let offset = (addr - region.start) as usize;
+        buf.copy_from_slice(&region.data[offset..offset + buf.len()]);
+        Ok(())
+    }
+
     /// Read data from memory with page fault simulation
     ///
     /// # Arguments
@@ -195,6 +210,14 @@ impl MockMemory {
         Ok(())
     }
 
+    /// Unmap the region starting at `addr`; later reads of it fail
+    pub fn free(&mut self, addr: u64) -> Result<()> {
+        self.regions
+            .remove(&addr)
+            .map(|_| ())
+            .ok_or_else(|| anyhow!("No memory region starts at 0x{:X}", addr))
+    }
+
     /// Get page size
     pub fn page_size(&self) -> usize {
         self.page_size
@@ -283,6 +306,34 @@ impl Default for MockMemory {
     }
 }
 
+/// 挡住 `from` 及以上地址的读取，直到 `open`，让扫描停在某个区域之前
+///
+/// 在取得模拟内存的锁之前等待，其他区域照常读取。最多等待 10 秒，测试失败时不会卡住。
+pub struct ReadGate {
+    from: u64,
+    opened: Mutex<bool>,
+    changed: Condvar,
+}
+
+impl ReadGate {
+    pub fn new(from: u64) -> Self {
+        Self { from, opened: Mutex::new(false), changed: Condvar::new() }
+    }
+
+    pub fn wait(&self, addr: u64) {
+        if addr < self.from {
+            return;
+        }
+        let opened = self.opened.lock().unwrap();
+        let _ = self.changed.wait_timeout_while(opened, Duration::from_secs(10), |opened| !*opened).unwrap();
+    }
+
+    pub fn open(&self) {
+        *self.opened.lock().unwrap() = true;
+        self.changed.notify_all();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
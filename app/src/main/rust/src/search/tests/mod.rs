@@ -1,7 +1,61 @@
 //! Test modules for search functionality
 
 pub mod mock_memory;
+pub mod temp_dir;
+#[cfg(test)]
+pub mod result_fixture;
+#[cfg(test)]
+pub mod engine_fixture;
 pub mod single_search_tests;
 pub mod group_search_tests;
 pub mod refine_search_tests;
-pub mod deep_search_tests;
\ No newline at end of file
+pub mod deep_search_tests;
+pub mod cancel_latency_tests;
+pub mod generation_tests;
+pub mod compat_tests;
+pub mod estimate_tests;
+pub mod scan_cache_tests;
+pub mod refine_strategy_tests;
+pub mod read_stats_tests;
+pub mod span_mode_tests;
+pub mod alloc_counter;
+pub mod alloc_tests;
+pub mod integrity_tests;
+pub mod stable_tests;
+pub mod region_group_tests;
+pub mod pressure_tests;
+pub mod provenance_tests;
+pub mod byte_search_tests;
+pub mod exact_snapshot_tests;
+pub mod target_pid_tests;
+pub mod auto_fuzzy_tests;
+pub mod pattern_replace_tests;
+pub mod fixed_offset_tests;
+pub mod result_file_tests;
+pub mod string_search_tests;
+pub mod filtered_index_tests;
+pub mod operator_search_tests;
+pub mod xor_search_tests;
+pub mod in_place_refine_tests;
+pub mod value_cache_tests;
+pub mod result_handle_tests;
+pub mod watchdog_tests;
+pub mod refine_stream_tests;
+pub mod alignment_tests;
+pub mod address_range_tests;
+pub mod fuzzy_resume_tests;
+pub mod chunk_boundary_tests;
+pub mod result_label_tests;
+pub mod refine_undo_tests;
+pub mod auto_type_tests;
+pub mod group_refine_dfs_tests;
+pub mod pattern_refine_tests;
+pub mod result_commit_tests;
+pub mod simd_scan_tests;
+pub mod result_cap_tests;
+pub mod address_sort_tests;
+pub mod pattern_fuzzy_tests;
+pub mod refine_coalesce_tests;
+pub mod session_tests;
+pub mod text_export_tests;
+pub mod page_cache_tests;
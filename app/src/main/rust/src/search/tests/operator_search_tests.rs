@@ -0,0 +1,130 @@
+//! Range and not-equal operator tests
+//!
+//! `100~150`、`~100`、`!0` 在单值扫描和联合搜索中的匹配，
+//! 联合搜索可以混合区间、不等于和精确值。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::group_search::{refine_group_values_with_cancel, search_in_buffer_group};
+    use crate::search::engine::single_search::search_in_chunks_with_status;
+    use crate::search::tests::mock_memory::MockMemory;
+    use crate::search::{SearchQuery, ValuePair, ValueType, parse_search_query};
+    use crate::wuwa::PageStatusBitmap;
+    use std::collections::BTreeSet;
+
+    const BASE: u64 = 0x7700000000;
+    const SIZE: usize = 0x2000;
+
+    fn read_all(mem: &MockMemory) -> (Vec<u8>, PageStatusBitmap) {
+        let mut buffer = vec![0u8; SIZE];
+        let mut page_status = PageStatusBitmap::new(SIZE, BASE as usize);
+        mem.mem_read_with_status(BASE, &mut buffer, &mut page_status).unwrap();
+        (buffer, page_status)
+    }
+
+    /// 单值扫描，返回结果地址（相对 BASE）
+    fn scan_single(mem: &MockMemory, input: &str) -> BTreeSet<u64> {
+        let query = parse_search_query(input, ValueType::Dword).unwrap();
+        let target = &query.values[0];
+        let (buffer, page_status) = read_all(mem);
+        let mut results = Vec::new();
+        search_in_chunks_with_status(
+            &buffer,
+            BASE,
+            BASE,
+            BASE + SIZE as u64,
+            target.value_type().size(),
+            target,
+            target.value_type(),
+            &page_status,
+            &mut results,
+            &|| false,
+        );
+        results.iter().map(|pair| pair.addr - BASE).collect()
+    }
+
+    fn scan_group(mem: &MockMemory, query: &SearchQuery) -> Vec<ValuePair> {
+        let (buffer, page_status) = read_all(mem);
+        let mut results = Vec::new();
+        let mut matches_checked = 0usize;
+        search_in_buffer_group(
+            &buffer,
+            BASE,
+            BASE,
+            BASE + SIZE as u64,
+            4,
+            query,
+            &page_status,
+            &mut results,
+            &mut matches_checked,
+            &|| false,
+        );
+        results
+    }
+
+    fn dword_memory() -> MockMemory {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, SIZE).unwrap();
+        for (offset, value) in [(0x10u64, 100i32), (0x14, 150), (0x18, 151), (0x1C, 99), (0x20, -5), (0x24, i32::MAX)] {
+            mem.mem_write_i32(BASE + offset, value).unwrap();
+        }
+        mem
+    }
+
+    #[test]
+    fn test_single_range_and_not_equal() {
+        let mem = dword_memory();
+        let all: BTreeSet<u64> = (0..SIZE as u64).step_by(4).collect();
+        let nonzero: BTreeSet<u64> = [0x10, 0x14, 0x18, 0x1C, 0x20, 0x24].into();
+
+        assert_eq!(scan_single(&mem, "100~150"), [0x10, 0x14].into());
+        assert_eq!(scan_single(&mem, "100~~150"), all.iter().copied().filter(|&addr| addr != 0x10 && addr != 0x14).collect());
+        // 下界为类型最小值：0 和负数都在范围内
+        assert_eq!(scan_single(&mem, "~100"), all.iter().copied().filter(|addr| ![0x14, 0x18, 0x24].contains(addr)).collect());
+        assert_eq!(scan_single(&mem, "!0"), nonzero);
+        assert_eq!(scan_single(&mem, "!4294967291"), all.iter().copied().filter(|&addr| addr != 0x20).collect());
+    }
+
+    #[test]
+    fn test_single_float_not_equal() {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, SIZE).unwrap();
+        mem.mem_write_f32(BASE + 0x40, 1.5).unwrap();
+        mem.mem_write_f32(BASE + 0x44, 1.5001).unwrap();
+
+        let results = scan_single(&mem, "!1.5F");
+        assert_eq!(results.len(), SIZE / 4 - 1);
+        assert!(!results.contains(&0x40));
+        assert!(results.contains(&0x44));
+        assert_eq!(scan_single(&mem, "1.4~1.6F"), [0x40, 0x44].into());
+    }
+
+    #[test]
+    fn test_group_mixed_operators() {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, SIZE).unwrap();
+        // 完整的一组
+        mem.mem_write_i32(BASE + 0x100, 120).unwrap();
+        mem.mem_write_i32(BASE + 0x104, 7).unwrap();
+        mem.mem_write_f32(BASE + 0x108, 3.5).unwrap();
+        // 160 不在区间内
+        mem.mem_write_i32(BASE + 0x400, 160).unwrap();
+        mem.mem_write_i32(BASE + 0x404, 7).unwrap();
+        mem.mem_write_f32(BASE + 0x408, 3.5).unwrap();
+        // 只有 0，没有满足 !0 的第三个值
+        mem.mem_write_i32(BASE + 0x800, 120).unwrap();
+        mem.mem_write_f32(BASE + 0x808, 3.5).unwrap();
+
+        let query = parse_search_query("100~150;!0;3.5F:16", ValueType::Dword).unwrap();
+        let results = scan_group(&mem, &query);
+        let found: BTreeSet<u64> = results.iter().map(|pair| pair.addr - BASE).collect();
+        assert_eq!(found, [0x100, 0x104, 0x108].into());
+
+        let addr_values = results.iter().map(|pair| (pair.addr, mem.mem_read(pair.addr, pair.value_type.size()).unwrap())).collect();
+        let refined = refine_group_values_with_cancel(addr_values, &query, None, None, &|| false, &|_, _| {});
+        assert_eq!(refined.len(), 3);
+
+        mem.mem_write_i32(BASE + 0x104, 0).unwrap();
+        assert!(scan_group(&mem, &query).is_empty());
+    }
+}
@@ -0,0 +1,128 @@
+//! Refine page cache tests
+//!
+//! 逐地址改善时同一页只读取一次：与逐地址直接读取的幸存结果相同，读取次数不超过页数，
+//! 失败页只尝试一次。超过容量时淘汰最久未用的页，新建的缓存读到的是当前值。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::batch_reader::{PageCache, PageCacheStats};
+    use crate::search::engine::manager::ValuePair;
+    use crate::search::engine::single_search::refine_values_with;
+    use crate::search::tests::mock_memory::MockMemory;
+    use crate::search::{SearchValue, ValueType};
+    use crate::wuwa::PageStatusBitmap;
+    use std::cell::{Cell, RefCell};
+
+    const BASE: u64 = 0x7B00000000;
+    const PAGE_SIZE: usize = 4096;
+    const PAGES: usize = 32;
+    const TARGET: u32 = 42;
+    const FAULTY_PAGES: [usize; 3] = [3, 10, 11];
+
+    /// 与驱动相同：读取覆盖的页都成功才算成功
+    fn read(mem: &MockMemory, reads: &Cell<usize>, addr: u64, buffer: &mut [u8]) -> bool {
+        reads.set(reads.get() + 1);
+        let mut page_status = PageStatusBitmap::new(buffer.len(), addr as usize);
+        let page_count = ((addr as usize % PAGE_SIZE) + buffer.len()).div_ceil(PAGE_SIZE);
+        mem.mem_read_with_status(addr, buffer, &mut page_status).is_ok() && (0..page_count).all(|page| page_status.is_page_success(page))
+    }
+
+    /// 每 4 字节一个 Dword 结果，从第 2 字节开始，每到页边界就有一个跨页的值
+    fn dense_setup() -> (MockMemory, Vec<ValuePair>) {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, PAGES * PAGE_SIZE).unwrap();
+        let mut pairs = Vec::new();
+        for i in 0..(PAGES * PAGE_SIZE / 4 - 1) {
+            let addr = BASE + 2 + (i * 4) as u64;
+            let value = if i.is_multiple_of(3) { TARGET } else { i as u32 + 1000 };
+            mem.mem_write_u32(addr, value).unwrap();
+            pairs.push(ValuePair::new(addr, ValueType::Dword));
+        }
+        mem.set_faulty_pages(BASE, &FAULTY_PAGES).unwrap();
+        (mem, pairs)
+    }
+
+    fn cached_refine(mem: &MockMemory, pairs: &[ValuePair], target: &SearchValue, capacity: usize) -> (Vec<ValuePair>, usize, PageCacheStats) {
+        let reads = Cell::new(0);
+        let stats = PageCacheStats::default();
+        let mut cache = PageCache::new(capacity, PAGE_SIZE, &stats);
+        let results = refine_values_with(
+            pairs,
+            target,
+            |addr, buffer| cache.read(addr, buffer, |page, buf| read(mem, &reads, page, buf)),
+            None,
+            None,
+            &|| false,
+            &|_, _| {},
+        );
+        drop(cache);
+        (results, reads.get(), stats)
+    }
+
+    #[test]
+    fn test_cached_refine_matches_direct_reads() {
+        let (mem, pairs) = dense_setup();
+        let target = SearchValue::fixed(TARGET as i128, ValueType::Dword);
+
+        let direct_reads = Cell::new(0);
+        let expected = refine_values_with(&pairs, &target, |addr, buffer| read(&mem, &direct_reads, addr, buffer), None, None, &|| false, &|_, _| {});
+        assert_eq!(direct_reads.get(), pairs.len());
+
+        let (results, reads, stats) = cached_refine(&mem, &pairs, &target, 256);
+        assert_eq!(results, expected);
+        // 每页只读取一次，失败页也不重试；从失败页开始的跨页值不再查看下一页
+        assert_eq!(reads, PAGES);
+        assert_eq!(stats.misses(), PAGES);
+        assert_eq!(stats.failed_pages(), FAULTY_PAGES.len());
+        assert_eq!(stats.hits() + stats.misses(), pairs.len() + PAGES - 1 - FAULTY_PAGES.len());
+
+        // 容量为 0 时逐地址直接读取
+        let (results, reads, stats) = cached_refine(&mem, &pairs, &target, 0);
+        assert_eq!(results, expected);
+        assert_eq!(reads, pairs.len());
+        assert_eq!(stats.hits() + stats.misses(), 0);
+    }
+
+    #[test]
+    fn test_lru_eviction_and_fresh_values() {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, 4 * PAGE_SIZE).unwrap();
+        mem.mem_write_u32(BASE, 1).unwrap();
+        let pages = RefCell::new(Vec::new());
+        let stats = PageCacheStats::default();
+        let mut cache = PageCache::new(2, PAGE_SIZE, &stats);
+        let mut buffer = [0u8; 4];
+        let mut access = |cache: &mut PageCache, page: usize| {
+            cache.read(BASE + (page * PAGE_SIZE) as u64, &mut buffer, |addr, buf| {
+                pages.borrow_mut().push(((addr - BASE) as usize) / PAGE_SIZE);
+                mem.mem_read_into(addr, buf).is_ok()
+            })
+        };
+
+        // 容量 2：访问 0、1、0 后再读 2，淘汰最久未用的 1
+        for page in [0, 1, 0, 2, 0, 1] {
+            assert!(access(&mut cache, page));
+        }
+        assert_eq!(pages.borrow().as_slice(), &[0, 1, 2, 1]);
+        assert_eq!((stats.hits(), stats.misses()), (2, 4));
+        drop(cache);
+
+        // 值在两次改善之间变化，新建的缓存重新读取
+        mem.mem_write_u32(BASE, 2).unwrap();
+        let stats = PageCacheStats::default();
+        let mut cache = PageCache::new(2, PAGE_SIZE, &stats);
+        assert!(cache.read(BASE, &mut buffer, |addr, buf| mem.mem_read_into(addr, buf).is_ok()));
+        assert_eq!(u32::from_le_bytes(buffer), 2);
+        // 超出映射的页读取失败，同一次改善内不再重试
+        let unmapped = BASE + (8 * PAGE_SIZE) as u64;
+        let attempts = Cell::new(0);
+        for _ in 0..3 {
+            assert!(!cache.read(unmapped, &mut buffer, |addr, buf| {
+                attempts.set(attempts.get() + 1);
+                mem.mem_read_into(addr, buf).is_ok()
+            }));
+        }
+        assert_eq!(attempts.get(), 1);
+        assert_eq!(stats.to_string(), "hits=2, misses=2, failed pages=1");
+    }
+}
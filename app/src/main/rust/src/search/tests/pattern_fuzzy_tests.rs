@@ -0,0 +1,113 @@
+//! Pattern fuzzy tests
+//!
+//! 特征码搜索的精确结果用 keep_results 转为模糊结果后，按特征码长度读取值：
+//! 不超过 8 字节的直接保存，更长的保存摘要，第 8 字节之后的变化同样能被 Changed 发现；
+//! 除 Changed / Unchanged 以外的条件都不成立，清空结果时特征码长度一起清除。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::batch_reader::ReadResultItem;
+    use crate::search::engine::compat::capture_fuzzy_values;
+    use crate::search::result_manager::{FuzzySearchResultItem, SearchResultManager, SearchResultMode};
+    use crate::search::tests::mock_memory::MockMemory;
+    use crate::search::{FuzzyCondition, ValuePair, ValueType};
+    use crate::search::tests::temp_dir::TempDir;
+
+    const BASE: u64 = 0x7A00000000;
+    /// 比 8 字节长的特征码
+    const PATTERN: [u8; 12] = [0xDE, 0xAD, 0xBE, 0xEF, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
+    /// 三个命中，相邻两个在同一页上
+    const HITS: [u64; 3] = [BASE + 0x100, BASE + 0x180, BASE + 0x2100];
+
+    fn memory() -> MockMemory {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, 0x4000).unwrap();
+        for addr in HITS {
+            mem.mem_write(addr, &PATTERN).unwrap();
+        }
+        mem
+    }
+
+    /// 与 keep_results 转换相同：按特征码长度读取精确结果的当前值
+    fn capture(mem: &MockMemory, pattern_len: usize) -> Vec<FuzzySearchResultItem> {
+        let pairs: Vec<ValuePair> = HITS.iter().map(|&addr| ValuePair::new(addr, ValueType::Pattern)).collect();
+        let read = |addr: u64, buf: &mut [u8]| mem.mem_read_into(addr, buf);
+        capture_fuzzy_values(&pairs, pattern_len, read, &|| false, &|_| {})
+    }
+
+    /// 与 fuzzy_refine_search_with 相同的比较，当前值从模拟内存读取
+    fn refine(mem: &MockMemory, items: &[FuzzySearchResultItem], pattern_len: usize, condition: FuzzyCondition) -> Vec<u64> {
+        items
+            .iter()
+            .filter_map(|item| {
+                let current = mem.mem_read(item.address, item.read_size(pattern_len)).unwrap();
+                ReadResultItem::new(item, &current).refine(condition)
+            })
+            .map(|item| item.address)
+            .collect()
+    }
+
+    #[test]
+    fn test_long_pattern_changed_after_eighth_byte() {
+        let mut mem = memory();
+        let items = capture(&mem, PATTERN.len());
+        assert_eq!(items.len(), HITS.len());
+        assert!(items.iter().all(|item| item.value == FuzzySearchResultItem::variable_value(&PATTERN)));
+        assert_eq!(items[0].read_size(PATTERN.len()), PATTERN.len());
+
+        assert_eq!(refine(&mem, &items, PATTERN.len(), FuzzyCondition::Unchanged), HITS.to_vec());
+        assert!(refine(&mem, &items, PATTERN.len(), FuzzyCondition::Changed).is_empty());
+
+        // 只改动第 11 个字节，前 8 字节不变
+        mem.mem_write(HITS[1] + 10, &[0xFF]).unwrap();
+        assert_eq!(refine(&mem, &items, PATTERN.len(), FuzzyCondition::Changed), vec![HITS[1]]);
+        assert_eq!(refine(&mem, &items, PATTERN.len(), FuzzyCondition::Unchanged), vec![HITS[0], HITS[2]]);
+
+        // 存活项保存新值的摘要，再比较时以它为基准
+        let current = mem.mem_read(HITS[1], PATTERN.len()).unwrap();
+        let refined = ReadResultItem::new(&items[1], &current).refine(FuzzyCondition::Changed).unwrap();
+        assert_eq!(refined.value, FuzzySearchResultItem::variable_value(&current));
+        assert!(refined.matches_condition(&current, FuzzyCondition::Unchanged));
+
+        // 数值条件对特征码没有意义
+        for condition in [FuzzyCondition::Increased, FuzzyCondition::Decreased, FuzzyCondition::IncreasedBy(1)] {
+            assert!(refine(&mem, &items, PATTERN.len(), condition).is_empty(), "{:?}", condition);
+        }
+        assert_eq!(refine(&mem, &items, PATTERN.len(), FuzzyCondition::Initial).len(), HITS.len());
+    }
+
+    #[test]
+    fn test_short_pattern_keeps_raw_bytes() {
+        let mut mem = memory();
+        let items = capture(&mem, 4);
+        assert!(items.iter().all(|item| item.value == [0xDE, 0xAD, 0xBE, 0xEF, 0, 0, 0, 0]));
+
+        mem.mem_write(HITS[2] + 3, &[0x00]).unwrap();
+        assert_eq!(refine(&mem, &items, 4, FuzzyCondition::Changed), vec![HITS[2]]);
+        // 特征码之后的字节不参与比较
+        mem.mem_write(HITS[0] + 4, &[0xFF]).unwrap();
+        assert_eq!(refine(&mem, &items, 4, FuzzyCondition::Unchanged), vec![HITS[0], HITS[1]]);
+    }
+
+    #[test]
+    fn test_pattern_len_cleared_with_results() {
+        let dir = TempDir::new("pattern_fuzzy");
+        let mem = memory();
+        let mut mgr = SearchResultManager::new(1024 * 1024, dir.to_path_buf());
+        mgr.set_mode(SearchResultMode::Fuzzy).unwrap();
+        mgr.add_fuzzy_results_batch(capture(&mem, PATTERN.len())).unwrap();
+        mgr.set_fuzzy_pattern_len(Some(PATTERN.len()));
+        assert_eq!(mgr.fuzzy_pattern_len(), Some(PATTERN.len()));
+
+        mgr.clear().unwrap();
+        assert_eq!(mgr.fuzzy_pattern_len(), None);
+
+        // 离开模糊模式同样清除
+        mgr.set_fuzzy_pattern_len(Some(PATTERN.len()));
+        mgr.set_mode(SearchResultMode::Exact).unwrap();
+        mgr.set_mode(SearchResultMode::Fuzzy).unwrap();
+        assert_eq!(mgr.fuzzy_pattern_len(), None);
+
+        drop(mgr);
+    }
+}
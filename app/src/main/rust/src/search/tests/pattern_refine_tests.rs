@@ -0,0 +1,75 @@
+//! Pattern refine tests
+//!
+//! 特征码结果的改善：新的特征码只在每个结果地址上匹配一次，不在附近重新扫描；半字节通配照常生效。
+//! 也可以用普通值改善，按匹配起始处的值比较，幸存的结果仍是特征码结果。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::single_search::refine_values_with;
+    use crate::search::{create_pattern_search_value, parse_search_query, ValuePair, ValueType};
+
+    const BASE: u64 = 0x7C00000000;
+
+    fn no_cancel() -> bool {
+        false
+    }
+
+    fn memory() -> Vec<u8> {
+        let mut memory = vec![0u8; 0x100];
+        // 0x00 / 0x20：与 "1A ?B C? ??" 匹配
+        memory[0x00..0x04].copy_from_slice(&[0x1A, 0x2B, 0xC3, 0x44]);
+        memory[0x20..0x24].copy_from_slice(&[0x1A, 0xFB, 0xC0, 0x00]);
+        // 0x40：第二个字节的低半字节变了
+        memory[0x40..0x44].copy_from_slice(&[0x1A, 0x2C, 0xC3, 0x44]);
+        // 0x60：原来的匹配后移了一个字节
+        memory[0x61..0x65].copy_from_slice(&[0x1A, 0x2B, 0xC3, 0x44]);
+        memory
+    }
+
+    fn refine(memory: &[u8], addresses: &[(u64, ValueType)], query: &crate::search::SearchValue) -> Vec<(u64, ValueType)> {
+        let read = |addr: u64, buffer: &mut [u8]| {
+            let offset = (addr - BASE) as usize;
+            buffer.copy_from_slice(&memory[offset..offset + buffer.len()]);
+            true
+        };
+        let pairs: Vec<ValuePair> = addresses.iter().map(|&(offset, value_type)| ValuePair::new(BASE + offset, value_type)).collect();
+        refine_values_with(&pairs, query, read, None, None, &no_cancel, &|_, _| {})
+            .iter()
+            .map(|pair| (pair.addr - BASE, pair.value_type))
+            .collect()
+    }
+
+    #[test]
+    fn test_wildcard_nibbles_survive_refine() {
+        let memory = memory();
+        let results: Vec<(u64, ValueType)> = [0x00, 0x20, 0x40, 0x60].iter().map(|&offset| (offset, ValueType::Pattern)).collect();
+
+        let pattern = create_pattern_search_value("1A ?B C? ??").unwrap();
+        assert_eq!(refine(&memory, &results, &pattern), vec![(0x00, ValueType::Pattern), (0x20, ValueType::Pattern)]);
+
+        // 更严格的特征码继续缩小
+        let pattern = create_pattern_search_value("1A 2B ?3").unwrap();
+        assert_eq!(refine(&memory, &results, &pattern), vec![(0x00, ValueType::Pattern)]);
+
+        // 特征码只匹配特征码结果
+        let dwords = [(0x00, ValueType::Dword), (0x20, ValueType::Dword)];
+        assert!(refine(&memory, &dwords, &create_pattern_search_value("1A ?? ?? ??").unwrap()).is_empty());
+    }
+
+    #[test]
+    fn test_plain_value_refines_pattern_results_at_offset_zero() {
+        let memory = memory();
+        let results: Vec<(u64, ValueType)> = [0x00, 0x20, 0x40, 0x60].iter().map(|&offset| (offset, ValueType::Pattern)).collect();
+
+        let value = parse_search_query("0x44C32B1A", ValueType::Dword).unwrap().values[0].clone();
+        assert_eq!(refine(&memory, &results, &value), vec![(0x00, ValueType::Pattern)]);
+
+        // Word 只比较匹配起始的两个字节
+        let value = parse_search_query("0xFB1A", ValueType::Word).unwrap().values[0].clone();
+        assert_eq!(refine(&memory, &results, &value), vec![(0x20, ValueType::Pattern)]);
+
+        // 0x60 的匹配起始处是 0
+        let value = parse_search_query("0", ValueType::Byte).unwrap().values[0].clone();
+        assert_eq!(refine(&memory, &results, &value), vec![(0x60, ValueType::Pattern)]);
+    }
+}
@@ -0,0 +1,108 @@
+//! Pattern replace tests
+//!
+//! 用 mock 内存修补匹配：保留字节不变、搜索后内存变化的匹配跳过、
+//! 跨页的替换在后一页写入失败时回滚前一页。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::pattern_replace::{PatchOutcome, PatchStats, apply_replacement, patch_match_with, validate_replacement};
+    use crate::search::parse_pattern;
+    use crate::search::tests::mock_memory::MockMemory;
+    use anyhow::anyhow;
+
+    const BASE: u64 = 0x7600000000;
+    const PAGE: usize = 4096;
+
+    fn patch(mem: &mut MockMemory, address: u64, pattern: &str, replacement: &[Option<u8>]) -> PatchOutcome {
+        let pattern = parse_pattern(pattern).unwrap();
+        let current = mem.mem_read(address, pattern.len());
+        patch_match_with(
+            address,
+            &pattern,
+            replacement,
+            PAGE,
+            |_, buf| {
+                buf.copy_from_slice(&current?);
+                Ok(())
+            },
+            |addr, bytes| mem.mem_write(addr, bytes),
+        )
+    }
+
+    #[test]
+    fn test_validate_replacement() {
+        let pattern = parse_pattern("1A ?B C? ??").unwrap();
+        assert!(validate_replacement(&pattern, &[Some(0x90), None, Some(0x90)]).is_ok());
+        assert!(validate_replacement(&pattern, &[Some(0x90); 5]).is_err());
+        assert!(validate_replacement(&pattern, &[None, None]).is_err());
+        assert!(validate_replacement(&[], &[Some(0x90)]).is_err());
+        assert_eq!(apply_replacement(&[1, 2, 3], &[None, Some(9)]), vec![1, 9]);
+    }
+
+    #[test]
+    fn test_patch_keeps_wildcard_bytes() {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, PAGE).unwrap();
+        mem.mem_write(BASE + 0x10, &[0x1A, 0x5B, 0xC7, 0x42]).unwrap();
+
+        let outcome = patch(&mut mem, BASE + 0x10, "1A ?B C? ??", &[Some(0x90), None, Some(0x1F)]);
+        assert_eq!(outcome, PatchOutcome::Patched);
+        assert_eq!(mem.mem_read(BASE + 0x10, 4).unwrap(), vec![0x90, 0x5B, 0x1F, 0x42]);
+
+        // 已修补的地址不再匹配；替换结果与原字节相同时不写入
+        assert_eq!(patch(&mut mem, BASE + 0x10, "1A ?B C? ??", &[Some(0x90)]), PatchOutcome::Mismatch);
+        assert_eq!(patch(&mut mem, BASE + 0x11, "5B", &[Some(0x5B)]), PatchOutcome::Unchanged);
+        assert_eq!(patch(&mut mem, BASE + 0x10000, "5B", &[Some(0x00)]), PatchOutcome::ReadFailed);
+
+        let mut stats = PatchStats::default();
+        for outcome in [
+            PatchOutcome::Patched,
+            PatchOutcome::Mismatch,
+            PatchOutcome::ReadFailed,
+            PatchOutcome::WriteFailed,
+        ] {
+            stats.record(outcome);
+        }
+        assert_eq!((stats.patched, stats.mismatched, stats.failed), (1, 1, 2));
+    }
+
+    #[test]
+    fn test_patch_across_pages_rolls_back() {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, PAGE * 2).unwrap();
+        let address = BASE + PAGE as u64 - 2;
+        mem.mem_write(address, &[0xAA, 0xBB, 0xCC, 0xDD]).unwrap();
+        let pattern = parse_pattern("AA BB CC DD").unwrap();
+        let replacement = [Some(0x11), Some(0x22), Some(0x33), Some(0x44)];
+
+        // 第二页不可写：第一页写入后被恢复
+        let current = mem.mem_read(address, 4).unwrap();
+        let mut writes = Vec::new();
+        let outcome = patch_match_with(
+            address,
+            &pattern,
+            &replacement,
+            PAGE,
+            |_, buf| {
+                buf.copy_from_slice(&current);
+                Ok(())
+            },
+            |addr, bytes| {
+                writes.push((addr, bytes.to_vec()));
+                if addr >= BASE + PAGE as u64 {
+                    return Err(anyhow!("read-only page"));
+                }
+                mem.mem_write(addr, bytes)
+            },
+        );
+        assert_eq!(outcome, PatchOutcome::WriteFailed);
+        assert_eq!(
+            writes,
+            vec![(address, vec![0x11, 0x22]), (BASE + PAGE as u64, vec![0x33, 0x44]), (address, vec![0xAA, 0xBB])]
+        );
+        assert_eq!(mem.mem_read(address, 4).unwrap(), vec![0xAA, 0xBB, 0xCC, 0xDD]);
+
+        assert_eq!(patch(&mut mem, address, "AA BB CC DD", &replacement), PatchOutcome::Patched);
+        assert_eq!(mem.mem_read(address, 4).unwrap(), vec![0x11, 0x22, 0x33, 0x44]);
+    }
+}
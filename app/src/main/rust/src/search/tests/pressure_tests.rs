@@ -0,0 +1,148 @@
+//! Memory pressure ladder tests
+//!
+//! 注入假的 RSS 读取，按区域顺序驱动降级阶梯，断言 累积 -> 流式写入 -> 停止 按顺序触发，
+//! 并且阶梯升级后提交线程把每一级之前的结果都写入了结果管理器。
+
+#[cfg(test)]
+mod tests {
+    use crate::core::memory_pressure::MemoryPressureGuard;
+    use crate::search::ValueType;
+    use crate::search::engine::ValuePair;
+    use crate::search::engine::pressure::{PressureLadder, PressureReport, ScanStage};
+    use crate::search::engine::result_commit::{CommitOutcome, RegionBatch, ResultCommitter};
+    use crate::search::result_manager::{SearchResultItem, SearchResultManager};
+    use crate::search::tests::temp_dir::TempDir;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+
+    const MB: u64 = 1024 * 1024;
+    const SOFT: u64 = 100 * MB;
+    const HARD: u64 = 200 * MB;
+    const CHUNK: usize = 512 * 1024;
+    const BASE: u64 = 0x7400000000;
+    const PER_REGION: u64 = 3;
+    const NO_LIMIT: usize = usize::MAX;
+
+    fn fake_guard() -> (MemoryPressureGuard, Arc<AtomicU64>) {
+        let rss = Arc::new(AtomicU64::new(0));
+        let source = Arc::clone(&rss);
+        let guard = MemoryPressureGuard::with_reader(Box::new(move || Some(source.load(Ordering::Relaxed))), SOFT, HARD);
+        (guard, rss)
+    }
+
+    /// 区域 i 的结果，倒序给出以验证提交前排序
+    fn region_results(region: u64) -> Vec<ValuePair> {
+        (0..PER_REGION)
+            .rev()
+            .map(|k| ValuePair::new(BASE + region * 0x1000 + k * 4, ValueType::Dword))
+            .collect()
+    }
+
+    /// 按区域顺序扫描：每个区域开始前按 `rss_mb` 采样一次，模拟后台采样任务；
+    /// 阶梯离开 Buffering 后提交线程开始写入
+    fn run_regions<C>(ladder: &PressureLadder, committer: &mut ResultCommitter<C>, guard: &MemoryPressureGuard, rss: &AtomicU64, rss_mb: &[u64]) -> Vec<Option<usize>>
+    where
+        C: FnMut(Vec<ValuePair>) -> anyhow::Result<()>,
+    {
+        rss_mb
+            .iter()
+            .enumerate()
+            .map(|(region, &mb)| {
+                rss.store(mb * MB, Ordering::Relaxed);
+                guard.sample();
+                let chunk = ladder.before_region();
+                if ladder.stage() != ScanStage::Buffering {
+                    committer.start_committing().unwrap();
+                }
+                let batch = match chunk {
+                    Some(_) => RegionBatch::new(region, region_results(region as u64)),
+                    None => RegionBatch::empty(region),
+                };
+                committer.accept(batch).unwrap();
+                chunk
+            })
+            .collect()
+    }
+
+    fn addresses(batch: &[ValuePair]) -> Vec<u64> {
+        batch.iter().map(|pair| pair.addr).collect()
+    }
+
+    #[test]
+    fn test_ladder_triggers_in_order() {
+        let (guard, rss) = fake_guard();
+        let dir = TempDir::new("pressure_ladder");
+        let mut store = SearchResultManager::new(0, dir.to_path_buf());
+        let mut batches = Vec::new();
+
+        let ladder = PressureLadder::new(&guard, CHUNK);
+        let mut committer = ResultCommitter::new(NO_LIMIT, NO_LIMIT, |batch: Vec<ValuePair>| {
+            batches.push(addresses(&batch));
+            store.add_results_batch(batch.iter().map(SearchResultItem::from).collect())
+        });
+
+        // 正常、正常、软、软、回落到正常（不恢复）、硬、硬
+        let chunks = run_regions(&ladder, &mut committer, &guard, &rss, &[50, 50, 150, 120, 90, 250, 50]);
+        let degraded = CHUNK / 4;
+        assert_eq!(
+            chunks,
+            vec![Some(CHUNK), Some(CHUNK), Some(degraded), Some(degraded), Some(degraded), None, None]
+        );
+
+        let outcome = committer.finish().unwrap();
+        assert_eq!(outcome, CommitOutcome::Committed { count: 5 * PER_REGION as usize, runs: 1 });
+        assert_eq!(ladder.finish(), PressureReport { stage: ScanStage::Stopped, skipped_regions: 2 });
+
+        // 写入的结果按区域顺序排列，整体按地址有序
+        let written: Vec<u64> = batches.concat();
+        let mut expected: Vec<u64> = (0..5).flat_map(|r| addresses(&region_results(r))).collect();
+        expected.sort_unstable();
+        assert_eq!(written, expected);
+
+        // 停止前扫描过的区域全部保存在结果管理器里
+        assert_eq!(store.total_count(), 5 * PER_REGION as usize);
+        drop(store);
+    }
+
+    #[test]
+    fn test_no_pressure_keeps_buffering() {
+        let (guard, rss) = fake_guard();
+        let mut flushed = 0usize;
+        let ladder = PressureLadder::new(&guard, CHUNK);
+        let mut committer = ResultCommitter::new(NO_LIMIT, NO_LIMIT, |batch: Vec<ValuePair>| {
+            flushed += batch.len();
+            Ok(())
+        });
+
+        let chunks = run_regions(&ladder, &mut committer, &guard, &rss, &[10, 60, 99]);
+        assert_eq!(chunks, vec![Some(CHUNK); 3]);
+
+        let CommitOutcome::Retained(retained) = committer.finish().unwrap() else {
+            panic!("results should stay in memory without pressure");
+        };
+        assert_eq!(retained.len(), 3 * PER_REGION as usize);
+        assert_eq!(ladder.finish(), PressureReport { stage: ScanStage::Buffering, skipped_regions: 0 });
+        assert_eq!(flushed, 0);
+    }
+
+    #[test]
+    fn test_hard_limit_first_still_flushes_buffered_results() {
+        let (guard, rss) = fake_guard();
+        let mut batches = Vec::new();
+        let ladder = PressureLadder::new(&guard, CHUNK);
+        let mut committer = ResultCommitter::new(NO_LIMIT, NO_LIMIT, |batch: Vec<ValuePair>| {
+            batches.push(batch.len());
+            Ok(())
+        });
+
+        // 直接从正常跳到硬限制：累积的结果先写入，再停止
+        let chunks = run_regions(&ladder, &mut committer, &guard, &rss, &[50, 50, 300, 50]);
+        assert_eq!(chunks, vec![Some(CHUNK), Some(CHUNK), None, None]);
+
+        let outcome = committer.finish().unwrap();
+        assert_eq!(outcome, CommitOutcome::Committed { count: 2 * PER_REGION as usize, runs: 1 });
+        assert_eq!(batches, vec![2 * PER_REGION as usize]);
+        let report = ladder.finish();
+        assert_eq!((report.stage, report.skipped_regions), (ScanStage::Stopped, 2));
+    }
+}
@@ -0,0 +1,140 @@
+//! Pass provenance tests
+//!
+//! 首次扫描的结果为第 0 轮，手动添加为第 1 轮；细化（只保留部分结果）后幸存的结果保留原来的轮次，
+//! 并能按轮次过滤、按"最旧的在前"排序，返回的索引始终是结果存储中的索引。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::ResultOrder;
+    use crate::search::engine::provenance::{PassLookup, PassOrder};
+    use crate::search::result_manager::FuzzySearchResultItem;
+    use crate::search::result_manager::SearchResultMode;
+    use crate::search::{SearchEngineManager, SearchResultItem, ValueType};
+    use crate::search::tests::temp_dir::TempDir;
+
+    const BASE: u64 = 0x7400000000;
+
+    fn exact_batch(addrs: &[u64]) -> Vec<SearchResultItem> {
+        addrs.iter().map(|&addr| SearchResultItem::new_exact(addr, ValueType::Dword)).collect()
+    }
+
+    fn summary(page: &[(usize, SearchResultItem)]) -> Vec<(usize, u64, u8)> {
+        page.iter()
+            .map(|(index, item)| match item {
+                SearchResultItem::Exact(exact) => (*index, exact.address, exact.pass),
+                SearchResultItem::Fuzzy(fuzzy) => (*index, fuzzy.address, fuzzy.pass),
+            })
+            .collect()
+    }
+
+    /// 扫描（第 0 轮）-> 手动添加附近地址（第 1 轮）-> 细化，幸存结果保留轮次
+    fn setup(name: &str) -> (SearchEngineManager, TempDir) {
+        let dir = TempDir::new(name);
+        let mut manager = SearchEngineManager::new();
+        manager.init(0, dir.to_string_lossy().into_owned(), 0).unwrap();
+        manager.set_result_mode(SearchResultMode::Exact).unwrap();
+
+        manager.add_results_batch(exact_batch(&[BASE, BASE + 0x10, BASE + 0x20, BASE + 0x30])).unwrap();
+        manager.add_results_batch(exact_batch(&[BASE + 0x8, BASE + 0x18, BASE + 0x28])).unwrap();
+        // 存储顺序：0x00 0x10 0x20 0x30 0x08 0x18 0x28，保留 0x00 0x20 0x30 0x08 0x28
+        manager.keep_only_results(vec![0, 2, 3, 4, 6]).unwrap();
+        (manager, dir)
+    }
+
+    #[test]
+    fn test_passes_survive_refine() {
+        let (manager, _dir) = setup("provenance_refine");
+
+        let page = manager.get_filtered_results(0, 100).unwrap();
+        assert_eq!(
+            summary(&page),
+            vec![(0, BASE, 0), (1, BASE + 0x20, 0), (2, BASE + 0x30, 0), (3, BASE + 0x8, 1), (4, BASE + 0x28, 1)]
+        );
+
+        // 清空后重新开始计数
+        let mut manager = manager;
+        manager.clear_results().unwrap();
+        manager.add_results_batch(exact_batch(&[BASE])).unwrap();
+        assert_eq!(manager.get_filtered_results(0, 1).unwrap()[0].1.pass(), 0);
+
+        drop(manager);
+    }
+
+    #[test]
+    fn test_filter_by_pass() {
+        let (mut manager, _dir) = setup("provenance_filter");
+
+        manager.set_pass_filter(Some(1), None);
+        let page = manager.get_filtered_results(0, 100).unwrap();
+        assert_eq!(summary(&page), vec![(3, BASE + 0x8, 1), (4, BASE + 0x28, 1)]);
+
+        manager.set_pass_filter(None, Some(0));
+        let page = manager.get_filtered_results(0, 100).unwrap();
+        assert_eq!(page.iter().map(|(index, _)| *index).collect::<Vec<_>>(), vec![0, 1, 2]);
+
+        manager.set_pass_filter(Some(2), None);
+        assert!(manager.get_filtered_results(0, 100).unwrap().is_empty());
+
+        drop(manager);
+    }
+
+    #[test]
+    fn test_oldest_first_order() {
+        let dir = TempDir::new("provenance_order");
+        let mut manager = SearchEngineManager::new();
+        manager.init(0, dir.to_string_lossy().into_owned(), 0).unwrap();
+        manager.set_result_mode(SearchResultMode::Fuzzy).unwrap();
+
+        let fuzzy =
+            |addrs: &[u64]| -> Vec<SearchResultItem> { addrs.iter().map(|&addr| SearchResultItem::new_fuzzy(addr, [0; 8], ValueType::Dword)).collect() };
+        manager.add_results_batch(fuzzy(&[BASE, BASE + 0x20, BASE + 0x40])).unwrap();
+        manager.add_results_batch(fuzzy(&[BASE + 0x10, BASE + 0x30])).unwrap();
+
+        // 模糊细化按地址顺序改写整个结果集，两轮的结果在存储中交错
+        let result_mgr = manager.result_manager_mut().unwrap();
+        let mut all = result_mgr.get_all_fuzzy_results().unwrap();
+        all.sort();
+        result_mgr.replace_all_fuzzy_results(all).unwrap();
+
+        // 存储：0x00(0) 0x10(1) 0x20(0) 0x30(1) 0x40(0)
+        let storage = manager.get_filtered_results(0, 100).unwrap();
+        assert_eq!(storage.iter().map(|(_, item)| item.pass()).collect::<Vec<_>>(), vec![0, 1, 0, 1, 0]);
+
+        manager.set_result_order(ResultOrder::OldestFirst);
+        let page = manager.get_filtered_results(0, 100).unwrap();
+        assert_eq!(
+            summary(&page),
+            vec![(0, BASE, 0), (2, BASE + 0x20, 0), (4, BASE + 0x40, 0), (1, BASE + 0x10, 1), (3, BASE + 0x30, 1)]
+        );
+
+        // 按轮次过滤后在过滤视图上分页，索引仍是存储索引
+        let page = manager.get_filtered_results(2, 2).unwrap();
+        assert_eq!(summary(&page), vec![(4, BASE + 0x40, 0), (1, BASE + 0x10, 1)]);
+        manager.set_pass_filter(Some(1), None);
+        let page = manager.get_filtered_results(0, 2).unwrap();
+        assert_eq!(summary(&page), vec![(1, BASE + 0x10, 1), (3, BASE + 0x30, 1)]);
+        assert!(manager.get_filtered_results(2, 2).unwrap().is_empty());
+
+        drop(manager);
+    }
+
+    #[test]
+    fn test_pass_order_and_lookup() {
+        let passes = [2u8, 0, 1, 0, 2, 1];
+        let order = PassOrder::build(&passes);
+        assert_eq!(order.page(0, 6, passes.len()), vec![1, 3, 2, 5, 0, 4]);
+        assert_eq!(order.page(4, 10, passes.len()), vec![0, 4]);
+        assert!(order.page(6, 1, passes.len()).is_empty());
+        assert!(matches!(PassOrder::build(&[0, 0, 1, 3]), PassOrder::Identity));
+
+        let lookup = PassLookup::from_pairs([(BASE + 0x20, 1), (BASE, 0), (BASE + 0x10, 2)]);
+        assert_eq!((lookup.get(BASE), lookup.get(BASE + 0x10), lookup.get(BASE + 0x20)), (0, 2, 1));
+        assert_eq!(lookup.get(BASE + 0x30), 0);
+        assert!(matches!(PassLookup::from_pairs([(BASE, 3), (BASE + 4, 3)]), PassLookup::Uniform(3)));
+
+        // 模糊结果改写值时保留轮次
+        let item = FuzzySearchResultItem::new(BASE, [1, 0, 0, 0, 0, 0, 0, 0], ValueType::Dword).with_pass(4);
+        let updated = item.with_new_value(&[2, 0, 0, 0]);
+        assert_eq!({ updated.pass }, 4);
+    }
+}
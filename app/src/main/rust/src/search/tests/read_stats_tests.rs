@@ -0,0 +1,137 @@
+//! Read byte accounting tests
+//!
+//! Regions are scanned chunk by chunk over MockMemory the same way the region search loops do,
+//! recording every chunk read into `ReadStats`.
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::manager::ValuePair;
+    use crate::search::engine::read_stats::{ReadStats, is_nothing_readable, mb_per_sec};
+    use crate::search::engine::single_search::search_in_chunks_with_status;
+    use crate::search::tests::mock_memory::MockMemory;
+    use crate::search::{SearchValue, ValueType};
+    use crate::wuwa::PageStatusBitmap;
+
+    const BASE: u64 = 0x7700000000;
+    const SIZE: usize = 0x10000;
+    const CHUNK_SIZE: usize = 0x4000;
+
+    fn no_cancel() -> bool {
+        false
+    }
+
+    fn target() -> SearchValue {
+        SearchValue::fixed(0x1234_5678, ValueType::Dword)
+    }
+
+    fn scan(mem: &MockMemory, start: u64, end: u64, stats: &ReadStats) -> Vec<ValuePair> {
+        let target = target();
+        let mut results = Vec::new();
+        let mut buffer = vec![0u8; CHUNK_SIZE];
+        let mut current = start & !(mem.page_size() as u64 - 1);
+
+        while current < end {
+            let chunk_end = (current + CHUNK_SIZE as u64).min(end);
+            let chunk_len = (chunk_end - current) as usize;
+            let mut page_status = PageStatusBitmap::new(chunk_len, current as usize);
+            let read_result = mem.mem_read_with_status(current, &mut buffer[..chunk_len], &mut page_status);
+            stats.record_chunk(current, chunk_len, read_result.is_ok(), &page_status, mem.page_size());
+
+            if read_result.is_ok() && page_status.success_count() > 0 {
+                search_in_chunks_with_status(
+                    &buffer[..chunk_len],
+                    current,
+                    start,
+                    end,
+                    4,
+                    &target,
+                    ValueType::Dword,
+                    &page_status,
+                    &mut results,
+                    &no_cancel,
+                );
+            }
+            current = chunk_end;
+        }
+        results
+    }
+
+    #[test]
+    fn test_all_reads_failing_is_nothing_readable() {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, SIZE).unwrap();
+        let pages: Vec<usize> = (0..SIZE / mem.page_size()).collect();
+        mem.set_faulty_pages(BASE, &pages).unwrap();
+        mem.mem_write_u32(BASE + 0x100, 0x1234_5678).unwrap();
+
+        let stats = ReadStats::new();
+        let mut results = scan(&mem, BASE, BASE + SIZE as u64, &stats);
+        // 未映射的区域整块读取失败
+        results.extend(scan(&mem, 0x7800000000, 0x7800000000 + SIZE as u64, &stats));
+
+        let (readable, failed) = stats.snapshot();
+        assert!(results.is_empty());
+        assert_eq!(readable, 0);
+        assert_eq!(failed, 2 * SIZE as u64);
+        assert_eq!(stats.failed_pages(), (2 * SIZE / mem.page_size()) as u64);
+        assert!(is_nothing_readable(results.len(), readable, 2 * SIZE as u64));
+    }
+
+    #[test]
+    fn test_empty_result_with_readable_memory_is_not_flagged() {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, SIZE).unwrap();
+        mem.mem_write_u32(BASE + 0x100, 0x1111_1111).unwrap();
+
+        let stats = ReadStats::new();
+        let results = scan(&mem, BASE, BASE + SIZE as u64, &stats);
+
+        let (readable, failed) = stats.snapshot();
+        assert!(results.is_empty());
+        assert_eq!(readable, SIZE as u64);
+        assert_eq!(failed, 0);
+        assert_eq!(stats.failed_pages(), 0);
+        assert!(!is_nothing_readable(results.len(), readable, SIZE as u64));
+        // 有结果时无论读了多少都不标记
+        assert!(!is_nothing_readable(1, 0, SIZE as u64));
+    }
+
+    #[test]
+    fn test_counters_match_readable_pages() {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, SIZE).unwrap();
+        let page_size = mem.page_size();
+        let faulty = [1usize, 4, 5, 15];
+        mem.set_faulty_pages(BASE, &faulty).unwrap();
+        mem.mem_write_u32(BASE, 0x1234_5678).unwrap();
+        mem.mem_write_u32(BASE + 2 * page_size as u64, 0x1234_5678).unwrap();
+        mem.mem_write_u32(BASE + 4 * page_size as u64, 0x1234_5678).unwrap();
+
+        // 结束地址不对齐，最后一页只算到区域末尾
+        let end = BASE + SIZE as u64 - 0x800;
+        let stats = ReadStats::new();
+        let results = scan(&mem, BASE, end, &stats);
+
+        let expected_readable: u64 = (0..SIZE / page_size)
+            .filter(|page| !faulty.contains(page))
+            .map(|page| {
+                let page_start = BASE + (page * page_size) as u64;
+                (page_start + page_size as u64).min(end).saturating_sub(page_start)
+            })
+            .sum();
+
+        let (readable, failed) = stats.snapshot();
+        assert_eq!(results.len(), 2);
+        assert_eq!(readable, expected_readable);
+        assert_eq!(readable + failed, end - BASE);
+        assert_eq!(stats.failed_pages(), faulty.len() as u64);
+        assert!(!is_nothing_readable(results.len(), readable, end - BASE));
+    }
+
+    #[test]
+    fn test_throughput() {
+        assert_eq!(mb_per_sec(64 << 20, std::time::Duration::from_secs(2)), 32.0);
+        assert_eq!(mb_per_sec(0, std::time::Duration::from_secs(1)), 0.0);
+        assert_eq!(mb_per_sec(1 << 20, std::time::Duration::ZERO), 0.0);
+    }
+}
@@ -0,0 +1,163 @@
+//! Coalesced refine read tests
+//!
+//! 逐地址改善搜索把窗口内相邻的结果合并成一次读取。用逐地址读取作为对照，比较幸存结果和读取次数：
+//! 失败页上的结果被丢弃而不是中断，跨页的值要求两页都读取成功，跨越未映射空洞的读取退回逐地址读取。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::manager::ValuePair;
+    use crate::search::engine::single_search::{
+        CoalescedReader, DEFAULT_REFINE_READ_WINDOW, refine_values_coalesced_stream_with, refine_values_with,
+    };
+    use crate::search::tests::mock_memory::MockMemory;
+    use crate::search::{SearchValue, ValueType};
+    use crate::wuwa::PageStatusBitmap;
+    use std::cell::Cell;
+    use std::time::{Duration, Instant};
+
+    const BASE: u64 = 0x7900000000;
+    const PAGE_SIZE: usize = 4096;
+    const PAGES: usize = 64;
+    const TARGET: u32 = 42;
+    /// 模拟一次驱动调用的固定开销
+    const IOCTL_COST: Duration = Duration::from_micros(2);
+
+    fn simulate_ioctl() {
+        let started = Instant::now();
+        while started.elapsed() < IOCTL_COST {
+            std::hint::spin_loop();
+        }
+    }
+
+    /// 与驱动相同：按页记录读取状态，整段不在映射内时失败
+    fn read_span(mem: &MockMemory, reads: &Cell<usize>, addr: u64, buffer: &mut [u8], page_status: &mut PageStatusBitmap) -> bool {
+        reads.set(reads.get() + 1);
+        simulate_ioctl();
+        mem.mem_read_with_status(addr, buffer, page_status).is_ok()
+    }
+
+    /// 逐地址读取：值覆盖的页都读取成功才算成功
+    fn read_one(mem: &MockMemory, reads: &Cell<usize>, addr: u64, buffer: &mut [u8]) -> bool {
+        let mut page_status = PageStatusBitmap::new(buffer.len(), addr as usize);
+        let page_count = ((addr as usize % PAGE_SIZE) + buffer.len()).div_ceil(PAGE_SIZE);
+        read_span(mem, reads, addr, buffer, &mut page_status) && (0..page_count).all(|page| page_status.is_page_success(page))
+    }
+
+    /// 每 4 字节一个 Dword 结果，从第 2 字节开始，每到页边界就有一个跨页的值
+    fn dense_setup() -> (MockMemory, Vec<ValuePair>) {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, PAGES * PAGE_SIZE).unwrap();
+        let mut pairs = Vec::new();
+        for i in 0..(PAGES * PAGE_SIZE / 4 - 1) {
+            let addr = BASE + 2 + (i * 4) as u64;
+            let value = if i.is_multiple_of(3) { TARGET } else { i as u32 + 1000 };
+            mem.mem_write_u32(addr, value).unwrap();
+            pairs.push(ValuePair::new(addr, ValueType::Dword));
+        }
+        mem.set_faulty_pages(BASE, &[3, 10, 11, 40]).unwrap();
+        (mem, pairs)
+    }
+
+    fn per_item(mem: &MockMemory, pairs: &[ValuePair], target: &SearchValue) -> (Vec<ValuePair>, usize) {
+        let reads = Cell::new(0);
+        let results = refine_values_with(pairs, target, |addr, buffer| read_one(mem, &reads, addr, buffer), None, None, &|| false, &|_, _| {});
+        (results, reads.get())
+    }
+
+    fn coalesced(mem: &MockMemory, pairs: &[ValuePair], target: &SearchValue, batch_size: usize, window: usize) -> (Vec<ValuePair>, usize) {
+        let reads = Cell::new(0);
+        let results = refine_values_coalesced_stream_with(
+            pairs.iter().cloned(),
+            target,
+            batch_size,
+            window,
+            |addr, buffer, page_status| read_span(mem, &reads, addr, buffer, page_status),
+            None,
+            None,
+            &|| false,
+            &|_, _| {},
+        );
+        (results, reads.get())
+    }
+
+    #[test]
+    fn test_coalesced_refine_matches_per_item_with_fewer_reads() {
+        let (mem, pairs) = dense_setup();
+        let target = SearchValue::fixed(TARGET as i128, ValueType::Dword);
+
+        let started = Instant::now();
+        let (expected, per_item_reads) = per_item(&mem, &pairs, &target);
+        let per_item_time = started.elapsed();
+
+        let started = Instant::now();
+        let (results, coalesced_reads) = coalesced(&mem, &pairs, &target, pairs.len(), DEFAULT_REFINE_READ_WINDOW);
+        let coalesced_time = started.elapsed();
+
+        println!(
+            "refine over {} items: per item {} reads in {:?}, coalesced {} reads in {:?}",
+            pairs.len(),
+            per_item_reads,
+            per_item_time,
+            coalesced_reads,
+            coalesced_time
+        );
+
+        assert_eq!(results, expected);
+        assert_eq!(per_item_reads, pairs.len());
+        assert!(coalesced_reads * 10 <= per_item_reads, "{} coalesced reads for {} items", coalesced_reads, pairs.len());
+
+        // 失败页上的结果和跨进失败页的值都被丢弃，其他页的结果保留
+        let page_of = |addr: u64| ((addr - BASE) / PAGE_SIZE as u64) as usize;
+        assert!(results.iter().all(|pair| ![3, 10, 11, 40].contains(&page_of(pair.addr)) && ![3, 10, 11, 40].contains(&page_of(pair.addr + 3))));
+        assert!(results.iter().any(|pair| page_of(pair.addr) == 12));
+        assert!(results.iter().any(|pair| page_of(pair.addr) != page_of(pair.addr + 3)));
+    }
+
+    #[test]
+    fn test_coalesced_refine_independent_of_batch_and_window() {
+        let (mem, pairs) = dense_setup();
+        // 稀疏的子集：相邻结果相距超过默认窗口，每个地址单独成段
+        let sparse: Vec<ValuePair> = pairs.iter().step_by(5000).cloned().collect();
+        let target = SearchValue::fixed(TARGET as i128, ValueType::Dword);
+
+        for subset in [&pairs[..], &sparse[..]] {
+            let (expected, _) = per_item(&mem, subset, &target);
+            for (batch_size, window) in [(1000, 4096), (777, 65536), (subset.len(), 1)] {
+                let (results, _) = coalesced(&mem, subset, &target, batch_size, window);
+                assert_eq!(results, expected, "batch {} window {}", batch_size, window);
+            }
+        }
+
+        let (_, sparse_reads) = coalesced(&mem, &sparse, &target, sparse.len(), DEFAULT_REFINE_READ_WINDOW);
+        assert_eq!(sparse_reads, sparse.len());
+    }
+
+    #[test]
+    fn test_span_across_unmapped_gap_falls_back_per_item() {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, PAGE_SIZE).unwrap();
+        // 中间空一页没有映射
+        mem.malloc(BASE + 2 * PAGE_SIZE as u64, PAGE_SIZE).unwrap();
+        let addrs = [BASE + 0xFF0, BASE + 0xFFC, BASE + 2 * PAGE_SIZE as u64 + 0x10];
+        for addr in addrs {
+            mem.mem_write_u32(addr, TARGET).unwrap();
+        }
+        let pairs: Vec<ValuePair> = addrs.iter().map(|&addr| ValuePair::new(addr, ValueType::Dword)).collect();
+
+        let reads = Cell::new(0);
+        let mut reader = CoalescedReader::new(&pairs, 4, DEFAULT_REFINE_READ_WINDOW, |addr, buffer: &mut [u8], page_status: &mut PageStatusBitmap| {
+            read_span(&mem, &reads, addr, buffer, page_status)
+        });
+        let mut value = [0u8; 4];
+        for addr in addrs {
+            assert!(reader.read(addr, &mut value), "0x{:X}", addr);
+            assert_eq!(u32::from_le_bytes(value), TARGET);
+        }
+        // 一次失败的整段读取，之后逐个地址读取
+        assert_eq!(reader.reads(), 1 + addrs.len());
+
+        let target = SearchValue::fixed(TARGET as i128, ValueType::Dword);
+        let (results, _) = coalesced(&mem, &pairs, &target, pairs.len(), DEFAULT_REFINE_READ_WINDOW);
+        assert_eq!(results, pairs);
+    }
+}
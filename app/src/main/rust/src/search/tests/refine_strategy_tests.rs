@@ -0,0 +1,129 @@
+//! Refine strategy tests
+//!
+//! The rescan-and-intersect refine must keep exactly the survivors the per-item
+//! refine keeps. Per-item is reproduced here with MockMemory reads, since the real
+//! one goes through the driver.
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::manager::ValuePair;
+    use crate::search::engine::refine_strategy::{
+        occupied_regions, rescan_and_intersect_with, RefineCostModel, RefineStrategy,
+    };
+    use crate::search::tests::mock_memory::MockMemory;
+    use crate::search::{SearchValue, ValueType};
+    use crate::wuwa::PageStatusBitmap;
+
+    const BASE: u64 = 0x7700000000;
+    const REGION_SIZE: usize = 0x40000;
+    const TARGET: u32 = 0x0BAD_F00D;
+    const CHUNK_SIZE: usize = 0x8000;
+
+    fn no_cancel() -> bool {
+        false
+    }
+
+    fn no_progress(_: usize, _: usize) {}
+
+    /// 逐地址读取的参考实现
+    fn per_item(mem: &MockMemory, current: &[ValuePair], target: &SearchValue) -> Vec<ValuePair> {
+        let mut survivors: Vec<ValuePair> = current
+            .iter()
+            .filter(|pair| pair.value_type == target.value_type())
+            .filter(|pair| {
+                let size = pair.value_type.size();
+                let mut buf = vec![0u8; size];
+                let mut page_status = PageStatusBitmap::new(size, pair.addr as usize);
+                mem.mem_read_with_status(pair.addr, &mut buf, &mut page_status).is_ok()
+                    && page_status.success_count() > 0
+                    && target.matched(&buf).unwrap()
+            })
+            .cloned()
+            .collect();
+        survivors.sort_unstable();
+        survivors
+    }
+
+    fn rescan(mem: &MockMemory, current: &[ValuePair], target: &SearchValue) -> Vec<ValuePair> {
+        rescan_and_intersect_with(
+            current,
+            target,
+            CHUNK_SIZE,
+            |addr, buf, page_status| mem.mem_read_with_status(addr, buf, page_status),
+            &no_cancel,
+            &no_progress,
+        )
+        .unwrap()
+    }
+
+    /// 每 stride 字节放一个结果，其中每三个有一个的值已经改变
+    fn build(mem: &mut MockMemory, stride: u64) -> Vec<ValuePair> {
+        let mut current = Vec::new();
+        let mut addr = BASE;
+        let mut idx = 0u64;
+        while addr + 4 <= BASE + REGION_SIZE as u64 {
+            let value = if idx.is_multiple_of(3) { TARGET + 1 } else { TARGET };
+            mem.mem_write_u32(addr, value).unwrap();
+            current.push(ValuePair::new(addr, ValueType::Dword));
+            addr += stride;
+            idx += 1;
+        }
+        current
+    }
+
+    #[test]
+    fn test_rescan_matches_per_item_across_densities() {
+        let target = SearchValue::fixed(TARGET as i128, ValueType::Dword);
+
+        for stride in [4u64, 0x40, 0x1000, 0x9000] {
+            let mut mem = MockMemory::new();
+            mem.malloc(BASE, REGION_SIZE).unwrap();
+            let mut current = build(&mut mem, stride);
+
+            // 不对齐的结果、其他类型的结果、读取失败的页
+            mem.mem_write_u32(BASE + 0x2002, TARGET).unwrap();
+            current.push(ValuePair::new(BASE + 0x2002, ValueType::Dword));
+            current.push(ValuePair::new(BASE + 0x3000, ValueType::Float));
+            mem.set_faulty_pages(BASE, &[5]).unwrap();
+            // 未排序的输入
+            current.reverse();
+
+            let expected = per_item(&mem, &current, &target);
+            let actual = rescan(&mem, &current, &target);
+            assert!(!expected.is_empty());
+            assert_eq!(actual, expected, "stride 0x{:X}", stride);
+            assert!(actual.iter().all(|pair| pair.addr & !0xFFF != BASE + 0x5000), "faulty page must drop its results");
+        }
+    }
+
+    #[test]
+    fn test_occupied_regions_merge_pages() {
+        let results = [
+            ValuePair::new(BASE + 0x10, ValueType::Dword),
+            ValuePair::new(BASE + 0x20, ValueType::Dword),
+            ValuePair::new(BASE + 0x1FFE, ValueType::Dword),
+            ValuePair::new(BASE + 0x8000, ValueType::Qword),
+        ];
+        assert_eq!(
+            occupied_regions(&results, 0x1000),
+            vec![(BASE, BASE + 0x3000), (BASE + 0x8000, BASE + 0x9000)]
+        );
+    }
+
+    #[test]
+    fn test_cost_model_extremes() {
+        let model = RefineCostModel::default();
+        const GIB: u64 = 1024 * 1024 * 1024;
+
+        // 少量结果分散在很大的范围里：逐个读取
+        assert_eq!(model.choose(1_000, 1_000 * 4096), RefineStrategy::PerItem);
+        assert_eq!(model.choose(10, 64 * GIB), RefineStrategy::PerItem);
+
+        // 千万级结果挤在几百 MB 的页里：重新扫描
+        assert_eq!(model.choose(20_000_000, 256 * 1024 * 1024), RefineStrategy::RescanAndIntersect);
+
+        // 吞吐未知（为 0）时不会选择重新扫描
+        let unknown = RefineCostModel { scan_bytes_per_sec: 0.0, ..model };
+        assert_eq!(unknown.choose(20_000_000, 4096), RefineStrategy::PerItem);
+    }
+}
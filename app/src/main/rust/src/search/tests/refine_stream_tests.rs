@@ -0,0 +1,199 @@
+//! Streaming refine tests
+//!
+//! 改善搜索按地址顺序分批读取已有结果：结果跨越内存缓冲区和磁盘、由多次扫描追加成多个升序段时，
+//! 游标仍按地址顺序输出并找回每条结果的轮次。分批的单值和组改善与一次性读取全部结果的结果相同，
+//! 组改善的锚点窗口跨越批边界时不会丢失。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::group_search::{refine_group_stream_with, refine_group_values_with_cancel};
+    use crate::search::engine::result_stream::ResultStream;
+    use crate::search::engine::single_search::{refine_values_stream_with, refine_values_with};
+    use crate::search::result_manager::cursor::MAX_MERGE_RUNS;
+    use crate::search::result_manager::{ExactSearchResultItem, FuzzySearchResultItem, SearchResultManager, SearchResultMode};
+    use crate::search::tests::mock_memory::MockMemory;
+    use crate::search::{SearchMode, SearchQuery, SearchResultItem, SearchValue, SpanMode, ValuePair, ValueType};
+    use crate::search::tests::result_fixture::{split_result_manager, MEMORY_ITEMS};
+    use crate::search::tests::temp_dir::TempDir;
+    use std::cell::Cell;
+    use std::collections::BTreeSet;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    const BASE: u64 = 0x7900000000;
+    const SIZE: usize = 0x2000;
+
+    fn exact(address: u64, pass: u8) -> SearchResultItem {
+        SearchResultItem::new_exact(address, ValueType::Dword).with_pass(pass)
+    }
+
+    fn read_all(mgr: &SearchResultManager, batch_size: usize) -> Vec<ExactSearchResultItem> {
+        let mut cursor = mgr.cursor(batch_size).unwrap();
+        let mut items = Vec::new();
+        loop {
+            let batch = cursor.next_batch(mgr).unwrap();
+            if batch.is_empty() {
+                break;
+            }
+            assert!(batch.len() <= batch_size);
+            items.extend(batch);
+        }
+        items
+    }
+
+    #[test]
+    fn test_cursor_merges_runs_across_memory_and_disk() {
+        let dir = TempDir::new("refine_stream_cursor");
+        let mut mgr = split_result_manager(&dir, SearchResultMode::Exact, MEMORY_ITEMS);
+        // 第一次扫描的结果一半在内存一半在磁盘，保留结果的扫描在后面追加交错的第二段
+        mgr.add_results_batch((0..10).map(|i| exact(BASE + i * 8, 0)).collect()).unwrap();
+        mgr.add_results_batch((0..10).map(|i| exact(BASE + i * 8 + 4, 1)).collect()).unwrap();
+
+        let mut cursor = mgr.cursor(3).unwrap();
+        assert_eq!((cursor.total(), cursor.run_count()), (20, 2));
+
+        let mut stream = ResultStream::new(&mut cursor, |cursor| cursor.next_batch(&mgr));
+        let addresses: Vec<u64> = stream.by_ref().map(|pair| pair.addr).collect();
+        let passes = stream.finish().unwrap();
+        assert_eq!(addresses, (0..20).map(|i| BASE + i * 4).collect::<Vec<_>>());
+        assert_eq!((passes.get(BASE), passes.get(BASE + 4), passes.get(BASE + 0x48), passes.get(BASE + 0x4C)), (0, 1, 0, 1));
+
+        // 回到开头可以再遍历一次
+        cursor.rewind();
+        let mut again = Vec::new();
+        loop {
+            let batch = cursor.next_batch(&mgr).unwrap();
+            if batch.is_empty() {
+                break;
+            }
+            again.extend(batch.iter().map(|item| item.address));
+        }
+        assert_eq!(again, addresses);
+
+        // 遍历期间结果集变化时返回错误
+        cursor.rewind();
+        mgr.remove_result(0).unwrap();
+        assert!(cursor.next_batch(&mgr).is_err());
+        let mut stream = ResultStream::new(&mut cursor, |cursor| cursor.next_batch(&mgr));
+        assert_eq!(stream.by_ref().count(), 0);
+        assert!(stream.finish().is_err());
+
+    }
+
+    #[test]
+    fn test_cursor_fuzzy_results_and_many_runs() {
+        let dir = TempDir::new("refine_stream_fuzzy");
+        let mut mgr = split_result_manager(&dir, SearchResultMode::Fuzzy, MEMORY_ITEMS);
+        let fuzzy = [(BASE + 8, 2u8), (BASE, 0), (BASE + 4, 1)]
+            .iter()
+            .map(|&(address, pass)| FuzzySearchResultItem::new(address, [0; 8], ValueType::Float).with_pass(pass))
+            .collect();
+        mgr.add_fuzzy_results_batch(fuzzy).unwrap();
+        let items: Vec<_> = read_all(&mgr, 2).iter().map(|item| (item.address, item.typ, item.pass)).collect();
+        assert_eq!(items, vec![(BASE, ValueType::Float, 0), (BASE + 4, ValueType::Float, 1), (BASE + 8, ValueType::Float, 2)]);
+
+        // 升序段太多时退化为一次性排序
+        mgr.set_mode(SearchResultMode::Exact).unwrap();
+        mgr.clear().unwrap();
+        let count = MAX_MERGE_RUNS as u64 + 10;
+        mgr.add_results_batch((0..count).rev().map(|i| exact(BASE + i * 4, 0)).collect()).unwrap();
+        assert_eq!(mgr.cursor(16).unwrap().run_count(), 0);
+        let addresses: Vec<u64> = read_all(&mgr, 16).iter().map(|item| item.address).collect();
+        assert_eq!(addresses, (0..count).map(|i| BASE + i * 4).collect::<Vec<_>>());
+
+    }
+
+    /// 每 0x100 字节一组 100/200/300，组内位置轮换，组间填充其他值；另有一段不可读的结果
+    fn group_memory() -> (MockMemory, Vec<ValuePair>) {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, SIZE).unwrap();
+        for offset in (0..SIZE as u64).step_by(4) {
+            mem.mem_write_u32(BASE + offset, 7).unwrap();
+        }
+        let layouts: [[u64; 3]; 4] = [[0, 8, 16], [16, 8, 0], [8, 0, 16], [0, 4, 20]];
+        for group in 0..(SIZE as u64 / 0x100) {
+            let layout = layouts[group as usize % layouts.len()];
+            for (offset, value) in layout.iter().zip([100u32, 200, 300]) {
+                mem.mem_write_u32(BASE + group * 0x100 + 0x40 + offset, value).unwrap();
+            }
+        }
+
+        let mut pairs: Vec<ValuePair> = (0..SIZE as u64).step_by(4).map(|offset| ValuePair::new(BASE + offset, ValueType::Dword)).collect();
+        pairs.extend((0..8).map(|i| ValuePair::new(BASE + SIZE as u64 + i * 4, ValueType::Dword)));
+        // 重复的地址
+        pairs.insert(17, pairs[16].clone());
+        (mem, pairs)
+    }
+
+    #[test]
+    fn test_group_stream_matches_full_refine() {
+        let (mem, pairs) = group_memory();
+        let reads = Cell::new(0);
+        let read = |addr: u64, buf: &mut [u8]| {
+            reads.set(reads.get() + 1);
+            mem.mem_read_into(addr, buf).is_ok()
+        };
+
+        let values = vec![SearchValue::fixed(100, ValueType::Dword), SearchValue::fixed(200, ValueType::Dword), SearchValue::fixed(300, ValueType::Dword)];
+        let queries = [
+            SearchQuery::new(values.clone(), SearchMode::Unordered, 16),
+            SearchQuery::new(values.clone(), SearchMode::Unordered, 16).with_span_mode(SpanMode::GroupSpan),
+            SearchQuery::new(values.clone(), SearchMode::Ordered, 20),
+            SearchQuery::new(values[..1].to_vec(), SearchMode::Unordered, 16),
+        ];
+
+        for query in &queries {
+            let addr_values = pairs
+                .iter()
+                .filter_map(|pair| mem.mem_read(pair.addr, pair.value_type.size()).ok().map(|bytes| (pair.addr, bytes)))
+                .collect();
+            let expected: BTreeSet<u64> = refine_group_values_with_cancel(addr_values, query, None, None, &|| false, &|_, _| {})
+                .iter()
+                .map(|pair| pair.addr)
+                .collect();
+            assert!(!expected.is_empty());
+
+            // 批大小 1 时每个锚点的窗口都跨越批边界
+            for batch_size in [1, 3, 7, 64, pairs.len() + 1] {
+                reads.set(0);
+                let processed = Arc::new(AtomicUsize::new(0));
+                let refined: BTreeSet<u64> =
+                    refine_group_stream_with(pairs.iter().cloned(), query, batch_size, read, Some(&processed), None, &|| false, &|_, _| {})
+                        .iter()
+                        .map(|pair| pair.addr)
+                        .collect();
+                assert_eq!(refined, expected, "mode={:?} span={:?} batch={}", query.mode, query.span_mode, batch_size);
+                // 每个地址只读取一次，重复的地址不读取
+                assert_eq!(reads.get(), pairs.len() - 1);
+                assert_eq!(processed.load(Ordering::Relaxed), pairs.len());
+            }
+        }
+
+        // 取消时返回空集合
+        let cancelled = refine_group_stream_with(pairs.iter().cloned(), &queries[0], 3, read, None, None, &|| true, &|_, _| {});
+        assert_eq!(cancelled.len(), 0);
+    }
+
+    #[test]
+    fn test_single_stream_matches_full_refine() {
+        let (mem, pairs) = group_memory();
+        let read = |addr: u64, buf: &mut [u8]| mem.mem_read_into(addr, buf).is_ok();
+        let target = SearchValue::fixed(200, ValueType::Dword);
+
+        let expected = refine_values_with(&pairs, &target, read, None, None, &|| false, &|_, _| {});
+        assert_eq!(expected.len(), SIZE / 0x100);
+
+        let processed = Arc::new(AtomicUsize::new(0));
+        let found = Arc::new(AtomicUsize::new(0));
+        let progress = Mutex::new(Vec::new());
+        let refined = refine_values_stream_with(pairs.iter().cloned(), &target, 5, read, Some(&processed), Some(&found), &|| false, &|done, hits| {
+            progress.lock().unwrap().push((done, hits))
+        });
+        assert_eq!(refined, expected);
+
+        // 进度按全部批次累计
+        let progress = progress.into_inner().unwrap();
+        assert!(progress.windows(2).all(|w| w[0].0 <= w[1].0));
+        assert_eq!(progress.last(), Some(&(pairs.len(), expected.len())));
+    }
+}
@@ -0,0 +1,153 @@
+//! Refine undo tests
+//!
+//! 改善替换结果之前当前结果集移入撤销槽（磁盘部分改名为 `*.undo`），撤销时换回，
+//! 包括模式、标签和磁盘上的结果；就地细化复制一份。撤销槽被丢弃或管理器销毁时删除撤销文件。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::result_manager::{FuzzySearchResultItem, SearchResultManager, SearchResultMode};
+    use crate::search::{SearchResultItem, ValueType};
+    use crate::search::tests::result_fixture::{split_result_manager, MEMORY_ITEMS};
+    use crate::search::tests::temp_dir::TempDir;
+    use std::path::Path;
+
+    const BASE: u64 = 0x7D00000000;
+
+    fn undo_files(dir: &Path) -> usize {
+        std::fs::read_dir(dir)
+            .unwrap()
+            .filter(|entry| entry.as_ref().unwrap().file_name().to_string_lossy().ends_with(".undo"))
+            .count()
+    }
+
+    fn addresses(mgr: &SearchResultManager) -> Vec<u64> {
+        mgr.records(0, mgr.total_count()).unwrap().iter().map(|item| item.address).collect()
+    }
+
+    fn fuzzy_values(mgr: &SearchResultManager) -> Vec<i64> {
+        mgr.get_all_fuzzy_results().unwrap().iter().map(|item| item.as_i64()).collect()
+    }
+
+    fn exact_items(range: std::ops::Range<u64>) -> Vec<SearchResultItem> {
+        range.map(|i| SearchResultItem::new_exact(BASE + i * 4, ValueType::Dword)).collect()
+    }
+
+    #[test]
+    fn test_undo_restores_exact_results_from_memory_and_disk() {
+        let dir = TempDir::new("refine_undo_exact");
+        let mut mgr = split_result_manager(&dir, SearchResultMode::Exact, MEMORY_ITEMS);
+        mgr.add_results_batch(exact_items(0..10)).unwrap();
+        mgr.set_label(mgr.handle_at(7).unwrap(), "HP".to_string()).unwrap();
+        let before = addresses(&mgr);
+        assert!(!mgr.can_undo());
+
+        // 改善：移入撤销槽后写入幸存的结果，幸存者多到需要新的磁盘文件
+        mgr.stash_for_undo().unwrap();
+        assert_eq!(mgr.total_count(), 0);
+        assert_eq!(undo_files(&dir), 1);
+        mgr.clear().unwrap();
+        mgr.add_results_batch(exact_items(20..26)).unwrap();
+        assert_eq!(mgr.label(BASE + 7 * 4, ValueType::Dword), None);
+        // 改善之后的删除一起撤销
+        mgr.remove_by_handles(vec![mgr.handle_at(0).unwrap()]).unwrap();
+        assert!(mgr.can_undo());
+
+        assert_eq!(mgr.undo_refine().unwrap(), 10);
+        assert_eq!(addresses(&mgr), before);
+        assert_eq!(mgr.label(BASE + 7 * 4, ValueType::Dword), Some("HP"));
+        assert!(mgr.verify_integrity().store.ok);
+        assert_eq!(undo_files(&dir), 0);
+        assert!(!mgr.can_undo());
+        assert!(mgr.undo_refine().is_err());
+
+        // 恢复后的存储可以继续写入
+        mgr.add_results_batch(exact_items(30..32)).unwrap();
+        assert_eq!(mgr.total_count(), 12);
+
+        drop(mgr);
+    }
+
+    #[test]
+    fn test_undo_restores_mode_changed_by_refine() {
+        let dir = TempDir::new("refine_undo_mode");
+        let mut mgr = split_result_manager(&dir, SearchResultMode::Fuzzy, MEMORY_ITEMS);
+        let fuzzy: Vec<_> = (0..8).map(|i| FuzzySearchResultItem::from_bytes(BASE + i * 4, &(i as i32 * 10).to_le_bytes(), ValueType::Dword)).collect();
+        mgr.add_fuzzy_results_batch(fuzzy).unwrap();
+
+        // 精确改善把模糊结果换成精确结果
+        mgr.stash_for_undo().unwrap();
+        mgr.clear().unwrap();
+        mgr.set_mode(SearchResultMode::Exact).unwrap();
+        mgr.add_results_batch(exact_items(2..3)).unwrap();
+
+        assert_eq!(mgr.undo_refine().unwrap(), 8);
+        assert_eq!(mgr.get_mode(), SearchResultMode::Fuzzy);
+        assert_eq!(fuzzy_values(&mgr), (0..8).map(|i| i * 10).collect::<Vec<i64>>());
+        assert!(mgr.verify_integrity().store.ok);
+
+        drop(mgr);
+    }
+
+    #[test]
+    fn test_undo_in_place_refine_uses_journal() {
+        let dir = TempDir::new("refine_undo_in_place");
+        let mut mgr = split_result_manager(&dir, SearchResultMode::Fuzzy, MEMORY_ITEMS);
+        let fuzzy: Vec<_> = (0..8).map(|i| FuzzySearchResultItem::from_bytes(BASE + i * 4, &(i as i32).to_le_bytes(), ValueType::Dword)).collect();
+        mgr.add_fuzzy_results_batch(fuzzy.clone()).unwrap();
+
+        // 就地细化：旧值先记入日志，之后改写值并压缩，内存和磁盘上的结果都被改写
+        let mut journal = mgr.begin_refine_journal().unwrap();
+        journal.record(&mgr.get_all_fuzzy_results().unwrap()).unwrap();
+        let updates: Vec<_> = [1usize, 6].iter().map(|&i| (i, fuzzy[i].with_new_value(&100i32.to_le_bytes()))).collect();
+        mgr.update_fuzzy_results(&updates).unwrap();
+        mgr.commit_refine_journal(journal).unwrap();
+        assert_eq!(mgr.total_count(), 8);
+        mgr.remove_results_batch(vec![0, 2, 3, 4, 5, 7]).unwrap();
+        assert_eq!(fuzzy_values(&mgr), vec![100, 100]);
+
+        assert_eq!(mgr.undo_refine().unwrap(), 8);
+        assert_eq!(fuzzy_values(&mgr), (0..8).collect::<Vec<i64>>());
+        assert!(mgr.verify_integrity().store.ok);
+
+        // 精确结果没有就地细化
+        mgr.set_mode(SearchResultMode::Exact).unwrap();
+        assert!(mgr.begin_refine_journal().is_err());
+
+        drop(mgr);
+    }
+
+    #[test]
+    fn test_discarded_or_dropped_undo_slot_removes_files() {
+        let dir = TempDir::new("refine_undo_discard");
+        let mut mgr = split_result_manager(&dir, SearchResultMode::Exact, MEMORY_ITEMS);
+        mgr.add_results_batch(exact_items(0..10)).unwrap();
+
+        mgr.stash_for_undo().unwrap();
+        assert_eq!(undo_files(&dir), 1);
+        mgr.discard_undo();
+        assert_eq!(undo_files(&dir), 0);
+        assert!(!mgr.can_undo());
+
+        // 第二次改善替换撤销槽，只保留一层
+        mgr.add_results_batch(exact_items(0..10)).unwrap();
+        mgr.stash_for_undo().unwrap();
+        mgr.add_results_batch(exact_items(0..6)).unwrap();
+        mgr.stash_for_undo().unwrap();
+        assert_eq!(undo_files(&dir), 1);
+        assert_eq!(mgr.undo_refine().unwrap(), 6);
+
+        // 管理器销毁时撤销文件和结果文件一起删除
+        mgr.stash_for_undo().unwrap();
+        mgr.add_results_batch(exact_items(0..8)).unwrap();
+        drop(mgr);
+        assert_eq!(undo_files(&dir), 0);
+
+        // 上次进程遗留的撤销文件在启动时删除
+        std::fs::write(dir.join("mamu_fuzzy_results.bin.undo"), [0u8; 22]).unwrap();
+        let mgr = split_result_manager(&dir, SearchResultMode::Exact, MEMORY_ITEMS);
+        assert_eq!(undo_files(&dir), 0);
+        assert!(!mgr.can_undo());
+
+        drop(mgr);
+    }
+}
@@ -0,0 +1,149 @@
+//! Region group tests
+//!
+//! 结果分布在几个假区域里（模块、匿名映射、堆，以及映射中已经不存在的地址），
+//! 验证分组边界、计数、分组内分页，以及结果集变化或区域映射刷新后缓存失效。
+
+#[cfg(test)]
+mod tests {
+    use crate::core::region_map::{MappedRegion, RegionMap};
+    use crate::search::engine::region_groups::UNMAPPED_GROUP_NAME;
+    use crate::search::result_manager::SearchResultMode;
+    use crate::search::{SearchEngineManager, SearchResultItem, ValueType};
+    use crate::search::tests::temp_dir::TempDir;
+    use std::sync::Arc;
+
+    const LIB: u64 = 0x7A00000000;
+    const ANON: u64 = 0x7A00010000;
+    const HEAP: u64 = 0x7B00000000;
+    const GAP: u64 = 0x7A80000000;
+    const TAIL: u64 = 0x7C00000000;
+
+    fn region(start: u64, end: u64, name: &str) -> MappedRegion {
+        MappedRegion {
+            start,
+            end,
+            type_: 0b011,
+            name: name.to_string(),
+        }
+    }
+
+    fn full_map() -> Arc<RegionMap> {
+        Arc::new(RegionMap::new(
+            1000,
+            vec![
+                region(HEAP, HEAP + 0x1000, "[heap]"),
+                region(LIB, LIB + 0x10000, "/data/app/com.example/lib/arm64/libunity.so"),
+                region(ANON, ANON + 0x10000, ""),
+            ],
+        ))
+    }
+
+    /// 3 条在模块，2 条在匿名映射，1 条在空隙，4 条在堆，2 条在最后一个区域之后
+    fn addresses() -> Vec<u64> {
+        let mut addrs = vec![LIB, LIB + 0x10, LIB + 0xFFFC, ANON + 0x100, ANON + 0x200, GAP];
+        addrs.extend((0..4).map(|i| HEAP + i * 8));
+        addrs.extend([TAIL, TAIL + 4]);
+        addrs
+    }
+
+    fn setup(name: &str) -> (SearchEngineManager, TempDir) {
+        let dir = TempDir::new(name);
+        let mut manager = SearchEngineManager::new();
+        manager.init(0, dir.to_string_lossy().into_owned(), 0).unwrap();
+        manager.set_result_mode(SearchResultMode::Exact).unwrap();
+        let results = addresses()
+            .into_iter()
+            .map(|addr| SearchResultItem::new_exact(addr, ValueType::Dword))
+            .collect();
+        manager.add_results_batch(results).unwrap();
+        (manager, dir)
+    }
+
+    fn page_addresses(manager: &SearchEngineManager, map: &Arc<RegionMap>, group: usize, start: usize, size: usize) -> Vec<(usize, u64)> {
+        manager
+            .results_for_group_with(map, group, start, size)
+            .unwrap()
+            .into_iter()
+            .map(|(index, item)| match item {
+                SearchResultItem::Exact(exact) => (index, exact.address),
+                SearchResultItem::Fuzzy(fuzzy) => (index, fuzzy.address),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_group_boundaries_and_counts() {
+        let (manager, _dir) = setup("region_groups_bounds");
+        let groups = manager.region_groups_with(&full_map()).unwrap();
+
+        let summary: Vec<(&str, usize, usize, bool)> = groups.iter().map(|g| (g.name.as_str(), g.count, g.first_index, g.mapped)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("libunity.so", 3, 0, true),
+                ("anonymous:7a00010000", 2, 3, true),
+                (UNMAPPED_GROUP_NAME, 1, 5, false),
+                ("[heap]", 4, 6, true),
+                (UNMAPPED_GROUP_NAME, 2, 10, false),
+            ]
+        );
+        assert_eq!((groups[0].start, groups[0].end), (LIB, LIB + 0x10000));
+        // 不在映射中的结果以所在空隙为边界
+        assert_eq!((groups[2].start, groups[2].end), (ANON + 0x10000, HEAP));
+        assert_eq!((groups[4].start, groups[4].end), (HEAP + 0x1000, u64::MAX));
+        assert_eq!(groups.iter().map(|g| g.count).sum::<usize>(), manager.get_total_count().unwrap());
+    }
+
+    #[test]
+    fn test_paging_within_group() {
+        let (manager, _dir) = setup("region_groups_paging");
+        let map = full_map();
+
+        assert_eq!(page_addresses(&manager, &map, 3, 1, 2), vec![(7, HEAP + 8), (8, HEAP + 16)]);
+        // 页尾截断在分组末尾，不会读到下一个分组
+        assert_eq!(page_addresses(&manager, &map, 3, 3, 10), vec![(9, HEAP + 24)]);
+        assert!(page_addresses(&manager, &map, 3, 4, 10).is_empty());
+        assert_eq!(page_addresses(&manager, &map, 2, 0, 10), vec![(5, GAP)]);
+        assert!(manager.results_for_group_with(&map, 5, 0, 10).is_err());
+    }
+
+    #[test]
+    fn test_cache_invalidation() {
+        let (mut manager, _dir) = setup("region_groups_cache");
+        let map = full_map();
+
+        let first = manager.region_groups_with(&map).unwrap();
+        assert!(Arc::ptr_eq(&first, &manager.region_groups_with(&map).unwrap()));
+
+        // 改善搜索：清空后写入剩下的结果
+        manager.clear_results().unwrap();
+        let refined = [LIB + 0x10, HEAP, HEAP + 8].map(|addr| SearchResultItem::new_exact(addr, ValueType::Dword));
+        manager.add_results_batch(refined.into()).unwrap();
+        let groups = manager.region_groups_with(&map).unwrap();
+        assert!(!Arc::ptr_eq(&first, &groups));
+        let summary: Vec<(&str, usize, usize)> = groups.iter().map(|g| (g.name.as_str(), g.count, g.first_index)).collect();
+        assert_eq!(summary, vec![("libunity.so", 1, 0), ("[heap]", 2, 1)]);
+        assert_eq!(page_addresses(&manager, &map, 1, 0, 10), vec![(1, HEAP), (2, HEAP + 8)]);
+
+        // 删除结果同样失效
+        manager.keep_only_results(vec![1, 2]).unwrap();
+        let groups = manager.region_groups_with(&map).unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!((groups[0].count, groups[0].first_index), (2, 0));
+
+        // 区域映射刷新后（模块被卸载）按新映射重新分组
+        let unloaded = Arc::new(RegionMap::new(1000, vec![region(HEAP, HEAP + 0x1000, "[heap]")]));
+        manager.clear_results().unwrap();
+        manager
+            .add_results_batch(vec![
+                SearchResultItem::new_exact(LIB, ValueType::Dword),
+                SearchResultItem::new_exact(HEAP, ValueType::Dword),
+            ])
+            .unwrap();
+        let before = manager.region_groups_with(&map).unwrap();
+        assert_eq!(before[0].name, "libunity.so");
+        let after = manager.region_groups_with(&unloaded).unwrap();
+        assert_eq!((after[0].name.as_str(), after[0].mapped), (UNMAPPED_GROUP_NAME, false));
+        assert_eq!((after[0].start, after[0].end), (0, HEAP));
+    }
+}
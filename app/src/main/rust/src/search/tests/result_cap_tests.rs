@@ -0,0 +1,94 @@
+//! Result cap tests
+//!
+//! 首次扫描设置了结果数上限时，区域扫描每块之后累计结果数，下一个检查点发现达到上限就停下来：
+//! 一个巨大的区域最多多扫一块，停止标志置位而取消标志不置位，已经找到的结果照常返回。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::cancel::CancelSource;
+    use crate::search::engine::group_search::scan_region_group_chunks;
+    use crate::search::engine::read_stats::ReadStats;
+    use crate::search::tests::mock_memory::MockMemory;
+    use crate::search::{parse_search_query, SearchQuery, ValueType};
+    use crate::wuwa::PageStatusBitmap;
+    use tokio_util::sync::CancellationToken;
+
+    const BASE: u64 = 0x7C00000000;
+    const SIZE: usize = 8 * 1024 * 1024;
+    const CHUNK: usize = 64 * 1024;
+    /// "7;7::8" 在全是 7 的内存里每个锚点报告自己和下一个值，一块最多这么多个结果
+    const MAX_PER_CHUNK: u64 = (CHUNK / 4 * 2) as u64;
+
+    /// 每个 Dword 都是 7 的区域
+    fn filled_memory() -> MockMemory {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, SIZE).unwrap();
+        let pattern: Vec<u8> = 7u32.to_le_bytes().iter().copied().cycle().take(SIZE).collect();
+        mem.mem_write(BASE, &pattern).unwrap();
+        mem
+    }
+
+    fn query() -> SearchQuery {
+        parse_search_query("7;7::8", ValueType::Dword).unwrap()
+    }
+
+    /// 与首次扫描相同的检查：取消或结果数达到上限
+    fn scan_with_cap(mem: &MockMemory, max_results: usize) -> (Vec<u64>, ReadStats, CancelSource) {
+        let stats = ReadStats::new();
+        let cancel = CancelSource::new(CancellationToken::new());
+        let check_scan_stopped = || cancel.poll_scan(stats.matches(), max_results);
+        let read = |addr: u64, buf: &mut [u8], page_status: &mut PageStatusBitmap| mem.mem_read_with_status(addr, buf, page_status);
+        let results = scan_region_group_chunks(&query(), BASE, BASE + SIZE as u64, CHUNK, false, read, &check_scan_stopped, &stats);
+        (results.iter().map(|pair| pair.addr).collect(), stats, cancel)
+    }
+
+    #[test]
+    fn test_cap_stops_a_huge_region_within_one_chunk() {
+        const CAP: usize = 100_000;
+        let mem = filled_memory();
+        let (results, stats, cancel) = scan_with_cap(&mem, CAP);
+
+        assert!(cancel.is_stopped());
+        assert!(!cancel.is_cancelled(), "reaching the cap is not a cancellation");
+        assert!(stats.matches() >= CAP as u64);
+        assert!(stats.matches() < CAP as u64 + MAX_PER_CHUNK, "scanned past the cap: {} matches", stats.matches());
+
+        // 只读了上限需要的几块，而不是整个区域
+        let (readable, _) = stats.snapshot();
+        assert!(readable < (SIZE / 4) as u64, "read {} bytes", readable);
+
+        // 停止前找到的结果照常返回，都在读过的范围内
+        assert!(!results.is_empty());
+        assert!(results.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(results.iter().all(|&addr| addr < BASE + readable));
+    }
+
+    #[test]
+    fn test_zero_cap_scans_everything() {
+        let mem = filled_memory();
+        let (results, stats, cancel) = scan_with_cap(&mem, 0);
+
+        assert!(!cancel.is_stopped());
+        assert_eq!(stats.snapshot(), (SIZE as u64, 0));
+        assert_eq!(results.len(), SIZE / 4);
+        assert!(stats.matches() >= results.len() as u64);
+    }
+
+    #[test]
+    fn test_cancel_is_not_a_stop() {
+        let token = CancellationToken::new();
+        let cancel = CancelSource::new(token.clone());
+        assert!(!cancel.poll_scan(10, 100));
+        assert!(!cancel.is_stopped());
+
+        token.cancel();
+        assert!(cancel.poll_scan(10, 100));
+        assert!(!cancel.is_stopped());
+
+        // 达到上限后停止标志保持置位
+        let cancel = CancelSource::new(CancellationToken::new());
+        assert!(cancel.poll_scan(100, 100));
+        assert!(cancel.poll_scan(0, 100));
+        assert!(cancel.is_stopped() && !cancel.is_cancelled());
+    }
+}
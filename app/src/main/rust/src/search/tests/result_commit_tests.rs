@@ -0,0 +1,214 @@
+//! Result committer tests
+//!
+//! 首次扫描的结果按区域经有界通道交给提交线程：乱序到达的区域按编号顺序写入，结果文件整体有序；
+//! 超过保留上限后开始写入，等待的结果太多时不再等待前面的区域；取消后提交线程退出，扫描线程的发送失败返回。
+//! 通过全局引擎取消时，已经写入结果管理器的结果被丢弃。
+
+#[cfg(test)]
+mod tests {
+    use crate::core::memory_pressure::default_limits;
+    use crate::core::{DRIVER_MANAGER, MEMORY_GUARD};
+    use crate::search::engine::result_commit::{run_committer, CommitOutcome, RegionBatch, ResultCommitter, COMMIT_BATCH};
+    use crate::search::engine::{ValuePair, SEARCH_ENGINE_MANAGER, SearchStatus};
+    use crate::search::tests::engine_fixture::EngineFixture;
+    use crate::search::tests::mock_memory::{MockMemory, ReadGate};
+    use crate::search::{parse_search_query, ValueType};
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::{mpsc, Arc};
+    use std::time::{Duration, Instant};
+
+    const BASE: u64 = 0x7500000000;
+    const NO_LIMIT: usize = usize::MAX;
+
+    /// 区域 i 的 n 个结果
+    fn region(i: usize, n: u64) -> RegionBatch {
+        RegionBatch::new(i, (0..n).map(|k| ValuePair::new(BASE + i as u64 * 0x10000 + k * 4, ValueType::Dword)).collect())
+    }
+
+    fn addresses(batch: &[ValuePair]) -> Vec<u64> {
+        batch.iter().map(|pair| pair.addr).collect()
+    }
+
+    #[test]
+    fn test_region_batch_sorts_and_dedups() {
+        let batch = RegionBatch::new(
+            0,
+            vec![
+                ValuePair::new(BASE + 8, ValueType::Float),
+                ValuePair::new(BASE + 8, ValueType::Dword),
+                ValuePair::new(BASE, ValueType::Dword),
+                ValuePair::new(BASE + 8, ValueType::Float),
+            ],
+        );
+        let pairs: Vec<(u64, ValueType)> = batch.results.iter().map(|pair| (pair.addr - BASE, pair.value_type)).collect();
+        assert_eq!(pairs, vec![(0, ValueType::Dword), (8, ValueType::Dword), (8, ValueType::Float)]);
+    }
+
+    #[test]
+    fn test_out_of_order_regions_commit_in_region_order() {
+        let mut written = Vec::new();
+        let mut committer = ResultCommitter::new(0, NO_LIMIT, |batch: Vec<ValuePair>| {
+            written.extend(addresses(&batch));
+            Ok(())
+        });
+
+        for batch in [region(2, 3), region(0, 2), RegionBatch::empty(3), region(1, 4)] {
+            committer.accept(batch).unwrap();
+        }
+        assert!(!committer.is_retaining());
+        assert_eq!(committer.found(), 9);
+        assert_eq!(committer.finish().unwrap(), CommitOutcome::Committed { count: 9, runs: 1 });
+
+        let mut expected = written.clone();
+        expected.sort_unstable();
+        assert_eq!(written, expected);
+        assert_eq!(written.len(), 9);
+    }
+
+    #[test]
+    fn test_retain_limit_switches_to_committing() {
+        let mut written = Vec::new();
+        let mut committer = ResultCommitter::new(5, NO_LIMIT, |batch: Vec<ValuePair>| {
+            written.push(addresses(&batch));
+            Ok(())
+        });
+
+        committer.accept(region(1, 3)).unwrap();
+        assert!(committer.is_retaining());
+        // 超过上限：区域 0 还没到，区域 1、2 继续等待
+        committer.accept(region(2, 3)).unwrap();
+        assert!(!committer.is_retaining());
+        assert_eq!(committer.found(), 6);
+        committer.accept(region(0, 1)).unwrap();
+
+        assert_eq!(committer.finish().unwrap(), CommitOutcome::Committed { count: 7, runs: 1 });
+        let written = written.concat();
+        assert!(written.is_sorted());
+        assert_eq!(written.len(), 7);
+
+        // 没有超过上限时结果留在内存里，按区域顺序拼接
+        let mut committer = ResultCommitter::new(NO_LIMIT, NO_LIMIT, |_: Vec<ValuePair>| unreachable!());
+        committer.accept(region(1, 2)).unwrap();
+        committer.accept(region(0, 2)).unwrap();
+        let CommitOutcome::Retained(retained) = committer.finish().unwrap() else {
+            panic!("results should be retained");
+        };
+        assert!(addresses(&retained).is_sorted());
+    }
+
+    #[test]
+    fn test_reorder_limit_commits_later_regions_early() {
+        let mut written = Vec::new();
+        let mut committer = ResultCommitter::new(0, 4, |batch: Vec<ValuePair>| {
+            written.extend(addresses(&batch));
+            Ok(())
+        });
+
+        // 区域 0 迟迟不到，等待的结果超过 4 个后区域 1 先写入
+        committer.accept(region(1, 3)).unwrap();
+        committer.accept(region(2, 3)).unwrap();
+        // 区域 0 到达后越过已经写入的区域 1，接着写入区域 2、3
+        committer.accept(region(0, 2)).unwrap();
+        committer.accept(region(3, 1)).unwrap();
+
+        // 写入顺序是 1 | 0 2 3，分成两段
+        assert_eq!(committer.finish().unwrap(), CommitOutcome::Committed { count: 9, runs: 2 });
+        assert_eq!(written.len(), 9);
+        let mut sorted = written.clone();
+        sorted.sort_unstable();
+        sorted.dedup();
+        assert_eq!(sorted.len(), 9);
+    }
+
+    #[test]
+    fn test_committer_thread_stops_on_cancel() {
+        let cancelled = AtomicBool::new(false);
+        let found = AtomicUsize::new(0);
+        let check_cancelled = || cancelled.load(Ordering::Relaxed);
+        let (sender, receiver) = mpsc::sync_channel::<RegionBatch>(1);
+
+        std::thread::scope(|scope| {
+            let committer = scope.spawn(|| {
+                let committer = ResultCommitter::new(0, NO_LIMIT, |_: Vec<ValuePair>| Ok(()));
+                run_committer(committer, receiver, || false, &check_cancelled, |n| found.store(n, Ordering::Relaxed))
+            });
+
+            sender.send(region(0, 3)).unwrap();
+            while found.load(Ordering::Relaxed) < 3 {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            cancelled.store(true, Ordering::Relaxed);
+            assert!(committer.join().unwrap().is_none());
+
+            // 提交线程退出后接收端已经丢弃，发送立即失败，不会阻塞在满的通道上
+            assert!(sender.send(region(1, 1)).is_err());
+        });
+    }
+
+    #[test]
+    fn test_committer_thread_finishes_when_senders_drop() {
+        let (sender, receiver) = mpsc::sync_channel::<RegionBatch>(1);
+        let outcome = std::thread::scope(|scope| {
+            let committer = scope.spawn(|| {
+                let committer = ResultCommitter::new(NO_LIMIT, NO_LIMIT, |_: Vec<ValuePair>| Ok(()));
+                // 压力升高后不再保留
+                run_committer(committer, receiver, || true, &|| false, |_| {})
+            });
+            for i in (0..4).rev() {
+                sender.send(region(i, 2)).unwrap();
+            }
+            drop(sender);
+            committer.join().unwrap()
+        });
+        assert_eq!(outcome.unwrap().unwrap(), CommitOutcome::Committed { count: 8, runs: 1 });
+    }
+
+    /// 测试期间让内存压力处于 Soft，扫描一开始就按区域提交；结束时恢复默认限制
+    struct SoftPressure;
+
+    impl SoftPressure {
+        fn new() -> Self {
+            MEMORY_GUARD.set_limits(1, u64::MAX).unwrap();
+            MEMORY_GUARD.sample();
+            Self
+        }
+    }
+
+    impl Drop for SoftPressure {
+        fn drop(&mut self) {
+            let (soft, hard) = default_limits();
+            MEMORY_GUARD.set_limits(soft, hard).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_cancel_after_commit_discards_committed_results() {
+        // 第一个区域的结果超过一批，第二个区域的读取被挡住，直到第一批已经写入
+        let first_len = (COMMIT_BATCH + 1024) * 4;
+        let second = BASE + 0x1000000;
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, first_len).unwrap();
+        mem.mem_write(BASE, &7u32.to_le_bytes().repeat(first_len / 4)).unwrap();
+        mem.malloc(second, 0x10000).unwrap();
+        let fixture = EngineFixture::new("commit_cancel", mem);
+        let _pressure = SoftPressure::new();
+        let gate = Arc::new(ReadGate::new(second));
+        DRIVER_MANAGER.write().unwrap().set_test_read_gate(Some(Arc::clone(&gate)));
+
+        let query = parse_search_query("7", ValueType::Dword).unwrap();
+        let regions = vec![(BASE, BASE + first_len as u64), (second, second + 0x10000)];
+        SEARCH_ENGINE_MANAGER.write().unwrap().start_search_async(query, regions, false, false, false).unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while SEARCH_ENGINE_MANAGER.read().unwrap().get_total_count().unwrap() == 0 && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        let committed = SEARCH_ENGINE_MANAGER.read().unwrap().get_total_count().unwrap();
+        fixture.request_cancel();
+        gate.open();
+
+        assert!(committed >= COMMIT_BATCH, "no batch was committed before the cancel");
+        assert_eq!(fixture.wait_idle(), SearchStatus::Cancelled);
+        assert_eq!(SEARCH_ENGINE_MANAGER.read().unwrap().get_total_count().unwrap(), 0);
+    }
+}
@@ -0,0 +1,190 @@
+//! Result file export / import tests
+//!
+//! 精确和模糊结果导出后重新载入，轮次、Auto 解释和特征码长度保留；导入时切换模式。
+//! 头部损坏、截断、记录损坏的文件返回错误，不会 panic。
+//! 导入后按模块的新基址平移结果地址。
+
+#[cfg(test)]
+mod tests {
+    use crate::core::address_rebase::AddressRebase;
+    use crate::search::result_manager::{FuzzySearchResultItem, SearchResultManager, SearchResultMode};
+    use crate::search::{SearchEngineManager, SearchResultItem, ValueType};
+    use crate::search::tests::result_fixture::{split_result_manager, MEMORY_ITEMS};
+    use crate::search::tests::temp_dir::TempDir;
+    use std::path::Path;
+
+    const BASE: u64 = 0x7800000000;
+
+    fn exact_items(mgr: &SearchResultManager) -> Vec<(u64, ValueType, u8)> {
+        mgr.get_all_exact_results()
+            .unwrap()
+            .iter()
+            .map(|item| (item.address, item.typ, item.pass))
+            .collect()
+    }
+
+    fn fuzzy_items(mgr: &SearchResultManager) -> Vec<(u64, [u8; 8], ValueType, u8, u8)> {
+        mgr.get_all_fuzzy_results()
+            .unwrap()
+            .iter()
+            .map(|item| (item.address, item.value, item.value_type, item.pass, item.auto_types))
+            .collect()
+    }
+
+    fn exact_manager(dir: &Path) -> SearchResultManager {
+        let mut mgr = split_result_manager(&dir.join("exact"), SearchResultMode::Exact, MEMORY_ITEMS);
+        let results = (0..10)
+            .map(|i| SearchResultItem::new_exact(BASE + i * 4, ValueType::Dword).with_pass((i % 3) as u8))
+            .collect();
+        mgr.add_results_batch(results).unwrap();
+        mgr
+    }
+
+    #[test]
+    fn test_round_trip_switches_mode() {
+        let dir = TempDir::new("result_file_round_trip");
+        for sub in ["exact", "fuzzy"] {
+            std::fs::create_dir_all(dir.join(sub)).unwrap();
+        }
+
+        let mut fuzzy = split_result_manager(&dir.join("fuzzy"), SearchResultMode::Fuzzy, MEMORY_ITEMS);
+        let items = (0..10u64)
+            .map(|i| {
+                let item = FuzzySearchResultItem::from_bytes(BASE + i * 8, &(i * 0x1_0000_0001).to_le_bytes(), ValueType::Auto);
+                item.with_pass(i as u8)
+                    .with_auto_types(FuzzySearchResultItem::AUTO_DWORD | FuzzySearchResultItem::AUTO_QWORD)
+            })
+            .collect();
+        fuzzy.add_fuzzy_results_batch(items).unwrap();
+        let fuzzy_path = dir.join("fuzzy.mres");
+        assert_eq!(fuzzy.export_to_file(&fuzzy_path, None).unwrap(), 10);
+
+        let mut exact = exact_manager(&dir);
+        let exact_path = dir.join("exact.mres");
+        assert_eq!(exact.export_to_file(&exact_path, Some(12)).unwrap(), 10);
+        let exported_exact = exact_items(&exact);
+
+        // 精确模式下导入模糊文件：切换到模糊模式，内存和磁盘两部分都按原顺序恢复
+        let header = exact.import_from_file(&fuzzy_path).unwrap();
+        assert_eq!((header.mode, header.count, header.pattern_len), (SearchResultMode::Fuzzy, 10, None));
+        assert_eq!(exact.get_mode(), SearchResultMode::Fuzzy);
+        assert_eq!(fuzzy_items(&exact), fuzzy_items(&fuzzy));
+        assert_eq!(exact.current_pass(), 9);
+
+        let header = fuzzy.import_from_file(&exact_path).unwrap();
+        assert_eq!((header.mode, header.count, header.pattern_len), (SearchResultMode::Exact, 10, Some(12)));
+        assert_eq!(fuzzy.get_mode(), SearchResultMode::Exact);
+        assert_eq!(exact_items(&fuzzy), exported_exact);
+        assert!(!dir.join("exact.part").exists());
+
+        drop((exact, fuzzy));
+    }
+
+    #[test]
+    fn test_corrupt_files_are_rejected() {
+        let dir = TempDir::new("result_file_corrupt");
+        std::fs::create_dir_all(dir.join("exact")).unwrap();
+        let mut mgr = exact_manager(&dir);
+        let path = dir.join("results.mres");
+        mgr.export_to_file(&path, None).unwrap();
+        let good = std::fs::read(&path).unwrap();
+        let before = exact_items(&mgr);
+
+        let corrupt = |name: &str, bytes: &[u8]| {
+            let path = dir.join(name);
+            std::fs::write(&path, bytes).unwrap();
+            path
+        };
+        let mut bad_magic = good.clone();
+        bad_magic[0] = b'X';
+        let mut bad_version = good.clone();
+        bad_version[8] = 99;
+        let mut bad_mode = good.clone();
+        bad_mode[9] = 7;
+        let mut extra = good.clone();
+        extra.push(0);
+
+        // 头部或长度不对：报错且不动当前结果
+        for path in [
+            corrupt("magic", &bad_magic),
+            corrupt("version", &bad_version),
+            corrupt("mode", &bad_mode),
+            corrupt("truncated", &good[..good.len() - 1]),
+            corrupt("header_only", &good[..10]),
+            corrupt("extra", &extra),
+            corrupt("empty", &[]),
+            dir.join("missing"),
+        ] {
+            assert!(mgr.import_from_file(&path).is_err(), "{:?} should be rejected", path);
+            assert_eq!(exact_items(&mgr), before);
+        }
+
+        // 记录中的类型损坏：长度正确，读到时才发现，已载入的部分被清空
+        let mut bad_type = good.clone();
+        let last_record = good.len() - 10;
+        bad_type[last_record + 8] = 0xEE;
+        assert!(mgr.import_from_file(&corrupt("type", &bad_type)).is_err());
+        assert_eq!(mgr.total_count(), 0);
+
+        drop(mgr);
+    }
+
+    #[test]
+    fn test_engine_export_import() {
+        let dir = TempDir::new("result_file_engine");
+        let mut manager = SearchEngineManager::new();
+        manager.init(0, dir.to_string_lossy().into_owned(), 0).unwrap();
+        manager.set_result_mode(SearchResultMode::Exact).unwrap();
+        let results = (0..3).map(|i| SearchResultItem::new_exact(BASE + i * 4, ValueType::Float)).collect();
+        manager.add_results_batch(results).unwrap();
+
+        let path = dir.join("session.mres");
+        assert_eq!(manager.export_results(path.clone()).unwrap(), 3);
+        manager.set_result_mode(SearchResultMode::Fuzzy).unwrap();
+        assert_eq!(manager.get_total_count().unwrap(), 0);
+
+        assert_eq!(manager.import_results(path).unwrap(), 3);
+        assert_eq!(manager.get_current_mode().unwrap(), SearchResultMode::Exact);
+        assert_eq!(manager.get_total_count().unwrap(), 3);
+        assert_eq!(manager.get_current_pattern_len(), None);
+        assert!(manager.import_results(dir.join("missing")).is_err());
+
+        drop(manager);
+    }
+
+    #[test]
+    fn test_rebase_imported_results() {
+        let dir = TempDir::new("result_file_rebase");
+        for sub in ["exact", "fuzzy"] {
+            std::fs::create_dir_all(dir.join(sub)).unwrap();
+        }
+        let path = dir.join("exact.mres");
+        exact_manager(&dir).export_to_file(&path, None).unwrap();
+
+        // 前 4 条在旧模块 [BASE, BASE + 0x10) 内，模块重新加载到更高的地址后排到最后
+        let mut mgr = split_result_manager(&dir.join("exact"), SearchResultMode::Exact, MEMORY_ITEMS);
+        mgr.import_from_file(&path).unwrap();
+        let new_base = BASE + 0x1000;
+        assert_eq!(mgr.rebase(&AddressRebase::new(BASE, new_base, 0x10)).unwrap(), 4);
+
+        let mut expected: Vec<_> = (4..10).map(|i| (BASE + i * 4, ValueType::Dword, (i % 3) as u8)).collect();
+        expected.extend((0..4).map(|i| (new_base + i * 4, ValueType::Dword, (i % 3) as u8)));
+        assert_eq!(exact_items(&mgr), expected);
+        assert_eq!(mgr.rebase(&AddressRebase::new(0x1000, 0x2000, 0x100)).unwrap(), 0);
+
+        let mut fuzzy = split_result_manager(&dir.join("fuzzy"), SearchResultMode::Fuzzy, MEMORY_ITEMS);
+        let items = (0..8u64)
+            .map(|i| FuzzySearchResultItem::from_bytes(BASE + i * 4, &(i as u32).to_le_bytes(), ValueType::Dword).with_pass(1))
+            .collect();
+        fuzzy.add_fuzzy_results_batch(items).unwrap();
+        assert_eq!(fuzzy.rebase(&AddressRebase::new(BASE + 0x10, BASE - 0x100, 0x100)).unwrap(), 4);
+        let rebased = fuzzy_items(&fuzzy);
+        assert_eq!(rebased.len(), 8);
+        assert_eq!(rebased[0].0, BASE - 0x100);
+        assert_eq!(rebased[0].1[0], 4);
+        assert!(rebased.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert!(rebased.iter().all(|item| item.3 == 1));
+
+        drop((mgr, fuzzy));
+    }
+}
@@ -0,0 +1,26 @@
+//! Result manager fixture
+//!
+//! 结果跨越内存缓冲区和磁盘文件的测试用。内存缓冲区按条数计算，精确和模糊结果的记录大小不同，
+//! 按模式换算成字节数，使前 N 条结果留在内存缓冲区，其余写入磁盘。
+
+use crate::search::result_manager::{ExactSearchResultItem, FuzzySearchResultItem, SearchResultManager, SearchResultMode};
+use std::path::Path;
+
+/// 跨越内存和磁盘的测试默认留在内存缓冲区的结果数
+pub const MEMORY_ITEMS: usize = 4;
+
+/// `mode` 模式下正好容纳 `items` 条结果的内存缓冲区字节数
+pub(crate) fn memory_buffer_for(mode: SearchResultMode, items: usize) -> usize {
+    let item_size = match mode {
+        SearchResultMode::Exact => size_of::<ExactSearchResultItem>(),
+        SearchResultMode::Fuzzy => size_of::<FuzzySearchResultItem>(),
+    };
+    items * item_size
+}
+
+/// 在 `dir` 创建 `mode` 模式的结果管理器，前 `memory_items` 条结果在内存缓冲区，其余写入磁盘
+pub(crate) fn split_result_manager(dir: &Path, mode: SearchResultMode, memory_items: usize) -> SearchResultManager {
+    let mut mgr = SearchResultManager::new(memory_buffer_for(mode, memory_items), dir.to_path_buf());
+    mgr.set_mode(mode).unwrap();
+    mgr
+}
@@ -0,0 +1,85 @@
+//! Stable result handle tests
+//!
+//! UI 取到一页结果的句柄之后结果集发生变化（删除、就地改善），按句柄删除 / 保留仍然作用于原来的结果；
+//! 结果集被整体替换后旧句柄不再匹配任何结果。结果跨内存缓冲区和磁盘文件。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::result_manager::{FuzzySearchResultItem, SearchResultManager, SearchResultMode};
+    use crate::search::{SearchResultItem, ValueType};
+    use crate::search::tests::result_fixture::{split_result_manager, MEMORY_ITEMS};
+    use crate::search::tests::temp_dir::TempDir;
+
+    const BASE: u64 = 0x7900000000;
+
+    fn addresses(mgr: &SearchResultManager) -> Vec<u64> {
+        mgr.get_results(0, mgr.total_count())
+            .unwrap()
+            .iter()
+            .map(|item| match item {
+                SearchResultItem::Exact(exact) => exact.address,
+                SearchResultItem::Fuzzy(fuzzy) => fuzzy.address,
+            })
+            .collect()
+    }
+
+    fn page_handles(mgr: &SearchResultManager, start: usize, size: usize) -> Vec<u64> {
+        (start..start + size).map(|index| mgr.handle_at(index).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_handles_survive_position_shifts() {
+        let dir = TempDir::new("result_handles_shift");
+        let mut mgr = split_result_manager(&dir, SearchResultMode::Exact, MEMORY_ITEMS);
+        mgr.add_results_batch((0..12).map(|i| SearchResultItem::new_exact(BASE + i * 4, ValueType::Dword)).collect()).unwrap();
+
+        // UI 取了第 6..9 条并选中
+        let selected = page_handles(&mgr, 6, 3);
+        // 删除之前另一次操作删掉了前面的结果，位置整体前移
+        mgr.remove_results_batch(vec![0, 2, 5]).unwrap();
+        assert_eq!(mgr.remove_by_handles(selected).unwrap(), 3);
+
+        let expected: Vec<u64> = [1, 3, 4, 9, 10, 11].iter().map(|i| BASE + i * 4).collect();
+        assert_eq!(addresses(&mgr), expected);
+
+        // 已经删除的结果再次删除时忽略
+        let first = mgr.handle_at(0).unwrap();
+        assert_eq!(mgr.remove_by_handles(vec![first]).unwrap(), 1);
+        assert_eq!(mgr.remove_by_handles(vec![first]).unwrap(), 0);
+        assert_eq!(mgr.total_count(), 5);
+
+        // 之后新增的结果得到新的句柄
+        mgr.add_results_batch(vec![SearchResultItem::new_exact(BASE + 0x100, ValueType::Dword)]).unwrap();
+        let keep = vec![mgr.handle_at(1).unwrap(), mgr.handle_at(5).unwrap()];
+        assert!(!keep.contains(&first));
+        assert_eq!(mgr.keep_only_handles(keep).unwrap(), 2);
+        assert_eq!(addresses(&mgr), vec![BASE + 4 * 4, BASE + 0x100]);
+
+        drop(mgr);
+    }
+
+    #[test]
+    fn test_replaced_results_invalidate_handles() {
+        let dir = TempDir::new("result_handles_replace");
+        let mut mgr = split_result_manager(&dir, SearchResultMode::Fuzzy, MEMORY_ITEMS);
+        let items: Vec<FuzzySearchResultItem> =
+            (0..8u64).map(|i| FuzzySearchResultItem::from_bytes(BASE + i * 4, &(i as u32).to_le_bytes(), ValueType::Dword)).collect();
+        mgr.add_fuzzy_results_batch(items.clone()).unwrap();
+        let selected = page_handles(&mgr, 0, 4);
+
+        // 就地改善：更新值并压缩掉删除的项，幸存结果保留句柄
+        let updates: Vec<(usize, FuzzySearchResultItem)> = (0..8).map(|i| (i, items[i].with_new_value(&[9, 0, 0, 0]))).collect();
+        mgr.update_fuzzy_results(&updates).unwrap();
+        mgr.remove_results_batch(vec![1, 3]).unwrap();
+        assert_eq!(mgr.position_of_handle(selected[2]), Some(1));
+        assert_eq!(mgr.position_of_handle(selected[1]), None);
+
+        // 整体替换的改善搜索之后旧的选择不再匹配，保留操作不会清空结果
+        mgr.replace_all_fuzzy_results(items[4..].to_vec()).unwrap();
+        assert!(mgr.keep_only_handles(selected.clone()).is_err());
+        assert_eq!(mgr.remove_by_handles(selected).unwrap(), 0);
+        assert_eq!(addresses(&mgr), (4..8).map(|i| BASE + i * 4).collect::<Vec<_>>());
+
+        drop(mgr);
+    }
+}
@@ -0,0 +1,116 @@
+//! Result label tests
+//!
+//! 标签按句柄设置、按 (地址, 类型) 保存：删除 / 保留和清空再写入的改善之后，仍在结果集里的地址保留标签，
+//! 被删除的结果的标签被清理。标签随导出文件保存和载入，旧版本（没有标签）的文件仍然可以导入。
+
+#[cfg(test)]
+mod tests {
+    use crate::core::address_rebase::AddressRebase;
+    use crate::search::result_manager::{MAX_LABEL_BYTES, SearchResultManager, SearchResultMode};
+    use crate::search::{SearchResultItem, ValueType};
+    use crate::search::tests::result_fixture::{split_result_manager, MEMORY_ITEMS};
+    use crate::search::tests::temp_dir::TempDir;
+    use std::path::Path;
+
+    const BASE: u64 = 0x7C00000000;
+
+    fn exact_manager(dir: &Path, count: u64) -> SearchResultManager {
+        let mut mgr = split_result_manager(dir, SearchResultMode::Exact, MEMORY_ITEMS);
+        mgr.add_results_batch((0..count).map(|i| SearchResultItem::new_exact(BASE + i * 4, ValueType::Dword)).collect()).unwrap();
+        mgr
+    }
+
+    fn label_of(mgr: &SearchResultManager, address: u64) -> Option<&str> {
+        mgr.label(address, ValueType::Dword)
+    }
+
+    #[test]
+    fn test_labels_follow_results_through_remove_and_refine() {
+        let dir = TempDir::new("result_labels_refine");
+        let mut mgr = exact_manager(&dir, 10);
+        let handles: Vec<u64> = (0..10).map(|i| mgr.handle_at(i).unwrap()).collect();
+        mgr.set_label(handles[2], "HP".to_string()).unwrap();
+        mgr.set_label(handles[5], "gold".to_string()).unwrap();
+        mgr.set_label(handles[7], "mana".to_string()).unwrap();
+        assert_eq!(mgr.get_labels(&handles[1..3]).unwrap(), vec![None, Some("HP".to_string())]);
+
+        // 删除前面的结果，位置前移，标签仍然跟着地址
+        mgr.remove_by_handles(vec![handles[0], handles[7]]).unwrap();
+        assert_eq!(mgr.get_labels(&[handles[2], handles[5], handles[7]]).unwrap(), vec![Some("HP".to_string()), Some("gold".to_string()), None]);
+        assert_eq!(label_of(&mgr, BASE + 7 * 4), None);
+
+        // 改善搜索：清空后写入幸存的结果，句柄重新分配，幸存地址的标签保留
+        mgr.clear().unwrap();
+        mgr.add_results_batch([1, 5, 9].iter().map(|i| SearchResultItem::new_exact(BASE + i * 4, ValueType::Dword)).collect()).unwrap();
+        assert_eq!(label_of(&mgr, BASE + 5 * 4), Some("gold"));
+        assert_eq!(label_of(&mgr, BASE + 2 * 4), None);
+        let gold = mgr.handle_at(1).unwrap();
+        assert_eq!(mgr.get_labels(&[gold, handles[5]]).unwrap(), vec![Some("gold".to_string()), None]);
+
+        // 同一地址的其他类型不共享标签，空字符串删除标签
+        assert_eq!(mgr.label(BASE + 5 * 4, ValueType::Float), None);
+        mgr.set_label(gold, String::new()).unwrap();
+        assert_eq!(label_of(&mgr, BASE + 5 * 4), None);
+
+        assert!(mgr.set_label(handles[0], "gone".to_string()).is_err());
+        assert!(mgr.set_label(gold, "x".repeat(MAX_LABEL_BYTES + 1)).is_err());
+
+        drop(mgr);
+    }
+
+    #[test]
+    fn test_labels_round_trip_through_export() {
+        let dir = TempDir::new("result_labels_export");
+        let mut mgr = exact_manager(&dir, 6);
+        let first = mgr.handle_at(0).unwrap();
+        let last = mgr.handle_at(5).unwrap();
+        mgr.set_label(first, "生命值".to_string()).unwrap();
+        mgr.set_label(last, "gold".to_string()).unwrap();
+
+        let path = dir.join("labels.mres");
+        mgr.export_to_file(&path, None).unwrap();
+        mgr.clear_labels();
+        mgr.clear().unwrap();
+
+        mgr.import_from_file(&path).unwrap();
+        assert_eq!(mgr.total_count(), 6);
+        assert_eq!(label_of(&mgr, BASE), Some("生命值"));
+        assert_eq!(label_of(&mgr, BASE + 5 * 4), Some("gold"));
+
+        // 版本 1：没有 labels_len 和标签段
+        let good = std::fs::read(&path).unwrap();
+        let labels_len = u32::from_le_bytes(good[22..26].try_into().unwrap()) as usize;
+        let mut v1 = good[..22].to_vec();
+        v1[8] = 1;
+        v1.extend_from_slice(&good[26 + labels_len..]);
+        let v1_path = dir.join("v1.mres");
+        std::fs::write(&v1_path, &v1).unwrap();
+        mgr.import_from_file(&v1_path).unwrap();
+        assert_eq!(mgr.total_count(), 6);
+        assert_eq!(label_of(&mgr, BASE), None);
+
+        // 标签段截断或长度不对时拒绝导入
+        let mut bad_labels = good.clone();
+        bad_labels[22..26].copy_from_slice(&(labels_len as u32 + 1).to_le_bytes());
+        let bad_path = dir.join("bad_labels.mres");
+        std::fs::write(&bad_path, &bad_labels).unwrap();
+        assert!(mgr.import_from_file(&bad_path).is_err());
+
+        drop(mgr);
+    }
+
+    #[test]
+    fn test_labels_move_with_rebase() {
+        let dir = TempDir::new("result_labels_rebase");
+        let mut mgr = exact_manager(&dir, 8);
+        let handle = mgr.handle_at(1).unwrap();
+        mgr.set_label(handle, "HP".to_string()).unwrap();
+
+        let new_base = BASE + 0x1000;
+        assert_eq!(mgr.rebase(&AddressRebase::new(BASE, new_base, 0x10)).unwrap(), 4);
+        assert_eq!(label_of(&mgr, BASE + 4), None);
+        assert_eq!(label_of(&mgr, new_base + 4), Some("HP"));
+
+        drop(mgr);
+    }
+}
@@ -0,0 +1,162 @@
+//! Per-region scan cache tests
+//!
+//! Regions are scanned through `scan_region_cached` with MockMemory reads; the
+//! scan closure counts full region reads so reuse can be asserted as zero reads.
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::manager::ValuePair;
+    use crate::search::engine::scan_cache::{query_fingerprint, scan_region_cached, ScanCache, DEFAULT_SCAN_CACHE_MAX_BYTES};
+    use crate::search::engine::single_search::search_in_chunks_with_status;
+    use crate::search::tests::mock_memory::MockMemory;
+    use crate::search::{SearchMode, SearchQuery, SearchValue, ValueType};
+    use crate::search::tests::temp_dir::TempDir;
+    use crate::wuwa::PageStatusBitmap;
+    use std::cell::Cell;
+
+    const REGION_A: (u64, u64) = (0x7500000000, 0x7500010000);
+    const REGION_B: (u64, u64) = (0x7600000000, 0x7600010000);
+    const TARGET: u32 = 0x1234_5678;
+
+    fn no_cancel() -> bool {
+        false
+    }
+
+    fn setup() -> MockMemory {
+        let mut mem = MockMemory::new();
+        for (start, end) in [REGION_A, REGION_B] {
+            mem.malloc(start, (end - start) as usize).unwrap();
+            mem.mem_write_u32(start + 0x10, TARGET).unwrap();
+            mem.mem_write_u32(start + 0x8000, TARGET).unwrap();
+        }
+        mem
+    }
+
+    fn query() -> SearchQuery {
+        SearchQuery::new(vec![SearchValue::fixed(TARGET as i128, ValueType::Dword)], SearchMode::Ordered, 0)
+    }
+
+    /// 扫描所有区域，返回 (每个区域的结果, 复用的区域数)
+    fn scan_all(mem: &MockMemory, cache: Option<&ScanCache>, scan_reads: &Cell<usize>) -> (Vec<Vec<ValuePair>>, usize) {
+        let fingerprint = query_fingerprint(&query(), false);
+        let value = query().values[0].clone();
+        let mut reused_count = 0;
+        let mut all = Vec::new();
+
+        for (start, end) in [REGION_A, REGION_B] {
+            let sample_read = |addr: u64, buf: &mut [u8]| -> anyhow::Result<()> {
+                buf.copy_from_slice(&mem.mem_read(addr, buf.len())?);
+                Ok(())
+            };
+            let scan = || -> anyhow::Result<Vec<ValuePair>> {
+                scan_reads.set(scan_reads.get() + 1);
+                let size = (end - start) as usize;
+                let mut buffer = vec![0u8; size];
+                let mut page_status = PageStatusBitmap::new(size, start as usize);
+                mem.mem_read_with_status(start, &mut buffer, &mut page_status)?;
+                let mut results = Vec::new();
+                search_in_chunks_with_status(&buffer, start, start, end, 4, &value, ValueType::Dword, &page_status, &mut results, &no_cancel);
+                Ok(results)
+            };
+
+            let (result, reused) = scan_region_cached(cache, fingerprint, (start, end), mem.page_size(), sample_read, scan, &no_cancel);
+            reused_count += reused as usize;
+            all.push(result.unwrap());
+        }
+        (all, reused_count)
+    }
+
+    #[test]
+    fn test_identical_rescan_reuses_all_regions() {
+        let dir = TempDir::new("scan_cache_reuse");
+        let cache = ScanCache::new(dir.to_path_buf(), DEFAULT_SCAN_CACHE_MAX_BYTES).unwrap();
+        let mem = setup();
+        let scan_reads = Cell::new(0);
+
+        let (first, reused) = scan_all(&mem, Some(&cache), &scan_reads);
+        assert_eq!(reused, 0);
+        assert_eq!(scan_reads.get(), 2);
+        assert_eq!(first[0].len(), 2);
+        assert!(cache.size_bytes() > 0);
+
+        let (second, reused) = scan_all(&mem, Some(&cache), &scan_reads);
+        assert_eq!(reused, 2);
+        assert_eq!(scan_reads.get(), 2, "reused regions must not be read");
+        assert_eq!(second, first);
+
+        // 不同的查询不会命中
+        let other = SearchQuery::new(vec![SearchValue::fixed(1, ValueType::Dword)], SearchMode::Ordered, 0);
+        assert_ne!(query_fingerprint(&other, false), query_fingerprint(&query(), false));
+        assert_ne!(query_fingerprint(&query(), true), query_fingerprint(&query(), false));
+    }
+
+    #[test]
+    fn test_mutated_sample_page_triggers_rescan() {
+        let dir = TempDir::new("scan_cache_mutate");
+        let cache = ScanCache::new(dir.to_path_buf(), DEFAULT_SCAN_CACHE_MAX_BYTES).unwrap();
+        let mut mem = setup();
+        let scan_reads = Cell::new(0);
+        scan_all(&mem, Some(&cache), &scan_reads);
+
+        // 区域 B 的采样页（第一页）里多了一个目标值
+        mem.mem_write_u32(REGION_B.0 + 0x20, TARGET).unwrap();
+        let (results, reused) = scan_all(&mem, Some(&cache), &scan_reads);
+        assert_eq!(reused, 1);
+        assert_eq!(scan_reads.get(), 3);
+        assert_eq!(results[1].len(), 3);
+
+        // 采样页之间的变化无法发现：这是文档说明的尽力而为行为
+        mem.mem_write_u32(REGION_A.0 + 0x4000, TARGET).unwrap();
+        let (results, reused) = scan_all(&mem, Some(&cache), &scan_reads);
+        assert_eq!(reused, 2);
+        assert_eq!(results[0].len(), 2);
+    }
+
+    #[test]
+    fn test_corrupt_header_is_a_miss() {
+        let dir = TempDir::new("scan_cache_corrupt");
+        let cache = ScanCache::new(dir.to_path_buf(), DEFAULT_SCAN_CACHE_MAX_BYTES).unwrap();
+        let results = vec![ValuePair::new(REGION_A.0 + 0x10, ValueType::Dword), ValuePair::new(REGION_A.0 + 0x8000, ValueType::Dword)];
+        cache.store(1, REGION_A.0, REGION_A.1, 42, &results).unwrap();
+        assert_eq!(cache.lookup(1, REGION_A.0, REGION_A.1, 42), Some(results));
+
+        // 写入走临时文件再 rename，目录里只剩缓存文件本身
+        let files: Vec<_> = std::fs::read_dir(&*dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+        assert_eq!(files.len(), 1);
+        let path = &files[0];
+        assert_eq!(path.extension().unwrap(), "bin");
+
+        // 文件头里的记录数远超文件长度：不按它分配内存，按未命中处理
+        let mut bytes = std::fs::read(path).unwrap();
+        bytes[8..16].copy_from_slice(&u64::MAX.to_le_bytes());
+        std::fs::write(path, &bytes).unwrap();
+        assert_eq!(cache.lookup(1, REGION_A.0, REGION_A.1, 42), None);
+
+        // 截断在记录中间
+        bytes[8..16].copy_from_slice(&2u64.to_le_bytes());
+        bytes.truncate(bytes.len() - 4);
+        std::fs::write(path, &bytes).unwrap();
+        assert_eq!(cache.lookup(1, REGION_A.0, REGION_A.1, 42), None);
+    }
+
+    #[test]
+    fn test_opt_out_never_consults_cache() {
+        let dir = TempDir::new("scan_cache_off");
+        let mem = setup();
+        let scan_reads = Cell::new(0);
+
+        let (first, reused) = scan_all(&mem, None, &scan_reads);
+        let (second, reused_again) = scan_all(&mem, None, &scan_reads);
+        assert_eq!(reused + reused_again, 0);
+        assert_eq!(scan_reads.get(), 4);
+        assert_eq!(first, second);
+        assert!(std::fs::read_dir(&dir).unwrap().next().is_none());
+
+        // 关闭时也不做采样读取
+        let fingerprint = query_fingerprint(&query(), false);
+        let sample_read = |_: u64, _: &mut [u8]| -> anyhow::Result<()> { panic!("cache disabled, no sampling expected") };
+        let (result, reused) = scan_region_cached(None, fingerprint, REGION_A, mem.page_size(), sample_read, || Ok(Vec::new()), &no_cancel);
+        assert!(result.unwrap().is_empty());
+        assert!(!reused);
+    }
+}
@@ -0,0 +1,153 @@
+//! Search session tests
+//!
+//! 会话保存后清空结果再载入：结果数、模式、特征码长度和兼容模式请求都恢复，相对路径落在缓存目录下。
+//! 元数据的 magic、版本不对或与结果文件不一致时拒绝载入，不会留下半个结果集。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::session::{SESSION_DIR_NAME, SESSION_META_FILE, SESSION_RESULTS_FILE, SessionMeta};
+    use crate::search::result_manager::{FuzzySearchResultItem, SearchResultManager, SearchResultMode};
+    use crate::search::{SearchEngineManager, SearchResultItem, ValueType};
+    use crate::search::tests::temp_dir::TempDir;
+    use std::path::{Path, PathBuf};
+
+    const BASE: u64 = 0x7800000000;
+    const PATTERN_LEN: usize = 12;
+
+    fn engine(dir: &Path) -> SearchEngineManager {
+        let mut manager = SearchEngineManager::new();
+        // 不留内存缓冲区，结果全部在磁盘上
+        manager.init(0, dir.to_string_lossy().into_owned(), 0).unwrap();
+        manager
+    }
+
+    /// 特征码结果转成的模糊结果，通过导入让引擎带上特征码长度
+    fn pattern_fuzzy_engine(dir: &Path, count: u64) -> SearchEngineManager {
+        let store_dir = dir.join("store");
+        std::fs::create_dir_all(&store_dir).unwrap();
+        let mut mgr = SearchResultManager::new(0, store_dir);
+        mgr.set_mode(SearchResultMode::Fuzzy).unwrap();
+        let items = (0..count)
+            .map(|i| FuzzySearchResultItem::from_bytes(BASE + i * 0x20, &[i as u8; PATTERN_LEN], ValueType::Pattern).with_pass((i % 4) as u8))
+            .collect();
+        mgr.add_fuzzy_results_batch(items).unwrap();
+        let path = dir.join("seed.mres");
+        mgr.export_to_file(&path, Some(PATTERN_LEN)).unwrap();
+        drop(mgr);
+
+        let mut manager = engine(dir);
+        manager.import_results(path).unwrap();
+        manager
+    }
+
+    fn fuzzy_items(manager: &SearchEngineManager) -> Vec<(u64, [u8; 8], ValueType, u8)> {
+        let total = manager.get_total_count().unwrap();
+        manager
+            .get_results(0, total)
+            .unwrap()
+            .into_iter()
+            .map(|item| match item {
+                SearchResultItem::Fuzzy(item) => (item.address, item.value, item.value_type, item.pass),
+                SearchResultItem::Exact(_) => panic!("expected fuzzy result"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fuzzy_session_round_trip() {
+        let dir = TempDir::new("session_round_trip");
+        let mut manager = pattern_fuzzy_engine(&dir, 1000);
+        manager.set_compatibility_mode(true);
+        let saved = fuzzy_items(&manager);
+
+        // 相对路径保存在缓存目录的 sessions 子目录下
+        assert_eq!(manager.save_session(PathBuf::from("progress")).unwrap(), 1000);
+        let session_dir = dir.join(SESSION_DIR_NAME).join("progress");
+        assert!(session_dir.join(SESSION_RESULTS_FILE).is_file());
+        let meta = SessionMeta::load(&session_dir).unwrap();
+        assert_eq!((meta.mode, meta.count, meta.pattern_len, meta.fuzzy_pattern_len), (SearchResultMode::Fuzzy, 1000, Some(PATTERN_LEN), Some(PATTERN_LEN)));
+        assert!(meta.compat_requested);
+
+        manager.clear_results().unwrap();
+        manager.set_compatibility_mode(false);
+        manager.set_result_mode(SearchResultMode::Exact).unwrap();
+        assert_eq!(manager.get_total_count().unwrap(), 0);
+
+        assert_eq!(manager.load_session(session_dir.to_path_buf()).unwrap(), 1000);
+        assert_eq!(manager.get_current_mode().unwrap(), SearchResultMode::Fuzzy);
+        assert_eq!(manager.get_total_count().unwrap(), 1000);
+        assert_eq!(manager.get_current_pattern_len(), Some(PATTERN_LEN));
+        assert!(manager.get_compatibility_mode().requested);
+        assert_eq!(fuzzy_items(&manager), saved);
+
+        // 覆盖保存缩小后的结果集
+        manager.clear_results().unwrap();
+        manager.set_result_mode(SearchResultMode::Exact).unwrap();
+        manager.add_results_batch((0..3).map(|i| SearchResultItem::new_exact(BASE + i * 4, ValueType::Dword)).collect()).unwrap();
+        assert_eq!(manager.save_session(session_dir.to_path_buf()).unwrap(), 3);
+        assert_eq!(manager.load_session(PathBuf::from("progress")).unwrap(), 3);
+        assert_eq!(manager.get_current_mode().unwrap(), SearchResultMode::Exact);
+
+        drop(manager);
+    }
+
+    #[test]
+    fn test_meta_encoding() {
+        let meta = SessionMeta {
+            mode: SearchResultMode::Exact,
+            compat_requested: true,
+            compat_deferred: true,
+            pattern_len: None,
+            fuzzy_pattern_len: Some(3),
+            count: 200_000_000,
+        };
+        assert_eq!(SessionMeta::decode(&meta.encode().unwrap()).unwrap(), meta);
+
+        let mut bytes = meta.encode().unwrap();
+        bytes[0] ^= 0xFF;
+        assert!(SessionMeta::decode(&bytes).unwrap_err().to_string().contains("magic"));
+        let mut bytes = meta.encode().unwrap();
+        bytes[8] = 99;
+        assert!(SessionMeta::decode(&bytes).unwrap_err().to_string().contains("version"));
+        let mut bytes = meta.encode().unwrap();
+        bytes[9] = 7;
+        assert!(SessionMeta::decode(&bytes).is_err());
+        assert!(SessionMeta::decode(&meta.encode().unwrap()[..20]).is_err());
+    }
+
+    #[test]
+    fn test_invalid_sessions_rejected() {
+        let dir = TempDir::new("session_invalid");
+        let mut manager = pattern_fuzzy_engine(&dir, 10);
+        let session_dir = dir.join("saved");
+        manager.save_session(session_dir.to_path_buf()).unwrap();
+        let meta_path = session_dir.join(SESSION_META_FILE);
+        let good = std::fs::read(&meta_path).unwrap();
+
+        // 元数据损坏：拒绝载入，当前结果不变
+        let mut bad_magic = good.clone();
+        bad_magic[..8].copy_from_slice(b"NOTASESS");
+        std::fs::write(&meta_path, &bad_magic).unwrap();
+        assert!(manager.load_session(session_dir.to_path_buf()).is_err());
+        assert_eq!(manager.get_total_count().unwrap(), 10);
+
+        // 元数据与结果文件的数量不一致：拒绝并清空载入的部分
+        let mut meta = SessionMeta::decode(&good).unwrap();
+        meta.count = 11;
+        meta.save(&session_dir).unwrap();
+        let err = manager.load_session(session_dir.to_path_buf()).unwrap_err();
+        assert!(err.to_string().contains("does not match"), "{}", err);
+        assert_eq!(manager.get_total_count().unwrap(), 0);
+        assert_eq!(manager.get_current_pattern_len(), None);
+
+        // 没有元数据（例如保存到一半失败）的目录不是会话
+        std::fs::remove_file(&meta_path).unwrap();
+        assert!(manager.load_session(session_dir.to_path_buf()).is_err());
+        assert!(manager.load_session(PathBuf::from("missing")).is_err());
+
+        std::fs::write(&meta_path, &good).unwrap();
+        assert_eq!(manager.load_session(session_dir).unwrap(), 10);
+
+        drop(manager);
+    }
+}
@@ -0,0 +1,176 @@
+//! SIMD comparison kernel tests
+//!
+//! 1MB 随机数据中放入已知的命中，Dword 精确值 / 范围和 Float 约等于的 SIMD 路径与标量路径、
+//! 以及逐个元素调用 `SearchValue::matched` 的结果必须完全相同；经过 `search_in_chunks_with_status`
+//! 时失败页里的命中不报告。最后一个测试打印两条路径的耗时。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::simd_scan::{scan_words, scan_words_scalar, CompareKernel};
+    use crate::search::engine::single_search::search_in_chunks_with_status;
+    use crate::search::{SearchValue, ValueType, PAGE_SIZE};
+    use crate::wuwa::PageStatusBitmap;
+    use std::time::Instant;
+
+    const BASE: u64 = 0x7D00000000;
+    const SIZE: usize = 1024 * 1024;
+
+    /// 固定种子的 xorshift，测试结果可复现
+    fn random_buffer(seed: u64) -> Vec<u8> {
+        let mut state = seed;
+        let mut buffer = Vec::with_capacity(SIZE);
+        while buffer.len() < SIZE {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            buffer.extend_from_slice(&state.to_le_bytes());
+        }
+        buffer
+    }
+
+    /// 每隔 `stride` 字节（从 `first` 开始）写入一个值
+    fn plant(buffer: &mut [u8], first: usize, stride: usize, bytes: [u8; 4]) {
+        for offset in (first..buffer.len() - 4).step_by(stride) {
+            buffer[offset..offset + 4].copy_from_slice(&bytes);
+        }
+    }
+
+    fn planted_buffer() -> Vec<u8> {
+        let mut buffer = random_buffer(0x9E3779B97F4A7C15);
+        plant(&mut buffer, 0x10, 0x1000, 1234567u32.to_le_bytes());
+        plant(&mut buffer, 0x24, 0x800, 0u32.to_le_bytes());
+        plant(&mut buffer, 0x28, 0x1800, u32::MAX.to_le_bytes());
+        plant(&mut buffer, 0x38, 0x777 * 4, 150i32.to_le_bytes());
+        plant(&mut buffer, 0x4C, 0x333 * 4, (-20i32).to_le_bytes());
+        plant(&mut buffer, 0x60, 0x900, 1.23456f32.to_le_bytes());
+        // 与目标相差 1 个和 40 个 ulp 的 Float，检查误差边界附近的比较
+        plant(&mut buffer, 0x74, 0xA00, f32::from_bits(1.23456f32.to_bits() + 1).to_le_bytes());
+        plant(&mut buffer, 0x88, 0xB00, f32::from_bits(1.23456f32.to_bits() + 40).to_le_bytes());
+        plant(&mut buffer, 0x9C, 0xC00, f32::NAN.to_le_bytes());
+        buffer
+    }
+
+    fn targets() -> Vec<SearchValue> {
+        vec![
+            SearchValue::fixed(1234567, ValueType::Dword),
+            SearchValue::fixed(0, ValueType::Dword),
+            SearchValue::fixed(-1, ValueType::Dword),
+            SearchValue::range(100, 200, ValueType::Dword, false),
+            SearchValue::range(-50, -10, ValueType::Dword, false),
+            SearchValue::range(-1_000_000, 1_000_000, ValueType::Dword, true),
+            SearchValue::range(i64::MIN as i128, 0, ValueType::Dword, false),
+            SearchValue::fixed_float(1.23456, ValueType::Float),
+            SearchValue::fixed_float(0.0, ValueType::Float),
+        ]
+    }
+
+    /// 逐个 4 字节元素调用 matched，作为对照
+    fn brute_force(target: &SearchValue, bytes: &[u8], base: u64) -> Vec<u64> {
+        bytes
+            .chunks_exact(4)
+            .enumerate()
+            .filter(|(_, word)| target.matched(word).unwrap())
+            .map(|(i, _)| base + (i * 4) as u64)
+            .collect()
+    }
+
+    #[test]
+    fn test_kernels_cover_common_dword_and_float_scans() {
+        assert_eq!(CompareKernel::for_target(&SearchValue::fixed(7, ValueType::Dword), 4), Some(CompareKernel::DwordEq(7)));
+        assert!(CompareKernel::for_target(&SearchValue::fixed(7, ValueType::Dword), 1).is_none());
+        assert!(CompareKernel::for_target(&SearchValue::fixed(7, ValueType::Qword), 4).is_none());
+        assert!(CompareKernel::for_target(&SearchValue::range_float(1.0, 2.0, ValueType::Float, false), 4).is_none());
+        // 与 i32 没有交集的范围交给标量路径
+        assert!(CompareKernel::for_target(&SearchValue::range(1 << 40, 1 << 41, ValueType::Dword, false), 4).is_none());
+        for target in targets() {
+            assert!(CompareKernel::for_target(&target, 4).is_some());
+        }
+    }
+
+    #[test]
+    fn test_simd_and_scalar_paths_return_identical_hits() {
+        let buffer = planted_buffer();
+        for target in targets() {
+            let kernel = CompareKernel::for_target(&target, 4).unwrap();
+            let expected = brute_force(&target, &buffer, BASE);
+            assert!(!expected.is_empty(), "{:?} should hit the planted values", kernel);
+
+            let mut scalar = Vec::new();
+            scan_words_scalar(&kernel, &buffer, BASE, &mut scalar);
+            assert_eq!(scalar, expected, "{:?}", kernel);
+
+            let mut simd = Vec::new();
+            scan_words(&kernel, &buffer, BASE, &mut simd);
+            assert_eq!(simd, expected, "{:?}", kernel);
+
+            // 长度不是 16 的倍数时尾部走标量路径
+            for tail in [4, 8, 12] {
+                let bytes = &buffer[..SIZE - 16 + tail];
+                let mut simd = Vec::new();
+                scan_words(&kernel, bytes, BASE, &mut simd);
+                assert_eq!(simd, brute_force(&target, bytes, BASE), "{:?} tail {}", kernel, tail);
+            }
+        }
+    }
+
+    #[test]
+    fn test_chunk_scan_matches_brute_force_on_successful_pages() {
+        let buffer = planted_buffer();
+        let page = *PAGE_SIZE;
+        let mut page_status = PageStatusBitmap::new(SIZE, BASE as usize);
+        for index in (0..SIZE / page).filter(|index| index % 7 != 3) {
+            page_status.mark_success(index);
+        }
+
+        // 区域的首尾不按页对齐，也不按 4 对齐
+        let region = (BASE + 0x1002, BASE + SIZE as u64 - 0x803);
+        for target in targets() {
+            let mut results = Vec::new();
+            search_in_chunks_with_status(&buffer, BASE, region.0, region.1, 4, &target, ValueType::Dword, &page_status, &mut results, &|| false);
+            let found: Vec<u64> = results.iter().map(|pair| pair.addr).collect();
+
+            let expected: Vec<u64> = brute_force(&target, &buffer, BASE)
+                .into_iter()
+                .filter(|&addr| addr >= region.0 && addr + 4 <= region.1)
+                .filter(|&addr| (addr - BASE) as usize / page % 7 != 3)
+                .collect();
+            assert_eq!(found, expected, "{:?}", target);
+        }
+    }
+
+    #[test]
+    fn test_simd_speedup() {
+        const ROUNDS: usize = 32;
+        // 非 aarch64 平台上两条路径都是标量实现
+        let path = if cfg!(target_arch = "aarch64") { "neon" } else { "scalar fallback" };
+        let buffer = planted_buffer();
+        for target in [SearchValue::fixed(0, ValueType::Dword), SearchValue::range(100, 200, ValueType::Dword, false), SearchValue::fixed_float(1.23456, ValueType::Float)] {
+            let kernel = CompareKernel::for_target(&target, 4).unwrap();
+            let mut out = Vec::new();
+
+            let started = Instant::now();
+            for _ in 0..ROUNDS {
+                out.clear();
+                scan_words_scalar(&kernel, &buffer, BASE, &mut out);
+            }
+            let scalar = started.elapsed();
+
+            let started = Instant::now();
+            for _ in 0..ROUNDS {
+                out.clear();
+                scan_words(&kernel, &buffer, BASE, &mut out);
+            }
+            let simd = started.elapsed();
+
+            println!(
+                "{:?}: scalar {:?}, {} {:?} per MB ({:.2}x, {} hits)",
+                kernel,
+                scalar / ROUNDS as u32,
+                path,
+                simd / ROUNDS as u32,
+                scalar.as_secs_f64() / simd.as_secs_f64().max(f64::EPSILON),
+                out.len()
+            );
+        }
+    }
+}
@@ -0,0 +1,184 @@
+//! Group range semantics tests
+//!
+//! 首次扫描之后紧跟一次内存没有变化的改善搜索，结果集必须完全一致。
+//! 布局放在 range 边界附近：元素恰好距离 range、锚点位于组的两端或中间。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::group_search::{refine_group_values_with_cancel, search_in_buffer_group, search_in_buffer_group_deep_with_cancel};
+    use crate::search::tests::mock_memory::MockMemory;
+    use crate::search::{SearchMode, SearchQuery, SearchValue, SpanMode, ValuePair, ValueType};
+    use crate::wuwa::PageStatusBitmap;
+    use std::collections::BTreeSet;
+
+    const BASE: u64 = 0x7600000000;
+    const SIZE: usize = 0x4000;
+    const RANGE: u16 = 16;
+
+    fn dword(value: i128) -> SearchValue {
+        SearchValue::fixed(value, ValueType::Dword)
+    }
+
+    fn memory_with(layout: &[(u64, u32)]) -> MockMemory {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, SIZE).unwrap();
+        for (offset, value) in layout {
+            mem.mem_write_u32(BASE + offset, *value).unwrap();
+        }
+        mem
+    }
+
+    fn scan(mem: &MockMemory, query: &SearchQuery, deep: bool) -> Vec<ValuePair> {
+        let mut buffer = vec![0u8; SIZE];
+        let mut page_status = PageStatusBitmap::new(SIZE, BASE as usize);
+        mem.mem_read_with_status(BASE, &mut buffer, &mut page_status).unwrap();
+
+        let mut results = Vec::new();
+        let mut matches_checked = 0usize;
+        let region_end = BASE + SIZE as u64;
+        if deep {
+            search_in_buffer_group_deep_with_cancel(
+                &buffer,
+                BASE,
+                BASE,
+                region_end,
+                4,
+                query,
+                &page_status,
+                &mut results,
+                &mut matches_checked,
+                &|| false,
+            );
+        } else {
+            search_in_buffer_group(
+                &buffer,
+                BASE,
+                BASE,
+                region_end,
+                4,
+                query,
+                &page_status,
+                &mut results,
+                &mut matches_checked,
+                &|| false,
+            );
+        }
+        results
+    }
+
+    fn refine(mem: &MockMemory, query: &SearchQuery, results: &[ValuePair]) -> BTreeSet<u64> {
+        let addr_values = results
+            .iter()
+            .map(|pair| (pair.addr, mem.mem_read(pair.addr, pair.value_type.size()).unwrap()))
+            .collect();
+        let refined = refine_group_values_with_cancel(addr_values, query, None, None, &|| false, &|_, _| {});
+        refined.iter().map(|pair| pair.addr).collect()
+    }
+
+    /// 扫描、改善各一次，断言两次结果相同并返回结果地址（相对 BASE）。
+    /// 深度搜索之后的改善取所有组合，与管理器相同
+    fn scan_then_refine(mem: &MockMemory, query: &SearchQuery, deep: bool) -> BTreeSet<u64> {
+        let results = scan(mem, query, deep);
+        let scanned: BTreeSet<u64> = results.iter().map(|pair| pair.addr).collect();
+        let refined = refine(mem, &query.clone().with_first_match_only(!deep), &results);
+        assert_eq!(
+            scanned, refined,
+            "refine changed results: mode={:?} span={:?} deep={}",
+            query.mode, query.span_mode, deep
+        );
+        scanned.iter().map(|addr| addr - BASE).collect()
+    }
+
+    fn offsets(groups: &[&[u64]]) -> BTreeSet<u64> {
+        groups.iter().flat_map(|group| group.iter().copied()).collect()
+    }
+
+    // 100 是锚点，各组之间相距远大于 range
+    const LOW_END: [u64; 3] = [0x100, 0x108, 0x110]; // 100 在最低端，300 恰好距离 range
+    const HIGH_END: [u64; 3] = [0x210, 0x208, 0x200]; // 100 在最高端
+    const MIDDLE_WIDE: [u64; 3] = [0x300, 0x2F0, 0x310]; // 两侧各距离 range，跨度 2 * range
+    const MIDDLE_NARROW: [u64; 3] = [0x400, 0x3F8, 0x408]; // 跨度恰好 range
+    const OVER: [u64; 3] = [0x500, 0x504, 0x514]; // 300 超出 range 4 字节
+
+    fn unordered_layout() -> Vec<(u64, u32)> {
+        [LOW_END, HIGH_END, MIDDLE_WIDE, MIDDLE_NARROW, OVER]
+            .iter()
+            .flat_map(|group| group.iter().zip([100u32, 200, 300]).map(|(offset, value)| (*offset, value)))
+            .collect()
+    }
+
+    #[test]
+    fn test_unordered_scan_then_refine_is_stable() {
+        let mem = memory_with(&unordered_layout());
+        let values = vec![dword(100), dword(200), dword(300)];
+
+        for deep in [false, true] {
+            let query = SearchQuery::new(values.clone(), SearchMode::Unordered, RANGE);
+            assert_eq!(
+                scan_then_refine(&mem, &query, deep),
+                offsets(&[&LOW_END, &HIGH_END, &MIDDLE_WIDE, &MIDDLE_NARROW])
+            );
+
+            let query = query.with_span_mode(SpanMode::GroupSpan);
+            assert_eq!(scan_then_refine(&mem, &query, deep), offsets(&[&LOW_END, &HIGH_END, &MIDDLE_NARROW]));
+        }
+    }
+
+    #[test]
+    fn test_ordered_anchor_at_either_end_is_stable() {
+        // 锚点在组首：100, 200, 300 递增，最后一个恰好距离 range
+        // 锚点在组尾：范围值在前，固定值 100 在最后
+        let layout = [
+            (0x100, 100),
+            (0x108, 200),
+            (0x110, 300),
+            (0x200, 200),
+            (0x208, 300),
+            (0x210, 100),
+            // 顺序颠倒，Ordered 下不成立
+            (0x310, 100),
+            (0x308, 200),
+            (0x300, 300),
+        ];
+        let mem = memory_with(&layout);
+
+        for span_mode in [SpanMode::FromAnchor, SpanMode::GroupSpan] {
+            for deep in [false, true] {
+                let query = SearchQuery::new(vec![dword(100), dword(200), dword(300)], SearchMode::Ordered, RANGE).with_span_mode(span_mode);
+                assert_eq!(scan_then_refine(&mem, &query, deep), offsets(&[&[0x100, 0x108, 0x110]]));
+
+                let values = vec![
+                    SearchValue::range(190, 210, ValueType::Dword, false),
+                    SearchValue::range(290, 310, ValueType::Dword, false),
+                    dword(100),
+                ];
+                let query = SearchQuery::new(values, SearchMode::Ordered, RANGE).with_span_mode(span_mode);
+                assert_eq!(query.anchor_index(), 2);
+                assert_eq!(scan_then_refine(&mem, &query, deep), offsets(&[&[0x200, 0x208, 0x210]]));
+            }
+        }
+    }
+
+    #[test]
+    fn test_duplicates_near_boundary_are_stable() {
+        // 两个 300 都在 range 内，普通模式只取第一组，深度模式取全部，改善都不应改变结果
+        let layout = [(0x100, 100), (0x104, 200), (0x108, 300), (0x110, 300), (0x0F0, 200)];
+        let mem = memory_with(&layout);
+
+        for span_mode in [SpanMode::FromAnchor, SpanMode::GroupSpan] {
+            let query = SearchQuery::new(vec![dword(100), dword(200), dword(300)], SearchMode::Unordered, RANGE).with_span_mode(span_mode);
+            let first = match span_mode {
+                SpanMode::FromAnchor => offsets(&[&[0x100, 0x0F0, 0x108]]),
+                // 0x0F0 上的 200 与任何 300 的跨度都超过 range，回溯到 0x104
+                SpanMode::GroupSpan => offsets(&[&[0x100, 0x104, 0x108]]),
+            };
+            assert_eq!(scan_then_refine(&mem, &query, false), first);
+
+            let expected = match span_mode {
+                SpanMode::FromAnchor => offsets(&[&[0x100, 0x104, 0x0F0, 0x108, 0x110]]),
+                SpanMode::GroupSpan => offsets(&[&[0x100, 0x104, 0x108, 0x110]]),
+            };
+            assert_eq!(scan_then_refine(&mem, &query, true), expected);
+        }
+    }
+}
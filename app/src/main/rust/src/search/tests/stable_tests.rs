@@ -0,0 +1,201 @@
+//! "Unchanged for N seconds" refine tests
+//!
+//! 用假时钟驱动观察循环：sleep 只推进时钟并应用到点的内存修改，
+//! 这样每个地址在什么时候变化、被哪一次采样看到都是确定的。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::fuzzy_search::{StableOutcome, StableWindow, refine_stable_with};
+    use crate::search::engine::{SEARCH_ENGINE_MANAGER, SearchErrorCode, SearchStatus};
+    use crate::search::result_manager::FuzzySearchResultItem;
+    use crate::search::tests::engine_fixture::EngineFixture;
+    use crate::search::tests::mock_memory::MockMemory;
+    use crate::search::{ValueType, parse_search_query};
+    use std::cell::{Cell, RefCell};
+    use std::time::Duration;
+
+    const BASE: u64 = 0x7300000000;
+    const WINDOW: Duration = Duration::from_millis(5000);
+    const INTERVAL: Duration = Duration::from_millis(1000);
+
+    fn addr(idx: usize) -> u64 {
+        BASE + (idx * 4) as u64
+    }
+
+    /// 在 `at_ms` 时把第 `idx` 项改成 `value`
+    struct Mutation {
+        at_ms: u64,
+        idx: usize,
+        value: i32,
+    }
+
+    fn mutation(at_ms: u64, idx: usize, value: i32) -> Mutation {
+        Mutation { at_ms, idx, value }
+    }
+
+    struct Harness {
+        mem: RefCell<MockMemory>,
+        clock: Cell<Duration>,
+        pending: RefCell<Vec<Mutation>>,
+        progress: RefCell<Vec<(u64, usize)>>,
+    }
+
+    impl Harness {
+        /// 第 i 项初始值为 i * 10
+        fn new(count: usize, mutations: Vec<Mutation>) -> (Self, Vec<FuzzySearchResultItem>) {
+            let mut mem = MockMemory::new();
+            mem.malloc(BASE, 4096).unwrap();
+            let items = (0..count)
+                .map(|idx| {
+                    let value = idx as i32 * 10;
+                    mem.mem_write_i32(addr(idx), value).unwrap();
+                    FuzzySearchResultItem::from_bytes(addr(idx), &value.to_le_bytes(), ValueType::Dword)
+                })
+                .collect();
+            let harness = Self {
+                mem: RefCell::new(mem),
+                clock: Cell::new(Duration::ZERO),
+                pending: RefCell::new(mutations),
+                progress: RefCell::new(Vec::new()),
+            };
+            (harness, items)
+        }
+
+        fn sleep(&self, duration: Duration) {
+            let now = self.clock.get() + duration;
+            self.clock.set(now);
+            let mut mem = self.mem.borrow_mut();
+            self.pending.borrow_mut().retain(|m| {
+                if Duration::from_millis(m.at_ms) <= now {
+                    mem.mem_write_i32(addr(m.idx), m.value).unwrap();
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+
+        fn run<C: Fn() -> bool>(&self, items: &[FuzzySearchResultItem], check_cancelled: C) -> StableOutcome {
+            refine_stable_with(
+                items.iter().copied(),
+                StableWindow {
+                    duration: WINDOW,
+                    interval: INTERVAL,
+                },
+                |address, buf| self.mem.borrow().mem_read_into(address, buf).is_ok(),
+                || self.clock.get(),
+                |duration| self.sleep(duration),
+                check_cancelled,
+                |elapsed, remaining| self.progress.borrow_mut().push((elapsed.as_millis() as u64, remaining)),
+            )
+        }
+    }
+
+    fn addresses(outcome: &StableOutcome) -> Vec<u64> {
+        outcome.survivors.iter().map(|item| item.address).collect()
+    }
+
+    #[test]
+    fn test_drops_items_changed_within_window() {
+        let (harness, mut items) = Harness::new(
+            6,
+            vec![
+                mutation(500, 1, 7),
+                mutation(1500, 2, 7),
+                // 两次采样之间变化又恢复，采样看不到
+                mutation(3500, 3, 7),
+                mutation(3600, 3, 30),
+                // 最后一次采样在窗口终点
+                mutation(4999, 4, 7),
+                mutation(6000, 5, 7),
+            ],
+        );
+        // 不可读的地址在窗口起点就被剔除
+        items.push(FuzzySearchResultItem::from_bytes(BASE + 0x10000, &[0; 4], ValueType::Dword));
+
+        let outcome = harness.run(&items, || false);
+
+        assert!(!outcome.cancelled);
+        assert_eq!(addresses(&outcome), vec![addr(0), addr(3), addr(5)]);
+        assert_eq!(outcome.samples, 6);
+        assert_eq!(harness.clock.get(), WINDOW);
+        assert_eq!(*harness.progress.borrow(), vec![(0, 6), (1000, 5), (2000, 4), (3000, 4), (4000, 4), (5000, 3)]);
+    }
+
+    #[test]
+    fn test_baseline_is_read_at_window_start() {
+        // 存储的旧值与当前内存不同：以窗口起点读到的值为准，幸存项带上这个值
+        let (harness, mut items) = Harness::new(2, vec![mutation(2500, 1, 99)]);
+        items[0] = FuzzySearchResultItem::from_bytes(addr(0), &123i32.to_le_bytes(), ValueType::Dword);
+
+        let outcome = harness.run(&items, || false);
+
+        assert_eq!(addresses(&outcome), vec![addr(0)]);
+        assert_eq!(outcome.survivors[0].value[..4], 0i32.to_le_bytes());
+    }
+
+    #[test]
+    fn test_stops_when_every_item_changed() {
+        let mutations = (0..4).map(|idx| mutation(1200, idx, -1)).collect();
+        let (harness, items) = Harness::new(4, mutations);
+
+        let outcome = harness.run(&items, || false);
+
+        assert!(outcome.survivors.is_empty());
+        assert_eq!(outcome.samples, 3);
+        assert_eq!(harness.clock.get(), Duration::from_millis(2000));
+    }
+
+    #[test]
+    fn test_cancel_mid_window_keeps_partial_set() {
+        let (harness, items) = Harness::new(4, vec![mutation(1500, 1, 7), mutation(2600, 2, 7), mutation(4000, 3, 7)]);
+
+        let outcome = harness.run(&items, || harness.clock.get() >= Duration::from_millis(2500));
+
+        // 2500ms 取消：1500ms 的变化已被剔除，之后的变化不再观察
+        assert!(outcome.cancelled);
+        assert_eq!(addresses(&outcome), vec![addr(0), addr(2), addr(3)]);
+        assert_eq!(outcome.samples, 3);
+        // 分片等待，取消在一个分片内生效
+        assert!(harness.clock.get() < Duration::from_millis(2600));
+    }
+
+    /// 通过全局引擎调用：参数错误和非模糊模式与其他入口一样写入 Error 状态和错误码
+    #[test]
+    fn test_refine_stable_for_reports_invalid_requests() {
+        let (harness, _) = Harness::new(4, Vec::new());
+        let fixture = EngineFixture::new("stable_invalid", harness.mem.into_inner());
+        let regions = vec![(BASE, BASE + 4096)];
+
+        let query = parse_search_query("10", ValueType::Dword).unwrap();
+        SEARCH_ENGINE_MANAGER.write().unwrap().start_search_async(query, regions.clone(), false, false, false).unwrap();
+        assert_eq!(fixture.wait_idle(), SearchStatus::Completed);
+        assert!(SEARCH_ENGINE_MANAGER.write().unwrap().refine_stable_for(1000, 100).is_err());
+        assert_eq!(fixture.wait_idle(), SearchStatus::Error);
+        assert_eq!(fixture.error_code(), SearchErrorCode::InvalidQuery as i32);
+
+        SEARCH_ENGINE_MANAGER.write().unwrap().start_fuzzy_search_async(ValueType::Dword, None, regions, false, false).unwrap();
+        assert_eq!(fixture.wait_idle(), SearchStatus::Completed);
+        assert!(SEARCH_ENGINE_MANAGER.write().unwrap().refine_stable_for(0, 100).is_err());
+        assert_eq!(fixture.wait_idle(), SearchStatus::Error);
+        assert_eq!(fixture.error_code(), SearchErrorCode::InvalidQuery as i32);
+    }
+
+    /// 窗口起点的基准值还没读完就在共享缓冲区请求取消：结果集不变
+    #[test]
+    fn test_refine_stable_for_cancelled_at_window_start() {
+        let (harness, _) = Harness::new(4, Vec::new());
+        let fixture = EngineFixture::new("stable_cancel", harness.mem.into_inner());
+
+        SEARCH_ENGINE_MANAGER.write().unwrap().start_fuzzy_search_async(ValueType::Dword, None, vec![(BASE, BASE + 4096)], false, false).unwrap();
+        assert_eq!(fixture.wait_idle(), SearchStatus::Completed);
+        let before = SEARCH_ENGINE_MANAGER.read().unwrap().get_total_count().unwrap();
+
+        let guard = fixture.memory.lock().unwrap();
+        SEARCH_ENGINE_MANAGER.write().unwrap().refine_stable_for(60_000, 1000).unwrap();
+        fixture.request_cancel();
+        drop(guard);
+        assert_eq!(fixture.wait_idle(), SearchStatus::Cancelled);
+        assert_eq!(SEARCH_ENGINE_MANAGER.read().unwrap().get_total_count().unwrap(), before);
+    }
+}
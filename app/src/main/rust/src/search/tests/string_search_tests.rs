@@ -0,0 +1,134 @@
+//! String search tests
+//!
+//! 用 mock 内存按块扫描 UTF-8 / UTF-16 字符串：跨块、跨页的匹配，失败页中断的匹配，
+//! UTF-16 的对齐，i 后缀忽略大小写，以及按匹配长度读取的改善搜索。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::manager::ValuePair;
+    use crate::search::engine::single_search::{refine_values_with, search_text_in_buffer};
+    use crate::search::tests::mock_memory::MockMemory;
+    use crate::search::{SearchValue, ValueType, parse_search_query};
+    use crate::wuwa::PageStatusBitmap;
+
+    const BASE: u64 = 0x7a00000000;
+    const PAGE_SIZE: usize = 4096;
+    const PAGES: usize = 16;
+    const CHUNK_SIZE: usize = 0x4000;
+
+    fn no_cancel() -> bool {
+        false
+    }
+
+    fn text_value(query: &str, value_type: ValueType) -> SearchValue {
+        parse_search_query(query, value_type).unwrap().values.remove(0)
+    }
+
+    /// 与 search_region_single_with_cancel 相同的分块方式：每块多读 len - 1 字节，
+    /// 只保留起始地址落在本块内的匹配
+    fn scan(mem: &MockMemory, target: &SearchValue, start: u64, end: u64) -> Vec<u64> {
+        let overlap = target.byte_len() - 1;
+        let mut buffer = vec![0u8; CHUNK_SIZE + overlap];
+        let mut results = Vec::new();
+        let mut current = start & !(PAGE_SIZE as u64 - 1);
+        while current < end {
+            let chunk_end = (current + CHUNK_SIZE as u64).min(end);
+            let chunk_len = ((chunk_end + overlap as u64).min(end) - current) as usize;
+            let mut page_status = PageStatusBitmap::new(chunk_len, current as usize);
+            if mem.mem_read_with_status(current, &mut buffer[..chunk_len], &mut page_status).is_ok() {
+                search_text_in_buffer(
+                    &buffer[..chunk_len],
+                    current,
+                    (current.max(start), chunk_end),
+                    target,
+                    &page_status,
+                    &mut results,
+                    &no_cancel,
+                );
+            }
+            current = chunk_end;
+        }
+        results.sort();
+        results.into_iter().map(|pair| pair.addr).collect()
+    }
+
+    fn setup_memory() -> MockMemory {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, PAGES * PAGE_SIZE).unwrap();
+        mem
+    }
+
+    #[test]
+    fn test_utf8_matches_across_chunks_and_pages() {
+        let mut mem = setup_memory();
+        let end = BASE + (PAGES * PAGE_SIZE) as u64;
+        let across_chunk = BASE + CHUNK_SIZE as u64 - 3;
+        let across_page = BASE + PAGE_SIZE as u64 * 6 - 2;
+        let across_faulty = BASE + PAGE_SIZE as u64 * 10 - 2;
+        for addr in [BASE + 0x11, across_chunk, across_page, across_faulty, end - 6] {
+            mem.mem_write(addr, b"Player").unwrap();
+        }
+        mem.mem_write(BASE + 0x100, b"player").unwrap();
+        mem.set_faulty_pages(BASE, &[10]).unwrap();
+
+        let target = text_value(r#""Player""#, ValueType::Dword);
+        assert_eq!(scan(&mem, &target, BASE, end), vec![BASE + 0x11, across_chunk, across_page, end - 6]);
+
+        // 起始地址不在区域内的匹配不计入
+        assert_eq!(scan(&mem, &target, BASE + 0x12, across_page), vec![across_chunk]);
+
+        let target = text_value(r#""PLAYER"i"#, ValueType::Dword);
+        assert_eq!(
+            scan(&mem, &target, BASE, end),
+            vec![BASE + 0x11, BASE + 0x100, across_chunk, across_page, end - 6]
+        );
+    }
+
+    #[test]
+    fn test_utf16_requires_alignment() {
+        let mut mem = setup_memory();
+        let end = BASE + (PAGES * PAGE_SIZE) as u64;
+        let encoded: Vec<u8> = "Hp名".encode_utf16().flat_map(u16::to_le_bytes).collect();
+        mem.mem_write(BASE + 0x20, &encoded).unwrap();
+        mem.mem_write(BASE + 0x41, &encoded).unwrap();
+        mem.mem_write(BASE + CHUNK_SIZE as u64 - 2, &encoded).unwrap();
+        let lower: Vec<u8> = "hP名".encode_utf16().flat_map(u16::to_le_bytes).collect();
+        mem.mem_write(BASE + 0x80, &lower).unwrap();
+
+        let target = text_value(r#""Hp名""#, ValueType::Utf16String);
+        assert_eq!(scan(&mem, &target, BASE, end), vec![BASE + 0x20, BASE + CHUNK_SIZE as u64 - 2]);
+
+        let target = text_value(r#""Hp名"i"#, ValueType::Utf16String);
+        assert_eq!(scan(&mem, &target, BASE, end), vec![BASE + 0x20, BASE + 0x80, BASE + CHUNK_SIZE as u64 - 2]);
+    }
+
+    #[test]
+    fn test_refine_reads_match_length() {
+        let mut mem = setup_memory();
+        let addrs = [BASE + 0x10, BASE + 0x40, BASE + PAGE_SIZE as u64 - 3];
+        for &addr in &addrs {
+            mem.mem_write(addr, b"Gold:99").unwrap();
+        }
+        let pairs: Vec<ValuePair> = addrs.iter().map(|&addr| ValuePair::new(addr, ValueType::Utf8String)).collect();
+
+        // 只改最后一个字节，改善搜索必须读完整个长度才能排除
+        mem.mem_write(BASE + 0x46, b"8").unwrap();
+        let refine = |query: &str| {
+            let target = text_value(query, ValueType::Utf8String);
+            let refined = refine_values_with(
+                &pairs,
+                &target,
+                |addr, buffer| mem.mem_read_into(addr, buffer).is_ok(),
+                None,
+                None,
+                &no_cancel,
+                &|_, _| {},
+            );
+            refined.into_iter().map(|pair| pair.addr).collect::<Vec<_>>()
+        };
+
+        assert_eq!(refine(r#""Gold:99""#), vec![addrs[0], addrs[2]]);
+        assert_eq!(refine(r#""gold:9"i"#), addrs.to_vec());
+        assert!(refine(r#""Gold:999""#).is_empty());
+    }
+}
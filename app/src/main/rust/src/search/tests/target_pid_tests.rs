@@ -0,0 +1,105 @@
+//! Target pid tests
+//!
+//! 首次搜索指定了其他进程时，改善、值捕获和模糊读取都读取这个进程，而不是绑定进程。
+//! 两个进程在相同地址上放不同的值，读错进程时幸存的结果不同。
+
+#[cfg(test)]
+mod tests {
+    use crate::core::DRIVER_MANAGER;
+    use crate::search::engine::{SearchStatus, SEARCH_ENGINE_MANAGER};
+    use crate::search::tests::engine_fixture::EngineFixture;
+    use crate::search::tests::mock_memory::MockMemory;
+    use crate::search::{parse_search_query, FuzzyCondition, SearchResultItem, ValueType};
+    use std::sync::{Arc, Mutex};
+
+    const BASE: u64 = 0x7500000000;
+    const SECONDARY_PID: i32 = 4242;
+
+    fn addr(idx: usize) -> u64 {
+        BASE + (idx * 0x10) as u64
+    }
+
+    fn memory_with(values: &[i32]) -> MockMemory {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, 4096).unwrap();
+        for (idx, value) in values.iter().enumerate() {
+            mem.mem_write_i32(addr(idx), *value).unwrap();
+        }
+        mem
+    }
+
+    fn results() -> Vec<SearchResultItem> {
+        let manager = SEARCH_ENGINE_MANAGER.read().unwrap();
+        manager.get_results(0, manager.get_total_count().unwrap()).unwrap()
+    }
+
+    fn addresses() -> Vec<u64> {
+        results()
+            .iter()
+            .map(|result| match result {
+                SearchResultItem::Exact(item) => item.address,
+                SearchResultItem::Fuzzy(item) => item.address,
+            })
+            .collect()
+    }
+
+    fn search(fixture: &EngineFixture, input: &str, target_pid: Option<i32>) {
+        let query = parse_search_query(input, ValueType::Dword).unwrap().with_target_pid(target_pid);
+        SEARCH_ENGINE_MANAGER.write().unwrap().start_search_async(query, vec![(BASE, BASE + 4096)], false, false, false).unwrap();
+        assert_eq!(fixture.wait_idle(), SearchStatus::Completed);
+    }
+
+    fn refine(fixture: &EngineFixture, input: &str, condition: Option<FuzzyCondition>) {
+        let query = parse_search_query(input, ValueType::Dword).unwrap();
+        SEARCH_ENGINE_MANAGER.write().unwrap().start_refine_async(query, condition).unwrap();
+        assert_eq!(fixture.wait_idle(), SearchStatus::Completed);
+    }
+
+    fn write_secondary(secondary: &Mutex<MockMemory>, idx: usize, value: i32) {
+        secondary.lock().unwrap().mem_write_i32(addr(idx), value).unwrap();
+    }
+
+    #[test]
+    fn test_refine_reads_the_searched_process() {
+        // 绑定进程里只有第 0 项是 100
+        let fixture = EngineFixture::new("target_pid_refine", memory_with(&[100, 7, 7]));
+        let secondary = Arc::new(Mutex::new(memory_with(&[100, 100, 100])));
+        DRIVER_MANAGER.write().unwrap().set_test_process_memory(SECONDARY_PID, Arc::clone(&secondary));
+
+        search(&fixture, "100", Some(SECONDARY_PID));
+        assert_eq!(addresses(), vec![addr(0), addr(1), addr(2)]);
+
+        // 条件改善以搜索时缓存的值为旧值
+        write_secondary(&secondary, 1, 5);
+        write_secondary(&secondary, 2, 150);
+        refine(&fixture, "0", Some(FuzzyCondition::Decreased));
+        assert_eq!(addresses(), vec![addr(1)]);
+
+        // 值改善
+        search(&fixture, "100", Some(SECONDARY_PID));
+        assert_eq!(addresses(), vec![addr(0)]);
+        write_secondary(&secondary, 0, 150);
+        refine(&fixture, "150", None);
+        assert_eq!(addresses(), vec![addr(0)]);
+
+        // 保留结果转换为模糊结果时捕获的值，以及之后的模糊改善
+        SEARCH_ENGINE_MANAGER
+            .write()
+            .unwrap()
+            .start_fuzzy_search_async(ValueType::Dword, None, vec![(BASE, BASE + 4096)], true, false)
+            .unwrap();
+        assert_eq!(fixture.wait_idle(), SearchStatus::Completed);
+        write_secondary(&secondary, 0, 160);
+        refine(&fixture, "0", Some(FuzzyCondition::Increased));
+        match results().as_slice() {
+            [SearchResultItem::Fuzzy(item)] => assert_eq!((item.address, item.as_i64()), (addr(0), 160)),
+            other => panic!("expected one fuzzy result, got {}", other.len()),
+        }
+
+        // 新的搜索不指定进程时回到绑定进程
+        search(&fixture, "7", None);
+        assert_eq!(addresses(), vec![addr(1), addr(2)]);
+        refine(&fixture, "7", None);
+        assert_eq!(addresses(), vec![addr(1), addr(2)]);
+    }
+}
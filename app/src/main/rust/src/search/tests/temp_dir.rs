@@ -0,0 +1,44 @@
+//! Temporary directories for tests
+//!
+//! 每个测试使用自己的缓存目录，名字带纳秒时间戳和进程内序号，并行运行的测试不会冲突。
+//! `TempDir` 离开作用域时连同内容删除；先声明目录再创建使用它的管理器，管理器先于目录销毁。
+
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// 创建 `<系统临时目录>/mamu_<name>_<纳秒>_<序号>`
+    pub fn new(name: &str) -> Self {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let dir = std::env::temp_dir().join(format!("mamu_{}_{}_{}", name, nanos, id));
+        std::fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
@@ -0,0 +1,127 @@
+//! Result text export tests
+//!
+//! 结果集按批从内存缓冲区和磁盘部分写出为 CSV / JSON Lines，行数和顺序与结果集一致。
+//! 模糊结果的值与结果列表的格式相同：Xor 解码、字符串按需加引号、只有摘要的长特征码值留空。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::result_manager::{FuzzySearchResultItem, ResultExportFormat, SearchResultManager, SearchResultMode};
+    use crate::search::{SearchResultItem, ValueType, XorKey};
+    use crate::search::tests::temp_dir::TempDir;
+    use std::path::Path;
+
+    const BASE: u64 = 0x7A00000000;
+
+    /// 内存缓冲区只放得下很少的结果，其余在磁盘上
+    fn manager(dir: &Path) -> SearchResultManager {
+        let store_dir = dir.join("store");
+        std::fs::create_dir_all(&store_dir).unwrap();
+        SearchResultManager::new(1024, store_dir)
+    }
+
+    fn lines(path: &Path) -> Vec<String> {
+        std::fs::read_to_string(path).unwrap().lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_fuzzy_export_streams_memory_and_disk() {
+        let dir = TempDir::new("text_export_fuzzy");
+        let mut mgr = manager(&dir);
+        mgr.set_mode(SearchResultMode::Fuzzy).unwrap();
+        let count = 150_000u64;
+        let items = (0..count)
+            .map(|i| FuzzySearchResultItem::from_bytes(BASE + i * 4, &(i as i32 - 5).to_le_bytes(), ValueType::Dword))
+            .collect();
+        mgr.add_fuzzy_results_batch(items).unwrap();
+
+        let csv = dir.join("results.csv");
+        assert_eq!(mgr.export(&csv, ResultExportFormat::Csv, XorKey::Address).unwrap(), count as usize);
+        let rows = lines(&csv);
+        assert_eq!(rows.len(), count as usize + 1);
+        assert_eq!(rows[0], "index,address,type,value");
+        assert_eq!(rows[1], format!("0,0x{:X},{},-5", BASE, ValueType::Dword.to_id()));
+        assert_eq!(rows[count as usize], format!("{},0x{:X},{},{}", count - 1, BASE + (count - 1) * 4, ValueType::Dword.to_id(), count - 6));
+
+        let jsonl = dir.join("results.jsonl");
+        assert_eq!(mgr.export(&jsonl, ResultExportFormat::JsonLines, XorKey::Address).unwrap(), count as usize);
+        let rows = lines(&jsonl);
+        assert_eq!(rows.len(), count as usize);
+        let row: serde_json::Value = serde_json::from_str(&rows[70_000]).unwrap();
+        assert_eq!(row["index"], 70_000);
+        assert_eq!(row["address"], format!("0x{:X}", BASE + 70_000 * 4));
+        assert_eq!(row["type"], ValueType::Dword.to_id());
+        assert_eq!(row["value"], "69995");
+        assert!(!dir.join("results.part").exists());
+
+        drop(mgr);
+    }
+
+    #[test]
+    fn test_fuzzy_values_formatted_like_result_list() {
+        let dir = TempDir::new("text_export_values");
+        let mut mgr = manager(&dir);
+        mgr.set_mode(SearchResultMode::Fuzzy).unwrap();
+        // Xor 保存的是内存中的编码值
+        let encoded = (1234u32 ^ (BASE as u32)).to_le_bytes();
+        mgr.add_fuzzy_results_batch(vec![
+            FuzzySearchResultItem::from_bytes(BASE, &encoded, ValueType::Xor),
+            FuzzySearchResultItem::from_bytes(BASE + 8, &1.5f32.to_le_bytes(), ValueType::Float),
+        ])
+        .unwrap();
+        let path = dir.join("values.csv");
+        mgr.export(&path, ResultExportFormat::Csv, XorKey::Address).unwrap();
+        let rows = lines(&path);
+        assert_eq!(rows[1], format!("0,0x{:X},{},1234", BASE, ValueType::Xor.to_id()));
+        assert_eq!(rows[2], format!("1,0x{:X},{},1.5", BASE + 8, ValueType::Float.to_id()));
+
+        // 含逗号和引号的短字符串在 CSV 中加引号，JSON 中转义
+        mgr.clear().unwrap();
+        mgr.set_mode(SearchResultMode::Fuzzy).unwrap();
+        mgr.add_fuzzy_results_batch(vec![FuzzySearchResultItem::from_bytes(BASE, b"a,\"b\"", ValueType::Utf8String)]).unwrap();
+        mgr.set_fuzzy_pattern_len(Some(5));
+        mgr.export(&path, ResultExportFormat::Csv, XorKey::Address).unwrap();
+        assert_eq!(lines(&path)[1], format!("0,0x{:X},{},\"a,\"\"b\"\"\"", BASE, ValueType::Utf8String.to_id()));
+        mgr.export(&path, ResultExportFormat::JsonLines, XorKey::Address).unwrap();
+        let row: serde_json::Value = serde_json::from_str(&lines(&path)[0]).unwrap();
+        assert_eq!(row["value"], "a,\"b\"");
+
+        // 长于 8 字节的特征码只有摘要
+        mgr.clear().unwrap();
+        mgr.set_mode(SearchResultMode::Fuzzy).unwrap();
+        mgr.add_fuzzy_results_batch(vec![FuzzySearchResultItem::from_bytes(BASE, &[0xAB; 12], ValueType::Pattern)]).unwrap();
+        mgr.set_fuzzy_pattern_len(Some(12));
+        mgr.export(&path, ResultExportFormat::JsonLines, XorKey::Address).unwrap();
+        let row: serde_json::Value = serde_json::from_str(&lines(&path)[0]).unwrap();
+        assert!(row["value"].is_null());
+        mgr.export(&path, ResultExportFormat::Csv, XorKey::Address).unwrap();
+        assert_eq!(lines(&path)[1], format!("0,0x{:X},{},", BASE, ValueType::Pattern.to_id()));
+
+        drop(mgr);
+    }
+
+    #[test]
+    fn test_exact_export_and_io_errors() {
+        let dir = TempDir::new("text_export_exact");
+        let mut mgr = manager(&dir);
+        mgr.set_mode(SearchResultMode::Exact).unwrap();
+        for i in 0..1000u64 {
+            mgr.add_result(SearchResultItem::new_exact(BASE + i * 8, ValueType::Qword)).unwrap();
+        }
+
+        let path = dir.join("exact.jsonl");
+        assert_eq!(mgr.export(&path, ResultExportFormat::JsonLines, XorKey::Address).unwrap(), 1000);
+        let rows = lines(&path);
+        assert_eq!(rows[999], format!(r#"{{"index":999,"address":"0x{:X}","type":{}}}"#, BASE + 999 * 8, ValueType::Qword.to_id()));
+
+        let path = dir.join("exact.csv");
+        mgr.export(&path, ResultExportFormat::Csv, XorKey::Address).unwrap();
+        assert_eq!(lines(&path)[..2], ["index,address,type".to_string(), format!("0,0x{:X},{}", BASE, ValueType::Qword.to_id())]);
+
+        // 目录不存在时返回错误，不留下临时文件
+        assert!(mgr.export(&dir.join("missing").join("out.csv"), ResultExportFormat::Csv, XorKey::Address).is_err());
+        assert_eq!(ResultExportFormat::from_id(1), Some(ResultExportFormat::JsonLines));
+        assert_eq!(ResultExportFormat::from_id(2), None);
+
+        drop(mgr);
+    }
+}
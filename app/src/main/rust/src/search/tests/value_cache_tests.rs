@@ -0,0 +1,63 @@
+//! Exact result value cache tests
+//!
+//! 扫描时缓存的值在翻页时直接使用；下拉刷新只重新读取请求的一页，清空结果时缓存一起清空。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::result_manager::{ExactValueCache, SearchResultMode};
+    use crate::search::{SearchEngineManager, SearchResultItem, ValueType};
+    use crate::search::tests::temp_dir::TempDir;
+    use anyhow::anyhow;
+
+    const BASE: u64 = 0x7700000000;
+
+    /// 第 i 个结果的值为 i + offset，第 7 个结果所在的 16 字节不可读
+    fn memory(offset: u32) -> impl Fn(u64, &mut [u8]) -> anyhow::Result<()> + Sync {
+        move |addr, buf| {
+            let unreadable = BASE + 7 * 0x10..BASE + 8 * 0x10;
+            if addr < unreadable.end && addr + buf.len() as u64 > unreadable.start {
+                return Err(anyhow!("unreadable"));
+            }
+            for (i, byte) in buf.iter_mut().enumerate() {
+                let at = addr + i as u64 - BASE;
+                let value = (at / 0x10) as u32 + offset;
+                *byte = value.to_le_bytes().get((at % 0x10) as usize).copied().unwrap_or(0);
+            }
+            Ok(())
+        }
+    }
+
+    fn cached(manager: &SearchEngineManager, index: u64) -> Option<u32> {
+        manager.exact_value(BASE + index * 0x10).map(|bytes| u32::from_le_bytes(bytes[..4].try_into().unwrap()))
+    }
+
+    #[test]
+    fn test_cached_values_and_page_refresh() {
+        let dir = TempDir::new("value_cache");
+        let mut manager = SearchEngineManager::new();
+        manager.init(0, dir.to_string_lossy().into_owned(), 0).unwrap();
+        manager.set_result_mode(SearchResultMode::Exact).unwrap();
+
+        let addrs: Vec<u64> = (0..10u64).map(|i| BASE + i * 0x10).collect();
+        manager.add_results_batch(addrs.iter().map(|&addr| SearchResultItem::new_exact(addr, ValueType::Dword)).collect()).unwrap();
+        let entries: Vec<(u64, usize)> = addrs.iter().map(|&addr| (addr, 4)).collect();
+        let values = ExactValueCache::capture(&entries, memory(100), &|| false).unwrap();
+        manager.result_manager_mut().unwrap().set_exact_values(values);
+
+        assert_eq!(cached(&manager, 3), Some(103));
+        assert_eq!(cached(&manager, 7), None);
+
+        // 只刷新第 2..6 项；读取失败的第 7 项没有缓存，不计入
+        assert_eq!(manager.refresh_result_values(2, 6, memory(200)).unwrap(), 5);
+        assert_eq!(cached(&manager, 1), Some(101));
+        assert_eq!(cached(&manager, 2), Some(202));
+        assert_eq!(cached(&manager, 6), Some(206));
+        assert_eq!(cached(&manager, 8), Some(108));
+        assert_eq!(manager.refresh_result_values(20, 5, memory(200)).unwrap(), 0);
+
+        manager.clear_results().unwrap();
+        assert_eq!(cached(&manager, 2), None);
+
+        drop(manager);
+    }
+}
@@ -0,0 +1,80 @@
+//! Search watchdog tests
+//!
+//! 用一个永远不结束的任务模拟卡死的搜索：心跳超时后判定为卡死，恢复后引擎可以开始新的搜索；
+//! 手动重置同样清理任务，并清空卡住之前写入的结果。
+
+#[cfg(test)]
+mod tests {
+    use crate::core::globals::TOKIO_RUNTIME;
+    use crate::search::engine::shared_buffer::layout;
+    use crate::search::engine::watchdog::WatchdogVerdict;
+    use crate::search::engine::{SearchErrorCode, SearchStatus, SHARED_BUFFER_SIZE};
+    use crate::search::{SearchEngineManager, SearchResultItem, ValueType};
+    use crate::search::tests::temp_dir::TempDir;
+    use std::time::Duration;
+
+    const TIMEOUT: Duration = Duration::from_millis(50);
+
+    fn wedged_search() -> tokio::task::JoinHandle<()> {
+        TOKIO_RUNTIME.spawn(std::future::pending())
+    }
+
+    fn error_code(memory: &[u8]) -> i32 {
+        i32::from_le_bytes(memory[layout::ERROR_CODE..layout::ERROR_CODE + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_stalled_search_is_recovered() {
+        let mut memory = [0u8; SHARED_BUFFER_SIZE];
+        let mut manager = SearchEngineManager::new();
+        // 不启动全局看门狗，由测试直接检查
+        manager.set_stall_timeout(Duration::ZERO);
+        assert!(manager.set_shared_buffer(memory.as_mut_ptr(), memory.len()));
+
+        let (search_id, cancel_token) = manager.track_test_search(wedged_search());
+        assert!(manager.is_searching());
+        assert_eq!(manager.watchdog_verdict(search_id, TIMEOUT), WatchdogVerdict::Healthy);
+
+        std::thread::sleep(TIMEOUT * 2);
+        assert_eq!(manager.watchdog_verdict(search_id, TIMEOUT), WatchdogVerdict::Stalled);
+        // 旧搜索的看门狗不处理新的搜索
+        assert_eq!(manager.watchdog_verdict(search_id - 1, TIMEOUT), WatchdogVerdict::Finished);
+
+        manager.recover_stalled_search();
+        assert!(!manager.is_searching());
+        assert!(cancel_token.is_cancelled());
+        assert_eq!(manager.search_status().0, SearchStatus::Error);
+        manager.clear_shared_buffer();
+        assert_eq!(error_code(&memory), SearchErrorCode::Stalled as i32);
+        assert_eq!(manager.watchdog_verdict(search_id, TIMEOUT), WatchdogVerdict::Finished);
+    }
+
+    #[test]
+    fn test_force_reset_clears_partial_results() {
+        let dir = TempDir::new("watchdog");
+
+        let mut memory = [0u8; SHARED_BUFFER_SIZE];
+        let mut manager = SearchEngineManager::new();
+        manager.set_stall_timeout(Duration::ZERO);
+        manager.init(0, dir.to_string_lossy().into_owned(), 0).unwrap();
+        assert!(manager.set_shared_buffer(memory.as_mut_ptr(), memory.len()));
+
+        // 空闲时重置不改变状态
+        manager.force_reset().unwrap();
+        assert_eq!(manager.search_status().0, SearchStatus::Idle);
+
+        let (_, cancel_token) = manager.track_test_search(wedged_search());
+        manager
+            .add_results_batch((0..16).map(|i| SearchResultItem::new_exact(0x7000_0000 + i * 4, ValueType::Dword)).collect())
+            .unwrap();
+        assert_eq!(manager.get_total_count().unwrap(), 16);
+
+        manager.force_reset().unwrap();
+        assert!(!manager.is_searching());
+        assert!(cancel_token.is_cancelled());
+        assert_eq!(manager.get_total_count().unwrap(), 0);
+        assert_eq!(manager.search_status().0, SearchStatus::Error);
+        manager.clear_shared_buffer();
+        assert_eq!(error_code(&memory), SearchErrorCode::Stalled as i32);
+    }
+}
@@ -0,0 +1,119 @@
+//! Xor value search tests
+//!
+//! `123X` 按地址密钥解码、`123:X<密钥>` 按固定密钥解码后比较，
+//! 改善搜索沿用同样的密钥，写入时按地址重新编码。
+
+#[cfg(test)]
+mod tests {
+    use crate::search::engine::single_search::{refine_values_with, search_in_chunks_with_status};
+    use crate::search::tests::mock_memory::MockMemory;
+    use crate::search::{SearchValue, ValuePair, ValueType, XorKey, parse_search_query, parse_typed_value};
+    use crate::wuwa::PageStatusBitmap;
+
+    const BASE: u64 = 0x7712345000;
+    const SIZE: usize = 0x2000;
+    const FIXED_KEY: u32 = 0x5A5A_1234;
+
+    fn xor_value(input: &str) -> SearchValue {
+        parse_search_query(input, ValueType::Dword).unwrap().values.remove(0)
+    }
+
+    fn scan(mem: &MockMemory, target: &SearchValue) -> Vec<u64> {
+        let mut buffer = vec![0u8; SIZE];
+        let mut page_status = PageStatusBitmap::new(SIZE, BASE as usize);
+        mem.mem_read_with_status(BASE, &mut buffer, &mut page_status).unwrap();
+
+        let mut results = Vec::new();
+        search_in_chunks_with_status(
+            &buffer,
+            BASE,
+            BASE,
+            BASE + SIZE as u64,
+            target.value_type().size(),
+            target,
+            target.value_type(),
+            &page_status,
+            &mut results,
+            &|| false,
+        );
+        results.iter().map(|pair| pair.addr - BASE).collect()
+    }
+
+    fn refine(mem: &MockMemory, offsets: &[u64], target: &SearchValue) -> Vec<u64> {
+        let pairs: Vec<ValuePair> = offsets.iter().map(|&offset| ValuePair::new(BASE + offset, ValueType::Xor)).collect();
+        refine_values_with(&pairs, target, |addr, buffer| mem.mem_read_into(addr, buffer).is_ok(), None, None, &|| false, &|_, _| {})
+            .into_iter()
+            .map(|pair| pair.addr - BASE)
+            .collect()
+    }
+
+    /// 按密钥编码后写入
+    fn write_encoded(mem: &mut MockMemory, offset: u64, value: i32, key: XorKey) {
+        let addr = BASE + offset;
+        let mut bytes = value.to_le_bytes();
+        key.apply(&mut bytes, addr);
+        mem.mem_write(addr, &bytes).unwrap();
+    }
+
+    fn setup_memory() -> MockMemory {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, SIZE).unwrap();
+        mem
+    }
+
+    #[test]
+    fn test_address_keyed_search_and_refine() {
+        let mut mem = setup_memory();
+        for offset in [0x10, 0x100, 0x1FFC] {
+            write_encoded(&mut mem, offset, 123, XorKey::Address);
+        }
+        // 明文 123 和按固定密钥编码的 123 都不应匹配
+        mem.mem_write_i32(BASE + 0x20, 123).unwrap();
+        write_encoded(&mut mem, 0x30, 123, XorKey::Fixed(FIXED_KEY));
+        write_encoded(&mut mem, 0x40, -7, XorKey::Address);
+
+        assert_eq!(scan(&mem, &xor_value("123X")), vec![0x10, 0x100, 0x1FFC]);
+        assert_eq!(scan(&mem, &xor_value("-7X")), vec![0x40]);
+        // 普通 Dword 搜索看到的是密文
+        assert_eq!(scan(&mem, &xor_value("123D")), vec![0x20]);
+
+        write_encoded(&mut mem, 0x100, 150, XorKey::Address);
+        assert_eq!(refine(&mem, &[0x10, 0x100, 0x1FFC], &xor_value("150X")), vec![0x100]);
+    }
+
+    #[test]
+    fn test_fixed_key_search_and_refine() {
+        let mut mem = setup_memory();
+        write_encoded(&mut mem, 0x10, 1000, XorKey::Fixed(FIXED_KEY));
+        write_encoded(&mut mem, 0x800, 1000, XorKey::Fixed(FIXED_KEY));
+        write_encoded(&mut mem, 0x20, 1000, XorKey::Address);
+
+        let target = xor_value("1000:X0x5A5A1234");
+        assert!(target.matched_at(&(1000 ^ FIXED_KEY).to_le_bytes(), 0).unwrap());
+        assert_eq!(scan(&mem, &target), vec![0x10, 0x800]);
+
+        write_encoded(&mut mem, 0x800, 999, XorKey::Fixed(FIXED_KEY));
+        assert_eq!(refine(&mem, &[0x10, 0x800], &target), vec![0x10]);
+        assert_eq!(refine(&mem, &[0x10, 0x800], &SearchValue::xor(999, XorKey::Fixed(FIXED_KEY))), vec![0x800]);
+    }
+
+    #[test]
+    fn test_write_re_encodes_for_decoded_display() {
+        let mut mem = setup_memory();
+        let addr = BASE + 0x44;
+
+        // 写入路径：解析成明文后按结果的密钥编码
+        for key in [XorKey::Address, XorKey::Fixed(FIXED_KEY)] {
+            let mut bytes = parse_typed_value("-42", ValueType::Xor).unwrap();
+            key.apply(&mut bytes, addr);
+            mem.mem_write(addr, &bytes).unwrap();
+
+            // 显示路径：读出密文后解码
+            let mut raw = mem.mem_read(addr, 4).unwrap();
+            assert_ne!(i32::from_le_bytes(raw[..4].try_into().unwrap()), -42);
+            key.apply(&mut raw, addr);
+            assert_eq!(i32::from_le_bytes(raw[..4].try_into().unwrap()), -42);
+            assert_eq!(scan(&mem, &SearchValue::xor(-42i32 as u32, key)), vec![0x44]);
+        }
+    }
+}
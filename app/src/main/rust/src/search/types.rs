@@ -14,6 +14,10 @@ pub enum ValueType {
     Xor = 7,
     /// 特征码搜索类型
     Pattern = 8,
+    /// UTF-8 字符串
+    Utf8String = 9,
+    /// UTF-16 LE 字符串
+    Utf16String = 10,
 }
 
 impl ValueType {
@@ -29,6 +33,8 @@ impl ValueType {
             6 => Some(Self::Auto),
             7 => Some(Self::Xor),
             8 => Some(Self::Pattern),
+            9 => Some(Self::Utf8String),
+            10 => Some(Self::Utf16String),
             _ => None,
         }
     }
@@ -66,6 +72,7 @@ impl ValueType {
             ValueType::Auto => 4,
             ValueType::Xor => 4,
             ValueType::Pattern => 0, // 可变长度，由 pattern 决定
+            ValueType::Utf8String | ValueType::Utf16String => 0, // 可变长度，由字符串决定
         }
     }
 
@@ -73,6 +80,17 @@ impl ValueType {
     pub fn is_float_type(&self) -> bool {
         matches!(self, ValueType::Float | ValueType::Double)
     }
+
+    #[inline]
+    pub fn is_text_type(&self) -> bool {
+        matches!(self, ValueType::Utf8String | ValueType::Utf16String)
+    }
+
+    /// 长度由搜索内容决定的类型，结果长度记录在搜索引擎的当前匹配长度中
+    #[inline]
+    pub fn is_variable_len(&self) -> bool {
+        matches!(self, ValueType::Pattern | ValueType::Utf8String | ValueType::Utf16String)
+    }
 }
 
 impl fmt::Display for ValueType {
@@ -87,6 +105,8 @@ impl fmt::Display for ValueType {
             ValueType::Auto => write!(f, "Auto"),
             ValueType::Xor => write!(f, "Xor"),
             ValueType::Pattern => write!(f, "Pattern"),
+            ValueType::Utf8String => write!(f, "UTF-8"),
+            ValueType::Utf16String => write!(f, "UTF-16"),
         }
     }
 }
@@ -124,6 +144,61 @@ pub enum SearchValue {
     Pattern {
         pattern: Vec<(u8, u8)>,
     },
+    /// 字符串搜索，bytes 是按 value_type 编码后的内容
+    /// - ignore_case: 只折叠 ASCII 字母，匹配长度不变
+    Text {
+        bytes: Vec<u8>,
+        value_type: ValueType,
+        ignore_case: bool,
+    },
+    /// XOR 加密的 Dword，value 是解码后的明文，内存中存的是 value ^ key
+    Xor {
+        value: u32,
+        key: XorKey,
+    },
+}
+
+/// 浮点数约等于搜索的误差
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FloatTolerance {
+    /// |x - v| <= |v| * epsilon
+    Relative(f64),
+    /// |x - v| <= delta
+    Absolute(f64),
+}
+
+impl FloatTolerance {
+    /// `v~` 不写误差时使用的相对误差
+    pub const DEFAULT_RELATIVE: f64 = 1e-6;
+}
+
+/// XOR 加密值的密钥
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum XorKey {
+    /// GameGuardian 的方式：密钥是值所在地址的低 32 位
+    #[default]
+    Address,
+    /// 固定密钥
+    Fixed(u32),
+}
+
+impl XorKey {
+    /// 地址 addr 处使用的密钥
+    #[inline]
+    pub fn at(self, addr: u64) -> u32 {
+        match self {
+            XorKey::Address => addr as u32,
+            XorKey::Fixed(key) => key,
+        }
+    }
+
+    /// 就地编码或解码 addr 处的 4 字节（XOR 是对称的），不足 4 字节时不做处理
+    #[inline]
+    pub fn apply(self, bytes: &mut [u8], addr: u64) {
+        if let Some(word) = bytes.first_chunk_mut::<4>() {
+            *word = (u32::from_le_bytes(*word) ^ self.at(addr)).to_le_bytes();
+        }
+    }
 }
 
 impl SearchValue {
@@ -160,6 +235,64 @@ impl SearchValue {
         }
     }
 
+    /// `!v`：不等于 v，用只含 v 的排除区间表示
+    ///
+    /// RangeInt 按有符号数读取内存，所以超出有符号范围的值（例如 `!4294967295D`）先换算成同宽度的有符号值。
+    pub fn not_equal(value: i128, value_type: ValueType) -> Self {
+        let bits = value_type.size() * 8;
+        let value = if bits < 128 && value >= 1i128 << (bits - 1) { value - (1i128 << bits) } else { value };
+        SearchValue::range(value, value, value_type, true)
+    }
+
+    /// `!v`：浮点数不等于 v，排除与 FixedFloat 相同容差内的值
+    pub fn not_equal_float(value: f64, value_type: ValueType) -> Self {
+        let epsilon = float_epsilon(value_type.size());
+        SearchValue::range_float(value - epsilon, value + epsilon, value_type, true)
+    }
+
+    /// `v~`、`v~t`：浮点数约等于 v
+    ///
+    /// 误差至少为一个最小的非规格化数，所以 0 附近的非规格化值也能匹配。输入的十进制边界（例如 `3.14~0.01`
+    /// 的 3.15）也算在范围内：Float 的边界取最接近的 f32，Double 的边界各放宽一个单位抵消加减的舍入误差。
+    /// 边界不超过类型的最大有限值：NaN 不匹配任何值，无穷只匹配同号的无穷本身。
+    pub fn approx_float(value: f64, tolerance: FloatTolerance, value_type: ValueType, exclude: bool) -> Self {
+        if !value.is_finite() {
+            return SearchValue::range_float(value, value, value_type, exclude);
+        }
+        let (max, min_delta) = if value_type == ValueType::Float {
+            (f32::MAX as f64, f32::from_bits(1) as f64)
+        } else {
+            (f64::MAX, f64::from_bits(1))
+        };
+        let delta = match tolerance {
+            FloatTolerance::Relative(epsilon) => value.abs() * epsilon,
+            FloatTolerance::Absolute(delta) => delta,
+        }
+        .max(min_delta);
+        let start = (value - delta).max(-max);
+        let end = (value + delta).min(max);
+        if value_type == ValueType::Float {
+            SearchValue::range_float(start as f32 as f64, end as f32 as f64, value_type, exclude)
+        } else {
+            SearchValue::range_float(start.next_down().max(-max), end.next_up().min(max), value_type, exclude)
+        }
+    }
+
+    #[inline]
+    pub fn xor(value: u32, key: XorKey) -> Self {
+        SearchValue::Xor { value, key }
+    }
+
+    /// 按 value_type（Utf8String 或 Utf16String）编码字符串，UTF-16 使用小端序
+    pub fn text(text: &str, value_type: ValueType, ignore_case: bool) -> Self {
+        let bytes = if value_type == ValueType::Utf16String {
+            text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+        } else {
+            text.as_bytes().to_vec()
+        };
+        SearchValue::Text { bytes, value_type, ignore_case }
+    }
+
     #[inline]
     pub fn value_type(&self) -> ValueType {
         match self {
@@ -168,6 +301,8 @@ impl SearchValue {
             SearchValue::FixedFloat { value_type, .. } => *value_type,
             SearchValue::RangeFloat { value_type, .. } => *value_type,
             SearchValue::Pattern { .. } => ValueType::Pattern,
+            SearchValue::Text { value_type, .. } => *value_type,
+            SearchValue::Xor { .. } => ValueType::Xor,
         }
     }
 
@@ -191,6 +326,49 @@ impl SearchValue {
         matches!(self, SearchValue::Pattern { .. })
     }
 
+    #[inline]
+    pub fn is_text(&self) -> bool {
+        matches!(self, SearchValue::Text { .. })
+    }
+
+    /// Auto 精确值的具体解释，由字面量决定：带小数点的按 Float（超出 Float 范围时按 Double）；
+    /// 整数取数值能放下的 Byte / Word / Dword（都放不下时取 Qword），Float 能精确表示时再加上 Float。
+    /// 其他值只有自身
+    pub fn auto_candidates(&self) -> Vec<SearchValue> {
+        match *self {
+            SearchValue::FixedInt { value, value_type: ValueType::Auto } => {
+                let value = i128::from_le_bytes(value);
+                let mut candidates: Vec<SearchValue> = [ValueType::Byte, ValueType::Word, ValueType::Dword]
+                    .into_iter()
+                    .filter(|value_type| fits_int_width(value, value_type.size()))
+                    .map(|value_type| SearchValue::fixed(value, value_type))
+                    .collect();
+                if candidates.is_empty() {
+                    candidates.push(SearchValue::fixed(value, ValueType::Qword));
+                }
+                if (value as f32) as i128 == value {
+                    candidates.push(SearchValue::fixed_float(value as f64, ValueType::Float));
+                }
+                candidates
+            },
+            SearchValue::FixedFloat { value, value_type: ValueType::Auto } => {
+                let value_type = if value.is_finite() && value.abs() > f32::MAX as f64 { ValueType::Double } else { ValueType::Float };
+                vec![SearchValue::fixed_float(value, value_type)]
+            },
+            _ => vec![self.clone()],
+        }
+    }
+
+    /// 匹配的字节数：特征码和字符串由内容决定，其他类型为类型大小
+    #[inline]
+    pub fn byte_len(&self) -> usize {
+        match self {
+            SearchValue::Pattern { pattern } => pattern.len(),
+            SearchValue::Text { bytes, .. } => bytes.len(),
+            _ => self.value_type().size(),
+        }
+    }
+
     /// 获取特征码长度
     #[inline]
     pub fn pattern_len(&self) -> Option<usize> {
@@ -226,6 +404,47 @@ impl SearchValue {
         }
     }
 
+    /// 字符串匹配：忽略大小写时 UTF-8 按字节、UTF-16 按码元折叠 ASCII 字母
+    #[inline]
+    pub fn match_text(&self, data: &[u8]) -> bool {
+        let SearchValue::Text { bytes, value_type, ignore_case } = self else {
+            return false;
+        };
+        if data.len() < bytes.len() {
+            return false;
+        }
+        let data = &data[..bytes.len()];
+        if !*ignore_case {
+            return data == bytes.as_slice();
+        }
+        match value_type {
+            ValueType::Utf16String => data.chunks_exact(2).zip(bytes.chunks_exact(2)).all(|(a, b)| {
+                let fold = |unit: u16| if unit < 0x80 { (unit as u8).to_ascii_lowercase() as u16 } else { unit };
+                fold(u16::from_le_bytes([a[0], a[1]])) == fold(u16::from_le_bytes([b[0], b[1]]))
+            }),
+            _ => data.eq_ignore_ascii_case(bytes),
+        }
+    }
+
+    #[inline]
+    pub fn is_xor(&self) -> bool {
+        matches!(self, SearchValue::Xor { .. })
+    }
+
+    /// 匹配 addr 处的值：XOR 值按该地址的密钥解码后比较，其他类型与 `matched` 相同
+    #[inline]
+    pub fn matched_at(&self, other: &[u8], addr: u64) -> anyhow::Result<bool> {
+        match self {
+            SearchValue::Xor { value, key } => {
+                let Some(word) = other.first_chunk::<4>() else {
+                    return Err(anyhow!("Input slice too small: expected at least 4 bytes, got {}", other.len()));
+                };
+                Ok(u32::from_le_bytes(*word) ^ key.at(addr) == *value)
+            },
+            _ => self.matched(other),
+        }
+    }
+
     #[inline]
     pub fn matched(&self, other: &[u8]) -> anyhow::Result<bool> {
         match self {
@@ -252,13 +471,7 @@ impl SearchValue {
                     },
                     _ => return Err(anyhow!("Invalid float size: {}", size)),
                 };
-                // 使用类型相关的容差：f32 精度较低，需要更大的 epsilon
-                let epsilon = match size {
-                    4 => f32::EPSILON as f64,  // Float (f32) 使用 f32::EPSILON (~1.19e-7)
-                    8 => f64::EPSILON,          // Double (f64) 使用 f64::EPSILON (~2.22e-16)
-                    _ => f64::EPSILON,
-                };
-                Ok((*value - other_value).abs() < epsilon)
+                Ok((*value - other_value).abs() < float_epsilon(size))
             },
             SearchValue::RangeInt {
                 start,
@@ -327,10 +540,22 @@ impl SearchValue {
                 // Pattern 使用 match_pattern 方法
                 Ok(self.match_pattern(other))
             },
+            SearchValue::Text { .. } => Ok(self.match_text(other)),
+            SearchValue::Xor { key: XorKey::Fixed(_), .. } => self.matched_at(other, 0),
+            SearchValue::Xor { key: XorKey::Address, .. } => Err(anyhow!("Address-keyed Xor values need the address, use matched_at")),
         }
     }
 }
 
+/// 浮点数相等比较的容差：f32 精度较低，需要更大的 epsilon
+#[inline]
+fn float_epsilon(size: usize) -> f64 {
+    match size {
+        4 => f32::EPSILON as f64, // Float (f32) 使用 f32::EPSILON (~1.19e-7)
+        _ => f64::EPSILON,        // Double (f64) 使用 f64::EPSILON (~2.22e-16)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SearchMode {
     Unordered,
@@ -389,17 +614,162 @@ impl FuzzyCondition {
     }
 }
 
+/// 组搜索 range 的含义，按元素起始地址计算，边界包含在内
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SpanMode {
+    /// 每个元素到锚点的距离不超过 range（默认，与旧行为兼容）
+    #[default]
+    FromAnchor,
+    /// 整组的跨度不超过 range：最大地址 - 最小地址 <= range
+    GroupSpan,
+}
+
+/// 组搜索中某个值相对锚点（第一个值）起始地址的固定偏移，允许 ±tolerance 字节的误差
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValueOffset {
+    pub offset: i64,
+    pub tolerance: u32,
+}
+
+impl ValueOffset {
+    /// 偏移和误差合起来离锚点最远的距离不能超过它，保证分块扫描的重叠区能覆盖整组
+    pub const MAX_REACH: u64 = 65536;
+
+    #[inline]
+    pub fn new(offset: i64, tolerance: u32) -> Self {
+        ValueOffset { offset, tolerance }
+    }
+
+    /// 离锚点最远的距离
+    #[inline]
+    pub fn reach(&self) -> u64 {
+        self.offset.unsigned_abs() + self.tolerance as u64
+    }
+}
+
+/// 指定的对齐最多是值类型大小的这么多倍
+/// 整数能否写进 `size` 字节：有符号最小值到无符号最大值
+#[inline]
+fn fits_int_width(value: i128, size: usize) -> bool {
+    let bits = size * 8;
+    (-(1i128 << (bits - 1))..=(1i128 << bits) - 1).contains(&value)
+}
+
+pub const MAX_ALIGNMENT_FACTOR: usize = 8;
+
+/// 检查候选地址的对齐：必须是 2 的幂，且不超过 `value_size * MAX_ALIGNMENT_FACTOR`
+pub fn check_alignment(alignment: usize, value_size: usize) -> Result<(), String> {
+    if !alignment.is_power_of_two() {
+        return Err(format!("Alignment must be a power of two, got {}", alignment));
+    }
+    if alignment > value_size * MAX_ALIGNMENT_FACTOR {
+        return Err(format!("Alignment {} exceeds {} times the value size {}", alignment, MAX_ALIGNMENT_FACTOR, value_size));
+    }
+    Ok(())
+}
+
 #[derive(Debug, Clone)]
 pub struct SearchQuery {
     pub values: Vec<SearchValue>,
     pub mode: SearchMode,
     pub range: u16,
+    pub span_mode: SpanMode,
+    /// 与 values 一一对应的固定偏移，为空表示按 range 窗口搜索。
+    /// 给出偏移时锚点固定为第一个值，其后每个值都必须带偏移，mode / range / span_mode 不再生效
+    pub offsets: Vec<Option<ValueOffset>>,
+    /// 首次扫描读取的进程，None 表示绑定的进程；其他进程通过次要句柄读取（见 secondary_procs）
+    pub target_pid: Option<i32>,
+    /// 首次扫描候选地址的对齐，None 表示按每个值自身的大小对齐。
+    /// 小于值大小时值可能跨页、跨块，改善搜索按地址读取，不受对齐影响
+    pub alignment: Option<usize>,
+    /// 组改善时每个锚点找到第一组满足的组合就停止回溯（与普通组搜索相同），false 时取所有组合（与深度搜索相同）
+    pub first_match_only: bool,
+    /// 首次扫描的结果数上限，0 表示不限制。达到上限后停止扫描，保存已经找到的结果（可能略多于上限）
+    pub max_results: usize,
 }
 
 impl SearchQuery {
     #[inline]
     pub fn new(values: Vec<SearchValue>, mode: SearchMode, range: u16) -> Self {
-        SearchQuery { values, mode, range }
+        SearchQuery {
+            values,
+            mode,
+            range,
+            span_mode: SpanMode::default(),
+            offsets: Vec::new(),
+            target_pid: None,
+            alignment: None,
+            first_match_only: true,
+            max_results: 0,
+        }
+    }
+
+    #[inline]
+    pub fn with_offsets(mut self, offsets: Vec<Option<ValueOffset>>) -> Self {
+        self.offsets = offsets;
+        self
+    }
+
+    /// 是否按固定偏移匹配
+    #[inline]
+    pub fn has_offsets(&self) -> bool {
+        self.offsets.iter().any(Option::is_some)
+    }
+
+    #[inline]
+    pub fn value_offset(&self, idx: usize) -> Option<ValueOffset> {
+        self.offsets.get(idx).copied().flatten()
+    }
+
+    /// 按固定偏移匹配且所有偏移都没有误差，每个值只有一个可能的地址
+    #[inline]
+    pub fn has_exact_offsets(&self) -> bool {
+        self.has_offsets() && self.offsets.iter().flatten().all(|offset| offset.tolerance == 0)
+    }
+
+    #[inline]
+    pub fn with_span_mode(mut self, span_mode: SpanMode) -> Self {
+        self.span_mode = span_mode;
+        self
+    }
+
+    #[inline]
+    pub fn with_target_pid(mut self, target_pid: Option<i32>) -> Self {
+        self.target_pid = target_pid;
+        self
+    }
+
+    #[inline]
+    pub fn with_max_results(mut self, max_results: usize) -> Self {
+        self.max_results = max_results;
+        self
+    }
+
+    #[inline]
+    pub fn with_alignment(mut self, alignment: Option<usize>) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    #[inline]
+    pub fn with_first_match_only(mut self, first_match_only: bool) -> Self {
+        self.first_match_only = first_match_only;
+        self
+    }
+
+    /// 某个类型的值在首次扫描中的对齐：指定了 alignment 时使用它，否则按类型大小
+    #[inline]
+    pub fn alignment_for(&self, value_type: ValueType) -> usize {
+        self.alignment.unwrap_or(value_type.size()).max(1)
+    }
+
+    /// 锚点值的下标：第一个固定值，没有固定值时取第一个值；按固定偏移匹配时总是第一个值。
+    /// 首次扫描和改善搜索都以它为锚点，FromAnchor 的距离也从它开始计算
+    pub fn anchor_index(&self) -> usize {
+        if self.has_offsets() {
+            return 0;
+        }
+        self.values.iter().position(|v| v.is_fixed()).unwrap_or(0)
     }
 
     pub fn total_size(&self) -> usize {
@@ -421,13 +791,141 @@ impl SearchQuery {
             return Err("Maximum 64 values allowed".to_string());
         }
 
+        if let Some(text) = self.values.iter().find(|value| value.is_text()) {
+            if self.values.len() > 1 {
+                return Err("String values cannot be combined with other values".to_string());
+            }
+            if text.byte_len() == 0 {
+                return Err("String must not be empty".to_string());
+            }
+        }
+
+        if self.values.len() > 1 && self.values.iter().any(SearchValue::is_xor) {
+            return Err("Xor values cannot be combined with other values".to_string());
+        }
+
+        if let Some(alignment) = self.alignment {
+            if self.is_text() {
+                return Err("Alignment cannot be set for string values".to_string());
+            }
+            // 组搜索按最小的值类型限制，每个值都要能落在这个对齐上
+            let min_size = self.values.iter().map(|v| v.value_type().size()).min().unwrap_or(1);
+            check_alignment(alignment, min_size)?;
+        }
+
+        if self.has_offsets() {
+            return self.validate_offsets();
+        }
+
         if self.values.len() >= 2 && self.range < 2 {
             return Err("Range must be at least 2 for group search".to_string());
         }
 
         Ok(())
     }
+
+    /// 单个字符串值的查询
+    #[inline]
+    pub fn is_text(&self) -> bool {
+        self.values.len() == 1 && self.values[0].is_text()
+    }
+
+    fn validate_offsets(&self) -> Result<(), String> {
+        if self.offsets.len() != self.values.len() {
+            return Err("Offsets must be given for every value".to_string());
+        }
+
+        if self.offsets[0].is_some() {
+            return Err("The first value is the anchor, its offset can only be @0".to_string());
+        }
+
+        for (idx, offset) in self.offsets.iter().enumerate().skip(1) {
+            match offset {
+                None => return Err(format!("Value {} has no offset, every value after the anchor needs one", idx + 1)),
+                Some(offset) if offset.reach() > ValueOffset::MAX_REACH => {
+                    return Err(format!("Offset {:+}~{} of value {} exceeds {}", offset.offset, offset.tolerance, idx + 1, ValueOffset::MAX_REACH));
+                }
+                Some(_) => {}
+            }
+        }
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
-mod test {}
+mod test {
+    use super::*;
+
+    fn matches_f32(value: &SearchValue, x: f32) -> bool {
+        value.matched(&x.to_le_bytes()).unwrap()
+    }
+
+    fn matches_f64(value: &SearchValue, x: f64) -> bool {
+        value.matched(&x.to_le_bytes()).unwrap()
+    }
+
+    fn matches_typed(value: &SearchValue, x: f64) -> bool {
+        match value.value_type() {
+            ValueType::Float => matches_f32(value, x as f32),
+            _ => matches_f64(value, x),
+        }
+    }
+
+    #[test]
+    fn test_approx_float_denormals() {
+        // 相对误差对非规格化数小于一个最小单位，按一个单位计算
+        let relative = FloatTolerance::Relative(FloatTolerance::DEFAULT_RELATIVE);
+        let value = SearchValue::approx_float(f32::from_bits(7) as f64, relative, ValueType::Float, false);
+        for bits in 6..=8 {
+            assert!(matches_f32(&value, f32::from_bits(bits)), "{}", bits);
+        }
+        assert!(!matches_f32(&value, f32::from_bits(5)));
+        assert!(!matches_f32(&value, f32::from_bits(9)));
+
+        // Double 的边界再各放宽一个单位
+        let value = SearchValue::approx_float(f64::from_bits(7), relative, ValueType::Double, false);
+        assert!(matches_f64(&value, f64::from_bits(9)));
+        assert!(!matches_f64(&value, f64::from_bits(10)));
+        assert!(!matches_f64(&value, f64::from_bits(4)));
+
+        // 0 附近：正负零和最小的非规格化数
+        let value = SearchValue::approx_float(0.0, relative, ValueType::Float, false);
+        for x in [0.0, -0.0, f32::from_bits(1), -f32::from_bits(1)] {
+            assert!(matches_f32(&value, x), "{}", x);
+        }
+        assert!(!matches_f32(&value, f32::from_bits(2)));
+        assert!(!matches_f32(&value, f32::MIN_POSITIVE));
+
+        // 绝对误差跨过规格化数的边界
+        let value = SearchValue::approx_float(f32::MIN_POSITIVE as f64, FloatTolerance::Absolute(f32::MIN_POSITIVE as f64 / 2.0), ValueType::Float, false);
+        assert!(matches_f32(&value, f32::MIN_POSITIVE / 2.0));
+        assert!(matches_f32(&value, f32::from_bits(f32::MIN_POSITIVE.to_bits() - 1)));
+        assert!(!matches_f32(&value, f32::MIN_POSITIVE / 2.0 - f32::from_bits(1)));
+    }
+
+    #[test]
+    fn test_approx_float_nan_and_infinity() {
+        let relative = FloatTolerance::Relative(FloatTolerance::DEFAULT_RELATIVE);
+        for value_type in [ValueType::Float, ValueType::Double] {
+            for exclude in [false, true] {
+                let value = SearchValue::approx_float(f64::NAN, relative, value_type, exclude);
+                for x in [f64::NAN, 0.0, 1.0, f64::INFINITY] {
+                    assert!(!matches_typed(&value, x), "{} {} {}", value_type, exclude, x);
+                }
+            }
+
+            let value = SearchValue::approx_float(f64::INFINITY, FloatTolerance::Absolute(1e300), value_type, false);
+            assert!(matches_typed(&value, f64::INFINITY));
+            assert!(!matches_typed(&value, f64::NEG_INFINITY));
+            assert!(!matches_typed(&value, f32::MAX as f64));
+
+            // 有限值的误差再大也不会匹配到无穷和 NaN
+            let value = SearchValue::approx_float(1.0, FloatTolerance::Absolute(f64::MAX), value_type, false);
+            assert!(matches_typed(&value, -1e30));
+            for x in [f64::INFINITY, f64::NEG_INFINITY, f64::NAN] {
+                assert!(!matches_typed(&value, x), "{} {}", value_type, x);
+            }
+        }
+    }
+}
@@ -39,7 +39,8 @@ use nix::sys::socket::{AddressFamily, SockFlag, SockType, socket};
 use nix::{NixPath, libc};
 use std::ffi::c_void;
 use std::mem::{MaybeUninit, size_of};
-use std::os::fd::{AsFd, AsRawFd, FromRawFd, OwnedFd};
+use std::num::NonZeroUsize;
+use std::os::fd::{AsRawFd, BorrowedFd, FromRawFd, OwnedFd};
 use std::ptr::NonNull;
 
 // IOCTL command definitions (magic number 'W')
@@ -289,6 +290,44 @@ pub struct WuwaMemRegionEntry {
     pub name: [u8; 4096], // Region name (PATH_MAX), mangled format
 }
 
+impl WuwaMemRegionEntry {
+    /// 区域名，已还原内核转义的字符，见 `demangle_region_name`
+    pub fn name(&self) -> String {
+        demangle_region_name(&self.name)
+    }
+}
+
+/// 还原内核路径的转义：与 /proc/pid/maps 相同，换行等特殊字符写成 `\ooo` 三位八进制。
+/// 其他反斜杠原样保留，无效的 UTF-8 替换为 U+FFFD
+pub fn demangle_region_name(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&c| c == 0).unwrap_or(bytes.len());
+    let bytes = &bytes[..end];
+    if !bytes.contains(&b'\\') {
+        return String::from_utf8_lossy(bytes).into_owned();
+    }
+
+    // 超过 \377 的三位数不是一个字节，不当作转义
+    let escape_at = |i: usize| match bytes.get(i + 1..i + 4) {
+        Some(&[a @ b'0'..=b'3', b @ b'0'..=b'7', c @ b'0'..=b'7']) => Some((a - b'0') << 6 | (b - b'0') << 3 | (c - b'0')),
+        _ => None,
+    };
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match escape_at(i).filter(|_| bytes[i] == b'\\') {
+            Some(byte) => {
+                out.push(byte);
+                i += 4;
+            },
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            },
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
 /// Command structure for querying memory regions
 #[repr(C)]
 pub struct WuwaQueryMemRegionsCmd {
@@ -667,6 +706,84 @@ pub struct MemRegionsResult {
     pub entry_count: size_t, // Number of region entries
 }
 
+impl MemRegionsResult {
+    /// 只读映射驱动返回的区域缓冲区。fd 的所有权转移给返回值，映射失败时这里就关闭
+    pub fn map(self) -> Result<MappedMemRegions, anyhow::Error> {
+        let mut regions = MappedMemRegions {
+            fd: self.fd,
+            mapped: None,
+            size: self.buffer_size,
+            count: 0,
+        };
+        let Some(size) = NonZeroUsize::new(self.buffer_size) else {
+            return Ok(regions);
+        };
+        let borrowed_fd = unsafe { BorrowedFd::borrow_raw(self.fd) };
+        let mapped = unsafe { mmap(None, size, ProtFlags::PROT_READ, MapFlags::MAP_PRIVATE, borrowed_fd, 0) }
+            .map_err(|e| anyhow!("Failed to mmap memory regions buffer: {}", e))?;
+        regions.mapped = Some(mapped);
+        // 条目数不能超出缓冲区
+        regions.count = self.entry_count.min(self.buffer_size / size_of::<WuwaMemRegionEntry>());
+        Ok(regions)
+    }
+}
+
+/// 映射后的内存区域列表，drop 时解除映射并关闭 fd，任何提前返回的路径都不会泄漏
+pub struct MappedMemRegions {
+    fd: c_int,
+    mapped: Option<NonNull<c_void>>,
+    size: usize,
+    count: usize,
+}
+
+impl MappedMemRegions {
+    pub fn entries(&self) -> &[WuwaMemRegionEntry] {
+        match self.mapped {
+            // 条目是 packed 的，对齐为 1
+            Some(mapped) => unsafe { std::slice::from_raw_parts(mapped.as_ptr() as *const WuwaMemRegionEntry, self.count) },
+            None => &[],
+        }
+    }
+}
+
+impl Drop for MappedMemRegions {
+    fn drop(&mut self) {
+        unsafe {
+            if let Some(mapped) = self.mapped.take() {
+                let _ = munmap(mapped, self.size);
+            }
+            libc::close(self.fd);
+        }
+    }
+}
+
+/// 查询内存区域时在构造 Java 对象之前做的过滤，默认值不过滤任何区域
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemRegionFilter {
+    /// 必须具有的 MEM_* 权限位，0 表示不限
+    pub perm_mask: u32,
+    /// 区域名（还原转义后）必须包含的子串，空串表示不限
+    pub name: String,
+    /// 与 `[start, end)` 相交的区域才保留，end 为 0 表示没有上界
+    pub start: u64,
+    pub end: u64,
+}
+
+impl MemRegionFilter {
+    pub fn is_empty(&self) -> bool {
+        self.perm_mask == 0 && self.name.is_empty() && self.start == 0 && self.end == 0
+    }
+
+    /// 名字按需才解码：只有地址和权限都满足时才检查名字
+    pub fn matches(&self, entry: &WuwaMemRegionEntry) -> bool {
+        let (start, end, type_) = (entry.start, entry.end, entry.type_);
+        if type_ & self.perm_mask != self.perm_mask || end <= self.start || (self.end != 0 && start >= self.end) {
+            return false;
+        }
+        self.name.is_empty() || entry.name().contains(&self.name)
+    }
+}
+
 /// WuWa driver connection handle
 pub struct WuWaDriver {
     sock: OwnedFd,
@@ -1121,66 +1238,29 @@ impl WuWaDriver {
     }
 
     /// Read Unreal Engine FString from target process
+    ///
+    /// Strings longer than `FSTRING_DEFAULT_MAX_LEN` are truncated.
     pub fn read_fstring(&self, pid: pid_t, addr: usize) -> Result<String, anyhow::Error> {
-        let len = self.read::<u32>(pid, addr + 8)? as usize;
-        if len == 0 {
-            return Ok("".to_string());
-        }
-        let ue_name = self.read::<usize>(pid, addr)?;
-        let mut tmp = vec![];
-        unsafe {
-            self.read_to_utf8(pid, ue_name as *const u16, &mut tmp, len - 1)?;
-        }
-        String::from_utf8(tmp).map_err(|e| anyhow!("FString decode failed: {:?}", e))
+        read_fstring_with(addr, FSTRING_DEFAULT_MAX_LEN, false, |va, buf, status| {
+            self.read_physical_memory_with_status(pid, va, buf.as_mut_ptr() as usize, buf.len(), status)
+                .map(|_| ())
+        })
     }
 
-    /// Read FString with length limit
+    /// Read FString with length limit, fails if the FString is longer than `max_len` characters
     pub fn read_fstring_limit(&self, pid: pid_t, addr: usize, max_len: usize) -> Result<String, anyhow::Error> {
-        let len = self.read::<u32>(pid, addr + 8)? as usize;
-        if len == 0 {
-            return Ok("".to_string());
-        }
-
-        if len > max_len {
-            return Err(anyhow!("FString length {} exceeds limit {}", len, max_len));
-        }
-
-        let ue_name = self.read::<usize>(pid, addr)?;
-        let mut tmp = vec![];
-        unsafe {
-            self.read_to_utf8(pid, ue_name as *const u16, &mut tmp, len - 1)?;
-        }
-        String::from_utf8(tmp).map_err(|e| anyhow!("FString decode failed: {:?}", e))
+        read_fstring_with(addr, max_len, true, |va, buf, status| {
+            self.read_physical_memory_with_status(pid, va, buf.as_mut_ptr() as usize, buf.len(), status)
+                .map(|_| ())
+        })
     }
 
-    /// Convert UTF-16 in target process to UTF-8 in local buffer
-    pub unsafe fn read_to_utf8(
-        &self,
-        pid: pid_t,
-        ptr: *const u16,
-        buf: &mut Vec<u8>,
-        length: usize,
-    ) -> Result<(), anyhow::Error> {
-        let mut temp_utf16 = ptr;
-        let end = ptr.add(length);
-
-        while temp_utf16 < end {
-            let utf16_char = self.read::<u16>(pid, temp_utf16 as usize)?;
-
-            if utf16_char <= 0x007F {
-                buf.push(utf16_char as u8);
-            } else if utf16_char <= 0x07FF {
-                buf.push(((utf16_char >> 6) | 0xC0) as u8);
-                buf.push(((utf16_char & 0x3F) | 0x80) as u8);
-            } else {
-                buf.push(((utf16_char >> 12) | 0xE0) as u8);
-                buf.push(((utf16_char >> 6 & 0x3F) | 0x80) as u8);
-                buf.push(((utf16_char & 0x3F) | 0x80) as u8);
-            }
-
-            temp_utf16 = temp_utf16.add(1);
-        }
-        Ok(())
+    /// Read NUL-terminated ANSI/UTF-8 string, at most `max_len` bytes
+    pub fn read_cstring(&self, pid: pid_t, addr: usize, max_len: usize) -> Result<String, anyhow::Error> {
+        read_cstring_with(addr, max_len, |va, buf, status| {
+            self.read_physical_memory_with_status(pid, va, buf.as_mut_ptr() as usize, buf.len(), status)
+                .map(|_| ())
+        })
     }
 
     /// Get module/library base address in target process
@@ -1550,7 +1630,8 @@ impl WuWaDriver {
     ///
     /// This is an improved version that retrieves both PIDs and detailed information
     /// in a single call. Automatically filters out processes with empty names.
-    /// Results are sorted by priority (lower priority value = higher priority = appears first).
+    /// Results are sorted by priority (lower priority value = higher priority = appears first),
+    /// processes with equal priority are ordered by pid so repeated calls return the same order.
     ///
     /// # Returns
     /// Vector of process information structs sorted by priority, empty vector on failure
@@ -1572,8 +1653,8 @@ impl WuWaDriver {
             }
         }
 
-        // Sort by priority (lower value = higher priority)
-        result.sort_by(|a, b| a.prio.cmp(&b.prio));
+        // Sort by priority (lower value = higher priority), ties by pid
+        crate::core::process_list::sort_processes(&mut result, crate::core::process_list::ProcessSortMode::Priority);
 
         result
     }
@@ -1663,3 +1744,301 @@ impl WuWaDriver {
         })
     }
 }
+
+/// read_fstring 默认的最大字符数（含结尾 NUL）
+pub const FSTRING_DEFAULT_MAX_LEN: usize = 4096;
+
+/// FString 头部：TCHAR* Data + int32 ArrayNum
+const FSTRING_HEADER_SIZE: usize = 12;
+
+/// 单次读取 [addr, addr + len)，返回第一个失败页之前的可读前缀
+///
+/// `read` 只会被调用一次，读取失败的页通过 PageStatusBitmap 报告。
+pub fn read_readable_prefix<R>(addr: usize, len: usize, read: R) -> Result<Vec<u8>, anyhow::Error>
+where
+    R: FnOnce(usize, &mut [u8], &mut PageStatusBitmap) -> Result<(), anyhow::Error>,
+{
+    if len == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut buffer = vec![0u8; len];
+    let mut status = PageStatusBitmap::new(len, addr);
+    read(addr, &mut buffer, &mut status)?;
+
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+    let first_page = addr & !(page_size - 1);
+    let covered_pages = (addr + len - first_page).div_ceil(page_size);
+    if let Some(failed) = (0..covered_pages).find(|&page| !status.is_page_success(page)) {
+        let readable = (first_page + failed * page_size).saturating_sub(addr);
+        buffer.truncate(readable);
+    }
+    Ok(buffer)
+}
+
+/// UTF-16LE 解码为 UTF-8，遇到 NUL 截断，非法代理对替换为 U+FFFD，末尾的半个字符被丢弃
+pub fn decode_utf16_lossy(bytes: &[u8]) -> String {
+    let units = bytes
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .take_while(|&unit| unit != 0);
+    char::decode_utf16(units)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect()
+}
+
+/// NUL 结尾的 ANSI/UTF-8 字符串解码，非法序列替换为 U+FFFD
+pub fn decode_cstring_lossy(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+/// 通过给定的读取函数读取 FString：一次读取头部，一次读取字符数据
+///
+/// - `max_len`: 最大字符数（含结尾 NUL）
+/// - `strict`: 为 true 时超过 max_len 返回错误，否则截断到 max_len（即最多 max_len - 1 个字符）
+pub fn read_fstring_with<R>(addr: usize, max_len: usize, strict: bool, mut read: R) -> Result<String, anyhow::Error>
+where
+    R: FnMut(usize, &mut [u8], &mut PageStatusBitmap) -> Result<(), anyhow::Error>,
+{
+    let header = read_readable_prefix(addr, FSTRING_HEADER_SIZE, &mut read)?;
+    if header.len() < FSTRING_HEADER_SIZE {
+        return Err(anyhow!("FString header at 0x{:x} is not readable", addr));
+    }
+
+    let data = u64::from_le_bytes(header[0..8].try_into().unwrap()) as usize;
+    let len = i32::from_le_bytes(header[8..12].try_into().unwrap());
+    if len < 0 {
+        return Err(anyhow!("Invalid FString length {}", len));
+    }
+    // ArrayNum 包含结尾 NUL
+    let len = len as usize;
+    if len == 0 {
+        return Ok(String::new());
+    }
+    if strict && len > max_len {
+        return Err(anyhow!("FString length {} exceeds limit {}", len, max_len));
+    }
+
+    let chars = (len - 1).min(max_len.saturating_sub(1));
+    let bytes = read_readable_prefix(data, chars * 2, read)?;
+    Ok(decode_utf16_lossy(&bytes))
+}
+
+/// 通过给定的读取函数单次读取 NUL 结尾字符串，最多 max_len 字节
+pub fn read_cstring_with<R>(addr: usize, max_len: usize, read: R) -> Result<String, anyhow::Error>
+where
+    R: FnOnce(usize, &mut [u8], &mut PageStatusBitmap) -> Result<(), anyhow::Error>,
+{
+    let bytes = read_readable_prefix(addr, max_len, read)?;
+    Ok(decode_cstring_lossy(&bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::tests::mock_memory::MockMemory;
+    use std::cell::Cell;
+    use std::os::fd::IntoRawFd;
+
+    const BASE: u64 = 0x7300000000;
+    const PAGE: u64 = 4096;
+
+    fn new_memory() -> MockMemory {
+        let mut mem = MockMemory::new();
+        mem.malloc(BASE, 4 * PAGE as usize).unwrap();
+        mem
+    }
+
+    /// Writes an FString header at `header` pointing to UTF-16 `units` (plus NUL) at `data`.
+    fn write_fstring(mem: &mut MockMemory, header: u64, data: u64, units: &[u16]) {
+        mem.mem_write_u64(header, data).unwrap();
+        mem.mem_write_u32(header + 8, units.len() as u32 + 1).unwrap();
+        let mut bytes: Vec<u8> = units.iter().flat_map(|u| u.to_le_bytes()).collect();
+        bytes.extend_from_slice(&[0, 0]);
+        mem.mem_write(data, &bytes).unwrap();
+    }
+
+    fn fstring(mem: &MockMemory, addr: u64, max_len: usize, strict: bool, reads: &Cell<usize>) -> Result<String, anyhow::Error> {
+        read_fstring_with(addr as usize, max_len, strict, |va, buf, status| {
+            reads.set(reads.get() + 1);
+            mem.mem_read_with_status(va as u64, buf, status)
+        })
+    }
+
+    fn cstring(mem: &MockMemory, addr: u64, max_len: usize, reads: &Cell<usize>) -> Result<String, anyhow::Error> {
+        read_cstring_with(addr as usize, max_len, |va, buf, status| {
+            reads.set(reads.get() + 1);
+            mem.mem_read_with_status(va as u64, buf, status)
+        })
+    }
+
+    #[test]
+    fn test_fstring_surrogate_pairs() {
+        let mut mem = new_memory();
+        let text = "a😀b中";
+        let units: Vec<u16> = text.encode_utf16().collect();
+        write_fstring(&mut mem, BASE, BASE + 0x100, &units);
+
+        let reads = Cell::new(0);
+        assert_eq!(fstring(&mem, BASE, 64, true, &reads).unwrap(), text);
+
+        // 孤立的高代理项替换为 U+FFFD 而不是报错
+        write_fstring(&mut mem, BASE, BASE + 0x100, &[b'x' as u16, 0xD800, b'y' as u16]);
+        assert_eq!(fstring(&mem, BASE, 64, true, &reads).unwrap(), "x\u{FFFD}y");
+    }
+
+    #[test]
+    fn test_embedded_nul_and_invalid_utf8() {
+        let mut mem = new_memory();
+        mem.mem_write(BASE, b"abc\0def\0").unwrap();
+        mem.mem_write(BASE + 0x10, b"ok\xFFz\0").unwrap();
+
+        let reads = Cell::new(0);
+        assert_eq!(cstring(&mem, BASE, 64, &reads).unwrap(), "abc");
+        assert_eq!(cstring(&mem, BASE + 0x10, 64, &reads).unwrap(), "ok\u{FFFD}z");
+
+        let units: Vec<u16> = "ab\0cd".encode_utf16().collect();
+        write_fstring(&mut mem, BASE + 0x40, BASE + 0x100, &units);
+        assert_eq!(fstring(&mem, BASE + 0x40, 64, true, &reads).unwrap(), "ab");
+    }
+
+    #[test]
+    fn test_string_crossing_unreadable_page() {
+        let mut mem = new_memory();
+        // 第 1 页不可读，字符串从第 0 页末尾开始且没有 NUL
+        let start = BASE + PAGE - 6;
+        mem.mem_write(start, b"tail!!").unwrap();
+        mem.set_faulty_pages(BASE, &[1]).unwrap();
+
+        let reads = Cell::new(0);
+        assert_eq!(cstring(&mem, start, 64, &reads).unwrap(), "tail!!");
+
+        let units: Vec<u16> = "xyz".encode_utf16().collect();
+        let data = BASE + PAGE - 4;
+        mem.mem_write(data, &units.iter().flat_map(|u| u.to_le_bytes()).collect::<Vec<_>>()[..4]).unwrap();
+        mem.mem_write_u64(BASE, data).unwrap();
+        mem.mem_write_u32(BASE + 8, 4).unwrap();
+        assert_eq!(fstring(&mem, BASE, 64, true, &reads).unwrap(), "xy");
+
+        // 头部所在页不可读时返回错误
+        assert!(fstring(&mem, BASE + PAGE, 64, true, &reads).is_err());
+    }
+
+    #[test]
+    fn test_fstring_length_limit() {
+        let mut mem = new_memory();
+        let units: Vec<u16> = (0..99).map(|i| b'a' as u16 + (i % 26)).collect();
+        write_fstring(&mut mem, BASE, BASE + 0x100, &units);
+
+        let reads = Cell::new(0);
+        let err = fstring(&mem, BASE, 10, true, &reads).unwrap_err();
+        assert!(err.to_string().contains("exceeds limit"), "{}", err);
+        // 非严格模式截断到 max_len，与严格模式一样 max_len 包含结尾 NUL
+        assert_eq!(fstring(&mem, BASE, 10, false, &reads).unwrap().chars().count(), 9);
+        assert_eq!(fstring(&mem, BASE, 100, false, &reads).unwrap().chars().count(), 99);
+        assert_eq!(fstring(&mem, BASE, 0, false, &reads).unwrap(), "");
+        assert_eq!(fstring(&mem, BASE, 100, true, &reads).unwrap().chars().count(), 99);
+
+        // len == 0 是空字符串，与 max_len 无关
+        mem.mem_write_u32(BASE + 8, 0).unwrap();
+        assert_eq!(fstring(&mem, BASE, 0, true, &reads).unwrap(), "");
+        // 负长度视为损坏的结构
+        mem.mem_write_i32(BASE + 8, -1).unwrap();
+        assert!(fstring(&mem, BASE, 10, true, &reads).is_err());
+    }
+
+    #[test]
+    fn test_single_read_per_string() {
+        let mut mem = new_memory();
+        let units: Vec<u16> = "长字符串".encode_utf16().cycle().take(2000).collect();
+        write_fstring(&mut mem, BASE, BASE + 0x100, &units);
+
+        let reads = Cell::new(0);
+        assert_eq!(fstring(&mem, BASE, 4096, true, &reads).unwrap().chars().count(), 2000);
+        // 头部一次 + 字符数据一次，与长度无关
+        assert_eq!(reads.get(), 2);
+
+        mem.mem_write(BASE + 2 * PAGE, &[b'z'; 1000]).unwrap();
+        reads.set(0);
+        assert_eq!(cstring(&mem, BASE + 2 * PAGE, 512, &reads).unwrap().len(), 512);
+        assert_eq!(reads.get(), 1);
+    }
+
+    fn region(start: u64, end: u64, type_: u32, name: &[u8]) -> WuwaMemRegionEntry {
+        let mut entry = WuwaMemRegionEntry { start, end, type_, _reserved: 0, name: [0; 4096] };
+        entry.name[..name.len()].copy_from_slice(name);
+        entry
+    }
+
+    fn entry_bytes(entry: &WuwaMemRegionEntry) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(entry as *const WuwaMemRegionEntry as *const u8, size_of::<WuwaMemRegionEntry>()) }
+    }
+
+    fn fd_is_open(fd: c_int) -> bool {
+        unsafe { libc::fcntl(fd, libc::F_GETFD) != -1 }
+    }
+
+    #[test]
+    fn test_demangle_region_name() {
+        // 名字在第一个 NUL 处结束
+        assert_eq!(demangle_region_name(b"/data/app/libgame.so\0garbage"), "/data/app/libgame.so");
+        assert_eq!(demangle_region_name(br"/data/local/tmp/a\012b (deleted)"), "/data/local/tmp/a\nb (deleted)");
+        assert_eq!(demangle_region_name(br"/sdcard/my\040file\134x"), r"/sdcard/my file\x");
+        // 不完整或超出一个字节的转义原样保留
+        assert_eq!(demangle_region_name(br"a\01"), r"a\01");
+        assert_eq!(demangle_region_name(br"a\400b"), r"a\400b");
+        assert_eq!(demangle_region_name(b"[anon:libc_malloc]"), "[anon:libc_malloc]");
+        assert_eq!(demangle_region_name(br"bad\377"), "bad\u{FFFD}");
+    }
+
+    #[test]
+    fn test_mem_region_filter() {
+        const RW: u32 = MEM_READABLE | MEM_WRITABLE;
+        let heap = region(0x1000, 0x3000, RW, b"[anon:libc_malloc]");
+        let lib = region(0x7000, 0x8000, MEM_READABLE | MEM_EXECUTABLE, br"/data/app/lib\040game.so");
+
+        let empty = MemRegionFilter::default();
+        assert!(empty.is_empty());
+        assert!(empty.matches(&heap) && empty.matches(&lib));
+
+        let writable = MemRegionFilter { perm_mask: MEM_WRITABLE, ..Default::default() };
+        assert!(writable.matches(&heap) && !writable.matches(&lib));
+
+        // 名字在还原转义之后比较
+        let named = MemRegionFilter { name: "lib game".to_string(), ..Default::default() };
+        assert!(!named.matches(&heap) && named.matches(&lib));
+
+        // 与 [start, end) 相交即保留，end 为 0 没有上界
+        let range = |start, end| MemRegionFilter { start, end, ..Default::default() };
+        assert!(range(0x2FFF, 0x3000).matches(&heap));
+        assert!(!range(0x3000, 0x7000).matches(&heap) && !range(0x3000, 0x7000).matches(&lib));
+        assert!(range(0x7FFF, 0).matches(&lib) && !range(0x8000, 0).matches(&lib));
+        assert!(!range(0, 0x1000).matches(&heap));
+    }
+
+    #[test]
+    fn test_mapped_regions_release_fd() {
+        let entries = [region(0x1000, 0x2000, MEM_READABLE, b"a"), region(0x3000, 0x4000, MEM_READABLE, b"b")];
+        let path = std::env::temp_dir().join(format!("mamu_regions_{}", std::process::id()));
+        let bytes: Vec<u8> = entries.iter().flat_map(|entry| entry_bytes(entry).to_vec()).collect();
+        std::fs::write(&path, &bytes).unwrap();
+
+        let open = || std::fs::File::open(&path).unwrap().into_raw_fd();
+        let fd = open();
+        // 多报的条目数不会读出缓冲区
+        let mapped = MemRegionsResult { fd, buffer_size: bytes.len(), entry_count: 5 }.map().unwrap();
+        assert_eq!(mapped.entries().iter().map(|entry| entry.name()).collect::<Vec<_>>(), ["a", "b"]);
+        assert!(fd_is_open(fd));
+        drop(mapped);
+        assert!(!fd_is_open(fd));
+
+        let fd = open();
+        let empty = MemRegionsResult { fd, buffer_size: 0, entry_count: 0 }.map().unwrap();
+        assert!(empty.entries().is_empty());
+        drop(empty);
+        assert!(!fd_is_open(fd));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}